@@ -3,12 +3,12 @@
 
 #![cfg_attr(target_arch = "wasm32", no_main)]
 
-use alloy_primitives::{address, U256};
-use alloy_sol_types::{sol, SolCall};
+use alloy_primitives::address;
+use alloy_sol_types::sol;
 use call_evm_counter::{CallCounterAbi, CallCounterOperation};
 use linera_sdk::{
     abis::evm::EvmAbi,
-    linera_base_types::{Amount, ApplicationId, EvmOperation, WithContractAbi},
+    linera_base_types::{Amount, ApplicationId, WithContractAbi},
     Contract, ContractRuntime,
 };
 
@@ -22,17 +22,6 @@ impl WithContractAbi for CallCounterContract {
     type Abi = CallCounterAbi;
 }
 
-impl CallCounterContract {
-    fn process_operation(&mut self, operation: &Vec<u8>) -> u64 {
-        let evm_counter_id = self.runtime.application_parameters();
-        let result = self
-            .runtime
-            .call_application(true, evm_counter_id, operation);
-        let arr: [u8; 32] = result.try_into().expect("result should have length 32");
-        U256::from_be_bytes(arr).to::<u64>()
-    }
-}
-
 impl Contract for CallCounterContract {
     type Message = ();
     type InstantiationArgument = ();
@@ -50,22 +39,21 @@ impl Contract for CallCounterContract {
 
     async fn execute_operation(&mut self, operation: CallCounterOperation) -> u64 {
         sol! {
-            function increment(uint64 input);
-            function call_from_wasm(address remote_address);
+            function increment(uint64 input) returns (uint64);
+            function call_from_wasm(address remote_address) returns (uint64);
         }
+        let evm_counter_id = self.runtime.application_parameters();
         match operation {
             CallCounterOperation::Increment(increment) => {
-                let operation = incrementCall { input: increment };
-                let operation = EvmOperation::new(Amount::ZERO, operation.abi_encode());
-                let operation = operation.to_bytes().unwrap();
-                self.process_operation(&operation)
+                let call = incrementCall { input: increment };
+                self.runtime
+                    .call_evm_application(true, evm_counter_id, &call, Amount::ZERO)
             }
             CallCounterOperation::TestCallAddress => {
                 let remote_address = address!("0000000000000000000000000000000000000000");
-                let operation = call_from_wasmCall { remote_address };
-                let operation = EvmOperation::new(Amount::ZERO, operation.abi_encode());
-                let operation = operation.to_bytes().unwrap();
-                self.process_operation(&operation)
+                let call = call_from_wasmCall { remote_address };
+                self.runtime
+                    .call_evm_application(true, evm_counter_id, &call, Amount::ZERO)
             }
         }
     }
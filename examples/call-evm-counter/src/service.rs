@@ -5,12 +5,12 @@
 
 use std::sync::Arc;
 
-use alloy_primitives::{address, U256};
-use alloy_sol_types::{sol, SolCall};
+use alloy_primitives::address;
+use alloy_sol_types::sol;
 use call_evm_counter::{CallCounterOperation, CallCounterRequest};
 use linera_sdk::{
     abis::evm::EvmAbi,
-    linera_base_types::{ApplicationId, EvmQuery, WithServiceAbi},
+    linera_base_types::{ApplicationId, WithServiceAbi},
     Service, ServiceRuntime,
 };
 
@@ -24,16 +24,6 @@ impl WithServiceAbi for CallCounterService {
     type Abi = call_evm_counter::CallCounterAbi;
 }
 
-impl CallCounterService {
-    fn process_query(&self, query: Vec<u8>) -> u64 {
-        let query = EvmQuery::Query(query);
-        let evm_counter_id = self.runtime.application_parameters();
-        let result = self.runtime.query_application(evm_counter_id, &query);
-        let arr: [u8; 32] = result.try_into().expect("result should have length 32");
-        U256::from_be_bytes(arr).to::<u64>()
-    }
-}
-
 impl Service for CallCounterService {
     type Parameters = ApplicationId<EvmAbi>;
 
@@ -45,20 +35,18 @@ impl Service for CallCounterService {
 
     async fn handle_query(&self, request: CallCounterRequest) -> u64 {
         sol! {
-            function get_value();
-            function call_from_wasm(address remote_address);
+            function get_value() returns (uint64);
+            function call_from_wasm(address remote_address) returns (uint64);
         }
+        let evm_counter_id = self.runtime.application_parameters();
         match request {
-            CallCounterRequest::Query => {
-                let query = get_valueCall {};
-                let query = query.abi_encode();
-                self.process_query(query)
-            }
+            CallCounterRequest::Query => self
+                .runtime
+                .query_evm_application(evm_counter_id, &get_valueCall {}),
             CallCounterRequest::TestCallAddress => {
                 let remote_address = address!("0000000000000000000000000000000000002000");
-                let call_from_wasm = call_from_wasmCall { remote_address };
-                let call_from_wasm = call_from_wasm.abi_encode();
-                self.process_query(call_from_wasm)
+                let call = call_from_wasmCall { remote_address };
+                self.runtime.query_evm_application(evm_counter_id, &call)
             }
             CallCounterRequest::Increment(value) => {
                 let operation = CallCounterOperation::Increment(value);
@@ -0,0 +1,26 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decodes block proposals and runs the checks done by a validator before executing them,
+//! signing them with a known key so that the checks following the signature are reached.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linera_chain::data_types::BlockProposal;
+
+fuzz_target!(|data: &[u8]| {
+    let Some(proposal) = linera_fuzz::decode::<BlockProposal>(data) else {
+        return;
+    };
+    let _ = proposal.content.block.check_proposal_size(u64::MAX);
+    if proposal.check_invariants().is_err() {
+        return;
+    }
+    let _ = proposal.check_signature();
+    let proposal = linera_fuzz::sign_proposal(proposal);
+    assert!(proposal.check_signature().is_ok());
+    let _ = proposal.required_blob_ids().count();
+    let _ = proposal.expected_blob_ids().count();
+    let _ = proposal.content.block.published_blob_ids();
+});
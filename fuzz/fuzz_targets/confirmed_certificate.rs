@@ -0,0 +1,22 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decodes confirmed block certificates and checks them against a committee, both as
+//! received and signed by that committee.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linera_chain::types::ConfirmedBlockCertificate;
+
+fuzz_target!(|data: &[u8]| {
+    let Some(certificate) = linera_fuzz::decode::<ConfirmedBlockCertificate>(data) else {
+        return;
+    };
+    let _ = certificate.check(&linera_fuzz::COMMITTEE);
+    let certificate = linera_fuzz::sign_confirmed(certificate);
+    let _ = certificate.check(&linera_fuzz::COMMITTEE);
+    let _ = certificate.hash();
+    let _ = certificate.lite_certificate();
+    let _ = certificate.block().required_blob_ids();
+});
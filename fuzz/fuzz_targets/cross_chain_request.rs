@@ -0,0 +1,19 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decodes cross-chain requests exchanged between validator shards.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linera_base::data_types::BlockHeight;
+use linera_core::data_types::CrossChainRequest;
+
+fuzz_target!(|data: &[u8]| {
+    let Some(request) = linera_fuzz::decode::<CrossChainRequest>(data) else {
+        return;
+    };
+    let _ = request.target_chain_id();
+    let _ = request.has_messages_lower_or_equal_than(BlockHeight::ZERO);
+    let _ = request.has_messages_lower_or_equal_than(BlockHeight::MAX);
+});
@@ -0,0 +1,20 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decodes lite certificates and checks them against a committee, both as received and
+//! signed by that committee.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linera_chain::types::LiteCertificate;
+
+fuzz_target!(|data: &[u8]| {
+    let Some(certificate) = linera_fuzz::decode::<LiteCertificate<'static>>(data) else {
+        return;
+    };
+    let _ = certificate.check(&linera_fuzz::COMMITTEE);
+    let certificate = linera_fuzz::sign_lite(certificate);
+    let _ = certificate.check(&linera_fuzz::COMMITTEE);
+    let _ = certificate.full_justification_commitment();
+});
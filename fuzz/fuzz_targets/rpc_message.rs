@@ -0,0 +1,15 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decodes messages received by validators and proxies over the simple transport.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linera_rpc::RpcMessage;
+
+fuzz_target!(|data: &[u8]| {
+    if let Some(message) = linera_fuzz::decode::<RpcMessage>(data) {
+        let _ = message.target_chain_id();
+    }
+});
@@ -0,0 +1,21 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decodes validated block certificates and checks them against a committee, both as
+//! received and signed by that committee.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use linera_chain::types::ValidatedBlockCertificate;
+
+fuzz_target!(|data: &[u8]| {
+    let Some(certificate) = linera_fuzz::decode::<ValidatedBlockCertificate>(data) else {
+        return;
+    };
+    let _ = certificate.check(&linera_fuzz::COMMITTEE);
+    let certificate = linera_fuzz::sign_validated(certificate);
+    let _ = certificate.check(&linera_fuzz::COMMITTEE);
+    let _ = certificate.hash();
+    let _ = certificate.lite_certificate();
+});
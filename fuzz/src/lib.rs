@@ -0,0 +1,135 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Shared helpers of the fuzz targets.
+
+use std::{borrow::Cow, sync::LazyLock};
+
+use linera_base::crypto::{AccountPublicKey, AccountSecretKey, ValidatorKeypair};
+use linera_chain::{
+    data_types::{BlockProposal, LiteVote, Vote},
+    types::{
+        CertificateValue, ConfirmedBlockCertificate, GenericCertificate, LiteCertificate,
+        ValidatedBlockCertificate,
+    },
+};
+use linera_execution::committee::Committee;
+use rand_chacha::{rand_core::SeedableRng as _, ChaCha8Rng};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The seed of the keys used by the fuzz targets, fixed so that crashes are reproducible.
+const KEY_SEED: u64 = 0;
+
+/// The key pairs of the validators of [`COMMITTEE`].
+pub static VALIDATORS: LazyLock<Vec<ValidatorKeypair>> = LazyLock::new(|| {
+    let mut rng = ChaCha8Rng::seed_from_u64(KEY_SEED);
+    (0..4)
+        .map(|_| ValidatorKeypair::generate_from(&mut rng))
+        .collect()
+});
+
+/// A committee against which fuzzed certificates are checked.
+pub static COMMITTEE: LazyLock<Committee> = LazyLock::new(|| {
+    let keys = VALIDATORS
+        .iter()
+        .zip(0..)
+        .map(|(key_pair, i)| (key_pair.public_key, AccountPublicKey::test_key(i)))
+        .collect();
+    Committee::make_simple(keys)
+});
+
+/// The key with which fuzzed block proposals are signed.
+pub static PROPOSER: LazyLock<AccountSecretKey> = LazyLock::new(|| {
+    let mut rng = ChaCha8Rng::seed_from_u64(KEY_SEED + 1);
+    AccountSecretKey::generate_from(&mut rng)
+});
+
+/// Deserializes `data`, and checks that serializing the result again gives back the input.
+/// BCS is canonical, so any other outcome would make signatures and hashes ambiguous.
+pub fn decode<T: Serialize + DeserializeOwned>(data: &[u8]) -> Option<T> {
+    let value = bcs::from_bytes::<T>(data).ok()?;
+    let bytes = bcs::to_bytes(&value).expect("serializing a deserialized value should not fail");
+    assert_eq!(bytes, data, "BCS round-trip changed the input");
+    Some(value)
+}
+
+/// Replaces the signatures of `quorum` with those of all validators of [`COMMITTEE`] over
+/// the same payload, so that the checks behind signature verification are reached.
+pub fn sign_quorum<T: CertificateValue>(quorum: GenericCertificate<T>) -> GenericCertificate<T> {
+    let round = quorum.round();
+    let unlocking_round = quorum.unlocking_round();
+    let first_round = quorum.first_round();
+    let commitment = quorum.justification_commitment();
+    let value = quorum.into_value();
+    let signatures = VALIDATORS
+        .iter()
+        .map(|key_pair| {
+            let secret_key = &key_pair.secret_key;
+            let vote = if first_round {
+                Vote::new_with_first_round(value.clone(), round, true, commitment, secret_key)
+            } else {
+                Vote::new_with_unlocking_round(
+                    value.clone(),
+                    round,
+                    unlocking_round,
+                    commitment,
+                    secret_key,
+                )
+            };
+            (key_pair.public_key, vote.signature)
+        })
+        .collect();
+    GenericCertificate::new_with_payload(
+        value,
+        round,
+        unlocking_round,
+        first_round,
+        commitment,
+        signatures,
+    )
+}
+
+/// Signs the quorum of `certificate` with the validators of [`COMMITTEE`].
+pub fn sign_confirmed(certificate: ConfirmedBlockCertificate) -> ConfirmedBlockCertificate {
+    let (quorum, justification) = certificate.into_parts();
+    ConfirmedBlockCertificate::from_parts(sign_quorum(quorum), justification)
+}
+
+/// Signs the quorum of `certificate` with the validators of [`COMMITTEE`].
+pub fn sign_validated(certificate: ValidatedBlockCertificate) -> ValidatedBlockCertificate {
+    let (quorum, justification) = certificate.into_parts();
+    ValidatedBlockCertificate::from_parts(sign_quorum(quorum), justification)
+}
+
+/// Signs `certificate` with the validators of [`COMMITTEE`]. Lite votes can only be signed
+/// without an unlocking round, first-round attestation or justification commitment, so
+/// these are cleared.
+pub fn sign_lite(certificate: LiteCertificate<'_>) -> LiteCertificate<'static> {
+    let signatures = VALIDATORS
+        .iter()
+        .map(|key_pair| {
+            let vote = LiteVote::new(
+                certificate.value.clone(),
+                certificate.round,
+                &key_pair.secret_key,
+            );
+            (key_pair.public_key, vote.signature)
+        })
+        .collect();
+    let mut signed = LiteCertificate::new_with_payload(
+        certificate.value,
+        certificate.round,
+        None,
+        false,
+        None,
+        signatures,
+    );
+    signed.justification = Cow::Owned(certificate.justification.into_owned());
+    signed
+}
+
+/// Signs the content of `proposal` with [`PROPOSER`].
+pub fn sign_proposal(mut proposal: BlockProposal) -> BlockProposal {
+    proposal.signature = PROPOSER.sign(&proposal.content);
+    proposal
+}
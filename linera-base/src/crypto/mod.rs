@@ -9,7 +9,7 @@ mod hash;
 #[allow(dead_code)]
 mod secp256k1;
 pub mod signer;
-use std::{fmt::Display, io, num::ParseIntError, str::FromStr};
+use std::{collections::BTreeSet, fmt::Display, io, num::ParseIntError, str::FromStr};
 
 use allocative::Allocative;
 use alloy_primitives::FixedBytes;
@@ -36,6 +36,40 @@ pub type ValidatorSignature = secp256k1::Secp256k1Signature;
 /// The key pair of a validator.
 pub type ValidatorKeypair = secp256k1::Secp256k1KeyPair;
 
+/// Signs on behalf of a validator: either with the validator's secret key directly, or
+/// by delegating to a separate signer process that holds the key, e.g. in an enclave.
+///
+/// Signing is asynchronous and may fail, since a remote signer may be slow or unreachable.
+#[async_trait::async_trait]
+pub trait ValidatorSigner: Send + Sync {
+    /// Returns the public key of the validator.
+    fn public_key(&self) -> ValidatorPublicKey;
+
+    /// Signs the given prehashed message.
+    async fn sign_prehash(&self, prehash: CryptoHash) -> Result<ValidatorSignature, CryptoError>;
+}
+
+#[async_trait::async_trait]
+impl ValidatorSigner for ValidatorSecretKey {
+    fn public_key(&self) -> ValidatorPublicKey {
+        self.public()
+    }
+
+    async fn sign_prehash(&self, prehash: CryptoHash) -> Result<ValidatorSignature, CryptoError> {
+        Ok(ValidatorSignature::sign_prehash(self, prehash))
+    }
+}
+
+impl dyn ValidatorSigner + '_ {
+    /// Computes the validator's signature for `value`.
+    pub async fn sign<'de, T>(&self, value: &T) -> Result<ValidatorSignature, CryptoError>
+    where
+        T: BcsSignable<'de>,
+    {
+        self.sign_prehash(CryptoHash::new(value)).await
+    }
+}
+
 /// The public key of a chain owner.
 /// The corresponding private key is allowed to propose blocks
 /// on the chain and transfer account's tokens.
@@ -108,6 +142,46 @@ pub enum AccountSignature {
     },
 }
 
+/// A signature scheme chain owners can sign with.
+#[derive(
+    Serialize, Deserialize, Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone, Hash, Allocative,
+)]
+pub enum SignatureScheme {
+    /// Ed25519.
+    Ed25519,
+    /// secp256k1.
+    Secp256k1,
+    /// EVM secp256k1, identifying the signer by its EVM address.
+    EvmSecp256k1,
+}
+
+impl SignatureScheme {
+    /// Returns whether the scheme is in the `allowed` set of a chain. An empty set allows all
+    /// schemes.
+    pub fn is_allowed_by(self, allowed: &BTreeSet<SignatureScheme>) -> bool {
+        allowed.is_empty() || allowed.contains(&self)
+    }
+
+    /// Returns the schemes that an owner with the given address could sign with.
+    pub fn candidates_for(owner: &AccountOwner) -> &'static [SignatureScheme] {
+        match owner {
+            AccountOwner::Reserved(_) => &[],
+            AccountOwner::Address32(_) => &[SignatureScheme::Ed25519, SignatureScheme::Secp256k1],
+            AccountOwner::Address20(_) => &[SignatureScheme::EvmSecp256k1],
+        }
+    }
+}
+
+impl Display for SignatureScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignatureScheme::Ed25519 => write!(f, "Ed25519"),
+            SignatureScheme::Secp256k1 => write!(f, "secp256k1"),
+            SignatureScheme::EvmSecp256k1 => write!(f, "EVM secp256k1"),
+        }
+    }
+}
+
 impl AccountSecretKey {
     /// Returns the public key corresponding to this secret key.
     pub fn public(&self) -> AccountPublicKey {
@@ -206,6 +280,15 @@ impl AccountSecretKey {
 }
 
 impl AccountPublicKey {
+    /// Returns the signature scheme of the key.
+    pub fn scheme(&self) -> SignatureScheme {
+        match self {
+            AccountPublicKey::Ed25519(_) => SignatureScheme::Ed25519,
+            AccountPublicKey::Secp256k1(_) => SignatureScheme::Secp256k1,
+            AccountPublicKey::EvmSecp256k1(_) => SignatureScheme::EvmSecp256k1,
+        }
+    }
+
     /// Returns the byte representation of the public key.
     pub fn as_bytes(&self) -> Vec<u8> {
         bcs::to_bytes(&self).expect("serialization to bytes should not fail")
@@ -260,6 +343,15 @@ impl AccountSignature {
         bcs::from_bytes(bytes).map_err(CryptoError::SignatureParseError)
     }
 
+    /// Returns the scheme of the signature.
+    pub fn scheme(&self) -> SignatureScheme {
+        match self {
+            AccountSignature::Ed25519 { .. } => SignatureScheme::Ed25519,
+            AccountSignature::Secp256k1 { .. } => SignatureScheme::Secp256k1,
+            AccountSignature::EvmSecp256k1 { .. } => SignatureScheme::EvmSecp256k1,
+        }
+    }
+
     /// Returns the [`AccountOwner`] of the account that signed the value.
     pub fn owner(&self) -> AccountOwner {
         match self {
@@ -334,6 +426,8 @@ pub enum CryptoError {
     PublicKeyParseError(bcs::Error),
     #[error("could not parse signature: {0}")]
     SignatureParseError(bcs::Error),
+    #[error("the validator signer failed: {0}")]
+    SignerFailed(String),
 }
 
 #[cfg(with_getrandom)]
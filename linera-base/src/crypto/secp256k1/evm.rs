@@ -438,20 +438,41 @@ impl EvmSignature {
     where
         T: BcsSignable<'de> + fmt::Debug,
     {
-        let msg = CryptoHash::new(value).as_bytes().0;
+        let prehash = CryptoHash::new(value);
+        self.check_with_recover_inner(prehash, sender_address, T::type_name())
+    }
+
+    /// Checks a signature of a prehash against a recovered public key. This is what the
+    /// [`Signer`](crate::crypto::Signer) trait signs, and what external wallets sign as an
+    /// EIP-191 message, e.g. with `personal_sign`.
+    pub fn check_prehash_with_recover(
+        &self,
+        prehash: CryptoHash,
+        sender_address: [u8; 20],
+    ) -> Result<EvmPublicKey, CryptoError> {
+        self.check_with_recover_inner(prehash, sender_address, "CryptoHash")
+    }
+
+    fn check_with_recover_inner(
+        &self,
+        prehash: CryptoHash,
+        sender_address: [u8; 20],
+        type_name: &str,
+    ) -> Result<EvmPublicKey, CryptoError> {
+        let msg = prehash.as_bytes().0;
         let recovered_public_key = match self.0.recover_from_msg(msg) {
             Ok(public_key) => EvmPublicKey(public_key),
             Err(_) => {
                 return Err(CryptoError::InvalidSignature {
                     error: "Failed to recover public key from signature".to_string(),
-                    type_name: T::type_name().to_string(),
+                    type_name: type_name.to_string(),
                 });
             }
         };
         if recovered_public_key.address() != alloy_primitives::Address::new(sender_address) {
             return Err(CryptoError::InvalidSignature {
                 error: "Recovered public key does not match sender address".to_string(),
-                type_name: T::type_name().to_string(),
+                type_name: type_name.to_string(),
             });
         }
         Ok(recovered_public_key)
@@ -735,4 +756,33 @@ mod tests {
         let public_key = EvmPublicKey::recover_from_msg(&sig, &msg).unwrap();
         assert_eq!(public_key, key_pair.public_key);
     }
+
+    #[test]
+    fn external_wallet_signature() {
+        use std::str::FromStr;
+
+        use crate::crypto::{
+            secp256k1::evm::{EvmKeyPair, EvmSignature},
+            CryptoHash, TestString,
+        };
+        let key_pair = EvmKeyPair::generate();
+        let address = key_pair.public_key.address();
+        let msg = TestString("hello".into());
+        let prehash = CryptoHash::new(&msg);
+        // Wallets such as MetaMask return `personal_sign` signatures as hex.
+        let hex_signature = format!(
+            "0x{}",
+            hex::encode(EvmSignature::new(prehash, &key_pair.secret_key).as_bytes())
+        );
+        let sig = EvmSignature::from_str(&hex_signature).unwrap();
+
+        let public_key = sig
+            .check_prehash_with_recover(prehash, address.0 .0)
+            .unwrap();
+        assert_eq!(public_key, key_pair.public_key);
+        let other_prehash = CryptoHash::new(&TestString("bye".into()));
+        assert!(sig
+            .check_prehash_with_recover(other_prehash, address.0 .0)
+            .is_err());
+    }
 }
@@ -405,6 +405,23 @@ impl Secp256k1Signature {
         self.verify_inner::<T>(prehash, author)
     }
 
+    /// Checks a signature over a prehash.
+    pub fn check_prehash(
+        &self,
+        prehash: CryptoHash,
+        author: Secp256k1PublicKey,
+    ) -> Result<(), CryptoError> {
+        use k256::ecdsa::signature::hazmat::PrehashVerifier;
+
+        author
+            .0
+            .verify_prehash(&prehash.as_bytes().0, &self.0)
+            .map_err(|error| CryptoError::InvalidSignature {
+                error: error.to_string(),
+                type_name: "CryptoHash".to_string(),
+            })
+    }
+
     /// Verifies a batch of signatures.
     ///
     /// Returns an error on first failed signature.
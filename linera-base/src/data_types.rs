@@ -13,7 +13,10 @@ use std::{
     io, iter,
     num::ParseIntError,
     str::FromStr,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
 use allocative::{Allocative, Visitor};
@@ -535,17 +538,34 @@ impl TimeDelta {
 )]
 pub struct Timestamp(u64);
 
+/// How many microseconds [`Timestamp::now`] is ahead of the system clock.
+static VIRTUAL_TIME_OFFSET: AtomicU64 = AtomicU64::new(0);
+
 impl Timestamp {
-    /// Returns the current time according to the system clock.
+    /// Returns the current time according to the system clock, shifted by the virtual time
+    /// offset, if any.
     pub fn now() -> Timestamp {
-        Timestamp(
-            SystemTime::UNIX_EPOCH
-                .elapsed()
-                .expect("system time should be after Unix epoch")
-                .as_micros()
-                .try_into()
-                .unwrap_or(u64::MAX),
-        )
+        let micros: u64 = SystemTime::UNIX_EPOCH
+            .elapsed()
+            .expect("system time should be after Unix epoch")
+            .as_micros()
+            .try_into()
+            .unwrap_or(u64::MAX);
+        Timestamp(micros.saturating_add(VIRTUAL_TIME_OFFSET.load(Ordering::Relaxed)))
+    }
+
+    /// Sets how far [`Timestamp::now`] is ahead of the system clock in this process.
+    ///
+    /// This is only meant for local test networks running in virtual time, where all
+    /// processes share the same offset: the offset should only ever increase, so that time
+    /// never goes backwards.
+    pub fn set_virtual_offset(offset: TimeDelta) {
+        VIRTUAL_TIME_OFFSET.store(offset.as_micros(), Ordering::Relaxed);
+    }
+
+    /// Returns how far [`Timestamp::now`] is ahead of the system clock in this process.
+    pub fn virtual_offset() -> TimeDelta {
+        TimeDelta::from_micros(VIRTUAL_TIME_OFFSET.load(Ordering::Relaxed))
     }
 
     /// Returns the number of microseconds since the Unix epoch.
@@ -657,6 +677,59 @@ pub struct Resources {
     // TODO(#1533): Allow declaring calls to other applications instead of having to count them here.
 }
 
+/// The size of the committee of validators currently in charge of a chain.
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, WitLoad, WitStore, WitType,
+)]
+pub struct CommitteeSummary {
+    /// The number of validators in the committee.
+    pub validator_count: u32,
+    /// The total voting power, i.e. stake, of the validators.
+    pub total_votes: u64,
+}
+
+/// The prices of the resources consumed by a block, as set by the committee's resource
+/// control policy.
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize, WitLoad, WitStore, WitType,
+)]
+pub struct ResourcePrices {
+    /// The price per unit of fuel (aka gas) for Wasm execution.
+    pub wasm_fuel_unit: Amount,
+    /// The price per unit of fuel (aka gas) for EVM execution.
+    pub evm_fuel_unit: Amount,
+    /// The price of one read operation.
+    pub read_operation: Amount,
+    /// The price of one write operation.
+    pub write_operation: Amount,
+    /// The price of accessing one byte from the runtime.
+    pub byte_runtime: Amount,
+    /// The price of reading a byte.
+    pub byte_read: Amount,
+    /// The price of writing a byte.
+    pub byte_written: Amount,
+    /// The base price to read a blob.
+    pub blob_read: Amount,
+    /// The base price to publish a blob.
+    pub blob_published: Amount,
+    /// The price to read a blob, per byte.
+    pub blob_byte_read: Amount,
+    /// The price to publish a blob, per byte.
+    pub blob_byte_published: Amount,
+    /// The base price of adding an operation to a block.
+    pub operation: Amount,
+    /// The additional price for each byte in the argument of a user operation.
+    pub operation_byte: Amount,
+    /// The base price of sending a message from a block.
+    pub message: Amount,
+    /// The additional price for each byte in the argument of a user message.
+    pub message_byte: Amount,
+    /// The price per query to a service as an oracle.
+    pub service_as_oracle_query: Amount,
+    /// The price for a performing an HTTP request.
+    pub http_request: Amount,
+}
+
 /// A request to send a message.
 #[derive(Clone, Debug, Deserialize, Serialize, WitLoad, WitType)]
 #[cfg_attr(with_testing, derive(Eq, PartialEq, WitStore))]
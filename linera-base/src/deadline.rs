@@ -0,0 +1,121 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/*!
+Deadlines of the requests being served, so that work done for a caller that already gave
+up can be abandoned early.
+
+A server sets the deadline of a request with [`scope`], and the code running on its
+behalf calls [`check`] before expensive steps, e.g. waiting for a lock or reading from
+storage. Deadlines are only tracked natively; on Wasm, [`check`] always succeeds.
+ */
+
+use std::future::Future;
+
+use crate::time::Instant;
+
+/// The error returned when the deadline of the request being served has passed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("the deadline of the request has passed")]
+pub struct DeadlineExceeded;
+
+#[cfg(not(target_arch = "wasm32"))]
+tokio::task_local! {
+    static DEADLINE: Instant;
+}
+
+/// Runs `future` on behalf of a request with the given deadline, if any.
+///
+/// If an enclosing scope already has an earlier deadline, that one is kept.
+#[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+pub async fn scope<F: Future>(deadline: Option<Instant>, future: F) -> F::Output {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(deadline) =
+        deadline.filter(|deadline| current().is_none_or(|outer| *deadline < outer))
+    {
+        return DEADLINE.scope(deadline, future).await;
+    }
+    future.await
+}
+
+/// Returns the deadline of the request being served, if any.
+pub fn current() -> Option<Instant> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        DEADLINE.try_with(|deadline| *deadline).ok()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        None
+    }
+}
+
+/// Returns an error if the deadline of the request being served has passed.
+///
+/// The `stage` names the work that is abandoned, for the metrics.
+#[cfg_attr(not(with_metrics), allow(unused_variables))]
+pub fn check(stage: &'static str) -> Result<(), DeadlineExceeded> {
+    match current() {
+        Some(deadline) if Instant::now() >= deadline => {
+            #[cfg(with_metrics)]
+            metrics::DEADLINE_EXCEEDED_COUNT
+                .with_label_values(&[stage])
+                .inc();
+            Err(DeadlineExceeded)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(with_metrics)]
+mod metrics {
+    use std::sync::LazyLock;
+
+    use prometheus::IntCounterVec;
+
+    use crate::prometheus_util::register_int_counter_vec;
+
+    /// The number of times work was abandoned because its request's deadline had passed.
+    pub static DEADLINE_EXCEEDED_COUNT: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec(
+            "request_deadline_exceeded",
+            "Work abandoned because the deadline of its request had passed, by stage",
+            &["stage"],
+        )
+    });
+}
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::{check, current, scope, DeadlineExceeded};
+    use crate::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn test_deadline_scope() {
+        assert_eq!(current(), None);
+        assert_eq!(check("test"), Ok(()));
+
+        let now = Instant::now();
+        let later = now + Duration::from_secs(60);
+        let earlier = now - Duration::from_secs(1);
+        scope(Some(later), async {
+            assert_eq!(current(), Some(later));
+            assert_eq!(check("test"), Ok(()));
+            // A later deadline does not extend the enclosing one.
+            scope(Some(later + Duration::from_secs(1)), async {
+                assert_eq!(current(), Some(later));
+            })
+            .await;
+            scope(Some(earlier), async {
+                assert_eq!(check("test"), Err(DeadlineExceeded));
+            })
+            .await;
+            scope(None, async {
+                assert_eq!(current(), Some(later));
+            })
+            .await;
+        })
+        .await;
+        assert_eq!(current(), None);
+    }
+}
@@ -29,6 +29,7 @@ pub mod abi;
 pub mod command;
 pub mod crypto;
 pub mod data_types;
+pub mod deadline;
 mod graphql;
 pub mod hashed;
 pub mod http;
@@ -16,6 +16,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
+    crypto::SignatureScheme,
     data_types::{Round, TimeDelta},
     doc_scalar,
     identifiers::AccountOwner,
@@ -225,6 +226,23 @@ impl ChainOwnership {
         Some(next_round)
     }
 
+    /// Returns an owner or the first leader whose address cannot belong to a key of one of the
+    /// `allowed` signature schemes, if any. Such an owner could never propose a block.
+    pub fn owner_with_disallowed_signature_scheme(
+        &self,
+        allowed: &BTreeSet<SignatureScheme>,
+    ) -> Option<&AccountOwner> {
+        self.all_owners()
+            .chain(&self.first_leader)
+            .find(|owner| match owner {
+                // Reserved owners don't sign anything.
+                AccountOwner::Reserved(_) => false,
+                _ => !SignatureScheme::candidates_for(owner)
+                    .iter()
+                    .any(|scheme| scheme.is_allowed_by(allowed)),
+            })
+    }
+
     /// Returns whether the given owner a super owner and there are no regular owners.
     pub fn is_super_owner_no_regular_owners(&self, owner: &AccountOwner) -> bool {
         self.owners.is_empty() && self.super_owners.contains(owner)
@@ -297,6 +315,33 @@ mod tests {
             Some(TimeDelta::from_secs(18))
         );
     }
+
+    #[test]
+    fn test_ownership_signature_schemes() {
+        let ed25519_owner = AccountOwner::from(Ed25519SecretKey::generate().public());
+        let evm_owner = AccountOwner::Address20([1; 20]);
+        let mut ownership = ChainOwnership::single(ed25519_owner).with_regular_owner(evm_owner, 1);
+        let all = BTreeSet::new();
+        assert!(SignatureScheme::Secp256k1.is_allowed_by(&all));
+        assert_eq!(ownership.owner_with_disallowed_signature_scheme(&all), None);
+
+        let ed25519 = BTreeSet::from([SignatureScheme::Ed25519]);
+        assert!(SignatureScheme::Ed25519.is_allowed_by(&ed25519));
+        assert!(!SignatureScheme::EvmSecp256k1.is_allowed_by(&ed25519));
+        assert_eq!(
+            ownership.owner_with_disallowed_signature_scheme(&ed25519),
+            Some(&evm_owner)
+        );
+
+        let evm = BTreeSet::from([SignatureScheme::EvmSecp256k1]);
+        assert_eq!(
+            ownership.owner_with_disallowed_signature_scheme(&evm),
+            Some(&ed25519_owner)
+        );
+        ownership.owners.remove(&ed25519_owner);
+        ownership.owners.insert(AccountOwner::CHAIN, 1);
+        assert_eq!(ownership.owner_with_disallowed_signature_scheme(&evm), None);
+    }
 }
 
 doc_scalar!(ChainOwnership, "Represents the owner(s) of a chain");
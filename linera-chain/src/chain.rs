@@ -11,7 +11,7 @@ use linera_base::{
     crypto::{CryptoHash, ValidatorPublicKey},
     data_types::{
         ApplicationDescription, ApplicationPermissions, ArithmeticError, Blob, BlockHeight, Cursor,
-        Epoch, NonCanonicalBTreeMap, NonCanonicalBTreeSet, OracleResponse, Timestamp,
+        Epoch, NonCanonicalBTreeMap, NonCanonicalBTreeSet, OracleResponse, TimeDelta, Timestamp,
     },
     ensure,
     hashed::Hashed,
@@ -47,7 +47,7 @@ use crate::{
     manager::ChainManager,
     outbox::OutboxStateView,
     pending_blobs::PendingBlobsView,
-    ChainError, ChainExecutionContext, ExecutionError, ExecutionResultExt,
+    scheduler, ChainError, ChainExecutionContext, ExecutionError, ExecutionResultExt,
 };
 
 #[cfg(test)]
@@ -315,7 +315,8 @@ where
     /// Outboxes with at least one pending message. This allows us to avoid loading all outboxes.
     pub nonempty_outboxes: RegisterView<C, NonCanonicalBTreeSet<ChainId>>,
 
-    /// Inboxes with at least one pending added bundle. This allows us to avoid loading all inboxes.
+    /// Inboxes with at least one pending added bundle or block reference. This allows us to avoid
+    /// loading all inboxes.
     pub nonempty_inboxes: RegisterView<C, NonCanonicalBTreeSet<ChainId>>,
 
     /// The local wall-clock time when this chain's state was last established — by
@@ -355,6 +356,15 @@ where
     /// they have never been filtered — a pre-existing database entry (migration), or a validator
     /// that tracks all chains and never filters.
     pub outbox_index_tracked_hash: RegisterView<C, Option<CryptoHash>>,
+
+    /// The local wall-clock time when this ephemeral chain's state was pruned, if it was.
+    /// A pruned chain keeps its tip, consensus and system state, so that it is never
+    /// re-initialized from its description, but no longer accepts blocks or queries.
+    pub pruned_at: RegisterView<C, Option<Timestamp>>,
+
+    /// Origins whose cross-chain update was refused because their inbox was full. Once such an
+    /// inbox has room again, the worker asks the origin to resend the refused bundles.
+    pub refused_inboxes: RegisterView<C, NonCanonicalBTreeSet<ChainId>>,
 }
 
 /// Block-chaining state.
@@ -555,6 +565,34 @@ where
         Ok(())
     }
 
+    /// Returns whether this is a closed ephemeral chain whose latest block is at least
+    /// `retention` older than `local_time`, with no outgoing messages left to deliver.
+    pub fn is_prunable(&self, local_time: Timestamp, retention: TimeDelta) -> bool {
+        let system = &self.execution_state.system;
+        self.pruned_at.get().is_none()
+            && *system.ephemeral.get()
+            && *system.closed.get()
+            && self.nonempty_outboxes.get().is_empty()
+            && system.progress.get().timestamp.saturating_add(retention) <= local_time
+    }
+
+    /// Deletes the application state, inboxes, received log and pending blobs of this
+    /// chain. The chain tip, consensus and system state are kept, so that the chain still
+    /// reports as closed instead of being re-initialized.
+    pub fn prune(&mut self, local_time: Timestamp) {
+        self.execution_state.users.clear();
+        self.execution_state.event_indexes.clear();
+        self.pending_validated_blobs.clear();
+        self.pending_proposed_blobs.clear();
+        self.received_log.clear();
+        self.received_certificate_trackers.clear();
+        self.inboxes.clear();
+        self.nonempty_inboxes.clear();
+        self.refused_inboxes.clear();
+        self.next_expected_events.clear();
+        self.pruned_at.set(Some(local_time));
+    }
+
     /// Inserts `(height, hash)` into `block_hashes` and updates the
     /// `next_height_to_preprocess` register accordingly. Every write to
     /// `block_hashes` must go through this helper so the register stays in sync.
@@ -591,7 +629,6 @@ where
         add_to_received_log: bool,
     ) -> Result<(), ChainError> {
         assert!(!bundle.messages.is_empty());
-        let chain_id = self.chain_id();
         tracing::trace!(
             "Processing new messages from {origin} at height {}",
             bundle.height,
@@ -601,19 +638,7 @@ where
             height: bundle.height,
         };
 
-        match self.initialize_if_needed(local_time).await {
-            Ok(_) => (),
-            // if the only issue was that we couldn't initialize the chain because of a
-            // missing chain description blob, we might still want to update the inbox
-            Err(ChainError::ExecutionError(exec_err, _))
-                if matches!(*exec_err, ExecutionError::BlobsNotFound(ref blobs)
-                if blobs.iter().all(|blob_id| {
-                    blob_id.blob_type == BlobType::ChainDescription && blob_id.hash == chain_id.0
-                })) => {}
-            err => {
-                return err;
-            }
-        }
+        self.initialize_for_inbox(local_time).await?;
 
         // Process the inbox bundle and update the inbox state.
         let newly_added = inbox
@@ -636,6 +661,54 @@ where
         Ok(())
     }
 
+    /// Records that the block of `origin` at the given height and with the given hash has
+    /// message bundles for this chain, without reading them yet. The bundles are added to
+    /// the inbox once they are about to be consumed.
+    #[instrument(skip_all, fields(
+        chain_id = %self.chain_id(),
+        origin = %origin,
+        %height
+    ))]
+    pub async fn receive_block_reference_with_inbox(
+        &mut self,
+        inbox: &mut InboxStateView<C>,
+        origin: &ChainId,
+        height: BlockHeight,
+        hash: CryptoHash,
+        local_time: Timestamp,
+    ) -> Result<(), ChainError> {
+        tracing::trace!("Processing a new block reference from {origin} at height {height}");
+        self.initialize_for_inbox(local_time).await?;
+        inbox.block_references.push_back((height, hash));
+        self.nonempty_inboxes.get_mut().insert(*origin);
+        // Remember the certificate for future validator/client synchronizations.
+        self.received_log.push(ChainAndHeight {
+            chain_id: *origin,
+            height,
+        });
+        Ok(())
+    }
+
+    /// Initializes the chain before updating an inbox, unless only its description blob
+    /// is missing.
+    async fn initialize_for_inbox(&mut self, local_time: Timestamp) -> Result<(), ChainError> {
+        let chain_id = self.chain_id();
+        match self.initialize_if_needed(local_time).await {
+            Ok(_) => Ok(()),
+            // if the only issue was that we couldn't initialize the chain because of a
+            // missing chain description blob, we might still want to update the inbox
+            Err(ChainError::ExecutionError(exec_err, _))
+                if matches!(*exec_err, ExecutionError::BlobsNotFound(ref blobs)
+                if blobs.iter().all(|blob_id| {
+                    blob_id.blob_type == BlobType::ChainDescription && blob_id.hash == chain_id.0
+                })) =>
+            {
+                Ok(())
+            }
+            err => err,
+        }
+    }
+
     /// Updates the `received_log` trackers.
     pub fn update_received_certificate_trackers(
         &mut self,
@@ -729,7 +802,7 @@ where
                 }
             }
             inbox.observe_size_metric();
-            if inbox.added_bundles.count() == 0 {
+            if inbox.pending_count() == 0 {
                 self.nonempty_inboxes.get_mut().remove(&origin);
             }
         }
@@ -882,7 +955,7 @@ where
             None
         };
 
-        chain.system.progress.get_mut().timestamp = block.timestamp;
+        chain.system.set_block_timestamp(block.timestamp);
 
         let start_epoch = *chain.system.epoch.get();
 
@@ -932,7 +1005,22 @@ where
         let mut cumulative_bundle_time = Duration::ZERO;
 
         let mut i = 0;
+        let mut batch_end = 0;
         while i < block.transactions.len() {
+            if i >= batch_end {
+                // Load the contracts of the next batch of independent transactions together.
+                let batch = scheduler::first_batch(&block.transactions[i..]);
+                batch_end = i + batch.len();
+                if batch.len() > 1 {
+                    chain
+                        .preload_contracts(
+                            batch
+                                .into_iter()
+                                .flat_map(|footprint| footprint.applications),
+                        )
+                        .await;
+                }
+            }
             let transaction = &mut block.transactions[i];
             let is_bundle = matches!(transaction, Transaction::ReceiveMessages(_));
             let is_stream_update = transaction.is_update_stream();
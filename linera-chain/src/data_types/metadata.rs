@@ -110,6 +110,8 @@ pub struct SystemOperationMetadata {
     pub epoch: Option<i32>,
     /// `UpdateStream` operation details
     pub update_stream: Option<UpdateStreamMetadata>,
+    /// The allowed signature schemes (`ChangeSignatureSchemes`); if empty, all are allowed
+    pub signature_schemes: Option<Vec<String>>,
 }
 
 impl SystemOperationMetadata {
@@ -129,6 +131,7 @@ impl SystemOperationMetadata {
             publish_module: None,
             epoch: None,
             update_stream: None,
+            signature_schemes: None,
         }
     }
 }
@@ -443,6 +446,13 @@ impl From<&SystemOperation> for SystemOperationMetadata {
                 ..SystemOperationMetadata::new("UpdateStream")
             },
             SystemOperation::Checkpoint => SystemOperationMetadata::new("Checkpoint"),
+            SystemOperation::ChangeSignatureSchemes(schemes) => SystemOperationMetadata {
+                signature_schemes: Some(
+                    schemes.iter().map(|scheme| format!("{scheme:?}")).collect(),
+                ),
+                ..SystemOperationMetadata::new("ChangeSignatureSchemes")
+            },
+            SystemOperation::MakeEphemeral => SystemOperationMetadata::new("MakeEphemeral"),
         }
     }
 }
@@ -465,6 +475,26 @@ impl From<&AdminOperation> for AdminOperationMetadata {
                 epoch: Some(epoch.0 as i32),
                 blob_hash: None,
             },
+            AdminOperation::ProposeCommittee { blob_hash, .. } => AdminOperationMetadata {
+                admin_operation_type: "ProposeCommittee".to_string(),
+                epoch: None,
+                blob_hash: Some(*blob_hash),
+            },
+            AdminOperation::VoteOnProposal { .. } => AdminOperationMetadata {
+                admin_operation_type: "VoteOnProposal".to_string(),
+                epoch: None,
+                blob_hash: None,
+            },
+            AdminOperation::ResolveProposal { .. } => AdminOperationMetadata {
+                admin_operation_type: "ResolveProposal".to_string(),
+                epoch: None,
+                blob_hash: None,
+            },
+            AdminOperation::UpdateHttpAllowList { blob_hash, .. } => AdminOperationMetadata {
+                admin_operation_type: "UpdateHttpAllowList".to_string(),
+                epoch: None,
+                blob_hash: Some(*blob_hash),
+            },
         }
     }
 }
@@ -14,7 +14,7 @@ use linera_base::{
     bcs,
     crypto::{
         AccountSignature, BcsHashable, BcsSignable, CryptoError, CryptoHash, Signer,
-        ValidatorPublicKey, ValidatorSecretKey, ValidatorSignature,
+        ValidatorPublicKey, ValidatorSecretKey, ValidatorSignature, ValidatorSigner,
     },
     data_types::{
         Amount, Blob, BlockHeight, Cursor, Epoch, Event, MessagePolicy, OracleResponse, Round,
@@ -695,6 +695,38 @@ impl<T> Vote<T> {
         }
     }
 
+    /// Uses the validator signer to create a signed object with the given unlocking round,
+    /// first-round attestation and justification commitment (see [`VoteValue`]).
+    pub async fn signed_by(
+        value: T,
+        round: Round,
+        unlocking_round: Option<Round>,
+        first_round: bool,
+        justification_commitment: Option<CryptoHash>,
+        signer: &dyn ValidatorSigner,
+    ) -> Result<Self, CryptoError>
+    where
+        T: CertificateValue,
+    {
+        let hash_and_round = VoteValue(
+            value.hash(),
+            round,
+            T::KIND,
+            unlocking_round,
+            first_round,
+            justification_commitment,
+        );
+        let signature = signer.sign(&hash_and_round).await?;
+        Ok(Self {
+            value,
+            round,
+            unlocking_round,
+            first_round,
+            justification_commitment,
+            signature,
+        })
+    }
+
     /// Returns the vote, with a `LiteValue` instead of the full value.
     pub fn lite(&self) -> LiteVote
     where
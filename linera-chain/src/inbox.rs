@@ -3,6 +3,7 @@
 
 use allocative::Allocative;
 use linera_base::{
+    crypto::CryptoHash,
     data_types::{ArithmeticError, BlockHeight, Cursor},
     ensure,
     identifiers::ChainId,
@@ -85,6 +86,11 @@ where
     /// validator (e.g. when a sender re-pushes) must be a no-op rather than re-enter
     /// the inbox or the removed-by-anticipation queue.
     pub restored_cursor: RegisterView<C, Cursor>,
+    /// The heights and hashes of the origin's blocks with bundles for this chain that were
+    /// received in pull mode, by increasing heights. Their bundles are read from storage
+    /// and added to `added_bundles` only once they are about to be consumed.
+    #[cfg_attr(with_graphql, graphql(skip))]
+    pub block_references: QueueView<C, (BlockHeight, CryptoHash)>,
 }
 
 #[derive(Error, Debug)]
@@ -150,7 +156,7 @@ where
 {
     /// Converts the internal cursor for added bundles into an externally-visible block height.
     /// This makes sense because the rest of the system always adds bundles one block at a time.
-    pub fn next_block_height_to_receive(&self) -> Result<BlockHeight, ChainError> {
+    pub fn next_block_height_to_add(&self) -> Result<BlockHeight, ChainError> {
         let cursor = self.next_cursor_to_add.get();
         if cursor.index == 0 {
             Ok(cursor.height)
@@ -159,12 +165,26 @@ where
         }
     }
 
+    /// Returns the next block height to receive from the origin, taking into account the
+    /// blocks that were only referenced so far.
+    pub async fn next_block_height_to_receive(&self) -> Result<BlockHeight, ChainError> {
+        match self.block_references.back().await? {
+            Some((height, _)) => Ok(height.try_add_one()?),
+            None => self.next_block_height_to_add(),
+        }
+    }
+
+    /// Returns the number of added bundles and block references waiting to be consumed.
+    pub fn pending_count(&self) -> usize {
+        self.added_bundles.count() + self.block_references.count()
+    }
+
     /// Observes the current inbox size in the metrics histogram.
     pub fn observe_size_metric(&self) {
         #[cfg(with_metrics)]
         metrics::INBOX_SIZE
             .with_label_values(&[])
-            .observe(self.added_bundles.count() as f64);
+            .observe(self.pending_count() as f64);
     }
 
     /// Reconciles this inbox with the producer's snapshot at checkpoint time.
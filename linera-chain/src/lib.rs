@@ -23,6 +23,7 @@ pub mod justification;
 pub mod manager;
 mod outbox;
 mod pending_blobs;
+pub mod scheduler;
 #[cfg(with_testing)]
 pub mod test;
 
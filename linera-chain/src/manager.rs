@@ -74,7 +74,7 @@ use allocative::Allocative;
 use custom_debug_derive::Debug;
 use futures::future::Either;
 use linera_base::{
-    crypto::{AccountPublicKey, ValidatorSecretKey},
+    crypto::{AccountPublicKey, ValidatorSigner},
     data_types::{Blob, BlockHeight, Epoch, NonCanonicalBTreeMap, Round, Timestamp},
     ensure,
     identifiers::{AccountOwner, BlobId, ChainId},
@@ -90,7 +90,7 @@ use linera_views::{
 };
 use rand_chacha::{rand_core::SeedableRng, ChaCha8Rng};
 use rand_distr::{Distribution, WeightedAliasIndex};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{
     block::{Block, ConfirmedBlock, Timeout, ValidatedBlock},
@@ -271,6 +271,28 @@ where
         self.validated_vote.get().as_ref()
     }
 
+    /// Returns the persisted votes and proposals that the pending changes discard, e.g.
+    /// because the chain moved on to a later round or block height.
+    pub async fn superseded_round_state(&self) -> Option<SupersededRoundState> {
+        let proposed = replaced_value(&self.proposed).await;
+        let signed_proposal = replaced_value(&self.signed_proposal)
+            .await
+            .filter(|signed| {
+                proposed
+                    .as_ref()
+                    .is_none_or(|proposed| proposed.content != signed.content)
+            });
+        let state = SupersededRoundState {
+            signed_proposal,
+            proposed,
+            confirmed_vote: replaced_value(&self.confirmed_vote).await,
+            validated_vote: replaced_value(&self.validated_vote).await,
+            timeout_vote: replaced_value(&self.timeout_vote).await,
+            fallback_vote: replaced_value(&self.fallback_vote).await,
+        };
+        (!state.is_empty()).then_some(state)
+    }
+
     /// Returns the lowest round where we can still vote to validate or confirm a block. This is
     /// the round to which the timeout applies.
     ///
@@ -370,13 +392,13 @@ where
 
     /// Checks if the current round has timed out, and signs a `Timeout`. Returns `true` if the
     /// chain manager's state has changed.
-    pub fn create_timeout_vote(
+    pub async fn create_timeout_vote(
         &mut self,
         chain_id: ChainId,
         height: BlockHeight,
         round: Round,
         epoch: Epoch,
-        key_pair: Option<&ValidatorSecretKey>,
+        key_pair: Option<&dyn ValidatorSigner>,
         local_time: Timestamp,
     ) -> Result<bool, ChainError> {
         let Some(key_pair) = key_pair else {
@@ -399,8 +421,8 @@ where
             }
         }
         let value = Timeout::new(chain_id, height, epoch);
-        self.timeout_vote
-            .set(Some(Vote::new(value, round, key_pair)));
+        let vote = Vote::signed_by(value, round, None, false, None, key_pair).await?;
+        self.timeout_vote.set(Some(vote));
         Ok(true)
     }
 
@@ -408,24 +430,24 @@ where
     ///
     /// This must only be called after verifying that the condition for fallback mode is
     /// satisfied locally.
-    pub fn vote_fallback(
+    pub async fn vote_fallback(
         &mut self,
         chain_id: ChainId,
         height: BlockHeight,
         epoch: Epoch,
-        key_pair: Option<&ValidatorSecretKey>,
-    ) -> bool {
+        key_pair: Option<&dyn ValidatorSigner>,
+    ) -> Result<bool, ChainError> {
         let Some(key_pair) = key_pair else {
-            return false; // We are not a validator.
+            return Ok(false); // We are not a validator.
         };
         if self.fallback_vote.get().is_some() || self.current_round() >= Round::Validator(0) {
-            return false; // We already signed this or are already in fallback mode.
+            return Ok(false); // We already signed this or are already in fallback mode.
         }
         let value = Timeout::new(chain_id, height, epoch);
         let last_regular_round = Round::SingleLeader(u32::MAX);
-        self.fallback_vote
-            .set(Some(Vote::new(value, last_regular_round, key_pair)));
-        true
+        let vote = Vote::signed_by(value, last_regular_round, None, false, None, key_pair).await?;
+        self.fallback_vote.set(Some(vote));
+        Ok(true)
     }
 
     /// Verifies that a validated block is still relevant and should be handled.
@@ -456,11 +478,11 @@ where
     }
 
     /// Signs a vote to validate the proposed block.
-    pub fn create_vote(
+    pub async fn create_vote(
         &mut self,
         proposal: &BlockProposal,
         block: Block,
-        key_pair: Option<&ValidatorSecretKey>,
+        key_pair: Option<&dyn ValidatorSigner>,
         local_time: Timestamp,
         blobs: BTreeMap<BlobId, Blob>,
     ) -> Result<Option<ValidatedOrConfirmedVote<'_>>, ChainError> {
@@ -520,7 +542,7 @@ where
             // chain may be omitted: such a block is always the lower one in any fork. A fast
             // block needs no validation, so there is no quorum to commit to.
             let first_round = round == self.ownership.get().first_round();
-            let vote = Vote::new_with_first_round(value, round, first_round, None, key_pair);
+            let vote = Vote::signed_by(value, round, None, first_round, None, key_pair).await?;
             Ok(Some(Either::Right(
                 self.confirmed_vote.get_mut().insert(vote),
             )))
@@ -539,13 +561,15 @@ where
                 Some(OriginalProposal::Fast(_)) | None => (None, None),
             };
             let value = ValidatedBlock::new(block);
-            let vote = Vote::new_with_unlocking_round(
+            let vote = Vote::signed_by(
                 value,
                 round,
                 unlocking_round,
+                false,
                 justification_commitment,
                 key_pair,
-            );
+            )
+            .await?;
             Ok(Some(Either::Left(
                 self.validated_vote.get_mut().insert(vote),
             )))
@@ -553,13 +577,13 @@ where
     }
 
     /// Signs a vote to confirm the validated block.
-    pub fn create_final_vote(
+    pub async fn create_final_vote(
         &mut self,
         validated: ValidatedBlockCertificate,
-        key_pair: Option<&ValidatorSecretKey>,
+        key_pair: Option<&dyn ValidatorSigner>,
         local_time: Timestamp,
         blobs: BTreeMap<BlobId, Blob>,
-    ) -> Result<(), ViewError> {
+    ) -> Result<(), ChainError> {
         let round = validated.round;
         let confirmed_block = ConfirmedBlock::new(validated.inner().block().clone());
         // Vote to confirm. Attest whether this confirmation is in the chain's first round, so the
@@ -578,13 +602,15 @@ where
             if self.current_round() != round {
                 return Ok(()); // We never vote in a past round.
             }
-            let vote = Vote::new_with_first_round(
+            let vote = Vote::signed_by(
                 confirmed_block,
                 round,
+                None,
                 first_round,
                 justification_commitment,
                 key_pair,
-            );
+            )
+            .await?;
             // Ok to overwrite validation votes with confirmation votes at equal or higher round.
             self.confirmed_vote.set(Some(vote));
             self.validated_vote.set(None);
@@ -857,6 +883,49 @@ impl ManagerSafetySnapshot {
     }
 }
 
+/// Returns the value persisted in `register` if a pending change replaces it.
+async fn replaced_value<C, T>(register: &RegisterView<C, Option<T>>) -> Option<T>
+where
+    C: Context,
+    T: Clone + Send + Sync + Serialize + DeserializeOwned,
+{
+    if register.has_pending_changes().await {
+        register.stored_value().clone()
+    } else {
+        None
+    }
+}
+
+/// Votes and proposals that a chain manager discarded. Validators keep them in storage for
+/// a short time only, e.g. to investigate a reported equivocation.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SupersededRoundState {
+    /// The discarded authenticated proposal, if it differs from `proposed`.
+    pub signed_proposal: Option<BlockProposal>,
+    /// The discarded checked proposal.
+    pub proposed: Option<BlockProposal>,
+    /// The discarded vote to confirm a block.
+    pub confirmed_vote: Option<Vote<ConfirmedBlock>>,
+    /// The discarded vote to validate a block.
+    pub validated_vote: Option<Vote<ValidatedBlock>>,
+    /// The discarded timeout vote.
+    pub timeout_vote: Option<Vote<Timeout>>,
+    /// The discarded fallback vote.
+    pub fallback_vote: Option<Vote<Timeout>>,
+}
+
+impl SupersededRoundState {
+    /// Returns whether nothing was discarded.
+    pub fn is_empty(&self) -> bool {
+        self.signed_proposal.is_none()
+            && self.proposed.is_none()
+            && self.confirmed_vote.is_none()
+            && self.validated_vote.is_none()
+            && self.timeout_vote.is_none()
+            && self.fallback_vote.is_none()
+    }
+}
+
 /// Chain manager information that is included in `ChainInfo` sent to clients.
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(with_testing, derive(Eq, PartialEq))]
@@ -0,0 +1,95 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Scheduling the transactions of a block into batches of independent transactions.
+//!
+//! A transaction's footprint is the set of applications whose state it targets, plus
+//! whether it changes the system state beyond the fees and counters every transaction
+//! updates. Consecutive transactions with disjoint footprints form a batch: the
+//! applications they need are loaded concurrently before the batch executes.
+//! The transactions themselves are still applied one by one, in block order, so that the
+//! outcome of a block does not depend on the schedule.
+
+use std::collections::BTreeSet;
+
+use linera_base::identifiers::{ApplicationId, GenericApplicationId};
+
+use crate::data_types::Transaction;
+
+/// The parts of the chain state a transaction is expected to change.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TransactionFootprint {
+    /// The applications whose operations or messages the transaction executes.
+    pub applications: BTreeSet<ApplicationId>,
+    /// Whether the transaction executes system operations or messages.
+    pub system: bool,
+}
+
+impl TransactionFootprint {
+    /// Returns the footprint of the given transaction.
+    pub fn of(transaction: &Transaction) -> Self {
+        let mut footprint = Self::default();
+        match transaction {
+            Transaction::ExecuteOperation(operation) => {
+                footprint.add(operation.application_id());
+            }
+            Transaction::ReceiveMessages(bundle) => {
+                for posted_message in bundle.messages() {
+                    footprint.add(posted_message.message.application_id());
+                }
+            }
+        }
+        footprint
+    }
+
+    fn add(&mut self, application_id: GenericApplicationId) {
+        match application_id {
+            GenericApplicationId::System => self.system = true,
+            GenericApplicationId::User(application_id) => {
+                self.applications.insert(application_id);
+            }
+        }
+    }
+
+    /// Returns whether the two transactions may change the same state. System transactions
+    /// can change anything, e.g. the application permissions, so they conflict with every
+    /// transaction.
+    pub fn conflicts_with(&self, other: &Self) -> bool {
+        self.system || other.system || !self.applications.is_disjoint(&other.applications)
+    }
+}
+
+/// Splits the transactions into consecutive batches of pairwise non-conflicting
+/// transactions, covering all of them in order.
+#[cfg(test)]
+fn schedule(transactions: &[Transaction]) -> Vec<std::ops::Range<usize>> {
+    let mut batches = Vec::new();
+    let mut start = 0;
+    while start < transactions.len() {
+        let end = start + first_batch(&transactions[start..]).len();
+        batches.push(start..end);
+        start = end;
+    }
+    batches
+}
+
+/// Returns the footprints of the longest prefix of the transactions that are pairwise
+/// non-conflicting. The prefix is empty only if there are no transactions.
+pub fn first_batch(transactions: &[Transaction]) -> Vec<TransactionFootprint> {
+    let mut footprints: Vec<TransactionFootprint> = Vec::new();
+    for transaction in transactions {
+        let footprint = TransactionFootprint::of(transaction);
+        if footprints
+            .iter()
+            .any(|other| other.conflicts_with(&footprint))
+        {
+            break;
+        }
+        footprints.push(footprint);
+    }
+    footprints
+}
+
+#[cfg(test)]
+#[path = "unit_tests/scheduler_tests.rs"]
+mod scheduler_tests;
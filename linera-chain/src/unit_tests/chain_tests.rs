@@ -15,7 +15,7 @@ use linera_base::{
     crypto::{AccountPublicKey, CryptoHash, ValidatorPublicKey},
     data_types::{
         Amount, ApplicationDescription, ApplicationPermissions, Blob, BlockHeight, Bytecode,
-        ChainDescription, ChainOrigin, Epoch, InitialChainConfig, Timestamp,
+        ChainDescription, ChainOrigin, Epoch, InitialChainConfig, TimeDelta, Timestamp,
     },
     http,
     identifiers::{Account, AccountOwner, ApplicationId, ChainId, ModuleId},
@@ -1193,3 +1193,45 @@ async fn test_reconcile_outbox_index_full_mode_reindexes_all() -> anyhow::Result
     assert!(!chain.reconcile_outbox_index(None).await?);
     Ok(())
 }
+
+#[tokio::test]
+async fn test_prune_closed_ephemeral_chain() -> anyhow::Result<()> {
+    let mut env = TestEnvironment::new();
+    let config = env.make_open_chain_config();
+    let chain_id = env.make_child_chain_description_with_config(3, config).id();
+    let mut chain = ChainStateView::new(chain_id).await;
+    chain
+        .context()
+        .extra()
+        .add_blobs([committee_blob(ResourceControlPolicy::default())])
+        .await?;
+    chain
+        .context()
+        .extra()
+        .add_blobs(env.description_blobs())
+        .await?;
+    chain.initialize_if_needed(Timestamp::from(0)).await?;
+
+    let retention = TimeDelta::from_secs(60);
+    let expired = Timestamp::from(0).saturating_add(retention);
+    // A closed chain is only pruned if it is ephemeral.
+    chain.execution_state.system.closed.set(true);
+    assert!(!chain.is_prunable(expired, retention));
+
+    chain.execution_state.system.ephemeral.set(true);
+    assert!(!chain.is_prunable(Timestamp::from(1), retention));
+    assert!(chain.is_prunable(expired, retention));
+
+    // An open ephemeral chain is never pruned.
+    chain.execution_state.system.closed.set(false);
+    assert!(!chain.is_prunable(expired, retention));
+    chain.execution_state.system.closed.set(true);
+
+    chain.prune(expired);
+    assert_eq!(*chain.pruned_at.get(), Some(expired));
+    // The chain stays active and closed, so it is not re-initialized or pruned again.
+    assert!(chain.is_active().await?);
+    assert!(*chain.execution_state.system.closed.get());
+    assert!(!chain.is_prunable(expired, retention));
+    Ok(())
+}
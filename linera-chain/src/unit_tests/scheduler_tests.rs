@@ -0,0 +1,102 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{Amount, BlockHeight, Timestamp},
+    identifiers::{Account, AccountOwner, ChainId},
+};
+use linera_execution::{Message, MessageKind, Operation, SystemMessage, SystemOperation};
+
+use super::*;
+use crate::data_types::{IncomingBundle, MessageAction, MessageBundle, PostedMessage};
+
+fn user_operation(application_id: ApplicationId) -> Transaction {
+    Transaction::ExecuteOperation(Operation::User {
+        application_id,
+        bytes: Vec::new(),
+    })
+}
+
+fn system_operation() -> Transaction {
+    Transaction::ExecuteOperation(Operation::system(SystemOperation::Transfer {
+        owner: AccountOwner::CHAIN,
+        recipient: Account::chain(ChainId(CryptoHash::test_hash("recipient"))),
+        amount: Amount::ONE,
+    }))
+}
+
+fn bundle(messages: Vec<Message>) -> Transaction {
+    let messages = messages
+        .into_iter()
+        .map(|message| PostedMessage {
+            authenticated_owner: None,
+            grant: Amount::ZERO,
+            refund_grant_to: None,
+            kind: MessageKind::Simple,
+            message,
+        })
+        .collect();
+    Transaction::ReceiveMessages(IncomingBundle {
+        origin: ChainId(CryptoHash::test_hash("origin")),
+        bundle: MessageBundle {
+            height: BlockHeight::ZERO,
+            timestamp: Timestamp::from(0),
+            certificate_hash: CryptoHash::test_hash("certificate"),
+            transaction_index: 0,
+            messages,
+        },
+        action: MessageAction::Accept,
+    })
+}
+
+#[test]
+fn test_footprints() {
+    let app1 = ApplicationId::new(CryptoHash::test_hash("app1"));
+    let app2 = ApplicationId::new(CryptoHash::test_hash("app2"));
+
+    let first = TransactionFootprint::of(&user_operation(app1));
+    let second = TransactionFootprint::of(&bundle(vec![Message::User {
+        application_id: app2,
+        bytes: Vec::new(),
+    }]));
+    assert!(!first.system);
+    assert!(!first.conflicts_with(&second));
+    assert!(first.conflicts_with(&first));
+
+    let mixed = TransactionFootprint::of(&bundle(vec![
+        Message::User {
+            application_id: app2,
+            bytes: Vec::new(),
+        },
+        Message::System(SystemMessage::Credit {
+            target: AccountOwner::CHAIN,
+            amount: Amount::ONE,
+            source: AccountOwner::CHAIN,
+        }),
+    ]));
+    assert!(mixed.system);
+    assert_eq!(mixed.applications, BTreeSet::from([app2]));
+    assert!(mixed.conflicts_with(&first));
+}
+
+#[test]
+fn test_schedule() {
+    let app1 = ApplicationId::new(CryptoHash::test_hash("app1"));
+    let app2 = ApplicationId::new(CryptoHash::test_hash("app2"));
+    let app3 = ApplicationId::new(CryptoHash::test_hash("app3"));
+
+    assert!(schedule(&[]).is_empty());
+
+    let transactions = [
+        user_operation(app1),
+        user_operation(app2),
+        user_operation(app3),
+        // Conflicts with the first transaction.
+        user_operation(app1),
+        user_operation(app2),
+        system_operation(),
+        user_operation(app3),
+    ];
+    assert_eq!(schedule(&transactions), vec![0..3, 3..5, 5..6, 6..7]);
+}
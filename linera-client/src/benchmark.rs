@@ -11,7 +11,7 @@ use std::{
 };
 
 use linera_base::{
-    data_types::{Amount, Timestamp},
+    data_types::{Amount, Blob, Timestamp},
     identifiers::{Account, AccountOwner, ApplicationId, ChainId},
     time::Instant,
 };
@@ -24,7 +24,7 @@ use linera_execution::{system::SystemOperation, Operation};
 use linera_sdk::abis::fungible::FungibleOperation;
 use num_format::{Locale, ToFormattedString};
 use prometheus_parse::{HistogramCount, Scrape, Value};
-use rand::{rngs::SmallRng, seq::SliceRandom, thread_rng, SeedableRng};
+use rand::{rngs::SmallRng, seq::SliceRandom, thread_rng, RngCore as _, SeedableRng};
 use serde::{Deserialize, Serialize};
 use tokio::{
     sync::{mpsc, Barrier, Notify},
@@ -46,6 +46,12 @@ use crate::chain_listener::{ChainListener, ClientContext};
 pub trait OperationGenerator: Send + 'static {
     /// Generate a batch of operations for a single block.
     fn generate_operations(&mut self, owner: AccountOwner, count: usize) -> Vec<Operation>;
+
+    /// Returns the blobs that the operations last generated publish, to be included in
+    /// the same block.
+    fn take_blobs(&mut self) -> Vec<Blob> {
+        Vec::new()
+    }
 }
 
 /// Generates native fungible token transfer operations between chains.
@@ -193,6 +199,98 @@ impl OperationGenerator for FungibleTransferGenerator {
     }
 }
 
+/// Generates operations of a user application, to load-test the execution of arbitrary
+/// applications rather than token transfers.
+pub struct ApplicationCallGenerator {
+    application_id: ApplicationId,
+    operation: Option<Vec<u8>>,
+    payload_size: usize,
+    rng: SmallRng,
+}
+
+impl ApplicationCallGenerator {
+    /// Creates a generator of operations of the given application. Every operation is the
+    /// given serialized `operation`, if any; otherwise it is a BCS-encoded byte vector of
+    /// `payload_size` random bytes.
+    pub fn new(
+        application_id: ApplicationId,
+        operation: Option<Vec<u8>>,
+        payload_size: usize,
+    ) -> Result<Self, BenchmarkError> {
+        Ok(Self {
+            application_id,
+            operation,
+            payload_size,
+            rng: SmallRng::from_rng(thread_rng())?,
+        })
+    }
+
+    fn next_operation_bytes(&mut self) -> Vec<u8> {
+        match &self.operation {
+            Some(operation) => operation.clone(),
+            None => {
+                let mut payload = vec![0u8; self.payload_size];
+                self.rng.fill_bytes(&mut payload);
+                bcs::to_bytes(&payload).expect("should serialize a byte vector")
+            }
+        }
+    }
+}
+
+impl OperationGenerator for ApplicationCallGenerator {
+    fn generate_operations(&mut self, _owner: AccountOwner, count: usize) -> Vec<Operation> {
+        (0..count)
+            .map(|_| Operation::User {
+                application_id: self.application_id,
+                bytes: self.next_operation_bytes(),
+            })
+            .collect()
+    }
+}
+
+/// Generates publications of data blobs with random contents, to load-test the storage
+/// and propagation of blobs.
+pub struct BlobPublicationGenerator {
+    blob_size: usize,
+    rng: SmallRng,
+    blobs: Vec<Blob>,
+}
+
+impl BlobPublicationGenerator {
+    /// Creates a generator of data blobs of `blob_size` random bytes.
+    pub fn new(blob_size: usize) -> Result<Self, BenchmarkError> {
+        Ok(Self {
+            blob_size,
+            rng: SmallRng::from_rng(thread_rng())?,
+            blobs: Vec::new(),
+        })
+    }
+}
+
+impl OperationGenerator for BlobPublicationGenerator {
+    fn generate_operations(&mut self, _owner: AccountOwner, count: usize) -> Vec<Operation> {
+        self.blobs = (0..count)
+            .map(|_| {
+                let mut bytes = vec![0u8; self.blob_size];
+                self.rng.fill_bytes(&mut bytes);
+                Blob::new_data(bytes)
+            })
+            .collect();
+        self.blobs
+            .iter()
+            .map(|blob| {
+                Operation::system(SystemOperation::PublishDataBlob {
+                    blob_hash: blob.id().hash,
+                })
+            })
+            .collect()
+    }
+
+    fn take_blobs(&mut self) -> Vec<Blob> {
+        std::mem::take(&mut self.blobs)
+    }
+}
+
 const PROXY_LATENCY_P99_THRESHOLD: f64 = 400.0;
 const LATENCY_METRIC_PREFIX: &str = "linera_proxy_request_latency";
 
@@ -768,10 +866,11 @@ impl<Env: Environment> Benchmark<Env> {
                     info!("Shutdown signal received, stopping benchmark");
                     break;
                 }
-                result = chain_client.execute_operations(
-                    generator.generate_operations(owner, transactions_per_block),
-                    vec![]
-                ) => {
+                result = async {
+                    let operations = generator.generate_operations(owner, transactions_per_block);
+                    let blobs = generator.take_blobs();
+                    chain_client.execute_operations(operations, blobs).await
+                } => {
                     result
                         .map_err(BenchmarkError::ChainClient)?
                         .expect("should execute block with operations");
@@ -788,14 +887,21 @@ impl<Env: Environment> Benchmark<Env> {
         Ok(())
     }
 
-    /// Closes the chain that was created for the benchmark.
+    /// Closes the chain that was created for the benchmark. If `ephemeral` is true, the
+    /// chain is made ephemeral in the same block, so that validators can prune its state.
     pub async fn close_benchmark_chain(
         chain_client: &ChainClient<Env>,
+        ephemeral: bool,
     ) -> Result<(), BenchmarkError> {
         let start = Instant::now();
+        let mut operations = Vec::new();
+        if ephemeral {
+            operations.push(Operation::system(SystemOperation::MakeEphemeral));
+        }
+        operations.push(Operation::system(SystemOperation::CloseChain));
         loop {
             let result = chain_client
-                .execute_operation(Operation::system(SystemOperation::CloseChain))
+                .execute_operations(operations.clone(), vec![])
                 .await?;
             match result {
                 ClientOutcome::Committed(_) => break,
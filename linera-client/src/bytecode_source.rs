@@ -0,0 +1,448 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    fmt,
+    future::Future,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use linera_base::{data_types::Bytecode, hex};
+use serde::Deserialize;
+use sha2::{Digest as _, Sha256};
+use tracing::info;
+
+/// The media types of OCI manifests we accept.
+const OCI_MANIFEST_MEDIA_TYPES: &str =
+    "application/vnd.oci.image.manifest.v1+json, application/vnd.docker.distribution.manifest.v2+json";
+
+/// An error while loading bytecode from a [`BytecodeSource`].
+#[derive(Debug, thiserror::Error)]
+pub enum BytecodeSourceError {
+    /// The source could not be parsed.
+    #[error("invalid bytecode source {0:?}: {1}")]
+    InvalidSource(String, &'static str),
+    /// The digest could not be parsed.
+    #[error("invalid digest {0:?}: expected `sha256:` followed by 64 hexadecimal digits")]
+    InvalidDigest(String),
+    /// The downloaded content does not have the pinned digest.
+    #[error("the content of {location} has digest {actual}, but {expected} was pinned")]
+    DigestMismatch {
+        /// Where the content comes from.
+        location: String,
+        /// The pinned digest.
+        expected: Sha256Digest,
+        /// The digest of the content.
+        actual: Sha256Digest,
+    },
+    /// The OCI manifest has no layer that could be the bytecode.
+    #[error("no Wasm layer found in the OCI manifest of {0}")]
+    MissingOciLayer(String),
+    /// A local file could not be read, or the cache could not be written.
+    #[error("failed to load bytecode from {0}: {1}")]
+    Io(String, #[source] std::io::Error),
+    /// A download failed.
+    #[error("failed to download {0}: {1}")]
+    Http(String, #[source] reqwest::Error),
+    /// The OCI manifest could not be parsed.
+    #[error("invalid OCI manifest for {0}: {1}")]
+    InvalidOciManifest(String, #[source] serde_json::Error),
+}
+
+/// A SHA-256 digest, written `sha256:<hex>`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Sha256Digest([u8; 32]);
+
+impl Sha256Digest {
+    /// Computes the digest of the given bytes.
+    pub fn of(bytes: &[u8]) -> Self {
+        Self(Sha256::digest(bytes).into())
+    }
+
+    fn check(&self, location: &str, bytes: &[u8]) -> Result<(), BytecodeSourceError> {
+        let actual = Self::of(bytes);
+        if actual != *self {
+            return Err(BytecodeSourceError::DigestMismatch {
+                location: location.to_owned(),
+                expected: *self,
+                actual,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Sha256Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "sha256:{}", hex::encode(self.0))
+    }
+}
+
+impl FromStr for Sha256Digest {
+    type Err = BytecodeSourceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || BytecodeSourceError::InvalidDigest(s.to_owned());
+        let hex = s.strip_prefix("sha256:").ok_or_else(invalid)?;
+        let bytes = hex::decode(hex).map_err(|_| invalid())?;
+        Ok(Self(bytes.try_into().map_err(|_| invalid())?))
+    }
+}
+
+/// A reference to an artifact in an OCI registry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OciReference {
+    /// The registry's host, with an optional port.
+    pub registry: String,
+    /// The repository in the registry.
+    pub repository: String,
+    /// The tag, if the artifact is not pinned.
+    pub tag: Option<String>,
+    /// The digest of the artifact's manifest, if pinned.
+    pub digest: Option<Sha256Digest>,
+}
+
+impl OciReference {
+    /// Returns the tag or digest to request the manifest with.
+    fn reference(&self) -> String {
+        match (&self.digest, &self.tag) {
+            (Some(digest), _) => digest.to_string(),
+            (None, Some(tag)) => tag.clone(),
+            (None, None) => "latest".to_owned(),
+        }
+    }
+
+    /// Returns the base URL of the repository in the registry API. Registries on the
+    /// local host are accessed over plain HTTP, for testing.
+    fn api_url(&self) -> String {
+        let local = ["localhost", "127.0.0.1"]
+            .iter()
+            .any(|host| self.registry == *host || self.registry.starts_with(&format!("{host}:")));
+        let scheme = if local { "http" } else { "https" };
+        format!("{scheme}://{}/v2/{}", self.registry, self.repository)
+    }
+}
+
+impl fmt::Display for OciReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "oci://{}/{}", self.registry, self.repository)?;
+        if let Some(tag) = &self.tag {
+            write!(f, ":{tag}")?;
+        }
+        if let Some(digest) = &self.digest {
+            write!(f, "@{digest}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Where to load bytecode from.
+///
+/// Parsed from a path, from `https://<url>[#sha256=<hex>]`, or from
+/// `oci://<registry>/<repository>[:<tag>][@sha256:<hex>]`, where OCI digests pin the
+/// artifact's manifest. Downloads are cached by digest, so pinned bytecode is only
+/// downloaded once.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BytecodeSource {
+    /// A local file.
+    File(PathBuf),
+    /// A file downloaded over HTTPS.
+    Url {
+        /// The URL, without the digest.
+        url: String,
+        /// The digest of the file, if pinned.
+        digest: Option<Sha256Digest>,
+    },
+    /// The single Wasm layer of an artifact in an OCI registry.
+    Oci(OciReference),
+}
+
+impl From<PathBuf> for BytecodeSource {
+    fn from(path: PathBuf) -> Self {
+        BytecodeSource::File(path)
+    }
+}
+
+impl fmt::Display for BytecodeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BytecodeSource::File(path) => write!(f, "{}", path.display()),
+            BytecodeSource::Url { url, digest: None } => write!(f, "{url}"),
+            BytecodeSource::Url {
+                url,
+                digest: Some(digest),
+            } => write!(f, "{url}#{}", digest.to_string().replacen(':', "=", 1)),
+            BytecodeSource::Oci(reference) => write!(f, "{reference}"),
+        }
+    }
+}
+
+impl FromStr for BytecodeSource {
+    type Err = BytecodeSourceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason| BytecodeSourceError::InvalidSource(s.to_owned(), reason);
+        if s.starts_with("https://") {
+            let (url, digest) = match s.split_once('#') {
+                Some((url, fragment)) => {
+                    let hex = fragment
+                        .strip_prefix("sha256=")
+                        .ok_or_else(|| invalid("the URL fragment must be `sha256=<hex>`"))?;
+                    (url, Some(format!("sha256:{hex}").parse()?))
+                }
+                None => (s, None),
+            };
+            return Ok(BytecodeSource::Url {
+                url: url.to_owned(),
+                digest,
+            });
+        }
+        if s.starts_with("http://") {
+            return Err(invalid("bytecode can only be downloaded over HTTPS"));
+        }
+        let Some(rest) = s.strip_prefix("oci://") else {
+            return Ok(BytecodeSource::File(s.into()));
+        };
+        let (name, digest) = match rest.split_once('@') {
+            Some((name, digest)) => (name, Some(digest.parse()?)),
+            None => (rest, None),
+        };
+        let (registry, path) = name
+            .split_once('/')
+            .ok_or_else(|| invalid("expected `oci://<registry>/<repository>`"))?;
+        // A colon in the last path segment separates the tag.
+        let (repository, tag) = match path.rsplit_once(':') {
+            Some((repository, tag)) if !tag.contains('/') => (repository, Some(tag.to_owned())),
+            _ => (path, None),
+        };
+        if registry.is_empty() || repository.is_empty() || tag.as_deref() == Some("") {
+            return Err(invalid("expected `oci://<registry>/<repository>[:<tag>]`"));
+        }
+        Ok(BytecodeSource::Oci(OciReference {
+            registry: registry.to_owned(),
+            repository: repository.to_owned(),
+            tag,
+            digest,
+        }))
+    }
+}
+
+/// An OCI image manifest, with the fields we use.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OciManifest {
+    layers: Vec<OciDescriptor>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OciDescriptor {
+    media_type: String,
+    digest: String,
+}
+
+impl OciManifest {
+    /// Returns the layer containing the bytecode: the only layer, or the only Wasm layer.
+    fn bytecode_layer(&self) -> Option<&OciDescriptor> {
+        if let [layer] = self.layers.as_slice() {
+            return Some(layer);
+        }
+        let mut wasm_layers = self
+            .layers
+            .iter()
+            .filter(|layer| layer.media_type.contains("wasm"));
+        let layer = wasm_layers.next()?;
+        wasm_layers.next().is_none().then_some(layer)
+    }
+}
+
+/// The token response of a registry's authorization service.
+#[derive(Deserialize)]
+struct TokenResponse {
+    #[serde(alias = "access_token")]
+    token: String,
+}
+
+/// Returns the default directory of the download cache.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("linera").join("bytecode"))
+}
+
+impl BytecodeSource {
+    /// Loads the bytecode, using the default download cache.
+    pub async fn load(&self) -> Result<Bytecode, BytecodeSourceError> {
+        self.load_with_cache(default_cache_dir().as_deref()).await
+    }
+
+    /// Loads the bytecode, using the download cache in the given directory, if any.
+    pub async fn load_with_cache(
+        &self,
+        cache_dir: Option<&Path>,
+    ) -> Result<Bytecode, BytecodeSourceError> {
+        let bytes = match self {
+            BytecodeSource::File(path) => {
+                return Bytecode::load_from_file(path)
+                    .await
+                    .map_err(|error| BytecodeSourceError::Io(self.to_string(), error));
+            }
+            BytecodeSource::Url { url, digest } => {
+                let cache = Cache(cache_dir);
+                let bytes = cache
+                    .fetch(*digest, url, || async {
+                        let response = reqwest::get(url).await?.error_for_status()?;
+                        Ok(response.bytes().await?.to_vec())
+                    })
+                    .await?;
+                if digest.is_none() {
+                    info!(
+                        "Downloaded {url} with digest {}; append `#sha256=<hex>` to the URL \
+                         to pin it",
+                        Sha256Digest::of(&bytes)
+                    );
+                }
+                bytes
+            }
+            BytecodeSource::Oci(reference) => fetch_oci(reference, Cache(cache_dir)).await?,
+        };
+        Ok(Bytecode::new(bytes))
+    }
+}
+
+/// The download cache, storing contents by digest.
+#[derive(Clone, Copy)]
+struct Cache<'a>(Option<&'a Path>);
+
+impl Cache<'_> {
+    /// Returns the content with the given digest from the cache, or fetches it. Fetched
+    /// content is checked against the digest, if any, and cached.
+    async fn fetch<F>(
+        self,
+        digest: Option<Sha256Digest>,
+        source: &str,
+        fetch: impl FnOnce() -> F,
+    ) -> Result<Vec<u8>, BytecodeSourceError>
+    where
+        F: Future<Output = Result<Vec<u8>, reqwest::Error>>,
+    {
+        if let (Some(dir), Some(digest)) = (self.0, digest) {
+            let path = dir.join(digest.to_string().replacen(':', "-", 1));
+            if let Ok(bytes) = tokio::fs::read(&path).await {
+                // Ignore corrupted entries.
+                if digest.check(source, &bytes).is_ok() {
+                    return Ok(bytes);
+                }
+            }
+        }
+        let bytes = fetch()
+            .await
+            .map_err(|error| BytecodeSourceError::Http(source.to_owned(), error))?;
+        if let Some(digest) = digest {
+            digest.check(source, &bytes)?;
+        }
+        if let Some(dir) = self.0 {
+            let digest = Sha256Digest::of(&bytes);
+            let path = dir.join(digest.to_string().replacen(':', "-", 1));
+            let io_error = |error| BytecodeSourceError::Io(dir.display().to_string(), error);
+            tokio::fs::create_dir_all(dir).await.map_err(io_error)?;
+            tokio::fs::write(&path, &bytes).await.map_err(io_error)?;
+        }
+        Ok(bytes)
+    }
+}
+
+/// Downloads the bytecode layer of an OCI artifact, authenticating anonymously if the
+/// registry requires a token.
+async fn fetch_oci(
+    reference: &OciReference,
+    cache: Cache<'_>,
+) -> Result<Vec<u8>, BytecodeSourceError> {
+    let source = reference.to_string();
+    let http_error = |error| BytecodeSourceError::Http(source.clone(), error);
+    let client = reqwest::Client::new();
+    let api_url = reference.api_url();
+    let manifest_url = format!("{api_url}/manifests/{}", reference.reference());
+    let token = anonymous_token(&client, &manifest_url)
+        .await
+        .map_err(http_error)?;
+    let get = |url: String, accept: Option<&'static str>| {
+        let mut request = client.get(url);
+        if let Some(accept) = accept {
+            request = request.header(reqwest::header::ACCEPT, accept);
+        }
+        if let Some(token) = &token {
+            request = request.bearer_auth(token);
+        }
+        async move {
+            let response = request.send().await?.error_for_status()?;
+            Ok(response.bytes().await?.to_vec())
+        }
+    };
+
+    let manifest = cache
+        .fetch(reference.digest, &source, || {
+            get(manifest_url.clone(), Some(OCI_MANIFEST_MEDIA_TYPES))
+        })
+        .await?;
+    let manifest = serde_json::from_slice::<OciManifest>(&manifest)
+        .map_err(|error| BytecodeSourceError::InvalidOciManifest(source.clone(), error))?;
+    let layer = manifest
+        .bytecode_layer()
+        .ok_or_else(|| BytecodeSourceError::MissingOciLayer(source.clone()))?;
+    // Layers are content-addressed, so they are always checked against their digest.
+    let layer_digest = layer.digest.parse()?;
+    let bytes = cache
+        .fetch(Some(layer_digest), &source, || {
+            get(format!("{api_url}/blobs/{}", layer.digest), None)
+        })
+        .await?;
+    if reference.digest.is_none() {
+        info!("Downloaded {source}; append `@<manifest digest>` to the reference to pin it");
+    }
+    Ok(bytes)
+}
+
+/// Returns an anonymous token for the given registry URL, if the registry requires one.
+async fn anonymous_token(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<Option<String>, reqwest::Error> {
+    let response = client.head(url).send().await?;
+    if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+        return Ok(None);
+    }
+    let Some(challenge) = response
+        .headers()
+        .get(reqwest::header::WWW_AUTHENTICATE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return Ok(None);
+    };
+    let mut realm = None;
+    let mut query = Vec::new();
+    for parameter in challenge.split(',') {
+        let Some((key, value)) = parameter.trim().split_once('=') else {
+            continue;
+        };
+        let value = value.trim_matches('"').to_owned();
+        match key {
+            "realm" => realm = Some(value),
+            "service" | "scope" => query.push((key.to_owned(), value)),
+            _ => {}
+        }
+    }
+    let Some(realm) = realm else {
+        return Ok(None);
+    };
+    let response = client
+        .get(realm)
+        .query(&query)
+        .send()
+        .await?
+        .error_for_status()?;
+    let token = serde_json::from_slice::<TokenResponse>(&response.bytes().await?)
+        .map(|response| response.token)
+        .ok();
+    Ok(token)
+}
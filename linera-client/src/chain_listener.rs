@@ -3,6 +3,7 @@
 
 use std::{
     collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    num::NonZeroU64,
     sync::Arc,
     time::Duration,
 };
@@ -21,6 +22,7 @@ use linera_core::{
         chain_client::{self, ChainClient},
         AbortOnDrop, ListeningMode,
     },
+    data_types::ClientOutcome,
     node::NotificationStream,
     worker::{Notification, Reason},
     Environment, Wallet,
@@ -30,10 +32,13 @@ use tokio::sync::{mpsc::UnboundedReceiver, Notify};
 use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, instrument, warn, Instrument as _};
 
-use crate::error::{self, Error};
+use crate::{
+    error::{self, Error},
+    proposal_policy::{self, ProposalDecision, ProposalPolicy, ProposalPolicyKind, ProposalState},
+};
 
 /// The configuration for the chain listener.
-#[derive(Default, Debug, Clone, clap::Args, serde::Serialize, serde::Deserialize, tsify::Tsify)]
+#[derive(Debug, Clone, clap::Args, serde::Serialize, serde::Deserialize, tsify::Tsify)]
 #[serde(rename_all = "camelCase")]
 pub struct ChainListenerConfig {
     /// Do not create blocks automatically to receive incoming messages. Instead, wait for
@@ -62,6 +67,176 @@ pub struct ChainListenerConfig {
         env = "LINERA_LISTENER_DELAY_AFTER"
     )]
     pub delay_after_ms: u64,
+
+    /// React only to the given kinds of notifications. By default, the listener reacts to
+    /// all of them.
+    #[serde(default)]
+    #[arg(
+        long = "listener-notifications",
+        value_delimiter = ',',
+        env = "LINERA_LISTENER_NOTIFICATIONS"
+    )]
+    pub notification_kinds: Vec<NotificationKind>,
+
+    /// React only to notifications from the given chains, and only process their inboxes.
+    /// By default, the listener reacts to notifications from all the chains it listens to,
+    /// including the admin chain and the chains publishing events that the wallet's chains
+    /// subscribe to.
+    #[serde(default)]
+    #[arg(
+        long = "listener-include-chains",
+        value_delimiter = ',',
+        env = "LINERA_LISTENER_INCLUDE_CHAINS"
+    )]
+    pub include_chains: Vec<ChainId>,
+
+    /// Ignore notifications from the given chains, and never process their inboxes.
+    #[serde(default)]
+    #[arg(
+        long = "listener-exclude-chains",
+        value_delimiter = ',',
+        env = "LINERA_LISTENER_EXCLUDE_CHAINS"
+    )]
+    pub exclude_chains: Vec<ChainId>,
+
+    /// When to propose the blocks processing a chain's inbox. Services sharing a chain with
+    /// other owners can use this to avoid racing them.
+    #[serde(default)]
+    #[arg(
+        long = "listener-proposal-policy",
+        default_value = "immediate",
+        env = "LINERA_LISTENER_PROPOSAL_POLICY"
+    )]
+    pub proposal_policy: ProposalPolicyKind,
+
+    /// With the `batch` proposal policy: the number of pending message bundles to wait for.
+    #[serde(default = "ChainListenerConfig::default_proposal_batch_size")]
+    #[arg(
+        long = "listener-proposal-batch-size",
+        default_value = "10",
+        env = "LINERA_LISTENER_PROPOSAL_BATCH_SIZE"
+    )]
+    pub proposal_batch_size: usize,
+
+    /// With the `batch`, `timer` and `fallback-only` proposal policies: how long to wait
+    /// before proposing.
+    #[serde(default = "ChainListenerConfig::default_proposal_delay_ms")]
+    #[arg(
+        long = "listener-proposal-delay-ms",
+        default_value = "1000",
+        env = "LINERA_LISTENER_PROPOSAL_DELAY_MS"
+    )]
+    pub proposal_delay_ms: u64,
+
+    /// Publish a checkpoint of each chain the wallet can propose on every time it grows by
+    /// the given number of blocks, and in every new epoch, so that new nodes can sync from a
+    /// recent checkpoint certified by the current committee instead of replaying the chain's
+    /// history.
+    #[serde(default)]
+    #[arg(
+        long = "listener-checkpoint-interval",
+        env = "LINERA_LISTENER_CHECKPOINT_INTERVAL",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub checkpoint_interval: Option<u64>,
+}
+
+impl Default for ChainListenerConfig {
+    fn default() -> Self {
+        Self {
+            skip_process_inbox: false,
+            delay_before_ms: 0,
+            delay_after_ms: 0,
+            notification_kinds: Vec::new(),
+            include_chains: Vec::new(),
+            exclude_chains: Vec::new(),
+            proposal_policy: ProposalPolicyKind::default(),
+            proposal_batch_size: Self::default_proposal_batch_size(),
+            proposal_delay_ms: Self::default_proposal_delay_ms(),
+            checkpoint_interval: None,
+        }
+    }
+}
+
+impl ChainListenerConfig {
+    // These must match the `default_value` of the corresponding command-line arguments.
+    fn default_proposal_batch_size() -> usize {
+        10
+    }
+
+    fn default_proposal_delay_ms() -> u64 {
+        1_000
+    }
+
+    /// Returns whether the listener reacts to notifications of the given kind.
+    pub fn reacts_to(&self, kind: NotificationKind) -> bool {
+        self.notification_kinds.is_empty() || self.notification_kinds.contains(&kind)
+    }
+
+    /// Returns whether the listener reacts to notifications from the given chain.
+    pub fn reacts_to_chain(&self, chain_id: ChainId) -> bool {
+        !self.exclude_chains.contains(&chain_id)
+            && (self.include_chains.is_empty() || self.include_chains.contains(&chain_id))
+    }
+
+    /// Returns the configured proposal policy.
+    pub fn proposal_policy(&self) -> Arc<dyn ProposalPolicy> {
+        let delay = TimeDelta::from_millis(self.proposal_delay_ms);
+        match self.proposal_policy {
+            ProposalPolicyKind::Immediate => Arc::new(proposal_policy::Immediate),
+            ProposalPolicyKind::Batch => Arc::new(proposal_policy::Batch {
+                min_bundles: self.proposal_batch_size,
+                max_delay: delay,
+            }),
+            ProposalPolicyKind::Timer => Arc::new(proposal_policy::Timer { interval: delay }),
+            ProposalPolicyKind::FallbackOnly => Arc::new(proposal_policy::FallbackOnly { delay }),
+        }
+    }
+
+    /// Returns whether the listener reacts to the given notification.
+    fn is_relevant(&self, notification: &Notification) -> bool {
+        self.reacts_to_chain(notification.chain_id)
+            && NotificationKind::of(&notification.reason).is_none_or(|kind| self.reacts_to(kind))
+    }
+}
+
+/// The kinds of notifications the chain listener can react to.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    clap::ValueEnum,
+    serde::Serialize,
+    serde::Deserialize,
+    tsify::Tsify,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationKind {
+    /// A new block was added to a chain: update the wallet and track new chains.
+    NewBlock,
+    /// New messages arrived in a chain's inbox: process the inbox.
+    NewMessage,
+    /// A chain entered a new round: update the validators.
+    NewRound,
+    /// A publisher emitted events a chain subscribes to: process them.
+    NewEvent,
+    /// A round timed out while processing an inbox: retry once the timeout has passed.
+    Timeout,
+}
+
+impl NotificationKind {
+    /// Returns the kind of notifications with the given reason, if the listener reacts to it.
+    fn of(reason: &Reason) -> Option<Self> {
+        match reason {
+            Reason::NewBlock { .. } => Some(NotificationKind::NewBlock),
+            Reason::NewIncomingBundle { .. } => Some(NotificationKind::NewMessage),
+            Reason::NewRound { .. } => Some(NotificationKind::NewRound),
+            Reason::NewEvents { .. } => Some(NotificationKind::NewEvent),
+            Reason::BlockExecuted { .. } => None,
+        }
+    }
 }
 
 type ContextChainClient<C> = ChainClient<<C as ClientContext>::Environment>;
@@ -115,11 +290,20 @@ pub trait ClientContext {
                 .map_err(error::Inner::wallet)?
                 .unwrap_or_default();
             let follow_only = chain.is_follow_only();
+            let pending_fast_proposal = match chain.pending_fast_proposal {
+                Some(stored) => Some(
+                    stored
+                        .load(self.client().storage_client())
+                        .make_sync()
+                        .await?,
+                ),
+                None => None,
+            };
             Ok(self.client().create_chain_client(
                 chain_id,
                 chain.block_hash,
                 chain.next_block_height,
-                &chain.pending_fast_proposal,
+                &pending_fast_proposal,
                 chain.owner,
                 self.timing_sender(),
                 follow_only,
@@ -240,6 +424,10 @@ struct ListeningClient<C: ClientContext> {
     inbox_task: Task<()>,
     /// Cancellation token for the per-chain inbox task (child of the global token).
     inbox_cancellation: CancellationToken,
+    /// Signal to wake the per-chain checkpoint task.
+    checkpoint_notify: Arc<Notify>,
+    /// The per-chain checkpoint task, if periodic checkpoints are enabled.
+    checkpoint_task: Option<Task<()>>,
 }
 
 impl<C: ClientContext + 'static> ListeningClient<C> {
@@ -252,12 +440,32 @@ impl<C: ClientContext + 'static> ListeningClient<C> {
         background_sync: Task<()>,
         context: &Arc<Mutex<C>>,
         config: &Arc<ChainListenerConfig>,
+        proposal_policy: &Arc<dyn ProposalPolicy>,
         parent_cancellation: &CancellationToken,
     ) -> Self {
         let inbox_notify = Arc::new(Notify::new());
         let inbox_cancellation = parent_cancellation.child_token();
-        let inbox_task =
-            Self::spawn_inbox_task(&client, context, config, &inbox_notify, &inbox_cancellation);
+        let checkpoint_notify = Arc::new(Notify::new());
+        let checkpoint_task =
+            config
+                .checkpoint_interval
+                .and_then(NonZeroU64::new)
+                .map(|interval| {
+                    Task::spawn(checkpoint_loop(
+                        client.clone(),
+                        interval,
+                        Arc::clone(&checkpoint_notify),
+                        parent_cancellation.clone(),
+                    ))
+                });
+        let inbox_task = Self::spawn_inbox_task(
+            &client,
+            context,
+            config,
+            proposal_policy,
+            &inbox_notify,
+            &inbox_cancellation,
+        );
         Self {
             client,
             abort_handle,
@@ -268,6 +476,8 @@ impl<C: ClientContext + 'static> ListeningClient<C> {
             inbox_notify,
             inbox_task,
             inbox_cancellation,
+            checkpoint_notify,
+            checkpoint_task,
         }
     }
 
@@ -278,6 +488,7 @@ impl<C: ClientContext + 'static> ListeningClient<C> {
         parent_cancellation: &CancellationToken,
         context: &Arc<Mutex<C>>,
         config: &Arc<ChainListenerConfig>,
+        proposal_policy: &Arc<dyn ProposalPolicy>,
     ) {
         self.inbox_cancellation.cancel();
         self.inbox_cancellation = parent_cancellation.child_token();
@@ -285,6 +496,7 @@ impl<C: ClientContext + 'static> ListeningClient<C> {
             &self.client,
             context,
             config,
+            proposal_policy,
             &self.inbox_notify,
             &self.inbox_cancellation,
         );
@@ -294,6 +506,7 @@ impl<C: ClientContext + 'static> ListeningClient<C> {
         client: &ContextChainClient<C>,
         context: &Arc<Mutex<C>>,
         config: &Arc<ChainListenerConfig>,
+        proposal_policy: &Arc<dyn ProposalPolicy>,
         inbox_notify: &Arc<Notify>,
         inbox_cancellation: &CancellationToken,
     ) -> Task<()> {
@@ -301,6 +514,7 @@ impl<C: ClientContext + 'static> ListeningClient<C> {
             client.clone(),
             Arc::clone(context),
             Arc::clone(config),
+            Arc::clone(proposal_policy),
             Arc::clone(inbox_notify),
             inbox_cancellation.clone(),
         ))
@@ -310,10 +524,15 @@ impl<C: ClientContext + 'static> ListeningClient<C> {
         // TODO(#4965): this is unnecessary: the join handle now also acts as an abort handle
         drop(self.abort_handle);
         self.inbox_cancellation.cancel();
-        futures::future::join3(
+        futures::future::join4(
             self.listener.cancel(),
             self.background_sync.cancel(),
             self.inbox_task.cancel(),
+            async {
+                if let Some(checkpoint_task) = self.checkpoint_task {
+                    checkpoint_task.cancel().await;
+                }
+            },
         )
         .await;
     }
@@ -336,6 +555,8 @@ pub struct ChainListener<C: ClientContext> {
     context: Arc<Mutex<C>>,
     storage: <C::Environment as Environment>::Storage,
     config: Arc<ChainListenerConfig>,
+    /// When to propose the blocks processing the chains' inboxes.
+    proposal_policy: Arc<dyn ProposalPolicy>,
     listening: BTreeMap<ChainId, ListeningClient<C>>,
     cancellation_token: CancellationToken,
     /// Map from publishing chain to subscriber chains.
@@ -360,6 +581,7 @@ impl<C: ClientContext + 'static> ChainListener<C> {
         Self {
             storage,
             context,
+            proposal_policy: config.proposal_policy(),
             config: Arc::new(config),
             listening: Default::default(),
             cancellation_token,
@@ -369,6 +591,12 @@ impl<C: ClientContext + 'static> ChainListener<C> {
         }
     }
 
+    /// Replaces the proposal policy selected by the configuration with a custom one.
+    pub fn with_proposal_policy(mut self, proposal_policy: Arc<dyn ProposalPolicy>) -> Self {
+        self.proposal_policy = proposal_policy;
+        self
+    }
+
     /// Runs the chain listener.
     #[instrument(skip(self))]
     pub async fn run(mut self) -> Result<impl Future<Output = Result<(), Error>>, Error> {
@@ -426,6 +654,14 @@ impl<C: ClientContext + 'static> ChainListener<C> {
 
     /// Processes a notification, updating local chains and validators as needed.
     async fn process_notification(&mut self, notification: Notification) -> Result<(), Error> {
+        if !self.config.is_relevant(&notification) {
+            debug!(
+                chain_id = %notification.chain_id,
+                reason = ?notification.reason,
+                "ChainListener: ignoring notification due to listener configuration"
+            );
+            return Ok(());
+        }
         Self::sleep(self.config.delay_before_ms).await;
         let Some(listening_client) = self.listening.get(&notification.chain_id) else {
             warn!(
@@ -469,6 +705,7 @@ impl<C: ClientContext + 'static> ChainListener<C> {
                     }
                 }
                 self.process_new_events(notification.chain_id);
+                self.maybe_notify_checkpoint(notification.chain_id);
             }
             Reason::NewEvents { .. } => {
                 self.process_new_events(notification.chain_id);
@@ -632,6 +869,7 @@ impl<C: ClientContext + 'static> ChainListener<C> {
             background_sync_task,
             &self.context,
             &self.config,
+            &self.proposal_policy,
             &self.cancellation_token,
         );
         self.listening.insert(chain_id, listening_client);
@@ -827,6 +1065,7 @@ impl<C: ClientContext + 'static> ChainListener<C> {
                                     &self.cancellation_token,
                                     &self.context,
                                     &self.config,
+                                    &self.proposal_policy,
                                 );
                             }
                         }
@@ -942,6 +1181,13 @@ impl<C: ClientContext + 'static> ChainListener<C> {
         Ok(chains)
     }
 
+    /// Signals the per-chain checkpoint task to publish a checkpoint if one is due.
+    fn maybe_notify_checkpoint(&self, chain_id: ChainId) {
+        if let Some(listening_client) = self.listening.get(&chain_id) {
+            listening_client.checkpoint_notify.notify_one();
+        }
+    }
+
     /// Signals the per-chain inbox processing task to wake up and process the inbox.
     fn maybe_notify_inbox_processing(&self, chain_id: ChainId) {
         if let Some(listening_client) = self.listening.get(&chain_id) {
@@ -957,6 +1203,44 @@ impl<C: ClientContext + 'static> ChainListener<C> {
     }
 }
 
+/// Per-chain checkpoint loop. Wakes on `checkpoint_notify` signals, sent for every new
+/// block, and publishes a checkpoint when one is due. Multiple notifications while busy
+/// collapse into a single permit.
+async fn checkpoint_loop<C: ClientContext>(
+    client: ContextChainClient<C>,
+    interval: NonZeroU64,
+    checkpoint_notify: Arc<Notify>,
+    cancellation_token: CancellationToken,
+) {
+    let chain_id = client.chain_id();
+    loop {
+        futures::select! {
+            () = cancellation_token.cancelled().fuse() => break,
+            () = checkpoint_notify.notified().fuse() => {
+                // Any chain we can propose on is checkpointed: this includes chains in
+                // fallback mode, where the committee's validators may propose.
+                if client.preferred_owner().is_none() || client.identity().await.is_err() {
+                    continue;
+                }
+                match client.checkpoint_if_due(interval).await {
+                    Ok(None | Some(ClientOutcome::Committed(_))) => {}
+                    Ok(Some(ClientOutcome::WaitForTimeout(timeout))) => {
+                        debug!(
+                            %chain_id,
+                            ?timeout,
+                            "Checkpoint postponed until the round times out"
+                        );
+                    }
+                    Ok(Some(ClientOutcome::Conflict(_))) => {
+                        debug!(%chain_id, "Checkpoint postponed due to a conflicting block");
+                    }
+                    Err(error) => warn!(%chain_id, %error, "Failed to publish a checkpoint"),
+                }
+            }
+        }
+    }
+}
+
 /// Per-chain inbox processing loop. Runs as a long-lived tokio task. Wakes on
 /// `inbox_notify` signals and processes the inbox, handling round-leader timeouts
 /// internally. Multiple notifications while busy collapse into a single permit.
@@ -964,11 +1248,12 @@ async fn inbox_processing_loop<C: ClientContext>(
     client: ContextChainClient<C>,
     context: Arc<Mutex<C>>,
     config: Arc<ChainListenerConfig>,
+    proposal_policy: Arc<dyn ProposalPolicy>,
     inbox_notify: Arc<Notify>,
     cancellation_token: CancellationToken,
 ) {
     let chain_id = client.chain_id();
-    loop {
+    'notifications: loop {
         futures::select! {
             () = cancellation_token.cancelled().fuse() => break,
             () = inbox_notify.notified().fuse() => {
@@ -976,6 +1261,10 @@ async fn inbox_processing_loop<C: ClientContext>(
                     debug!("Not processing inbox for {chain_id:.8} due to listener configuration");
                     continue;
                 }
+                if !config.reacts_to_chain(chain_id) {
+                    debug!("Not processing inbox for excluded chain {chain_id:.8}");
+                    continue;
+                }
                 if !client.is_tracked() {
                     debug!("Not processing inbox for non-tracked chain {chain_id:.8}");
                     continue;
@@ -984,6 +1273,44 @@ async fn inbox_processing_loop<C: ClientContext>(
                     debug!("Not processing inbox for follow-only chain {chain_id:.8}");
                     continue;
                 }
+
+                // Wait until the proposal policy lets us propose. New messages interrupt the
+                // wait, so that the policy can reconsider.
+                let waiting_since = Timestamp::now();
+                loop {
+                    let decision =
+                        match proposal_decision(&client, &*proposal_policy, waiting_since).await {
+                            Ok(decision) => decision,
+                            Err(error) => {
+                                warn!(%error, "Failed to apply the proposal policy; proposing now");
+                                ProposalDecision::Propose
+                            }
+                        };
+                    match decision {
+                        ProposalDecision::Propose => break,
+                        ProposalDecision::WaitForMessages => {
+                            debug!(
+                                "Not processing inbox for {chain_id:.8} due to the proposal policy"
+                            );
+                            continue 'notifications;
+                        }
+                        ProposalDecision::WaitUntil(deadline) => {
+                            debug!(
+                                %chain_id,
+                                %deadline,
+                                "waiting before proposing, due to the proposal policy",
+                            );
+                            let delta = deadline.delta_since(Timestamp::now());
+                            if delta > TimeDelta::ZERO {
+                                futures::select! {
+                                    () = cancellation_token.cancelled().fuse() => return,
+                                    () = linera_base::time::timer::sleep(delta.as_duration()).fuse() => {},
+                                    () = inbox_notify.notified().fuse() => {},
+                                }
+                            }
+                        }
+                    }
+                }
                 debug!("Processing inbox for {chain_id:.8}");
 
                 // Inner loop handles round-leader timeouts: if we can't produce a block
@@ -1011,6 +1338,17 @@ async fn inbox_processing_loop<C: ClientContext>(
                             }
                             break;
                         }
+                        Ok((certs, Some(new_timeout)))
+                            if !config.reacts_to(NotificationKind::Timeout) =>
+                        {
+                            info!(
+                                %chain_id,
+                                created_block_count = %certs.len(),
+                                timeout = %new_timeout,
+                                "not waiting for round timeout due to listener configuration",
+                            );
+                            break;
+                        }
                         Ok((certs, Some(new_timeout))) => {
                             info!(
                                 %chain_id,
@@ -1038,6 +1376,28 @@ async fn inbox_processing_loop<C: ClientContext>(
     }
 }
 
+/// Asks the proposal policy whether to propose a block processing the chain's inbox now.
+async fn proposal_decision<Env: Environment>(
+    client: &ChainClient<Env>,
+    proposal_policy: &dyn ProposalPolicy,
+    waiting_since: Timestamp,
+) -> Result<ProposalDecision, chain_client::Error> {
+    let Some(owner) = client.preferred_owner() else {
+        return Ok(ProposalDecision::WaitForMessages);
+    };
+    let info = client.chain_info_with_manager_values().await?;
+    let state = ProposalState {
+        now: Timestamp::now(),
+        waiting_since,
+        pending_bundles: client.pending_message_bundle_count().await?,
+        owner,
+        current_round: info.manager.current_round,
+        leader: info.manager.leader,
+        round_timeout: info.manager.round_timeout,
+    };
+    Ok(proposal_policy.decide(&state))
+}
+
 enum Action {
     Notification(Notification),
     Stop,
@@ -0,0 +1,114 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{crypto::CryptoHash, data_types::BlobContent};
+use serde::{Deserialize, Serialize};
+
+/// The size of the chunks if not specified, unless the resource control policy only allows
+/// smaller blobs.
+pub const DEFAULT_CHUNK_SIZE: u64 = 500_000;
+
+/// The value of [`ChunkManifest::format`].
+pub const MANIFEST_FORMAT: &str = "linera-chunked-data-blob-v1";
+
+/// The data blob listing the chunks of a file too large to be published as a single blob.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkManifest {
+    /// Identifies the blob as a manifest: always [`MANIFEST_FORMAT`].
+    pub format: String,
+    /// The size of the file, in bytes.
+    pub size: u64,
+    /// The hash the file would have as a single data blob.
+    pub content_hash: CryptoHash,
+    /// The hashes of the data blobs holding the chunks of the file, in order.
+    pub chunks: Vec<CryptoHash>,
+}
+
+/// An error reading or reassembling a chunked data blob.
+#[derive(Debug, thiserror::Error)]
+pub enum ChunkedBlobError {
+    /// The blob is not a manifest.
+    #[error("the data blob is not a chunk manifest: {0}")]
+    InvalidManifest(String),
+    /// The chunks do not add up to the file described by the manifest.
+    #[error(
+        "the reassembled chunks do not match the manifest: expected {expected_size} bytes \
+         with hash {expected_hash}, got {size} bytes with hash {hash}"
+    )]
+    Mismatch {
+        /// The size of the file according to the manifest.
+        expected_size: u64,
+        /// The hash of the file according to the manifest.
+        expected_hash: CryptoHash,
+        /// The size of the reassembled file.
+        size: u64,
+        /// The hash of the reassembled file.
+        hash: CryptoHash,
+    },
+}
+
+/// Splits `bytes` into chunks of at most `chunk_size` bytes, and returns them together
+/// with their manifest.
+///
+/// # Panics
+///
+/// If `chunk_size` is zero.
+pub fn split(bytes: &[u8], chunk_size: usize) -> (Vec<Vec<u8>>, ChunkManifest) {
+    let chunks = bytes
+        .chunks(chunk_size)
+        .map(<[u8]>::to_vec)
+        .collect::<Vec<_>>();
+    let manifest = ChunkManifest {
+        format: MANIFEST_FORMAT.to_string(),
+        size: bytes.len() as u64,
+        content_hash: content_hash(bytes.to_vec()),
+        chunks: chunks
+            .iter()
+            .map(|chunk| content_hash(chunk.clone()))
+            .collect(),
+    };
+    (chunks, manifest)
+}
+
+impl ChunkManifest {
+    /// Returns the contents of the manifest blob.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("serializing a manifest cannot fail")
+    }
+
+    /// Parses the contents of a manifest blob.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ChunkedBlobError> {
+        let manifest = serde_json::from_slice::<Self>(bytes)
+            .map_err(|error| ChunkedBlobError::InvalidManifest(error.to_string()))?;
+        if manifest.format != MANIFEST_FORMAT {
+            return Err(ChunkedBlobError::InvalidManifest(format!(
+                "unknown format {:?}",
+                manifest.format
+            )));
+        }
+        Ok(manifest)
+    }
+
+    /// Concatenates the chunks, in the order of the manifest, and checks that they form the
+    /// original file.
+    pub fn reassemble(&self, chunks: Vec<Vec<u8>>) -> Result<Vec<u8>, ChunkedBlobError> {
+        let bytes = chunks.concat();
+        let size = bytes.len() as u64;
+        let hash = content_hash(bytes.clone());
+        if size != self.size || hash != self.content_hash {
+            return Err(ChunkedBlobError::Mismatch {
+                expected_size: self.size,
+                expected_hash: self.content_hash,
+                size,
+                hash,
+            });
+        }
+        Ok(bytes)
+    }
+}
+
+/// Returns the hash of `bytes` as a data blob.
+fn content_hash(bytes: Vec<u8>) -> CryptoHash {
+    CryptoHash::new(&BlobContent::new_data(bytes))
+}
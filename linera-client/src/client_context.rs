@@ -1,15 +1,18 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::sync::Arc;
+use std::{
+    collections::{BTreeSet, VecDeque},
+    sync::Arc,
+};
 
 #[cfg(not(web))]
 use futures::StreamExt as _;
 use futures::{Future, TryStreamExt as _};
 use linera_base::{
-    crypto::{CryptoHash, ValidatorPublicKey},
-    data_types::{ChainDescription, Epoch, Timestamp},
-    identifiers::{Account, AccountOwner, ChainId},
+    crypto::{CryptoHash, Signer as _, ValidatorPublicKey},
+    data_types::{ApplicationDescription, BlockHeight, ChainDescription, Epoch, Timestamp},
+    identifiers::{Account, AccountOwner, ApplicationId, ChainId},
     ownership::ChainOwnership,
     time::{Duration, Instant},
     util::future::FutureSyncExt as _,
@@ -17,12 +20,15 @@ use linera_base::{
 use linera_chain::{manager::LockingBlock, types::ConfirmedBlockCertificate};
 use linera_core::{
     client::{chain_client, ChainClient, Client, ListeningMode},
-    data_types::{ChainInfo, ChainInfoQuery, ClientOutcome},
+    data_types::{ChainInfo, ChainInfoQuery, ClientOutcome, OrphanedProposal},
     join_set_ext::JoinSet,
     node::ValidatorNode,
     wallet, Environment, JoinSetExt as _, Wallet as _,
 };
-use linera_rpc::node_provider::{NodeOptions, NodeProvider};
+use linera_rpc::{
+    node_provider::{NodeOptions, NodeProvider},
+    EndpointSelection,
+};
 use linera_storage::Storage as _;
 use linera_version::VersionInfo;
 use thiserror_context::Context;
@@ -34,11 +40,7 @@ use {
         client_metrics::ClientMetrics,
     },
     futures::stream,
-    linera_base::{
-        crypto::AccountPublicKey,
-        data_types::{Amount, BlockHeight},
-        identifiers::{ApplicationId, BlobType},
-    },
+    linera_base::{crypto::AccountPublicKey, data_types::Amount, identifiers::BlobType},
     linera_execution::{
         system::{OpenChainConfig, SystemOperation},
         Operation,
@@ -48,12 +50,21 @@ use {
 };
 #[cfg(feature = "fs")]
 use {
+    crate::{
+        bytecode_source::BytecodeSource,
+        chunked_blob::{self, ChunkManifest},
+        module_attestation::{
+            AttestationError, BuildInfo, ModuleAttestation, ModuleProvenance, VerifiedAttestation,
+        },
+        spending_journal::{self, SpendingJournal},
+    },
     linera_base::{
-        data_types::{BlobContent, Bytecode},
+        data_types::{Blob, BlobContent},
         identifiers::ModuleId,
         vm::VmRuntime,
     },
     linera_core::client::create_bytecode_blobs,
+    linera_execution::BlobOrigin,
     std::{fs, path::PathBuf},
 };
 
@@ -61,7 +72,9 @@ use crate::{
     chain_listener::{self, ClientContext as _, ClientContextExt as _},
     client_options::{ChainOwnershipConfig, Options},
     config::GenesisConfig,
-    error, util, Error,
+    error,
+    ownership_proof::OwnershipChallenge,
+    util, Error,
 };
 
 /// Results from querying a validator about version, network description, and chain info.
@@ -90,6 +103,50 @@ impl ValidatorQueryResults {
         errors
     }
 
+    /// Returns the validator information as a JSON object: the public key, address and
+    /// weight if given, the version, genesis config hash and chain info that could be
+    /// queried, and the errors.
+    pub fn to_json(
+        &self,
+        public_key: Option<&ValidatorPublicKey>,
+        address: Option<&str>,
+        weight: Option<u64>,
+    ) -> serde_json::Value {
+        let version = self.version_info.as_ref().ok().map(|version_info| {
+            serde_json::json!({
+                "crateVersion": version_info.crate_version.to_string(),
+                "rpcHash": version_info.rpc_hash,
+                "graphqlHash": version_info.graphql_hash,
+                "witHash": version_info.wit_hash,
+                "gitCommit": version_info.git_commit,
+                "gitDirty": version_info.git_dirty,
+            })
+        });
+        let chain_info = self.chain_info.as_ref().ok().map(|info| {
+            serde_json::json!({
+                "blockHash": info.block_hash,
+                "nextBlockHeight": info.next_block_height,
+                "timestamp": info.timestamp,
+                "epoch": info.epoch,
+                "round": info.manager.current_round.to_string(),
+                "leader": info.manager.leader,
+            })
+        });
+        serde_json::json!({
+            "publicKey": public_key,
+            "address": address,
+            "weight": weight,
+            "version": version,
+            "genesisConfigHash": self.genesis_config_hash.as_ref().ok(),
+            "chainInfo": chain_info,
+            "errors": self
+                .errors()
+                .into_iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+        })
+    }
+
     /// Prints validator information to stdout.
     ///
     /// Prints public key, address, and optionally weight, version info, and chain info.
@@ -208,6 +265,112 @@ impl ValidatorQueryResults {
     }
 }
 
+/// The health of a validator, as measured by querying it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorHealth {
+    /// The round-trip time of the queries to the validator.
+    pub latency: Duration,
+    /// How many blocks the validator is behind the highest height seen for the chain, or
+    /// `None` if its chain info is unavailable.
+    pub height_lag: Option<u64>,
+    /// Whether the validator runs a different release than this client, or `None` if its
+    /// version is unavailable.
+    pub version_skew: Option<bool>,
+    /// The number of queries that failed.
+    pub error_count: usize,
+}
+
+impl ValidatorHealth {
+    /// Latencies above this lower the score.
+    pub const SLOW_LATENCY: Duration = Duration::from_millis(500);
+    /// Latencies above this lower the score further.
+    pub const VERY_SLOW_LATENCY: Duration = Duration::from_secs(2);
+
+    /// Returns the health of a validator whose queries returned `results` after `latency`,
+    /// given the highest block height seen for the chain.
+    pub fn new(
+        results: &ValidatorQueryResults,
+        latency: Duration,
+        highest_height: BlockHeight,
+    ) -> Self {
+        Self {
+            latency,
+            height_lag: results
+                .chain_info
+                .as_ref()
+                .ok()
+                .map(|info| highest_height.0.saturating_sub(info.next_block_height.0)),
+            version_skew: results.version_info.as_ref().ok().map(|version_info| {
+                version_info.crate_version != linera_version::VERSION_INFO.crate_version
+            }),
+            error_count: results.errors().len(),
+        }
+    }
+
+    /// Returns a score between 0 for an unreachable validator and 100 for a healthy one.
+    ///
+    /// Each failed query costs 25 points, each block of lag 2 points (at most 30), a
+    /// different release 10 points, and a slow or very slow response 10 or 20 points.
+    pub fn score(&self) -> u8 {
+        if self.height_lag.is_none() && self.version_skew.is_none() {
+            return 0;
+        }
+        let errors = u64::try_from(self.error_count)
+            .unwrap_or(u64::MAX)
+            .saturating_mul(25);
+        let lag = self.height_lag.unwrap_or(0).saturating_mul(2).min(30);
+        let skew = if self.version_skew == Some(true) {
+            10
+        } else {
+            0
+        };
+        let latency = if self.latency > Self::VERY_SLOW_LATENCY {
+            20
+        } else if self.latency > Self::SLOW_LATENCY {
+            10
+        } else {
+            0
+        };
+        let penalty = errors.saturating_add(lag + skew + latency);
+        u8::try_from(100u64.saturating_sub(penalty)).expect("at most 100")
+    }
+
+    /// Returns the health as a JSON object.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "score": self.score(),
+            "latencyMs": u64::try_from(self.latency.as_millis()).unwrap_or(u64::MAX),
+            "heightLag": self.height_lag,
+            "versionSkew": self.version_skew,
+            "errorCount": self.error_count,
+        })
+    }
+}
+
+impl std::fmt::Display for ValidatorHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}/100 (latency {} ms, ",
+            self.score(),
+            self.latency.as_millis()
+        )?;
+        match self.height_lag {
+            Some(lag) => write!(f, "{lag} block(s) behind, ")?,
+            None => write!(f, "height unknown, ")?,
+        }
+        match self.version_skew {
+            Some(true) => write!(f, "different release")?,
+            Some(false) => write!(f, "same release")?,
+            None => write!(f, "release unknown")?,
+        }
+        if self.error_count > 0 {
+            write!(f, ", {} error(s)", self.error_count)?;
+        }
+        write!(f, ")")
+    }
+}
+
 /// The state shared by the client commands: the core client, wallet configuration, and
 /// network timeouts.
 pub struct ClientContext<Env: Environment> {
@@ -226,6 +389,10 @@ pub struct ClientContext<Env: Environment> {
     pub max_retries: u32,
     /// The maximum backoff between retries of a failed request to a validator.
     pub max_backoff: Duration,
+    /// The maximum number of concurrent requests to each validator, if limited.
+    pub max_concurrent_requests: Option<usize>,
+    /// How to choose among the proxies of a validator with several addresses.
+    pub endpoint_selection: EndpointSelection,
     /// The set of background tasks listening for chain notifications.
     pub chain_listeners: JoinSet,
     /// The default chain used when no chain is explicitly specified.
@@ -234,6 +401,9 @@ pub struct ClientContext<Env: Environment> {
     /// The metrics collector, if metrics collection is enabled.
     #[cfg(not(web))]
     pub client_metrics: Option<ClientMetrics>,
+    /// Where the tokens moved by the blocks of this client are recorded, if anywhere.
+    #[cfg(feature = "fs")]
+    pub spending_journal: Option<SpendingJournal>,
 }
 
 impl<Env: Environment> chain_listener::ClientContext for ClientContext<Env> {
@@ -306,6 +476,8 @@ where
             retry_delay: options.retry_delay,
             max_retries: options.max_retries,
             max_backoff: options.max_backoff,
+            max_concurrent_requests: Some(options.max_concurrent_requests_per_validator),
+            endpoint_selection: options.endpoint_selection,
         });
         let chain_modes: Vec<_> = wallet
             .items()
@@ -366,9 +538,13 @@ where
             retry_delay: options.retry_delay,
             max_retries: options.max_retries,
             max_backoff: options.max_backoff,
+            max_concurrent_requests: Some(options.max_concurrent_requests_per_validator),
+            endpoint_selection: options.endpoint_selection,
             chain_listeners: JoinSet::default(),
             #[cfg(not(web))]
             client_metrics,
+            #[cfg(feature = "fs")]
+            spending_journal: None,
         })
     }
 }
@@ -427,6 +603,8 @@ impl<Env: Environment> ClientContext<Env> {
             retry_delay: self.retry_delay,
             max_retries: self.max_retries,
             max_backoff: self.max_backoff,
+            max_concurrent_requests: self.max_concurrent_requests,
+            endpoint_selection: self.endpoint_selection,
         }
     }
 
@@ -443,19 +621,24 @@ impl<Env: Environment> ClientContext<Env> {
     ) -> Result<(), Error> {
         let info = client.chain_info().await?;
         let chain_id = info.chain_id;
-        let existing_owner = self
+        let existing_chain = self
             .wallet()
             .get(chain_id)
             .await
-            .map_err(error::Inner::wallet)?
-            .and_then(|chain| chain.owner);
+            .map_err(error::Inner::wallet)?;
+        #[cfg(feature = "fs")]
+        if let (Some(journal), Some(existing_chain)) = (&self.spending_journal, &existing_chain) {
+            Self::record_new_blocks(
+                journal,
+                client,
+                existing_chain.next_block_height,
+                info.next_block_height,
+            )
+            .await;
+        }
+        let existing_owner = existing_chain.and_then(|chain| chain.owner);
 
-        // Only persist proposals that were made in the fast round: they need to be
-        // remembered across sessions to make sure there are no conflicting fast proposals.
-        let pending_fast_proposal = client
-            .pending_proposal()
-            .await
-            .filter(|p| p.round.is_some_and(|r| r.is_fast()));
+        let pending_fast_proposal = client.stored_fast_proposal().await?;
         let new_chain = wallet::Chain {
             pending_fast_proposal,
             owner: existing_owner,
@@ -470,6 +653,136 @@ impl<Env: Environment> ClientContext<Env> {
         Ok(())
     }
 
+    /// Resolves the pending proposals of the wallet's chains that were overtaken by a
+    /// different block, see [`ChainClient::resolve_orphaned_proposal`], and warns about the
+    /// conflicts that are not safe to resolve automatically.
+    ///
+    /// The wallet only remembers the proposals made in the fast round across sessions, so
+    /// only the owned chains with such a proposal are checked with the validators. Failing
+    /// to check a chain is logged, and the other chains are still checked.
+    pub async fn resolve_orphaned_proposals(&self) -> Result<(), Error> {
+        let chain_ids = self
+            .wallet()
+            .items()
+            .try_filter_map(|(chain_id, chain)| async move {
+                Ok(
+                    (chain.owner.is_some() && chain.pending_fast_proposal.is_some())
+                        .then_some(chain_id),
+                )
+            })
+            .try_collect::<Vec<_>>()
+            .await
+            .map_err(error::Inner::wallet)?;
+        for chain_id in chain_ids {
+            let chain_client = match self.make_chain_client(chain_id).await {
+                Ok(chain_client) => chain_client,
+                Err(error) => {
+                    warn!(%chain_id, %error, "Failed to check the pending proposal");
+                    continue;
+                }
+            };
+            match chain_client.resolve_orphaned_proposal().await {
+                Ok(None) => continue,
+                Ok(Some(OrphanedProposal::Resolved {
+                    height,
+                    certified_hash,
+                    requeued,
+                })) => {
+                    info!(
+                        %chain_id, %height, %certified_hash,
+                        "Discarded the pending proposal, overtaken by a different block"
+                    );
+                    match requeued {
+                        Some(ClientOutcome::Committed(certificate)) => info!(
+                            %chain_id,
+                            "Committed the operations of the pending proposal again in block {}",
+                            certificate.hash()
+                        ),
+                        Some(ClientOutcome::WaitForTimeout(timeout)) => warn!(
+                            %chain_id,
+                            "Could not propose the operations of the pending proposal again \
+                             before the round times out: {timeout}"
+                        ),
+                        Some(ClientOutcome::Conflict(certificate)) => warn!(
+                            %chain_id,
+                            "Could not propose the operations of the pending proposal again: \
+                             block {} was committed instead",
+                            certificate.hash()
+                        ),
+                        None => {}
+                    }
+                }
+                Ok(Some(OrphanedProposal::Unsafe { height, reason })) => warn!(
+                    %chain_id, %height,
+                    "Conflicting pending proposal needs attention: {reason}"
+                ),
+                Err(error) => {
+                    warn!(%chain_id, %error, "Failed to check the pending proposal");
+                    continue;
+                }
+            }
+            if let Err(error) = self.update_wallet_from_client(&chain_client).await {
+                warn!(%chain_id, %error, "Failed to update the wallet");
+            }
+        }
+        Ok(())
+    }
+
+    /// Records the tokens moved by the chain's blocks at the heights from `start` to `end`,
+    /// excluded, reading them from storage. Fees are only known for the blocks that this
+    /// client executed recently.
+    ///
+    /// The blocks were committed already, so failing to record them is not an error.
+    #[cfg(feature = "fs")]
+    async fn record_new_blocks<Env_: Environment>(
+        journal: &SpendingJournal,
+        client: &ChainClient<Env_>,
+        start: BlockHeight,
+        end: BlockHeight,
+    ) {
+        let chain_id = client.chain_id();
+        let heights = (start.0..end.0).map(BlockHeight).collect::<Vec<_>>();
+        let certificates = match client
+            .storage_client()
+            .read_certificates_by_heights(chain_id, &heights)
+            .await
+        {
+            Ok(certificates) => certificates,
+            Err(error) => {
+                warn!(%chain_id, %error, "Failed to read the new blocks to record");
+                return;
+            }
+        };
+        let fees = client
+            .recent_block_fees()
+            .into_iter()
+            .collect::<std::collections::HashMap<_, _>>();
+        let mut entries = Vec::new();
+        for (height, certificate) in heights.into_iter().zip(certificates) {
+            let Some(certificate) = certificate else {
+                warn!(%chain_id, %height, "Cannot record a block missing from storage");
+                continue;
+            };
+            let block_hash = certificate.hash();
+            let block_fees = fees.get(&block_hash).copied().unwrap_or_else(|| {
+                debug!(%chain_id, %height, "The fees of the block are unknown");
+                linera_base::data_types::Amount::ZERO
+            });
+            entries.extend(spending_journal::entries_of_block(
+                certificate.block(),
+                block_hash,
+                block_fees,
+            ));
+        }
+        if let Err(error) = journal.append(&entries) {
+            warn!(
+                %error,
+                "Failed to record the tokens moved by the new blocks in {}",
+                journal.path().display()
+            );
+        }
+    }
+
     /// Remembers the new chain and its owner (if any) in the wallet.
     pub async fn update_wallet_for_new_chain(
         &mut self,
@@ -592,6 +905,32 @@ impl<Env: Environment> ClientContext<Env> {
         Ok(())
     }
 
+    /// Checks that the client controls the key of `owner`, by signing a random challenge
+    /// with it, e.g. before assigning the owner to `chain_id`.
+    pub async fn verify_key_ownership(
+        &self,
+        owner: AccountOwner,
+        chain_id: ChainId,
+    ) -> Result<(), Error> {
+        let challenge = OwnershipChallenge::new(owner, chain_id);
+        let signature = self
+            .client
+            .signer()
+            .sign(&owner, &CryptoHash::new(&challenge))
+            .await
+            .map_err(|error| error::Inner::OwnershipProof {
+                owner,
+                error: error.to_string(),
+            })?;
+        challenge
+            .verify(&signature)
+            .map_err(|error| error::Inner::OwnershipProof {
+                owner,
+                error: error.to_string(),
+            })?;
+        Ok(())
+    }
+
     /// Applies the given function to the chain client.
     ///
     /// Updates the wallet regardless of the outcome. As long as the function returns a round
@@ -704,6 +1043,63 @@ impl<Env: Environment> ClientContext<Env> {
         Ok(())
     }
 
+    /// Replaces the preferred owner of the given chain with `new_owner` in the chain's
+    /// ownership, keeping its role and weight. The wallet is only updated once the change
+    /// is confirmed, so that it never refers to a key that doesn't own the chain yet.
+    ///
+    /// Returns the replaced owner.
+    pub async fn rotate_key(
+        &mut self,
+        chain_id: Option<ChainId>,
+        new_owner: AccountOwner,
+    ) -> Result<AccountOwner, Error> {
+        let chain_id = chain_id.unwrap_or_else(|| self.default_chain());
+        let mut chain_client = self.make_chain_client(chain_id).await?;
+        let Some(old_owner) = chain_client.preferred_owner() else {
+            tracing::error!(%chain_id, "The wallet has no owner key for this chain.");
+            return Err(error::Inner::ChainOwnership.into());
+        };
+        let mut ownership = chain_client.query_chain_ownership().await?;
+        if !ownership.is_owner(&old_owner) {
+            tracing::error!(%chain_id, %old_owner, "The wallet's key does not own this chain.");
+            return Err(error::Inner::ChainOwnership.into());
+        }
+        if ownership.super_owners.remove(&old_owner) {
+            ownership.super_owners.insert(new_owner);
+        }
+        if let Some(weight) = ownership.owners.remove(&old_owner) {
+            ownership.owners.insert(new_owner, weight);
+        }
+        if ownership.first_leader == Some(old_owner) {
+            ownership.first_leader = Some(new_owner);
+        }
+        info!(%chain_id, %old_owner, %new_owner, "Rotating the owner key of a chain");
+        let time_start = Instant::now();
+
+        let certificate = self
+            .apply_client_command(&chain_client, |chain_client| {
+                let ownership = ownership.clone();
+                let chain_client = chain_client.clone();
+                async move {
+                    chain_client
+                        .change_ownership(ownership)
+                        .await
+                        .map_err(Error::from)
+                        .context("Failed to rotate the owner key")
+                }
+            })
+            .await?;
+        let time_total = time_start.elapsed();
+        info!("Operation confirmed after {} ms", time_total.as_millis());
+        debug!("{:?}", certificate);
+        chain_client.set_preferred_owner(new_owner);
+        self.wallet()
+            .modify(chain_id, |chain| chain.owner = Some(new_owner))
+            .await
+            .map_err(error::Inner::wallet)?;
+        Ok(old_owner)
+    }
+
     /// Checks that the validator's version info is compatible with the local version.
     pub async fn check_compatible_version_info(
         &self,
@@ -845,46 +1241,79 @@ impl<Env: Environment> ClientContext<Env> {
             chain_info,
         })
     }
+
+    /// Resolves the given required applications and, transitively, their own dependencies,
+    /// so that creating an application requiring them fails early with an actionable error
+    /// instead of failing during execution.
+    ///
+    /// Returns the descriptions of all the dependencies, in the order they were resolved.
+    pub async fn resolve_required_applications(
+        &self,
+        chain_client: &ChainClient<Env>,
+        required_application_ids: &[ApplicationId],
+    ) -> Result<Vec<(ApplicationId, ApplicationDescription)>, Error> {
+        let mut resolved = Vec::new();
+        let mut seen = BTreeSet::new();
+        let mut pending = required_application_ids
+            .iter()
+            .map(|application_id| (*application_id, None))
+            .collect::<VecDeque<_>>();
+        while let Some((application_id, required_by)) = pending.pop_front() {
+            if !seen.insert(application_id) {
+                continue;
+            }
+            let description = chain_client
+                .get_application_description(application_id)
+                .await
+                .map_err(|error| error::Inner::MissingRequiredApplication {
+                    application_id,
+                    required_by: required_by.map_or_else(
+                        || "the new application".to_string(),
+                        |required_by| format!("application {required_by}"),
+                    ),
+                    error: Box::new(error),
+                })?;
+            debug!(
+                %application_id,
+                module_id = %description.module_id,
+                creator_chain_id = %description.creator_chain_id,
+                "Resolved required application"
+            );
+            pending.extend(
+                description
+                    .required_application_ids
+                    .iter()
+                    .map(|dependency| (*dependency, Some(application_id))),
+            );
+            resolved.push((application_id, description));
+        }
+        Ok(resolved)
+    }
 }
 
 #[cfg(feature = "fs")]
 impl<Env: Environment> ClientContext<Env> {
     /// Publishes a module from its contract and service bytecode files.
+    ///
+    /// If the same module was already published, e.g. by an earlier attempt that failed to
+    /// create an application from it, it is reused instead of being published again.
     pub async fn publish_module(
         &mut self,
         chain_client: &ChainClient<Env>,
-        contract: PathBuf,
-        service: PathBuf,
+        contract: BytecodeSource,
+        service: BytecodeSource,
         vm_runtime: VmRuntime,
         formats: Option<PathBuf>,
     ) -> Result<ModuleId, Error> {
-        info!("Loading bytecode files");
-        let contract_bytecode = Bytecode::load_from_file(&contract).await.map_err(|e| {
-            std::io::Error::new(
-                e.kind(),
-                format!("failed to load contract bytecode from {contract:?}: {e}"),
-            )
-        })?;
-        let service_bytecode = Bytecode::load_from_file(&service).await.map_err(|e| {
-            std::io::Error::new(
-                e.kind(),
-                format!("failed to load service bytecode from {service:?}: {e}"),
-            )
-        })?;
-
-        let formats_bytes = match formats {
-            Some(path) => Some(bcs::to_bytes(&load_formats_from_snap(&path)?)?),
-            None => None,
-        };
+        let (blobs, module_id) = self
+            .load_module_blobs(contract, service, vm_runtime, formats)
+            .await?;
+        if self.is_module_published(module_id).await? {
+            info!("Module {module_id} is already published; reusing it");
+            return Ok(module_id);
+        }
 
         info!("Publishing module");
-        let (blobs, module_id) = create_bytecode_blobs(
-            contract_bytecode,
-            service_bytecode,
-            vm_runtime,
-            formats_bytes,
-        )
-        .await;
         let (module_id, _) = self
             .apply_client_command(chain_client, |chain_client| {
                 let blobs = blobs.clone();
@@ -905,6 +1334,236 @@ impl<Env: Environment> ClientContext<Env> {
         Ok(module_id)
     }
 
+    /// Publishes a module like [`publish_module`](Self::publish_module), together with an
+    /// attestation of its provenance signed by `signer`, and returns the hash of the
+    /// attestation blob.
+    ///
+    /// The attestation is published in the same block as the module, where
+    /// [`verify_module_attestations`](Self::verify_module_attestations) finds it. If the
+    /// module was already published, only the attestation is published.
+    pub async fn publish_attested_module(
+        &mut self,
+        chain_client: &ChainClient<Env>,
+        contract: BytecodeSource,
+        service: BytecodeSource,
+        vm_runtime: VmRuntime,
+        formats: Option<PathBuf>,
+        build: BuildInfo,
+        signer: AccountOwner,
+    ) -> Result<(ModuleId, CryptoHash), Error> {
+        let (blobs, module_id) = self
+            .load_module_blobs(contract, service, vm_runtime, formats)
+            .await?;
+        let provenance = ModuleProvenance::new(module_id, build);
+        let signature = self
+            .client
+            .signer()
+            .sign(&signer, &CryptoHash::new(&provenance))
+            .await
+            .map_err(|error| error::Inner::AttestationSigning {
+                owner: signer,
+                module_id,
+                error: error.to_string(),
+            })?;
+        let attestation_blob = ModuleAttestation {
+            provenance,
+            signature,
+        }
+        .to_blob();
+        let attestation_hash = attestation_blob.id().hash;
+
+        if self.is_module_published(module_id).await? {
+            info!("Module {module_id} is already published; publishing its attestation");
+            self.apply_client_command(chain_client, |chain_client| {
+                let bytes = attestation_blob.bytes().to_vec();
+                let chain_client = chain_client.clone();
+                async move {
+                    chain_client
+                        .publish_data_blob(bytes)
+                        .await
+                        .context("Failed to publish the module attestation")
+                }
+            })
+            .await?;
+        } else {
+            info!("Publishing module with its attestation");
+            self.apply_client_command(chain_client, |chain_client| {
+                let blobs = blobs.clone();
+                let attestation_blob = attestation_blob.clone();
+                let chain_client = chain_client.clone();
+                async move {
+                    chain_client
+                        .publish_module_blobs_with_data(blobs, module_id, vec![attestation_blob])
+                        .await
+                        .context("Failed to publish module")
+                }
+            })
+            .await?;
+            info!("{}", "Module published successfully!");
+        }
+
+        info!("Synchronizing client and processing inbox");
+        self.process_inbox(chain_client).await?;
+        Ok((module_id, attestation_hash))
+    }
+
+    /// Verifies the provenance attestations of a module, and returns them with their
+    /// signers.
+    ///
+    /// Without `attestation_hashes`, the attestations are looked up in the block that
+    /// published the module, which must be known to this client. Otherwise the given data
+    /// blobs are downloaded and must all be valid attestations of the module.
+    pub async fn verify_module_attestations(
+        &self,
+        module_id: ModuleId,
+        attestation_hashes: Vec<CryptoHash>,
+    ) -> Result<Vec<VerifiedAttestation>, Error> {
+        let scanning = attestation_hashes.is_empty();
+        let attestation_hashes = if scanning {
+            self.attestation_blobs_of_publishing_block(module_id)
+                .await?
+        } else {
+            attestation_hashes
+        };
+        let mut verified = Vec::new();
+        for blob_hash in attestation_hashes {
+            let blob = self.client.get_data_blob(blob_hash).await?;
+            let Some(attestation) = ModuleAttestation::from_blob(&blob) else {
+                if scanning {
+                    continue;
+                }
+                return Err(AttestationError::NotAnAttestation(blob_hash).into());
+            };
+            let signer = match attestation.verify(module_id) {
+                Ok(signer) => signer,
+                Err(AttestationError::WrongModule { .. }) if scanning => continue,
+                Err(error) => return Err(error.into()),
+            };
+            verified.push(VerifiedAttestation {
+                blob_hash,
+                signer,
+                provenance: attestation.provenance,
+            });
+        }
+        if verified.is_empty() {
+            return Err(AttestationError::NotFound(module_id).into());
+        }
+        Ok(verified)
+    }
+
+    /// Returns the hashes of the data blobs published by the block that published the
+    /// given module.
+    async fn attestation_blobs_of_publishing_block(
+        &self,
+        module_id: ModuleId,
+    ) -> Result<Vec<CryptoHash>, Error> {
+        let storage = self.client.storage_client();
+        let contract_blob_id = module_id.contract_bytecode_blob_id();
+        let Some(BlobOrigin::Published {
+            chain_id,
+            block_height,
+        }) = storage
+            .read_blob_state(contract_blob_id)
+            .await?
+            .map(|state| state.origin)
+        else {
+            return Err(AttestationError::UnknownModule(module_id).into());
+        };
+        let Some(certificate) = storage
+            .read_certificates_by_heights(chain_id, &[block_height])
+            .await?
+            .into_iter()
+            .next()
+            .flatten()
+        else {
+            return Err(AttestationError::UnknownModule(module_id).into());
+        };
+        Ok(certificate
+            .block()
+            .published_blob_ids()
+            .into_iter()
+            .filter(|blob_id| blob_id.blob_type == linera_base::identifiers::BlobType::Data)
+            .map(|blob_id| blob_id.hash)
+            .collect())
+    }
+
+    /// Loads the bytecode and, if given, the formats of a module, and returns the blobs
+    /// that publish it together with its ID.
+    pub async fn load_module_blobs(
+        &self,
+        contract: BytecodeSource,
+        service: BytecodeSource,
+        vm_runtime: VmRuntime,
+        formats: Option<PathBuf>,
+    ) -> Result<(Vec<Blob>, ModuleId), Error> {
+        info!("Loading bytecode");
+        let contract_bytecode = contract.load().await?;
+        let service_bytecode = service.load().await?;
+
+        let formats_bytes = match formats {
+            Some(path) => Some(bcs::to_bytes(&load_formats_from_snap(&path)?)?),
+            None => None,
+        };
+
+        Ok(create_bytecode_blobs(
+            contract_bytecode,
+            service_bytecode,
+            vm_runtime,
+            formats_bytes,
+        )
+        .await)
+    }
+
+    /// Returns whether all the bytecode blobs of the module were published in blocks known
+    /// to this client.
+    pub async fn is_module_published(&self, module_id: ModuleId) -> Result<bool, Error> {
+        let storage = self.client.storage_client();
+        for blob_id in module_id.bytecode_blob_ids() {
+            if !storage.contains_blob_state(blob_id).await? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Creates an application from a module published by
+    /// [`publish_module`](Self::publish_module).
+    ///
+    /// Published modules cannot be removed, so if the application cannot be created, the
+    /// error names the module, which is reused when the command is retried.
+    pub async fn create_application_from_module(
+        &mut self,
+        chain_client: &ChainClient<Env>,
+        module_id: ModuleId,
+        parameters: Vec<u8>,
+        argument: Vec<u8>,
+        required_application_ids: Vec<ApplicationId>,
+    ) -> Result<ApplicationId, Error> {
+        let (application_id, _) = self
+            .apply_client_command(chain_client, move |chain_client| {
+                let parameters = parameters.clone();
+                let argument = argument.clone();
+                let chain_client = chain_client.clone();
+                let required_application_ids = required_application_ids.clone();
+                async move {
+                    chain_client
+                        .create_application_untyped(
+                            module_id,
+                            parameters,
+                            argument,
+                            required_application_ids,
+                        )
+                        .await
+                }
+            })
+            .await
+            .map_err(|error| error::Inner::ApplicationCreation {
+                module_id,
+                error: Box::new(error),
+            })?;
+        Ok(application_id)
+    }
+
     /// Publishes a data blob loaded from the given file.
     pub async fn publish_data_blob(
         &mut self,
@@ -958,6 +1617,94 @@ impl<Env: Environment> ClientContext<Env> {
         info!("{}", "Data blob verified successfully!");
         Ok(())
     }
+
+    /// Publishes a file too large for a single data blob: splits it into data blobs of at
+    /// most `chunk_size` bytes, published over as many blocks as the resource control
+    /// policy requires, and then publishes a manifest blob listing them. Returns the hash of
+    /// the manifest.
+    pub async fn publish_chunked_data_blob(
+        &mut self,
+        chain_client: &ChainClient<Env>,
+        blob_path: PathBuf,
+        chunk_size: Option<u64>,
+    ) -> Result<CryptoHash, Error> {
+        info!("Loading data blob file");
+        let blob_bytes = fs::read(&blob_path).map_err(|e| {
+            std::io::Error::new(
+                e.kind(),
+                format!("failed to load data blob bytes from {blob_path:?}: {e}"),
+            )
+        })?;
+
+        let committee = chain_client.local_committee().await?;
+        let policy = committee.policy();
+        let chunk_size = chunk_size
+            .unwrap_or(chunked_blob::DEFAULT_CHUNK_SIZE)
+            .min(policy.maximum_blob_size)
+            .max(1);
+        // Leave room for the rest of the proposal.
+        let chunks_per_block = (policy.maximum_block_proposal_size / chunk_size)
+            .saturating_sub(1)
+            .min(policy.maximum_published_blobs)
+            .max(1);
+        let (chunks, manifest) = chunked_blob::split(
+            &blob_bytes,
+            usize::try_from(chunk_size).unwrap_or(usize::MAX),
+        );
+        let chunks_per_block = usize::try_from(chunks_per_block).unwrap_or(usize::MAX);
+        let block_count = chunks.len().div_ceil(chunks_per_block);
+
+        for (index, group) in chunks.chunks(chunks_per_block).enumerate() {
+            info!("Publishing chunks in block {} of {block_count}", index + 1);
+            self.apply_client_command(chain_client, |chain_client| {
+                let group = group.to_vec();
+                let chain_client = chain_client.clone();
+                async move {
+                    chain_client
+                        .publish_data_blobs(group)
+                        .await
+                        .context("Failed to publish data blob chunks")
+                }
+            })
+            .await?;
+        }
+
+        info!(
+            "Publishing the manifest of {} chunks",
+            manifest.chunks.len()
+        );
+        let manifest_bytes = manifest.to_bytes();
+        self.apply_client_command(chain_client, |chain_client| {
+            let manifest_bytes = manifest_bytes.clone();
+            let chain_client = chain_client.clone();
+            async move {
+                chain_client
+                    .publish_data_blob(manifest_bytes)
+                    .await
+                    .context("Failed to publish the chunk manifest")
+            }
+        })
+        .await?;
+
+        info!("{}", "Chunked data blob published successfully!");
+        Ok(CryptoHash::new(&BlobContent::new_data(manifest_bytes)))
+    }
+
+    /// Downloads the chunks listed by the manifest blob with the given hash, and returns the
+    /// file they form.
+    pub async fn reassemble_chunked_data_blob(
+        &self,
+        chain_client: &ChainClient<Env>,
+        hash: CryptoHash,
+    ) -> Result<Vec<u8>, Error> {
+        let manifest = ChunkManifest::from_bytes(&chain_client.get_data_blob(hash).await?)?;
+        info!("Downloading {} chunks", manifest.chunks.len());
+        let mut chunks = Vec::with_capacity(manifest.chunks.len());
+        for chunk_hash in &manifest.chunks {
+            chunks.push(chain_client.get_data_blob(*chunk_hash).await?);
+        }
+        Ok(manifest.reassemble(chunks)?)
+    }
 }
 
 /// Reads an insta SNAP file containing a YAML-encoded `Formats` value and parses
@@ -1068,17 +1815,22 @@ impl<Env: Environment> ClientContext<Env> {
     }
 
     /// Closes the benchmark chains, or processes their inboxes and updates the wallet.
+    ///
+    /// If `ephemeral_chains` is true, closed chains are made ephemeral first, so that
+    /// validators can prune their state.
     pub async fn wrap_up_benchmark(
         &mut self,
         chain_clients: Vec<ChainClient<Env>>,
         close_chains: bool,
+        ephemeral_chains: bool,
         wrap_up_max_in_flight: usize,
     ) -> Result<(), Error> {
         if close_chains {
             info!("Closing chains...");
             let stream = stream::iter(chain_clients)
                 .map(|chain_client| async move {
-                    Benchmark::<Env>::close_benchmark_chain(&chain_client).await?;
+                    Benchmark::<Env>::close_benchmark_chain(&chain_client, ephemeral_chains)
+                        .await?;
                     info!("Closed chain {:?}", chain_client.chain_id());
                     Ok::<(), BenchmarkError>(())
                 })
@@ -1099,10 +1851,7 @@ impl<Env: Environment> ClientContext<Env> {
             for chain_client in chain_clients {
                 let info = chain_client.chain_info().await?;
                 let client_owner = chain_client.preferred_owner();
-                let pending_fast_proposal = chain_client
-                    .pending_proposal()
-                    .await
-                    .filter(|p| p.round.is_some_and(|r| r.is_fast()));
+                let pending_fast_proposal = chain_client.stored_fast_proposal().await?;
                 self.wallet()
                     .insert(
                         info.chain_id,
@@ -7,6 +7,7 @@ use std::{
 };
 
 use linera_base::{
+    crypto::ValidatorPublicKey,
     data_types::{ApplicationPermissions, BlanketMessagePolicy, MessagePolicy, TimeDelta},
     identifiers::{AccountOwner, ApplicationId, ChainId, GenericApplicationId},
     ownership::ChainOwnership,
@@ -22,6 +23,7 @@ use linera_core::{
     DEFAULT_QUORUM_GRACE_PERIOD,
 };
 use linera_execution::ResourceControlPolicy;
+use linera_rpc::EndpointSelection;
 
 #[cfg(not(web))]
 use crate::client_metrics::TimingConfig;
@@ -124,6 +126,26 @@ pub struct Options {
     )]
     pub max_backoff: Duration,
 
+    /// Maximum number of concurrent requests to each validator. Block proposals and
+    /// certificates are sent first, then synchronization requests, then blobs.
+    #[arg(long, default_value = "100")]
+    pub max_concurrent_requests_per_validator: usize,
+
+    /// How to choose among the proxies of a validator that lists several network
+    /// addresses.
+    #[arg(long, default_value_t, value_enum)]
+    pub endpoint_selection: EndpointSelection,
+
+    /// Keep using the network addresses of the committee even if validators announce new
+    /// ones in signed address updates.
+    #[arg(long)]
+    pub ignore_validator_address_updates: bool,
+
+    /// The network address to use for a validator, as `PUBLIC_KEY=ADDRESS`, regardless of the
+    /// committee and of the addresses it announces. Can be repeated.
+    #[arg(long = "pin-validator-address", value_parser = util::parse_validator_address_pin)]
+    pub pinned_validator_addresses: Vec<(ValidatorPublicKey, String)>,
+
     /// Initial probe interval (ms) for the notification circuit breaker. When a validator's
     /// notification stream exhausts retries, the circuit breaker waits this long before
     /// probing again. Doubles on each failed probe.
@@ -377,6 +399,8 @@ impl Options {
             notification_circuit_breaker_max_probe_interval: self
                 .notification_circuit_breaker_max_probe_interval,
             max_event_stream_queries: self.max_event_stream_queries,
+            follow_validator_address_updates: !self.ignore_validator_address_updates,
+            pinned_validator_addresses: self.pinned_validator_addresses.iter().cloned().collect(),
         }
     }
 
@@ -2,12 +2,13 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::iter::IntoIterator;
+use std::{collections::BTreeSet, fmt, iter::IntoIterator};
 
 use linera_base::{
     crypto::{AccountPublicKey, ValidatorPublicKey, ValidatorSecretKey},
     data_types::{Amount, ArithmeticError, Timestamp},
 };
+use linera_core::data_types::SignedValidatorAddress;
 pub use linera_core::genesis_config::{Error as GenesisConfigError, GenesisConfig};
 use linera_execution::{
     committee::{Committee, ValidatorState},
@@ -25,6 +26,22 @@ pub struct ValidatorConfig {
     pub account_key: AccountPublicKey,
     /// The network configuration for the validator.
     pub network: ValidatorPublicNetworkConfig,
+    /// The network configurations of further proxies through which the validator can be
+    /// reached. Clients spread their requests over all of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_networks: Vec<ValidatorPublicNetworkConfig>,
+}
+
+impl ValidatorConfig {
+    /// Returns the network address recorded in the committee: the addresses of all the
+    /// validator's proxies, separated by commas.
+    pub fn network_address(&self) -> String {
+        std::iter::once(&self.network)
+            .chain(&self.additional_networks)
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(&linera_rpc::balanced::ADDRESS_SEPARATOR.to_string())
+    }
 }
 
 /// The private configuration of a validator service.
@@ -32,10 +49,140 @@ pub struct ValidatorConfig {
 pub struct ValidatorServerConfig {
     /// The public configuration of the validator.
     pub validator: ValidatorConfig,
-    /// The secret key of the validator.
-    pub validator_secret: ValidatorSecretKey,
+    /// The secret key of the validator. Omitted when the key is only held by a separate
+    /// signer process (see `linera-validator-signer`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator_secret: Option<ValidatorSecretKey>,
     /// The internal network configuration of the validator.
     pub internal_network: ValidatorInternalNetworkConfig,
+    /// The alerts raised by the validator binaries, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<AlertConfig>,
+    /// The latest signed announcement of the validator's network address, if it changed
+    /// since the committee was created. Included in the chain information sent to clients.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address_update: Option<SignedValidatorAddress>,
+}
+
+/// A condition under which the validator binaries raise an alert.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlertCondition {
+    /// The 99th percentile of storage latencies is too high.
+    StorageLatency,
+    /// Too many requests fail while handling chains.
+    ChainErrors,
+    /// Too many requests fail because of missing blobs.
+    MissingBlobs,
+    /// Blocks from epochs this validator does not know yet are being rejected.
+    EpochLag,
+}
+
+impl fmt::Display for AlertCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            AlertCondition::StorageLatency => "storage-latency",
+            AlertCondition::ChainErrors => "chain-errors",
+            AlertCondition::MissingBlobs => "missing-blobs",
+            AlertCondition::EpochLag => "epoch-lag",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Where the alerts of a validator are sent.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum AlertNotifierConfig {
+    /// Posts each alert as JSON to the given URL.
+    Webhook {
+        /// The URL receiving the alerts.
+        url: String,
+    },
+    /// Triggers and resolves PagerDuty incidents through the Events API v2.
+    PagerDuty {
+        /// The integration key of the PagerDuty service.
+        routing_key: String,
+        /// The URL of the Events API, if not the default one.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        url: Option<String>,
+    },
+    /// Prints each alert as a line of JSON on the standard output.
+    StdoutJson,
+}
+
+/// The alert conditions checked by a validator, and the notifiers of its alerts.
+///
+/// Each condition is evaluated over the metrics recorded since the previous check. An alert
+/// is sent when a condition starts to hold and again when it is resolved.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertConfig {
+    /// The number of seconds between two checks of the conditions.
+    #[serde(default = "AlertConfig::default_check_interval_secs")]
+    pub check_interval_secs: u64,
+    /// The 99th percentile of storage latencies, in milliseconds, above which an alert is
+    /// raised.
+    #[serde(default = "AlertConfig::default_storage_latency_p99_ms")]
+    pub storage_latency_p99_ms: u64,
+    /// The number of failed requests between two checks from which an alert is raised.
+    #[serde(default = "AlertConfig::default_chain_errors")]
+    pub chain_errors: u64,
+    /// The number of requests failing because of missing blobs between two checks from
+    /// which an alert is raised.
+    #[serde(default = "AlertConfig::default_missing_blobs")]
+    pub missing_blobs: u64,
+    /// The number of blocks rejected between two checks because their epoch is ahead of
+    /// their chain's from which an alert is raised.
+    #[serde(default = "AlertConfig::default_future_epoch_blocks")]
+    pub future_epoch_blocks: u64,
+    /// The conditions that are not checked.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub disabled: BTreeSet<AlertCondition>,
+    /// Where the alerts are sent.
+    #[serde(default)]
+    pub notifiers: Vec<AlertNotifierConfig>,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: Self::default_check_interval_secs(),
+            storage_latency_p99_ms: Self::default_storage_latency_p99_ms(),
+            chain_errors: Self::default_chain_errors(),
+            missing_blobs: Self::default_missing_blobs(),
+            future_epoch_blocks: Self::default_future_epoch_blocks(),
+            disabled: BTreeSet::new(),
+            notifiers: Vec::new(),
+        }
+    }
+}
+
+impl AlertConfig {
+    fn default_check_interval_secs() -> u64 {
+        60
+    }
+
+    fn default_storage_latency_p99_ms() -> u64 {
+        1_000
+    }
+
+    fn default_chain_errors() -> u64 {
+        100
+    }
+
+    fn default_missing_blobs() -> u64 {
+        10
+    }
+
+    fn default_future_epoch_blocks() -> u64 {
+        1
+    }
+
+    /// Returns whether the given condition is checked.
+    pub fn is_enabled(&self, condition: AlertCondition) -> bool {
+        !self.disabled.contains(&condition)
+    }
 }
 
 /// The (public) configuration for all validators.
@@ -58,7 +205,7 @@ impl CommitteeConfig {
                 (
                     v.public_key,
                     ValidatorState {
-                        network_address: v.network.to_string(),
+                        network_address: v.network_address(),
                         votes: 100,
                         account_public_key: v.account_key,
                     },
@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use linera_base::{
-    crypto::ValidatorPublicKey, data_types::NetworkDescription, identifiers::ChainId,
+    crypto::ValidatorPublicKey,
+    data_types::NetworkDescription,
+    identifiers::{AccountOwner, ApplicationId, ChainId, ModuleId},
 };
 use linera_core::node::NodeError;
 use linera_version::VersionInfo;
@@ -34,8 +36,23 @@ pub(crate) enum Inner {
     RemoteNode(#[from] linera_core::node::NodeError),
     #[error("arithmetic error: {0}")]
     Arithmetic(#[from] linera_base::data_types::ArithmeticError),
+    #[cfg(feature = "fs")]
+    #[error("bytecode error: {0}")]
+    BytecodeSource(#[from] crate::bytecode_source::BytecodeSourceError),
+    #[error("chunked data blob error: {0}")]
+    ChunkedBlob(#[from] crate::chunked_blob::ChunkedBlobError),
+    #[error("module attestation error: {0}")]
+    ModuleAttestation(#[from] crate::module_attestation::AttestationError),
+    #[error("the key of {owner} failed to sign the attestation of module {module_id}: {error}")]
+    AttestationSigning {
+        owner: AccountOwner,
+        module_id: ModuleId,
+        error: String,
+    },
     #[error("incorrect chain ownership")]
     ChainOwnership,
+    #[error("the key of {owner} failed to sign an ownership challenge: {error}")]
+    OwnershipProof { owner: AccountOwner, error: String },
     #[cfg(not(web))]
     #[error("Benchmark error: {0}")]
     Benchmark(#[from] BenchmarkError),
@@ -61,6 +78,25 @@ pub(crate) enum Inner {
     },
     #[error("Signature for public key {public_key} is invalid.")]
     InvalidSignature { public_key: ValidatorPublicKey },
+    #[error(
+        "application {application_id}, required by {required_by}, could not be found: {error}. \
+         Check that the application ID is correct and that the application was created, \
+         e.g. with `linera create-application`"
+    )]
+    MissingRequiredApplication {
+        application_id: ApplicationId,
+        required_by: String,
+        error: Box<linera_core::client::chain_client::Error>,
+    },
+    #[error(
+        "failed to create the application from module {module_id}: {error}. \
+         Published modules cannot be removed: retrying the command reuses the module, \
+         as does `linera create-application {module_id}`"
+    )]
+    ApplicationCreation {
+        module_id: ModuleId,
+        error: Box<Error>,
+    },
     #[error("Failed to get chain info for validator {address} and chain {chain_id}: {error}")]
     UnavailableChainInfo {
         address: String,
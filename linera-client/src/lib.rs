@@ -7,6 +7,11 @@
 #![deny(missing_docs)]
 #![allow(async_fn_in_trait)]
 
+/// Loading application bytecode from files, HTTPS URLs and OCI registries.
+#[cfg(feature = "fs")]
+pub mod bytecode_source;
+/// Splitting files too large for a single data blob into chunks listed by a manifest blob.
+pub mod chunked_blob;
 /// Listens for notifications on the chains tracked by a client and reacts to them.
 pub mod chain_listener;
 /// The context bundling the wallet, storage, and configuration a client operates with.
@@ -21,6 +26,19 @@ pub use client_options::Options;
 /// Configuration types for wallets, committees, and validator servers.
 pub mod config;
 mod error;
+/// Signed provenance attestations of published modules.
+pub mod module_attestation;
+/// Challenges proving that the client controls the key of an owner.
+pub mod ownership_proof;
+/// Policies deciding when the chain listener proposes blocks.
+pub mod proposal_policy;
+/// Command outputs signed by a wallet key, so that third parties can verify them.
+pub mod signed_output;
+/// A relay brokering signatures from external EVM wallets, such as MetaMask.
+pub mod signing_relay;
+/// A local record of the tokens moved by the blocks of the client.
+#[cfg(feature = "fs")]
+pub mod spending_journal;
 /// Assorted parsing and command-line helper utilities.
 pub mod util;
 
@@ -0,0 +1,133 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{
+    crypto::{AccountSignature, BcsSignable, CryptoError, CryptoHash},
+    data_types::Blob,
+    identifiers::{AccountOwner, BlobType, ModuleId},
+};
+use serde::{Deserialize, Serialize};
+
+/// The type of the provenance statements, which tells them apart from other data blobs.
+pub const PREDICATE_TYPE: &str = "https://linera.io/attestation/module-provenance/v1";
+
+/// How the bytecode of a module was built, as claimed by its builder.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildInfo {
+    /// Who built the module, e.g. the CI workflow that ran the build.
+    pub builder_id: String,
+    /// Where the source code is, e.g. the URL of a Git repository.
+    pub source_repository: Option<String>,
+    /// The revision of the source code, e.g. a Git commit hash.
+    pub source_commit: String,
+    /// The toolchain that built the bytecode, e.g. the output of `rustc --version`.
+    pub toolchain: String,
+}
+
+/// A statement tying the bytecode of a module to the source and toolchain it was built
+/// from, in the spirit of SLSA provenance.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleProvenance {
+    /// Always [`PREDICATE_TYPE`].
+    pub predicate_type: String,
+    /// The module whose bytecode was built.
+    pub module_id: ModuleId,
+    /// How the bytecode was built.
+    pub build: BuildInfo,
+}
+
+impl BcsSignable<'_> for ModuleProvenance {}
+
+impl ModuleProvenance {
+    /// Creates the provenance statement of the given module.
+    pub fn new(module_id: ModuleId, build: BuildInfo) -> Self {
+        Self {
+            predicate_type: PREDICATE_TYPE.to_owned(),
+            module_id,
+            build,
+        }
+    }
+}
+
+/// A provenance statement signed by its builder, published as a data blob.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModuleAttestation {
+    /// The signed statement.
+    pub provenance: ModuleProvenance,
+    /// The signature of the statement.
+    pub signature: AccountSignature,
+}
+
+/// An error verifying a [`ModuleAttestation`].
+#[derive(Debug, thiserror::Error)]
+pub enum AttestationError {
+    /// The signature of the statement is invalid.
+    #[error("invalid signature: {0}")]
+    InvalidSignature(#[from] CryptoError),
+    /// The statement is about another module.
+    #[error("the attestation is about module {found}, not {expected}")]
+    WrongModule {
+        /// The module being verified.
+        expected: ModuleId,
+        /// The module of the attestation.
+        found: ModuleId,
+    },
+    /// The blob does not hold an attestation.
+    #[error("data blob {0} is not a module attestation")]
+    NotAnAttestation(CryptoHash),
+    /// No attestation of the module was found.
+    #[error(
+        "no attestation of module {0} was found in the block that published it; pass the \
+         hash of its attestation blob if it was published later"
+    )]
+    NotFound(ModuleId),
+    /// The block that published the module is unknown to this client.
+    #[error(
+        "module {0} was not published in a block known to this client; synchronize the \
+         chain that published it first"
+    )]
+    UnknownModule(ModuleId),
+}
+
+impl ModuleAttestation {
+    /// Returns the data blob holding the attestation.
+    pub fn to_blob(&self) -> Blob {
+        Blob::new_data(bcs::to_bytes(self).expect("serializing an attestation should not fail"))
+    }
+
+    /// Parses the attestation held by a data blob, if it holds one.
+    pub fn from_blob(blob: &Blob) -> Option<Self> {
+        if blob.id().blob_type != BlobType::Data {
+            return None;
+        }
+        let attestation = bcs::from_bytes::<Self>(blob.bytes()).ok()?;
+        (attestation.provenance.predicate_type == PREDICATE_TYPE).then_some(attestation)
+    }
+
+    /// Checks that the attestation is about `module_id` and that its signature is valid,
+    /// and returns the owner of the key that signed it.
+    pub fn verify(&self, module_id: ModuleId) -> Result<AccountOwner, AttestationError> {
+        if self.provenance.module_id != module_id {
+            return Err(AttestationError::WrongModule {
+                expected: module_id,
+                found: self.provenance.module_id,
+            });
+        }
+        self.signature.verify(&self.provenance)?;
+        Ok(self.signature.owner())
+    }
+}
+
+/// An attestation of a module that was verified, for `linera module verify`.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedAttestation {
+    /// The hash of the data blob holding the attestation.
+    pub blob_hash: CryptoHash,
+    /// The owner of the key that signed the attestation.
+    pub signer: AccountOwner,
+    /// The attested provenance.
+    pub provenance: ModuleProvenance,
+}
@@ -0,0 +1,46 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{
+    crypto::{AccountSignature, BcsSignable, CryptoError},
+    identifiers::{AccountOwner, ChainId},
+};
+use serde::{Deserialize, Serialize};
+
+/// A random challenge that the key of an owner signs, to prove that the client controls it
+/// before assigning it to a chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnershipChallenge {
+    /// The owner whose key is challenged.
+    pub owner: AccountOwner,
+    /// The chain the owner is about to be assigned to.
+    pub chain_id: ChainId,
+    /// Random bytes, so that the signature of another challenge cannot be replayed.
+    pub nonce: [u8; 32],
+}
+
+impl BcsSignable<'_> for OwnershipChallenge {}
+
+impl OwnershipChallenge {
+    /// Creates a new random challenge for the given owner.
+    pub fn new(owner: AccountOwner, chain_id: ChainId) -> Self {
+        Self {
+            owner,
+            chain_id,
+            nonce: rand::random(),
+        }
+    }
+
+    /// Checks that `signature` is a valid signature of the challenge by its owner. EVM
+    /// signatures are checked as EIP-191 messages.
+    pub fn verify(&self, signature: &AccountSignature) -> Result<(), CryptoError> {
+        signature.verify(self)?;
+        if signature.owner() != self.owner {
+            return Err(CryptoError::InvalidSignature {
+                error: format!("the challenge was signed by {}", signature.owner()),
+                type_name: "OwnershipChallenge".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,161 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fmt::Debug;
+
+use linera_base::{
+    data_types::{Round, TimeDelta, Timestamp},
+    identifiers::AccountOwner,
+};
+
+/// What the chain listener knows about a chain when deciding whether to propose a block
+/// processing its inbox.
+#[derive(Clone, Debug)]
+pub struct ProposalState {
+    /// The current time.
+    pub now: Timestamp,
+    /// Since when the listener has been asked to process the inbox without proposing.
+    pub waiting_since: Timestamp,
+    /// The number of message bundles waiting in the inbox.
+    pub pending_bundles: usize,
+    /// The owner the listener proposes as.
+    pub owner: AccountOwner,
+    /// The chain's current round.
+    pub current_round: Round,
+    /// The leader of the current round, or `None` if every owner may propose.
+    pub leader: Option<AccountOwner>,
+    /// When the current round times out, if it does.
+    pub round_timeout: Option<Timestamp>,
+}
+
+impl ProposalState {
+    /// Returns whether the listener's owner is the leader of the current round.
+    pub fn is_leader(&self) -> bool {
+        self.leader == Some(self.owner)
+    }
+}
+
+/// The decision of a [`ProposalPolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProposalDecision {
+    /// Propose now.
+    Propose,
+    /// Decide again at the given time, or earlier if new messages arrive.
+    WaitUntil(Timestamp),
+    /// Do not propose until new messages arrive.
+    WaitForMessages,
+}
+
+/// Controls when the chain listener proposes the blocks that process a chain's inbox.
+///
+/// On chains with several owners, services sharing a chain can use this to avoid racing each
+/// other and wasting rounds.
+pub trait ProposalPolicy: Debug + Send + Sync {
+    /// Decides whether to propose a block now.
+    fn decide(&self, state: &ProposalState) -> ProposalDecision;
+}
+
+/// Proposes as soon as there are messages to process.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Immediate;
+
+impl ProposalPolicy for Immediate {
+    fn decide(&self, _state: &ProposalState) -> ProposalDecision {
+        ProposalDecision::Propose
+    }
+}
+
+/// Proposes once enough message bundles are waiting, or once the oldest has waited for
+/// `max_delay`.
+#[derive(Clone, Copy, Debug)]
+pub struct Batch {
+    /// The number of pending message bundles to wait for.
+    pub min_bundles: usize,
+    /// The longest time to wait for them.
+    pub max_delay: TimeDelta,
+}
+
+impl ProposalPolicy for Batch {
+    fn decide(&self, state: &ProposalState) -> ProposalDecision {
+        let deadline = state.waiting_since.saturating_add(self.max_delay);
+        if state.pending_bundles >= self.min_bundles || state.now >= deadline {
+            ProposalDecision::Propose
+        } else {
+            ProposalDecision::WaitUntil(deadline)
+        }
+    }
+}
+
+/// Collects messages, and proposes `interval` after the first one arrived.
+#[derive(Clone, Copy, Debug)]
+pub struct Timer {
+    /// How long to collect messages for.
+    pub interval: TimeDelta,
+}
+
+impl ProposalPolicy for Timer {
+    fn decide(&self, state: &ProposalState) -> ProposalDecision {
+        let deadline = state.waiting_since.saturating_add(self.interval);
+        if state.now >= deadline {
+            ProposalDecision::Propose
+        } else {
+            ProposalDecision::WaitUntil(deadline)
+        }
+    }
+}
+
+/// Leaves the inbox to the other owners: proposes only as the leader of the current round, or
+/// if messages are still waiting after `delay`.
+#[derive(Clone, Copy, Debug)]
+pub struct FallbackOnly {
+    /// How long to leave the inbox to the other owners.
+    pub delay: TimeDelta,
+}
+
+impl ProposalPolicy for FallbackOnly {
+    fn decide(&self, state: &ProposalState) -> ProposalDecision {
+        if state.is_leader() {
+            return ProposalDecision::Propose;
+        }
+        if state.pending_bundles == 0 {
+            return ProposalDecision::WaitForMessages;
+        }
+        let deadline = state.waiting_since.saturating_add(self.delay);
+        if state.now >= deadline {
+            return ProposalDecision::Propose;
+        }
+        // We may become the leader when the round times out.
+        let wake_up = state
+            .round_timeout
+            .filter(|timeout| *timeout > state.now)
+            .map_or(deadline, |timeout| timeout.min(deadline));
+        ProposalDecision::WaitUntil(wake_up)
+    }
+}
+
+/// The kinds of built-in proposal policies.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    clap::ValueEnum,
+    serde::Serialize,
+    serde::Deserialize,
+    tsify::Tsify,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ProposalPolicyKind {
+    /// Propose as soon as there are messages to process.
+    #[default]
+    Immediate,
+    /// Propose once enough message bundles are waiting, or after the proposal delay.
+    Batch,
+    /// Propose the proposal delay after the first message arrived.
+    Timer,
+    /// Propose only as the round leader, or if messages are still waiting after the
+    /// proposal delay.
+    FallbackOnly,
+}
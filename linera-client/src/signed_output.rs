@@ -0,0 +1,89 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{
+    crypto::{AccountSignature, BcsSignable, CryptoError, CryptoHash},
+    data_types::{BlockHeight, Timestamp},
+    identifiers::{AccountOwner, ChainId},
+};
+use linera_core::data_types::ChainInfo;
+use serde::{Deserialize, Serialize};
+
+/// A command output, together with the state of the chain the client read it from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutputStatement {
+    /// The output, as canonical JSON: without whitespace, and with sorted object keys.
+    pub output: String,
+    /// The chain the output was read from.
+    pub chain_id: ChainId,
+    /// The hash of the chain's latest block known to the client, if any.
+    pub block_hash: Option<CryptoHash>,
+    /// The height of the chain's next block.
+    pub next_block_height: BlockHeight,
+    /// The time at which the client produced the output.
+    pub timestamp: Timestamp,
+}
+
+impl BcsSignable<'_> for OutputStatement {}
+
+impl OutputStatement {
+    /// Creates a statement about the given output, read from the chain described by `info`.
+    pub fn new(output: &serde_json::Value, info: &ChainInfo) -> Self {
+        Self {
+            output: canonical_json(output),
+            chain_id: info.chain_id,
+            block_hash: info.block_hash,
+            next_block_height: info.next_block_height,
+            timestamp: Timestamp::now(),
+        }
+    }
+}
+
+/// Returns `value` as canonical JSON: without whitespace, and with object keys sorted
+/// recursively. Objects keep their insertion order otherwise, since `serde_json` is built
+/// with the `preserve_order` feature.
+pub fn canonical_json(value: &serde_json::Value) -> String {
+    sort_keys(value).to_string()
+}
+
+fn sort_keys(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries = map.iter().collect::<Vec<_>>();
+            entries.sort_unstable_by(|(key1, _), (key2, _)| key1.cmp(key2));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), sort_keys(value)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(values) => {
+            serde_json::Value::Array(values.iter().map(sort_keys).collect())
+        }
+        value => value.clone(),
+    }
+}
+
+/// An output signed by a key of the client's wallet, which third parties can verify.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignedOutput {
+    /// The signed statement.
+    pub statement: OutputStatement,
+    /// The signature of the statement.
+    pub signature: AccountSignature,
+}
+
+impl SignedOutput {
+    /// Checks the signature, and returns the owner of the key that signed the output. Whether
+    /// that owner owns the chain must be checked separately.
+    pub fn verify(&self) -> Result<AccountOwner, CryptoError> {
+        self.signature.verify(&self.statement)?;
+        Ok(self.signature.owner())
+    }
+
+    /// Returns the signed output.
+    pub fn output(&self) -> Result<serde_json::Value, serde_json::Error> {
+        serde_json::from_str(&self.statement.output)
+    }
+}
@@ -0,0 +1,158 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::BTreeMap,
+    str::FromStr as _,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use futures::FutureExt as _;
+use linera_base::{
+    crypto::{AccountSignature, CryptoError, CryptoHash, EvmSignature},
+    identifiers::AccountOwner,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+
+/// How long a signing request waits for a signature by default.
+pub const DEFAULT_SIGNING_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A request to sign a value with the key of an external EVM wallet.
+#[derive(Clone, Debug, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct SigningRequest {
+    /// The ID of the request, to submit the signature with.
+    pub id: u64,
+    /// The owner whose signature is requested.
+    pub owner: AccountOwner,
+    /// The message to sign as an EIP-191 message, e.g. with `personal_sign`: the hash
+    /// of the value, as hex.
+    pub message: String,
+}
+
+/// An error of a [`SigningRelay`].
+#[derive(Debug, thiserror::Error)]
+pub enum SigningRelayError {
+    /// The owner is not an EVM address.
+    #[error("owner {0} is not an EVM address")]
+    NotEvmOwner(AccountOwner),
+    /// No signature was submitted in time.
+    #[error("no signature was submitted for {0} in time")]
+    Timeout(AccountOwner),
+    /// There is no pending request with this ID.
+    #[error("unknown signing request {0}")]
+    UnknownRequest(u64),
+    /// The submitted signature is not valid.
+    #[error("invalid signature: {0}")]
+    InvalidSignature(#[from] CryptoError),
+}
+
+struct PendingRequest {
+    owner: AccountOwner,
+    hash: CryptoHash,
+    sender: oneshot::Sender<AccountSignature>,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_id: u64,
+    pending: BTreeMap<u64, PendingRequest>,
+}
+
+/// Brokers signatures by owners whose keys stay in an external EVM wallet, such as
+/// MetaMask: the client waits for a signature while a web page or another frontend lists
+/// the pending requests, has the wallet sign them, and submits the signatures.
+#[derive(Clone)]
+pub struct SigningRelay {
+    inner: Arc<Mutex<Inner>>,
+    timeout: Duration,
+}
+
+impl Default for SigningRelay {
+    fn default() -> Self {
+        Self::new(DEFAULT_SIGNING_TIMEOUT)
+    }
+}
+
+impl SigningRelay {
+    /// Creates a relay whose requests wait for a signature for at most `timeout`.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            inner: Arc::default(),
+            timeout,
+        }
+    }
+
+    /// Requests a signature of `hash` by `owner`, and waits until it is submitted.
+    pub async fn sign(
+        &self,
+        owner: AccountOwner,
+        hash: CryptoHash,
+    ) -> Result<AccountSignature, SigningRelayError> {
+        if !matches!(owner, AccountOwner::Address20(_)) {
+            return Err(SigningRelayError::NotEvmOwner(owner));
+        }
+        let (sender, receiver) = oneshot::channel();
+        let id = {
+            let mut inner = self.inner.lock().unwrap();
+            let id = inner.next_id;
+            inner.next_id += 1;
+            inner.pending.insert(
+                id,
+                PendingRequest {
+                    owner,
+                    hash,
+                    sender,
+                },
+            );
+            id
+        };
+        tracing::info!(%owner, id, "Waiting for a signature from an external wallet");
+        let result = futures::select! {
+            signature = receiver.fuse() => signature.ok(),
+            () = linera_base::time::timer::sleep(self.timeout).fuse() => None,
+        };
+        self.inner.lock().unwrap().pending.remove(&id);
+        result.ok_or(SigningRelayError::Timeout(owner))
+    }
+
+    /// Returns the requests waiting for a signature.
+    pub fn pending_requests(&self) -> Vec<SigningRequest> {
+        self.inner
+            .lock()
+            .unwrap()
+            .pending
+            .iter()
+            .map(|(id, request)| SigningRequest {
+                id: *id,
+                owner: request.owner,
+                message: format!("0x{}", request.hash),
+            })
+            .collect()
+    }
+
+    /// Submits the hex-encoded signature for the request with the given ID, after checking
+    /// that it was made by the requested owner.
+    pub fn submit(&self, id: u64, signature: &str) -> Result<(), SigningRelayError> {
+        let signature = EvmSignature::from_str(signature)?;
+        let mut inner = self.inner.lock().unwrap();
+        let request = inner
+            .pending
+            .get(&id)
+            .ok_or(SigningRelayError::UnknownRequest(id))?;
+        let AccountOwner::Address20(address) = request.owner else {
+            return Err(SigningRelayError::NotEvmOwner(request.owner));
+        };
+        signature.check_prehash_with_recover(request.hash, address)?;
+        let request = inner
+            .pending
+            .remove(&id)
+            .expect("request should be pending");
+        // The requester may have timed out in the meantime.
+        let _ = request
+            .sender
+            .send(AccountSignature::EvmSecp256k1 { signature, address });
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A local record of the tokens moved by the blocks of the wallet's chains.
+//!
+//! Whenever the wallet is updated with new blocks of one of its chains, the transfers they
+//! make and receive are read from the confirmed blocks in storage and appended to a JSON
+//! Lines file, so that statements can be produced later without contacting the
+//! validators. The fees charged for a block are not part of it: they are only recorded for
+//! the blocks that this client executed recently, and not e.g. for blocks proposed by
+//! other owners of a multi-owner chain.
+
+use std::{
+    io::{BufRead as _, BufReader, Write as _},
+    path::{Path, PathBuf},
+};
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{Amount, BlockHeight, Timestamp},
+    identifiers::{Account, AccountOwner, ChainId},
+};
+use linera_chain::{
+    data_types::{MessageAction, Transaction},
+    types::Block,
+};
+use linera_execution::{Message, MessageKind, Operation, SystemMessage, SystemOperation};
+use serde::{Deserialize, Serialize};
+
+/// The direction of a movement of tokens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FlowKind {
+    /// Tokens sent by an account of the chain.
+    TransferOut,
+    /// Tokens received by an account of the chain.
+    TransferIn,
+    /// Fees charged for the block.
+    Fee,
+}
+
+impl FlowKind {
+    /// Returns the name of the kind, as written in the journal.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FlowKind::TransferOut => "transfer-out",
+            FlowKind::TransferIn => "transfer-in",
+            FlowKind::Fee => "fee",
+        }
+    }
+}
+
+/// A movement of tokens in a block.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    /// The chain of the block.
+    pub chain_id: ChainId,
+    /// The height of the block.
+    pub height: BlockHeight,
+    /// The hash of the block.
+    pub block_hash: CryptoHash,
+    /// The timestamp of the block.
+    pub timestamp: Timestamp,
+    /// Whether the tokens were sent, received or charged as fees.
+    pub kind: FlowKind,
+    /// The account on the chain whose balance changed. For fees, the signer of the block,
+    /// who pays them if its account has enough tokens, or else the chain.
+    pub owner: AccountOwner,
+    /// The recipient of a transfer out, or the sender of a transfer in.
+    pub counterparty: Option<Account>,
+    /// The number of tokens.
+    pub amount: Amount,
+}
+
+/// Returns the movements of tokens of `block`, which was charged the given fees.
+pub fn entries_of_block(block: &Block, block_hash: CryptoHash, fees: Amount) -> Vec<JournalEntry> {
+    let header = &block.header;
+    let entry = |kind, owner, counterparty, amount| JournalEntry {
+        chain_id: header.chain_id,
+        height: header.height,
+        block_hash,
+        timestamp: header.timestamp,
+        kind,
+        owner,
+        counterparty,
+        amount,
+    };
+    let mut entries = Vec::new();
+    for transaction in &block.body.transactions {
+        match transaction {
+            Transaction::ExecuteOperation(Operation::System(operation)) => {
+                if let SystemOperation::Transfer {
+                    owner,
+                    recipient,
+                    amount,
+                } = operation.as_ref()
+                {
+                    entries.push(entry(
+                        FlowKind::TransferOut,
+                        *owner,
+                        Some(*recipient),
+                        *amount,
+                    ));
+                    // Transfers within the chain are credited right away.
+                    if recipient.chain_id == header.chain_id {
+                        let sender = Account::new(header.chain_id, *owner);
+                        entries.push(entry(
+                            FlowKind::TransferIn,
+                            recipient.owner,
+                            Some(sender),
+                            *amount,
+                        ));
+                    }
+                }
+            }
+            Transaction::ExecuteOperation(Operation::User { .. }) => {}
+            Transaction::ReceiveMessages(bundle) => {
+                if bundle.action != MessageAction::Accept {
+                    continue;
+                }
+                for posted in &bundle.bundle.messages {
+                    let Message::System(SystemMessage::Credit {
+                        target,
+                        amount,
+                        source,
+                    }) = &posted.message
+                    else {
+                        continue;
+                    };
+                    // A bouncing credit returns the tokens to their sender.
+                    let (owner, sender) = if posted.kind == MessageKind::Bouncing {
+                        (*source, *target)
+                    } else {
+                        (*target, *source)
+                    };
+                    let counterparty = Account::new(bundle.origin, sender);
+                    entries.push(entry(
+                        FlowKind::TransferIn,
+                        owner,
+                        Some(counterparty),
+                        *amount,
+                    ));
+                }
+            }
+        }
+    }
+    if fees > Amount::ZERO {
+        let payer = header.authenticated_owner.unwrap_or(AccountOwner::CHAIN);
+        entries.push(entry(FlowKind::Fee, payer, None, fees));
+    }
+    entries
+}
+
+/// The journal file of a wallet.
+#[derive(Clone, Debug)]
+pub struct SpendingJournal {
+    path: PathBuf,
+}
+
+impl SpendingJournal {
+    /// Uses the journal at `path`, which is created when the first entry is recorded.
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// Returns the path of the journal file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Appends the given entries to the journal.
+    pub fn append(&self, entries: &[JournalEntry]) -> anyhow::Result<()> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let mut contents = Vec::new();
+        for entry in entries {
+            serde_json::to_writer(&mut contents, entry)?;
+            contents.push(b'\n');
+        }
+        let mut file = fs_err::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(&contents)?;
+        Ok(())
+    }
+
+    /// Returns the entries recorded in the journal, oldest first.
+    pub fn read(&self) -> anyhow::Result<Vec<JournalEntry>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+        let file = fs_err::File::open(&self.path)?;
+        let mut entries = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(&line).map_err(|error| {
+                anyhow::anyhow!(
+                    "invalid entry on line {} of {}: {error}",
+                    index + 1,
+                    self.path.display()
+                )
+            })?;
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
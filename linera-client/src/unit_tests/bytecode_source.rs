@@ -0,0 +1,81 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for [`BytecodeSource`].
+
+use std::path::PathBuf;
+
+use crate::bytecode_source::{BytecodeSource, BytecodeSourceError, OciReference, Sha256Digest};
+
+#[test]
+fn test_parse_bytecode_sources() {
+    let digest = Sha256Digest::of(b"bytecode");
+    let hex = digest
+        .to_string()
+        .strip_prefix("sha256:")
+        .unwrap()
+        .to_owned();
+
+    assert_eq!(
+        "app/contract.wasm".parse::<BytecodeSource>().unwrap(),
+        BytecodeSource::File(PathBuf::from("app/contract.wasm"))
+    );
+    let url = format!("https://example.com/contract.wasm#sha256={hex}");
+    let source = url.parse::<BytecodeSource>().unwrap();
+    assert_eq!(
+        source,
+        BytecodeSource::Url {
+            url: "https://example.com/contract.wasm".to_owned(),
+            digest: Some(digest),
+        }
+    );
+    assert_eq!(source.to_string(), url);
+    let reference = format!("oci://localhost:5000/org/app/contract:1.0@{digest}");
+    let source = reference.parse::<BytecodeSource>().unwrap();
+    assert_eq!(
+        source,
+        BytecodeSource::Oci(OciReference {
+            registry: "localhost:5000".to_owned(),
+            repository: "org/app/contract".to_owned(),
+            tag: Some("1.0".to_owned()),
+            digest: Some(digest),
+        })
+    );
+    assert_eq!(source.to_string(), reference);
+
+    for invalid in [
+        "http://example.com/contract.wasm",
+        "https://example.com/contract.wasm#md5=00",
+        "https://example.com/contract.wasm#sha256=00",
+        "oci://ghcr.io",
+        "oci://ghcr.io/app:",
+    ] {
+        assert!(invalid.parse::<BytecodeSource>().is_err(), "{invalid}");
+    }
+}
+
+#[tokio::test]
+async fn test_pinned_bytecode_is_loaded_from_cache() {
+    let cache = tempfile::tempdir().unwrap();
+    let bytes = b"cached bytecode".to_vec();
+    let digest = Sha256Digest::of(&bytes);
+    let file_name = digest.to_string().replacen(':', "-", 1);
+    std::fs::write(cache.path().join(file_name), &bytes).unwrap();
+
+    // The host does not exist: the bytecode can only come from the cache.
+    let source = BytecodeSource::Url {
+        url: "https://bytecode.invalid/contract.wasm".to_owned(),
+        digest: Some(digest),
+    };
+    let bytecode = source.load_with_cache(Some(cache.path())).await.unwrap();
+    assert_eq!(bytecode.bytes, bytes);
+
+    let source = BytecodeSource::Url {
+        url: "https://bytecode.invalid/contract.wasm".to_owned(),
+        digest: Some(Sha256Digest::of(b"other bytecode")),
+    };
+    assert!(matches!(
+        source.load_with_cache(Some(cache.path())).await,
+        Err(BytecodeSourceError::Http(..))
+    ));
+}
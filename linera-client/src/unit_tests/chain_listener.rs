@@ -9,6 +9,7 @@ use linera_base::{
     data_types::{Amount, ApplicationPermissions, BlockHeight, Epoch, TimeDelta, Timestamp},
     identifiers::{Account, AccountOwner, ChainId},
     ownership::{ChainOwnership, TimeoutConfig},
+    util::future::FutureSyncExt as _,
 };
 use linera_core::{
     client::{chain_client, ChainClient, Client, ListeningMode},
@@ -71,10 +72,7 @@ impl chain_listener::ClientContext for ClientContext {
     ) -> Result<(), Error> {
         let info = client.chain_info().await?;
         let existing_owner = self.wallet().get(info.chain_id).and_then(|c| c.owner);
-        let pending_fast_proposal = client
-            .pending_proposal()
-            .await
-            .filter(|p| p.round.is_some_and(|r| r.is_fast()));
+        let pending_fast_proposal = client.stored_fast_proposal().make_sync().await?;
         self.wallet().insert(
             info.chain_id,
             wallet::Chain {
@@ -367,6 +365,124 @@ async fn test_chain_listener_follow_only() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests that the chain listener ignores notifications from excluded chains.
+/// We set up a listener with two owned chains A and B, excluding A. The sender sends a
+/// message to A first, then to B. Once the listener processes B's inbox, we know it must
+/// have also seen A's notification, but A's inbox should remain unprocessed.
+#[test_log::test(tokio::test)]
+async fn test_chain_listener_excluded_chain() -> anyhow::Result<()> {
+    let signer = InMemorySigner::new(Some(42));
+    let storage_builder = MemoryStorageBuilder::default();
+    let clock = storage_builder.clock().clone();
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer.clone()).await?;
+
+    let sender = builder.add_root_chain(0, Amount::from_tokens(10)).await?;
+    let chain_a = builder.add_root_chain(1, Amount::ZERO).await?;
+    let chain_b = builder.add_root_chain(2, Amount::ZERO).await?;
+    let chain_a_id = chain_a.chain_id();
+    let chain_b_id = chain_b.chain_id();
+    let config = ChainListenerConfig {
+        exclude_chains: vec![chain_a_id],
+        ..ChainListenerConfig::default()
+    };
+
+    let genesis_config = GenesisConfig::new_for_testing(&builder);
+    let admin_chain_id = genesis_config.admin_chain_id();
+    let storage = builder.make_storage().await?;
+
+    let context = ClientContext {
+        client: Arc::new(Client::new(
+            environment::Impl {
+                storage: storage.clone(),
+                network: builder.make_node_provider(),
+                signer,
+                wallet: environment::TestWallet::default(),
+            },
+            admin_chain_id,
+            false,
+            [
+                (chain_a_id, ListeningMode::FullChain),
+                (chain_b_id, ListeningMode::FullChain),
+            ],
+            "Client node with an excluded chain".to_string(),
+            Some(Duration::from_secs(30)),
+            Some(Duration::from_secs(1)),
+            1000,
+            chain_client::Options::test_default(),
+            DEFAULT_BLOCK_CACHE_SIZE,
+            DEFAULT_EXECUTION_STATE_CACHE_SIZE,
+            &linera_core::client::RequestsSchedulerConfig::default(),
+        )),
+    };
+    for chain in [&chain_a, &chain_b] {
+        let info = chain.chain_info().await?;
+        context.wallet().insert(
+            chain.chain_id(),
+            wallet::Chain {
+                owner: chain.preferred_owner(),
+                block_hash: info.block_hash,
+                next_block_height: info.next_block_height,
+                timestamp: clock.current_time(),
+                pending_fast_proposal: None,
+                epoch: Some(info.epoch),
+            },
+        );
+    }
+
+    let context = Arc::new(Mutex::new(context));
+    let cancellation_token = CancellationToken::new();
+    let child_token = cancellation_token.child_token();
+    let chain_listener = ChainListener::new(
+        config,
+        context.clone(),
+        storage.clone(),
+        child_token,
+        tokio::sync::mpsc::unbounded_channel().1,
+        false, // Unit test doesn't need background sync
+    )
+    .run()
+    .await
+    .unwrap();
+
+    let handle = linera_base::Task::spawn(async move { chain_listener.await.unwrap() });
+
+    sender
+        .transfer(AccountOwner::CHAIN, Amount::ONE, Account::chain(chain_a_id))
+        .await?;
+    sender
+        .transfer(AccountOwner::CHAIN, Amount::ONE, Account::chain(chain_b_id))
+        .await?;
+
+    for i in 0.. {
+        tokio::task::yield_now().await;
+
+        chain_b.synchronize_from_validators().await?;
+        let chain_b_info = chain_b.chain_info().await?;
+        if chain_b_info.next_block_height >= BlockHeight::from(1) {
+            break;
+        }
+        if i >= 50 {
+            panic!(
+                "Chain B's inbox was not processed by the listener. Expected height >= 1, got {}",
+                chain_b_info.next_block_height
+            );
+        }
+    }
+
+    chain_a.synchronize_from_validators().await?;
+    let chain_a_info = chain_a.chain_info().await?;
+    assert_eq!(
+        chain_a_info.next_block_height,
+        BlockHeight::ZERO,
+        "Excluded chain A should not have had its inbox processed"
+    );
+
+    cancellation_token.cancel();
+    handle.await;
+
+    Ok(())
+}
+
 /// Tests that the chain listener always listens to the admin chain.
 #[test_log::test(tokio::test)]
 async fn test_chain_listener_admin_chain() -> anyhow::Result<()> {
@@ -1005,3 +1121,27 @@ async fn test_chain_listener_auto_assigns_on_new_chains() -> anyhow::Result<()>
 
     Ok(())
 }
+
+/// Tests that the configuration has the same defaults on the command line, in
+/// configuration files and in code.
+#[test]
+fn test_chain_listener_config_defaults() {
+    #[derive(clap::Parser)]
+    struct Options {
+        #[command(flatten)]
+        config: ChainListenerConfig,
+    }
+
+    let default = ChainListenerConfig::default();
+    assert_eq!(default.proposal_batch_size, 10);
+    assert_eq!(default.proposal_delay_ms, 1_000);
+    let from_args = <Options as clap::Parser>::parse_from(["linera"]).config;
+    let from_json = serde_json::from_str::<ChainListenerConfig>("{}").unwrap();
+    for config in [from_args, from_json] {
+        assert_eq!(config.proposal_batch_size, default.proposal_batch_size);
+        assert_eq!(config.proposal_delay_ms, default.proposal_delay_ms);
+        assert_eq!(config.delay_before_ms, default.delay_before_ms);
+        assert_eq!(config.delay_after_ms, default.delay_after_ms);
+        assert_eq!(config.proposal_policy, default.proposal_policy);
+    }
+}
@@ -0,0 +1,50 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{crypto::CryptoHash, data_types::BlobContent};
+
+use crate::chunked_blob::{split, ChunkManifest, ChunkedBlobError};
+
+#[test]
+fn test_split_and_reassemble() -> anyhow::Result<()> {
+    let bytes = (0..=255u8).cycle().take(1000).collect::<Vec<_>>();
+    let (chunks, manifest) = split(&bytes, 300);
+    assert_eq!(
+        chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+        [300, 300, 300, 100]
+    );
+    assert_eq!(manifest.size, 1000);
+    assert_eq!(
+        manifest.content_hash,
+        CryptoHash::new(&BlobContent::new_data(bytes.clone()))
+    );
+    assert_eq!(
+        manifest.chunks[3],
+        CryptoHash::new(&BlobContent::new_data(chunks[3].clone()))
+    );
+
+    let manifest = ChunkManifest::from_bytes(&manifest.to_bytes())?;
+    assert_eq!(manifest.reassemble(chunks.clone())?, bytes);
+
+    let mut swapped = chunks;
+    swapped.swap(0, 1);
+    assert!(matches!(
+        manifest.reassemble(swapped),
+        Err(ChunkedBlobError::Mismatch { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_other_blobs_are_not_manifests() {
+    assert!(matches!(
+        ChunkManifest::from_bytes(b"some data"),
+        Err(ChunkedBlobError::InvalidManifest(_))
+    ));
+    let (_, mut manifest) = split(b"some data", 4);
+    manifest.format = "something else".to_string();
+    assert!(matches!(
+        ChunkManifest::from_bytes(&manifest.to_bytes()),
+        Err(ChunkedBlobError::InvalidManifest(_))
+    ));
+}
@@ -1,7 +1,9 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-//! Tests for [`ClientContext::update_wallet_from_client`].
+//! Tests for [`ClientContext::update_wallet_from_client`],
+//! [`ClientContext::resolve_required_applications`], `ClientContext::publish_module` and
+//! [`ValidatorHealth::score`].
 
 use std::{
     collections::{BTreeMap, BTreeSet},
@@ -10,9 +12,9 @@ use std::{
 };
 
 use linera_base::{
-    crypto::InMemorySigner,
+    crypto::{CryptoHash, InMemorySigner},
     data_types::{Amount, Round, TimeDelta},
-    identifiers::{AccountOwner, ChainId},
+    identifiers::{AccountOwner, ApplicationId, ChainId},
     ownership::{ChainOwnership, TimeoutConfig},
 };
 use linera_core::{
@@ -20,11 +22,15 @@ use linera_core::{
     environment,
     join_set_ext::JoinSet,
     test_utils::{FaultType, MemoryStorageBuilder, TestBuilder},
+    wallet::StoredProposal,
     worker::{DEFAULT_BLOCK_CACHE_SIZE, DEFAULT_EXECUTION_STATE_CACHE_SIZE},
 };
-use linera_rpc::node_provider::DEFAULT_MAX_BACKOFF;
+use linera_rpc::{node_provider::DEFAULT_MAX_BACKOFF, EndpointSelection};
 
-use crate::{client_context::ClientContext, config::GenesisConfig};
+use crate::{
+    client_context::{ClientContext, ValidatorHealth},
+    config::GenesisConfig,
+};
 
 /// Builds a production [`ClientContext`] with a fresh in-memory wallet, sharing validators
 /// with the given [`TestBuilder`].
@@ -63,9 +69,13 @@ async fn make_context(
         retry_delay: Duration::from_secs(1),
         max_retries: 10,
         max_backoff: DEFAULT_MAX_BACKOFF,
+        max_concurrent_requests: None,
+        endpoint_selection: EndpointSelection::default(),
         chain_listeners: JoinSet::default(),
         default_chain: None,
         client_metrics: None,
+        #[cfg(feature = "fs")]
+        spending_journal: None,
     })
 }
 
@@ -114,6 +124,8 @@ async fn test_wallet_persists_fast_pending_proposal() -> anyhow::Result<()> {
         .expect("wallet missing chain entry")
         .pending_fast_proposal
         .expect("wallet missing fast pending proposal");
+    assert!(matches!(stored, StoredProposal::Blob { .. }));
+    let stored = stored.load(client.storage_client()).await?;
     assert_eq!(stored.round, Some(Round::Fast));
     Ok(())
 }
@@ -157,3 +169,135 @@ async fn test_wallet_drops_non_fast_pending_proposal() -> anyhow::Result<()> {
     assert!(stored.pending_fast_proposal.is_none());
     Ok(())
 }
+
+/// Creating an application with an unknown dependency must fail before anything is executed.
+#[test_log::test(tokio::test)]
+async fn test_resolve_missing_required_application() -> anyhow::Result<()> {
+    let signer = InMemorySigner::new(None);
+    let mut builder =
+        TestBuilder::new(MemoryStorageBuilder::default(), 4, 0, signer.clone()).await?;
+    let client = builder.add_root_chain(1, Amount::from_tokens(10)).await?;
+    let chain_id = client.chain_id();
+    let context = make_context(&mut builder, signer, chain_id).await?;
+
+    assert!(context
+        .resolve_required_applications(&client, &[])
+        .await?
+        .is_empty());
+    let missing = ApplicationId::new(CryptoHash::test_hash("missing application"));
+    let error = context
+        .resolve_required_applications(&client, &[missing])
+        .await
+        .expect_err("resolving an unknown application should fail");
+    let message = error.to_string();
+    assert!(message.contains(&missing.to_string()), "{message}");
+    assert!(
+        message.contains("required by the new application"),
+        "{message}"
+    );
+    Ok(())
+}
+
+/// Publishing a module again reuses the published one, and failing to create an application
+/// from it names the module to reuse.
+#[cfg(feature = "fs")]
+#[test_log::test(tokio::test)]
+async fn test_publish_module_reuses_published_module() -> anyhow::Result<()> {
+    use linera_base::{
+        data_types::{Epoch, Timestamp},
+        vm::VmRuntime,
+    };
+
+    use crate::{bytecode_source::BytecodeSource, chain_listener::ClientContext as _};
+
+    let signer = InMemorySigner::new(None);
+    let mut builder =
+        TestBuilder::new(MemoryStorageBuilder::default(), 4, 0, signer.clone()).await?;
+    let client = builder.add_root_chain(1, Amount::from_tokens(10)).await?;
+    let chain_id = client.chain_id();
+    let owner = client.identity().await?;
+    let mut context = make_context(&mut builder, signer, chain_id).await?;
+    context
+        .update_wallet_for_new_chain(chain_id, Some(owner), Timestamp::from(0), Epoch::ZERO)
+        .await?;
+    let chain_client = context.make_chain_client(chain_id).await?;
+
+    // The bytecode is not valid Wasm: publishing does not check it, instantiating does.
+    let directory = tempfile::tempdir()?;
+    let contract = directory.path().join("contract.wasm");
+    let service = directory.path().join("service.wasm");
+    std::fs::write(&contract, b"not a contract")?;
+    std::fs::write(&service, b"not a service")?;
+    let (contract, service) = (
+        BytecodeSource::from(contract),
+        BytecodeSource::from(service),
+    );
+
+    let module_id = context
+        .publish_module(
+            &chain_client,
+            contract.clone(),
+            service.clone(),
+            VmRuntime::Wasm,
+            None,
+        )
+        .await?;
+    assert!(context.is_module_published(module_id).await?);
+    let next_block_height = chain_client.chain_info().await?.next_block_height;
+    let republished_module_id = context
+        .publish_module(&chain_client, contract, service, VmRuntime::Wasm, None)
+        .await?;
+    assert_eq!(republished_module_id, module_id);
+    assert_eq!(
+        chain_client.chain_info().await?.next_block_height,
+        next_block_height,
+        "the module should not be published twice"
+    );
+
+    let error = context
+        .create_application_from_module(&chain_client, module_id, vec![], vec![], vec![])
+        .await
+        .expect_err("instantiating invalid bytecode should fail");
+    let message = error.to_string();
+    assert!(message.contains(&module_id.to_string()), "{message}");
+    assert!(
+        message.contains("retrying the command reuses the module"),
+        "{message}"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_validator_health_score() {
+    let healthy = ValidatorHealth {
+        latency: Duration::from_millis(50),
+        height_lag: Some(0),
+        version_skew: Some(false),
+        error_count: 0,
+    };
+    assert_eq!(healthy.score(), 100);
+
+    let slow_and_behind = ValidatorHealth {
+        latency: Duration::from_secs(1),
+        height_lag: Some(3),
+        version_skew: Some(true),
+        ..healthy.clone()
+    };
+    assert_eq!(slow_and_behind.score(), 100 - 10 - 6 - 10);
+
+    // The lag penalty is capped.
+    let far_behind = ValidatorHealth {
+        height_lag: Some(1000),
+        error_count: 1,
+        ..healthy.clone()
+    };
+    assert_eq!(far_behind.score(), 100 - 30 - 25);
+
+    let unreachable = ValidatorHealth {
+        height_lag: None,
+        version_skew: None,
+        error_count: 3,
+        ..healthy
+    };
+    assert_eq!(unreachable.score(), 0);
+}
@@ -1,5 +1,15 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "fs")]
+mod bytecode_source;
 mod chain_listener;
+mod chunked_blob;
 mod client_context;
+mod module_attestation;
+mod ownership_proof;
+mod proposal_policy;
+mod signed_output;
+mod signing_relay;
+#[cfg(feature = "fs")]
+mod spending_journal;
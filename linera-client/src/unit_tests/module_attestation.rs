@@ -0,0 +1,63 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for [`ModuleAttestation`].
+
+use linera_base::{
+    crypto::{AccountSecretKey, CryptoHash},
+    data_types::Blob,
+    identifiers::ModuleId,
+    vm::VmRuntime,
+};
+
+use crate::module_attestation::{AttestationError, BuildInfo, ModuleAttestation, ModuleProvenance};
+
+fn test_module_id(name: &str) -> ModuleId {
+    ModuleId::new(
+        CryptoHash::test_hash(format!("{name} contract")),
+        CryptoHash::test_hash(format!("{name} service")),
+        VmRuntime::Wasm,
+    )
+}
+
+#[test]
+fn test_module_attestation_verification() {
+    let secret = AccountSecretKey::generate();
+    let module_id = test_module_id("counter");
+    let provenance = ModuleProvenance::new(
+        module_id,
+        BuildInfo {
+            builder_id: "https://github.com/linera-io/linera-protocol/actions".to_string(),
+            source_repository: Some("https://github.com/linera-io/linera-protocol".to_string()),
+            source_commit: "0123456789abcdef".to_string(),
+            toolchain: "rustc 1.86.0".to_string(),
+        },
+    );
+    let signature = secret.sign(&provenance);
+    let attestation = ModuleAttestation {
+        provenance,
+        signature,
+    };
+    assert_eq!(
+        attestation.verify(module_id).unwrap(),
+        secret.public().into()
+    );
+
+    let parsed = ModuleAttestation::from_blob(&attestation.to_blob()).unwrap();
+    assert_eq!(parsed.provenance, attestation.provenance);
+    assert!(
+        ModuleAttestation::from_blob(&Blob::new_data(b"not an attestation".to_vec())).is_none()
+    );
+
+    assert!(matches!(
+        attestation.verify(test_module_id("other")),
+        Err(AttestationError::WrongModule { .. })
+    ));
+
+    let mut tampered = attestation;
+    tampered.provenance.build.source_commit = "fedcba9876543210".to_string();
+    assert!(matches!(
+        tampered.verify(module_id),
+        Err(AttestationError::InvalidSignature(_))
+    ));
+}
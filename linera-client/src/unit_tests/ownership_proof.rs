@@ -0,0 +1,33 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for [`OwnershipChallenge`].
+
+use linera_base::{
+    crypto::{AccountSecretKey, CryptoHash, EvmSecretKey},
+    identifiers::{AccountOwner, ChainId},
+};
+
+use crate::ownership_proof::OwnershipChallenge;
+
+#[test]
+fn test_ownership_challenge_verification() {
+    let chain_id = ChainId(CryptoHash::test_hash("chain"));
+    for secret in [
+        AccountSecretKey::generate(),
+        AccountSecretKey::EvmSecp256k1(EvmSecretKey::generate()),
+    ] {
+        let owner = AccountOwner::from(secret.public());
+        let challenge = OwnershipChallenge::new(owner, chain_id);
+        let signature = secret.sign(&challenge);
+        challenge.verify(&signature).unwrap();
+
+        // The signature of another challenge is rejected.
+        let other = OwnershipChallenge::new(owner, chain_id);
+        assert!(other.verify(&signature).is_err());
+
+        // A valid signature by another key is rejected.
+        let impostor = AccountSecretKey::generate();
+        assert!(challenge.verify(&impostor.sign(&challenge)).is_err());
+    }
+}
@@ -0,0 +1,90 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for the built-in [`ProposalPolicy`] implementations.
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{Round, TimeDelta, Timestamp},
+    identifiers::AccountOwner,
+};
+
+use crate::proposal_policy::{
+    Batch, FallbackOnly, Immediate, ProposalDecision, ProposalPolicy, ProposalState, Timer,
+};
+
+fn state(now: u64, pending_bundles: usize) -> ProposalState {
+    ProposalState {
+        now: Timestamp::from(now),
+        waiting_since: Timestamp::from(1_000),
+        pending_bundles,
+        owner: AccountOwner::from(CryptoHash::test_hash("owner")),
+        current_round: Round::MultiLeader(0),
+        leader: None,
+        round_timeout: None,
+    }
+}
+
+#[test]
+fn test_immediate_and_timer_policies() {
+    assert_eq!(
+        Immediate.decide(&state(1_000, 0)),
+        ProposalDecision::Propose
+    );
+
+    let timer = Timer {
+        interval: TimeDelta::from_micros(500),
+    };
+    assert_eq!(
+        timer.decide(&state(1_200, 3)),
+        ProposalDecision::WaitUntil(Timestamp::from(1_500))
+    );
+    assert_eq!(timer.decide(&state(1_500, 3)), ProposalDecision::Propose);
+}
+
+#[test]
+fn test_batch_policy() {
+    let batch = Batch {
+        min_bundles: 3,
+        max_delay: TimeDelta::from_micros(500),
+    };
+    assert_eq!(
+        batch.decide(&state(1_200, 2)),
+        ProposalDecision::WaitUntil(Timestamp::from(1_500))
+    );
+    assert_eq!(batch.decide(&state(1_200, 3)), ProposalDecision::Propose);
+    assert_eq!(batch.decide(&state(1_500, 1)), ProposalDecision::Propose);
+}
+
+#[test]
+fn test_fallback_only_policy() {
+    let fallback = FallbackOnly {
+        delay: TimeDelta::from_micros(500),
+    };
+    // Another owner may process the messages first.
+    assert_eq!(
+        fallback.decide(&state(1_200, 1)),
+        ProposalDecision::WaitUntil(Timestamp::from(1_500))
+    );
+    // They did.
+    assert_eq!(
+        fallback.decide(&state(1_500, 0)),
+        ProposalDecision::WaitForMessages
+    );
+    // They did not.
+    assert_eq!(fallback.decide(&state(1_500, 1)), ProposalDecision::Propose);
+
+    // We may become the leader when the round times out.
+    let mut not_leader = state(1_200, 1);
+    not_leader.current_round = Round::SingleLeader(0);
+    not_leader.leader = Some(AccountOwner::from(CryptoHash::test_hash("other")));
+    not_leader.round_timeout = Some(Timestamp::from(1_300));
+    assert_eq!(
+        fallback.decide(&not_leader),
+        ProposalDecision::WaitUntil(Timestamp::from(1_300))
+    );
+
+    let mut leader = not_leader;
+    leader.leader = Some(leader.owner);
+    assert_eq!(fallback.decide(&leader), ProposalDecision::Propose);
+}
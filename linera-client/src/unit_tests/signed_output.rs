@@ -0,0 +1,48 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for [`SignedOutput`].
+
+use linera_base::{
+    crypto::{AccountSecretKey, CryptoHash},
+    data_types::{BlockHeight, Timestamp},
+    identifiers::ChainId,
+};
+
+use crate::signed_output::{canonical_json, OutputStatement, SignedOutput};
+
+#[test]
+fn test_signed_output_verification() {
+    let secret = AccountSecretKey::generate();
+    let output = serde_json::json!({ "balance": "10.", "account": "0x01" });
+    let statement = OutputStatement {
+        output: canonical_json(&output),
+        chain_id: ChainId(CryptoHash::test_hash("chain")),
+        block_hash: Some(CryptoHash::test_hash("block")),
+        next_block_height: BlockHeight(3),
+        timestamp: Timestamp::from(1_000),
+    };
+    assert_eq!(statement.output, r#"{"account":"0x01","balance":"10."}"#);
+    let signature = secret.sign(&statement);
+    let mut signed = SignedOutput {
+        statement,
+        signature,
+    };
+    assert_eq!(signed.verify().unwrap(), secret.public().into());
+    assert_eq!(signed.output().unwrap(), output);
+
+    signed.statement.next_block_height = BlockHeight(4);
+    assert!(signed.verify().is_err());
+}
+
+#[test]
+fn test_canonical_json_sorts_keys() {
+    let output = serde_json::json!({
+        "zeta": [{ "b": 2, "a": 1 }],
+        "alpha": { "y": null, "x": "value" },
+    });
+    assert_eq!(
+        canonical_json(&output),
+        r#"{"alpha":{"x":"value","y":null},"zeta":[{"a":1,"b":2}]}"#
+    );
+}
@@ -0,0 +1,73 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests for [`SigningRelay`].
+
+use std::time::Duration;
+
+use linera_base::{
+    crypto::{AccountSignature, CryptoHash, EvmSecretKey, EvmSignature, TestString},
+    identifiers::AccountOwner,
+};
+
+use crate::signing_relay::{SigningRelay, SigningRelayError};
+
+/// Signs the message of a request as an external wallet would, with `personal_sign`.
+fn personal_sign(secret: &EvmSecretKey, message: &str) -> String {
+    let hash = message
+        .strip_prefix("0x")
+        .unwrap()
+        .parse::<CryptoHash>()
+        .unwrap();
+    let signature = EvmSignature::new(hash, secret);
+    format!("0x{}", linera_base::hex::encode(signature.as_bytes()))
+}
+
+#[tokio::test]
+async fn test_signing_relay() {
+    let secret = EvmSecretKey::generate();
+    let owner = AccountOwner::Address20(secret.address().0 .0);
+    let relay = SigningRelay::default();
+    let value = TestString::new("block proposal");
+    let hash = CryptoHash::new(&value);
+
+    let signing = tokio::spawn({
+        let relay = relay.clone();
+        async move { relay.sign(owner, hash).await }
+    });
+    let request = loop {
+        if let Some(request) = relay.pending_requests().pop() {
+            break request;
+        }
+        tokio::task::yield_now().await;
+    };
+    assert_eq!(request.owner, owner);
+
+    // A signature by another key is rejected.
+    let other_secret = EvmSecretKey::generate();
+    let wrong_signature = personal_sign(&other_secret, &request.message);
+    assert!(matches!(
+        relay.submit(request.id, &wrong_signature),
+        Err(SigningRelayError::InvalidSignature(_))
+    ));
+
+    relay
+        .submit(request.id, &personal_sign(&secret, &request.message))
+        .unwrap();
+    let signature = signing.await.unwrap().unwrap();
+    assert!(matches!(signature, AccountSignature::EvmSecp256k1 { .. }));
+    signature.verify(&value).unwrap();
+    assert!(relay.pending_requests().is_empty());
+}
+
+#[tokio::test]
+async fn test_signing_relay_timeout() {
+    let owner = AccountOwner::Address20([1; 20]);
+    let relay = SigningRelay::new(Duration::from_millis(10));
+    let hash = CryptoHash::new(&TestString::new("block proposal"));
+    assert!(matches!(
+        relay.sign(owner, hash).await,
+        Err(SigningRelayError::Timeout(_))
+    ));
+    assert!(relay.pending_requests().is_empty());
+}
@@ -0,0 +1,130 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{Amount, BlockHeight, Timestamp},
+    identifiers::{Account, AccountOwner, ChainId},
+};
+use linera_chain::{
+    data_types::{BlockExecutionOutcome, IncomingBundle, MessageAction, MessageBundle},
+    test::{make_first_block, BlockTestExt as _, MessageTestExt as _},
+};
+use linera_execution::{MessageKind, SystemMessage};
+
+use crate::spending_journal::{entries_of_block, FlowKind, JournalEntry, SpendingJournal};
+
+fn credit_bundle(origin: ChainId, target: AccountOwner, kind: MessageKind) -> IncomingBundle {
+    IncomingBundle {
+        origin,
+        bundle: MessageBundle {
+            height: BlockHeight(3),
+            timestamp: Timestamp::from(0),
+            certificate_hash: CryptoHash::test_hash("sender block"),
+            transaction_index: 0,
+            messages: vec![SystemMessage::Credit {
+                target,
+                amount: Amount::from_tokens(2),
+                source: AccountOwner::CHAIN,
+            }
+            .to_posted(kind)],
+        },
+        action: MessageAction::Accept,
+    }
+}
+
+#[test]
+fn test_entries_of_block() {
+    let chain_id = ChainId(CryptoHash::test_hash("chain"));
+    let other_chain_id = ChainId(CryptoHash::test_hash("other chain"));
+    let owner = AccountOwner::from(CryptoHash::test_hash("owner"));
+    let mut rejected = credit_bundle(other_chain_id, owner, MessageKind::Tracked);
+    rejected.action = MessageAction::Reject;
+    let block = BlockExecutionOutcome::default().with(
+        make_first_block(chain_id)
+            .with_authenticated_owner(Some(owner))
+            .with_incoming_bundle(credit_bundle(other_chain_id, owner, MessageKind::Tracked))
+            .with_incoming_bundle(rejected)
+            .with_transfer(owner, Account::chain(other_chain_id), Amount::ONE)
+            .with_transfer(owner, Account::chain(chain_id), Amount::ONE),
+    );
+    let block_hash = block.hash();
+    let entries = entries_of_block(&block, block_hash, Amount::from_millis(5));
+    let flows = entries
+        .iter()
+        .map(|entry| (entry.kind, entry.owner, entry.counterparty, entry.amount))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        flows,
+        [
+            (
+                FlowKind::TransferIn,
+                owner,
+                Some(Account::chain(other_chain_id)),
+                Amount::from_tokens(2)
+            ),
+            (
+                FlowKind::TransferOut,
+                owner,
+                Some(Account::chain(other_chain_id)),
+                Amount::ONE
+            ),
+            (
+                FlowKind::TransferOut,
+                owner,
+                Some(Account::chain(chain_id)),
+                Amount::ONE
+            ),
+            (
+                FlowKind::TransferIn,
+                AccountOwner::CHAIN,
+                Some(Account::new(chain_id, owner)),
+                Amount::ONE
+            ),
+            (FlowKind::Fee, owner, None, Amount::from_millis(5)),
+        ]
+    );
+    assert!(entries
+        .iter()
+        .all(|entry| entry.chain_id == chain_id && entry.block_hash == block_hash));
+}
+
+#[test]
+fn test_bouncing_credit_returns_tokens_to_sender() {
+    let chain_id = ChainId(CryptoHash::test_hash("chain"));
+    let other_chain_id = ChainId(CryptoHash::test_hash("other chain"));
+    let recipient = AccountOwner::from(CryptoHash::test_hash("recipient"));
+    let block =
+        BlockExecutionOutcome::default().with(make_first_block(chain_id).with_incoming_bundle(
+            credit_bundle(other_chain_id, recipient, MessageKind::Bouncing),
+        ));
+    let entries = entries_of_block(&block, block.hash(), Amount::ZERO);
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].owner, AccountOwner::CHAIN);
+    assert_eq!(
+        entries[0].counterparty,
+        Some(Account::new(other_chain_id, recipient))
+    );
+}
+
+#[test]
+fn test_journal_round_trip() -> anyhow::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let journal = SpendingJournal::new(dir.path().join("wallet.journal.jsonl"));
+    assert!(journal.read()?.is_empty());
+    let entry = |height| JournalEntry {
+        chain_id: ChainId(CryptoHash::test_hash("chain")),
+        height: BlockHeight(height),
+        block_hash: CryptoHash::test_hash(format!("block {height}")),
+        timestamp: Timestamp::from(height * 1_000_000),
+        kind: FlowKind::Fee,
+        owner: AccountOwner::CHAIN,
+        counterparty: None,
+        amount: Amount::from_millis(u128::from(height)),
+    };
+    journal.append(&[entry(1), entry(2)])?;
+    journal.append(&[])?;
+    journal.append(&[entry(3)])?;
+    assert_eq!(journal.read()?, [entry(1), entry(2), entry(3)]);
+    Ok(())
+}
@@ -5,7 +5,7 @@ use std::{collections::HashSet, num::ParseIntError, str::FromStr};
 
 use futures::future;
 use linera_base::{
-    crypto::CryptoError,
+    crypto::{CryptoError, ValidatorPublicKey},
     data_types::{TimeDelta, Timestamp},
     identifiers::{ApplicationId, ChainId, GenericApplicationId},
     time::Duration,
@@ -55,6 +55,14 @@ pub fn parse_chain_set(s: &str) -> Result<HashSet<ChainId>, CryptoError> {
     }
 }
 
+/// Parses a `PUBLIC_KEY=ADDRESS` pair, pinning the network address of a validator.
+pub fn parse_validator_address_pin(s: &str) -> anyhow::Result<(ValidatorPublicKey, String)> {
+    let Some((public_key, address)) = s.split_once('=') else {
+        anyhow::bail!("expected `PUBLIC_KEY=ADDRESS`, got {s:?}");
+    };
+    Ok((public_key.trim().parse()?, address.trim().to_string()))
+}
+
 /// Parses a comma-separated list of application IDs into a set.
 pub fn parse_app_set(s: &str) -> anyhow::Result<HashSet<GenericApplicationId>> {
     s.trim()
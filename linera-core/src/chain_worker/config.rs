@@ -5,9 +5,13 @@
 
 use std::{collections::HashSet, sync::Arc};
 
-use linera_base::{crypto::ValidatorSecretKey, identifiers::ChainId, time::Duration};
+use linera_base::{
+    crypto::{ValidatorSecretKey, ValidatorSigner},
+    identifiers::ChainId,
+    time::Duration,
+};
 
-use crate::CHAIN_INFO_MAX_RECEIVED_LOG_ENTRIES;
+use crate::{data_types::SignedValidatorAddress, CHAIN_INFO_MAX_RECEIVED_LOG_ENTRIES};
 
 /// Configuration parameters for the chain worker and its owning
 /// [`WorkerState`][`crate::worker::WorkerState`].
@@ -18,6 +22,9 @@ pub struct ChainWorkerConfig {
     /// The signature key pair of the validator. The key may be missing for replicas
     /// without voting rights (possibly with a partial view of chains).
     pub key_pair: Option<Arc<ValidatorSecretKey>>,
+    /// A signer holding the validator's key in a separate process, used instead of
+    /// `key_pair` when the key must not be present in the worker's memory.
+    pub remote_signer: Option<Arc<dyn ValidatorSigner>>,
     /// Whether inactive chains are allowed in storage.
     pub allow_inactive_chains: bool,
     /// Whether the user application services should be long-lived.
@@ -45,6 +52,15 @@ pub struct ChainWorkerConfig {
     /// the cost of more lock acquisitions; larger values amortize lock and storage
     /// overhead better.
     pub cross_chain_batch_size_limit: usize,
+    /// Maximum number of message bundles from the same origin waiting in an inbox.
+    /// Cross-chain updates that would exceed it are refused, unless the inbox is empty,
+    /// so that the sender retries later. Defaults to `usize::MAX` (no limit).
+    pub max_pending_bundles_per_origin: usize,
+    /// Whether to send `NotifyRecipient` cross-chain requests, with the hashes of the
+    /// blocks, instead of `UpdateRecipient` requests with their messages. The recipient
+    /// stores these references in its inbox and reads the messages from the blocks in
+    /// storage once they are consumed.
+    pub pull_cross_chain_messages: bool,
     /// Whether to attempt recovery via `RevertConfirm` when an inbox gap is detected.
     pub allow_revert_confirm: bool,
     /// If set, reset the chain state and re-execute all blocks when the chain
@@ -56,6 +72,12 @@ pub struct ChainWorkerConfig {
     /// mechanisms. If `None`, every chain is eligible (subject to the
     /// respective feature flag). If `Some`, only chains in the set are.
     pub recovery_whitelist: Option<HashSet<ChainId>>,
+    /// If set, closed ephemeral chains whose latest block is older than this are
+    /// pruned: their application state, inboxes and received log are deleted.
+    pub ephemeral_chain_retention: Option<Duration>,
+    /// The validator's latest signed network address, if it changed since the current
+    /// committee was created. It is included in the answers to chain info queries.
+    pub address_update: Option<SignedValidatorAddress>,
 }
 
 impl ChainWorkerConfig {
@@ -71,6 +93,15 @@ impl ChainWorkerConfig {
         self.key_pair.as_ref().map(Arc::as_ref)
     }
 
+    /// Gets the signer for the validator's votes and responses: the local key pair if
+    /// available, otherwise the remote signer, if any.
+    pub fn signer(&self) -> Option<&dyn ValidatorSigner> {
+        match &self.key_pair {
+            Some(key_pair) => Some(&**key_pair),
+            None => self.remote_signer.as_deref(),
+        }
+    }
+
     /// Returns whether `chain_id` is allowed to attempt the `RevertConfirm` and
     /// corrupted-state-reset recovery mechanisms.
     pub(crate) fn recovery_allowed_for(&self, chain_id: &ChainId) -> bool {
@@ -85,6 +116,7 @@ impl Default for ChainWorkerConfig {
         Self {
             nickname: String::new(),
             key_pair: None,
+            remote_signer: None,
             allow_inactive_chains: false,
             long_lived_services: false,
             block_time_grace_period: Default::default(),
@@ -95,9 +127,13 @@ impl Default for ChainWorkerConfig {
             execution_state_cache_size: 10_000,
             cross_chain_message_chunk_limit: usize::MAX,
             cross_chain_batch_size_limit: 1000,
+            max_pending_bundles_per_origin: usize::MAX,
+            pull_cross_chain_messages: false,
             allow_revert_confirm: false,
             reset_on_corrupted_chain_state: None,
             recovery_whitelist: None,
+            ephemeral_chain_retention: None,
+            address_update: None,
         }
     }
 }
@@ -148,10 +148,13 @@ pub(crate) fn create_chain_worker<S: Storage + Clone + 'static>(
 }
 
 /// Acquires a read lock, updating the last-access timestamp.
+///
+/// Fails if the deadline of the request passed while waiting for the lock.
 pub(crate) async fn read_lock<S: Storage + Clone + 'static>(
     state: &Arc<RwLock<ChainWorkerState<S>>>,
 ) -> Result<OwnedRwLockReadGuard<ChainWorkerState<S>>, WorkerError> {
     let guard = state.clone().read_owned().await;
+    linera_base::deadline::check("chain_worker_read_lock")?;
     guard.check_not_poisoned()?;
     guard.touch();
     Ok(guard)
@@ -184,11 +187,13 @@ pub(crate) async fn read_lock_initialized<S: Storage + Clone + 'static>(
 /// Acquires a write lock, updating the last-access timestamp.
 ///
 /// Returns a [`RollbackGuard`] that automatically rolls back uncommitted changes
-/// when dropped, ensuring cancellation safety.
+/// when dropped, ensuring cancellation safety. Fails if the deadline of the request
+/// passed while waiting for the lock.
 pub(crate) async fn write_lock<S: Storage + Clone + 'static>(
     state: &Arc<RwLock<ChainWorkerState<S>>>,
 ) -> Result<RollbackGuard<S>, WorkerError> {
     let guard = RollbackGuard(state.clone().write_owned().await);
+    linera_base::deadline::check("chain_worker_write_lock")?;
     guard.check_not_poisoned()?;
     guard.touch();
     Ok(guard)
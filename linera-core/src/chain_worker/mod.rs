@@ -9,5 +9,7 @@ pub(crate) mod handle;
 pub(crate) mod state;
 
 pub(super) use self::delivery_notifier::DeliveryNotifier;
-pub(crate) use self::state::{BlockOutcome, CrossChainUpdateResult, EventSubscriptionsResult};
+pub(crate) use self::state::{
+    BlockOutcome, CrossChainUpdateResult, EventSubscriptionsResult, PruningOutcome,
+};
 pub use self::{config::ChainWorkerConfig, state::ProcessConfirmedBlockMode};
@@ -16,7 +16,7 @@ use linera_base::{
     crypto::{CryptoHash, ValidatorPublicKey},
     data_types::{
         ApplicationDescription, ArithmeticError, Blob, BlockHeight, Epoch, OracleResponse, Round,
-        Timestamp,
+        TimeDelta, Timestamp,
     },
     ensure,
     hashed::Hashed,
@@ -55,7 +55,7 @@ use crate::{
     chain_worker::{handle::AtomicTimestamp, ChainWorkerConfig, DeliveryNotifier},
     client::{ChainModes, ListeningMode},
     data_types::{ChainInfo, ChainInfoQuery, ChainInfoResponse, CrossChainRequest},
-    worker::{BatchRequest, NetworkActions, Notification, Reason, WorkerError},
+    worker::{BatchRequest, CrossChainUpdate, NetworkActions, Notification, Reason, WorkerError},
 };
 
 /// Type alias for event subscriptions result.
@@ -88,6 +88,13 @@ mod metrics {
         )
     });
 
+    pub static FUTURE_EPOCH_BLOCKS_REJECTED_TOTAL: LazyLock<IntCounter> = LazyLock::new(|| {
+        register_int_counter(
+            "future_epoch_blocks_rejected_total",
+            "Total number of blocks rejected because their epoch is ahead of their chain's",
+        )
+    });
+
     pub static BLOCK_PROPOSALS_RECEIVED_TOTAL: LazyLock<IntCounter> = LazyLock::new(|| {
         register_int_counter(
             "block_proposals_received_total",
@@ -102,6 +109,14 @@ mod metrics {
             &["error_type"],
         )
     });
+
+    pub static CROSS_CHAIN_UPDATES_REFUSED_TOTAL: LazyLock<IntCounter> = LazyLock::new(|| {
+        register_int_counter(
+            "cross_chain_updates_refused_total",
+            "Total number of cross-chain updates refused because the inbox of their origin \
+             holds too many pending bundles",
+        )
+    });
 }
 
 /// The state of the chain worker.
@@ -147,6 +162,21 @@ pub(crate) enum CrossChainUpdateResult {
         origin: ChainId,
         retransmit_from: BlockHeight,
     },
+    /// The inbox for messages from the origin already holds `pending` bundles, and
+    /// accepting the update would exceed the configured limit. The sender should retry
+    /// later.
+    InboxFull { pending: usize, limit: usize },
+}
+
+/// The result of trying to prune a closed ephemeral chain.
+pub(crate) enum PruningOutcome {
+    /// The chain state was pruned.
+    Pruned,
+    /// The chain can be pruned later, once its retention period has passed and its
+    /// outgoing messages have been delivered.
+    Pending,
+    /// The chain is not a closed ephemeral chain, or it was already pruned.
+    NotPrunable,
 }
 
 /// Whether the block was processed or skipped. Used for metrics.
@@ -590,8 +620,43 @@ where
         // Make the outbox index authoritative for the current tracked set first, so it already
         // holds only `is_full` targets and needs no read-time filtering.
         let tracked = self.reconcile_tracked_outboxes().await?;
-        self.build_network_actions(old_round, tracked.as_deref().map(|h| h.inner()))
-            .await
+        let resend_requests = self.take_resend_requests().await?;
+        let mut actions = self
+            .build_network_actions(old_round, tracked.as_deref().map(|h| h.inner()))
+            .await?;
+        actions.cross_chain_requests.extend(resend_requests);
+        Ok(actions)
+    }
+
+    /// Returns a `RevertConfirm` request for each origin whose update was refused because its
+    /// inbox was full, if that inbox has room again, and forgets these refusals.
+    async fn take_resend_requests(&mut self) -> Result<Vec<CrossChainRequest>, WorkerError> {
+        let refused = self.chain.refused_inboxes.get();
+        if refused.is_empty() {
+            return Ok(Vec::new());
+        }
+        let origins = refused.iter().copied().collect::<Vec<_>>();
+        let recipient = self.chain_id();
+        let limit = self.config.max_pending_bundles_per_origin;
+        let mut requests = Vec::new();
+        for origin in origins {
+            let Some(inbox) = self.chain.inboxes.try_load_entry(&origin).await? else {
+                self.chain.refused_inboxes.get_mut().remove(&origin);
+                continue;
+            };
+            if inbox.pending_count() >= limit {
+                continue;
+            }
+            let retransmit_from = inbox.next_block_height_to_receive().await?;
+            drop(inbox);
+            self.chain.refused_inboxes.get_mut().remove(&origin);
+            requests.push(CrossChainRequest::RevertConfirm {
+                sender: origin,
+                recipient,
+                retransmit_from,
+            });
+        }
+        Ok(requests)
     }
 
     /// Builds the pending cross-chain actions from the already-reconciled outbox index.
@@ -707,6 +772,7 @@ where
                 Some(*prev_height)
             });
             let mut bundles = Vec::new();
+            let mut blocks = Vec::new();
             let mut bundles_size = 0;
             for height in heights {
                 let Some(confirmed_block) = height_to_blocks.get(&height) else {
@@ -744,10 +810,25 @@ where
                         break;
                     }
                 }
+                if !new_bundles.is_empty() {
+                    blocks.push((height, confirmed_block.inner().hash()));
+                }
                 bundles.extend(new_bundles);
                 bundles_size += new_size;
             }
-            if !bundles.is_empty() {
+            if bundles.is_empty() {
+                continue;
+            }
+            // In pull mode, the chunks have the same blocks, but the recipient reads their
+            // messages from storage.
+            if self.config.pull_cross_chain_messages {
+                cross_chain_requests.push(CrossChainRequest::NotifyRecipient {
+                    sender,
+                    recipient,
+                    blocks,
+                    previous_height,
+                });
+            } else {
                 cross_chain_requests.push(CrossChainRequest::UpdateRecipient {
                     sender,
                     recipient,
@@ -911,12 +992,15 @@ where
             .filter_map(|(blob_id, maybe_blob)| Some((blob_id, maybe_blob?)))
             .collect();
         let old_round = self.chain.manager.current_round();
-        self.chain.manager.create_final_vote(
-            certificate,
-            self.config.key_pair(),
-            self.storage.clock().current_time(),
-            blobs,
-        )?;
+        self.chain
+            .manager
+            .create_final_vote(
+                certificate,
+                self.config.signer(),
+                self.storage.clock().current_time(),
+                blobs,
+            )
+            .await?;
         self.save().await?;
         let actions = self.create_network_actions(Some(old_round)).await?;
         Ok((
@@ -1254,6 +1338,7 @@ where
         let block = certificate.block();
         let chain_id = block.header.chain_id;
         let height = block.header.height;
+        let timestamp = block.header.timestamp;
 
         // This should always be true for valid certificates.
         ensure!(
@@ -1294,6 +1379,7 @@ where
             );
         }
         let tracked = self.reconcile_tracked_outboxes().await?;
+        let was_closed_ephemeral = self.is_closed_ephemeral();
         let chain = &mut self.chain;
         chain
             .remove_bundles_from_inboxes(
@@ -1368,6 +1454,12 @@ where
                 },
             });
         }
+        if !was_closed_ephemeral && self.is_closed_ephemeral() {
+            // Index the chain before saving it, so that it is never left out of the index.
+            self.storage
+                .add_closed_ephemeral_chain(chain_id, timestamp)
+                .await?;
+        }
         self.save().await?;
 
         self.block_values
@@ -1417,40 +1509,25 @@ where
         bundles: Vec<(Epoch, MessageBundle)>,
         sender_previous_height: Option<BlockHeight>,
     ) -> Result<CrossChainUpdateResult, WorkerError> {
+        // Bundles referenced in pull mode must be added first, to keep the inbox in order.
+        self.add_referenced_bundles([origin]).await?;
         // Only process certificates with relevant heights and epochs.
         let mut inbox = self.chain.inboxes.try_load_entry_mut(&origin).await?;
-        let next_height_to_receive = inbox.next_block_height_to_receive()?;
+        let next_height_to_receive = inbox.next_block_height_to_receive().await?;
         let last_anticipated_block_height = inbox
             .removed_bundles
             .back()
             .await?
             .map(|bundle| bundle.height);
 
-        // Proactive gap detection: if the sender declares a predecessor height that
-        // we haven't received yet, the inbox has a gap.
-        if let Some(prev) = sender_previous_height {
-            if prev >= next_height_to_receive {
-                let chain_id = self.chain_id();
-                if self.config.allow_revert_confirm && self.config.recovery_allowed_for(&chain_id) {
-                    warn!(
-                        %chain_id,
-                        "Inbox gap detected from {origin}: \
-                        sender declares previous height {prev} but we only have up to \
-                        {next_height_to_receive}; requesting resend",
-                    );
-                    return Ok(CrossChainUpdateResult::GapDetected {
-                        origin,
-                        retransmit_from: next_height_to_receive,
-                    });
-                }
-                return Err(ChainError::InboxGapDetected {
-                    chain_id,
-                    origin,
-                    expected_height: prev,
-                    actual_height: bundles.first().map(|(_, b)| b.height).unwrap_or_default(),
-                }
-                .into());
-            }
+        let actual_height = bundles.first().map(|(_, b)| b.height).unwrap_or_default();
+        if let Some(result) = self.detect_inbox_gap(
+            origin,
+            sender_previous_height,
+            next_height_to_receive,
+            actual_height,
+        )? {
+            return Ok(result);
         }
 
         let bundles = self
@@ -1464,6 +1541,11 @@ where
         let Some(last_updated_height) = bundles.last().map(|bundle| bundle.height) else {
             return Ok(CrossChainUpdateResult::NothingToDo);
         };
+        if let Some(result) =
+            self.refuse_if_inbox_full(origin, inbox.pending_count(), bundles.len())
+        {
+            return Ok(result);
+        }
         // Process the received messages in certificates.
         let local_time = self.storage.clock().current_time();
         let mut previous_height = None;
@@ -1483,6 +1565,143 @@ where
         }
         inbox.observe_size_metric();
         drop(inbox);
+        self.updated_if_active(origin, last_updated_height).await
+    }
+
+    /// Updates the chain's inboxes in pull mode, receiving only the heights and hashes of
+    /// the sender's blocks with messages for this chain. Their bundles are read from storage
+    /// when they are about to be consumed.
+    #[instrument(level = "debug", skip(self, blocks), fields(chain_id = %self.chain_id()))]
+    pub(crate) async fn process_cross_chain_notification(
+        &mut self,
+        origin: ChainId,
+        blocks: Vec<(BlockHeight, CryptoHash)>,
+        sender_previous_height: Option<BlockHeight>,
+    ) -> Result<CrossChainUpdateResult, WorkerError> {
+        let mut inbox = self.chain.inboxes.try_load_entry_mut(&origin).await?;
+        let next_height_to_receive = inbox.next_block_height_to_receive().await?;
+
+        let actual_height = blocks
+            .first()
+            .map(|(height, _)| *height)
+            .unwrap_or_default();
+        if let Some(result) = self.detect_inbox_gap(
+            origin,
+            sender_previous_height,
+            next_height_to_receive,
+            actual_height,
+        )? {
+            return Ok(result);
+        }
+
+        ensure!(
+            blocks.windows(2).all(|w| w[0].0 < w[1].0),
+            WorkerError::InvalidCrossChainRequest
+        );
+        let blocks = blocks
+            .into_iter()
+            .filter(|(height, _)| *height >= next_height_to_receive)
+            .collect::<Vec<_>>();
+        let Some(last_updated_height) = blocks.last().map(|(height, _)| *height) else {
+            return Ok(CrossChainUpdateResult::NothingToDo);
+        };
+        // Only accept references to blocks that can be read later.
+        let mut missing = Vec::new();
+        for (_, hash) in &blocks {
+            if !self.storage.contains_certificate(*hash).await? {
+                missing.push(*hash);
+            }
+        }
+        ensure!(
+            missing.is_empty(),
+            WorkerError::ReadCertificatesError(missing)
+        );
+        if let Some(result) = self.refuse_if_inbox_full(origin, inbox.pending_count(), blocks.len())
+        {
+            return Ok(result);
+        }
+        let local_time = self.storage.clock().current_time();
+        for (height, hash) in blocks {
+            self.chain
+                .receive_block_reference_with_inbox(&mut inbox, &origin, height, hash, local_time)
+                .await?;
+        }
+        inbox.observe_size_metric();
+        drop(inbox);
+        self.updated_if_active(origin, last_updated_height).await
+    }
+
+    /// Returns the result of a cross-chain update if the sender declares a predecessor
+    /// height that we haven't received yet, i.e. if the inbox has a gap.
+    fn detect_inbox_gap(
+        &self,
+        origin: ChainId,
+        sender_previous_height: Option<BlockHeight>,
+        next_height_to_receive: BlockHeight,
+        actual_height: BlockHeight,
+    ) -> Result<Option<CrossChainUpdateResult>, WorkerError> {
+        let Some(prev) = sender_previous_height else {
+            return Ok(None);
+        };
+        if prev < next_height_to_receive {
+            return Ok(None);
+        }
+        let chain_id = self.chain_id();
+        if self.config.allow_revert_confirm && self.config.recovery_allowed_for(&chain_id) {
+            warn!(
+                %chain_id,
+                "Inbox gap detected from {origin}: \
+                sender declares previous height {prev} but we only have up to \
+                {next_height_to_receive}; requesting resend",
+            );
+            return Ok(Some(CrossChainUpdateResult::GapDetected {
+                origin,
+                retransmit_from: next_height_to_receive,
+            }));
+        }
+        Err(ChainError::InboxGapDetected {
+            chain_id,
+            origin,
+            expected_height: prev,
+            actual_height,
+        }
+        .into())
+    }
+
+    /// Refuses a cross-chain update adding `count` entries to an inbox with `pending`
+    /// ones, if that exceeds the limit. An empty inbox always accepts the update, so that a
+    /// single large one cannot be refused forever.
+    fn refuse_if_inbox_full(
+        &mut self,
+        origin: ChainId,
+        pending: usize,
+        count: usize,
+    ) -> Option<CrossChainUpdateResult> {
+        let limit = self.config.max_pending_bundles_per_origin;
+        if pending == 0 || pending.saturating_add(count) <= limit {
+            return None;
+        }
+        #[cfg(with_metrics)]
+        metrics::CROSS_CHAIN_UPDATES_REFUSED_TOTAL.inc();
+        debug!(
+            chain_id = %self.chain_id(),
+            %origin,
+            pending,
+            limit,
+            "Refusing cross-chain update: too many pending bundles from its origin",
+        );
+        // Remember the refusal, so that we ask the origin to resend once the inbox drains.
+        self.chain.refused_inboxes.get_mut().insert(origin);
+        Some(CrossChainUpdateResult::InboxFull { pending, limit })
+    }
+
+    /// Returns the result of a cross-chain update that received blocks up to
+    /// `last_updated_height`, unless the recipient must not be created yet.
+    async fn updated_if_active(
+        &self,
+        origin: ChainId,
+        last_updated_height: BlockHeight,
+    ) -> Result<CrossChainUpdateResult, WorkerError> {
         if !self.config.allow_inactive_chains && !self.chain.is_active().await? {
             // Refuse to create a chain state if the chain is still inactive by
             // now. Accordingly, do not send a confirmation, so that the
@@ -1497,6 +1716,86 @@ where
         Ok(CrossChainUpdateResult::Updated(last_updated_height))
     }
 
+    /// Reads the bundles of the blocks referenced in the inboxes from the given origins,
+    /// and adds them to these inboxes, so that they can be consumed.
+    async fn add_referenced_bundles(
+        &mut self,
+        origins: impl IntoIterator<Item = ChainId>,
+    ) -> Result<(), WorkerError> {
+        let local_time = self.storage.clock().current_time();
+        for origin in origins {
+            let mut inbox = self.chain.inboxes.try_load_entry_mut(&origin).await?;
+            if inbox.block_references.count() == 0 {
+                continue;
+            }
+            let last_anticipated_block_height = inbox
+                .removed_bundles
+                .back()
+                .await?
+                .map(|bundle| bundle.height);
+            let bundles = self
+                .read_referenced_bundles(
+                    origin,
+                    inbox.block_references.elements().await?,
+                    inbox.next_block_height_to_add()?,
+                    last_anticipated_block_height,
+                )
+                .await?;
+            inbox.block_references.clear();
+            for bundle in bundles {
+                // The blocks were added to the received log when they were referenced.
+                self.chain
+                    .receive_message_bundle_with_inbox(
+                        &mut inbox, &origin, bundle, local_time, false,
+                    )
+                    .await?;
+            }
+            inbox.observe_size_metric();
+            if inbox.pending_count() == 0 {
+                self.chain.nonempty_inboxes.get_mut().remove(&origin);
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the referenced blocks of `origin` from storage, and returns their bundles for
+    /// this chain that were not added yet and whose epochs are still trusted.
+    async fn read_referenced_bundles(
+        &self,
+        origin: ChainId,
+        references: Vec<(BlockHeight, CryptoHash)>,
+        next_height_to_add: BlockHeight,
+        last_anticipated_block_height: Option<BlockHeight>,
+    ) -> Result<Vec<MessageBundle>, WorkerError> {
+        let hashes = references.iter().map(|(_, hash)| *hash).collect::<Vec<_>>();
+        let blocks = self.storage.read_confirmed_blocks(hashes).await?;
+        let recipient = self.chain_id();
+        let mut bundles = Vec::new();
+        let mut missing = Vec::new();
+        for ((height, hash), block) in references.into_iter().zip(blocks) {
+            match block {
+                Some(block)
+                    if block.block().header.chain_id == origin
+                        && block.block().header.height == height =>
+                {
+                    bundles.extend(block.block().message_bundles_for(recipient, hash));
+                }
+                _ => missing.push(hash),
+            }
+        }
+        ensure!(
+            missing.is_empty(),
+            WorkerError::ReadCertificatesError(missing)
+        );
+        self.select_message_bundles(
+            &origin,
+            next_height_to_add,
+            last_anticipated_block_height,
+            bundles,
+        )
+        .await
+    }
+
     /// Handles the cross-chain request confirming that the recipient was updated.
     #[instrument(skip_all, fields(
         chain_id = %self.chain_id(),
@@ -1548,7 +1847,7 @@ where
             match request {
                 BatchRequest::Update {
                     origin,
-                    bundles,
+                    update,
                     previous_height,
                     result_sender,
                 } => {
@@ -1556,9 +1855,16 @@ where
                         send_result(result_sender, Err(WorkerError::BatchRolledBack));
                         continue;
                     }
-                    let result = self
-                        .process_cross_chain_update(origin, bundles, previous_height)
-                        .await;
+                    let result = match update {
+                        CrossChainUpdate::Bundles(bundles) => {
+                            self.process_cross_chain_update(origin, bundles, previous_height)
+                                .await
+                        }
+                        CrossChainUpdate::BlockReferences(blocks) => {
+                            self.process_cross_chain_notification(origin, blocks, previous_height)
+                                .await
+                        }
+                    };
                     let update_result = match result {
                         Ok(update_result) => update_result,
                         Err(error) => {
@@ -1570,7 +1876,8 @@ where
                         }
                     };
                     match &update_result {
-                        CrossChainUpdateResult::Updated(_) => need_save = true,
+                        CrossChainUpdateResult::Updated(_)
+                        | CrossChainUpdateResult::InboxFull { .. } => need_save = true,
                         CrossChainUpdateResult::GapDetected { .. }
                         | CrossChainUpdateResult::NothingToDo => {}
                     }
@@ -1731,8 +2038,12 @@ where
             .await?;
 
         if new_heights.is_empty() {
+            // The heights are still queued, e.g. because the recipient refused them while its
+            // inbox was full: send them again.
             debug!("RevertConfirm: all heights already in outbox for {recipient}");
-            return Ok(NetworkActions::default());
+            return self
+                .create_cross_chain_actions_for_recipient(recipient)
+                .await;
         }
 
         // 3. Update the indices only for tracked recipients (mirroring `process_outgoing_messages`):
@@ -1946,7 +2257,7 @@ where
         origin: ChainId,
     ) -> Result<BlockHeight, WorkerError> {
         Ok(match self.chain.inboxes.try_load_entry(&origin).await? {
-            Some(inbox) => inbox.next_block_height_to_receive()?,
+            Some(inbox) => inbox.next_block_height_to_receive().await?,
             None => BlockHeight::ZERO,
         })
     }
@@ -2098,11 +2409,12 @@ where
         );
         let epoch = chain.execution_state.system.epoch.get();
         let chain_id = chain.chain_id();
-        let key_pair = self.config.key_pair();
+        let key_pair = self.config.signer();
         let local_time = self.storage.clock().current_time();
         if chain
             .manager
-            .create_timeout_vote(chain_id, height, round, *epoch, key_pair, local_time)?
+            .create_timeout_vote(chain_id, height, round, *epoch, key_pair, local_time)
+            .await?
         {
             self.save().await?;
         }
@@ -2144,10 +2456,11 @@ where
         if elapsed >= chain.ownership().await?.timeout_config.fallback_duration {
             let chain_id = chain.chain_id();
             let height = chain.tip_state.get().next_block_height;
-            let key_pair = self.config.key_pair();
+            let key_pair = self.config.signer();
             if chain
                 .manager
                 .vote_fallback(chain_id, height, epoch, key_pair)
+                .await?
             {
                 self.save().await?;
             }
@@ -2229,6 +2542,7 @@ where
         block_hash: Option<CryptoHash>,
     ) -> Result<(QueryOutcome, BlockHeight), WorkerError> {
         self.initialize_and_save_if_needed().await?;
+        self.ensure_not_pruned()?;
         let next_block_height = self.chain.tip_state.get().next_block_height;
         let local_time = self.storage.clock().current_time();
         // Try to use a cached execution state for the requested block.
@@ -2326,6 +2640,11 @@ where
         let (_, committee) = self.chain.current_committee().await?;
         block.check_proposal_size(committee.policy().maximum_block_proposal_size)?;
 
+        let origins = block
+            .incoming_bundles()
+            .map(|bundle| bundle.origin)
+            .collect::<BTreeSet<_>>();
+        self.add_referenced_bundles(origins).await?;
         self.chain
             .remove_bundles_from_inboxes(block.timestamp, true, block.incoming_bundles())
             .await?;
@@ -2412,6 +2731,7 @@ where
         proposal: BlockProposal,
     ) -> Result<(ChainInfoResponse, NetworkActions), WorkerError> {
         self.initialize_and_save_if_needed().await?;
+        self.ensure_not_pruned()?;
         proposal
             .check_invariants()
             .map_err(|msg| WorkerError::InvalidBlockProposal(msg.to_string()))?;
@@ -2436,6 +2756,13 @@ where
             chain.manager.can_propose(&owner, proposal.content.round),
             WorkerError::InvalidOwner
         );
+        let ownership = chain.manager.ownership.get();
+        let allowed_schemes = chain.execution_state.system.allowed_signature_schemes.get();
+        let scheme = proposal.signature.scheme();
+        ensure!(
+            scheme.is_allowed_by(allowed_schemes),
+            WorkerError::DisallowedSignatureScheme(scheme)
+        );
         let old_round = self.chain.manager.current_round();
         match original_proposal {
             None => {
@@ -2460,14 +2787,14 @@ where
                 };
                 let super_owner = original_proposal.owner();
                 ensure!(
-                    chain
-                        .manager
-                        .ownership
-                        .get()
-                        .super_owners
-                        .contains(&super_owner),
+                    ownership.super_owners.contains(&super_owner),
                     WorkerError::InvalidOwner
                 );
+                let scheme = signature.scheme();
+                ensure!(
+                    scheme.is_allowed_by(allowed_schemes),
+                    WorkerError::DisallowedSignatureScheme(scheme)
+                );
                 if let Some(signer) = block.authenticated_owner {
                     // Check the authentication of the operations in the new block.
                     ensure!(signer == super_owner, WorkerError::InvalidSigner(signer));
@@ -2519,7 +2846,7 @@ where
             outcome,
         } = content;
 
-        if self.config.key_pair().is_some()
+        if self.config.signer().is_some()
             && block.timestamp.duration_since(local_time) > self.config.block_time_grace_period
         {
             return Err(WorkerError::InvalidTimestamp {
@@ -2532,6 +2859,11 @@ where
         // timestamps (within the grace period) before acquiring the chain lock. By the time
         // we reach here, the block timestamp should be in the past or very close to current time.
 
+        let origins = block
+            .incoming_bundles()
+            .map(|bundle| bundle.origin)
+            .collect::<BTreeSet<_>>();
+        self.add_referenced_bundles(origins).await?;
         self.chain
             .remove_bundles_from_inboxes(block.timestamp, true, block.incoming_bundles())
             .await?;
@@ -2562,9 +2894,12 @@ where
         let blobs = self
             .get_required_blobs(proposal.expected_blob_ids(), block.created_blobs())
             .await?;
-        let key_pair = self.config.key_pair();
+        let key_pair = self.config.signer();
         let manager = &mut self.chain.manager;
-        match manager.create_vote(&proposal, block, key_pair, local_time, blobs)? {
+        match manager
+            .create_vote(&proposal, block, key_pair, local_time, blobs)
+            .await?
+        {
             // Cache the value we voted on, so the client doesn't have to send it again.
             Some(Either::Left(vote)) => {
                 self.block_values
@@ -2631,7 +2966,25 @@ where
                 let inbox = inbox.ok_or_else(|| {
                     ChainError::InternalError(format!("Missing inbox for origin {origin}"))
                 })?;
-                for bundle in inbox.added_bundles.elements().await? {
+                let mut pending_bundles = inbox.added_bundles.elements().await?;
+                if inbox.block_references.count() > 0 {
+                    // In pull mode, read the referenced bundles without adding them yet.
+                    let last_anticipated_block_height = inbox
+                        .removed_bundles
+                        .back()
+                        .await?
+                        .map(|bundle| bundle.height);
+                    let referenced_bundles = self
+                        .read_referenced_bundles(
+                            origin,
+                            inbox.block_references.elements().await?,
+                            inbox.next_block_height_to_add()?,
+                            last_anticipated_block_height,
+                        )
+                        .await?;
+                    pending_bundles.extend(referenced_bundles);
+                }
+                for bundle in pending_bundles {
                     bundles.push(IncomingBundle {
                         origin,
                         bundle,
@@ -2692,7 +3045,8 @@ where
         if query.request_latest_checkpoint_height {
             info.requested_latest_checkpoint_height = *self.chain.latest_checkpoint_height.get();
         }
-        Ok(ChainInfoResponse::new(info, self.config.key_pair()))
+        info.validator_address = self.config.address_update.clone();
+        Ok(ChainInfoResponse::signed_by(info, self.config.signer()).await?)
     }
 
     /// Executes a block with a specified policy for handling bundle failures.
@@ -2752,18 +3106,66 @@ where
         Ok(())
     }
 
+    /// Returns whether this is a closed ephemeral chain that has not been pruned yet.
+    fn is_closed_ephemeral(&self) -> bool {
+        let system = &self.chain.execution_state.system;
+        *system.closed.get() && *system.ephemeral.get() && self.chain.pruned_at.get().is_none()
+    }
+
+    /// Prunes the chain state if this is a closed ephemeral chain whose latest block is
+    /// older than the configured retention period.
+    #[instrument(skip_all, fields(
+        chain_id = %self.chain_id()
+    ))]
+    pub(crate) async fn prune_if_expired(&mut self) -> Result<PruningOutcome, WorkerError> {
+        let Some(retention) = self.config.ephemeral_chain_retention else {
+            return Ok(PruningOutcome::Pending);
+        };
+        if !self.is_closed_ephemeral() {
+            return Ok(PruningOutcome::NotPrunable);
+        }
+        let local_time = self.storage.clock().current_time();
+        if !self
+            .chain
+            .is_prunable(local_time, TimeDelta::from_duration(retention))
+        {
+            return Ok(PruningOutcome::Pending);
+        }
+        info!(chain_id = %self.chain_id(), "Pruning closed ephemeral chain");
+        self.chain.prune(local_time);
+        self.save().await?;
+        Ok(PruningOutcome::Pruned)
+    }
+
+    /// Returns an error if the chain state has been pruned.
+    fn ensure_not_pruned(&self) -> Result<(), WorkerError> {
+        ensure!(
+            self.chain.pruned_at.get().is_none(),
+            WorkerError::ChainPruned(self.chain_id())
+        );
+        Ok(())
+    }
+
     pub(crate) async fn chain_info_response(&mut self) -> Result<ChainInfoResponse, WorkerError> {
         let info = ChainInfo::from_chain_view(&mut self.chain).await?;
-        Ok(ChainInfoResponse::new(info, self.config.key_pair()))
+        Ok(ChainInfoResponse::signed_by(info, self.config.signer()).await?)
     }
 
     /// Stores the chain state in persistent storage.
     ///
     /// If the save fails, the worker is marked as poisoned and must be reloaded.
+    ///
+    /// On validators, the votes and proposals that the chain manager discards are kept
+    /// separately, with a time-to-live.
     #[instrument(skip_all, fields(
         chain_id = %self.chain_id()
     ))]
     pub(crate) async fn save(&mut self) -> Result<(), WorkerError> {
+        let superseded = if self.config.signer().is_some() {
+            self.chain.manager.superseded_round_state().await
+        } else {
+            None
+        };
         if let Err(error) = self.chain.save().await {
             if error.must_reload_view() {
                 tracing::error!(
@@ -2775,6 +3177,20 @@ where
             }
             return Err(WorkerError::ViewError(error));
         }
+        if let Some(state) = superseded {
+            let now = self.storage.clock().current_time();
+            if let Err(error) = self
+                .storage
+                .write_superseded_round_state(self.chain_id(), now, &state)
+                .await
+            {
+                tracing::warn!(
+                    %error,
+                    chain_id = %self.chain_id(),
+                    "Failed to keep the discarded votes and proposals"
+                );
+            }
+        }
         Ok(())
     }
 
@@ -2869,6 +3285,10 @@ fn check_block_epoch(
     block_chain: ChainId,
     block_epoch: Epoch,
 ) -> Result<(), WorkerError> {
+    #[cfg(with_metrics)]
+    if block_epoch > chain_epoch {
+        metrics::FUTURE_EPOCH_BLOCKS_REJECTED_TOTAL.inc();
+    }
     ensure!(
         block_epoch == chain_epoch,
         WorkerError::InvalidEpoch {
@@ -3,9 +3,10 @@
 
 mod state;
 use std::{
-    collections::{hash_map, BTreeMap, BTreeSet, HashMap, HashSet},
+    collections::{hash_map, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     convert::Infallible,
     iter,
+    num::NonZeroU64,
     sync::Arc,
 };
 
@@ -18,7 +19,10 @@ use futures::{
 use linera_base::prometheus_util::MeasureLatency as _;
 use linera_base::{
     abi::Abi,
-    crypto::{signer, CryptoHash, Signer, ValidatorPublicKey},
+    crypto::{
+        signer, AccountSignature, CryptoError, CryptoHash, SignatureScheme, Signer,
+        ValidatorPublicKey,
+    },
     data_types::{
         Amount, ApplicationDescription, ApplicationPermissions, ArithmeticError, Blob, BlobContent,
         BlockHeight, ChainDescription, Epoch, MessagePolicy, Round, TimeDelta, Timestamp,
@@ -36,7 +40,7 @@ use linera_base::{data_types::Bytecode, vm::VmRuntime};
 use linera_chain::{
     data_types::{
         BlockProposal, BundleExecutionPolicy, BundleFailurePolicy, ChainAndHeight, IncomingBundle,
-        ProposedBlock, Transaction,
+        ProposalContent, ProposedBlock, Transaction,
     },
     manager::LockingBlock,
     types::{
@@ -48,10 +52,11 @@ use linera_chain::{
 use linera_execution::{
     committee::Committee,
     system::{
-        AdminOperation, OpenChainConfig, SystemOperation, EPOCH_STREAM_NAME,
+        AdminOperation, GovernanceProposal, OpenChainConfig, SystemOperation, EPOCH_STREAM_NAME,
         REMOVED_EPOCH_STREAM_NAME,
     },
-    ExecutionError, Operation, Query, QueryOutcome,
+    EventIndexKey, ExecutionError, HttpAllowListUpdate, Operation, Query, QueryOutcome,
+    ResourceTracker,
 };
 use linera_storage::{Arc as CacheArc, Clock as _, Storage as _};
 use linera_views::ViewError;
@@ -67,7 +72,10 @@ use super::{
     ListeningMode, PendingProposal, TimingType,
 };
 use crate::{
-    data_types::{ChainInfo, ChainInfoQuery, ClientOutcome, RoundTimeout},
+    data_types::{
+        BlockPackingProgress, ChainInfo, ChainInfoQuery, ClientOutcome, DryRunOutcome,
+        FeeSuggestion, OrphanedProposal, RoundTimeout, UnsignedBlockProposal,
+    },
     environment::Environment,
     local_node::{LocalNodeClient, LocalNodeError},
     node::{
@@ -76,6 +84,7 @@ use crate::{
     },
     remote_node::RemoteNode,
     updater::{communicate_with_quorum, CommunicateAction, CommunicationError, ValidatorUpdater},
+    wallet::StoredProposal,
     worker::{Notification, Reason, WorkerError},
 };
 
@@ -131,6 +140,12 @@ pub struct Options {
     /// Maximum number of event stream IDs to include in a single `PreviousEventBlocks`
     /// request. Larger sets are split into multiple requests.
     pub max_event_stream_queries: usize,
+    /// Whether to use the new network addresses that validators announce in their chain
+    /// information, instead of those of the committee.
+    pub follow_validator_address_updates: bool,
+    /// Network addresses to use for some validators, regardless of the committee and of the
+    /// addresses they announce.
+    pub pinned_validator_addresses: BTreeMap<ValidatorPublicKey, String>,
 }
 
 struct CircuitBreakerState {
@@ -178,6 +193,8 @@ impl Options {
             notification_circuit_breaker_initial_probe_interval: Duration::from_secs(300),
             notification_circuit_breaker_max_probe_interval: Duration::from_secs(3600),
             max_event_stream_queries: DEFAULT_MAX_EVENT_STREAM_QUERIES,
+            follow_validator_address_updates: true,
+            pinned_validator_addresses: BTreeMap::new(),
         }
     }
 }
@@ -197,6 +214,14 @@ impl Options {
     }
 }
 
+/// The safety margin added by [`ChainClient::suggested_fee_budget`], in percent.
+const FEE_BUDGET_HEADROOM_PERCENT: u128 = 20;
+
+/// The share of the maximum block (proposal) size that
+/// [`ChainClient::execute_operations_in_blocks`] fills with operations, in percent. The rest
+/// is left for the block header, the incoming messages and the signature.
+const PACKED_OPERATIONS_SIZE_PERCENT: u64 = 80;
+
 /// Client to operate a chain by interacting with validators and the given local storage
 /// implementation.
 /// * The chain being operated is called the "local chain" or just the "chain".
@@ -260,6 +285,12 @@ pub enum Error {
     #[error("Missing confirmed block: {0:?}")]
     MissingConfirmedBlock(CryptoHash),
 
+    #[error("No validator has the block at height {height} of chain {chain_id}")]
+    MissingCertificateAtHeight {
+        chain_id: ChainId,
+        height: BlockHeight,
+    },
+
     #[error("JSON (de)serialization error: {0}")]
     JsonError(#[from] serde_json::Error),
 
@@ -357,6 +388,9 @@ pub enum Error {
          different outcomes for the same block"
     )]
     ExecutionOutcomeMismatch,
+
+    #[error("Invalid signature of the block proposal: {0}")]
+    InvalidProposalSignature(CryptoError),
 }
 
 impl From<Infallible> for Error {
@@ -371,6 +405,24 @@ impl Error {
         Self::Signer(Box::new(err))
     }
 
+    /// Returns whether a block was rejected because it exceeds a per-block limit, such as
+    /// the maximum proposal size or a fuel cap, so that it may succeed with fewer
+    /// operations.
+    pub fn is_block_limit_error(&self) -> bool {
+        let chain_error = match self {
+            Error::ChainError(chain_error) => chain_error,
+            Error::LocalNodeError(LocalNodeError::WorkerError(WorkerError::ChainError(
+                chain_error,
+            ))) => &**chain_error,
+            _ => return false,
+        };
+        match chain_error {
+            ChainError::BlockProposalTooLarge(_) => true,
+            ChainError::ExecutionError(error, _) => error.is_limit_error(),
+            _ => false,
+        }
+    }
+
     /// Returns the qualified error variant name for the `error_type` metric label,
     /// delegating to the wrapped error's `error_type()` so the underlying worker or
     /// chain error name is surfaced rather than just the outer variant.
@@ -427,12 +479,53 @@ impl<Env: Environment> ChainClient<Env> {
             .proposal_mutex()
     }
 
+    /// Records the fees charged for a block this client executed on the chain.
+    fn record_fees(&self, block_hash: CryptoHash, fees: Amount) {
+        #[cfg(with_metrics)]
+        super::metrics::BLOCK_FEES
+            .with_label_values(&[])
+            .observe(fees.to_attos() as f64 / Amount::ONE.to_attos() as f64);
+        if let Some(state) = self.client.chains.pin().get(&self.chain_id) {
+            state.record_fees(block_hash, fees);
+        }
+    }
+
+    /// Returns the hashes of the latest blocks this client executed on the chain,
+    /// oldest first, with the fees charged for them. Not all of them were necessarily
+    /// committed.
+    pub fn recent_block_fees(&self) -> Vec<(CryptoHash, Amount)> {
+        self.client
+            .chains
+            .pin()
+            .get(&self.chain_id)
+            .map(|state| state.recent_block_fees())
+            .unwrap_or_default()
+    }
+
     /// Returns the pending proposal, if any.
     #[instrument(level = "trace", skip(self))]
     pub async fn pending_proposal(&self) -> Option<PendingProposal> {
         self.proposal_mutex().lock().await.clone()
     }
 
+    /// Returns the pending proposal made in the fast round, if any, written to the client's
+    /// storage so that the wallet only needs to remember a reference to it.
+    ///
+    /// Fast proposals must be remembered across sessions to make sure there are no
+    /// conflicting fast proposals.
+    pub async fn stored_fast_proposal(&self) -> Result<Option<StoredProposal>, Error> {
+        let Some(proposal) = self
+            .pending_proposal()
+            .await
+            .filter(|proposal| proposal.round.is_some_and(|round| round.is_fast()))
+        else {
+            return Ok(None);
+        };
+        Ok(Some(
+            StoredProposal::store(&proposal, self.storage_client()).await?,
+        ))
+    }
+
     /// Gets a reference to the client's signer instance.
     #[instrument(level = "trace", skip(self))]
     pub fn signer(&self) -> &impl Signer {
@@ -609,6 +702,19 @@ impl<Env: Environment> ChainClient<Env> {
             .await
     }
 
+    /// Returns the contents of the data blob with the given hash. Fetches the blob from
+    /// the validators if necessary.
+    pub async fn get_data_blob(&self, hash: CryptoHash) -> Result<Vec<u8>, Error> {
+        Ok(self.client.get_data_blob(hash).await?.bytes().to_vec())
+    }
+
+    /// Returns the number of message bundles the next block would receive, up to
+    /// `max_pending_message_bundles`.
+    #[instrument(level = "trace")]
+    pub async fn pending_message_bundle_count(&self) -> Result<usize, Error> {
+        Ok(self.pending_message_bundles().await?.len())
+    }
+
     /// Obtains up to `self.options.max_pending_message_bundles` pending message bundles for the
     /// local chain.
     #[instrument(level = "trace")]
@@ -751,6 +857,22 @@ impl<Env: Environment> ChainClient<Env> {
         let Some(preferred_owner) = self.preferred_owner else {
             return Err(Error::NoAccountKeyConfigured(self.chain_id));
         };
+        self.check_can_propose(&preferred_owner).await?;
+
+        let has_signer = self.has_key_for(&preferred_owner).await?;
+
+        if !has_signer {
+            warn!(%self.chain_id, ?preferred_owner,
+                "Chain is one of the owners but its Signer instance doesn't contain the key",
+            );
+            return Err(Error::CannotFindKeyForChain(self.chain_id));
+        }
+
+        Ok(preferred_owner)
+    }
+
+    /// Checks that `owner` may propose blocks on the chain, whether or not we hold its key.
+    async fn check_can_propose(&self, owner: &AccountOwner) -> Result<(), Error> {
         let manager = self.chain_info().await?.manager;
         ensure!(
             manager.ownership.is_active(),
@@ -766,32 +888,20 @@ impl<Env: Environment> ChainClient<Env> {
             BTreeSet::new()
         };
 
-        let is_owner = manager
-            .ownership
-            .can_propose_in_multi_leader_round(&preferred_owner)
-            || fallback_owners.contains(&preferred_owner);
+        let is_owner = manager.ownership.can_propose_in_multi_leader_round(owner)
+            || fallback_owners.contains(owner);
 
         if !is_owner {
             warn!(
                 chain_id = %self.chain_id,
                 ownership = ?manager.ownership,
                 ?fallback_owners,
-                ?preferred_owner,
-                "The preferred owner is not configured as an owner of this chain",
+                ?owner,
+                "The owner is not configured as an owner of this chain",
             );
             return Err(Error::NotAnOwner(self.chain_id));
         }
-
-        let has_signer = self.has_key_for(&preferred_owner).await?;
-
-        if !has_signer {
-            warn!(%self.chain_id, ?preferred_owner,
-                "Chain is one of the owners but its Signer instance doesn't contain the key",
-            );
-            return Err(Error::CannotFindKeyForChain(self.chain_id));
-        }
-
-        Ok(preferred_owner)
+        Ok(())
     }
 
     /// Prepares the chain for a new owner by fetching the chain description and validating access.
@@ -1401,6 +1511,85 @@ impl<Env: Environment> ChainClient<Env> {
         result
     }
 
+    /// Executes the operations in as many consecutive blocks as needed, so that no block
+    /// exceeds the maximum block and proposal sizes or the other per-block limits of the
+    /// policy, such as the fuel caps. `on_block` is called after each committed block.
+    ///
+    /// Operations are first packed by their serialized size. If a block still exceeds a
+    /// limit when it is executed, it is retried with half as many operations.
+    ///
+    /// Operations are removed from the front of `operations` once their block is committed,
+    /// so that the call can be repeated to execute the rest if it returns a timeout or an
+    /// error. If not all operations could be executed due to a timeout, the timestamp for
+    /// when to retry is returned, too.
+    #[instrument(level = "trace", skip(operations, on_block))]
+    pub async fn execute_operations_in_blocks(
+        &self,
+        operations: &mut VecDeque<Operation>,
+        mut on_block: impl FnMut(BlockPackingProgress),
+    ) -> Result<(Vec<ConfirmedBlockCertificate>, Option<RoundTimeout>), Error> {
+        let committee = self.local_committee().await?;
+        let policy = committee.policy();
+        let size_budget = policy
+            .maximum_block_proposal_size
+            .min(policy.maximum_block_size)
+            / 100
+            * PACKED_OPERATIONS_SIZE_PERCENT;
+        let mut max_operations = operations.len();
+        let mut certificates = Vec::new();
+        let mut executed_operations = 0;
+        while !operations.is_empty() {
+            let mut block_operations = Vec::new();
+            let mut size = 0u64;
+            for operation in operations.iter().take(max_operations) {
+                let operation_size = bcs::serialized_size(operation)? as u64;
+                if !block_operations.is_empty() && size + operation_size > size_budget {
+                    break;
+                }
+                size += operation_size;
+                block_operations.push(operation.clone());
+            }
+            let count = block_operations.len();
+            match self.execute_operations(block_operations, vec![]).await {
+                Ok(ClientOutcome::Committed(certificate)) => {
+                    operations.drain(..count);
+                    executed_operations += count;
+                    let progress = BlockPackingProgress {
+                        height: certificate.block().header.height,
+                        block_operations: count,
+                        executed_operations,
+                        remaining_operations: operations.len(),
+                    };
+                    info!(
+                        chain_id = %self.chain_id,
+                        height = %progress.height,
+                        "Committed a block with {count} operations, {} remaining",
+                        progress.remaining_operations
+                    );
+                    on_block(progress);
+                    certificates.push(certificate);
+                }
+                Ok(ClientOutcome::WaitForTimeout(timeout)) => {
+                    return Ok((certificates, Some(timeout)));
+                }
+                Ok(ClientOutcome::Conflict(certificate)) => {
+                    return Err(Error::Conflict(certificate.hash()));
+                }
+                Err(error) if error.is_block_limit_error() && count > 1 => {
+                    max_operations = count / 2;
+                    debug!(
+                        chain_id = %self.chain_id,
+                        %error,
+                        "A block with {count} operations exceeds a limit; retrying with \
+                         {max_operations}"
+                    );
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Ok((certificates, None))
+    }
+
     /// Executes an operation.
     pub async fn execute_operation(
         &self,
@@ -1410,6 +1599,134 @@ impl<Env: Environment> ChainClient<Env> {
             .await
     }
 
+    /// Prepares a block with the given operations and blobs, proposed by `owner` in the
+    /// current round, without signing it: the owner's key may be kept elsewhere, e.g. on an
+    /// air-gapped machine. Submit the proposal with [`Self::submit_signed_proposal`] once
+    /// the owner has signed its [`UnsignedBlockProposal::signing_hash`].
+    ///
+    /// The proposal can only be committed if the chain does not move to another block or
+    /// round in the meantime.
+    #[instrument(level = "trace", skip(operations, blobs))]
+    pub async fn prepare_unsigned_proposal(
+        &self,
+        operations: Vec<Operation>,
+        blobs: Vec<Blob>,
+        owner: AccountOwner,
+    ) -> Result<ClientOutcome<UnsignedBlockProposal>, Error> {
+        self.prepare_chain().await?;
+        self.check_can_propose(&owner).await?;
+        let info = self.request_leader_timeout_if_needed().await?;
+        ensure!(
+            info.manager.requested_locking.is_none(),
+            Error::BlockProposalError(
+                "the chain has a locking block that must be committed first, \
+                 e.g. with the `linera retry-pending-block` command"
+            )
+        );
+        let transactions = self.prepend_epochs_messages_and_events(operations).await?;
+        let timestamp = self.next_timestamp(&transactions, info.timestamp);
+        let proposed_block = ProposedBlock {
+            epoch: info.epoch,
+            chain_id: self.chain_id,
+            transactions,
+            previous_block_hash: info.block_hash,
+            height: info.next_block_height,
+            authenticated_owner: Some(owner),
+            timestamp,
+        };
+        let round = self.round_for_oracle(&info, &owner).await?;
+        let (block, _, _, _) = self
+            .client
+            .stage_block_execution(
+                proposed_block,
+                round,
+                blobs.clone(),
+                self.options.bundle_execution_policy(),
+            )
+            .await?;
+        let has_oracle_responses = block.has_oracle_responses();
+        let (proposed_block, _) = block.into_proposal();
+        let round = match self
+            .round_for_new_proposal(&info, &owner, has_oracle_responses)
+            .await?
+        {
+            Either::Left(round) => round,
+            Either::Right(timeout) => return Ok(ClientOutcome::WaitForTimeout(timeout)),
+        };
+        Ok(ClientOutcome::Committed(UnsignedBlockProposal {
+            owner,
+            content: ProposalContent {
+                block: proposed_block,
+                round,
+                outcome: None,
+            },
+            blobs,
+        }))
+    }
+
+    /// Submits a proposal prepared by [`Self::prepare_unsigned_proposal`], with the
+    /// signature of its owner.
+    #[instrument(level = "trace", skip(proposal, signature))]
+    pub async fn submit_signed_proposal(
+        &self,
+        proposal: UnsignedBlockProposal,
+        signature: AccountSignature,
+    ) -> Result<ConfirmedBlockCertificate, Error> {
+        let UnsignedBlockProposal {
+            owner,
+            content,
+            blobs,
+        } = proposal;
+        ensure!(
+            content.block.chain_id == self.chain_id,
+            Error::BlockProposalError("the proposal is for another chain")
+        );
+        ensure!(
+            signature.owner() == owner && content.block.authenticated_owner == Some(owner),
+            Error::BlockProposalError("the proposal must be signed by its owner")
+        );
+        let proposal = Box::new(BlockProposal {
+            content,
+            signature,
+            original_proposal: None,
+        });
+        proposal
+            .check_signature()
+            .map_err(Error::InvalidProposalSignature)?;
+
+        self.prepare_chain().await?;
+        let mutex = self.proposal_mutex();
+        let _proposal_guard = mutex.lock_owned().await;
+        let info = self.chain_info_with_manager_values().await?;
+        let ProposalContent { block, round, .. } = &proposal.content;
+        ensure!(
+            block.height == info.next_block_height && *round >= info.manager.current_round,
+            Error::BlockProposalError(
+                "the chain has moved to another block or round since the proposal was \
+                 prepared; prepare and sign a new one"
+            )
+        );
+        let (executed_block, _, _, _) = self
+            .client
+            .stage_block_execution(
+                block.clone(),
+                round.multi_leader(),
+                blobs.clone(),
+                BundleExecutionPolicy::committed(),
+            )
+            .await?;
+        let already_handled_locally = info.manager.already_handled_proposal(*round, block);
+        let mut snapshot = None;
+        self.submit_proposal(
+            proposal,
+            executed_block,
+            blobs,
+            already_handled_locally,
+            &mut snapshot,
+        )
+        .await
+    }
+
     /// Executes a new block.
     ///
     /// This must be preceded by a call to `prepare_chain()`.
@@ -1628,7 +1945,7 @@ impl<Env: Environment> ChainClient<Env> {
         let round = self.round_for_oracle(&info, &identity).await?;
         // Make sure every incoming message succeeds and otherwise remove them.
         // Also, compute the final certified hash while we're at it.
-        let (block, _, never_reject_origins) = self
+        let (block, _, resource_tracker, never_reject_origins) = self
             .client
             .stage_block_execution(
                 proposed_block,
@@ -1637,6 +1954,7 @@ impl<Env: Environment> ChainClient<Env> {
                 self.options.bundle_execution_policy(),
             )
             .await?;
+        self.record_fees(block.hash(), resource_tracker.fees);
         // Record origins whose bundles were discarded due to the never-reject policy so
         // that `process_inbox` stops retrying them until the client is restarted.
         if !never_reject_origins.is_empty() {
@@ -1757,6 +2075,89 @@ impl<Env: Environment> ChainClient<Env> {
         }
     }
 
+    /// Recommends a budget for the fees of a block with the given operations, so that
+    /// automated senders can keep enough tokens on the chain.
+    ///
+    /// The operations are executed locally in a block on top of the current chain state,
+    /// together with the incoming messages it would pick up, and the fees are compared with
+    /// those of the latest blocks this client executed. The chain is not modified.
+    #[instrument(level = "trace", skip(operations))]
+    pub async fn suggested_fee_budget(
+        &self,
+        operations: Vec<Operation>,
+    ) -> Result<FeeSuggestion, Error> {
+        let info = self.chain_info().await?;
+        let resource_tracker = self
+            .stage_operations_locally(&info, operations, Vec::new())
+            .await?;
+        let estimated = resource_tracker.fees;
+        let recent_maximum = self
+            .client
+            .chains
+            .pin()
+            .get(&self.chain_id)
+            .and_then(|state| state.recent_fees().into_iter().max());
+        let suggested = recent_maximum
+            .map_or(estimated, |maximum| maximum.max(estimated))
+            .saturating_mul(100 + FEE_BUDGET_HEADROOM_PERCENT)
+            .saturating_div(100);
+        Ok(FeeSuggestion {
+            estimated,
+            recent_maximum,
+            suggested,
+            chain_balance: info.chain_balance,
+        })
+    }
+
+    /// Executes a block with the given operations and blobs locally, without proposing it,
+    /// and reports the resources it uses and the fees it is charged under the current
+    /// resource control policy.
+    ///
+    /// Like a proposal, the block also handles the incoming messages it would pick up. The
+    /// chain is not modified.
+    #[instrument(level = "trace", skip(operations, blobs))]
+    pub async fn dry_run(
+        &self,
+        operations: Vec<Operation>,
+        blobs: Vec<Blob>,
+    ) -> Result<DryRunOutcome, Error> {
+        let info = self.prepare_chain().await?;
+        let resources = self
+            .stage_operations_locally(&info, operations, blobs)
+            .await?;
+        Ok(DryRunOutcome {
+            resources,
+            chain_balance: info.chain_balance,
+        })
+    }
+
+    /// Executes a block with the given operations and blobs locally on top of the chain
+    /// state described by `info`, together with the incoming messages it would pick up, and
+    /// returns the resources it uses.
+    async fn stage_operations_locally(
+        &self,
+        info: &ChainInfo,
+        operations: Vec<Operation>,
+        blobs: Vec<Blob>,
+    ) -> Result<ResourceTracker, Error> {
+        let transactions = self.prepend_epochs_messages_and_events(operations).await?;
+        let timestamp = self.next_timestamp(&transactions, info.timestamp);
+        let block = ProposedBlock {
+            epoch: info.epoch,
+            chain_id: self.chain_id,
+            transactions,
+            previous_block_hash: info.block_hash,
+            height: info.next_block_height,
+            authenticated_owner: self.preferred_owner,
+            timestamp,
+        };
+        let (_, _, resource_tracker, _) = self
+            .client
+            .stage_block_execution(block, None, blobs, self.options.bundle_execution_policy())
+            .await?;
+        Ok(resource_tracker)
+    }
+
     /// Obtains the local balance of the chain account after staging the execution of
     /// incoming messages in a new block.
     ///
@@ -1836,7 +2237,7 @@ impl<Env: Environment> ChainClient<Env> {
             )
             .await
         {
-            Ok((_, response, _)) => Ok((
+            Ok((_, response, _, _)) => Ok((
                 response.info.chain_balance,
                 response.info.requested_owner_balance,
             )),
@@ -2151,7 +2552,7 @@ impl<Env: Environment> ChainClient<Env> {
                         .get_locking_blobs(&blob_ids, self.chain_id)
                         .await?
                         .ok_or_else(|| Error::InternalError("Missing local locking blobs"))?;
-                    let (block, _, _) = self
+                    let (block, _, _, _) = self
                         .client
                         .stage_block_execution(
                             proposed_block,
@@ -2203,7 +2604,7 @@ impl<Env: Environment> ChainClient<Env> {
             let blobs = pending.blobs.clone();
             let staging_outcome = pending.auto_retry_outcome.as_ref();
             let round = self.round_for_oracle(&info, &owner).await?;
-            let (block, _, _) = self
+            let (block, _, _, _) = self
                 .client
                 .stage_block_execution(
                     proposed_block,
@@ -2265,6 +2666,31 @@ impl<Env: Environment> ChainClient<Env> {
                     .map_err(Error::signer_failure)?,
             )
         };
+        let block = Block::new(proposed_block, outcome);
+        let certificate = self
+            .submit_proposal(proposal, block, blobs, already_handled_locally, snapshot)
+            .await?;
+        tracing::debug!(
+            total_process_ms = process_start.elapsed().as_millis(),
+            "process_pending_block_without_prepare completing"
+        );
+        // Clear the pending proposal now that the block has been committed.
+        *proposal_guard = None;
+        Ok(ClientOutcome::Committed(Some(certificate)))
+    }
+
+    /// Handles the signed `proposal` of `block` in the local node, sends it to the
+    /// validators and, unless it is in the fast round, finalizes the validated block.
+    async fn submit_proposal(
+        &self,
+        proposal: Box<BlockProposal>,
+        block: Block,
+        blobs: Vec<Blob>,
+        already_handled_locally: bool,
+        snapshot: &mut Option<ConsensusStateSnapshot>,
+    ) -> Result<ConfirmedBlockCertificate, Error> {
+        let local_node = &self.client.local_node;
+        let round = proposal.content.round;
         if !already_handled_locally {
             // Check the final block proposal. This will be cheaper after #1401.
             if let Err(err) = local_node.handle_block_proposal(*proposal.clone()).await {
@@ -2285,7 +2711,6 @@ impl<Env: Environment> ChainClient<Env> {
         // network calls below.
         *snapshot = Some(self.consensus_state_snapshot().await?);
         let committee = self.local_committee().await?;
-        let block = Block::new(proposed_block, outcome);
         // Send the query to validators.
         let submit_block_proposal_start = linera_base::time::Instant::now();
         let certificate = if round.is_fast() {
@@ -2302,19 +2727,11 @@ impl<Env: Environment> ChainClient<Env> {
             self.client.finalize_block(&committee, certificate).await?
         };
         self.send_timing(submit_block_proposal_start, TimingType::SubmitBlockProposal);
-        tracing::debug!(
-            total_process_ms = process_start.elapsed().as_millis(),
-            "process_pending_block_without_prepare completing"
-        );
         debug!(round = %certificate.round, "Sending confirmed block to validators");
         let certificate = self.client.storage_client().cache_certificate(certificate);
         self.update_validators(Some(&committee), Some(certificate.clone()))
             .await?;
-        // Clear the pending proposal now that the block has been committed.
-        *proposal_guard = None;
-        Ok(ClientOutcome::Committed(Some(CacheArc::unwrap_or_clone(
-            certificate,
-        ))))
+        Ok(CacheArc::unwrap_or_clone(certificate))
     }
 
     #[expect(
@@ -2463,6 +2880,115 @@ impl<Env: Environment> ChainClient<Env> {
         *self.proposal_mutex().lock().await = None;
     }
 
+    /// Checks the pending proposal, if any, against the blocks certified by the validators,
+    /// and resolves it if a different block was certified at its height.
+    ///
+    /// In that case the certified blocks are adopted, the pending proposal is discarded and
+    /// its operations are proposed again in a new block. This is not safe if some of these
+    /// operations were executed by the certified blocks already, e.g. because another client
+    /// proposed them, nor if the validators are locked on a different block at the height
+    /// of the pending proposal: such conflicts are returned as
+    /// [`OrphanedProposal::Unsafe`], without proposing anything.
+    ///
+    /// Returns `None` if there is no pending proposal, if it was committed, or if it can
+    /// still be committed.
+    #[instrument(level = "trace")]
+    pub async fn resolve_orphaned_proposal(&self) -> Result<Option<OrphanedProposal>, Error> {
+        let Some(pending) = self.pending_proposal().await else {
+            return Ok(None);
+        };
+        let height = pending.block.height;
+        self.synchronize_chain_state(self.chain_id).await?;
+        let info = self.chain_info_with_manager_values().await?;
+        if height >= info.next_block_height {
+            let locked_elsewhere = match info.manager.requested_locking.as_deref() {
+                Some(LockingBlock::Regular(certificate)) => {
+                    !certificate.block().matches_proposed_block(&pending.block)
+                }
+                Some(LockingBlock::Fast(proposal)) => proposal.content.block != pending.block,
+                None => false,
+            };
+            if !locked_elsewhere {
+                return Ok(None);
+            }
+            return Ok(Some(OrphanedProposal::Unsafe {
+                height,
+                reason: "the validators are locked on a different block at this height, which \
+                         will be committed instead; the operations of the pending proposal \
+                         must then be submitted again"
+                    .to_owned(),
+            }));
+        }
+        let heights = (height.0..info.next_block_height.0)
+            .map(BlockHeight)
+            .collect::<Vec<_>>();
+        let certificates = self
+            .client
+            .storage_client()
+            .read_certificates_by_heights(self.chain_id, &heights)
+            .await?;
+        let Some(certified) = certificates.first().cloned().flatten() else {
+            return Ok(Some(OrphanedProposal::Unsafe {
+                height,
+                reason: "the block certified at this height is not available locally; the \
+                         pending proposal was kept"
+                    .to_owned(),
+            }));
+        };
+        self.clear_pending_proposal().await;
+        if certified.block().matches_proposed_block(&pending.block) {
+            return Ok(None);
+        }
+        // Epoch changes and stream updates are added to every block again.
+        let operations = pending
+            .block
+            .operations()
+            .filter(|operation| {
+                !matches!(
+                    operation,
+                    Operation::System(operation) if matches!(
+                        **operation,
+                        SystemOperation::ProcessNewEpoch(_) | SystemOperation::UpdateStream { .. }
+                    )
+                )
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        let executed = operations
+            .iter()
+            .filter(|operation| {
+                certificates.iter().flatten().any(|certificate| {
+                    certificate
+                        .block()
+                        .body
+                        .operations()
+                        .any(|certified| certified == *operation)
+                })
+            })
+            .count();
+        if executed > 0 {
+            return Ok(Some(OrphanedProposal::Unsafe {
+                height,
+                reason: format!(
+                    "{executed} of the {} operations of the pending proposal were executed by \
+                     the blocks certified since; the pending proposal was discarded without \
+                     proposing the others again",
+                    operations.len()
+                ),
+            }));
+        }
+        let requeued = if operations.is_empty() {
+            None
+        } else {
+            Some(self.execute_operations(operations, pending.blobs).await?)
+        };
+        Ok(Some(OrphanedProposal::Resolved {
+            height,
+            certified_hash: certified.hash(),
+            requeued,
+        }))
+    }
+
     /// Rotates the key of the chain.
     ///
     /// Replaces current owners of the chain with the new key pair.
@@ -2552,6 +3078,34 @@ impl<Env: Environment> ChainClient<Env> {
         .await
     }
 
+    /// Returns the signature schemes owners may sign block proposals with on this chain. An
+    /// empty set allows all schemes.
+    #[instrument(level = "trace")]
+    pub async fn query_signature_schemes(&self) -> Result<BTreeSet<SignatureScheme>, Error> {
+        Ok(self
+            .client
+            .local_node
+            .chain_state_view(self.chain_id)
+            .await?
+            .execution_state
+            .system
+            .allowed_signature_schemes
+            .get()
+            .clone())
+    }
+
+    /// Changes the signature schemes owners may sign block proposals with on this chain.
+    #[instrument(level = "trace")]
+    pub async fn change_signature_schemes(
+        &self,
+        schemes: BTreeSet<SignatureScheme>,
+    ) -> Result<ClientOutcome<ConfirmedBlockCertificate>, Error> {
+        self.execute_operation(SystemOperation::ChangeSignatureSchemes(
+            schemes.into_iter().collect(),
+        ))
+        .await
+    }
+
     /// Returns the current application permissions on this chain.
     #[instrument(level = "trace")]
     pub async fn query_application_permissions(&self) -> Result<ApplicationPermissions, Error> {
@@ -2639,6 +3193,63 @@ impl<Env: Environment> ChainClient<Env> {
         self.execute_operation(SystemOperation::Checkpoint).await
     }
 
+    /// Publishes a checkpoint if the chain grew by at least `interval` blocks since its
+    /// latest checkpoint, or since its creation if it has none. A checkpoint is also due
+    /// when the latest one was certified by an earlier committee: once that committee is
+    /// revoked, new nodes can no longer verify it. Returns `None` if no checkpoint is due.
+    #[instrument(level = "trace")]
+    pub async fn checkpoint_if_due(
+        &self,
+        interval: NonZeroU64,
+    ) -> Result<Option<ClientOutcome<ConfirmedBlockCertificate>>, Error> {
+        let query = ChainInfoQuery::new(self.chain_id).with_latest_checkpoint_height();
+        let info = self
+            .client
+            .local_node
+            .handle_chain_info_query(query)
+            .await?
+            .info;
+        let latest = info.requested_latest_checkpoint_height;
+        let next_checkpoint_height = latest
+            .unwrap_or(BlockHeight::ZERO)
+            .0
+            .saturating_add(interval.get());
+        let due = if info.next_block_height.0 >= next_checkpoint_height {
+            true
+        } else if let Some(latest) = latest {
+            self.checkpoint_epoch(latest)
+                .await?
+                .is_some_and(|epoch| epoch < info.epoch)
+        } else {
+            false
+        };
+        if !due {
+            return Ok(None);
+        }
+        info!(
+            chain_id = %self.chain_id,
+            height = %info.next_block_height,
+            epoch = %info.epoch,
+            "Publishing a periodic checkpoint",
+        );
+        Ok(Some(self.checkpoint().await?))
+    }
+
+    /// Returns the epoch of the checkpoint at the given height, if its certificate is
+    /// available locally.
+    async fn checkpoint_epoch(&self, height: BlockHeight) -> Result<Option<Epoch>, Error> {
+        let certificates = self
+            .client
+            .storage_client()
+            .read_certificates_by_heights(self.chain_id, &[height])
+            .await?;
+        Ok(certificates
+            .into_iter()
+            .next()
+            .flatten()
+            .map(|certificate| certificate.block().header.epoch))
+    }
+
     /// Closes the chain (and loses everything in it!!).
     /// Returns `None` if the chain was already closed.
     #[instrument(level = "trace")]
@@ -2680,14 +3291,32 @@ impl<Env: Environment> ChainClient<Env> {
         blobs: Vec<Blob>,
         module_id: ModuleId,
     ) -> Result<ClientOutcome<(ModuleId, ConfirmedBlockCertificate)>, Error> {
-        self.execute_operations(
-            vec![Operation::system(SystemOperation::PublishModule {
-                module_id,
-            })],
-            blobs,
-        )
-        .await?
-        .try_map(|certificate| Ok((module_id, certificate)))
+        self.publish_module_blobs_with_data(blobs, module_id, Vec::new())
+            .await
+    }
+
+    /// Publishes some module together with data blobs about it, e.g. attestations of its
+    /// provenance, in the same block.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[instrument(level = "trace", skip(blobs, module_id, data_blobs))]
+    pub async fn publish_module_blobs_with_data(
+        &self,
+        blobs: Vec<Blob>,
+        module_id: ModuleId,
+        data_blobs: Vec<Blob>,
+    ) -> Result<ClientOutcome<(ModuleId, ConfirmedBlockCertificate)>, Error> {
+        let operations = iter::once(Operation::system(SystemOperation::PublishModule {
+            module_id,
+        }))
+        .chain(data_blobs.iter().map(|blob| {
+            Operation::system(SystemOperation::PublishDataBlob {
+                blob_hash: blob.id().hash,
+            })
+        }))
+        .collect();
+        self.execute_operations(operations, blobs.into_iter().chain(data_blobs).collect())
+            .await?
+            .try_map(|certificate| Ok((module_id, certificate)))
     }
 
     /// Publishes some data blobs.
@@ -2823,6 +3452,128 @@ impl<Env: Environment> ChainClient<Env> {
         .await
     }
 
+    /// Adds and removes hosts that contracts and services can send HTTP requests to, by
+    /// starting a new epoch with the current committee of this admin chain and only this
+    /// change. Returns `None` if the update would not change the committee.
+    ///
+    /// If the committee changes concurrently, the update fails instead of undoing that
+    /// change.
+    #[instrument(level = "trace")]
+    pub async fn update_http_allow_list(
+        &self,
+        update: HttpAllowListUpdate,
+    ) -> Result<ClientOutcome<Option<ConfirmedBlockCertificate>>, Error> {
+        let committee = self.local_committee().await?;
+        let mut policy = committee.policy().clone();
+        policy.update_http_request_allow_list(&update);
+        if committee.policy() == &policy {
+            return Ok(ClientOutcome::Committed(None));
+        }
+        let committee = Committee::new(committee.validators().clone(), policy)?;
+        let blob = Blob::new(BlobContent::new_committee(bcs::to_bytes(&committee)?));
+        let blob_hash = blob.id().hash;
+        match self
+            .execute_operations(
+                vec![Operation::system(SystemOperation::Admin(
+                    AdminOperation::PublishCommitteeBlob { blob_hash },
+                ))],
+                vec![blob],
+            )
+            .await?
+        {
+            ClientOutcome::Committed(_) => {}
+            outcome @ ClientOutcome::WaitForTimeout(_) | outcome @ ClientOutcome::Conflict(_) => {
+                return Ok(outcome.map(Some))
+            }
+        }
+        let outcome = self
+            .execute_operation(SystemOperation::Admin(
+                AdminOperation::UpdateHttpAllowList { update, blob_hash },
+            ))
+            .await?;
+        Ok(outcome.map(Some))
+    }
+
+    /// Publishes a committee and proposes to start using it once the owners of this admin
+    /// chain have approved it during the voting period.
+    #[instrument(level = "trace", skip(committee))]
+    pub async fn propose_new_committee(
+        &self,
+        committee: Committee,
+        voting_period: TimeDelta,
+    ) -> Result<ClientOutcome<ConfirmedBlockCertificate>, Error> {
+        let blob = Blob::new(BlobContent::new_committee(bcs::to_bytes(&committee)?));
+        let blob_hash = blob.id().hash;
+        match self
+            .execute_operations(
+                vec![Operation::system(SystemOperation::Admin(
+                    AdminOperation::PublishCommitteeBlob { blob_hash },
+                ))],
+                vec![blob],
+            )
+            .await?
+        {
+            ClientOutcome::Committed(_) => {}
+            outcome @ ClientOutcome::WaitForTimeout(_) | outcome @ ClientOutcome::Conflict(_) => {
+                return Ok(outcome)
+            }
+        }
+        self.execute_operation(SystemOperation::Admin(AdminOperation::ProposeCommittee {
+            blob_hash,
+            voting_period,
+        }))
+        .await
+    }
+
+    /// Votes on a governance proposal of this admin chain.
+    #[instrument(level = "trace")]
+    pub async fn vote_on_governance_proposal(
+        &self,
+        proposal_id: u32,
+        approve: bool,
+    ) -> Result<ClientOutcome<ConfirmedBlockCertificate>, Error> {
+        self.execute_operation(SystemOperation::Admin(AdminOperation::VoteOnProposal {
+            proposal_id,
+            approve,
+        }))
+        .await
+    }
+
+    /// Resolves a governance proposal of this admin chain whose voting period is over,
+    /// starting to use its committee if it was approved.
+    #[instrument(level = "trace")]
+    pub async fn resolve_governance_proposal(
+        &self,
+        proposal_id: u32,
+    ) -> Result<ClientOutcome<ConfirmedBlockCertificate>, Error> {
+        self.execute_operation(SystemOperation::Admin(AdminOperation::ResolveProposal {
+            proposal_id,
+        }))
+        .await
+    }
+
+    /// Returns the unresolved governance proposals of this admin chain, by ID, together with
+    /// the chain's ownership, which determines how the votes are tallied.
+    ///
+    /// Does not synchronize with validators.
+    #[instrument(level = "trace")]
+    pub async fn governance_proposals(
+        &self,
+    ) -> Result<(BTreeMap<u32, GovernanceProposal>, ChainOwnership), Error> {
+        let chain = self.chain_state_view().await?;
+        let system = &chain.execution_state.system;
+        let mut proposals = BTreeMap::new();
+        system
+            .governance_proposals
+            .for_each_index_value(|proposal_id, proposal| {
+                proposals.insert(proposal_id, proposal.into_owned());
+                Ok(())
+            })
+            .await?;
+        let ownership = system.ownership.get().await?.clone();
+        Ok((proposals, ownership))
+    }
+
     /// Synchronizes the chain with the validators and creates blocks without any operations to
     /// process all incoming messages. This may require several blocks.
     ///
@@ -2916,6 +3667,116 @@ impl<Env: Environment> ChainClient<Env> {
             .await?)
     }
 
+    /// Returns the events of a stream of this chain that their application indexed under the
+    /// given field and key, skipping the first `start` matches and returning at most `limit`.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn indexed_events(
+        &self,
+        index_key: EventIndexKey,
+        start: u32,
+        limit: u32,
+    ) -> Result<Vec<IndexAndEvent>, Error> {
+        let indices = {
+            let chain = self.chain_state_view().await?;
+            match chain
+                .execution_state
+                .event_indexes
+                .try_load_entry(&index_key)
+                .await?
+            {
+                Some(log) => {
+                    let start = (start as usize).min(log.count());
+                    let end = start.saturating_add(limit as usize).min(log.count());
+                    log.read(start..end).await?
+                }
+                None => Vec::new(),
+            }
+        };
+        self.read_stream_events(&index_key.stream_id, indices).await
+    }
+
+    /// Returns the events of a stream of this chain that their application indexed under the
+    /// given field and key, starting at the event with index `start_index` and returning at
+    /// most `limit`.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn indexed_events_from_index(
+        &self,
+        index_key: EventIndexKey,
+        start_index: u32,
+        limit: u32,
+    ) -> Result<Vec<IndexAndEvent>, Error> {
+        let indices = {
+            let chain = self.chain_state_view().await?;
+            match chain
+                .execution_state
+                .event_indexes
+                .try_load_entry(&index_key)
+                .await?
+            {
+                Some(log) => {
+                    // The indices are increasing: find the first one at or after `start_index`.
+                    let (mut low, mut high) = (0, log.count());
+                    while low < high {
+                        let middle = low + (high - low) / 2;
+                        if log
+                            .get(middle)
+                            .await?
+                            .is_some_and(|index| index < start_index)
+                        {
+                            low = middle + 1;
+                        } else {
+                            high = middle;
+                        }
+                    }
+                    let end = low.saturating_add(limit as usize).min(log.count());
+                    log.read(low..end).await?
+                }
+                None => Vec::new(),
+            }
+        };
+        self.read_stream_events(&index_key.stream_id, indices).await
+    }
+
+    /// Reads the events of a stream of this chain with the given indices.
+    async fn read_stream_events(
+        &self,
+        stream_id: &StreamId,
+        indices: Vec<u32>,
+    ) -> Result<Vec<IndexAndEvent>, Error> {
+        let mut events = Vec::with_capacity(indices.len());
+        for index in indices {
+            let event_id = EventId {
+                chain_id: self.chain_id,
+                stream_id: stream_id.clone(),
+                index,
+            };
+            let event = self
+                .client
+                .storage_client()
+                .read_event(event_id.clone())
+                .await?
+                .ok_or_else(|| ViewError::NotFound(format!("event {event_id:?}")))?;
+            events.push(IndexAndEvent {
+                index,
+                event: event.to_vec(),
+            });
+        }
+        Ok(events)
+    }
+
+    /// Returns the index the next event of the given stream of this chain will have.
+    #[instrument(level = "trace", skip(self))]
+    pub async fn next_event_index(&self, stream_id: &StreamId) -> Result<u32, Error> {
+        let chain = self.chain_state_view().await?;
+        Ok(chain
+            .execution_state
+            .system
+            .stream_event_counts
+            .get(stream_id)
+            .await?
+            .unwrap_or(0))
+    }
+
     /// Deprecates all configurations of voting rights up to the given one (admin chains only).
     /// Emits a `RemoveCommittee` event for every still-active epoch up to and including
     /// `revoked_epoch`.
@@ -2980,6 +3841,29 @@ impl<Env: Environment> ChainClient<Env> {
             .map(|b| b.into_std())
     }
 
+    /// Reads the confirmed block with the given hash from storage, or downloads its
+    /// certificate from the validators and stores it if it is missing locally.
+    #[instrument(level = "trace", skip(hash))]
+    pub async fn read_or_download_confirmed_block(
+        &self,
+        hash: CryptoHash,
+    ) -> Result<Arc<ConfirmedBlock>, Error> {
+        if let Some(block) = self
+            .client
+            .storage_client()
+            .read_confirmed_block(hash)
+            .await?
+        {
+            return Ok(block.into_std());
+        }
+        debug!(%hash, "Confirmed block missing locally; downloading it from validators");
+        let validators = self.client.validator_nodes().await?;
+        self.client
+            .download_pre_checkpoint_blocks(&validators, &[hash])
+            .await?;
+        self.read_confirmed_block(hash).await
+    }
+
     /// Reads the confirmed block certificate with the given hash from storage.
     #[instrument(level = "trace", skip(hash))]
     pub async fn read_certificate(
@@ -3300,8 +4184,9 @@ impl<Env: Environment> ChainClient<Env> {
             };
             let nodes = self
                 .client
-                .validator_node_provider()
                 .make_nodes(&committee)?
+                .into_iter()
+                .map(|remote_node| (remote_node.public_key, remote_node.node))
                 .collect::<HashMap<_, _>>();
             (nodes, self.client.local_node.clone())
         };
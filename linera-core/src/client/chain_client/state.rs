@@ -2,8 +2,9 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::sync::Arc;
+use std::{collections::VecDeque, sync::Arc};
 
+use linera_base::{crypto::CryptoHash, data_types::Amount};
 use tokio::sync::Mutex;
 
 use super::super::PendingProposal;
@@ -23,14 +24,22 @@ pub struct State {
     /// If true, only download blocks for this chain without fetching manager values.
     /// Use this for chains we're interested in observing but don't intend to propose blocks for.
     follow_only: bool,
+
+    /// The hashes and fees of the latest blocks this client executed on the chain, oldest
+    /// first.
+    recent_fees: Arc<std::sync::Mutex<VecDeque<(CryptoHash, Amount)>>>,
 }
 
+/// The number of blocks whose fees are remembered in [`State::recent_fees`].
+const RECENT_FEES_CAPACITY: usize = 32;
+
 impl State {
     /// Creates a new chain client [`State`] with the given pending proposal and follow-only mode.
     pub fn new(pending_proposal: Option<PendingProposal>, follow_only: bool) -> State {
         State {
             proposal_mutex: Arc::new(Mutex::new(pending_proposal)),
             follow_only,
+            recent_fees: Arc::default(),
         }
     }
 
@@ -40,11 +49,12 @@ impl State {
     }
 
     /// Returns a new `State` with the given `follow_only` value, sharing the same
-    /// proposal mutex.
+    /// proposal mutex and fee history.
     pub(crate) fn with_follow_only(&self, follow_only: bool) -> State {
         State {
             proposal_mutex: Arc::clone(&self.proposal_mutex),
             follow_only,
+            recent_fees: Arc::clone(&self.recent_fees),
         }
     }
 
@@ -52,4 +62,29 @@ impl State {
     pub(super) fn proposal_mutex(&self) -> Arc<Mutex<Option<PendingProposal>>> {
         Arc::clone(&self.proposal_mutex)
     }
+
+    /// Records the fees of a block executed on this chain.
+    pub(super) fn record_fees(&self, block_hash: CryptoHash, fees: Amount) {
+        let mut recent_fees = self.recent_fees.lock().unwrap();
+        if recent_fees.len() == RECENT_FEES_CAPACITY {
+            recent_fees.pop_front();
+        }
+        recent_fees.push_back((block_hash, fees));
+    }
+
+    /// Returns the fees of the latest blocks executed on this chain, oldest first.
+    pub(super) fn recent_fees(&self) -> Vec<Amount> {
+        self.recent_fees
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, fees)| *fees)
+            .collect()
+    }
+
+    /// Returns the hashes and fees of the latest blocks executed on this chain, oldest
+    /// first.
+    pub(super) fn recent_block_fees(&self) -> Vec<(CryptoHash, Amount)> {
+        self.recent_fees.lock().unwrap().iter().copied().collect()
+    }
 }
@@ -42,13 +42,14 @@ use linera_chain::{
     },
     ChainError, ChainIdSet,
 };
-use linera_execution::{committee::Committee, ExecutionError};
+use linera_execution::{committee::Committee, ExecutionError, ResourceTracker};
 use linera_storage::{Arc as CacheArc, Clock as _, ResultReadCertificates, Storage as _};
 use rand::seq::SliceRandom;
 use received_log::ReceivedLogs;
 use serde::{Deserialize, Serialize};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, instrument, trace, warn};
+use validator_addresses::ValidatorAddresses;
 
 use crate::{
     data_types::{ChainInfo, ChainInfoQuery, ChainInfoResponse},
@@ -75,6 +76,7 @@ pub mod requests_scheduler;
 
 pub use requests_scheduler::{RequestsScheduler, RequestsSchedulerConfig, ScoringWeights};
 mod received_log;
+mod validator_addresses;
 mod validator_trackers;
 
 #[cfg(with_metrics)]
@@ -82,7 +84,8 @@ mod metrics {
     use std::sync::LazyLock;
 
     use linera_base::prometheus_util::{
-        exponential_bucket_latencies, register_histogram_vec, register_int_counter_vec,
+        exponential_bucket_interval, exponential_bucket_latencies, register_histogram_vec,
+        register_int_counter_vec,
     };
     use prometheus::{HistogramVec, IntCounterVec};
 
@@ -132,6 +135,15 @@ mod metrics {
         )
     });
 
+    pub static BLOCK_FEES: LazyLock<HistogramVec> = LazyLock::new(|| {
+        register_histogram_vec(
+            "block_fees",
+            "Fees charged for the blocks staged by the client for proposal, in tokens",
+            &[],
+            exponential_bucket_interval(1e-6, 1e3),
+        )
+    });
+
     pub static BLOCK_STAGING_FAILURES_TOTAL: LazyLock<IntCounterVec> = LazyLock::new(|| {
         register_int_counter_vec(
             "block_staging_failures_total",
@@ -364,6 +376,8 @@ pub struct Client<Env: Environment> {
     chains: papaya::HashMap<ChainId, chain_client::State>,
     /// Configuration options.
     options: chain_client::Options,
+    /// The addresses used to reach the validators.
+    validator_addresses: ValidatorAddresses,
 }
 
 /// Boxed future returned by `receive_sender_certificate`. It is `Send` off the `web`
@@ -426,6 +440,10 @@ impl<Env: Environment> Client<Env> {
             requests_scheduler_config,
             clock,
         ));
+        let validator_addresses = ValidatorAddresses::new(
+            options.follow_validator_address_updates,
+            options.pinned_validator_addresses.clone(),
+        );
 
         Self {
             environment,
@@ -436,6 +454,7 @@ impl<Env: Environment> Client<Env> {
             chain_modes,
             notifier: Arc::new(ChannelNotifier::default()),
             options,
+            validator_addresses,
         }
     }
 
@@ -1128,31 +1147,44 @@ impl<Env: Environment> Client<Env> {
     /// through `handle_certificate_with_retry` ensures the sender block's own
     /// blob/event dependencies (e.g. a `ChainDescription` blob or an admin-chain
     /// epoch event for a revoked epoch) get resolved before the cert is accepted.
+    /// If a validator fails to provide a block, or the block fails to be processed, the
+    /// next validator is tried.
     async fn download_pre_checkpoint_blocks(
         &self,
         nodes: &[RemoteNode<Env::ValidatorNode>],
         hashes: &[CryptoHash],
     ) -> Result<(), chain_client::Error> {
-        for hash in hashes {
+        'hashes: for hash in hashes {
             let mut last_error = None;
             for node in nodes {
-                match node.node.download_certificate(*hash).await {
-                    Ok(certificate) => {
+                let result = match node.node.download_certificate(*hash).await {
+                    Ok(certificate) if certificate.hash() == *hash => {
                         Box::pin(self.handle_certificate_with_retry(
                             &certificate,
                             nodes,
                             ProcessConfirmedBlockMode::Auto,
                         ))
-                        .await?;
-                        last_error = None;
-                        break;
+                        .await
+                    }
+                    Ok(_) => Err(NodeError::UnexpectedCertificateValue.into()),
+                    Err(error) => Err(error.into()),
+                };
+                match result {
+                    Ok(_) => continue 'hashes,
+                    Err(error) => {
+                        debug!(
+                            %hash,
+                            validator = node.address(),
+                            %error,
+                            "Failed to obtain a block from a validator; trying the next one"
+                        );
+                        last_error = Some(error);
                     }
-                    Err(error) => last_error = Some(error),
                 }
             }
-            if let Some(error) = last_error {
-                return Err(error.into());
-            }
+            return Err(
+                last_error.unwrap_or(chain_client::Error::ReadCertificatesError(vec![*hash]))
+            );
         }
         Ok(())
     }
@@ -1176,6 +1208,85 @@ impl<Env: Environment> Client<Env> {
             .await
     }
 
+    /// Processes a confirmed block certificate received from a block exporter feed rather
+    /// than from the validators, after writing the blobs that it needs.
+    ///
+    /// The certificate is checked against the committee of its epoch, as usual, and the
+    /// subscribers to the chain are notified of the new block.
+    pub async fn process_exported_certificate(
+        &self,
+        certificate: ConfirmedBlockCertificate,
+        blobs: &[Blob],
+    ) -> Result<ChainInfoResponse, LocalNodeError> {
+        self.local_node.storage_client().write_blobs(blobs).await?;
+        self.handle_certificate::<ConfirmedBlock>(certificate).await
+    }
+
+    /// Returns the certificate of the confirmed block with the given hash without processing
+    /// it, reading it from local storage or else downloading it from the validators.
+    ///
+    /// Downloaded certificates are checked against the committee of their epoch.
+    pub async fn fetch_certificate(
+        &self,
+        hash: CryptoHash,
+    ) -> Result<ConfirmedBlockCertificate, chain_client::Error> {
+        if let Some(certificate) = self.storage_client().read_certificate(hash).await? {
+            return Ok((*certificate).clone());
+        }
+        let mut last_error = None;
+        for node in self.validator_nodes().await? {
+            match node.node.download_certificate(hash).await {
+                Ok(certificate) if certificate.hash() == hash => {
+                    self.check_certificate(&certificate).await?.into_result()?;
+                    return Ok(certificate);
+                }
+                Ok(_) => last_error = Some(NodeError::UnexpectedCertificateValue),
+                Err(error) => last_error = Some(error),
+            }
+        }
+        Err(last_error.map_or(
+            chain_client::Error::ReadCertificatesError(vec![hash]),
+            Into::into,
+        ))
+    }
+
+    /// Returns the certificate of the confirmed block at the given height of a chain without
+    /// processing it, reading it from local storage or else downloading it from the
+    /// validators.
+    ///
+    /// Downloaded certificates are checked against the committee of their epoch.
+    pub async fn fetch_certificate_at_height(
+        &self,
+        chain_id: ChainId,
+        height: BlockHeight,
+    ) -> Result<ConfirmedBlockCertificate, chain_client::Error> {
+        if let Some(certificate) = self
+            .try_read_local_certificate(chain_id, height, None)
+            .await?
+        {
+            return Ok((*certificate).clone());
+        }
+        let mut last_error = None;
+        for node in self.validator_nodes().await? {
+            match node
+                .download_certificates_by_heights(chain_id, vec![height])
+                .await
+            {
+                Ok(certificates) => {
+                    if let Some(certificate) = certificates.into_iter().next() {
+                        self.check_certificate(&certificate).await?.into_result()?;
+                        return Ok(certificate);
+                    }
+                }
+                Err(error) => last_error = Some(error),
+            }
+        }
+        Err(last_error.map_or(
+            chain_client::Error::MissingCertificateAtHeight { chain_id, height },
+            Into::into,
+        ))
+    }
+
     /// Obtains the committee for the latest epoch on the admin chain.
     pub async fn admin_committee(&self) -> Result<(Epoch, Arc<Committee>), LocalNodeError> {
         let info = self.local_node.chain_info(self.admin_chain_id).await?;
@@ -1186,6 +1297,7 @@ impl<Env: Environment> Client<Env> {
             .storage_client()
             .get_or_load_committee_by_hash(hash)
             .await?;
+        self.validator_addresses.update_epoch(info.epoch);
         Ok((info.epoch, committee))
     }
 
@@ -1197,14 +1309,15 @@ impl<Env: Environment> Client<Env> {
         Ok(self.make_nodes(&committee)?)
     }
 
-    /// Creates a [`RemoteNode`] for each validator in the committee.
+    /// Creates a [`RemoteNode`] for each validator in the committee, using the pinned or
+    /// latest announced address of each validator if any.
     fn make_nodes(
         &self,
         committee: &Committee,
     ) -> Result<Vec<RemoteNode<Env::ValidatorNode>>, NodeError> {
         Ok(self
             .validator_node_provider()
-            .make_nodes(committee)?
+            .make_nodes_from_list(self.validator_addresses.addresses(committee))?
             .map(|(public_key, node)| RemoteNode { public_key, node })
             .collect())
     }
@@ -1271,6 +1384,24 @@ impl<Env: Environment> Client<Env> {
             .into_std())
     }
 
+    /// Returns the data blob with the given hash, fetching it from the current validators
+    /// if it is not available locally.
+    pub async fn get_data_blob(&self, hash: CryptoHash) -> Result<Arc<Blob>, chain_client::Error> {
+        let blob_id = BlobId::new(hash, BlobType::Data);
+        let blob = self.local_node.storage_client().read_blob(blob_id).await?;
+        if let Some(blob) = blob {
+            return Ok(blob.into_std());
+        }
+        Box::pin(self.synchronize_chain_state(self.admin_chain_id)).await?;
+        let nodes = self.validator_nodes().await?;
+        Ok(self
+            .update_local_node_with_blobs_from(vec![blob_id], &nodes)
+            .await?
+            .pop()
+            .unwrap() // Returns exactly as many blobs as passed-in IDs.
+            .into_std())
+    }
+
     /// Returns the `ApplicationDescription` of the given application, fetching its
     /// description blob from the validators if it is not available locally.
     pub async fn get_application_description(
@@ -2150,6 +2281,9 @@ impl<Env: Environment> Client<Env> {
         };
         let query = query.with_latest_checkpoint_height();
         let remote_info = remote_node.handle_chain_info_query(query).await?;
+        if let Some(update) = &remote_info.validator_address {
+            self.validator_addresses.record(remote_node.public_key, update);
+        }
 
         // If the validator advertises a checkpoint and our local tip is below it, fetch
         // the checkpoint cert and blob and apply the cert directly. The chain worker's
@@ -2451,7 +2585,8 @@ impl<Env: Environment> Client<Env> {
         round: Option<u32>,
         published_blobs: Vec<Blob>,
         policy: BundleExecutionPolicy,
-    ) -> Result<(Block, ChainInfoResponse, HashSet<ChainId>), chain_client::Error> {
+    ) -> Result<(Block, ChainInfoResponse, ResourceTracker, HashSet<ChainId>), chain_client::Error>
+    {
         let mut events = EventSetDownloader::new(self);
         loop {
             let result = self
@@ -2486,14 +2621,14 @@ impl<Env: Environment> Client<Env> {
                 };
                 self.notifier.notify(&[notification]);
             }
-            let (
-                _modified_block,
+            let (_modified_block, executed_block, response, resource_tracker, never_reject_origins) =
+                result?;
+            return Ok((
                 executed_block,
                 response,
-                _resource_tracker,
+                resource_tracker,
                 never_reject_origins,
-            ) = result?;
-            return Ok((executed_block, response, never_reject_origins));
+            ));
         }
     }
 }
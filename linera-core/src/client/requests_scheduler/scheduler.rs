@@ -72,6 +72,15 @@ pub(super) mod metrics {
         )
     });
 
+    /// Counter of requests sent to further validators because earlier ones failed or were
+    /// slow to respond.
+    pub(super) static REQUEST_RETRY_TOTAL: LazyLock<IntCounter> = LazyLock::new(|| {
+        register_int_counter(
+            "requests_scheduler_request_retry_total",
+            "Number of requests sent to further validators after the first one",
+        )
+    });
+
     /// Counter for requests that were resolved from the response cache.
     pub(super) static REQUEST_CACHE_DEDUPLICATION: LazyLock<IntCounter> = LazyLock::new(|| {
         register_int_counter(
@@ -768,6 +777,15 @@ impl<Env: Environment> RequestsScheduler<Env> {
         F: Fn(RemoteNode<Env::ValidatorNode>) -> Fut,
         Fut: Future<Output = Result<T, NodeError>> + 'static,
     {
+        #[cfg(with_metrics)]
+        let attempts = std::sync::atomic::AtomicUsize::new(0);
+        let operation = move |peer: RemoteNode<Env::ValidatorNode>| {
+            #[cfg(with_metrics)]
+            if attempts.fetch_add(1, std::sync::atomic::Ordering::Relaxed) > 0 {
+                metrics::REQUEST_RETRY_TOTAL.inc();
+            }
+            operation(peer)
+        };
         // Source additional peers from the in-flight tracker's alternative queue (populated by
         // concurrent deduplicated requests), staggering with a linearly-growing delay.
         crate::client::hedged_fan_out(
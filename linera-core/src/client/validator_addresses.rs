@@ -0,0 +1,242 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use linera_base::{crypto::ValidatorPublicKey, data_types::Epoch};
+use linera_execution::committee::Committee;
+use tracing::{info, warn};
+
+use crate::data_types::{SignedValidatorAddress, ValidatorAddressUpdate};
+
+/// The network addresses used to reach the validators, which may differ from those of the
+/// committee: validators can announce new addresses with signed updates, and the client
+/// can pin the addresses of some validators.
+///
+/// Updates are only kept in memory: validators keep including their latest one in their
+/// chain info responses, so the client learns it again after a restart. Updates from epochs
+/// before the latest known one are dropped, since the newer committee publishes its own
+/// addresses.
+pub(super) struct ValidatorAddresses {
+    /// Whether to use the addresses announced by the validators.
+    follow_updates: bool,
+    /// The addresses used regardless of the committee and of the updates.
+    pinned: BTreeMap<ValidatorPublicKey, String>,
+    /// The latest known epoch, and the latest update received from each validator.
+    updates: Mutex<(Epoch, BTreeMap<ValidatorPublicKey, ValidatorAddressUpdate>)>,
+}
+
+impl ValidatorAddresses {
+    pub(super) fn new(follow_updates: bool, pinned: BTreeMap<ValidatorPublicKey, String>) -> Self {
+        Self {
+            follow_updates,
+            pinned,
+            updates: Mutex::default(),
+        }
+    }
+
+    /// Records that the committee of `epoch` is known, dropping the updates from earlier
+    /// epochs.
+    pub(super) fn update_epoch(&self, epoch: Epoch) {
+        let (latest_epoch, updates) = &mut *self.updates.lock().unwrap();
+        if epoch > *latest_epoch {
+            *latest_epoch = epoch;
+            updates.retain(|_, update| update.epoch >= epoch);
+        }
+    }
+
+    /// Records an update received from the validator `sender`, if it is a newer valid
+    /// update of its own address, not from an earlier epoch than the latest known one.
+    /// Returns whether it was accepted.
+    pub(super) fn record(
+        &self,
+        sender: ValidatorPublicKey,
+        update: &SignedValidatorAddress,
+    ) -> bool {
+        if !self.follow_updates || update.update.public_key != sender {
+            return false;
+        }
+        let (latest_epoch, updates) = &mut *self.updates.lock().unwrap();
+        if update.update.epoch < *latest_epoch
+            || updates.get(&sender).is_some_and(|latest| {
+                (latest.epoch, latest.version) >= (update.update.epoch, update.update.version)
+            })
+        {
+            return false;
+        }
+        if let Err(error) = update.check() {
+            warn!(validator = %sender, %error, "Invalid validator address update");
+            return false;
+        }
+        info!(
+            validator = %sender,
+            address = %update.update.network_address,
+            epoch = %update.update.epoch,
+            version = update.update.version,
+            "Using the new address announced by the validator"
+        );
+        updates.insert(sender, update.update.clone());
+        true
+    }
+
+    /// Returns the address to use for each validator of the committee: the pinned one, or
+    /// else the latest announced one, or else the committee's.
+    pub(super) fn addresses(&self, committee: &Committee) -> Vec<(ValidatorPublicKey, String)> {
+        let (_, updates) = &*self.updates.lock().unwrap();
+        committee
+            .validator_addresses()
+            .map(|(public_key, address)| {
+                let address = self
+                    .pinned
+                    .get(&public_key)
+                    .or_else(|| {
+                        updates
+                            .get(&public_key)
+                            .map(|update| &update.network_address)
+                    })
+                    .map_or(address, String::as_str);
+                (public_key, address.to_owned())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use linera_base::{
+        crypto::{AccountPublicKey, ValidatorKeypair, ValidatorPublicKey},
+        data_types::Epoch,
+    };
+    use linera_execution::{
+        committee::{Committee, ValidatorState},
+        ResourceControlPolicy,
+    };
+
+    use super::ValidatorAddresses;
+    use crate::data_types::{SignedValidatorAddress, ValidatorAddressUpdate};
+
+    fn committee(validators: &[(ValidatorPublicKey, &str)]) -> Committee {
+        let validators = validators
+            .iter()
+            .map(|(public_key, address)| {
+                let state = ValidatorState {
+                    network_address: address.to_string(),
+                    votes: 1,
+                    account_public_key: AccountPublicKey::test_key(0),
+                };
+                (*public_key, state)
+            })
+            .collect();
+        Committee::new(validators, ResourceControlPolicy::default()).unwrap()
+    }
+
+    fn update(
+        keypair: &ValidatorKeypair,
+        address: &str,
+        epoch: u32,
+        version: u64,
+    ) -> SignedValidatorAddress {
+        let update = ValidatorAddressUpdate {
+            public_key: keypair.public_key,
+            network_address: address.to_string(),
+            epoch: Epoch(epoch),
+            version,
+        };
+        SignedValidatorAddress::new(update, &keypair.secret_key)
+    }
+
+    #[test]
+    fn test_validator_address_updates() {
+        let validator = ValidatorKeypair::generate();
+        let other = ValidatorKeypair::generate();
+        let committee = committee(&[
+            (validator.public_key, "grpc:old:443"),
+            (other.public_key, "grpc:other:443"),
+        ]);
+        let addresses = ValidatorAddresses::new(true, BTreeMap::new());
+
+        // Only the validator itself can announce its address, with a valid signature.
+        assert!(!addresses.record(other.public_key, &update(&validator, "grpc:evil:443", 0, 1)));
+        let mut forged = update(&validator, "grpc:new:443", 0, 1);
+        forged.update.network_address = "grpc:evil:443".to_string();
+        assert!(!addresses.record(validator.public_key, &forged));
+
+        assert!(addresses.record(
+            validator.public_key,
+            &update(&validator, "grpc:new:443", 0, 2)
+        ));
+        // Older updates are ignored.
+        assert!(!addresses.record(
+            validator.public_key,
+            &update(&validator, "grpc:older:443", 0, 1)
+        ));
+        assert_eq!(
+            addresses.addresses(&committee),
+            committee_order(
+                &committee,
+                [
+                    (validator.public_key, "grpc:new:443"),
+                    (other.public_key, "grpc:other:443"),
+                ]
+            )
+        );
+
+        // Updates from a later epoch take precedence regardless of their version, and once
+        // that epoch is known, updates from earlier epochs are dropped.
+        assert!(addresses.record(
+            validator.public_key,
+            &update(&validator, "grpc:newer:443", 1, 1)
+        ));
+        assert!(!addresses.record(
+            validator.public_key,
+            &update(&validator, "grpc:older:443", 0, 3)
+        ));
+        addresses.update_epoch(Epoch(2));
+        assert_eq!(
+            addresses.addresses(&committee),
+            committee_order(
+                &committee,
+                [
+                    (validator.public_key, "grpc:old:443"),
+                    (other.public_key, "grpc:other:443"),
+                ]
+            )
+        );
+        assert!(!addresses.record(
+            validator.public_key,
+            &update(&validator, "grpc:newer:443", 1, 2)
+        ));
+
+        // Pinned addresses take precedence, and updates can be ignored.
+        let pinned = BTreeMap::from([(other.public_key, "grpc:pinned:443".to_string())]);
+        let addresses = ValidatorAddresses::new(false, pinned);
+        assert!(!addresses.record(
+            validator.public_key,
+            &update(&validator, "grpc:new:443", 0, 3)
+        ));
+        assert_eq!(
+            addresses.addresses(&committee),
+            committee_order(
+                &committee,
+                [
+                    (validator.public_key, "grpc:old:443"),
+                    (other.public_key, "grpc:pinned:443"),
+                ]
+            )
+        );
+    }
+
+    /// Returns the expected addresses in the order of the committee.
+    fn committee_order(
+        committee: &Committee,
+        expected: [(ValidatorPublicKey, &str); 2],
+    ) -> Vec<(ValidatorPublicKey, String)> {
+        let expected = BTreeMap::from(expected);
+        committee
+            .validator_addresses()
+            .map(|(public_key, _)| (public_key, expected[&public_key].to_string()))
+            .collect()
+    }
+}
@@ -8,18 +8,18 @@ use custom_debug_derive::Debug;
 use linera_base::{
     crypto::{
         BcsSignable, CryptoError, CryptoHash, ValidatorPublicKey, ValidatorSecretKey,
-        ValidatorSignature,
+        ValidatorSignature, ValidatorSigner,
     },
-    data_types::{Amount, BlockHeight, ChainDescription, Epoch, Round, Timestamp},
+    data_types::{Amount, Blob, BlockHeight, ChainDescription, Epoch, Round, Timestamp},
     identifiers::{AccountOwner, ChainId, StreamId},
 };
 use linera_chain::{
-    data_types::{ChainAndHeight, IncomingBundle, MessageBundle},
+    data_types::{ChainAndHeight, IncomingBundle, MessageBundle, ProposalContent},
     manager::ChainManagerInfo,
     types::ConfirmedBlockCertificate,
     ChainStateView,
 };
-use linera_execution::ExecutionRuntimeContext;
+use linera_execution::{ExecutionRuntimeContext, ResourceTracker};
 use linera_storage::ChainRuntimeContext;
 use linera_views::{context::Context, ViewError};
 use serde::{Deserialize, Serialize};
@@ -185,6 +185,10 @@ pub struct ChainInfo {
     /// `None` if no such block exists or the field was not requested.
     #[debug(skip_if = Option::is_none)]
     pub requested_latest_checkpoint_height: Option<BlockHeight>,
+    /// The latest network address announced by the validator answering the query, if it
+    /// changed since the current committee was created.
+    #[debug(skip_if = Option::is_none)]
+    pub validator_address: Option<SignedValidatorAddress>,
 }
 
 impl ChainInfo {
@@ -245,12 +249,25 @@ pub enum CrossChainRequest {
     /// Request the sender to revert a previous confirmation and resend bundles
     /// starting from the given height. This is used to recover from state
     /// inconsistencies where the recipient lost persisted state after a
-    /// confirmation was sent.
+    /// confirmation was sent, and to ask for bundles that the recipient refused
+    /// while its inbox was full.
     RevertConfirm {
         sender: ChainId,
         recipient: ChainId,
         retransmit_from: BlockHeight,
     },
+    /// Notify the recipient of confirmed blocks of the sender with messages for it, without
+    /// the messages: the recipient stores references to the blocks in its inbox, and reads
+    /// them from storage once the messages are consumed. Blocks must be given by increasing
+    /// heights.
+    NotifyRecipient {
+        sender: ChainId,
+        recipient: ChainId,
+        /// The heights and hashes of the blocks.
+        blocks: Vec<(BlockHeight, CryptoHash)>,
+        /// As in `UpdateRecipient`.
+        previous_height: Option<BlockHeight>,
+    },
 }
 
 impl CrossChainRequest {
@@ -258,7 +275,7 @@ impl CrossChainRequest {
     pub fn target_chain_id(&self) -> ChainId {
         use CrossChainRequest::*;
         match self {
-            UpdateRecipient { recipient, .. } => *recipient,
+            UpdateRecipient { recipient, .. } | NotifyRecipient { recipient, .. } => *recipient,
             ConfirmUpdatedRecipient { sender, .. } => *sender,
             RevertConfirm { sender, .. } => *sender,
         }
@@ -271,6 +288,10 @@ impl CrossChainRequest {
                 debug_assert!(bundles.windows(2).all(|w| w[0].1.height <= w[1].1.height));
                 matches!(bundles.first(), Some((_, h)) if h.height <= height)
             }
+            CrossChainRequest::NotifyRecipient { blocks, .. } => {
+                debug_assert!(blocks.windows(2).all(|w| w[0].0 <= w[1].0));
+                matches!(blocks.first(), Some((h, _)) if *h <= height)
+            }
             _ => false,
         }
     }
@@ -303,6 +324,7 @@ impl ChainInfo {
             requested_received_log: Vec::new(),
             requested_previous_event_blocks: BTreeMap::new(),
             requested_latest_checkpoint_height: None,
+            validator_address: None,
         })
     }
 }
@@ -315,6 +337,20 @@ impl ChainInfoResponse {
         Self { info, signature }
     }
 
+    /// Creates a response from the given [`ChainInfo`], signing it with the validator's
+    /// signer if there is one.
+    pub async fn signed_by(
+        info: impl Into<ChainInfo>,
+        signer: Option<&dyn ValidatorSigner>,
+    ) -> Result<Self, CryptoError> {
+        let info = Box::new(info.into());
+        let signature = match signer {
+            Some(signer) => Some(signer.sign(&*info).await?),
+            None => None,
+        };
+        Ok(Self { info, signature })
+    }
+
     /// Signs the [`ChainInfo`] stored inside this [`ChainInfoResponse`] with the provided
     /// [`ValidatorSecretKey`].
     pub fn sign(&mut self, key_pair: &ValidatorSecretKey) {
@@ -332,6 +368,58 @@ impl ChainInfoResponse {
 
 impl BcsSignable<'_> for ChainInfo {}
 
+/// A new network address of a validator, e.g. after moving to a new load balancer.
+///
+/// Validators can change their address this way without a new committee: clients that
+/// receive the signed update use the new address instead of the one in the committee.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(with_testing, derive(Eq, PartialEq))]
+pub struct ValidatorAddressUpdate {
+    /// The validator whose address changes.
+    pub public_key: ValidatorPublicKey,
+    /// The new network address, in the format of the committee's addresses.
+    pub network_address: String,
+    /// The epoch of the committee the update applies to. Clients ignore updates from
+    /// earlier epochs, since a newer committee publishes its own addresses.
+    pub epoch: Epoch,
+    /// Increases with each update, so that clients ignore older ones from the same epoch.
+    pub version: u64,
+}
+
+impl BcsSignable<'_> for ValidatorAddressUpdate {}
+
+/// A [`ValidatorAddressUpdate`] signed by the validator.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(with_testing, derive(Eq, PartialEq))]
+pub struct SignedValidatorAddress {
+    /// The update.
+    pub update: ValidatorAddressUpdate,
+    /// The validator's signature of the update.
+    pub signature: ValidatorSignature,
+}
+
+impl SignedValidatorAddress {
+    /// Signs the update with the validator's key.
+    pub fn new(update: ValidatorAddressUpdate, secret_key: &ValidatorSecretKey) -> Self {
+        let signature = ValidatorSignature::new(&update, secret_key);
+        Self { update, signature }
+    }
+
+    /// Signs the update with the validator's signer.
+    pub async fn signed_by(
+        update: ValidatorAddressUpdate,
+        signer: &dyn ValidatorSigner,
+    ) -> Result<Self, CryptoError> {
+        let signature = signer.sign(&update).await?;
+        Ok(Self { update, signature })
+    }
+
+    /// Verifies that the update is signed by the validator it is about.
+    pub fn check(&self) -> Result<(), CryptoError> {
+        self.signature.check(&self.update, self.update.public_key)
+    }
+}
+
 /// Request for downloading certificates by heights.
 #[derive(Clone)]
 pub struct CertificatesByHeightRequest {
@@ -391,6 +479,92 @@ impl fmt::Debug for CertificatesByHeightRequest {
     }
 }
 
+/// A recommended budget for the fees of a block.
+#[derive(Clone, Debug, Serialize, Deserialize, async_graphql::SimpleObject)]
+pub struct FeeSuggestion {
+    /// The fees charged for a block with the operations, on top of the current chain state.
+    pub estimated: Amount,
+    /// The highest fees of the latest blocks this client executed on the chain, if any.
+    pub recent_maximum: Option<Amount>,
+    /// The recommended budget: the larger of the above, plus a safety margin.
+    pub suggested: Amount,
+    /// The current balance of the chain, which pays for the fees.
+    pub chain_balance: Amount,
+}
+
+/// The resources used by a block that was executed locally without being proposed.
+#[derive(Clone, Debug)]
+pub struct DryRunOutcome {
+    /// The resources used by the block, including the fees charged for them.
+    pub resources: ResourceTracker,
+    /// The balance of the chain before the block, which pays for the fees.
+    pub chain_balance: Amount,
+}
+
+/// The progress of [`ChainClient::execute_operations_in_blocks`], reported after each
+/// committed block.
+///
+/// [`ChainClient::execute_operations_in_blocks`]: crate::client::ChainClient::execute_operations_in_blocks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockPackingProgress {
+    /// The height of the block that was committed.
+    pub height: BlockHeight,
+    /// The number of operations in that block.
+    pub block_operations: usize,
+    /// The number of operations committed so far, including those of that block.
+    pub executed_operations: usize,
+    /// The number of operations still to be executed.
+    pub remaining_operations: usize,
+}
+
+/// A block proposal prepared by [`ChainClient::prepare_unsigned_proposal`], for its owner
+/// to sign elsewhere, e.g. on an air-gapped machine.
+///
+/// [`ChainClient::prepare_unsigned_proposal`]: crate::client::ChainClient::prepare_unsigned_proposal
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnsignedBlockProposal {
+    /// The owner who must sign the proposal.
+    pub owner: AccountOwner,
+    /// The content of the proposal, which the signature covers.
+    pub content: ProposalContent,
+    /// The blobs published by the block.
+    #[debug(skip_if = Vec::is_empty)]
+    pub blobs: Vec<Blob>,
+}
+
+impl UnsignedBlockProposal {
+    /// Returns the hash that the owner signs.
+    pub fn signing_hash(&self) -> CryptoHash {
+        CryptoHash::new(&self.content)
+    }
+}
+
+/// A pending proposal found by [`ChainClient::resolve_orphaned_proposal`] to have been
+/// overtaken by a different block, certified by the validators.
+///
+/// [`ChainClient::resolve_orphaned_proposal`]: crate::client::ChainClient::resolve_orphaned_proposal
+#[derive(Debug)]
+pub enum OrphanedProposal {
+    /// A different block was certified at the height of the pending proposal. The pending
+    /// proposal was discarded and its operations, if any, were proposed again.
+    Resolved {
+        /// The height of the discarded proposal.
+        height: BlockHeight,
+        /// The hash of the block certified at that height.
+        certified_hash: CryptoHash,
+        /// The outcome of proposing the operations again, or `None` if there were none.
+        requeued: Option<ClientOutcome<ConfirmedBlockCertificate>>,
+    },
+    /// The pending proposal conflicts with the certified blocks in a way that is not safe
+    /// to resolve automatically.
+    Unsafe {
+        /// The height of the pending proposal.
+        height: BlockHeight,
+        /// What the conflict is, and what happened to the pending proposal.
+        reason: String,
+    },
+}
+
 /// The outcome of trying to commit a list of operations to the chain.
 #[derive(Debug)]
 pub enum ClientOutcome<T> {
@@ -6,9 +6,11 @@ use std::ops::Deref;
 use futures::{Stream, StreamExt as _, TryStreamExt as _};
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{BlockHeight, ChainDescription, Epoch, Timestamp},
-    identifiers::{AccountOwner, ChainId},
+    data_types::{Blob, BlockHeight, ChainDescription, Epoch, Timestamp},
+    identifiers::{AccountOwner, BlobId, ChainId},
 };
+use linera_storage::Storage;
+use linera_views::ViewError;
 
 use crate::{client::PendingProposal, data_types::ChainInfo};
 
@@ -23,10 +25,52 @@ pub struct Chain {
     pub block_hash: Option<CryptoHash>,
     pub next_block_height: BlockHeight,
     pub timestamp: Timestamp,
-    pub pending_fast_proposal: Option<PendingProposal>,
+    pub pending_fast_proposal: Option<StoredProposal>,
     pub epoch: Option<Epoch>,
 }
 
+/// A pending proposal remembered by the wallet.
+///
+/// Proposals can be large, so the wallet only keeps the ID of the data blob holding them
+/// in the client's storage. Wallets written by earlier versions hold the proposals
+/// themselves.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+pub enum StoredProposal {
+    /// The proposal is the content of this data blob in the client's storage.
+    Blob {
+        /// The ID of the data blob.
+        blob_id: BlobId,
+    },
+    /// The proposal itself.
+    Inline(Box<PendingProposal>),
+}
+
+impl StoredProposal {
+    /// Writes the proposal to `storage` and returns a reference to it.
+    pub async fn store<S: Storage>(
+        proposal: &PendingProposal,
+        storage: &S,
+    ) -> Result<Self, ViewError> {
+        let blob = Blob::new_data(bcs::to_bytes(proposal)?);
+        storage.write_blob(&blob).await?;
+        Ok(Self::Blob { blob_id: blob.id() })
+    }
+
+    /// Returns the proposal, reading it from `storage` if needed.
+    pub async fn load<S: Storage>(self, storage: &S) -> Result<PendingProposal, ViewError> {
+        match self {
+            Self::Inline(proposal) => Ok(*proposal),
+            Self::Blob { blob_id } => {
+                let blob = storage.read_blob(blob_id).await?.ok_or_else(|| {
+                    ViewError::NotFound(format!("pending proposal in blob {blob_id}"))
+                })?;
+                Ok(bcs::from_bytes(blob.bytes())?)
+            }
+        }
+    }
+}
+
 impl From<&ChainInfo> for Chain {
     fn from(info: &ChainInfo) -> Self {
         Self {
@@ -23,6 +23,7 @@ pub mod node;
 /// Utilities for notifying subscribers about chain events.
 pub mod notifier;
 mod remote_node;
+pub mod replay;
 /// Helpers for writing tests against the core protocol.
 #[cfg(with_testing)]
 #[path = "unit_tests/test_utils.rs"]
@@ -0,0 +1,470 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deterministic re-execution of confirmed blocks, to debug divergences between the
+//! certified outcome of a block and what the local code computes for it, or to read the
+//! state of a chain as of an earlier block.
+
+use std::collections::BTreeMap;
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{Amount, Blob, BlockHeight, Event, OracleResponse},
+    identifiers::{AccountOwner, BlobId, ChainId, StreamId},
+};
+use linera_chain::{
+    data_types::{BlockExecutionOutcome, BundleExecutionPolicy, OperationResult},
+    types::{Block, ConfirmedBlock, ConfirmedBlockCertificate},
+    BlockExecutionPhase, ChainError, ChainStateView,
+};
+use linera_execution::OutgoingMessage;
+use linera_storage::{ChainRuntimeContext, Clock as _, Storage};
+use linera_views::{
+    context::{Context as _, ViewContext},
+    memory::{MemoryDatabase, MemoryStore, MemoryStoreConfig},
+    store::KeyValueDatabase as _,
+    views::{RootView as _, View as _},
+    ViewError,
+};
+use serde::Serialize;
+use thiserror::Error;
+
+/// The number of certificates read from storage at once while rebuilding the pre-state.
+const CERTIFICATE_BATCH_SIZE: u64 = 100;
+
+/// An error while replaying a block.
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    /// The certificate to replay is not in storage.
+    #[error("certificate {0} not found in storage")]
+    CertificateNotFound(CryptoHash),
+    /// An earlier block of the chain is not in storage, so the pre-state cannot be rebuilt.
+    #[error("block {height} of chain {chain_id} not found in storage; sync the chain first")]
+    MissingBlock {
+        /// The chain of the missing block.
+        chain_id: ChainId,
+        /// The height of the missing block.
+        height: BlockHeight,
+    },
+    /// A blob published by a block is not in storage.
+    #[error("blob {0} not found in storage")]
+    MissingBlob(BlobId),
+    /// Re-executing an earlier block already diverged from its certificate.
+    #[error(
+        "re-executing block {height} of chain {chain_id} already diverges; replay that block first"
+    )]
+    EarlierDivergence {
+        /// The chain of the diverging block.
+        chain_id: ChainId,
+        /// The height of the diverging block.
+        height: BlockHeight,
+    },
+    /// An error of the chain state.
+    #[error(transparent)]
+    Chain(#[from] ChainError),
+    /// An error of the storage.
+    #[error(transparent)]
+    View(#[from] ViewError),
+}
+
+/// How a replayed block differs from its certificate.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum Divergence {
+    /// The execution state hash after the block differs.
+    StateHash {
+        /// The certified state hash.
+        certified: CryptoHash,
+        /// The replayed state hash.
+        replayed: CryptoHash,
+    },
+    /// A transaction made different oracle requests or got different responses.
+    OracleResponses {
+        /// The index of the transaction in the block.
+        transaction_index: usize,
+        /// The certified oracle responses.
+        certified: Vec<OracleResponse>,
+        /// The replayed oracle responses.
+        replayed: Vec<OracleResponse>,
+    },
+    /// A transaction sent different messages.
+    Messages {
+        /// The index of the transaction in the block.
+        transaction_index: usize,
+        /// The certified outgoing messages.
+        certified: Vec<OutgoingMessage>,
+        /// The replayed outgoing messages.
+        replayed: Vec<OutgoingMessage>,
+    },
+    /// A transaction emitted different events.
+    Events {
+        /// The index of the transaction in the block.
+        transaction_index: usize,
+        /// The certified events.
+        certified: Vec<Event>,
+        /// The replayed events.
+        replayed: Vec<Event>,
+    },
+    /// A transaction created different blobs.
+    Blobs {
+        /// The index of the transaction in the block.
+        transaction_index: usize,
+        /// The IDs of the certified blobs.
+        certified: Vec<BlobId>,
+        /// The IDs of the replayed blobs.
+        replayed: Vec<BlobId>,
+    },
+    /// An operation returned a different result.
+    OperationResult {
+        /// The index of the operation in the block.
+        operation_index: usize,
+        /// The certified result.
+        certified: OperationResult,
+        /// The replayed result.
+        replayed: OperationResult,
+    },
+    /// The previous blocks that sent messages to the same recipients differ.
+    PreviousMessageBlocks {
+        /// The certified previous blocks.
+        certified: BTreeMap<ChainId, (CryptoHash, BlockHeight)>,
+        /// The replayed previous blocks.
+        replayed: BTreeMap<ChainId, (CryptoHash, BlockHeight)>,
+    },
+    /// The previous blocks that published events to the same streams differ.
+    PreviousEventBlocks {
+        /// The certified previous blocks.
+        certified: BTreeMap<StreamId, (CryptoHash, BlockHeight)>,
+        /// The replayed previous blocks.
+        replayed: BTreeMap<StreamId, (CryptoHash, BlockHeight)>,
+    },
+}
+
+/// The result of replaying a confirmed block.
+#[derive(Clone, Debug, Serialize)]
+pub struct ReplayReport {
+    /// The chain of the block.
+    pub chain_id: ChainId,
+    /// The height of the block.
+    pub height: BlockHeight,
+    /// The hash of the certified block.
+    pub block_hash: CryptoHash,
+    /// Whether the recorded oracle responses were replayed, instead of querying the oracles
+    /// again.
+    pub replayed_oracle_responses: bool,
+    /// The error, if re-executing the block failed.
+    pub execution_error: Option<String>,
+    /// How the re-executed block differs from the certificate.
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayReport {
+    /// Returns whether the block re-executed to exactly the certified outcome.
+    pub fn is_consistent(&self) -> bool {
+        self.execution_error.is_none() && self.divergences.is_empty()
+    }
+}
+
+type ReplayContext<S> = ViewContext<ChainRuntimeContext<S>, MemoryStore>;
+
+/// Re-executes the confirmed block with the given hash against the state of its chain just
+/// before it, and compares the outcome with the certified one.
+///
+/// The pre-state is rebuilt in memory from the chain's earlier blocks in `storage`, which is
+/// not modified. Unless `replay_oracle_responses` is set, the block's oracles are queried
+/// again, as when the block was first proposed.
+pub async fn replay_block<S>(
+    storage: &S,
+    hash: CryptoHash,
+    replay_oracle_responses: bool,
+) -> Result<ReplayReport, ReplayError>
+where
+    S: Storage + Clone + 'static,
+{
+    let certificate = storage
+        .read_certificate(hash)
+        .await?
+        .ok_or(ReplayError::CertificateNotFound(hash))?;
+    let chain_id = certificate.block().header.chain_id;
+    let height = certificate.block().header.height;
+    let (_database, mut chain) =
+        rebuild_chain(storage, chain_id, height, &format!("replay-{hash}")).await?;
+
+    let block = certificate.block();
+    let published_blobs = read_published_blobs(storage, &certificate).await?;
+    chain
+        .remove_bundles_from_inboxes(block.header.timestamp, false, block.body.incoming_bundles())
+        .await?;
+    let (proposed_block, certified) = certificate.value().clone().into_block().into_proposal();
+    let oracle_responses = replay_oracle_responses.then(|| certified.oracle_responses.clone());
+    let result = Box::pin(chain.execute_block(
+        proposed_block,
+        block.header.timestamp,
+        certificate.round.multi_leader(),
+        &published_blobs,
+        oracle_responses,
+        BundleExecutionPolicy::committed(),
+        BlockExecutionPhase::HandleProposal,
+    ))
+    .await;
+    let (execution_error, divergences) = match result {
+        Ok((_, replayed, _, _)) => (None, divergences(&certified, &replayed)),
+        Err(error) => (Some(error.to_string()), Vec::new()),
+    };
+    Ok(ReplayReport {
+        chain_id,
+        height,
+        block_hash: hash,
+        replayed_oracle_responses: replay_oracle_responses,
+        execution_error,
+        divergences,
+    })
+}
+
+/// Returns the balance of the `owner` on the chain, or the chain balance for
+/// [`AccountOwner::CHAIN`], right after the block at the given `height`.
+///
+/// The state is rebuilt in memory from the chain's blocks in `storage`, which is not
+/// modified.
+pub async fn owner_balance_at_height<S>(
+    storage: &S,
+    chain_id: ChainId,
+    owner: AccountOwner,
+    height: BlockHeight,
+) -> Result<Amount, ReplayError>
+where
+    S: Storage + Clone + 'static,
+{
+    let next_height = height.try_add_one().map_err(ChainError::from)?;
+    let namespace = format!("balance-{chain_id}-{height}");
+    let (_database, chain) = rebuild_chain(storage, chain_id, next_height, &namespace).await?;
+    let system = &chain.execution_state.system;
+    if owner.is_chain() {
+        Ok(*system.balance.get())
+    } else {
+        Ok(system.balances.get(&owner).await?.unwrap_or_default())
+    }
+}
+
+/// Rebuilds in memory the state of the chain after its blocks below `next_height`, from the
+/// certificates in `storage`.
+///
+/// The returned database holds the in-memory state, and must outlive the chain.
+async fn rebuild_chain<S>(
+    storage: &S,
+    chain_id: ChainId,
+    next_height: BlockHeight,
+    namespace: &str,
+) -> Result<(MemoryDatabase, ChainStateView<ReplayContext<S>>), ReplayError>
+where
+    S: Storage + Clone + 'static,
+{
+    let config = MemoryStoreConfig { kill_on_drop: true };
+    let database = MemoryDatabase::maybe_create_and_connect(&config, namespace)
+        .await
+        .map_err(ViewError::from)?;
+    let extra = storage
+        .load_chain(chain_id)
+        .await?
+        .context()
+        .extra()
+        .clone();
+    let context = ReplayContext::<S>::create_root_context(
+        database.open_exclusive(&[]).map_err(ViewError::from)?,
+        extra,
+    )
+    .await
+    .map_err(ViewError::from)?;
+    let mut chain = ChainStateView::load(context).await?;
+    // The state hash depends on the history of saves: like the worker, save the chain once
+    // it is initialized, and after every block.
+    chain
+        .initialize_if_needed(storage.clock().current_time())
+        .await?;
+    chain.save().await?;
+
+    let mut height = BlockHeight::ZERO;
+    while height < next_height {
+        let end = next_height
+            .0
+            .min(height.0.saturating_add(CERTIFICATE_BATCH_SIZE));
+        let heights = (height.0..end).map(BlockHeight).collect::<Vec<_>>();
+        let certificates = storage
+            .read_certificates_by_heights(chain_id, &heights)
+            .await?;
+        for (height, certificate) in heights.into_iter().zip(certificates) {
+            let certificate = certificate.ok_or(ReplayError::MissingBlock { chain_id, height })?;
+            Box::pin(apply_certified_block(storage, &mut chain, &certificate)).await?;
+        }
+        height = BlockHeight(end);
+    }
+    Ok((database, chain))
+}
+
+/// Executes a certified block on the in-memory chain, as a validator would, and applies it.
+async fn apply_certified_block<S>(
+    storage: &S,
+    chain: &mut ChainStateView<ReplayContext<S>>,
+    certificate: &ConfirmedBlockCertificate,
+) -> Result<(), ReplayError>
+where
+    S: Storage + Clone + 'static,
+{
+    let block = certificate.block();
+    let published_blobs = read_published_blobs(storage, certificate).await?;
+    chain
+        .remove_bundles_from_inboxes(block.header.timestamp, false, block.body.incoming_bundles())
+        .await?;
+    let (proposed_block, outcome) = certificate.value().clone().into_block().into_proposal();
+    let oracle_responses = Some(outcome.oracle_responses.clone());
+    let (proposed_block, replayed, _, _) = Box::pin(chain.execute_block(
+        proposed_block,
+        block.header.timestamp,
+        None,
+        &published_blobs,
+        oracle_responses,
+        BundleExecutionPolicy::committed(),
+        BlockExecutionPhase::HandleConfirmed,
+    ))
+    .await?;
+    if replayed != outcome {
+        return Err(ReplayError::EarlierDivergence {
+            chain_id: block.header.chain_id,
+            height: block.header.height,
+        });
+    }
+    let confirmed_block = ConfirmedBlock::new(Block::new(proposed_block, replayed));
+    chain
+        .apply_confirmed_block(&confirmed_block, block.header.timestamp, None)
+        .await?;
+    chain.save().await?;
+    Ok(())
+}
+
+async fn read_published_blobs<S: Storage>(
+    storage: &S,
+    certificate: &ConfirmedBlockCertificate,
+) -> Result<Vec<Blob>, ReplayError> {
+    let blob_ids = certificate
+        .block()
+        .published_blob_ids()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let blobs = storage.read_blobs(&blob_ids).await?;
+    blob_ids
+        .into_iter()
+        .zip(blobs)
+        .map(|(blob_id, blob)| {
+            blob.map(|blob| (*blob).clone())
+                .ok_or(ReplayError::MissingBlob(blob_id))
+        })
+        .collect()
+}
+
+/// Lists how the `replayed` outcome of a block differs from the `certified` one.
+pub fn divergences(
+    certified: &BlockExecutionOutcome,
+    replayed: &BlockExecutionOutcome,
+) -> Vec<Divergence> {
+    let mut divergences = Vec::new();
+    if certified.state_hash != replayed.state_hash {
+        divergences.push(Divergence::StateHash {
+            certified: certified.state_hash,
+            replayed: replayed.state_hash,
+        });
+    }
+    let transaction_count = certified
+        .oracle_responses
+        .len()
+        .max(replayed.oracle_responses.len())
+        .max(certified.messages.len())
+        .max(replayed.messages.len());
+    for transaction_index in 0..transaction_count {
+        let (certified_responses, replayed_responses) = at_index(
+            &certified.oracle_responses,
+            &replayed.oracle_responses,
+            transaction_index,
+        );
+        if certified_responses != replayed_responses {
+            divergences.push(Divergence::OracleResponses {
+                transaction_index,
+                certified: certified_responses,
+                replayed: replayed_responses,
+            });
+        }
+        let (certified_messages, replayed_messages) =
+            at_index(&certified.messages, &replayed.messages, transaction_index);
+        if certified_messages != replayed_messages {
+            divergences.push(Divergence::Messages {
+                transaction_index,
+                certified: certified_messages,
+                replayed: replayed_messages,
+            });
+        }
+        let (certified_events, replayed_events) =
+            at_index(&certified.events, &replayed.events, transaction_index);
+        if certified_events != replayed_events {
+            divergences.push(Divergence::Events {
+                transaction_index,
+                certified: certified_events,
+                replayed: replayed_events,
+            });
+        }
+        let (certified_blobs, replayed_blobs) =
+            at_index(&certified.blobs, &replayed.blobs, transaction_index);
+        if certified_blobs != replayed_blobs {
+            divergences.push(Divergence::Blobs {
+                transaction_index,
+                certified: certified_blobs.iter().map(|blob| blob.id()).collect(),
+                replayed: replayed_blobs.iter().map(|blob| blob.id()).collect(),
+            });
+        }
+    }
+    let operation_count = certified
+        .operation_results
+        .len()
+        .max(replayed.operation_results.len());
+    for operation_index in 0..operation_count {
+        let certified_result = certified
+            .operation_results
+            .get(operation_index)
+            .cloned()
+            .unwrap_or_default();
+        let replayed_result = replayed
+            .operation_results
+            .get(operation_index)
+            .cloned()
+            .unwrap_or_default();
+        if certified_result != replayed_result {
+            divergences.push(Divergence::OperationResult {
+                operation_index,
+                certified: certified_result,
+                replayed: replayed_result,
+            });
+        }
+    }
+    if certified.previous_message_blocks != replayed.previous_message_blocks {
+        divergences.push(Divergence::PreviousMessageBlocks {
+            certified: certified.previous_message_blocks.clone(),
+            replayed: replayed.previous_message_blocks.clone(),
+        });
+    }
+    if certified.previous_event_blocks != replayed.previous_event_blocks {
+        divergences.push(Divergence::PreviousEventBlocks {
+            certified: certified.previous_event_blocks.clone(),
+            replayed: replayed.previous_event_blocks.clone(),
+        });
+    }
+    divergences
+}
+
+/// Returns the entries of both lists at `index`, or empty ones where a list is too short.
+fn at_index<T: Clone>(certified: &[Vec<T>], replayed: &[Vec<T>], index: usize) -> (Vec<T>, Vec<T>) {
+    (
+        certified.get(index).cloned().unwrap_or_default(),
+        replayed.get(index).cloned().unwrap_or_default(),
+    )
+}
+
+#[cfg(test)]
+#[path = "unit_tests/replay_tests.rs"]
+mod tests;
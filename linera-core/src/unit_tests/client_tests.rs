@@ -8,12 +8,15 @@ mod test_helpers;
 #[path = "./wasm_client_tests.rs"]
 mod wasm;
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    num::NonZeroU64,
+};
 
 use assert_matches::assert_matches;
 use futures::StreamExt;
 use linera_base::{
-    crypto::{AccountSecretKey, CryptoHash, InMemorySigner},
+    crypto::{AccountSecretKey, CryptoHash, InMemorySigner, Signer as _},
     data_types::*,
     identifiers::{Account, AccountOwner, ApplicationId, BlobId, BlobType, GenericApplicationId},
     ownership::{ChainOwnership, TimeoutConfig},
@@ -25,8 +28,10 @@ use linera_chain::{
     ChainError, ChainExecutionContext,
 };
 use linera_execution::{
-    committee::Committee, system::SystemOperation, ExecutionError, Message, MessageKind, Operation,
-    QueryOutcome, ResourceControlPolicy, SystemMessage, SystemQuery, SystemResponse,
+    committee::Committee,
+    system::{AdminOperation, SystemOperation},
+    ExecutionError, HttpAllowListUpdate, Message, MessageKind, Operation, QueryOutcome,
+    ResourceControlPolicy, SystemMessage, SystemQuery, SystemResponse,
 };
 use linera_storage::Storage;
 use rand::Rng;
@@ -47,6 +52,7 @@ use crate::{
         chain_client::{self, ChainClient},
         ClientOutcome, ListeningMode,
     },
+    data_types::OrphanedProposal,
     local_node::LocalNodeError,
     node::{
         NodeError::{self, ClientIoError},
@@ -755,6 +761,135 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new(); "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_suggested_fee_budget<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer)
+        .await?
+        .with_policy(ResourceControlPolicy::all_categories());
+    let sender = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+    let receiver = builder.add_root_chain(2, Amount::ZERO).await?;
+    let operation = Operation::system(SystemOperation::Transfer {
+        owner: AccountOwner::CHAIN,
+        recipient: Account::chain(receiver.chain_id()),
+        amount: Amount::ONE,
+    });
+
+    let suggestion = sender.suggested_fee_budget(vec![operation.clone()]).await?;
+    assert!(suggestion.estimated > Amount::ZERO);
+    assert_eq!(suggestion.recent_maximum, None);
+    assert!(suggestion.suggested > suggestion.estimated);
+    assert_eq!(suggestion.chain_balance, Amount::from_tokens(4));
+    // Estimating the fees does not modify the chain.
+    assert_eq!(sender.local_balance().await?, Amount::from_tokens(4));
+
+    sender
+        .execute_operation(operation.clone())
+        .await
+        .unwrap_ok_committed();
+    assert_eq!(
+        sender.local_balance().await?,
+        Amount::from_tokens(3).try_sub(suggestion.estimated)?
+    );
+
+    let suggestion = sender.suggested_fee_budget(vec![operation]).await?;
+    assert_eq!(suggestion.recent_maximum, Some(suggestion.estimated));
+    Ok(())
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new(); "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_dry_run<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer)
+        .await?
+        .with_policy(ResourceControlPolicy::all_categories());
+    let publisher = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+    let blob = Blob::new_data(b"dry run".to_vec());
+    let operation = Operation::system(SystemOperation::PublishDataBlob {
+        blob_hash: blob.id().hash,
+    });
+
+    let outcome = publisher
+        .dry_run(vec![operation.clone()], vec![blob.clone()])
+        .await?;
+    assert_eq!(outcome.chain_balance, Amount::from_tokens(4));
+    assert_eq!(outcome.resources.operations, 1);
+    assert_eq!(outcome.resources.blobs_published, 1);
+    assert!(outcome.resources.fees > Amount::ZERO);
+    // Nothing was committed or published.
+    assert_eq!(publisher.local_balance().await?, Amount::from_tokens(4));
+    assert_eq!(
+        publisher.chain_info().await?.next_block_height,
+        BlockHeight::ZERO
+    );
+
+    publisher
+        .execute_operations(vec![operation], vec![blob])
+        .await
+        .unwrap_ok_committed();
+    assert_eq!(
+        publisher.local_balance().await?,
+        Amount::from_tokens(4).try_sub(outcome.resources.fees)?
+    );
+    Ok(())
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new(); "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_read_or_download_confirmed_block<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer).await?;
+    let sender = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+    let receiver_id = builder.add_root_chain(2, Amount::ZERO).await?.chain_id();
+    let certificate = sender
+        .transfer_to_account(
+            AccountOwner::CHAIN,
+            Amount::ONE,
+            Account::chain(receiver_id),
+        )
+        .await
+        .unwrap_ok_committed();
+    let hash = certificate.hash();
+
+    // A new client only has the genesis state, so it must download the block. The faulty
+    // validator doesn't have it, so the client must fall back to another one.
+    let client = builder
+        .make_client(receiver_id, None, BlockHeight::ZERO)
+        .await?;
+    assert_matches!(
+        client.read_confirmed_block(hash).await,
+        Err(chain_client::Error::MissingConfirmedBlock(_))
+    );
+    let block = client.read_or_download_confirmed_block(hash).await?;
+    assert_eq!(*block, *certificate.value());
+    // Now the block is served from local storage.
+    assert_eq!(
+        *client.read_confirmed_block(hash).await?,
+        *certificate.value()
+    );
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new(); "storage_service"))]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
@@ -961,6 +1096,88 @@ where
     Ok(())
 }
 
+/// A pending proposal overtaken by a different block certified at its height, e.g. by
+/// another client of the same owner, is discarded and its operations are proposed again,
+/// unless the certified blocks executed them already.
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new(); "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_resolve_orphaned_proposal<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 0, signer).await?;
+    let client_a = builder.add_root_chain(1, Amount::from_tokens(10)).await?;
+    let chain_id = client_a.chain_id();
+    let client_b = builder
+        .make_client(chain_id, None, BlockHeight::ZERO)
+        .await?;
+    assert!(client_a.resolve_orphaned_proposal().await?.is_none());
+
+    // The validators reject the first client's proposal, so it remains pending.
+    builder.set_fault_type([0, 1, 2, 3], FaultType::OfflineWithInfo);
+    assert!(client_a
+        .burn(AccountOwner::CHAIN, Amount::ONE)
+        .await
+        .is_err());
+    let pending = client_a.pending_proposal().await.unwrap();
+    builder.set_fault_type([0, 1, 2, 3], FaultType::Honest);
+
+    // The other client commits a different block at the same height.
+    let certificate = client_b
+        .burn(AccountOwner::CHAIN, Amount::from_tokens(2))
+        .await
+        .unwrap_ok_committed();
+    assert_eq!(certificate.block().header.height, BlockHeight::ZERO);
+
+    let orphaned = client_a.resolve_orphaned_proposal().await?;
+    let Some(OrphanedProposal::Resolved {
+        height,
+        certified_hash,
+        requeued: Some(requeued),
+    }) = orphaned
+    else {
+        panic!("unexpected resolution: {orphaned:?}");
+    };
+    assert_eq!(height, BlockHeight::ZERO);
+    assert_eq!(certified_hash, certificate.hash());
+    let requeued = requeued.unwrap();
+    assert_eq!(requeued.block().header.height, BlockHeight::from(1));
+    assert!(requeued
+        .block()
+        .body
+        .operations()
+        .eq(pending.block.operations()));
+    assert!(client_a.pending_proposal().await.is_none());
+
+    // If the other client commits the same operation instead, it is not proposed again.
+    builder.set_fault_type([0, 1, 2, 3], FaultType::OfflineWithInfo);
+    assert!(client_a
+        .burn(AccountOwner::CHAIN, Amount::ONE)
+        .await
+        .is_err());
+    builder.set_fault_type([0, 1, 2, 3], FaultType::Honest);
+    client_b.synchronize_from_validators().await?;
+    client_b
+        .burn(AccountOwner::CHAIN, Amount::ONE)
+        .await
+        .unwrap_ok_committed();
+    assert_matches!(
+        client_a.resolve_orphaned_proposal().await?,
+        Some(OrphanedProposal::Unsafe { height, .. }) if height == BlockHeight::from(2)
+    );
+    assert!(client_a.pending_proposal().await.is_none());
+    assert_eq!(
+        client_a.chain_info().await?.next_block_height,
+        BlockHeight::from(3)
+    );
+
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new(); "storage_service"))]
 #[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
@@ -1416,6 +1633,157 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new(); "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_governance_proposals<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let signer = InMemorySigner::new(None);
+    let clock = storage_builder.clock().clone();
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer).await?;
+    let admin = builder.add_root_chain(0, Amount::from_tokens(3)).await?;
+    let validators = builder.initial_committee.validators().clone();
+    let voting_period = TimeDelta::from_secs(60);
+
+    // Two proposals: one approved and one rejected.
+    for _ in 0..2 {
+        let committee = Committee::new(validators.clone(), ResourceControlPolicy::only_fuel())?;
+        admin
+            .propose_new_committee(committee, voting_period)
+            .await
+            .unwrap_ok_committed();
+    }
+    let (proposals, _) = admin.governance_proposals().await?;
+    assert_eq!(proposals.keys().copied().collect::<Vec<_>>(), vec![0, 1]);
+    admin
+        .vote_on_governance_proposal(0, true)
+        .await
+        .unwrap_ok_committed();
+    admin
+        .vote_on_governance_proposal(1, false)
+        .await
+        .unwrap_ok_committed();
+    let (proposals, ownership) = admin.governance_proposals().await?;
+    assert_eq!(proposals[&0].tally(&ownership), (1, 0));
+    assert!(proposals[&0].is_approved(&ownership));
+    assert!(!proposals[&1].is_approved(&ownership));
+
+    // Proposals can't be resolved during the voting period.
+    assert_matches!(
+        admin.resolve_governance_proposal(0).await,
+        Err(chain_client::Error::LocalNodeError(
+            LocalNodeError::WorkerError(WorkerError::ChainError(chain_error))
+        )) if matches!(&*chain_error, ChainError::ExecutionError(
+            error, ChainExecutionContext::Operation(0)
+        ) if matches!(**error, ExecutionError::GovernanceVotingOpen(0)))
+    );
+    admin.clear_pending_proposal().await;
+
+    // After the voting period, votes are closed and the proposals can be resolved.
+    clock.add(voting_period);
+    admin
+        .resolve_governance_proposal(1)
+        .await
+        .unwrap_ok_committed();
+    assert_eq!(admin.chain_info().await?.epoch, Epoch::ZERO);
+    admin
+        .resolve_governance_proposal(0)
+        .await
+        .unwrap_ok_committed();
+    assert_eq!(admin.chain_info().await?.epoch, Epoch::from(1));
+    assert!(admin.governance_proposals().await?.0.is_empty());
+    Ok(())
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new(); "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_update_http_allow_list<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer).await?;
+    let admin = builder.add_root_chain(0, Amount::from_tokens(3)).await?;
+    let app_id = ApplicationId::new(CryptoHash::test_hash("app"));
+    let other_app_id = ApplicationId::new(CryptoHash::test_hash("other"));
+    let update = |application_id, add: &[&str], remove: &[&str]| HttpAllowListUpdate {
+        application_id,
+        add: add.iter().map(|host| host.to_string()).collect(),
+        remove: remove.iter().map(|host| host.to_string()).collect(),
+    };
+
+    // Hosts are added to the shared list and to an application's own list, one epoch each.
+    admin
+        .update_http_allow_list(update(None, &["a.com", "b.com"], &[]))
+        .await
+        .unwrap_ok_committed()
+        .expect("the update should change the committee");
+    admin
+        .update_http_allow_list(update(Some(app_id), &["c.com"], &[]))
+        .await
+        .unwrap_ok_committed()
+        .expect("the update should change the committee");
+    admin
+        .update_http_allow_list(update(None, &[], &["a.com"]))
+        .await
+        .unwrap_ok_committed()
+        .expect("the update should change the committee");
+    assert_eq!(admin.chain_info().await?.epoch, Epoch::from(3));
+    let committee = admin.local_committee().await?;
+    let policy = committee.policy();
+    assert_eq!(
+        policy.http_request_allow_list,
+        BTreeSet::from(["b.com".to_string()])
+    );
+    assert!(policy.is_http_request_allowed(&other_app_id, "b.com"));
+    assert!(policy.is_http_request_allowed(&app_id, "c.com"));
+    assert!(!policy.is_http_request_allowed(&other_app_id, "c.com"));
+
+    // Updates that change nothing don't start a new epoch.
+    assert!(admin
+        .update_http_allow_list(update(None, &[], &["a.com"]))
+        .await
+        .unwrap_ok_committed()
+        .is_none());
+
+    // A committee that is not the current one with exactly the update applied is rejected.
+    let mut policy = policy.clone();
+    policy.http_request_allow_list.clear();
+    let stale = Committee::new(committee.validators().clone(), policy)?;
+    let blob = Blob::new_committee(bcs::to_bytes(&stale)?);
+    let blob_hash = blob.id().hash;
+    admin
+        .execute_operations(
+            vec![Operation::system(SystemOperation::Admin(
+                AdminOperation::PublishCommitteeBlob { blob_hash },
+            ))],
+            vec![blob],
+        )
+        .await
+        .unwrap_ok_committed();
+    assert_matches!(
+        admin
+            .execute_operation(SystemOperation::Admin(AdminOperation::UpdateHttpAllowList {
+                update: update(None, &["d.com"], &[]),
+                blob_hash,
+            }))
+            .await,
+        Err(chain_client::Error::LocalNodeError(
+            LocalNodeError::WorkerError(WorkerError::ChainError(chain_error))
+        )) if matches!(&*chain_error, ChainError::ExecutionError(
+            error, ChainExecutionContext::Operation(0)
+        ) if matches!(**error, ExecutionError::HttpAllowListUpdateMismatch))
+    );
+    Ok(())
+}
+
 /// Tests that a client whose local view of the admin chain is stale can still use a blob
 /// whose publishing certificate was signed by a committee from an epoch the client has
 /// not heard of yet.
@@ -3420,6 +3788,118 @@ where
     Ok(())
 }
 
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new(); "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_execute_operations_in_blocks<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let policy = ResourceControlPolicy {
+        maximum_block_proposal_size: 1_000,
+        ..ResourceControlPolicy::default()
+    };
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 0, signer)
+        .await?
+        .with_policy(policy);
+    let sender = builder.add_root_chain(1, Amount::from_tokens(10)).await?;
+    let receiver = builder.add_root_chain(2, Amount::ZERO).await?;
+    let recipient = Account::chain(receiver.chain_id());
+    let transfer = Operation::system(SystemOperation::Transfer {
+        owner: AccountOwner::CHAIN,
+        recipient,
+        amount: Amount::from_millis(1),
+    });
+
+    // All the transfers together exceed the maximum proposal size.
+    let mut operations = std::iter::repeat_n(transfer, 50).collect::<VecDeque<_>>();
+    assert_matches!(
+        sender
+            .execute_operations(operations.iter().cloned().collect(), vec![])
+            .await,
+        Err(error) if error.is_block_limit_error()
+    );
+
+    let mut progress = Vec::new();
+    let (certificates, timeout) = sender
+        .execute_operations_in_blocks(&mut operations, |block| progress.push(block))
+        .await?;
+    assert!(timeout.is_none());
+    assert!(operations.is_empty());
+    assert!(certificates.len() > 1);
+    assert_eq!(progress.len(), certificates.len());
+    assert_eq!(progress.last().unwrap().executed_operations, 50);
+    assert_eq!(progress.last().unwrap().remaining_operations, 0);
+    for (block, certificate) in progress.iter().zip(&certificates) {
+        assert_eq!(block.height, certificate.block().header.height);
+        assert_eq!(
+            block.block_operations,
+            certificate.block().body.operations().count()
+        );
+    }
+    assert_eq!(
+        sender.local_balance().await?,
+        Amount::from_tokens(10).try_sub(Amount::from_millis(50))?
+    );
+    Ok(())
+}
+
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new(); "storage_service"))]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_offline_signed_proposal<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 0, signer).await?;
+    let sender = builder.add_root_chain(1, Amount::from_tokens(3)).await?;
+    let receiver = builder.add_root_chain(2, Amount::ZERO).await?;
+    let owner = sender.identity().await?;
+    let transfer = Operation::system(SystemOperation::Transfer {
+        owner: AccountOwner::CHAIN,
+        recipient: Account::chain(receiver.chain_id()),
+        amount: Amount::ONE,
+    });
+
+    let ClientOutcome::Committed(proposal) = sender
+        .prepare_unsigned_proposal(vec![transfer], vec![], owner)
+        .await?
+    else {
+        panic!("preparing a proposal on a single-owner chain should not wait for a timeout");
+    };
+    assert_eq!(proposal.content.block.height, BlockHeight::ZERO);
+    // Nothing was committed while preparing the proposal.
+    assert_eq!(sender.local_balance().await?, Amount::from_tokens(3));
+
+    // A signature of anything else than the proposal is rejected.
+    let wrong_signature = sender
+        .signer()
+        .sign(&owner, &CryptoHash::test_hash("something else"))
+        .await?;
+    assert_matches!(
+        sender
+            .submit_signed_proposal(proposal.clone(), wrong_signature)
+            .await,
+        Err(chain_client::Error::InvalidProposalSignature(_))
+    );
+
+    let signature = sender
+        .signer()
+        .sign(&owner, &proposal.signing_hash())
+        .await?;
+    let certificate = sender.submit_signed_proposal(proposal, signature).await?;
+    assert_eq!(certificate.block().header.height, BlockHeight::ZERO);
+    assert_eq!(certificate.block().header.authenticated_owner, Some(owner));
+    assert_eq!(sender.local_balance().await?, Amount::from_tokens(2));
+    Ok(())
+}
+
 #[test_case(MemoryStorageBuilder::default(); "memory")]
 #[cfg_attr(feature = "storage-service", test_case(ServiceStorageBuilder::new(); "storage_service"))]
 #[test_log::test(tokio::test)]
@@ -4341,6 +4821,73 @@ where
     Ok(())
 }
 
+/// Verifies that `checkpoint_if_due` only publishes a checkpoint once the chain grew by the
+/// given interval since its latest checkpoint.
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[test_log::test(tokio::test)]
+async fn test_periodic_checkpoint<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer).await?;
+    let sender = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+    let recipient = builder.add_root_chain(2, Amount::ZERO).await?;
+    let interval = NonZeroU64::new(3).unwrap();
+    let transfer = || {
+        sender.transfer_to_account(
+            AccountOwner::CHAIN,
+            Amount::ONE,
+            Account::chain(recipient.chain_id()),
+        )
+    };
+
+    transfer().await.unwrap_ok_committed();
+    transfer().await.unwrap_ok_committed();
+    assert!(sender.checkpoint_if_due(interval).await?.is_none());
+    transfer().await.unwrap_ok_committed();
+    let checkpoint = sender.checkpoint_if_due(interval).await?.unwrap().unwrap();
+    assert_eq!(checkpoint.block().header.height, BlockHeight::from(3));
+    assert!(checkpoint.block().starts_with_checkpoint());
+
+    // The next checkpoint is due three blocks after this one.
+    transfer().await.unwrap_ok_committed();
+    assert!(sender.checkpoint_if_due(interval).await?.is_none());
+    Ok(())
+}
+
+/// Verifies that `checkpoint_if_due` publishes a checkpoint in every new epoch, even before
+/// the chain grew by the given interval.
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[test_log::test(tokio::test)]
+async fn test_periodic_checkpoint_in_new_epoch<B>(storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(storage_builder, 4, 1, signer).await?;
+    let admin = builder.add_root_chain(0, Amount::from_tokens(3)).await?;
+    let user = builder.add_root_chain(1, Amount::from_tokens(3)).await?;
+    let interval = NonZeroU64::new(100).unwrap();
+    user.checkpoint().await.unwrap_ok_committed();
+    assert!(user.checkpoint_if_due(interval).await?.is_none());
+
+    let validators = builder.initial_committee.validators().clone();
+    let committee = Committee::new(validators, ResourceControlPolicy::only_fuel())?;
+    admin.stage_new_committee(committee).await.unwrap();
+    user.synchronize_from_validators().await.unwrap();
+    user.process_inbox().await.unwrap();
+    assert_eq!(user.chain_info().await?.epoch, Epoch::from(1));
+
+    // The latest checkpoint was certified by the previous committee.
+    let checkpoint = user.checkpoint_if_due(interval).await?.unwrap().unwrap();
+    assert_eq!(checkpoint.block().header.epoch, Epoch::from(1));
+    assert!(user.checkpoint_if_due(interval).await?.is_none());
+    Ok(())
+}
+
 /// Verifies the push side of the checkpoint flow: a validator that missed the whole
 /// chain is brought up to speed by the proposing client pushing only the latest
 /// checkpoint plus the pre-checkpoint sender blocks it certifies, not every
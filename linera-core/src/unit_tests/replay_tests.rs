@@ -0,0 +1,95 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use linera_base::{
+    crypto::InMemorySigner,
+    data_types::{Amount, BlockHeight},
+    identifiers::{Account, AccountOwner},
+};
+
+use super::{owner_balance_at_height, replay_block};
+use crate::test_utils::{ClientOutcomeResultExt as _, MemoryStorageBuilder, TestBuilder};
+
+#[test_log::test(tokio::test)]
+async fn test_replay_block() -> anyhow::Result<()> {
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(MemoryStorageBuilder::default(), 4, 1, signer).await?;
+    let sender = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+    let receiver = builder.add_root_chain(2, Amount::ZERO).await?;
+    let mut certificates = Vec::new();
+    for _ in 0..3 {
+        let certificate = sender
+            .transfer_to_account(
+                AccountOwner::CHAIN,
+                Amount::ONE,
+                Account::chain(receiver.chain_id()),
+            )
+            .await
+            .unwrap_ok_committed();
+        certificates.push(certificate);
+    }
+    receiver.synchronize_from_validators().await?;
+    let (received, _) = Box::pin(receiver.process_inbox()).await?;
+    assert_eq!(received.len(), 1);
+    let report = Box::pin(replay_block(
+        receiver.storage_client(),
+        received[0].hash(),
+        false,
+    ))
+    .await?;
+    assert!(report.is_consistent(), "{report:?}");
+
+    let storage = sender.storage_client();
+    for (height, certificate) in certificates.iter().enumerate() {
+        for replay_oracle_responses in [true, false] {
+            let report = Box::pin(replay_block(
+                storage,
+                certificate.hash(),
+                replay_oracle_responses,
+            ))
+            .await?;
+            assert_eq!(report.height, BlockHeight::from(height as u64));
+            assert!(report.is_consistent(), "{report:?}");
+        }
+    }
+    Ok(())
+}
+
+#[test_log::test(tokio::test)]
+async fn test_owner_balance_at_height() -> anyhow::Result<()> {
+    let signer = InMemorySigner::new(None);
+    let mut builder = TestBuilder::new(MemoryStorageBuilder::default(), 4, 1, signer).await?;
+    let sender = builder.add_root_chain(1, Amount::from_tokens(4)).await?;
+    let receiver = builder.add_root_chain(2, Amount::ZERO).await?;
+    for _ in 0..3 {
+        sender
+            .transfer_to_account(
+                AccountOwner::CHAIN,
+                Amount::ONE,
+                Account::chain(receiver.chain_id()),
+            )
+            .await
+            .unwrap_ok_committed();
+    }
+
+    let storage = sender.storage_client();
+    for (height, tokens) in [(0, 3), (1, 2), (2, 1)] {
+        let balance = Box::pin(owner_balance_at_height(
+            storage,
+            sender.chain_id(),
+            AccountOwner::CHAIN,
+            BlockHeight(height),
+        ))
+        .await?;
+        assert_eq!(balance, Amount::from_tokens(tokens));
+    }
+    assert!(Box::pin(owner_balance_at_height(
+        storage,
+        sender.chain_id(),
+        AccountOwner::CHAIN,
+        BlockHeight(3),
+    ))
+    .await
+    .is_err());
+    Ok(())
+}
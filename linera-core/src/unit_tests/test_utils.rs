@@ -80,6 +80,30 @@ pub enum FaultType {
     DontSendValidateVote,
 }
 
+/// The environment variable naming the directory where network inputs seen by test
+/// validators are recorded, as seeds for the fuzz targets in `fuzz/`.
+pub const FUZZ_CORPUS_DIR_VARIABLE: &str = "LINERA_FUZZ_CORPUS_DIR";
+
+/// Records the BCS serialization of `value` in the corpus of the given fuzz target, if
+/// [`FUZZ_CORPUS_DIR_VARIABLE`] is set. Files are named after the hash of their content, so
+/// that identical inputs are only recorded once.
+pub fn record_fuzz_seed<T: serde::Serialize>(target: &str, value: &T) {
+    use std::hash::{DefaultHasher, Hash as _, Hasher as _};
+
+    let Some(corpus_dir) = std::env::var_os(FUZZ_CORPUS_DIR_VARIABLE) else {
+        return;
+    };
+    let bytes = bcs::to_bytes(value).expect("serialization should not fail");
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    let dir = std::path::Path::new(&corpus_dir).join(target);
+    let result = std::fs::create_dir_all(&dir)
+        .and_then(|()| std::fs::write(dir.join(format!("{:016x}", hasher.finish())), &bytes));
+    if let Err(error) = result {
+        tracing::warn!(%error, "failed to record fuzz seed for {target}");
+    }
+}
+
 /// A validator used for testing. "Faulty" validators ignore block proposals (but not
 /// certificates or info queries) and have the wrong initial balance for all chains.
 ///
@@ -119,6 +143,7 @@ where
         &self,
         proposal: BlockProposal,
     ) -> Result<ChainInfoResponse, NodeError> {
+        record_fuzz_seed("block_proposal", &proposal);
         self.spawn_and_receive(move |validator, sender| {
             validator.do_handle_block_proposal(proposal, sender)
         })
@@ -130,6 +155,7 @@ where
         certificate: LiteCertificate<'_>,
         _delivery: CrossChainMessageDelivery,
     ) -> Result<ChainInfoResponse, NodeError> {
+        record_fuzz_seed("lite_certificate", &certificate);
         let certificate = certificate.cloned();
         self.spawn_and_receive(move |validator, sender| {
             validator.do_handle_lite_certificate(certificate, sender)
@@ -151,6 +177,7 @@ where
         &self,
         certificate: ValidatedBlockCertificate,
     ) -> Result<ChainInfoResponse, NodeError> {
+        record_fuzz_seed("validated_certificate", &certificate);
         self.spawn_and_receive(move |validator, sender| {
             validator.do_handle_certificate::<ValidatedBlock>(certificate, sender)
         })
@@ -162,6 +189,7 @@ where
         certificate: CacheArc<ConfirmedBlockCertificate>,
         _delivery: CrossChainMessageDelivery,
     ) -> Result<ChainInfoResponse, NodeError> {
+        record_fuzz_seed("confirmed_certificate", &*certificate);
         self.spawn_and_receive(move |validator, sender| {
             validator.do_handle_certificate::<ConfirmedBlock>(
                 CacheArc::unwrap_or_clone(certificate),
@@ -613,9 +641,8 @@ where
             Err(error) => Err(error),
             Ok(entry) => match entry {
                 Some(certificate) => Ok(CacheArc::unwrap_or_clone(certificate)),
-                None => {
-                    panic!("Missing certificate: {hash}");
-                }
+                // Like a real validator, e.g. one that does not track the chain.
+                None => Err(NodeError::MissingCertificates(vec![hash])),
             },
         };
 
@@ -1761,6 +1761,15 @@ where
         chain_info_response.info.manager.pending.unwrap(),
         pending_vote
     );
+    // The validation vote that was replaced is kept in storage for a while.
+    let superseded = env
+        .executing_worker()
+        .storage_client()
+        .read_superseded_round_states(chain_1)
+        .await?;
+    assert!(superseded
+        .iter()
+        .any(|(_, state)| state.validated_vote.is_some()));
     Ok(())
 }
 
@@ -1960,7 +1969,10 @@ where
         .try_load_entry(&chain_3)
         .await?
         .expect("Missing inbox for `chain_3` in `chain_1`");
-    assert_eq!(BlockHeight::ZERO, inbox.next_block_height_to_receive()?);
+    assert_eq!(
+        BlockHeight::ZERO,
+        inbox.next_block_height_to_receive().await?
+    );
     assert_eq!(inbox.added_bundles.count(), 0);
     assert_matches!(
         inbox
@@ -2147,7 +2159,10 @@ where
         .try_load_entry(&chain_1)
         .await?
         .expect("Missing inbox for chain_1 in chain_2");
-    assert_eq!(BlockHeight::from(1), inbox.next_block_height_to_receive()?);
+    assert_eq!(
+        BlockHeight::from(1),
+        inbox.next_block_height_to_receive().await?
+    );
     assert_matches!(
         inbox.added_bundles.front().await?.unwrap(),
         MessageBundle {
@@ -2225,7 +2240,10 @@ where
         .try_load_entry(&chain_1)
         .await?
         .expect("Missing inbox for `chain_1` in `chain_2`");
-    assert_eq!(BlockHeight::from(1), inbox.next_block_height_to_receive()?);
+    assert_eq!(
+        BlockHeight::from(1),
+        inbox.next_block_height_to_receive().await?
+    );
     assert_matches!(
         inbox
             .added_bundles
@@ -4599,6 +4617,152 @@ where
     Ok(())
 }
 
+/// Tests that a recipient refuses cross-chain updates from an origin whose bundles already
+/// fill its inbox, and accepts them again once the inbox has room.
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_cross_chain_update_refused_when_inbox_is_full<B>(
+    mut storage_builder: B,
+) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let sender_key_pair = AccountSecretKey::generate();
+    let mut env = TestEnvironment::new(&mut storage_builder, true, false).await?;
+    env.worker = env.worker.with_max_pending_bundles_per_origin(1);
+    let chain_1 = env
+        .add_root_chain(1, sender_key_pair.public().into(), Amount::from_tokens(100))
+        .await
+        .id();
+    let chain_2 = env
+        .add_root_chain(2, AccountPublicKey::test_key(2).into(), Amount::ONE)
+        .await
+        .id();
+
+    let cert_0 = env
+        .make_simple_transfer_certificate(
+            chain_1,
+            sender_key_pair.public(),
+            chain_2,
+            Amount::from_tokens(5),
+            Vec::new(),
+            None,
+        )
+        .await;
+    let cert_1 = env
+        .make_simple_transfer_certificate(
+            chain_1,
+            sender_key_pair.public(),
+            chain_2,
+            Amount::from_tokens(3),
+            Vec::new(),
+            Some(&cert_0),
+        )
+        .await;
+    for certificate in [&cert_0, &cert_1] {
+        env.worker()
+            .process_confirmed_block(
+                certificate.clone(),
+                ProcessConfirmedBlockMode::Execute,
+                None,
+            )
+            .await?;
+    }
+
+    // The empty inbox accepts the first bundle.
+    let actions = env
+        .worker()
+        .handle_cross_chain_request(update_recipient_direct(chain_2, &cert_0))
+        .await?;
+    assert!(actions
+        .cross_chain_requests
+        .iter()
+        .any(|request| matches!(request, CrossChainRequest::ConfirmUpdatedRecipient { .. })));
+
+    // The second one would exceed the limit.
+    assert_matches!(
+        env.worker()
+            .handle_cross_chain_request(update_recipient_direct(chain_2, &cert_1))
+            .await,
+        Err(WorkerError::InboxFull {
+            origin,
+            recipient,
+            pending: 1,
+            limit: 1,
+        }) if origin == chain_1 && recipient == chain_2
+    );
+    let chain = env.worker().chain_state_view(chain_2).await?;
+    let inbox = chain
+        .inboxes
+        .try_load_entry(&chain_1)
+        .await?
+        .expect("chain_2 should have an inbox for chain_1");
+    assert_eq!(inbox.added_bundles.count(), 1);
+    drop(inbox);
+    assert!(chain.refused_inboxes.get().contains(&chain_1));
+    drop(chain);
+
+    // Executing a block that receives the first bundle drains the inbox, so chain_2 asks
+    // chain_1 to resend the refused bundles.
+    let receive_cert = env
+        .execute_proposal(
+            make_first_block(chain_2).with_incoming_bundle(IncomingBundle {
+                origin: chain_1,
+                bundle: MessageBundle {
+                    certificate_hash: cert_0.hash(),
+                    height: BlockHeight::ZERO,
+                    timestamp: Timestamp::from(0),
+                    transaction_index: 0,
+                    messages: vec![system_credit_message(Amount::from_tokens(5))
+                        .to_posted(MessageKind::Tracked)],
+                },
+                action: MessageAction::Accept,
+            }),
+            vec![],
+        )
+        .await?;
+    let (_, actions, _) = env
+        .worker()
+        .process_confirmed_block(receive_cert, ProcessConfirmedBlockMode::Execute, None)
+        .await?;
+    let resend_request = actions
+        .cross_chain_requests
+        .into_iter()
+        .find(|request| matches!(request, CrossChainRequest::RevertConfirm { .. }))
+        .expect("chain_2 should ask chain_1 to resend the refused bundles");
+    assert_eq!(
+        resend_request,
+        CrossChainRequest::RevertConfirm {
+            sender: chain_1,
+            recipient: chain_2,
+            retransmit_from: BlockHeight::from(1),
+        }
+    );
+
+    // The bundles are still in chain_1's outbox, so chain_1 sends them again, and the
+    // inbox now accepts them.
+    let actions = env
+        .worker()
+        .handle_cross_chain_request(resend_request)
+        .await?;
+    assert!(!actions.cross_chain_requests.is_empty());
+    let mut confirmations = Vec::new();
+    for request in actions.cross_chain_requests {
+        let actions = env.worker().handle_cross_chain_request(request).await?;
+        confirmations.extend(actions.cross_chain_requests);
+    }
+    assert!(confirmations.iter().any(|request| matches!(
+        request,
+        CrossChainRequest::ConfirmUpdatedRecipient { latest_height, .. }
+            if *latest_height == BlockHeight::from(1)
+    )));
+    let chain = env.worker().chain_state_view(chain_2).await?;
+    assert!(chain.refused_inboxes.get().is_empty());
+    Ok(())
+}
+
 /// Tests the RevertConfirm recovery mechanism.
 ///
 /// Simulates the scenario where the sender's outbox was drained (via a spurious
@@ -5088,7 +5252,7 @@ where
         .await?
         .expect("chain_2 should have an inbox for chain_1");
     assert_eq!(
-        inbox.next_block_height_to_receive()?,
+        inbox.next_block_height_to_receive().await?,
         BlockHeight::from(3),
         "All three heights should have been received"
     );
@@ -5096,6 +5260,117 @@ where
     Ok(())
 }
 
+/// Tests that in pull mode, the sender only notifies the recipient of its blocks, and the
+/// recipient reads the messages from storage once they are consumed.
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[test_log::test(tokio::test)]
+async fn test_cross_chain_pull_messages<B>(mut storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let mut signer = InMemorySigner::new(None);
+    let sender_public_key = signer.generate_new();
+    let recipient_owner = signer.generate_new().into();
+    let mut env = TestEnvironment::new(&mut storage_builder, false, false).await?;
+    env.worker = env.worker.with_pull_cross_chain_messages(true);
+
+    let chain_1 = env
+        .add_root_chain(1, sender_public_key.into(), Amount::from_tokens(100))
+        .await
+        .id();
+    let chain_2 = env
+        .add_root_chain(2, recipient_owner, Amount::ONE)
+        .await
+        .id();
+
+    let certificate = env
+        .make_simple_transfer_certificate(
+            chain_1,
+            sender_public_key,
+            chain_2,
+            Amount::from_tokens(5),
+            Vec::new(),
+            None,
+        )
+        .await;
+    let hash = certificate.hash();
+    let (_, actions, _) = env
+        .worker()
+        .process_confirmed_block(certificate, ProcessConfirmedBlockMode::Execute, None)
+        .await?;
+    assert_eq!(
+        actions.cross_chain_requests,
+        vec![CrossChainRequest::NotifyRecipient {
+            sender: chain_1,
+            recipient: chain_2,
+            blocks: vec![(BlockHeight::ZERO, hash)],
+            previous_height: None,
+        }]
+    );
+
+    // A notification about an unknown block is refused.
+    let unknown = CrossChainRequest::NotifyRecipient {
+        sender: chain_1,
+        recipient: chain_2,
+        blocks: vec![(BlockHeight::ZERO, CryptoHash::test_hash("unknown"))],
+        previous_height: None,
+    };
+    assert_matches!(
+        env.worker().handle_cross_chain_request(unknown).await,
+        Err(WorkerError::ReadCertificatesError(_))
+    );
+
+    let mut requests = std::collections::VecDeque::from(actions.cross_chain_requests);
+    while let Some(request) = requests.pop_front() {
+        let actions = env.worker().handle_cross_chain_request(request).await?;
+        requests.extend(actions.cross_chain_requests);
+    }
+
+    // Only a reference to the block is stored in the inbox.
+    {
+        let chain = env.worker().chain_state_view(chain_2).await?;
+        let inbox = chain
+            .inboxes
+            .try_load_entry(&chain_1)
+            .await?
+            .expect("chain_2 should have an inbox for chain_1");
+        assert_eq!(
+            inbox.next_block_height_to_receive().await?,
+            BlockHeight::from(1)
+        );
+        assert_eq!(inbox.added_bundles.count(), 0);
+        assert_eq!(inbox.block_references.count(), 1);
+    }
+
+    // The pending bundles are read from the referenced block.
+    let query = ChainInfoQuery::new(chain_2).with_pending_message_bundles();
+    let response = env.worker().handle_chain_info_query(query).await?;
+    let bundles = response.info.requested_pending_message_bundles;
+    assert_eq!(bundles.len(), 1);
+    assert_eq!(bundles[0].origin, chain_1);
+    assert_eq!(bundles[0].bundle.certificate_hash, hash);
+
+    // Consuming the bundle adds it to the inbox first.
+    let block_proposal = make_first_block(chain_2)
+        .with_incoming_bundle(bundles[0].clone())
+        .with_authenticated_owner(Some(recipient_owner))
+        .into_first_proposal(recipient_owner, &signer)
+        .await
+        .unwrap();
+    env.worker().handle_block_proposal(block_proposal).await.0?;
+    let chain = env.worker().chain_state_view(chain_2).await?;
+    let inbox = chain
+        .inboxes
+        .try_load_entry(&chain_1)
+        .await?
+        .expect("chain_2 should have an inbox for chain_1");
+    assert_eq!(inbox.block_references.count(), 0);
+    assert_eq!(inbox.added_bundles.count(), 0);
+    assert!(!chain.nonempty_inboxes.get().contains(&chain_1));
+
+    Ok(())
+}
+
 /// Tests that gap detection works correctly with chunked cross-chain messages.
 ///
 /// With chunk_limit=1, each block produces a separate UpdateRecipient. If we skip
@@ -5199,7 +5474,7 @@ where
         .await?
         .expect("chain_2 should have an inbox for chain_1");
     assert_eq!(
-        inbox.next_block_height_to_receive()?,
+        inbox.next_block_height_to_receive().await?,
         BlockHeight::from(1),
         "Only height 0 should have been received before gap detection"
     );
@@ -5502,3 +5777,79 @@ where
     }
     Ok(())
 }
+
+/// Tests that closed ephemeral chains are indexed when they are closed, and pruned from the
+/// index once their retention period has passed, while other closed chains are kept.
+#[test_case(MemoryStorageBuilder::default(); "memory")]
+#[cfg_attr(feature = "rocksdb", test_case(RocksDbStorageBuilder::new().await; "rocks_db"))]
+#[cfg_attr(feature = "scylladb", test_case(ScyllaDbStorageBuilder::default(); "scylla_db"))]
+#[test_log::test(tokio::test)]
+async fn test_prune_closed_ephemeral_chains<B>(mut storage_builder: B) -> anyhow::Result<()>
+where
+    B: StorageBuilder,
+{
+    let owner = AccountOwner::from(AccountSecretKey::generate().public());
+    let mut env = TestEnvironment::new(&mut storage_builder, false, false).await?;
+    let clock = storage_builder.clock();
+    let retention = Duration::from_secs(60);
+    env.executing_worker = env
+        .executing_worker
+        .with_ephemeral_chain_retention(retention);
+    let ephemeral_chain = env.add_root_chain(1, owner, Amount::ONE).await.id();
+    let persistent_chain = env.add_root_chain(2, owner, Amount::ONE).await.id();
+
+    let proposal = make_first_block(ephemeral_chain)
+        .with_operation(SystemOperation::MakeEphemeral)
+        .with_operation(SystemOperation::CloseChain)
+        .with_authenticated_owner(Some(owner));
+    env.execute_proposal(proposal, vec![]).await?;
+    let proposal = make_first_block(persistent_chain)
+        .with_operation(SystemOperation::CloseChain)
+        .with_authenticated_owner(Some(owner));
+    env.execute_proposal(proposal, vec![]).await?;
+
+    // Only the ephemeral chain is indexed, and its retention period hasn't passed yet.
+    let storage = env.executing_worker().storage_client();
+    assert_eq!(
+        storage
+            .read_closed_ephemeral_chains(Timestamp::from(u64::MAX))
+            .await?,
+        vec![(Timestamp::from(0), ephemeral_chain)]
+    );
+    let pruned = env
+        .executing_worker()
+        .prune_ephemeral_chains(|_| true)
+        .await?;
+    assert!(pruned.is_empty());
+
+    // Chains handled by other shards are skipped.
+    clock.set(Timestamp::from(0).saturating_add(TimeDelta::from_duration(retention)));
+    let pruned = env
+        .executing_worker()
+        .prune_ephemeral_chains(|chain_id| chain_id != ephemeral_chain)
+        .await?;
+    assert!(pruned.is_empty());
+
+    let pruned = env
+        .executing_worker()
+        .prune_ephemeral_chains(|_| true)
+        .await?;
+    assert_eq!(pruned, vec![ephemeral_chain]);
+    let chain = env
+        .executing_worker()
+        .chain_state_view(ephemeral_chain)
+        .await?;
+    assert!(chain.pruned_at.get().is_some());
+    drop(chain);
+    let chain = env
+        .executing_worker()
+        .chain_state_view(persistent_chain)
+        .await?;
+    assert!(chain.pruned_at.get().is_none());
+    drop(chain);
+    assert!(storage
+        .read_closed_ephemeral_chains(Timestamp::from(u64::MAX))
+        .await?
+        .is_empty());
+    Ok(())
+}
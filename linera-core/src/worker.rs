@@ -15,11 +15,12 @@ use futures::{
     FutureExt as _,
 };
 use linera_base::{
-    crypto::{CryptoError, CryptoHash, ValidatorPublicKey},
+    crypto::{CryptoError, CryptoHash, SignatureScheme, ValidatorPublicKey},
     data_types::{
         ApplicationDescription, ArithmeticError, Blob, BlockHeight, Epoch, Round, TimeDelta,
         Timestamp,
     },
+    deadline::{self, DeadlineExceeded},
     doc_scalar,
     identifiers::{AccountOwner, ApplicationId, BlobId, ChainId, EventId, StreamId},
 };
@@ -66,7 +67,7 @@ pub(crate) use crate::chain_worker::EventSubscriptionsResult;
 use crate::{
     chain_worker::{
         handle, state::ChainWorkerState, BlockOutcome, ChainWorkerConfig, CrossChainUpdateResult,
-        DeliveryNotifier, ProcessConfirmedBlockMode,
+        DeliveryNotifier, ProcessConfirmedBlockMode, PruningOutcome,
     },
     client::{ChainModes, ListeningMode},
     data_types::{ChainInfoQuery, ChainInfoResponse, CrossChainRequest},
@@ -361,6 +362,12 @@ pub enum WorkerError {
     #[error("Operations in the block are not authenticated by the proper owner: {0}")]
     InvalidSigner(AccountOwner),
 
+    #[error("Block proposals on this chain must not be signed with {0}")]
+    DisallowedSignatureScheme(SignatureScheme),
+
+    #[error("The state of ephemeral chain {0} has been pruned")]
+    ChainPruned(ChainId),
+
     // Chaining
     #[error(
         "Chain is expecting a next block at height {expected_block_height} but the given block \
@@ -434,6 +441,18 @@ pub enum WorkerError {
     PoisonedWorker,
     #[error("Cross-chain batch was rolled back due to an error in another request")]
     BatchRolledBack,
+    #[error(transparent)]
+    DeadlineExceeded(#[from] DeadlineExceeded),
+    #[error(
+        "The inbox of chain {recipient} holds {pending} bundles from {origin}, \
+         refusing more than {limit}"
+    )]
+    InboxFull {
+        origin: ChainId,
+        recipient: ChainId,
+        pending: usize,
+        limit: usize,
+    },
 }
 
 impl WorkerError {
@@ -446,6 +465,8 @@ impl WorkerError {
             | WorkerError::ArithmeticError(_)
             | WorkerError::InvalidOwner
             | WorkerError::InvalidSigner(_)
+            | WorkerError::DisallowedSignatureScheme(_)
+            | WorkerError::ChainPruned(_)
             | WorkerError::UnexpectedBlockHeight { .. }
             | WorkerError::InvalidEpoch { .. }
             | WorkerError::EventsNotFound(_)
@@ -459,7 +480,11 @@ impl WorkerError {
             | WorkerError::InvalidBlockProposal(_)
             | WorkerError::UnexpectedBlob
             | WorkerError::TooManyPublishedBlobs(_)
-            | WorkerError::ViewError(ViewError::NotFound(_)) => false,
+            | WorkerError::DeadlineExceeded(_)
+            | WorkerError::InboxFull { .. }
+            | WorkerError::ViewError(ViewError::NotFound(_) | ViewError::DeadlineExceeded(_)) => {
+                false
+            }
             WorkerError::BcsError(_)
             | WorkerError::InvalidCrossChainRequest
             | WorkerError::ViewError(_)
@@ -566,11 +591,29 @@ type ChainWorkerFuture<S> = Shared<oneshot::Receiver<ChainWorkerWeak<S>>>;
 /// Callers that find a pending entry clone the `Shared` future and await it.
 type ChainWorkerMap<S> = Arc<papaya::HashMap<ChainId, ChainWorkerFuture<S>>>;
 
+/// The content of a cross-chain update.
+pub(crate) enum CrossChainUpdate {
+    /// The message bundles, with the epochs of their blocks.
+    Bundles(Vec<(Epoch, MessageBundle)>),
+    /// In pull mode, the heights and hashes of the sender's blocks with the bundles.
+    BlockReferences(Vec<(BlockHeight, CryptoHash)>),
+}
+
+impl CrossChainUpdate {
+    /// Returns the number of bundles or block references.
+    fn len(&self) -> usize {
+        match self {
+            CrossChainUpdate::Bundles(bundles) => bundles.len(),
+            CrossChainUpdate::BlockReferences(blocks) => blocks.len(),
+        }
+    }
+}
+
 /// A cross-chain request waiting to be processed in a batch.
 pub(crate) enum BatchRequest {
     Update {
         origin: ChainId,
-        bundles: Vec<(Epoch, MessageBundle)>,
+        update: CrossChainUpdate,
         previous_height: Option<BlockHeight>,
         result_sender: oneshot::Sender<Result<CrossChainUpdateResult, WorkerError>>,
     },
@@ -765,6 +808,32 @@ where
         self
     }
 
+    /// Returns an instance with the specified limit of pending bundles per origin.
+    #[cfg(with_testing)]
+    #[instrument(level = "trace", skip(self))]
+    pub fn with_max_pending_bundles_per_origin(mut self, limit: usize) -> Self {
+        self.chain_worker_config.max_pending_bundles_per_origin = limit;
+        self
+    }
+
+    /// Returns an instance that prunes closed ephemeral chains after the given retention
+    /// period.
+    #[cfg(with_testing)]
+    #[instrument(level = "trace", skip(self))]
+    pub fn with_ephemeral_chain_retention(mut self, retention: Duration) -> Self {
+        self.chain_worker_config.ephemeral_chain_retention = Some(retention);
+        self
+    }
+
+    /// Returns an instance that sends the hashes of blocks in cross-chain requests, letting
+    /// the recipients read their messages from storage.
+    #[cfg(with_testing)]
+    #[instrument(level = "trace", skip(self))]
+    pub fn with_pull_cross_chain_messages(mut self, value: bool) -> Self {
+        self.chain_worker_config.pull_cross_chain_messages = value;
+        self
+    }
+
     /// Returns the worker's nickname.
     #[instrument(level = "trace", skip(self))]
     pub fn nickname(&self) -> &str {
@@ -961,7 +1030,8 @@ where
     {
         let notifications = (*notifier).clone();
         let this = self.clone();
-        linera_base::Task::spawn(async move {
+        let deadline = deadline::current();
+        linera_base::Task::spawn(deadline::scope(deadline, async move {
             let (response, actions) =
                 <C::Value as ProcessableCertificate>::process_certificate(&this, certificate)
                     .await?;
@@ -973,7 +1043,7 @@ where
                 notifications.notify(&actions.notifications);
             }
             Ok(response)
-        })
+        }))
         .await
     }
 
@@ -990,7 +1060,8 @@ where
     ) -> Result<ChainInfoResponse, WorkerError> {
         let notifications = (*notifier).clone();
         let this = self.clone();
-        linera_base::Task::spawn(async move {
+        let deadline = deadline::current();
+        linera_base::Task::spawn(deadline::scope(deadline, async move {
             let (response, actions) =
                 Box::pin(this.handle_confirmed_certificate(certificate, mode, None)).await?;
             notifications.notify(&actions.notifications);
@@ -1001,7 +1072,7 @@ where
                 notifications.notify(&actions.notifications);
             }
             Ok(response)
-        })
+        }))
         .await
     }
 
@@ -1046,6 +1117,10 @@ where
     /// would be left at a partial tip serving stale reads with no `RevertConfirm`
     /// retransmission. Running recovery in the detached task makes it survive caller
     /// cancellation, just like the write itself.
+    ///
+    /// The deadline of the request, if any, is carried into the detached task, so that
+    /// the write is abandoned before it starts if the caller's deadline passed while
+    /// waiting for the lock.
     async fn chain_write<R, F, Fut>(&self, chain_id: ChainId, f: F) -> Result<R, WorkerError>
     where
         F: FnOnce(handle::RollbackGuard<StorageClient>) -> Fut
@@ -1056,11 +1131,12 @@ where
     {
         let state = self.get_or_create_chain_worker(chain_id).await?;
         let this = self.clone();
+        let deadline = deadline::current();
         Box::pin(wrap_future(linera_base::task::run_detached(async move {
-            let result = async {
+            let result = deadline::scope(deadline, async {
                 let guard = handle::write_lock(&state).await?;
                 f(guard).await
-            }
+            })
             .await;
             if let Err(error) = &result {
                 if error.must_reload_view() {
@@ -1453,23 +1529,23 @@ where
 
     /// Enqueues a cross-chain update request and cooperatively drives the
     /// batch-processing future until the result is ready.
-    #[instrument(level = "trace", skip(self, origin, recipient, bundles), fields(
+    #[instrument(level = "trace", skip(self, origin, recipient, update), fields(
         nickname = %self.nickname(),
         origin = %origin,
         recipient = %recipient,
-        num_bundles = %bundles.len()
+        num_bundles = %update.len()
     ))]
     async fn process_cross_chain_update(
         &self,
         origin: ChainId,
         recipient: ChainId,
-        bundles: Vec<(Epoch, MessageBundle)>,
+        update: CrossChainUpdate,
         previous_height: Option<BlockHeight>,
     ) -> Result<CrossChainUpdateResult, WorkerError> {
         let (result_sender, receiver) = oneshot::channel();
         let request = BatchRequest::Update {
             origin,
-            bundles,
+            update,
             previous_height,
             result_sender,
         };
@@ -1586,6 +1662,57 @@ where
         guard.reset_and_reexecute_chain().await
     }
 
+    /// Prunes the state of the closed ephemeral chains whose latest block is older than the
+    /// configured retention period. Only the chains in the storage's index of closed
+    /// ephemeral chains for which `handles_chain` returns true are locked and checked.
+    /// Returns the IDs of the chains that were pruned. Chains that fail to load or save are
+    /// logged and retried the next time.
+    #[instrument(skip_all, fields(nickname = %self.nickname()))]
+    pub async fn prune_ephemeral_chains(
+        &self,
+        handles_chain: impl Fn(ChainId) -> bool,
+    ) -> Result<Vec<ChainId>, WorkerError> {
+        let mut pruned = Vec::new();
+        let Some(retention) = self.chain_worker_config.ephemeral_chain_retention else {
+            return Ok(pruned);
+        };
+        let closed_before = self
+            .storage
+            .clock()
+            .current_time()
+            .saturating_sub(TimeDelta::from_duration(retention));
+        let mut done = Vec::new();
+        for (closed_at, chain_id) in self
+            .storage
+            .read_closed_ephemeral_chains(closed_before)
+            .await?
+        {
+            if !handles_chain(chain_id) {
+                continue;
+            }
+            match self.prune_ephemeral_chain(chain_id).await {
+                Ok(PruningOutcome::Pruned) => {
+                    pruned.push(chain_id);
+                    done.push((closed_at, chain_id));
+                }
+                Ok(PruningOutcome::NotPrunable) => done.push((closed_at, chain_id)),
+                Ok(PruningOutcome::Pending) => {}
+                Err(error) => warn!(%chain_id, %error, "Failed to prune ephemeral chain"),
+            }
+        }
+        self.storage.remove_closed_ephemeral_chains(&done).await?;
+        Ok(pruned)
+    }
+
+    async fn prune_ephemeral_chain(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<PruningOutcome, WorkerError> {
+        let state = self.get_or_create_chain_worker(chain_id).await?;
+        let mut guard = handle::write_lock(&state).await?;
+        guard.prune_if_expired().await
+    }
+
     /// Returns a read-only view of the [`ChainStateView`] of a chain referenced by its
     /// [`ChainId`].
     ///
@@ -1847,40 +1974,19 @@ where
                 bundles,
                 previous_height,
             } => {
-                let mut actions = NetworkActions::default();
-                let origin = sender;
-                match self
-                    .process_cross_chain_update(origin, recipient, bundles, previous_height)
-                    .await?
-                {
-                    CrossChainUpdateResult::NothingToDo => {}
-                    CrossChainUpdateResult::Updated(height) => {
-                        actions.notifications.push(Notification {
-                            chain_id: recipient,
-                            reason: Reason::NewIncomingBundle { origin, height },
-                        });
-                        actions.cross_chain_requests.push(
-                            CrossChainRequest::ConfirmUpdatedRecipient {
-                                sender,
-                                recipient,
-                                latest_height: height,
-                            },
-                        );
-                    }
-                    CrossChainUpdateResult::GapDetected {
-                        origin,
-                        retransmit_from,
-                    } => {
-                        actions
-                            .cross_chain_requests
-                            .push(CrossChainRequest::RevertConfirm {
-                                sender: origin,
-                                recipient,
-                                retransmit_from,
-                            });
-                    }
-                }
-                Ok(actions)
+                let update = CrossChainUpdate::Bundles(bundles);
+                self.update_recipient(sender, recipient, update, previous_height)
+                    .await
+            }
+            CrossChainRequest::NotifyRecipient {
+                sender,
+                recipient,
+                blocks,
+                previous_height,
+            } => {
+                let update = CrossChainUpdate::BlockReferences(blocks);
+                self.update_recipient(sender, recipient, update, previous_height)
+                    .await
             }
             CrossChainRequest::ConfirmUpdatedRecipient {
                 sender,
@@ -1907,6 +2013,59 @@ where
         }
     }
 
+    /// Adds the bundles or block references sent by `sender` to the inbox of `recipient`,
+    /// and returns the resulting confirmation or retransmission request.
+    async fn update_recipient(
+        &self,
+        sender: ChainId,
+        recipient: ChainId,
+        update: CrossChainUpdate,
+        previous_height: Option<BlockHeight>,
+    ) -> Result<NetworkActions, WorkerError> {
+        let mut actions = NetworkActions::default();
+        let origin = sender;
+        match self
+            .process_cross_chain_update(origin, recipient, update, previous_height)
+            .await?
+        {
+            CrossChainUpdateResult::NothingToDo => {}
+            CrossChainUpdateResult::Updated(height) => {
+                actions.notifications.push(Notification {
+                    chain_id: recipient,
+                    reason: Reason::NewIncomingBundle { origin, height },
+                });
+                actions
+                    .cross_chain_requests
+                    .push(CrossChainRequest::ConfirmUpdatedRecipient {
+                        sender,
+                        recipient,
+                        latest_height: height,
+                    });
+            }
+            CrossChainUpdateResult::GapDetected {
+                origin,
+                retransmit_from,
+            } => {
+                actions
+                    .cross_chain_requests
+                    .push(CrossChainRequest::RevertConfirm {
+                        sender: origin,
+                        recipient,
+                        retransmit_from,
+                    });
+            }
+            CrossChainUpdateResult::InboxFull { pending, limit } => {
+                return Err(WorkerError::InboxFull {
+                    origin,
+                    recipient,
+                    pending,
+                    limit,
+                });
+            }
+        }
+        Ok(actions)
+    }
+
     /// Updates the received certificate trackers to at least the given values.
     #[instrument(skip_all, fields(
         nickname = %self.nickname(),
@@ -2114,11 +2273,11 @@ where
     #[instrument(level = "trace", skip(self))]
     pub fn public_key(&self) -> ValidatorPublicKey {
         self.chain_worker_config
-            .key_pair()
+            .signer()
             .expect(
                 "Test validator should have a key pair assigned to it \
                 in order to obtain its public key",
             )
-            .public()
+            .public_key()
     }
 }
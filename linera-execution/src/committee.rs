@@ -2,21 +2,23 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{borrow::Cow, collections::BTreeMap, sync::Arc};
+use std::{borrow::Cow, collections::BTreeMap, fmt, sync::Arc};
 
 use allocative::Allocative;
 use linera_base::{
     crypto::{AccountPublicKey, CryptoHash, ValidatorPublicKey},
-    data_types::ArithmeticError,
+    data_types::{ArithmeticError, CommitteeSummary},
 };
 use serde::{Deserialize, Serialize};
 
-use crate::policy::ResourceControlPolicy;
+use crate::policy::{OriginalPolicy, OriginalPolicyRef, ProtocolFlag, ResourceControlPolicy};
 
 /// Public state of a validator.
 #[derive(Eq, PartialEq, Hash, Clone, Debug, Serialize, Deserialize, Allocative)]
 pub struct ValidatorState {
-    /// The network address (in a string format understood by the networking layer).
+    /// The network address (in a string format understood by the networking layer). A
+    /// validator reachable through several proxies lists their addresses separated by
+    /// commas.
     pub network_address: String,
     /// The voting power.
     pub votes: u64,
@@ -79,13 +81,103 @@ struct CommitteeFull<'a> {
     policy: Cow<'a, ResourceControlPolicy>,
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename = "Committee")]
+/// The binary format of committees, used in committee blobs: the validators and the policy
+/// in its original format, followed by the policy fields added since then, each only if the
+/// [`ProtocolFlag`] of its feature is enabled. Committees that don't use these features keep
+/// the encoding, and thus the blob hash, they had before the fields were added.
 struct CommitteeMinimal<'a> {
     validators: Cow<'a, BTreeMap<ValidatorPublicKey, ValidatorState>>,
     policy: Cow<'a, ResourceControlPolicy>,
 }
 
+impl Serialize for CommitteeMinimal<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        use serde::ser::{Error as _, SerializeTuple as _};
+
+        let policy = &*self.policy;
+        // BCS encodes tuples without their length, so the optional fields can simply follow.
+        let mut tuple = serializer.serialize_tuple(4)?;
+        tuple.serialize_element(&self.validators)?;
+        tuple.serialize_element(&OriginalPolicyRef(policy))?;
+        if policy.flags.contains(&ProtocolFlag::HttpAllowListOverrides) {
+            tuple.serialize_element(&policy.http_request_allow_list_overrides)?;
+        } else if !policy.http_request_allow_list_overrides.is_empty() {
+            return Err(S::Error::custom(
+                "HTTP allow list overrides require the HttpAllowListOverrides protocol flag",
+            ));
+        }
+        let quotas = (
+            policy.maximum_http_requests_per_application_per_block,
+            policy.maximum_http_response_bytes_per_application_per_block,
+            policy.maximum_http_requests_per_application_per_epoch,
+            policy.maximum_http_response_bytes_per_application_per_epoch,
+        );
+        if policy.flags.contains(&ProtocolFlag::HttpApplicationQuotas) {
+            tuple.serialize_element(&quotas)?;
+        } else if quotas != (u64::MAX, u64::MAX, u64::MAX, u64::MAX) {
+            return Err(S::Error::custom(
+                "HTTP application quotas require the HttpApplicationQuotas protocol flag",
+            ));
+        }
+        tuple.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for CommitteeMinimal<'static> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        deserializer.deserialize_tuple(4, CommitteeMinimalVisitor)
+    }
+}
+
+struct CommitteeMinimalVisitor;
+
+impl<'de> serde::de::Visitor<'de> for CommitteeMinimalVisitor {
+    type Value = CommitteeMinimal<'static>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a committee")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::SeqAccess<'de>,
+    {
+        use serde::de::Error as _;
+
+        let validators = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let OriginalPolicy(mut policy) = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        if policy.flags.contains(&ProtocolFlag::HttpAllowListOverrides) {
+            policy.http_request_allow_list_overrides = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(2, &self))?;
+        }
+        if policy.flags.contains(&ProtocolFlag::HttpApplicationQuotas) {
+            (
+                policy.maximum_http_requests_per_application_per_block,
+                policy.maximum_http_response_bytes_per_application_per_block,
+                policy.maximum_http_requests_per_application_per_epoch,
+                policy.maximum_http_response_bytes_per_application_per_epoch,
+            ) = seq
+                .next_element()?
+                .ok_or_else(|| A::Error::invalid_length(3, &self))?;
+        }
+        Ok(CommitteeMinimal {
+            validators: Cow::Owned(validators),
+            policy: Cow::Owned(policy),
+        })
+    }
+}
+
 impl TryFrom<CommitteeFull<'static>> for Committee {
     type Error = String;
 
@@ -257,6 +349,14 @@ impl Committee {
         self.total_votes
     }
 
+    /// Returns the size of this committee, as seen by applications.
+    pub fn summary(&self) -> CommitteeSummary {
+        CommitteeSummary {
+            validator_count: self.validators.len().try_into().unwrap_or(u32::MAX),
+            total_votes: self.total_votes,
+        }
+    }
+
     /// Returns the resource control policy of this committee.
     pub fn policy(&self) -> &ResourceControlPolicy {
         &self.policy
@@ -304,7 +404,94 @@ impl SharedCommittees {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+
+    use linera_base::identifiers::ApplicationId;
+
     use super::*;
+    use crate::HttpAllowListUpdate;
+
+    /// Returns a committee blob in the format from before the policy fields added later, and
+    /// the committee it encodes.
+    fn original_committee_blob() -> (Vec<u8>, Committee) {
+        let mut bytes = vec![0]; // No validators.
+        bytes.extend([0; 17 * 16]); // The 17 prices, all zero.
+        for limit in 1..=13u64 {
+            bytes.extend(limit.to_le_bytes()); // The 13 limits.
+        }
+        bytes.extend(b"\x01\x0bexample.com"); // The allowed HTTP hosts.
+        bytes.extend([0, 0]); // No free applications and no flags.
+        let policy = ResourceControlPolicy {
+            maximum_wasm_fuel_per_block: 1,
+            maximum_evm_fuel_per_block: 2,
+            maximum_service_oracle_execution_ms: 3,
+            maximum_block_size: 4,
+            maximum_bytecode_size: 5,
+            maximum_blob_size: 6,
+            maximum_published_blobs: 7,
+            maximum_block_proposal_size: 8,
+            maximum_bytes_read_per_block: 9,
+            maximum_bytes_written_per_block: 10,
+            maximum_oracle_response_bytes: 11,
+            maximum_http_response_bytes: 12,
+            http_request_timeout_ms: 13,
+            http_request_allow_list: BTreeSet::from(["example.com".to_string()]),
+            ..ResourceControlPolicy::no_fees()
+        };
+        let committee = Committee::new(BTreeMap::new(), policy).unwrap();
+        (bytes, committee)
+    }
+
+    #[test]
+    fn test_committee_blob_from_before_policy_extensions() {
+        let (bytes, committee) = original_committee_blob();
+        assert_eq!(bcs::from_bytes::<Committee>(&bytes).unwrap(), committee);
+        // Committees that don't use the newer fields keep their encoding and blob hash.
+        assert_eq!(bcs::to_bytes(&committee).unwrap(), bytes);
+    }
+
+    #[test]
+    fn test_committee_blob_with_allow_list_overrides() {
+        let (original_bytes, mut committee) = original_committee_blob();
+        committee
+            .policy_mut()
+            .update_http_request_allow_list(&HttpAllowListUpdate {
+                application_id: Some(ApplicationId::default()),
+                add: BTreeSet::from(["api.example.com".to_string()]),
+                remove: BTreeSet::new(),
+            });
+        let bytes = bcs::to_bytes(&committee).unwrap();
+        assert_eq!(bcs::from_bytes::<Committee>(&bytes).unwrap(), committee);
+        // The overrides follow the original format, which only differs by the enabled flag.
+        let flags_offset = original_bytes.len() - 1;
+        assert_eq!(bytes[..flags_offset], original_bytes[..flags_offset]);
+        assert_eq!(bytes[flags_offset..flags_offset + 2], [1, 2]);
+
+        // Without their flag, the overrides can't be encoded.
+        committee.policy_mut().flags.clear();
+        assert!(bcs::to_bytes(&committee).is_err());
+    }
+
+    #[test]
+    fn test_committee_blob_with_http_application_quotas() {
+        let (original_bytes, mut committee) = original_committee_blob();
+        let policy = committee.policy_mut();
+        policy.maximum_http_requests_per_application_per_block = 2;
+        policy.maximum_http_response_bytes_per_application_per_epoch = 1_000;
+        assert!(bcs::to_bytes(&committee).is_err());
+
+        committee
+            .policy_mut()
+            .flags
+            .insert(ProtocolFlag::HttpApplicationQuotas);
+        let bytes = bcs::to_bytes(&committee).unwrap();
+        assert_eq!(bcs::from_bytes::<Committee>(&bytes).unwrap(), committee);
+        // The quotas follow the original format, which only differs by the enabled flag.
+        let flags_offset = original_bytes.len() - 1;
+        assert_eq!(bytes[..flags_offset], original_bytes[..flags_offset]);
+        assert_eq!(bytes[flags_offset..flags_offset + 2], [1, 1]);
+        assert_eq!(bytes.len(), original_bytes.len() + 1 + 4 * 8);
+    }
 
     #[test]
     fn shared_committees_insert_and_get() {
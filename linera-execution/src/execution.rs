@@ -20,6 +20,7 @@ use linera_views::{
     context::Context,
     historical_hash_wrapper::HistoricallyHashableView,
     key_value_store_view::KeyValueStoreView,
+    log_view::LogView,
     map_view::MapView,
     reentrant_collection_view::ReentrantCollectionView,
     views::{ClonableView, ReplaceContext, View},
@@ -76,11 +77,27 @@ impl<C: Context, C2: Context> ReplaceContext<C2> for ExecutionStateViewInner<C>
     }
 }
 
+/// The key of a secondary index over the events of a stream: it selects the events that
+/// their application indexed with the given value of the given field.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, Allocative)]
+pub struct EventIndexKey {
+    /// The stream of the indexed events.
+    pub stream_id: StreamId,
+    /// The indexed field, e.g. `to`.
+    pub field: String,
+    /// The value of the field, e.g. the serialized recipient.
+    pub key: Vec<u8>,
+}
+
 /// A view accessing the execution state of a chain.
 #[derive(Debug, ClonableView, View, Allocative)]
 #[allocative(bound = "C")]
 pub struct ExecutionStateView<C> {
     inner: HistoricallyHashableView<C, ExecutionStateViewInner<C>>,
+    /// The indexes of the events emitted on this chain, as declared by the emitting
+    /// applications: the indices in the stream of the matching events, in increasing order.
+    /// Indexes are maintained locally and are not part of the execution state hash.
+    pub event_indexes: ReentrantCollectionView<C, EventIndexKey, LogView<C, u32>>,
 }
 
 impl<C> Deref for ExecutionStateView<C> {
@@ -223,6 +240,7 @@ impl<C: Context, C2: Context> ReplaceContext<C2> for ExecutionStateView<C> {
     ) -> Self::Target {
         ExecutionStateView {
             inner: self.inner.with_context(ctx.clone()).await,
+            event_indexes: self.event_indexes.with_context(ctx.clone()).await,
         }
     }
 }
@@ -413,10 +431,14 @@ where
             query,
             None,
             BTreeMap::new(),
+            true,
         )
         .await
     }
 
+    /// Runs a query against a user application's service. If `use_service_overrides` is
+    /// set, locally reloaded service modules are used; this must not be the case when the
+    /// response is recorded in a block.
     pub(crate) async fn query_user_application_with_deadline(
         &mut self,
         application_id: ApplicationId,
@@ -424,6 +446,7 @@ where
         query: Vec<u8>,
         deadline: Option<Instant>,
         created_blobs: BTreeMap<BlobId, BlobContent>,
+        use_service_overrides: bool,
     ) -> Result<QueryOutcome<Vec<u8>>, ExecutionError> {
         let (execution_state_sender, mut execution_state_receiver) =
             futures::channel::mpsc::unbounded();
@@ -431,6 +454,9 @@ where
         let mut resource_controller = ResourceController::default();
         let thread_pool = self.context().extra().thread_pool().clone();
         let mut actor = ExecutionStateActor::new(self, &mut txn_tracker, &mut resource_controller);
+        if use_service_overrides {
+            actor = actor.with_service_overrides();
+        }
 
         let (codes, descriptions) = actor.service_and_dependencies(application_id).await?;
 
@@ -481,7 +507,8 @@ where
 
         let mut txn_tracker = TransactionTracker::default();
         let mut resource_controller = ResourceController::default();
-        let mut actor = ExecutionStateActor::new(self, &mut txn_tracker, &mut resource_controller);
+        let mut actor = ExecutionStateActor::new(self, &mut txn_tracker, &mut resource_controller)
+            .with_service_overrides();
 
         loop {
             futures::select! {
@@ -510,4 +537,41 @@ where
         }
         Ok(applications)
     }
+
+    /// Loads the contracts of the given applications concurrently, so that executing their
+    /// transactions does not wait for each one to be fetched in turn.
+    ///
+    /// This does not change the chain state. Applications that cannot be loaded yet, e.g.
+    /// because they are created in the current block, are skipped and loaded as usual when
+    /// they execute.
+    pub async fn preload_contracts(
+        &self,
+        application_ids: impl IntoIterator<Item = ApplicationId>,
+    ) {
+        let context = self.context();
+        let extra = context.extra();
+        let txn_tracker = TransactionTracker::default();
+        let loads = application_ids
+            .into_iter()
+            .filter(|application_id| !extra.user_contracts().pin().contains_key(application_id))
+            .map(|application_id| {
+                let txn_tracker = &txn_tracker;
+                async move {
+                    let blob_content = self
+                        .system
+                        .read_blob_content(application_id.description_blob_id())
+                        .await?;
+                    let description: ApplicationDescription =
+                        bcs::from_bytes(blob_content.bytes())?;
+                    extra.get_user_contract(&description, txn_tracker).await?;
+                    Ok::<_, ExecutionError>(())
+                }
+                .map(move |result| (application_id, result))
+            });
+        for (application_id, result) in futures::future::join_all(loads).await {
+            if let Err(error) = result {
+                tracing::debug!(%application_id, %error, "Failed to preload a contract");
+            }
+        }
+    }
 }
@@ -14,8 +14,8 @@ use futures::{channel::mpsc, StreamExt as _};
 use linera_base::prometheus_util::MeasureLatency as _;
 use linera_base::{
     data_types::{
-        Amount, ApplicationPermissions, ArithmeticError, BlobContent, BlockHeight, OracleResponse,
-        StreamUpdate, Timestamp,
+        Amount, ApplicationPermissions, ArithmeticError, BlobContent, BlockHeight,
+        CommitteeSummary, OracleResponse, StreamUpdate, Timestamp,
     },
     ensure, hex_debug, hex_vec_debug, http,
     identifiers::{
@@ -32,14 +32,15 @@ use tracing::{info_span, instrument, Instrument as _};
 
 use crate::{
     execution::UserAction,
+    profiler::ActionProfile,
     runtime::ContractSyncRuntime,
     system::{CreateApplicationResult, OpenChainConfig},
     util::{OracleResponseExt as _, RespondExt as _},
-    ApplicationDescription, ApplicationId, ExecutionError, ExecutionRuntimeContext,
+    ApplicationDescription, ApplicationId, EventIndexKey, ExecutionError, ExecutionRuntimeContext,
     ExecutionStateView, JsVec, Message, MessageContext, MessageKind, ModuleId, Operation,
-    OperationContext, OutgoingMessage, ProcessStreamsContext, QueryContext, QueryOutcome,
-    ResourceController, SystemMessage, SystemOperation, TransactionTracker, UserContractCode,
-    UserServiceCode,
+    OperationContext, OutgoingMessage, ProcessStreamsContext, ProtocolFlag, QueryContext,
+    QueryOutcome, ResourceController, SystemMessage, SystemOperation, TransactionTracker,
+    UserContractCode, UserServiceCode,
 };
 
 /// Actor for handling requests to the execution state.
@@ -47,6 +48,9 @@ pub struct ExecutionStateActor<'a, C> {
     state: &'a mut ExecutionStateView<C>,
     txn_tracker: &'a mut TransactionTracker,
     resource_controller: &'a mut ResourceController<Option<AccountOwner>>,
+    /// Whether services are loaded for a query from outside the chain, and may therefore
+    /// use locally reloaded service modules.
+    use_service_overrides: bool,
 }
 
 #[cfg(with_metrics)]
@@ -94,9 +98,18 @@ where
             state,
             txn_tracker,
             resource_controller,
+            use_service_overrides: false,
         }
     }
 
+    /// Loads services with [`ExecutionRuntimeContext::get_user_service_for_query`], so that
+    /// locally reloaded service modules are used. Only for queries whose responses are not
+    /// recorded in blocks.
+    pub(crate) fn with_service_overrides(mut self) -> Self {
+        self.use_service_overrides = true;
+        self
+    }
+
     #[instrument(skip_all, fields(application_id = %id))]
     pub(crate) async fn load_contract(
         &mut self,
@@ -139,12 +152,16 @@ where
                     .await?
             }
         };
-        let code = self
-            .state
-            .context()
-            .extra()
-            .get_user_service(&description, self.txn_tracker)
-            .await?;
+        let extra = self.state.context().extra();
+        let code = if self.use_service_overrides {
+            extra
+                .get_user_service_for_query(&description, self.txn_tracker)
+                .await?
+        } else {
+            extra
+                .get_user_service(&description, self.txn_tracker)
+                .await?
+        };
         Ok((code, description))
     }
 
@@ -310,6 +327,22 @@ where
                 callback.respond(timestamp);
             }
 
+            RecentBlockTimestamps { callback } => {
+                let timestamps = self.state.system.progress.get().recent_timestamps.clone();
+                callback.respond(timestamps);
+            }
+
+            CommitteeSummary { callback } => {
+                let summary = self
+                    .state
+                    .system
+                    .current_committee()
+                    .await?
+                    .map(|(_epoch, committee)| committee.summary())
+                    .unwrap_or_default();
+                callback.respond(summary);
+            }
+
             ChainOwnership { callback } => {
                 let ownership = self.state.system.ownership.get().await?.clone();
                 callback.respond(ownership);
@@ -461,8 +494,7 @@ where
                 if !app_permissions.can_manage_chain(&application_id) {
                     callback.respond(Err(ExecutionError::UnauthorizedApplication(application_id)));
                 } else {
-                    self.state.system.ownership.set(ownership);
-                    callback.respond(Ok(()));
+                    callback.respond(self.state.system.change_ownership(ownership));
                 }
             }
 
@@ -513,9 +545,34 @@ where
 
             PerformHttpRequest {
                 request,
+                application_id,
+                block_height,
                 http_responses_are_oracle_responses,
                 callback,
             } => {
+                let mut usage = None;
+                if self
+                    .resource_controller
+                    .policy()
+                    .flags
+                    .contains(&ProtocolFlag::HttpApplicationQuotas)
+                {
+                    let epoch = *self.state.system.epoch.get();
+                    let mut application_usage = self
+                        .state
+                        .system
+                        .http_usage
+                        .get(&application_id)
+                        .await?
+                        .unwrap_or_default();
+                    self.resource_controller.track_application_http_request(
+                        application_id,
+                        &mut application_usage,
+                        epoch,
+                        block_height,
+                    )?;
+                    usage = Some(application_usage);
+                }
                 let system = &mut self.state.system;
                 let response = self
                     .txn_tracker
@@ -537,10 +594,10 @@ where
                             .current_committee()
                             .await?
                             .ok_or_else(|| ExecutionError::UnauthorizedHttpRequest(url.clone()))?;
-                        let allowed_hosts = &committee.policy().http_request_allow_list;
-
                         ensure!(
-                            allowed_hosts.contains(host),
+                            committee
+                                .policy()
+                                .is_http_request_allowed(&application_id, host),
                             ExecutionError::UnauthorizedHttpRequest(url)
                         );
 
@@ -568,6 +625,17 @@ where
                     })
                     .await?
                     .to_http_response()?;
+                if let Some(mut usage) = usage {
+                    self.resource_controller.track_application_http_response(
+                        application_id,
+                        &mut usage,
+                        response.body.len() as u64,
+                    )?;
+                    self.state
+                        .system
+                        .http_usage
+                        .insert(&application_id, usage)?;
+                }
                 callback.respond(response);
             }
 
@@ -615,6 +683,7 @@ where
             Emit {
                 stream_id,
                 value,
+                index_entries,
                 callback,
             } => {
                 let count = self
@@ -629,6 +698,18 @@ where
                     .with_state(&mut self.state.system)
                     .await?
                     .track_event_published(&value)?;
+                for (field, key) in index_entries {
+                    let index_key = EventIndexKey {
+                        stream_id: stream_id.clone(),
+                        field,
+                        key,
+                    };
+                    self.state
+                        .event_indexes
+                        .try_load_entry_mut(&index_key)
+                        .await?
+                        .push(index);
+                }
                 self.txn_tracker.add_event(stream_id, index, value);
                 callback.respond(index)
             }
@@ -748,6 +829,7 @@ where
                             query,
                             deadline,
                             created_blobs,
+                            false,
                         ))
                         .await?;
                         ensure!(
@@ -1070,6 +1152,9 @@ where
             .execution_runtime_config()
             .allow_application_logs;
 
+        let profile = ActionProfile::start(chain_id);
+        let runtime_profile = profile.clone();
+
         let contract_runtime_task = self
             .state
             .context()
@@ -1083,6 +1168,7 @@ where
                     controller,
                     &action,
                     allow_application_logs,
+                    runtime_profile,
                 );
 
                 for (code, description) in codes.0.into_iter().zip(descriptions) {
@@ -1095,14 +1181,25 @@ where
 
         async {
             while let Some(request) = execution_state_receiver.next().await {
+                let Some(profile) = &profile else {
+                    self.handle_request(request).await?;
+                    continue;
+                };
+                let host_function = request.as_ref().to_string();
+                let start = Instant::now();
                 self.handle_request(request).await?;
+                profile.record_host_function(&host_function, start.elapsed());
             }
             Ok::<(), ExecutionError>(())
         }
         .instrument(info_span!("handle_runtime_requests"))
         .await?;
 
-        let (result, controller) = contract_runtime_task.await??;
+        let runtime_result = contract_runtime_task.await;
+        if let Some(profile) = profile {
+            profile.finish();
+        }
+        let (result, controller) = runtime_result??;
 
         self.resource_controller.is_free = false;
 
@@ -1422,6 +1519,16 @@ pub enum ExecutionRequest {
         callback: Sender<Timestamp>,
     },
 
+    RecentBlockTimestamps {
+        #[debug(skip)]
+        callback: Sender<Vec<Timestamp>>,
+    },
+
+    CommitteeSummary {
+        #[debug(skip)]
+        callback: Sender<CommitteeSummary>,
+    },
+
     ChainOwnership {
         #[debug(skip)]
         callback: Sender<ChainOwnership>,
@@ -1540,6 +1647,9 @@ pub enum ExecutionRequest {
 
     PerformHttpRequest {
         request: http::Request,
+        application_id: ApplicationId,
+        /// The block the request is made in, or the next block for services.
+        block_height: BlockHeight,
         http_responses_are_oracle_responses: bool,
         #[debug(skip)]
         callback: Sender<http::Response>,
@@ -1561,6 +1671,7 @@ pub enum ExecutionRequest {
         stream_id: StreamId,
         #[debug(with = hex_debug)]
         value: Vec<u8>,
+        index_entries: Vec<(String, Vec<u8>)>,
         #[debug(skip)]
         callback: Sender<u32>,
     },
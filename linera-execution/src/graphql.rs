@@ -7,7 +7,7 @@ use linera_base::{
     crypto::{CryptoHash, ValidatorPublicKey},
     data_types::{Amount, ChainDescription, Epoch, Timestamp},
     doc_scalar,
-    identifiers::{AccountOwner, ChainId},
+    identifiers::{AccountOwner, ApplicationId, ChainId},
     ownership::ChainOwnership,
 };
 use linera_views::{context::Context, map_view::MapView};
@@ -16,7 +16,7 @@ use crate::{
     committee::{Committee, ValidatorState},
     policy::ResourceControlPolicy,
     system::UserData,
-    ExecutionStateView, SystemExecutionStateView,
+    ExecutionStateView, HttpUsage, SystemExecutionStateView,
 };
 
 doc_scalar!(UserData, "Optional user message attached to a transfer");
@@ -104,4 +104,9 @@ impl<C: Send + Sync + Context> SystemExecutionStateView<C> {
     async fn _timestamp(&self) -> &Timestamp {
         &self.progress.get().timestamp
     }
+
+    #[graphql(derived(name = "http_usage"))]
+    async fn _http_usage(&self) -> &MapView<C, ApplicationId, HttpUsage> {
+        &self.http_usage
+    }
 }
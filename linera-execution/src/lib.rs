@@ -14,6 +14,8 @@ pub mod execution_state_actor;
 #[cfg(with_graphql)]
 mod graphql;
 mod policy;
+/// Opt-in profiling of the execution time of user applications, per chain.
+pub mod profiler;
 mod resources;
 mod runtime;
 /// The system application implementing core chain functionality.
@@ -39,8 +41,8 @@ use linera_base::{
     crypto::{BcsHashable, CryptoHash},
     data_types::{
         Amount, ApplicationDescription, ApplicationPermissions, ArithmeticError, Blob, BlockHeight,
-        Bytecode, DecompressionError, Epoch, NetworkDescription, SendMessageRequest, StreamUpdate,
-        Timestamp,
+        Bytecode, CommitteeSummary, DecompressionError, Epoch, NetworkDescription, ResourcePrices,
+        SendMessageRequest, StreamUpdate, Timestamp,
     },
     doc_scalar, ensure, hex_debug, http,
     identifiers::{
@@ -71,17 +73,17 @@ pub use crate::wasm::{
 };
 pub use crate::{
     committee::{Committee, SharedCommittees},
-    execution::{ExecutionStateView, ServiceRuntimeEndpoint},
+    execution::{EventIndexKey, ExecutionStateView, ServiceRuntimeEndpoint},
     execution_state_actor::{ExecutionRequest, ExecutionStateActor},
-    policy::{ProtocolFlag, ResourceControlPolicy},
-    resources::{BalanceHolder, ResourceController, ResourceTracker},
+    policy::{HttpAllowListUpdate, ProtocolFlag, ResourceControlPolicy},
+    resources::{BalanceHolder, HttpUsage, ResourceController, ResourceTracker},
     runtime::{
         ContractSyncRuntimeHandle, ServiceRuntimeRequest, ServiceSyncRuntime,
         ServiceSyncRuntimeHandle,
     },
     system::{
         ChainProgress, SystemExecutionStateView, SystemMessage, SystemOperation, SystemQuery,
-        SystemResponse,
+        SystemResponse, MAX_RECENT_BLOCK_TIMESTAMPS,
     },
     transaction_tracker::{PreparedCheckpoint, TransactionOutcome, TransactionTracker},
 };
@@ -96,6 +98,12 @@ pub const LINERA_TYPES_SOL: &str = include_str!("../solidity/LineraTypes.sol");
 /// The maximum length of a stream name.
 const MAX_STREAM_NAME_LEN: usize = 64;
 
+/// The maximum number of index entries of an event.
+const MAX_EVENT_INDEX_ENTRIES: usize = 8;
+
+/// The maximum length of the field and of the key of an event index entry.
+const MAX_EVENT_INDEX_KEY_LEN: usize = 64;
+
 /// An implementation of [`UserContractModule`].
 #[derive(Clone)]
 pub struct UserContractCode(Box<dyn UserContractModule>);
@@ -272,6 +280,11 @@ pub enum ExecutionError {
     },
     #[error("Failed to load bytecode from storage {0:?}")]
     ApplicationBytecodeNotFound(Box<ApplicationDescription>),
+    #[error("Module {module_id:?} cannot replace the service of application {application_id}")]
+    IncompatibleServiceModule {
+        application_id: Box<ApplicationId>,
+        module_id: Box<ModuleId>,
+    },
     // TODO(#2927): support dynamic loading of modules on the Web
     #[error("Unsupported dynamic application load: {0:?}")]
     UnsupportedDynamicApplicationLoad(Box<ApplicationId>),
@@ -290,6 +303,10 @@ pub enum ExecutionError {
     BlockTooLarge,
     #[error("HTTP response exceeds the size limit of {limit} bytes, having at least {size} bytes")]
     HttpResponseSizeLimitExceeded { limit: u64, size: u64 },
+    #[error("Application {0} exceeded its quota of HTTP requests or response bytes in this block")]
+    MaximumHttpUsagePerBlockExceeded(ApplicationId),
+    #[error("Application {0} exceeded its quota of HTTP requests or response bytes in this epoch")]
+    MaximumHttpUsagePerEpochExceeded(ApplicationId),
     #[error("Runtime failed to respond to application")]
     MissingRuntimeResponse,
     #[error("Application is not authorized to perform system operations on this chain: {0:}")]
@@ -316,6 +333,11 @@ pub enum ExecutionError {
 
     #[error("Stream names can be at most {MAX_STREAM_NAME_LEN} bytes.")]
     StreamNameTooLong,
+    #[error(
+        "Events can have at most {MAX_EVENT_INDEX_ENTRIES} index entries, whose fields and keys \
+         can be at most {MAX_EVENT_INDEX_KEY_LEN} bytes."
+    )]
+    EventIndexTooLarge,
     #[error("Blob exceeds size limit")]
     BlobTooLarge,
     #[error("Bytecode exceeds size limit")]
@@ -379,6 +401,20 @@ pub enum ExecutionError {
     UnsubscribedUpdateStream,
     #[error("Checkpoint precondition failed: {0}")]
     CheckpointPreconditionFailed(&'static str),
+    #[error("Governance operations must be authenticated by an owner of the admin chain")]
+    NotAdminChainOwner,
+    #[error("Unknown governance proposal {0}")]
+    UnknownGovernanceProposal(u32),
+    #[error("The voting period of governance proposal {0} is over")]
+    GovernanceVotingClosed(u32),
+    #[error("The voting period of governance proposal {0} is not over yet")]
+    GovernanceVotingOpen(u32),
+    #[error(
+        "The published committee is not the current one with the HTTP allow list update applied"
+    )]
+    HttpAllowListUpdateMismatch,
+    #[error("Owner {0} cannot sign with any of the chain's allowed signature schemes")]
+    DisallowedOwnerSignatureScheme(AccountOwner),
 }
 
 impl ExecutionError {
@@ -402,6 +438,8 @@ impl ExecutionError {
             | ExecutionError::ServiceOracleResponseTooLarge
             | ExecutionError::BlockTooLarge
             | ExecutionError::HttpResponseSizeLimitExceeded { .. }
+            | ExecutionError::MaximumHttpUsagePerBlockExceeded(_)
+            | ExecutionError::MaximumHttpUsagePerEpochExceeded(_)
             | ExecutionError::UnauthorizedApplication(_)
             | ExecutionError::UnexpectedOracleResponse
             | ExecutionError::JsonError(_)
@@ -410,6 +448,7 @@ impl ExecutionError {
             | ExecutionError::ServiceOracleQueryOperations(_)
             | ExecutionError::AssertBefore { .. }
             | ExecutionError::StreamNameTooLong
+            | ExecutionError::EventIndexTooLarge
             | ExecutionError::BlobTooLarge
             | ExecutionError::BytecodeTooLarge
             | ExecutionError::UnauthorizedHttpRequest(_)
@@ -434,6 +473,13 @@ impl ExecutionError {
             | ExecutionError::OutdatedUpdateStream
             | ExecutionError::UnsubscribedUpdateStream
             | ExecutionError::CheckpointPreconditionFailed(_)
+            | ExecutionError::NotAdminChainOwner
+            | ExecutionError::UnknownGovernanceProposal(_)
+            | ExecutionError::GovernanceVotingClosed(_)
+            | ExecutionError::GovernanceVotingOpen(_)
+            | ExecutionError::HttpAllowListUpdateMismatch
+            | ExecutionError::DisallowedOwnerSignatureScheme(_)
+            | ExecutionError::IncompatibleServiceModule { .. }
             | ExecutionError::ViewError(ViewError::NotFound(_)) => false,
             #[cfg(with_wasm_runtime)]
             ExecutionError::WasmError(_) => false,
@@ -468,6 +514,7 @@ impl ExecutionError {
                 | ExecutionError::MaximumFuelExceeded(_)
                 | ExecutionError::MaximumServiceOracleExecutionTimeExceeded
                 | ExecutionError::BlockTooLarge
+                | ExecutionError::MaximumHttpUsagePerBlockExceeded(_)
         )
     }
 
@@ -562,6 +609,18 @@ pub trait ExecutionRuntimeContext {
         txn_tracker: &TransactionTracker,
     ) -> Result<UserServiceCode, ExecutionError>;
 
+    /// Loads the service for the given application to answer a query from outside the
+    /// chain, e.g. from the node service. Unlike [`Self::get_user_service`], this may use
+    /// a locally reloaded service module. It is never used when the response is recorded
+    /// in a block, e.g. for service oracle calls.
+    async fn get_user_service_for_query(
+        &self,
+        description: &ApplicationDescription,
+        txn_tracker: &TransactionTracker,
+    ) -> Result<UserServiceCode, ExecutionError> {
+        self.get_user_service(description, txn_tracker).await
+    }
+
     /// Returns the blob with the given ID, if it is available.
     async fn get_blob(&self, blob_id: BlobId) -> Result<Option<Arc<Blob>>, ViewError>;
 
@@ -1061,6 +1120,15 @@ pub trait ContractRuntime: BaseRuntime {
     /// Adds a new item to an event stream. Returns the new event's index in the stream.
     fn emit(&mut self, name: StreamName, value: Vec<u8>) -> Result<u32, ExecutionError>;
 
+    /// Adds a new item to an event stream, and records it in the stream's indexes under the
+    /// given `(field, key)` entries. Returns the new event's index in the stream.
+    fn emit_indexed(
+        &mut self,
+        name: StreamName,
+        value: Vec<u8>,
+        index_entries: Vec<(String, Vec<u8>)>,
+    ) -> Result<u32, ExecutionError>;
+
     /// Reads an event from a stream. Returns the event's value.
     ///
     /// Returns an error if the event doesn't exist.
@@ -1143,6 +1211,16 @@ pub trait ContractRuntime: BaseRuntime {
     /// Returns the multi-leader round in which this block was validated.
     fn validation_round(&mut self) -> Result<Option<u32>, ExecutionError>;
 
+    /// Returns the number of validators and the total votes of the current committee.
+    fn read_committee_summary(&mut self) -> Result<CommitteeSummary, ExecutionError>;
+
+    /// Returns the prices of the resource control policy in force for this block.
+    fn read_resource_prices(&mut self) -> Result<ResourcePrices, ExecutionError>;
+
+    /// Returns the timestamps of the latest blocks of the current chain, oldest first,
+    /// including the current block.
+    fn read_recent_block_timestamps(&mut self) -> Result<Vec<Timestamp>, ExecutionError>;
+
     /// Writes a batch of changes.
     fn write_batch(&mut self, batch: Batch) -> Result<(), ExecutionError>;
 }
@@ -1345,6 +1423,7 @@ pub struct TestExecutionRuntimeContext {
     execution_runtime_config: ExecutionRuntimeConfig,
     user_contracts: Arc<papaya::HashMap<ApplicationId, UserContractCode>>,
     user_services: Arc<papaya::HashMap<ApplicationId, UserServiceCode>>,
+    service_overrides: Arc<papaya::HashMap<ApplicationId, UserServiceCode>>,
     blobs: Arc<papaya::HashMap<BlobId, Blob>>,
     events: Arc<papaya::HashMap<EventId, Vec<u8>>>,
 }
@@ -1359,10 +1438,17 @@ impl TestExecutionRuntimeContext {
             execution_runtime_config,
             user_contracts: Arc::default(),
             user_services: Arc::default(),
+            service_overrides: Arc::default(),
             blobs: Arc::default(),
             events: Arc::default(),
         }
     }
+
+    /// Returns the services used instead of the regular ones for queries from outside the
+    /// chain.
+    pub fn service_overrides(&self) -> &Arc<papaya::HashMap<ApplicationId, UserServiceCode>> {
+        &self.service_overrides
+    }
 }
 
 #[cfg(with_testing)]
@@ -1419,6 +1505,18 @@ impl ExecutionRuntimeContext for TestExecutionRuntimeContext {
             .clone())
     }
 
+    async fn get_user_service_for_query(
+        &self,
+        description: &ApplicationDescription,
+        txn_tracker: &TransactionTracker,
+    ) -> Result<UserServiceCode, ExecutionError> {
+        let application_id: ApplicationId = description.into();
+        if let Some(service) = self.service_overrides.pin().get(&application_id) {
+            return Ok(service.clone());
+        }
+        self.get_user_service(description, txn_tracker).await
+    }
+
     async fn get_blob(&self, blob_id: BlobId) -> Result<Option<Arc<Blob>>, ViewError> {
         Ok(self.blobs.pin().get(&blob_id).cloned().map(Arc::new))
     }
@@ -9,11 +9,16 @@
 //! It also sets overarching limits such as the maximum fuel allowed per block,
 //! the maximum block size, and limits on concurrent operations.
 
-use std::{collections::BTreeSet, fmt};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
 
 use allocative::Allocative;
 use linera_base::{
-    data_types::{Amount, ArithmeticError, BlobContent, CompressedBytecode, Resources},
+    data_types::{
+        Amount, ArithmeticError, BlobContent, CompressedBytecode, ResourcePrices, Resources,
+    },
     ensure,
     identifiers::{ApplicationId, BlobType},
     vm::VmRuntime,
@@ -46,9 +51,40 @@ use crate::ExecutionError;
 pub enum ProtocolFlag {
     #[doc(hidden)]
     _Reserved = 0,
+    /// Enforces the per-application HTTP quotas of the policy, e.g.
+    /// [`ResourceControlPolicy::maximum_http_requests_per_application_per_block`]. Without
+    /// this flag, the quotas are ignored and no HTTP usage is recorded in the chain state, so
+    /// that chains keep executing blocks the same way as before the quotas were introduced.
+    /// Committee blobs only encode the quotas if this flag is enabled.
+    HttpApplicationQuotas = 1,
+    /// Lets specific applications send HTTP requests to more hosts, as listed in
+    /// [`ResourceControlPolicy::http_request_allow_list_overrides`]. Committee blobs only
+    /// encode these hosts if this flag is enabled.
+    HttpAllowListOverrides = 2,
+}
+
+/// A change to the hosts that contracts and services can send HTTP requests to.
+#[derive(Default, Eq, PartialEq, Hash, Clone, Debug, Serialize, Deserialize, Allocative)]
+pub struct HttpAllowListUpdate {
+    /// The application whose own allowed hosts are changed, or `None` to change the hosts
+    /// allowed for all applications.
+    pub application_id: Option<ApplicationId>,
+    /// The hosts to allow.
+    pub add: BTreeSet<String>,
+    /// The hosts to no longer allow. Hosts in both sets end up removed.
+    pub remove: BTreeSet<String>,
 }
 
 /// A collection of prices and limits associated with block execution.
+///
+/// The policy is part of every committee blob, so its binary format must stay readable by
+/// validators and clients: committee blobs contain the fields of the original format,
+/// followed by each field added since then only if the [`ProtocolFlag`] of its feature is
+/// enabled (see [`Committee`](crate::committee::Committee)). New fields go at the end of the
+/// struct, with a `serde` default so that older JSON configurations still load.
+///
+/// Any change to the policy of a running network, including to the HTTP allow lists,
+/// registers a new committee in the next epoch.
 #[derive(Eq, PartialEq, Hash, Clone, Debug, Serialize, Deserialize, Allocative)]
 pub struct ResourceControlPolicy {
     /// The price per unit of fuel (aka gas) for Wasm execution.
@@ -121,6 +157,109 @@ pub struct ResourceControlPolicy {
     pub free_application_ids: BTreeSet<ApplicationId>,
     /// The set of optional protocol features that are enabled.
     pub flags: BTreeSet<ProtocolFlag>,
+    /// The hosts that specific applications can send HTTP requests to, in addition to
+    /// [`ResourceControlPolicy::http_request_allow_list`]. Only part of committee blobs if
+    /// [`ProtocolFlag::HttpAllowListOverrides`] is enabled.
+    #[serde(default)]
+    pub http_request_allow_list_overrides: BTreeMap<ApplicationId, BTreeSet<String>>,
+    /// The maximum number of HTTP requests that an application can make in a block.
+    ///
+    /// This quota and the three following ones are only part of committee blobs, and only
+    /// enforced, if [`ProtocolFlag::HttpApplicationQuotas`] is enabled.
+    #[serde(default = "unlimited")]
+    pub maximum_http_requests_per_application_per_block: u64,
+    /// The maximum number of bytes of HTTP responses that an application can receive in a
+    /// block.
+    #[serde(default = "unlimited")]
+    pub maximum_http_response_bytes_per_application_per_block: u64,
+    /// The maximum number of HTTP requests that an application can make on a chain during
+    /// an epoch.
+    #[serde(default = "unlimited")]
+    pub maximum_http_requests_per_application_per_epoch: u64,
+    /// The maximum number of bytes of HTTP responses that an application can receive on a
+    /// chain during an epoch.
+    #[serde(default = "unlimited")]
+    pub maximum_http_response_bytes_per_application_per_epoch: u64,
+}
+
+/// The original format of [`ResourceControlPolicy`], as used in committee blobs. The fields
+/// added since then are left out, and take their default values when reading it.
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ResourceControlPolicy", rename = "ResourceControlPolicy")]
+// The struct only describes the format: `serde` reads and writes the policy's fields.
+#[allow(dead_code)]
+pub(crate) struct OriginalPolicyFormat {
+    wasm_fuel_unit: Amount,
+    evm_fuel_unit: Amount,
+    read_operation: Amount,
+    write_operation: Amount,
+    byte_runtime: Amount,
+    byte_read: Amount,
+    byte_written: Amount,
+    blob_read: Amount,
+    blob_published: Amount,
+    blob_byte_read: Amount,
+    blob_byte_published: Amount,
+    operation: Amount,
+    operation_byte: Amount,
+    message: Amount,
+    message_byte: Amount,
+    service_as_oracle_query: Amount,
+    http_request: Amount,
+    maximum_wasm_fuel_per_block: u64,
+    maximum_evm_fuel_per_block: u64,
+    maximum_service_oracle_execution_ms: u64,
+    maximum_block_size: u64,
+    maximum_bytecode_size: u64,
+    maximum_blob_size: u64,
+    maximum_published_blobs: u64,
+    maximum_block_proposal_size: u64,
+    maximum_bytes_read_per_block: u64,
+    maximum_bytes_written_per_block: u64,
+    maximum_oracle_response_bytes: u64,
+    maximum_http_response_bytes: u64,
+    http_request_timeout_ms: u64,
+    http_request_allow_list: BTreeSet<String>,
+    free_application_ids: BTreeSet<ApplicationId>,
+    flags: BTreeSet<ProtocolFlag>,
+    #[serde(skip)]
+    http_request_allow_list_overrides: BTreeMap<ApplicationId, BTreeSet<String>>,
+    #[serde(skip, default = "unlimited")]
+    maximum_http_requests_per_application_per_block: u64,
+    #[serde(skip, default = "unlimited")]
+    maximum_http_response_bytes_per_application_per_block: u64,
+    #[serde(skip, default = "unlimited")]
+    maximum_http_requests_per_application_per_epoch: u64,
+    #[serde(skip, default = "unlimited")]
+    maximum_http_response_bytes_per_application_per_epoch: u64,
+}
+
+fn unlimited() -> u64 {
+    u64::MAX
+}
+
+/// A policy to serialize in its [`OriginalPolicyFormat`].
+pub(crate) struct OriginalPolicyRef<'a>(pub &'a ResourceControlPolicy);
+
+impl Serialize for OriginalPolicyRef<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        OriginalPolicyFormat::serialize(self.0, serializer)
+    }
+}
+
+/// A policy deserialized from its [`OriginalPolicyFormat`].
+pub(crate) struct OriginalPolicy(pub ResourceControlPolicy);
+
+impl<'de> Deserialize<'de> for OriginalPolicy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::de::Deserializer<'de>,
+    {
+        OriginalPolicyFormat::deserialize(deserializer).map(OriginalPolicy)
+    }
 }
 
 impl fmt::Display for ResourceControlPolicy {
@@ -159,6 +298,11 @@ impl fmt::Display for ResourceControlPolicy {
             http_request_timeout_ms,
             free_application_ids,
             flags,
+            http_request_allow_list_overrides,
+            maximum_http_requests_per_application_per_block,
+            maximum_http_response_bytes_per_application_per_block,
+            maximum_http_requests_per_application_per_epoch,
+            maximum_http_response_bytes_per_application_per_epoch,
         } = self;
         write!(
             f,
@@ -194,7 +338,17 @@ impl fmt::Display for ResourceControlPolicy {
             {maximum_oracle_response_bytes} maximum number of bytes of an oracle response\n\
             {maximum_http_response_bytes} maximum number of bytes of an HTTP response\n\
             {http_request_timeout_ms} ms timeout for HTTP requests\n\
+            {maximum_http_requests_per_application_per_block} maximum HTTP requests per \
+                application per block\n\
+            {maximum_http_response_bytes_per_application_per_block} maximum bytes of HTTP \
+                responses per application per block\n\
+            {maximum_http_requests_per_application_per_epoch} maximum HTTP requests per \
+                application per epoch\n\
+            {maximum_http_response_bytes_per_application_per_epoch} maximum bytes of HTTP \
+                responses per application per epoch\n\
             HTTP hosts allowed for contracts and services: {http_request_allow_list:#?}\n\
+            HTTP hosts allowed for specific applications: \
+                {http_request_allow_list_overrides:#?}\n\
             Free application IDs: {free_application_ids:#?}\n\
             Enabled protocol flags: {flags:#?}\n",
         )?;
@@ -247,6 +401,11 @@ impl ResourceControlPolicy {
             http_request_allow_list: BTreeSet::new(),
             free_application_ids: BTreeSet::new(),
             flags: BTreeSet::new(),
+            http_request_allow_list_overrides: BTreeMap::new(),
+            maximum_http_requests_per_application_per_block: u64::MAX,
+            maximum_http_response_bytes_per_application_per_block: u64::MAX,
+            maximum_http_requests_per_application_per_epoch: u64::MAX,
+            maximum_http_response_bytes_per_application_per_epoch: u64::MAX,
         }
     }
 
@@ -255,6 +414,55 @@ impl ResourceControlPolicy {
         self.free_application_ids.contains(app_id)
     }
 
+    /// Returns whether the given application can send HTTP requests to `host`.
+    pub fn is_http_request_allowed(&self, app_id: &ApplicationId, host: &str) -> bool {
+        self.http_request_allow_list.contains(host)
+            || self
+                .http_request_allow_list_overrides
+                .get(app_id)
+                .is_some_and(|hosts| hosts.contains(host))
+    }
+
+    /// Adds and removes the hosts of the given update, leaving the other allowed hosts
+    /// unchanged.
+    pub fn update_http_request_allow_list(&mut self, update: &HttpAllowListUpdate) {
+        let HttpAllowListUpdate {
+            application_id,
+            add,
+            remove,
+        } = update;
+        let hosts = match application_id {
+            None => &mut self.http_request_allow_list,
+            Some(app_id) => {
+                self.flags.insert(ProtocolFlag::HttpAllowListOverrides);
+                self.http_request_allow_list_overrides
+                    .entry(*app_id)
+                    .or_default()
+            }
+        };
+        hosts.extend(add.iter().cloned());
+        hosts.retain(|host| !remove.contains(host));
+        self.http_request_allow_list_overrides
+            .retain(|_, hosts| !hosts.is_empty());
+    }
+
+    /// Enables the protocol flags of the optional features whose fields are set, since
+    /// committees only encode these fields together with their flag.
+    pub fn enable_flags_of_set_fields(&mut self) {
+        if !self.http_request_allow_list_overrides.is_empty() {
+            self.flags.insert(ProtocolFlag::HttpAllowListOverrides);
+        }
+        let quotas = [
+            self.maximum_http_requests_per_application_per_block,
+            self.maximum_http_response_bytes_per_application_per_block,
+            self.maximum_http_requests_per_application_per_epoch,
+            self.maximum_http_response_bytes_per_application_per_epoch,
+        ];
+        if quotas != [u64::MAX; 4] {
+            self.flags.insert(ProtocolFlag::HttpApplicationQuotas);
+        }
+    }
+
     /// The maximum fuel per block according to the `VmRuntime`.
     pub fn maximum_fuel_per_block(&self, vm_runtime: VmRuntime) -> u64 {
         match vm_runtime {
@@ -263,6 +471,72 @@ impl ResourceControlPolicy {
         }
     }
 
+    /// Returns the prices of this policy, as seen by applications.
+    ///
+    /// The prices are exactly the [`Amount`] fields of the policy. The policy is destructured
+    /// without `..`, so a field added to it must be classified here as a price or a limit.
+    pub fn prices(&self) -> ResourcePrices {
+        let &ResourceControlPolicy {
+            wasm_fuel_unit,
+            evm_fuel_unit,
+            read_operation,
+            write_operation,
+            byte_runtime,
+            byte_read,
+            byte_written,
+            blob_read,
+            blob_published,
+            blob_byte_read,
+            blob_byte_published,
+            operation,
+            operation_byte,
+            message,
+            message_byte,
+            service_as_oracle_query,
+            http_request,
+            maximum_wasm_fuel_per_block: _,
+            maximum_evm_fuel_per_block: _,
+            maximum_service_oracle_execution_ms: _,
+            maximum_block_size: _,
+            maximum_blob_size: _,
+            maximum_published_blobs: _,
+            maximum_bytecode_size: _,
+            maximum_block_proposal_size: _,
+            maximum_bytes_read_per_block: _,
+            maximum_bytes_written_per_block: _,
+            maximum_oracle_response_bytes: _,
+            maximum_http_response_bytes: _,
+            http_request_allow_list: _,
+            http_request_timeout_ms: _,
+            free_application_ids: _,
+            flags: _,
+            http_request_allow_list_overrides: _,
+            maximum_http_requests_per_application_per_block: _,
+            maximum_http_response_bytes_per_application_per_block: _,
+            maximum_http_requests_per_application_per_epoch: _,
+            maximum_http_response_bytes_per_application_per_epoch: _,
+        } = self;
+        ResourcePrices {
+            wasm_fuel_unit,
+            evm_fuel_unit,
+            read_operation,
+            write_operation,
+            byte_runtime,
+            byte_read,
+            byte_written,
+            blob_read,
+            blob_published,
+            blob_byte_read,
+            blob_byte_published,
+            operation,
+            operation_byte,
+            message,
+            message_byte,
+            service_as_oracle_query,
+            http_request,
+        }
+    }
+
     /// Creates a policy with no cost for anything except fuel.
     ///
     /// This can be used in tests that need whole numbers in their chain balance.
@@ -332,6 +606,11 @@ impl ResourceControlPolicy {
             http_request_allow_list: BTreeSet::new(),
             free_application_ids: BTreeSet::new(),
             flags: BTreeSet::new(),
+            http_request_allow_list_overrides: BTreeMap::new(),
+            maximum_http_requests_per_application_per_block: u64::MAX,
+            maximum_http_response_bytes_per_application_per_block: u64::MAX,
+            maximum_http_requests_per_application_per_epoch: u64::MAX,
+            maximum_http_response_bytes_per_application_per_epoch: u64::MAX,
         }
     }
 
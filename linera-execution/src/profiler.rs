@@ -0,0 +1,185 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Opt-in profiling of the time spent executing user applications, per chain.
+//!
+//! Once [`enable`]d, every user action executed in a block records how long each
+//! application ran, and how long it waited for each request to the execution state, e.g. to
+//! read its storage or to load another contract. These requests are the host functions
+//! that are not answered by the runtime alone. The times are aggregated per chain, as the
+//! time spent in each stack of frames excluding the frames it called, like the samples of
+//! a CPU profiler, so that they can be rendered as a flamegraph.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    mem,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, LazyLock, Mutex,
+    },
+    time::Duration,
+};
+
+use linera_base::{identifiers::ChainId, time::Instant};
+
+#[cfg(test)]
+#[path = "unit_tests/profiler_tests.rs"]
+mod tests;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+static PROFILES: LazyLock<Mutex<BTreeMap<ChainId, ChainProfile>>> =
+    LazyLock::new(|| Mutex::new(BTreeMap::new()));
+
+/// Starts profiling the execution of user applications in this process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Returns whether the execution of user applications is profiled.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Returns the profiles recorded so far, by chain.
+pub fn profiles() -> BTreeMap<ChainId, ChainProfile> {
+    PROFILES.lock().unwrap().clone()
+}
+
+/// Discards the profiles recorded so far.
+pub fn reset() {
+    PROFILES.lock().unwrap().clear();
+}
+
+/// The execution time of the applications of a chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChainProfile {
+    /// The time spent in each stack of frames, outermost first, excluding the time spent
+    /// in the frames it called. A frame is an application ID or the name of a host
+    /// function.
+    pub stacks: BTreeMap<Vec<String>, Duration>,
+}
+
+impl ChainProfile {
+    /// Returns the total time recorded for the chain.
+    pub fn total(&self) -> Duration {
+        self.stacks.values().sum()
+    }
+
+    fn add(&mut self, stack: Vec<String>, duration: Duration) {
+        *self.stacks.entry(stack).or_default() += duration;
+    }
+}
+
+/// Renders `profiles` in the folded format of flamegraph tools such as `inferno` or
+/// `flamegraph.pl`: one line per stack, with the chain ID as the outermost frame and the
+/// time in microseconds.
+pub fn folded(profiles: &BTreeMap<ChainId, ChainProfile>) -> String {
+    let mut output = String::new();
+    for (chain_id, profile) in profiles {
+        for (stack, duration) in &profile.stacks {
+            let micros = duration.as_micros();
+            if micros > 0 {
+                writeln!(output, "{chain_id};{} {micros}", stack.join(";"))
+                    .expect("writing to a string cannot fail");
+            }
+        }
+    }
+    output
+}
+
+/// A frame being executed.
+#[derive(Debug)]
+struct Frame {
+    name: String,
+    start: Instant,
+    /// The time spent in the frames called from this one.
+    children: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Recording {
+    frames: Vec<Frame>,
+    profile: ChainProfile,
+}
+
+impl Recording {
+    fn stack(&self) -> Vec<String> {
+        self.frames.iter().map(|frame| frame.name.clone()).collect()
+    }
+
+    fn exit(&mut self) {
+        let stack = self.stack();
+        let Some(frame) = self.frames.pop() else {
+            return;
+        };
+        let elapsed = frame.start.elapsed();
+        self.profile
+            .add(stack, elapsed.saturating_sub(frame.children));
+        if let Some(caller) = self.frames.last_mut() {
+            caller.children += elapsed;
+        }
+    }
+}
+
+/// The profile of a single user action, shared by the runtime executing the applications
+/// and the actor answering their requests to the execution state.
+#[derive(Clone, Debug)]
+pub(crate) struct ActionProfile {
+    chain_id: ChainId,
+    recording: Arc<Mutex<Recording>>,
+}
+
+impl ActionProfile {
+    /// Starts profiling an action on the given chain, if profiling is enabled.
+    pub(crate) fn start(chain_id: ChainId) -> Option<Self> {
+        is_enabled().then(|| ActionProfile {
+            chain_id,
+            recording: Arc::default(),
+        })
+    }
+
+    /// Records that the given application starts executing, called by the current one.
+    pub(crate) fn enter(&self, name: String) {
+        self.recording.lock().unwrap().frames.push(Frame {
+            name,
+            start: Instant::now(),
+            children: Duration::ZERO,
+        });
+    }
+
+    /// Records that the current application returns to its caller.
+    pub(crate) fn exit(&self) {
+        self.recording.lock().unwrap().exit();
+    }
+
+    /// Records that the current application waited `duration` for the host function
+    /// `name`.
+    pub(crate) fn record_host_function(&self, name: &str, duration: Duration) {
+        let mut recording = self.recording.lock().unwrap();
+        let mut stack = recording.stack();
+        stack.push(name.to_string());
+        recording.profile.add(stack, duration);
+        if let Some(frame) = recording.frames.last_mut() {
+            frame.children += duration;
+        }
+    }
+
+    /// Adds the times recorded for the action to the profile of its chain. Applications
+    /// that did not return, e.g. because they failed, are considered to end now.
+    pub(crate) fn finish(self) {
+        let profile = {
+            let mut recording = self.recording.lock().unwrap();
+            while !recording.frames.is_empty() {
+                recording.exit();
+            }
+            mem::take(&mut recording.profile)
+        };
+        let mut profiles = PROFILES.lock().unwrap();
+        let chain_profile = profiles.entry(self.chain_id).or_default();
+        for (stack, duration) in profile.stacks {
+            chain_profile.add(stack, duration);
+        }
+    }
+}
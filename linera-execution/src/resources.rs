@@ -5,16 +5,17 @@
 
 use std::{fmt, sync::Arc, time::Duration};
 
+use allocative::Allocative;
 use custom_debug_derive::Debug;
 use linera_base::{
-    data_types::{Amount, ApplicationDescription, ArithmeticError, Blob},
+    data_types::{Amount, ApplicationDescription, ArithmeticError, Blob, BlockHeight, Epoch},
     ensure,
-    identifiers::AccountOwner,
+    identifiers::{AccountOwner, ApplicationId},
     ownership::ChainOwnership,
     vm::VmRuntime,
 };
 use linera_views::{context::Context, ViewError};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{ExecutionError, Message, Operation, ResourceControlPolicy, SystemExecutionStateView};
 
@@ -51,6 +52,68 @@ impl<Account, Tracker> ResourceController<Account, Tracker> {
     pub fn tracker(&self) -> &Tracker {
         &self.tracker
     }
+
+    /// Counts an HTTP request of an application in its `usage`, failing if the application
+    /// already used up its quota of requests for the current block or epoch.
+    pub fn track_application_http_request(
+        &self,
+        application_id: ApplicationId,
+        usage: &mut HttpUsage,
+        epoch: Epoch,
+        block_height: BlockHeight,
+    ) -> Result<(), ExecutionError> {
+        usage.roll_over(epoch, block_height);
+        usage.block_requests = usage
+            .block_requests
+            .checked_add(1)
+            .ok_or(ArithmeticError::Overflow)?;
+        usage.epoch_requests = usage
+            .epoch_requests
+            .checked_add(1)
+            .ok_or(ArithmeticError::Overflow)?;
+        self.check_application_http_usage(application_id, usage)
+    }
+
+    /// Counts the size of an HTTP response in the `usage` of the application that made the
+    /// request, failing if the application exceeded its quota of bytes for the current block
+    /// or epoch.
+    pub fn track_application_http_response(
+        &self,
+        application_id: ApplicationId,
+        usage: &mut HttpUsage,
+        size: u64,
+    ) -> Result<(), ExecutionError> {
+        usage.block_response_bytes = usage
+            .block_response_bytes
+            .checked_add(size)
+            .ok_or(ArithmeticError::Overflow)?;
+        usage.epoch_response_bytes = usage
+            .epoch_response_bytes
+            .checked_add(size)
+            .ok_or(ArithmeticError::Overflow)?;
+        self.check_application_http_usage(application_id, usage)
+    }
+
+    fn check_application_http_usage(
+        &self,
+        application_id: ApplicationId,
+        usage: &HttpUsage,
+    ) -> Result<(), ExecutionError> {
+        let policy = &self.policy;
+        ensure!(
+            usage.block_requests <= policy.maximum_http_requests_per_application_per_block
+                && usage.block_response_bytes
+                    <= policy.maximum_http_response_bytes_per_application_per_block,
+            ExecutionError::MaximumHttpUsagePerBlockExceeded(application_id)
+        );
+        ensure!(
+            usage.epoch_requests <= policy.maximum_http_requests_per_application_per_epoch
+                && usage.epoch_response_bytes
+                    <= policy.maximum_http_response_bytes_per_application_per_epoch,
+            ExecutionError::MaximumHttpUsagePerEpochExceeded(application_id)
+        );
+        Ok(())
+    }
 }
 
 /// The runtime size of an `Amount`.
@@ -68,6 +131,12 @@ pub const RUNTIME_CHAIN_ID_SIZE: u32 = 32;
 /// The runtime size of a `Timestamp`.
 pub const RUNTIME_TIMESTAMP_SIZE: u32 = 8;
 
+/// The runtime size of a `CommitteeSummary`: a `u32` validator count and `u64` total votes.
+pub const RUNTIME_COMMITTEE_SUMMARY_SIZE: u32 = 4 + 8;
+
+/// The runtime size of `ResourcePrices`, which consists of 17 `Amount`s.
+pub const RUNTIME_RESOURCE_PRICES_SIZE: u32 = 17 * RUNTIME_AMOUNT_SIZE;
+
 /// The runtime size of the weight of an owner.
 pub const RUNTIME_OWNER_WEIGHT_SIZE: u32 = 8;
 
@@ -97,18 +166,25 @@ pub const RUNTIME_CONSTANT_APPLICATION_DESCRIPTION_SIZE: u32 = 2 * RUNTIME_CRYPT
 
 #[cfg(test)]
 mod tests {
-    use std::mem::size_of;
+    use std::{mem::size_of, sync::Arc};
 
     use linera_base::{
         crypto::CryptoHash,
-        data_types::{Amount, ApplicationDescription, BlockHeight, Timestamp},
+        data_types::{
+            Amount, ApplicationDescription, BlockHeight, CommitteeSummary, Epoch, ResourcePrices,
+            Timestamp,
+        },
         identifiers::{ApplicationId, ChainId, ModuleId},
     };
 
-    use crate::resources::{
-        RUNTIME_AMOUNT_SIZE, RUNTIME_APPLICATION_ID_SIZE, RUNTIME_BLOCK_HEIGHT_SIZE,
-        RUNTIME_CHAIN_ID_SIZE, RUNTIME_CONSTANT_APPLICATION_DESCRIPTION_SIZE,
-        RUNTIME_OWNER_WEIGHT_SIZE, RUNTIME_TIMESTAMP_SIZE,
+    use crate::{
+        resources::{
+            HttpUsage, ResourceController, RUNTIME_AMOUNT_SIZE, RUNTIME_APPLICATION_ID_SIZE,
+            RUNTIME_BLOCK_HEIGHT_SIZE, RUNTIME_CHAIN_ID_SIZE, RUNTIME_COMMITTEE_SUMMARY_SIZE,
+            RUNTIME_CONSTANT_APPLICATION_DESCRIPTION_SIZE, RUNTIME_OWNER_WEIGHT_SIZE,
+            RUNTIME_RESOURCE_PRICES_SIZE, RUNTIME_TIMESTAMP_SIZE,
+        },
+        ExecutionError, ResourceControlPolicy,
     };
 
     #[test]
@@ -122,6 +198,14 @@ mod tests {
         assert_eq!(RUNTIME_CHAIN_ID_SIZE as usize, size_of::<ChainId>());
         assert_eq!(RUNTIME_TIMESTAMP_SIZE as usize, size_of::<Timestamp>());
         assert_eq!(RUNTIME_OWNER_WEIGHT_SIZE as usize, size_of::<u64>());
+        assert_eq!(
+            RUNTIME_RESOURCE_PRICES_SIZE as usize,
+            size_of::<ResourcePrices>()
+        );
+        assert_eq!(
+            RUNTIME_COMMITTEE_SUMMARY_SIZE as usize,
+            bcs::serialized_size(&CommitteeSummary::default()).unwrap()
+        );
     }
 
     /// Verifies that `RUNTIME_CONSTANT_APPLICATION_DESCRIPTION_SIZE` matches the actual
@@ -148,6 +232,67 @@ mod tests {
             RUNTIME_CONSTANT_APPLICATION_DESCRIPTION_SIZE as usize + 2
         );
     }
+
+    #[test]
+    fn test_application_http_quotas() {
+        let policy = ResourceControlPolicy {
+            maximum_http_requests_per_application_per_block: 2,
+            maximum_http_requests_per_application_per_epoch: 3,
+            maximum_http_response_bytes_per_application_per_block: 100,
+            ..ResourceControlPolicy::default()
+        };
+        let controller = ResourceController::new(Arc::new(policy), (), Amount::ZERO);
+        let application_id = ApplicationId::default();
+        let mut usage = HttpUsage::default();
+        let (epoch, height) = (Epoch::ZERO, BlockHeight(1));
+
+        for _ in 0..2 {
+            controller
+                .track_application_http_request(application_id, &mut usage, epoch, height)
+                .unwrap();
+        }
+        assert!(matches!(
+            controller.track_application_http_request(application_id, &mut usage, epoch, height),
+            Err(ExecutionError::MaximumHttpUsagePerBlockExceeded(_))
+        ));
+
+        // The response sizes count towards the same quotas.
+        let mut usage = HttpUsage::default();
+        controller
+            .track_application_http_request(application_id, &mut usage, epoch, height)
+            .unwrap();
+        assert!(matches!(
+            controller.track_application_http_response(application_id, &mut usage, 101),
+            Err(ExecutionError::MaximumHttpUsagePerBlockExceeded(_))
+        ));
+        // The block counts start over in the next block, but not the epoch counts.
+        for height in [BlockHeight(2), BlockHeight(3)] {
+            controller
+                .track_application_http_request(application_id, &mut usage, epoch, height)
+                .unwrap();
+        }
+        assert!(matches!(
+            controller.track_application_http_request(
+                application_id,
+                &mut usage,
+                epoch,
+                BlockHeight(4)
+            ),
+            Err(ExecutionError::MaximumHttpUsagePerEpochExceeded(_))
+        ));
+
+        // All counts start over in the next epoch.
+        controller
+            .track_application_http_request(
+                application_id,
+                &mut usage,
+                Epoch::from(1),
+                BlockHeight(5),
+            )
+            .unwrap();
+        assert_eq!(usage.epoch_requests, 1);
+        assert_eq!(usage.block_requests, 1);
+    }
 }
 
 /// The resources used so far by an execution process.
@@ -155,7 +300,7 @@ mod tests {
 /// a specific execution flow. This could be the execution of a block,
 /// the processing of a single message, or a specific phase within these
 /// broader operations.
-#[derive(Copy, Debug, Clone, Default)]
+#[derive(Copy, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ResourceTracker {
     /// The total size of the block so far.
     pub block_size: u64,
@@ -205,6 +350,8 @@ pub struct ResourceTracker {
     pub service_oracle_execution: Duration,
     /// The amount allocated to message grants.
     pub grants: Amount,
+    /// The total amount charged to the paying account so far, including grants.
+    pub fees: Amount,
 }
 
 impl ResourceTracker {
@@ -342,6 +489,53 @@ impl fmt::Display for ResourceTracker {
     }
 }
 
+/// The HTTP requests that an application made on a chain, counted against the
+/// per-application quotas of the [`ResourceControlPolicy`].
+///
+/// Only the counts of the latest block and epoch in which the application made requests are
+/// kept: the counts start over in a new block or epoch.
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Eq,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    Allocative,
+    async_graphql::SimpleObject,
+)]
+pub struct HttpUsage {
+    /// The epoch of the `epoch_*` counts.
+    pub epoch: Epoch,
+    /// The number of HTTP requests made in the epoch.
+    pub epoch_requests: u64,
+    /// The number of bytes of the HTTP responses received in the epoch.
+    pub epoch_response_bytes: u64,
+    /// The height of the block of the `block_*` counts.
+    pub block_height: BlockHeight,
+    /// The number of HTTP requests made in the block.
+    pub block_requests: u64,
+    /// The number of bytes of the HTTP responses received in the block.
+    pub block_response_bytes: u64,
+}
+
+impl HttpUsage {
+    /// Starts the counts over if they are for an earlier epoch or block.
+    fn roll_over(&mut self, epoch: Epoch, block_height: BlockHeight) {
+        if self.epoch != epoch {
+            self.epoch = epoch;
+            self.epoch_requests = 0;
+            self.epoch_response_bytes = 0;
+        }
+        if self.block_height != block_height {
+            self.block_height = block_height;
+            self.block_requests = 0;
+            self.block_response_bytes = 0;
+        }
+    }
+}
+
 /// How to access the balance of an account.
 pub trait BalanceHolder {
     /// Returns the balance of the account.
@@ -396,6 +590,7 @@ where
                 fees,
                 balance: self.balance().unwrap_or(Amount::MAX),
             })?;
+        self.tracker.as_mut().fees.try_add_assign(fees)?;
         Ok(())
     }
 
@@ -544,6 +739,25 @@ where
         self.track_size_runtime_operations(RUNTIME_TIMESTAMP_SIZE)
     }
 
+    /// Tracks runtime reading of `Timestamp`s.
+    pub(crate) fn track_runtime_timestamps(&mut self, count: usize) -> Result<(), ExecutionError> {
+        let size = u32::try_from(count)
+            .ok()
+            .and_then(|count| count.checked_mul(RUNTIME_TIMESTAMP_SIZE))
+            .ok_or(ArithmeticError::Overflow)?;
+        self.track_size_runtime_operations(size)
+    }
+
+    /// Tracks runtime reading of the `CommitteeSummary`.
+    pub(crate) fn track_runtime_committee_summary(&mut self) -> Result<(), ExecutionError> {
+        self.track_size_runtime_operations(RUNTIME_COMMITTEE_SUMMARY_SIZE)
+    }
+
+    /// Tracks runtime reading of the `ResourcePrices`.
+    pub(crate) fn track_runtime_resource_prices(&mut self) -> Result<(), ExecutionError> {
+        self.track_size_runtime_operations(RUNTIME_RESOURCE_PRICES_SIZE)
+    }
+
     /// Tracks runtime reading of balance
     pub(crate) fn track_runtime_balance(&mut self) -> Result<(), ExecutionError> {
         self.track_size_runtime_operations(RUNTIME_AMOUNT_SIZE)
@@ -12,7 +12,7 @@ use custom_debug_derive::Debug;
 use linera_base::{
     data_types::{
         Amount, ApplicationPermissions, ArithmeticError, Blob, BlockHeight, Bytecode,
-        SendMessageRequest, Timestamp,
+        CommitteeSummary, ResourcePrices, SendMessageRequest, Timestamp,
     },
     ensure, http,
     identifiers::{
@@ -29,13 +29,15 @@ use tracing::instrument;
 use crate::{
     execution::UserAction,
     execution_state_actor::{ExecutionRequest, ExecutionStateSender},
+    profiler::ActionProfile,
     resources::ResourceController,
     system::CreateApplicationResult,
     util::{ReceiverExt, UnboundedSenderExt},
     ApplicationDescription, ApplicationId, BaseRuntime, ContractRuntime, DataBlobHash,
     ExecutionError, FinalizeContext, Message, MessageContext, MessageKind, ModuleId, Operation,
     OutgoingMessage, QueryContext, QueryOutcome, ServiceRuntime, UserContractCode,
-    UserContractInstance, UserServiceCode, UserServiceInstance, MAX_STREAM_NAME_LEN,
+    UserContractInstance, UserServiceCode, UserServiceInstance, MAX_EVENT_INDEX_ENTRIES,
+    MAX_EVENT_INDEX_KEY_LEN, MAX_STREAM_NAME_LEN,
 };
 
 #[cfg(test)]
@@ -136,6 +138,9 @@ pub struct SyncRuntimeInternal<UserInstance: WithContext> {
     user_context: UserInstance::UserContext,
     /// Whether contract log messages should be output.
     allow_application_logs: bool,
+    /// Where the execution time of the applications is recorded, if profiling is enabled.
+    #[debug(skip_if = Option::is_none)]
+    profile: Option<ActionProfile>,
 }
 
 /// The runtime status of an application.
@@ -344,6 +349,7 @@ impl<UserInstance: WithContext> SyncRuntimeInternal<UserInstance> {
             scheduled_operations: Vec::new(),
             user_context,
             allow_application_logs,
+            profile: None,
         }
     }
 
@@ -364,6 +370,9 @@ impl<UserInstance: WithContext> SyncRuntimeInternal<UserInstance> {
     ///
     /// Ensures the application's ID is also tracked in the `active_applications` set.
     fn push_application(&mut self, status: ApplicationStatus) {
+        if let Some(profile) = &self.profile {
+            profile.enter(status.id.to_string());
+        }
         self.active_applications.insert(status.id);
         self.call_stack.push(status);
     }
@@ -381,6 +390,9 @@ impl<UserInstance: WithContext> SyncRuntimeInternal<UserInstance> {
             .pop()
             .expect("Can't remove application from empty call stack");
         assert!(self.active_applications.remove(&status.id));
+        if let Some(profile) = &self.profile {
+            profile.exit();
+        }
         status
     }
 
@@ -954,6 +966,8 @@ where
         this.execution_state_sender
             .send_request(|callback| ExecutionRequest::PerformHttpRequest {
                 request,
+                application_id: app_id,
+                block_height: this.height,
                 http_responses_are_oracle_responses:
                     Self::LIMIT_HTTP_RESPONSE_SIZE_TO_ORACLE_RESPONSE_SIZE,
                 callback,
@@ -1050,25 +1064,26 @@ impl ContractSyncRuntime {
         resource_controller: ResourceController,
         action: &UserAction,
         allow_application_logs: bool,
+        profile: Option<ActionProfile>,
     ) -> Self {
-        SyncRuntime(Some(ContractSyncRuntimeHandle::from(
-            SyncRuntimeInternal::new(
-                chain_id,
-                action.height(),
-                action.round(),
-                if let UserAction::Message(context, _) = action {
-                    Some(context.into())
-                } else {
-                    None
-                },
-                execution_state_sender,
-                None,
-                refund_grant_to,
-                resource_controller,
-                action.timestamp(),
-                allow_application_logs,
-            ),
-        )))
+        let mut runtime = SyncRuntimeInternal::new(
+            chain_id,
+            action.height(),
+            action.round(),
+            if let UserAction::Message(context, _) = action {
+                Some(context.into())
+            } else {
+                None
+            },
+            execution_state_sender,
+            None,
+            refund_grant_to,
+            resource_controller,
+            action.timestamp(),
+            allow_application_logs,
+        );
+        runtime.profile = profile;
+        SyncRuntime(Some(ContractSyncRuntimeHandle::from(runtime)))
     }
 
     /// Preloads the code of a contract into the runtime's memory.
@@ -1434,27 +1449,49 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
     }
 
     fn emit(&mut self, stream_name: StreamName, value: Vec<u8>) -> Result<u32, ExecutionError> {
+        self.emit_indexed(stream_name, value, Vec::new())
+    }
+
+    fn emit_indexed(
+        &mut self,
+        stream_name: StreamName,
+        value: Vec<u8>,
+        index_entries: Vec<(String, Vec<u8>)>,
+    ) -> Result<u32, ExecutionError> {
         let mut this = self.inner();
         ensure!(
             stream_name.0.len() <= MAX_STREAM_NAME_LEN,
             ExecutionError::StreamNameTooLong
         );
+        ensure!(
+            index_entries.len() <= MAX_EVENT_INDEX_ENTRIES
+                && index_entries.iter().all(|(field, key)| {
+                    field.len() <= MAX_EVENT_INDEX_KEY_LEN && key.len() <= MAX_EVENT_INDEX_KEY_LEN
+                }),
+            ExecutionError::EventIndexTooLarge
+        );
         let application_id = GenericApplicationId::User(this.current_application().id);
         let stream_id = StreamId {
             stream_name,
             application_id,
         };
-        let value_len = value.len() as u64;
+        let bytes_written = value.len()
+            + index_entries
+                .iter()
+                .map(|(field, key)| field.len() + key.len())
+                .sum::<usize>();
         let index = this
             .execution_state_sender
             .send_request(|callback| ExecutionRequest::Emit {
                 stream_id,
                 value,
+                index_entries,
                 callback,
             })?
             .recv_response()?;
         // TODO(#365): Consider separate event fee categories.
-        this.resource_controller.track_bytes_written(value_len)?;
+        this.resource_controller
+            .track_bytes_written(bytes_written as u64)?;
         Ok(index)
     }
 
@@ -1716,6 +1753,34 @@ impl ContractRuntime for ContractSyncRuntimeHandle {
             .recv_response()
     }
 
+    fn read_committee_summary(&mut self) -> Result<CommitteeSummary, ExecutionError> {
+        let mut this = self.inner();
+        let summary = this
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::CommitteeSummary { callback })?
+            .recv_response()?;
+        this.resource_controller.track_runtime_committee_summary()?;
+        Ok(summary)
+    }
+
+    fn read_resource_prices(&mut self) -> Result<ResourcePrices, ExecutionError> {
+        let mut this = self.inner();
+        let prices = this.resource_controller.policy().prices();
+        this.resource_controller.track_runtime_resource_prices()?;
+        Ok(prices)
+    }
+
+    fn read_recent_block_timestamps(&mut self) -> Result<Vec<Timestamp>, ExecutionError> {
+        let mut this = self.inner();
+        let timestamps = this
+            .execution_state_sender
+            .send_request(|callback| ExecutionRequest::RecentBlockTimestamps { callback })?
+            .recv_response()?;
+        this.resource_controller
+            .track_runtime_timestamps(timestamps.len())?;
+        Ok(timestamps)
+    }
+
     fn write_batch(&mut self, batch: Batch) -> Result<(), ExecutionError> {
         let mut this = self.inner();
         let id = this.current_application().id;
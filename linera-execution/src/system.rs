@@ -14,11 +14,11 @@ use std::{
 use allocative::Allocative;
 use custom_debug_derive::Debug;
 use linera_base::{
-    crypto::CryptoHash,
+    crypto::{CryptoHash, SignatureScheme},
     data_types::{
         Amount, ApplicationPermissions, ArithmeticError, Blob, BlobContent, BlockHeight,
         ChainDescription, ChainOrigin, Cursor, Epoch, InitialChainConfig, OracleResponse,
-        Timestamp,
+        TimeDelta, Timestamp,
     },
     ensure, hex_debug,
     identifiers::{
@@ -41,8 +41,9 @@ use serde::{Deserialize, Serialize};
 use crate::test_utils::SystemExecutionState;
 use crate::{
     committee::Committee, util::OracleResponseExt as _, ApplicationDescription, ApplicationId,
-    ExecutionError, ExecutionRuntimeContext, MessageContext, MessageKind, OperationContext,
-    OutgoingMessage, QueryContext, QueryOutcome, ResourceController, TransactionTracker,
+    ExecutionError, ExecutionRuntimeContext, HttpAllowListUpdate, HttpUsage, MessageContext,
+    MessageKind, OperationContext, OutgoingMessage, QueryContext, QueryOutcome, ResourceController,
+    TransactionTracker,
 };
 
 /// The event stream name for new epochs and committees.
@@ -76,10 +77,13 @@ mod metrics {
     });
 }
 
-/// Per-block state of a chain: the timestamp of its most recent block together with
+/// The number of block timestamps kept in [`ChainProgress::recent_timestamps`].
+pub const MAX_RECENT_BLOCK_TIMESTAMPS: usize = 16;
+
+/// Per-block state of a chain: the timestamps of its most recent blocks together with
 /// cumulative counts of the transactions and messages processed so far. Stored as a
 /// single value so that each block updates only one key.
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Allocative)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize, Allocative)]
 pub struct ChainProgress {
     /// The timestamp of the most recent block.
     pub timestamp: Timestamp,
@@ -89,6 +93,9 @@ pub struct ChainProgress {
     pub num_operations: u32,
     /// Number of outgoing messages sent so far.
     pub num_outgoing_messages: u32,
+    /// The timestamps of the latest blocks, oldest first, including the current one. At most
+    /// [`MAX_RECENT_BLOCK_TIMESTAMPS`] are kept.
+    pub recent_timestamps: Vec<Timestamp>,
 }
 
 /// A view accessing the execution state of the system of a chain.
@@ -141,8 +148,23 @@ pub struct SystemExecutionStateView<C> {
     /// `CheckpointAck` messages here is what breaks the otherwise-perpetual
     /// notification ping-pong between two chains that ever exchanged a real message.
     pub pending_checkpoint_ack_targets: SetView<C, ChainId>,
-    /// The most recent block's timestamp and cumulative transaction/message counts.
+    /// The most recent blocks' timestamps and cumulative transaction/message counts.
     pub progress: RegisterView<C, ChainProgress>,
+    /// On the admin chain, the governance proposals that have not been resolved yet.
+    pub governance_proposals: MapView<C, u32, GovernanceProposal>,
+    /// On the admin chain, the ID of the next governance proposal.
+    pub next_governance_proposal_id: RegisterView<C, u32>,
+    /// The HTTP requests of each application, counted against the per-application quotas
+    /// of the resource control policy if the `HttpApplicationQuotas` flag is enabled.
+    pub http_usage: MapView<C, ApplicationId, HttpUsage>,
+    /// The signature schemes owners may sign block proposals with. If empty, all schemes are
+    /// allowed. This is kept out of [`ChainOwnership`] so that the format of ownership
+    /// changes and chain descriptions stays the same.
+    pub allowed_signature_schemes: RegisterView<C, BTreeSet<SignatureScheme>>,
+    /// Whether this chain is ephemeral: validators may prune its state once it has been
+    /// closed for a while. This is kept out of the chain description so that its format
+    /// and the chain ID stay the same.
+    pub ephemeral: RegisterView<C, bool>,
 }
 
 impl<C: Context, C2: Context> ReplaceContext<C2> for SystemExecutionStateView<C> {
@@ -175,10 +197,62 @@ impl<C: Context, C2: Context> ReplaceContext<C2> for SystemExecutionStateView<C>
                 .with_context(ctx.clone())
                 .await,
             progress: self.progress.with_context(ctx.clone()).await,
+            governance_proposals: self.governance_proposals.with_context(ctx.clone()).await,
+            next_governance_proposal_id: self
+                .next_governance_proposal_id
+                .with_context(ctx.clone())
+                .await,
+            http_usage: self.http_usage.with_context(ctx.clone()).await,
+            allowed_signature_schemes: self
+                .allowed_signature_schemes
+                .with_context(ctx.clone())
+                .await,
+            ephemeral: self.ephemeral.with_context(ctx.clone()).await,
         }
     }
 }
 
+/// A proposal to install a new committee, i.e. to change the validators or the resource
+/// control policy, which the owners of the admin chain vote on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Allocative)]
+pub struct GovernanceProposal {
+    /// The hash of the published committee blob to install if the proposal is approved.
+    pub blob_hash: CryptoHash,
+    /// The owner who created the proposal.
+    pub proposer: AccountOwner,
+    /// The end of the voting period: votes are accepted before this time, and the proposal
+    /// can only be resolved after it.
+    pub voting_deadline: Timestamp,
+    /// The votes cast so far: `true` to approve, `false` to reject.
+    pub votes: BTreeMap<AccountOwner, bool>,
+}
+
+impl GovernanceProposal {
+    /// Returns the numbers of approvals and rejections cast by the current owners of the
+    /// admin chain.
+    pub fn tally(&self, ownership: &ChainOwnership) -> (usize, usize) {
+        let owners = ownership.all_owners().collect::<BTreeSet<_>>();
+        self.votes
+            .iter()
+            .filter(|(owner, _)| owners.contains(owner))
+            .fold((0, 0), |(approvals, rejections), (_, approve)| {
+                if *approve {
+                    (approvals + 1, rejections)
+                } else {
+                    (approvals, rejections + 1)
+                }
+            })
+    }
+
+    /// Returns whether a strict majority of the current owners of the admin chain approved
+    /// the proposal.
+    pub fn is_approved(&self, ownership: &ChainOwnership) -> bool {
+        let num_owners = ownership.all_owners().collect::<BTreeSet<_>>().len();
+        let (approvals, _) = self.tally(ownership);
+        approvals * 2 > num_owners
+    }
+}
+
 /// The applications subscribing to a particular stream, and their per-application event indices.
 #[derive(Debug, Clone, Serialize, Deserialize, Allocative)]
 pub struct EventSubscriptions {
@@ -319,6 +393,12 @@ pub enum SystemOperation {
     /// future nodes to bootstrap from the snapshot instead of replaying the chain's
     /// history. Subject to a strict set of preconditions on the chain's state.
     Checkpoint,
+    /// Restricts the signature schemes owners may sign block proposals with. An empty list
+    /// allows all schemes. Fails if an owner could no longer sign with any allowed scheme.
+    ChangeSignatureSchemes(Vec<SignatureScheme>),
+    /// Makes the chain ephemeral: once it is closed, validators may prune its state after
+    /// their retention period. This cannot be undone.
+    MakeEphemeral,
 }
 
 /// Operations that are only allowed on the admin chain.
@@ -334,6 +414,25 @@ pub enum AdminOperation {
     /// Removes a committee. Blocks signed by this committee will only be accepted once they
     /// have been followed (hence re-certified) by a block certified by a recent committee.
     RemoveCommittee { epoch: Epoch },
+    /// Proposes to register a published committee in the next epoch, if the owners of the
+    /// admin chain approve it during the voting period.
+    ProposeCommittee {
+        blob_hash: CryptoHash,
+        voting_period: TimeDelta,
+    },
+    /// Votes on a governance proposal, as an owner of the admin chain.
+    VoteOnProposal { proposal_id: u32, approve: bool },
+    /// Tallies the votes on a governance proposal whose voting period is over, registers its
+    /// committee in the next epoch if a majority of the owners approved it, and removes it.
+    ResolveProposal { proposal_id: u32 },
+    /// Adds and removes hosts that contracts and services can send HTTP requests to, by
+    /// registering a published committee in the next epoch. The committee must be the
+    /// current one with only this change applied, so that concurrent updates are rejected
+    /// rather than undone.
+    UpdateHttpAllowList {
+        update: HttpAllowListUpdate,
+        blob_hash: CryptoHash,
+    },
 }
 
 /// A system message meant to be executed on a remote chain.
@@ -399,6 +498,16 @@ where
             && self.admin_chain_id.get().is_some())
     }
 
+    /// Records the timestamp of the block that is about to be executed.
+    pub fn set_block_timestamp(&mut self, timestamp: Timestamp) {
+        let progress = self.progress.get_mut();
+        progress.timestamp = timestamp;
+        let timestamps = &mut progress.recent_timestamps;
+        timestamps.push(timestamp);
+        let excess = timestamps.len().saturating_sub(MAX_RECENT_BLOCK_TIMESTAMPS);
+        timestamps.drain(..excess);
+    }
+
     /// Returns the current committee, if the chain has been initialized.
     pub async fn current_committee(
         &self,
@@ -455,15 +564,20 @@ where
                 open_multi_leader_rounds,
                 timeout_config,
             } => {
-                self.ownership.set(ChainOwnership {
+                self.change_ownership(ChainOwnership {
                     super_owners: super_owners.into_iter().collect(),
                     owners: owners.into_iter().collect(),
                     first_leader,
                     multi_leader_rounds,
                     open_multi_leader_rounds,
                     timeout_config,
-                });
+                })?;
+            }
+            ChangeSignatureSchemes(schemes) => {
+                self.change_signature_schemes(schemes.into_iter().collect())
+                    .await?;
             }
+            MakeEphemeral => self.ephemeral.set(true),
             ChangeApplicationPermissions(application_permissions) => {
                 self.application_permissions.set(application_permissions);
             }
@@ -509,24 +623,8 @@ where
                         )?;
                     }
                     AdminOperation::CreateCommittee { epoch, blob_hash } => {
-                        self.check_next_epoch(epoch)?;
-                        let blob_id = BlobId::new(blob_hash, BlobType::Committee);
-                        // Validate that the blob exists and deserializes as a Committee.
-                        self.context()
-                            .extra()
-                            .get_or_load_committee_by_hash(blob_hash)
+                        self.create_committee(epoch, blob_hash, context.timestamp, txn_tracker)
                             .await?;
-                        self.blob_used(txn_tracker, blob_id).await?;
-                        self.committee_hash.set(Some(blob_hash));
-                        self.epoch.set(epoch);
-                        let event_data = EpochEventData {
-                            blob_hash,
-                            timestamp: context.timestamp,
-                        };
-                        let stream_id = StreamId::system(EPOCH_STREAM_NAME);
-                        let next_index = epoch.0.checked_add(1).ok_or(ArithmeticError::Overflow)?;
-                        self.stream_event_counts.insert(&stream_id, next_index)?;
-                        txn_tracker.add_event(stream_id, epoch.0, bcs::to_bytes(&event_data)?);
                     }
                     AdminOperation::RemoveCommittee { epoch } => {
                         let stream_id = StreamId::system(REMOVED_EPOCH_STREAM_NAME);
@@ -541,6 +639,93 @@ where
                         self.stream_event_counts.insert(&stream_id, next_index)?;
                         txn_tracker.add_event(stream_id, epoch.0, vec![]);
                     }
+                    AdminOperation::ProposeCommittee {
+                        blob_hash,
+                        voting_period,
+                    } => {
+                        let proposer = self.admin_chain_owner(context.authenticated_owner).await?;
+                        // Validate that the blob exists and deserializes as a Committee.
+                        self.context()
+                            .extra()
+                            .get_or_load_committee_by_hash(blob_hash)
+                            .await?;
+                        let proposal_id = *self.next_governance_proposal_id.get();
+                        let proposal = GovernanceProposal {
+                            blob_hash,
+                            proposer,
+                            voting_deadline: context.timestamp.saturating_add(voting_period),
+                            votes: BTreeMap::new(),
+                        };
+                        self.governance_proposals.insert(&proposal_id, proposal)?;
+                        self.next_governance_proposal_id.set(
+                            proposal_id
+                                .checked_add(1)
+                                .ok_or(ArithmeticError::Overflow)?,
+                        );
+                    }
+                    AdminOperation::VoteOnProposal {
+                        proposal_id,
+                        approve,
+                    } => {
+                        let voter = self.admin_chain_owner(context.authenticated_owner).await?;
+                        let proposal = self
+                            .governance_proposals
+                            .get_mut(&proposal_id)
+                            .await?
+                            .ok_or(ExecutionError::UnknownGovernanceProposal(proposal_id))?;
+                        ensure!(
+                            context.timestamp < proposal.voting_deadline,
+                            ExecutionError::GovernanceVotingClosed(proposal_id)
+                        );
+                        proposal.votes.insert(voter, approve);
+                    }
+                    AdminOperation::ResolveProposal { proposal_id } => {
+                        let proposal = self
+                            .governance_proposals
+                            .get(&proposal_id)
+                            .await?
+                            .ok_or(ExecutionError::UnknownGovernanceProposal(proposal_id))?;
+                        ensure!(
+                            context.timestamp >= proposal.voting_deadline,
+                            ExecutionError::GovernanceVotingOpen(proposal_id)
+                        );
+                        self.governance_proposals.remove(&proposal_id)?;
+                        if proposal.is_approved(self.ownership.get().await?) {
+                            let epoch = self.epoch.get().try_add_one()?;
+                            self.create_committee(
+                                epoch,
+                                proposal.blob_hash,
+                                context.timestamp,
+                                txn_tracker,
+                            )
+                            .await?;
+                        }
+                    }
+                    AdminOperation::UpdateHttpAllowList { update, blob_hash } => {
+                        self.admin_chain_owner(context.authenticated_owner).await?;
+                        let (epoch, committee) = self.current_committee().await?.ok_or(
+                            ExecutionError::InternalError("The admin chain has no committee"),
+                        )?;
+                        let mut policy = committee.policy().clone();
+                        policy.update_http_request_allow_list(&update);
+                        let expected = Committee::new(committee.validators().clone(), policy)?;
+                        let published = self
+                            .context()
+                            .extra()
+                            .get_or_load_committee_by_hash(blob_hash)
+                            .await?;
+                        ensure!(
+                            *published == expected,
+                            ExecutionError::HttpAllowListUpdateMismatch
+                        );
+                        self.create_committee(
+                            epoch.try_add_one()?,
+                            blob_hash,
+                            context.timestamp,
+                            txn_tracker,
+                        )
+                        .await?;
+                    }
                 }
             }
             PublishModule { module_id } => {
@@ -686,6 +871,47 @@ where
         Ok(())
     }
 
+    /// Registers the committee published in the given blob as the committee of `epoch`, which
+    /// must be the chain's next epoch.
+    async fn create_committee(
+        &mut self,
+        epoch: Epoch,
+        blob_hash: CryptoHash,
+        timestamp: Timestamp,
+        txn_tracker: &mut TransactionTracker,
+    ) -> Result<(), ExecutionError> {
+        self.check_next_epoch(epoch)?;
+        let blob_id = BlobId::new(blob_hash, BlobType::Committee);
+        // Validate that the blob exists and deserializes as a Committee.
+        self.context()
+            .extra()
+            .get_or_load_committee_by_hash(blob_hash)
+            .await?;
+        self.blob_used(txn_tracker, blob_id).await?;
+        self.committee_hash.set(Some(blob_hash));
+        self.epoch.set(epoch);
+        let event_data = EpochEventData {
+            blob_hash,
+            timestamp,
+        };
+        let stream_id = StreamId::system(EPOCH_STREAM_NAME);
+        let next_index = epoch.0.checked_add(1).ok_or(ArithmeticError::Overflow)?;
+        self.stream_event_counts.insert(&stream_id, next_index)?;
+        txn_tracker.add_event(stream_id, epoch.0, bcs::to_bytes(&event_data)?);
+        Ok(())
+    }
+
+    /// Returns the authenticated owner, if it is an owner of this (admin) chain.
+    async fn admin_chain_owner(
+        &self,
+        authenticated_owner: Option<AccountOwner>,
+    ) -> Result<AccountOwner, ExecutionError> {
+        let ownership = self.ownership.get().await?;
+        authenticated_owner
+            .filter(|owner| ownership.is_owner(owner))
+            .ok_or(ExecutionError::NotAdminChainOwner)
+    }
+
     async fn credit(&mut self, owner: &AccountOwner, amount: Amount) -> Result<(), ExecutionError> {
         if owner == &AccountOwner::CHAIN {
             let new_balance = self.balance.get().saturating_add(amount);
@@ -1028,6 +1254,25 @@ where
         Ok(child_id)
     }
 
+    /// Replaces the ownership of the chain, checking that every owner can sign with one of
+    /// the chain's allowed signature schemes.
+    pub fn change_ownership(&mut self, ownership: ChainOwnership) -> Result<(), ExecutionError> {
+        check_signature_schemes(&ownership, self.allowed_signature_schemes.get())?;
+        self.ownership.set(ownership);
+        Ok(())
+    }
+
+    /// Replaces the signature schemes owners may sign block proposals with, checking that
+    /// every owner can sign with one of them.
+    pub async fn change_signature_schemes(
+        &mut self,
+        schemes: BTreeSet<SignatureScheme>,
+    ) -> Result<(), ExecutionError> {
+        check_signature_schemes(self.ownership.get().await?, &schemes)?;
+        self.allowed_signature_schemes.set(schemes);
+        Ok(())
+    }
+
     /// Marks the chain as closed.
     pub fn close_chain(&mut self) {
         self.closed.set(true);
@@ -1184,3 +1429,14 @@ where
         Ok(blob_ids)
     }
 }
+
+/// Checks that every owner of the chain can sign with one of the allowed signature schemes.
+fn check_signature_schemes(
+    ownership: &ChainOwnership,
+    allowed: &BTreeSet<SignatureScheme>,
+) -> Result<(), ExecutionError> {
+    match ownership.owner_with_disallowed_signature_scheme(allowed) {
+        Some(owner) => Err(ExecutionError::DisallowedOwnerSignatureScheme(*owner)),
+        None => Ok(()),
+    }
+}
@@ -194,6 +194,7 @@ impl SystemExecutionState {
             num_incoming_bundles,
             num_operations,
             num_outgoing_messages,
+            ..ChainProgress::default()
         });
         view
     }
@@ -0,0 +1,55 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Execution profiler unit tests.
+
+use std::time::Duration;
+
+use linera_base::{crypto::CryptoHash, identifiers::ChainId};
+
+use super::{enable, folded, profiles, ActionProfile};
+
+/// Tests that host functions are attributed to the innermost application, and that the
+/// time of the frames they were called from excludes them.
+#[test]
+fn test_action_profile_aggregates_stacks_per_chain() {
+    let chain_id = ChainId(CryptoHash::test_hash("profiled chain"));
+    enable();
+
+    for _ in 0..2 {
+        let profile = ActionProfile::start(chain_id).expect("profiling is enabled");
+        profile.enter("caller".to_string());
+        profile.enter("callee".to_string());
+        profile.record_host_function("ReadValueBytes", Duration::from_millis(5));
+        profile.exit();
+        profile.record_host_function("Emit", Duration::from_millis(1));
+        // The caller fails without returning.
+        profile.finish();
+    }
+
+    let profile = profiles().remove(&chain_id).unwrap();
+    let stack = |frames: &[&str]| frames.iter().map(ToString::to_string).collect::<Vec<_>>();
+    assert_eq!(
+        profile.stacks.keys().cloned().collect::<Vec<_>>(),
+        [
+            stack(&["caller"]),
+            stack(&["caller", "Emit"]),
+            stack(&["caller", "callee"]),
+            stack(&["caller", "callee", "ReadValueBytes"]),
+        ]
+    );
+    assert_eq!(
+        profile.stacks[&stack(&["caller", "callee", "ReadValueBytes"])],
+        Duration::from_millis(10)
+    );
+    assert_eq!(
+        profile.stacks[&stack(&["caller", "Emit"])],
+        Duration::from_millis(2)
+    );
+    assert!(profile.total() >= Duration::from_millis(12));
+
+    let folded = folded(&[(chain_id, profile)].into_iter().collect());
+    assert!(folded
+        .lines()
+        .any(|line| line == format!("{chain_id};caller;callee;ReadValueBytes 10000")));
+}
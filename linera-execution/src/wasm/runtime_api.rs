@@ -6,7 +6,7 @@ use std::{any::Any, collections::HashMap, marker::PhantomData};
 use linera_base::{
     data_types::{
         Amount, ApplicationDescription, ApplicationPermissions, BlockHeight, Bytecode,
-        SendMessageRequest, Timestamp,
+        CommitteeSummary, ResourcePrices, SendMessageRequest, Timestamp,
     },
     http,
     identifiers::{Account, AccountOwner, ApplicationId, ChainId, StreamName},
@@ -705,6 +705,21 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Adds a new item to an event stream, and records it in the stream's indexes under the
+    /// given `(field, key)` entries. Returns the new event's index in the stream.
+    fn emit_indexed(
+        caller: &mut Caller,
+        name: StreamName,
+        value: Vec<u8>,
+        index_entries: Vec<(String, Vec<u8>)>,
+    ) -> Result<u32, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime
+            .emit_indexed(name, value, index_entries)
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Reads an event from a stream. Returns the event's value.
     ///
     /// Returns an error if the event doesn't exist.
@@ -792,6 +807,34 @@ where
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
+    /// Returns the number of validators and the total votes of the current committee.
+    fn read_committee_summary(caller: &mut Caller) -> Result<CommitteeSummary, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime_mut()
+            .read_committee_summary()
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Returns the prices of the resource control policy in force for this block.
+    fn read_resource_prices(caller: &mut Caller) -> Result<ResourcePrices, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime_mut()
+            .read_resource_prices()
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
+    /// Returns the timestamps of the latest blocks of the current chain, oldest first,
+    /// including the current block.
+    fn read_recent_block_timestamps(caller: &mut Caller) -> Result<Vec<Timestamp>, RuntimeError> {
+        caller
+            .user_data_mut()
+            .runtime_mut()
+            .read_recent_block_timestamps()
+            .map_err(|error| RuntimeError::Custom(error.into()))
+    }
+
     /// Writes a batch of `operations` to storage.
     fn write_batch(
         caller: &mut Caller,
@@ -800,7 +843,10 @@ where
         caller
             .user_data_mut()
             .runtime_mut()
-            .write_batch(Batch { operations })
+            .write_batch(Batch {
+                operations,
+                ttl: None,
+            })
             .map_err(|error| RuntimeError::Custom(error.into()))
     }
 
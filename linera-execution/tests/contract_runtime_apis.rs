@@ -12,24 +12,31 @@ use linera_base::{
     crypto::{AccountPublicKey, CryptoHash},
     data_types::{
         Amount, ApplicationDescription, ApplicationPermissions, Blob, BlockHeight, Bytecode,
-        CompressedBytecode, OracleResponse,
+        CompressedBytecode, OracleResponse, Timestamp,
     },
     http,
-    identifiers::{Account, AccountOwner, ApplicationId, DataBlobHash, ModuleId},
+    identifiers::{
+        Account, AccountOwner, ApplicationId, DataBlobHash, ModuleId, StreamId, StreamName,
+    },
     ownership::ChainOwnership,
     vm::VmRuntime,
 };
 use linera_execution::{
     test_utils::{
-        create_dummy_message_context, create_dummy_operation_context, dummy_chain_description,
-        dummy_chain_description_with_ownership_and_balance, test_accounts_strategy, ExpectedCall,
+        create_dummy_message_context, create_dummy_operation_context, create_dummy_query_context,
+        dummy_chain_description, dummy_chain_description_with_ownership_and_balance,
+        dummy_committee, test_accounts_strategy, ExpectedCall, MockApplication,
         RegisterMockApplication, SystemExecutionState,
     },
-    BaseRuntime, ContractRuntime, ExecutionError, ExecutionStateActor, Message, MessageContext,
-    Operation, OperationContext, ResourceController, SystemExecutionStateView,
-    TestExecutionRuntimeContext, TransactionOutcome, TransactionTracker,
+    BaseRuntime, ContractRuntime, EventIndexKey, ExecutionError, ExecutionStateActor, Message,
+    MessageContext, Operation, OperationContext, Query, QueryResponse, ResourceControlPolicy,
+    ResourceController, ResourceTracker, SystemExecutionStateView, TestExecutionRuntimeContext,
+    TransactionOutcome, TransactionTracker, MAX_RECENT_BLOCK_TIMESTAMPS,
+};
+use linera_views::{
+    context::{Context as _, MemoryContext},
+    views::View as _,
 };
-use linera_views::context::MemoryContext;
 use test_case::{test_case, test_matrix};
 use test_strategy::proptest;
 
@@ -843,6 +850,80 @@ async fn test_query_service(authorized_apps: Option<Vec<()>>) -> Result<(), Exec
     Ok(())
 }
 
+/// Tests that a reloaded service is used for queries from outside the chain, but not for
+/// service oracle calls from contracts.
+#[test_log::test(tokio::test)]
+async fn test_service_override_is_not_used_by_oracles() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState {
+        ownership: ChainOwnership::default(),
+        balance: Amount::ONE,
+        balances: BTreeMap::new(),
+        ..SystemExecutionState::new(description)
+    }
+    .into_view()
+    .await;
+
+    let (application_id, application) = view
+        .register_mock_application_with(
+            TransferTestEndpoint::sender_application_description(),
+            TransferTestEndpoint::sender_application_contract_blob(),
+            TransferTestEndpoint::sender_application_service_blob(),
+        )
+        .await?;
+    let reloaded_service = MockApplication::default();
+    view.context()
+        .extra()
+        .service_overrides()
+        .pin()
+        .insert(application_id, reloaded_service.clone().into());
+
+    reloaded_service.expect_call(ExpectedCall::handle_query(|_service, _query| {
+        Ok(b"reloaded".to_vec())
+    }));
+    let query = Query::User {
+        application_id,
+        bytes: vec![],
+    };
+    let outcome = view
+        .query_application(create_dummy_query_context(), query, None)
+        .await?;
+    assert_eq!(outcome.response, QueryResponse::User(b"reloaded".to_vec()));
+
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            assert_eq!(runtime.query_service(application_id, vec![])?, b"original");
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+    application.expect_call(ExpectedCall::handle_query(|_service, _query| {
+        Ok(b"original".to_vec())
+    }));
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    // Execute without replaying, so that the service actually runs as an oracle.
+    let mut txn_tracker = TransactionTracker::new(Timestamp::from(0), 0, 0, 0, None, &[]);
+    ExecutionStateActor::new(&mut view, &mut txn_tracker, &mut controller)
+        .execute_operation(context, operation)
+        .await?;
+
+    let TransactionOutcome {
+        oracle_responses, ..
+    } = txn_tracker.into_outcome()?;
+    assert_eq!(
+        oracle_responses.last(),
+        Some(&OracleResponse::Service(b"original".to_vec()))
+    );
+    Ok(())
+}
+
 /// Tests the contract system API to make HTTP requests.
 #[test_case(None => matches Ok(_); "when all authorized")]
 #[test_case(Some(vec![()]) => matches Ok(_); "when single app authorized")]
@@ -968,6 +1049,77 @@ async fn test_create_multiple_data_blobs() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Tests that events emitted with index entries are recorded in the secondary indexes.
+#[test_log::test(tokio::test)]
+async fn test_emit_indexed_events() -> anyhow::Result<()> {
+    let description = dummy_chain_description(0);
+    let chain_id = description.id();
+    let mut view = SystemExecutionState::new(description).into_view().await;
+    let (application_id, application, blobs) = view.register_mock_application(0).await.unwrap();
+    let stream_name = StreamName(b"transfers".to_vec());
+
+    application.expect_call(ExpectedCall::execute_operation({
+        let stream_name = stream_name.clone();
+        move |runtime, _operation| {
+            let to_alice = vec![("to".to_owned(), b"alice".to_vec())];
+            let to_bob = vec![("to".to_owned(), b"bob".to_vec())];
+            assert_eq!(
+                runtime.emit_indexed(stream_name.clone(), b"1".to_vec(), to_alice.clone())?,
+                0
+            );
+            assert_eq!(
+                runtime.emit_indexed(stream_name.clone(), b"2".to_vec(), to_bob)?,
+                1
+            );
+            assert_eq!(
+                runtime.emit_indexed(stream_name.clone(), b"3".to_vec(), to_alice)?,
+                2
+            );
+            assert_matches!(
+                runtime.emit_indexed(
+                    stream_name.clone(),
+                    b"4".to_vec(),
+                    vec![("to".to_owned(), vec![0; 65])]
+                ),
+                Err(ExecutionError::EventIndexTooLarge)
+            );
+            Ok(vec![])
+        }
+    }));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller = ResourceController::default();
+    let operation = Operation::User {
+        application_id,
+        bytes: vec![],
+    };
+    let mut tracker = TransactionTracker::new_replaying_blobs(blobs);
+    ExecutionStateActor::new(&mut view, &mut tracker, &mut controller)
+        .execute_operation(context, operation)
+        .await?;
+
+    let stream_id = StreamId {
+        application_id: application_id.into(),
+        stream_name,
+    };
+    for (key, expected) in [(&b"alice"[..], vec![0, 2]), (&b"bob"[..], vec![1])] {
+        let index_key = EventIndexKey {
+            stream_id: stream_id.clone(),
+            field: "to".to_owned(),
+            key: key.to_vec(),
+        };
+        let log = view
+            .event_indexes
+            .try_load_entry(&index_key)
+            .await?
+            .expect("the index should exist");
+        assert_eq!(log.read(..).await?, expected);
+    }
+
+    Ok(())
+}
+
 /// Tests that publish_module with different bytecode creates different modules.
 #[test_log::test(tokio::test)]
 async fn test_publish_module_different_bytecode() -> anyhow::Result<()> {
@@ -1181,3 +1333,53 @@ async fn test_unauthorized_change_ownership_system_api() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Tests the contract system APIs to read the committee, the resource prices and the
+/// timestamps of recent blocks.
+#[test_log::test(tokio::test)]
+async fn test_chain_metadata_system_api() -> anyhow::Result<()> {
+    let (state, chain_id) = SystemExecutionState::dummy_chain_state(0);
+    let mut view = state.into_view().await;
+    let timestamps = (1..=MAX_RECENT_BLOCK_TIMESTAMPS as u64 + 2)
+        .map(Timestamp::from)
+        .collect::<Vec<_>>();
+    for timestamp in &timestamps {
+        view.system.set_block_timestamp(*timestamp);
+    }
+    let expected_timestamps = timestamps[2..].to_vec();
+
+    let (application_id, application, blobs) = view.register_mock_application(0).await?;
+
+    let policy = ResourceControlPolicy {
+        operation: Amount::from_millis(3),
+        message_byte: Amount::from_micros(7),
+        ..ResourceControlPolicy::no_fees()
+    };
+    let expected_prices = policy.prices();
+    let expected_summary = dummy_committee().summary();
+    application.expect_call(ExpectedCall::execute_operation(
+        move |runtime, _operation| {
+            assert_eq!(runtime.read_committee_summary()?, expected_summary);
+            assert_eq!(runtime.read_resource_prices()?, expected_prices);
+            assert_eq!(runtime.read_recent_block_timestamps()?, expected_timestamps);
+            Ok(vec![])
+        },
+    ));
+    application.expect_call(ExpectedCall::default_finalize());
+
+    let context = create_dummy_operation_context(chain_id);
+    let mut controller =
+        ResourceController::new(Arc::new(policy), ResourceTracker::default(), None);
+    let mut txn_tracker = TransactionTracker::new_replaying_blobs(blobs.iter());
+    ExecutionStateActor::new(&mut view, &mut txn_tracker, &mut controller)
+        .execute_operation(
+            context,
+            Operation::User {
+                application_id,
+                bytes: vec![],
+            },
+        )
+        .await?;
+
+    Ok(())
+}
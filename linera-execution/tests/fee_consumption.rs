@@ -3,7 +3,11 @@
 
 //! Tests for how the runtime computes fees based on consumed resources.
 
-use std::{collections::BTreeSet, sync::Arc, vec};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    vec,
+};
 
 use linera_base::{
     crypto::AccountPublicKey,
@@ -233,11 +237,16 @@ async fn test_fee_consumption(
         maximum_oracle_response_bytes: 73,
         maximum_http_response_bytes: 79,
         http_request_timeout_ms: 83,
+        maximum_http_requests_per_application_per_block: 107,
+        maximum_http_response_bytes_per_application_per_block: 109,
+        maximum_http_requests_per_application_per_epoch: 113,
+        maximum_http_response_bytes_per_application_per_epoch: 127,
         blob_read: Amount::from_tokens(89),
         blob_published: Amount::from_tokens(97),
         blob_byte_read: Amount::from_tokens(101),
         blob_byte_published: Amount::from_tokens(103),
         http_request_allow_list: BTreeSet::new(),
+        http_request_allow_list_overrides: BTreeMap::new(),
         free_application_ids: BTreeSet::new(),
         flags: BTreeSet::new(),
     };
@@ -1,20 +1,23 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::collections::BTreeSet;
+
+use assert_matches::assert_matches;
 use linera_base::{
-    crypto::AccountSecretKey,
+    crypto::{AccountSecretKey, SignatureScheme},
     data_types::{Amount, BlockHeight, Timestamp},
     identifiers::{Account, AccountOwner},
-    ownership::ChainOwnership,
+    ownership::{ChainOwnership, TimeoutConfig},
 };
 use linera_execution::{
     test_utils::{
         dummy_chain_description, dummy_chain_description_with_ownership_and_balance,
         SystemExecutionState,
     },
-    ExecutionStateActor, Message, MessageContext, Operation, OperationContext, Query, QueryContext,
-    QueryOutcome, QueryResponse, ResourceController, SystemMessage, SystemOperation, SystemQuery,
-    SystemResponse, TransactionTracker,
+    ExecutionError, ExecutionStateActor, Message, MessageContext, Operation, OperationContext,
+    Query, QueryContext, QueryOutcome, QueryResponse, ResourceController, SystemMessage,
+    SystemOperation, SystemQuery, SystemResponse, TransactionTracker,
 };
 
 #[tokio::test]
@@ -60,6 +63,103 @@ async fn test_simple_system_operation() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_signature_schemes_are_checked_against_owners() -> anyhow::Result<()> {
+    let owner = AccountOwner::from(AccountSecretKey::generate().public());
+    let evm_owner = AccountOwner::Address20([1; 20]);
+    let ownership = ChainOwnership {
+        super_owners: [owner].into_iter().collect(),
+        ..ChainOwnership::default()
+    };
+    let description =
+        dummy_chain_description_with_ownership_and_balance(0, ownership.clone(), Amount::ZERO);
+    let chain_id = description.id();
+    let state = SystemExecutionState {
+        description: Some(description),
+        ownership,
+        ..SystemExecutionState::default()
+    };
+    let mut view = state.into_view().await;
+    let context = OperationContext {
+        chain_id,
+        height: BlockHeight(0),
+        round: Some(0),
+        authenticated_owner: Some(owner),
+        timestamp: Default::default(),
+    };
+    let add_evm_owner = SystemOperation::ChangeOwnership {
+        super_owners: vec![owner],
+        owners: vec![(evm_owner, 100)],
+        first_leader: None,
+        multi_leader_rounds: 5,
+        open_multi_leader_rounds: false,
+        timeout_config: TimeoutConfig::default(),
+    };
+    let mut controller = ResourceController::default();
+    let mut txn_tracker = TransactionTracker::new_replaying(Vec::new());
+
+    // Only allow Ed25519: the current owner can still sign.
+    ExecutionStateActor::new(&mut view, &mut txn_tracker, &mut controller)
+        .execute_operation(
+            context,
+            Operation::system(SystemOperation::ChangeSignatureSchemes(vec![
+                SignatureScheme::Ed25519,
+            ])),
+        )
+        .await?;
+    assert_eq!(
+        view.system.allowed_signature_schemes.get(),
+        &BTreeSet::from([SignatureScheme::Ed25519])
+    );
+
+    // The EVM owner could never sign a proposal if only Ed25519 is allowed.
+    let result = ExecutionStateActor::new(&mut view, &mut txn_tracker, &mut controller)
+        .execute_operation(context, Operation::system(add_evm_owner.clone()))
+        .await;
+    assert_matches!(
+        result,
+        Err(ExecutionError::DisallowedOwnerSignatureScheme(rejected)) if rejected == evm_owner
+    );
+    assert!(view.system.ownership.get().await?.owners.is_empty());
+
+    // Once EVM signatures are allowed too, the EVM owner can be added, but EVM signatures
+    // can then no longer be disallowed.
+    let schemes = vec![SignatureScheme::Ed25519, SignatureScheme::EvmSecp256k1];
+    ExecutionStateActor::new(&mut view, &mut txn_tracker, &mut controller)
+        .execute_operation(
+            context,
+            Operation::system(SystemOperation::ChangeSignatureSchemes(schemes.clone())),
+        )
+        .await?;
+    ExecutionStateActor::new(&mut view, &mut txn_tracker, &mut controller)
+        .execute_operation(context, Operation::system(add_evm_owner))
+        .await?;
+    assert!(view
+        .system
+        .ownership
+        .get()
+        .await?
+        .owners
+        .contains_key(&evm_owner));
+    let result = ExecutionStateActor::new(&mut view, &mut txn_tracker, &mut controller)
+        .execute_operation(
+            context,
+            Operation::system(SystemOperation::ChangeSignatureSchemes(vec![
+                SignatureScheme::Ed25519,
+            ])),
+        )
+        .await;
+    assert_matches!(
+        result,
+        Err(ExecutionError::DisallowedOwnerSignatureScheme(rejected)) if rejected == evm_owner
+    );
+    assert_eq!(
+        view.system.allowed_signature_schemes.get(),
+        &BTreeSet::from_iter(schemes)
+    );
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_simple_system_message() -> anyhow::Result<()> {
     let mut state = SystemExecutionState::default();
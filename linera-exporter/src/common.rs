@@ -8,9 +8,13 @@ use custom_debug_derive::Debug;
 use linera_base::{
     crypto::CryptoHash,
     data_types::BlockHeight,
-    identifiers::{BlobId, ChainId},
+    identifiers::{BlobId, BlobType, ChainId, StreamId},
+};
+use linera_chain::{block::Block, data_types::IncomingBundle, types::ConfirmedBlock};
+use linera_execution::{
+    system::{AdminOperation, EpochEventData, EPOCH_STREAM_NAME},
+    Operation, SystemOperation,
 };
-use linera_chain::{data_types::IncomingBundle, types::ConfirmedBlock};
 use linera_rpc::grpc::{GrpcError, GrpcProtoConversionError};
 use linera_sdk::views::ViewError;
 use serde::{Deserialize, Serialize};
@@ -181,3 +185,32 @@ impl IntoFuture for ExporterCancellationSignal {
 pub fn get_address(port: u16) -> SocketAddr {
     SocketAddr::from(([0, 0, 0, 0], port))
 }
+
+/// Returns the committee blob registered by the given block, if any: either directly with
+/// `CreateCommittee`, or when resolving an approved governance proposal, which only shows
+/// in the block's epoch events.
+pub(crate) fn new_committee_blob(block: &Block) -> Option<BlobId> {
+    let from_operation = block.body.operations().find_map(|operation| {
+        if let Operation::System(boxed) = operation {
+            if let SystemOperation::Admin(AdminOperation::CreateCommittee { blob_hash, .. }) =
+                boxed.as_ref()
+            {
+                return Some(*blob_hash);
+            }
+        }
+        None
+    });
+    let epoch_stream_id = StreamId::system(EPOCH_STREAM_NAME);
+    from_operation
+        .or_else(|| {
+            block
+                .body
+                .events
+                .iter()
+                .flatten()
+                .filter(|event| event.stream_id == epoch_stream_id)
+                .find_map(|event| bcs::from_bytes::<EpochEventData>(&event.value).ok())
+                .map(|data| data.blob_hash)
+        })
+        .map(|blob_hash| BlobId::new(blob_hash, BlobType::Committee))
+}
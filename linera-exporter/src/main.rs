@@ -20,7 +20,7 @@ use linera_exporter::{
 };
 #[cfg(with_metrics)]
 use linera_metrics::monitoring_server;
-use linera_rpc::NodeOptions;
+use linera_rpc::{EndpointSelection, NodeOptions};
 use linera_storage::Storage;
 use linera_storage_runtime::{CommonStorageOptions, Runnable, StorageConfig};
 use tokio_util::sync::CancellationToken;
@@ -243,6 +243,8 @@ impl RunOptions {
             retry_delay: self.retry_delay,
             max_retries: self.max_retries,
             max_backoff: self.max_backoff,
+            max_concurrent_requests: None,
+            endpoint_selection: EndpointSelection::default(),
         };
 
         if let Some(port) = self.metrics_port {
@@ -3,13 +3,12 @@
 
 use std::collections::HashSet;
 
-use linera_base::identifiers::{BlobId, BlobType};
+use linera_base::identifiers::BlobId;
 use linera_chain::types::{CertificateValue, ConfirmedBlock};
-use linera_execution::{system::AdminOperation, Operation, SystemOperation};
 use linera_storage::Storage;
 
 use crate::{
-    common::{BlockId, CanonicalBlock, ExporterError},
+    common::{new_committee_blob, BlockId, CanonicalBlock, ExporterError},
     storage::BlockProcessorStorage,
 };
 
@@ -174,19 +173,7 @@ impl ProcessedBlock {
             .map(BlockId::from_incoming_bundle);
         dependencies.extend(message_senders);
 
-        let new_committee = block.block().body.operations().find_map(|m| {
-            if let Operation::System(boxed) = m {
-                if let SystemOperation::Admin(AdminOperation::CreateCommittee {
-                    blob_hash, ..
-                }) = boxed.as_ref()
-                {
-                    let committee_blob = BlobId::new(*blob_hash, BlobType::Committee);
-                    return Some(committee_blob);
-                }
-            }
-
-            None
-        });
+        let new_committee = new_committee_blob(block.block());
 
         Self {
             dependencies,
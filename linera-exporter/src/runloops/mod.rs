@@ -17,7 +17,7 @@ use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use validator_exporter::Exporter as ValidatorExporter;
 
 use crate::{
-    common::{BlockId, ExporterError},
+    common::{new_committee_blob, BlockId, ExporterError},
     config::{DestinationConfig, DestinationId, LimitsConfig},
     runloops::task_manager::ExportersTracker,
     storage::BlockProcessorStorage,
@@ -29,7 +29,6 @@ mod logging_exporter;
 mod task_manager;
 mod validator_exporter;
 
-#[cfg(test)]
 pub use indexer::indexer_api;
 
 /// Spawns the block processor on a dedicated thread and returns a sender for new block IDs.
@@ -266,9 +265,6 @@ async fn scan_canonical_blocks_for_committee<S>(
 where
     S: Storage + Clone + Send + Sync + 'static,
 {
-    use linera_base::identifiers::BlobType;
-    use linera_execution::{system::AdminOperation, Operation, SystemOperation};
-
     let latest_index = exporter_storage.get_latest_index();
     if latest_index == 0 {
         tracing::info!("No blocks in canonical state to scan");
@@ -294,18 +290,8 @@ where
             }
         };
 
-        // Check if this block has a CreateCommittee operation
-        let committee_blob_id = block_cert.value().block().body.operations().find_map(|op| {
-            if let Operation::System(boxed) = op {
-                if let SystemOperation::Admin(AdminOperation::CreateCommittee {
-                    blob_hash, ..
-                }) = boxed.as_ref()
-                {
-                    return Some(BlobId::new(*blob_hash, BlobType::Committee));
-                }
-            }
-            None
-        });
+        // Check if this block registers a new committee
+        let committee_blob_id = new_committee_blob(block_cert.value().block());
 
         if let Some(blob_id) = committee_blob_id {
             tracing::info!(index, ?blob_id, "Found committee blob via backward scan");
@@ -1,7 +1,8 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-//! SQLite database module for storing chain assignments and daily claim tracking.
+//! SQLite database module for storing chain assignments, daily claim tracking, redeemed
+//! invite codes, the ledger of granted tokens and the rate limit of the faucet's balance.
 
 // SQLite (via sqlx) has no native unsigned integer types; casts between
 // `u64` and `i64` at the SQL boundary are by design.
@@ -12,7 +13,7 @@ use std::{collections::BTreeMap, path::PathBuf};
 use anyhow::Context as _;
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{BlockHeight, Timestamp},
+    data_types::{Amount, BlockHeight, Timestamp},
     identifiers::{AccountOwner, ChainId},
 };
 use linera_core::client::ChainClient;
@@ -37,6 +38,30 @@ pub struct ClaimRecord {
     pub timestamp: Timestamp,
 }
 
+/// A grant of tokens, as recorded in the claim ledger.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grant {
+    /// The owner who claimed the tokens.
+    pub owner: AccountOwner,
+    /// The chain the tokens were granted to.
+    pub chain_id: ChainId,
+    /// The amount of tokens granted.
+    pub amount: Amount,
+    /// The timestamp of the block that granted the tokens.
+    pub timestamp: Timestamp,
+    /// Whether this was a daily claim, rather than the initial claim creating the chain.
+    pub daily: bool,
+}
+
+/// The start of the curve along which the faucet's balance is allowed to decrease.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimitStart {
+    /// The time at which the curve starts.
+    pub timestamp: Timestamp,
+    /// The faucet's balance at that time.
+    pub balance: Amount,
+}
+
 /// Schema for creating the chains table.
 const CREATE_CHAINS_TABLE: &str = r#"
 CREATE TABLE IF NOT EXISTS chains (
@@ -57,6 +82,36 @@ CREATE TABLE IF NOT EXISTS daily_claims (
 );
 "#;
 
+/// Schema for creating the redeemed_invites table.
+const CREATE_REDEEMED_INVITES_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS redeemed_invites (
+    nonce TEXT PRIMARY KEY NOT NULL,
+    owner TEXT NOT NULL
+);
+"#;
+
+/// Schema for creating the grants table, the ledger of all tokens granted by the faucet.
+const CREATE_GRANTS_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS grants (
+    owner TEXT NOT NULL,
+    chain_id TEXT NOT NULL,
+    amount TEXT NOT NULL,
+    granted_at INTEGER NOT NULL,
+    daily INTEGER NOT NULL,
+    PRIMARY KEY (owner, chain_id, granted_at, daily)
+);
+"#;
+
+/// Schema for creating the rate_limit table, which holds a single row.
+const CREATE_RATE_LIMIT_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS rate_limit (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 0),
+    end_timestamp INTEGER NOT NULL,
+    start_timestamp INTEGER NOT NULL,
+    start_balance TEXT NOT NULL
+);
+"#;
+
 impl FaucetDatabase {
     /// Creates a new SQLite database connection.
     pub async fn new(database_path: &PathBuf) -> anyhow::Result<Self> {
@@ -95,6 +150,18 @@ impl FaucetDatabase {
             .execute(&self.pool)
             .await
             .context("Failed to create daily_claims table")?;
+        sqlx::query(CREATE_REDEEMED_INVITES_TABLE)
+            .execute(&self.pool)
+            .await
+            .context("Failed to create redeemed_invites table")?;
+        sqlx::query(CREATE_GRANTS_TABLE)
+            .execute(&self.pool)
+            .await
+            .context("Failed to create grants table")?;
+        sqlx::query(CREATE_RATE_LIMIT_TABLE)
+            .execute(&self.pool)
+            .await
+            .context("Failed to create rate_limit table")?;
         info!("Database schema initialized");
         Ok(())
     }
@@ -204,7 +271,18 @@ impl FaucetDatabase {
                 .ok_or_else(|| anyhow::anyhow!("Certificate not found for hash {hash}"))?;
 
             let block_timestamp = certificate.block().header.timestamp;
-            let chains_to_store = super::extract_opened_single_owner_chains(&certificate)?
+            let opened_chains = super::extract_opened_single_owner_chains(&certificate)?;
+            let grants = opened_chains
+                .iter()
+                .map(|(owner, description)| Grant {
+                    owner: *owner,
+                    chain_id: description.id(),
+                    amount: description.config().balance,
+                    timestamp: block_timestamp,
+                    daily: false,
+                })
+                .collect::<Vec<_>>();
+            let chains_to_store = opened_chains
                 .into_iter()
                 .map(|(owner, description)| (owner, description.id()))
                 .collect::<Vec<_>>();
@@ -216,6 +294,7 @@ impl FaucetDatabase {
                 );
                 self.store_chains_batch(chains_to_store, block_timestamp)
                     .await?;
+                self.store_grants_batch(grants).await?;
             }
         }
 
@@ -341,4 +420,141 @@ impl FaucetDatabase {
         tx.commit().await?;
         Ok(())
     }
+
+    /// Returns whether the invite with the given nonce has already been redeemed.
+    pub async fn is_invite_redeemed(&self, nonce: &CryptoHash) -> anyhow::Result<bool> {
+        let row = sqlx::query("SELECT owner FROM redeemed_invites WHERE nonce = ?")
+            .bind(nonce.to_string())
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.is_some())
+    }
+
+    /// Stores multiple redeemed invites, with the owners who redeemed them, in a single
+    /// transaction.
+    pub async fn store_redeemed_invites_batch(
+        &self,
+        invites: Vec<(CryptoHash, AccountOwner)>,
+    ) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for (nonce, owner) in invites {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO redeemed_invites (nonce, owner)
+                VALUES (?, ?)
+                "#,
+            )
+            .bind(nonce.to_string())
+            .bind(owner.to_string())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Stores multiple grants in the ledger in a single transaction. Grants that are already
+    /// recorded are ignored, so that replaying a block does not count its grants twice.
+    pub async fn store_grants_batch(&self, grants: Vec<Grant>) -> anyhow::Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for grant in grants {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO grants (owner, chain_id, amount, granted_at, daily)
+                VALUES (?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(grant.owner.to_string())
+            .bind(grant.chain_id.to_string())
+            .bind(u128::from(grant.amount).to_string())
+            .bind(grant.timestamp.micros() as i64)
+            .bind(grant.daily)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Returns the grants made to an owner, oldest first.
+    pub async fn grants(&self, owner: &AccountOwner) -> anyhow::Result<Vec<Grant>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT chain_id, amount, granted_at, daily FROM grants
+            WHERE owner = ? ORDER BY granted_at, daily
+            "#,
+        )
+        .bind(owner.to_string())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let chain_id_str: String = row.get("chain_id");
+                let amount_str: String = row.get("amount");
+                let granted_at_micros: i64 = row.get("granted_at");
+                Ok(Grant {
+                    owner: *owner,
+                    chain_id: chain_id_str.parse()?,
+                    amount: Amount::from_attos(amount_str.parse()?),
+                    timestamp: Timestamp::from(granted_at_micros as u64),
+                    daily: row.get("daily"),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the start of the rate limit that ends at `end_timestamp`.
+    ///
+    /// The start is persisted, so that restarting the faucet does not reset its rate limit. A
+    /// new start at `now` with the current `balance` is recorded if there is none yet, if the
+    /// end of the rate limit changed, or if the faucet was topped up above its start balance.
+    pub async fn rate_limit_start(
+        &self,
+        end_timestamp: Timestamp,
+        now: Timestamp,
+        balance: Amount,
+    ) -> anyhow::Result<RateLimitStart> {
+        let mut tx = self.pool.begin().await?;
+
+        let row = sqlx::query(
+            "SELECT end_timestamp, start_timestamp, start_balance FROM rate_limit WHERE id = 0",
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(row) = row {
+            let stored_end: i64 = row.get("end_timestamp");
+            let start_timestamp: i64 = row.get("start_timestamp");
+            let start_balance_str: String = row.get("start_balance");
+            let start = RateLimitStart {
+                timestamp: Timestamp::from(start_timestamp as u64),
+                balance: Amount::from_attos(start_balance_str.parse()?),
+            };
+            if stored_end as u64 == end_timestamp.micros() && balance <= start.balance {
+                return Ok(start);
+            }
+        }
+
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO rate_limit (id, end_timestamp, start_timestamp, start_balance)
+            VALUES (0, ?, ?, ?)
+            "#,
+        )
+        .bind(end_timestamp.micros() as i64)
+        .bind(now.micros() as i64)
+        .bind(u128::from(balance).to_string())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(RateLimitStart {
+            timestamp: now,
+            balance,
+        })
+    }
 }
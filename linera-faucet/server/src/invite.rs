@@ -0,0 +1,83 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Invitation codes, minted offline by the faucet operator and redeemed on claim.
+
+use std::{fmt, str::FromStr};
+
+use linera_base::{
+    bcs,
+    crypto::{AccountSignature, BcsSignable, CryptoHash},
+    data_types::{Amount, Timestamp},
+    hex,
+    identifiers::AccountOwner,
+};
+use serde::{Deserialize, Serialize};
+
+/// An invitation to claim a chain from the faucet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Invite {
+    /// A random nonce identifying the invite. Each nonce can be redeemed only once.
+    pub nonce: CryptoHash,
+    /// The amount of tokens granted, instead of the faucet's default claim amount.
+    pub amount: Option<Amount>,
+    /// The time after which the invite can no longer be redeemed.
+    pub expiration: Option<Timestamp>,
+}
+
+impl BcsSignable<'_> for Invite {}
+
+/// An invite signed by the faucet operator, as handed out to users.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InviteCode {
+    /// The signed invite.
+    pub invite: Invite,
+    /// The signature of the invite by the faucet operator.
+    pub signature: AccountSignature,
+}
+
+/// The reasons an invite code is rejected.
+#[derive(Debug, thiserror::Error)]
+pub enum InviteError {
+    /// The code could not be decoded.
+    #[error("malformed invite code")]
+    Malformed,
+    /// The code was not signed by the faucet operator.
+    #[error("the invite code was not signed by this faucet's operator")]
+    InvalidSignature,
+    /// The code is past its expiration time.
+    #[error("the invite code expired at {0}")]
+    Expired(Timestamp),
+    /// The code has already been redeemed.
+    #[error("the invite code has already been redeemed")]
+    AlreadyRedeemed,
+}
+
+impl InviteCode {
+    /// Checks that the code was signed by `signer` and has not expired at time `now`.
+    pub fn verify(&self, signer: AccountOwner, now: Timestamp) -> Result<&Invite, InviteError> {
+        if self.signature.owner() != signer || self.signature.verify(&self.invite).is_err() {
+            return Err(InviteError::InvalidSignature);
+        }
+        match self.invite.expiration {
+            Some(expiration) if now > expiration => Err(InviteError::Expired(expiration)),
+            _ => Ok(&self.invite),
+        }
+    }
+}
+
+impl fmt::Display for InviteCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = bcs::to_bytes(self).expect("serialization should not fail");
+        write!(f, "{}", hex::encode(bytes))
+    }
+}
+
+impl FromStr for InviteCode {
+    type Err = InviteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s.trim()).map_err(|_| InviteError::Malformed)?;
+        bcs::from_bytes(&bytes).map_err(|_| InviteError::Malformed)
+    }
+}
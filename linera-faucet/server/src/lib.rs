@@ -7,13 +7,21 @@
 //! The server component of the Linera faucet.
 
 mod database;
-
-use std::{collections::VecDeque, future::IntoFuture, net::SocketAddr, path::PathBuf, sync::Arc};
+pub mod invite;
+pub mod quota;
+
+use std::{
+    collections::{HashSet, VecDeque},
+    future::IntoFuture,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    sync::Arc,
+};
 
 use anyhow::Context as _;
-use async_graphql::{EmptySubscription, Error, Schema, SimpleObject};
+use async_graphql::{Context, EmptySubscription, Error, Schema, SimpleObject};
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
-use axum::{Extension, Router};
+use axum::{extract::ConnectInfo, http::HeaderMap, Extension, Router};
 use futures::{lock::Mutex, FutureExt as _};
 #[cfg(with_metrics)]
 use linera_base::prometheus_util::MeasureLatency as _;
@@ -48,7 +56,11 @@ use tokio_util::sync::CancellationToken;
 use tower_http::cors::CorsLayer;
 use tracing::info;
 
-use crate::database::FaucetDatabase;
+use crate::{
+    database::{FaucetDatabase, Grant, RateLimitStart},
+    invite::{Invite, InviteCode, InviteError},
+    quota::{ClientIp, QuotaConfig, RequestQuotas},
+};
 
 // Prometheus metrics for the faucet
 #[cfg(with_metrics)]
@@ -140,6 +152,14 @@ mod metrics {
         )
     });
 
+    pub static QUOTA_REJECTIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec(
+            "faucet_quota_rejections_total",
+            "Number of requests rejected for exceeding a per-IP or per-owner quota",
+            &["quota"],
+        )
+    });
+
     pub static INSUFFICIENT_BALANCE_REJECTIONS: LazyLock<IntCounterVec> = LazyLock::new(|| {
         register_int_counter_vec(
             "faucet_insufficient_balance_rejections_total",
@@ -196,6 +216,10 @@ pub struct MutationRoot<S> {
     initial_claim_amount: Amount,
     /// Amount for daily claims (token transfer).
     daily_claim_amount: Amount,
+    /// If set, initial claims require an invite code signed by this owner.
+    invite_signer: Option<AccountOwner>,
+    /// The per-IP and per-owner request quotas, shared by all requests.
+    quotas: Arc<RequestQuotas>,
 }
 
 /// The result of a successful `claim` or `dailyClaim` mutation.
@@ -249,6 +273,8 @@ struct PendingRequest {
     amount: Amount,
     /// For daily claims, the period number to store.
     daily_period: u64,
+    /// For initial claims, the nonce of the redeemed invite, if any.
+    invite_nonce: Option<CryptoHash>,
     responder: oneshot::Sender<PendingResponse>,
     #[cfg(with_metrics)]
     queued_at: std::time::Instant,
@@ -411,14 +437,27 @@ where
     S: Storage + Send + Sync + 'static,
 {
     /// Creates a new chain with the given authentication key, and transfers tokens to it.
-    async fn claim(&self, owner: AccountOwner) -> Result<ChainDescription, Error> {
-        record_claim_latency(self.do_claim(owner)).await
+    /// If the faucet requires invites, a valid invite code must be provided; it determines
+    /// the amount of tokens if it specifies one, and can only be redeemed once.
+    async fn claim(
+        &self,
+        ctx: &Context<'_>,
+        owner: AccountOwner,
+        invite: Option<String>,
+    ) -> Result<ChainDescription, Error> {
+        self.check_quota(ctx.data_opt::<ClientIp>().map(|ip| ip.0), owner)?;
+        record_claim_latency(self.do_claim(owner, invite)).await
     }
 
     /// Transfers a daily amount of tokens to the user's existing chain.
     /// The user must have already claimed a chain. Each user can claim once per 24-hour
     /// period, measured from their initial claim time.
-    async fn daily_claim(&self, owner: AccountOwner) -> Result<ClaimOutcome, Error> {
+    async fn daily_claim(
+        &self,
+        ctx: &Context<'_>,
+        owner: AccountOwner,
+    ) -> Result<ClaimOutcome, Error> {
+        self.check_quota(ctx.data_opt::<ClientIp>().map(|ip| ip.0), owner)?;
         record_claim_latency(self.do_daily_claim(owner)).await
     }
 }
@@ -427,7 +466,29 @@ impl<S> MutationRoot<S>
 where
     S: Storage + Send + Sync + 'static,
 {
-    async fn do_claim(&self, owner: AccountOwner) -> Result<ChainDescription, Error> {
+    /// Counts a request from `ip` for `owner` against the quotas, or rejects it.
+    fn check_quota(&self, ip: Option<IpAddr>, owner: AccountOwner) -> Result<(), Error> {
+        let now = self.storage.clock().current_time();
+        if let Err(error) = self.quotas.check(ip, owner, now) {
+            #[cfg(with_metrics)]
+            {
+                let label = match error {
+                    quota::QuotaError::OwnerExceeded(_) => "owner",
+                    quota::QuotaError::IpExceeded(_) | quota::QuotaError::UnknownIp => "ip",
+                };
+                metrics::QUOTA_REJECTIONS.with_label_values(&[label]).inc();
+            }
+            tracing::debug!(?ip, %owner, %error, "Rejected a claim request");
+            return Err(error.into());
+        }
+        Ok(())
+    }
+
+    async fn do_claim(
+        &self,
+        owner: AccountOwner,
+        invite: Option<String>,
+    ) -> Result<ChainDescription, Error> {
         // Check if this owner already has a chain.
         #[cfg(with_metrics)]
         let histogram = metrics::DATABASE_OPERATION_LATENCY.with_label_values(&["get_chain_id"]);
@@ -445,6 +506,12 @@ where
             return get_chain_description_from_storage(&self.storage, existing_chain_id).await;
         }
 
+        let invite = self.redeemable_invite(invite.as_deref()).await?;
+        let amount = invite
+            .as_ref()
+            .and_then(|invite| invite.amount)
+            .unwrap_or(self.initial_claim_amount);
+
         // Create a oneshot channel to receive the result.
         let (tx, rx) = oneshot::channel();
 
@@ -454,8 +521,9 @@ where
             requests.push_back(PendingRequest {
                 owner,
                 target_chain_id: None,
-                amount: self.initial_claim_amount,
+                amount,
                 daily_period: 0,
+                invite_nonce: invite.map(|invite| invite.nonce),
                 responder: tx,
                 #[cfg(with_metrics)]
                 queued_at: std::time::Instant::now(),
@@ -492,6 +560,29 @@ where
         }
     }
 
+    /// Checks the invite code of an initial claim, if the faucet requires invites.
+    async fn redeemable_invite(&self, invite: Option<&str>) -> Result<Option<Invite>, Error> {
+        let Some(signer) = self.invite_signer else {
+            if invite.is_some() {
+                return Err(Error::new("This faucet does not accept invite codes"));
+            }
+            return Ok(None);
+        };
+        let code = invite
+            .ok_or_else(|| Error::new("This faucet requires an invite code"))?
+            .parse::<InviteCode>()?;
+        let now = self.storage.clock().current_time();
+        let invite = code.verify(signer, now)?;
+        if self
+            .faucet_storage
+            .is_invite_redeemed(&invite.nonce)
+            .await?
+        {
+            return Err(InviteError::AlreadyRedeemed.into());
+        }
+        Ok(Some(invite.clone()))
+    }
+
     async fn do_daily_claim(&self, owner: AccountOwner) -> Result<ClaimOutcome, Error> {
         if self.daily_claim_amount == Amount::ZERO {
             return Err(Error::new("Daily claims are not enabled on this faucet"));
@@ -545,6 +636,7 @@ where
                 target_chain_id: Some(target_chain_id),
                 amount,
                 daily_period,
+                invite_nonce: None,
                 responder: tx,
                 #[cfg(with_metrics)]
                 queued_at: std::time::Instant::now(),
@@ -734,12 +826,21 @@ where
     // Collects requests from the queue; validates and filters them.
     async fn get_request_batch(&self) -> Vec<PendingRequest> {
         let mut batch_requests = Vec::new();
+        let mut invite_nonces = HashSet::new();
         let mut requests = self.pending_requests.lock().await;
         while batch_requests.len() < self.config.max_batch_size {
             let Some(request) = requests.pop_front() else {
                 break;
             };
 
+            // An invite can only be redeemed once, even by concurrent requests.
+            if let Some(nonce) = request.invite_nonce {
+                if !invite_nonces.insert(nonce) {
+                    request.send_err(InviteError::AlreadyRedeemed.into());
+                    continue;
+                }
+            }
+
             match self.validate_request(&request).await {
                 Ok(()) => {
                     batch_requests.push(request);
@@ -798,6 +899,11 @@ where
                     return Err(Error::new(err.to_string()));
                 }
             }
+            if let Some(nonce) = &request.invite_nonce {
+                if self.faucet_storage.is_invite_redeemed(nonce).await? {
+                    return Err(InviteError::AlreadyRedeemed.into());
+                }
+            }
         }
         Ok(())
     }
@@ -986,6 +1092,27 @@ where
                     .map(|chain_id| (r.owner, chain_id, r.daily_period))
             })
             .collect();
+        let grants: Vec<_> = requests
+            .iter()
+            .filter_map(|r| {
+                let chain_id = match r.target_chain_id {
+                    Some(chain_id) => chain_id,
+                    None => initial_desc_map.get(&r.owner)?.id(),
+                };
+                Some(Grant {
+                    owner: r.owner,
+                    chain_id,
+                    amount: r.amount,
+                    timestamp: block_timestamp,
+                    daily: r.is_daily(),
+                })
+            })
+            .collect();
+        let redeemed_invites: Vec<_> = requests
+            .iter()
+            .filter(|r| initial_desc_map.contains_key(&r.owner))
+            .filter_map(|r| r.invite_nonce.map(|nonce| (nonce, r.owner)))
+            .collect();
 
         let store_initial = async {
             if initial_chains.is_empty() {
@@ -1004,7 +1131,24 @@ where
                 .await
         };
 
-        if let Err(e) = futures::try_join!(store_initial, store_daily) {
+        let store_invites = async {
+            if redeemed_invites.is_empty() {
+                return Ok(());
+            }
+            self.faucet_storage
+                .store_redeemed_invites_batch(redeemed_invites)
+                .await
+        };
+
+        let store_grants = async {
+            if grants.is_empty() {
+                return Ok(());
+            }
+            self.faucet_storage.store_grants_batch(grants).await
+        };
+
+        if let Err(e) = futures::try_join!(store_initial, store_daily, store_invites, store_grants)
+        {
             let error_msg = format!("Failed to save claims to database: {e}");
             Self::send_err(requests, error_msg.clone());
             anyhow::bail!(error_msg);
@@ -1070,6 +1214,8 @@ where
     metrics_port: u16,
     initial_claim_amount: Amount,
     daily_claim_amount: Amount,
+    invite_signer: Option<AccountOwner>,
+    quotas: Arc<RequestQuotas>,
     end_timestamp: Timestamp,
     start_timestamp: Timestamp,
     start_balance: Amount,
@@ -1099,6 +1245,8 @@ where
             metrics_port: self.metrics_port,
             initial_claim_amount: self.initial_claim_amount,
             daily_claim_amount: self.daily_claim_amount,
+            invite_signer: self.invite_signer,
+            quotas: Arc::clone(&self.quotas),
             end_timestamp: self.end_timestamp,
             start_timestamp: self.start_timestamp,
             start_balance: self.start_balance,
@@ -1125,6 +1273,10 @@ pub struct FaucetConfig {
     pub initial_claim_amount: Amount,
     /// The amount of tokens granted on a daily claim.
     pub daily_claim_amount: Amount,
+    /// If set, initial claims require an invite code signed by this owner.
+    pub invite_signer: Option<AccountOwner>,
+    /// The per-IP and per-owner limits on claim requests.
+    pub quotas: QuotaConfig,
     /// The time at which the faucet's balance must reach zero.
     pub end_timestamp: Timestamp,
     /// The genesis configuration of the network.
@@ -1148,9 +1300,9 @@ where
         let storage = context.storage().clone();
         let client = context.make_chain_client(config.chain_id).await?;
         let context = Arc::new(Mutex::new(context));
-        let start_timestamp = client.storage_client().clock().current_time();
+        let now = client.storage_client().clock().current_time();
         client.process_inbox().await?;
-        let start_balance = client.local_balance().await?;
+        let balance = client.local_balance().await?;
 
         // Use provided storage path
         let storage_path = config.storage_path.clone();
@@ -1165,6 +1317,15 @@ where
             tracing::warn!("Failed to synchronize database with blockchain: {}", e);
         }
 
+        // Resume the rate limit from before a restart, if any.
+        let RateLimitStart {
+            timestamp: start_timestamp,
+            balance: start_balance,
+        } = faucet_storage
+            .rate_limit_start(config.end_timestamp, now, balance)
+            .await
+            .context("Failed to load the faucet's rate limit")?;
+
         let faucet_storage = Arc::new(faucet_storage);
 
         // Initialize batching components
@@ -1183,6 +1344,8 @@ where
             metrics_port: config.metrics_port,
             initial_claim_amount: config.initial_claim_amount,
             daily_claim_amount: config.daily_claim_amount,
+            invite_signer: config.invite_signer,
+            quotas: Arc::new(RequestQuotas::new(config.quotas)),
             end_timestamp: config.end_timestamp,
             start_timestamp,
             start_balance,
@@ -1209,6 +1372,8 @@ where
             storage: self.storage.clone(),
             initial_claim_amount: self.initial_claim_amount,
             daily_claim_amount: self.daily_claim_amount,
+            invite_signer: self.invite_signer,
+            quotas: Arc::clone(&self.quotas),
         };
         let query_root = QueryRoot {
             genesis_config: Arc::clone(&self.genesis_config),
@@ -1275,9 +1440,12 @@ where
         let batch_processor_task = batch_processor.run(cancellation_token.clone());
         let tcp_listener =
             tokio::net::TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], port))).await?;
-        let server = axum::serve(tcp_listener, app)
-            .with_graceful_shutdown(cancellation_token.cancelled_owned())
-            .into_future();
+        let server = axum::serve(
+            tcp_listener,
+            app.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(cancellation_token.cancelled_owned())
+        .into_future();
         futures::select! {
             result = Box::pin(chain_listener).fuse() => result?,
             _ = Box::pin(batch_processor_task).fuse() => {},
@@ -1288,9 +1456,19 @@ where
     }
 
     /// Executes a GraphQL query and generates a response for our `Schema`.
-    async fn index_handler(service: Extension<Self>, request: GraphQLRequest) -> GraphQLResponse {
+    async fn index_handler(
+        service: Extension<Self>,
+        ConnectInfo(peer): ConnectInfo<SocketAddr>,
+        headers: HeaderMap,
+        request: GraphQLRequest,
+    ) -> GraphQLResponse {
+        let trust_forwarded_for = service.0.quotas.config().trust_forwarded_for;
+        let client_ip = ClientIp::new(peer.ip(), &headers, trust_forwarded_for);
         let schema = service.0.schema();
-        schema.execute(request.into_inner()).await.into()
+        schema
+            .execute(request.into_inner().data(client_ip))
+            .await
+            .into()
     }
 }
 
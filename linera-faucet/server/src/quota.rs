@@ -0,0 +1,202 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Per-client request quotas, so that a single client cannot drain the faucet in a loop.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    net::IpAddr,
+    num::NonZeroU32,
+    sync::Mutex,
+};
+
+use axum::http::HeaderMap;
+use linera_base::{
+    data_types::{TimeDelta, Timestamp},
+    identifiers::AccountOwner,
+};
+
+/// The number of tracked clients above which expired entries are dropped.
+const MIN_PRUNE_THRESHOLD: usize = 1024;
+
+/// The limits on how many claim requests a client can make.
+#[derive(Clone, Copy, Debug)]
+pub struct QuotaConfig {
+    /// The maximum number of requests from the same IP address within a window, if limited.
+    pub max_requests_per_ip: Option<NonZeroU32>,
+    /// The maximum number of requests for the same owner within a window, if limited.
+    pub max_requests_per_owner: Option<NonZeroU32>,
+    /// The length of the sliding window over which requests are counted.
+    pub window: TimeDelta,
+    /// Whether to take the client's IP address from the `X-Forwarded-For` header set by a
+    /// reverse proxy, rather than from the connection.
+    pub trust_forwarded_for: bool,
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        Self {
+            max_requests_per_ip: None,
+            max_requests_per_owner: None,
+            window: TimeDelta::from_secs(60 * 60),
+            trust_forwarded_for: false,
+        }
+    }
+}
+
+/// The reasons a request exceeds its quota.
+#[derive(Debug, thiserror::Error)]
+pub enum QuotaError {
+    /// Too many requests came from the client's IP address.
+    #[error("too many requests from this IP address; try again after {0}")]
+    IpExceeded(Timestamp),
+    /// Too many requests were made for the owner.
+    #[error("too many requests for this owner; try again after {0}")]
+    OwnerExceeded(Timestamp),
+    /// The faucet limits requests per IP address, but the client's address is unknown.
+    #[error("the client's IP address is unknown")]
+    UnknownIp,
+}
+
+/// The IP address a GraphQL request was sent from.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ClientIp(pub IpAddr);
+
+impl ClientIp {
+    /// Returns the address of the client, given the peer address of the connection.
+    pub(crate) fn new(peer: IpAddr, headers: &HeaderMap, trust_forwarded_for: bool) -> Self {
+        if trust_forwarded_for {
+            // The first address of the header is the original client.
+            let forwarded = headers
+                .get("x-forwarded-for")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.split(',').next())
+                .and_then(|address| address.trim().parse().ok());
+            if let Some(address) = forwarded {
+                return Self(address);
+            }
+        }
+        Self(peer)
+    }
+}
+
+/// The times of the recent requests of each client.
+#[derive(Debug)]
+struct SlidingWindows<K> {
+    requests: HashMap<K, VecDeque<Timestamp>>,
+    prune_threshold: usize,
+}
+
+impl<K> Default for SlidingWindows<K> {
+    fn default() -> Self {
+        Self {
+            requests: HashMap::new(),
+            prune_threshold: MIN_PRUNE_THRESHOLD,
+        }
+    }
+}
+
+impl<K: Hash + Eq> SlidingWindows<K> {
+    /// Returns the time after which `key` can make another request, if it is over the limit.
+    fn retry_after(
+        &mut self,
+        key: &K,
+        limit: NonZeroU32,
+        window: TimeDelta,
+        now: Timestamp,
+    ) -> Option<Timestamp> {
+        let times = self.requests.get_mut(key)?;
+        expire(times, window, now);
+        if times.len() < limit.get() as usize {
+            return None;
+        }
+        times.front().map(|oldest| oldest.saturating_add(window))
+    }
+
+    /// Records a request by `key` at time `now`.
+    fn record(&mut self, key: K, window: TimeDelta, now: Timestamp) {
+        self.requests.entry(key).or_default().push_back(now);
+        if self.requests.len() > self.prune_threshold {
+            // Forget clients without recent requests, at a cost amortized over insertions.
+            self.requests.retain(|_, times| {
+                expire(times, window, now);
+                !times.is_empty()
+            });
+            self.prune_threshold = (2 * self.requests.len()).max(MIN_PRUNE_THRESHOLD);
+        }
+    }
+}
+
+/// Removes the requests that are no longer within the window ending at `now`.
+fn expire(times: &mut VecDeque<Timestamp>, window: TimeDelta, now: Timestamp) {
+    while times
+        .front()
+        .is_some_and(|time| time.saturating_add(window) <= now)
+    {
+        times.pop_front();
+    }
+}
+
+#[derive(Debug, Default)]
+struct QuotaState {
+    by_ip: SlidingWindows<IpAddr>,
+    by_owner: SlidingWindows<AccountOwner>,
+}
+
+/// Tracks the claim requests of each IP address and owner over a sliding window.
+#[derive(Debug)]
+pub(crate) struct RequestQuotas {
+    config: QuotaConfig,
+    state: Mutex<QuotaState>,
+}
+
+impl RequestQuotas {
+    /// Creates the quotas with the given limits.
+    pub(crate) fn new(config: QuotaConfig) -> Self {
+        Self {
+            config,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Returns the limits.
+    pub(crate) fn config(&self) -> &QuotaConfig {
+        &self.config
+    }
+
+    /// Checks that a request from `ip` for `owner` at time `now` is within the quotas, and
+    /// counts it if it is. Rejected requests are not counted.
+    pub(crate) fn check(
+        &self,
+        ip: Option<IpAddr>,
+        owner: AccountOwner,
+        now: Timestamp,
+    ) -> Result<(), QuotaError> {
+        let QuotaConfig {
+            max_requests_per_ip,
+            max_requests_per_owner,
+            window,
+            ..
+        } = self.config;
+        let mut state = self.state.lock().unwrap();
+        if let Some(limit) = max_requests_per_ip {
+            let ip = ip.ok_or(QuotaError::UnknownIp)?;
+            if let Some(time) = state.by_ip.retry_after(&ip, limit, window, now) {
+                return Err(QuotaError::IpExceeded(time));
+            }
+        }
+        if let Some(limit) = max_requests_per_owner {
+            if let Some(time) = state.by_owner.retry_after(&owner, limit, window, now) {
+                return Err(QuotaError::OwnerExceeded(time));
+            }
+        }
+        if let (Some(_), Some(ip)) = (max_requests_per_ip, ip) {
+            state.by_ip.record(ip, window, now);
+        }
+        if max_requests_per_owner.is_some() {
+            state.by_owner.record(owner, window, now);
+        }
+        Ok(())
+    }
+}
@@ -3,12 +3,12 @@
 
 #![allow(clippy::large_futures)]
 
-use std::{collections::VecDeque, path::PathBuf, sync::Arc};
+use std::{collections::VecDeque, net::IpAddr, num::NonZeroU32, path::PathBuf, sync::Arc};
 
 use futures::lock::Mutex;
 use linera_base::{
-    crypto::{AccountPublicKey, CryptoHash, InMemorySigner, TestString},
-    data_types::{Amount, Epoch, Timestamp},
+    crypto::{AccountPublicKey, AccountSecretKey, CryptoHash, InMemorySigner, TestString},
+    data_types::{Amount, Epoch, TimeDelta, Timestamp},
     identifiers::{AccountOwner, ChainId},
 };
 use linera_client::chain_listener;
@@ -24,7 +24,10 @@ use tokio::sync::{oneshot, Notify};
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    database::FaucetDatabase, BatchProcessor, BatchProcessorConfig, MutationRoot, PendingRequest,
+    database::{FaucetDatabase, RateLimitStart},
+    invite::{Invite, InviteCode},
+    quota::{QuotaConfig, RequestQuotas},
+    BatchProcessor, BatchProcessorConfig, MutationRoot, PendingRequest,
 };
 
 struct ClientContext {
@@ -98,6 +101,8 @@ struct FaucetTestConfig {
     initial_tokens: u128,
     initial_claim_amount: Amount,
     daily_claim_amount: Amount,
+    invite_signer: Option<AccountOwner>,
+    quotas: QuotaConfig,
     batch_config: BatchProcessorConfig,
 }
 
@@ -107,6 +112,8 @@ impl FaucetTestConfig {
             initial_tokens,
             initial_claim_amount: Amount::from_tokens(1),
             daily_claim_amount: Amount::ZERO,
+            invite_signer: None,
+            quotas: QuotaConfig::default(),
             batch_config: BatchProcessorConfig {
                 end_timestamp: Timestamp::from(0),
                 start_timestamp: Timestamp::from(0),
@@ -147,6 +154,8 @@ impl FaucetTestEnv {
             storage: client.storage_client().clone(),
             initial_claim_amount: config.initial_claim_amount,
             daily_claim_amount: config.daily_claim_amount,
+            invite_signer: config.invite_signer,
+            quotas: Arc::new(RequestQuotas::new(config.quotas)),
         };
 
         Ok(Self {
@@ -203,6 +212,8 @@ impl FaucetTestEnv {
             storage: self.client.storage_client().clone(),
             initial_claim_amount: self.root.initial_claim_amount,
             daily_claim_amount: self.root.daily_claim_amount,
+            invite_signer: self.root.invite_signer,
+            quotas: Arc::clone(&self.root.quotas),
         };
 
         let batch_processor = BatchProcessor::new(
@@ -259,7 +270,7 @@ async fn test_faucet_rate_limiting() -> anyhow::Result<()> {
     env.clock.set(Timestamp::from(999));
     let result1 = env
         .root
-        .do_claim(AccountPublicKey::test_key(0).into())
+        .do_claim(AccountPublicKey::test_key(0).into(), None)
         .await;
     assert!(
         result1.is_err(),
@@ -270,14 +281,14 @@ async fn test_faucet_rate_limiting() -> anyhow::Result<()> {
     env.clock.set(Timestamp::from(1000));
     let result2 = env
         .root
-        .do_claim(AccountPublicKey::test_key(1).into())
+        .do_claim(AccountPublicKey::test_key(1).into(), None)
         .await;
     assert!(result2.is_ok(), "First claim should succeed at time 1000");
 
     // Test: immediate second claim should fail (rate limit)
     let result3 = env
         .root
-        .do_claim(AccountPublicKey::test_key(2).into())
+        .do_claim(AccountPublicKey::test_key(2).into(), None)
         .await;
     assert!(
         result3.is_err(),
@@ -288,20 +299,20 @@ async fn test_faucet_rate_limiting() -> anyhow::Result<()> {
     env.clock.set(Timestamp::from(3000));
     let result4 = env
         .root
-        .do_claim(AccountPublicKey::test_key(3).into())
+        .do_claim(AccountPublicKey::test_key(3).into(), None)
         .await;
     assert!(result4.is_ok(), "Third claim should succeed at time 3000");
 
     let result5 = env
         .root
-        .do_claim(AccountPublicKey::test_key(4).into())
+        .do_claim(AccountPublicKey::test_key(4).into(), None)
         .await;
     assert!(result5.is_ok(), "Fourth claim should succeed at time 3000");
 
     // Test: too many claims should eventually fail
     let result6 = env
         .root
-        .do_claim(AccountPublicKey::test_key(5).into())
+        .do_claim(AccountPublicKey::test_key(5).into(), None)
         .await;
     assert!(
         result6.is_err(),
@@ -393,6 +404,7 @@ async fn test_batch_size_reduction_on_limit_errors() -> anyhow::Result<()> {
                 target_chain_id: None,
                 amount: Amount::from_tokens(1),
                 daily_period: 0,
+                invite_nonce: None,
                 responder: tx,
                 #[cfg(with_metrics)]
                 queued_at: std::time::Instant::now(),
@@ -428,21 +440,21 @@ async fn test_faucet_persistence() -> anyhow::Result<()> {
     // Claim chains for two different owners
     let chain_1 = env
         .root
-        .do_claim(test_owner_1)
+        .do_claim(test_owner_1, None)
         .await
         .expect("First claim should succeed");
 
     env.clock.set(Timestamp::from(2000));
     let chain_2 = env
         .root
-        .do_claim(test_owner_2)
+        .do_claim(test_owner_2, None)
         .await
         .expect("Second claim should succeed");
 
     // Verify that immediate re-claims return the same chains
     let chain_1_again = env
         .root
-        .do_claim(test_owner_1)
+        .do_claim(test_owner_1, None)
         .await
         .expect("Re-claim should return existing chain");
     assert_eq!(
@@ -453,7 +465,7 @@ async fn test_faucet_persistence() -> anyhow::Result<()> {
 
     let chain_2_again = env
         .root
-        .do_claim(test_owner_2)
+        .do_claim(test_owner_2, None)
         .await
         .expect("Re-claim should return existing chain");
     assert_eq!(
@@ -504,7 +516,7 @@ async fn test_faucet_persistence() -> anyhow::Result<()> {
 
     // Verify that the new instance returns the same chain IDs for the same owners
     let chain_1_after_restart = root_2
-        .do_claim(test_owner_1)
+        .do_claim(test_owner_1, None)
         .await
         .expect("Should return existing chain after restart");
     assert_eq!(
@@ -514,7 +526,7 @@ async fn test_faucet_persistence() -> anyhow::Result<()> {
     );
 
     let chain_2_after_restart = root_2
-        .do_claim(test_owner_2)
+        .do_claim(test_owner_2, None)
         .await
         .expect("Should return existing chain after restart");
     assert_eq!(
@@ -527,7 +539,7 @@ async fn test_faucet_persistence() -> anyhow::Result<()> {
     env.clock.set(Timestamp::from(3000));
     let test_owner_3 = AccountPublicKey::test_key(44).into();
     let chain_3 = root_2
-        .do_claim(test_owner_3)
+        .do_claim(test_owner_3, None)
         .await
         .expect("New owner should be able to claim after restart");
 
@@ -566,14 +578,14 @@ async fn test_blockchain_sync_after_database_deletion() -> anyhow::Result<()> {
     // Claim chains for two different owners
     let chain_1 = env
         .root
-        .do_claim(test_owner_1)
+        .do_claim(test_owner_1, None)
         .await
         .expect("First claim should succeed");
 
     env.clock.set(Timestamp::from(2000));
     let chain_2 = env
         .root
-        .do_claim(test_owner_2)
+        .do_claim(test_owner_2, None)
         .await
         .expect("Second claim should succeed");
 
@@ -584,7 +596,7 @@ async fn test_blockchain_sync_after_database_deletion() -> anyhow::Result<()> {
     // Verify initial state works correctly
     let chain_1_again = env
         .root
-        .do_claim(test_owner_1)
+        .do_claim(test_owner_1, None)
         .await
         .expect("Re-claim should return existing chain");
     assert_eq!(
@@ -612,7 +624,7 @@ async fn test_blockchain_sync_after_database_deletion() -> anyhow::Result<()> {
 
     // Test that the blockchain sync correctly restored the chain mappings
     let chain_1_after_sync = root_2
-        .do_claim(test_owner_1)
+        .do_claim(test_owner_1, None)
         .await
         .expect("Should return existing chain after blockchain sync");
     assert_eq!(
@@ -622,7 +634,7 @@ async fn test_blockchain_sync_after_database_deletion() -> anyhow::Result<()> {
     );
 
     let chain_2_after_sync = root_2
-        .do_claim(test_owner_2)
+        .do_claim(test_owner_2, None)
         .await
         .expect("Should return existing chain after blockchain sync");
     assert_eq!(
@@ -637,7 +649,7 @@ async fn test_blockchain_sync_after_database_deletion() -> anyhow::Result<()> {
     env.clock.set(Timestamp::from(3000));
     let test_owner_3 = AccountPublicKey::test_key(102).into();
     let chain_3 = root_2
-        .do_claim(test_owner_3)
+        .do_claim(test_owner_3, None)
         .await
         .expect("New owner should be able to claim after sync");
 
@@ -655,7 +667,7 @@ async fn test_blockchain_sync_after_database_deletion() -> anyhow::Result<()> {
 
     // Verify that the new chain mapping is also persisted
     let chain_3_again = root_2
-        .do_claim(test_owner_3)
+        .do_claim(test_owner_3, None)
         .await
         .expect("Re-claim should return the new chain");
     assert_eq!(
@@ -691,7 +703,7 @@ async fn test_daily_claim_flow() -> anyhow::Result<()> {
     // Step 2: Do the initial claim to create a chain.
     let description = env
         .root
-        .do_claim(test_owner)
+        .do_claim(test_owner, None)
         .await
         .expect("Initial claim should succeed");
     let chain_id = description.id();
@@ -735,5 +747,175 @@ async fn test_daily_claim_flow() -> anyhow::Result<()> {
     assert_eq!(outcome_2.chain_id, chain_id);
     assert_eq!(outcome_2.amount, daily_amount);
 
+    // Step 8: The ledger records every grant, and survives reopening the database.
+    handle.stop().await?;
+    let faucet_storage = FaucetDatabase::new(&env.storage_path).await?;
+    let grants = faucet_storage.grants(&test_owner).await?;
+    let summary = grants
+        .iter()
+        .map(|grant| (grant.chain_id, grant.amount, grant.daily))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (chain_id, Amount::from_tokens(1), false),
+            (chain_id, daily_amount, true),
+            (chain_id, daily_amount, true),
+        ]
+    );
+    assert!(grants[0].timestamp < grants[1].timestamp);
+    assert!(grants[1].timestamp < grants[2].timestamp);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rate_limit_start_persistence() -> anyhow::Result<()> {
+    // Test that the start of the rate limit is kept across restarts, unless it changed.
+
+    let temp_dir = tempfile::tempdir()?;
+    let path = temp_dir.path().join("faucet.sqlite");
+    let end = Timestamp::from(10_000);
+    let start = RateLimitStart {
+        timestamp: Timestamp::from(1_000),
+        balance: Amount::from_tokens(100),
+    };
+
+    let database = FaucetDatabase::new(&path).await?;
+    let first = database
+        .rate_limit_start(end, start.timestamp, start.balance)
+        .await?;
+    assert_eq!(first, start);
+    drop(database);
+
+    // After a restart with a lower balance, the original start is used.
+    let database = FaucetDatabase::new(&path).await?;
+    let resumed = database
+        .rate_limit_start(end, Timestamp::from(5_000), Amount::from_tokens(60))
+        .await?;
+    assert_eq!(resumed, start);
+
+    // Topping up the faucet starts a new curve.
+    let topped_up = RateLimitStart {
+        timestamp: Timestamp::from(6_000),
+        balance: Amount::from_tokens(200),
+    };
+    let restarted = database
+        .rate_limit_start(end, topped_up.timestamp, topped_up.balance)
+        .await?;
+    assert_eq!(restarted, topped_up);
+
+    // So does changing the end of the rate limit.
+    let extended = RateLimitStart {
+        timestamp: Timestamp::from(7_000),
+        balance: Amount::from_tokens(150),
+    };
+    let restarted = database
+        .rate_limit_start(
+            Timestamp::from(20_000),
+            extended.timestamp,
+            extended.balance,
+        )
+        .await?;
+    assert_eq!(restarted, extended);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invite_claims() -> anyhow::Result<()> {
+    // Test that a faucet requiring invites only accepts valid, unexpired, unused invites.
+
+    let operator = AccountSecretKey::generate();
+    let mut config = FaucetTestConfig::new(100);
+    config.batch_config.max_batch_size = 10;
+    config.invite_signer = Some(operator.public().into());
+    let batch_config = config.batch_config.clone();
+    let env = FaucetTestEnv::new(config).await?;
+    let handle = env.spawn_processor(batch_config);
+
+    let sign = |signer: &AccountSecretKey, nonce: &str, amount, expiration| {
+        let invite = Invite {
+            nonce: CryptoHash::new(&TestString(nonce.into())),
+            amount,
+            expiration,
+        };
+        InviteCode {
+            signature: signer.sign(&invite),
+            invite,
+        }
+        .to_string()
+    };
+
+    // Claims without an invite, or with an invite not signed by the operator, fail.
+    let owner_1 = AccountPublicKey::test_key(1).into();
+    assert!(env.root.do_claim(owner_1, None).await.is_err());
+    let forged = sign(&AccountSecretKey::generate(), "forged", None, None);
+    assert!(env.root.do_claim(owner_1, Some(forged)).await.is_err());
+
+    // A valid invite grants the amount it specifies, and only once.
+    let code = sign(&operator, "first", Some(Amount::from_tokens(3)), None);
+    let description = env
+        .root
+        .do_claim(owner_1, Some(code.clone()))
+        .await
+        .expect("Claim with a valid invite should succeed");
+    assert_eq!(description.config().balance, Amount::from_tokens(3));
+    let owner_2 = AccountPublicKey::test_key(2).into();
+    assert!(env.root.do_claim(owner_2, Some(code)).await.is_err());
+
+    // Expired invites are rejected; others grant the default amount.
+    env.clock.set(Timestamp::from(2000));
+    let expired = sign(&operator, "expired", None, Some(Timestamp::from(1000)));
+    assert!(env.root.do_claim(owner_2, Some(expired)).await.is_err());
+    let code = sign(&operator, "second", None, Some(Timestamp::from(3000)));
+    let description = env
+        .root
+        .do_claim(owner_2, Some(code))
+        .await
+        .expect("Claim with an unexpired invite should succeed");
+    assert_eq!(description.config().balance, Amount::from_tokens(1));
+
     handle.stop().await
 }
+
+#[tokio::test]
+async fn test_request_quotas() -> anyhow::Result<()> {
+    // Test that requests are limited per IP address and per owner over a sliding window.
+
+    let mut config = FaucetTestConfig::new(100);
+    config.quotas = QuotaConfig {
+        max_requests_per_ip: NonZeroU32::new(3),
+        max_requests_per_owner: NonZeroU32::new(2),
+        window: TimeDelta::from_micros(1000),
+        trust_forwarded_for: false,
+    };
+    let env = FaucetTestEnv::new(config).await?;
+    let ip_1 = Some(IpAddr::from([10, 0, 0, 1]));
+    let ip_2 = Some(IpAddr::from([10, 0, 0, 2]));
+    let owner_1 = AccountPublicKey::test_key(1).into();
+    let owner_2 = AccountPublicKey::test_key(2).into();
+    let owner_3 = AccountPublicKey::test_key(3).into();
+
+    // The same owner can only make two requests, even from different addresses.
+    env.root.check_quota(ip_1, owner_1)?;
+    env.clock.set(Timestamp::from(100));
+    env.root.check_quota(ip_2, owner_1)?;
+    assert!(env.root.check_quota(ip_2, owner_1).is_err());
+
+    // The same address can only make three requests, even for different owners.
+    env.root.check_quota(ip_1, owner_2)?;
+    env.root.check_quota(ip_1, owner_3)?;
+    assert!(env.root.check_quota(ip_1, owner_3).is_err());
+    assert!(env.root.check_quota(None, owner_3).is_err());
+
+    // Once the first request leaves the window, the address can make one more request.
+    env.clock.set(Timestamp::from(1000));
+    env.root.check_quota(ip_1, owner_3)?;
+    assert!(env.root.check_quota(ip_1, owner_3).is_err());
+
+    // The owner's requests leave the window one by one, too.
+    assert!(env.root.check_quota(ip_2, owner_1).is_ok());
+    assert!(env.root.check_quota(ip_2, owner_1).is_err());
+    env.clock.set(Timestamp::from(1100));
+    env.root.check_quota(ip_2, owner_1)?;
+    Ok(())
+}
@@ -291,6 +291,8 @@ impl PostgresDatabase {
                     SystemOperation::ChangeOwnership { .. } => "ChangeOwnership",
                     SystemOperation::VerifyBlob { .. } => "VerifyBlob",
                     SystemOperation::Checkpoint => "Checkpoint",
+                    SystemOperation::ChangeSignatureSchemes(_) => "ChangeSignatureSchemes",
+                    SystemOperation::MakeEphemeral => "MakeEphemeral",
                 };
                 ("System", None, Some(sys_op_type))
             }
@@ -102,6 +102,8 @@ impl ScyllaDbRunner {
             uri: config.client.uri.clone(),
             max_concurrent_queries: config.client.max_concurrent_queries,
             replication_factor: config.client.replication_factor,
+            schema_migration_dry_run: false,
+            write_aggregation_max_writes: None,
         };
         let store_config = ScyllaDbStoreConfig {
             inner_config,
@@ -86,9 +86,20 @@ pub fn start_metrics(
     address: impl ToSocketAddrs + Debug + Send + 'static,
     shutdown_signal: CancellationToken,
     memory_profiling: MemoryProfiling,
+) {
+    start_metrics_with_routes(address, shutdown_signal, memory_profiling, Router::new());
+}
+
+/// Starts the metrics HTTP server like [`start_metrics`], additionally serving `routes`,
+/// e.g. administration endpoints.
+pub fn start_metrics_with_routes(
+    address: impl ToSocketAddrs + Debug + Send + 'static,
+    shutdown_signal: CancellationToken,
+    memory_profiling: MemoryProfiling,
+    routes: Router,
 ) {
     crate::runtime_metrics::register();
-    let app = metrics_router(memory_profiling);
+    let app = metrics_router(memory_profiling).merge(routes);
 
     tokio::spawn(async move {
         let listener = tokio::net::TcpListener::bind(address)
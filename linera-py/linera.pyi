@@ -0,0 +1,70 @@
+# Copyright (c) Zefchain Labs, Inc.
+# SPDX-License-Identifier: Apache-2.0
+
+"""Type stubs of the `linera` extension module."""
+
+from os import PathLike
+from types import TracebackType
+from typing import Any, Optional, Union
+
+class LineraError(Exception):
+    """An error returned by the Linera client."""
+
+class Notifications:
+    """The notifications of a chain, as an asynchronous iterator of dictionaries.
+
+    Each notification has the keys `chain_id` and `reason`, in the same JSON format as the
+    node service.
+    """
+
+    def __aiter__(self) -> Notifications: ...
+    async def __anext__(self) -> dict[str, Any]: ...
+
+class Client:
+    """A client of the Linera network, using a wallet and its keystore.
+
+    Accounts are given as `chain-id` or `owner@chain-id`, and amounts as decimal numbers of
+    tokens, e.g. `"1.5"`.
+    """
+
+    @staticmethod
+    async def connect(
+        wallet: Optional[Union[str, PathLike[str]]] = None,
+        keystore: Optional[Union[str, PathLike[str]]] = None,
+        storage: Optional[str] = None,
+        with_wallet: Optional[str] = None,
+    ) -> Client:
+        """Connects to the network with the given wallet, keystore and storage."""
+
+    @property
+    def default_chain(self) -> Optional[str]:
+        """The default chain of the wallet, if any."""
+
+    @property
+    def chains(self) -> list[str]:
+        """The chains of the wallet, when the client connected."""
+
+    async def balance(self, account: Optional[str] = None) -> str:
+        """Returns the balance of an account, or of the default chain."""
+
+    async def transfer(
+        self, recipient: str, amount: str, sender: Optional[str] = None
+    ) -> str:
+        """Transfers tokens, and returns the hash of the confirmed block."""
+
+    async def synchronize(self, chain_id: Optional[str] = None) -> None:
+        """Synchronizes a chain, or the default chain, with the validators."""
+
+    async def subscribe(self, chain_id: Optional[str] = None) -> Notifications:
+        """Subscribes to the notifications of a chain, or of the default chain."""
+
+    async def close(self) -> None:
+        """Stops the client, and waits until its chains are no longer followed."""
+
+    async def __aenter__(self) -> Client: ...
+    async def __aexit__(
+        self,
+        exc_type: Optional[type[BaseException]],
+        exc: Optional[BaseException],
+        traceback: Optional[TracebackType],
+    ) -> None: ...
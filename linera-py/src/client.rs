@@ -0,0 +1,506 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Context as _;
+use async_trait::async_trait;
+use futures::{lock::Mutex as AsyncMutex, TryStreamExt as _};
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::Amount,
+    identifiers::{Account, ChainId},
+};
+use linera_client::{
+    chain_listener::{ChainListener, ClientContext as _},
+    client_context::ClientContext,
+};
+use linera_core::{environment::Wallet as _, node::NotificationStream};
+use linera_execution::WithWasmDefault as _;
+use linera_service::{
+    cli::common_options::CommonCliOptions,
+    storage::Runnable,
+    storage_wallet::OpenStorageWalletJob,
+    wallet_backend::{ClientWallet, WalletBackend},
+};
+use linera_storage::Storage;
+use linera_wallet_json::Keystore;
+use pyo3::prelude::*;
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{notifications::Notifications, to_py_err};
+
+/// The size of the block cache, as in the `linera` tool.
+const BLOCK_CACHE_SIZE: usize = 5000;
+/// The size of the execution state cache, as in the `linera` tool.
+const EXECUTION_STATE_CACHE_SIZE: usize = 10000;
+
+type Environment<S> =
+    linera_core::environment::Impl<S, linera_rpc::NodeProvider, Keystore, ClientWallet>;
+type SharedContext<S> = Arc<AsyncMutex<ClientContext<Environment<S>>>>;
+type Responder<T> = oneshot::Sender<anyhow::Result<T>>;
+
+/// A request from Python code to the task running the client.
+enum Request {
+    /// Returns the balance of an account, or of the default chain.
+    Balance {
+        account: Option<Account>,
+        responder: Responder<Amount>,
+    },
+    /// Transfers tokens, and returns the hash of the confirmed block.
+    Transfer {
+        sender: Option<Account>,
+        recipient: Account,
+        amount: Amount,
+        responder: Responder<CryptoHash>,
+    },
+    /// Synchronizes a chain, or the default chain, with the validators.
+    Synchronize {
+        chain_id: Option<ChainId>,
+        responder: Responder<()>,
+    },
+    /// Returns the notifications of a chain, or of the default chain.
+    Subscribe {
+        chain_id: Option<ChainId>,
+        responder: Responder<NotificationStream>,
+    },
+}
+
+/// The chains of the wallet, reported once the client is connected.
+struct WalletChains {
+    default_chain: Option<ChainId>,
+    chain_ids: Vec<ChainId>,
+}
+
+/// Runs the client against the configured storage, serving requests until the [`Client`]
+/// is closed.
+struct ClientJob {
+    options: CommonCliOptions,
+    wallet: ClientWallet,
+    requests: mpsc::UnboundedReceiver<Request>,
+    connected: oneshot::Sender<anyhow::Result<WalletChains>>,
+}
+
+#[async_trait]
+impl Runnable for ClientJob {
+    type Output = anyhow::Result<()>;
+
+    async fn run<S>(self, storage: S) -> anyhow::Result<()>
+    where
+        S: Storage + Clone + Send + Sync + 'static,
+    {
+        let ClientJob {
+            options,
+            wallet,
+            mut requests,
+            connected,
+        } = self;
+        let client_options = linera_client::Options::default();
+        let (context, chains) =
+            match connect(&options, &client_options, storage.clone(), wallet).await {
+                Ok(connection) => connection,
+                Err(error) => {
+                    let message = format!("{error:#}");
+                    let _ = connected.send(Err(error));
+                    anyhow::bail!(message);
+                }
+            };
+        let context = Arc::new(AsyncMutex::new(context));
+
+        // Follow the wallet's chains, so that balances stay current and notifications arrive.
+        let cancellation_token = CancellationToken::new();
+        let chain_listener = ChainListener::new(
+            client_options.chain_listener_config,
+            Arc::clone(&context),
+            storage,
+            cancellation_token.clone(),
+            mpsc::unbounded_channel().1,
+            true, // Enable background sync
+        )
+        .run()
+        .await?;
+        let chain_listener = tokio::spawn(chain_listener);
+        if connected.send(Ok(chains)).is_err() {
+            tracing::debug!("The client was dropped while connecting");
+        }
+
+        while let Some(request) = requests.recv().await {
+            tokio::spawn(handle(Arc::clone(&context), request));
+        }
+
+        cancellation_token.cancel();
+        chain_listener.await??;
+        Ok(())
+    }
+}
+
+/// Creates the client context, and reads the chains of the wallet.
+async fn connect<S: Storage + Clone + Send + Sync + 'static>(
+    options: &CommonCliOptions,
+    client_options: &linera_client::Options,
+    storage: S,
+    wallet: ClientWallet,
+) -> anyhow::Result<(ClientContext<Environment<S>>, WalletChains)> {
+    let keystore = options.keystore()?;
+    let genesis_config = wallet.genesis_config().clone();
+    let default_chain = wallet.default_chain();
+    let chain_ids = wallet.chain_ids().try_collect().await?;
+    let context = ClientContext::new(
+        storage,
+        wallet,
+        keystore,
+        client_options,
+        default_chain,
+        genesis_config,
+        BLOCK_CACHE_SIZE,
+        EXECUTION_STATE_CACHE_SIZE,
+    )
+    .await?;
+    let chains = WalletChains {
+        default_chain,
+        chain_ids,
+    };
+    Ok((context, chains))
+}
+
+/// Serves a single request.
+async fn handle<S: Storage + Clone + Send + Sync + 'static>(
+    context: SharedContext<S>,
+    request: Request,
+) {
+    match request {
+        Request::Balance { account, responder } => {
+            let _ = responder.send(balance(&context, account).await);
+        }
+        Request::Transfer {
+            sender,
+            recipient,
+            amount,
+            responder,
+        } => {
+            let _ = responder.send(transfer(&context, sender, recipient, amount).await);
+        }
+        Request::Synchronize {
+            chain_id,
+            responder,
+        } => {
+            let _ = responder.send(synchronize(&context, chain_id).await);
+        }
+        Request::Subscribe {
+            chain_id,
+            responder,
+        } => {
+            let _ = responder.send(subscribe(&context, chain_id).await);
+        }
+    }
+}
+
+async fn default_account<S: Storage + Clone + Send + Sync + 'static>(
+    context: &SharedContext<S>,
+    account: Option<Account>,
+) -> anyhow::Result<Account> {
+    match account {
+        Some(account) => Ok(account),
+        None => {
+            let context = context.lock().await;
+            anyhow::ensure!(
+                context.has_default_chain(),
+                "The wallet has no default chain"
+            );
+            Ok(context.default_account())
+        }
+    }
+}
+
+async fn balance<S: Storage + Clone + Send + Sync + 'static>(
+    context: &SharedContext<S>,
+    account: Option<Account>,
+) -> anyhow::Result<Amount> {
+    let account = default_account(context, account).await?;
+    let chain_client = context
+        .lock()
+        .await
+        .make_chain_client(account.chain_id)
+        .await?;
+    Ok(chain_client.query_owner_balance(account.owner).await?)
+}
+
+async fn transfer<S: Storage + Clone + Send + Sync + 'static>(
+    context: &SharedContext<S>,
+    sender: Option<Account>,
+    recipient: Account,
+    amount: Amount,
+) -> anyhow::Result<CryptoHash> {
+    let sender = default_account(context, sender).await?;
+    let mut context = context.lock().await;
+    let chain_client = context.make_chain_client(sender.chain_id).await?;
+    let certificate = context
+        .apply_client_command(&chain_client, |chain_client| {
+            let chain_client = chain_client.clone();
+            async move {
+                chain_client
+                    .transfer_to_account(sender.owner, amount, recipient)
+                    .await
+            }
+        })
+        .await
+        .context("Failed to make transfer")?;
+    Ok(certificate.hash())
+}
+
+async fn synchronize<S: Storage + Clone + Send + Sync + 'static>(
+    context: &SharedContext<S>,
+    chain_id: Option<ChainId>,
+) -> anyhow::Result<()> {
+    let chain_id = default_account(context, chain_id.map(Account::chain))
+        .await?
+        .chain_id;
+    let mut context = context.lock().await;
+    let chain_client = context.make_chain_client(chain_id).await?;
+    chain_client.synchronize_from_validators().await?;
+    context.update_wallet_from_client(&chain_client).await?;
+    Ok(())
+}
+
+async fn subscribe<S: Storage + Clone + Send + Sync + 'static>(
+    context: &SharedContext<S>,
+    chain_id: Option<ChainId>,
+) -> anyhow::Result<NotificationStream> {
+    let chain_id = default_account(context, chain_id.map(Account::chain))
+        .await?
+        .chain_id;
+    let chain_client = context.lock().await.make_chain_client(chain_id).await?;
+    Ok(chain_client.subscribe()?)
+}
+
+/// A client of the Linera network, using a wallet and its keystore.
+///
+/// The wallet, keystore and storage are found as by the `linera` tool: from the arguments,
+/// else from the `LINERA_WALLET`, `LINERA_KEYSTORE` and `LINERA_STORAGE` environment
+/// variables, else in the default configuration directory.
+#[pyclass(module = "linera")]
+pub struct Client {
+    requests: Option<mpsc::UnboundedSender<Request>>,
+    task: Option<JoinHandle<anyhow::Result<()>>>,
+    default_chain: Option<ChainId>,
+    chain_ids: Vec<ChainId>,
+}
+
+impl Client {
+    async fn open(options: CommonCliOptions) -> anyhow::Result<Self> {
+        let store_config = options
+            .storage_config()?
+            .add_common_storage_options(&options.common_storage_options)?;
+        let cache_sizes = options.common_storage_options.storage_cache_config();
+        let wallet = match options.wallet_backend {
+            WalletBackend::File => ClientWallet::File(Box::new(options.wallet()?)),
+            WalletBackend::Storage => ClientWallet::Storage(
+                store_config
+                    .clone()
+                    .run_with_store(cache_sizes, OpenStorageWalletJob)
+                    .await?,
+            ),
+        };
+        let wasm_runtime = options.wasm_runtime.with_wasm_default();
+        let application_logs = options.application_logs;
+        let (sender, requests) = mpsc::unbounded_channel();
+        let (connected, connection) = oneshot::channel();
+        let job = ClientJob {
+            options,
+            wallet,
+            requests,
+            connected,
+        };
+        let task = tokio::spawn(async move {
+            store_config
+                .run_with_storage(wasm_runtime, application_logs, cache_sizes, job)
+                .await?
+        });
+        let chains = match connection.await {
+            Ok(chains) => chains?,
+            // The job failed before it could connect.
+            Err(_) => {
+                return Err(task
+                    .await?
+                    .err()
+                    .unwrap_or_else(|| anyhow::anyhow!("The client stopped while connecting")))
+            }
+        };
+        Ok(Self {
+            requests: Some(sender),
+            task: Some(task),
+            default_chain: chains.default_chain,
+            chain_ids: chains.chain_ids,
+        })
+    }
+
+    /// Sends a request to the task running the client, and returns its response.
+    fn request<T: Send + 'static>(
+        &self,
+        make_request: impl FnOnce(Responder<T>) -> Request,
+    ) -> impl std::future::Future<Output = PyResult<T>> {
+        let (responder, response) = oneshot::channel();
+        let sent = match &self.requests {
+            Some(requests) => requests.send(make_request(responder)).is_ok(),
+            None => false,
+        };
+        async move {
+            if !sent {
+                return Err(to_py_err(anyhow::anyhow!("The client is closed")));
+            }
+            response
+                .await
+                .map_err(|_| to_py_err(anyhow::anyhow!("The client stopped")))?
+                .map_err(to_py_err)
+        }
+    }
+}
+
+/// Parses a value given as a string by Python code.
+fn parse<T>(value: &str) -> PyResult<T>
+where
+    T: std::str::FromStr,
+    T::Err: Into<anyhow::Error>,
+{
+    value
+        .parse()
+        .map_err(|error: T::Err| to_py_err(error.into()))
+}
+
+#[pymethods]
+impl Client {
+    /// Connects to the network with the given wallet, keystore and storage.
+    #[staticmethod]
+    #[pyo3(signature = (wallet=None, keystore=None, storage=None, with_wallet=None))]
+    fn connect(
+        py: Python<'_>,
+        wallet: Option<PathBuf>,
+        keystore: Option<PathBuf>,
+        storage: Option<String>,
+        with_wallet: Option<String>,
+    ) -> PyResult<Bound<'_, PyAny>> {
+        let mut options = <CommonCliOptions as clap::Parser>::try_parse_from(["linera"])
+            .map_err(|error| to_py_err(error.into()))?;
+        options.wallet_state_path = wallet;
+        options.keystore_path = keystore;
+        options.storage_config = storage;
+        options.with_wallet = with_wallet;
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Client::open(options).await.map_err(to_py_err)
+        })
+    }
+
+    /// The default chain of the wallet, if any.
+    #[getter]
+    fn default_chain(&self) -> Option<String> {
+        self.default_chain.map(|chain_id| chain_id.to_string())
+    }
+
+    /// The chains of the wallet, when the client connected.
+    #[getter]
+    fn chains(&self) -> Vec<String> {
+        self.chain_ids.iter().map(ToString::to_string).collect()
+    }
+
+    /// Returns the balance of an account, `chain-id` or `owner@chain-id`, or of the default
+    /// chain, as a decimal number of tokens.
+    #[pyo3(signature = (account=None))]
+    fn balance<'py>(&self, py: Python<'py>, account: Option<&str>) -> PyResult<Bound<'py, PyAny>> {
+        let account = account.map(parse).transpose()?;
+        let response = self.request(|responder| Request::Balance { account, responder });
+        pyo3_async_runtimes::tokio::future_into_py(
+            py,
+            async move { Ok(response.await?.to_string()) },
+        )
+    }
+
+    /// Transfers `amount` tokens, a decimal number, to the `recipient` account, and returns
+    /// the hash of the confirmed block. The tokens come from the `sender` account, or from
+    /// the balance of the default chain.
+    #[pyo3(signature = (recipient, amount, sender=None))]
+    fn transfer<'py>(
+        &self,
+        py: Python<'py>,
+        recipient: &str,
+        amount: &str,
+        sender: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let recipient = parse(recipient)?;
+        let amount = parse(amount)?;
+        let sender = sender.map(parse).transpose()?;
+        let response = self.request(|responder| Request::Transfer {
+            sender,
+            recipient,
+            amount,
+            responder,
+        });
+        pyo3_async_runtimes::tokio::future_into_py(
+            py,
+            async move { Ok(response.await?.to_string()) },
+        )
+    }
+
+    /// Synchronizes a chain, or the default chain, with the validators.
+    #[pyo3(signature = (chain_id=None))]
+    fn synchronize<'py>(
+        &self,
+        py: Python<'py>,
+        chain_id: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let chain_id = chain_id.map(parse).transpose()?;
+        let response = self.request(|responder| Request::Synchronize {
+            chain_id,
+            responder,
+        });
+        pyo3_async_runtimes::tokio::future_into_py(py, response)
+    }
+
+    /// Subscribes to the notifications of a chain, or of the default chain.
+    #[pyo3(signature = (chain_id=None))]
+    fn subscribe<'py>(
+        &self,
+        py: Python<'py>,
+        chain_id: Option<&str>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let chain_id = chain_id.map(parse).transpose()?;
+        let response = self.request(|responder| Request::Subscribe {
+            chain_id,
+            responder,
+        });
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Ok(Notifications::new(response.await?))
+        })
+    }
+
+    /// Stops the client, and waits until its chains are no longer followed.
+    fn close<'py>(&mut self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        // Dropping the sender ends the task once the pending requests are served.
+        self.requests = None;
+        let task = self.task.take();
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            if let Some(task) = task {
+                task.await
+                    .map_err(|error| to_py_err(error.into()))?
+                    .map_err(to_py_err)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn __aenter__(slf: Py<Self>, py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+        pyo3_async_runtimes::tokio::future_into_py(py, async move { Ok(slf) })
+    }
+
+    #[pyo3(signature = (*_args))]
+    fn __aexit__<'py>(
+        &mut self,
+        py: Python<'py>,
+        _args: &Bound<'py, pyo3::types::PyTuple>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        self.close(py)
+    }
+}
@@ -0,0 +1,41 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+/*!
+# `linera-py`
+
+This crate defines the Python bindings to the client API, so that scripts and bots can use
+a wallet without going through the `linera` command-line tool.
+
+It is compiled to a Python extension module named `linera` with `maturin`. Every method
+that talks to the network returns an `asyncio` awaitable; the client itself runs on a Tokio
+runtime owned by the extension module.
+*/
+
+use pyo3::{create_exception, exceptions::PyException, prelude::*};
+
+pub mod client;
+pub use client::Client;
+pub mod notifications;
+pub use notifications::Notifications;
+
+create_exception!(
+    linera,
+    LineraError,
+    PyException,
+    "An error returned by the Linera client."
+);
+
+/// Converts an error of the client into a Python exception.
+pub(crate) fn to_py_err(error: anyhow::Error) -> PyErr {
+    LineraError::new_err(format!("{error:#}"))
+}
+
+/// The `linera` Python module.
+#[pymodule]
+fn linera(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Client>()?;
+    module.add_class::<Notifications>()?;
+    module.add("LineraError", module.py().get_type::<LineraError>())?;
+    Ok(())
+}
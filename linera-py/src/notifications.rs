@@ -0,0 +1,51 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::Arc;
+
+use futures::StreamExt as _;
+use linera_core::node::NotificationStream;
+use pyo3::{exceptions::PyStopAsyncIteration, prelude::*};
+use tokio::sync::Mutex;
+
+use crate::to_py_err;
+
+/// The notifications of a chain, as an asynchronous iterator of dictionaries.
+///
+/// Each notification has the keys `chain_id` and `reason`, e.g. a `NewBlock` or
+/// `NewIncomingBundle` reason, in the same JSON format as the node service.
+#[pyclass(module = "linera")]
+pub struct Notifications {
+    stream: Arc<Mutex<NotificationStream>>,
+}
+
+impl Notifications {
+    pub(crate) fn new(stream: NotificationStream) -> Self {
+        Self {
+            stream: Arc::new(Mutex::new(stream)),
+        }
+    }
+}
+
+#[pymethods]
+impl Notifications {
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Waits for the next notification. The iteration stops when the client is closed.
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let stream = Arc::clone(&self.stream);
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let Some(notification) = stream.lock().await.next().await else {
+                return Err(PyStopAsyncIteration::new_err(()));
+            };
+            let json =
+                serde_json::to_string(&notification).map_err(|error| to_py_err(error.into()))?;
+            Python::with_gil(|py| {
+                let value = py.import("json")?.call_method1("loads", (json,))?;
+                Ok(value.unbind())
+            })
+        })
+    }
+}
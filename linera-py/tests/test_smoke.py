@@ -0,0 +1,29 @@
+# Copyright (c) Zefchain Labs, Inc.
+# SPDX-License-Identifier: Apache-2.0
+
+"""Smoke tests of the `linera` extension module, which need no running network."""
+
+import os
+import tempfile
+import unittest
+
+import linera
+
+
+class SmokeTest(unittest.IsolatedAsyncioTestCase):
+    def test_module_exports(self):
+        self.assertTrue(issubclass(linera.LineraError, Exception))
+        self.assertTrue(callable(linera.Client.connect))
+
+    async def test_connect_with_missing_wallet_raises_linera_error(self):
+        with tempfile.TemporaryDirectory() as directory:
+            with self.assertRaises(linera.LineraError):
+                await linera.Client.connect(
+                    wallet=os.path.join(directory, "wallet.json"),
+                    keystore=os.path.join(directory, "keystore.json"),
+                    storage="memory:genesis.json:default",
+                )
+
+
+if __name__ == "__main__":
+    unittest.main()
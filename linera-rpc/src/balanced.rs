@@ -0,0 +1,411 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{BlobContent, BlockHeight, NetworkDescription},
+    identifiers::{BlobId, ChainId, EventId},
+    time::{Duration, Instant},
+};
+use linera_chain::{
+    data_types::BlockProposal,
+    types::{
+        ConfirmedBlockCertificate, LiteCertificate, TimeoutCertificate, ValidatedBlockCertificate,
+    },
+};
+use linera_core::{
+    data_types::{ChainInfoQuery, ChainInfoResponse},
+    node::{BlobStream, CrossChainMessageDelivery, NodeError, NotificationStream, ValidatorNode},
+};
+use linera_storage::Arc as CacheArc;
+use tracing::debug;
+
+use crate::grpc::GrpcClient;
+
+/// The separator between the addresses of a validator reachable through several proxies.
+pub const ADDRESS_SEPARATOR: char = ',';
+
+/// How long an endpoint that failed is only used as a last resort.
+pub const ENDPOINT_COOLDOWN: Duration = Duration::from_secs(10);
+
+/// How requests are spread over the endpoints of a validator.
+#[derive(
+    clap::ValueEnum,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub enum EndpointSelection {
+    /// Use the healthy endpoints in turn.
+    RoundRobin,
+    /// Prefer the healthy endpoint with the lowest observed latency.
+    #[default]
+    Latency,
+}
+
+/// What we know about the health of one endpoint. This is shared by all the clients of
+/// the same address, created by the same node provider.
+#[derive(Debug, Default)]
+pub struct EndpointHealth {
+    state: Mutex<HealthState>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct HealthState {
+    /// The moving average of the latency of successful requests, if any.
+    latency: Option<Duration>,
+    /// When the last request failed because the endpoint could not be reached, unless a
+    /// request succeeded since then.
+    failed_at: Option<Instant>,
+}
+
+impl EndpointHealth {
+    fn state(&self) -> HealthState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Records a successful request that took `latency`.
+    pub fn record_success(&self, latency: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state.failed_at = None;
+        state.latency = Some(match state.latency {
+            None => latency,
+            Some(average) => (average * 3 + latency) / 4,
+        });
+    }
+
+    /// Records a request that failed because the endpoint could not be reached.
+    pub fn record_failure(&self) {
+        self.state.lock().unwrap().failed_at = Some(Instant::now());
+    }
+}
+
+/// Returns the order in which the endpoints should be tried: endpoints that did not fail
+/// recently come first, according to the selection policy, followed by the others, least
+/// recently failed first.
+fn endpoint_order(
+    health: &[Arc<EndpointHealth>],
+    selection: EndpointSelection,
+    turn: usize,
+) -> Vec<usize> {
+    let states = health
+        .iter()
+        .map(|health| health.state())
+        .collect::<Vec<_>>();
+    let (mut healthy, mut failed): (Vec<usize>, Vec<usize>) =
+        (0..states.len()).partition(|&index| match states[index].failed_at {
+            None => true,
+            Some(failed_at) => failed_at.elapsed() >= ENDPOINT_COOLDOWN,
+        });
+    match selection {
+        EndpointSelection::RoundRobin => {
+            if !healthy.is_empty() {
+                let len = healthy.len();
+                healthy.rotate_left(turn % len);
+            }
+        }
+        // Endpoints without a latency yet come first, so that they get measured.
+        EndpointSelection::Latency => healthy.sort_by_key(|&index| states[index].latency),
+    }
+    failed.sort_by_key(|&index| states[index].failed_at);
+    healthy.extend(failed);
+    healthy
+}
+
+/// Returns whether the error means that the endpoint could not be reached, as opposed to
+/// the validator rejecting the request.
+fn is_endpoint_failure(error: &NodeError) -> bool {
+    matches!(
+        error,
+        NodeError::GrpcError { .. } | NodeError::ClientIoError { .. }
+    )
+}
+
+/// A client for a validator that is reachable through several gRPC proxies. Each request
+/// goes to one endpoint, chosen according to the [`EndpointSelection`], and is retried on
+/// the next endpoint if the first one cannot be reached.
+#[derive(Clone)]
+pub struct BalancedClient {
+    address: String,
+    endpoints: Arc<[(GrpcClient, Arc<EndpointHealth>)]>,
+    selection: EndpointSelection,
+    turn: Arc<AtomicUsize>,
+}
+
+impl BalancedClient {
+    /// Creates a client spreading requests over the given endpoints. The address is the
+    /// validator's full network address, listing all of them.
+    pub fn new(
+        address: String,
+        endpoints: Vec<(GrpcClient, Arc<EndpointHealth>)>,
+        selection: EndpointSelection,
+    ) -> Self {
+        assert!(
+            !endpoints.is_empty(),
+            "a validator needs at least one endpoint"
+        );
+        Self {
+            address,
+            endpoints: endpoints.into(),
+            selection,
+            turn: Arc::default(),
+        }
+    }
+
+    /// Returns the addresses of the endpoints.
+    pub fn endpoint_addresses(&self) -> impl Iterator<Item = &str> {
+        self.endpoints.iter().map(|(client, _)| client.address())
+    }
+
+    async fn call<'a, T, F, Fut>(&'a self, mut request: F) -> Result<T, NodeError>
+    where
+        F: FnMut(&'a GrpcClient) -> Fut,
+        Fut: Future<Output = Result<T, NodeError>>,
+    {
+        let health = self
+            .endpoints
+            .iter()
+            .map(|(_, health)| health.clone())
+            .collect::<Vec<_>>();
+        let turn = self.turn.fetch_add(1, Ordering::Relaxed);
+        let mut last_error = None;
+        for index in endpoint_order(&health, self.selection, turn) {
+            let (client, health) = &self.endpoints[index];
+            let start = Instant::now();
+            match request(client).await {
+                Err(error) if is_endpoint_failure(&error) => {
+                    debug!(
+                        address = client.address(),
+                        %error,
+                        "validator endpoint failed; trying the next one"
+                    );
+                    health.record_failure();
+                    last_error = Some(error);
+                }
+                result => {
+                    health.record_success(start.elapsed());
+                    return result;
+                }
+            }
+        }
+        Err(last_error.expect("there is at least one endpoint"))
+    }
+}
+
+impl ValidatorNode for BalancedClient {
+    type NotificationStream = NotificationStream;
+
+    fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    async fn handle_block_proposal(
+        &self,
+        proposal: BlockProposal,
+    ) -> Result<ChainInfoResponse, NodeError> {
+        self.call(|client| client.handle_block_proposal(proposal.clone()))
+            .await
+    }
+
+    async fn handle_lite_certificate(
+        &self,
+        certificate: LiteCertificate<'_>,
+        delivery: CrossChainMessageDelivery,
+    ) -> Result<ChainInfoResponse, NodeError> {
+        self.call(|client| client.handle_lite_certificate(certificate.clone(), delivery))
+            .await
+    }
+
+    async fn handle_timeout_certificate(
+        &self,
+        certificate: TimeoutCertificate,
+    ) -> Result<ChainInfoResponse, NodeError> {
+        self.call(|client| client.handle_timeout_certificate(certificate.clone()))
+            .await
+    }
+
+    async fn handle_confirmed_certificate(
+        &self,
+        certificate: CacheArc<ConfirmedBlockCertificate>,
+        delivery: CrossChainMessageDelivery,
+    ) -> Result<ChainInfoResponse, NodeError> {
+        self.call(|client| client.handle_confirmed_certificate(certificate.clone(), delivery))
+            .await
+    }
+
+    async fn handle_validated_certificate(
+        &self,
+        certificate: ValidatedBlockCertificate,
+    ) -> Result<ChainInfoResponse, NodeError> {
+        self.call(|client| Box::pin(client.handle_validated_certificate(certificate.clone())))
+            .await
+    }
+
+    async fn handle_chain_info_query(
+        &self,
+        query: ChainInfoQuery,
+    ) -> Result<ChainInfoResponse, NodeError> {
+        self.call(|client| client.handle_chain_info_query(query.clone()))
+            .await
+    }
+
+    async fn subscribe(&self, chains: Vec<ChainId>) -> Result<Self::NotificationStream, NodeError> {
+        let stream = self.call(|client| client.subscribe(chains.clone())).await?;
+        Ok(Box::pin(stream))
+    }
+
+    async fn get_version_info(&self) -> Result<linera_version::VersionInfo, NodeError> {
+        self.call(|client| client.get_version_info()).await
+    }
+
+    async fn get_network_description(&self) -> Result<NetworkDescription, NodeError> {
+        self.call(|client| client.get_network_description()).await
+    }
+
+    async fn upload_blob(&self, content: BlobContent) -> Result<BlobId, NodeError> {
+        self.call(|client| client.upload_blob(content.clone()))
+            .await
+    }
+
+    async fn download_blob(&self, blob_id: BlobId) -> Result<BlobContent, NodeError> {
+        self.call(|client| client.download_blob(blob_id)).await
+    }
+
+    async fn download_blobs(&self, blob_ids: Vec<BlobId>) -> Result<BlobStream, NodeError> {
+        self.call(|client| client.download_blobs(blob_ids.clone()))
+            .await
+    }
+
+    async fn download_pending_blob(
+        &self,
+        chain_id: ChainId,
+        blob_id: BlobId,
+    ) -> Result<BlobContent, NodeError> {
+        self.call(|client| client.download_pending_blob(chain_id, blob_id))
+            .await
+    }
+
+    async fn handle_pending_blob(
+        &self,
+        chain_id: ChainId,
+        blob: BlobContent,
+    ) -> Result<ChainInfoResponse, NodeError> {
+        self.call(|client| client.handle_pending_blob(chain_id, blob.clone()))
+            .await
+    }
+
+    async fn download_certificate(
+        &self,
+        hash: CryptoHash,
+    ) -> Result<ConfirmedBlockCertificate, NodeError> {
+        self.call(|client| client.download_certificate(hash)).await
+    }
+
+    async fn download_certificates(
+        &self,
+        hashes: Vec<CryptoHash>,
+    ) -> Result<Vec<ConfirmedBlockCertificate>, NodeError> {
+        self.call(|client| client.download_certificates(hashes.clone()))
+            .await
+    }
+
+    async fn download_certificates_by_heights(
+        &self,
+        chain_id: ChainId,
+        heights: Vec<BlockHeight>,
+    ) -> Result<Vec<ConfirmedBlockCertificate>, NodeError> {
+        self.call(|client| client.download_certificates_by_heights(chain_id, heights.clone()))
+            .await
+    }
+
+    async fn event_block_heights(
+        &self,
+        event_ids: Vec<EventId>,
+    ) -> Result<Vec<Option<BlockHeight>>, NodeError> {
+        self.call(|client| client.event_block_heights(event_ids.clone()))
+            .await
+    }
+
+    async fn blob_last_used_by(&self, blob_id: BlobId) -> Result<CryptoHash, NodeError> {
+        self.call(|client| client.blob_last_used_by(blob_id)).await
+    }
+
+    async fn blob_last_used_by_certificate(
+        &self,
+        blob_id: BlobId,
+    ) -> Result<ConfirmedBlockCertificate, NodeError> {
+        self.call(|client| client.blob_last_used_by_certificate(blob_id))
+            .await
+    }
+
+    async fn missing_blob_ids(&self, blob_ids: Vec<BlobId>) -> Result<Vec<BlobId>, NodeError> {
+        self.call(|client| client.missing_blob_ids(blob_ids.clone()))
+            .await
+    }
+
+    async fn get_shard_info(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<linera_core::data_types::ShardInfo, NodeError> {
+        self.call(|client| client.get_shard_info(chain_id)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoints(count: usize) -> Vec<Arc<EndpointHealth>> {
+        (0..count).map(|_| Arc::default()).collect()
+    }
+
+    #[test]
+    fn test_round_robin_skips_failed_endpoints() {
+        let health = endpoints(3);
+        let order = |turn| endpoint_order(&health, EndpointSelection::RoundRobin, turn);
+        assert_eq!(order(0), vec![0, 1, 2]);
+        assert_eq!(order(1), vec![1, 2, 0]);
+        assert_eq!(order(5), vec![2, 0, 1]);
+
+        health[1].record_failure();
+        assert_eq!(order(0), vec![0, 2, 1]);
+        assert_eq!(order(1), vec![2, 0, 1]);
+
+        health[1].record_success(Duration::from_millis(10));
+        assert_eq!(order(1), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_latency_selection() {
+        let health = endpoints(3);
+        health[0].record_success(Duration::from_millis(30));
+        health[1].record_success(Duration::from_millis(10));
+        let order = || endpoint_order(&health, EndpointSelection::Latency, 0);
+        // The unmeasured endpoint is tried first.
+        assert_eq!(order(), vec![2, 1, 0]);
+
+        health[2].record_success(Duration::from_millis(20));
+        assert_eq!(order(), vec![1, 2, 0]);
+
+        // The average moves towards new measurements.
+        health[1].record_success(Duration::from_millis(70));
+        assert_eq!(health[1].state().latency, Some(Duration::from_millis(25)));
+        assert_eq!(order(), vec![2, 1, 0]);
+
+        health[1].record_failure();
+        assert_eq!(order(), vec![2, 0, 1]);
+    }
+}
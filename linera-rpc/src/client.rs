@@ -18,15 +18,18 @@ use linera_core::{
 };
 use linera_storage::Arc as CacheArc;
 
-use crate::grpc::GrpcClient;
 #[cfg(with_simple_network)]
 use crate::simple::SimpleClient;
+use crate::{balanced::BalancedClient, grpc::GrpcClient};
 
 /// A client for communicating with a validator over one of the supported networks.
 #[derive(Clone)]
 pub enum Client {
     /// A client using the gRPC network.
     Grpc(Box<GrpcClient>),
+    /// A client spreading requests over the gRPC proxies of a validator with several
+    /// addresses.
+    Balanced(Box<BalancedClient>),
     /// A client using the simple (UDP or TCP) network.
     #[cfg(with_simple_network)]
     Simple(SimpleClient),
@@ -51,6 +54,7 @@ impl ValidatorNode for Client {
     fn address(&self) -> String {
         match self {
             Client::Grpc(grpc_client) => grpc_client.address().to_string(),
+            Client::Balanced(balanced_client) => balanced_client.address(),
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.address(),
         }
@@ -62,6 +66,9 @@ impl ValidatorNode for Client {
     ) -> Result<ChainInfoResponse, NodeError> {
         match self {
             Client::Grpc(grpc_client) => grpc_client.handle_block_proposal(proposal).await,
+            Client::Balanced(balanced_client) => {
+                balanced_client.handle_block_proposal(proposal).await
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.handle_block_proposal(proposal).await,
@@ -79,6 +86,11 @@ impl ValidatorNode for Client {
                     .handle_lite_certificate(certificate, delivery)
                     .await
             }
+            Client::Balanced(balanced_client) => {
+                balanced_client
+                    .handle_lite_certificate(certificate, delivery)
+                    .await
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => {
@@ -95,6 +107,11 @@ impl ValidatorNode for Client {
     ) -> Result<ChainInfoResponse, NodeError> {
         match self {
             Client::Grpc(grpc_client) => grpc_client.handle_timeout_certificate(certificate).await,
+            Client::Balanced(balanced_client) => {
+                balanced_client
+                    .handle_timeout_certificate(certificate)
+                    .await
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => {
@@ -114,6 +131,11 @@ impl ValidatorNode for Client {
                     .handle_confirmed_certificate(certificate, delivery)
                     .await
             }
+            Client::Balanced(balanced_client) => {
+                balanced_client
+                    .handle_confirmed_certificate(certificate, delivery)
+                    .await
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => {
@@ -132,6 +154,11 @@ impl ValidatorNode for Client {
             Client::Grpc(grpc_client) => {
                 grpc_client.handle_validated_certificate(certificate).await
             }
+            Client::Balanced(balanced_client) => {
+                balanced_client
+                    .handle_validated_certificate(certificate)
+                    .await
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => {
@@ -148,6 +175,9 @@ impl ValidatorNode for Client {
     ) -> Result<ChainInfoResponse, NodeError> {
         match self {
             Client::Grpc(grpc_client) => grpc_client.handle_chain_info_query(query).await,
+            Client::Balanced(balanced_client) => {
+                balanced_client.handle_chain_info_query(query).await
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.handle_chain_info_query(query).await,
@@ -157,6 +187,7 @@ impl ValidatorNode for Client {
     async fn subscribe(&self, chains: Vec<ChainId>) -> Result<Self::NotificationStream, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => Box::pin(grpc_client.subscribe(chains).await?),
+            Client::Balanced(balanced_client) => balanced_client.subscribe(chains).await?,
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => Box::pin(simple_client.subscribe(chains).await?),
@@ -166,6 +197,7 @@ impl ValidatorNode for Client {
     async fn get_version_info(&self) -> Result<linera_version::VersionInfo, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.get_version_info().await?,
+            Client::Balanced(balanced_client) => balanced_client.get_version_info().await?,
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.get_version_info().await?,
@@ -175,6 +207,7 @@ impl ValidatorNode for Client {
     async fn get_network_description(&self) -> Result<NetworkDescription, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.get_network_description().await?,
+            Client::Balanced(balanced_client) => balanced_client.get_network_description().await?,
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.get_network_description().await?,
@@ -184,6 +217,7 @@ impl ValidatorNode for Client {
     async fn upload_blob(&self, content: BlobContent) -> Result<BlobId, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.upload_blob(content).await?,
+            Client::Balanced(balanced_client) => balanced_client.upload_blob(content).await?,
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.upload_blob(content).await?,
@@ -193,6 +227,7 @@ impl ValidatorNode for Client {
     async fn download_blob(&self, blob_id: BlobId) -> Result<BlobContent, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.download_blob(blob_id).await?,
+            Client::Balanced(balanced_client) => balanced_client.download_blob(blob_id).await?,
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.download_blob(blob_id).await?,
@@ -202,6 +237,7 @@ impl ValidatorNode for Client {
     async fn download_blobs(&self, blob_ids: Vec<BlobId>) -> Result<BlobStream, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.download_blobs(blob_ids).await?,
+            Client::Balanced(balanced_client) => balanced_client.download_blobs(blob_ids).await?,
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.download_blobs(blob_ids).await?,
@@ -217,6 +253,11 @@ impl ValidatorNode for Client {
             Client::Grpc(grpc_client) => {
                 grpc_client.download_pending_blob(chain_id, blob_id).await?
             }
+            Client::Balanced(balanced_client) => {
+                balanced_client
+                    .download_pending_blob(chain_id, blob_id)
+                    .await?
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => {
@@ -234,6 +275,9 @@ impl ValidatorNode for Client {
     ) -> Result<ChainInfoResponse, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.handle_pending_blob(chain_id, blob).await?,
+            Client::Balanced(balanced_client) => {
+                balanced_client.handle_pending_blob(chain_id, blob).await?
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => {
@@ -248,6 +292,7 @@ impl ValidatorNode for Client {
     ) -> Result<ConfirmedBlockCertificate, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.download_certificate(hash).await?,
+            Client::Balanced(balanced_client) => balanced_client.download_certificate(hash).await?,
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.download_certificate(hash).await?,
@@ -260,6 +305,9 @@ impl ValidatorNode for Client {
     ) -> Result<Vec<ConfirmedBlockCertificate>, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.download_certificates(hashes).await?,
+            Client::Balanced(balanced_client) => {
+                balanced_client.download_certificates(hashes).await?
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.download_certificates(hashes).await?,
@@ -278,6 +326,11 @@ impl ValidatorNode for Client {
                     .download_certificates_by_heights(chain_id, heights)
                     .await?
             }
+            Client::Balanced(balanced_client) => {
+                balanced_client
+                    .download_certificates_by_heights(chain_id, heights)
+                    .await?
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => {
@@ -294,6 +347,9 @@ impl ValidatorNode for Client {
     ) -> Result<Vec<Option<BlockHeight>>, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.event_block_heights(event_ids).await?,
+            Client::Balanced(balanced_client) => {
+                balanced_client.event_block_heights(event_ids).await?
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.event_block_heights(event_ids).await?,
@@ -303,6 +359,7 @@ impl ValidatorNode for Client {
     async fn blob_last_used_by(&self, blob_id: BlobId) -> Result<CryptoHash, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.blob_last_used_by(blob_id).await?,
+            Client::Balanced(balanced_client) => balanced_client.blob_last_used_by(blob_id).await?,
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.blob_last_used_by(blob_id).await?,
@@ -315,6 +372,11 @@ impl ValidatorNode for Client {
     ) -> Result<ConfirmedBlockCertificate, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.blob_last_used_by_certificate(blob_id).await?,
+            Client::Balanced(balanced_client) => {
+                balanced_client
+                    .blob_last_used_by_certificate(blob_id)
+                    .await?
+            }
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => {
@@ -326,6 +388,7 @@ impl ValidatorNode for Client {
     async fn missing_blob_ids(&self, blob_ids: Vec<BlobId>) -> Result<Vec<BlobId>, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.missing_blob_ids(blob_ids).await?,
+            Client::Balanced(balanced_client) => balanced_client.missing_blob_ids(blob_ids).await?,
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.missing_blob_ids(blob_ids).await?,
@@ -338,6 +401,7 @@ impl ValidatorNode for Client {
     ) -> Result<linera_core::data_types::ShardInfo, NodeError> {
         Ok(match self {
             Client::Grpc(grpc_client) => grpc_client.get_shard_info(chain_id).await?,
+            Client::Balanced(balanced_client) => balanced_client.get_shard_info(chain_id).await?,
 
             #[cfg(with_simple_network)]
             Client::Simple(simple_client) => simple_client.get_shard_info(chain_id).await?,
@@ -37,6 +37,21 @@ pub struct CrossChainConfig {
     /// Drop cross-chain messages randomly at the given rate (0 <= rate < 1) (meant for testing).
     #[arg(long = "cross-chain-sender-failure-rate", default_value = "0.0")]
     pub(crate) sender_failure_rate: f32,
+
+    /// Maximum number of message bundles from the same origin waiting in a chain's inbox.
+    /// Beyond it, further bundles from that origin are refused and the sending validator
+    /// retries later.
+    #[arg(
+        long = "cross-chain-max-pending-bundles-per-origin",
+        default_value = "10000"
+    )]
+    pub max_pending_bundles_per_origin: usize,
+
+    /// Only send the hashes of the blocks with messages to the recipient chains, and let
+    /// the recipients' shards read the messages from storage once they are consumed,
+    /// instead of sending the messages themselves.
+    #[arg(long = "cross-chain-pull-messages")]
+    pub pull_messages: bool,
 }
 
 impl Default for CrossChainConfig {
@@ -48,7 +63,7 @@ impl Default for CrossChainConfig {
 impl CrossChainConfig {
     /// Returns the command-line arguments corresponding to this configuration.
     pub fn to_args(&self) -> Vec<String> {
-        vec![
+        let mut args = vec![
             "--cross-chain-queue-size".to_string(),
             self.queue_size.to_string(),
             "--cross-chain-max-retries".to_string(),
@@ -61,7 +76,13 @@ impl CrossChainConfig {
             self.sender_delay_ms.to_string(),
             "--cross-chain-sender-failure-rate".to_string(),
             self.sender_failure_rate.to_string(),
-        ]
+            "--cross-chain-max-pending-bundles-per-origin".to_string(),
+            self.max_pending_bundles_per_origin.to_string(),
+        ];
+        if self.pull_messages {
+            args.push("--cross-chain-pull-messages".to_string());
+        }
+        args
     }
 }
 
@@ -96,7 +96,11 @@ pub(crate) async fn forward_cross_chain_queries<F, G>(
                             "Failed to send cross-chain query",
                         );
 
-                        Action::Retry
+                        if error.is::<InboxFull>() {
+                            Action::Backpressure
+                        } else {
+                            Action::Retry
+                        }
                     } else {
                         trace!(from_shard = this_shard, to_shard, "Sent cross-chain query",);
 
@@ -113,6 +117,11 @@ pub(crate) async fn forward_cross_chain_queries<F, G>(
                     linera_base::time::timer::sleep(delay).await;
                     Action::Proceed { id: state.id }
                 }
+
+                Action::Backpressure => {
+                    linera_base::time::timer::sleep(cross_chain_max_backoff).await;
+                    Action::Proceed { id: state.id }
+                }
             },
         )
     };
@@ -205,6 +214,9 @@ impl QueueId {
         let (sender, recipient, is_update) = match request {
             CrossChainRequest::UpdateRecipient {
                 sender, recipient, ..
+            }
+            | CrossChainRequest::NotifyRecipient {
+                sender, recipient, ..
             } => (*sender, *recipient, true),
             CrossChainRequest::ConfirmUpdatedRecipient {
                 sender, recipient, ..
@@ -221,11 +233,21 @@ impl QueueId {
     }
 }
 
+/// The error returned by a request handler when the recipient refused the update because its
+/// inbox for the sender is full.
+#[derive(Debug, thiserror::Error)]
+#[error("the recipient's inbox is full")]
+pub(crate) struct InboxFull;
+
 enum Action {
     /// The request has been sent successfully and the next request can be sent.
     Proceed { id: usize },
     /// The request failed and should be retried.
     Retry,
+    /// The recipient refused the request until its inbox drains. It is retried after the
+    /// maximum backoff without counting against the retry budget; the recipient also
+    /// re-requests the bundles itself once it has room.
+    Backpressure,
 }
 
 #[derive(Clone)]
@@ -259,6 +281,9 @@ impl JobState {
             Action::Proceed { id } => self.id < *id,
             // If the action is to retry and the maximum number of retries has been reached.
             Action::Retry => self.retries >= max_retries,
+            // Backpressure is not a failure, so the request is kept until it is accepted or
+            // superseded.
+            Action::Backpressure => false,
         }
     }
 }
@@ -0,0 +1,64 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Decoding of the consensus-critical values received over the network.
+//!
+//! `bincode` accepts some encodings that differ from the one it produces, e.g. with
+//! trailing bytes. With the `strict-decoding` feature, such non-canonical encodings of
+//! proposals and certificates are rejected, so that every value has a single valid encoding.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(all(with_metrics, feature = "strict-decoding"))]
+mod metrics {
+    use std::sync::LazyLock;
+
+    use linera_base::prometheus_util::register_int_counter_vec;
+    use prometheus::IntCounterVec;
+
+    pub static NON_CANONICAL_ENCODINGS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+        register_int_counter_vec(
+            "non_canonical_encodings",
+            "Number of non-canonical encodings rejected by strict decoding, by type",
+            &["type"],
+        )
+    });
+}
+
+/// Deserializes a value with `bincode`. In strict mode, the bytes must also be the
+/// canonical encoding of the value, i.e. the one it serializes to.
+pub(crate) fn deserialize<T>(bytes: &[u8]) -> Result<T, bincode::Error>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let value = bincode::deserialize(bytes)?;
+    #[cfg(feature = "strict-decoding")]
+    if bincode::serialize(&value)? != bytes {
+        let type_name = std::any::type_name::<T>();
+        #[cfg(with_metrics)]
+        metrics::NON_CANONICAL_ENCODINGS
+            .with_label_values(&[type_name])
+            .inc();
+        return Err(Box::new(bincode::ErrorKind::Custom(format!(
+            "non-canonical encoding of {type_name}"
+        ))));
+    }
+    Ok(value)
+}
+
+#[cfg(all(test, feature = "strict-decoding"))]
+mod tests {
+    use linera_base::data_types::Round;
+
+    #[test]
+    fn test_strict_decoding_rejects_trailing_bytes() {
+        let mut bytes = bincode::serialize(&Round::SingleLeader(3)).unwrap();
+        assert_eq!(
+            super::deserialize::<Round>(&bytes).unwrap(),
+            Round::SingleLeader(3)
+        );
+        bytes.push(0);
+        assert!(bincode::deserialize::<Round>(&bytes).is_ok());
+        assert!(super::deserialize::<Round>(&bytes).is_err());
+    }
+}
@@ -55,6 +55,7 @@ use tracing::{debug, instrument, trace, Level};
 
 use super::{
     api::{self, validator_node_client::ValidatorNodeClient, SubscriptionRequest},
+    pool::{RequestLimiter, RequestPriority},
     transport, GRPC_MAX_MESSAGE_SIZE,
 };
 #[cfg(feature = "opentelemetry")]
@@ -76,6 +77,11 @@ pub struct GrpcClient {
     /// Tracks when each validator address last had a subscription failure, so that
     /// other chains don't independently retry the same dead validator.
     subscription_cooldowns: Arc<papaya::HashMap<String, Instant>>,
+    /// Shared by all the clients of the same validator, to cap concurrent requests.
+    limiter: Arc<RequestLimiter>,
+    /// The timeout sent along with each request, so that the validator stops working on
+    /// requests we no longer wait for.
+    request_timeout: Option<Duration>,
 }
 
 impl GrpcClient {
@@ -87,6 +93,7 @@ impl GrpcClient {
         max_retries: u32,
         max_backoff: Duration,
         subscription_cooldowns: Arc<papaya::HashMap<String, Instant>>,
+        limiter: Arc<RequestLimiter>,
     ) -> Self {
         let client = ValidatorNodeClient::new(channel)
             .max_encoding_message_size(GRPC_MAX_MESSAGE_SIZE)
@@ -98,9 +105,18 @@ impl GrpcClient {
             max_retries,
             max_backoff,
             subscription_cooldowns,
+            limiter,
+            request_timeout: None,
         }
     }
 
+    /// Sends the given timeout along with each request, so that the validator can
+    /// abandon the requests we no longer wait for.
+    pub fn with_request_timeout(mut self, request_timeout: Option<Duration>) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
     /// Returns the address of the validator this client connects to.
     pub fn address(&self) -> &str {
         &self.address
@@ -155,6 +171,7 @@ impl GrpcClient {
         f: F,
         request: impl TryInto<R> + fmt::Debug + Clone,
         handler: &str,
+        priority: RequestPriority,
     ) -> Result<S, NodeError>
     where
         F: Fn(ValidatorNodeClient<transport::Channel>, Request<R>) -> Fut,
@@ -166,14 +183,20 @@ impl GrpcClient {
             error: "could not convert request to proto".to_string(),
         })?;
         loop {
-            #[allow(unused_mut)]
             let mut request = Request::new(request_inner.clone());
+            if let Some(timeout) = self.request_timeout {
+                request.set_timeout(timeout);
+            }
             // Inject OpenTelemetry context (trace context + baggage) into gRPC metadata.
             // This uses get_context_with_traffic_type() to also check the LINERA_TRAFFIC_TYPE
             // environment variable, allowing benchmark tools to mark their traffic as synthetic.
             #[cfg(feature = "opentelemetry")]
             inject_context(&get_context_with_traffic_type(), request.metadata_mut());
-            match f(self.client.clone(), request).await {
+            let permit = self.limiter.acquire(priority).await;
+            let result = f(self.client.clone(), request).await;
+            // Release the permit before waiting to retry.
+            drop(permit);
+            match result {
                 Err(s) if Self::is_retryable(&s) && retry_count < self.max_retries => {
                     let delay = crate::jittered_backoff_delay(
                         self.retry_delay,
@@ -236,7 +259,7 @@ impl TryFrom<api::PendingBlobResult> for BlobContent {
 }
 
 macro_rules! client_delegate {
-    ($self:ident, $handler:ident, $req:ident) => {{
+    ($self:ident, $handler:ident, $req:ident, $priority:ident) => {{
         debug!(
             handler = stringify!($handler),
             request = ?$req,
@@ -247,6 +270,7 @@ macro_rules! client_delegate {
                 |mut client, req| async move { client.$handler(req).await },
                 $req,
                 stringify!($handler),
+                RequestPriority::$priority,
             )
             .await
     }};
@@ -264,7 +288,12 @@ impl ValidatorNode for GrpcClient {
         &self,
         proposal: data_types::BlockProposal,
     ) -> Result<linera_core::data_types::ChainInfoResponse, NodeError> {
-        GrpcClient::try_into_chain_info(client_delegate!(self, handle_block_proposal, proposal)?)
+        GrpcClient::try_into_chain_info(client_delegate!(
+            self,
+            handle_block_proposal,
+            proposal,
+            Consensus
+        )?)
     }
 
     #[instrument(target = "grpc_client", skip_all, fields(address = self.address))]
@@ -278,7 +307,12 @@ impl ValidatorNode for GrpcClient {
             certificate,
             wait_for_outgoing_messages,
         };
-        GrpcClient::try_into_chain_info(client_delegate!(self, handle_lite_certificate, request)?)
+        GrpcClient::try_into_chain_info(client_delegate!(
+            self,
+            handle_lite_certificate,
+            request,
+            Consensus
+        )?)
     }
 
     #[instrument(target = "grpc_client", skip_all, err(level = Level::DEBUG), fields(address = self.address))]
@@ -295,7 +329,8 @@ impl ValidatorNode for GrpcClient {
         GrpcClient::try_into_chain_info(client_delegate!(
             self,
             handle_confirmed_certificate,
-            request
+            request,
+            Consensus
         )?)
     }
 
@@ -308,7 +343,8 @@ impl ValidatorNode for GrpcClient {
         GrpcClient::try_into_chain_info(client_delegate!(
             self,
             handle_validated_certificate,
-            request
+            request,
+            Consensus
         )?)
     }
 
@@ -321,7 +357,8 @@ impl ValidatorNode for GrpcClient {
         GrpcClient::try_into_chain_info(client_delegate!(
             self,
             handle_timeout_certificate,
-            request
+            request,
+            Consensus
         )?)
     }
 
@@ -330,7 +367,12 @@ impl ValidatorNode for GrpcClient {
         &self,
         query: linera_core::data_types::ChainInfoQuery,
     ) -> Result<linera_core::data_types::ChainInfoResponse, NodeError> {
-        GrpcClient::try_into_chain_info(client_delegate!(self, handle_chain_info_query, query)?)
+        GrpcClient::try_into_chain_info(client_delegate!(
+            self,
+            handle_chain_info_query,
+            query,
+            Sync
+        )?)
     }
 
     #[instrument(target = "grpc_client", skip_all, err(level = Level::DEBUG), fields(address = self.address))]
@@ -470,23 +512,23 @@ impl ValidatorNode for GrpcClient {
     #[instrument(target = "grpc_client", skip_all, err(level = Level::DEBUG), fields(address = self.address))]
     async fn get_version_info(&self) -> Result<VersionInfo, NodeError> {
         let req = ();
-        Ok(client_delegate!(self, get_version_info, req)?.into())
+        Ok(client_delegate!(self, get_version_info, req, Sync)?.into())
     }
 
     #[instrument(target = "grpc_client", skip_all, err(level = Level::DEBUG), fields(address = self.address))]
     async fn get_network_description(&self) -> Result<NetworkDescription, NodeError> {
         let req = ();
-        Ok(client_delegate!(self, get_network_description, req)?.try_into()?)
+        Ok(client_delegate!(self, get_network_description, req, Sync)?.try_into()?)
     }
 
     #[instrument(target = "grpc_client", skip(self), err(level = Level::DEBUG), fields(address = self.address))]
     async fn upload_blob(&self, content: BlobContent) -> Result<BlobId, NodeError> {
-        Ok(client_delegate!(self, upload_blob, content)?.try_into()?)
+        Ok(client_delegate!(self, upload_blob, content, Blob)?.try_into()?)
     }
 
     #[instrument(target = "grpc_client", skip(self), err(level = Level::DEBUG), fields(address = self.address))]
     async fn download_blob(&self, blob_id: BlobId) -> Result<BlobContent, NodeError> {
-        Ok(client_delegate!(self, download_blob, blob_id)?.try_into()?)
+        Ok(client_delegate!(self, download_blob, blob_id, Blob)?.try_into()?)
     }
 
     #[instrument(target = "grpc_client", skip(self), err(level = Level::DEBUG), fields(address = self.address))]
@@ -497,6 +539,7 @@ impl ValidatorNode for GrpcClient {
             "sending gRPC request"
         );
         let request = api::BlobIds::try_from(blob_ids)?;
+        let _permit = self.limiter.acquire(RequestPriority::Blob).await;
         let stream = self
             .client
             .clone()
@@ -522,7 +565,7 @@ impl ValidatorNode for GrpcClient {
         blob_id: BlobId,
     ) -> Result<BlobContent, NodeError> {
         let req = (chain_id, blob_id);
-        client_delegate!(self, download_pending_blob, req)?.try_into()
+        client_delegate!(self, download_pending_blob, req, Blob)?.try_into()
     }
 
     #[instrument(target = "grpc_client", skip(self), err(level = Level::DEBUG), fields(address = self.address))]
@@ -532,7 +575,12 @@ impl ValidatorNode for GrpcClient {
         blob: BlobContent,
     ) -> Result<ChainInfoResponse, NodeError> {
         let req = (chain_id, blob);
-        GrpcClient::try_into_chain_info(client_delegate!(self, handle_pending_blob, req)?)
+        GrpcClient::try_into_chain_info(client_delegate!(
+            self,
+            handle_pending_blob,
+            req,
+            Consensus
+        )?)
     }
 
     #[instrument(target = "grpc_client", skip_all, err(level = Level::DEBUG), fields(address = self.address))]
@@ -543,7 +591,8 @@ impl ValidatorNode for GrpcClient {
         ConfirmedBlockCertificate::try_from(Certificate::try_from(client_delegate!(
             self,
             download_certificate,
-            hash
+            hash,
+            Sync
         )?)?)
         .map_err(|_| NodeError::UnexpectedCertificateValue)
     }
@@ -561,7 +610,8 @@ impl ValidatorNode for GrpcClient {
             let mut received: Vec<_> = Vec::<Certificate>::try_from(client_delegate!(
                 self,
                 download_certificates,
-                missing
+                missing,
+                Sync
             )?)?
             .into_iter()
             .map(|cert| {
@@ -600,7 +650,7 @@ impl ValidatorNode for GrpcClient {
                 heights: missing.iter().copied().collect(),
             };
             let mut received: Vec<_> =
-                client_delegate!(self, download_raw_certificates_by_heights, request)?
+                client_delegate!(self, download_raw_certificates_by_heights, request, Sync)?
                     .certificates
                     .into_iter()
                     .map(
@@ -637,7 +687,7 @@ impl ValidatorNode for GrpcClient {
 
     #[instrument(target = "grpc_client", skip(self), err(level = Level::DEBUG), fields(address = self.address))]
     async fn blob_last_used_by(&self, blob_id: BlobId) -> Result<CryptoHash, NodeError> {
-        Ok(client_delegate!(self, blob_last_used_by, blob_id)?.try_into()?)
+        Ok(client_delegate!(self, blob_last_used_by, blob_id, Sync)?.try_into()?)
     }
 
     #[instrument(target = "grpc_client", skip(self), err(level = Level::DEBUG), fields(address = self.address))]
@@ -645,7 +695,7 @@ impl ValidatorNode for GrpcClient {
         &self,
         blob_id: BlobId,
     ) -> Result<ConfirmedBlockCertificate, NodeError> {
-        Ok(client_delegate!(self, blob_last_used_by_certificate, blob_id)?.try_into()?)
+        Ok(client_delegate!(self, blob_last_used_by_certificate, blob_id, Sync)?.try_into()?)
     }
 
     #[instrument(target = "grpc_client", skip(self), err(level = Level::DEBUG), fields(address = self.address))]
@@ -654,12 +704,12 @@ impl ValidatorNode for GrpcClient {
         event_ids: Vec<EventId>,
     ) -> Result<Vec<Option<BlockHeight>>, NodeError> {
         let request = api::EventBlockHeightsRequest::from(event_ids);
-        Ok(client_delegate!(self, event_block_heights, request)?.try_into()?)
+        Ok(client_delegate!(self, event_block_heights, request, Sync)?.try_into()?)
     }
 
     #[instrument(target = "grpc_client", skip(self), err(level = Level::DEBUG), fields(address = self.address))]
     async fn missing_blob_ids(&self, blob_ids: Vec<BlobId>) -> Result<Vec<BlobId>, NodeError> {
-        Ok(client_delegate!(self, missing_blob_ids, blob_ids)?.try_into()?)
+        Ok(client_delegate!(self, missing_blob_ids, blob_ids, Sync)?.try_into()?)
     }
 
     #[expect(
@@ -671,7 +721,7 @@ impl ValidatorNode for GrpcClient {
         &self,
         chain_id: ChainId,
     ) -> Result<linera_core::data_types::ShardInfo, NodeError> {
-        let response = client_delegate!(self, get_shard_info, chain_id)?;
+        let response = client_delegate!(self, get_shard_info, chain_id, Sync)?;
         Ok(linera_core::data_types::ShardInfo {
             shard_id: response.shard_id as usize,
             total_shards: response.total_shards as usize,
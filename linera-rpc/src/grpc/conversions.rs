@@ -31,8 +31,8 @@ use tonic::{Code, Status};
 
 use super::api::{self, PendingBlobRequest};
 use crate::{
-    HandleConfirmedCertificateRequest, HandleLiteCertRequest, HandleTimeoutCertificateRequest,
-    HandleValidatedCertificateRequest,
+    decoding, HandleConfirmedCertificateRequest, HandleLiteCertRequest,
+    HandleTimeoutCertificateRequest, HandleValidatedCertificateRequest,
 };
 
 #[derive(Error, Debug)]
@@ -72,7 +72,7 @@ fn deserialize_justification(bytes: &[u8]) -> Result<JustificationChain, GrpcPro
     if bytes.is_empty() {
         Ok(JustificationChain::default())
     } else {
-        Ok(bincode::deserialize(bytes)?)
+        Ok(decoding::deserialize(bytes)?)
     }
 }
 
@@ -268,7 +268,7 @@ impl TryFrom<api::BlockProposal> for BlockProposal {
     type Error = GrpcProtoConversionError;
 
     fn try_from(block_proposal: api::BlockProposal) -> Result<Self, Self::Error> {
-        let content: ProposalContent = bincode::deserialize(&block_proposal.content)?;
+        let content: ProposalContent = decoding::deserialize(&block_proposal.content)?;
         ensure!(
             Some(content.block.chain_id.into()) == block_proposal.chain_id,
             GrpcProtoConversionError::InconsistentChainId
@@ -278,7 +278,7 @@ impl TryFrom<api::BlockProposal> for BlockProposal {
             signature: try_proto_convert(block_proposal.signature)?,
             original_proposal: block_proposal
                 .original_proposal
-                .map(|bytes| bincode::deserialize(&bytes))
+                .map(|bytes| decoding::deserialize(&bytes))
                 .transpose()?,
         })
     }
@@ -327,6 +327,17 @@ impl TryFrom<api::CrossChainRequest> for CrossChainRequest {
                     .ok_or(GrpcProtoConversionError::MissingField)?
                     .into(),
             },
+            Inner::NotifyRecipient(api::NotifyRecipient {
+                sender,
+                recipient,
+                blocks,
+                previous_height,
+            }) => CrossChainRequest::NotifyRecipient {
+                sender: try_proto_convert(sender)?,
+                recipient: try_proto_convert(recipient)?,
+                blocks: bincode::deserialize(&blocks)?,
+                previous_height: previous_height.map(Into::into),
+            },
         };
         Ok(ccr)
     }
@@ -368,6 +379,17 @@ impl TryFrom<CrossChainRequest> for api::CrossChainRequest {
                 recipient: Some(recipient.into()),
                 retransmit_from: Some(retransmit_from.into()),
             }),
+            CrossChainRequest::NotifyRecipient {
+                sender,
+                recipient,
+                blocks,
+                previous_height,
+            } => Inner::NotifyRecipient(api::NotifyRecipient {
+                sender: Some(sender.into()),
+                recipient: Some(recipient.into()),
+                blocks: bincode::serialize(&blocks)?,
+                previous_height: previous_height.map(Into::into),
+            }),
         };
         Ok(Self { inner: Some(inner) })
     }
@@ -392,9 +414,9 @@ impl TryFrom<api::LiteCertificate> for HandleLiteCertRequest<'_> {
             chain_id: try_proto_convert(certificate.chain_id)?,
             kind,
         };
-        let signatures: Vec<_> = bincode::deserialize(&certificate.signatures)?;
-        let round = bincode::deserialize(&certificate.round)?;
-        let unlocking_round = bincode::deserialize(&certificate.unlocking_round)?;
+        let signatures: Vec<_> = decoding::deserialize(&certificate.signatures)?;
+        let round = decoding::deserialize(&certificate.round)?;
+        let unlocking_round = decoding::deserialize(&certificate.unlocking_round)?;
         let justification = deserialize_justification(&certificate.justification)?;
         // The signed justification commitment is not on the wire: it is derived from the carried
         // chain, which `LiteCertificate::check` binds it to anyway. A certificate whose voters
@@ -541,12 +563,12 @@ impl TryFrom<api::Certificate> for TimeoutCertificate {
     type Error = GrpcProtoConversionError;
 
     fn try_from(certificate: api::Certificate) -> Result<Self, Self::Error> {
-        let round = bincode::deserialize(&certificate.round)?;
-        let signatures = bincode::deserialize(&certificate.signatures)?;
+        let round = decoding::deserialize(&certificate.round)?;
+        let signatures = decoding::deserialize(&certificate.signatures)?;
         let cert_type = certificate.kind;
 
         if cert_type == api::CertificateKind::Timeout as i32 {
-            let value: Timeout = bincode::deserialize(&certificate.value)?;
+            let value: Timeout = decoding::deserialize(&certificate.value)?;
             Ok(TimeoutCertificate::new(value, round, signatures))
         } else {
             Err(GrpcProtoConversionError::InvalidCertificateType)
@@ -558,12 +580,12 @@ impl TryFrom<api::Certificate> for ValidatedBlockCertificate {
     type Error = GrpcProtoConversionError;
 
     fn try_from(certificate: api::Certificate) -> Result<Self, Self::Error> {
-        let round = bincode::deserialize(&certificate.round)?;
-        let signatures = bincode::deserialize(&certificate.signatures)?;
+        let round = decoding::deserialize(&certificate.round)?;
+        let signatures = decoding::deserialize(&certificate.signatures)?;
         let cert_type = certificate.kind;
 
         if cert_type == api::CertificateKind::Validated as i32 {
-            let value: ValidatedBlock = bincode::deserialize(&certificate.value)?;
+            let value: ValidatedBlock = decoding::deserialize(&certificate.value)?;
             let below = deserialize_justification(&certificate.justification)?;
             // The signed unlocking round and justification commitment are derived from the
             // carried chain, which the certificate check binds them to anyway.
@@ -587,12 +609,12 @@ impl TryFrom<api::Certificate> for ConfirmedBlockCertificate {
     type Error = GrpcProtoConversionError;
 
     fn try_from(certificate: api::Certificate) -> Result<Self, Self::Error> {
-        let round = bincode::deserialize(&certificate.round)?;
-        let signatures = bincode::deserialize(&certificate.signatures)?;
+        let round = decoding::deserialize(&certificate.round)?;
+        let signatures = decoding::deserialize(&certificate.signatures)?;
         let cert_type = certificate.kind;
 
         if cert_type == api::CertificateKind::Confirmed as i32 {
-            let value: ConfirmedBlock = bincode::deserialize(&certificate.value)?;
+            let value: ConfirmedBlock = decoding::deserialize(&certificate.value)?;
             let validated = deserialize_justification(&certificate.justification)?;
             // The signed justification commitment is derived from the carried chain, which the
             // certificate check binds it to anyway.
@@ -1252,6 +1274,7 @@ pub mod tests {
             requested_received_log: vec![],
             requested_previous_event_blocks: BTreeMap::new(),
             requested_latest_checkpoint_height: None,
+            validator_address: None,
         });
 
         let chain_info_response_none = ChainInfoResponse {
@@ -1383,6 +1406,14 @@ pub mod tests {
         round_trip_check::<_, api::CrossChainRequest>(
             &cross_chain_request_confirm_updated_recipient,
         );
+
+        let cross_chain_request_notify_recipient = CrossChainRequest::NotifyRecipient {
+            sender: dummy_chain_id(0),
+            recipient: dummy_chain_id(1),
+            blocks: vec![(BlockHeight(3), CryptoHash::test_hash("block"))],
+            previous_height: None,
+        };
+        round_trip_check::<_, api::CrossChainRequest>(&cross_chain_request_notify_recipient);
     }
 
     #[test]
@@ -0,0 +1,110 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::task::{Context, Poll};
+
+use futures::{future::BoxFuture, FutureExt as _};
+use linera_base::{
+    deadline,
+    time::{Duration, Instant},
+};
+use tower::{Layer, Service};
+
+/// The header in which gRPC clients send how long they wait for the response.
+const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
+
+/// Parses the value of a `grpc-timeout` header: at most eight digits followed by a unit,
+/// e.g. `"500m"` for 500 milliseconds.
+pub fn parse_grpc_timeout(value: &str) -> Option<Duration> {
+    if value.len() < 2 || value.len() > 9 || !value.is_ascii() {
+        return None;
+    }
+    let (digits, unit) = value.split_at(value.len() - 1);
+    if !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let amount = digits.parse::<u64>().ok()?;
+    Some(match unit {
+        "H" => Duration::from_secs(amount * 60 * 60),
+        "M" => Duration::from_secs(amount * 60),
+        "S" => Duration::from_secs(amount),
+        "m" => Duration::from_millis(amount),
+        "u" => Duration::from_micros(amount),
+        "n" => Duration::from_nanos(amount),
+        _ => return None,
+    })
+}
+
+/// Returns the time left until the deadline of the request being served, if any, so
+/// that the requests made on its behalf can be given the same deadline.
+pub fn remaining_timeout() -> Option<Duration> {
+    deadline::current().map(|deadline| deadline.saturating_duration_since(Instant::now()))
+}
+
+/// A Tower layer that serves each gRPC request within the deadline given by its
+/// `grpc-timeout` header, so that the work done on behalf of a caller that already gave
+/// up can be abandoned.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GrpcDeadlineLayer;
+
+/// The Tower service produced by [`GrpcDeadlineLayer`].
+#[derive(Clone, Debug)]
+pub struct GrpcDeadlineService<S> {
+    service: S,
+}
+
+impl<S> Layer<S> for GrpcDeadlineLayer {
+    type Service = GrpcDeadlineService<S>;
+
+    fn layer(&self, service: S) -> Self::Service {
+        GrpcDeadlineService { service }
+    }
+}
+
+impl<S, B> Service<http::Request<B>> for GrpcDeadlineService<S>
+where
+    S: Service<http::Request<B>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<S::Response, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let deadline = request
+            .headers()
+            .get(GRPC_TIMEOUT_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_grpc_timeout)
+            .and_then(|timeout| Instant::now().checked_add(timeout));
+        deadline::scope(deadline, self.service.call(request)).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::time::Duration;
+
+    use super::parse_grpc_timeout;
+
+    #[test]
+    fn test_parse_grpc_timeout() {
+        assert_eq!(parse_grpc_timeout("500m"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_grpc_timeout("2S"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_grpc_timeout("1H"), Some(Duration::from_secs(3600)));
+        assert_eq!(
+            parse_grpc_timeout("99999999n"),
+            Some(Duration::from_nanos(99_999_999))
+        );
+        assert_eq!(parse_grpc_timeout("m"), None);
+        assert_eq!(parse_grpc_timeout("100"), None);
+        assert_eq!(parse_grpc_timeout("100x"), None);
+        assert_eq!(parse_grpc_timeout("123456789m"), None);
+        assert_eq!(parse_grpc_timeout("-1S"), None);
+        assert_eq!(parse_grpc_timeout("1é"), None);
+    }
+}
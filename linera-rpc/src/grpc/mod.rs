@@ -3,6 +3,9 @@
 
 mod client;
 mod conversions;
+/// Propagation of the callers' deadlines into the work done on their behalf.
+#[cfg(with_server)]
+pub mod deadline;
 mod node_provider;
 /// A pool of reusable gRPC transport channels.
 pub mod pool;
@@ -24,6 +24,8 @@ pub struct GrpcNodeProvider {
     /// fails, the failure time is recorded here so that other chains (which share the
     /// same provider) skip retrying the same dead validator.
     subscription_cooldowns: Arc<papaya::HashMap<String, Instant>>,
+    /// The timeout sent along with each request.
+    request_timeout: Option<Duration>,
 }
 
 impl GrpcNodeProvider {
@@ -33,13 +35,16 @@ impl GrpcNodeProvider {
         let retry_delay = options.retry_delay;
         let max_retries = options.max_retries;
         let max_backoff = options.max_backoff;
-        let pool = GrpcConnectionPool::new(transport_options);
+        let request_timeout = transport_options.timeout;
+        let pool = GrpcConnectionPool::new(transport_options)
+            .with_max_concurrent_requests(options.max_concurrent_requests);
         Self {
             pool,
             retry_delay,
             max_retries,
             max_backoff,
             subscription_cooldowns: Arc::new(papaya::HashMap::new()),
+            request_timeout,
         }
     }
 }
@@ -60,6 +65,7 @@ impl ValidatorNodeProvider for GrpcNodeProvider {
                 .map_err(|error| NodeError::GrpcError {
                     error: format!("error creating channel: {error}"),
                 })?;
+        let limiter = self.pool.limiter(&http_address);
 
         Ok(GrpcClient::new(
             http_address,
@@ -68,6 +74,8 @@ impl ValidatorNodeProvider for GrpcNodeProvider {
             self.max_retries,
             self.max_backoff,
             self.subscription_cooldowns.clone(),
-        ))
+            limiter,
+        )
+        .with_request_timeout(self.request_timeout))
     }
 }
@@ -1,17 +1,26 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::sync::Arc;
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
 
+use futures::channel::oneshot;
 use linera_base::time::Duration;
 
 use super::{transport, GrpcError};
 
 /// A pool of transport channels to be used by gRPC.
+///
+/// Channels are shared by all the clients of the same address, so that they reuse the same
+/// connection. Optionally, the number of concurrent requests to each address is capped.
 #[derive(Clone, Default)]
 pub struct GrpcConnectionPool {
     options: transport::Options,
+    max_concurrent_requests: Option<usize>,
     channels: Arc<papaya::HashMap<String, transport::Channel>>,
+    limiters: Arc<papaya::HashMap<String, Arc<RequestLimiter>>>,
 }
 
 impl GrpcConnectionPool {
@@ -19,7 +28,7 @@ impl GrpcConnectionPool {
     pub fn new(options: transport::Options) -> Self {
         Self {
             options,
-            channels: Arc::new(papaya::HashMap::default()),
+            ..Self::default()
         }
     }
 
@@ -35,6 +44,13 @@ impl GrpcConnectionPool {
         self
     }
 
+    /// Sets the maximum number of concurrent requests to each address, or `None` for no
+    /// limit.
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: Option<usize>) -> Self {
+        self.max_concurrent_requests = max_concurrent_requests;
+        self
+    }
+
     /// Obtains a channel for the current address. Either clones an existing one (thereby
     /// reusing the connection), or creates one if needed. New channels do not create a
     /// connection immediately.
@@ -46,4 +62,189 @@ impl GrpcConnectionPool {
         let channel = transport::create_channel(address.clone(), &self.options)?;
         Ok(pinned.get_or_insert(address, channel).clone())
     }
+
+    /// Returns the limiter shared by all the requests to the given address.
+    pub fn limiter(&self, address: &str) -> Arc<RequestLimiter> {
+        let pinned = self.limiters.pin();
+        if let Some(limiter) = pinned.get(address) {
+            return limiter.clone();
+        }
+        let limiter = Arc::new(RequestLimiter::new(self.max_concurrent_requests));
+        pinned.get_or_insert(address.to_owned(), limiter).clone()
+    }
+}
+
+/// The priority of a request. When the requests to an address are capped, waiting requests
+/// are sent in order of priority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RequestPriority {
+    /// Block proposals, votes and certificates: the chain cannot make progress without them.
+    Consensus = 0,
+    /// Queries and downloads to synchronize chains.
+    Sync = 1,
+    /// Blob uploads and downloads, which can be large.
+    Blob = 2,
+}
+
+/// The number of request priorities.
+const NUM_PRIORITIES: usize = 3;
+
+/// Caps the number of concurrent requests to one address, and lets waiting requests
+/// through in order of priority.
+#[derive(Debug)]
+pub struct RequestLimiter {
+    limit: Option<usize>,
+    state: Mutex<LimiterState>,
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    /// The number of permits currently held.
+    in_flight: usize,
+    /// The requests waiting for a permit, by priority.
+    waiting: [VecDeque<oneshot::Sender<()>>; NUM_PRIORITIES],
+}
+
+impl RequestLimiter {
+    /// Creates a limiter allowing at most `limit` concurrent requests, or any number if
+    /// `None`.
+    pub fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Waits until a request with the given priority may be sent. The returned permit must
+    /// be held until the request completed.
+    pub async fn acquire(self: &Arc<Self>, priority: RequestPriority) -> RequestPermit {
+        let Some(limit) = self.limit else {
+            return RequestPermit { limiter: None };
+        };
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if state.in_flight < limit {
+                state.in_flight += 1;
+                return RequestPermit {
+                    limiter: Some(self.clone()),
+                };
+            }
+            let (sender, receiver) = oneshot::channel();
+            state.waiting[priority as usize].push_back(sender);
+            receiver
+        };
+        let mut waiter = Waiter {
+            limiter: self.clone(),
+            receiver: Some(receiver),
+        };
+        if let Some(receiver) = waiter.receiver.as_mut() {
+            // The sender is only dropped with the limiter, which the waiter keeps alive.
+            receiver.await.expect("the limiter should be alive");
+        }
+        // The permit was handed over to us.
+        waiter.receiver = None;
+        RequestPermit {
+            limiter: Some(self.clone()),
+        }
+    }
+
+    /// Returns the number of requests waiting for a permit.
+    pub fn num_waiting(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state.waiting.iter().map(VecDeque::len).sum()
+    }
+
+    /// Hands a released permit over to the waiting request with the highest priority, if any.
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        for queue in &mut state.waiting {
+            while let Some(sender) = queue.pop_front() {
+                // Requests that stopped waiting have dropped their receiver.
+                if sender.send(()).is_ok() {
+                    return;
+                }
+            }
+        }
+        state.in_flight -= 1;
+    }
+}
+
+/// A request waiting for a permit. If it stops waiting after a permit was handed over to
+/// it, the permit is released again.
+struct Waiter {
+    limiter: Arc<RequestLimiter>,
+    receiver: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for Waiter {
+    fn drop(&mut self) {
+        if let Some(mut receiver) = self.receiver.take() {
+            receiver.close();
+            if let Ok(Some(())) = receiver.try_recv() {
+                self.limiter.release();
+            }
+        }
+    }
+}
+
+/// Allows one request to be sent, until dropped.
+pub struct RequestPermit {
+    limiter: Option<Arc<RequestLimiter>>,
+}
+
+impl Drop for RequestPermit {
+    fn drop(&mut self) {
+        if let Some(limiter) = &self.limiter {
+            limiter.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::FutureExt as _;
+
+    use super::{RequestLimiter, RequestPriority};
+
+    #[test]
+    fn test_request_limiter_priorities() {
+        let limiter = Arc::new(RequestLimiter::new(Some(1)));
+        let permit = limiter
+            .acquire(RequestPriority::Sync)
+            .now_or_never()
+            .unwrap();
+        let mut blob = Box::pin(limiter.acquire(RequestPriority::Blob));
+        let mut consensus = Box::pin(limiter.acquire(RequestPriority::Consensus));
+        assert!((&mut blob).now_or_never().is_none());
+        assert!((&mut consensus).now_or_never().is_none());
+        assert_eq!(limiter.num_waiting(), 2);
+
+        // The consensus request goes first, even though it arrived last.
+        drop(permit);
+        assert!((&mut blob).now_or_never().is_none());
+        let permit = (&mut consensus).now_or_never().unwrap();
+        drop(permit);
+        assert!((&mut blob).now_or_never().is_some());
+        assert_eq!(limiter.num_waiting(), 0);
+    }
+
+    #[test]
+    fn test_request_limiter_cancelled_waiter() {
+        let limiter = Arc::new(RequestLimiter::new(Some(1)));
+        let permit = limiter
+            .acquire(RequestPriority::Sync)
+            .now_or_never()
+            .unwrap();
+        let mut cancelled = Box::pin(limiter.acquire(RequestPriority::Consensus));
+        assert!((&mut cancelled).now_or_never().is_none());
+        // The permit is handed over to the waiter, which then gives up.
+        drop(permit);
+        drop(cancelled);
+        assert!(limiter
+            .acquire(RequestPriority::Blob)
+            .now_or_never()
+            .is_some());
+    }
 }
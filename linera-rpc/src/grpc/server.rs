@@ -16,13 +16,13 @@ use linera_base::{data_types::Blob, identifiers::ChainId, time::Duration};
 use linera_core::{
     join_set_ext::JoinSet,
     node::NodeError,
-    worker::{NetworkActions, Notification, Reason, WorkerState},
+    worker::{NetworkActions, Notification, Reason, WorkerError, WorkerState},
     JoinSetExt as _, ProcessConfirmedBlockMode, TaskHandle,
 };
 use linera_storage::Storage;
 use tokio::sync::{broadcast::error::RecvError, oneshot};
 use tokio_util::sync::CancellationToken;
-use tonic::{transport::Channel, Request, Response, Status};
+use tonic::{transport::Channel, Code, Request, Response, Status};
 use tower::{builder::ServiceBuilder, Layer, Service};
 use tracing::{debug, error, info, instrument, trace, warn};
 
@@ -35,6 +35,7 @@ use super::{
         BlockProposal, ChainInfoQuery, ChainInfoResult, CrossChainRequest,
         HandlePendingBlobRequest, LiteCertificate, PendingBlobRequest, PendingBlobResult,
     },
+    deadline::GrpcDeadlineLayer,
     pool::GrpcConnectionPool,
     GrpcError, GRPC_MAX_MESSAGE_SIZE,
 };
@@ -498,12 +499,14 @@ where
                 ServiceBuilder::new()
                     .layer(crate::propagation::OtelContextLayer)
                     .layer(GrpcPrometheusMetricsMiddlewareLayer)
+                    .layer(GrpcDeadlineLayer)
                     .into_inner(),
             );
             #[cfg(not(feature = "opentelemetry"))]
             let mut server = tonic::transport::Server::builder().layer(
                 ServiceBuilder::new()
                     .layer(GrpcPrometheusMetricsMiddlewareLayer)
+                    .layer(GrpcDeadlineLayer)
                     .into_inner(),
             );
             server
@@ -666,7 +669,14 @@ where
                         .max_decoding_message_size(GRPC_MAX_MESSAGE_SIZE);
                     client
                         .handle_cross_chain_request(Request::new(request.try_into()?))
-                        .await?;
+                        .await
+                        .map_err(|status| {
+                            if status.code() == Code::ResourceExhausted {
+                                anyhow::Error::new(cross_chain_message_queue::InboxFull)
+                            } else {
+                                status.into()
+                            }
+                        })?;
                     anyhow::Result::<_, anyhow::Error>::Ok(())
                 }
             };
@@ -712,7 +722,7 @@ where
         "unknown"
     }
 
-    fn log_error(&self, error: &linera_core::worker::WorkerError, context: &str) {
+    fn log_error(&self, error: &WorkerError, context: &str) {
         let nickname = self.state.nickname();
         if error.is_local() {
             error!(nickname, %error, "{}", context);
@@ -1067,6 +1077,10 @@ where
                     &error.error_type(),
                 );
                 self.log_error(&error, "Failed to handle cross-chain request");
+                // Tell the sending validator to retry later.
+                if let WorkerError::InboxFull { .. } = error {
+                    return Err(Status::resource_exhausted(error.to_string()));
+                }
             }
         }
         Ok(Response::new(()))
@@ -1135,7 +1149,8 @@ impl GrpcProxyable for CrossChainRequest {
         match self.inner.as_ref()? {
             Inner::UpdateRecipient(api::UpdateRecipient { recipient, .. })
             | Inner::ConfirmUpdatedRecipient(api::ConfirmUpdatedRecipient { recipient, .. })
-            | Inner::RevertConfirm(api::RevertConfirm { recipient, .. }) => {
+            | Inner::RevertConfirm(api::RevertConfirm { recipient, .. })
+            | Inner::NotifyRecipient(api::NotifyRecipient { recipient, .. }) => {
                 recipient.clone()?.try_into().ok()
             }
         }
@@ -14,10 +14,13 @@ pub mod config;
 /// Construction of validator-node clients from network configuration.
 pub mod node_provider;
 
+/// Load balancing across the proxies of a validator that has several addresses.
+pub mod balanced;
 /// A network-agnostic client for talking to a validator node.
 pub mod client;
 
 mod cross_chain_message_queue;
+mod decoding;
 mod message;
 /// The simple custom-TCP/UDP network transport.
 #[cfg(with_simple_network)]
@@ -30,6 +33,7 @@ pub mod grpc;
 #[cfg(feature = "opentelemetry")]
 pub mod propagation;
 
+pub use balanced::EndpointSelection;
 pub use client::Client;
 pub use message::{RpcMessage, ShardInfo};
 pub use node_provider::{NodeOptions, NodeProvider, DEFAULT_MAX_BACKOFF};
@@ -1,12 +1,18 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
+use std::sync::Arc;
+
 use linera_base::time::Duration;
 use linera_core::node::{NodeError, ValidatorNodeProvider};
 
 #[cfg(with_simple_network)]
 use crate::simple::SimpleNodeProvider;
-use crate::{client::Client, grpc::GrpcNodeProvider};
+use crate::{
+    balanced::{BalancedClient, EndpointHealth, EndpointSelection, ADDRESS_SEPARATOR},
+    client::Client,
+    grpc::GrpcNodeProvider,
+};
 
 /// A general node provider which delegates node provision to the underlying
 /// node provider according to the `ValidatorPublicNetworkConfig`.
@@ -15,6 +21,10 @@ pub struct NodeProvider {
     grpc: GrpcNodeProvider,
     #[cfg(with_simple_network)]
     simple: SimpleNodeProvider,
+    endpoint_selection: EndpointSelection,
+    /// The health of the endpoints of validators with several addresses, shared by all
+    /// the clients created by this provider.
+    endpoint_health: Arc<papaya::HashMap<String, Arc<EndpointHealth>>>,
 }
 
 impl NodeProvider {
@@ -24,8 +34,37 @@ impl NodeProvider {
             grpc: GrpcNodeProvider::new(options),
             #[cfg(with_simple_network)]
             simple: SimpleNodeProvider::new(options),
+            endpoint_selection: options.endpoint_selection,
+            endpoint_health: Arc::default(),
         }
     }
+
+    /// Creates a client for a validator with several proxies. Only gRPC addresses are
+    /// supported.
+    fn make_balanced_node(&self, address: &str) -> Result<BalancedClient, NodeError> {
+        let endpoint_health = self.endpoint_health.pin();
+        let endpoints = address
+            .split(ADDRESS_SEPARATOR)
+            .map(|endpoint| {
+                let endpoint = endpoint.trim();
+                if !endpoint.starts_with("grpc") {
+                    return Err(NodeError::CannotResolveValidatorAddress {
+                        address: address.to_string(),
+                    });
+                }
+                let client = self.grpc.make_node(endpoint)?;
+                let health = endpoint_health
+                    .get_or_insert_with(client.address().to_string(), Arc::default)
+                    .clone();
+                Ok((client, health))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(BalancedClient::new(
+            address.to_string(),
+            endpoints,
+            self.endpoint_selection,
+        ))
+    }
 }
 
 impl ValidatorNodeProvider for NodeProvider {
@@ -34,6 +73,12 @@ impl ValidatorNodeProvider for NodeProvider {
     fn make_node(&self, address: &str) -> anyhow::Result<Self::Node, NodeError> {
         let address = address.to_lowercase();
 
+        if address.contains(ADDRESS_SEPARATOR) {
+            return Ok(Client::Balanced(Box::new(
+                self.make_balanced_node(&address)?,
+            )));
+        }
+
         #[cfg(with_simple_network)]
         if address.starts_with("tcp") || address.starts_with("udp") {
             return Ok(Client::Simple(self.simple.make_node(&address)?));
@@ -67,6 +112,11 @@ pub struct NodeOptions {
     pub max_retries: u32,
     /// The maximum backoff delay between retries.
     pub max_backoff: Duration,
+    /// The maximum number of concurrent requests to each validator, or `None` for no limit.
+    /// Notification subscriptions are not counted.
+    pub max_concurrent_requests: Option<usize>,
+    /// How to choose among the addresses of a validator that has several of them.
+    pub endpoint_selection: EndpointSelection,
 }
 
 impl Default for NodeOptions {
@@ -77,6 +127,8 @@ impl Default for NodeOptions {
             retry_delay: Duration::ZERO,
             max_retries: 0,
             max_backoff: DEFAULT_MAX_BACKOFF,
+            max_concurrent_requests: None,
+            endpoint_selection: EndpointSelection::default(),
         }
     }
 }
@@ -80,8 +80,8 @@ impl Decoder for Codec {
         let _prefix = buffer.split_to(PREFIX_SIZE.into());
         let payload = buffer.split_to(payload_size);
 
-        let message =
-            bincode::deserialize(&payload).map_err(|error| Error::Deserialization(*error))?;
+        let message = crate::decoding::deserialize(&payload)
+            .map_err(|error| Error::Deserialization(*error))?;
 
         Ok(Some(message))
     }
@@ -344,6 +344,8 @@ where
                         self.handle_network_actions(actions);
                     }
                     Err(error) => {
+                        // An `InboxFull` refusal cannot be reported back to the sender here:
+                        // the recipient re-requests the bundles once its inbox has room.
                         self.log_error(&error, "Failed to handle cross-chain request");
                     }
                 }
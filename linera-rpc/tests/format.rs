@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use linera_base::{
-    crypto::{AccountPublicKey, AccountSignature, CryptoHash, TestString},
+    crypto::{AccountPublicKey, AccountSignature, CryptoHash, SignatureScheme, TestString},
     data_types::{BlobContent, ChainDescription, ChainOrigin, OracleResponse, Round},
     identifiers::{Account, AccountOwner, BlobType, GenericApplicationId},
     ownership::ChainOwnership,
@@ -17,7 +17,7 @@ use linera_chain::{
 use linera_core::{data_types::CrossChainRequest, node::NodeError, worker::Reason};
 use linera_execution::{
     system::{AdminOperation, SystemMessage, SystemOperation},
-    Message, MessageKind, Operation,
+    Message, MessageKind, Operation, ResourceControlPolicy,
 };
 use linera_rpc::RpcMessage;
 use serde_reflection::{Registry, Result, Samples, Tracer, TracerConfig};
@@ -55,6 +55,7 @@ fn get_registry() -> Result<Registry> {
     // 2. Trace the main entry point(s) + every enum separately.
     tracer.trace_type::<AccountPublicKey>(&samples)?;
     tracer.trace_type::<AccountSignature>(&samples)?;
+    tracer.trace_type::<SignatureScheme>(&samples)?;
     tracer.trace_type::<Round>(&samples)?;
     tracer.trace_type::<OracleResponse>(&samples)?;
     tracer.trace_type::<Account>(&samples)?;
@@ -86,6 +87,7 @@ fn get_registry() -> Result<Registry> {
     tracer.trace_type::<BlobType>(&samples)?;
     tracer.trace_type::<BlobContent>(&samples)?;
     tracer.trace_type::<AccountOwner>(&samples)?;
+    tracer.trace_type::<ResourceControlPolicy>(&samples)?;
     tracer.registry()
 }
 
@@ -31,6 +31,7 @@ async fn client() {
         max_retries,
         linera_rpc::node_provider::DEFAULT_MAX_BACKOFF,
         std::sync::Arc::new(papaya::HashMap::new()),
+        std::sync::Arc::new(linera_rpc::grpc::pool::RequestLimiter::new(None)),
     )
     .get_version_info()
     .await
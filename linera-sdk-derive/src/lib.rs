@@ -5,6 +5,7 @@
 
 #![deny(missing_docs)]
 
+mod linera_app;
 mod stable_enum;
 mod utils;
 
@@ -12,7 +13,7 @@ use proc_macro::TokenStream;
 use proc_macro2::{Ident, Span};
 use syn::{
     __private::{quote::quote, TokenStream2},
-    parse_macro_input, Fields, ItemEnum,
+    parse_macro_input, Fields, ItemEnum, ItemStruct,
 };
 
 use crate::utils::{concat, snakify};
@@ -62,6 +63,36 @@ pub fn derive_stable_enum_in_crate(input: TokenStream) -> TokenStream {
         .into()
 }
 
+/// Declares the struct holding an application's parameters or instantiation argument.
+/// Used as `#[linera_app(parameters)]` or `#[linera_app(instantiation_argument)]`, it
+/// expands to:
+///
+/// * `serde::Serialize` / `serde::Deserialize` impls;
+/// * an `async_graphql` scalar, so that the struct can be used in GraphQL schemas; and
+/// * a `linera_sdk::arguments::AppArgument` impl, whose `validate` method checks the
+///   constraints declared on the fields and whose `descriptor` describes the fields.
+///
+/// Fields can be constrained with `#[linera_app(...)]` attributes: `range = 1..=18`,
+/// `non_empty`, `max_len = 8`, or `validate = path::to::function`, where the function
+/// takes a reference to the field and returns a `Result` with a displayable error.
+#[proc_macro_attribute]
+pub fn linera_app(args: TokenStream, input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as ItemStruct);
+    linera_app::generate(args.into(), input, linera_app::CrateRoot::LineraSdk)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
+/// Same as [`linera_app`] but referring to the SDK through `crate::...` instead of
+/// `::linera_sdk::...`. Used inside the `linera-sdk` crate itself (and only there).
+#[proc_macro_attribute]
+pub fn linera_app_in_crate(args: TokenStream, input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as ItemStruct);
+    linera_app::generate(args.into(), input, linera_app::CrateRoot::Crate)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
+
 fn generate_mutation_root_code(input: ItemEnum, crate_root: &str) -> TokenStream2 {
     let crate_root = Ident::new(crate_root, Span::call_site());
     let enum_name = input.ident;
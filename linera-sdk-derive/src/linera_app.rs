@@ -0,0 +1,353 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Code generation for the `#[linera_app]` attribute macro.
+//!
+//! The macro is placed on the struct holding an application's parameters or
+//! instantiation argument. It derives the serde implementations, a GraphQL scalar, and
+//! an `AppArgument` implementation validating the field constraints declared with
+//! `#[linera_app(...)]` field attributes and describing the fields.
+
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{
+    __private::quote::{quote, ToTokens as _},
+    meta::ParseNestedMeta,
+    spanned::Spanned,
+    Error, Expr, ExprRange, Fields, ItemStruct, LitInt, Meta, Path, Result,
+};
+
+/// Where the `linera_sdk::arguments` module lives, from the perspective of the code
+/// being generated.
+pub enum CrateRoot {
+    /// Generated code lives outside `linera-sdk`: use `::linera_sdk::...`.
+    LineraSdk,
+    /// Generated code lives inside `linera-sdk`: use `crate::...`.
+    Crate,
+}
+
+impl CrateRoot {
+    fn module_path(&self) -> TokenStream2 {
+        match self {
+            CrateRoot::LineraSdk => quote! { ::linera_sdk::arguments },
+            CrateRoot::Crate => quote! { crate::arguments },
+        }
+    }
+
+    /// The same path as a string, for `#[serde(crate = "...")]`.
+    fn serde_path_str(&self) -> &'static str {
+        match self {
+            CrateRoot::LineraSdk => "::linera_sdk::arguments::__private::serde",
+            CrateRoot::Crate => "crate::arguments::__private::serde",
+        }
+    }
+}
+
+/// Whether the struct holds the application parameters or its instantiation argument.
+enum Kind {
+    Parameters,
+    InstantiationArgument,
+}
+
+/// Parses the arguments of the attribute on the struct itself.
+fn parse_kind(args: TokenStream2) -> Result<Kind> {
+    let mut kind = None;
+    let parser = syn::meta::parser(|meta| {
+        let new_kind = if meta.path.is_ident("parameters") {
+            Kind::Parameters
+        } else if meta.path.is_ident("instantiation_argument") {
+            Kind::InstantiationArgument
+        } else {
+            return Err(meta.error("expected `parameters` or `instantiation_argument`"));
+        };
+        if kind.replace(new_kind).is_some() {
+            return Err(meta.error("the kind of argument is specified more than once"));
+        }
+        Ok(())
+    });
+    syn::parse::Parser::parse2(parser, args.clone())?;
+    kind.ok_or_else(|| {
+        Error::new(
+            args.span(),
+            "expected `#[linera_app(parameters)]` or `#[linera_app(instantiation_argument)]`",
+        )
+    })
+}
+
+/// A constraint on the value of a field.
+enum Constraint {
+    /// The value must be within the range.
+    Range(ExprRange),
+    /// The value must not be empty.
+    NonEmpty,
+    /// The length of the value must be at most the given one.
+    MaxLen(LitInt),
+    /// The given function must accept the value.
+    Validate(Path),
+}
+
+impl Constraint {
+    fn parse(meta: &ParseNestedMeta) -> Result<Self> {
+        if meta.path.is_ident("range") {
+            match meta.value()?.parse::<Expr>()? {
+                Expr::Range(range) if range.start.is_some() || range.end.is_some() => {
+                    Ok(Constraint::Range(range))
+                }
+                expr => Err(Error::new(expr.span(), "expected a range, e.g. `1..=18`")),
+            }
+        } else if meta.path.is_ident("non_empty") {
+            Ok(Constraint::NonEmpty)
+        } else if meta.path.is_ident("max_len") {
+            Ok(Constraint::MaxLen(meta.value()?.parse()?))
+        } else if meta.path.is_ident("validate") {
+            Ok(Constraint::Validate(meta.value()?.parse()?))
+        } else {
+            Err(meta.error("expected `range`, `non_empty`, `max_len` or `validate`"))
+        }
+    }
+
+    /// Returns a human-readable description of the constraint.
+    fn describe(&self) -> String {
+        match self {
+            Constraint::Range(range) => {
+                let range = range.to_token_stream().to_string().replace(' ', "");
+                format!("range {range}")
+            }
+            Constraint::NonEmpty => "non-empty".to_string(),
+            Constraint::MaxLen(max_len) => format!("at most {max_len} long"),
+            Constraint::Validate(path) => {
+                let path = path.to_token_stream().to_string().replace(' ', "");
+                format!("validated by {path}")
+            }
+        }
+    }
+
+    /// Returns the code checking the constraint on `__value`, a reference to the field.
+    fn check(&self, field_name: &str, module: &TokenStream2) -> TokenStream2 {
+        let error = quote! { #module::ArgumentError::InvalidField };
+        match self {
+            Constraint::Range(range) => {
+                let description = self.describe();
+                quote! {
+                    if !(#range).contains(__value) {
+                        return ::core::result::Result::Err(#error {
+                            field: #field_name,
+                            reason: ::std::format!("{:?} is not in the {}", __value, #description),
+                        });
+                    }
+                }
+            }
+            Constraint::NonEmpty => quote! {
+                if __value.is_empty() {
+                    return ::core::result::Result::Err(#error {
+                        field: #field_name,
+                        reason: ::std::string::String::from("must not be empty"),
+                    });
+                }
+            },
+            Constraint::MaxLen(max_len) => quote! {
+                if __value.len() > #max_len {
+                    return ::core::result::Result::Err(#error {
+                        field: #field_name,
+                        reason: ::std::format!("length {} exceeds {}", __value.len(), #max_len),
+                    });
+                }
+            },
+            Constraint::Validate(path) => quote! {
+                if let ::core::result::Result::Err(__reason) = #path(__value) {
+                    return ::core::result::Result::Err(#error {
+                        field: #field_name,
+                        reason: ::std::string::ToString::to_string(&__reason),
+                    });
+                }
+            },
+        }
+    }
+}
+
+/// Returns the documentation of an item, with the lines joined by spaces.
+fn documentation(attrs: &[syn::Attribute]) -> String {
+    attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(name_value) if name_value.path.is_ident("doc") => {
+                match &name_value.value {
+                    Expr::Lit(syn::ExprLit {
+                        lit: syn::Lit::Str(doc),
+                        ..
+                    }) => Some(doc.value().trim().to_string()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Emits the struct, without the field attributes of the macro, together with the derived
+/// implementations.
+pub fn generate(
+    args: TokenStream2,
+    mut input: ItemStruct,
+    crate_root: CrateRoot,
+) -> Result<TokenStream2> {
+    let kind = parse_kind(args)?;
+    if !input.generics.params.is_empty() || input.generics.where_clause.is_some() {
+        return Err(Error::new(
+            input.generics.span(),
+            "#[linera_app] does not support generic structs",
+        ));
+    }
+    let Fields::Named(fields) = &mut input.fields else {
+        return Err(Error::new(
+            input.fields.span(),
+            "#[linera_app] requires a struct with named fields",
+        ));
+    };
+
+    let module = crate_root.module_path();
+    let mut checks = Vec::new();
+    let mut descriptors = Vec::new();
+    for field in &mut fields.named {
+        let mut constraints = Vec::new();
+        let mut error = None;
+        field.attrs.retain(|attr| {
+            if !attr.path().is_ident("linera_app") {
+                return true;
+            }
+            if let Err(new_error) = attr.parse_nested_meta(|meta| {
+                constraints.push(Constraint::parse(&meta)?);
+                Ok(())
+            }) {
+                error.get_or_insert(new_error);
+            }
+            false
+        });
+        if let Some(error) = error {
+            return Err(error);
+        }
+
+        let ident = field
+            .ident
+            .as_ref()
+            .expect("named fields always have names");
+        let name = ident.to_string();
+        let type_name = field.ty.to_token_stream().to_string().replace(' ', "");
+        let description = documentation(&field.attrs);
+        let field_checks = constraints
+            .iter()
+            .map(|constraint| constraint.check(&name, &module));
+        if !constraints.is_empty() {
+            checks.push(quote! {
+                {
+                    let __value = &self.#ident;
+                    #(#field_checks)*
+                }
+            });
+        }
+        let constraint_descriptions = constraints.iter().map(Constraint::describe);
+        descriptors.push(quote! {
+            #module::FieldDescriptor {
+                name: #name,
+                type_name: #type_name,
+                description: #description,
+                constraints: ::std::vec![#(#constraint_descriptions),*],
+            }
+        });
+    }
+
+    let ident = &input.ident;
+    let name = ident.to_string();
+    let description = documentation(&input.attrs);
+    let kind = match kind {
+        Kind::Parameters => quote! { #module::ArgumentKind::Parameters },
+        Kind::InstantiationArgument => quote! { #module::ArgumentKind::InstantiationArgument },
+    };
+    let serde_path = crate_root.serde_path_str();
+
+    Ok(quote! {
+        #[derive(
+            #module::__private::serde::Serialize,
+            #module::__private::serde::Deserialize,
+        )]
+        #[serde(crate = #serde_path)]
+        #input
+
+        #module::__private::async_graphql::scalar!(#ident);
+
+        #[automatically_derived]
+        impl #module::AppArgument for #ident {
+            const KIND: #module::ArgumentKind = #kind;
+
+            fn validate(&self) -> ::core::result::Result<(), #module::ArgumentError> {
+                #(#checks)*
+                ::core::result::Result::Ok(())
+            }
+
+            fn descriptor() -> #module::ArgumentDescriptor {
+                #module::ArgumentDescriptor {
+                    name: #name,
+                    kind: #kind,
+                    description: #description,
+                    fields: ::std::vec![#(#descriptors),*],
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use syn::{__private::quote::quote, parse_quote};
+
+    use super::{generate, CrateRoot};
+
+    #[test]
+    fn test_linera_app_rejects_unknown_constraints() {
+        let input = parse_quote! {
+            struct Parameters {
+                #[linera_app(positive)]
+                amount: u64,
+            }
+        };
+        let error = generate(quote! { parameters }, input, CrateRoot::LineraSdk).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected `range`, `non_empty`, `max_len` or `validate`"
+        );
+
+        let input = parse_quote! {
+            struct Parameters {
+                amount: u64,
+            }
+        };
+        let error = generate(quote! { state }, input, CrateRoot::LineraSdk).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "expected `parameters` or `instantiation_argument`"
+        );
+    }
+
+    #[test]
+    fn test_linera_app_strips_field_attributes() {
+        let input = parse_quote! {
+            /// The parameters.
+            struct Parameters {
+                /// The ticker symbol.
+                #[linera_app(non_empty, max_len = 8)]
+                ticker: String,
+                #[linera_app(range = 0..=18)]
+                decimals: u8,
+            }
+        };
+        let output = generate(quote! { parameters }, input, CrateRoot::LineraSdk)
+            .unwrap()
+            .to_string();
+        assert!(!output.contains("linera_app"));
+        assert!(output.contains("\"at most 8 long\""));
+        assert!(output.contains("\"range 0..=18\""));
+        assert!(output.contains("\"The ticker symbol.\""));
+        assert!(output.contains("ArgumentKind :: Parameters"));
+    }
+}
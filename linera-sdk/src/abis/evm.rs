@@ -2,10 +2,20 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! An ABI for applications that implement an EVM runtime.
+//!
+//! With the `evm` feature, this module also translates between Solidity ABI-encoded calls
+//! and the operations and queries of EVM applications, so that Wasm applications can call
+//! EVM applications with typed calls declared with `alloy_sol_types::sol!`, and decode the
+//! calls they receive from EVM applications.
+
+#[cfg(feature = "evm")]
+pub use alloy_sol_types::{self, SolCall};
 use linera_base::{
     abi::{ContractAbi, ServiceAbi},
     vm::EvmQuery,
 };
+#[cfg(feature = "evm")]
+use linera_base::{data_types::Amount, vm::EvmOperation};
 
 /// An ABI for applications that implement an EVM runtime.
 #[derive(PartialEq)]
@@ -35,3 +45,90 @@ impl ServiceAbi for EvmAbi {
     type Query = EvmQuery;
     type QueryResponse = Vec<u8>;
 }
+
+/// Returns the operation of an EVM application that calls the Solidity function of `call`,
+/// transferring `value` to it.
+#[cfg(feature = "evm")]
+pub fn encode_operation<C: SolCall>(call: &C, value: Amount) -> Vec<u8> {
+    EvmOperation::new(value, call.abi_encode())
+        .to_bytes()
+        .expect("Failed to serialize EVM operation")
+}
+
+/// Returns the query of an EVM application that calls the Solidity function of `call`
+/// without committing its changes.
+#[cfg(feature = "evm")]
+pub fn encode_query<C: SolCall>(call: &C) -> EvmQuery {
+    EvmQuery::Query(call.abi_encode())
+}
+
+/// Decodes the values returned by the Solidity function of `C`.
+#[cfg(feature = "evm")]
+pub fn decode_response<C: SolCall>(response: &[u8]) -> Result<C::Return, alloy_sol_types::Error> {
+    C::abi_decode_returns(response)
+}
+
+/// Decodes a Solidity ABI-encoded call that an EVM application made to this application,
+/// e.g. in the `deserialize_operation` of an ABI callable from EVM applications.
+#[cfg(feature = "evm")]
+pub fn decode_call<C: SolCall>(operation: &[u8]) -> Result<C, alloy_sol_types::Error> {
+    C::abi_decode(operation)
+}
+
+/// Encodes the values returned to an EVM application by the Solidity function of `C`,
+/// e.g. in the `serialize_response` of an ABI callable from EVM applications.
+#[cfg(feature = "evm")]
+pub fn encode_response<C: SolCall>(response: &C::Return) -> Vec<u8> {
+    C::abi_encode_returns(response)
+}
+
+#[cfg(all(test, feature = "evm"))]
+mod tests {
+    use alloy_sol_types::{sol, SolCall};
+    use linera_base::{
+        data_types::Amount,
+        vm::{EvmOperation, EvmQuery},
+    };
+
+    use super::{decode_call, decode_response, encode_operation, encode_query, encode_response};
+
+    sol! {
+        function transfer(address to, uint64 amount) returns (bool);
+        function balance() returns (uint64);
+    }
+
+    #[test]
+    fn wasm_to_evm_calls() {
+        let call = transferCall {
+            to: [7; 20].into(),
+            amount: 42,
+        };
+        let operation = encode_operation(&call, Amount::from_tokens(2));
+        let expected = EvmOperation::new(Amount::from_tokens(2), call.abi_encode());
+        assert_eq!(operation, expected.to_bytes().unwrap());
+
+        let EvmQuery::Query(query) = encode_query(&balanceCall {}) else {
+            panic!("not a query");
+        };
+        assert_eq!(query, balanceCall::SELECTOR);
+
+        let response = transferCall::abi_encode_returns(&true);
+        assert!(decode_response::<transferCall>(&response).unwrap());
+        assert!(decode_response::<balanceCall>(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn evm_to_wasm_calls() {
+        let call = transferCall {
+            to: [7; 20].into(),
+            amount: 42,
+        };
+        let decoded = decode_call::<transferCall>(&call.abi_encode()).unwrap();
+        assert_eq!(decoded.to, call.to);
+        assert_eq!(decoded.amount, 42);
+        assert!(decode_call::<balanceCall>(&call.abi_encode()).is_err());
+
+        let response = encode_response::<balanceCall>(&1000);
+        assert_eq!(balanceCall::abi_decode_returns(&response).unwrap(), 1000);
+    }
+}
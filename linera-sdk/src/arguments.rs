@@ -0,0 +1,209 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Declaring and validating the parameters and instantiation arguments of applications.
+//!
+//! The [`linera_app`] attribute derives everything an application needs from the
+//! struct definition:
+//!
+//! ```ignore
+//! use linera_sdk::arguments::linera_app;
+//!
+//! /// The parameters of the token.
+//! #[linera_app(parameters)]
+//! #[derive(Clone, Debug)]
+//! pub struct Parameters {
+//!     /// The ticker symbol.
+//!     #[linera_app(non_empty, max_len = 8)]
+//!     pub ticker_symbol: String,
+//!     /// The number of decimals.
+//!     #[linera_app(range = 0..=18)]
+//!     pub decimals: u8,
+//! }
+//! ```
+//!
+//! Contracts can then check the values they receive with [`AppArgument::assert_valid`],
+//! and clients parse and validate JSON with [`AppArgument::from_json`].
+
+/// Re-exports the `#[linera_app]` attribute macro.
+pub use linera_sdk_derive::linera_app;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Private re-exports of crates referenced by the `#[linera_app]` macro. Lets downstream
+/// crates use the macro without taking direct dependencies on the crates being
+/// re-exported. Not part of the public API; do not use directly.
+#[doc(hidden)]
+pub mod __private {
+    pub use async_graphql;
+    pub use serde;
+}
+
+/// What an [`AppArgument`] is used for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ArgumentKind {
+    /// The parameters of the application, available on every chain.
+    Parameters,
+    /// The argument passed to the application when it is instantiated.
+    InstantiationArgument,
+}
+
+/// An error when parsing or validating an [`AppArgument`].
+#[derive(Debug, thiserror::Error)]
+pub enum ArgumentError {
+    /// The value is not valid JSON for the type.
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A field violates one of its constraints.
+    #[error("invalid field `{field}`: {reason}")]
+    InvalidField {
+        /// The name of the field.
+        field: &'static str,
+        /// The violated constraint.
+        reason: String,
+    },
+}
+
+/// The description of an [`AppArgument`] type, e.g. for tools generating forms or
+/// checking arguments before creating an application.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArgumentDescriptor {
+    /// The name of the type.
+    pub name: &'static str,
+    /// What the type is used for.
+    pub kind: ArgumentKind,
+    /// The documentation of the type.
+    pub description: &'static str,
+    /// The fields of the type.
+    pub fields: Vec<FieldDescriptor>,
+}
+
+/// The description of a field of an [`AppArgument`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldDescriptor {
+    /// The name of the field.
+    pub name: &'static str,
+    /// The Rust type of the field.
+    pub type_name: &'static str,
+    /// The documentation of the field.
+    pub description: &'static str,
+    /// The constraints on the value of the field.
+    pub constraints: Vec<&'static str>,
+}
+
+/// The parameters or instantiation argument of an application. Implemented with the
+/// [`linera_app`] attribute.
+pub trait AppArgument: Serialize + DeserializeOwned {
+    /// What the type is used for.
+    const KIND: ArgumentKind;
+
+    /// Checks the constraints declared on the fields.
+    fn validate(&self) -> Result<(), ArgumentError>;
+
+    /// Describes the type and its fields.
+    fn descriptor() -> ArgumentDescriptor;
+
+    /// Parses and validates a JSON value, e.g. given on the command line.
+    fn from_json(json: &str) -> Result<Self, ArgumentError> {
+        let value: Self = serde_json::from_str(json)?;
+        value.validate()?;
+        Ok(value)
+    }
+
+    /// Panics if the value is invalid. Meant to be called by contracts on the arguments
+    /// they receive, so that invalid ones make the transaction fail.
+    fn assert_valid(&self) {
+        if let Err(error) = self.validate() {
+            panic!("Invalid application {:?}: {error}", Self::KIND);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_sdk_derive::linera_app_in_crate;
+
+    use super::*;
+
+    fn check_even(value: &u32) -> Result<(), &'static str> {
+        if value.is_multiple_of(2) {
+            Ok(())
+        } else {
+            Err("must be even")
+        }
+    }
+
+    /// Test parameters.
+    #[linera_app_in_crate(parameters)]
+    #[derive(Debug, PartialEq)]
+    struct Parameters {
+        /// The ticker symbol.
+        #[linera_app(non_empty, max_len = 4)]
+        ticker: String,
+        #[linera_app(range = 0..=18)]
+        decimals: u8,
+        #[linera_app(validate = check_even)]
+        pairs: u32,
+        owners: Vec<String>,
+    }
+
+    #[test]
+    fn test_validation() {
+        let parameters =
+            Parameters::from_json(r#"{"ticker": "FUN", "decimals": 6, "pairs": 2, "owners": []}"#)
+                .unwrap();
+        assert_eq!(parameters.ticker, "FUN");
+
+        let invalid = [
+            (
+                r#"{"ticker": "", "decimals": 6, "pairs": 2, "owners": []}"#,
+                "invalid field `ticker`: must not be empty",
+            ),
+            (
+                r#"{"ticker": "TOOLONG", "decimals": 6, "pairs": 2, "owners": []}"#,
+                "invalid field `ticker`: length 7 exceeds 4",
+            ),
+            (
+                r#"{"ticker": "FUN", "decimals": 19, "pairs": 2, "owners": []}"#,
+                "invalid field `decimals`: 19 is not in the range 0..=18",
+            ),
+            (
+                r#"{"ticker": "FUN", "decimals": 6, "pairs": 3, "owners": []}"#,
+                "invalid field `pairs`: must be even",
+            ),
+        ];
+        for (json, message) in invalid {
+            let error = Parameters::from_json(json).unwrap_err();
+            assert_eq!(error.to_string(), message);
+        }
+        assert!(matches!(
+            Parameters::from_json("{}"),
+            Err(ArgumentError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_descriptor() {
+        let descriptor = Parameters::descriptor();
+        assert_eq!(descriptor.name, "Parameters");
+        assert_eq!(descriptor.kind, ArgumentKind::Parameters);
+        assert_eq!(descriptor.description, "Test parameters.");
+        assert_eq!(
+            descriptor.fields[0],
+            FieldDescriptor {
+                name: "ticker",
+                type_name: "String",
+                description: "The ticker symbol.",
+                constraints: vec!["non-empty", "at most 4 long"],
+            }
+        );
+        assert_eq!(
+            descriptor.fields[2].constraints,
+            ["validated by check_even"]
+        );
+        assert_eq!(descriptor.fields[3].type_name, "Vec<String>");
+        assert!(descriptor.fields[3].constraints.is_empty());
+    }
+}
@@ -5,7 +5,7 @@
 
 use linera_base::{
     crypto::CryptoHash,
-    data_types::{Amount, StreamUpdate, Timestamp},
+    data_types::{Amount, CommitteeSummary, ResourcePrices, StreamUpdate, Timestamp},
     identifiers::{
         AccountOwner, ApplicationId, ChainId, DataBlobHash, GenericApplicationId, ModuleId,
         StreamId, StreamName,
@@ -106,6 +106,39 @@ impl From<wit_contract_api::Amount> for Amount {
     }
 }
 
+impl From<wit_contract_api::CommitteeSummary> for CommitteeSummary {
+    fn from(summary: wit_contract_api::CommitteeSummary) -> Self {
+        CommitteeSummary {
+            validator_count: summary.validator_count,
+            total_votes: summary.total_votes,
+        }
+    }
+}
+
+impl From<wit_contract_api::ResourcePrices> for ResourcePrices {
+    fn from(prices: wit_contract_api::ResourcePrices) -> Self {
+        ResourcePrices {
+            wasm_fuel_unit: prices.wasm_fuel_unit.into(),
+            evm_fuel_unit: prices.evm_fuel_unit.into(),
+            read_operation: prices.read_operation.into(),
+            write_operation: prices.write_operation.into(),
+            byte_runtime: prices.byte_runtime.into(),
+            byte_read: prices.byte_read.into(),
+            byte_written: prices.byte_written.into(),
+            blob_read: prices.blob_read.into(),
+            blob_published: prices.blob_published.into(),
+            blob_byte_read: prices.blob_byte_read.into(),
+            blob_byte_published: prices.blob_byte_published.into(),
+            operation: prices.operation.into(),
+            operation_byte: prices.operation_byte.into(),
+            message: prices.message.into(),
+            message_byte: prices.message_byte.into(),
+            service_as_oracle_query: prices.service_as_oracle_query.into(),
+            http_request: prices.http_request.into(),
+        }
+    }
+}
+
 impl From<wit_contract_api::ManageChainError> for ManageChainError {
     fn from(guest: wit_contract_api::ManageChainError) -> Self {
         match guest {
@@ -6,8 +6,8 @@
 use linera_base::{
     abi::{ContractAbi, ServiceAbi},
     data_types::{
-        Amount, ApplicationDescription, ApplicationPermissions, BlockHeight, Bytecode, Resources,
-        SendMessageRequest, Timestamp,
+        Amount, ApplicationDescription, ApplicationPermissions, BlockHeight, Bytecode,
+        CommitteeSummary, ResourcePrices, Resources, SendMessageRequest, Timestamp,
     },
     ensure, http,
     identifiers::{
@@ -19,6 +19,8 @@ use linera_base::{
 use serde::Serialize;
 
 use super::wit::{base_runtime_api as base_wit, contract_runtime_api as contract_wit};
+#[cfg(feature = "evm")]
+use crate::abis::evm;
 use crate::{Contract, KeyValueStore, ViewStorageContext};
 
 /// The common runtime to interface with the host executing the contract.
@@ -307,6 +309,22 @@ where
             .expect("Failed to deserialize `Response` in cross-application call")
     }
 
+    /// Calls the Solidity function of `call` on an EVM application, transferring `value` to
+    /// it, and returns the values the function returned.
+    #[cfg(feature = "evm")]
+    pub fn call_evm_application<C: evm::SolCall>(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId<evm::EvmAbi>,
+        call: &C,
+        value: Amount,
+    ) -> C::Return {
+        let operation = evm::encode_operation(call, value);
+        let response = self.call_application(authenticated, application, &operation);
+        evm::decode_response::<C>(&response)
+            .expect("Failed to decode the response of an EVM application")
+    }
+
     /// Adds a new item to an event stream. Returns the new event's index in the stream.
     pub fn emit(&mut self, name: StreamName, value: &Application::EventValue) -> u32 {
         contract_wit::emit(
@@ -315,6 +333,23 @@ where
         )
     }
 
+    /// Adds a new item to an event stream, and indexes it under the given `(field, key)`
+    /// entries, e.g. `("to", bcs::to_bytes(&recipient)?)`. Nodes maintain these indexes so that
+    /// their node service can list the events of the stream with a given field value.
+    /// Returns the new event's index in the stream.
+    pub fn emit_indexed(
+        &mut self,
+        name: StreamName,
+        value: &Application::EventValue,
+        index_entries: Vec<(String, Vec<u8>)>,
+    ) -> u32 {
+        contract_wit::emit_indexed(
+            &name.into(),
+            &bcs::to_bytes(value).expect("Failed to serialize event"),
+            &index_entries,
+        )
+    }
+
     /// Reads an event from a stream. Returns the event's value.
     ///
     /// Fails the block if the event doesn't exist.
@@ -461,6 +496,27 @@ where
     pub fn validation_round(&mut self) -> Option<u32> {
         contract_wit::validation_round()
     }
+
+    /// Returns the number of validators and the total votes, i.e. stake, of the committee
+    /// currently in charge of this chain.
+    pub fn committee_summary(&mut self) -> CommitteeSummary {
+        contract_wit::read_committee_summary().into()
+    }
+
+    /// Returns the prices of the resources consumed by this block, e.g. to derive dynamic
+    /// fees from them.
+    pub fn resource_prices(&mut self) -> ResourcePrices {
+        contract_wit::read_resource_prices().into()
+    }
+
+    /// Returns the timestamps of the latest blocks of the current chain, oldest first,
+    /// including the current block.
+    pub fn recent_block_timestamps(&mut self) -> Vec<Timestamp> {
+        contract_wit::read_recent_block_timestamps()
+            .into_iter()
+            .map(Timestamp::from)
+            .collect()
+    }
 }
 
 /// A helper type that uses the builder pattern to configure how a message is sent, and then
@@ -13,8 +13,8 @@ use std::{
 use linera_base::{
     abi::{ContractAbi, ServiceAbi},
     data_types::{
-        Amount, ApplicationDescription, ApplicationPermissions, BlockHeight, Bytecode, Resources,
-        SendMessageRequest, Timestamp,
+        Amount, ApplicationDescription, ApplicationPermissions, BlockHeight, Bytecode,
+        CommitteeSummary, ResourcePrices, Resources, SendMessageRequest, Timestamp,
     },
     ensure, http,
     identifiers::{
@@ -26,6 +26,8 @@ use linera_base::{
 };
 use serde::Serialize;
 
+#[cfg(feature = "evm")]
+use crate::abis::evm;
 use crate::{Contract, KeyValueStore, ViewStorageContext};
 
 struct ExpectedPublishModuleCall {
@@ -89,6 +91,9 @@ where
     expected_create_application_calls: VecDeque<ExpectedCreateApplicationCall>,
     expected_create_data_blob_calls: VecDeque<ExpectedCreateDataBlobCall>,
     remaining_fuel: Option<u64>,
+    committee_summary: Option<CommitteeSummary>,
+    resource_prices: Option<ResourcePrices>,
+    recent_block_timestamps: Option<Vec<Timestamp>>,
     key_value_store: KeyValueStore,
 }
 
@@ -143,6 +148,9 @@ where
             expected_create_application_calls: VecDeque::new(),
             expected_create_data_blob_calls: VecDeque::new(),
             remaining_fuel: None,
+            committee_summary: None,
+            resource_prices: None,
+            recent_block_timestamps: None,
             key_value_store: KeyValueStore::mock().to_mut(),
         }
     }
@@ -1062,6 +1070,22 @@ where
             .expect("Failed to deserialize `Response` in test runtime cross-application call")
     }
 
+    /// Calls the Solidity function of `call` on an EVM application, transferring `value` to
+    /// it, and returns the values the function returned.
+    #[cfg(feature = "evm")]
+    pub fn call_evm_application<C: evm::SolCall>(
+        &mut self,
+        authenticated: bool,
+        application: ApplicationId<evm::EvmAbi>,
+        call: &C,
+        value: Amount,
+    ) -> C::Return {
+        let operation = evm::encode_operation(call, value);
+        let response = self.call_application(authenticated, application, &operation);
+        evm::decode_response::<C>(&response)
+            .expect("Failed to decode the response of an EVM application")
+    }
+
     /// Adds a new item to an event stream. Returns the new event's index in the stream.
     pub fn emit(&mut self, name: StreamName, value: &Application::EventValue) -> u32 {
         let value = bcs::to_bytes(value).expect("Failed to serialize event value");
@@ -1070,6 +1094,17 @@ where
         entry.len() as u32 - 1
     }
 
+    /// Adds a new item to an event stream, indexed under the given `(field, key)` entries.
+    /// Returns the new event's index in the stream. The mock runtime doesn't keep indexes.
+    pub fn emit_indexed(
+        &mut self,
+        name: StreamName,
+        value: &Application::EventValue,
+        _index_entries: Vec<(String, Vec<u8>)>,
+    ) -> u32 {
+        self.emit(name, value)
+    }
+
     /// Adds an event to a stream, so that it can be read using `read_event`.
     pub fn add_event(&mut self, chain_id: ChainId, name: StreamName, index: u32, value: &[u8]) {
         self.events.insert((chain_id, name, index), value.to_vec());
@@ -1246,6 +1281,68 @@ where
     pub fn remaining_fuel(&mut self) -> u64 {
         self.remaining_fuel.unwrap_or(u64::MAX)
     }
+
+    /// Configures the committee summary to return during the test.
+    pub fn with_committee_summary(mut self, committee_summary: CommitteeSummary) -> Self {
+        self.committee_summary = Some(committee_summary);
+        self
+    }
+
+    /// Configures the committee summary to return during the test.
+    pub fn set_committee_summary(&mut self, committee_summary: CommitteeSummary) -> &mut Self {
+        self.committee_summary = Some(committee_summary);
+        self
+    }
+
+    /// Returns the number of validators and the total votes, i.e. stake, of the committee
+    /// currently in charge of this chain.
+    pub fn committee_summary(&mut self) -> CommitteeSummary {
+        self.committee_summary.expect(
+            "Committee summary has not been mocked, \
+            please call `MockContractRuntime::set_committee_summary` first",
+        )
+    }
+
+    /// Configures the resource prices to return during the test.
+    pub fn with_resource_prices(mut self, resource_prices: ResourcePrices) -> Self {
+        self.resource_prices = Some(resource_prices);
+        self
+    }
+
+    /// Configures the resource prices to return during the test.
+    pub fn set_resource_prices(&mut self, resource_prices: ResourcePrices) -> &mut Self {
+        self.resource_prices = Some(resource_prices);
+        self
+    }
+
+    /// Returns the prices of the resources consumed by this block.
+    pub fn resource_prices(&mut self) -> ResourcePrices {
+        self.resource_prices.expect(
+            "Resource prices have not been mocked, \
+            please call `MockContractRuntime::set_resource_prices` first",
+        )
+    }
+
+    /// Configures the recent block timestamps to return during the test.
+    pub fn with_recent_block_timestamps(mut self, timestamps: Vec<Timestamp>) -> Self {
+        self.recent_block_timestamps = Some(timestamps);
+        self
+    }
+
+    /// Configures the recent block timestamps to return during the test.
+    pub fn set_recent_block_timestamps(&mut self, timestamps: Vec<Timestamp>) -> &mut Self {
+        self.recent_block_timestamps = Some(timestamps);
+        self
+    }
+
+    /// Returns the timestamps of the latest blocks of the current chain, oldest first,
+    /// including the current block.
+    pub fn recent_block_timestamps(&mut self) -> Vec<Timestamp> {
+        self.recent_block_timestamps.clone().expect(
+            "Recent block timestamps have not been mocked, \
+            please call `MockContractRuntime::set_recent_block_timestamps` first",
+        )
+    }
 }
 
 /// A type alias for the handler for cross-application calls.
@@ -30,6 +30,7 @@
 pub mod util;
 
 pub mod abis;
+pub mod arguments;
 mod base;
 pub mod contract;
 #[cfg(feature = "ethereum")]
@@ -13,6 +13,8 @@ use linera_base::{
 };
 
 use super::wit::{base_runtime_api as base_wit, service_runtime_api as service_wit};
+#[cfg(feature = "evm")]
+use crate::abis::evm;
 use crate::{KeyValueStore, Service, ViewStorageContext};
 
 /// The runtime available during execution of a query.
@@ -217,6 +219,19 @@ where
         serde_json::from_slice(&response_bytes)
             .expect("Failed to deserialize query response from application")
     }
+
+    /// Calls the Solidity function of `call` on an EVM application without committing its
+    /// changes, and returns the values the function returned.
+    #[cfg(feature = "evm")]
+    pub fn query_evm_application<C: evm::SolCall>(
+        &self,
+        application: ApplicationId<evm::EvmAbi>,
+        call: &C,
+    ) -> C::Return {
+        let response = self.query_application(application, &evm::encode_query(call));
+        evm::decode_response::<C>(&response)
+            .expect("Failed to decode the response of an EVM application")
+    }
 }
 
 impl<Application> ServiceRuntime<Application>
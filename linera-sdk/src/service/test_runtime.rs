@@ -16,6 +16,8 @@ use linera_base::{
     identifiers::{AccountOwner, ApplicationId, ChainId, DataBlobHash},
 };
 
+#[cfg(feature = "evm")]
+use crate::abis::evm;
 use crate::{KeyValueStore, Service, ViewStorageContext};
 
 /// The runtime available during execution of a query.
@@ -458,6 +460,19 @@ where
             .expect("Failed to deserialize query response from application")
     }
 
+    /// Calls the Solidity function of `call` on an EVM application without committing its
+    /// changes, and returns the values the function returned.
+    #[cfg(feature = "evm")]
+    pub fn query_evm_application<C: evm::SolCall>(
+        &self,
+        application: ApplicationId<evm::EvmAbi>,
+        call: &C,
+    ) -> C::Return {
+        let response = self.query_application(application, &evm::encode_query(call));
+        evm::decode_response::<C>(&response)
+            .expect("Failed to decode the response of an EVM application")
+    }
+
     /// Adds an expected `http_request` call, and the response it should return in the test.
     pub fn add_expected_http_request(&mut self, request: http::Request, response: http::Response) {
         self.expected_http_requests
@@ -17,12 +17,12 @@ use linera_chain::{
     },
     justification::JustificationChain,
     test::VoteTestExt,
-    types::{ConfirmedBlock, ConfirmedBlockCertificate},
+    types::{Block, ConfirmedBlock, ConfirmedBlockCertificate},
 };
 use linera_core::{data_types::ChainInfoQuery, worker::WorkerError};
 use linera_execution::{system::SystemOperation, Operation, ResourceTracker};
 
-use super::TestValidator;
+use super::{replay::RecordedBlock, TestValidator};
 
 /// A helper type to build a block proposal using the builder pattern, and then signing them into
 /// [`ConfirmedBlockCertificate`]s using a [`TestValidator`].
@@ -232,10 +232,24 @@ impl BlockBuilder {
     /// Tries to sign the prepared block with the [`TestValidator`]'s keys and return the
     /// resulting [`Certificate`] and the [`ResourceTracker`] with execution costs.
     /// Returns an error if block execution fails.
+    ///
+    /// When replaying a recorded test, the recorded block is signed instead, so that it is
+    /// executed with the recorded timestamp and oracle responses when the certificate is
+    /// handled.
     pub(crate) async fn try_sign(
         self,
         blobs: &[Blob],
     ) -> Result<(ConfirmedBlockCertificate, ResourceTracker), WorkerError> {
+        let recorder = self.validator.recorder();
+        if let Some(RecordedBlock {
+            block: Some(block),
+            resource_tracker,
+            ..
+        }) = recorder.and_then(|recorder| recorder.next_recorded_block(&self.block))
+        {
+            let certificate = self.certify(block).await;
+            return Ok((certificate, resource_tracker));
+        }
         let published_blobs = self
             .block
             .published_blob_ids()
@@ -248,17 +262,33 @@ impl BlockBuilder {
                     .clone()
             })
             .collect();
-        let (_, block, _, resource_tracker, _) = self
+        let result = self
             .validator
             .worker()
             .stage_block_execution(
-                self.block,
+                self.block.clone(),
                 None,
                 published_blobs,
                 BundleExecutionPolicy::committed(),
             )
-            .await?;
+            .await;
+        if let Some(recorder) = recorder {
+            recorder.record_block(RecordedBlock {
+                proposal: self.block.clone(),
+                block: result.as_ref().ok().map(|(_, block, ..)| block.clone()),
+                resource_tracker: result
+                    .as_ref()
+                    .map_or_else(|_| ResourceTracker::default(), |(.., tracker, _)| *tracker),
+            });
+        }
+        let (_, block, _, resource_tracker, _) = result?;
+
+        let certificate = self.certify(block).await;
+        Ok((certificate, resource_tracker))
+    }
 
+    /// Signs an executed block with the [`TestValidator`]'s keys.
+    async fn certify(&self, block: Block) -> ConfirmedBlockCertificate {
         let value = ConfirmedBlock::new(block);
         // Confirm in the chain's first round so the votes can carry the first-round attestation
         // and the certificate needs no justification chain. The chain's current ownership (the
@@ -278,9 +308,6 @@ impl BlockBuilder {
         let quorum =
             Vote::new_with_first_round(value, round, true, None, self.validator.key_pair())
                 .into_certificate(public_key);
-        let certificate =
-            ConfirmedBlockCertificate::from_parts(quorum, JustificationChain::default());
-
-        Ok((certificate, resource_tracker))
+        ConfirmedBlockCertificate::from_parts(quorum, JustificationChain::default())
     }
 }
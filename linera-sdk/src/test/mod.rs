@@ -16,6 +16,8 @@ mod block;
 mod chain;
 mod mock_stubs;
 #[cfg(with_integration_testing)]
+pub mod replay;
+#[cfg(with_integration_testing)]
 mod validator;
 
 #[cfg(with_integration_testing)]
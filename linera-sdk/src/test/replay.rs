@@ -0,0 +1,227 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Recording and deterministic replay of the blocks executed by a test.
+//!
+//! If the `LINERA_TEST_RECORD_DIR` environment variable is set, the [`TestValidator`] of each
+//! test records the blocks that it executes, with their timestamps and oracle responses. When
+//! the test fails, the recording is written to `<LINERA_TEST_RECORD_DIR>/<test name>.replay`.
+//!
+//! If the `LINERA_TEST_REPLAY` environment variable points to such a file, the validator of
+//! the recorded test generates the same keys, and every block that the test adds is executed
+//! with the recorded timestamp and oracle responses instead of querying the clock, HTTP
+//! servers and services again. This is what `linera project test --record` and
+//! `linera project test --replay` do.
+//!
+//! Only the last [`TestValidator`] created by a test is recorded, and the keys of the chains
+//! must be created by the validator, e.g. with [`TestValidator::new_chain`], so that they are
+//! the same when replaying.
+//!
+//! [`TestValidator`]: super::TestValidator
+//! [`TestValidator::new_chain`]: super::TestValidator::new_chain
+
+use std::{
+    collections::{hash_map::RandomState, BTreeMap},
+    env, fs,
+    hash::{BuildHasher, Hasher},
+    panic,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, Once, PoisonError, Weak,
+    },
+    thread,
+};
+
+use linera_base::crypto::{AccountSecretKey, CryptoRng, ValidatorKeypair};
+use linera_chain::{data_types::ProposedBlock, types::Block};
+use linera_execution::ResourceTracker;
+use serde::{Deserialize, Serialize};
+
+/// The environment variable with the directory where the recordings of failing tests are
+/// written.
+pub const RECORD_DIR_VARIABLE: &str = "LINERA_TEST_RECORD_DIR";
+
+/// The environment variable with the path of the recording to replay.
+pub const REPLAY_VARIABLE: &str = "LINERA_TEST_REPLAY";
+
+/// The recorders of the tests being recorded, by test name.
+static RECORDERS: Mutex<BTreeMap<String, Weak<Recorder>>> = Mutex::new(BTreeMap::new());
+
+/// The contents of a replay file.
+#[derive(Serialize, Deserialize)]
+struct Recording {
+    /// The name of the recorded test.
+    test_name: String,
+    /// The seed of the keys generated by the validator.
+    seed: u64,
+    /// The blocks proposed by the test, in order.
+    blocks: Vec<RecordedBlock>,
+}
+
+/// A block proposed during a recorded test.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct RecordedBlock {
+    /// The block as proposed by the test.
+    pub proposal: ProposedBlock,
+    /// The executed block, with its oracle responses, or `None` if the execution failed.
+    pub block: Option<Block>,
+    /// The resources used to execute the block.
+    pub resource_tracker: ResourceTracker,
+}
+
+/// Records the blocks of a test, or replays the blocks of a recorded test.
+pub(crate) struct Recorder {
+    test_name: String,
+    seed: u64,
+    rng: Mutex<Box<dyn CryptoRng>>,
+    mode: Mode,
+}
+
+enum Mode {
+    Record {
+        path: PathBuf,
+        blocks: Mutex<Vec<RecordedBlock>>,
+    },
+    Replay {
+        blocks: Vec<RecordedBlock>,
+        next: AtomicUsize,
+    },
+}
+
+impl Recorder {
+    /// Starts recording or replaying the current test, if the environment says so.
+    ///
+    /// The test is identified by the name of the current thread, which the test harness sets
+    /// to the name of the test.
+    pub(crate) fn from_env() -> Option<Arc<Self>> {
+        let test_name = thread::current().name()?.to_owned();
+        if let Some(path) = env::var_os(REPLAY_VARIABLE) {
+            let path = Path::new(&path);
+            let bytes = fs::read(path).unwrap_or_else(|error| {
+                panic!("Failed to read replay file {}: {error}", path.display())
+            });
+            let recording = bcs::from_bytes::<Recording>(&bytes)
+                .unwrap_or_else(|error| panic!("Invalid replay file {}: {error}", path.display()));
+            if recording.test_name != test_name {
+                return None;
+            }
+            return Some(Arc::new(Recorder {
+                test_name,
+                seed: recording.seed,
+                rng: Mutex::new(Some(recording.seed).into()),
+                mode: Mode::Replay {
+                    blocks: recording.blocks,
+                    next: AtomicUsize::new(0),
+                },
+            }));
+        }
+        let directory = PathBuf::from(env::var_os(RECORD_DIR_VARIABLE)?);
+        let seed = RandomState::new().build_hasher().finish();
+        let recorder = Arc::new(Recorder {
+            seed,
+            rng: Mutex::new(Some(seed).into()),
+            mode: Mode::Record {
+                path: directory.join(format!("{test_name}.replay")),
+                blocks: Mutex::default(),
+            },
+            test_name: test_name.clone(),
+        });
+        install_panic_hook();
+        RECORDERS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(test_name, Arc::downgrade(&recorder));
+        Some(recorder)
+    }
+
+    /// Generates the next account key of the test.
+    pub(crate) fn generate_account_key(&self) -> AccountSecretKey {
+        AccountSecretKey::generate_from(&mut *self.rng.lock().unwrap())
+    }
+
+    /// Generates the next validator key of the test.
+    pub(crate) fn generate_validator_key(&self) -> ValidatorKeypair {
+        ValidatorKeypair::generate_from(&mut *self.rng.lock().unwrap())
+    }
+
+    /// Returns the recording of the next block when replaying, after checking that the test
+    /// proposed the same block again, up to its timestamp.
+    pub(crate) fn next_recorded_block(&self, proposal: &ProposedBlock) -> Option<RecordedBlock> {
+        let Mode::Replay { blocks, next } = &self.mode else {
+            return None;
+        };
+        let index = next.fetch_add(1, Ordering::SeqCst);
+        let recorded = blocks.get(index).unwrap_or_else(|| {
+            panic!(
+                "Test proposed more blocks than the {} of its recording",
+                blocks.len()
+            )
+        });
+        let proposal = ProposedBlock {
+            timestamp: recorded.proposal.timestamp,
+            ..proposal.clone()
+        };
+        assert!(
+            proposal == recorded.proposal,
+            "Test diverged from its recording at block {index}:\n\
+            Proposed: {proposal:#?}\n\
+            Recorded: {:#?}",
+            recorded.proposal
+        );
+        Some(recorded.clone())
+    }
+
+    /// Records a block proposed by the test, and its execution.
+    pub(crate) fn record_block(&self, block: RecordedBlock) {
+        if let Mode::Record { blocks, .. } = &self.mode {
+            blocks.lock().unwrap().push(block);
+        }
+    }
+
+    /// Writes the recording to its replay file.
+    fn write(&self) {
+        let Mode::Record { path, blocks } = &self.mode else {
+            return;
+        };
+        // The test may have panicked while holding the lock.
+        let Ok(blocks) = blocks.try_lock() else {
+            return;
+        };
+        let recording = Recording {
+            test_name: self.test_name.clone(),
+            seed: self.seed,
+            blocks: blocks.clone(),
+        };
+        let bytes = bcs::to_bytes(&recording).expect("Failed to serialize recording");
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(path, bytes));
+        match result {
+            Ok(()) => eprintln!("Recorded failing test to {}", path.display()),
+            Err(error) => eprintln!("Failed to write replay file {}: {error}", path.display()),
+        }
+    }
+}
+
+/// Installs a panic hook that writes the recording of a test when it fails.
+fn install_panic_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let previous_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let recorder = thread::current().name().and_then(|test_name| {
+                RECORDERS
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .get(test_name)
+                    .and_then(Weak::upgrade)
+            });
+            if let Some(recorder) = recorder {
+                recorder.write();
+            }
+            previous_hook(info);
+        }));
+    });
+}
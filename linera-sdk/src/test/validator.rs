@@ -31,7 +31,7 @@ use linera_storage::{DbStorage, Storage, TestClock};
 use linera_views::memory::MemoryDatabase;
 use serde::Serialize;
 
-use super::ActiveChain;
+use super::{replay::Recorder, ActiveChain};
 use crate::ContractAbi;
 
 /// A minimal validator implementation suited for tests.
@@ -56,6 +56,7 @@ pub struct TestValidator {
     clock: TestClock,
     admin_chain_id: ChainId,
     chains: Arc<papaya::HashMap<ChainId, ActiveChain>>,
+    recorder: Option<Arc<Recorder>>,
 }
 
 impl Clone for TestValidator {
@@ -69,6 +70,7 @@ impl Clone for TestValidator {
             worker: self.worker.clone(),
             clock: self.clock.clone(),
             chains: self.chains.clone(),
+            recorder: self.recorder.clone(),
         }
     }
 }
@@ -76,8 +78,13 @@ impl Clone for TestValidator {
 impl TestValidator {
     /// Creates a new [`TestValidator`].
     pub async fn new() -> Self {
-        let validator_keypair = ValidatorKeypair::generate();
-        let account_secret = AccountSecretKey::generate();
+        let recorder = Recorder::from_env();
+        let validator_keypair = recorder
+            .as_deref()
+            .map_or_else(ValidatorKeypair::generate, Recorder::generate_validator_key);
+        let account_secret = recorder
+            .as_deref()
+            .map_or_else(AccountSecretKey::generate, Recorder::generate_account_key);
         let epoch = Epoch::ZERO;
         let committee = Committee::make_simple(vec![(
             validator_keypair.public_key,
@@ -96,7 +103,9 @@ impl TestValidator {
         let worker = WorkerState::new(storage.clone(), config, None);
 
         // Create an admin chain.
-        let key_pair = AccountSecretKey::generate();
+        let key_pair = recorder
+            .as_deref()
+            .map_or_else(AccountSecretKey::generate, Recorder::generate_account_key);
 
         let new_chain_config = InitialChainConfig {
             ownership: ChainOwnership::single(key_pair.public().into()),
@@ -143,6 +152,7 @@ impl TestValidator {
             clock,
             admin_chain_id,
             chains: Arc::default(),
+            recorder,
         };
 
         let chain = ActiveChain::new(key_pair, description.clone(), validator.clone());
@@ -211,6 +221,11 @@ impl TestValidator {
         self.worker.clone()
     }
 
+    /// Returns the [`Recorder`] of the test, if it is being recorded or replayed.
+    pub(crate) fn recorder(&self) -> Option<&Recorder> {
+        self.recorder.as_deref()
+    }
+
     /// Returns the [`TestClock`] of this validator.
     pub fn clock(&self) -> &TestClock {
         &self.clock
@@ -298,7 +313,9 @@ impl TestValidator {
     /// Creates a new microchain and returns the [`ActiveChain`] that can be used to add blocks to
     /// it.
     pub async fn new_chain(&self) -> ActiveChain {
-        let key_pair = AccountSecretKey::generate();
+        let key_pair = self
+            .recorder()
+            .map_or_else(AccountSecretKey::generate, Recorder::generate_account_key);
         Box::pin(self.new_chain_with_keypair(key_pair)).await
     }
 
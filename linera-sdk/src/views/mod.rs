@@ -11,6 +11,7 @@ mod system_api;
 pub use linera_views::{
     self,
     common::CustomSerialize,
+    migration::{MigrationStep, VersionedView},
     views::{RootView, View},
     ViewError,
 };
@@ -248,6 +248,7 @@ mod from {
                 }))
             }
             "CloseChain" => Ok(SystemOperation::CloseChain),
+            "MakeEphemeral" => Ok(SystemOperation::MakeEphemeral),
             "ChangeOwnership" => {
                 let change_ownership = system_op.change_ownership.ok_or_else(|| {
                     ConversionError::UnexpectedCertificateType(
@@ -506,6 +507,20 @@ mod from {
                     next_index: stream.next_index as u32,
                 })
             }
+            "ChangeSignatureSchemes" => {
+                let schemes = system_op.signature_schemes.ok_or_else(|| {
+                    ConversionError::UnexpectedCertificateType(
+                        "Missing signature_schemes for ChangeSignatureSchemes operation"
+                            .to_string(),
+                    )
+                })?;
+                let schemes = schemes
+                    .into_iter()
+                    .map(|scheme| serde_json::from_value(serde_json::Value::String(scheme)))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(ConversionError::Serde)?;
+                Ok(SystemOperation::ChangeSignatureSchemes(schemes))
+            }
             _ => Err(ConversionError::UnexpectedCertificateType(format!(
                 "Unknown system operation type: {}",
                 system_op.system_operation_type
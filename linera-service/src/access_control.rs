@@ -0,0 +1,420 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::SystemTime,
+};
+
+use async_graphql::{
+    parser::types::{
+        DocumentOperations, ExecutableDocument, OperationType, Selection, SelectionSet,
+    },
+    Request, Value,
+};
+use linera_base::identifiers::{ApplicationId, ChainId};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use tracing::warn;
+
+/// The access tokens accepted by a node service, by name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AccessTokens {
+    /// The tokens, by name.
+    #[serde(default)]
+    pub tokens: BTreeMap<String, AccessToken>,
+}
+
+/// A token allowing its bearer to run some mutations.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccessToken {
+    /// The hex-encoded SHA-256 hash of the token's secret. The secret itself is not stored.
+    pub secret_hash: String,
+    /// The mutations the token allows.
+    pub scope: AccessScope,
+}
+
+/// The mutations allowed by an access token.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessScope {
+    /// The chains on which mutations are allowed. Any chain if empty.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub chains: BTreeSet<ChainId>,
+    /// The applications whose mutations are allowed, or `None` for any application.
+    #[serde(default)]
+    pub applications: Option<BTreeSet<ApplicationId>>,
+    /// Whether the mutations of the node service itself, e.g. transfers, are allowed.
+    #[serde(default)]
+    pub system: bool,
+    /// The names of the allowed mutations. Any mutation if empty.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub mutations: BTreeSet<String>,
+}
+
+/// A top-level field of a GraphQL mutation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MutationTarget {
+    /// The chain that the mutation changes, if known.
+    pub chain_id: Option<ChainId>,
+    /// The application receiving the mutation, or `None` for the node service itself.
+    pub application_id: Option<ApplicationId>,
+    /// The name of the mutation.
+    pub mutation: String,
+}
+
+/// An error authorizing a request.
+#[derive(Debug, thiserror::Error)]
+pub enum AccessError {
+    /// The request has no token, or a malformed one.
+    #[error("mutations require an access token, sent as `Authorization: Bearer <token>`")]
+    MissingToken,
+    /// The token is unknown, revoked or rotated.
+    #[error("invalid access token")]
+    InvalidToken,
+    /// The token does not allow the mutation.
+    #[error("the access token does not allow the mutation `{0}`")]
+    Forbidden(String),
+    /// The request could not be parsed.
+    #[error("invalid GraphQL request: {0}")]
+    InvalidRequest(String),
+}
+
+impl AccessScope {
+    /// Returns whether the scope allows the given mutation.
+    pub fn allows(&self, target: &MutationTarget) -> bool {
+        let chain_allowed = self.chains.is_empty()
+            || target
+                .chain_id
+                .is_some_and(|chain_id| self.chains.contains(&chain_id));
+        let target_allowed = match (&target.application_id, &self.applications) {
+            (None, _) => self.system,
+            (Some(_), None) => true,
+            (Some(application_id), Some(applications)) => applications.contains(application_id),
+        };
+        let mutation_allowed =
+            self.mutations.is_empty() || self.mutations.contains(&target.mutation);
+        chain_allowed && target_allowed && mutation_allowed
+    }
+}
+
+impl AccessTokens {
+    /// Creates a token with the given name and scope, and returns its secret value. Fails if
+    /// a token with that name already exists.
+    pub fn issue(&mut self, name: &str, scope: AccessScope) -> anyhow::Result<String> {
+        anyhow::ensure!(
+            !name.is_empty() && !name.contains('.'),
+            "token names must be non-empty and must not contain `.`"
+        );
+        anyhow::ensure!(
+            !self.tokens.contains_key(name),
+            "a token named {name} already exists"
+        );
+        let (token, secret_hash) = new_secret(name);
+        self.tokens
+            .insert(name.to_string(), AccessToken { secret_hash, scope });
+        Ok(token)
+    }
+
+    /// Replaces the secret of a token, keeping its scope, and returns the new secret value.
+    /// The previous value stops being accepted.
+    pub fn rotate(&mut self, name: &str) -> anyhow::Result<String> {
+        let token = self
+            .tokens
+            .get_mut(name)
+            .ok_or_else(|| anyhow::anyhow!("no token named {name}"))?;
+        let (value, secret_hash) = new_secret(name);
+        token.secret_hash = secret_hash;
+        Ok(value)
+    }
+
+    /// Removes a token.
+    pub fn revoke(&mut self, name: &str) -> anyhow::Result<()> {
+        anyhow::ensure!(self.tokens.remove(name).is_some(), "no token named {name}");
+        Ok(())
+    }
+
+    /// Returns the scope of the token with the given value, if it is valid.
+    fn scope(&self, token: &str) -> Option<&AccessScope> {
+        let (name, secret) = token.split_once('.')?;
+        let token = self.tokens.get(name)?;
+        (token.secret_hash == hash_secret(secret)).then_some(&token.scope)
+    }
+}
+
+/// Returns a new token value for the given name, and the hash of its secret.
+fn new_secret(name: &str) -> (String, String) {
+    let secret = hex::encode(rand::random::<[u8; 32]>());
+    let secret_hash = hash_secret(&secret);
+    (format!("{name}.{secret}"), secret_hash)
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Reads the tokens from the file at `path`. A missing file has no tokens.
+pub fn read_tokens(path: &Path) -> anyhow::Result<AccessTokens> {
+    match fs_err::read(path) {
+        Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(AccessTokens::default()),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Restricts the mutations of the node service to the bearers of tokens listed in a file.
+///
+/// The file is read again whenever it changes, so that issued, rotated and revoked tokens
+/// take effect without restarting the service.
+pub struct AccessControl {
+    path: PathBuf,
+    state: Mutex<(Option<SystemTime>, AccessTokens)>,
+}
+
+impl AccessControl {
+    /// Loads the tokens from the file at `path`. Until the file exists, all mutations are
+    /// denied.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let modified = fs_err::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        let tokens = read_tokens(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            state: Mutex::new((modified, tokens)),
+        })
+    }
+
+    /// Checks that the token, taken from the `Authorization` header, allows all the
+    /// mutations. Requests without mutations are always allowed.
+    pub fn authorize(
+        &self,
+        authorization: Option<&str>,
+        targets: &[MutationTarget],
+    ) -> Result<(), AccessError> {
+        if targets.is_empty() {
+            return Ok(());
+        }
+        let token = authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .ok_or(AccessError::MissingToken)?;
+        let mut state = self.state.lock().unwrap();
+        self.reload_if_changed(&mut state);
+        let scope = state.1.scope(token).ok_or(AccessError::InvalidToken)?;
+        for target in targets {
+            if !scope.allows(target) {
+                return Err(AccessError::Forbidden(target.mutation.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    fn reload_if_changed(&self, state: &mut (Option<SystemTime>, AccessTokens)) {
+        let modified = fs_err::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified == state.0 {
+            return;
+        }
+        match read_tokens(&self.path) {
+            Ok(tokens) => *state = (modified, tokens),
+            Err(error) => warn!("Failed to reload the access token file: {error}"),
+        }
+    }
+}
+
+/// Returns the top-level fields of the mutations in a GraphQL request.
+///
+/// The chain of each mutation is `chain_id` if given, or else the value of the field's
+/// `chainId` argument, if any.
+pub fn mutation_targets(
+    request: &Request,
+    chain_id: Option<ChainId>,
+    application_id: Option<ApplicationId>,
+) -> Result<Vec<MutationTarget>, AccessError> {
+    let document = async_graphql::parser::parse_query(&request.query)
+        .map_err(|error| AccessError::InvalidRequest(error.to_string()))?;
+    let operations = match &document.operations {
+        DocumentOperations::Single(operation) => vec![&operation.node],
+        DocumentOperations::Multiple(operations) => operations
+            .iter()
+            .filter(|(name, _)| {
+                request
+                    .operation_name
+                    .as_ref()
+                    .is_none_or(|operation_name| name.as_str() == operation_name)
+            })
+            .map(|(_, operation)| &operation.node)
+            .collect(),
+    };
+    let mut targets = Vec::new();
+    for operation in operations {
+        if operation.ty != OperationType::Mutation {
+            continue;
+        }
+        collect_targets(
+            &document,
+            &operation.selection_set.node,
+            request,
+            chain_id,
+            application_id,
+            &mut targets,
+        )?;
+    }
+    Ok(targets)
+}
+
+fn collect_targets(
+    document: &ExecutableDocument,
+    selection_set: &SelectionSet,
+    request: &Request,
+    chain_id: Option<ChainId>,
+    application_id: Option<ApplicationId>,
+    targets: &mut Vec<MutationTarget>,
+) -> Result<(), AccessError> {
+    for selection in &selection_set.items {
+        match &selection.node {
+            Selection::Field(field) => {
+                let field = &field.node;
+                let chain_id = match chain_id {
+                    Some(chain_id) => Some(chain_id),
+                    None => field
+                        .get_argument("chainId")
+                        .map(|value| {
+                            let value = value.node.clone().into_const_with(|name| {
+                                request.variables.get(&name).cloned().ok_or_else(|| {
+                                    AccessError::InvalidRequest(format!("missing variable ${name}"))
+                                })
+                            })?;
+                            chain_argument(value)
+                        })
+                        .transpose()?,
+                };
+                targets.push(MutationTarget {
+                    chain_id,
+                    application_id,
+                    mutation: field.name.node.to_string(),
+                });
+            }
+            Selection::InlineFragment(fragment) => collect_targets(
+                document,
+                &fragment.node.selection_set.node,
+                request,
+                chain_id,
+                application_id,
+                targets,
+            )?,
+            Selection::FragmentSpread(spread) => {
+                let name = &spread.node.fragment_name.node;
+                let fragment = document.fragments.get(name).ok_or_else(|| {
+                    AccessError::InvalidRequest(format!("unknown fragment {name}"))
+                })?;
+                collect_targets(
+                    document,
+                    &fragment.node.selection_set.node,
+                    request,
+                    chain_id,
+                    application_id,
+                    targets,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parses the value of a `chainId` argument, with its variables resolved.
+fn chain_argument(value: Value) -> Result<ChainId, AccessError> {
+    match value {
+        Value::String(chain_id) => chain_id
+            .parse()
+            .map_err(|_| AccessError::InvalidRequest(format!("invalid chain ID {chain_id}"))),
+        value => Err(AccessError::InvalidRequest(format!(
+            "invalid chain ID {value}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use async_graphql::{Request, Variables};
+    use linera_base::{
+        crypto::CryptoHash,
+        identifiers::{ApplicationId, ChainId},
+    };
+
+    use super::{mutation_targets, AccessScope, AccessTokens, MutationTarget};
+
+    #[test]
+    fn test_mutation_targets() {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let request = Request::new(
+            "query Q { balance } \
+             mutation M($chain: ChainId!) { transfer(chainId: $chain) ...F } \
+             fragment F on MutationRoot { close { ok } }",
+        )
+        .variables(Variables::from_json(
+            serde_json::json!({ "chain": chain_id }),
+        ));
+        let targets = mutation_targets(&request, None, None).unwrap();
+        assert_eq!(
+            targets,
+            vec![
+                MutationTarget {
+                    chain_id: Some(chain_id),
+                    application_id: None,
+                    mutation: "transfer".to_string(),
+                },
+                MutationTarget {
+                    chain_id: None,
+                    application_id: None,
+                    mutation: "close".to_string(),
+                },
+            ]
+        );
+
+        let request = Request::new("query { value }");
+        assert!(mutation_targets(&request, None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scoped_tokens() {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let other_chain_id = ChainId(CryptoHash::test_hash("other chain"));
+        let application_id = ApplicationId::new(CryptoHash::test_hash("application"));
+        let target = |chain_id, application_id, mutation: &str| MutationTarget {
+            chain_id: Some(chain_id),
+            application_id,
+            mutation: mutation.to_string(),
+        };
+
+        let mut tokens = AccessTokens::default();
+        let token = tokens
+            .issue(
+                "frontend",
+                AccessScope {
+                    chains: BTreeSet::from([chain_id]),
+                    applications: Some(BTreeSet::from([application_id])),
+                    system: false,
+                    mutations: BTreeSet::from(["increment".to_string()]),
+                },
+            )
+            .unwrap();
+        let scope = tokens.scope(&token).unwrap();
+        assert!(scope.allows(&target(chain_id, Some(application_id), "increment")));
+        assert!(!scope.allows(&target(chain_id, Some(application_id), "reset")));
+        assert!(!scope.allows(&target(other_chain_id, Some(application_id), "increment")));
+        assert!(!scope.allows(&target(chain_id, None, "increment")));
+
+        assert!(tokens.issue("frontend", AccessScope::default()).is_err());
+        let rotated = tokens.rotate("frontend").unwrap();
+        assert!(tokens.scope(&token).is_none());
+        assert!(tokens.scope(&rotated).is_some());
+        tokens.revoke("frontend").unwrap();
+        assert!(tokens.scope(&rotated).is_none());
+    }
+}
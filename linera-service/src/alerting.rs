@@ -0,0 +1,405 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{collections::BTreeMap, time::Duration};
+
+use linera_base::data_types::Timestamp;
+use linera_client::config::{AlertCondition, AlertConfig, AlertNotifierConfig};
+use prometheus::proto::{MetricFamily, MetricType};
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// The default URL of the PagerDuty Events API v2.
+const PAGER_DUTY_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+/// The operations whose latencies are checked by [`AlertCondition::StorageLatency`].
+const STORAGE_OPERATIONS: &[&str] = &[
+    "read_value_bytes",
+    "contains_key",
+    "contains_keys",
+    "read_multi_value_bytes",
+    "find_keys_by_prefix",
+    "find_key_values_by_prefix",
+    "write_batch",
+];
+/// The metric counting the failed requests of the validator shards.
+const REQUEST_ERROR_METRIC: &str = "linera_server_request_error";
+/// The metric counting the blocks rejected for being ahead of their chain's epoch.
+const FUTURE_EPOCH_METRIC: &str = "linera_future_epoch_blocks_rejected_total";
+
+/// Whether an alert started or stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlertStatus {
+    /// The condition started to hold.
+    Firing,
+    /// The condition no longer holds.
+    Resolved,
+}
+
+/// An alert sent to the notifiers.
+#[derive(Clone, Debug, Serialize)]
+pub struct Alert {
+    /// The condition of the alert.
+    pub condition: AlertCondition,
+    /// Whether the alert started or stopped.
+    pub status: AlertStatus,
+    /// The binary raising the alert.
+    pub source: String,
+    /// A human-readable description of the alert.
+    pub summary: String,
+    /// When the alert was raised.
+    pub timestamp: Timestamp,
+}
+
+/// The metrics checked by the alert conditions, as recorded at some point in time.
+#[derive(Clone, Debug, Default)]
+struct MetricsSnapshot {
+    /// The cumulative number of storage operations for each latency bucket, keyed by the
+    /// bits of the bucket's upper bound in milliseconds. The bits of non-negative floats
+    /// are ordered like the floats themselves.
+    storage_latency_buckets: BTreeMap<u64, u64>,
+    /// The number of storage operations.
+    storage_operations: u64,
+    /// The number of failed requests.
+    request_errors: u64,
+    /// The number of requests that failed because of missing blobs.
+    missing_blobs: u64,
+    /// The number of blocks rejected for being ahead of their chain's epoch.
+    future_epoch_blocks: u64,
+}
+
+impl MetricsSnapshot {
+    fn from_families(families: &[MetricFamily]) -> Self {
+        let mut snapshot = Self::default();
+        for family in families {
+            let name = family.get_name();
+            if family.get_field_type() == MetricType::HISTOGRAM && is_storage_latency(name) {
+                for metric in family.get_metric() {
+                    let histogram = metric.get_histogram();
+                    snapshot.storage_operations += histogram.get_sample_count();
+                    for bucket in histogram.get_bucket() {
+                        *snapshot
+                            .storage_latency_buckets
+                            .entry(bucket.get_upper_bound().to_bits())
+                            .or_default() += bucket.get_cumulative_count();
+                    }
+                }
+            } else if name == REQUEST_ERROR_METRIC {
+                for metric in family.get_metric() {
+                    let count = counter_value(metric);
+                    snapshot.request_errors += count;
+                    let is_missing_blobs = metric.get_label().iter().any(|label| {
+                        label.get_name() == "error_type"
+                            && label.get_value().ends_with("::BlobsNotFound")
+                    });
+                    if is_missing_blobs {
+                        snapshot.missing_blobs += count;
+                    }
+                }
+            } else if name == FUTURE_EPOCH_METRIC {
+                snapshot.future_epoch_blocks +=
+                    family.get_metric().iter().map(counter_value).sum::<u64>();
+            }
+        }
+        snapshot
+    }
+
+    /// Returns the 99th percentile of the storage latencies recorded after `previous`, in
+    /// milliseconds, if any operation was recorded.
+    fn storage_latency_p99(&self, previous: &Self) -> Option<f64> {
+        let operations = self
+            .storage_operations
+            .saturating_sub(previous.storage_operations);
+        if operations == 0 {
+            return None;
+        }
+        let rank = (operations * 99).div_ceil(100);
+        for (bound, count) in &self.storage_latency_buckets {
+            let previous_count = previous
+                .storage_latency_buckets
+                .get(bound)
+                .copied()
+                .unwrap_or_default();
+            if count.saturating_sub(previous_count) >= rank {
+                return Some(f64::from_bits(*bound));
+            }
+        }
+        Some(f64::INFINITY)
+    }
+}
+
+fn is_storage_latency(name: &str) -> bool {
+    name.strip_suffix("_latency").is_some_and(|name| {
+        STORAGE_OPERATIONS
+            .iter()
+            .any(|operation| name.ends_with(&format!("_{operation}")))
+    })
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "counters hold non-negative integers"
+)]
+fn counter_value(metric: &prometheus::proto::Metric) -> u64 {
+    metric.get_counter().get_value() as u64
+}
+
+/// Checks the alert conditions of a validator binary from its metrics, and sends the
+/// resulting alerts to the configured notifiers.
+pub struct AlertMonitor {
+    config: AlertConfig,
+    source: String,
+    client: reqwest::Client,
+    previous: Option<MetricsSnapshot>,
+    firing: BTreeMap<AlertCondition, String>,
+}
+
+impl AlertMonitor {
+    /// Creates a monitor for the binary named `source`.
+    pub fn new(config: AlertConfig, source: String) -> Self {
+        Self {
+            config,
+            source,
+            client: reqwest::Client::new(),
+            previous: None,
+            firing: BTreeMap::new(),
+        }
+    }
+
+    /// Checks the conditions periodically until `shutdown` is cancelled.
+    pub async fn run(mut self, shutdown: CancellationToken) {
+        let period = Duration::from_secs(self.config.check_interval_secs.max(1));
+        let mut interval = tokio::time::interval(period);
+        info!(source = %self.source, "Checking alert conditions every {period:?}");
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                () = shutdown.cancelled() => return,
+            }
+            let snapshot = MetricsSnapshot::from_families(&prometheus::gather());
+            for alert in self.check(snapshot) {
+                self.notify(&alert).await;
+            }
+        }
+    }
+
+    /// Records a new snapshot of the metrics and returns the alerts whose condition started
+    /// or stopped holding since the previous one.
+    fn check(&mut self, snapshot: MetricsSnapshot) -> Vec<Alert> {
+        let Some(previous) = self.previous.replace(snapshot) else {
+            return Vec::new();
+        };
+        let current = self
+            .previous
+            .as_ref()
+            .expect("the snapshot was just recorded");
+        let config = &self.config;
+        let mut alerts = Vec::new();
+        let conditions = [
+            (
+                AlertCondition::StorageLatency,
+                current
+                    .storage_latency_p99(&previous)
+                    .filter(|p99| *p99 > config.storage_latency_p99_ms as f64)
+                    .map(|p99| {
+                        format!(
+                            "the 99th percentile of storage latencies is above {p99} ms \
+                            (threshold: {} ms)",
+                            config.storage_latency_p99_ms
+                        )
+                    }),
+            ),
+            (
+                AlertCondition::ChainErrors,
+                exceeded(
+                    current.request_errors,
+                    previous.request_errors,
+                    config.chain_errors,
+                )
+                .map(|count| format!("{count} requests failed since the last check")),
+            ),
+            (
+                AlertCondition::MissingBlobs,
+                exceeded(
+                    current.missing_blobs,
+                    previous.missing_blobs,
+                    config.missing_blobs,
+                )
+                .map(|count| {
+                    format!("{count} requests failed because of missing blobs since the last check")
+                }),
+            ),
+            (
+                AlertCondition::EpochLag,
+                exceeded(
+                    current.future_epoch_blocks,
+                    previous.future_epoch_blocks,
+                    config.future_epoch_blocks,
+                )
+                .map(|count| {
+                    format!(
+                        "{count} blocks from epochs this validator does not know yet were \
+                        rejected since the last check"
+                    )
+                }),
+            ),
+        ];
+        for (condition, summary) in conditions {
+            if !config.is_enabled(condition) {
+                continue;
+            }
+            let (status, summary) = match (summary, self.firing.contains_key(&condition)) {
+                (Some(summary), false) => {
+                    self.firing.insert(condition, summary.clone());
+                    (AlertStatus::Firing, summary)
+                }
+                (None, true) => {
+                    let summary = self.firing.remove(&condition).unwrap_or_default();
+                    (AlertStatus::Resolved, format!("resolved: {summary}"))
+                }
+                (Some(_), true) | (None, false) => continue,
+            };
+            alerts.push(Alert {
+                condition,
+                status,
+                source: self.source.clone(),
+                summary,
+                timestamp: Timestamp::now(),
+            });
+        }
+        alerts
+    }
+
+    /// Sends the alert to every notifier, logging the failures.
+    async fn notify(&self, alert: &Alert) {
+        warn!(
+            condition = %alert.condition,
+            status = ?alert.status,
+            "Alert: {}",
+            alert.summary
+        );
+        for notifier in &self.config.notifiers {
+            if let Err(error) = self.send(notifier, alert).await {
+                warn!(%error, condition = %alert.condition, "Failed to send alert");
+            }
+        }
+    }
+
+    async fn send(&self, notifier: &AlertNotifierConfig, alert: &Alert) -> anyhow::Result<()> {
+        match notifier {
+            AlertNotifierConfig::Webhook { url } => {
+                self.client
+                    .post(url)
+                    .json(alert)
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            AlertNotifierConfig::PagerDuty { routing_key, url } => {
+                self.client
+                    .post(url.as_deref().unwrap_or(PAGER_DUTY_EVENTS_URL))
+                    .json(&pager_duty_event(routing_key, alert))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            AlertNotifierConfig::StdoutJson => {
+                println!("{}", serde_json::to_string(alert)?);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the increase of a counter since the previous check if it reached `threshold`.
+fn exceeded(current: u64, previous: u64, threshold: u64) -> Option<u64> {
+    let increase = current.saturating_sub(previous);
+    (increase > 0 && increase >= threshold).then_some(increase)
+}
+
+/// Returns the PagerDuty event triggering or resolving the incident of the alert.
+fn pager_duty_event(routing_key: &str, alert: &Alert) -> serde_json::Value {
+    let event_action = match alert.status {
+        AlertStatus::Firing => "trigger",
+        AlertStatus::Resolved => "resolve",
+    };
+    serde_json::json!({
+        "routing_key": routing_key,
+        "event_action": event_action,
+        "dedup_key": format!("{}/{}", alert.source, alert.condition),
+        "payload": {
+            "summary": alert.summary,
+            "source": alert.source,
+            "severity": "critical",
+            "component": alert.condition.to_string(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_client::config::{AlertCondition, AlertConfig};
+
+    use super::{pager_duty_event, AlertMonitor, AlertStatus, MetricsSnapshot};
+
+    fn snapshot(buckets: &[(f64, u64)], request_errors: u64) -> MetricsSnapshot {
+        MetricsSnapshot {
+            storage_latency_buckets: buckets
+                .iter()
+                .map(|(bound, count)| (bound.to_bits(), *count))
+                .collect(),
+            storage_operations: buckets.last().map_or(0, |(_, count)| *count),
+            request_errors,
+            ..MetricsSnapshot::default()
+        }
+    }
+
+    #[test]
+    fn storage_latency_percentile() {
+        let previous = snapshot(&[(1.0, 100), (10.0, 100), (100.0, 100)], 0);
+        let fast = snapshot(&[(1.0, 300), (10.0, 300), (100.0, 300)], 0);
+        assert_eq!(fast.storage_latency_p99(&previous), Some(1.0));
+        let slow = snapshot(&[(1.0, 150), (10.0, 160), (100.0, 300)], 0);
+        assert_eq!(slow.storage_latency_p99(&previous), Some(100.0));
+        assert_eq!(previous.storage_latency_p99(&previous), None);
+    }
+
+    #[test]
+    fn alerts_fire_and_resolve() {
+        let config = AlertConfig {
+            storage_latency_p99_ms: 50,
+            chain_errors: 10,
+            disabled: [AlertCondition::MissingBlobs].into_iter().collect(),
+            ..AlertConfig::default()
+        };
+        let mut monitor = AlertMonitor::new(config, "validator".to_string());
+        assert!(monitor
+            .check(snapshot(&[(1.0, 0), (100.0, 0)], 0))
+            .is_empty());
+
+        let alerts = monitor.check(snapshot(&[(1.0, 10), (100.0, 100)], 20));
+        let fired = alerts
+            .iter()
+            .map(|alert| (alert.condition, alert.status))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fired,
+            [
+                (AlertCondition::StorageLatency, AlertStatus::Firing),
+                (AlertCondition::ChainErrors, AlertStatus::Firing),
+            ]
+        );
+
+        // Conditions that still hold are not reported again.
+        let alerts = monitor.check(snapshot(&[(1.0, 10), (100.0, 200)], 25));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].condition, AlertCondition::ChainErrors);
+        assert_eq!(alerts[0].status, AlertStatus::Resolved);
+
+        let event = pager_duty_event("key", &alerts[0]);
+        assert_eq!(event["event_action"], "resolve");
+        assert_eq!(event["dedup_key"], "validator/chain-errors");
+    }
+}
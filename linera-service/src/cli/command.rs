@@ -1,17 +1,22 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{borrow::Cow, num::NonZeroU16, path::PathBuf};
+use std::{
+    borrow::Cow,
+    num::{NonZeroU16, NonZeroU32, NonZeroUsize},
+    path::PathBuf,
+};
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use linera_base::{
-    crypto::{AccountPublicKey, CryptoHash, ValidatorPublicKey},
+    crypto::{AccountPublicKey, CryptoHash, SignatureScheme, ValidatorPublicKey},
     data_types::{Amount, BlockHeight, Epoch, Timestamp},
     identifiers::{Account, AccountOwner, ApplicationId, ChainId, ModuleId, StreamId},
     time::Duration,
     vm::VmRuntime,
 };
 use linera_client::{
+    bytecode_source::BytecodeSource,
     chain_listener::ChainListenerConfig,
     client_options::{
         ApplicationPermissionsConfig, ChainOwnershipConfig, ResourceControlPolicyConfig,
@@ -21,7 +26,9 @@ use linera_client::{
 use linera_rpc::config::CrossChainConfig;
 
 use crate::{
-    cli::validator, query_subscription::parse_subscription_ttl, task_processor::parse_operator,
+    cli::validator, event_index::EventIndexOptions, query_limits::QueryLimitOptions,
+    query_subscription::parse_subscription_ttl, service_security::ServiceSecurityOptions,
+    task_processor::parse_operator,
 };
 
 const DEFAULT_TOKENS_PER_CHAIN: Amount = Amount::from_millis(100);
@@ -29,6 +36,7 @@ const DEFAULT_TRANSACTIONS_PER_BLOCK: usize = 1;
 const DEFAULT_WRAP_UP_MAX_IN_FLIGHT: usize = 5;
 const DEFAULT_NUM_CHAINS: usize = 10;
 const DEFAULT_BPS: usize = 10;
+const DEFAULT_PAYLOAD_SIZE: usize = 100;
 
 /// Specification for a validator to be added to the committee.
 #[derive(Clone, Debug)]
@@ -62,6 +70,19 @@ impl std::str::FromStr for ValidatorToAdd {
     }
 }
 
+/// The kind of operations in the blocks of a benchmark.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BenchmarkWorkload {
+    /// Transfers of native tokens, or of the `--fungible-application-id` tokens.
+    #[default]
+    Transfers,
+    /// Operations of the `--application-id` application.
+    ApplicationCalls,
+    /// Publications of data blobs of `--payload-size` random bytes.
+    Blobs,
+}
+
 #[derive(Clone, clap::Args, serde::Serialize)]
 #[serde(rename_all = "kebab-case")]
 /// Options controlling the behavior of the benchmark command.
@@ -84,6 +105,24 @@ pub struct BenchmarkOptions {
     #[arg(long)]
     pub fungible_application_id: Option<ApplicationId>,
 
+    /// The kind of operations in the blocks.
+    #[arg(long, value_enum, default_value_t = BenchmarkWorkload::Transfers)]
+    pub workload: BenchmarkWorkload,
+
+    /// The application whose operations are executed, with `--workload application-calls`.
+    #[arg(long, required_if_eq("workload", "application-calls"))]
+    pub application_id: Option<ApplicationId>,
+
+    /// The serialized operation executed with `--workload application-calls`, as hex. By
+    /// default, each operation is a BCS-encoded byte vector of `--payload-size` random
+    /// bytes.
+    #[arg(long)]
+    pub application_operation: Option<String>,
+
+    /// The size in bytes of the random payload of each application operation or blob.
+    #[arg(long, default_value_t = DEFAULT_PAYLOAD_SIZE)]
+    pub payload_size: usize,
+
     /// The fixed BPS (Blocks Per Second) rate that block proposals will be sent at.
     #[arg(long, default_value_t = DEFAULT_BPS)]
     pub bps: usize,
@@ -94,6 +133,11 @@ pub struct BenchmarkOptions {
     #[arg(long)]
     pub close_chains: bool,
 
+    /// If provided, the chains closed after the benchmark are not made ephemeral, i.e.
+    /// validators keep their state instead of pruning it.
+    #[arg(long)]
+    pub persistent_chains: bool,
+
     /// A comma-separated list of host:port pairs to query for health metrics.
     /// If provided, the benchmark will check these endpoints for validator health
     /// and terminate if any validator is unhealthy.
@@ -144,8 +188,13 @@ impl Default for BenchmarkOptions {
             transactions_per_block: DEFAULT_TRANSACTIONS_PER_BLOCK,
             wrap_up_max_in_flight: DEFAULT_WRAP_UP_MAX_IN_FLIGHT,
             fungible_application_id: None,
+            workload: BenchmarkWorkload::Transfers,
+            application_id: None,
+            application_operation: None,
+            payload_size: DEFAULT_PAYLOAD_SIZE,
             bps: DEFAULT_BPS,
             close_chains: false,
+            persistent_chains: false,
             health_check_endpoints: None,
             confirm_before_start: false,
             runtime_in_seconds: None,
@@ -209,7 +258,8 @@ impl BenchmarkCommand {
 }
 
 use crate::util::{
-    DEFAULT_PAUSE_AFTER_GQL_MUTATIONS_SECS, DEFAULT_PAUSE_AFTER_LINERA_SERVICE_SECS,
+    DEFAULT_JAVASCRIPT_RUNNER, DEFAULT_PAUSE_AFTER_GQL_MUTATIONS_SECS,
+    DEFAULT_PAUSE_AFTER_LINERA_SERVICE_SECS, DEFAULT_PYTHON_RUNNER, DEFAULT_TYPESCRIPT_RUNNER,
 };
 
 /// Optional overrides for fields in the active resource control policy.
@@ -336,6 +386,33 @@ pub struct ResourceControlPolicyOverrides {
     #[arg(long)]
     pub http_request_timeout_ms: Option<u64>,
 
+    /// Set the maximum number of HTTP requests that an application can make in a block.
+    ///
+    /// Setting it enables the `HttpApplicationQuotas` protocol flag, which enforces the quotas.
+    #[arg(long)]
+    pub maximum_http_requests_per_application_per_block: Option<u64>,
+
+    /// Set the maximum number of bytes of HTTP responses that an application can receive in a
+    /// block.
+    ///
+    /// Setting it enables the `HttpApplicationQuotas` protocol flag, which enforces the quotas.
+    #[arg(long)]
+    pub maximum_http_response_bytes_per_application_per_block: Option<u64>,
+
+    /// Set the maximum number of HTTP requests that an application can make on a chain during
+    /// an epoch.
+    ///
+    /// Setting it enables the `HttpApplicationQuotas` protocol flag, which enforces the quotas.
+    #[arg(long)]
+    pub maximum_http_requests_per_application_per_epoch: Option<u64>,
+
+    /// Set the maximum number of bytes of HTTP responses that an application can receive on a
+    /// chain during an epoch.
+    ///
+    /// Setting it enables the `HttpApplicationQuotas` protocol flag, which enforces the quotas.
+    #[arg(long)]
+    pub maximum_http_response_bytes_per_application_per_epoch: Option<u64>,
+
     /// Set the list of hosts that contracts and services can send HTTP requests to.
     #[arg(long, value_delimiter = ',')]
     pub http_request_allow_list: Option<Vec<String>>,
@@ -364,6 +441,26 @@ pub enum ClientCommand {
 
         /// Amount to transfer
         amount: Amount,
+
+        /// Execute the block locally and report the resources it uses and its fees under
+        /// the current resource control policy, without submitting anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Transfer funds to several recipients in a single block.
+    ///
+    /// The transfers are read from a file: a JSON array of
+    /// `{"recipient": <ACCOUNT>, "amount": <AMOUNT>}` objects if its name ends in `.json`,
+    /// otherwise CSV lines `<ACCOUNT>,<AMOUNT>`, with an optional `recipient,amount`
+    /// header.
+    TransferMany {
+        /// Sending chain ID (must be one of our chains)
+        #[arg(long = "from")]
+        sender: Account,
+
+        /// The file listing the recipients and amounts
+        transfers_path: PathBuf,
     },
 
     /// Open (i.e. activate) a new chain deriving the UID from an existing one.
@@ -446,6 +543,16 @@ pub enum ClientCommand {
         owner: AccountOwner,
     },
 
+    /// Replace the wallet's owner key of a chain with a newly generated one.
+    ///
+    /// The new key takes the role and weight of the old one in the chain's ownership. The
+    /// wallet only switches to the new key once the ownership change is confirmed.
+    RotateKey {
+        /// The ID of the chain whose owner key will be rotated.
+        #[arg(long)]
+        chain_id: Option<ChainId>,
+    },
+
     /// Changes the application permissions configuration.
     ChangeApplicationPermissions {
         /// The ID of the chain to which the new permissions will be applied.
@@ -457,6 +564,20 @@ pub enum ClientCommand {
         application_permissions_config: ApplicationPermissionsConfig,
     },
 
+    /// Restrict the signature schemes the chain's owners may sign block proposals with.
+    ///
+    /// Fails if an owner could no longer sign with any of the allowed schemes.
+    ChangeSignatureSchemes {
+        /// The ID of the chain whose signature schemes will be changed.
+        #[arg(long)]
+        chain_id: Option<ChainId>,
+
+        /// A JSON list of the allowed signature schemes, e.g. `["Ed25519"]` or
+        /// `["EvmSecp256k1"]`. An empty list allows all schemes.
+        #[arg(long, value_parser = util::parse_json::<Vec<SignatureScheme>>)]
+        schemes: std::vec::Vec<SignatureScheme>,
+    },
+
     /// Close an existing chain.
     ///
     /// A closed chain cannot execute operations or accept messages anymore.
@@ -491,6 +612,16 @@ pub enum ClientCommand {
         /// chain balance. By default, we read the chain balance of the default chain in
         /// the wallet.
         account: Option<Account>,
+
+        /// Read the balance as it was right after the block at this height instead, by
+        /// re-executing the chain's blocks stored locally.
+        #[arg(long)]
+        at_height: Option<BlockHeight>,
+
+        /// Print a JSON statement of the output and the chain's latest block, signed by the
+        /// chain's key in the wallet, that others can check with `linera verify-output`.
+        #[arg(long)]
+        sign_output: bool,
     },
 
     /// Simulate the execution of one block made of pending messages from the local inbox,
@@ -503,6 +634,16 @@ pub enum ClientCommand {
         /// chain balance. By default, we read the chain balance of the default chain in
         /// the wallet.
         account: Option<Account>,
+
+        /// Read the balance as it was right after the block at this height instead, by
+        /// re-executing the chain's blocks stored locally.
+        #[arg(long)]
+        at_height: Option<BlockHeight>,
+
+        /// Print a JSON statement of the output and the chain's latest block, signed by the
+        /// chain's key in the wallet, that others can check with `linera verify-output`.
+        #[arg(long)]
+        sign_output: bool,
     },
 
     /// (DEPRECATED) Synchronize the local state of the chain with a quorum validators, then query the
@@ -514,12 +655,18 @@ pub enum ClientCommand {
         /// chain balance. By default, we read the chain balance of the default chain in
         /// the wallet.
         account: Option<Account>,
+
+        /// Print a JSON statement of the output and the chain's latest block, signed by the
+        /// chain's key in the wallet, that others can check with `linera verify-output`.
+        #[arg(long)]
+        sign_output: bool,
     },
 
     /// Synchronize the local state of the chain with a quorum validators.
     Sync {
         /// The chain to synchronize with validators. If omitted, synchronizes the
         /// default chain of the wallet.
+        #[arg(conflicts_with = "all")]
         chain_id: Option<ChainId>,
 
         /// Stop synchronizing at this block height (exclusive). For instance,
@@ -533,6 +680,15 @@ pub enum ClientCommand {
         /// `YYYY-MM-DD HH:MM:SS` in UTC.
         #[arg(long)]
         until_block_time: Option<Timestamp>,
+
+        /// Synchronize every chain in the wallet concurrently, and print a summary for each
+        /// chain.
+        #[arg(long)]
+        all: bool,
+
+        /// The maximum number of chains synchronized at the same time with `--all`.
+        #[arg(long, default_value = "8", requires = "all")]
+        parallelism: NonZeroUsize,
     },
 
     /// Process all pending incoming messages from the inbox of the given chain by creating as many
@@ -555,6 +711,15 @@ pub enum ClientCommand {
         epoch: Epoch,
     },
 
+    /// Create, list, vote on and resolve admin chain governance proposals.
+    #[command(subcommand)]
+    Governance(GovernanceCommand),
+
+    /// Update parts of the resource control policy, leaving the rest of it unchanged. Like
+    /// any policy change, each update registers a new committee in the next epoch.
+    #[command(subcommand)]
+    Policy(PolicyCommand),
+
     /// View or update the resource control policy
     ResourceControlPolicy {
         /// Overrides for individual resource control policy parameters.
@@ -740,6 +905,33 @@ pub enum ClientCommand {
         #[arg(long)]
         http_request_timeout_ms: Option<u64>,
 
+        /// Set the maximum number of HTTP requests that an application can make in a block.
+        ///
+        /// Setting it enables the `HttpApplicationQuotas` protocol flag, which enforces the quotas.
+        #[arg(long)]
+        maximum_http_requests_per_application_per_block: Option<u64>,
+
+        /// Set the maximum number of bytes of HTTP responses that an application can receive in a
+        /// block.
+        ///
+        /// Setting it enables the `HttpApplicationQuotas` protocol flag, which enforces the quotas.
+        #[arg(long)]
+        maximum_http_response_bytes_per_application_per_block: Option<u64>,
+
+        /// Set the maximum number of HTTP requests that an application can make on a chain during
+        /// an epoch.
+        ///
+        /// Setting it enables the `HttpApplicationQuotas` protocol flag, which enforces the quotas.
+        #[arg(long)]
+        maximum_http_requests_per_application_per_epoch: Option<u64>,
+
+        /// Set the maximum number of bytes of HTTP responses that an application can receive on a
+        /// chain during an epoch.
+        ///
+        /// Setting it enables the `HttpApplicationQuotas` protocol flag, which enforces the quotas.
+        #[arg(long)]
+        maximum_http_response_bytes_per_application_per_epoch: Option<u64>,
+
         /// Set the list of hosts that contracts and services can send HTTP requests to.
         #[arg(long, value_delimiter = ',')]
         http_request_allow_list: Option<Vec<String>>,
@@ -773,19 +965,24 @@ pub enum ClientCommand {
     },
 
     /// Run a GraphQL service to explore and extend the chains of the wallet.
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Service {
+        /// Commands to administer a running service.
+        #[command(subcommand)]
+        command: Option<ServiceCommand>,
+
         /// Configuration for the chain listener backing the service.
         #[command(flatten)]
         config: ChainListenerConfig,
 
         /// The port on which to run the server
-        #[arg(long)]
-        port: NonZeroU16,
+        #[arg(long, required = true)]
+        port: Option<NonZeroU16>,
 
         /// The port to expose metrics on.
         #[cfg(with_metrics)]
-        #[arg(long)]
-        metrics_port: NonZeroU16,
+        #[arg(long, required = true)]
+        metrics_port: Option<NonZeroU16>,
 
         /// Application IDs of operator applications to watch.
         /// When specified, a task processor is started alongside the node service.
@@ -840,6 +1037,54 @@ pub enum ClientCommand {
         /// new blocks or processing incoming messages.
         #[arg(long)]
         pause: bool,
+
+        /// Reload the service of an application when a module with the same contract
+        /// bytecode is published on one of the wallet's chains, so that a republished
+        /// service is used without restarting. Not effective with `--long-lived-services`.
+        #[arg(long)]
+        hot_reload_services: bool,
+
+        /// Path to a TOML file describing the routes of the webhook bridge. Each route maps
+        /// authenticated HTTP POST requests on `/webhooks/<name>` to a GraphQL mutation of
+        /// an application.
+        #[arg(long)]
+        webhook_config: Option<PathBuf>,
+
+        /// Path to a JSON file storing the durable cursors of block stream consumers. When
+        /// set, the `confirmedBlocks` subscription can resume from the last block
+        /// acknowledged by a consumer with the `acknowledgeBlocks` mutation.
+        #[arg(long)]
+        consumer_cursors: Option<PathBuf>,
+
+        /// Path to a JSON file of access tokens, managed with `linera service token`. When
+        /// set, mutations require an `Authorization: Bearer <token>` header with a token
+        /// whose scope allows them, while queries remain open. The file is read again when
+        /// it changes.
+        #[arg(long)]
+        access_tokens: Option<PathBuf>,
+
+        /// Options of the local index of application events.
+        #[command(flatten)]
+        event_index: EventIndexOptions,
+
+        /// Serve a small block explorer at `/explorer`, showing the wallet's chains, their
+        /// balances, latest blocks and event streams through the GraphQL API.
+        #[arg(long)]
+        explorer: bool,
+
+        /// Run as a read replica fed by a block exporter: receive the blocks of the chains on
+        /// this port, from an indexer destination of the exporter, instead of synchronizing
+        /// with the validators. Implies `--read-only` and `--pause`.
+        #[arg(long)]
+        exporter_feed_port: Option<NonZeroU16>,
+
+        /// Limits on the GraphQL queries, and persisted queries.
+        #[command(flatten)]
+        query_limits: QueryLimitOptions,
+
+        /// TLS and API keys, to expose the service beyond localhost.
+        #[command(flatten)]
+        security: ServiceSecurityOptions,
     },
 
     /// Query an application with a read-only GraphQL query.
@@ -854,11 +1099,29 @@ pub enum ClientCommand {
 
         /// The GraphQL query to send (e.g. "value" for a counter application).
         query: String,
+
+        /// Print a JSON statement of the output and the chain's latest block, signed by the
+        /// chain's key in the wallet, that others can check with `linera verify-output`.
+        #[arg(long)]
+        sign_output: bool,
+    },
+
+    /// Check the signature of an output printed with `--sign-output`, and that the signing
+    /// key is an owner of the chain, then print the output and the state of the chain it was
+    /// read from.
+    VerifyOutput {
+        /// The file containing the signed output. By default, it is read from standard input.
+        path: Option<PathBuf>,
     },
 
     /// Run a GraphQL service that exposes a faucet where users can claim tokens.
     /// This gives away the chain's tokens, and is mainly intended for testing.
+    #[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
     Faucet {
+        /// Offline commands for the operator of a faucet.
+        #[command(subcommand)]
+        command: Option<FaucetCommand>,
+
         /// The chain that gives away its tokens.
         chain_id: Option<ChainId>,
 
@@ -872,8 +1135,8 @@ pub enum ClientCommand {
         metrics_port: u16,
 
         /// The number of tokens to send to each new chain.
-        #[arg(long)]
-        amount: Amount,
+        #[arg(long, required = true)]
+        amount: Option<Amount>,
 
         /// The number of tokens to send per daily claim. Set to 0 to disable daily claims.
         #[arg(long, default_value = "0")]
@@ -889,21 +1152,52 @@ pub enum ClientCommand {
         config: ChainListenerConfig,
 
         /// Path to the persistent storage file for faucet mappings.
-        #[arg(long)]
-        storage_path: PathBuf,
+        #[arg(long, required = true)]
+        storage_path: Option<PathBuf>,
 
         /// Maximum number of operations to include in a single block (default: 100).
         #[arg(long, default_value = "100")]
         max_batch_size: usize,
+
+        /// Require an invite code signed by this owner to claim a chain. Invite codes are
+        /// minted with `linera faucet mint-invites`.
+        #[arg(long)]
+        invite_signer: Option<AccountOwner>,
+
+        /// The maximum number of claim requests from the same IP address within the quota
+        /// window. Unlimited by default.
+        #[arg(long)]
+        max_requests_per_ip: Option<NonZeroU32>,
+
+        /// The maximum number of claim requests for the same owner within the quota window.
+        /// Unlimited by default.
+        #[arg(long)]
+        max_requests_per_owner: Option<NonZeroU32>,
+
+        /// The length of the sliding window over which claim requests are counted, in
+        /// seconds.
+        #[arg(long, default_value = "3600", value_parser = util::parse_secs)]
+        quota_window: Duration,
+
+        /// Take the client's IP address from the `X-Forwarded-For` header, when the faucet
+        /// runs behind a trusted reverse proxy.
+        #[arg(long)]
+        trust_forwarded_for: bool,
     },
 
     /// Publish module.
     PublishModule {
-        /// Path to the Wasm file for the application "contract" bytecode.
-        contract: PathBuf,
-
-        /// Path to the Wasm file for the application "service" bytecode.
-        service: PathBuf,
+        /// The application "contract" bytecode: the path to a Wasm file, an
+        /// `https://` URL or an `oci://<registry>/<repository>[:<tag>]` reference. Remote
+        /// bytecode can be pinned with `#sha256=<hex>` after a URL or `@sha256:<hex>` after
+        /// an OCI reference, and is cached locally.
+        contract: BytecodeSource,
+
+        /// The application "service" bytecode: the path to a Wasm file, an
+        /// `https://` URL or an `oci://<registry>/<repository>[:<tag>]` reference. Remote
+        /// bytecode can be pinned with `#sha256=<hex>` after a URL or `@sha256:<hex>` after
+        /// an OCI reference, and is cached locally.
+        service: BytecodeSource,
 
         /// The virtual machine runtime to use.
         #[arg(long, default_value = "wasm")]
@@ -920,6 +1214,41 @@ pub enum ClientCommand {
         /// An optional chain ID to publish the module. The default chain of the wallet
         /// is used otherwise.
         publisher: Option<ChainId>,
+
+        /// Execute the block locally and report the resources it uses and its fees under
+        /// the current resource control policy, without submitting anything.
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Publish a signed attestation of the provenance of the module, i.e. who built it
+        /// from which source and toolchain, in the same block as the module. It can be
+        /// checked with `linera module verify`.
+        #[arg(long, conflicts_with = "dry_run")]
+        attest: bool,
+
+        /// The revision of the source code the module was built from. Defaults to the
+        /// output of `git rev-parse HEAD`.
+        #[arg(long, requires = "attest")]
+        source_commit: Option<String>,
+
+        /// The repository of the source code, e.g. its URL.
+        #[arg(long, requires = "attest")]
+        source_repository: Option<String>,
+
+        /// The toolchain the module was built with. Defaults to the output of
+        /// `rustc --version`.
+        #[arg(long, requires = "attest")]
+        toolchain: Option<String>,
+
+        /// The identity of the builder, e.g. the URL of a CI run. Defaults to the owner
+        /// signing the attestation.
+        #[arg(long, requires = "attest")]
+        builder_id: Option<String>,
+
+        /// The owner whose key signs the attestation. Defaults to the preferred owner of
+        /// the publisher chain.
+        #[arg(long, requires = "attest")]
+        attestation_signer: Option<AccountOwner>,
     },
 
     /// Print events from a specific chain and stream from a specified index.
@@ -936,13 +1265,41 @@ pub enum ClientCommand {
         start_index: u32,
     },
 
+    /// Follow a stream of events of a chain, printing each event as a line of JSON as soon
+    /// as it is emitted, until interrupted.
+    SubscribeEvents {
+        /// The chain to follow. If omitted, follow the default chain of the wallet.
+        chain_id: Option<ChainId>,
+
+        /// The stream to follow.
+        #[arg(long)]
+        stream_id: StreamId,
+
+        /// The index of the first event to print, e.g. the one after the last event a
+        /// previous run printed. If omitted, only new events are printed.
+        #[arg(long)]
+        from_index: Option<u32>,
+    },
+
     /// Publish a data blob of binary data.
+    ///
+    /// With `--chunked`, the file is split into several data blobs, published over as many
+    /// blocks as needed, and the hash of a manifest blob listing them is printed. Use
+    /// `read-data-blob --reassemble` to download the file again.
     PublishDataBlob {
         /// Path to data blob file to be published.
         blob_path: PathBuf,
         /// An optional chain ID to publish the blob. The default chain of the wallet
         /// is used otherwise.
         publisher: Option<ChainId>,
+        /// Split the file into chunks, for files larger than the maximum blob or block
+        /// proposal size.
+        #[arg(long)]
+        chunked: bool,
+        /// The maximum size of a chunk, in bytes. Defaults to 500 kB, or the maximum blob
+        /// size if smaller.
+        #[arg(long, requires = "chunked")]
+        chunk_size: Option<u64>,
     },
 
     // TODO(#2490): Consider removing or renaming this.
@@ -953,6 +1310,14 @@ pub enum ClientCommand {
         /// An optional chain ID to verify the blob. The default chain of the wallet
         /// is used otherwise.
         reader: Option<ChainId>,
+        /// Treat the blob as the manifest of a chunked data blob: download its chunks and
+        /// write the file they form.
+        #[arg(long)]
+        reassemble: bool,
+        /// Where to write the reassembled file. It is written to the standard output
+        /// otherwise.
+        #[arg(long, requires = "reassemble")]
+        output: Option<PathBuf>,
     },
 
     /// Describe an existing application: print its `ApplicationDescription` (module
@@ -992,15 +1357,32 @@ pub enum ClientCommand {
         /// The list of required dependencies of application, if any.
         #[arg(long, num_args(0..))]
         required_application_ids: Option<Vec<ApplicationId>>,
+
+        /// Estimate the fees of the instantiation and, if the creator chain cannot pay
+        /// for them, first transfer the shortfall to it from the given treasury chain
+        /// (must be one of our chains).
+        #[arg(long = "auto-fund", value_name = "TREASURY_CHAIN_ID")]
+        auto_fund: Option<ChainId>,
+
+        /// Execute the block locally and report the resources it uses and its fees under
+        /// the current resource control policy, without submitting anything.
+        #[arg(long, conflicts_with = "auto_fund")]
+        dry_run: bool,
     },
 
     /// Create an application, and publish the required module.
     PublishAndCreate {
-        /// Path to the Wasm file for the application "contract" bytecode.
-        contract: PathBuf,
-
-        /// Path to the Wasm file for the application "service" bytecode.
-        service: PathBuf,
+        /// The application "contract" bytecode: the path to a Wasm file, an
+        /// `https://` URL or an `oci://<registry>/<repository>[:<tag>]` reference. Remote
+        /// bytecode can be pinned with `#sha256=<hex>` after a URL or `@sha256:<hex>` after
+        /// an OCI reference, and is cached locally.
+        contract: BytecodeSource,
+
+        /// The application "service" bytecode: the path to a Wasm file, an
+        /// `https://` URL or an `oci://<registry>/<repository>[:<tag>]` reference. Remote
+        /// bytecode can be pinned with `#sha256=<hex>` after a URL or `@sha256:<hex>` after
+        /// an OCI reference, and is cached locally.
+        service: BytecodeSource,
 
         /// The virtual machine runtime to use.
         #[arg(long, default_value = "wasm")]
@@ -1029,6 +1411,12 @@ pub enum ClientCommand {
         /// The list of required dependencies of application, if any.
         #[arg(long, num_args(0..))]
         required_application_ids: Option<Vec<ApplicationId>>,
+
+        /// Execute the publication of the module and the creation of the application
+        /// locally, in a single block, and report the resources they use and their fees
+        /// under the current resource control policy, without submitting anything.
+        #[arg(long)]
+        dry_run: bool,
     },
 
     /// Create an unassigned key pair.
@@ -1039,12 +1427,24 @@ pub enum ClientCommand {
     /// otherwise block proposals will fail when signing with it.
     Assign {
         /// The owner to assign.
-        #[arg(long)]
-        owner: AccountOwner,
+        #[arg(long, required_unless_present = "external_evm")]
+        owner: Option<AccountOwner>,
+
+        /// Assign instead the owner with this EVM address, whose key stays in an external
+        /// wallet such as MetaMask. Block proposals are then signed in that wallet, through
+        /// the signing page.
+        #[arg(long, conflicts_with = "owner")]
+        external_evm: Option<alloy_primitives::Address>,
 
         /// The ID of the chain.
         #[arg(long)]
         chain_id: ChainId,
+
+        /// Before linking the owner, check that its key can sign: it must sign a random
+        /// challenge, as an EIP-191 message for EVM keys. Without this, an owner whose key
+        /// is missing is only detected when a block proposal fails to be signed.
+        #[arg(long)]
+        verify: bool,
     },
 
     /// Retry a block we unsuccessfully tried to propose earlier.
@@ -1056,6 +1456,42 @@ pub enum ClientCommand {
         chain_id: Option<ChainId>,
     },
 
+    /// Re-execute a confirmed block locally against the state of its chain just before it,
+    /// and report how the outcome differs from the certified one.
+    ///
+    /// The block and the earlier blocks of its chain must be in the client's storage, e.g.
+    /// after `linera sync`. Exits with an error if the outcomes differ.
+    ReplayBlock {
+        /// The hash of the certificate of the block.
+        #[arg(long)]
+        certificate: CryptoHash,
+
+        /// Reuse the recorded oracle responses instead of querying the oracles again.
+        #[arg(long)]
+        replay_oracle_responses: bool,
+    },
+
+    /// Show a confirmed block certificate in decoded form: its transactions with the
+    /// messages, events, oracle responses and blobs they produced, and its signatures with
+    /// the weights of the validators.
+    ///
+    /// The certificate is read from the client's storage, or else downloaded from the
+    /// validators and checked, without being processed.
+    InspectBlock {
+        /// The hash of the certificate.
+        #[arg(long, conflicts_with = "height", required_unless_present = "height")]
+        certificate: Option<CryptoHash>,
+
+        /// The chain of the block, if it is given by its height. If not specified, the
+        /// wallet's default chain is used.
+        #[arg(long, requires = "height")]
+        chain_id: Option<ChainId>,
+
+        /// The height of the block.
+        #[arg(long)]
+        height: Option<BlockHeight>,
+    },
+
     /// Execute a raw user operation on an application.
     ///
     /// The operation bytes are provided as a hex string (BCS-encoded).
@@ -1073,14 +1509,86 @@ pub enum ClientCommand {
         chain_id: Option<ChainId>,
     },
 
+    /// Propose the operations of several subcommands in a single block, so that they are
+    /// committed together or not at all.
+    ///
+    /// Each subcommand is given as one quoted argument, e.g.
+    /// `linera batch 'transfer 1 --from <CHAIN> --to <ACCOUNT>' 'publish-data-blob data.bin'`.
+    /// The subcommands `transfer`, `publish-data-blob`, `create-application` and
+    /// `execute-operation` are supported, and must all act on the same chain. Prints the
+    /// hash of each published data blob and the ID of each created application, in order.
+    Batch {
+        /// The subcommands to batch, each as a single argument.
+        #[arg(required = true)]
+        commands: Vec<String>,
+
+        /// The chain to propose the block on. Defaults to the chain given to the
+        /// subcommands, or to the wallet's default chain.
+        #[arg(long)]
+        chain_id: Option<ChainId>,
+    },
+
+    /// Prepare a block proposal to be signed offline, e.g. on an air-gapped machine.
+    ///
+    /// The block is made of the operations of the given subcommands, as with `linera batch`,
+    /// and is executed locally without being committed. The proposal is written to the
+    /// `--payload` file as JSON and its signing hash is printed: the owner signs that hash,
+    /// and `linera submit-signed` submits the proposal with the signature.
+    SignProposal {
+        /// The subcommands to propose, each as a single argument.
+        #[arg(required = true)]
+        commands: Vec<String>,
+
+        /// The chain to propose the block on. Defaults to the chain given to the
+        /// subcommands, or to the wallet's default chain.
+        #[arg(long)]
+        chain_id: Option<ChainId>,
+
+        /// The owner who will sign the proposal. Defaults to the owner of the chain in the
+        /// wallet.
+        #[arg(long)]
+        owner: Option<AccountOwner>,
+
+        /// The file to write the proposal to.
+        #[arg(long)]
+        payload: PathBuf,
+    },
+
+    /// Submit a block proposal prepared by `linera sign-proposal`, with a signature of its
+    /// signing hash by the owner, and wait for the block to be confirmed.
+    SubmitSigned {
+        /// The file the proposal was written to.
+        #[arg(long)]
+        payload: PathBuf,
+
+        /// The BCS-encoded signature as a hex string.
+        #[arg(long)]
+        signature: String,
+    },
+
     /// Show the contents of the wallet.
     #[command(subcommand)]
     Wallet(WalletCommand),
 
+    /// Show the `linera.toml` configuration files providing defaults for the options.
+    ///
+    /// A `linera.toml` file maps the long names of the options of `linera` (not those of its
+    /// subcommands) to their values, e.g. `storage = "rocksdb:linera.db"`. The user-level
+    /// file lives in the Linera configuration directory and the project-level file is the
+    /// nearest `linera.toml` in the current directory or its parents. Options given on the
+    /// command line or through environment variables take precedence over the project-level
+    /// file, which takes precedence over the user-level file.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+
     /// Show the information about a chain.
     #[command(subcommand)]
     Chain(ChainCommand),
 
+    /// Inspect published modules.
+    #[command(subcommand)]
+    Module(ModuleCommand),
+
     /// Manage Linera projects.
     #[command(subcommand)]
     Project(ProjectCommand),
@@ -1097,17 +1605,33 @@ pub enum ClientCommand {
     #[command(subcommand)]
     Storage(DatabaseToolCommand),
 
+    /// Tools for developers of Linera and of other implementations of its protocol.
+    #[command(subcommand)]
+    Dev(DevCommand),
+
     /// Print CLI help in Markdown format, and exit.
     #[command(hide = true)]
     HelpMarkdown,
 
-    /// Extract a Bash and GraphQL script embedded in a markdown file and print it on
-    /// `stdout`.
+    /// Extract a Bash script running the Bash, GraphQL, TypeScript, JavaScript and Python
+    /// code blocks embedded in a markdown file and print it on `stdout`.
     #[command(hide = true)]
     ExtractScriptFromMarkdown {
         /// The source file
         path: PathBuf,
 
+        /// The command running TypeScript code blocks, given the path of a file.
+        #[arg(long, default_value = DEFAULT_TYPESCRIPT_RUNNER)]
+        typescript_runner: String,
+
+        /// The command running JavaScript code blocks, given the path of a file.
+        #[arg(long, default_value = DEFAULT_JAVASCRIPT_RUNNER)]
+        javascript_runner: String,
+
+        /// The command running Python code blocks, given the path of a file.
+        #[arg(long, default_value = DEFAULT_PYTHON_RUNNER)]
+        python_runner: String,
+
         /// Insert a pause of N seconds after calls to `linera service`.
         #[arg(long, default_value = DEFAULT_PAUSE_AFTER_LINERA_SERVICE_SECS, value_parser = util::parse_secs)]
         pause_after_linera_service: Duration,
@@ -1130,12 +1654,15 @@ impl ClientCommand {
     pub fn log_file_name(&self) -> Cow<'static, str> {
         match self {
             ClientCommand::Transfer { .. }
+            | ClientCommand::TransferMany { .. }
             | ClientCommand::OpenChain { .. }
             | ClientCommand::OpenMultiOwnerChain { .. }
             | ClientCommand::ShowOwnership { .. }
             | ClientCommand::ChangeOwnership { .. }
             | ClientCommand::SetPreferredOwner { .. }
+            | ClientCommand::RotateKey { .. }
             | ClientCommand::ChangeApplicationPermissions { .. }
+            | ClientCommand::ChangeSignatureSchemes { .. }
             | ClientCommand::CloseChain { .. }
             | ClientCommand::Checkpoint { .. }
             | ClientCommand::ShowNetworkDescription
@@ -1145,11 +1672,14 @@ impl ClientCommand {
             | ClientCommand::Sync { .. }
             | ClientCommand::ProcessInbox { .. }
             | ClientCommand::QueryShardInfo { .. }
+            | ClientCommand::Governance { .. }
+            | ClientCommand::Policy { .. }
             | ClientCommand::ResourceControlPolicy { .. }
             | ClientCommand::RevokeEpochs { .. }
             | ClientCommand::CreateGenesisConfig { .. }
             | ClientCommand::PublishModule { .. }
             | ClientCommand::ListEventsFromIndex { .. }
+            | ClientCommand::SubscribeEvents { .. }
             | ClientCommand::PublishDataBlob { .. }
             | ClientCommand::ReadDataBlob { .. }
             | ClientCommand::DescribeApplication { .. }
@@ -1159,25 +1689,64 @@ impl ClientCommand {
             | ClientCommand::Assign { .. }
             | ClientCommand::Wallet { .. }
             | ClientCommand::Chain { .. }
+            | ClientCommand::Module { .. }
             | ClientCommand::Validator { .. }
             | ClientCommand::RetryPendingBlock { .. }
-            | ClientCommand::QueryApplication { .. } => "client".into(),
-            ClientCommand::ExecuteOperation { .. } => "client".into(),
+            | ClientCommand::ReplayBlock { .. }
+            | ClientCommand::InspectBlock { .. }
+            | ClientCommand::QueryApplication { .. }
+            | ClientCommand::VerifyOutput { .. } => "client".into(),
+            ClientCommand::ExecuteOperation { .. }
+            | ClientCommand::Batch { .. }
+            | ClientCommand::SignProposal { .. }
+            | ClientCommand::SubmitSigned { .. } => "client".into(),
             ClientCommand::Benchmark(BenchmarkCommand::Single { .. }) => "single-benchmark".into(),
             ClientCommand::Benchmark(BenchmarkCommand::Multi { .. }) => "multi-benchmark".into(),
             ClientCommand::Net { .. } => "net".into(),
             ClientCommand::Project { .. } => "project".into(),
             ClientCommand::Watch { .. } => "watch".into(),
             ClientCommand::Storage { .. } => "storage".into(),
-            ClientCommand::Service { port, .. } => format!("service-{port}").into(),
+            ClientCommand::Service {
+                command: Some(_), ..
+            } => "tool".into(),
+            ClientCommand::Service { port, .. } => match port {
+                Some(port) => format!("service-{port}").into(),
+                None => "service".into(),
+            },
             ClientCommand::Faucet { .. } => "faucet".into(),
-            ClientCommand::HelpMarkdown
+            ClientCommand::Dev(_)
+            | ClientCommand::Config(_)
+            | ClientCommand::HelpMarkdown
             | ClientCommand::ExtractScriptFromMarkdown { .. }
             | ClientCommand::Completion { .. } => "tool".into(),
         }
     }
 }
 
+#[derive(Clone, clap::Subcommand)]
+/// The subcommands for developer tools.
+pub enum DevCommand {
+    /// Print canonical BCS encodings and hashes of representative protocol structures
+    /// (operations, signatures for both key schemes, proposals and certificates) as a
+    /// versioned JSON suite, to check the conformance of other client implementations.
+    GenTestVectors {
+        /// The file to write the test vectors to. Defaults to `stdout`.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// The subcommands for the `linera.toml` configuration files.
+#[derive(Clone, clap::Subcommand)]
+pub enum ConfigCommand {
+    /// Show the configuration files and the options they set.
+    Show {
+        /// Instead, show the effective value of every option and where it comes from.
+        #[arg(long)]
+        effective: bool,
+    },
+}
+
 #[derive(Clone, clap::Parser)]
 /// The subcommands for managing the storage database.
 pub enum DatabaseToolCommand {
@@ -1208,9 +1777,47 @@ pub enum DatabaseToolCommand {
 
     /// List the event IDs in the database
     ListEventIds,
+
+    /// Check the consistency of the chains, certificates and blobs in the database, e.g.
+    /// after a crash or a restore. The validator or client using the database must be
+    /// stopped. Exits with status 1 if inconsistencies remain.
+    Fsck {
+        /// Repair the inconsistencies that can be repaired locally.
+        #[arg(long)]
+        repair: bool,
+    },
+
+    /// Export the blocks, operations, messages, and balances of a chain to Parquet files,
+    /// for analytics with e.g. DuckDB or Spark. Each export only adds the blocks created
+    /// since the previous export to the same directory.
+    ExportParquet {
+        /// The chain to export.
+        #[arg(long)]
+        chain: ChainId,
+
+        /// The directory of the Parquet files, with one subdirectory per table.
+        #[arg(long)]
+        output: PathBuf,
+    },
+
+    /// Export the whole namespace, i.e. the chain states, certificates and blobs, to a
+    /// compressed snapshot file, e.g. for backups or to move the storage to another machine.
+    /// The validator or client using the database must be stopped.
+    ExportSnapshot {
+        /// The snapshot file to create.
+        #[arg(long)]
+        output: PathBuf,
+    },
+
+    /// Import a snapshot created by `export-snapshot` into a new namespace, possibly in
+    /// another kind of database.
+    ImportSnapshot {
+        /// The snapshot file to import.
+        #[arg(long)]
+        input: PathBuf,
+    },
 }
 
-#[expect(clippy::large_enum_variant)]
 #[derive(Clone, clap::Parser)]
 /// The subcommands for managing a local Linera network.
 pub enum NetCommand {
@@ -1294,6 +1901,79 @@ pub enum NetCommand {
         /// Set the list of hosts that contracts and services can send HTTP requests to.
         #[arg(long, value_delimiter = ',')]
         http_request_allow_list: Option<Vec<String>>,
+
+        /// Wait until the validators, the faucet, and the block exporter pass their health
+        /// checks, then print a readiness report in JSON on stdout, instead of the `export`
+        /// lines. The report is also written to `ready.json` in the working directory, for
+        /// `linera net wait-ready`.
+        #[arg(long)]
+        wait_ready: bool,
+
+        /// How long each service may take to pass its health check with `--wait-ready`, in
+        /// seconds.
+        #[arg(long, default_value = "120", value_parser = util::parse_secs)]
+        ready_timeout: Duration,
+
+        /// Deploy the validators on the remote hosts listed in this inventory file, over
+        /// SSH, instead of running them locally. The file is in TOML and lists the proxy
+        /// host, the shards and the storage of each validator. Binaries and configuration
+        /// files are copied with `scp`, and the logs of the services are collected in the
+        /// `logs` directory of `--path` when the network is terminated.
+        #[arg(long, conflicts_with_all = ["validators", "proxies", "shards", "with_block_exporter"])]
+        ssh_inventory: Option<PathBuf>,
+
+        /// Run the network in virtual time: the validators and clients follow a virtual clock
+        /// instead of the system clock, which only moves forward when advanced through the
+        /// admin endpoint, e.g. to test vesting or auctions without waiting. Clients outside
+        /// the network must set the printed `LINERA_VIRTUAL_TIME` variable to follow it too.
+        #[arg(long, conflicts_with = "ssh_inventory")]
+        virtual_time: bool,
+
+        /// The local port of the admin endpoint of the virtual time: `GET /time` returns it,
+        /// and `POST /advance` with a JSON body `{"micros": N}` moves it forward.
+        #[arg(long, default_value = "8082")]
+        virtual_time_port: NonZeroU16,
+    },
+
+    /// Wait until a local network started with `linera net up --wait-ready` is ready, then
+    /// print its readiness report in JSON and exit. Exits with an error if the network is
+    /// not ready in time.
+    WaitReady {
+        /// The working directory of the local network, as given to `linera net up --path`.
+        #[arg(long)]
+        path: PathBuf,
+
+        /// How long to wait for the network, in seconds.
+        #[arg(long, default_value = "300", value_parser = util::parse_secs)]
+        timeout: Duration,
+    },
+
+    /// Save the validators' storage, wallets, and genesis configuration of a local network
+    /// started with `linera net up` into a snapshot directory. The network should be idle
+    /// while the snapshot is taken.
+    Snapshot {
+        /// The directory where the snapshot is created. It must not exist yet.
+        dir: PathBuf,
+
+        /// The working directory of the local network. If none, then the directory
+        /// containing the wallet is used.
+        #[arg(long)]
+        path: Option<PathBuf>,
+    },
+
+    /// Start a local network from a snapshot created by `linera net snapshot`.
+    Restore {
+        /// The directory containing the snapshot.
+        dir: PathBuf,
+
+        /// Run with a specific path where the restored wallet and validator input files
+        /// are written. If none, then a temporary directory is created.
+        #[arg(long)]
+        path: Option<String>,
+
+        /// The configuration for cross-chain messages.
+        #[clap(flatten)]
+        cross_chain_config: CrossChainConfig,
     },
 
     /// Print a bash helper script to make `linera net up` easier to use. The script is
@@ -1301,6 +1981,117 @@ pub enum NetCommand {
     Helper,
 }
 
+#[derive(Clone, clap::Subcommand)]
+/// The offline subcommands for the operator of a faucet.
+pub enum FaucetCommand {
+    /// Mint signed, single-use invite codes for a faucet started with `--invite-signer`.
+    /// The codes are printed one per line.
+    MintInvites {
+        /// The number of invite codes to mint.
+        #[arg(long, default_value = "1")]
+        count: usize,
+
+        /// The owner signing the invites, whose key must be in the keystore. Defaults to the
+        /// owner of the default chain.
+        #[arg(long)]
+        signer: Option<AccountOwner>,
+
+        /// The number of tokens granted by each invite, instead of the faucet's `--amount`.
+        #[arg(long)]
+        amount: Option<Amount>,
+
+        /// The time after which the invites can no longer be redeemed.
+        #[arg(long)]
+        expires_at: Option<DateTime<Utc>>,
+    },
+}
+
+#[derive(Clone, clap::Subcommand)]
+/// The subcommands administering a running node service or validator.
+pub enum ServiceCommand {
+    /// Change the log filter of a running node service, or of a validator server or proxy
+    /// through its metrics port, without restarting it. Prints the new filter.
+    SetLogLevel {
+        /// The new filter, in the syntax of `RUST_LOG`, e.g. `linera_core=debug,info` to
+        /// log the `linera_core` crate at the debug level and everything else at the info
+        /// level.
+        filter: String,
+
+        /// The URL of the node service, or of the metrics endpoint of a validator server or
+        /// proxy.
+        #[arg(long, default_value = "http://localhost:8080")]
+        url: String,
+    },
+
+    /// Manage the access tokens restricting the mutations of a node service.
+    #[command(subcommand)]
+    Token(AccessTokenCommand),
+}
+
+#[derive(Clone, clap::Subcommand)]
+/// The subcommands managing the access tokens of a node service.
+pub enum AccessTokenCommand {
+    /// Issue a new access token and print it. Only a hash of the token is stored, so it
+    /// cannot be printed again.
+    Issue {
+        /// The file of access tokens, created if missing.
+        #[arg(long)]
+        access_tokens: PathBuf,
+
+        /// A unique name for the token.
+        name: String,
+
+        /// A chain on which the token allows mutations. Can be repeated. Any chain if
+        /// omitted.
+        #[arg(long = "chain")]
+        chains: Vec<ChainId>,
+
+        /// An application whose mutations the token allows. Can be repeated.
+        #[arg(long = "application")]
+        applications: Vec<ApplicationId>,
+
+        /// Allow the mutations of every application.
+        #[arg(long, conflicts_with = "applications")]
+        any_application: bool,
+
+        /// Allow the mutations of the node service itself, e.g. transfers.
+        #[arg(long)]
+        system: bool,
+
+        /// The name of a mutation the token allows. Can be repeated. Any mutation if
+        /// omitted.
+        #[arg(long = "mutation")]
+        mutations: Vec<String>,
+    },
+
+    /// Replace the secret of an access token, keeping its scope, and print the new token.
+    Rotate {
+        /// The file of access tokens.
+        #[arg(long)]
+        access_tokens: PathBuf,
+
+        /// The name of the token.
+        name: String,
+    },
+
+    /// Revoke an access token.
+    Revoke {
+        /// The file of access tokens.
+        #[arg(long)]
+        access_tokens: PathBuf,
+
+        /// The name of the token.
+        name: String,
+    },
+
+    /// Print the names and scopes of the access tokens.
+    List {
+        /// The file of access tokens.
+        #[arg(long)]
+        access_tokens: PathBuf,
+    },
+}
+
 #[derive(Clone, clap::Subcommand)]
 /// The subcommands for managing the wallet.
 pub enum WalletCommand {
@@ -1314,6 +2105,14 @@ pub enum WalletCommand {
         /// Print only the chains that we have a key pair for.
         #[arg(long)]
         owned: bool,
+        /// Show a live dashboard of the chains' balances, heights, pending proposals and
+        /// inboxes, updated from the validators' notifications. Press `q` to quit.
+        #[arg(long, conflicts_with = "short")]
+        watch: bool,
+        /// Query the balance and height of each chain from the validators, and print them
+        /// with their totals. Chains are queried concurrently.
+        #[arg(long, conflicts_with_all = ["short", "watch"])]
+        with_balances: bool,
     },
 
     /// Change the wallet default chain.
@@ -1339,6 +2138,15 @@ pub enum WalletCommand {
         /// TESTING ONLY.
         #[arg(long)]
         testing_prng_seed: Option<u64>,
+
+        /// Read the genesis configuration, the keys and the chains of the wallet from the
+        /// environment variables `LINERA_GENESIS_CONFIG`, `LINERA_KEYS`, `LINERA_CHAINS` and
+        /// `LINERA_DEFAULT_CHAIN`, or from the files named by the same variables with a
+        /// `_FILE` suffix, e.g. mounted secrets. No keystore file is written: later commands
+        /// read the keys from the environment too. With `--wallet-backend storage`, nothing
+        /// is written outside the storage.
+        #[arg(long, conflicts_with = "testing_prng_seed")]
+        from_env: bool,
     },
 
     /// Request a new chain from a faucet and add it to the wallet.
@@ -1388,6 +2196,64 @@ pub enum WalletCommand {
         /// The chain to forget.
         chain_id: ChainId,
     },
+    /// Copy the wallet file into the client's storage, to use it with
+    /// `--wallet-backend storage`.
+    Migrate,
+
+    /// Write the wallet and its keystore, including the secret keys, to an archive
+    /// encrypted with a passphrase.
+    ///
+    /// The passphrase is read from the `LINERA_WALLET_PASSPHRASE` environment variable,
+    /// or from the file given with `--passphrase-file`.
+    Export {
+        /// Path of the archive to create.
+        output: PathBuf,
+
+        /// Read the passphrase from the first line of this file.
+        #[arg(long)]
+        passphrase_file: Option<PathBuf>,
+    },
+
+    /// Restore the wallet and its keystore from an archive created with `wallet export`.
+    ///
+    /// The wallet and keystore files must not exist yet. The passphrase is read as for
+    /// `wallet export`.
+    Import {
+        /// Path of the archive to restore.
+        input: PathBuf,
+
+        /// Read the passphrase from the first line of this file.
+        #[arg(long)]
+        passphrase_file: Option<PathBuf>,
+    },
+
+    /// Print a statement of the tokens sent, received and paid as fees by the blocks of
+    /// the wallet's chains, from its local spending journal.
+    ///
+    /// Fees are only included for the blocks that this wallet proposed, and not e.g. for
+    /// the blocks of other owners of a shared chain.
+    Statement {
+        /// Only include the movements of this chain.
+        chain_id: Option<ChainId>,
+
+        /// The first day to include, in UTC, e.g. `2026-01-01`.
+        #[arg(long)]
+        from: Option<NaiveDate>,
+
+        /// The last day to include, in UTC.
+        #[arg(long)]
+        to: Option<NaiveDate>,
+
+        /// Print the statement as CSV, with one debit or credit per line.
+        #[arg(long)]
+        csv: bool,
+
+        /// A CSV file with the value of one token in another currency, as lines
+        /// `date,rate`. Each movement is also valued at the latest rate on or before its
+        /// day.
+        #[arg(long)]
+        exchange_rates: Option<PathBuf>,
+    },
 }
 
 #[derive(Clone, clap::Subcommand)]
@@ -1410,6 +2276,101 @@ pub enum ChainCommand {
     },
 }
 
+#[derive(Clone, clap::Subcommand)]
+/// The subcommands for inspecting published modules.
+pub enum ModuleCommand {
+    /// Verify the provenance attestations of a module published with
+    /// `linera publish-module --attest`, and show who signed them and what they state.
+    ///
+    /// The attestations are looked up in the block that published the module, which must be
+    /// in the local storage, e.g. after synchronizing the publisher chain.
+    Verify {
+        /// The module to verify.
+        module_id: ModuleId,
+
+        /// The hash of an attestation blob to verify, e.g. one published after the module.
+        /// Can be repeated. If omitted, the attestations published with the module are
+        /// verified.
+        #[arg(long)]
+        attestation: Vec<CryptoHash>,
+    },
+}
+
+#[derive(Clone, clap::Subcommand)]
+/// The subcommands for updating parts of the resource control policy.
+///
+/// Unlike `resource-control-policy`, which replaces whole lists, these add or remove single
+/// entries, and fail rather than undo a concurrent change of the committee.
+pub enum PolicyCommand {
+    /// Allow contracts and services to send HTTP requests to the given hosts.
+    AllowHttp {
+        /// The hosts to allow.
+        #[arg(required = true)]
+        hosts: Vec<String>,
+
+        /// Only allow the given application to send requests to the hosts, instead of all
+        /// applications.
+        #[arg(long)]
+        application_id: Option<ApplicationId>,
+    },
+
+    /// No longer allow contracts and services to send HTTP requests to the given hosts.
+    DisallowHttp {
+        /// The hosts to no longer allow.
+        #[arg(required = true)]
+        hosts: Vec<String>,
+
+        /// Remove the hosts that were allowed for the given application only, instead of
+        /// those allowed for all applications.
+        #[arg(long)]
+        application_id: Option<ApplicationId>,
+    },
+}
+
+#[derive(Clone, clap::Subcommand)]
+/// The subcommands for admin chain governance.
+///
+/// Unlike `resource-control-policy` and the `validator` commands, which install a new
+/// committee right away, proposals only take effect once a majority of the owners of the
+/// admin chain approved them during the voting period.
+pub enum GovernanceCommand {
+    /// Print the current committee as JSON, to be edited into a proposal.
+    ShowCommittee,
+
+    /// Propose to start using a new committee, i.e. new validators or a new resource
+    /// control policy.
+    Propose {
+        /// The path to the JSON file describing the proposed committee, in the format
+        /// printed by `show-committee`.
+        #[arg(long = "committee")]
+        committee_path: PathBuf,
+
+        /// The duration of the voting period, in seconds.
+        #[arg(long, default_value = "86400", value_parser = util::parse_secs)]
+        voting_period: Duration,
+    },
+
+    /// List the unresolved proposals, with the current tally of their votes.
+    List,
+
+    /// Vote on a proposal, as an owner of the admin chain.
+    Vote {
+        /// The ID of the proposal.
+        proposal_id: u32,
+
+        /// Vote against the proposal instead of approving it.
+        #[arg(long)]
+        reject: bool,
+    },
+
+    /// Resolve a proposal whose voting period is over: start using its committee if it
+    /// was approved, and remove it in any case.
+    Resolve {
+        /// The ID of the proposal.
+        proposal_id: u32,
+    },
+}
+
 #[derive(Clone, clap::Parser)]
 /// The subcommands for managing Linera projects.
 pub enum ProjectCommand {
@@ -1426,14 +2387,64 @@ pub enum ProjectCommand {
         /// The directory will be created if it doesn't exist.
         #[arg(long)]
         dir: Option<PathBuf>,
+
+        /// The virtual machine of the application: `wasm` for a Rust project, or `evm` for
+        /// a Solidity project built with Foundry.
+        #[arg(long, default_value = "wasm")]
+        vm: VmRuntime,
     },
 
     /// Test a Linera project.
     ///
-    /// Equivalent to running `cargo test` with the appropriate test runner.
+    /// Equivalent to running `cargo test` with the appropriate test runner, or `forge test`
+    /// for an EVM project.
     Test {
         /// The path of the root of the Linera project to test.
         path: Option<PathBuf>,
+
+        /// Collect the code coverage of the tests into `lcov.info`, with `cargo llvm-cov`, or
+        /// `forge coverage` for an EVM project.
+        #[arg(long)]
+        coverage: bool,
+
+        /// Record the blocks of each failing integration test, with their timestamps and
+        /// oracle responses, into a replay file in the `replays` directory of the project.
+        #[arg(long, conflicts_with = "replay")]
+        record: bool,
+
+        /// Run the test recorded in the given replay file again, executing its blocks with
+        /// the recorded timestamps and oracle responses.
+        #[arg(long)]
+        replay: Option<PathBuf>,
+    },
+
+    /// Build a Linera project and print the ID of the resulting module.
+    ///
+    /// With `--reproducible`, the build is set up so that anyone building the same sources
+    /// obtains the same bytecode, and hence the same module ID, as the published one.
+    Build {
+        /// The path of the root of the Linera project.
+        /// Defaults to current working directory if unspecified.
+        path: Option<PathBuf>,
+
+        /// Specify the name of the Linera project.
+        /// This is used to locate the generated bytecode files. The generated bytecode files should
+        /// be of the form `<name>_{contract,service}.wasm`.
+        ///
+        /// Defaults to the package name in Cargo.toml, with dashes replaced by
+        /// underscores. For an EVM project, this is the name of the contract to publish,
+        /// which defaults to the only Solidity file in `src`.
+        name: Option<String>,
+
+        /// Build with the locked dependencies, a single codegen unit and local paths
+        /// remapped, so that the bytecode does not depend on the machine.
+        #[arg(long)]
+        reproducible: bool,
+
+        /// Run the reproducible build inside the given Docker image, e.g. `rust:1.95.0`,
+        /// instead of with the local toolchain.
+        #[arg(long, requires = "reproducible")]
+        docker_image: Option<String>,
     },
 
     /// Build and publish a Linera project.
@@ -1447,7 +2458,8 @@ pub enum ProjectCommand {
         /// be of the form `<name>_{contract,service}.wasm`.
         ///
         /// Defaults to the package name in Cargo.toml, with dashes replaced by
-        /// underscores.
+        /// underscores. For an EVM project, this is the name of the contract to publish,
+        /// which defaults to the only Solidity file in `src`.
         name: Option<String>,
 
         /// An optional chain ID to publish the module. The default chain of the wallet
@@ -1455,10 +2467,16 @@ pub enum ProjectCommand {
         publisher: Option<ChainId>,
 
         /// The virtual machine runtime to use.
-        #[arg(long, default_value = "wasm")]
-        vm_runtime: VmRuntime,
+        ///
+        /// Defaults to `evm` for a project with a `foundry.toml` file, and to `wasm`
+        /// otherwise.
+        #[arg(long)]
+        vm_runtime: Option<VmRuntime>,
 
         /// The shared parameters as JSON string.
+        ///
+        /// For an EVM project, these are the ABI-encoded constructor arguments, as an array
+        /// of bytes, and default to none.
         #[arg(long)]
         json_parameters: Option<String>,
 
@@ -10,7 +10,9 @@ use linera_client::config::GenesisConfig;
 use linera_execution::WasmRuntime;
 
 use crate::{
+    cli::wallet_env,
     storage::{CommonStorageOptions, StorageConfig},
+    wallet_backend::WalletBackend,
     Wallet,
 };
 
@@ -22,6 +24,17 @@ pub struct CommonCliOptions {
     #[arg(long = "wallet")]
     pub wallet_state_path: Option<PathBuf>,
 
+    /// Where to keep the wallet: in the file given by `--wallet`, or in a namespace of the
+    /// storage given by `--storage`, where several clients can share it. Use `linera wallet
+    /// migrate` to copy a wallet file into storage.
+    #[arg(
+        long,
+        env = "LINERA_WALLET_BACKEND",
+        value_enum,
+        default_value_t = WalletBackend::File
+    )]
+    pub wallet_backend: WalletBackend,
+
     /// Sets the file storing the keystore state.
     #[arg(long = "keystore")]
     pub keystore_path: Option<PathBuf>,
@@ -101,18 +114,30 @@ impl CommonCliOptions {
         linera_wallet_json::paths::wallet_path(self.wallet_state_path.as_ref(), &self.suffix())
     }
 
+    /// Returns the path to the spending journal of the wallet, next to the wallet file.
+    pub fn spending_journal_path(&self) -> Result<PathBuf, Error> {
+        Ok(self.wallet_path()?.with_extension("journal.jsonl"))
+    }
+
     /// Returns the path to the keystore file.
     pub fn keystore_path(&self) -> Result<PathBuf, Error> {
         linera_wallet_json::paths::keystore_path(self.keystore_path.as_ref(), &self.suffix())
     }
 
-    /// Reads and returns the wallet.
+    /// Reads and returns the wallet file.
     pub fn wallet(&self) -> Result<Wallet, Error> {
+        if self.wallet_backend == WalletBackend::Storage {
+            bail!("This command does not support the `storage` wallet backend");
+        }
         Ok(Wallet::read(&self.wallet_path()?)?)
     }
 
-    /// Reads and returns the keystore.
+    /// Reads and returns the keystore: the one given by `LINERA_KEYS` or `LINERA_KEYS_FILE`,
+    /// if any, or else the keystore file.
     pub fn keystore(&self) -> Result<linera_wallet_json::Keystore, Error> {
+        if let Some(keystore) = wallet_env::keystore(&self.suffix())? {
+            return Ok(keystore);
+        }
         Ok(linera_wallet_json::Keystore::read(&self.keystore_path()?)?)
     }
 
@@ -0,0 +1,364 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Configuration files providing defaults for the options of the client.
+//!
+//! A `linera.toml` file maps the long names of the options of `linera` itself, not those of
+//! its subcommands, to their values, e.g. `storage = "rocksdb:linera.db"` or
+//! `send-timeout-ms = 8000`. The user-level file lives in the Linera configuration
+//! directory, and the project-level file is the nearest `linera.toml` in the current
+//! directory or its parents. Options given on the command line or through their
+//! environment variables take precedence over the project-level file, which takes
+//! precedence over the user-level file.
+
+use std::{
+    collections::BTreeMap,
+    env, fmt,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, ensure, Context as _};
+use clap::{parser::ValueSource, ArgAction, ArgMatches, Command};
+use serde::Serialize;
+
+/// The name of the configuration files.
+pub const CONFIG_FILE_NAME: &str = "linera.toml";
+
+/// The options whose environment variables are read by the client rather than by `clap`,
+/// with the names of the variables before the `--with-wallet` suffix.
+const CLIENT_ENV_VARS: &[(&str, &str)] = &[
+    ("wallet", "LINERA_WALLET"),
+    ("keystore", "LINERA_KEYSTORE"),
+    ("storage", "LINERA_STORAGE"),
+];
+
+/// A configuration file and the options it sets.
+#[derive(Clone, Debug, Serialize)]
+pub struct ConfigFile {
+    /// The path of the file.
+    pub path: PathBuf,
+    /// The options, by long name.
+    pub options: toml::Table,
+}
+
+impl ConfigFile {
+    /// Reads the configuration file at `path`.
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs_err::read_to_string(path)?;
+        let options = toml::from_str(&contents)
+            .with_context(|| format!("invalid configuration file {}", path.display()))?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            options,
+        })
+    }
+
+    /// Returns the values of the given option as they would be written on the command
+    /// line, if the file sets it.
+    fn values(&self, name: &str) -> anyhow::Result<Option<Vec<String>>> {
+        let Some(value) = self.options.get(name) else {
+            return Ok(None);
+        };
+        let values = match value {
+            toml::Value::Array(items) => items.iter().map(scalar).collect(),
+            value => scalar(value).map(|value| vec![value]),
+        };
+        values.map(Some).with_context(|| {
+            format!(
+                "option `{name}` in {} must be a string, a number, a boolean or an array of these",
+                self.path.display()
+            )
+        })
+    }
+}
+
+/// Returns the value as it would be written on the command line, if it is not an array or
+/// a table.
+fn scalar(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(value) => Some(value.clone()),
+        toml::Value::Integer(value) => Some(value.to_string()),
+        toml::Value::Float(value) => Some(value.to_string()),
+        toml::Value::Boolean(value) => Some(value.to_string()),
+        toml::Value::Datetime(value) => Some(value.to_string()),
+        toml::Value::Array(_) | toml::Value::Table(_) => None,
+    }
+}
+
+/// Returns the user-level and the project-level configuration files that exist, in
+/// increasing order of precedence.
+pub fn load() -> anyhow::Result<Vec<ConfigFile>> {
+    let user = linera_wallet_json::paths::config_dir()
+        .ok()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .filter(|path| path.is_file());
+    let project = env::current_dir()?
+        .ancestors()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .find(|path| path.is_file())
+        .filter(|path| Some(path) != user.as_ref());
+    user.into_iter()
+        .chain(project)
+        .map(|path| ConfigFile::read(&path))
+        .collect()
+}
+
+/// Where the effective value of an option comes from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum OptionSource {
+    /// The option was given on the command line.
+    CommandLine,
+    /// The option was read from an environment variable.
+    Environment {
+        /// The name of the variable.
+        variable: String,
+    },
+    /// The option was read from a configuration file.
+    ConfigFile {
+        /// The path of the file.
+        path: PathBuf,
+    },
+    /// The option has its default value, if any.
+    Default,
+}
+
+impl fmt::Display for OptionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionSource::CommandLine => write!(f, "command line"),
+            OptionSource::Environment { variable } => write!(f, "environment variable {variable}"),
+            OptionSource::ConfigFile { path } => write!(f, "{}", path.display()),
+            OptionSource::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// The effective value of an option and where it comes from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveOption {
+    /// The long name of the option.
+    pub name: String,
+    /// The values of the option, as they would be written on the command line.
+    pub values: Vec<String>,
+    /// Where the values come from.
+    pub source: OptionSource,
+}
+
+/// The options taken from the configuration files.
+#[derive(Clone, Debug, Default)]
+pub struct Resolution {
+    /// The arguments to add to the command line.
+    pub args: Vec<String>,
+    /// The effective values of all the options of the command.
+    pub effective: Vec<EffectiveOption>,
+}
+
+/// Resolves the options of `command` that were parsed into `matches` without the
+/// configuration files, taking the values of `files` for the options that were neither
+/// given on the command line nor through an environment variable.
+pub fn resolve(
+    command: &Command,
+    matches: &ArgMatches,
+    files: &[ConfigFile],
+) -> anyhow::Result<Resolution> {
+    let arguments = command
+        .get_arguments()
+        .filter(|arg| {
+            !matches!(
+                arg.get_action(),
+                ArgAction::Help | ArgAction::HelpShort | ArgAction::HelpLong | ArgAction::Version
+            )
+        })
+        .filter_map(|arg| Some((arg.get_long()?, arg)))
+        .collect::<BTreeMap<_, _>>();
+    for file in files {
+        for name in file.options.keys() {
+            ensure!(
+                arguments.contains_key(name.as_str()),
+                "unknown option `{name}` in {}; only the options of `linera` itself, \
+                 not those of its subcommands, can be configured",
+                file.path.display()
+            );
+        }
+    }
+    let suffix = matches
+        .try_get_one::<String>("with_wallet")
+        .ok()
+        .flatten()
+        .map(|wallet| format!("_{wallet}"))
+        .unwrap_or_default();
+
+    let mut resolution = Resolution::default();
+    for (name, arg) in arguments {
+        let id = arg.get_id().as_str();
+        let parsed_values = || {
+            matches
+                .try_get_raw(id)
+                .ok()
+                .flatten()
+                .map(|values| {
+                    values
+                        .map(|value| value.to_string_lossy().into_owned())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let client_env_var = CLIENT_ENV_VARS
+            .iter()
+            .find(|(option, _)| *option == name)
+            .map(|(_, variable)| format!("{variable}{suffix}"))
+            .filter(|variable| env::var_os(variable).is_some());
+        let (values, source) = match (matches.value_source(id), client_env_var) {
+            (Some(ValueSource::CommandLine), _) => (parsed_values(), OptionSource::CommandLine),
+            (Some(ValueSource::EnvVariable), _) => {
+                let variable = arg
+                    .get_env()
+                    .map(|variable| variable.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                (parsed_values(), OptionSource::Environment { variable })
+            }
+            (_, Some(variable)) => {
+                let value = env::var_os(&variable)
+                    .map(|value| value.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                (vec![value], OptionSource::Environment { variable })
+            }
+            _ => match configured_values(files, name)? {
+                Some((values, path)) => {
+                    push_args(&mut resolution.args, arg, name, &values)?;
+                    (values, OptionSource::ConfigFile { path })
+                }
+                None => (parsed_values(), OptionSource::Default),
+            },
+        };
+        resolution.effective.push(EffectiveOption {
+            name: name.to_string(),
+            values,
+            source,
+        });
+    }
+    Ok(resolution)
+}
+
+/// Returns the values of the option in the file with the highest precedence that sets it,
+/// and the path of that file.
+fn configured_values(
+    files: &[ConfigFile],
+    name: &str,
+) -> anyhow::Result<Option<(Vec<String>, PathBuf)>> {
+    for file in files.iter().rev() {
+        if let Some(values) = file.values(name)? {
+            return Ok(Some((values, file.path.clone())));
+        }
+    }
+    Ok(None)
+}
+
+/// Adds the command-line arguments setting the option to the given values.
+fn push_args(
+    args: &mut Vec<String>,
+    arg: &clap::Arg,
+    name: &str,
+    values: &[String],
+) -> anyhow::Result<()> {
+    if matches!(arg.get_action(), ArgAction::SetTrue) {
+        match values {
+            [value] if value == "true" => args.push(format!("--{name}")),
+            [value] if value == "false" => {}
+            _ => bail!("option `{name}` must be a boolean"),
+        }
+        return Ok(());
+    }
+    args.extend(values.iter().map(|value| format!("--{name}={value}")));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use clap::{CommandFactory as _, Parser as _};
+
+    use super::{resolve, ConfigFile, OptionSource};
+
+    #[derive(clap::Parser)]
+    struct Options {
+        #[arg(long, default_value = "4000")]
+        send_timeout_ms: u64,
+        #[arg(long, env = "LINERA_CONFIG_FILE_TEST_FAUCET")]
+        faucet: Option<String>,
+        #[arg(long)]
+        verbose: bool,
+        #[command(subcommand)]
+        _command: Command,
+    }
+
+    #[derive(clap::Subcommand)]
+    enum Command {
+        Sync {
+            #[arg(long)]
+            _chain_id: Option<String>,
+        },
+    }
+
+    fn file(path: &str, contents: &str) -> ConfigFile {
+        ConfigFile {
+            path: PathBuf::from(path),
+            options: toml::from_str(contents).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_configuration_precedence() -> anyhow::Result<()> {
+        let files = [
+            file("user.toml", "send-timeout-ms = 1000\nverbose = true"),
+            file(
+                "project.toml",
+                "send-timeout-ms = 2000\nfaucet = \"http://faucet\"",
+            ),
+        ];
+        let command = Options::command();
+        let args = ["linera", "--faucet", "http://other", "sync"];
+        let matches = command.clone().try_get_matches_from(args)?;
+        let resolution = resolve(&command, &matches, &files)?;
+        assert_eq!(resolution.args, ["--send-timeout-ms=2000", "--verbose"]);
+        let source = |name: &str| {
+            resolution
+                .effective
+                .iter()
+                .find(|option| option.name == name)
+                .map(|option| option.source.clone())
+        };
+        assert_eq!(source("faucet"), Some(OptionSource::CommandLine));
+        assert_eq!(
+            source("send-timeout-ms"),
+            Some(OptionSource::ConfigFile {
+                path: PathBuf::from("project.toml")
+            })
+        );
+
+        let configured = resolution.args.iter().map(String::as_str);
+        let options = Options::try_parse_from(
+            args[..1]
+                .iter()
+                .copied()
+                .chain(configured)
+                .chain(args[1..].iter().copied()),
+        )?;
+        assert_eq!(options.send_timeout_ms, 2000);
+        assert!(options.verbose);
+        assert_eq!(options.faucet.as_deref(), Some("http://other"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_subcommand_options_cannot_be_configured() -> anyhow::Result<()> {
+        let command = Options::command();
+        let matches = command.clone().try_get_matches_from(["linera", "sync"])?;
+        let files = [file("linera.toml", "chain-id = \"abc\"")];
+        assert!(resolve(&command, &matches, &files).is_err());
+        Ok(())
+    }
+}
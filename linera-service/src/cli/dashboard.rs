@@ -0,0 +1,259 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The live wallet dashboard of `linera wallet show --watch`.
+
+use std::{collections::BTreeMap, time::Duration};
+
+use futures::{stream, StreamExt as _};
+use linera_base::{
+    data_types::{Amount, BlockHeight, Timestamp},
+    identifiers::ChainId,
+};
+use linera_client::{chain_listener::ClientContext as _, client_context::ClientContext};
+use linera_core::{client::ChainClient, Environment};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Modifier, Style, Stylize as _},
+    text::Line,
+    widgets::{Block, Cell, Paragraph, Row, Table},
+    Frame,
+};
+use tokio::task::JoinSet;
+
+use crate::cli::wallet_balances::MAX_CONCURRENT_QUERIES;
+
+/// How often the keyboard is checked for the keys that quit the dashboard.
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What the dashboard shows about a chain.
+#[derive(Clone, Debug, Default)]
+struct ChainRow {
+    /// The chain's own balance.
+    balance: Option<Amount>,
+    /// The height of the next block.
+    next_block_height: Option<BlockHeight>,
+    /// The height of the block this client proposed and is still waiting for, if any.
+    pending_proposal: Option<BlockHeight>,
+    /// The number of message bundles waiting in the inbox.
+    inbox: Option<usize>,
+    /// When the row was last updated.
+    updated_at: Option<Timestamp>,
+    /// The error of the last update, if it failed.
+    error: Option<String>,
+}
+
+impl ChainRow {
+    async fn load<Env: Environment>(chain_client: &ChainClient<Env>) -> Self {
+        let mut row = Self {
+            updated_at: Some(Timestamp::now()),
+            pending_proposal: chain_client
+                .pending_proposal()
+                .await
+                .map(|proposal| proposal.block.height),
+            ..Self::default()
+        };
+        match chain_client.chain_info().await {
+            Ok(info) => row.next_block_height = Some(info.next_block_height),
+            Err(error) => row.error = Some(error.to_string()),
+        }
+        match chain_client.local_balance().await {
+            Ok(balance) => row.balance = Some(balance),
+            Err(error) => row.error = Some(error.to_string()),
+        }
+        match chain_client.pending_message_bundle_count().await {
+            Ok(count) => row.inbox = Some(count),
+            Err(error) => row.error = Some(error.to_string()),
+        }
+        row
+    }
+
+    fn cells(&self, chain_id: ChainId, is_default: bool) -> Row<'static> {
+        fn show<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map_or_else(|| "-".into(), T::to_string)
+        }
+        let chain = if is_default {
+            format!("{chain_id} (default)")
+        } else {
+            chain_id.to_string()
+        };
+        let row = Row::new([
+            Cell::new(chain),
+            Cell::new(show(&self.balance)),
+            Cell::new(show(&self.next_block_height)),
+            Cell::new(show(&self.pending_proposal)),
+            Cell::new(show(&self.inbox)),
+            Cell::new(show(&self.updated_at)),
+        ]);
+        match &self.error {
+            Some(_) => row.red(),
+            None => row,
+        }
+    }
+}
+
+/// Shows the given chains of the wallet, updating them whenever a notification arrives,
+/// until the user presses `q` or Esc.
+pub async fn run<Env: Environment>(
+    context: &ClientContext<Env>,
+    chain_ids: Vec<ChainId>,
+    default_chain: Option<ChainId>,
+) -> anyhow::Result<()> {
+    let mut listeners = JoinSet::new();
+    let mut notification_streams = Vec::new();
+    let mut chain_clients = BTreeMap::new();
+    for chain_id in chain_ids {
+        let chain_client = context.make_chain_client(chain_id).await?;
+        let (listener, abort, notifications) = chain_client.listen().await?;
+        listeners.spawn(async move {
+            let _abort = abort;
+            listener.await
+        });
+        notification_streams.push(notifications);
+        chain_clients.insert(chain_id, chain_client);
+    }
+    let mut rows =
+        stream::iter(&chain_clients)
+            .map(|(chain_id, chain_client)| async move {
+                (*chain_id, ChainRow::load(chain_client).await)
+            })
+            .buffer_unordered(MAX_CONCURRENT_QUERIES)
+            .collect::<BTreeMap<_, _>>()
+            .await;
+    let mut notifications = stream::select_all(notification_streams);
+
+    let mut terminal = ratatui::init();
+    let result = async {
+        let mut input_poll = tokio::time::interval(INPUT_POLL_INTERVAL);
+        loop {
+            terminal.draw(|frame| render(frame, &rows, default_chain))?;
+            tokio::select! {
+                Some(notification) = notifications.next() => {
+                    let chain_id = notification.chain_id;
+                    if let Some(chain_client) = chain_clients.get(&chain_id) {
+                        rows.insert(chain_id, ChainRow::load(chain_client).await);
+                    }
+                }
+                _ = input_poll.tick() => {
+                    if quit_requested()? {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+    .await;
+    ratatui::restore();
+    listeners.abort_all();
+    result
+}
+
+/// Returns whether one of the keys that quit the dashboard was pressed.
+fn quit_requested() -> anyhow::Result<bool> {
+    while event::poll(Duration::ZERO)? {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press
+                && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc)
+            {
+                return Ok(true);
+            }
+        }
+    }
+    Ok(false)
+}
+
+fn render(frame: &mut Frame, rows: &BTreeMap<ChainId, ChainRow>, default_chain: Option<ChainId>) {
+    let [table_area, errors_area] =
+        Layout::vertical([Constraint::Fill(1), Constraint::Length(errors_height(rows))])
+            .areas(frame.area());
+    let header = Row::new([
+        "Chain",
+        "Balance",
+        "Height",
+        "Pending proposal",
+        "Inbox",
+        "Updated",
+    ])
+    .add_modifier(Modifier::BOLD);
+    let table = Table::new(
+        rows.iter()
+            .map(|(chain_id, row)| row.cells(*chain_id, Some(*chain_id) == default_chain)),
+        [
+            Constraint::Min(64),
+            Constraint::Length(20),
+            Constraint::Length(8),
+            Constraint::Length(17),
+            Constraint::Length(6),
+            Constraint::Length(27),
+        ],
+    )
+    .header(header)
+    .block(
+        Block::bordered()
+            .title(" Linera wallet ")
+            .title_bottom(Line::from(" q: quit ").right_aligned()),
+    );
+    frame.render_widget(table, table_area);
+    let errors = rows
+        .iter()
+        .filter_map(|(chain_id, row)| {
+            Some(Line::from(format!("{chain_id}: {}", row.error.as_ref()?)))
+        })
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        frame.render_widget(
+            Paragraph::new(errors)
+                .style(Style::new().red())
+                .block(Block::bordered().title(" Errors ")),
+            errors_area,
+        );
+    }
+}
+
+/// Returns the height of the area listing the errors of the last updates.
+fn errors_height(rows: &BTreeMap<ChainId, ChainRow>) -> u16 {
+    let count = rows.values().filter(|row| row.error.is_some()).count();
+    if count == 0 {
+        0
+    } else {
+        u16::try_from(count).unwrap_or(u16::MAX).saturating_add(2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use linera_base::{
+        data_types::{Amount, BlockHeight},
+        identifiers::ChainId,
+    };
+    use ratatui::{backend::TestBackend, Terminal};
+
+    use super::{render, ChainRow};
+
+    #[test]
+    fn test_render_dashboard() {
+        let default_chain = ChainId::default();
+        let rows = BTreeMap::from([(
+            default_chain,
+            ChainRow {
+                balance: Some(Amount::from_tokens(7)),
+                next_block_height: Some(BlockHeight(3)),
+                pending_proposal: Some(BlockHeight(3)),
+                inbox: Some(2),
+                updated_at: None,
+                error: Some("connection lost".to_string()),
+            },
+        )]);
+        let mut terminal = Terminal::new(TestBackend::new(160, 8)).unwrap();
+        terminal
+            .draw(|frame| render(frame, &rows, Some(default_chain)))
+            .unwrap();
+        let screen = format!("{:?}", terminal.backend().buffer());
+        assert!(screen.contains(&format!("{default_chain} (default)")));
+        assert!(screen.contains("7."));
+        assert!(screen.contains(&format!("{default_chain}: connection lost")));
+    }
+}
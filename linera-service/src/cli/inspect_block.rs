@@ -0,0 +1,288 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The decoding of a block certificate, for `linera inspect-block`.
+
+use linera_base::{
+    crypto::{CryptoHash, ValidatorPublicKey, ValidatorSignature},
+    data_types::{BlockHeight, Epoch, Event, OracleResponse, Round, Timestamp},
+    identifiers::{AccountOwner, BlobId, ChainId},
+};
+use linera_chain::{
+    data_types::{OperationResult, Transaction},
+    types::ConfirmedBlockCertificate,
+};
+use linera_execution::{committee::Committee, OutgoingMessage};
+use serde::Serialize;
+
+/// A transaction of an inspected block, with everything it produced.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectedTransaction {
+    /// The index of the transaction in the block.
+    pub index: usize,
+    /// The incoming messages or the operation.
+    pub transaction: Transaction,
+    /// The result of the operation, if the transaction is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operation_result: Option<OperationResult>,
+    /// The messages sent by the transaction.
+    pub messages: Vec<OutgoingMessage>,
+    /// The events emitted by the transaction.
+    pub events: Vec<Event>,
+    /// The oracle responses recorded by the transaction.
+    pub oracle_responses: Vec<OracleResponse>,
+    /// The blobs created by the transaction.
+    pub created_blobs: Vec<BlobId>,
+}
+
+/// A validator signature of an inspected certificate.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InspectedSignature {
+    /// The validator.
+    pub validator: ValidatorPublicKey,
+    /// The voting weight of the validator, if the committee of the epoch is known.
+    pub weight: Option<u64>,
+    /// The signature.
+    pub signature: ValidatorSignature,
+}
+
+/// A decoded block certificate.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockInspection {
+    /// The hash of the certificate.
+    pub certificate_hash: CryptoHash,
+    /// The chain of the block.
+    pub chain_id: ChainId,
+    /// The height of the block.
+    pub height: BlockHeight,
+    /// The epoch of the block.
+    pub epoch: Epoch,
+    /// The timestamp of the block.
+    pub timestamp: Timestamp,
+    /// The round in which the block was confirmed.
+    pub round: Round,
+    /// The owner who proposed the block and paid for it, if any.
+    pub authenticated_owner: Option<AccountOwner>,
+    /// The hash of the previous block of the chain, if any.
+    pub previous_block_hash: Option<CryptoHash>,
+    /// The hash of the execution state of the chain after the block.
+    pub state_hash: CryptoHash,
+    /// The transactions of the block.
+    pub transactions: Vec<InspectedTransaction>,
+    /// The signatures of the certificate.
+    pub signatures: Vec<InspectedSignature>,
+    /// The total weight of the signatures, if the committee of the epoch is known.
+    pub signed_weight: Option<u64>,
+    /// The weight needed for a quorum, if the committee of the epoch is known.
+    pub quorum_threshold: Option<u64>,
+    /// The total weight of the committee, if it is known.
+    pub total_weight: Option<u64>,
+}
+
+impl BlockInspection {
+    /// Decodes a certificate, with the weights of its signatures if the committee of its
+    /// epoch is given.
+    pub fn new(certificate: &ConfirmedBlockCertificate, committee: Option<&Committee>) -> Self {
+        let block = certificate.block();
+        let body = &block.body;
+        let mut operation_results = body.operation_results.iter();
+        let transactions = body
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(index, transaction)| InspectedTransaction {
+                index,
+                transaction: transaction.clone(),
+                operation_result: match transaction {
+                    Transaction::ExecuteOperation(_) => operation_results.next().cloned(),
+                    Transaction::ReceiveMessages(_) => None,
+                },
+                messages: body.messages.get(index).cloned().unwrap_or_default(),
+                events: body.events.get(index).cloned().unwrap_or_default(),
+                oracle_responses: body
+                    .oracle_responses
+                    .get(index)
+                    .cloned()
+                    .unwrap_or_default(),
+                created_blobs: body
+                    .blobs
+                    .get(index)
+                    .into_iter()
+                    .flatten()
+                    .map(|blob| blob.id())
+                    .collect(),
+            })
+            .collect();
+        let signatures = certificate
+            .signatures()
+            .iter()
+            .map(|(validator, signature)| InspectedSignature {
+                validator: *validator,
+                weight: committee.map(|committee| committee.weight(validator)),
+                signature: *signature,
+            })
+            .collect::<Vec<_>>();
+        let signed_weight = committee.map(|_| {
+            signatures
+                .iter()
+                .filter_map(|signature| signature.weight)
+                .sum()
+        });
+        Self {
+            certificate_hash: certificate.hash(),
+            chain_id: block.header.chain_id,
+            height: block.header.height,
+            epoch: block.header.epoch,
+            timestamp: block.header.timestamp,
+            round: certificate.round(),
+            authenticated_owner: block.header.authenticated_owner,
+            previous_block_hash: block.header.previous_block_hash,
+            state_hash: block.header.state_hash,
+            transactions,
+            signatures,
+            signed_weight,
+            quorum_threshold: committee.map(Committee::quorum_threshold),
+            total_weight: committee.map(Committee::total_votes),
+        }
+    }
+
+    /// Prints the block in a human-readable form.
+    pub fn print_text(&self) {
+        fn show<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map_or_else(|| "-".into(), T::to_string)
+        }
+        println!("Certificate:     {}", self.certificate_hash);
+        println!("Chain:           {}", self.chain_id);
+        println!("Height:          {}", self.height);
+        println!("Epoch:           {}", self.epoch);
+        println!("Timestamp:       {}", self.timestamp);
+        println!("Round:           {}", self.round);
+        println!("Owner:           {}", show(&self.authenticated_owner));
+        println!("Previous block:  {}", show(&self.previous_block_hash));
+        println!("State hash:      {}", self.state_hash);
+        for transaction in &self.transactions {
+            println!();
+            match &transaction.transaction {
+                Transaction::ExecuteOperation(operation) => {
+                    println!("Transaction {}: operation {operation:?}", transaction.index);
+                }
+                Transaction::ReceiveMessages(bundle) => {
+                    println!(
+                        "Transaction {}: {:?} messages from {} at height {}",
+                        transaction.index, bundle.action, bundle.origin, bundle.bundle.height
+                    );
+                    for posted in &bundle.bundle.messages {
+                        println!("  Received {:?}", posted.message);
+                    }
+                }
+            }
+            if let Some(result) = &transaction.operation_result {
+                println!("  Result {result:?}");
+            }
+            for message in &transaction.messages {
+                println!(
+                    "  {:?} message to {}: {:?}",
+                    message.kind, message.destination, message.message
+                );
+            }
+            for event in &transaction.events {
+                println!("  Event {event:?}");
+            }
+            for response in &transaction.oracle_responses {
+                println!("  Oracle response {response:?}");
+            }
+            for blob_id in &transaction.created_blobs {
+                println!("  Created blob {blob_id}");
+            }
+        }
+        println!();
+        println!(
+            "Signatures: {} (weight {} of {}, quorum {})",
+            self.signatures.len(),
+            show(&self.signed_weight),
+            show(&self.total_weight),
+            show(&self.quorum_threshold)
+        );
+        for signature in &self.signatures {
+            println!(
+                "  {}  weight {}",
+                signature.validator,
+                show(&signature.weight)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::{
+        crypto::{AccountPublicKey, CryptoHash, ValidatorKeypair},
+        data_types::{BlockHeight, Round},
+        identifiers::{ApplicationId, ChainId},
+    };
+    use linera_chain::{
+        data_types::{LiteValue, LiteVote, OperationResult, Transaction},
+        test::BlockBuilder,
+        types::{ConfirmedBlock, ConfirmedBlockCertificate},
+    };
+    use linera_execution::{committee::Committee, Operation};
+
+    use super::BlockInspection;
+
+    #[test]
+    fn test_block_inspection() {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let operation = Operation::User {
+            application_id: ApplicationId::new(CryptoHash::test_hash("application")),
+            bytes: vec![1, 2, 3],
+        };
+        let block = BlockBuilder::new(chain_id, BlockHeight(2))
+            .with_transaction(Transaction::ExecuteOperation(operation.clone()))
+            .with_messages(Vec::new())
+            .with_events(Vec::new())
+            .with_oracle_responses(Vec::new())
+            .with_blobs(Vec::new())
+            .with_operation_result(OperationResult(vec![4]))
+            .build();
+        let value = ConfirmedBlock::new(block);
+        let signer = ValidatorKeypair::generate();
+        let outsider = ValidatorKeypair::generate();
+        let signatures = [&signer, &outsider]
+            .into_iter()
+            .map(|keypair| {
+                let vote = LiteVote::new(LiteValue::new(&value), Round::Fast, &keypair.secret_key);
+                (keypair.public_key, vote.signature)
+            })
+            .collect();
+        let certificate = ConfirmedBlockCertificate::new(value, Round::Fast, signatures);
+        let committee =
+            Committee::make_simple(vec![(signer.public_key, AccountPublicKey::test_key(1))]);
+
+        let inspection = BlockInspection::new(&certificate, Some(&committee));
+        assert_eq!(inspection.certificate_hash, certificate.hash());
+        assert_eq!(inspection.height, BlockHeight(2));
+        assert_eq!(inspection.transactions.len(), 1);
+        assert_eq!(
+            inspection.transactions[0].transaction,
+            Transaction::ExecuteOperation(operation)
+        );
+        assert_eq!(
+            inspection.transactions[0].operation_result,
+            Some(OperationResult(vec![4]))
+        );
+        assert_eq!(inspection.signatures[0].weight, Some(100));
+        assert_eq!(inspection.signatures[1].weight, Some(0));
+        assert_eq!(inspection.signed_weight, Some(100));
+        assert_eq!(inspection.total_weight, Some(100));
+
+        let inspection = BlockInspection::new(&certificate, None);
+        assert_eq!(inspection.signatures[0].weight, None);
+        assert_eq!(inspection.signed_weight, None);
+        let json = serde_json::to_value(&inspection).unwrap();
+        assert_eq!(json["height"], 2);
+        assert!(json["transactions"][0].get("operationResult").is_some());
+    }
+}
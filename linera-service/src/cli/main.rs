@@ -24,7 +24,7 @@ mod options;
 use std::{
     collections::{BTreeMap, BTreeSet},
     env,
-    path::PathBuf,
+    path::{Path, PathBuf},
     process,
     sync::Arc,
 };
@@ -32,55 +32,81 @@ use std::{
 use anyhow::{bail, ensure, Context, Error};
 use async_trait::async_trait;
 use chrono::Utc;
+use clap::Parser as _;
 use clap_complete::generate;
 use colored::Colorize;
-use futures::{lock::Mutex, FutureExt as _, StreamExt as _};
+use futures::{lock::Mutex, FutureExt as _, StreamExt as _, TryStreamExt as _};
 use linera_base::{
-    crypto::Signer,
-    data_types::{ApplicationPermissions, TimeDelta, Timestamp},
-    identifiers::{AccountOwner, ChainId},
+    crypto::{AccountSignature, CryptoHash, Signer},
+    data_types::{Amount, ApplicationPermissions, Blob, Bytecode, TimeDelta, Timestamp},
+    identifiers::{Account, AccountOwner, ApplicationId, BlobType, ChainId},
     listen_for_shutdown_signals,
     ownership::ChainOwnership,
     time::{Duration, Instant},
+    vm::{EvmInstantiation, VmRuntime},
 };
 use linera_client::{
     benchmark::{
-        BenchmarkConfig, FungibleTransferGenerator, NativeFungibleTransferGenerator,
-        OperationGenerator,
+        ApplicationCallGenerator, BenchmarkConfig, BlobPublicationGenerator,
+        FungibleTransferGenerator, NativeFungibleTransferGenerator, OperationGenerator,
     },
     chain_listener::{
         ChainListener, ChainListenerConfig, ClientContext as _, ClientContextExt as _,
     },
     config::{CommitteeConfig, GenesisConfig},
+    module_attestation::BuildInfo,
+    signed_output::{OutputStatement, SignedOutput},
+    signing_relay::SigningRelay,
+    spending_journal::SpendingJournal,
 };
 use linera_core::{
-    client::{chain_client, ListeningMode},
-    data_types::ClientOutcome,
+    client::{chain_client, create_bytecode_blobs, ListeningMode},
+    data_types::{ClientOutcome, DryRunOutcome, UnsignedBlockProposal},
     node::{ValidatorNode, ValidatorNodeProvider},
-    wallet,
+    replay, wallet,
     worker::Reason,
-    JoinSetExt as _, LocalNodeError,
+    JoinSetExt as _, LocalNodeError, Wallet as _,
+};
+use linera_execution::{
+    committee::Committee, system::SystemOperation, HttpAllowListUpdate, Operation,
+};
+use linera_faucet_server::{
+    invite::{Invite, InviteCode},
+    quota::QuotaConfig,
+    FaucetConfig, FaucetService,
 };
-use linera_execution::{committee::Committee, Operation};
-use linera_faucet_server::{FaucetConfig, FaucetService};
 #[cfg(with_metrics)]
 use linera_metrics::monitoring_server;
 use linera_persistent::{self as persistent, Persist as _};
 use linera_service::{
     cli::{
         command::{
-            BenchmarkCommand, BenchmarkOptions, ChainCommand, ClientCommand, DatabaseToolCommand,
-            NetCommand, ProjectCommand, ResourceControlPolicyOverrides, WalletCommand,
+            AccessTokenCommand, BenchmarkCommand, BenchmarkOptions, BenchmarkWorkload,
+            ChainCommand, ClientCommand, ConfigCommand, DatabaseToolCommand, DevCommand,
+            FaucetCommand, GovernanceCommand, ModuleCommand, NetCommand, PolicyCommand,
+            ProjectCommand, ResourceControlPolicyOverrides, ServiceCommand, WalletCommand,
         },
+        config_file, dashboard,
+        inspect_block::BlockInspection,
         net_up_utils,
+        output::{self, OutputFormat},
+        parquet_export, snapshot, statement, test_vectors,
+        wallet_balances::WalletBalances,
+        wallet_env::{self, ChainAssignment, WalletEnv},
+        wallet_sync::WalletSync,
     },
     cli_wrappers::{self, local_net::PathProvider, ClientWrapper, Network, OnClientDrop},
     controller::Controller,
     node_service::NodeService,
     project::{self, Project},
+    signing_page,
     storage::{Runnable, RunnableWithStore, StorageCacheConfig},
+    storage_wallet::MigrateWalletJob,
     task_processor::TaskProcessor,
-    util,
+    telemetry::{self, UsageReport},
+    util, virtual_time,
+    wallet_backend::WalletBackend,
+    Wallet,
 };
 use linera_storage::{DbStorage, Storage};
 use linera_views::store::{KeyValueDatabase, KeyValueStore};
@@ -99,6 +125,10 @@ use tracing::{debug, error, info, warn, Instrument as _};
 
 struct Job(Options);
 
+/// The safety margin added to the estimated fees of an instantiation by
+/// `linera create-application --auto-fund`, in percent.
+const AUTO_FUND_HEADROOM_PERCENT: u128 = 20;
+
 /// Check if an error is retryable (HTTP 502, 503, 504, timeouts, connection errors)
 fn is_retryable_error(err: &anyhow::Error) -> bool {
     // Check for reqwest errors in the error chain
@@ -154,6 +184,209 @@ fn read_json(string: Option<String>, path: Option<PathBuf>) -> anyhow::Result<Ve
     Ok(serde_json::to_vec(&value)?)
 }
 
+/// A transfer listed in the JSON file of `linera transfer-many`.
+#[derive(serde::Deserialize)]
+struct TransferEntry {
+    recipient: String,
+    amount: Amount,
+}
+
+/// Parses the recipients and amounts of `linera transfer-many`, given either as a JSON
+/// array or as CSV lines.
+fn parse_transfers(contents: &str, is_json: bool) -> anyhow::Result<Vec<(Account, Amount)>> {
+    if is_json {
+        return serde_json::from_str::<Vec<TransferEntry>>(contents)?
+            .into_iter()
+            .map(|entry| Ok((entry.recipient.parse()?, entry.amount)))
+            .collect();
+    }
+    let mut transfers = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || (index == 0 && line.eq_ignore_ascii_case("recipient,amount")) {
+            continue;
+        }
+        let (recipient, amount) = line
+            .split_once(',')
+            .with_context(|| format!("line {}: expected `<ACCOUNT>,<AMOUNT>`", index + 1))?;
+        let recipient = recipient
+            .trim()
+            .parse()
+            .with_context(|| format!("line {}: invalid recipient", index + 1))?;
+        let amount = amount
+            .trim()
+            .parse()
+            .with_context(|| format!("line {}: invalid amount", index + 1))?;
+        transfers.push((recipient, amount));
+    }
+    Ok(transfers)
+}
+
+fn read_transfers(path: &Path) -> anyhow::Result<Vec<(Account, Amount)>> {
+    let contents = fs_err::read_to_string(path)?;
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension == "json");
+    parse_transfers(&contents, is_json)
+        .with_context(|| format!("failed to read the transfers in {}", path.display()))
+}
+
+/// Returns the passphrase of a wallet archive, from the first line of the given file if
+/// any, or else from the `LINERA_WALLET_PASSPHRASE` environment variable.
+fn wallet_archive_passphrase(passphrase_file: Option<&Path>) -> anyhow::Result<String> {
+    let passphrase = match passphrase_file {
+        Some(path) => fs_err::read_to_string(path)?
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        None => env::var("LINERA_WALLET_PASSPHRASE").map_err(|_| {
+            anyhow::anyhow!("please set `LINERA_WALLET_PASSPHRASE` or specify `--passphrase-file`")
+        })?,
+    };
+    ensure!(!passphrase.is_empty(), "the passphrase must not be empty");
+    Ok(passphrase)
+}
+
+/// A subcommand of `linera batch`, parsed like the command of the same name.
+#[derive(clap::Parser)]
+#[command(no_binary_name = true)]
+struct BatchSubcommand {
+    #[command(subcommand)]
+    command: ClientCommand,
+}
+
+/// The operation that a subcommand of `linera batch` adds to the block.
+struct BatchOperation {
+    /// The chain given to the subcommand, if any.
+    chain_id: Option<ChainId>,
+    operation: Operation,
+    /// The data blob published by the operation, if any.
+    blob: Option<Blob>,
+    required_application_ids: Vec<ApplicationId>,
+}
+
+/// Parses a subcommand of `linera batch` into the operation it adds to the block.
+fn parse_batch_operation(command: &str) -> anyhow::Result<BatchOperation> {
+    let words = shlex::split(command).with_context(|| format!("invalid quoting in `{command}`"))?;
+    let BatchSubcommand { command: parsed } = BatchSubcommand::try_parse_from(&words)?;
+    let batch_operation = match parsed {
+        ClientCommand::Transfer {
+            sender,
+            recipient,
+            amount,
+            dry_run,
+        } => {
+            ensure!(!dry_run, "`--dry-run` is not supported in a batch");
+            BatchOperation {
+                chain_id: Some(sender.chain_id),
+                operation: Operation::system(SystemOperation::Transfer {
+                    owner: sender.owner,
+                    recipient,
+                    amount,
+                }),
+                blob: None,
+                required_application_ids: Vec::new(),
+            }
+        }
+        ClientCommand::PublishDataBlob {
+            blob_path,
+            publisher,
+            chunked,
+            ..
+        } => {
+            ensure!(!chunked, "`--chunked` is not supported in a batch");
+            let blob = Blob::new_data(fs_err::read(&blob_path)?);
+            BatchOperation {
+                chain_id: publisher,
+                operation: Operation::system(SystemOperation::PublishDataBlob {
+                    blob_hash: blob.id().hash,
+                }),
+                blob: Some(blob),
+                required_application_ids: Vec::new(),
+            }
+        }
+        ClientCommand::CreateApplication {
+            module_id,
+            creator,
+            json_parameters,
+            json_parameters_path,
+            json_argument,
+            json_argument_path,
+            required_application_ids,
+            auto_fund,
+            dry_run,
+        } => {
+            ensure!(
+                auto_fund.is_none(),
+                "`--auto-fund` is not supported in a batch"
+            );
+            ensure!(!dry_run, "`--dry-run` is not supported in a batch");
+            let required_application_ids = required_application_ids.unwrap_or_default();
+            BatchOperation {
+                chain_id: creator,
+                operation: Operation::system(SystemOperation::CreateApplication {
+                    module_id,
+                    parameters: read_json(json_parameters, json_parameters_path)?,
+                    instantiation_argument: read_json(json_argument, json_argument_path)?,
+                    required_application_ids: required_application_ids.clone(),
+                }),
+                blob: None,
+                required_application_ids,
+            }
+        }
+        ClientCommand::ExecuteOperation {
+            application_id,
+            operation,
+            chain_id,
+        } => BatchOperation {
+            chain_id,
+            operation: Operation::User {
+                application_id,
+                bytes: linera_base::hex::decode(&operation)
+                    .context("invalid hex for operation bytes")?,
+            },
+            blob: None,
+            required_application_ids: Vec::new(),
+        },
+        _ => bail!("`{command}` does not produce an operation that can be batched"),
+    };
+    Ok(batch_operation)
+}
+
+/// Returns the chain on which the operations of a batch are proposed: the given one, or else
+/// the one given to the subcommands, or else the default chain.
+fn batch_chain_id(
+    batch: &[BatchOperation],
+    chain_id: Option<ChainId>,
+    default_chain_id: ChainId,
+) -> anyhow::Result<ChainId> {
+    let chain_id = chain_id
+        .or_else(|| batch.iter().find_map(|entry| entry.chain_id))
+        .unwrap_or(default_chain_id);
+    if let Some(other_chain_id) = batch
+        .iter()
+        .filter_map(|entry| entry.chain_id)
+        .find(|other_chain_id| *other_chain_id != chain_id)
+    {
+        bail!(
+            "All operations of a batch must be on the same chain, \
+            but {other_chain_id} differs from {chain_id}"
+        );
+    }
+    Ok(chain_id)
+}
+
+/// The contents of the file written by `linera sign-proposal`.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProposalPayload {
+    /// The hash to be signed by the owner.
+    signing_hash: CryptoHash,
+    /// The proposal to submit with the signature.
+    proposal: UnsignedBlockProposal,
+}
+
 #[async_trait]
 impl Runnable for Job {
     type Output = anyhow::Result<()>;
@@ -163,9 +396,29 @@ impl Runnable for Job {
         S: Storage + Clone + Send + Sync + 'static,
     {
         let Job(options) = self;
-        let mut wallet = options.wallet()?;
+        let mut wallet = options.client_wallet().await?;
         let mut keystore = options.keystore()?;
 
+        // Owners whose keys stay in an external wallet sign through the signing relay. The
+        // node service serves the signing page itself.
+        let signing_relay = keystore
+            .has_external_evm_owners()
+            .then(SigningRelay::default);
+        if let Some(relay) = &signing_relay {
+            keystore.set_signing_relay(relay.clone());
+            if !matches!(options.command, ClientCommand::Service { .. }) {
+                let relay = relay.clone();
+                let port = options.signing_page_port;
+                tokio::spawn(async move {
+                    if let Err(error) =
+                        signing_page::serve(relay, port, CancellationToken::new()).await
+                    {
+                        warn!(%error, "Failed to serve the signing page");
+                    }
+                });
+            }
+        }
+
         let command = options.command.clone();
 
         use ClientCommand::*;
@@ -174,11 +427,24 @@ impl Runnable for Job {
                 sender,
                 recipient,
                 amount,
+                dry_run,
             } => {
                 let mut context = options
                     .create_client_context(storage, wallet, keystore)
                     .await?;
                 let chain_client = context.make_chain_client(sender.chain_id).await?;
+                if dry_run {
+                    let operation = Operation::system(SystemOperation::Transfer {
+                        owner: sender.owner,
+                        recipient,
+                        amount,
+                    });
+                    let outcome = chain_client
+                        .dry_run(vec![operation], Vec::new())
+                        .await
+                        .context("Failed to execute the transfer locally")?;
+                    return print_dry_run(&outcome, options.output);
+                }
                 info!(
                     "Starting transfer of {} native tokens from {} to {}",
                     amount, sender, recipient
@@ -200,6 +466,53 @@ impl Runnable for Job {
                 debug!("{:?}", certificate);
             }
 
+            TransferMany {
+                sender,
+                transfers_path,
+            } => {
+                let transfers = read_transfers(&transfers_path)?;
+                ensure!(
+                    !transfers.is_empty(),
+                    "No transfers in {}",
+                    transfers_path.display()
+                );
+                let total = transfers
+                    .iter()
+                    .try_fold(Amount::ZERO, |total, (_, amount)| total.try_add(*amount))?;
+                let operations = transfers
+                    .into_iter()
+                    .map(|(recipient, amount)| {
+                        Operation::system(SystemOperation::Transfer {
+                            owner: sender.owner,
+                            recipient,
+                            amount,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let mut context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let chain_client = context.make_chain_client(sender.chain_id).await?;
+                info!(
+                    "Starting {} transfers of {} native tokens in total from {}",
+                    operations.len(),
+                    total,
+                    sender
+                );
+                let time_start = Instant::now();
+                let certificate = context
+                    .apply_client_command(&chain_client, |chain_client| {
+                        let chain_client = chain_client.clone();
+                        let operations = operations.clone();
+                        async move { chain_client.execute_operations(operations, vec![]).await }
+                    })
+                    .await
+                    .context("Failed to make the transfers")?;
+                let time_total = time_start.elapsed();
+                info!("Transfers confirmed after {} ms", time_total.as_millis());
+                debug!("{:?}", certificate);
+            }
+
             OpenChain {
                 chain_id,
                 owner,
@@ -334,6 +647,16 @@ impl Runnable for Job {
                 context.set_preferred_owner(chain_id, owner).await?
             }
 
+            RotateKey { chain_id } => {
+                let new_owner = AccountOwner::from(keystore.generate_key().await?);
+                let mut context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let old_owner = context.rotate_key(chain_id, new_owner).await?;
+                info!("Replaced owner {old_owner} with {new_owner}");
+                println!("{new_owner}");
+            }
+
             ChangeApplicationPermissions {
                 chain_id,
                 application_permissions_config,
@@ -368,6 +691,31 @@ impl Runnable for Job {
                 debug!("{:?}", certificate);
             }
 
+            ChangeSignatureSchemes { chain_id, schemes } => {
+                let mut context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                let chain_client = context.make_chain_client(chain_id).await?;
+                info!("Changing the signature schemes of chain {chain_id}");
+                let time_start = Instant::now();
+                let schemes = schemes.into_iter().collect::<BTreeSet<_>>();
+                let certificate = context
+                    .apply_client_command(&chain_client, |chain_client| {
+                        let schemes = schemes.clone();
+                        let chain_client = chain_client.clone();
+                        async move { chain_client.change_signature_schemes(schemes).await }
+                    })
+                    .await
+                    .context("Failed to change signature schemes")?;
+                let time_total = time_start.elapsed();
+                info!(
+                    "Changing signature schemes confirmed after {} ms",
+                    time_total.as_millis()
+                );
+                debug!("{:?}", certificate);
+            }
+
             CloseChain { chain_id } => {
                 let mut context = options
                     .create_client_context(storage, wallet, keystore)
@@ -427,38 +775,87 @@ impl Runnable for Job {
                 println!("{json}");
             }
 
-            LocalBalance { account } => {
+            LocalBalance {
+                account,
+                at_height,
+                sign_output,
+            } => {
                 let context = options
                     .create_client_context(storage, wallet, keystore)
                     .await?;
                 let account = account.unwrap_or_else(|| context.default_account());
                 let chain_client = context.make_chain_client(account.chain_id).await?;
-                info!("Reading the balance of {} from the local state", account);
                 let time_start = Instant::now();
-                let balance = chain_client.local_owner_balance(account.owner).await?;
+                let balance = if let Some(height) = at_height {
+                    info!("Reconstructing the balance of {account} at height {height}");
+                    replay::owner_balance_at_height(
+                        context.storage(),
+                        account.chain_id,
+                        account.owner,
+                        height,
+                    )
+                    .await?
+                } else {
+                    info!("Reading the balance of {} from the local state", account);
+                    chain_client.local_owner_balance(account.owner).await?
+                };
                 let time_total = time_start.elapsed();
                 info!("Local balance obtained after {} ms", time_total.as_millis());
-                println!("{balance}");
+                print_balance(
+                    &chain_client,
+                    account,
+                    balance,
+                    at_height,
+                    sign_output,
+                    options.output,
+                )
+                .await?;
             }
 
-            QueryBalance { account } => {
+            QueryBalance {
+                account,
+                at_height,
+                sign_output,
+            } => {
                 let context = options
                     .create_client_context(storage, wallet, keystore)
                     .await?;
                 let account = account.unwrap_or_else(|| context.default_account());
                 let chain_client = context.make_chain_client(account.chain_id).await?;
-                info!(
-                    "Evaluating the local balance of {account} by staging execution of known \
-                    incoming messages"
-                );
                 let time_start = Instant::now();
-                let balance = chain_client.query_owner_balance(account.owner).await?;
+                let balance = if let Some(height) = at_height {
+                    info!("Reconstructing the balance of {account} at height {height}");
+                    replay::owner_balance_at_height(
+                        context.storage(),
+                        account.chain_id,
+                        account.owner,
+                        height,
+                    )
+                    .await?
+                } else {
+                    info!(
+                        "Evaluating the local balance of {account} by staging execution of \
+                        known incoming messages"
+                    );
+                    chain_client.query_owner_balance(account.owner).await?
+                };
                 let time_total = time_start.elapsed();
                 info!("Balance obtained after {} ms", time_total.as_millis());
-                println!("{balance}");
+                print_balance(
+                    &chain_client,
+                    account,
+                    balance,
+                    at_height,
+                    sign_output,
+                    options.output,
+                )
+                .await?;
             }
 
-            SyncBalance { account } => {
+            SyncBalance {
+                account,
+                sign_output,
+            } => {
                 let context = options
                     .create_client_context(storage, wallet, keystore)
                     .await?;
@@ -476,17 +873,50 @@ impl Runnable for Job {
                     "Synchronizing balance confirmed after {} ms",
                     time_total.as_millis()
                 );
-                println!("{balance}");
+                print_balance(
+                    &chain_client,
+                    account,
+                    balance,
+                    None,
+                    sign_output,
+                    options.output,
+                )
+                .await?;
             }
 
             Sync {
                 chain_id,
                 next_height,
                 until_block_time,
+                all,
+                parallelism,
             } => {
                 let context = options
                     .create_client_context(storage, wallet, keystore)
                     .await?;
+                if all {
+                    let chain_ids = context.wallet().chain_ids().try_collect().await?;
+                    let summary = WalletSync::run(
+                        &context,
+                        chain_ids,
+                        parallelism,
+                        next_height,
+                        until_block_time,
+                    )
+                    .await;
+                    if options.output.is_json() {
+                        output::print_json(&summary)?;
+                    } else {
+                        summary.print_table();
+                    }
+                    ensure!(
+                        summary.failed == 0,
+                        "Failed to synchronize {} of the {} chains",
+                        summary.failed,
+                        summary.chains.len()
+                    );
+                    return Ok(());
+                }
                 let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
                 let chain_client = context.make_chain_client(chain_id).await?;
                 info!("Synchronizing chain information");
@@ -514,6 +944,7 @@ impl Runnable for Job {
                 let follow_only = context
                     .wallet()
                     .get(chain_id)
+                    .await?
                     .is_some_and(|chain| chain.is_follow_only());
                 if follow_only {
                     anyhow::bail!(
@@ -531,6 +962,21 @@ impl Runnable for Job {
                     certificates.len(),
                     time_total.as_millis()
                 );
+                if options.output.is_json() {
+                    let blocks = certificates
+                        .iter()
+                        .map(|certificate| {
+                            serde_json::json!({
+                                "hash": certificate.hash(),
+                                "height": certificate.value().block().header.height,
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    output::print_json(&serde_json::json!({
+                        "chainId": chain_id,
+                        "blocks": blocks,
+                    }))?;
+                }
             }
 
             QueryShardInfo { chain_id } => {
@@ -626,6 +1072,10 @@ impl Runnable for Job {
                                             maximum_oracle_response_bytes,
                                             maximum_http_response_bytes,
                                             http_request_timeout_ms,
+                                            maximum_http_requests_per_application_per_block,
+                                            maximum_http_response_bytes_per_application_per_block,
+                                            maximum_http_requests_per_application_per_epoch,
+                                            maximum_http_response_bytes_per_application_per_epoch,
                                             http_request_allow_list,
                                             free_application_ids,
                                             flags,
@@ -697,9 +1147,27 @@ impl Runnable for Job {
                                             .unwrap_or(existing_policy.maximum_http_response_bytes),
                                         http_request_timeout_ms: http_request_timeout_ms
                                             .unwrap_or(existing_policy.http_request_timeout_ms),
+                                        maximum_http_requests_per_application_per_block:
+                                            maximum_http_requests_per_application_per_block.unwrap_or(
+                                                existing_policy.maximum_http_requests_per_application_per_block,
+                                            ),
+                                        maximum_http_response_bytes_per_application_per_block:
+                                            maximum_http_response_bytes_per_application_per_block.unwrap_or(
+                                                existing_policy.maximum_http_response_bytes_per_application_per_block,
+                                            ),
+                                        maximum_http_requests_per_application_per_epoch:
+                                            maximum_http_requests_per_application_per_epoch.unwrap_or(
+                                                existing_policy.maximum_http_requests_per_application_per_epoch,
+                                            ),
+                                        maximum_http_response_bytes_per_application_per_epoch:
+                                            maximum_http_response_bytes_per_application_per_epoch.unwrap_or(
+                                                existing_policy.maximum_http_response_bytes_per_application_per_epoch,
+                                            ),
                                         http_request_allow_list: http_request_allow_list
                                             .map(BTreeSet::from_iter)
                                             .unwrap_or(existing_policy.http_request_allow_list),
+                                        http_request_allow_list_overrides: existing_policy
+                                            .http_request_allow_list_overrides,
                                         free_application_ids: free_application_ids
                                             .map(|ids| {
                                                 ids.into_iter().map(|s| s.parse()).collect::<Result<
@@ -722,6 +1190,7 @@ impl Runnable for Job {
                                             .expect("Invalid protocol flag")
                                             .unwrap_or(existing_policy.flags),
                                     };
+                                    policy.enable_flags_of_set_fields();
                                     info!("{policy}");
                                     if committee.policy() == &policy {
                                         return Ok(ClientOutcome::Committed(None));
@@ -776,6 +1245,137 @@ impl Runnable for Job {
                 );
             }
 
+            Governance(governance_command) => {
+                let mut context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let admin_chain_id = context.admin_chain_id();
+                let chain_client = context.make_chain_client(admin_chain_id).await?;
+                chain_client.synchronize_chain_state(admin_chain_id).await?;
+                match governance_command {
+                    GovernanceCommand::ShowCommittee => {
+                        let committee = chain_client.local_committee().await?;
+                        println!("{}", serde_json::to_string_pretty(&*committee)?);
+                    }
+                    GovernanceCommand::Propose {
+                        committee_path,
+                        voting_period,
+                    } => {
+                        let committee: Committee =
+                            serde_json::from_reader(fs_err::File::open(&committee_path)?)
+                                .context("Failed to parse the proposed committee")?;
+                        let voting_period = TimeDelta::from_secs(voting_period.as_secs());
+                        info!("Proposing a new committee");
+                        context
+                            .apply_client_command(&chain_client, |chain_client| {
+                                let chain_client = chain_client.clone();
+                                let committee = committee.clone();
+                                async move {
+                                    chain_client
+                                        .propose_new_committee(committee, voting_period)
+                                        .await
+                                }
+                            })
+                            .await
+                            .context("Failed to create the governance proposal")?;
+                    }
+                    GovernanceCommand::List => {
+                        let (proposals, ownership) = chain_client.governance_proposals().await?;
+                        let proposals = proposals
+                            .into_iter()
+                            .map(|(proposal_id, proposal)| {
+                                let (approvals, rejections) = proposal.tally(&ownership);
+                                serde_json::json!({
+                                    "id": proposal_id,
+                                    "blob_hash": proposal.blob_hash,
+                                    "proposer": proposal.proposer,
+                                    "voting_deadline": proposal.voting_deadline,
+                                    "approvals": approvals,
+                                    "rejections": rejections,
+                                    "approved": proposal.is_approved(&ownership),
+                                    "votes": proposal.votes,
+                                })
+                            })
+                            .collect::<Vec<_>>();
+                        println!("{}", serde_json::to_string_pretty(&proposals)?);
+                    }
+                    GovernanceCommand::Vote {
+                        proposal_id,
+                        reject,
+                    } => {
+                        info!("Voting on governance proposal {proposal_id}");
+                        context
+                            .apply_client_command(&chain_client, |chain_client| {
+                                let chain_client = chain_client.clone();
+                                async move {
+                                    chain_client
+                                        .vote_on_governance_proposal(proposal_id, !reject)
+                                        .await
+                                }
+                            })
+                            .await
+                            .context("Failed to vote on the governance proposal")?;
+                    }
+                    GovernanceCommand::Resolve { proposal_id } => {
+                        info!("Resolving governance proposal {proposal_id}");
+                        context
+                            .apply_client_command(&chain_client, |chain_client| {
+                                let chain_client = chain_client.clone();
+                                async move {
+                                    chain_client.resolve_governance_proposal(proposal_id).await
+                                }
+                            })
+                            .await
+                            .context("Failed to resolve the governance proposal")?;
+                    }
+                }
+                context.update_wallet_from_client(&chain_client).await?;
+            }
+
+            Policy(policy_command) => {
+                let update = match policy_command {
+                    PolicyCommand::AllowHttp {
+                        hosts,
+                        application_id,
+                    } => HttpAllowListUpdate {
+                        application_id,
+                        add: hosts.into_iter().collect(),
+                        remove: BTreeSet::new(),
+                    },
+                    PolicyCommand::DisallowHttp {
+                        hosts,
+                        application_id,
+                    } => HttpAllowListUpdate {
+                        application_id,
+                        add: BTreeSet::new(),
+                        remove: hosts.into_iter().collect(),
+                    },
+                };
+                let mut context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let admin_chain_id = context.admin_chain_id();
+                let chain_client = context.make_chain_client(admin_chain_id).await?;
+                chain_client.synchronize_chain_state(admin_chain_id).await?;
+                info!("Updating the HTTP allow list");
+                let maybe_certificate = context
+                    .apply_client_command(&chain_client, |chain_client| {
+                        let chain_client = chain_client.clone();
+                        let update = update.clone();
+                        async move { chain_client.update_http_allow_list(update).await }
+                    })
+                    .await
+                    .context("Failed to update the HTTP allow list")?;
+                match maybe_certificate {
+                    Some(certificate) => info!(
+                        "Updated the HTTP allow list in block {}",
+                        certificate.hash()
+                    ),
+                    None => info!("The HTTP allow list is already up to date"),
+                }
+                context.update_wallet_from_client(&chain_client).await?;
+            }
+
             #[cfg_attr(
                 not(feature = "opentelemetry"),
                 allow(unreachable_code, unused_variables)
@@ -802,8 +1402,13 @@ impl Runnable for Job {
                             tokens_per_chain,
                             transactions_per_block,
                             fungible_application_id,
+                            workload,
+                            application_id,
+                            application_operation,
+                            payload_size,
                             bps,
                             close_chains,
+                            persistent_chains,
                             health_check_endpoints,
                             wrap_up_max_in_flight,
                             confirm_before_start,
@@ -824,6 +1429,10 @@ impl Runnable for Job {
                             "Number of transactions per block must be greater than 0"
                         );
                         assert!(bps > 0, "BPS must be greater than 0");
+                        let application_operation = application_operation
+                            .map(|operation| linera_base::hex::decode(&operation))
+                            .transpose()
+                            .context("invalid hex for the application operation")?;
 
                         let listener_config = ChainListenerConfig {
                             skip_process_inbox: true,
@@ -859,6 +1468,7 @@ impl Runnable for Job {
                                     .wrap_up_benchmark(
                                         chain_clients,
                                         close_chains,
+                                        !persistent_chains,
                                         wrap_up_max_in_flight,
                                     )
                                     .await?;
@@ -901,19 +1511,36 @@ impl Runnable for Job {
                                     .copied()
                                     .filter(|id| *id != source)
                                     .collect();
-                                if let Some(app_id) = fungible_application_id {
-                                    Ok(Box::new(FungibleTransferGenerator::new(
-                                        app_id,
-                                        source,
-                                        destinations,
-                                        single_destination_per_block,
-                                    )?))
-                                } else {
-                                    Ok(Box::new(NativeFungibleTransferGenerator::new(
-                                        source,
-                                        destinations,
-                                        single_destination_per_block,
-                                    )?))
+                                match (workload, fungible_application_id) {
+                                    (BenchmarkWorkload::Transfers, Some(app_id)) => {
+                                        Ok(Box::new(FungibleTransferGenerator::new(
+                                            app_id,
+                                            source,
+                                            destinations,
+                                            single_destination_per_block,
+                                        )?))
+                                    }
+                                    (BenchmarkWorkload::Transfers, None) => {
+                                        Ok(Box::new(NativeFungibleTransferGenerator::new(
+                                            source,
+                                            destinations,
+                                            single_destination_per_block,
+                                        )?))
+                                    }
+                                    (BenchmarkWorkload::ApplicationCalls, _) => {
+                                        let application_id = application_id.context(
+                                            "--workload application-calls requires \
+                                             --application-id",
+                                        )?;
+                                        Ok(Box::new(ApplicationCallGenerator::new(
+                                            application_id,
+                                            application_operation.clone(),
+                                            payload_size,
+                                        )?))
+                                    }
+                                    (BenchmarkWorkload::Blobs, _) => {
+                                        Ok(Box::new(BlobPublicationGenerator::new(payload_size)?))
+                                    }
                                 }
                             })
                             .collect::<Result<_, _>>()?;
@@ -935,7 +1562,12 @@ impl Runnable for Job {
                             .map_err(|_| anyhow::anyhow!("Failed to unwrap shared context"))?
                             .into_inner();
                         context
-                            .wrap_up_benchmark(chain_clients, close_chains, wrap_up_max_in_flight)
+                            .wrap_up_benchmark(
+                                chain_clients,
+                                close_chains,
+                                !persistent_chains,
+                                wrap_up_max_in_flight,
+                            )
                             .await?;
                     }
 
@@ -1267,6 +1899,7 @@ impl Runnable for Job {
                 chain_id,
                 application_id,
                 query,
+                sign_output,
             } => {
                 let context = options
                     .create_client_context(storage, wallet, keystore)
@@ -1287,7 +1920,11 @@ impl Runnable for Job {
                     linera_execution::QueryResponse::User(bytes) => {
                         let response: Value = serde_json::from_slice(&bytes)?;
                         let data = &response["data"];
-                        println!("{data}");
+                        if sign_output {
+                            print_signed_output(&chain_client, data.clone()).await?;
+                        } else {
+                            println!("{data}");
+                        }
                     }
                     linera_execution::QueryResponse::System(_) => {
                         unreachable!("cannot get a system response for a user query")
@@ -1295,7 +1932,45 @@ impl Runnable for Job {
                 }
             }
 
+            VerifyOutput { path } => {
+                let bytes = match path {
+                    Some(path) => fs_err::read(path)?,
+                    None => {
+                        let mut bytes = Vec::new();
+                        std::io::Read::read_to_end(&mut std::io::stdin(), &mut bytes)?;
+                        bytes
+                    }
+                };
+                let signed: SignedOutput = serde_json::from_slice(&bytes)?;
+                let signer = signed
+                    .verify()
+                    .context("The signature of the output is invalid")?;
+                let statement = &signed.statement;
+                let context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let chain_client = context.make_chain_client(statement.chain_id).await?;
+                chain_client.synchronize_from_validators().await?;
+                let ownership = chain_client.query_chain_ownership().await?;
+                ensure!(
+                    ownership.is_owner(&signer),
+                    "The output was signed by {signer}, which is not an owner of chain {}",
+                    statement.chain_id
+                );
+                info!(
+                    "Output signed by {signer} at {} for chain {} at height {} (block hash: {})",
+                    statement.timestamp,
+                    statement.chain_id,
+                    statement.next_block_height,
+                    statement
+                        .block_hash
+                        .map_or_else(|| "none".to_owned(), |hash| hash.to_string()),
+                );
+                println!("{}", signed.output()?);
+            }
+
             Service {
+                command: None,
                 config,
                 port,
                 #[cfg(with_metrics)]
@@ -1309,7 +1984,22 @@ impl Runnable for Job {
                 allowed_subscriptions,
                 subscription_ttls,
                 pause,
+                hot_reload_services,
+                webhook_config,
+                consumer_cursors,
+                access_tokens,
+                event_index,
+                explorer,
+                exporter_feed_port,
+                query_limits,
+                security,
             } => {
+                let port = port.context("`--port` is required")?;
+                // A read replica only learns about new blocks from the exporter feed.
+                let read_only = read_only || exporter_feed_port.is_some();
+                let pause = pause || exporter_feed_port.is_some();
+                #[cfg(with_metrics)]
+                let metrics_port = metrics_port.context("`--metrics-port` is required")?;
                 let context = options
                     .create_client_context(storage, wallet, keystore)
                     .await?;
@@ -1395,6 +2085,41 @@ impl Runnable for Job {
                     ))
                 };
 
+                let webhooks = webhook_config
+                    .map(|path| {
+                        let config = linera_service::webhook::WebhookConfig::read(&path)?;
+                        anyhow::Ok(Arc::new(linera_service::webhook::WebhookBridge::new(
+                            config,
+                        )?))
+                    })
+                    .transpose()?;
+
+                let consumer_cursors = consumer_cursors
+                    .map(|path| {
+                        anyhow::Ok(Arc::new(
+                            linera_service::consumer_cursor::ConsumerCursors::open(&path)?,
+                        ))
+                    })
+                    .transpose()?;
+
+                let access_control = access_tokens
+                    .map(|path| {
+                        anyhow::Ok(Arc::new(
+                            linera_service::access_control::AccessControl::open(&path)?,
+                        ))
+                    })
+                    .transpose()?;
+
+                let event_index = linera_service::event_index::EventIndex::open(
+                    context.lock().await.storage().clone(),
+                    event_index,
+                )
+                .await?;
+
+                let query_limits = linera_service::query_limits::QueryLimits::new(query_limits)?;
+                let tls = security.tls_config().await?;
+                let api_keys = security.api_keys()?;
+
                 let service = NodeService::new(
                     config,
                     port,
@@ -1408,11 +2133,23 @@ impl Runnable for Job {
                     cancellation_token.clone(),
                     options.enable_memory_profiling(),
                     pause,
-                );
+                    hot_reload_services,
+                    webhooks,
+                    consumer_cursors,
+                )
+                .with_signing_relay(signing_relay)
+                .with_access_control(access_control)
+                .with_event_index(event_index)
+                .with_explorer(explorer)
+                .with_exporter_feed(exporter_feed_port)
+                .with_query_limits(query_limits)
+                .with_tls(tls)
+                .with_api_keys(api_keys);
                 service.run(cancellation_token, command_receiver).await?;
             }
 
             Faucet {
+                command: None,
                 chain_id,
                 port,
                 #[cfg(with_metrics)]
@@ -1423,7 +2160,14 @@ impl Runnable for Job {
                 config,
                 storage_path,
                 max_batch_size,
+                invite_signer,
+                max_requests_per_ip,
+                max_requests_per_owner,
+                quota_window,
+                trust_forwarded_for,
             } => {
+                let amount = amount.context("`--amount` is required")?;
+                let storage_path = storage_path.context("`--storage-path` is required")?;
                 let genesis_config = wallet.genesis_config().clone();
 
                 let context = options
@@ -1449,6 +2193,13 @@ impl Runnable for Job {
                     chain_id,
                     initial_claim_amount: amount,
                     daily_claim_amount,
+                    invite_signer,
+                    quotas: QuotaConfig {
+                        max_requests_per_ip,
+                        max_requests_per_owner,
+                        window: TimeDelta::from_duration(quota_window),
+                        trust_forwarded_for,
+                    },
                     end_timestamp,
                     genesis_config: Arc::new(genesis_config),
                     chain_listener_config: config,
@@ -1469,6 +2220,13 @@ impl Runnable for Job {
                 vm_runtime,
                 formats,
                 publisher,
+                dry_run,
+                attest,
+                source_commit,
+                source_repository,
+                toolchain,
+                builder_id,
+                attestation_signer,
             } => {
                 let mut context = options
                     .create_client_context(storage, wallet, keystore)
@@ -1476,12 +2234,67 @@ impl Runnable for Job {
 
                 let start_time = Instant::now();
                 let publisher = publisher.unwrap_or_else(|| context.default_chain());
-                info!("Publishing module on chain {}", publisher);
                 let chain_client = context.make_chain_client(publisher).await?;
-                let module_id = context
-                    .publish_module(&chain_client, contract, service, vm_runtime, formats)
-                    .await?;
-                println!("{module_id}");
+                if dry_run {
+                    let (blobs, module_id) = context
+                        .load_module_blobs(contract, service, vm_runtime, formats)
+                        .await?;
+                    if context.is_module_published(module_id).await? {
+                        info!("Module {module_id} is already published and would be reused");
+                    }
+                    let operation = Operation::system(SystemOperation::PublishModule { module_id });
+                    let outcome = chain_client
+                        .dry_run(vec![operation], blobs)
+                        .await
+                        .context("Failed to execute the publication locally")?;
+                    return print_dry_run(&outcome, options.output);
+                }
+                info!("Publishing module on chain {}", publisher);
+                if attest {
+                    let signer = match attestation_signer {
+                        Some(signer) => signer,
+                        None => chain_client.preferred_owner().context(
+                            "The publisher chain has no preferred owner to sign the \
+                             attestation; use --attestation-signer",
+                        )?,
+                    };
+                    let source_commit = match source_commit {
+                        Some(source_commit) => source_commit,
+                        None => command_output("git", &["rev-parse", "HEAD"])
+                            .await
+                            .context("Failed to find the source commit; use --source-commit")?,
+                    };
+                    let toolchain = match toolchain {
+                        Some(toolchain) => toolchain,
+                        None => command_output("rustc", &["--version"])
+                            .await
+                            .context("Failed to find the toolchain; use --toolchain")?,
+                    };
+                    let build = BuildInfo {
+                        builder_id: builder_id.unwrap_or_else(|| signer.to_string()),
+                        source_repository,
+                        source_commit,
+                        toolchain,
+                    };
+                    let (module_id, attestation_hash) = context
+                        .publish_attested_module(
+                            &chain_client,
+                            contract,
+                            service,
+                            vm_runtime,
+                            formats,
+                            build,
+                            signer,
+                        )
+                        .await?;
+                    println!("{module_id}");
+                    println!("{attestation_hash}");
+                } else {
+                    let module_id = context
+                        .publish_module(&chain_client, contract, service, vm_runtime, formats)
+                        .await?;
+                    println!("{module_id}");
+                }
                 info!(
                     "Module published in {} ms",
                     start_time.elapsed().as_millis()
@@ -1506,9 +2319,61 @@ impl Runnable for Job {
                 info!("Events listed in {} ms", start_time.elapsed().as_millis());
             }
 
+            SubscribeEvents {
+                chain_id,
+                stream_id,
+                from_index,
+            } => {
+                let context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                context
+                    .client
+                    .extend_chain_mode(chain_id, ListeningMode::FollowChain);
+                let chain_client = context.make_chain_client(chain_id).await?;
+                // Listen before reading the stream, so that no new event is missed.
+                let (listener, _abort, mut notifications) = chain_client.listen().await?;
+                tokio::spawn(listener);
+                chain_client.synchronize_from_validators().await?;
+                let mut next_index = match from_index {
+                    Some(index) => index,
+                    None => chain_client.next_event_index(&stream_id).await?,
+                };
+                info!("Following stream {stream_id} of chain {chain_id} from index {next_index}");
+                loop {
+                    let events = chain_client
+                        .events_from_index(stream_id.clone(), next_index)
+                        .await?;
+                    for event in &events {
+                        println!("{}", serde_json::to_string(event)?);
+                    }
+                    if let Some(last) = events.last() {
+                        next_index = last.index.checked_add(1).context("the stream is full")?;
+                        continue;
+                    }
+                    // Wait for new events in the stream.
+                    loop {
+                        let notification = notifications
+                            .next()
+                            .await
+                            .context("stopped receiving notifications")?;
+                        if matches!(
+                            &notification.reason,
+                            Reason::NewEvents { event_streams, .. }
+                                if event_streams.contains(&stream_id)
+                        ) {
+                            break;
+                        }
+                    }
+                }
+            }
+
             PublishDataBlob {
                 blob_path,
                 publisher,
+                chunked,
+                chunk_size,
             } => {
                 let mut context = options
                     .create_client_context(storage, wallet, keystore)
@@ -1518,7 +2383,13 @@ impl Runnable for Job {
                 let publisher = publisher.unwrap_or_else(|| context.default_chain());
                 info!("Publishing data blob on chain {}", publisher);
                 let chain_client = context.make_chain_client(publisher).await?;
-                let hash = context.publish_data_blob(&chain_client, blob_path).await?;
+                let hash = if chunked {
+                    context
+                        .publish_chunked_data_blob(&chain_client, blob_path, chunk_size)
+                        .await?
+                } else {
+                    context.publish_data_blob(&chain_client, blob_path).await?
+                };
                 println!("{hash}");
                 info!(
                     "Data blob published in {} ms",
@@ -1527,7 +2398,12 @@ impl Runnable for Job {
             }
 
             // TODO(#2490): Consider removing or renaming this.
-            ReadDataBlob { hash, reader } => {
+            ReadDataBlob {
+                hash,
+                reader,
+                reassemble: false,
+                ..
+            } => {
                 let mut context = options
                     .create_client_context(storage, wallet, keystore)
                     .await?;
@@ -1540,6 +2416,35 @@ impl Runnable for Job {
                 info!("Data blob read in {} ms", start_time.elapsed().as_millis());
             }
 
+            ReadDataBlob {
+                hash,
+                reader,
+                reassemble: true,
+                output,
+            } => {
+                let context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+
+                let start_time = Instant::now();
+                let reader = reader.unwrap_or_else(|| context.default_chain());
+                info!("Reassembling chunked data blob {hash} via chain {reader}");
+                let chain_client = context.make_chain_client(reader).await?;
+                let bytes = context
+                    .reassemble_chunked_data_blob(&chain_client, hash)
+                    .await?;
+                if let Some(output) = output {
+                    fs_err::write(&output, &bytes)?;
+                } else {
+                    std::io::Write::write_all(&mut std::io::stdout(), &bytes)?;
+                }
+                info!(
+                    "Data blob of {} bytes reassembled in {} ms",
+                    bytes.len(),
+                    start_time.elapsed().as_millis()
+                );
+            }
+
             DescribeApplication { application_id } => {
                 let context = options
                     .create_client_context(storage, wallet, keystore)
@@ -1567,6 +2472,8 @@ impl Runnable for Job {
                 json_argument,
                 json_argument_path,
                 required_application_ids,
+                auto_fund,
+                dry_run,
             } => {
                 let mut context = options
                     .create_client_context(storage, wallet, keystore)
@@ -1578,10 +2485,76 @@ impl Runnable for Job {
                 let chain_client = context.make_chain_client(creator).await?;
                 let parameters = read_json(json_parameters, json_parameters_path)?;
                 let argument = read_json(json_argument, json_argument_path)?;
+                let required_application_ids = required_application_ids.unwrap_or_default();
+                context
+                    .resolve_required_applications(&chain_client, &required_application_ids)
+                    .await?;
+
+                if dry_run {
+                    let operation = Operation::system(SystemOperation::CreateApplication {
+                        module_id,
+                        parameters,
+                        instantiation_argument: argument,
+                        required_application_ids,
+                    });
+                    let outcome = chain_client
+                        .dry_run(vec![operation], Vec::new())
+                        .await
+                        .context("Failed to execute the instantiation locally")?;
+                    return print_dry_run(&outcome, options.output);
+                }
 
                 info!("Synchronizing");
                 context.process_inbox(&chain_client).await?;
 
+                if let Some(treasury) = auto_fund {
+                    // The creator chain may not be able to pay for executing the
+                    // instantiation locally, so the fees are estimated on top of the
+                    // treasury chain. The chain is not modified.
+                    let treasury_client = context.make_chain_client(treasury).await?;
+                    let operation = Operation::system(SystemOperation::CreateApplication {
+                        module_id,
+                        parameters: parameters.clone(),
+                        instantiation_argument: argument.clone(),
+                        required_application_ids: required_application_ids.clone(),
+                    });
+                    let estimated = treasury_client
+                        .suggested_fee_budget(vec![operation])
+                        .await
+                        .context("Failed to estimate the fees of the instantiation")?
+                        .estimated;
+                    let needed = estimated
+                        .saturating_mul(100 + AUTO_FUND_HEADROOM_PERCENT)
+                        .saturating_div(100);
+                    let balance = chain_client.query_balance().await?;
+                    info!(
+                        "Estimated instantiation fees: {}; balance of chain {}: {}",
+                        estimated, creator, balance
+                    );
+                    let shortfall = needed.saturating_sub(balance);
+                    if shortfall > Amount::ZERO {
+                        info!(
+                            "Transferring {} from treasury chain {} to chain {}",
+                            shortfall, treasury, creator
+                        );
+                        context
+                            .apply_client_command(&treasury_client, |treasury_client| {
+                                let treasury_client = treasury_client.clone();
+                                async move {
+                                    treasury_client
+                                        .transfer_to_account(
+                                            AccountOwner::CHAIN,
+                                            shortfall,
+                                            Account::chain(creator),
+                                        )
+                                        .await
+                                }
+                            })
+                            .await
+                            .context("Failed to fund the creator chain")?;
+                    }
+                }
+
                 let (application_id, _) = context
                     .apply_client_command(&chain_client, move |chain_client| {
                         let parameters = parameters.clone();
@@ -1594,7 +2567,7 @@ impl Runnable for Job {
                                     module_id,
                                     parameters,
                                     argument,
-                                    required_application_ids.unwrap_or_default(),
+                                    required_application_ids,
                                 )
                                 .await
                         }
@@ -1619,6 +2592,7 @@ impl Runnable for Job {
                 json_argument,
                 json_argument_path,
                 required_application_ids,
+                dry_run,
             } => {
                 let mut context = options
                     .create_client_context(storage, wallet, keystore)
@@ -1630,29 +2604,49 @@ impl Runnable for Job {
                 let chain_client = context.make_chain_client(publisher).await?;
                 let parameters = read_json(json_parameters, json_parameters_path)?;
                 let argument = read_json(json_argument, json_argument_path)?;
+                let required_application_ids = required_application_ids.unwrap_or_default();
+                context
+                    .resolve_required_applications(&chain_client, &required_application_ids)
+                    .await?;
+
+                if dry_run {
+                    let (mut blobs, module_id) = context
+                        .load_module_blobs(contract, service, vm_runtime, None)
+                        .await?;
+                    let mut operations = Vec::new();
+                    if context.is_module_published(module_id).await? {
+                        info!("Module {module_id} is already published and would be reused");
+                        blobs.clear();
+                    } else {
+                        operations.push(Operation::system(SystemOperation::PublishModule {
+                            module_id,
+                        }));
+                    }
+                    operations.push(Operation::system(SystemOperation::CreateApplication {
+                        module_id,
+                        parameters,
+                        instantiation_argument: argument,
+                        required_application_ids,
+                    }));
+                    let outcome = chain_client
+                        .dry_run(operations, blobs)
+                        .await
+                        .context("Failed to execute the publication and instantiation locally")?;
+                    return print_dry_run(&outcome, options.output);
+                }
                 let module_id = context
                     .publish_module(&chain_client, contract, service, vm_runtime, None)
                     .await?;
 
-                let (application_id, _) = context
-                    .apply_client_command(&chain_client, move |chain_client| {
-                        let parameters = parameters.clone();
-                        let argument = argument.clone();
-                        let chain_client = chain_client.clone();
-                        let required_application_ids = required_application_ids.clone();
-                        async move {
-                            chain_client
-                                .create_application_untyped(
-                                    module_id,
-                                    parameters,
-                                    argument,
-                                    required_application_ids.unwrap_or_default(),
-                                )
-                                .await
-                        }
-                    })
-                    .await
-                    .context("Failed to create application")?;
+                let application_id = context
+                    .create_application_from_module(
+                        &chain_client,
+                        module_id,
+                        parameters,
+                        argument,
+                        required_application_ids,
+                    )
+                    .await?;
                 info!("{}", "Application published successfully!".green().bold());
                 info!(
                     "Application published and created in {} ms",
@@ -1661,11 +2655,25 @@ impl Runnable for Job {
                 println!("{application_id}");
             }
 
-            Assign { owner, chain_id } => {
+            Assign {
+                owner,
+                external_evm,
+                chain_id,
+                verify,
+            } => {
+                let owner = match (owner, external_evm) {
+                    (Some(owner), _) => owner,
+                    (None, Some(address)) => keystore.add_external_evm_owner(address.0 .0).await?,
+                    (None, None) => bail!("either --owner or --external-evm is required"),
+                };
                 let mut context = options
                     .create_client_context(storage, wallet, keystore)
                     .await?;
                 let start_time = Instant::now();
+                if verify {
+                    info!("Asking the key of {owner} to sign an ownership challenge");
+                    context.verify_key_ownership(owner, chain_id).await?;
+                }
                 info!(
                     "Linking chain {chain_id} to its corresponding key in the wallet, owned by \
                     {owner}",
@@ -1704,6 +2712,170 @@ impl Runnable for Job {
                 debug!("{:?}", certificate);
             }
 
+            Batch { commands, chain_id } => {
+                let batch = commands
+                    .iter()
+                    .map(|command| parse_batch_operation(command))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let mut context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let chain_id = batch_chain_id(&batch, chain_id, context.default_chain())?;
+                let chain_client = context.make_chain_client(chain_id).await?;
+                let required_application_ids = batch
+                    .iter()
+                    .flat_map(|entry| entry.required_application_ids.iter().copied())
+                    .collect::<Vec<_>>();
+                context
+                    .resolve_required_applications(&chain_client, &required_application_ids)
+                    .await?;
+                let operations = batch
+                    .iter()
+                    .map(|entry| entry.operation.clone())
+                    .collect::<Vec<_>>();
+                let blobs = batch
+                    .iter()
+                    .filter_map(|entry| entry.blob.clone())
+                    .collect::<Vec<_>>();
+                info!(
+                    "Executing {} operations in a single block on chain {}",
+                    operations.len(),
+                    chain_id
+                );
+                let time_start = Instant::now();
+                let certificate = context
+                    .apply_client_command(&chain_client, |chain_client| {
+                        let chain_client = chain_client.clone();
+                        let operations = operations.clone();
+                        let blobs = blobs.clone();
+                        async move { chain_client.execute_operations(operations, blobs).await }
+                    })
+                    .await
+                    .context("Failed to execute the batch")?;
+                info!(
+                    "Batch confirmed after {} ms",
+                    time_start.elapsed().as_millis()
+                );
+                // The created applications, in the order of the operations creating them.
+                let mut application_ids = certificate
+                    .block()
+                    .body
+                    .blobs
+                    .iter()
+                    .flatten()
+                    .map(Blob::id)
+                    .filter(|blob_id| blob_id.blob_type == BlobType::ApplicationDescription)
+                    .map(|blob_id| ApplicationId::new(blob_id.hash));
+                for entry in &batch {
+                    if let Some(blob) = &entry.blob {
+                        println!("{}", blob.id().hash);
+                    } else if matches!(
+                        &entry.operation,
+                        Operation::System(operation)
+                            if matches!(**operation, SystemOperation::CreateApplication { .. })
+                    ) {
+                        if let Some(application_id) = application_ids.next() {
+                            println!("{application_id}");
+                        }
+                    }
+                }
+                debug!("{:?}", certificate);
+            }
+
+            SignProposal {
+                commands,
+                chain_id,
+                owner,
+                payload,
+            } => {
+                let batch = commands
+                    .iter()
+                    .map(|command| parse_batch_operation(command))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let mut context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let chain_id = batch_chain_id(&batch, chain_id, context.default_chain())?;
+                let chain_client = context.make_chain_client(chain_id).await?;
+                let owner = owner
+                    .or_else(|| chain_client.preferred_owner())
+                    .with_context(|| {
+                        format!("No owner of chain {chain_id} is known; use --owner")
+                    })?;
+                let required_application_ids = batch
+                    .iter()
+                    .flat_map(|entry| entry.required_application_ids.iter().copied())
+                    .collect::<Vec<_>>();
+                context
+                    .resolve_required_applications(&chain_client, &required_application_ids)
+                    .await?;
+                let operations = batch
+                    .iter()
+                    .map(|entry| entry.operation.clone())
+                    .collect::<Vec<_>>();
+                let blobs = batch
+                    .iter()
+                    .filter_map(|entry| entry.blob.clone())
+                    .collect::<Vec<_>>();
+                let proposal = context
+                    .apply_client_command(&chain_client, |chain_client| {
+                        let chain_client = chain_client.clone();
+                        let operations = operations.clone();
+                        let blobs = blobs.clone();
+                        async move {
+                            chain_client
+                                .prepare_unsigned_proposal(operations, blobs, owner)
+                                .await
+                        }
+                    })
+                    .await
+                    .context("Failed to prepare the proposal")?;
+                let signing_hash = proposal.signing_hash();
+                let contents = serde_json::to_string_pretty(&ProposalPayload {
+                    signing_hash,
+                    proposal,
+                })?;
+                fs_err::write(&payload, contents)?;
+                info!(
+                    "Proposal for chain {chain_id} written to {}; it must be signed by {owner}",
+                    payload.display()
+                );
+                println!("{signing_hash}");
+            }
+
+            SubmitSigned { payload, signature } => {
+                let ProposalPayload {
+                    signing_hash,
+                    proposal,
+                } = serde_json::from_str(&fs_err::read_to_string(&payload)?)
+                    .with_context(|| format!("invalid proposal file {}", payload.display()))?;
+                ensure!(
+                    signing_hash == proposal.signing_hash(),
+                    "The signing hash in {} does not match the proposal",
+                    payload.display()
+                );
+                let signature = AccountSignature::from_slice(
+                    &linera_base::hex::decode(signature.trim())
+                        .context("invalid hex for the signature")?,
+                )?;
+                let mut context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let chain_id = proposal.content.block.chain_id;
+                let chain_client = context.make_chain_client(chain_id).await?;
+                let time_start = Instant::now();
+                let result = chain_client
+                    .submit_signed_proposal(proposal, signature)
+                    .await;
+                context.update_wallet_from_client(&chain_client).await?;
+                let certificate = result.context("Failed to submit the signed proposal")?;
+                info!(
+                    "Block confirmed after {} ms",
+                    time_start.elapsed().as_millis()
+                );
+                println!("{}", certificate.hash());
+            }
+
             Project(project_command) => match project_command {
                 ProjectCommand::PublishAndCreate {
                     path,
@@ -1724,42 +2896,48 @@ impl Runnable for Job {
                     info!("Creating application on chain {}", publisher);
                     let chain_client = context.make_chain_client(publisher).await?;
 
-                    let parameters = read_json(json_parameters, json_parameters_path)?;
-                    let argument = read_json(json_argument, json_argument_path)?;
                     let project_path = path.unwrap_or_else(|| env::current_dir().unwrap());
-
                     let project = project::Project::from_existing_project(&project_path)?;
+                    let vm_runtime = vm_runtime.unwrap_or_else(|| project.vm_runtime());
+
+                    let mut parameters = read_json(json_parameters, json_parameters_path)?;
+                    let mut argument = read_json(json_argument, json_argument_path)?;
+                    if vm_runtime == VmRuntime::Evm {
+                        // EVM contracts need no parameters nor argument unless their
+                        // constructor or `instantiate` function takes some.
+                        if parameters == b"null" {
+                            parameters = serde_json::to_vec(&Vec::<u8>::new())?;
+                        }
+                        if argument == b"null" {
+                            argument = serde_json::to_vec(&EvmInstantiation::default())?;
+                        }
+                    }
+                    let required_application_ids = required_application_ids.unwrap_or_default();
+                    context
+                        .resolve_required_applications(&chain_client, &required_application_ids)
+                        .await?;
+
                     let (contract_path, service_path) = project.build(name)?;
 
                     let module_id = context
                         .publish_module(
                             &chain_client,
-                            contract_path,
-                            service_path,
+                            contract_path.into(),
+                            service_path.into(),
                             vm_runtime,
                             None,
                         )
                         .await?;
 
-                    let (application_id, _) = context
-                        .apply_client_command(&chain_client, move |chain_client| {
-                            let parameters = parameters.clone();
-                            let argument = argument.clone();
-                            let chain_client = chain_client.clone();
-                            let required_application_ids = required_application_ids.clone();
-                            async move {
-                                chain_client
-                                    .create_application_untyped(
-                                        module_id,
-                                        parameters,
-                                        argument,
-                                        required_application_ids.unwrap_or_default(),
-                                    )
-                                    .await
-                            }
-                        })
-                        .await
-                        .context("Failed to create application")?;
+                    let application_id = context
+                        .create_application_from_module(
+                            &chain_client,
+                            module_id,
+                            parameters,
+                            argument,
+                            required_application_ids,
+                        )
+                        .await?;
                     info!("{}", "Application published successfully!".green().bold());
                     info!(
                         "Project published and created in {} ms",
@@ -1818,16 +2996,18 @@ impl Runnable for Job {
                     Please make sure you are connecting to a genuine faucet."
                 );
 
-                wallet.insert(
-                    description.id(),
-                    &wallet::Chain {
-                        owner: Some(owner),
-                        ..(&description).into()
-                    },
-                )?;
+                wallet
+                    .insert(
+                        description.id(),
+                        wallet::Chain {
+                            owner: Some(owner),
+                            ..(&description).into()
+                        },
+                    )
+                    .await?;
 
                 if set_default {
-                    wallet.set_default_chain(description.id())?;
+                    wallet.set_default_chain(description.id()).await?;
                 }
 
                 let context = options
@@ -1847,6 +3027,32 @@ impl Runnable for Job {
                 );
             }
 
+            Wallet(WalletCommand::Init { from_env: true, .. }) => {
+                let wallet_env = WalletEnv::read(&options.common.suffix())?;
+                let mut context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                for ChainAssignment { chain_id, owner } in wallet_env.chains {
+                    match owner {
+                        Some(owner) => context.assign_new_chain_to_key(chain_id, owner).await?,
+                        None => {
+                            context
+                                .client
+                                .extend_chain_mode(chain_id, ListeningMode::FollowChain);
+                            let chain_client = context.make_chain_client(chain_id).await?;
+                            chain_client.fetch_chain_info().await?;
+                            context.update_wallet_from_client(&chain_client).await?;
+                        }
+                    }
+                }
+                drop(context);
+                if let Some(chain_id) = wallet_env.default_chain {
+                    let mut wallet = options.client_wallet().await?;
+                    wallet.set_default_chain(chain_id).await?;
+                    wallet.save()?;
+                }
+            }
+
             Wallet(WalletCommand::Init {
                 faucet,
                 genesis_config_path,
@@ -1872,6 +3078,36 @@ impl Runnable for Job {
                 context.update_wallet_from_client(&chain_client).await?;
             }
 
+            Wallet(WalletCommand::Show {
+                chain_id,
+                owned,
+                with_balances,
+                ..
+            }) => {
+                let default_chain = wallet.default_chain();
+                let context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let chain_ids = if let Some(chain_id) = chain_id {
+                    ensure!(!owned, "Cannot specify both --owned and a chain ID");
+                    vec![chain_id]
+                } else if owned {
+                    context.wallet().owned_chain_ids().try_collect().await?
+                } else {
+                    context.wallet().chain_ids().try_collect().await?
+                };
+                if with_balances {
+                    let balances = WalletBalances::query(&context, chain_ids, default_chain).await;
+                    if options.output.is_json() {
+                        output::print_json(&balances)?;
+                    } else {
+                        balances.print_table(default_chain);
+                    }
+                } else {
+                    dashboard::run(&context, chain_ids, default_chain).await?;
+                }
+            }
+
             Wallet(WalletCommand::FollowChain { chain_id, sync }) => {
                 let context = options
                     .create_client_context(storage, wallet, keystore)
@@ -1918,6 +3154,81 @@ impl Runnable for Job {
                 println!("{json}");
             }
 
+            ReplayBlock {
+                certificate,
+                replay_oracle_responses,
+            } => {
+                let context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let report =
+                    replay::replay_block(context.storage(), certificate, replay_oracle_responses)
+                        .await?;
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                ensure!(
+                    report.is_consistent(),
+                    "The replayed block diverges from its certificate"
+                );
+            }
+
+            Module(ModuleCommand::Verify {
+                module_id,
+                attestation,
+            }) => {
+                let context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let attestations = context
+                    .verify_module_attestations(module_id, attestation)
+                    .await?;
+                if options.output.is_json() {
+                    output::print_json(&attestations)?;
+                } else {
+                    for attestation in &attestations {
+                        let build = &attestation.provenance.build;
+                        println!("Attestation {}", attestation.blob_hash);
+                        println!("  Signer:     {}", attestation.signer);
+                        println!("  Builder:    {}", build.builder_id);
+                        if let Some(source_repository) = &build.source_repository {
+                            println!("  Repository: {source_repository}");
+                        }
+                        println!("  Commit:     {}", build.source_commit);
+                        println!("  Toolchain:  {}", build.toolchain);
+                    }
+                }
+            }
+
+            InspectBlock {
+                certificate,
+                chain_id,
+                height,
+            } => {
+                let context = options
+                    .create_client_context(storage, wallet, keystore)
+                    .await?;
+                let certificate = match (certificate, height) {
+                    (Some(hash), _) => context.client.fetch_certificate(hash).await?,
+                    (None, Some(height)) => {
+                        let chain_id = chain_id.unwrap_or_else(|| context.default_chain());
+                        context
+                            .client
+                            .fetch_certificate_at_height(chain_id, height)
+                            .await?
+                    }
+                    (None, None) => bail!("Either --certificate or --height is required"),
+                };
+                let committee = context
+                    .storage()
+                    .committee_for_epoch(certificate.block().header.epoch)
+                    .await?;
+                let inspection = BlockInspection::new(&certificate, committee.as_deref());
+                if options.output.is_json() {
+                    output::print_json(&inspection)?;
+                } else {
+                    inspection.print_text();
+                }
+            }
+
             Chain(ChainCommand::ShowChainDescription { chain_id }) => {
                 let context = options
                     .create_client_context(storage, wallet, keystore)
@@ -1942,16 +3253,25 @@ impl Runnable for Job {
                         &mut options
                             .create_client_context(storage, wallet, keystore)
                             .await?,
+                        options.output,
                     )
                     .await?;
             }
 
             CreateGenesisConfig { .. }
+            | Faucet {
+                command: Some(_), ..
+            }
+            | Service {
+                command: Some(_), ..
+            }
             | Keygen
             | Net(_)
             | Storage { .. }
             | Wallet(_)
+            | Config(_)
             | ExtractScriptFromMarkdown { .. }
+            | Dev(_)
             | HelpMarkdown
             | Completion { .. } => {
                 unreachable!()
@@ -1961,6 +3281,171 @@ impl Runnable for Job {
     }
 }
 
+/// Prints the balance of `account`, as of the block at `height` if given, in the requested
+/// format.
+async fn print_balance<Env: linera_core::Environment>(
+    chain_client: &chain_client::ChainClient<Env>,
+    account: Account,
+    balance: Amount,
+    height: Option<BlockHeight>,
+    sign_output: bool,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let mut output = serde_json::json!({ "account": account, "balance": balance });
+    if let Some(height) = height {
+        output["height"] = serde_json::json!(height);
+    }
+    if sign_output {
+        print_signed_output(chain_client, output).await
+    } else if format.is_json() {
+        output::print_json(&output)
+    } else {
+        println!("{balance}");
+        Ok(())
+    }
+}
+
+/// Runs a program and returns the first line of its output, failing if it fails or prints
+/// nothing.
+async fn command_output(program: &str, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new(program).args(args).output().await?;
+    ensure!(
+        output.status.success(),
+        "`{program} {}` failed: {}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    let stdout = String::from_utf8(output.stdout)?;
+    let line = stdout.lines().next().unwrap_or_default().trim();
+    ensure!(
+        !line.is_empty(),
+        "`{program} {}` printed nothing",
+        args.join(" ")
+    );
+    Ok(line.to_owned())
+}
+
+/// Prints the resources used by a block executed with `--dry-run` and the fees charged for
+/// them, in the requested format.
+fn print_dry_run(outcome: &DryRunOutcome, format: OutputFormat) -> anyhow::Result<()> {
+    let resources = &outcome.resources;
+    let usage = [
+        ("blockSize", "block size (bytes)", resources.block_size),
+        ("wasmFuel", "Wasm fuel", resources.wasm_fuel),
+        ("evmFuel", "EVM fuel", resources.evm_fuel),
+        ("operations", "operations", u64::from(resources.operations)),
+        (
+            "operationBytes",
+            "operation bytes",
+            resources.operation_bytes,
+        ),
+        (
+            "messages",
+            "outgoing messages",
+            u64::from(resources.messages),
+        ),
+        ("messageBytes", "message bytes", resources.message_bytes),
+        (
+            "readOperations",
+            "read operations",
+            u64::from(resources.read_operations),
+        ),
+        (
+            "writeOperations",
+            "write operations",
+            u64::from(resources.write_operations),
+        ),
+        ("bytesRead", "bytes read", resources.bytes_read),
+        ("bytesWritten", "bytes written", resources.bytes_written),
+        ("blobsRead", "blobs read", u64::from(resources.blobs_read)),
+        (
+            "blobBytesRead",
+            "blob bytes read",
+            resources.blob_bytes_read,
+        ),
+        (
+            "blobsPublished",
+            "blobs published",
+            u64::from(resources.blobs_published),
+        ),
+        (
+            "blobBytesPublished",
+            "blob bytes published",
+            resources.blob_bytes_published,
+        ),
+        (
+            "eventsRead",
+            "events read",
+            u64::from(resources.events_read),
+        ),
+        (
+            "eventsPublished",
+            "events published",
+            u64::from(resources.events_published),
+        ),
+        (
+            "httpRequests",
+            "HTTP requests",
+            u64::from(resources.http_requests),
+        ),
+        (
+            "serviceOracleQueries",
+            "service oracle queries",
+            u64::from(resources.service_oracle_queries),
+        ),
+    ];
+    if format.is_json() {
+        let usage = usage
+            .iter()
+            .map(|(key, _, value)| (key.to_string(), Value::from(*value)))
+            .collect::<serde_json::Map<_, _>>();
+        return output::print_json(&serde_json::json!({
+            "fees": resources.fees,
+            "grants": resources.grants,
+            "chainBalance": outcome.chain_balance,
+            "resources": usage,
+        }));
+    }
+    println!("Dry run: nothing was submitted.");
+    println!("Fees: {}", resources.fees);
+    if resources.grants > Amount::ZERO {
+        println!("Message grants included in the fees: {}", resources.grants);
+    }
+    println!("Chain balance: {}", outcome.chain_balance);
+    if resources.fees > outcome.chain_balance {
+        println!("The chain cannot pay for these fees.");
+    }
+    println!("Resources used:");
+    for (_, label, value) in usage {
+        if value > 0 {
+            println!("  {label}: {value}");
+        }
+    }
+    Ok(())
+}
+
+/// Prints `output` as a [`SignedOutput`], together with the latest block of the chain.
+async fn print_signed_output<Env: linera_core::Environment>(
+    chain_client: &chain_client::ChainClient<Env>,
+    output: Value,
+) -> anyhow::Result<()> {
+    let owner = chain_client
+        .preferred_owner()
+        .context("The chain has no key in the wallet to sign the output with")?;
+    let info = chain_client.chain_info().await?;
+    let statement = OutputStatement::new(&output, &info);
+    let signature = chain_client
+        .signer()
+        .sign(&owner, &CryptoHash::new(&statement))
+        .await?;
+    let signed = SignedOutput {
+        statement,
+        signature,
+    };
+    println!("{}", serde_json::to_string_pretty(&signed)?);
+    Ok(())
+}
+
 async fn kill_all_processes(pids: &[u32]) {
     for &pid in pids {
         info!("Killing benchmark process (pid {})", pid);
@@ -2100,6 +3585,89 @@ impl RunnableWithStore for DatabaseToolJob<'_> {
                     println!("{id}");
                 }
             }
+            DatabaseToolCommand::Fsck { repair } => {
+                let storage = DbStorage::<D, _>::maybe_create_and_connect(
+                    &config,
+                    &namespace,
+                    None,
+                    cache_sizes,
+                )
+                .await?;
+                let report = linera_storage::fsck::check_storage(&storage, *repair).await?;
+                info!(
+                    "Checked {} chains, {} certificates and {} blobs in {} ms",
+                    report.chains,
+                    report.certificates,
+                    report.blobs,
+                    start_time.elapsed().as_millis()
+                );
+                for inconsistency in &report.repaired {
+                    println!("{inconsistency} (repaired)");
+                }
+                for inconsistency in &report.inconsistencies {
+                    println!("{inconsistency}");
+                }
+                if !report.is_consistent() {
+                    return Ok(1);
+                }
+            }
+            DatabaseToolCommand::ExportParquet { chain, output } => {
+                let storage = DbStorage::<D, _>::maybe_create_and_connect(
+                    &config,
+                    &namespace,
+                    None,
+                    cache_sizes,
+                )
+                .await?;
+                fs_err::create_dir_all(output)?;
+                let summary = parquet_export::export_chain(&storage, *chain, output).await?;
+                info!(
+                    "Exported {} blocks of chain {chain} from height {}, with {} operations, \
+                     {} messages and {} balances, in {} ms",
+                    summary.blocks,
+                    summary.start,
+                    summary.operations,
+                    summary.messages,
+                    summary.balances,
+                    start_time.elapsed().as_millis()
+                );
+            }
+            DatabaseToolCommand::ExportSnapshot { output } => {
+                let database = D::connect(&config, &namespace).await?;
+                let summary = snapshot::export(&database, output).await?;
+                info!(
+                    "Exported {} partitions with {} keys of namespace {namespace} to {} in {} ms",
+                    summary.partitions,
+                    summary.keys,
+                    output.display(),
+                    start_time.elapsed().as_millis()
+                );
+            }
+            DatabaseToolCommand::ImportSnapshot { input } => {
+                ensure!(
+                    !D::exists(&config, &namespace).await?,
+                    "the namespace {namespace} already exists; snapshots are only imported \
+                     into new namespaces"
+                );
+                D::create(&config, &namespace).await?;
+                let database = D::connect(&config, &namespace).await?;
+                let summary = match snapshot::import(&database, input).await {
+                    Ok(summary) => summary,
+                    Err(error) => {
+                        // Do not leave a partially imported namespace behind.
+                        D::delete(&config, &namespace).await?;
+                        return Err(error);
+                    }
+                };
+                info!(
+                    "Imported {} partitions with {} keys from {} into namespace {namespace} \
+                     in {} ms",
+                    summary.partitions,
+                    summary.keys,
+                    input.display(),
+                    start_time.elapsed().as_millis()
+                );
+            }
         }
         Ok(0)
     }
@@ -2201,10 +3769,22 @@ fn main() -> anyhow::Result<process::ExitCode> {
         span.record("wallet_id", wallet_id);
     }
 
-    let result = runtime
-        .enable_all()
-        .build()?
-        .block_on(run(&options).instrument(span));
+    let result = runtime.enable_all().build()?.block_on(
+        async {
+            let start = Instant::now();
+            let result = run(&options).await;
+            let report = UsageReport::new(
+                telemetry::command_name::<Options>(env::args()),
+                start.elapsed(),
+                matches!(result, Ok(0)),
+            );
+            if let Err(error) = options.telemetry.submit(report).await {
+                warn!("Failed to report usage metrics: {error:#}");
+            }
+            result
+        }
+        .instrument(span),
+    );
 
     Ok(match result {
         Ok(0) => process::ExitCode::SUCCESS,
@@ -2244,18 +3824,36 @@ fn maybe_quiet_logs_for_benchmark(options: &Options) {
 async fn run(options: &Options) -> Result<i32, Error> {
     maybe_quiet_logs_for_benchmark(options);
     let _guard = init_tracing(options)?;
+    virtual_time::follow_from_env()?;
     match &options.command {
         ClientCommand::HelpMarkdown => {
             clap_markdown::print_help_markdown::<Options>();
             Ok(0)
         }
 
+        ClientCommand::Dev(DevCommand::GenTestVectors { output }) => {
+            let suite = test_vectors::generate()?;
+            let json = serde_json::to_string_pretty(&suite)?;
+            match output {
+                Some(path) => fs_err::write(path, json + "\n")?,
+                None => println!("{json}"),
+            }
+            Ok(0)
+        }
+
         ClientCommand::ExtractScriptFromMarkdown {
             path,
+            typescript_runner,
+            javascript_runner,
+            python_runner,
             pause_after_linera_service,
             pause_after_gql_mutations,
         } => {
-            let file = crate::util::Markdown::new(path)?;
+            let file = crate::util::Markdown::new(path)?.with_runners(crate::util::ScriptRunners {
+                typescript: typescript_runner.clone(),
+                javascript: javascript_runner.clone(),
+                python: python_runner.clone(),
+            });
             let pause_after_linera_service =
                 Some(*pause_after_linera_service).filter(|p| !p.is_zero());
             let pause_after_gql_mutations =
@@ -2268,6 +3866,40 @@ async fn run(options: &Options) -> Result<i32, Error> {
             Ok(0)
         }
 
+        ClientCommand::Config(ConfigCommand::Show { effective: true }) => {
+            if options.output.is_json() {
+                output::print_json(&options.effective_options)?;
+            } else {
+                for option in &options.effective_options {
+                    if option.values.is_empty() {
+                        continue;
+                    }
+                    println!(
+                        "{} = {} ({})",
+                        option.name,
+                        option.values.join(" "),
+                        option.source
+                    );
+                }
+            }
+            Ok(0)
+        }
+
+        ClientCommand::Config(ConfigCommand::Show { effective: false }) => {
+            let files = config_file::load()?;
+            if options.output.is_json() {
+                output::print_json(&files)?;
+            } else if files.is_empty() {
+                println!("No {} file found", config_file::CONFIG_FILE_NAME);
+            } else {
+                for file in files {
+                    println!("# {}", file.path.display());
+                    println!("{}", toml::to_string(&file.options)?);
+                }
+            }
+            Ok(0)
+        }
+
         ClientCommand::Completion { shell } => {
             let mut cmd = <Options as clap::CommandFactory>::command();
             generate(
@@ -2316,6 +3948,10 @@ async fn run(options: &Options) -> Result<i32, Error> {
             maximum_oracle_response_bytes,
             maximum_http_response_bytes,
             http_request_timeout_ms,
+            maximum_http_requests_per_application_per_block,
+            maximum_http_response_bytes_per_application_per_block,
+            maximum_http_requests_per_application_per_epoch,
+            maximum_http_response_bytes_per_application_per_epoch,
             http_request_allow_list,
             free_application_ids,
             flags,
@@ -2326,7 +3962,7 @@ async fn run(options: &Options) -> Result<i32, Error> {
             let committee_config: CommitteeConfig = util::read_json(committee_config_path)
                 .expect("Unable to read committee config file");
             let existing_policy = policy_config.into_policy();
-            let policy = linera_execution::ResourceControlPolicy {
+            let mut policy = linera_execution::ResourceControlPolicy {
                 wasm_fuel_unit: wasm_fuel_unit_price.unwrap_or(existing_policy.wasm_fuel_unit),
                 evm_fuel_unit: evm_fuel_unit_price.unwrap_or(existing_policy.evm_fuel_unit),
                 read_operation: read_operation_price.unwrap_or(existing_policy.read_operation),
@@ -2371,10 +4007,26 @@ async fn run(options: &Options) -> Result<i32, Error> {
                     .unwrap_or(existing_policy.maximum_http_response_bytes),
                 http_request_timeout_ms: http_request_timeout_ms
                     .unwrap_or(existing_policy.http_request_timeout_ms),
+                maximum_http_requests_per_application_per_block:
+                    maximum_http_requests_per_application_per_block
+                        .unwrap_or(existing_policy.maximum_http_requests_per_application_per_block),
+                maximum_http_response_bytes_per_application_per_block:
+                    maximum_http_response_bytes_per_application_per_block.unwrap_or(
+                        existing_policy.maximum_http_response_bytes_per_application_per_block,
+                    ),
+                maximum_http_requests_per_application_per_epoch:
+                    maximum_http_requests_per_application_per_epoch
+                        .unwrap_or(existing_policy.maximum_http_requests_per_application_per_epoch),
+                maximum_http_response_bytes_per_application_per_epoch:
+                    maximum_http_response_bytes_per_application_per_epoch.unwrap_or(
+                        existing_policy.maximum_http_response_bytes_per_application_per_epoch,
+                    ),
                 http_request_allow_list: http_request_allow_list
                     .as_ref()
                     .map(|list| list.iter().cloned().collect())
                     .unwrap_or(existing_policy.http_request_allow_list),
+                http_request_allow_list_overrides: existing_policy
+                    .http_request_allow_list_overrides,
                 free_application_ids: free_application_ids
                     .as_ref()
                     .map(|ids| {
@@ -2397,6 +4049,7 @@ async fn run(options: &Options) -> Result<i32, Error> {
                     .expect("Invalid protocol flag")
                     .unwrap_or(existing_policy.flags),
             };
+            policy.enable_flags_of_set_fields();
             let timestamp = start_timestamp.map_or_else(Timestamp::now, |st| {
                 let micros =
                     u64::try_from(st.timestamp_micros()).expect("Start timestamp before 1970");
@@ -2459,26 +4112,68 @@ async fn run(options: &Options) -> Result<i32, Error> {
                 name,
                 linera_root,
                 dir,
+                vm,
             } => {
                 let start_time = Instant::now();
-                Project::create_new(name, linera_root.as_ref().map(AsRef::as_ref), dir.clone())?;
+                Project::create_new(
+                    name,
+                    linera_root.as_ref().map(AsRef::as_ref),
+                    dir.clone(),
+                    *vm,
+                )?;
                 info!(
                     "New project created in {} ms",
                     start_time.elapsed().as_millis()
                 );
                 Ok(0)
             }
-            ProjectCommand::Test { path } => {
+            ProjectCommand::Test {
+                path,
+                coverage,
+                record,
+                replay,
+            } => {
                 let start_time = Instant::now();
                 let path = path.clone().unwrap_or_else(|| env::current_dir().unwrap());
                 let project = Project::from_existing_project(&path)?;
-                project.test()?;
+                project.test(*coverage, *record, replay.as_deref())?;
                 info!(
                     "Test project created in {} ms",
                     start_time.elapsed().as_millis()
                 );
                 Ok(0)
             }
+            ProjectCommand::Build {
+                path,
+                name,
+                reproducible,
+                docker_image,
+            } => {
+                let start_time = Instant::now();
+                let path = path.clone().unwrap_or_else(|| env::current_dir().unwrap());
+                let project = Project::from_existing_project(&path)?;
+                let (contract_path, service_path) = if *reproducible {
+                    project.build_reproducible(name.clone(), docker_image.as_deref())?
+                } else {
+                    project.build(name.clone())?
+                };
+                let contract = Bytecode::load_from_file(&contract_path)
+                    .await
+                    .with_context(|| format!("failed to load {}", contract_path.display()))?;
+                let service = Bytecode::load_from_file(&service_path)
+                    .await
+                    .with_context(|| format!("failed to load {}", service_path.display()))?;
+                let (_, module_id) =
+                    create_bytecode_blobs(contract, service, project.vm_runtime(), None).await;
+                info!(
+                    "Project built in {} ms (contract blob hash {}, service blob hash {})",
+                    start_time.elapsed().as_millis(),
+                    module_id.contract_blob_hash,
+                    module_id.service_blob_hash,
+                );
+                println!("{module_id}");
+                Ok(0)
+            }
             ProjectCommand::PublishAndCreate { .. } => {
                 let start_time = Instant::now();
                 let wallet_path = options.wallet_path()?;
@@ -2499,6 +4194,135 @@ async fn run(options: &Options) -> Result<i32, Error> {
             }
         },
 
+        ClientCommand::Faucet {
+            command:
+                Some(FaucetCommand::MintInvites {
+                    count,
+                    signer,
+                    amount,
+                    expires_at,
+                }),
+            ..
+        } => {
+            let signer = match signer {
+                Some(signer) => *signer,
+                None => {
+                    let wallet = options.wallet()?;
+                    let chain_id = wallet
+                        .default_chain()
+                        .context("No default chain; use `--signer` to choose a key")?;
+                    wallet
+                        .get(chain_id)
+                        .and_then(|chain| chain.owner)
+                        .context("The default chain has no owner; use `--signer` to choose a key")?
+                }
+            };
+            let expiration = expires_at
+                .map(|time| {
+                    u64::try_from(time.timestamp_micros())
+                        .map(Timestamp::from)
+                        .context("Expiration time before 1970")
+                })
+                .transpose()?;
+            let keystore = options.keystore()?;
+            for _ in 0..*count {
+                let invite = Invite {
+                    nonce: CryptoHash::from(rand::random::<[u64; 4]>()),
+                    amount: *amount,
+                    expiration,
+                };
+                let signature = keystore.sign(&signer, &CryptoHash::new(&invite)).await?;
+                println!("{}", InviteCode { invite, signature });
+            }
+            info!("Minted {count} invite codes signed by {signer}");
+            Ok(0)
+        }
+
+        ClientCommand::Service {
+            command: Some(ServiceCommand::SetLogLevel { filter, url }),
+            ..
+        } => {
+            let endpoint = format!(
+                "{}{}",
+                url.trim_end_matches('/'),
+                linera_service::tracing::log_filter::LOG_LEVEL_PATH
+            );
+            let response = reqwest::Client::new()
+                .put(&endpoint)
+                .body(filter.clone())
+                .send()
+                .await?;
+            let status = response.status();
+            let body = response.text().await?;
+            ensure!(
+                status.is_success(),
+                "Failed to set the log filter at {endpoint}: {status}: {body}"
+            );
+            println!("{body}");
+            Ok(0)
+        }
+
+        ClientCommand::Service {
+            command: Some(ServiceCommand::Token(command)),
+            ..
+        } => {
+            use linera_service::access_control::{AccessScope, AccessTokens};
+
+            match command {
+                AccessTokenCommand::Issue {
+                    access_tokens,
+                    name,
+                    chains,
+                    applications,
+                    any_application,
+                    system,
+                    mutations,
+                } => {
+                    let mut tokens = persistent::File::read_or_create(access_tokens, || {
+                        Ok(AccessTokens::default())
+                    })?;
+                    let scope = AccessScope {
+                        chains: chains.iter().copied().collect(),
+                        applications: (!any_application)
+                            .then(|| applications.iter().copied().collect()),
+                        system: *system,
+                        mutations: mutations.iter().cloned().collect(),
+                    };
+                    let token = tokens.issue(name, scope)?;
+                    tokens.save()?;
+                    println!("{token}");
+                }
+                AccessTokenCommand::Rotate {
+                    access_tokens,
+                    name,
+                } => {
+                    let mut tokens = persistent::File::<AccessTokens>::read(access_tokens)?;
+                    let token = tokens.rotate(name)?;
+                    tokens.save()?;
+                    println!("{token}");
+                }
+                AccessTokenCommand::Revoke {
+                    access_tokens,
+                    name,
+                } => {
+                    let mut tokens = persistent::File::<AccessTokens>::read(access_tokens)?;
+                    tokens.revoke(name)?;
+                    tokens.save()?;
+                    info!("Revoked the access token {name}");
+                }
+                AccessTokenCommand::List { access_tokens } => {
+                    let tokens = linera_service::access_control::read_tokens(access_tokens)?;
+                    let scopes = tokens
+                        .tokens
+                        .iter()
+                        .map(|(name, token)| (name, &token.scope))
+                        .collect::<BTreeMap<_, _>>();
+                    println!("{}", serde_json::to_string_pretty(&scopes)?);
+                }
+            }
+            Ok(0)
+        }
+
         ClientCommand::Keygen => {
             let start_time = Instant::now();
             let mut keystore = options.keystore()?;
@@ -2527,8 +4351,33 @@ async fn run(options: &Options) -> Result<i32, Error> {
                 exporter_address: block_exporter_address,
                 exporter_port: block_exporter_port,
                 http_request_allow_list,
+                wait_ready,
+                ready_timeout,
+                ssh_inventory,
+                virtual_time,
+                virtual_time_port,
                 ..
             } => {
+                if let Some(inventory) = ssh_inventory {
+                    net_up_utils::handle_net_up_ssh(
+                        inventory,
+                        *other_initial_chains,
+                        *initial_amount,
+                        *testing_prng_seed,
+                        *policy_config,
+                        cross_chain_config.clone(),
+                        path,
+                        external_protocol,
+                        *with_faucet,
+                        *faucet_port,
+                        *faucet_amount,
+                        http_request_allow_list.clone(),
+                        wait_ready.then_some(*ready_timeout),
+                    )
+                    .boxed()
+                    .await?;
+                    return Ok(0);
+                }
                 net_up_utils::handle_net_up_service(
                     *other_initial_chains,
                     *initial_amount,
@@ -2548,6 +4397,49 @@ async fn run(options: &Options) -> Result<i32, Error> {
                     *faucet_port,
                     *faucet_amount,
                     http_request_allow_list.clone(),
+                    wait_ready.then_some(*ready_timeout),
+                    virtual_time.then_some(*virtual_time_port),
+                )
+                .boxed()
+                .await?;
+                Ok(0)
+            }
+
+            NetCommand::WaitReady { path, timeout } => {
+                net_up_utils::handle_net_wait_ready(path, *timeout).await?;
+                Ok(0)
+            }
+
+            NetCommand::Snapshot { dir, path } => {
+                let path = match path {
+                    Some(path) => path.clone(),
+                    None => options
+                        .wallet_path()?
+                        .parent()
+                        .context("the wallet path has no parent directory")?
+                        .to_path_buf(),
+                };
+                net_up_utils::handle_net_snapshot(
+                    &path,
+                    dir,
+                    &options.common.common_storage_options,
+                )
+                .await?;
+                Ok(0)
+            }
+
+            NetCommand::Restore {
+                dir,
+                path,
+                cross_chain_config,
+            } => {
+                net_up_utils::handle_net_restore(
+                    dir,
+                    path,
+                    // Not using the default value for storage
+                    &options.common.storage_config,
+                    cross_chain_config.clone(),
+                    &options.common.common_storage_options,
                 )
                 .boxed()
                 .await?;
@@ -2570,10 +4462,29 @@ async fn run(options: &Options) -> Result<i32, Error> {
         }
 
         ClientCommand::Wallet(wallet_command) => match wallet_command {
+            WalletCommand::Show { watch: true, .. } => {
+                ensure!(
+                    !options.output.is_json(),
+                    "`linera wallet show --watch` has no JSON output"
+                );
+                options.run_with_storage(Job(options.clone())).await??;
+                Ok(0)
+            }
+
+            WalletCommand::Show {
+                with_balances: true,
+                ..
+            } => {
+                options.run_with_storage(Job(options.clone())).await??;
+                Ok(0)
+            }
+
             WalletCommand::Show {
                 chain_id,
                 short,
                 owned,
+                watch: false,
+                with_balances: false,
             } => {
                 let wallet_path = options.wallet_path()?;
                 tracing::info!("Reading wallet from file: {}", wallet_path.display());
@@ -2586,7 +4497,15 @@ async fn run(options: &Options) -> Result<i32, Error> {
                 } else {
                     wallet.chain_ids()
                 };
-                if *short {
+                if options.output.is_json() {
+                    if *short {
+                        output::print_json(&chain_ids)?;
+                    } else {
+                        output::print_json(&linera_wallet_json::display::to_json(
+                            &wallet, chain_ids,
+                        ))?;
+                    }
+                } else if *short {
                     for chain_id in chain_ids {
                         println!("{chain_id}");
                     }
@@ -2644,22 +4563,38 @@ async fn run(options: &Options) -> Result<i32, Error> {
                 genesis_config_path,
                 faucet,
                 testing_prng_seed,
+                from_env,
             } => {
                 let start_time = Instant::now();
-                let genesis_config: GenesisConfig = match (genesis_config_path, faucet) {
-                    (None, None) => {
-                        anyhow::bail!("please specify one of `--faucet` or `--genesis`.")
-                    }
-                    (Some(genesis_config_path), _) => util::read_json(genesis_config_path)?,
-                    (None, Some(url)) => {
-                        let faucet = cli_wrappers::Faucet::new(url.clone());
-                        let version_info = faucet
-                            .version_info()
-                            .await
-                            .context("Failed to obtain version information from the faucet")?;
-                        if !version_info.is_compatible_with(&linera_version::VERSION_INFO) {
-                            warn!(
-                                "\
+                let env_genesis_config = if *from_env {
+                    let suffix = options.common.suffix();
+                    ensure!(
+                        wallet_env::keystore(&suffix)?.is_some(),
+                        "`--from-env` requires the keys in LINERA_KEYS{suffix} or \
+                         LINERA_KEYS_FILE{suffix}"
+                    );
+                    WalletEnv::read(&suffix)?.genesis_config
+                } else {
+                    None
+                };
+                let genesis_config: GenesisConfig =
+                    match (env_genesis_config, genesis_config_path, faucet) {
+                        (Some(genesis_config), _, _) => genesis_config,
+                        (None, None, None) => {
+                            anyhow::bail!("please specify one of `--faucet` or `--genesis`.")
+                        }
+                        (None, Some(genesis_config_path), _) => {
+                            util::read_json(genesis_config_path)?
+                        }
+                        (None, None, Some(url)) => {
+                            let faucet = cli_wrappers::Faucet::new(url.clone());
+                            let version_info = faucet
+                                .version_info()
+                                .await
+                                .context("Failed to obtain version information from the faucet")?;
+                            if !version_info.is_compatible_with(&linera_version::VERSION_INFO) {
+                                warn!(
+                                    "\
 Make sure to use a Linera client compatible with this network.
 --- Faucet info ---\
 {}\
@@ -2667,19 +4602,29 @@ Make sure to use a Linera client compatible with this network.
 --- This binary ---\
 {}\
 -------------------",
-                                version_info,
-                                linera_version::VERSION_INFO,
-                            );
+                                    version_info,
+                                    linera_version::VERSION_INFO,
+                                );
+                            }
+                            faucet.genesis_config().await.context(
+                                "Failed to obtain the genesis configuration from the faucet",
+                            )?
                         }
-                        faucet
-                            .genesis_config()
-                            .await
-                            .context("Failed to obtain the genesis configuration from the faucet")?
-                    }
-                };
-                let mut keystore = options.create_keystore(*testing_prng_seed)?;
-                keystore.save().await?;
-                options.create_wallet(genesis_config)?.save()?;
+                    };
+                if !*from_env {
+                    let mut keystore = options.create_keystore(*testing_prng_seed)?;
+                    keystore.save().await?;
+                    options.create_wallet(genesis_config)?.save()?;
+                } else if options.common.wallet_backend == WalletBackend::Storage {
+                    let job = MigrateWalletJob {
+                        genesis_config,
+                        chains: Vec::new(),
+                        default: None,
+                    };
+                    options.run_with_store(job).await?;
+                } else {
+                    options.create_wallet(genesis_config)?.save()?;
+                }
                 options.initialize_storage().boxed().await?;
                 options.run_with_storage(Job(options.clone())).await??;
                 info!(
@@ -2707,6 +4652,89 @@ Make sure to use a Linera client compatible with this network.
                 Ok(0)
             }
 
+            WalletCommand::Migrate => {
+                let start_time = Instant::now();
+                let wallet = Wallet::read(&options.wallet_path()?)?;
+                let job = MigrateWalletJob {
+                    genesis_config: wallet.genesis_config().clone(),
+                    chains: wallet.items(),
+                    default: wallet.default_chain(),
+                };
+                options.run_with_store(job).await?;
+                info!(
+                    "Wallet copied into storage in {} ms",
+                    start_time.elapsed().as_millis()
+                );
+                Ok(0)
+            }
+
+            WalletCommand::Export {
+                output,
+                passphrase_file,
+            } => {
+                let start_time = Instant::now();
+                // Fail early if the wallet is in storage.
+                options.wallet()?;
+                let passphrase = wallet_archive_passphrase(passphrase_file.as_deref())?;
+                linera_wallet_json::archive::export(
+                    &options.wallet_path()?,
+                    &options.keystore_path()?,
+                    output,
+                    &passphrase,
+                )?;
+                info!(
+                    "Wallet exported to {} in {} ms",
+                    output.display(),
+                    start_time.elapsed().as_millis()
+                );
+                Ok(0)
+            }
+
+            WalletCommand::Import {
+                input,
+                passphrase_file,
+            } => {
+                let start_time = Instant::now();
+                let passphrase = wallet_archive_passphrase(passphrase_file.as_deref())?;
+                let wallet_path = options.wallet_path()?;
+                linera_wallet_json::archive::import(
+                    input,
+                    &wallet_path,
+                    &options.keystore_path()?,
+                    &passphrase,
+                )?;
+                info!(
+                    "Wallet imported to {} in {} ms",
+                    wallet_path.display(),
+                    start_time.elapsed().as_millis()
+                );
+                Ok(0)
+            }
+
+            WalletCommand::Statement {
+                chain_id,
+                from,
+                to,
+                csv,
+                exchange_rates,
+            } => {
+                let journal = SpendingJournal::new(options.spending_journal_path()?);
+                let rates = exchange_rates
+                    .as_deref()
+                    .map(statement::ExchangeRates::read)
+                    .transpose()?;
+                let lines =
+                    statement::statement(journal.read()?, *chain_id, *from, *to, rates.as_ref())?;
+                if *csv {
+                    print!("{}", statement::to_csv(&lines, rates.is_some()));
+                } else if options.output.is_json() {
+                    output::print_json(&lines)?;
+                } else {
+                    statement::print_text(&lines);
+                }
+                Ok(0)
+            }
+
             WalletCommand::FollowChain { .. } | WalletCommand::RequestChain { .. } => {
                 options.run_with_storage(Job(options.clone())).await??;
                 Ok(0)
@@ -2729,7 +4757,58 @@ Make sure to use a Linera client compatible with this network.
 mod tests {
     use std::ffi::OsStr;
 
-    use super::color_override_from_env;
+    use linera_base::{
+        crypto::CryptoHash,
+        data_types::Amount,
+        identifiers::{Account, ApplicationId, ChainId},
+    };
+    use linera_execution::{system::SystemOperation, Operation};
+
+    use super::{color_override_from_env, parse_batch_operation, parse_transfers};
+
+    #[test]
+    fn transfers_are_parsed_from_csv_and_json() {
+        let chain_id = ChainId("aa".repeat(32).parse::<CryptoHash>().unwrap());
+        let expected = vec![
+            (Account::chain(chain_id), Amount::from_tokens(1)),
+            (Account::chain(chain_id), Amount::from_millis(2500)),
+        ];
+        let csv = format!("recipient,amount\n{chain_id},1\n\n {chain_id} , 2.5\n");
+        assert_eq!(parse_transfers(&csv, false).unwrap(), expected);
+        let json = format!(
+            r#"[{{"recipient": "{chain_id}", "amount": "1"}},
+                {{"recipient": "{chain_id}", "amount": "2.5"}}]"#
+        );
+        assert_eq!(parse_transfers(&json, true).unwrap(), expected);
+
+        assert!(parse_transfers(&format!("{chain_id}"), false).is_err());
+        assert!(parse_transfers(&format!("{chain_id},lots"), false).is_err());
+    }
+
+    #[test]
+    fn batch_subcommands_are_parsed_into_operations() {
+        let hash = "aa".repeat(32).parse::<CryptoHash>().unwrap();
+        let chain_id = ChainId(hash);
+        let application_id = ApplicationId::new(hash);
+        let transfer =
+            parse_batch_operation(&format!("transfer 1.5 --from {chain_id} --to {chain_id}"))
+                .unwrap();
+        assert_eq!(transfer.chain_id, Some(chain_id));
+        assert!(matches!(
+            transfer.operation,
+            Operation::System(operation) if matches!(*operation, SystemOperation::Transfer { .. })
+        ));
+
+        let user = parse_batch_operation(&format!(
+            "execute-operation --application-id {application_id} --operation '0a0b'"
+        ))
+        .unwrap();
+        assert_eq!(user.chain_id, None);
+        assert!(matches!(user.operation, Operation::User { bytes, .. } if bytes == [10, 11]));
+
+        assert!(parse_batch_operation("sync").is_err());
+        assert!(parse_batch_operation("transfer 'unterminated").is_err());
+    }
 
     #[test]
     fn no_color_disables_colors() {
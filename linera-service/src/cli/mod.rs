@@ -7,7 +7,29 @@
 pub mod command;
 /// Options shared across multiple command-line subcommands.
 pub mod common_options;
+/// The `linera.toml` configuration files providing defaults for the options of the client.
+pub mod config_file;
+/// The live wallet dashboard.
+pub mod dashboard;
+/// The decoding of block certificates, for debugging.
+pub mod inspect_block;
 /// Helpers for the `net up` command that spins up a local network.
 pub mod net_up_utils;
+/// The human-readable or machine-readable output of the client commands.
+pub mod output;
+/// Exporting the blocks of a chain to Parquet files, for analytics.
+pub mod parquet_export;
+/// Portable snapshots of a storage namespace, for backups and migrations.
+pub mod snapshot;
+/// Statements of the tokens moved by the blocks of the wallet, from its spending journal.
+pub mod statement;
+/// Canonical encodings of protocol structures, for conformance tests of other clients.
+pub mod test_vectors;
 pub mod validator;
 pub mod validator_benchmark;
+/// The balances and heights of the wallet's chains, queried concurrently.
+pub mod wallet_balances;
+/// Wallets configured by environment variables, for containerized deployments.
+pub mod wallet_env;
+/// The synchronization of all the wallet's chains, concurrently.
+pub mod wallet_sync;
@@ -1,29 +1,126 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{num::NonZeroU16, str::FromStr};
+use std::{
+    fmt::Display,
+    future::Future,
+    num::NonZeroU16,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
-use linera_base::{data_types::Amount, listen_for_shutdown_signals, time::Duration};
+use anyhow::{bail, Context as _};
+use linera_base::{
+    data_types::Amount,
+    identifiers::ChainId,
+    listen_for_shutdown_signals,
+    time::{Duration, Instant},
+};
 use linera_client::client_options::ResourceControlPolicyConfig;
-use linera_rpc::config::CrossChainConfig;
+use linera_core::node::{ValidatorNode as _, ValidatorNodeProvider as _};
+use linera_rpc::config::{CrossChainConfig, ExporterServiceConfig};
 #[cfg(feature = "storage-service")]
 use linera_storage_service::{
     child::{StorageService, StorageServiceGuard},
     common::get_service_storage_binary,
 };
+use serde::{Deserialize, Serialize};
 use tokio_util::sync::CancellationToken;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
     cli_wrappers::{
         local_net::{
-            Database, ExportersSetup, InnerStorageConfigBuilder, LocalNetConfig, PathProvider,
+            Database, ExportersSetup, InnerStorageConfigBuilder, LocalNet, LocalNetConfig,
+            PathProvider, VIRTUAL_TIME_FILE,
         },
+        ssh_net::{SshInventory, SshNetConfig},
         ClientWrapper, FaucetService, LineraNet, LineraNetConfig, Network, NetworkConfig,
     },
-    storage::{InnerStorageConfig, StorageConfig},
+    storage::{
+        CommonStorageOptions, ExportNamespacesJob, ImportNamespacesJob, InnerStorageConfig,
+        NamespaceSnapshot, StorageConfig,
+    },
+    util,
+    virtual_time::{self, DEFAULT_ADMIN_PORT, VIRTUAL_TIME_ENV},
 };
 
+/// The file describing a local network, written in its working directory.
+const MANIFEST_FILE: &str = "local_net.json";
+/// The directory of a snapshot holding a copy of the network's working directory.
+const SNAPSHOT_NETWORK_DIR: &str = "network";
+/// The file of a snapshot holding the content of the validators' storage.
+const SNAPSHOT_STORAGE_FILE: &str = "storage.bcs";
+/// The file holding the readiness report of a local network started with `--wait-ready`,
+/// written in its working directory once all its services pass their health checks.
+const READINESS_FILE: &str = "ready.json";
+/// The delay between two attempts of a failed health check.
+const HEALTH_CHECK_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// What a local network exposes once all its services are healthy. Printed as JSON by
+/// `linera net up --wait-ready` and `linera net wait-ready`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetReadiness {
+    /// The addresses of the validators, e.g. `grpc:localhost:13001`.
+    pub validators: Vec<String>,
+    /// The URL of the faucet, if one was started.
+    pub faucet_url: Option<String>,
+    /// The chain the faucet creates new chains from, if one was started.
+    pub faucet_chain_id: Option<ChainId>,
+    /// The addresses of the block exporters the validators export to.
+    pub exporters: Vec<String>,
+    /// The admin chain of the network.
+    pub admin_chain_id: ChainId,
+    /// The chains of the admin wallet.
+    pub chain_ids: Vec<ChainId>,
+    /// The admin wallet, to use as `LINERA_WALLET`.
+    pub wallet: PathBuf,
+    /// The keystore of the admin wallet, to use as `LINERA_KEYSTORE`.
+    pub keystore: PathBuf,
+    /// The storage of the admin wallet, to use as `LINERA_STORAGE`.
+    pub storage: String,
+}
+
+/// The description of a local network needed to snapshot and restore it.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct LocalNetManifest {
+    /// The storage shared by the validators, including the common namespace.
+    storage: String,
+    /// The number of validators.
+    num_validators: usize,
+    /// The number of shards per validator.
+    num_shards: usize,
+    /// The number of proxies per validator.
+    num_proxies: usize,
+    /// The external protocol, either `grpc` or `grpcs`.
+    external_protocol: String,
+}
+
+impl LocalNetManifest {
+    fn read(network_dir: &Path) -> anyhow::Result<Self> {
+        util::read_json(network_dir.join(MANIFEST_FILE)).with_context(|| {
+            format!(
+                "{} does not look like the directory of a local network started with \
+                 `linera net up`",
+                network_dir.display()
+            )
+        })
+    }
+
+    fn write(&self, network_dir: &Path) -> anyhow::Result<()> {
+        let file = fs_err::File::create(network_dir.join(MANIFEST_FILE))?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    fn validator_namespaces(&self, common_namespace: &str) -> Vec<String> {
+        (0..self.num_validators)
+            .map(|validator| LocalNet::validator_namespace(common_namespace, validator))
+            .collect()
+    }
+}
+
 struct StorageConfigProvider {
     /// The storage config.
     config: StorageConfig,
@@ -94,6 +191,14 @@ impl StorageConfigProvider {
             InnerStorageConfig::ScyllaDb { .. } => Ok(Database::ScyllaDb),
             #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
             InnerStorageConfig::DualRocksDbScyllaDb { .. } => Ok(Database::DualRocksDbScyllaDb),
+            #[cfg(feature = "storage-service")]
+            InnerStorageConfig::FailoverService { .. } => {
+                anyhow::bail!("Not possible to work with failover storage")
+            }
+            #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+            InnerStorageConfig::FailoverRocksDbScyllaDb { .. } => {
+                anyhow::bail!("Not possible to work with failover storage")
+            }
         }
     }
 }
@@ -118,6 +223,8 @@ pub async fn handle_net_up_service(
     faucet_port: NonZeroU16,
     faucet_amount: Amount,
     http_request_allow_list: Option<Vec<String>>,
+    ready_timeout: Option<Duration>,
+    virtual_time_port: Option<NonZeroU16>,
 ) -> anyhow::Result<()> {
     assert!(
         num_initial_validators >= 1,
@@ -136,20 +243,25 @@ pub async fn handle_net_up_service(
     let namespace = storage.namespace().to_string();
     let database = storage.database()?;
     let storage_config_builder = InnerStorageConfigBuilder::ExistingConfig { storage_config };
-    let external = match external_protocol.as_str() {
-        "grpc" => Network::Grpc,
-        "grpcs" => Network::Grpcs,
-        _ => panic!("Only allowed options are grpc and grpcs"),
+    let manifest = LocalNetManifest {
+        storage: storage.config.to_string(),
+        num_validators: num_initial_validators,
+        num_shards,
+        num_proxies: 1, // Local networks currently support exactly 1 proxy.
+        external_protocol,
     };
-    let internal = Network::Grpc;
-    let network = NetworkConfig { external, internal };
+    let network = network_config(&manifest.external_protocol)?;
     let path_provider = PathProvider::from_path_option(path)?;
-    let num_proxies = 1; // Local networks currently support exactly 1 proxy.
+    let num_proxies = manifest.num_proxies;
     let block_exporters = ExportersSetup::new(
         with_block_exporter,
         block_exporter_address,
         block_exporter_port,
     );
+    let exporters = match &block_exporters {
+        ExportersSetup::Remote(exporters) => exporters.clone(),
+        ExportersSetup::Local(_) => Vec::new(),
+    };
     let initial_amount = Amount::from_tokens(initial_amount);
     let config = LocalNetConfig {
         network,
@@ -165,11 +277,90 @@ pub async fn handle_net_up_service(
         http_request_allow_list,
         cross_chain_config,
         storage_config_builder,
-        path_provider,
+        path_provider: path_provider.clone(),
         block_exporters,
         binary_dir: None,
+        virtual_time: virtual_time_port.is_some(),
+    };
+    let (mut net, client) = config.instantiate().await?;
+    manifest.write(path_provider.path())?;
+    let virtual_time = virtual_time_port
+        .map(|port| serve_virtual_time(&net, port, shutdown_notifier.clone()))
+        .transpose()?;
+    let readiness_checks = ready_timeout.map(|timeout| ReadinessChecks {
+        timeout,
+        validators: (0..num_initial_validators)
+            .map(|validator| net.validator_address(validator))
+            .collect(),
+        exporters,
+        network_dir: path_provider.path().to_path_buf(),
+    });
+    let faucet_service = print_messages_and_create_faucet(
+        client,
+        &mut net,
+        with_faucet,
+        faucet_port,
+        faucet_amount,
+        initial_amount,
+        readiness_checks.as_ref(),
+        virtual_time.as_ref(),
+    )
+    .await?;
+
+    let result = wait_for_shutdown(shutdown_notifier, &mut net, faucet_service).await;
+    if readiness_checks.is_some() {
+        // Do not let a later `linera net wait-ready` mistake a stopped network for a ready one.
+        if let Err(error) = fs_err::remove_file(path_provider.path().join(READINESS_FILE)) {
+            warn!("Failed to remove the readiness report: {error}");
+        }
+    }
+    result
+}
+
+/// Deploys a network on the remote hosts listed in an inventory file over SSH and,
+/// optionally, starts a faucet locally.
+#[expect(clippy::too_many_arguments)]
+pub async fn handle_net_up_ssh(
+    inventory: &Path,
+    num_other_initial_chains: u32,
+    initial_amount: u128,
+    testing_prng_seed: Option<u64>,
+    policy_config: ResourceControlPolicyConfig,
+    cross_chain_config: CrossChainConfig,
+    path: &Option<String>,
+    external_protocol: &str,
+    with_faucet: bool,
+    faucet_port: NonZeroU16,
+    faucet_amount: Amount,
+    http_request_allow_list: Option<Vec<String>>,
+    ready_timeout: Option<Duration>,
+) -> anyhow::Result<()> {
+    let shutdown_notifier = CancellationToken::new();
+    tokio::spawn(listen_for_shutdown_signals(shutdown_notifier.clone()));
+
+    let inventory = SshInventory::read(inventory)?;
+    let path_provider = PathProvider::from_path_option(path)?;
+    let initial_amount = Amount::from_tokens(initial_amount);
+    let config = SshNetConfig {
+        network: network_config(external_protocol)?,
+        inventory,
+        testing_prng_seed,
+        num_other_initial_chains,
+        initial_amount,
+        policy_config,
+        http_request_allow_list,
+        cross_chain_config,
+        path_provider: path_provider.clone(),
     };
     let (mut net, client) = config.instantiate().await?;
+    let readiness_checks = ready_timeout.map(|timeout| ReadinessChecks {
+        timeout,
+        validators: (0..net.num_validators())
+            .map(|validator| net.validator_address(validator))
+            .collect(),
+        exporters: Vec::new(),
+        network_dir: path_provider.path().to_path_buf(),
+    });
     let faucet_service = print_messages_and_create_faucet(
         client,
         &mut net,
@@ -177,10 +368,213 @@ pub async fn handle_net_up_service(
         faucet_port,
         faucet_amount,
         initial_amount,
+        readiness_checks.as_ref(),
+        None,
+    )
+    .await?;
+
+    let result = wait_for_shutdown(shutdown_notifier, &mut net, faucet_service).await;
+    if readiness_checks.is_some() {
+        if let Err(error) = fs_err::remove_file(path_provider.path().join(READINESS_FILE)) {
+            warn!("Failed to remove the readiness report: {error}");
+        }
+    }
+    result
+}
+
+/// Waits for the readiness report of a local network started with
+/// `linera net up --wait-ready`, then prints it.
+pub async fn handle_net_wait_ready(network_dir: &Path, timeout: Duration) -> anyhow::Result<()> {
+    let path = network_dir.join(READINESS_FILE);
+    let deadline = Instant::now() + timeout;
+    while !path.exists() {
+        if Instant::now() >= deadline {
+            bail!(
+                "The local network in {} is not ready after {timeout:?}",
+                network_dir.display()
+            );
+        }
+        linera_base::time::timer::sleep(HEALTH_CHECK_RETRY_DELAY).await;
+    }
+    let readiness: NetReadiness = util::read_json(&path)?;
+    println!("{}", serde_json::to_string(&readiness)?);
+    Ok(())
+}
+
+/// Saves the working directory and the validators' storage of a running local network
+/// into a new snapshot directory.
+pub async fn handle_net_snapshot(
+    network_dir: &Path,
+    snapshot_dir: &Path,
+    common_storage_options: &CommonStorageOptions,
+) -> anyhow::Result<()> {
+    if snapshot_dir.exists() {
+        bail!(
+            "The snapshot directory {} already exists",
+            snapshot_dir.display()
+        );
+    }
+    let manifest = LocalNetManifest::read(network_dir)?;
+    let storage = StorageConfig::from_str(&manifest.storage)?;
+    let namespaces = manifest.validator_namespaces(&storage.namespace);
+    let store_config = storage.add_common_storage_options(common_storage_options)?;
+    let cache_sizes = common_storage_options.storage_cache_config();
+    let snapshots = store_config
+        .run_with_store(cache_sizes, ExportNamespacesJob(namespaces))
+        .await?;
+
+    fs_err::create_dir_all(snapshot_dir)?;
+    copy_dir_all(network_dir, &snapshot_dir.join(SNAPSHOT_NETWORK_DIR))?;
+    fs_err::write(
+        snapshot_dir.join(SNAPSHOT_STORAGE_FILE),
+        bcs::to_bytes(&snapshots)?,
+    )?;
+    info!(
+        "Snapshot of {} validator(s) saved in {}",
+        manifest.num_validators,
+        snapshot_dir.display()
+    );
+    Ok(())
+}
+
+/// Starts a local test network from a snapshot made by [`handle_net_snapshot`].
+pub async fn handle_net_restore(
+    snapshot_dir: &Path,
+    path: &Option<String>,
+    storage: &Option<String>,
+    cross_chain_config: CrossChainConfig,
+    common_storage_options: &CommonStorageOptions,
+) -> anyhow::Result<()> {
+    let shutdown_notifier = CancellationToken::new();
+    tokio::spawn(listen_for_shutdown_signals(shutdown_notifier.clone()));
+
+    let snapshot_network_dir = snapshot_dir.join(SNAPSHOT_NETWORK_DIR);
+    let mut manifest = LocalNetManifest::read(&snapshot_network_dir)?;
+    let mut snapshots: Vec<NamespaceSnapshot> =
+        bcs::from_bytes(&fs_err::read(snapshot_dir.join(SNAPSHOT_STORAGE_FILE))?)?;
+    let path_provider = PathProvider::from_path_option(path)?;
+    copy_dir_all(&snapshot_network_dir, path_provider.path())?;
+
+    let storage = StorageConfigProvider::new(storage).await?;
+    let namespace = storage.namespace().to_string();
+    let namespaces = manifest.validator_namespaces(&namespace);
+    if namespaces.len() != snapshots.len() {
+        bail!(
+            "The snapshot contains the storage of {} validator(s) instead of {}",
+            snapshots.len(),
+            namespaces.len()
+        );
+    }
+    for (snapshot, namespace) in snapshots.iter_mut().zip(namespaces) {
+        snapshot.namespace = namespace;
+    }
+    let store_config = storage
+        .config
+        .add_common_storage_options(common_storage_options)?;
+    let cache_sizes = common_storage_options.storage_cache_config();
+    store_config
+        .run_with_store(cache_sizes, ImportNamespacesJob(snapshots))
+        .await?;
+    manifest.storage = storage.config.to_string();
+    manifest.write(path_provider.path())?;
+    // A network snapshotted in virtual time must keep following its clock: the timestamps of
+    // its blocks may be ahead of the system clock.
+    let has_virtual_time = path_provider.path().join(VIRTUAL_TIME_FILE).exists();
+
+    let config = LocalNetConfig {
+        network: network_config(&manifest.external_protocol)?,
+        database: storage.database()?,
+        testing_prng_seed: None,
+        namespace,
+        num_other_initial_chains: 0,
+        initial_amount: Amount::ZERO,
+        num_initial_validators: manifest.num_validators,
+        num_shards: manifest.num_shards,
+        num_proxies: manifest.num_proxies,
+        policy_config: ResourceControlPolicyConfig::NoFees,
+        http_request_allow_list: None,
+        cross_chain_config,
+        storage_config_builder: InnerStorageConfigBuilder::ExistingConfig {
+            storage_config: storage.inner_storage_config().clone(),
+        },
+        path_provider,
+        block_exporters: ExportersSetup::Local(vec![]),
+        binary_dir: None,
+        virtual_time: has_virtual_time,
+    };
+    let (mut net, client) = config.resume().await?;
+    let virtual_time = has_virtual_time
+        .then(|| serve_virtual_time(&net, DEFAULT_ADMIN_PORT, shutdown_notifier.clone()))
+        .transpose()?;
+    print_messages_and_create_faucet(
+        client,
+        &mut net,
+        false,
+        NonZeroU16::MIN,
+        Amount::ZERO,
+        Amount::ZERO,
+        None,
+        virtual_time.as_ref(),
     )
     .await?;
 
-    wait_for_shutdown(shutdown_notifier, &mut net, faucet_service).await
+    wait_for_shutdown(shutdown_notifier, &mut net, None).await
+}
+
+/// The virtual time of a local network and its admin endpoint.
+struct VirtualTime {
+    /// The clock file that the processes of the network follow.
+    path: PathBuf,
+    /// The local port of the admin endpoint.
+    port: NonZeroU16,
+}
+
+/// Serves the admin endpoint of the virtual time of `net` in the background, until
+/// `shutdown_notifier` is cancelled.
+fn serve_virtual_time(
+    net: &LocalNet,
+    port: NonZeroU16,
+    shutdown_notifier: CancellationToken,
+) -> anyhow::Result<VirtualTime> {
+    let clock = net
+        .virtual_clock()
+        .context("the local network has no virtual time")?
+        .clone();
+    let path = clock.path().to_path_buf();
+    tokio::spawn(async move {
+        if let Err(error) = virtual_time::serve(clock, port, shutdown_notifier).await {
+            warn!(%error, "Failed to serve the virtual time endpoint");
+        }
+    });
+    Ok(VirtualTime { path, port })
+}
+
+fn network_config(external_protocol: &str) -> anyhow::Result<NetworkConfig> {
+    let external = match external_protocol {
+        "grpc" => Network::Grpc,
+        "grpcs" => Network::Grpcs,
+        _ => bail!(
+            "Unsupported external protocol {external_protocol}: only grpc and grpcs are allowed"
+        ),
+    };
+    let internal = Network::Grpc;
+    Ok(NetworkConfig { external, internal })
+}
+
+/// Recursively copies the regular files of `source` into `destination`.
+fn copy_dir_all(source: &Path, destination: &Path) -> anyhow::Result<()> {
+    fs_err::create_dir_all(destination)?;
+    for entry in fs_err::read_dir(source)? {
+        let entry = entry?;
+        let file_type = entry.file_type()?;
+        let target = destination.join(entry.file_name());
+        if file_type.is_dir() {
+            copy_dir_all(&entry.path(), &target)?;
+        } else if file_type.is_file() {
+            fs_err::copy(entry.path(), target)?;
+        }
+    }
+    Ok(())
 }
 
 async fn wait_for_shutdown(
@@ -201,6 +595,19 @@ async fn wait_for_shutdown(
     Ok(())
 }
 
+/// How to check that a local network is ready, for `linera net up --wait-ready`.
+struct ReadinessChecks {
+    /// How long each service may take to pass its health check.
+    timeout: Duration,
+    /// The public addresses of the validators.
+    validators: Vec<String>,
+    /// The block exporters the validators export to.
+    exporters: Vec<ExporterServiceConfig>,
+    /// The working directory of the network, where the readiness report is written.
+    network_dir: PathBuf,
+}
+
+#[expect(clippy::too_many_arguments)]
 async fn print_messages_and_create_faucet(
     client: ClientWrapper,
     net: &mut impl LineraNet,
@@ -208,30 +615,57 @@ async fn print_messages_and_create_faucet(
     faucet_port: NonZeroU16,
     faucet_amount: Amount,
     initial_amount: Amount,
+    readiness_checks: Option<&ReadinessChecks>,
+    virtual_time: Option<&VirtualTime>,
 ) -> Result<Option<FaucetService>, anyhow::Error> {
     // Make time to (hopefully) display the message after the tracing logs.
     linera_base::time::timer::sleep(Duration::from_secs(1)).await;
 
     info!("Local test network successfully started.");
 
+    // With `--wait-ready`, the readiness report is the only output on stdout.
+    let print_export = |export: String| {
+        if readiness_checks.is_some() {
+            eprintln!("{export}");
+        } else {
+            println!("{export}");
+        }
+    };
+
     eprintln!(
         "To use the admin wallet of this test network, you may set \
          the environment variables LINERA_WALLET, LINERA_KEYSTORE, \
          and LINERA_STORAGE as follows.\n"
     );
-    println!(
+    print_export(format!(
         "export LINERA_WALLET=\"{}\"",
         client.wallet_path().display(),
-    );
-    println!(
+    ));
+    print_export(format!(
         "export LINERA_KEYSTORE=\"{}\"",
         client.keystore_path().display(),
-    );
-    println!("export LINERA_STORAGE=\"{}\"", client.storage_path(),);
+    ));
+    print_export(format!(
+        "export LINERA_STORAGE=\"{}\"",
+        client.storage_path()
+    ));
+
+    if let Some(virtual_time) = virtual_time {
+        eprintln!(
+            "\nThis network runs in virtual time. Clients follow it with LINERA_VIRTUAL_TIME, \
+             and `curl -X POST http://localhost:{}/advance -H 'Content-Type: application/json' \
+             -d '{{\"micros\": 3600000000}}'` moves it forward by one hour.\n",
+            virtual_time.port
+        );
+        print_export(format!(
+            "export {VIRTUAL_TIME_ENV}=\"{}\"",
+            virtual_time.path.display()
+        ));
+    }
 
     // Run the faucet using a separate wallet so it doesn't lock the admin wallet.
     // Keep half the balance on the admin chain for fee payments (e.g. committee changes).
-    let faucet_service = if with_faucet {
+    let faucet = if with_faucet {
         let faucet_client = net.make_client().await;
         faucet_client.wallet_init(None).await?;
         let faucet_balance = Amount::from_attos(initial_amount.to_attos() / 2);
@@ -240,21 +674,157 @@ async fn print_messages_and_create_faucet(
             .await?;
 
         eprintln!("To connect to this network, you can use the following faucet URL:");
-        println!("export LINERA_FAUCET_URL=\"http://localhost:{faucet_port}\"");
+        print_export(format!(
+            "export LINERA_FAUCET_URL=\"http://localhost:{faucet_port}\""
+        ));
 
         let service = faucet_client
             .run_faucet(Some(faucet_port.into()), Some(faucet_chain), faucet_amount)
             .await?;
-        Some(service)
+        Some((service, faucet_chain))
     } else {
         None
     };
 
-    println!();
+    if let Some(checks) = readiness_checks {
+        let readiness = wait_until_ready(&client, faucet.as_ref(), checks).await?;
+        let file = fs_err::File::create(checks.network_dir.join(READINESS_FILE))?;
+        serde_json::to_writer_pretty(file, &readiness)?;
+        println!("{}", serde_json::to_string(&readiness)?);
+    } else {
+        println!();
+    }
 
     eprintln!(
         "\nREADY!\nPress ^C to terminate the local test network and clean the temporary directory."
     );
 
-    Ok(faucet_service)
+    Ok(faucet.map(|(service, _)| service))
+}
+
+/// Waits until the validators, the faucet, and the block exporters pass their health
+/// checks, and returns the readiness report of the network.
+async fn wait_until_ready(
+    client: &ClientWrapper,
+    faucet: Option<&(FaucetService, ChainId)>,
+    checks: &ReadinessChecks,
+) -> anyhow::Result<NetReadiness> {
+    let node_provider = linera_rpc::NodeProvider::new(linera_rpc::NodeOptions {
+        send_timeout: Duration::from_secs(1),
+        recv_timeout: Duration::from_secs(1),
+        retry_delay: Duration::ZERO,
+        max_retries: 0,
+        ..Default::default()
+    });
+    for address in &checks.validators {
+        let node = node_provider.make_node(address)?;
+        wait_for_health(&format!("validator {address}"), checks.timeout, || {
+            node.get_version_info()
+        })
+        .await?;
+    }
+
+    let faucet_url = match faucet {
+        Some((service, _)) => {
+            let instance = service.instance();
+            wait_for_health("faucet", checks.timeout, || instance.version_info()).await?;
+            Some(instance.url().trim_end_matches('/').to_string())
+        }
+        None => None,
+    };
+
+    let mut exporters = Vec::new();
+    for exporter in &checks.exporters {
+        let address = format!("{}:{}", exporter.host, exporter.port);
+        let health = LocalNet::grpc_health_client(&exporter.host, exporter.port.into(), "http")?;
+        wait_for_health(&format!("block exporter {address}"), checks.timeout, || {
+            LocalNet::is_grpc_server_serving(&health)
+        })
+        .await?;
+        exporters.push(address);
+    }
+
+    let wallet = client.load_wallet()?;
+    Ok(NetReadiness {
+        validators: checks.validators.clone(),
+        faucet_url,
+        faucet_chain_id: faucet.map(|(_, chain_id)| *chain_id),
+        exporters,
+        admin_chain_id: wallet.genesis_admin_chain_id(),
+        chain_ids: wallet.chain_ids(),
+        wallet: client.wallet_path(),
+        keystore: client.keystore_path(),
+        storage: client.storage_path().to_string(),
+    })
+}
+
+/// Retries a health check until it succeeds or the timeout expires.
+async fn wait_for_health<F, T, E>(
+    nickname: &str,
+    timeout: Duration,
+    mut check: impl FnMut() -> F,
+) -> anyhow::Result<()>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        match check().await {
+            Ok(_) => {
+                info!("The {nickname} is healthy");
+                return Ok(());
+            }
+            Err(error) if Instant::now() < deadline => {
+                warn!("Waiting for the {nickname} to be healthy: {error}");
+                linera_base::time::timer::sleep(HEALTH_CHECK_RETRY_DELAY).await;
+            }
+            Err(error) => bail!("The {nickname} is not healthy after {timeout:?}: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_net_manifest_round_trip() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        assert!(LocalNetManifest::read(dir.path()).is_err());
+
+        let manifest = LocalNetManifest {
+            storage: "memory:genesis.json:linera".to_string(),
+            num_validators: 2,
+            num_shards: 4,
+            num_proxies: 1,
+            external_protocol: "grpcs".to_string(),
+        };
+        manifest.write(dir.path())?;
+        let read = LocalNetManifest::read(dir.path())?;
+        assert_eq!(read, manifest);
+
+        let storage = StorageConfig::from_str(&read.storage)?;
+        assert_eq!(storage.to_string(), read.storage);
+        assert_eq!(
+            read.validator_namespaces(&storage.namespace),
+            ["linera_server_0_db", "linera_server_1_db"]
+        );
+        let network = network_config(&read.external_protocol)?;
+        assert!(matches!(network.external, Network::Grpcs));
+        assert!(matches!(network.internal, Network::Grpc));
+        Ok(())
+    }
+
+    #[test]
+    fn test_network_config_rejects_unknown_protocols() {
+        assert!(matches!(
+            network_config("grpc"),
+            Ok(NetworkConfig {
+                external: Network::Grpc,
+                ..
+            })
+        ));
+        assert!(network_config("http").is_err());
+    }
 }
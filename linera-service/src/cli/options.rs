@@ -1,17 +1,26 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::path::PathBuf;
+use std::{num::NonZeroU16, path::PathBuf};
 
 use anyhow::Error;
-use linera_client::{client_context::ClientContext, config::GenesisConfig};
+use linera_client::{
+    client_context::ClientContext, config::GenesisConfig, spending_journal::SpendingJournal,
+};
 use linera_execution::WithWasmDefault as _;
 use linera_service::{
-    cli::{command::ClientCommand, common_options::CommonCliOptions},
+    cli::{
+        command::ClientCommand,
+        common_options::CommonCliOptions,
+        config_file::{self, EffectiveOption},
+        output::OutputFormat,
+    },
     storage::{Runnable, RunnableWithStore, StorageConfig},
+    storage_wallet::OpenStorageWalletJob,
+    wallet_backend::{ClientWallet, WalletBackend},
     Wallet,
 };
-use tracing::debug;
+use tracing::{debug, warn};
 
 #[derive(Clone, clap::Parser)]
 #[command(
@@ -53,19 +62,61 @@ pub struct Options {
     )]
     pub execution_state_cache_size: usize,
 
+    /// The local port of the web page through which external EVM wallets, such as
+    /// MetaMask, sign for the owners whose keys they hold.
+    #[arg(long, env = "LINERA_SIGNING_PAGE_PORT", default_value = "8078")]
+    pub signing_page_port: NonZeroU16,
+
     /// Enable jemalloc memory profiling endpoints on the metrics server.
     #[cfg(feature = "jemalloc")]
     #[arg(long, env = "LINERA_ENABLE_MEMORY_PROFILING")]
     pub enable_memory_profiling: bool,
 
+    /// How commands such as `query-balance`, `process-inbox`, `wallet show` and
+    /// `validator list` print their results: as text, or as a JSON document for scripts.
+    #[arg(long, env = "LINERA_OUTPUT", value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+
+    /// Before running the command, check the pending fast-round proposals of the wallet's
+    /// chains against the validators, and propose the operations again of those that were
+    /// overtaken by a different block.
+    #[arg(long, env = "LINERA_RESOLVE_ORPHANED_PROPOSALS")]
+    pub resolve_orphaned_proposals: bool,
+
+    #[command(flatten)]
+    pub telemetry: linera_service::telemetry::TelemetryOptions,
+
+    /// The effective values of the options above, and where they come from.
+    #[arg(skip)]
+    pub effective_options: Vec<EffectiveOption>,
+
     /// Subcommand.
     #[command(subcommand)]
     pub command: ClientCommand,
 }
 
 impl Options {
+    /// Parses the command line, taking the options that are not given on the command line
+    /// or through environment variables from the `linera.toml` configuration files.
     pub fn init() -> Self {
-        <Options as clap::Parser>::parse()
+        let mut command = <Options as clap::CommandFactory>::command();
+        let args = std::env::args_os().collect::<Vec<_>>();
+        let matches = command.clone().get_matches_from(&args);
+        let resolution = config_file::load()
+            .and_then(|files| config_file::resolve(&command, &matches, &files))
+            .unwrap_or_else(|error| {
+                command
+                    .error(clap::error::ErrorKind::InvalidValue, format!("{error:#}"))
+                    .exit()
+            });
+        let args = args[..1]
+            .iter()
+            .cloned()
+            .chain(resolution.args.iter().map(Into::into))
+            .chain(args[1..].iter().cloned());
+        let mut options = <Options as clap::Parser>::parse_from(args);
+        options.effective_options = resolution.effective;
+        options
     }
 
     pub fn enable_memory_profiling(&self) -> bool {
@@ -82,10 +133,12 @@ impl Options {
     pub async fn create_client_context<S, Si>(
         &self,
         storage: S,
-        wallet: Wallet,
+        wallet: ClientWallet,
         signer: Si,
     ) -> anyhow::Result<
-        ClientContext<linera_core::environment::Impl<S, linera_rpc::NodeProvider, Si, Wallet>>,
+        ClientContext<
+            linera_core::environment::Impl<S, linera_rpc::NodeProvider, Si, ClientWallet>,
+        >,
     >
     where
         S: linera_core::environment::Storage,
@@ -93,7 +146,7 @@ impl Options {
     {
         let genesis_config = wallet.genesis_config().clone();
         let default_chain = wallet.default_chain();
-        Ok(ClientContext::new(
+        let mut context = ClientContext::new(
             storage,
             wallet,
             signer,
@@ -103,7 +156,19 @@ impl Options {
             self.block_cache_size,
             self.execution_state_cache_size,
         )
-        .await?)
+        .await?;
+        // Without a writable home directory, e.g. in a container with a wallet in storage,
+        // there may be nowhere to keep the journal.
+        match self.spending_journal_path() {
+            Ok(path) => context.spending_journal = Some(SpendingJournal::new(path)),
+            Err(error) => warn!("Not recording the spending journal: {error}"),
+        }
+        if self.resolve_orphaned_proposals {
+            if let Err(error) = context.resolve_orphaned_proposals().await {
+                warn!("Failed to resolve orphaned proposals: {error}");
+            }
+        }
+        Ok(context)
     }
 
     pub async fn run_with_storage<R: Runnable>(&self, job: R) -> Result<R::Output, Error> {
@@ -137,7 +202,7 @@ impl Options {
         debug!("Initializing storage using configuration: {storage_config}");
         let store_config =
             storage_config.add_common_storage_options(&self.common.common_storage_options)?;
-        let wallet = self.wallet()?;
+        let wallet = self.client_wallet().await?;
         let cache_sizes = self.common.common_storage_options.storage_cache_config();
         linera_service::storage::initialize(store_config, cache_sizes, wallet.genesis_config())
             .await?;
@@ -155,10 +220,27 @@ impl Options {
         self.common.wallet_path()
     }
 
+    pub fn spending_journal_path(&self) -> Result<PathBuf, Error> {
+        self.common.spending_journal_path()
+    }
+
     pub fn wallet(&self) -> Result<Wallet, Error> {
         self.common.wallet()
     }
 
+    pub fn keystore_path(&self) -> Result<PathBuf, Error> {
+        self.common.keystore_path()
+    }
+
+    pub async fn client_wallet(&self) -> Result<ClientWallet, Error> {
+        match self.common.wallet_backend {
+            WalletBackend::File => Ok(ClientWallet::File(Box::new(self.wallet()?))),
+            WalletBackend::Storage => Ok(ClientWallet::Storage(
+                self.run_with_store(OpenStorageWalletJob).await?,
+            )),
+        }
+    }
+
     pub fn keystore(&self) -> Result<linera_wallet_json::Keystore, Error> {
         self.common.keystore()
     }
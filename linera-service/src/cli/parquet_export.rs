@@ -0,0 +1,565 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Exporting the blocks of a chain to Parquet files, for analytics with e.g. DuckDB or Spark.
+//!
+//! Every export adds one file per table, in `<output>/<table>/`, with the blocks added to the
+//! chain since the previous export. The next block height to export from is saved for each
+//! chain in `<output>/watermarks.json`. The tables are:
+//! * `blocks`: one row per block;
+//! * `operations`: one row per operation;
+//! * `messages`: one row per incoming or outgoing message;
+//! * `balances`: the balances of the chain and its owners after the last exported block.
+
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use anyhow::{bail, Context as _};
+use arrow_array::{
+    ArrayRef, BinaryArray, RecordBatch, StringArray, TimestampMicrosecondArray, UInt32Array,
+    UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use linera_base::{data_types::BlockHeight, identifiers::ChainId};
+use linera_chain::{
+    block::Block,
+    data_types::{MessageAction, Transaction},
+};
+use linera_execution::{Message, Operation};
+use linera_storage::Storage;
+use parquet::{arrow::ArrowWriter, basic::Compression, file::properties::WriterProperties};
+use tracing::info;
+
+use crate::util;
+
+/// The file holding the next block height to export for each chain.
+const WATERMARKS_FILE: &str = "watermarks.json";
+/// The number of blocks read from storage and written to the files at once.
+const BLOCK_BATCH_SIZE: usize = 100;
+
+/// The next block height to export for each chain.
+pub type Watermarks = BTreeMap<ChainId, BlockHeight>;
+
+/// What an export added to the tables.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    /// The first exported block height.
+    pub start: BlockHeight,
+    /// The number of exported blocks.
+    pub blocks: usize,
+    /// The number of exported operations.
+    pub operations: usize,
+    /// The number of exported messages.
+    pub messages: usize,
+    /// The number of exported balances.
+    pub balances: usize,
+}
+
+/// Reads the watermarks saved in the output directory, if any.
+pub fn read_watermarks(output: &Path) -> anyhow::Result<Watermarks> {
+    let path = output.join(WATERMARKS_FILE);
+    if !path.exists() {
+        return Ok(Watermarks::new());
+    }
+    util::read_json(path)
+}
+
+fn write_watermarks(output: &Path, watermarks: &Watermarks) -> anyhow::Result<()> {
+    // Write to a temporary file first, so that an interrupted export keeps the old watermarks.
+    let path = output.join(WATERMARKS_FILE);
+    let temporary_path = path.with_extension("json.tmp");
+    fs_err::write(&temporary_path, serde_json::to_vec_pretty(watermarks)?)?;
+    fs_err::rename(temporary_path, path)?;
+    Ok(())
+}
+
+/// Exports the blocks of the chain that are not exported yet, and its current balances.
+pub async fn export_chain<S: Storage>(
+    storage: &S,
+    chain_id: ChainId,
+    output: &Path,
+) -> anyhow::Result<ExportSummary> {
+    let mut watermarks = read_watermarks(output)?;
+    let start = watermarks
+        .get(&chain_id)
+        .copied()
+        .unwrap_or(BlockHeight::ZERO);
+    let chain = storage.load_chain(chain_id).await?;
+    let end = chain.tip_state.get().next_block_height;
+    let mut summary = ExportSummary {
+        start,
+        ..ExportSummary::default()
+    };
+    if start >= end {
+        info!("No new blocks to export on chain {chain_id} since height {start}");
+        return Ok(summary);
+    }
+    if let Some(checkpoint) = *chain.latest_checkpoint_height.get() {
+        if start < checkpoint {
+            bail!(
+                "The blocks of chain {chain_id} below height {checkpoint} were pruned; \
+                 cannot export from height {start}"
+            );
+        }
+    }
+
+    let last = BlockHeight(end.0 - 1);
+    let name = format!("{chain_id}-{start}-{last}.parquet");
+    let mut blocks = TableWriter::create(output, "blocks", &name, BlockRows::schema())?;
+    let mut operations = TableWriter::create(output, "operations", &name, OperationRows::schema())?;
+    let mut messages = TableWriter::create(output, "messages", &name, MessageRows::schema())?;
+
+    let heights = (start.0..end.0).map(BlockHeight).collect::<Vec<_>>();
+    for heights in heights.chunks(BLOCK_BATCH_SIZE) {
+        let certificates = storage
+            .read_certificates_by_heights(chain_id, heights)
+            .await?;
+        let mut block_rows = BlockRows::default();
+        let mut operation_rows = OperationRows::default();
+        let mut message_rows = MessageRows::default();
+        for (height, certificate) in heights.iter().zip(certificates) {
+            let certificate = certificate.with_context(|| {
+                format!("missing certificate for block {height} of chain {chain_id}")
+            })?;
+            let block = certificate.block();
+            block_rows.push(block, certificate.hash().to_string());
+            operation_rows.push(block)?;
+            message_rows.push(block)?;
+        }
+        summary.blocks += block_rows.len();
+        summary.operations += operation_rows.len();
+        summary.messages += message_rows.len();
+        blocks.write(block_rows.into_batch()?)?;
+        operations.write(operation_rows.into_batch()?)?;
+        messages.write(message_rows.into_batch()?)?;
+    }
+
+    let mut balance_rows = BalanceRows::default();
+    let system = &chain.execution_state.system;
+    balance_rows.push(chain_id, last, None, system.balance.get().to_string());
+    system
+        .balances
+        .for_each_index_value(|owner, balance| {
+            balance_rows.push(chain_id, last, Some(owner.to_string()), balance.to_string());
+            Ok(())
+        })
+        .await?;
+    summary.balances = balance_rows.len();
+    let mut balances = TableWriter::create(output, "balances", &name, BalanceRows::schema())?;
+    balances.write(balance_rows.into_batch()?)?;
+
+    blocks.close()?;
+    operations.close()?;
+    messages.close()?;
+    balances.close()?;
+    watermarks.insert(chain_id, end);
+    write_watermarks(output, &watermarks)?;
+    Ok(summary)
+}
+
+/// A Parquet file of a table being written.
+struct TableWriter {
+    writer: ArrowWriter<fs_err::File>,
+}
+
+impl TableWriter {
+    fn create(output: &Path, table: &str, name: &str, schema: SchemaRef) -> anyhow::Result<Self> {
+        let directory = output.join(table);
+        fs_err::create_dir_all(&directory)?;
+        let file = fs_err::File::create(directory.join(name))?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        let writer = ArrowWriter::try_new(file, schema, Some(properties))?;
+        Ok(Self { writer })
+    }
+
+    fn write(&mut self, batch: RecordBatch) -> anyhow::Result<()> {
+        self.writer.write(&batch)?;
+        Ok(())
+    }
+
+    fn close(self) -> anyhow::Result<()> {
+        self.writer.close()?;
+        Ok(())
+    }
+}
+
+fn utf8(name: &str, nullable: bool) -> Field {
+    Field::new(name, DataType::Utf8, nullable)
+}
+
+/// The columns of the `blocks` table.
+#[derive(Default)]
+struct BlockRows {
+    chain_id: Vec<String>,
+    height: Vec<u64>,
+    hash: Vec<String>,
+    timestamp: Vec<i64>,
+    epoch: Vec<u32>,
+    authenticated_owner: Vec<Option<String>>,
+    previous_block_hash: Vec<Option<String>>,
+    state_hash: Vec<String>,
+    transactions: Vec<u32>,
+    outgoing_messages: Vec<u32>,
+}
+
+impl BlockRows {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            utf8("chain_id", false),
+            Field::new("height", DataType::UInt64, false),
+            utf8("hash", false),
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+                false,
+            ),
+            Field::new("epoch", DataType::UInt32, false),
+            utf8("authenticated_owner", true),
+            utf8("previous_block_hash", true),
+            utf8("state_hash", false),
+            Field::new("transactions", DataType::UInt32, false),
+            Field::new("outgoing_messages", DataType::UInt32, false),
+        ]))
+    }
+
+    fn len(&self) -> usize {
+        self.height.len()
+    }
+
+    fn push(&mut self, block: &Block, hash: String) {
+        let header = &block.header;
+        self.chain_id.push(header.chain_id.to_string());
+        self.height.push(header.height.0);
+        self.hash.push(hash);
+        self.timestamp
+            .push(i64::try_from(header.timestamp.micros()).unwrap_or(i64::MAX));
+        self.epoch.push(header.epoch.0);
+        self.authenticated_owner
+            .push(header.authenticated_owner.map(|owner| owner.to_string()));
+        self.previous_block_hash
+            .push(header.previous_block_hash.map(|hash| hash.to_string()));
+        self.state_hash.push(header.state_hash.to_string());
+        self.transactions
+            .push(saturating_u32(block.body.transactions.len()));
+        self.outgoing_messages.push(saturating_u32(
+            block.body.messages.iter().map(Vec::len).sum(),
+        ));
+    }
+
+    fn into_batch(self) -> anyhow::Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(self.chain_id)),
+            Arc::new(UInt64Array::from(self.height)),
+            Arc::new(StringArray::from(self.hash)),
+            Arc::new(TimestampMicrosecondArray::from(self.timestamp).with_timezone("UTC")),
+            Arc::new(UInt32Array::from(self.epoch)),
+            Arc::new(StringArray::from(self.authenticated_owner)),
+            Arc::new(StringArray::from(self.previous_block_hash)),
+            Arc::new(StringArray::from(self.state_hash)),
+            Arc::new(UInt32Array::from(self.transactions)),
+            Arc::new(UInt32Array::from(self.outgoing_messages)),
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+}
+
+/// The columns of the `operations` table. System operations have no application ID and
+/// their bytes are BCS-encoded.
+#[derive(Default)]
+struct OperationRows {
+    chain_id: Vec<String>,
+    height: Vec<u64>,
+    transaction_index: Vec<u32>,
+    application_id: Vec<Option<String>>,
+    bytes: Vec<Vec<u8>>,
+}
+
+impl OperationRows {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            utf8("chain_id", false),
+            Field::new("height", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            utf8("application_id", true),
+            Field::new("bytes", DataType::Binary, false),
+        ]))
+    }
+
+    fn len(&self) -> usize {
+        self.height.len()
+    }
+
+    fn push(&mut self, block: &Block) -> anyhow::Result<()> {
+        for (index, transaction) in block.body.transactions.iter().enumerate() {
+            let Transaction::ExecuteOperation(operation) = transaction else {
+                continue;
+            };
+            let (application_id, bytes) = match operation {
+                Operation::System(operation) => (None, bcs::to_bytes(operation)?),
+                Operation::User {
+                    application_id,
+                    bytes,
+                } => (Some(application_id.to_string()), bytes.clone()),
+            };
+            self.chain_id.push(block.header.chain_id.to_string());
+            self.height.push(block.header.height.0);
+            self.transaction_index.push(saturating_u32(index));
+            self.application_id.push(application_id);
+            self.bytes.push(bytes);
+        }
+        Ok(())
+    }
+
+    fn into_batch(self) -> anyhow::Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(self.chain_id)),
+            Arc::new(UInt64Array::from(self.height)),
+            Arc::new(UInt32Array::from(self.transaction_index)),
+            Arc::new(StringArray::from(self.application_id)),
+            Arc::new(BinaryArray::from_iter_values(self.bytes)),
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+}
+
+/// The columns of the `messages` table. Incoming messages are the ones the block received
+/// from `other_chain_id`, outgoing ones the ones it sent to `other_chain_id`.
+#[derive(Default)]
+struct MessageRows {
+    chain_id: Vec<String>,
+    height: Vec<u64>,
+    transaction_index: Vec<u32>,
+    direction: Vec<&'static str>,
+    other_chain_id: Vec<String>,
+    kind: Vec<String>,
+    action: Vec<Option<&'static str>>,
+    authenticated_owner: Vec<Option<String>>,
+    grant: Vec<String>,
+    application_id: Vec<Option<String>>,
+    bytes: Vec<Vec<u8>>,
+}
+
+impl MessageRows {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            utf8("chain_id", false),
+            Field::new("height", DataType::UInt64, false),
+            Field::new("transaction_index", DataType::UInt32, false),
+            utf8("direction", false),
+            utf8("other_chain_id", false),
+            utf8("kind", false),
+            utf8("action", true),
+            utf8("authenticated_owner", true),
+            utf8("grant", false),
+            utf8("application_id", true),
+            Field::new("bytes", DataType::Binary, false),
+        ]))
+    }
+
+    fn len(&self) -> usize {
+        self.height.len()
+    }
+
+    #[expect(clippy::too_many_arguments)]
+    fn push_message(
+        &mut self,
+        block: &Block,
+        transaction_index: usize,
+        direction: &'static str,
+        other_chain_id: ChainId,
+        kind: String,
+        action: Option<&'static str>,
+        authenticated_owner: Option<String>,
+        grant: String,
+        message: &Message,
+    ) -> anyhow::Result<()> {
+        let (application_id, bytes) = match message {
+            Message::System(message) => (None, bcs::to_bytes(message)?),
+            Message::User {
+                application_id,
+                bytes,
+            } => (Some(application_id.to_string()), bytes.clone()),
+        };
+        self.chain_id.push(block.header.chain_id.to_string());
+        self.height.push(block.header.height.0);
+        self.transaction_index
+            .push(saturating_u32(transaction_index));
+        self.direction.push(direction);
+        self.other_chain_id.push(other_chain_id.to_string());
+        self.kind.push(kind);
+        self.action.push(action);
+        self.authenticated_owner.push(authenticated_owner);
+        self.grant.push(grant);
+        self.application_id.push(application_id);
+        self.bytes.push(bytes);
+        Ok(())
+    }
+
+    fn push(&mut self, block: &Block) -> anyhow::Result<()> {
+        for (index, transaction) in block.body.transactions.iter().enumerate() {
+            let Transaction::ReceiveMessages(bundle) = transaction else {
+                continue;
+            };
+            let action = match bundle.action {
+                MessageAction::Accept => "accept",
+                MessageAction::Reject => "reject",
+            };
+            for posted in bundle.messages() {
+                self.push_message(
+                    block,
+                    index,
+                    "incoming",
+                    bundle.origin,
+                    posted.kind.to_string(),
+                    Some(action),
+                    posted.authenticated_owner.map(|owner| owner.to_string()),
+                    posted.grant.to_string(),
+                    &posted.message,
+                )?;
+            }
+        }
+        for (index, outgoing_messages) in block.body.messages.iter().enumerate() {
+            for outgoing in outgoing_messages {
+                self.push_message(
+                    block,
+                    index,
+                    "outgoing",
+                    outgoing.destination,
+                    outgoing.kind.to_string(),
+                    None,
+                    outgoing.authenticated_owner.map(|owner| owner.to_string()),
+                    outgoing.grant.to_string(),
+                    &outgoing.message,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    fn into_batch(self) -> anyhow::Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(self.chain_id)),
+            Arc::new(UInt64Array::from(self.height)),
+            Arc::new(UInt32Array::from(self.transaction_index)),
+            Arc::new(StringArray::from(self.direction)),
+            Arc::new(StringArray::from(self.other_chain_id)),
+            Arc::new(StringArray::from(self.kind)),
+            Arc::new(StringArray::from(self.action)),
+            Arc::new(StringArray::from(self.authenticated_owner)),
+            Arc::new(StringArray::from(self.grant)),
+            Arc::new(StringArray::from(self.application_id)),
+            Arc::new(BinaryArray::from_iter_values(self.bytes)),
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+}
+
+/// The columns of the `balances` table. The balance of the chain itself has no owner.
+#[derive(Default)]
+struct BalanceRows {
+    chain_id: Vec<String>,
+    height: Vec<u64>,
+    owner: Vec<Option<String>>,
+    balance: Vec<String>,
+}
+
+impl BalanceRows {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            utf8("chain_id", false),
+            Field::new("height", DataType::UInt64, false),
+            utf8("owner", true),
+            utf8("balance", false),
+        ]))
+    }
+
+    fn len(&self) -> usize {
+        self.height.len()
+    }
+
+    fn push(
+        &mut self,
+        chain_id: ChainId,
+        height: BlockHeight,
+        owner: Option<String>,
+        balance: String,
+    ) {
+        self.chain_id.push(chain_id.to_string());
+        self.height.push(height.0);
+        self.owner.push(owner);
+        self.balance.push(balance);
+    }
+
+    fn into_batch(self) -> anyhow::Result<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from(self.chain_id)),
+            Arc::new(UInt64Array::from(self.height)),
+            Arc::new(StringArray::from(self.owner)),
+            Arc::new(StringArray::from(self.balance)),
+        ];
+        Ok(RecordBatch::try_new(Self::schema(), columns)?)
+    }
+}
+
+fn saturating_u32(value: usize) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::Array as _;
+    use linera_base::crypto::CryptoHash;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use super::*;
+
+    #[test]
+    fn test_watermarks_round_trip() {
+        let output = tempfile::tempdir().unwrap();
+        assert!(read_watermarks(output.path()).unwrap().is_empty());
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let watermarks = Watermarks::from([(chain_id, BlockHeight(7))]);
+        write_watermarks(output.path(), &watermarks).unwrap();
+        assert_eq!(read_watermarks(output.path()).unwrap(), watermarks);
+    }
+
+    #[test]
+    fn test_balances_table() {
+        let output = tempfile::tempdir().unwrap();
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let mut rows = BalanceRows::default();
+        rows.push(chain_id, BlockHeight(3), None, "10.".to_string());
+        rows.push(
+            chain_id,
+            BlockHeight(3),
+            Some("0x01".to_string()),
+            "2.5".to_string(),
+        );
+        let mut writer = TableWriter::create(
+            output.path(),
+            "balances",
+            "test.parquet",
+            BalanceRows::schema(),
+        )
+        .unwrap();
+        writer.write(rows.into_batch().unwrap()).unwrap();
+        writer.close().unwrap();
+
+        let file = fs_err::File::open(output.path().join("balances/test.parquet")).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file.into_parts().0)
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].schema(), BalanceRows::schema());
+        let owners = batches[0]
+            .column(2)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert!(owners.is_null(0));
+        assert_eq!(owners.value(1), "0x01");
+    }
+}
@@ -0,0 +1,230 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Portable snapshots of a storage namespace, for backups and for copying the storage of a
+//! validator or a client to another machine, possibly with another database backend.
+//!
+//! A snapshot contains every key and value of every partition of the namespace, e.g. the
+//! states of the chains, the certificates and the blobs. It is a zstd-compressed stream
+//! starting with a magic string and a format version, followed by BCS-encoded records,
+//! each preceded by its length. The last record counts the partitions and the keys, so
+//! that a truncated snapshot is detected when importing it.
+//!
+//! The database must not be written to while the snapshot is exported.
+
+use std::{
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use anyhow::{bail, ensure, Context as _};
+use linera_views::{
+    batch::Batch,
+    store::{
+        KeyValueDatabase, KeyValueStore, ReadableKeyValueStore as _, WritableKeyValueStore as _,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+/// The magic string at the start of every snapshot.
+const MAGIC: &[u8] = b"linera-storage-snapshot";
+
+/// The version of the snapshot format.
+const VERSION: u8 = 1;
+
+/// The zstd compression level.
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// The maximal number of bytes of keys and values written to the database at once when
+/// importing.
+const IMPORT_BATCH_BYTES: usize = 4 * 1024 * 1024;
+
+/// A record of a snapshot.
+#[derive(Debug, Serialize, Deserialize)]
+enum Record {
+    /// The start of a partition, with its root key. The following keys belong to it.
+    Partition(Vec<u8>),
+    /// A key of the current partition and its value.
+    KeyValue(Vec<u8>, Vec<u8>),
+    /// The end of the snapshot.
+    End(SnapshotSummary),
+}
+
+/// The contents of an exported or imported snapshot.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotSummary {
+    /// The number of partitions.
+    pub partitions: u64,
+    /// The number of keys, in all the partitions.
+    pub keys: u64,
+}
+
+/// Writes a snapshot of all the partitions of `database` to a new file at `output`.
+pub async fn export<D>(database: &D, output: &Path) -> anyhow::Result<SnapshotSummary>
+where
+    D: KeyValueDatabase,
+    D::Store: KeyValueStore,
+{
+    let file = fs_err::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(output)?;
+    let mut writer = zstd::Encoder::new(BufWriter::new(file), COMPRESSION_LEVEL)?;
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION])?;
+    let mut summary = SnapshotSummary::default();
+    for root_key in database.list_root_keys().await? {
+        let store = database.open_shared(&root_key)?;
+        let key_values = store.find_key_values_by_prefix(&[]).await?;
+        write_record(&mut writer, &Record::Partition(root_key))?;
+        summary.partitions += 1;
+        for (key, value) in key_values {
+            write_record(&mut writer, &Record::KeyValue(key, value))?;
+            summary.keys += 1;
+        }
+    }
+    write_record(&mut writer, &Record::End(summary))?;
+    writer.finish()?.flush()?;
+    Ok(summary)
+}
+
+/// Writes the contents of the snapshot at `input` to `database`, which should be empty.
+pub async fn import<D>(database: &D, input: &Path) -> anyhow::Result<SnapshotSummary>
+where
+    D: KeyValueDatabase,
+    D::Store: KeyValueStore,
+{
+    let file = fs_err::File::open(input)?;
+    let mut reader = zstd::Decoder::new(BufReader::new(file))
+        .with_context(|| format!("{} is not a storage snapshot", input.display()))?;
+    let mut header = [0u8; MAGIC.len() + 1];
+    reader
+        .read_exact(&mut header)
+        .ok()
+        .filter(|_| header.starts_with(MAGIC))
+        .with_context(|| format!("{} is not a storage snapshot", input.display()))?;
+    let version = header[MAGIC.len()];
+    ensure!(
+        version == VERSION,
+        "unsupported storage snapshot version {version}"
+    );
+
+    let mut summary = SnapshotSummary::default();
+    let mut partition: Option<(D::Store, Batch)> = None;
+    loop {
+        match read_record(&mut reader)? {
+            Record::Partition(root_key) => {
+                if let Some((store, batch)) = partition.take() {
+                    store.write_batch(batch).await?;
+                }
+                partition = Some((database.open_exclusive(&root_key)?, Batch::new()));
+                summary.partitions += 1;
+            }
+            Record::KeyValue(key, value) => {
+                let Some((store, batch)) = &mut partition else {
+                    bail!("the storage snapshot has a key outside of any partition");
+                };
+                batch.put_key_value_bytes(key, value);
+                summary.keys += 1;
+                if batch.size() >= IMPORT_BATCH_BYTES {
+                    store.write_batch(std::mem::take(batch)).await?;
+                }
+            }
+            Record::End(expected) => {
+                if let Some((store, batch)) = partition.take() {
+                    store.write_batch(batch).await?;
+                }
+                ensure!(
+                    summary == expected,
+                    "the storage snapshot is inconsistent: it has {} partitions and {} keys \
+                     instead of {} and {}",
+                    summary.partitions,
+                    summary.keys,
+                    expected.partitions,
+                    expected.keys
+                );
+                return Ok(summary);
+            }
+        }
+    }
+}
+
+/// Writes a record, preceded by its length.
+fn write_record(writer: &mut impl Write, record: &Record) -> anyhow::Result<()> {
+    let bytes = bcs::to_bytes(record)?;
+    writer.write_all(&u64::try_from(bytes.len())?.to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    Ok(())
+}
+
+/// Reads a record written by `write_record`.
+fn read_record(reader: &mut impl Read) -> anyhow::Result<Record> {
+    let mut length = [0u8; 8];
+    reader
+        .read_exact(&mut length)
+        .context("the storage snapshot is truncated")?;
+    let mut bytes = vec![0u8; usize::try_from(u64::from_le_bytes(length))?];
+    reader
+        .read_exact(&mut bytes)
+        .context("the storage snapshot is truncated")?;
+    Ok(bcs::from_bytes(&bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_views::{
+        batch::Batch,
+        memory::MemoryDatabase,
+        store::{
+            KeyValueDatabase as _, ReadableKeyValueStore as _, TestKeyValueDatabase as _,
+            WritableKeyValueStore as _,
+        },
+    };
+
+    use super::{export, import, SnapshotSummary};
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() -> anyhow::Result<()> {
+        let source = MemoryDatabase::connect_test_namespace().await?;
+        for (root_key, values) in [(vec![0], 3u8), (vec![1, 2], 1)] {
+            let mut batch = Batch::new();
+            for index in 0..values {
+                batch.put_key_value_bytes(vec![index], vec![index; 100]);
+            }
+            source.open_exclusive(&root_key)?.write_batch(batch).await?;
+        }
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("snapshot.zst");
+        let expected = SnapshotSummary {
+            partitions: 2,
+            keys: 4,
+        };
+        assert_eq!(export(&source, &path).await?, expected);
+        // Snapshots are never overwritten.
+        assert!(export(&source, &path).await.is_err());
+
+        let target = MemoryDatabase::connect_test_namespace().await?;
+        assert_eq!(import(&target, &path).await?, expected);
+        let mut root_keys = target.list_root_keys().await?;
+        root_keys.sort();
+        assert_eq!(root_keys, [vec![0], vec![1, 2]]);
+        let key_values = target
+            .open_shared(&[0])?
+            .find_key_values_by_prefix(&[])
+            .await?;
+        assert_eq!(key_values.len(), 3);
+        assert_eq!(key_values[2], (vec![2], vec![2; 100]));
+
+        // A truncated snapshot is rejected.
+        let bytes = fs_err::read(&path)?;
+        let truncated = dir.path().join("truncated.zst");
+        let decompressed = zstd::decode_all(&bytes[..])?;
+        fs_err::write(
+            &truncated,
+            zstd::encode_all(&decompressed[..decompressed.len() - 10], 3)?,
+        )?;
+        let target = MemoryDatabase::connect_test_namespace().await?;
+        assert!(import(&target, &truncated).await.is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Statements of the tokens moved by the blocks of a wallet, built from its spending
+//! journal (see [`linera_client::spending_journal`]).
+//!
+//! Each line of a statement is a debit or a credit of an account, with a reference to the
+//! block that moved the tokens. Given a table of exchange rates, each line is also valued in
+//! another currency, at the latest rate known on its day.
+
+use std::{collections::BTreeMap, fmt::Write as _, path::Path};
+
+use anyhow::{bail, Context as _};
+use chrono::{DateTime, NaiveDate, Utc};
+use linera_base::{
+    data_types::{Amount, Timestamp},
+    identifiers::ChainId,
+};
+use linera_client::spending_journal::{FlowKind, JournalEntry};
+use serde::Serialize;
+
+/// The format of the dates in statements.
+const DATE_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// The value of one token in another currency, per day.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExchangeRates(BTreeMap<NaiveDate, Amount>);
+
+impl ExchangeRates {
+    /// Reads the rates from a CSV file of `date,rate` lines.
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs_err::read_to_string(path)?;
+        Self::parse(&contents).with_context(|| format!("invalid rates in {}", path.display()))
+    }
+
+    /// Parses `date,rate` lines, e.g. `2026-01-01,0.25`. Empty lines, lines starting with
+    /// `#` and a `date,rate` header are ignored.
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let mut rates = BTreeMap::new();
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || (index == 0 && line == "date,rate") {
+                continue;
+            }
+            let Some((date, rate)) = line.split_once(',') else {
+                bail!("line {} is not of the form `date,rate`", index + 1);
+            };
+            let date = date
+                .trim()
+                .parse::<NaiveDate>()
+                .with_context(|| format!("invalid date on line {}", index + 1))?;
+            let rate = rate
+                .trim()
+                .parse::<Amount>()
+                .with_context(|| format!("invalid rate on line {}", index + 1))?;
+            rates.insert(date, rate);
+        }
+        Ok(ExchangeRates(rates))
+    }
+
+    /// Returns the latest rate on or before the given day.
+    pub fn rate_on(&self, date: NaiveDate) -> Option<Amount> {
+        self.0.range(..=date).next_back().map(|(_, rate)| *rate)
+    }
+}
+
+/// A debit or credit of a statement.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementLine {
+    /// The time of the block, in UTC.
+    pub date: DateTime<Utc>,
+    /// The movement of tokens.
+    #[serde(flatten)]
+    pub entry: JournalEntry,
+    /// The value of one token on that day, if exchange rates were given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exchange_rate: Option<Amount>,
+    /// The value of the tokens moved, at that rate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<Amount>,
+}
+
+impl StatementLine {
+    /// Returns whether the tokens left the account.
+    pub fn is_debit(&self) -> bool {
+        self.entry.kind != FlowKind::TransferIn
+    }
+}
+
+/// Returns the lines of the statement for the given entries of the journal, oldest first:
+/// only those of `chain_id` if given, and between the days `from` and `to`, both included.
+pub fn statement(
+    entries: Vec<JournalEntry>,
+    chain_id: Option<ChainId>,
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    rates: Option<&ExchangeRates>,
+) -> anyhow::Result<Vec<StatementLine>> {
+    let mut lines = Vec::new();
+    for entry in entries {
+        if chain_id.is_some_and(|chain_id| chain_id != entry.chain_id) {
+            continue;
+        }
+        let date = to_datetime(entry.timestamp)?;
+        let day = date.date_naive();
+        if from.is_some_and(|from| day < from) || to.is_some_and(|to| day > to) {
+            continue;
+        }
+        let exchange_rate = rates.and_then(|rates| rates.rate_on(day));
+        let value = exchange_rate
+            .map(|rate| {
+                convert(entry.amount, rate)
+                    .with_context(|| format!("the value of {} tokens is too large", entry.amount))
+            })
+            .transpose()?;
+        lines.push(StatementLine {
+            date,
+            entry,
+            exchange_rate,
+            value,
+        });
+    }
+    lines.sort_by_key(|line| (line.date, line.entry.chain_id, line.entry.height));
+    Ok(lines)
+}
+
+/// Renders the statement as CSV. The `exchange_rate` and `value` columns are only included
+/// if `with_rates` is set; they are empty for the lines before the first known rate.
+pub fn to_csv(lines: &[StatementLine], with_rates: bool) -> String {
+    let mut csv =
+        "date,chain_id,height,block_hash,kind,account,counterparty,debit,credit".to_string();
+    if with_rates {
+        csv.push_str(",exchange_rate,value");
+    }
+    csv.push('\n');
+    for line in lines {
+        let entry = &line.entry;
+        let counterparty = entry
+            .counterparty
+            .map(|account| account.to_string())
+            .unwrap_or_default();
+        let amount = entry.amount.to_string();
+        let (debit, credit) = if line.is_debit() {
+            (amount.as_str(), "")
+        } else {
+            ("", amount.as_str())
+        };
+        write!(
+            csv,
+            "{},{},{},{},{},{},{counterparty},{debit},{credit}",
+            line.date.format(DATE_FORMAT),
+            entry.chain_id,
+            entry.height,
+            entry.block_hash,
+            entry.kind.as_str(),
+            entry.owner,
+        )
+        .expect("writing to a string cannot fail");
+        if with_rates {
+            let column = |amount: Option<Amount>| {
+                amount.map(|amount| amount.to_string()).unwrap_or_default()
+            };
+            write!(
+                csv,
+                ",{},{}",
+                column(line.exchange_rate),
+                column(line.value)
+            )
+            .expect("writing to a string cannot fail");
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Prints the statement in a human-readable form, with the totals of each chain.
+pub fn print_text(lines: &[StatementLine]) {
+    let mut totals = BTreeMap::<ChainId, (Amount, Amount)>::new();
+    for line in lines {
+        let entry = &line.entry;
+        let sign = if line.is_debit() { '-' } else { '+' };
+        let counterparty = match (entry.kind, entry.counterparty) {
+            (FlowKind::TransferOut, Some(account)) => format!("to {account}"),
+            (FlowKind::TransferIn, Some(account)) => format!("from {account}"),
+            _ => String::new(),
+        };
+        println!(
+            "{}  {}  {:>6}  {:<12}  {sign}{:<24}  {}  {counterparty}",
+            line.date.format(DATE_FORMAT),
+            entry.chain_id,
+            entry.height,
+            entry.kind.as_str(),
+            entry.amount,
+            entry.owner,
+        );
+        let (debits, credits) = totals.entry(entry.chain_id).or_default();
+        let total = if line.is_debit() { debits } else { credits };
+        total.saturating_add_assign(entry.amount);
+    }
+    for (chain_id, (debits, credits)) in totals {
+        println!("Chain {chain_id}: {credits} tokens received, {debits} tokens spent");
+    }
+}
+
+fn to_datetime(timestamp: Timestamp) -> anyhow::Result<DateTime<Utc>> {
+    i64::try_from(timestamp.micros())
+        .ok()
+        .and_then(DateTime::from_timestamp_micros)
+        .with_context(|| format!("invalid block timestamp {timestamp}"))
+}
+
+/// Returns `amount * rate`, both having the same number of decimal places, or `None` if
+/// it overflows.
+fn convert(amount: Amount, rate: Amount) -> Option<Amount> {
+    let one = Amount::ONE.to_attos();
+    let (amount, rate) = (amount.to_attos(), rate.to_attos());
+    let (rate_units, rate_fraction) = (rate / one, rate % one);
+    let (amount_units, amount_fraction) = (amount / one, amount % one);
+    let attos = amount
+        .checked_mul(rate_units)?
+        .checked_add(amount_units.checked_mul(rate_fraction)?)?
+        // Both fractions are less than 10^18, so their product fits in a `u128`.
+        .checked_add(amount_fraction * rate_fraction / one)?;
+    Some(Amount::from_attos(attos))
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::{
+        crypto::CryptoHash,
+        data_types::{Amount, BlockHeight, Timestamp},
+        identifiers::{Account, AccountOwner, ChainId},
+    };
+    use linera_client::spending_journal::{FlowKind, JournalEntry};
+
+    use super::{convert, statement, to_csv, ExchangeRates};
+
+    /// 2026-01-01 00:00:00 UTC, in microseconds.
+    const NEW_YEAR: u64 = 1_767_225_600_000_000;
+    const DAY: u64 = 86_400_000_000;
+
+    fn entry(chain_id: ChainId, day: u64, kind: FlowKind, amount: Amount) -> JournalEntry {
+        JournalEntry {
+            chain_id,
+            height: BlockHeight(day),
+            block_hash: CryptoHash::test_hash(format!("block {day}")),
+            timestamp: Timestamp::from(NEW_YEAR + day * DAY),
+            kind,
+            owner: AccountOwner::CHAIN,
+            counterparty: (kind != FlowKind::Fee)
+                .then(|| Account::chain(ChainId(CryptoHash::test_hash("other chain")))),
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_convert() {
+        let tokens = |tokens: &str| tokens.parse::<Amount>().unwrap();
+        assert_eq!(convert(tokens("3"), tokens("0.5")), Some(tokens("1.5")));
+        assert_eq!(
+            convert(tokens("1.25"), tokens("2000.1")),
+            Some(tokens("2500.125"))
+        );
+        assert_eq!(
+            convert(tokens("0.000000000000000001"), tokens("0.5")),
+            Some(Amount::ZERO)
+        );
+        assert_eq!(convert(Amount::MAX, tokens("2")), None);
+    }
+
+    #[test]
+    fn test_statement_csv() -> anyhow::Result<()> {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let other_chain_id = ChainId(CryptoHash::test_hash("other chain"));
+        let entries = vec![
+            entry(chain_id, 0, FlowKind::TransferIn, Amount::from_tokens(10)),
+            entry(chain_id, 2, FlowKind::TransferOut, Amount::from_tokens(4)),
+            entry(chain_id, 2, FlowKind::Fee, Amount::from_millis(1)),
+            entry(other_chain_id, 2, FlowKind::Fee, Amount::from_millis(1)),
+            entry(chain_id, 5, FlowKind::TransferOut, Amount::ONE),
+        ];
+        let rates = ExchangeRates::parse("date,rate\n2026-01-02,0.5\n\n2026-01-04,2\n")?;
+        let lines = statement(
+            entries,
+            Some(chain_id),
+            Some("2026-01-01".parse()?),
+            Some("2026-01-05".parse()?),
+            Some(&rates),
+        )?;
+        let csv = to_csv(&lines, true);
+        let rows = csv
+            .lines()
+            .map(|row| {
+                let columns = row.split(',').collect::<Vec<_>>();
+                [
+                    columns[0],
+                    columns[4],
+                    columns[7],
+                    columns[8],
+                    columns[9],
+                    columns[10],
+                ]
+                .join(",")
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rows,
+            [
+                "date,kind,debit,credit,exchange_rate,value",
+                "2026-01-01 00:00:00,transfer-in,,10.,,",
+                "2026-01-03 00:00:00,transfer-out,4.,,0.5,2.",
+                "2026-01-03 00:00:00,fee,0.001,,0.5,0.0005",
+            ]
+        );
+        assert!(!to_csv(&lines, false).contains("exchange_rate"));
+        Ok(())
+    }
+}
@@ -0,0 +1,279 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The test vectors printed by `linera dev gen-test-vectors`: canonical BCS encodings and
+//! hashes of representative protocol structures, so that other implementations of the
+//! protocol can check that they encode, hash and sign them the same way.
+//!
+//! All keys are derived from fixed seeds, and both signature schemes sign deterministically,
+//! so the suite only changes when the protocol does.
+
+use linera_base::{
+    crypto::{
+        AccountPublicKey, AccountSecretKey, AccountSignature, BcsHashable, CryptoHash, CryptoRng,
+        Ed25519SecretKey, ValidatorKeypair,
+    },
+    data_types::{Amount, BlockHeight, Epoch, Round, Timestamp},
+    identifiers::{Account, AccountOwner, ApplicationId, ChainId},
+};
+use linera_chain::{
+    data_types::{
+        BlockExecutionOutcome, BlockProposal, LiteValue, LiteVote, OperationResult,
+        ProposalContent, ProposedBlock, Transaction,
+    },
+    types::{Block, CertificateValue as _, ConfirmedBlock, ConfirmedBlockCertificate},
+};
+use linera_execution::{system::SystemOperation, Operation};
+use serde::{Deserialize, Serialize};
+
+/// The version of the format of the suite. It changes whenever vectors are added, removed
+/// or reshaped.
+pub const TEST_VECTORS_VERSION: u32 = 1;
+
+/// A versioned suite of test vectors.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestVectorSuite {
+    /// The version of the format of the suite.
+    pub version: u32,
+    /// The version of the Linera crates that produced the suite.
+    pub crate_version: String,
+    /// The test vectors.
+    pub vectors: Vec<TestVector>,
+}
+
+/// A protocol value together with its canonical encoding and hash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TestVector {
+    /// A unique name for the vector.
+    pub name: String,
+    /// The Rust type of the value.
+    pub type_name: String,
+    /// The value, in its JSON representation.
+    pub value: serde_json::Value,
+    /// The hex-encoded BCS serialization of the value.
+    pub bcs: String,
+    /// The hash of the value, for values that are hashed by the protocol.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<CryptoHash>,
+    /// The inputs needed to reproduce the value, e.g. the key and the hash signed by a
+    /// signature.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inputs: Option<serde_json::Value>,
+}
+
+impl TestVector {
+    fn new<T: Serialize>(name: &str, value: &T) -> anyhow::Result<Self> {
+        let type_name = std::any::type_name::<T>()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        Ok(Self {
+            name: name.to_string(),
+            type_name,
+            value: serde_json::to_value(value)?,
+            bcs: hex::encode(bcs::to_bytes(value)?),
+            hash: None,
+            inputs: None,
+        })
+    }
+
+    fn hashed<'de, T: BcsHashable<'de>>(name: &str, value: &T) -> anyhow::Result<Self> {
+        Ok(Self {
+            hash: Some(CryptoHash::new(value)),
+            ..Self::new(name, value)?
+        })
+    }
+
+    fn with_hash(mut self, hash: CryptoHash) -> Self {
+        self.hash = Some(hash);
+        self
+    }
+
+    fn with_inputs(mut self, inputs: serde_json::Value) -> Self {
+        self.inputs = Some(inputs);
+        self
+    }
+}
+
+/// Returns a deterministic RNG for the given seed.
+fn rng(seed: u64) -> Box<dyn CryptoRng> {
+    Some(seed).into()
+}
+
+/// An account key and the owner it authenticates.
+struct Signer {
+    secret_key: AccountSecretKey,
+    public_key: AccountPublicKey,
+    owner: AccountOwner,
+}
+
+impl Signer {
+    fn new(secret_key: AccountSecretKey) -> Self {
+        let public_key = secret_key.public();
+        let owner = AccountOwner::from(public_key);
+        Self {
+            secret_key,
+            public_key,
+            owner,
+        }
+    }
+
+    fn signature_vector(&self, name: &str, prehash: CryptoHash) -> anyhow::Result<TestVector> {
+        let signature = self.secret_key.sign_prehash(prehash);
+        Ok(
+            TestVector::new(name, &signature)?.with_inputs(serde_json::json!({
+                "secret_key": self.secret_key,
+                "public_key": self.public_key,
+                "owner": self.owner,
+                "signed_hash": prehash,
+            })),
+        )
+    }
+
+    fn proposal(&self, block: ProposedBlock, round: Round) -> BlockProposal {
+        let content = ProposalContent {
+            block,
+            round,
+            outcome: None,
+        };
+        let signature: AccountSignature = self.secret_key.sign(&content);
+        BlockProposal {
+            content,
+            signature,
+            original_proposal: None,
+        }
+    }
+}
+
+/// Generates the test vector suite.
+pub fn generate() -> anyhow::Result<TestVectorSuite> {
+    let ed25519 = Signer::new(AccountSecretKey::Ed25519(Ed25519SecretKey::generate_from(
+        &mut rng(1),
+    )));
+    let secp256k1 = Signer::new(AccountSecretKey::Secp256k1(
+        ValidatorKeypair::generate_from(&mut rng(2)).secret_key,
+    ));
+    let validator = ValidatorKeypair::generate_from(&mut rng(3));
+
+    let chain_id = ChainId(CryptoHash::from([1, 2, 3, 4]));
+    let recipient = Account::new(ChainId(CryptoHash::from([5, 6, 7, 8])), secp256k1.owner);
+    let application_id = ApplicationId::new(CryptoHash::from([9, 10, 11, 12]));
+    let message_hash = CryptoHash::from([13, 14, 15, 16]);
+
+    let transfer = Operation::system(SystemOperation::Transfer {
+        owner: ed25519.owner,
+        recipient,
+        amount: Amount::from_tokens(3),
+    });
+    let user_operation = Operation::User {
+        application_id,
+        bytes: b"increment".to_vec(),
+    };
+    let transactions = vec![
+        Transaction::ExecuteOperation(transfer.clone()),
+        Transaction::ExecuteOperation(user_operation.clone()),
+    ];
+    let block = ProposedBlock {
+        chain_id,
+        epoch: Epoch::ZERO,
+        transactions: transactions.clone(),
+        height: BlockHeight(1),
+        timestamp: Timestamp::from(1_700_000_000_000_000),
+        authenticated_owner: Some(ed25519.owner),
+        previous_block_hash: Some(CryptoHash::from([17, 18, 19, 20])),
+    };
+    let ed25519_proposal = ed25519.proposal(block.clone(), Round::Fast);
+    let secp256k1_proposal = secp256k1.proposal(
+        ProposedBlock {
+            authenticated_owner: Some(secp256k1.owner),
+            ..block.clone()
+        },
+        Round::MultiLeader(0),
+    );
+
+    // The outcome is not the result of executing the block: the vectors only fix how
+    // blocks are encoded and hashed.
+    let outcome = BlockExecutionOutcome {
+        messages: vec![Vec::new(); transactions.len()],
+        previous_message_blocks: Default::default(),
+        previous_event_blocks: Default::default(),
+        state_hash: CryptoHash::from([21, 22, 23, 24]),
+        oracle_responses: vec![Vec::new(); transactions.len()],
+        events: vec![Vec::new(); transactions.len()],
+        blobs: vec![Vec::new(); transactions.len()],
+        operation_results: vec![OperationResult(Vec::new()); transactions.len()],
+    };
+    let confirmed_block = ConfirmedBlock::new(Block::new(block.clone(), outcome));
+    let round = Round::MultiLeader(0);
+    let vote = LiteVote::new(
+        LiteValue::new(&confirmed_block),
+        round,
+        &validator.secret_key,
+    );
+    let certificate = ConfirmedBlockCertificate::new(
+        confirmed_block.clone(),
+        round,
+        vec![(validator.public_key, vote.signature)],
+    );
+
+    let vectors = vec![
+        ed25519.signature_vector("account_signature_ed25519", message_hash)?,
+        secp256k1.signature_vector("account_signature_secp256k1", message_hash)?,
+        TestVector::hashed("operation_system_transfer", &transfer)?,
+        TestVector::hashed("operation_user", &user_operation)?,
+        TestVector::new("proposed_block", &block)?,
+        TestVector::new("block_proposal_ed25519", &ed25519_proposal)?
+            .with_hash(CryptoHash::new(&ed25519_proposal.content))
+            .with_inputs(serde_json::json!({ "secret_key": ed25519.secret_key })),
+        TestVector::new("block_proposal_secp256k1", &secp256k1_proposal)?
+            .with_hash(CryptoHash::new(&secp256k1_proposal.content))
+            .with_inputs(serde_json::json!({ "secret_key": secp256k1.secret_key })),
+        TestVector::new("confirmed_block", &confirmed_block)?.with_hash(confirmed_block.hash()),
+        TestVector::new("confirmed_block_certificate", &certificate)?
+            .with_hash(certificate.hash())
+            .with_inputs(serde_json::json!({
+                "validator_secret_key": validator.secret_key,
+                "validator_public_key": validator.public_key,
+            })),
+    ];
+    Ok(TestVectorSuite {
+        version: TEST_VECTORS_VERSION,
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        vectors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::crypto::AccountSignature;
+    use linera_chain::{data_types::BlockProposal, types::ConfirmedBlockCertificate};
+
+    use super::{generate, TEST_VECTORS_VERSION};
+
+    #[test]
+    fn test_vectors_are_deterministic_and_decode() {
+        let suite = generate().unwrap();
+        assert_eq!(suite, generate().unwrap());
+        assert_eq!(suite.version, TEST_VECTORS_VERSION);
+
+        let vector = |name: &str| {
+            let vector = suite
+                .vectors
+                .iter()
+                .find(|vector| vector.name == name)
+                .unwrap();
+            hex::decode(&vector.bcs).unwrap()
+        };
+        for name in ["account_signature_ed25519", "account_signature_secp256k1"] {
+            let _: AccountSignature = bcs::from_bytes(&vector(name)).unwrap();
+        }
+        for name in ["block_proposal_ed25519", "block_proposal_secp256k1"] {
+            let proposal: BlockProposal = bcs::from_bytes(&vector(name)).unwrap();
+            proposal.signature.verify(&proposal.content).unwrap();
+        }
+        let certificate: ConfirmedBlockCertificate =
+            bcs::from_bytes(&vector("confirmed_block_certificate")).unwrap();
+        assert_eq!(certificate.signatures().len(), 1);
+    }
+}
@@ -3,25 +3,39 @@
 
 //! Validator management commands.
 
-use std::{collections::HashMap, num::NonZero, str::FromStr};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    num::NonZero,
+    str::FromStr,
+};
 
 use anyhow::Context as _;
 use futures::stream::TryStreamExt as _;
 use linera_base::{
     crypto::{AccountPublicKey, ValidatorPublicKey},
-    data_types::BlockHeight,
-    identifiers::ChainId,
+    data_types::{BlockHeight, Epoch, Timestamp},
+    identifiers::{BlobId, BlobType, ChainId, IndexAndEvent, StreamId},
+};
+use linera_client::{
+    chain_listener::ClientContext as _,
+    client_context::{ClientContext, ValidatorHealth},
 };
-use linera_client::{chain_listener::ClientContext as _, client_context::ClientContext};
 use linera_core::{
     data_types::ClientOutcome,
     node::{ValidatorNode, ValidatorNodeProvider},
     Wallet as _,
 };
-use linera_execution::committee::{Committee, ValidatorState};
+use linera_execution::{
+    committee::{Committee, ValidatorState},
+    system::{EpochEventData, EPOCH_STREAM_NAME, REMOVED_EPOCH_STREAM_NAME},
+};
+use linera_storage::Storage as _;
 use serde::{Deserialize, Serialize};
 
-use crate::cli::validator_benchmark::Benchmark;
+use crate::cli::{
+    output::{self, OutputFormat},
+    validator_benchmark::Benchmark,
+};
 
 /// Type alias for the complex ClientContext type used throughout validator operations.
 /// This alias helps avoid clippy's type_complexity warnings while maintaining type safety.
@@ -166,14 +180,173 @@ pub struct Update {
 ///
 /// Displays the current validator set with their network addresses, voting weights,
 /// and connection status. Optionally filter by minimum voting weight.
+///
+/// With `--health`, also prints a health score for each validator, from its response
+/// latency, how far behind it is on the chain and whether it runs a different release.
+///
+/// With `--history`, instead prints the committee of every epoch read from the admin
+/// chain's epoch stream, with the validators added, removed or changed in each epoch.
 #[derive(Debug, Clone, clap::Parser)]
 pub struct List {
     /// Chain ID to query (defaults to default chain)
     #[arg(long)]
     chain_id: Option<ChainId>,
     /// Only show validators with at least this many votes
-    #[arg(long)]
+    #[arg(long, conflicts_with = "history")]
     min_votes: Option<u64>,
+    /// Print a health score for each validator, from 0 (unreachable) to 100
+    #[arg(long, conflicts_with = "history")]
+    health: bool,
+    /// Print the committee history across epochs instead of querying the validators
+    #[arg(long)]
+    history: bool,
+    /// Print the committee history as JSON
+    #[arg(long, requires = "history")]
+    json: bool,
+}
+
+/// The committee of an epoch, and how it differs from the committee of the previous epoch.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpochHistoryEntry {
+    /// The epoch.
+    pub epoch: Epoch,
+    /// When the epoch was created on the admin chain.
+    pub timestamp: Timestamp,
+    /// Whether the committee was removed, i.e. is no longer accepted.
+    pub removed: bool,
+    /// The number of validators in the committee.
+    pub validator_count: usize,
+    /// The sum of the votes of the validators.
+    pub total_votes: u64,
+    /// The changes since the previous epoch. For the first epoch, every validator is added.
+    pub changes: Vec<ValidatorChange>,
+}
+
+/// A change to a validator between two consecutive epochs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ValidatorChange {
+    /// The validator joined the committee.
+    #[serde(rename_all = "camelCase")]
+    Added {
+        /// The public key of the validator.
+        public_key: ValidatorPublicKey,
+        /// The network address of the validator.
+        network_address: String,
+        /// The votes of the validator.
+        votes: u64,
+    },
+    /// The validator left the committee.
+    #[serde(rename_all = "camelCase")]
+    Removed {
+        /// The public key of the validator.
+        public_key: ValidatorPublicKey,
+        /// The votes of the validator.
+        votes: u64,
+    },
+    /// The voting weight of the validator changed.
+    #[serde(rename_all = "camelCase")]
+    VotesChanged {
+        /// The public key of the validator.
+        public_key: ValidatorPublicKey,
+        /// The votes in the previous epoch.
+        old_votes: u64,
+        /// The votes in this epoch.
+        new_votes: u64,
+    },
+    /// The network address of the validator changed.
+    #[serde(rename_all = "camelCase")]
+    AddressChanged {
+        /// The public key of the validator.
+        public_key: ValidatorPublicKey,
+        /// The address in the previous epoch.
+        old_address: String,
+        /// The address in this epoch.
+        new_address: String,
+    },
+}
+
+impl std::fmt::Display for ValidatorChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidatorChange::Added {
+                public_key,
+                network_address,
+                votes,
+            } => write!(f, "+ {public_key} at {network_address} with {votes} votes"),
+            ValidatorChange::Removed { public_key, votes } => {
+                write!(f, "- {public_key} with {votes} votes")
+            }
+            ValidatorChange::VotesChanged {
+                public_key,
+                old_votes,
+                new_votes,
+            } => write!(f, "~ {public_key}: votes {old_votes} -> {new_votes}"),
+            ValidatorChange::AddressChanged {
+                public_key,
+                old_address,
+                new_address,
+            } => write!(f, "~ {public_key}: address {old_address} -> {new_address}"),
+        }
+    }
+}
+
+/// Returns the history of the given committees, in epoch order.
+pub fn committee_history(
+    committees: &[(Epoch, Timestamp, Committee)],
+    removed_epochs: &BTreeSet<Epoch>,
+) -> Vec<EpochHistoryEntry> {
+    let empty = BTreeMap::<ValidatorPublicKey, ValidatorState>::new();
+    let mut previous = &empty;
+    let mut history = Vec::new();
+    for (epoch, timestamp, committee) in committees {
+        let validators = committee.validators();
+        let mut changes = Vec::new();
+        for (public_key, state) in validators {
+            match previous.get(public_key) {
+                None => changes.push(ValidatorChange::Added {
+                    public_key: *public_key,
+                    network_address: state.network_address.clone(),
+                    votes: state.votes,
+                }),
+                Some(old) => {
+                    if old.votes != state.votes {
+                        changes.push(ValidatorChange::VotesChanged {
+                            public_key: *public_key,
+                            old_votes: old.votes,
+                            new_votes: state.votes,
+                        });
+                    }
+                    if old.network_address != state.network_address {
+                        changes.push(ValidatorChange::AddressChanged {
+                            public_key: *public_key,
+                            old_address: old.network_address.clone(),
+                            new_address: state.network_address.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        for (public_key, old) in previous {
+            if !validators.contains_key(public_key) {
+                changes.push(ValidatorChange::Removed {
+                    public_key: *public_key,
+                    votes: old.votes,
+                });
+            }
+        }
+        history.push(EpochHistoryEntry {
+            epoch: *epoch,
+            timestamp: *timestamp,
+            removed: removed_epochs.contains(epoch),
+            validator_count: validators.len(),
+            total_votes: committee.total_votes(),
+            changes,
+        });
+        previous = validators;
+    }
+    history
 }
 
 /// Query a single validator's state and connectivity.
@@ -255,12 +428,14 @@ fn parse_query_batch_file(input: clio::Input) -> anyhow::Result<QueryBatch> {
 }
 
 impl Command {
-    /// Main entry point for handling validator commands.
+    /// Main entry point for handling validator commands. The `list` and `query` commands
+    /// print their results in the given format.
     pub async fn run(
         &self,
         context: &mut ClientContext<
             impl linera_core::Environment<ValidatorNode = linera_rpc::Client>,
         >,
+        format: OutputFormat,
     ) -> anyhow::Result<()> {
         use Command::*;
 
@@ -269,8 +444,8 @@ impl Command {
             BatchQuery(command) => Box::pin(command.run(context)).await,
             Benchmark(command) => Box::pin(command.run(context)).await,
             Update(command) => command.run(context).await,
-            List(command) => command.run(context).await,
-            Query(command) => command.run(context).await,
+            List(command) => command.run(context, format).await,
+            Query(command) => command.run(context, format).await,
             QueryBlock(command) => command.run(context).await,
             Remove(command) => command.run(context).await,
             Sync(command) => Box::pin(command.run(context)).await,
@@ -634,9 +809,15 @@ impl List {
     async fn run(
         &self,
         context: &ClientContext<impl linera_core::Environment>,
+        format: OutputFormat,
     ) -> anyhow::Result<()> {
+        if self.history {
+            return self.print_history(context, format).await;
+        }
         let chain_id = self.chain_id.unwrap_or_else(|| context.default_chain());
-        println!("Querying validators about chain {chain_id}.\n");
+        if !format.is_json() {
+            println!("Querying validators about chain {chain_id}.\n");
+        }
 
         let local_results = context.query_local_node(chain_id).await?;
         let chain_client = context.make_chain_client(chain_id).await?;
@@ -659,14 +840,35 @@ impl List {
             }
             let address = &state.network_address;
             let node = node_provider.make_node(address)?;
+            let start_time = std::time::Instant::now();
             let results = context
                 .query_validator(address, &node, chain_id, Some(name))
                 .await;
-            validator_results.push((name, address, state.votes, results));
+            let latency = start_time.elapsed();
+            validator_results.push((name, address, state.votes, results, latency));
         }
 
+        // The lag of each validator is measured against the highest height anyone knows of.
+        let highest_height = std::iter::once(&local_results)
+            .chain(
+                validator_results
+                    .iter()
+                    .map(|(_, _, _, results, _)| results),
+            )
+            .filter_map(|results| results.chain_info.as_ref().ok())
+            .map(|info| info.next_block_height)
+            .max()
+            .unwrap_or_default();
+        let health = validator_results
+            .iter()
+            .map(|(_, _, _, results, latency)| {
+                self.health
+                    .then(|| ValidatorHealth::new(results, *latency, highest_height))
+            })
+            .collect::<Vec<_>>();
+
         let mut faulty_validators = std::collections::BTreeMap::<_, Vec<_>>::new();
-        for (name, address, _votes, results) in &validator_results {
+        for (name, address, _votes, results, _latency) in &validator_results {
             for error in results.errors() {
                 tracing::error!("{}", error);
                 faulty_validators
@@ -676,40 +878,143 @@ impl List {
             }
         }
 
-        // Print local node results first (everything)
-        println!("Local Node:");
-        local_results.print(None, None, None, None);
+        if format.is_json() {
+            let validators = validator_results
+                .iter()
+                .zip(&health)
+                .map(|((name, address, votes, results, _latency), health)| {
+                    let mut json = results.to_json(Some(name), Some(address), Some(*votes));
+                    if let Some(health) = health {
+                        json["health"] = health.to_json();
+                    }
+                    json
+                })
+                .collect::<Vec<_>>();
+            output::print_json(&serde_json::json!({
+                "chainId": chain_id,
+                "localNode": local_results.to_json(None, None, None),
+                "validators": validators,
+            }))?;
+        } else {
+            // Print local node results first (everything)
+            println!("Local Node:");
+            local_results.print(None, None, None, None);
+
+            // Print validator results (only differences from local node)
+            for (name, address, votes, results, _latency) in &validator_results {
+                results.print(
+                    Some(name),
+                    Some(address),
+                    Some(*votes),
+                    Some(&local_results),
+                );
+            }
 
-        // Print validator results (only differences from local node)
-        for (name, address, votes, results) in &validator_results {
-            results.print(
-                Some(name),
-                Some(address),
-                Some(*votes),
-                Some(&local_results),
-            );
+            if self.health {
+                println!("Validator health:");
+                for ((name, address, ..), health) in validator_results.iter().zip(&health) {
+                    if let Some(health) = health {
+                        println!("  {name} at {address}: {health}");
+                    }
+                }
+            }
         }
 
         if !faulty_validators.is_empty() {
-            println!("\nFaulty validators:");
-            for ((name, address), errors) in faulty_validators {
-                println!("  {} at {}: {} error(s)", name, address, errors.len());
+            if !format.is_json() {
+                println!("\nFaulty validators:");
+                for ((name, address), errors) in faulty_validators {
+                    println!("  {} at {}: {} error(s)", name, address, errors.len());
+                }
             }
             anyhow::bail!("Found faulty validators");
         }
 
         Ok(())
     }
+
+    async fn print_history(
+        &self,
+        context: &ClientContext<impl linera_core::Environment>,
+        format: OutputFormat,
+    ) -> anyhow::Result<()> {
+        let admin_chain_id = context.admin_chain_id();
+        let chain_client = context.make_chain_client(admin_chain_id).await?;
+        chain_client
+            .synchronize_from_validators()
+            .await
+            .context("Failed to synchronize the admin chain")?;
+
+        let storage = context.client.storage_client();
+        let network_description = storage
+            .read_network_description()
+            .await?
+            .context("Missing network description")?;
+        // The genesis committee is part of the network description; later committees are
+        // announced on the admin chain's epoch stream.
+        let mut epochs = vec![(
+            Epoch::ZERO,
+            network_description.genesis_committee_blob_hash,
+            network_description.genesis_timestamp,
+        )];
+        for IndexAndEvent { index, event } in storage
+            .read_events_from_index(&admin_chain_id, &StreamId::system(EPOCH_STREAM_NAME), 1)
+            .await?
+        {
+            let event_data: EpochEventData = bcs::from_bytes(&event)?;
+            epochs.push((Epoch(index), event_data.blob_hash, event_data.timestamp));
+        }
+        let removed_epochs = storage
+            .read_events_from_index(
+                &admin_chain_id,
+                &StreamId::system(REMOVED_EPOCH_STREAM_NAME),
+                0,
+            )
+            .await?
+            .into_iter()
+            .map(|event| Epoch(event.index))
+            .collect::<BTreeSet<_>>();
+
+        let mut committees = Vec::new();
+        for (epoch, blob_hash, timestamp) in epochs {
+            let blob_id = BlobId::new(blob_hash, BlobType::Committee);
+            let blob = storage
+                .read_blob(blob_id)
+                .await?
+                .with_context(|| format!("Missing committee blob {blob_id} of epoch {epoch}"))?;
+            let committee: Committee = bcs::from_bytes(blob.bytes())?;
+            committees.push((epoch, timestamp, committee));
+        }
+
+        let history = committee_history(&committees, &removed_epochs);
+        if self.json || format.is_json() {
+            return output::print_json(&history);
+        }
+        for entry in history {
+            let removed = if entry.removed { " (removed)" } else { "" };
+            println!(
+                "Epoch {} created at {}{removed}: {} validator(s), {} votes",
+                entry.epoch, entry.timestamp, entry.validator_count, entry.total_votes
+            );
+            for change in entry.changes {
+                println!("  {change}");
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Query {
     async fn run(
         &self,
         context: &ClientContext<impl linera_core::Environment>,
+        format: OutputFormat,
     ) -> anyhow::Result<()> {
         let node = context.make_node_provider().make_node(&self.address)?;
         let chain_id = self.chain_id.unwrap_or_else(|| context.default_chain());
-        println!("Querying validator about chain {chain_id}.\n");
+        if !format.is_json() {
+            println!("Querying validator about chain {chain_id}.\n");
+        }
 
         let results = context
             .query_validator(&self.address, &node, chain_id, self.public_key.as_ref())
@@ -719,7 +1024,15 @@ impl Query {
             tracing::error!("{}", error);
         }
 
-        results.print(self.public_key.as_ref(), Some(&self.address), None, None);
+        if format.is_json() {
+            output::print_json(&results.to_json(
+                self.public_key.as_ref(),
+                Some(&self.address),
+                None,
+            ))?;
+        } else {
+            results.print(self.public_key.as_ref(), Some(&self.address), None, None);
+        }
 
         if !results.errors().is_empty() {
             anyhow::bail!(
@@ -1010,4 +1323,71 @@ mod tests {
         assert_eq!(parsed_batch.validators[0].votes.0.get(), 100);
         assert_eq!(parsed_batch.validators[1].votes.0.get(), 150);
     }
+
+    #[test]
+    fn test_committee_history() {
+        let state = |address: &str, votes| ValidatorState {
+            network_address: address.to_string(),
+            votes,
+            account_public_key: AccountPublicKey::test_key(0),
+        };
+        let committee = |validators: Vec<(u8, ValidatorState)>| {
+            let validators = validators
+                .into_iter()
+                .map(|(key, state)| (ValidatorPublicKey::test_key(key), state))
+                .collect();
+            Committee::new(validators, Default::default()).unwrap()
+        };
+        let committees = [
+            (
+                Epoch::ZERO,
+                Timestamp::from(10),
+                committee(vec![(0, state("a", 1)), (1, state("b", 1))]),
+            ),
+            (
+                Epoch(1),
+                Timestamp::from(20),
+                committee(vec![(0, state("a", 3)), (2, state("c", 1))]),
+            ),
+            (
+                Epoch(2),
+                Timestamp::from(30),
+                committee(vec![(0, state("d", 3)), (2, state("c", 1))]),
+            ),
+        ];
+        let history = committee_history(&committees, &BTreeSet::from([Epoch::ZERO]));
+
+        assert_eq!(history.len(), 3);
+        assert!(history[0].removed);
+        assert_eq!(history[0].changes.len(), 2);
+        assert_eq!(history[1].total_votes, 4);
+        assert_eq!(
+            history[1].changes,
+            vec![
+                ValidatorChange::VotesChanged {
+                    public_key: ValidatorPublicKey::test_key(0),
+                    old_votes: 1,
+                    new_votes: 3,
+                },
+                ValidatorChange::Added {
+                    public_key: ValidatorPublicKey::test_key(2),
+                    network_address: "c".to_string(),
+                    votes: 1,
+                },
+                ValidatorChange::Removed {
+                    public_key: ValidatorPublicKey::test_key(1),
+                    votes: 1,
+                },
+            ]
+        );
+        assert!(!history[2].removed);
+        assert_eq!(
+            history[2].changes,
+            vec![ValidatorChange::AddressChanged {
+                public_key: ValidatorPublicKey::test_key(0),
+                old_address: "a".to_string(),
+                new_address: "d".to_string(),
+            }]
+        );
+    }
 }
@@ -0,0 +1,172 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The balances and heights of the wallet's chains, for `linera wallet show --with-balances`.
+
+use std::collections::BTreeMap;
+
+use futures::{stream, StreamExt as _};
+use linera_base::{
+    data_types::{Amount, BlockHeight},
+    identifiers::ChainId,
+};
+use linera_client::{chain_listener::ClientContext as _, client_context::ClientContext};
+use linera_core::Environment;
+use serde::Serialize;
+
+/// The maximum number of chains that are queried at the same time.
+pub const MAX_CONCURRENT_QUERIES: usize = 32;
+
+/// The balance and height of a chain, as known by the validators.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainBalance {
+    /// The chain.
+    pub chain_id: ChainId,
+    /// The chain's own balance.
+    pub balance: Option<Amount>,
+    /// The height of the next block.
+    pub next_block_height: Option<BlockHeight>,
+    /// Why the chain could not be queried, if it failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ChainBalance {
+    async fn query<Env: Environment>(context: &ClientContext<Env>, chain_id: ChainId) -> Self {
+        let mut chain_balance = Self {
+            chain_id,
+            ..Self::default()
+        };
+        let chain_client = match context.make_chain_client(chain_id).await {
+            Ok(chain_client) => chain_client,
+            Err(error) => {
+                chain_balance.error = Some(error.to_string());
+                return chain_balance;
+            }
+        };
+        match chain_client.synchronize_from_validators().await {
+            Ok(info) => chain_balance.next_block_height = Some(info.next_block_height),
+            Err(error) => {
+                chain_balance.error = Some(error.to_string());
+                return chain_balance;
+            }
+        }
+        match chain_client.local_balance().await {
+            Ok(balance) => chain_balance.balance = Some(balance),
+            Err(error) => chain_balance.error = Some(error.to_string()),
+        }
+        chain_balance
+    }
+}
+
+/// The balances of the wallet's chains, and their totals.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletBalances {
+    /// The chains, the default one first.
+    pub chains: Vec<ChainBalance>,
+    /// The sum of the balances of the chains that could be queried.
+    pub total_balance: Amount,
+    /// The number of blocks of the chains that could be queried.
+    pub total_blocks: u64,
+}
+
+impl WalletBalances {
+    /// Queries the given chains from the validators, at most [`MAX_CONCURRENT_QUERIES`] at
+    /// a time. Chains that fail to be queried are reported with their error.
+    pub async fn query<Env: Environment>(
+        context: &ClientContext<Env>,
+        chain_ids: Vec<ChainId>,
+        default_chain: Option<ChainId>,
+    ) -> Self {
+        let mut chains = stream::iter(chain_ids)
+            .map(|chain_id| ChainBalance::query(context, chain_id))
+            .buffer_unordered(MAX_CONCURRENT_QUERIES)
+            .map(|chain| (chain.chain_id, chain))
+            .collect::<BTreeMap<_, _>>()
+            .await;
+        let default = default_chain.and_then(|chain_id| chains.remove(&chain_id));
+        Self::new(default.into_iter().chain(chains.into_values()).collect())
+    }
+
+    fn new(chains: Vec<ChainBalance>) -> Self {
+        let total_balance = chains
+            .iter()
+            .filter_map(|chain| chain.balance)
+            .fold(Amount::ZERO, Amount::saturating_add);
+        let total_blocks = chains
+            .iter()
+            .filter_map(|chain| chain.next_block_height)
+            .map(|height| height.0)
+            .sum();
+        Self {
+            chains,
+            total_balance,
+            total_blocks,
+        }
+    }
+
+    /// Prints the chains as a table, followed by their totals.
+    pub fn print_table(&self, default_chain: Option<ChainId>) {
+        fn show<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map_or_else(|| "-".into(), T::to_string)
+        }
+        println!("{:<74}  {:>24}  {:>10}", "Chain", "Balance", "Blocks");
+        for chain in &self.chains {
+            let name = if Some(chain.chain_id) == default_chain {
+                format!("{} (default)", chain.chain_id)
+            } else {
+                chain.chain_id.to_string()
+            };
+            print!(
+                "{name:<74}  {:>24}  {:>10}",
+                show(&chain.balance),
+                show(&chain.next_block_height)
+            );
+            match &chain.error {
+                Some(error) => println!("  error: {error}"),
+                None => println!(),
+            }
+        }
+        let plural_s = if self.chains.len() == 1 { "" } else { "s" };
+        println!(
+            "{:<74}  {:>24}  {:>10}",
+            format!("Total ({} chain{plural_s})", self.chains.len()),
+            self.total_balance,
+            self.total_blocks
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::{
+        crypto::CryptoHash,
+        data_types::{Amount, BlockHeight},
+        identifiers::ChainId,
+    };
+
+    use super::{ChainBalance, WalletBalances};
+
+    #[test]
+    fn test_wallet_balance_totals() {
+        let chain = |name, balance: Option<u128>, height: Option<u64>| ChainBalance {
+            chain_id: ChainId(CryptoHash::test_hash(name)),
+            balance: balance.map(Amount::from_tokens),
+            next_block_height: height.map(BlockHeight),
+            error: balance.is_none().then(|| "unreachable".to_string()),
+        };
+        let balances = WalletBalances::new(vec![
+            chain("first", Some(3), Some(5)),
+            chain("second", Some(4), Some(1)),
+            chain("failed", None, None),
+        ]);
+        assert_eq!(balances.total_balance, Amount::from_tokens(7));
+        assert_eq!(balances.total_blocks, 6);
+        let json = serde_json::to_value(&balances).unwrap();
+        assert_eq!(json["chains"][2]["error"], "unreachable");
+        assert!(json["chains"][0].get("error").is_none());
+        assert_eq!(json["totalBlocks"], 6);
+    }
+}
@@ -0,0 +1,193 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A wallet configured by environment variables, for containerized deployments.
+//!
+//! Each value can be given directly in `LINERA_<NAME>`, or in the file named by
+//! `LINERA_<NAME>_FILE`, e.g. a secret mounted read-only into the container:
+//!
+//! * `GENESIS_CONFIG`: the genesis configuration, as written by `linera wallet
+//!   export-genesis`;
+//! * `KEYS`: the contents of a keystore file. The keystore is then never written, so keys
+//!   must not be generated with it;
+//! * `CHAINS`: a JSON list of the chains of the wallet, e.g. `[{"chainId": "…", "owner":
+//!   "0x…"}]`, where chains without an owner are only followed;
+//! * `DEFAULT_CHAIN`: the default chain, if not the first one.
+//!
+//! As for `LINERA_WALLET`, the names end with the suffix given by `--with-wallet`, if any.
+
+use std::env;
+
+use anyhow::{bail, ensure, Context as _};
+use linera_base::identifiers::{AccountOwner, ChainId};
+use linera_client::config::GenesisConfig;
+use serde::Deserialize;
+
+/// The name of the genesis configuration.
+pub const GENESIS_CONFIG: &str = "GENESIS_CONFIG";
+/// The name of the keystore.
+pub const KEYS: &str = "KEYS";
+/// The name of the list of chains.
+pub const CHAINS: &str = "CHAINS";
+/// The name of the default chain.
+pub const DEFAULT_CHAIN: &str = "DEFAULT_CHAIN";
+
+/// A chain of a wallet configured by the environment.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ChainAssignment {
+    /// The chain.
+    pub chain_id: ChainId,
+    /// The owner whose key is in the keystore, or `None` to only follow the chain.
+    #[serde(default)]
+    pub owner: Option<AccountOwner>,
+}
+
+/// The wallet configuration given by the environment.
+#[derive(Debug)]
+pub struct WalletEnv {
+    /// The genesis configuration, if given.
+    pub genesis_config: Option<GenesisConfig>,
+    /// The chains to add to the wallet.
+    pub chains: Vec<ChainAssignment>,
+    /// The default chain, if any.
+    pub default_chain: Option<ChainId>,
+}
+
+impl WalletEnv {
+    /// Reads the configuration from the environment variables with the given suffix.
+    pub fn read(suffix: &str) -> anyhow::Result<Self> {
+        Self::read_with(suffix, |name| env::var(name).ok())
+    }
+
+    fn read_with(suffix: &str, lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let genesis_config = read_value_with(GENESIS_CONFIG, suffix, &lookup)?
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .context("invalid genesis configuration in the environment")?;
+        let chains: Vec<ChainAssignment> = read_value_with(CHAINS, suffix, &lookup)?
+            .map(|json| serde_json::from_str(&json))
+            .transpose()
+            .context("invalid list of chains in the environment")?
+            .unwrap_or_default();
+        let default_chain = match read_value_with(DEFAULT_CHAIN, suffix, &lookup)? {
+            Some(chain_id) => {
+                let chain_id = chain_id
+                    .trim()
+                    .parse()
+                    .context("invalid default chain in the environment")?;
+                ensure!(
+                    chains.iter().any(|chain| chain.chain_id == chain_id),
+                    "the default chain {chain_id} is not in the list of chains"
+                );
+                Some(chain_id)
+            }
+            None => chains.first().map(|chain| chain.chain_id),
+        };
+        Ok(Self {
+            genesis_config,
+            chains,
+            default_chain,
+        })
+    }
+}
+
+/// Returns the keystore given by the environment variables with the given suffix, if any.
+pub fn keystore(suffix: &str) -> anyhow::Result<Option<linera_wallet_json::Keystore>> {
+    read_value(KEYS, suffix)?
+        .map(|json| linera_wallet_json::Keystore::from_json(&json))
+        .transpose()
+        .context("invalid keystore in the environment")
+}
+
+/// Returns the value of `LINERA_{name}{suffix}`, or else the contents of the file named by
+/// `LINERA_{name}_FILE{suffix}`, if either is set.
+pub fn read_value(name: &str, suffix: &str) -> anyhow::Result<Option<String>> {
+    read_value_with(name, suffix, |name| env::var(name).ok())
+}
+
+fn read_value_with(
+    name: &str,
+    suffix: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> anyhow::Result<Option<String>> {
+    let variable = format!("LINERA_{name}{suffix}");
+    let file_variable = format!("LINERA_{name}_FILE{suffix}");
+    match (lookup(&variable), lookup(&file_variable)) {
+        (Some(_), Some(_)) => bail!("only one of {variable} and {file_variable} may be set"),
+        (Some(value), None) => Ok(Some(value)),
+        (None, Some(path)) => Ok(Some(
+            fs_err::read_to_string(path).with_context(|| format!("reading {file_variable}"))?,
+        )),
+        (None, None) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, io::Write as _};
+
+    use linera_base::{
+        crypto::CryptoHash,
+        identifiers::{AccountOwner, ChainId},
+    };
+
+    use super::{ChainAssignment, WalletEnv};
+
+    #[test]
+    fn test_wallet_env() -> anyhow::Result<()> {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let followed = ChainId(CryptoHash::test_hash("followed"));
+        let owner = AccountOwner::from(CryptoHash::test_hash("owner"));
+        let mut chains = tempfile::NamedTempFile::new()?;
+        write!(
+            chains,
+            "{}",
+            serde_json::json!([
+                { "chainId": chain_id, "owner": owner },
+                { "chainId": followed },
+            ])
+        )?;
+        let mut variables = BTreeMap::from([(
+            "LINERA_CHAINS_FILE_1".to_string(),
+            chains.path().display().to_string(),
+        )]);
+
+        let lookup = |variables: &BTreeMap<String, String>| {
+            let variables = variables.clone();
+            move |name: &str| variables.get(name).cloned()
+        };
+        let wallet_env = WalletEnv::read_with("_1", lookup(&variables))?;
+        assert!(wallet_env.genesis_config.is_none());
+        assert_eq!(
+            wallet_env.chains,
+            [
+                ChainAssignment {
+                    chain_id,
+                    owner: Some(owner),
+                },
+                ChainAssignment {
+                    chain_id: followed,
+                    owner: None,
+                },
+            ]
+        );
+        assert_eq!(wallet_env.default_chain, Some(chain_id));
+        // Variables without the suffix are ignored.
+        assert!(WalletEnv::read_with("", lookup(&variables))?
+            .chains
+            .is_empty());
+
+        variables.insert("LINERA_DEFAULT_CHAIN_1".to_string(), followed.to_string());
+        let wallet_env = WalletEnv::read_with("_1", lookup(&variables))?;
+        assert_eq!(wallet_env.default_chain, Some(followed));
+
+        let unknown = ChainId(CryptoHash::test_hash("unknown"));
+        variables.insert("LINERA_DEFAULT_CHAIN_1".to_string(), unknown.to_string());
+        assert!(WalletEnv::read_with("_1", lookup(&variables)).is_err());
+
+        variables.insert("LINERA_CHAINS_1".to_string(), "[]".to_string());
+        assert!(WalletEnv::read_with("_1", lookup(&variables)).is_err());
+        Ok(())
+    }
+}
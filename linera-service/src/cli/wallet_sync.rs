@@ -0,0 +1,191 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The synchronization of all the wallet's chains, for `linera sync --all`.
+
+use std::{collections::BTreeMap, num::NonZeroUsize};
+
+use futures::{stream, StreamExt as _};
+use linera_base::{
+    data_types::{BlockHeight, Timestamp},
+    identifiers::ChainId,
+    time::Instant,
+};
+use linera_client::{chain_listener::ClientContext as _, client_context::ClientContext};
+use linera_core::Environment;
+use serde::Serialize;
+
+/// The outcome of synchronizing one chain with the validators.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainSync {
+    /// The chain.
+    pub chain_id: ChainId,
+    /// The height of the next block before synchronizing.
+    pub previous_height: Option<BlockHeight>,
+    /// The height of the next block after synchronizing.
+    pub next_block_height: Option<BlockHeight>,
+    /// How long the synchronization took, in milliseconds.
+    pub elapsed_ms: u64,
+    /// Why the chain could not be synchronized, if it failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ChainSync {
+    async fn run<Env: Environment>(
+        context: &ClientContext<Env>,
+        chain_id: ChainId,
+        next_height: Option<BlockHeight>,
+        until_block_time: Option<Timestamp>,
+    ) -> Self {
+        let start = Instant::now();
+        let mut chain_sync = Self {
+            chain_id,
+            ..Self::default()
+        };
+        if let Err(error) = chain_sync
+            .synchronize(context, next_height, until_block_time)
+            .await
+        {
+            chain_sync.error = Some(error.to_string());
+        }
+        chain_sync.elapsed_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+        chain_sync
+    }
+
+    async fn synchronize<Env: Environment>(
+        &mut self,
+        context: &ClientContext<Env>,
+        next_height: Option<BlockHeight>,
+        until_block_time: Option<Timestamp>,
+    ) -> Result<(), linera_client::Error> {
+        let chain_client = context.make_chain_client(self.chain_id).await?;
+        self.previous_height = Some(chain_client.chain_info().await?.next_block_height);
+        let info = if next_height.is_some() || until_block_time.is_some() {
+            chain_client
+                .synchronize_up_to(next_height, until_block_time)
+                .await?
+        } else {
+            chain_client.synchronize_from_validators().await?
+        };
+        self.next_block_height = Some(info.next_block_height);
+        context.update_wallet_from_client(&chain_client).await
+    }
+
+    /// Returns the number of blocks downloaded, if the chain was synchronized.
+    pub fn new_blocks(&self) -> Option<u64> {
+        let previous_height = self.previous_height?;
+        let next_block_height = self.next_block_height?;
+        Some(next_block_height.0.saturating_sub(previous_height.0))
+    }
+}
+
+/// The outcome of synchronizing the wallet's chains.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WalletSync {
+    /// The chains, in order of their IDs.
+    pub chains: Vec<ChainSync>,
+    /// The number of blocks downloaded for all chains.
+    pub total_new_blocks: u64,
+    /// The number of chains that could not be synchronized.
+    pub failed: usize,
+}
+
+impl WalletSync {
+    /// Synchronizes the given chains with the validators, at most `parallelism` at a time,
+    /// and updates the wallet. Chains that fail to be synchronized are reported with their
+    /// error.
+    pub async fn run<Env: Environment>(
+        context: &ClientContext<Env>,
+        chain_ids: Vec<ChainId>,
+        parallelism: NonZeroUsize,
+        next_height: Option<BlockHeight>,
+        until_block_time: Option<Timestamp>,
+    ) -> Self {
+        let chains = stream::iter(chain_ids)
+            .map(|chain_id| ChainSync::run(context, chain_id, next_height, until_block_time))
+            .buffer_unordered(parallelism.get())
+            .map(|chain| (chain.chain_id, chain))
+            .collect::<BTreeMap<_, _>>()
+            .await;
+        Self::new(chains.into_values().collect())
+    }
+
+    fn new(chains: Vec<ChainSync>) -> Self {
+        let total_new_blocks = chains.iter().filter_map(ChainSync::new_blocks).sum();
+        let failed = chains.iter().filter(|chain| chain.error.is_some()).count();
+        Self {
+            chains,
+            total_new_blocks,
+            failed,
+        }
+    }
+
+    /// Prints the chains as a table, followed by their totals.
+    pub fn print_table(&self) {
+        fn show<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map_or_else(|| "-".into(), T::to_string)
+        }
+        println!(
+            "{:<64}  {:>10}  {:>10}  {:>10}",
+            "Chain", "Blocks", "New", "Time (ms)"
+        );
+        for chain in &self.chains {
+            print!(
+                "{:<64}  {:>10}  {:>10}  {:>10}",
+                chain.chain_id,
+                show(&chain.next_block_height),
+                show(&chain.new_blocks()),
+                chain.elapsed_ms
+            );
+            match &chain.error {
+                Some(error) => println!("  error: {error}"),
+                None => println!(),
+            }
+        }
+        let plural_s = if self.chains.len() == 1 { "" } else { "s" };
+        println!(
+            "{:<64}  {:>10}  {:>10}",
+            format!(
+                "Total ({} chain{plural_s}, {} failed)",
+                self.chains.len(),
+                self.failed
+            ),
+            "",
+            self.total_new_blocks
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::{crypto::CryptoHash, data_types::BlockHeight, identifiers::ChainId};
+
+    use super::{ChainSync, WalletSync};
+
+    #[test]
+    fn test_wallet_sync_totals() {
+        let chain = |name, heights: Option<(u64, u64)>| ChainSync {
+            chain_id: ChainId(CryptoHash::test_hash(name)),
+            previous_height: heights.map(|(previous, _)| BlockHeight(previous)),
+            next_block_height: heights.map(|(_, next)| BlockHeight(next)),
+            elapsed_ms: 10,
+            error: heights.is_none().then(|| "unreachable".to_string()),
+        };
+        let summary = WalletSync::new(vec![
+            chain("first", Some((2, 5))),
+            chain("second", Some((1, 1))),
+            chain("failed", None),
+        ]);
+        assert_eq!(summary.chains[0].new_blocks(), Some(3));
+        assert_eq!(summary.chains[2].new_blocks(), None);
+        assert_eq!(summary.total_new_blocks, 3);
+        assert_eq!(summary.failed, 1);
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["chains"][2]["error"], "unreachable");
+        assert!(json["chains"][0].get("error").is_none());
+        assert_eq!(json["totalNewBlocks"], 3);
+    }
+}
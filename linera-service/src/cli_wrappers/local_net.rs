@@ -32,7 +32,7 @@ use linera_views::rocks_db::{RocksDbDatabase, RocksDbSpawnMode};
 use linera_views::{scylla_db::ScyllaDbDatabase, store::TestKeyValueDatabase as _};
 use tempfile::{tempdir, TempDir};
 use tokio::process::{Child, Command};
-use tonic::transport::{channel::ClientTlsConfig, Endpoint};
+use tonic::transport::{channel::ClientTlsConfig, Channel, Endpoint};
 use tonic_health::pb::{
     health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest,
 };
@@ -44,6 +44,7 @@ use crate::{
     },
     storage::{InnerStorageConfig, StorageConfig},
     util::ChildExt,
+    virtual_time::{VirtualClockFile, VIRTUAL_TIME_ENV},
 };
 
 /// Maximum allowed number of shards over all validators.
@@ -227,6 +228,9 @@ pub struct LocalNetConfig {
     /// Optional directory where the `linera`, `linera-proxy`, and `linera-server` binaries
     /// are located. If `None`, binaries are resolved from the current binary's directory.
     pub binary_dir: Option<PathBuf>,
+    /// Whether the validators and clients follow a virtual clock, whose file is in the
+    /// working directory, instead of the system clock.
+    pub virtual_time: bool,
 }
 
 /// The setup for the block exporters.
@@ -272,8 +276,12 @@ pub struct LocalNet {
     path_provider: PathProvider,
     block_exporters: ExportersSetup,
     binary_dir: Option<PathBuf>,
+    virtual_clock: Option<VirtualClockFile>,
 }
 
+/// The name of the file of the virtual clock in the working directory.
+pub const VIRTUAL_TIME_FILE: &str = "virtual_time";
+
 /// The name of the environment variable that allows specifying additional arguments to be passed
 /// to the binary when starting a server.
 const SERVER_ENV: &str = "LINERA_SERVER_PARAMS";
@@ -383,6 +391,7 @@ impl LocalNetConfig {
             block_exporters: ExportersSetup::Local(vec![]),
             http_request_allow_list: Some(vec!["localhost".to_string()]),
             binary_dir: None,
+            virtual_time: false,
         }
     }
 }
@@ -393,6 +402,7 @@ impl LineraNetConfig for LocalNetConfig {
 
     async fn instantiate(self) -> Result<(Self::Net, ClientWrapper)> {
         let storage_config = self.storage_config_builder.build(self.database).await?;
+        let virtual_clock = self.virtual_clock()?;
         let mut net = LocalNet::new(
             self.network,
             self.testing_prng_seed,
@@ -405,6 +415,7 @@ impl LineraNetConfig for LocalNetConfig {
             self.path_provider,
             self.block_exporters,
             self.binary_dir,
+            virtual_clock,
         );
         let client = net.make_client().await;
         ensure!(
@@ -434,6 +445,52 @@ impl LineraNetConfig for LocalNetConfig {
     }
 }
 
+impl LocalNetConfig {
+    /// Returns the virtual clock of the network, if enabled, creating it if it doesn't
+    /// exist yet, e.g. in a restored snapshot.
+    fn virtual_clock(&self) -> Result<Option<VirtualClockFile>> {
+        if !self.virtual_time {
+            return Ok(None);
+        }
+        let path = self.path_provider.path().join(VIRTUAL_TIME_FILE);
+        if path.exists() {
+            Ok(Some(VirtualClockFile::new(path)))
+        } else {
+            Ok(Some(VirtualClockFile::create(path)?))
+        }
+    }
+
+    /// Starts the network from the configuration files already present in the working
+    /// directory and from validator storages that were populated beforehand, e.g. when
+    /// restoring a snapshot. No keys or genesis configuration are generated.
+    pub async fn resume(self) -> Result<(LocalNet, ClientWrapper)> {
+        let storage_config = self.storage_config_builder.build(self.database).await?;
+        let virtual_clock = self.virtual_clock()?;
+        let mut net = LocalNet::new(
+            self.network,
+            self.testing_prng_seed,
+            self.namespace,
+            self.num_initial_validators,
+            self.num_proxies,
+            self.num_shards,
+            storage_config,
+            self.cross_chain_config,
+            self.path_provider,
+            self.block_exporters,
+            self.binary_dir,
+            virtual_clock,
+        );
+        let client = net.make_client().await;
+        for validator in 0..self.num_initial_validators {
+            let storage = net.validator_storage(validator);
+            net.initialized_validator_storages
+                .insert(validator, storage);
+            net.restart_validator(validator).await?;
+        }
+        Ok((net, client))
+    }
+}
+
 #[async_trait]
 impl LineraNet for LocalNet {
     async fn ensure_is_running(&mut self) -> Result<()> {
@@ -452,6 +509,11 @@ impl LineraNet for LocalNet {
             OnClientDrop::LeakChains,
             vec!["--wait-for-outgoing-messages".to_string()],
             self.binary_dir.clone(),
+        )
+        .with_virtual_time(
+            self.virtual_clock
+                .as_ref()
+                .map(|clock| clock.path().to_path_buf()),
         );
         if let Some(seed) = self.testing_prng_seed {
             self.testing_prng_seed = Some(seed + 1);
@@ -482,6 +544,7 @@ impl LocalNet {
         path_provider: PathProvider,
         block_exporters: ExportersSetup,
         binary_dir: Option<PathBuf>,
+        virtual_clock: Option<VirtualClockFile>,
     ) -> Self {
         Self {
             network,
@@ -499,9 +562,15 @@ impl LocalNet {
             path_provider,
             block_exporters,
             binary_dir,
+            virtual_clock,
         }
     }
 
+    /// Returns the virtual clock that the processes of the network follow, if any.
+    pub fn virtual_clock(&self) -> Option<&VirtualClockFile> {
+        self.virtual_clock.as_ref()
+    }
+
     async fn command_for_binary(&self, name: &'static str) -> Result<Command> {
         let path = if let Some(dir) = &self.binary_dir {
             dir.join(name)
@@ -510,6 +579,9 @@ impl LocalNet {
         };
         let mut command = Command::new(path);
         command.current_dir(self.path_provider.path());
+        if let Some(clock) = &self.virtual_clock {
+            command.env(VIRTUAL_TIME_ENV, clock.path());
+        }
         Ok(command)
     }
 
@@ -861,33 +933,52 @@ impl LocalNet {
         port: usize,
         scheme: &str,
     ) -> Result<()> {
+        let client = Self::grpc_health_client("localhost", port, scheme)?;
+        linera_base::time::timer::sleep(Duration::from_millis(100)).await;
+        for i in 0..10 {
+            linera_base::time::timer::sleep(Duration::from_millis(i * 500)).await;
+            if Self::is_grpc_server_serving(&client).await.is_ok() {
+                info!(?port, "Successfully started {nickname}");
+                return Ok(());
+            } else {
+                warn!("Waiting for {nickname} to start");
+            }
+        }
+        bail!("Failed to start {nickname}");
+    }
+
+    /// Returns a client of the gRPC health service of the server at the given host and port.
+    pub fn grpc_health_client(
+        host: &str,
+        port: usize,
+        scheme: &str,
+    ) -> Result<HealthClient<Channel>> {
         let endpoint = match scheme {
-            "http" => Endpoint::new(format!("http://localhost:{port}"))
+            "http" => Endpoint::new(format!("http://{host}:{port}"))
                 .context("endpoint should always parse")?,
             "https" => {
                 use linera_rpc::CERT_PEM;
                 let certificate = tonic::transport::Certificate::from_pem(CERT_PEM);
                 let tls_config = ClientTlsConfig::new().ca_certificate(certificate);
-                Endpoint::new(format!("https://localhost:{port}"))
+                Endpoint::new(format!("https://{host}:{port}"))
                     .context("endpoint should always parse")?
                     .tls_config(tls_config)?
             }
             _ => bail!("Only supported scheme are http and https"),
         };
-        let connection = endpoint.connect_lazy();
-        let mut client = HealthClient::new(connection);
-        linera_base::time::timer::sleep(Duration::from_millis(100)).await;
-        for i in 0..10 {
-            linera_base::time::timer::sleep(Duration::from_millis(i * 500)).await;
-            let result = client.check(HealthCheckRequest::default()).await;
-            if result.is_ok() && result.unwrap().get_ref().status() == ServingStatus::Serving {
-                info!(?port, "Successfully started {nickname}");
-                return Ok(());
-            } else {
-                warn!("Waiting for {nickname} to start");
-            }
-        }
-        bail!("Failed to start {nickname}");
+        Ok(HealthClient::new(endpoint.connect_lazy()))
+    }
+
+    /// Checks once that the server reports itself as serving.
+    pub async fn is_grpc_server_serving(client: &HealthClient<Channel>) -> Result<()> {
+        let response = client.clone().check(HealthCheckRequest::default()).await?;
+        let status = response.get_ref().status();
+        ensure!(
+            status == ServingStatus::Serving,
+            "the server is {}",
+            status.as_str_name()
+        );
+        Ok(())
     }
 
     async fn ensure_simple_server_has_started(
@@ -923,13 +1014,20 @@ impl LocalNet {
         bail!("Failed to start {nickname}");
     }
 
+    /// Returns the namespace used by the storage of the given validator.
+    pub fn validator_namespace(common_namespace: &str, validator: usize) -> String {
+        format!("{common_namespace}_server_{validator}_db")
+    }
+
+    fn validator_storage(&self, validator: usize) -> StorageConfig {
+        StorageConfig {
+            inner_storage_config: self.common_storage_config.clone(),
+            namespace: Self::validator_namespace(&self.common_namespace, validator),
+        }
+    }
+
     async fn initialize_storage(&mut self, validator: usize) -> Result<()> {
-        let namespace = format!("{}_server_{}_db", self.common_namespace, validator);
-        let inner_storage_config = self.common_storage_config.clone();
-        let storage = StorageConfig {
-            inner_storage_config,
-            namespace,
-        };
+        let storage = self.validator_storage(validator);
         let mut command = self.command_for_binary("linera").await?;
         if let Ok(var) = env::var(SERVER_ENV) {
             command.args(var.split_whitespace());
@@ -9,6 +9,8 @@ pub mod local_net;
 #[cfg(all(with_testing, feature = "remote-net"))]
 /// How to connect to running GCP devnet.
 pub mod remote_net;
+/// How to deploy Linera validators on remote hosts over SSH.
+pub mod ssh_net;
 /// How to run a Linera wallet and its GraphQL service.
 mod wallet;
 
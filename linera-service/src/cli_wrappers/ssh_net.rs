@@ -0,0 +1,635 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Deployment of a Linera network on remote hosts over SSH.
+//!
+//! The hosts are described by an inventory file. The configuration of the validators and
+//! the genesis configuration are generated locally, then the binaries and the
+//! configuration files are copied to the hosts with `scp`, and each shard and proxy is
+//! started in the background with `ssh`. The logs of the services are copied back into
+//! the working directory when the network is terminated.
+
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use async_trait::async_trait;
+use linera_base::{
+    command::{resolve_binary, CommandExt},
+    data_types::Amount,
+};
+use linera_client::client_options::ResourceControlPolicyConfig;
+use linera_rpc::config::CrossChainConfig;
+use serde::Deserialize;
+use tokio::process::Command;
+use tracing::{info, warn};
+
+use crate::cli_wrappers::{
+    local_net::{LocalNet, PathProvider},
+    ClientWrapper, LineraNet, LineraNetConfig, Network, NetworkConfig, OnClientDrop,
+};
+
+/// The binaries copied to every host.
+const BINARIES: [&str; 3] = ["linera", "linera-server", "linera-proxy"];
+/// The directory of the working directory where the logs of the services are collected.
+const LOGS_DIR: &str = "logs";
+
+/// The remote hosts of a network, as read from an inventory file in TOML.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SshInventory {
+    /// The directory on the hosts where the binaries, the configuration files and the logs
+    /// are kept. Relative paths are relative to the home directory of the SSH user.
+    #[serde(default = "default_remote_dir")]
+    pub remote_dir: String,
+    /// The SSH user, unless a validator sets its own.
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Extra options passed to `ssh` and `scp`, e.g. `["-i", "/path/to/key"]`.
+    #[serde(default)]
+    pub ssh_options: Vec<String>,
+    /// The local directory containing the binaries to copy to the hosts, e.g. binaries
+    /// built for their platform. If none, the binaries next to this one are copied.
+    #[serde(default)]
+    pub binary_dir: Option<PathBuf>,
+    /// The validators of the network.
+    pub validators: Vec<SshValidator>,
+}
+
+/// A validator of an [`SshInventory`], with its proxy and its shards.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SshValidator {
+    /// The host running the proxy, which is also the public address of the validator.
+    pub host: String,
+    /// The SSH user for the hosts of this validator.
+    #[serde(default)]
+    pub user: Option<String>,
+    /// The public port of the proxy.
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// The port on which the proxy receives messages from the shards.
+    #[serde(default = "default_proxy_private_port")]
+    pub proxy_private_port: u16,
+    /// The port on which the proxy serves metrics.
+    #[serde(default = "default_proxy_metrics_port")]
+    pub proxy_metrics_port: u16,
+    /// The storage shared by the proxy and the shards of the validator, as seen from its
+    /// hosts, e.g. `scylladb:tcp:10.0.0.5:9042:linera`.
+    pub storage: String,
+    /// The shards of the validator.
+    pub shards: Vec<SshShard>,
+}
+
+/// A shard of an [`SshValidator`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SshShard {
+    /// The host running the shard. If none, the shard runs on the host of the proxy.
+    #[serde(default)]
+    pub host: Option<String>,
+    /// The port on which the shard receives messages.
+    pub port: u16,
+    /// The port on which the shard serves metrics.
+    pub metrics_port: u16,
+}
+
+fn default_remote_dir() -> String {
+    "linera".to_string()
+}
+
+fn default_port() -> u16 {
+    19100
+}
+
+fn default_proxy_private_port() -> u16 {
+    20100
+}
+
+fn default_proxy_metrics_port() -> u16 {
+    21100
+}
+
+impl SshInventory {
+    /// Reads and checks the inventory file at `path`.
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = fs_err::read_to_string(path)?;
+        let inventory = toml::from_str::<Self>(&contents)
+            .with_context(|| format!("invalid inventory file {}", path.display()))?;
+        inventory.check()?;
+        Ok(inventory)
+    }
+
+    fn check(&self) -> Result<()> {
+        ensure!(
+            !self.validators.is_empty(),
+            "the inventory should list at least one validator"
+        );
+        for (index, validator) in self.validators.iter().enumerate() {
+            ensure!(
+                !validator.shards.is_empty(),
+                "validator {index} ({}) should have at least one shard",
+                validator.host
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the SSH destination of `host`, for the given validator.
+    fn destination(&self, validator: &SshValidator, host: &str) -> String {
+        match validator.user.as_ref().or(self.user.as_ref()) {
+            Some(user) => format!("{user}@{host}"),
+            None => host.to_string(),
+        }
+    }
+}
+
+impl SshValidator {
+    /// Returns the host of the given shard.
+    fn shard_host(&self, shard: usize) -> &str {
+        self.shards[shard].host.as_deref().unwrap_or(&self.host)
+    }
+}
+
+/// The information needed to start an [`SshNet`].
+pub struct SshNetConfig {
+    /// The network protocols used for the validators' internal and external endpoints.
+    pub network: NetworkConfig,
+    /// The remote hosts to deploy the network on.
+    pub inventory: SshInventory,
+    /// A seed for the PRNG used to generate keys, for testing.
+    pub testing_prng_seed: Option<u64>,
+    /// The number of initial chains besides the admin chain.
+    pub num_other_initial_chains: u32,
+    /// The initial balance of each initial chain.
+    pub initial_amount: Amount,
+    /// The resource control policy of the network.
+    pub policy_config: ResourceControlPolicyConfig,
+    /// The hosts that applications may send HTTP requests to.
+    pub http_request_allow_list: Option<Vec<String>>,
+    /// The configuration of cross-chain requests between shards.
+    pub cross_chain_config: CrossChainConfig,
+    /// The local working directory, where the configuration files and the logs are kept.
+    pub path_provider: PathProvider,
+}
+
+/// A Linera network running on remote hosts, started over SSH.
+pub struct SshNet {
+    network: NetworkConfig,
+    inventory: SshInventory,
+    testing_prng_seed: Option<u64>,
+    next_client_id: usize,
+    cross_chain_config: CrossChainConfig,
+    path_provider: PathProvider,
+    services: Vec<RemoteService>,
+}
+
+/// A shard or a proxy running in the background on a remote host.
+struct RemoteService {
+    /// The SSH destination of the host.
+    destination: String,
+    /// The name of the service, which is also the name of its log file.
+    name: String,
+    /// The process ID of the service on the host.
+    pid: u32,
+}
+
+#[async_trait]
+impl LineraNetConfig for SshNetConfig {
+    type Net = SshNet;
+
+    async fn instantiate(self) -> Result<(Self::Net, ClientWrapper)> {
+        ensure!(
+            matches!(self.network.external, Network::Grpc | Network::Grpcs)
+                && matches!(self.network.internal, Network::Grpc),
+            "SSH deployments only support gRPC"
+        );
+        let mut net = SshNet {
+            network: self.network,
+            inventory: self.inventory,
+            testing_prng_seed: self.testing_prng_seed,
+            next_client_id: 0,
+            cross_chain_config: self.cross_chain_config,
+            path_provider: self.path_provider,
+            services: Vec::new(),
+        };
+        let client = net.make_client().await;
+        net.generate_initial_validator_config().await?;
+        client
+            .create_genesis_config(
+                self.num_other_initial_chains,
+                self.initial_amount,
+                self.policy_config,
+                self.http_request_allow_list,
+            )
+            .await?;
+        net.run().await?;
+        Ok((net, client))
+    }
+}
+
+#[async_trait]
+impl LineraNet for SshNet {
+    async fn ensure_is_running(&mut self) -> Result<()> {
+        for service in &self.services {
+            let script = format!("kill -0 {}", service.pid);
+            self.run_remote(&service.destination, &script)
+                .await
+                .with_context(|| {
+                    format!("{} on {} is not running", service.name, service.destination)
+                })?;
+        }
+        Ok(())
+    }
+
+    async fn make_client(&mut self) -> ClientWrapper {
+        let client = ClientWrapper::new_with_extra_args(
+            self.path_provider.clone(),
+            self.network.external,
+            self.testing_prng_seed,
+            self.next_client_id,
+            OnClientDrop::LeakChains,
+            vec!["--wait-for-outgoing-messages".to_string()],
+            None,
+        );
+        if let Some(seed) = self.testing_prng_seed {
+            self.testing_prng_seed = Some(seed + 1);
+        }
+        self.next_client_id += 1;
+        client
+    }
+
+    async fn terminate(&mut self) -> Result<()> {
+        let logs_dir = self.path_provider.path().join(LOGS_DIR);
+        fs_err::create_dir_all(&logs_dir)?;
+        // Stop the proxies first, in the reverse order of their start.
+        for service in self.services.iter().rev() {
+            let script = format!("kill {}", service.pid);
+            if let Err(error) = self.run_remote(&service.destination, &script).await {
+                warn!(
+                    "Failed to stop {} on {}: {error}",
+                    service.name, service.destination
+                );
+            }
+            let remote_log = format!(
+                "{}:{}/{}.log",
+                service.destination, self.inventory.remote_dir, service.name
+            );
+            if let Err(error) = self
+                .scp_command()
+                .arg(remote_log)
+                .arg(&logs_dir)
+                .spawn_and_wait()
+                .await
+            {
+                warn!("Failed to collect the logs of {}: {error}", service.name);
+            }
+        }
+        self.services.clear();
+        info!("The logs of the services are in {}", logs_dir.display());
+        Ok(())
+    }
+}
+
+impl SshNet {
+    /// Returns the public address of a validator.
+    pub fn validator_address(&self, validator: usize) -> String {
+        let validator = &self.inventory.validators[validator];
+        let schema = self.network.external.schema();
+        format!("{schema}:{}:{}", validator.host, validator.port)
+    }
+
+    /// Returns the number of validators of the network.
+    pub fn num_validators(&self) -> usize {
+        self.inventory.validators.len()
+    }
+
+    fn ssh_command(&self, destination: &str) -> Command {
+        let mut command = Command::new("ssh");
+        command
+            .args(["-o", "BatchMode=yes"])
+            .args(&self.inventory.ssh_options)
+            .arg(destination);
+        command
+    }
+
+    fn scp_command(&self) -> Command {
+        let mut command = Command::new("scp");
+        command
+            .args(["-q", "-o", "BatchMode=yes"])
+            .args(&self.inventory.ssh_options);
+        command
+    }
+
+    /// Runs a shell script on a host and returns its output.
+    async fn run_remote(&self, destination: &str, script: &str) -> Result<String> {
+        self.ssh_command(destination)
+            .arg(script)
+            .spawn_and_wait_for_stdout()
+            .await
+    }
+
+    /// Writes the configuration of each validator and generates their keys locally.
+    async fn generate_initial_validator_config(&mut self) -> Result<()> {
+        let mut command = Command::new(self.local_binary("linera-server").await?);
+        command
+            .current_dir(self.path_provider.path())
+            .arg("generate");
+        if let Some(seed) = self.testing_prng_seed {
+            command.arg("--testing-prng-seed").arg(seed.to_string());
+            self.testing_prng_seed = Some(seed + 1);
+        }
+        command.arg("--validators");
+        for validator in 0..self.inventory.validators.len() {
+            let path = self
+                .path_provider
+                .path()
+                .join(format!("validator_{validator}.toml"));
+            fs_err::write(&path, self.configuration_string(validator))?;
+            command.arg(path);
+        }
+        command
+            .args(["--committee", "committee.json"])
+            .spawn_and_wait_for_stdout()
+            .await?;
+        Ok(())
+    }
+
+    fn configuration_string(&self, validator: usize) -> String {
+        let n = validator;
+        let validator = &self.inventory.validators[n];
+        let external_protocol = self.network.external.toml();
+        let internal_protocol = self.network.internal.toml();
+        let SshValidator {
+            host,
+            port,
+            proxy_private_port,
+            proxy_metrics_port,
+            ..
+        } = validator;
+        let mut content = format!(
+            r#"
+                server_config_path = "server_{n}.json"
+                host = "{host}"
+                port = {port}
+                external_protocol = {external_protocol}
+                internal_protocol = {internal_protocol}
+
+                [[proxies]]
+                host = "{host}"
+                public_port = {port}
+                private_port = {proxy_private_port}
+                metrics_port = {proxy_metrics_port}
+            "#
+        );
+        for (k, shard) in validator.shards.iter().enumerate() {
+            let shard_host = validator.shard_host(k);
+            let SshShard {
+                port, metrics_port, ..
+            } = shard;
+            content.push_str(&format!(
+                r#"
+
+                [[shards]]
+                host = "{shard_host}"
+                port = {port}
+                metrics_port = {metrics_port}
+                "#
+            ));
+        }
+        content
+    }
+
+    /// Returns the local path of the binary to copy to the hosts.
+    async fn local_binary(&self, name: &'static str) -> Result<PathBuf> {
+        match &self.inventory.binary_dir {
+            Some(dir) => Ok(dir.join(name)),
+            None => resolve_binary(name, env!("CARGO_PKG_NAME")).await,
+        }
+    }
+
+    async fn run(&mut self) -> Result<()> {
+        let mut binaries = Vec::new();
+        for name in BINARIES {
+            binaries.push(self.local_binary(name).await?);
+        }
+        for validator in 0..self.inventory.validators.len() {
+            self.provision_validator(validator, &binaries).await?;
+            self.start_validator(validator).await?;
+        }
+        Ok(())
+    }
+
+    /// Copies the binaries and the configuration files of a validator to its hosts.
+    async fn provision_validator(&self, validator: usize, binaries: &[PathBuf]) -> Result<()> {
+        let inventory = &self.inventory;
+        let config = &inventory.validators[validator];
+        let hosts = std::iter::once(config.host.as_str())
+            .chain((0..config.shards.len()).map(|shard| config.shard_host(shard)))
+            .collect::<BTreeSet<_>>();
+        let path = self.path_provider.path();
+        for host in hosts {
+            let destination = inventory.destination(config, host);
+            info!(
+                "Copying the binaries and configuration of validator {validator} to {destination}"
+            );
+            let script = format!("mkdir -p {}", shell_quote(&inventory.remote_dir));
+            self.run_remote(&destination, &script).await?;
+            self.scp_command()
+                .args(binaries)
+                .arg(path.join(format!("server_{validator}.json")))
+                .arg(path.join("genesis.json"))
+                .arg(format!("{destination}:{}/", inventory.remote_dir))
+                .spawn_and_wait()
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn start_validator(&mut self, validator: usize) -> Result<()> {
+        let config = self.inventory.validators[validator].clone();
+        let proxy_destination = self.inventory.destination(&config, &config.host);
+        let storage = &config.storage;
+        let args = [
+            "storage",
+            "initialize",
+            "--storage",
+            storage.as_str(),
+            "--genesis",
+            "genesis.json",
+        ];
+        let script = format!(
+            "cd {} && ./linera {}",
+            shell_quote(&self.inventory.remote_dir),
+            shell_words(args)
+        );
+        self.run_remote(&proxy_destination, &script).await?;
+
+        for (shard, shard_config) in config.shards.iter().enumerate() {
+            let host = config.shard_host(shard);
+            let mut args = vec![
+                "./linera-server".to_string(),
+                "run".to_string(),
+                "--storage".to_string(),
+                storage.clone(),
+                "--server".to_string(),
+                format!("server_{validator}.json"),
+                "--shard".to_string(),
+                shard.to_string(),
+            ];
+            args.extend(self.cross_chain_config.to_args());
+            let destination = self.inventory.destination(&config, host);
+            self.start_service(destination, format!("server_{validator}_{shard}"), args)
+                .await?;
+            let nickname = format!("validator server {validator}:{shard}");
+            Self::wait_until_serving(&nickname, host, shard_config.port, "http").await?;
+        }
+
+        let args = vec![
+            "./linera-proxy".to_string(),
+            format!("server_{validator}.json"),
+            "--storage".to_string(),
+            storage.clone(),
+            "--id".to_string(),
+            "0".to_string(),
+        ];
+        self.start_service(proxy_destination, format!("proxy_{validator}"), args)
+            .await?;
+        let scheme = match self.network.external {
+            Network::Grpcs => "https",
+            Network::Grpc | Network::Tcp | Network::Udp => "http",
+        };
+        let nickname = format!("validator proxy {validator}");
+        Self::wait_until_serving(&nickname, &config.host, config.port, scheme).await
+    }
+
+    /// Starts a command in the background on a host, with its output in `<name>.log`.
+    async fn start_service(
+        &mut self,
+        destination: String,
+        name: String,
+        args: Vec<String>,
+    ) -> Result<()> {
+        let script = format!(
+            "cd {} && nohup {} > {name}.log 2>&1 < /dev/null & echo $!",
+            shell_quote(&self.inventory.remote_dir),
+            shell_words(&args),
+        );
+        let output = self.run_remote(&destination, &script).await?;
+        let pid = output
+            .trim()
+            .parse::<u32>()
+            .with_context(|| format!("failed to start {name} on {destination}: {output:?}"))?;
+        info!("Started {name} on {destination} with PID {pid}");
+        self.services.push(RemoteService {
+            destination,
+            name,
+            pid,
+        });
+        Ok(())
+    }
+
+    /// Waits until the gRPC server at the given host and port responds as healthy.
+    async fn wait_until_serving(nickname: &str, host: &str, port: u16, scheme: &str) -> Result<()> {
+        let client = LocalNet::grpc_health_client(host, port.into(), scheme)?;
+        for i in 0..15 {
+            linera_base::time::timer::sleep(Duration::from_millis(i * 500)).await;
+            if LocalNet::is_grpc_server_serving(&client).await.is_ok() {
+                info!("Successfully started {nickname} on {host}:{port}");
+                return Ok(());
+            }
+            warn!("Waiting for {nickname} to start on {host}:{port}");
+        }
+        bail!("Failed to start {nickname} on {host}:{port}");
+    }
+}
+
+/// Quotes a word for a POSIX shell.
+fn shell_quote(word: &str) -> String {
+    if !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,@".contains(c))
+    {
+        return word.to_string();
+    }
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+/// Quotes and joins words into a command line for a POSIX shell.
+fn shell_words<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> String {
+    words
+        .into_iter()
+        .map(|word| shell_quote(word.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{shell_words, SshInventory};
+
+    #[test]
+    fn test_inventory() {
+        let inventory = toml::from_str::<SshInventory>(
+            r#"
+            user = "admin"
+            ssh_options = ["-i", "testnet.pem"]
+
+            [[validators]]
+            host = "10.0.0.1"
+            storage = "scylladb:tcp:10.0.0.5:9042:linera"
+            shards = [{ port = 19101, metrics_port = 21101 }]
+
+            [[validators]]
+            host = "10.0.1.1"
+            user = "ops"
+            port = 443
+            storage = "scylladb:tcp:10.0.0.5:9042:linera"
+            shards = [
+                { host = "10.0.1.2", port = 19101, metrics_port = 21101 },
+                { host = "10.0.1.3", port = 19101, metrics_port = 21101 },
+            ]
+            "#,
+        )
+        .unwrap();
+        inventory.check().unwrap();
+        assert_eq!(inventory.remote_dir, "linera");
+        let [first, second] = &inventory.validators[..] else {
+            panic!("expected two validators");
+        };
+        assert_eq!(first.port, 19100);
+        assert_eq!(first.shard_host(0), "10.0.0.1");
+        assert_eq!(inventory.destination(first, "10.0.0.1"), "admin@10.0.0.1");
+        assert_eq!(second.shard_host(1), "10.0.1.3");
+        assert_eq!(
+            inventory.destination(second, second.shard_host(0)),
+            "ops@10.0.1.2"
+        );
+    }
+
+    #[test]
+    fn test_inventory_without_shards() {
+        let inventory = toml::from_str::<SshInventory>(
+            r#"
+            [[validators]]
+            host = "10.0.0.1"
+            storage = "scylladb:tcp:10.0.0.5:9042:linera"
+            shards = []
+            "#,
+        )
+        .unwrap();
+        assert!(inventory.check().is_err());
+    }
+
+    #[test]
+    fn test_shell_words() {
+        assert_eq!(
+            shell_words(["./linera", "--storage", "rocksdb:/tmp/db", "it's", ""]),
+            r#"./linera --storage rocksdb:/tmp/db 'it'\''s' ''"#
+        );
+    }
+}
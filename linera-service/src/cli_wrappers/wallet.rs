@@ -57,6 +57,7 @@ use crate::{
         Network,
     },
     util::{self, ChildExt},
+    virtual_time::VIRTUAL_TIME_ENV,
     Wallet,
 };
 
@@ -84,6 +85,7 @@ pub struct ClientWrapper {
     pub path_provider: PathProvider,
     on_drop: OnClientDrop,
     extra_args: Vec<String>,
+    virtual_time: Option<PathBuf>,
 }
 
 /// Action to perform when the [`ClientWrapper`] is dropped.
@@ -144,9 +146,16 @@ impl ClientWrapper {
             path_provider,
             on_drop,
             extra_args,
+            virtual_time: None,
         }
     }
 
+    /// Makes the client follow the virtual time of the clock file at `path`, if any.
+    pub fn with_virtual_time(mut self, path: Option<PathBuf>) -> Self {
+        self.virtual_time = path;
+        self
+    }
+
     /// Runs `linera project new`.
     pub async fn project_new(&self, project_name: &str, linera_root: &Path) -> Result<TempDir> {
         let tmp = TempDir::new()?;
@@ -163,6 +172,21 @@ impl ClientWrapper {
         Ok(tmp)
     }
 
+    /// Runs `linera project new --vm evm`.
+    pub async fn project_new_evm(&self, project_name: &str) -> Result<TempDir> {
+        let tmp = TempDir::new()?;
+        let mut command = self.command().await?;
+        command
+            .current_dir(tmp.path())
+            .arg("project")
+            .arg("new")
+            .arg(project_name)
+            .args(["--vm", "evm"])
+            .spawn_and_wait_for_stdout()
+            .await?;
+        Ok(tmp)
+    }
+
     /// Runs `linera project publish`.
     pub async fn project_publish<T: Serialize>(
         &self,
@@ -212,6 +236,9 @@ impl ClientWrapper {
         for (key, value) in envs {
             command.env(key, value);
         }
+        if let Some(path) = &self.virtual_time {
+            command.env(VIRTUAL_TIME_ENV, path);
+        }
         for argument in arguments {
             command.arg(&*argument);
         }
@@ -1356,6 +1383,10 @@ impl ClientWrapper {
             maximum_oracle_response_bytes,
             maximum_http_response_bytes,
             http_request_timeout_ms,
+            maximum_http_requests_per_application_per_block,
+            maximum_http_response_bytes_per_application_per_block,
+            maximum_http_requests_per_application_per_epoch,
+            maximum_http_response_bytes_per_application_per_epoch,
             http_request_allow_list,
             free_application_ids,
             flags,
@@ -1450,6 +1481,30 @@ impl ClientWrapper {
         if let Some(value) = http_request_timeout_ms {
             command.args(["--http-request-timeout-ms", &value.to_string()]);
         }
+        if let Some(value) = maximum_http_requests_per_application_per_block {
+            command.args([
+                "--maximum-http-requests-per-application-per-block",
+                &value.to_string(),
+            ]);
+        }
+        if let Some(value) = maximum_http_response_bytes_per_application_per_block {
+            command.args([
+                "--maximum-http-response-bytes-per-application-per-block",
+                &value.to_string(),
+            ]);
+        }
+        if let Some(value) = maximum_http_requests_per_application_per_epoch {
+            command.args([
+                "--maximum-http-requests-per-application-per-epoch",
+                &value.to_string(),
+            ]);
+        }
+        if let Some(value) = maximum_http_response_bytes_per_application_per_epoch {
+            command.args([
+                "--maximum-http-response-bytes-per-application-per-epoch",
+                &value.to_string(),
+            ]);
+        }
         if let Some(values) = http_request_allow_list {
             command.args(["--http-request-allow-list", &values.join(",")]);
         }
@@ -0,0 +1,187 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::BTreeMap,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{bail, Context as _};
+use linera_base::{data_types::BlockHeight, identifiers::ChainId};
+use linera_persistent as persistent;
+
+/// The next block height to deliver, per consumer and chain.
+type Cursors = BTreeMap<String, BTreeMap<ChainId, BlockHeight>>;
+
+/// The durable delivery cursors of the consumers of the node service.
+///
+/// A consumer's cursor on a chain is the height of the first block that the consumer has
+/// not acknowledged yet. Block streams opened by a consumer start at its cursor, so blocks
+/// delivered but not acknowledged before a disconnect or a crash are delivered again. Since
+/// blocks are delivered in order of height, consumers can discard duplicates by height and
+/// process every block exactly once.
+///
+/// A consumer can only acknowledge blocks that were delivered to it, and cannot move its
+/// cursor backwards.
+pub struct ConsumerCursors {
+    file: Arc<Mutex<persistent::File<Cursors>>>,
+    /// The height following the last block delivered to each consumer on each chain since
+    /// the cursors were opened.
+    delivered: Mutex<BTreeMap<(String, ChainId), BlockHeight>>,
+}
+
+impl ConsumerCursors {
+    /// Opens the cursor file at `path`, creating it if it does not exist.
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        let file = persistent::File::read_or_create(path, || Ok(Cursors::default()))?;
+        Ok(Self {
+            file: Arc::new(Mutex::new(file)),
+            delivered: Mutex::default(),
+        })
+    }
+
+    /// Returns the height of the first block on the chain not acknowledged by the consumer.
+    pub fn next_height(&self, consumer: &str, chain_id: ChainId) -> BlockHeight {
+        let file = self.file.lock().unwrap();
+        file.get(consumer)
+            .and_then(|cursors| cursors.get(&chain_id))
+            .copied()
+            .unwrap_or(BlockHeight::ZERO)
+    }
+
+    /// Records that the block of the chain at `height` was delivered to the consumer.
+    pub fn record_delivery(&self, consumer: &str, chain_id: ChainId, height: BlockHeight) {
+        let Ok(next_height) = height.try_add_one() else {
+            return;
+        };
+        let mut delivered = self.delivered.lock().unwrap();
+        let entry = delivered
+            .entry((consumer.to_string(), chain_id))
+            .or_default();
+        *entry = (*entry).max(next_height);
+    }
+
+    /// Records that the consumer has processed all blocks of the chain up to and including
+    /// `height`, and returns the new cursor. Acknowledging the last acknowledged block again
+    /// has no effect; acknowledging an earlier block, or a block that was not delivered to
+    /// the consumer, is an error.
+    pub async fn acknowledge(
+        &self,
+        consumer: &str,
+        chain_id: ChainId,
+        height: BlockHeight,
+    ) -> anyhow::Result<BlockHeight> {
+        let next_height = height.try_add_one()?;
+        let delivered = self
+            .delivered
+            .lock()
+            .unwrap()
+            .get(&(consumer.to_string(), chain_id))
+            .copied()
+            .unwrap_or(BlockHeight::ZERO);
+        let file = self.file.clone();
+        let consumer = consumer.to_string();
+        // Saving the file is blocking I/O, so it must not run on the async runtime.
+        tokio::task::spawn_blocking(move || {
+            let mut file = file.lock().unwrap();
+            let cursor = file
+                .get(&consumer)
+                .and_then(|cursors| cursors.get(&chain_id))
+                .copied()
+                .unwrap_or(BlockHeight::ZERO);
+            if next_height == cursor {
+                return Ok(next_height);
+            }
+            if next_height < cursor {
+                bail!(
+                    "consumer {consumer} already acknowledged the blocks of chain {chain_id} \
+                     up to height {}",
+                    cursor.0 - 1
+                );
+            }
+            if next_height > delivered {
+                bail!(
+                    "block {height} of chain {chain_id} was not delivered to consumer {consumer}"
+                );
+            }
+            file.entry(consumer)
+                .or_default()
+                .insert(chain_id, next_height);
+            file.save()?;
+            Ok(next_height)
+        })
+        .await
+        .context("failed to save the consumer cursors")?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::{crypto::CryptoHash, data_types::BlockHeight, identifiers::ChainId};
+
+    use super::ConsumerCursors;
+
+    #[tokio::test]
+    async fn test_consumer_cursors_are_durable() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("cursors.json");
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+
+        let cursors = ConsumerCursors::open(&path)?;
+        assert_eq!(cursors.next_height("indexer", chain_id), BlockHeight::ZERO);
+        for height in 0..5 {
+            cursors.record_delivery("indexer", chain_id, BlockHeight(height));
+        }
+        assert_eq!(
+            cursors
+                .acknowledge("indexer", chain_id, BlockHeight(4))
+                .await?,
+            BlockHeight(5)
+        );
+        // Acknowledging the same block again is harmless.
+        assert_eq!(
+            cursors
+                .acknowledge("indexer", chain_id, BlockHeight(4))
+                .await?,
+            BlockHeight(5)
+        );
+        drop(cursors);
+
+        let cursors = ConsumerCursors::open(&path)?;
+        assert_eq!(cursors.next_height("indexer", chain_id), BlockHeight(5));
+        assert_eq!(cursors.next_height("other", chain_id), BlockHeight::ZERO);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_consumer_cursors_reject_invalid_acknowledgments() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cursors = ConsumerCursors::open(&dir.path().join("cursors.json"))?;
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+
+        for height in 0..3 {
+            cursors.record_delivery("indexer", chain_id, BlockHeight(height));
+        }
+        // Blocks that were not delivered, to this consumer or at all, can't be acknowledged.
+        assert!(cursors
+            .acknowledge("indexer", chain_id, BlockHeight(3))
+            .await
+            .is_err());
+        assert!(cursors
+            .acknowledge("other", chain_id, BlockHeight(0))
+            .await
+            .is_err());
+        cursors
+            .acknowledge("indexer", chain_id, BlockHeight(2))
+            .await?;
+        // The cursor can't move backwards.
+        assert!(cursors
+            .acknowledge("indexer", chain_id, BlockHeight(1))
+            .await
+            .is_err());
+        assert_eq!(cursors.next_height("indexer", chain_id), BlockHeight(3));
+        assert_eq!(cursors.next_height("other", chain_id), BlockHeight::ZERO);
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::BTreeSet;
+
+use futures::lock::Mutex;
+use linera_base::{
+    data_types::{BlockHeight, Timestamp},
+    identifiers::{ApplicationId, ChainId, GenericApplicationId, StreamId},
+};
+use linera_chain::block::Block;
+use linera_storage::Storage;
+use linera_views::{
+    collection_view::CollectionView,
+    context::Context,
+    map_view::MapView,
+    register_view::RegisterView,
+    views::{RootView, View},
+    ViewError,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+/// How many blocks are read from storage at once while catching up with a chain.
+const CATCH_UP_BATCH_SIZE: u64 = 100;
+
+/// Options of the local index of application events.
+#[derive(Clone, Debug, clap::Args)]
+pub struct EventIndexOptions {
+    /// Index the events that the given applications emit on the wallet's chains, so that
+    /// the `localEvents` query can return them without contacting validators.
+    #[arg(long = "index-events-of", value_delimiter = ',')]
+    pub applications: Vec<ApplicationId>,
+
+    /// The maximum number of events kept in the index per stream. Older events are
+    /// dropped first.
+    #[arg(long = "event-index-max-events-per-stream", default_value = "10000")]
+    pub max_events_per_stream: u32,
+
+    /// Events with a value larger than this many bytes are not indexed.
+    #[arg(long = "event-index-max-event-bytes", default_value = "65536")]
+    pub max_event_bytes: usize,
+}
+
+/// An event in the index, with the block that emitted it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexedEvent {
+    /// The index of the event in its stream.
+    pub index: u32,
+    /// The height of the block that emitted the event.
+    pub height: BlockHeight,
+    /// The timestamp of the block that emitted the event.
+    pub timestamp: Timestamp,
+    /// The BCS-encoded event value.
+    pub value: Vec<u8>,
+}
+
+/// The events of one stream in the index.
+#[derive(View)]
+pub struct IndexedStreamView<C> {
+    /// The index of the oldest event that may still be in the index, if any event was
+    /// indexed.
+    first_index: RegisterView<C, Option<u32>>,
+    /// The indexed events, by index.
+    events: MapView<C, u32, IndexedEvent>,
+}
+
+/// The local index of the events of selected applications.
+#[derive(RootView)]
+pub struct EventIndexView<C> {
+    /// The height of the next block to index, per chain.
+    next_heights: MapView<C, ChainId, BlockHeight>,
+    /// The indexed events, per chain and stream.
+    streams: CollectionView<C, (ChainId, StreamId), IndexedStreamView<C>>,
+}
+
+impl<C> EventIndexView<C>
+where
+    C: Context + Clone + Send + Sync + 'static,
+{
+    /// Adds the events of the given block emitted by the given applications, dropping the
+    /// oldest events of streams that exceed the limits of `options`.
+    pub async fn index_block(
+        &mut self,
+        block: &Block,
+        applications: &BTreeSet<ApplicationId>,
+        options: &EventIndexOptions,
+    ) -> Result<(), ViewError> {
+        let chain_id = block.header.chain_id;
+        for event in block.body.events.iter().flatten() {
+            let GenericApplicationId::User(application_id) = event.stream_id.application_id else {
+                continue;
+            };
+            if !applications.contains(&application_id) {
+                continue;
+            }
+            if event.value.len() > options.max_event_bytes {
+                debug!(
+                    %chain_id, stream_id = %event.stream_id, index = event.index,
+                    "Not indexing an event exceeding the size limit"
+                );
+                continue;
+            }
+            let stream = self
+                .streams
+                .load_entry_mut(&(chain_id, event.stream_id.clone()))
+                .await?;
+            stream.events.insert(
+                &event.index,
+                IndexedEvent {
+                    index: event.index,
+                    height: block.header.height,
+                    timestamp: block.header.timestamp,
+                    value: event.value.clone(),
+                },
+            )?;
+            let first_index = stream.first_index.get().unwrap_or(event.index);
+            let new_first_index = event
+                .index
+                .saturating_add(1)
+                .saturating_sub(options.max_events_per_stream)
+                .max(first_index);
+            for index in first_index..new_first_index {
+                stream.events.remove(&index)?;
+            }
+            stream.first_index.set(Some(new_first_index));
+        }
+        Ok(())
+    }
+
+    /// Returns at most `limit` indexed events of the stream, starting at `start_index`.
+    pub async fn events(
+        &self,
+        chain_id: ChainId,
+        stream_id: StreamId,
+        start_index: u32,
+        limit: u32,
+    ) -> Result<Vec<IndexedEvent>, ViewError> {
+        let Some(stream) = self.streams.try_load_entry(&(chain_id, stream_id)).await? else {
+            return Ok(Vec::new());
+        };
+        let start_index = start_index.max(stream.first_index.get().unwrap_or(0));
+        let indices = (start_index..start_index.saturating_add(limit)).collect::<Vec<_>>();
+        Ok(stream
+            .events
+            .multi_get(&indices)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+}
+
+/// The local index of the events that selected applications emit on the wallet's chains.
+///
+/// The index follows the confirmed blocks in the client's storage, so it can answer
+/// historical queries without contacting validators, e.g. while the node service is paused.
+pub struct EventIndex<S: Storage> {
+    storage: S,
+    view: Mutex<EventIndexView<S::EventIndexContext>>,
+    applications: BTreeSet<ApplicationId>,
+    options: EventIndexOptions,
+}
+
+impl<S> EventIndex<S>
+where
+    S: Storage + Clone + Send + Sync + 'static,
+{
+    /// Opens the index in the given storage, if `options` select any applications.
+    pub async fn open(storage: S, options: EventIndexOptions) -> Result<Option<Self>, ViewError> {
+        if options.applications.is_empty() {
+            return Ok(None);
+        }
+        let view = EventIndexView::load(storage.event_index_context().await?).await?;
+        Ok(Some(Self {
+            storage,
+            view: Mutex::new(view),
+            applications: options.applications.iter().copied().collect(),
+            options,
+        }))
+    }
+
+    /// Returns whether the events of the given application are indexed.
+    pub fn indexes(&self, application_id: &ApplicationId) -> bool {
+        self.applications.contains(application_id)
+    }
+
+    /// Indexes the blocks of the chain that are in storage and were not indexed yet.
+    pub async fn catch_up(&self, chain_id: ChainId) -> Result<(), ViewError> {
+        let mut view = self.view.lock().await;
+        let mut next_height = view
+            .next_heights
+            .get(&chain_id)
+            .await?
+            .unwrap_or(BlockHeight::ZERO);
+        'batches: loop {
+            let heights = (next_height.0..next_height.0.saturating_add(CATCH_UP_BATCH_SIZE))
+                .map(BlockHeight)
+                .collect::<Vec<_>>();
+            let certificates = self
+                .storage
+                .read_certificates_by_heights(chain_id, &heights)
+                .await?;
+            for certificate in certificates {
+                let Some(certificate) = certificate else {
+                    break 'batches;
+                };
+                view.index_block(certificate.block(), &self.applications, &self.options)
+                    .await?;
+                next_height = match next_height.try_add_one() {
+                    Ok(height) => height,
+                    Err(error) => {
+                        warn!(%chain_id, %error, "Cannot index blocks beyond the maximum height");
+                        break 'batches;
+                    }
+                };
+            }
+        }
+        view.next_heights.insert(&chain_id, next_height)?;
+        view.save().await
+    }
+
+    /// Returns at most `limit` indexed events of the stream on the given chain, starting at
+    /// `start_index`. Events that were dropped or not indexed because of the limits are
+    /// skipped.
+    pub async fn events(
+        &self,
+        chain_id: ChainId,
+        stream_id: StreamId,
+        start_index: u32,
+        limit: u32,
+    ) -> Result<Vec<IndexedEvent>, ViewError> {
+        self.view
+            .lock()
+            .await
+            .events(chain_id, stream_id, start_index, limit)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use linera_base::{
+        crypto::CryptoHash,
+        data_types::{BlockHeight, Event},
+        identifiers::{ApplicationId, ChainId, GenericApplicationId, StreamId, StreamName},
+    };
+    use linera_chain::test::BlockBuilder;
+    use linera_views::{context::MemoryContext, views::View as _};
+
+    use super::{EventIndexOptions, EventIndexView};
+
+    fn event(application_id: ApplicationId, index: u32, size: usize) -> Event {
+        Event {
+            stream_id: StreamId {
+                application_id: GenericApplicationId::User(application_id),
+                stream_name: StreamName(b"transfers".to_vec()),
+            },
+            index,
+            value: vec![index as u8; size],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_event_index_applies_opt_in_and_limits() -> anyhow::Result<()> {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let indexed = ApplicationId::new(CryptoHash::test_hash("indexed"));
+        let other = ApplicationId::new(CryptoHash::test_hash("other"));
+        let options = EventIndexOptions {
+            applications: vec![indexed],
+            max_events_per_stream: 2,
+            max_event_bytes: 8,
+        };
+        let applications = BTreeSet::from([indexed]);
+        let mut view = EventIndexView::load(MemoryContext::new_for_testing(())).await?;
+
+        let block = BlockBuilder::new(chain_id, BlockHeight(3))
+            .with_events(vec![event(indexed, 0, 4), event(other, 0, 4)])
+            .with_events(vec![event(indexed, 1, 100), event(indexed, 2, 4)])
+            .with_events(vec![event(indexed, 3, 4)])
+            .build();
+        view.index_block(&block, &applications, &options).await?;
+
+        let stream_id = event(indexed, 0, 0).stream_id;
+        let events = view.events(chain_id, stream_id, 0, 10).await?;
+        // Event 0 was dropped to keep two events, and event 1 exceeds the size limit.
+        assert_eq!(
+            events.iter().map(|event| event.index).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert!(events.iter().all(|event| event.height == BlockHeight(3)));
+        let other_stream_id = event(other, 0, 0).stream_id;
+        let other_events = view.events(chain_id, other_stream_id, 0, 10).await?;
+        assert!(other_events.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Serving the execution profiles of a validator, to find the applications that slow a shard
+//! down.
+//!
+//! With `--enable-execution-profiling`, the shards record the time spent in each
+//! application and host function of every block they execute, per chain (see
+//! [`linera_execution::profiler`]). [`router`] exposes these profiles on
+//! `/admin/execution-profile`: `GET` returns them in the folded format of flamegraph tools,
+//! or as a pprof profile with `?format=pprof`, optionally for a single chain with
+//! `?chain=<CHAIN_ID>`, and `DELETE` discards them.
+
+use axum::{
+    extract::Query,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use linera_base::identifiers::ChainId;
+use linera_execution::profiler;
+use serde::Deserialize;
+
+/// The path of the endpoint serving the execution profiles.
+pub const EXECUTION_PROFILE_PATH: &str = "/admin/execution-profile";
+
+/// The formats in which the profiles are served.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ProfileFormat {
+    /// One line per stack of frames, with the time in microseconds.
+    #[default]
+    Folded,
+    /// A profile in the protocol buffer format of `pprof`.
+    Pprof,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProfileQuery {
+    #[serde(default)]
+    format: ProfileFormat,
+    chain: Option<ChainId>,
+}
+
+/// Returns a router serving the execution profiles on [`EXECUTION_PROFILE_PATH`].
+pub fn router() -> Router {
+    Router::new().route(
+        EXECUTION_PROFILE_PATH,
+        get(get_profiles).delete(delete_profiles),
+    )
+}
+
+async fn get_profiles(Query(query): Query<ProfileQuery>) -> Response {
+    if !profiler::is_enabled() {
+        return not_enabled();
+    }
+    let mut profiles = profiler::profiles();
+    if let Some(chain_id) = query.chain {
+        profiles.retain(|id, _| *id == chain_id);
+    }
+    match query.format {
+        ProfileFormat::Folded => profiler::folded(&profiles).into_response(),
+        ProfileFormat::Pprof => (
+            [(header::CONTENT_TYPE, "application/octet-stream")],
+            pprof::encode(&profiles),
+        )
+            .into_response(),
+    }
+}
+
+async fn delete_profiles() -> Response {
+    if !profiler::is_enabled() {
+        return not_enabled();
+    }
+    profiler::reset();
+    StatusCode::NO_CONTENT.into_response()
+}
+
+fn not_enabled() -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        "execution profiling is not enabled on this server",
+    )
+        .into_response()
+}
+
+/// The subset of the `pprof` profile format needed for the execution profiles.
+///
+/// See <https://github.com/google/pprof/blob/main/proto/profile.proto>.
+mod pprof {
+    use std::collections::{BTreeMap, HashMap};
+
+    use linera_base::identifiers::ChainId;
+    use linera_execution::profiler::ChainProfile;
+    use prost::Message as _;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Profile {
+        #[prost(message, repeated, tag = "1")]
+        pub sample_type: Vec<ValueType>,
+        #[prost(message, repeated, tag = "2")]
+        pub sample: Vec<Sample>,
+        #[prost(message, repeated, tag = "4")]
+        pub location: Vec<Location>,
+        #[prost(message, repeated, tag = "5")]
+        pub function: Vec<Function>,
+        #[prost(string, repeated, tag = "6")]
+        pub string_table: Vec<String>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ValueType {
+        #[prost(int64, tag = "1")]
+        pub r#type: i64,
+        #[prost(int64, tag = "2")]
+        pub unit: i64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Sample {
+        /// The locations of the stack, innermost first.
+        #[prost(uint64, repeated, tag = "1")]
+        pub location_id: Vec<u64>,
+        #[prost(int64, repeated, tag = "2")]
+        pub value: Vec<i64>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Location {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(message, repeated, tag = "4")]
+        pub line: Vec<Line>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Line {
+        #[prost(uint64, tag = "1")]
+        pub function_id: u64,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct Function {
+        #[prost(uint64, tag = "1")]
+        pub id: u64,
+        #[prost(int64, tag = "2")]
+        pub name: i64,
+    }
+
+    /// Builds a profile with one location per frame name, the chain IDs being the outermost
+    /// frames, and the times in nanoseconds.
+    #[derive(Default)]
+    struct Builder {
+        profile: Profile,
+        strings: HashMap<String, i64>,
+        locations: HashMap<String, u64>,
+    }
+
+    impl Builder {
+        fn string(&mut self, value: &str) -> i64 {
+            if let Some(index) = self.strings.get(value) {
+                return *index;
+            }
+            let index = i64::try_from(self.profile.string_table.len()).expect("too many strings");
+            self.profile.string_table.push(value.to_string());
+            self.strings.insert(value.to_string(), index);
+            index
+        }
+
+        fn location(&mut self, name: &str) -> u64 {
+            if let Some(id) = self.locations.get(name) {
+                return *id;
+            }
+            // IDs must be non-zero.
+            let id = u64::try_from(self.profile.location.len()).expect("too many locations") + 1;
+            let name_index = self.string(name);
+            self.profile.function.push(Function {
+                id,
+                name: name_index,
+            });
+            self.profile.location.push(Location {
+                id,
+                line: vec![Line { function_id: id }],
+            });
+            self.locations.insert(name.to_string(), id);
+            id
+        }
+    }
+
+    /// Encodes `profiles` as a `pprof` profile.
+    pub fn encode(profiles: &BTreeMap<ChainId, ChainProfile>) -> Vec<u8> {
+        let mut builder = Builder::default();
+        // The first string must be empty.
+        builder.string("");
+        let sample_type = ValueType {
+            r#type: builder.string("time"),
+            unit: builder.string("nanoseconds"),
+        };
+        builder.profile.sample_type.push(sample_type);
+        for (chain_id, profile) in profiles {
+            let chain_id = chain_id.to_string();
+            for (stack, duration) in &profile.stacks {
+                let mut location_id = stack
+                    .iter()
+                    .rev()
+                    .map(|name| builder.location(name))
+                    .collect::<Vec<_>>();
+                location_id.push(builder.location(&chain_id));
+                let nanos = i64::try_from(duration.as_nanos()).unwrap_or(i64::MAX);
+                builder.profile.sample.push(Sample {
+                    location_id,
+                    value: vec![nanos],
+                });
+            }
+        }
+        builder.profile.encode_to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use linera_base::{crypto::CryptoHash, identifiers::ChainId};
+    use linera_execution::profiler::ChainProfile;
+    use prost::Message as _;
+
+    use super::pprof;
+
+    #[test]
+    fn test_pprof_encoding() {
+        let chain_id = ChainId(CryptoHash::test_hash("chain"));
+        let stacks = [
+            (vec!["app".to_string()], Duration::from_micros(3)),
+            (
+                vec!["app".to_string(), "ReadValueBytes".to_string()],
+                Duration::from_micros(5),
+            ),
+        ];
+        let profiles = BTreeMap::from([(
+            chain_id,
+            ChainProfile {
+                stacks: stacks.into_iter().collect(),
+            },
+        )]);
+        let profile = pprof::Profile::decode(&pprof::encode(&profiles)[..]).unwrap();
+        assert_eq!(profile.string_table[0], "");
+        assert_eq!(profile.location.len(), 3);
+        let names = |sample: &pprof::Sample| {
+            sample
+                .location_id
+                .iter()
+                .map(|id| {
+                    let function = &profile.function[usize::try_from(*id - 1).unwrap()];
+                    profile.string_table[usize::try_from(function.name).unwrap()].clone()
+                })
+                .collect::<Vec<_>>()
+        };
+        let samples = profile
+            .sample
+            .iter()
+            .map(|sample| (names(sample), sample.value.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            samples,
+            [
+                (vec!["app".to_string(), chain_id.to_string()], vec![3000]),
+                (
+                    vec![
+                        "ReadValueBytes".to_string(),
+                        "app".to_string(),
+                        chain_id.to_string()
+                    ],
+                    vec![5000]
+                ),
+            ]
+        );
+    }
+}
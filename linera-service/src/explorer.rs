@@ -0,0 +1,13 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use axum::{response::Html, routing::get, Router};
+
+/// The explorer page. It renders the wallet's chains, their balances, latest blocks and
+/// event streams, all read through the GraphQL API of the node service.
+const PAGE: &str = include_str!("../assets/explorer/index.html");
+
+/// Returns the route of the explorer page.
+pub fn router() -> Router {
+    Router::new().route("/", get(|| async { Html(PAGE) }))
+}
@@ -0,0 +1,122 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{mem, net::SocketAddr, num::NonZeroU16, pin::Pin, sync::Arc};
+
+use futures::{stream, Stream, StreamExt as _};
+use linera_base::data_types::Blob;
+use linera_chain::types::ConfirmedBlockCertificate;
+use linera_core::{client::Client, Environment};
+use linera_exporter::runloops::indexer_api::{
+    self,
+    element::Payload,
+    indexer_server::{Indexer, IndexerServer},
+    Element,
+};
+use tokio_util::sync::CancellationToken;
+use tonic::{transport::Server, Request, Response, Status, Streaming};
+use tracing::{debug, info, warn};
+
+/// Keeps the local state of a read replica of the node service up to date with the blocks
+/// pushed by a block exporter, without contacting the validators.
+///
+/// The feed serves the indexer protocol of the block exporter, so a replica is added by
+/// configuring an indexer destination of the exporter with the address of the feed. The
+/// exporter sends the blobs of each block followed by its certificate, which the feed
+/// verifies and processes locally as if it had been downloaded from the validators.
+pub struct ExporterFeed<Env: Environment> {
+    client: Arc<Client<Env>>,
+}
+
+impl<Env: Environment> ExporterFeed<Env> {
+    /// Creates a feed updating the local storage of the given client.
+    pub fn new(client: Arc<Client<Env>>) -> Self {
+        Self { client }
+    }
+
+    /// Receives blocks from the block exporter on the given port until the token is
+    /// cancelled.
+    pub async fn run(
+        self,
+        port: NonZeroU16,
+        cancellation_token: CancellationToken,
+    ) -> anyhow::Result<()> {
+        let address = SocketAddr::from(([0, 0, 0, 0], port.get()));
+        info!("Receiving blocks from the block exporter on {address}");
+        Server::builder()
+            .add_service(IndexerServer::new(self))
+            .serve_with_shutdown(address, cancellation_token.cancelled_owned())
+            .await?;
+        Ok(())
+    }
+}
+
+#[tonic::async_trait]
+impl<Env: Environment> Indexer for ExporterFeed<Env> {
+    type IndexBatchStream = Pin<Box<dyn Stream<Item = Result<(), Status>> + Send + 'static>>;
+
+    async fn index_batch(
+        &self,
+        request: Request<Streaming<Element>>,
+    ) -> Result<Response<Self::IndexBatchStream>, Status> {
+        let client = Arc::clone(&self.client);
+        let acknowledgements = stream::unfold(
+            (request.into_inner(), Vec::new()),
+            move |(mut elements, mut blobs)| {
+                let client = Arc::clone(&client);
+                async move {
+                    loop {
+                        let element = match elements.next().await? {
+                            Ok(element) => element,
+                            Err(status) => return Some((Err(status), (elements, blobs))),
+                        };
+                        let result = match element.payload {
+                            Some(Payload::Blob(blob)) => match Blob::try_from(blob) {
+                                Ok(blob) => {
+                                    blobs.push(blob);
+                                    continue;
+                                }
+                                Err(error) => {
+                                    Err(Status::invalid_argument(format!("invalid blob: {error}")))
+                                }
+                            },
+                            Some(Payload::Block(block)) => {
+                                process_block(&client, block, mem::take(&mut blobs)).await
+                            }
+                            None => Err(Status::invalid_argument("missing payload")),
+                        };
+                        return Some((result, (elements, blobs)));
+                    }
+                }
+            },
+        );
+        Ok(Response::new(Box::pin(acknowledgements)))
+    }
+}
+
+/// Processes a block received from the exporter, with the blobs sent before it.
+///
+/// Failures are reported to the exporter, which sends the block again later.
+async fn process_block<Env: Environment>(
+    client: &Client<Env>,
+    block: indexer_api::Block,
+    blobs: Vec<Blob>,
+) -> Result<(), Status> {
+    let certificate = ConfirmedBlockCertificate::try_from(block)
+        .map_err(|error| Status::invalid_argument(format!("invalid block: {error}")))?;
+    let chain_id = certificate.block().header.chain_id;
+    let height = certificate.block().header.height;
+    match client
+        .process_exported_certificate(certificate, &blobs)
+        .await
+    {
+        Ok(_) => {
+            debug!(%chain_id, %height, "Processed block from the block exporter");
+            Ok(())
+        }
+        Err(error) => {
+            warn!(%chain_id, %height, "Failed to process block from the block exporter: {error}");
+            Err(Status::internal(error.to_string()))
+        }
+    }
+}
@@ -7,22 +7,53 @@
 #![recursion_limit = "256"]
 #![deny(missing_docs)]
 
+/// Access tokens restricting the mutations of the node service.
+pub mod access_control;
+/// Alerts raised by the validator binaries from their own metrics.
+#[cfg(with_metrics)]
+pub mod alerting;
 pub mod cli;
 pub mod cli_wrappers;
 /// Configuration types for the service binaries.
 pub mod config;
+/// Durable cursors of the consumers of block streams served by the node service.
+pub mod consumer_cursor;
 /// The controller that orchestrates worker services.
 pub mod controller;
+/// A local index of application events, for queries that don't contact validators.
+pub mod event_index;
+/// The execution profiles of the applications run by a validator.
+pub mod execution_profile;
+/// A small block explorer page served by the node service.
+pub mod explorer;
+/// Read replicas of the node service, fed with blocks by a block exporter.
+pub mod exporter_feed;
 /// The GraphQL node service exposing wallet and chain state.
 pub mod node_service;
 /// Helpers for creating and building application projects.
 pub mod project;
+/// Limits on the GraphQL queries of the node service, and persisted queries.
+pub mod query_limits;
 /// Tracking of GraphQL subscriptions by query.
 pub mod query_subscription;
+/// Signing through a separate process holding the validator key, e.g. in an enclave.
+pub mod remote_signer;
+/// TLS and API keys securing the node service.
+pub mod service_security;
+/// The local web page through which external EVM wallets sign.
+pub mod signing_page;
 /// Storage backend selection for the service binaries.
 pub mod storage;
+pub mod storage_wallet;
 pub mod task_processor;
+/// Anonymous usage metrics that the client reports when opted in.
+pub mod telemetry;
 pub mod tracing;
 /// Assorted helper utilities for the service binaries.
 pub mod util;
+/// Virtual time for local test networks.
+pub mod virtual_time;
+pub mod wallet_backend;
+/// A bridge mapping authenticated incoming HTTP requests to application mutations.
+pub mod webhook;
 pub use linera_wallet_json::PersistentWallet as Wallet;
@@ -3,7 +3,7 @@
 
 use std::{
     borrow::Cow,
-    collections::BTreeSet,
+    collections::{BTreeSet, VecDeque},
     future::IntoFuture,
     iter,
     net::SocketAddr,
@@ -15,42 +15,55 @@ use async_graphql::{
     futures_util::Stream,
     registry::{MetaType, MetaTypeId, Registry},
     resolver_utils::ContainerType,
-    EmptyMutation, Error, MergedObject, OutputType, Positioned, Request, Response, ScalarType,
-    Schema, SimpleObject, Subscription,
+    EmptyMutation, Error, InputObject, MergedObject, OutputType, Positioned, Request, Response,
+    ScalarType, Schema, ServerError, SimpleObject, Subscription,
 };
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
-use axum::{extract::Path, http::StatusCode, response, response::IntoResponse, Extension, Router};
+use axum::{
+    body::Bytes,
+    extract::Path,
+    http::{HeaderMap, StatusCode},
+    response,
+    response::IntoResponse,
+    Extension, Router,
+};
+use axum_server::tls_rustls::RustlsConfig;
 use futures::{lock::Mutex, Future, FutureExt as _, StreamExt as _, TryStreamExt as _};
 use linera_base::{
     crypto::{CryptoError, CryptoHash},
     data_types::{
-        Amount, ApplicationDescription, ApplicationPermissions, BlockHeight, Bytecode, Epoch,
-        TimeDelta,
+        Amount, ApplicationDescription, ApplicationPermissions, Blob, BlockHeight, Bytecode, Epoch,
+        TimeDelta, Timestamp,
     },
+    hex,
     identifiers::{
-        Account, AccountOwner, ApplicationId, ChainId, IndexAndEvent, ModuleId, StreamId,
+        Account, AccountOwner, ApplicationId, BlobId, BlobType, ChainId, IndexAndEvent, ModuleId,
+        StreamId, StreamName,
     },
     ownership::{ChainOwnership, TimeoutConfig},
     vm::VmRuntime,
     BcsHexParseError,
 };
 use linera_chain::{types::ConfirmedBlock, ChainStateView};
-use linera_client::chain_listener::{
-    ChainListener, ChainListenerConfig, ClientContext, ClientContextExt as _, ListenerCommand,
+use linera_client::{
+    chain_listener::{
+        ChainListener, ChainListenerConfig, ClientContext, ClientContextExt as _, ListenerCommand,
+    },
+    signing_relay::{SigningRelay, SigningRequest},
 };
 use linera_core::{
     client::chain_client::{self, ChainClient},
-    data_types::ClientOutcome,
+    data_types::{ClientOutcome, FeeSuggestion},
     wallet::Wallet as _,
     worker::{ChainStateViewReadGuard, Notification, Reason},
 };
 use linera_execution::{
-    committee::Committee, system::AdminOperation, Operation, Query, QueryOutcome, QueryResponse,
-    SystemOperation,
+    committee::Committee, system::AdminOperation, EventIndexKey, Operation, Query, QueryOutcome,
+    QueryResponse, SystemOperation,
 };
 #[cfg(with_metrics)]
 use linera_metrics::monitoring_server;
-use linera_sdk::linera_base_types::BlobContent;
+use linera_sdk::{formats::Formats, linera_base_types::BlobContent};
 use linera_storage::Storage;
 use lru::LruCache;
 use serde::{Deserialize, Serialize};
@@ -58,9 +71,24 @@ use serde_json::json;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio_util::sync::CancellationToken;
 use tower_http::cors::CorsLayer;
-use tracing::{debug, info, instrument, trace};
+use tracing::{debug, error, info, instrument, trace, warn};
+
+use crate::{
+    access_control::{self, AccessControl, AccessError},
+    consumer_cursor::ConsumerCursors,
+    event_index::EventIndex,
+    exporter_feed::ExporterFeed,
+    query_limits::{QueryLimitError, QueryLimits},
+    service_security::{self, ApiKeys},
+    util,
+    webhook::{WebhookBridge, WebhookError},
+};
 
-use crate::util;
+/// The maximum number of blocks read from storage at once by a block stream.
+const BLOCK_STREAM_BATCH_SIZE: usize = 100;
+
+/// The maximum number of events read from storage at once by an event stream.
+const EVENT_STREAM_BATCH_SIZE: u32 = 100;
 
 /// A pre-serialized JSON string that implements [`OutputType`] as the `JSON` scalar.
 ///
@@ -117,23 +145,87 @@ pub struct Chains {
     pub default: Option<ChainId>,
 }
 
+/// A confirmed block delivered by a block stream.
+#[derive(SimpleObject, Clone)]
+pub struct BlockDelivery {
+    /// The chain of the block.
+    pub chain_id: ChainId,
+    /// The height of the block. Consumers acknowledge processed blocks by height.
+    pub height: BlockHeight,
+    /// The hash of the block.
+    pub hash: CryptoHash,
+    /// The block itself.
+    pub block: Arc<ConfirmedBlock>,
+}
+
+/// Selects the events of a stream that their application indexed with the given value of a
+/// field.
+#[derive(InputObject, Clone)]
+pub struct EventFilter {
+    /// The indexed field, e.g. `to`.
+    pub field: String,
+    /// The hex-encoded BCS value of the field.
+    pub key: String,
+}
+
+/// An event of an application's stream.
+#[derive(SimpleObject, Clone)]
+pub struct ApplicationEvent {
+    /// The index of the event in its stream.
+    pub index: u32,
+    /// The BCS-encoded event value.
+    pub bytes: Vec<u8>,
+    /// The event value as JSON, if the application's module was published with the formats
+    /// of its ABI.
+    value: Option<RawJson>,
+}
+
+/// An event of an application's stream, read from the local event index.
+#[derive(SimpleObject, Clone)]
+pub struct LocalEvent {
+    /// The index of the event in its stream.
+    pub index: u32,
+    /// The height of the block that emitted the event.
+    pub height: BlockHeight,
+    /// The timestamp of the block that emitted the event.
+    pub timestamp: Timestamp,
+    /// The BCS-encoded event value.
+    pub bytes: Vec<u8>,
+    /// The event value as JSON, if the application's module was published with the formats
+    /// of its ABI.
+    value: Option<RawJson>,
+}
+
+/// The local index of events of the storage used by the client context `C`.
+type EventIndexOf<C> =
+    EventIndex<<<C as ClientContext>::Environment as linera_core::Environment>::Storage>;
+
 /// Our root GraphQL query type.
-pub struct QueryRoot<C> {
+pub struct QueryRoot<C>
+where
+    C: ClientContext,
+{
     context: Arc<Mutex<C>>,
     port: NonZeroU16,
     default_chain: Option<ChainId>,
+    signing_relay: Option<SigningRelay>,
+    event_index: Option<Arc<EventIndexOf<C>>>,
 }
 
 /// Our root GraphQL subscription type.
 pub struct SubscriptionRoot<C> {
     context: Arc<Mutex<C>>,
     query_subscriptions: Option<Arc<crate::query_subscription::QuerySubscriptionManager>>,
+    consumer_cursors: Option<Arc<ConsumerCursors>>,
     cancellation_token: CancellationToken,
 }
 
 /// Our root GraphQL mutation type.
 pub struct MutationRoot<C> {
     context: Arc<Mutex<C>>,
+    query_cache: Option<Arc<QueryResponseCache>>,
+    consumer_cursors: Option<Arc<ConsumerCursors>>,
+    signing_relay: Option<SigningRelay>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -150,6 +242,12 @@ enum NodeServiceError {
     Client(#[from] linera_client::Error),
     #[error("scheduling operations from queries is disabled in read-only mode")]
     ReadOnlyModeOperationsNotAllowed,
+    #[error(transparent)]
+    Webhook(#[from] WebhookError),
+    #[error(transparent)]
+    Access(#[from] AccessError),
+    #[error(transparent)]
+    QueryLimit(#[from] QueryLimitError),
 }
 
 impl IntoResponse for NodeServiceError {
@@ -159,6 +257,18 @@ impl IntoResponse for NodeServiceError {
                 StatusCode::BAD_REQUEST
             }
             NodeServiceError::ReadOnlyModeOperationsNotAllowed => StatusCode::FORBIDDEN,
+            NodeServiceError::Webhook(WebhookError::UnknownRoute(_)) => StatusCode::NOT_FOUND,
+            NodeServiceError::Webhook(WebhookError::InvalidSignature) => StatusCode::UNAUTHORIZED,
+            NodeServiceError::Webhook(WebhookError::StaleTimestamp) => StatusCode::UNAUTHORIZED,
+            NodeServiceError::Webhook(WebhookError::DeliveryInProgress(_)) => StatusCode::CONFLICT,
+            NodeServiceError::Webhook(WebhookError::InvalidPayload(_)) => StatusCode::BAD_REQUEST,
+            NodeServiceError::Access(AccessError::MissingToken | AccessError::InvalidToken) => {
+                StatusCode::UNAUTHORIZED
+            }
+            NodeServiceError::Access(AccessError::Forbidden(_)) => StatusCode::FORBIDDEN,
+            NodeServiceError::Access(AccessError::InvalidRequest(_)) => StatusCode::BAD_REQUEST,
+            NodeServiceError::QueryLimit(QueryLimitError::NotPersisted) => StatusCode::FORBIDDEN,
+            NodeServiceError::QueryLimit(_) => StatusCode::BAD_REQUEST,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
         let body = json!({"error": self.to_string()}).to_string();
@@ -221,6 +331,282 @@ where
             .filter_map(|value| async move { value });
         Ok(futures::stream::iter(current).chain(changes).map(RawJson))
     }
+
+    /// Subscribes to the events of an application's stream, decoded with the formats of the
+    /// application's ABI if it was published with them. The stream starts at `startIndex` if
+    /// given, otherwise at the next new event. With a `filter`, only the events the
+    /// application indexed with the given field value are delivered.
+    async fn events(
+        &self,
+        #[graphql(desc = "The chain to watch.")] chain_id: ChainId,
+        #[graphql(desc = "The application that emits the events.")] application_id: ApplicationId,
+        #[graphql(desc = "The name of the stream.")] stream_name: String,
+        #[graphql(desc = "The indexed field value the events must have.")] filter: Option<
+            EventFilter,
+        >,
+        #[graphql(desc = "The index of the first event to deliver.")] start_index: Option<u32>,
+    ) -> Result<impl Stream<Item = ApplicationEvent>, Error> {
+        let client = self
+            .context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .await?;
+        let stream_id = StreamId {
+            application_id: application_id.into(),
+            stream_name: StreamName::from(stream_name),
+        };
+        let index_key = filter
+            .map(|filter| filter.into_index_key(stream_id.clone()))
+            .transpose()?;
+        let formats = application_formats(&client, application_id).await?;
+        // Subscribe before reading the stream's length, so that no new event is missed.
+        let notifications = client.subscribe()?;
+        let start_index = match start_index {
+            Some(index) => index,
+            None => client.next_event_index(&stream_id).await?,
+        };
+        let state = (client, notifications, start_index, VecDeque::new());
+        Ok(futures::stream::unfold(
+            state,
+            move |(client, mut notifications, mut next_index, mut pending)| {
+                let stream_id = stream_id.clone();
+                let index_key = index_key.clone();
+                let formats = formats.clone();
+                async move {
+                    loop {
+                        if let Some(event) = pending.pop_front() {
+                            return Some((event, (client, notifications, next_index, pending)));
+                        }
+                        let events = match read_application_events(
+                            &client,
+                            &stream_id,
+                            index_key.clone(),
+                            next_index,
+                            EVENT_STREAM_BATCH_SIZE,
+                        )
+                        .await
+                        {
+                            Ok(events) => events,
+                            Err(error) => {
+                                warn!(%chain_id, %stream_id, ?error, "Failed to read events");
+                                return None;
+                            }
+                        };
+                        if let Some(last) = events.last() {
+                            next_index = last.index.checked_add(1)?;
+                            pending.extend(decode_events(formats.as_deref(), events));
+                            continue;
+                        }
+                        // Wait for new events in the stream.
+                        loop {
+                            let notification = notifications.next().await?;
+                            if matches!(
+                                &notification.reason,
+                                Reason::NewEvents { event_streams, .. }
+                                    if event_streams.contains(&stream_id)
+                            ) {
+                                break;
+                            }
+                        }
+                    }
+                }
+            },
+        ))
+    }
+
+    /// Subscribes to the confirmed blocks of a chain, delivered in order of height without
+    /// gaps. The stream starts at `startHeight` if given, otherwise at the first block not
+    /// acknowledged by `consumer` (see `acknowledgeBlocks`), otherwise at the next new block.
+    async fn confirmed_blocks(
+        &self,
+        #[graphql(desc = "The chain to watch.")] chain_id: ChainId,
+        #[graphql(desc = "The consumer whose durable cursor the stream resumes from.")]
+        consumer: Option<String>,
+        #[graphql(desc = "The height of the first block to deliver.")] start_height: Option<
+            BlockHeight,
+        >,
+    ) -> Result<impl Stream<Item = BlockDelivery>, Error> {
+        let client = self
+            .context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .await?;
+        // Subscribe before reading the chain's height, so that no new block is missed.
+        let notifications = client.subscribe()?;
+        // Deliveries to a consumer are recorded, so that it can only acknowledge those.
+        let consumer = match consumer {
+            Some(consumer) => Some((
+                consumer,
+                self.consumer_cursors
+                    .clone()
+                    .ok_or_else(|| Error::new("consumer cursors are not enabled"))?,
+            )),
+            None => None,
+        };
+        let start_height = match (start_height, &consumer) {
+            (Some(height), _) => height,
+            (None, Some((consumer, cursors))) => cursors.next_height(consumer, chain_id),
+            (None, None) => client.chain_info().await?.next_block_height,
+        };
+        let state = (
+            client,
+            notifications,
+            start_height,
+            VecDeque::new(),
+            consumer,
+        );
+        Ok(futures::stream::unfold(
+            state,
+            move |(client, mut notifications, mut next_height, mut pending, consumer)| async move {
+                loop {
+                    if let Some(delivery) = pending.pop_front() {
+                        if let Some((consumer, cursors)) = &consumer {
+                            cursors.record_delivery(consumer, chain_id, delivery.height);
+                        }
+                        let state = (client, notifications, next_height, pending, consumer);
+                        return Some((delivery, state));
+                    }
+                    let tip = match client.chain_info().await {
+                        Ok(info) => info.next_block_height,
+                        Err(error) => {
+                            warn!(%chain_id, %error, "Failed to read the chain's height");
+                            return None;
+                        }
+                    };
+                    if next_height >= tip {
+                        // Wait for a block at or above the next height to be added.
+                        loop {
+                            let notification = notifications.next().await?;
+                            if matches!(
+                                notification.reason,
+                                Reason::NewBlock { height, .. } if height >= next_height
+                            ) {
+                                break;
+                            }
+                        }
+                        continue;
+                    }
+                    let heights = (next_height.0..tip.0)
+                        .take(BLOCK_STREAM_BATCH_SIZE)
+                        .map(BlockHeight)
+                        .collect::<Vec<_>>();
+                    let certificates = match client
+                        .storage_client()
+                        .read_certificates_by_heights(chain_id, &heights)
+                        .await
+                    {
+                        Ok(certificates) => certificates,
+                        Err(error) => {
+                            warn!(%chain_id, %error, "Failed to read confirmed blocks");
+                            return None;
+                        }
+                    };
+                    for (height, certificate) in heights.into_iter().zip(certificates) {
+                        let Some(certificate) = certificate else {
+                            // Blocks are delivered without gaps, so the stream ends here.
+                            warn!(%chain_id, %height, "Confirmed block is missing locally");
+                            return None;
+                        };
+                        pending.push_back(BlockDelivery {
+                            chain_id,
+                            height,
+                            hash: certificate.hash(),
+                            block: Arc::new(certificate.value().clone()),
+                        });
+                        next_height = height.try_add_one().ok()?;
+                    }
+                }
+            },
+        ))
+    }
+}
+
+impl EventFilter {
+    fn into_index_key(self, stream_id: StreamId) -> Result<EventIndexKey, Error> {
+        Ok(EventIndexKey {
+            stream_id,
+            field: self.field,
+            key: hex::decode(self.key)?,
+        })
+    }
+}
+
+/// Reads at most `limit` events of a stream, starting at the event with index `start_index`,
+/// and only the ones indexed with the given key if any.
+async fn read_application_events<Env: linera_core::Environment>(
+    client: &ChainClient<Env>,
+    stream_id: &StreamId,
+    index_key: Option<EventIndexKey>,
+    start_index: u32,
+    limit: u32,
+) -> Result<Vec<IndexAndEvent>, Error> {
+    Ok(match index_key {
+        Some(index_key) => {
+            client
+                .indexed_events_from_index(index_key, start_index, limit)
+                .await?
+        }
+        None => {
+            let mut events = client
+                .events_from_index(stream_id.clone(), start_index)
+                .await?;
+            events.truncate(limit as usize);
+            events
+        }
+    })
+}
+
+/// Returns the BCS-encoded formats of an application's ABI, if its module was published
+/// with them and they are available locally.
+async fn application_formats<Env: linera_core::Environment>(
+    client: &ChainClient<Env>,
+    application_id: ApplicationId,
+) -> Result<Option<linera_storage::Arc<Blob>>, Error> {
+    let storage = client.storage_client();
+    let Some(description) = storage
+        .read_blob(application_id.description_blob_id())
+        .await?
+    else {
+        return Ok(None);
+    };
+    let description = bcs::from_bytes::<ApplicationDescription>(description.bytes())?;
+    let Some(formats_blob_hash) = description.module_id.formats_blob_hash else {
+        return Ok(None);
+    };
+    let blob_id = BlobId::new(formats_blob_hash, BlobType::ApplicationFormats);
+    Ok(storage.read_blob(blob_id).await?)
+}
+
+/// Decodes the event values with the given formats blob, if any.
+fn decode_events(formats: Option<&Blob>, events: Vec<IndexAndEvent>) -> Vec<ApplicationEvent> {
+    let formats = formats.and_then(|blob| match bcs::from_bytes::<Formats>(blob.bytes()) {
+        Ok(formats) => Some(formats),
+        Err(error) => {
+            warn!(blob_id = %blob.id(), %error, "Malformed application formats");
+            None
+        }
+    });
+    events
+        .into_iter()
+        .map(|event| {
+            let value = formats.as_ref().and_then(|formats| {
+                match formats.decode_event_value(&event.event) {
+                    Ok(value) => Some(RawJson(value.to_string())),
+                    Err(error) => {
+                        debug!(index = event.index, %error, "Failed to decode event value");
+                        None
+                    }
+                }
+            });
+            ApplicationEvent {
+                index: event.index,
+                bytes: event.event,
+                value,
+            }
+        })
+        .collect()
 }
 
 impl<C> MutationRoot<C>
@@ -784,6 +1170,63 @@ and no system operations."
         })
         .await
     }
+
+    /// Reloads the service of an application without restarting the node service.
+    /// Subsequent queries run the service bytecode of the given module, or the
+    /// application's own service bytecode if no module is given. The module is loaded
+    /// first and must use the application's VM. Services called as oracles in blocks
+    /// always run the application's own service bytecode.
+    async fn reload_application_service(
+        &self,
+        #[graphql(desc = "The application whose service is reloaded")]
+        application_id: ApplicationId,
+        #[graphql(desc = "The module providing the new service bytecode")] module_id: Option<
+            ModuleId,
+        >,
+    ) -> Result<ApplicationId, Error> {
+        let storage = self.context.lock().await.storage().clone();
+        storage
+            .override_service_module(application_id, module_id)
+            .await?;
+        if let Some(cache) = &self.query_cache {
+            cache.invalidate_application(&application_id);
+        }
+        info!("Reloaded the service of application {application_id}");
+        Ok(application_id)
+    }
+
+    /// Acknowledges that a consumer has processed the blocks of a chain up to and including
+    /// the given height. Streams opened later by the consumer resume after that block.
+    /// Fails if the block was not delivered to the consumer by a `confirmedBlocks` stream,
+    /// or if the consumer already acknowledged a later block. Returns the height of the next
+    /// block the consumer will receive.
+    async fn acknowledge_blocks(
+        &self,
+        #[graphql(desc = "The consumer acknowledging the blocks")] consumer: String,
+        #[graphql(desc = "The chain of the blocks")] chain_id: ChainId,
+        #[graphql(desc = "The height of the last processed block")] height: BlockHeight,
+    ) -> Result<BlockHeight, Error> {
+        let cursors = self
+            .consumer_cursors
+            .as_ref()
+            .ok_or_else(|| Error::new("consumer cursors are not enabled"))?;
+        Ok(cursors.acknowledge(&consumer, chain_id, height).await?)
+    }
+
+    /// Submits the signature of a pending signing request, made by an external EVM wallet
+    /// with `personal_sign`.
+    async fn submit_signature(
+        &self,
+        #[graphql(desc = "The ID of the signing request")] id: u64,
+        #[graphql(desc = "The hex-encoded signature")] signature: String,
+    ) -> Result<bool, Error> {
+        let relay = self
+            .signing_relay
+            .as_ref()
+            .ok_or_else(|| Error::new("no owner signs with an external wallet"))?;
+        relay.submit(id, &signature)?;
+        Ok(true)
+    }
 }
 
 #[async_graphql::Object(cache_control(no_cache))]
@@ -858,7 +1301,7 @@ where
             None => client.chain_info().await?.block_hash,
         };
         if let Some(hash) = hash {
-            Ok(Some(client.read_confirmed_block(hash).await?))
+            Ok(Some(client.read_or_download_confirmed_block(hash).await?))
         } else {
             Ok(None)
         }
@@ -880,6 +1323,152 @@ where
             .await?)
     }
 
+    /// Returns the events of a stream that their application indexed with the given value
+    /// of a field, e.g. all `Transfer` events whose `to` field is a given account.
+    #[expect(clippy::too_many_arguments)]
+    async fn indexed_events(
+        &self,
+        chain_id: ChainId,
+        stream_id: StreamId,
+        #[graphql(desc = "The indexed field, e.g. `to`")] field: String,
+        #[graphql(desc = "The hex-encoded key the events were indexed with")] key: String,
+        #[graphql(desc = "The number of matching events to skip")] start: Option<u32>,
+        #[graphql(desc = "The maximum number of events to return")] limit: Option<u32>,
+    ) -> Result<Vec<IndexAndEvent>, Error> {
+        let index_key = EventIndexKey {
+            stream_id,
+            field,
+            key: hex::decode(key)?,
+        };
+        Ok(self
+            .context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .await?
+            .indexed_events(index_key, start.unwrap_or(0), limit.unwrap_or(100))
+            .await?)
+    }
+
+    /// Returns the events of an application's stream, decoded with the formats of the
+    /// application's ABI if it was published with them. With a `filter`, only the events
+    /// the application indexed with the given field value are returned.
+    #[expect(clippy::too_many_arguments)]
+    async fn events(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        #[graphql(desc = "The name of the stream")] stream_name: String,
+        #[graphql(desc = "The indexed field value the events must have")] filter: Option<
+            EventFilter,
+        >,
+        #[graphql(desc = "The index of the first event to return")] start_index: Option<u32>,
+        #[graphql(desc = "The maximum number of events to return")] limit: Option<u32>,
+    ) -> Result<Vec<ApplicationEvent>, Error> {
+        let client = self
+            .context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .await?;
+        let stream_id = StreamId {
+            application_id: application_id.into(),
+            stream_name: StreamName::from(stream_name),
+        };
+        let index_key = filter
+            .map(|filter| filter.into_index_key(stream_id.clone()))
+            .transpose()?;
+        let events = read_application_events(
+            &client,
+            &stream_id,
+            index_key,
+            start_index.unwrap_or(0),
+            limit.unwrap_or(100),
+        )
+        .await?;
+        let formats = application_formats(&client, application_id).await?;
+        Ok(decode_events(formats.as_deref(), events))
+    }
+
+    /// Returns the events of an application's stream from the local event index, without
+    /// contacting validators. Only the applications given with `--index-events-of` are
+    /// indexed, and only the latest events of each stream are kept.
+    async fn local_events(
+        &self,
+        chain_id: ChainId,
+        application_id: ApplicationId,
+        #[graphql(desc = "The name of the stream")] stream_name: String,
+        #[graphql(desc = "The index of the first event to return")] start_index: Option<u32>,
+        #[graphql(desc = "The maximum number of events to return")] limit: Option<u32>,
+    ) -> Result<Vec<LocalEvent>, Error> {
+        let event_index = self
+            .event_index
+            .as_ref()
+            .filter(|event_index| event_index.indexes(&application_id))
+            .ok_or_else(|| Error::new(format!("the events of {application_id} are not indexed")))?;
+        let stream_id = StreamId {
+            application_id: application_id.into(),
+            stream_name: StreamName::from(stream_name),
+        };
+        let events = event_index
+            .events(
+                chain_id,
+                stream_id,
+                start_index.unwrap_or(0),
+                limit.unwrap_or(100),
+            )
+            .await?;
+        let client = self
+            .context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .await?;
+        let formats = application_formats(&client, application_id).await?;
+        let decoded = decode_events(
+            formats.as_deref(),
+            events
+                .iter()
+                .map(|event| IndexAndEvent {
+                    index: event.index,
+                    event: event.value.clone(),
+                })
+                .collect(),
+        );
+        Ok(events
+            .into_iter()
+            .zip(decoded)
+            .map(|(event, decoded)| LocalEvent {
+                index: event.index,
+                height: event.height,
+                timestamp: event.timestamp,
+                bytes: decoded.bytes,
+                value: decoded.value,
+            })
+            .collect())
+    }
+
+    /// Recommends a budget for the fees of a block with the given operations, based on
+    /// their local execution and on the fees of the latest blocks of the chain.
+    async fn suggested_fee_budget(
+        &self,
+        chain_id: ChainId,
+        #[graphql(desc = "The operations, hex-encoded in BCS")] operations: Vec<String>,
+    ) -> Result<FeeSuggestion, Error> {
+        let operations = operations
+            .iter()
+            .map(|operation| Ok(bcs::from_bytes::<Operation>(&hex::decode(operation)?)?))
+            .collect::<Result<Vec<_>, Error>>()?;
+        Ok(self
+            .context
+            .lock()
+            .await
+            .make_chain_client(chain_id)
+            .await?
+            .suggested_fee_budget(operations)
+            .await?)
+    }
+
     async fn blocks(
         &self,
         from: Option<CryptoHash>,
@@ -906,7 +1495,7 @@ where
             let Some(next_hash) = hash else {
                 break;
             };
-            let value = client.read_confirmed_block(next_hash).await?;
+            let value = client.read_or_download_confirmed_block(next_hash).await?;
             hash = value.block().header.previous_block_hash;
             values.push(value);
         }
@@ -939,6 +1528,14 @@ where
         let blob = client.storage_client().read_blob(blob_id).await?;
         Ok(blob.map(|b| b.bytes().to_vec()))
     }
+
+    /// Returns the requests waiting for a signature by an external EVM wallet.
+    async fn signing_requests(&self) -> Vec<SigningRequest> {
+        self.signing_relay
+            .as_ref()
+            .map(SigningRelay::pending_requests)
+            .unwrap_or_default()
+    }
 }
 
 // What follows is a hack to add a chain_id field to `ChainStateView` based on
@@ -1258,6 +1855,25 @@ impl QueryResponseCache {
         }
     }
 
+    /// Removes the cached responses of the given application on all chains, e.g. after
+    /// its service was reloaded.
+    fn invalidate_application(&self, app_id: &ApplicationId) {
+        for mutex in self.chains.pin().values() {
+            let mut cache = mutex.lock().expect("LRU mutex poisoned");
+            let keys = cache
+                .lru
+                .iter()
+                .filter(|((id, _), _)| id == app_id)
+                .map(|(key, _)| key.clone())
+                .collect::<Vec<_>>();
+            for key in keys {
+                cache.lru.pop(&key);
+                #[cfg(with_metrics)]
+                query_cache_metrics::QUERY_CACHE_ENTRIES.dec();
+            }
+        }
+    }
+
     /// Called when a `NewBlock` notification arrives. Records the new
     /// `next_block_height` and clears all cached responses for the chain.
     fn invalidate_chain(&self, chain_id: &ChainId, next_block_height: BlockHeight) {
@@ -1316,6 +1932,29 @@ where
     enable_memory_profiling: bool,
     /// If true, do not start the chain listener; serve queries from local state only.
     pause: bool,
+    /// If true, reload the service of applications whose module is republished with the
+    /// same contract.
+    hot_reload_services: bool,
+    /// The optional bridge mapping incoming HTTP requests to application mutations.
+    webhooks: Option<Arc<WebhookBridge>>,
+    /// The durable cursors of the consumers of block streams, if enabled.
+    consumer_cursors: Option<Arc<ConsumerCursors>>,
+    /// The relay through which external EVM wallets sign, if some owners use one.
+    signing_relay: Option<SigningRelay>,
+    /// The tokens required to run mutations, if mutations are restricted.
+    access_control: Option<Arc<AccessControl>>,
+    /// The local index of application events, if some applications are indexed.
+    event_index: Option<Arc<EventIndexOf<C>>>,
+    /// If true, serve the block explorer page at `/explorer`.
+    explorer: bool,
+    /// The port on which to receive blocks from a block exporter, if any.
+    exporter_feed_port: Option<NonZeroU16>,
+    /// The limits on queries and the persisted queries, if any.
+    query_limits: Option<Arc<QueryLimits>>,
+    /// The certificate with which to serve HTTPS, if any.
+    tls: Option<RustlsConfig>,
+    /// The API keys required on all requests, if any.
+    api_keys: Option<Arc<ApiKeys>>,
 }
 
 impl<C> Clone for NodeService<C>
@@ -1336,6 +1975,17 @@ where
             cancellation_token: self.cancellation_token.clone(),
             enable_memory_profiling: self.enable_memory_profiling,
             pause: self.pause,
+            hot_reload_services: self.hot_reload_services,
+            webhooks: self.webhooks.clone(),
+            consumer_cursors: self.consumer_cursors.clone(),
+            signing_relay: self.signing_relay.clone(),
+            access_control: self.access_control.clone(),
+            event_index: self.event_index.clone(),
+            explorer: self.explorer,
+            exporter_feed_port: self.exporter_feed_port,
+            query_limits: self.query_limits.clone(),
+            tls: self.tls.clone(),
+            api_keys: self.api_keys.clone(),
         }
     }
 }
@@ -1362,6 +2012,9 @@ where
         cancellation_token: CancellationToken,
         enable_memory_profiling: bool,
         pause: bool,
+        hot_reload_services: bool,
+        webhooks: Option<Arc<WebhookBridge>>,
+        consumer_cursors: Option<Arc<ConsumerCursors>>,
     ) -> Self {
         let query_cache = query_cache_size.map(|size| Arc::new(QueryResponseCache::new(size)));
         Self {
@@ -1377,9 +2030,74 @@ where
             cancellation_token,
             enable_memory_profiling,
             pause,
+            hot_reload_services,
+            webhooks,
+            consumer_cursors,
+            signing_relay: None,
+            access_control: None,
+            event_index: None,
+            explorer: false,
+            exporter_feed_port: None,
+            query_limits: None,
+            tls: None,
+            api_keys: None,
         }
     }
 
+    /// Sets the relay through which external EVM wallets sign. The service then lists the
+    /// pending requests, accepts signatures, and serves the signing page at `/signing`.
+    pub fn with_signing_relay(mut self, signing_relay: Option<SigningRelay>) -> Self {
+        self.signing_relay = signing_relay;
+        self
+    }
+
+    /// Restricts mutations to the bearers of the access tokens listed in a file. Queries
+    /// remain open to everyone.
+    pub fn with_access_control(mut self, access_control: Option<Arc<AccessControl>>) -> Self {
+        self.access_control = access_control;
+        self
+    }
+
+    /// Maintains the given local index of application events from the blocks of the
+    /// wallet's chains, and serves it with the `localEvents` query.
+    pub fn with_event_index(mut self, event_index: Option<EventIndexOf<C>>) -> Self {
+        self.event_index = event_index.map(Arc::new);
+        self
+    }
+
+    /// Serves a small block explorer page at `/explorer`, which renders the wallet's chains
+    /// through the GraphQL API.
+    pub fn with_explorer(mut self, explorer: bool) -> Self {
+        self.explorer = explorer;
+        self
+    }
+
+    /// Keeps the wallet's chains up to date with the blocks received from a block exporter
+    /// on the given port, see [`ExporterFeed`].
+    pub fn with_exporter_feed(mut self, port: Option<NonZeroU16>) -> Self {
+        self.exporter_feed_port = port;
+        self
+    }
+
+    /// Rejects the queries of the node service and of applications that are too deep or
+    /// too complex, and serves the persisted queries, exempt from these limits.
+    pub fn with_query_limits(mut self, query_limits: Option<QueryLimits>) -> Self {
+        self.query_limits = query_limits.map(Arc::new);
+        self
+    }
+
+    /// Serves HTTPS with the given certificate, instead of HTTP.
+    pub fn with_tls(mut self, tls: Option<RustlsConfig>) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Rejects the requests without one of the given API keys, except the health check.
+    pub fn with_api_keys(mut self, api_keys: Option<ApiKeys>) -> Self {
+        self.api_keys = api_keys.map(Arc::new);
+        self
+    }
+
     /// Returns the socket address on which the metrics endpoint is served.
     #[cfg(with_metrics)]
     pub fn metrics_address(&self) -> SocketAddr {
@@ -1388,18 +2106,25 @@ where
 
     /// Builds the GraphQL schema served by the node service.
     pub fn schema(&self) -> NodeServiceSchema<C> {
+        self.schema_with_mutations(!self.read_only)
+    }
+
+    fn schema_with_mutations(&self, with_mutations: bool) -> NodeServiceSchema<C> {
         let query = QueryRoot {
             context: Arc::clone(&self.context),
             port: self.port,
             default_chain: self.default_chain,
+            signing_relay: self.signing_relay.clone(),
+            event_index: self.event_index.clone(),
         };
         let subscription = SubscriptionRoot {
             context: Arc::clone(&self.context),
             query_subscriptions: self.query_subscriptions.clone(),
+            consumer_cursors: self.consumer_cursors.clone(),
             cancellation_token: self.cancellation_token.clone(),
         };
 
-        if self.read_only {
+        if !with_mutations {
             NodeServiceSchema::ReadOnly(Schema::build(query, EmptyMutation, subscription).finish())
         } else {
             NodeServiceSchema::Full(
@@ -1407,6 +2132,9 @@ where
                     query,
                     MutationRoot {
                         context: Arc::clone(&self.context),
+                        query_cache: self.query_cache.clone(),
+                        consumer_cursors: self.consumer_cursors.clone(),
+                        signing_relay: self.signing_relay.clone(),
                     },
                     subscription,
                 )
@@ -1423,6 +2151,7 @@ where
         command_receiver: UnboundedReceiver<ListenerCommand>,
     ) -> Result<(), anyhow::Error> {
         let port = self.port.get();
+        let scheme = if self.tls.is_some() { "https" } else { "http" };
         let index_handler = axum::routing::get(util::graphiql).post(Self::index_handler);
         let application_handler =
             axum::routing::get(util::graphiql).post(Self::application_handler);
@@ -1435,28 +2164,65 @@ where
         )
         .await;
 
-        let base_router = Router::new()
+        let mut base_router = Router::new()
             .route("/", index_handler)
             .route(
                 "/chains/{chain_id}/applications/{application_id}",
                 application_handler,
             )
             .route("/ready", axum::routing::get(|| async { "ready!" }));
+        if self.webhooks.is_some() {
+            base_router = base_router.route(
+                "/webhooks/{name}",
+                axum::routing::post(Self::webhook_handler),
+            );
+        }
+        if let Some(relay) = &self.signing_relay {
+            base_router = base_router.nest("/signing", crate::signing_page::router(relay.clone()));
+            info!("Sign with your EVM wallet at {scheme}://localhost:{port}/signing");
+        }
+        if self.explorer {
+            base_router = base_router.nest("/explorer", crate::explorer::router());
+            info!("Block explorer: {scheme}://localhost:{port}/explorer");
+        }
+        // Changing the log filter is an administration task, not offered to the untrusted
+        // clients of a read-only service or of one restricting mutations.
+        if !self.read_only && self.access_control.is_none() {
+            base_router = base_router.merge(crate::tracing::log_filter::router());
+        }
 
-        // Create router with appropriate schema for WebSocket subscriptions.
-        let app = match self.schema() {
-            NodeServiceSchema::Full(schema) => {
-                base_router.route_service("/ws", GraphQLSubscription::new(schema))
-            }
-            NodeServiceSchema::ReadOnly(schema) => {
-                base_router.route_service("/ws", GraphQLSubscription::new(schema))
+        // Create router with appropriate schema for WebSocket subscriptions. Requests on
+        // WebSockets carry no access token, so they can't run restricted mutations.
+        let mut app =
+            match self.schema_with_mutations(!self.read_only && self.access_control.is_none()) {
+                NodeServiceSchema::Full(schema) => {
+                    base_router.route_service("/ws", GraphQLSubscription::new(schema))
+                }
+                NodeServiceSchema::ReadOnly(schema) => {
+                    base_router.route_service("/ws", GraphQLSubscription::new(schema))
+                }
             }
+            .layer(Extension(self.clone()));
+        if let Some(api_keys) = &self.api_keys {
+            app = app.layer(axum::middleware::from_fn_with_state(
+                Arc::clone(api_keys),
+                service_security::require_api_key,
+            ));
         }
-        .layer(Extension(self.clone()))
         // TODO(#551): Provide application authentication.
-        .layer(CorsLayer::permissive());
+        let app = app.layer(CorsLayer::permissive());
 
-        info!("GraphiQL IDE: http://localhost:{}", port);
+        if self.tls.is_some()
+            && self.api_keys.is_none()
+            && self.access_control.is_none()
+            && !self.read_only
+        {
+            warn!(
+                "Serving over TLS without API keys or access tokens: anyone reaching the \
+                 service can run mutations with the wallet's keys"
+            );
+        }
+        info!("GraphiQL IDE: {scheme}://localhost:{port}");
 
         // Spawn the cache invalidation listener if caching is enabled.
         if let Some(cache) = &self.query_cache {
@@ -1480,11 +2246,89 @@ where
             });
         }
 
-        let tcp_listener =
-            tokio::net::TcpListener::bind(SocketAddr::from(([0, 0, 0, 0], port))).await?;
-        let server = axum::serve(tcp_listener, app)
-            .with_graceful_shutdown(cancellation_token.clone().cancelled_owned())
-            .into_future();
+        if let Some(event_index) = &self.event_index {
+            let guard = self.context.lock().await;
+            let chain_ids: Vec<ChainId> = guard.wallet().chain_ids().try_collect().await?;
+            let (tx, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            guard.client().subscribe_extra(chain_ids.clone(), &tx);
+            drop(guard);
+            let event_index = Arc::clone(event_index);
+            tokio::spawn(async move {
+                // Index the blocks received while the service was not running first.
+                for chain_id in chain_ids {
+                    if let Err(error) = event_index.catch_up(chain_id).await {
+                        warn!(%chain_id, "Failed to index events: {error}");
+                    }
+                }
+                while let Some(notification) = receiver.recv().await {
+                    if !matches!(notification.reason, Reason::NewBlock { .. }) {
+                        continue;
+                    }
+                    if let Err(error) = event_index.catch_up(notification.chain_id).await {
+                        warn!(chain_id = %notification.chain_id, "Failed to index events: {error}");
+                    }
+                }
+            });
+        }
+
+        if self.hot_reload_services {
+            let guard = self.context.lock().await;
+            let chain_ids: Vec<ChainId> = guard.wallet().chain_ids().try_collect().await?;
+            let (tx, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+            guard.client().subscribe_extra(chain_ids, &tx);
+            drop(guard);
+            let context = Arc::clone(&self.context);
+            let query_cache = self.query_cache.clone();
+            tokio::spawn(async move {
+                while let Some(notification) = receiver.recv().await {
+                    let Reason::NewBlock { hash, .. } = notification.reason else {
+                        continue;
+                    };
+                    if let Err(error) =
+                        Self::reload_republished_services(&context, query_cache.as_deref(), hash)
+                            .await
+                    {
+                        warn!("Failed to reload application services: {error}");
+                    }
+                }
+            });
+        }
+
+        let address = SocketAddr::from(([0, 0, 0, 0], port));
+        let server = match &self.tls {
+            Some(tls) => {
+                let handle = axum_server::Handle::new();
+                tokio::spawn({
+                    let handle = handle.clone();
+                    let cancelled = cancellation_token.clone().cancelled_owned();
+                    async move {
+                        cancelled.await;
+                        handle.graceful_shutdown(None);
+                    }
+                });
+                axum_server::bind_rustls(address, tls.clone())
+                    .handle(handle)
+                    .serve(app.into_make_service())
+                    .boxed()
+            }
+            None => {
+                let tcp_listener = tokio::net::TcpListener::bind(address).await?;
+                axum::serve(tcp_listener, app)
+                    .with_graceful_shutdown(cancellation_token.clone().cancelled_owned())
+                    .into_future()
+                    .boxed()
+            }
+        };
+
+        if let Some(port) = self.exporter_feed_port {
+            let feed = ExporterFeed::new(self.context.lock().await.client().clone());
+            let cancellation_token = cancellation_token.clone();
+            tokio::spawn(async move {
+                if let Err(error) = feed.run(port, cancellation_token).await {
+                    error!("Failed to receive blocks from the block exporter: {error}");
+                }
+            });
+        }
 
         if self.pause {
             info!("Running in paused mode: chain synchronization is disabled");
@@ -1504,13 +2348,86 @@ where
             let mut chain_listener = Box::pin(chain_listener).fuse();
             futures::select! {
                 result = chain_listener => result?,
-                result = Box::pin(server).fuse() => result?,
+                result = server.fuse() => result?,
             };
         }
 
         Ok(())
     }
 
+    /// Reloads the service of the applications on the wallet's chains whose contract is
+    /// the same as the one of a module published in the given block. This lets developers
+    /// republish a module with a modified service and query it right away.
+    async fn reload_republished_services(
+        context: &Mutex<C>,
+        query_cache: Option<&QueryResponseCache>,
+        block_hash: CryptoHash,
+    ) -> Result<(), anyhow::Error> {
+        let guard = context.lock().await;
+        let storage = guard.storage().clone();
+        let Some(block) = storage.read_confirmed_block(block_hash).await? else {
+            return Ok(());
+        };
+        let module_ids = block
+            .block()
+            .body
+            .operations()
+            .filter_map(|operation| match operation {
+                Operation::System(operation) => match operation.as_ref() {
+                    SystemOperation::PublishModule { module_id } => Some(*module_id),
+                    _ => None,
+                },
+                Operation::User { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        if module_ids.is_empty() {
+            return Ok(());
+        }
+        let chain_ids: Vec<ChainId> = guard.wallet().chain_ids().try_collect().await?;
+        let mut clients = Vec::new();
+        for chain_id in chain_ids {
+            clients.push(guard.make_chain_client(chain_id).await?);
+        }
+        drop(guard);
+
+        for client in clients {
+            let applications = client
+                .chain_state_view()
+                .await?
+                .execution_state
+                .list_applications()
+                .await?;
+            for (application_id, description) in applications {
+                for module_id in &module_ids {
+                    if description.module_id.contract_blob_hash != module_id.contract_blob_hash
+                        || description.module_id.vm_runtime != module_id.vm_runtime
+                    {
+                        continue;
+                    }
+                    let service_module =
+                        (description.module_id != *module_id).then_some(*module_id);
+                    let current_module = storage
+                        .service_module_override(&application_id)
+                        .map(|(module_id, _)| module_id);
+                    if current_module == service_module {
+                        continue;
+                    }
+                    storage
+                        .override_service_module(application_id, service_module)
+                        .await?;
+                    if let Some(cache) = query_cache {
+                        cache.invalidate_application(&application_id);
+                    }
+                    info!(
+                        "Reloaded the service of application {application_id} \
+                         from module {module_id}"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Handles service queries for user applications (including mutations).
     async fn handle_service_request(
         &self,
@@ -1518,6 +2435,7 @@ where
         request: Vec<u8>,
         chain_id: ChainId,
         block_hash: Option<CryptoHash>,
+        operations_allowed: bool,
     ) -> Result<Vec<u8>, NodeServiceError> {
         // Only cache read-only queries against the latest state (block_hash == None).
         let cache = block_hash
@@ -1568,6 +2486,9 @@ where
         if self.read_only {
             return Err(NodeServiceError::ReadOnlyModeOperationsNotAllowed);
         }
+        if !operations_allowed {
+            return Err(AccessError::MissingToken.into());
+        }
 
         trace!("Query requested a new block with operations: {operations:?}");
         let client = self
@@ -1637,13 +2558,39 @@ where
     }
 
     /// Executes a GraphQL query and generates a response for our `Schema`.
-    async fn index_handler(service: Extension<Self>, request: GraphQLRequest) -> GraphQLResponse {
-        service
-            .0
-            .schema()
-            .execute(request.into_inner())
-            .await
-            .into()
+    async fn index_handler(
+        service: Extension<Self>,
+        headers: HeaderMap,
+        request: GraphQLRequest,
+    ) -> GraphQLResponse {
+        let mut request = request.into_inner();
+        if let Some(query_limits) = &service.0.query_limits {
+            if let Err(error) = query_limits.check(&mut request) {
+                return Response::from_errors(vec![ServerError::new(error.to_string(), None)])
+                    .into();
+            }
+        }
+        if let Err(error) = service.0.authorize_system_mutations(&headers, &request) {
+            return Response::from_errors(vec![ServerError::new(error.to_string(), None)]).into();
+        }
+        service.0.schema().execute(request).await.into()
+    }
+
+    /// Checks that the request's access token allows the mutations of the node service in
+    /// the request, if mutations are restricted.
+    fn authorize_system_mutations(
+        &self,
+        headers: &HeaderMap,
+        request: &Request,
+    ) -> Result<(), AccessError> {
+        let Some(access_control) = &self.access_control else {
+            return Ok(());
+        };
+        let mut targets = access_control::mutation_targets(request, None, None)?;
+        for target in &mut targets {
+            target.chain_id = target.chain_id.or(self.default_chain);
+        }
+        access_control.authorize(authorization(headers), &targets)
     }
 
     /// Executes a GraphQL query against an application.
@@ -1652,10 +2599,28 @@ where
     async fn application_handler(
         Path((chain_id, application_id)): Path<(String, String)>,
         service: Extension<Self>,
+        headers: HeaderMap,
         request: String,
     ) -> Result<Vec<u8>, NodeServiceError> {
         let chain_id: ChainId = chain_id.parse().map_err(NodeServiceError::InvalidChainId)?;
         let application_id: ApplicationId = application_id.parse()?;
+        let request = match &service.0.query_limits {
+            Some(query_limits) => query_limits.check_json(&request)?.unwrap_or(request),
+            None => request,
+        };
+        let mut operations_allowed = true;
+        if let Some(access_control) = &service.0.access_control {
+            let graphql_request = serde_json::from_str::<Request>(&request)
+                .map_err(|error| AccessError::InvalidRequest(error.to_string()))?;
+            let targets = access_control::mutation_targets(
+                &graphql_request,
+                Some(chain_id),
+                Some(application_id),
+            )?;
+            access_control.authorize(authorization(&headers), &targets)?;
+            // Only authorized mutations may schedule operations.
+            operations_allowed = !targets.is_empty();
+        }
 
         debug!(
             %chain_id,
@@ -1665,22 +2630,67 @@ where
         );
         let response = service
             .0
-            .handle_service_request(application_id, request.into_bytes(), chain_id, None)
+            .handle_service_request(
+                application_id,
+                request.into_bytes(),
+                chain_id,
+                None,
+                operations_allowed,
+            )
             .await?;
 
         Ok(response)
     }
+
+    /// Executes an authenticated request of the webhook bridge.
+    async fn webhook_handler(
+        Path(name): Path<String>,
+        service: Extension<Self>,
+        headers: HeaderMap,
+        body: Bytes,
+    ) -> Result<Vec<u8>, NodeServiceError> {
+        let Some(webhooks) = &service.0.webhooks else {
+            return Err(WebhookError::UnknownRoute(name).into());
+        };
+        debug!(%name, "processing webhook request");
+        webhooks
+            .handle(&name, &headers, &body, |request| {
+                service.0.handle_service_request(
+                    request.application_id,
+                    request.request,
+                    request.chain_id,
+                    None,
+                    true,
+                )
+            })
+            .await
+    }
+}
+
+/// Returns the value of the `Authorization` header, if any.
+fn authorization(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
 }
 
 #[cfg(test)]
 mod tests {
+    use axum::{
+        body::Body,
+        http::{header, Request, StatusCode},
+        Router,
+    };
     use linera_base::{
         crypto::CryptoHash,
-        data_types::BlockHeight,
-        identifiers::{ApplicationId, ChainId},
+        data_types::{Blob, BlockHeight},
+        identifiers::{ApplicationId, ChainId, IndexAndEvent},
     };
+    use linera_sdk::formats::Formats;
+    use serde_json::json;
+    use tower::Service as _;
 
-    use super::QueryResponseCache;
+    use super::{decode_events, QueryResponseCache};
 
     fn test_chain(n: u64) -> ChainId {
         ChainId(CryptoHash::test_hash(format!("chain-{n}")))
@@ -1785,4 +2795,58 @@ mod tests {
         // The stale insert should have been rejected.
         assert!(cache.get(chain, &app, b"q").is_none());
     }
+
+    #[test]
+    fn events_decoded_with_formats() {
+        let formats: Formats = serde_json::from_value(json!({
+            "REGISTRY": {},
+            "OPERATION": "UNIT",
+            "RESPONSE": "UNIT",
+            "MESSAGE": "UNIT",
+            "EVENT_VALUE": "U64",
+        }))
+        .unwrap();
+        let blob = Blob::new_application_formats(bcs::to_bytes(&formats).unwrap());
+        let events = vec![
+            IndexAndEvent {
+                index: 3,
+                event: bcs::to_bytes(&42u64).unwrap(),
+            },
+            IndexAndEvent {
+                index: 4,
+                event: vec![1],
+            },
+        ];
+
+        let decoded = decode_events(Some(&blob), events.clone());
+        assert_eq!(decoded[0].index, 3);
+        assert_eq!(decoded[0].value.as_ref().unwrap().0, "42");
+        // Events that do not match the formats are still returned, undecoded.
+        assert_eq!(decoded[1].bytes, vec![1]);
+        assert!(decoded[1].value.is_none());
+
+        let raw = decode_events(None, events);
+        assert!(raw.iter().all(|event| event.value.is_none()));
+    }
+
+    #[tokio::test]
+    async fn explorer_page_served_where_nested() {
+        let mut router = Router::new().nest("/explorer", crate::explorer::router());
+        let get = |uri: &str| Request::builder().uri(uri).body(Body::empty()).unwrap();
+
+        let response = router.call(get("/explorer")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/html"));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let page = String::from_utf8(body.to_vec()).unwrap();
+        assert!(page.contains("<title>Linera explorer</title>"));
+
+        let response = router.call(get("/explorer/missing")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
 }
@@ -12,19 +12,48 @@ use cargo_toml::Manifest;
 use convert_case::{Case, Casing};
 use current_platform::CURRENT_PLATFORM;
 use fs_err::File;
-use tracing::debug;
+use linera_base::vm::VmRuntime;
+use tracing::{debug, info};
+
+/// The path at which the workspace is placed for reproducible builds, so that the
+/// bytecode does not depend on where the sources were checked out.
+const REPRODUCIBLE_BUILD_ROOT: &str = "/build";
+
+/// The directory where Foundry writes the artifacts of an EVM project.
+const FOUNDRY_OUT_DIR: &str = "out";
+
+/// The file where the code coverage of the tests is written, in the LCOV format.
+const COVERAGE_FILE: &str = "lcov.info";
+
+/// The directory where the failing tests of a project are recorded.
+const REPLAY_DIR: &str = "replays";
+
+/// The environment variable that makes `linera_sdk::test::TestValidator` record the failing
+/// tests into the given directory.
+const RECORD_DIR_VARIABLE: &str = "LINERA_TEST_RECORD_DIR";
+
+/// The environment variable that makes `linera_sdk::test::TestValidator` replay the given
+/// recording.
+const REPLAY_VARIABLE: &str = "LINERA_TEST_REPLAY";
 
 /// A Linera application project on disk, rooted at a given directory.
+///
+/// Wasm projects are Rust crates built with Cargo; EVM projects are Solidity contracts
+/// built with Foundry.
 pub struct Project {
     root: PathBuf,
+    vm_runtime: VmRuntime,
 }
 
 impl Project {
     /// Creates a new application project from the template, scaffolding its files.
+    ///
+    /// The `linera_root` is only used by Wasm projects, to depend on a local `linera-sdk`.
     pub fn create_new(
         name: &str,
         linera_root: Option<&Path>,
         dir: Option<PathBuf>,
+        vm_runtime: VmRuntime,
     ) -> Result<Self> {
         ensure!(
             !name.contains(std::path::is_separator),
@@ -49,20 +78,29 @@ impl Project {
         debug!("Creating directory at {}", root.display());
         fs_err::create_dir_all(&root)?;
 
+        match vm_runtime {
+            VmRuntime::Wasm => Self::create_wasm_project(&root, name, linera_root)?,
+            VmRuntime::Evm => Self::create_evm_project(&root, name)?,
+        }
+
+        Ok(Self { root, vm_runtime })
+    }
+
+    fn create_wasm_project(root: &Path, name: &str, linera_root: Option<&Path>) -> Result<()> {
         debug!("Creating the source directory");
-        let source_directory = Self::create_source_directory(&root)?;
+        let source_directory = Self::create_source_directory(root, "src")?;
 
         debug!("Creating the tests directory");
-        let test_directory = Self::create_test_directory(&root)?;
+        let test_directory = Self::create_source_directory(root, "tests")?;
 
         debug!("Initializing git repository");
-        Self::initialize_git_repository(&root)?;
+        Self::initialize_git_repository(root, "/target\n/replays\n/lcov.info")?;
 
         debug!("Writing Cargo.toml");
-        Self::create_cargo_toml(&root, name, linera_root)?;
+        Self::create_cargo_toml(root, name, linera_root)?;
 
         debug!("Writing rust-toolchain.toml");
-        Self::create_rust_toolchain(&root)?;
+        Self::create_rust_toolchain(root)?;
 
         debug!("Writing state.rs");
         Self::create_state_file(&source_directory, name)?;
@@ -79,10 +117,52 @@ impl Project {
         debug!("Writing single_chain.rs");
         Self::create_test_file(&test_directory, name)?;
 
-        Ok(Self { root })
+        Ok(())
+    }
+
+    fn create_evm_project(root: &Path, name: &str) -> Result<()> {
+        let contract_name = name.to_case(Case::Pascal);
+
+        debug!("Creating the source directory");
+        let source_directory = Self::create_source_directory(root, "src")?;
+
+        debug!("Creating the test directory");
+        let test_directory = Self::create_source_directory(root, "test")?;
+
+        debug!("Initializing git repository");
+        Self::initialize_git_repository(root, "/out\n/cache\n/lcov.info")?;
+
+        debug!("Writing foundry.toml");
+        Self::write_string_to_file(
+            &root.join("foundry.toml"),
+            include_str!("../template/evm/foundry.toml.template"),
+        )?;
+
+        debug!("Writing {contract_name}.sol");
+        let contract_contents = format!(
+            include_str!("../template/evm/contract.sol.template"),
+            contract_name = contract_name,
+        );
+        Self::write_string_to_file(
+            &source_directory.join(format!("{contract_name}.sol")),
+            &contract_contents,
+        )?;
+
+        debug!("Writing {contract_name}.t.sol");
+        let test_contents = format!(
+            include_str!("../template/evm/test/contract.t.sol.template"),
+            contract_name = contract_name,
+        );
+        Self::write_string_to_file(
+            &test_directory.join(format!("{contract_name}.t.sol")),
+            &test_contents,
+        )
     }
 
     /// Opens an existing application project at the given root directory.
+    ///
+    /// A directory with a `foundry.toml` file is an EVM project, and one with a
+    /// `Cargo.toml` file is a Wasm project.
     pub fn from_existing_project(root: &Path) -> Result<Self> {
         let root = root.canonicalize().with_context(|| {
             format!(
@@ -91,24 +171,81 @@ impl Project {
                 root.display()
             )
         })?;
-        ensure!(
-            root.join("Cargo.toml").exists(),
-            "No Cargo.toml found at {}. \
-             The path must point to a Rust project directory.",
-            root.display()
-        );
-        Ok(Self { root })
+        let vm_runtime = if root.join("foundry.toml").exists() {
+            VmRuntime::Evm
+        } else {
+            ensure!(
+                root.join("Cargo.toml").exists(),
+                "No Cargo.toml or foundry.toml found at {}. \
+                 The path must point to a Rust or Foundry project directory.",
+                root.display()
+            );
+            VmRuntime::Wasm
+        };
+        Ok(Self { root, vm_runtime })
+    }
+
+    /// Returns the virtual machine that runs the project's application.
+    pub fn vm_runtime(&self) -> VmRuntime {
+        self.vm_runtime
     }
 
     /// Runs the unit and integration tests of an application.
-    pub fn test(&self) -> Result<()> {
-        let tests = Command::new("cargo")
-            .arg("test")
-            .args(["--target", CURRENT_PLATFORM])
-            .current_dir(&self.root)
-            .spawn()?
-            .wait()?;
+    ///
+    /// With `coverage`, the code coverage of the tests is written to `lcov.info`. With
+    /// `record`, the failing integration tests of a Wasm project are recorded into the
+    /// `replays` directory, and `replay` runs a recorded test again, with the timestamps and
+    /// oracle responses of the recording.
+    pub fn test(&self, coverage: bool, record: bool, replay: Option<&Path>) -> Result<()> {
+        let mut command = match self.vm_runtime {
+            VmRuntime::Wasm => {
+                let mut command = Command::new("cargo");
+                if coverage {
+                    command
+                        .args(["llvm-cov", "test", "--lcov", "--output-path", COVERAGE_FILE])
+                        .args(["--target", CURRENT_PLATFORM]);
+                } else {
+                    command.arg("test").args(["--target", CURRENT_PLATFORM]);
+                }
+                command
+            }
+            VmRuntime::Evm => {
+                ensure!(
+                    !record && replay.is_none(),
+                    "Recording and replaying tests is only supported for Wasm projects"
+                );
+                let mut command = Command::new("forge");
+                if coverage {
+                    command.args(["coverage", "--report", "lcov"]);
+                } else {
+                    command.arg("test");
+                }
+                command
+            }
+        };
+        if record {
+            command.env(RECORD_DIR_VARIABLE, self.root.join(REPLAY_DIR));
+        }
+        if let Some(replay) = replay {
+            let test_name = replay
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .with_context(|| format!("invalid replay file name {}", replay.display()))?;
+            let replay = replay
+                .canonicalize()
+                .with_context(|| format!("could not find replay file {}", replay.display()))?;
+            command
+                .args([test_name, "--", "--exact"])
+                .env(REPLAY_VARIABLE, replay);
+        }
+        let tests = command.current_dir(&self.root).spawn()?.wait()?;
         ensure!(tests.success(), "tests failed");
+        if coverage {
+            info!(
+                "Coverage written to {}",
+                self.root.join(COVERAGE_FILE).display()
+            );
+        }
         Ok(())
     }
 
@@ -134,19 +271,13 @@ impl Project {
         Ok(self.root.as_path())
     }
 
-    fn create_source_directory(project_root: &Path) -> Result<PathBuf> {
-        let source_directory = project_root.join("src");
-        fs_err::create_dir_all(&source_directory)?;
-        Ok(source_directory)
-    }
-
-    fn create_test_directory(project_root: &Path) -> Result<PathBuf> {
-        let test_directory = project_root.join("tests");
-        fs_err::create_dir_all(&test_directory)?;
-        Ok(test_directory)
+    fn create_source_directory(project_root: &Path, name: &str) -> Result<PathBuf> {
+        let directory = project_root.join(name);
+        fs_err::create_dir_all(&directory)?;
+        Ok(directory)
     }
 
-    fn initialize_git_repository(project_root: &Path) -> Result<()> {
+    fn initialize_git_repository(project_root: &Path, gitignore: &str) -> Result<()> {
         let output = Command::new("git")
             .args([
                 "init",
@@ -162,7 +293,7 @@ impl Project {
             project_root.display()
         );
 
-        Self::write_string_to_file(&project_root.join(".gitignore"), "/target")
+        Self::write_string_to_file(&project_root.join(".gitignore"), gitignore)
     }
 
     fn create_cargo_toml(
@@ -286,14 +417,15 @@ impl Project {
         (linera_sdk_dep, linera_sdk_dev_dep)
     }
 
-    /// Builds the project's contract and service to Wasm, returning their bytecode paths.
+    /// Builds the project's contract and service, returning their bytecode paths.
+    ///
+    /// For an EVM project, the contract and the service are the same file: the creation
+    /// bytecode of the contract.
     pub fn build(&self, name: Option<String>) -> Result<(PathBuf, PathBuf), anyhow::Error> {
-        let name = match name {
-            Some(name) => name,
-            None => self.project_package_name()?.replace('-', "_"),
-        };
-        let contract_name = format!("{name}_contract");
-        let service_name = format!("{name}_service");
+        if self.vm_runtime == VmRuntime::Evm {
+            let bytecode_path = self.build_evm(name)?;
+            return Ok((bytecode_path.clone(), bytecode_path));
+        }
         let cargo_build = Command::new("cargo")
             .arg("build")
             .arg("--release")
@@ -305,6 +437,160 @@ impl Project {
         let build_path = self
             .workspace_root()?
             .join("target/wasm32-unknown-unknown/release");
+        self.bytecode_paths(name, &build_path)
+    }
+
+    /// Builds the project's contract and service to Wasm so that the same sources always
+    /// produce the same bytecode, returning their bytecode paths.
+    ///
+    /// With a `docker_image`, the build runs inside that image with the workspace mounted
+    /// at a fixed path. Otherwise, it runs locally with the lock file enforced and the
+    /// local paths remapped, so the toolchain pinned by `rust-toolchain.toml` must be
+    /// installed.
+    pub fn build_reproducible(
+        &self,
+        name: Option<String>,
+        docker_image: Option<&str>,
+    ) -> Result<(PathBuf, PathBuf), anyhow::Error> {
+        ensure!(
+            self.vm_runtime == VmRuntime::Wasm,
+            "Reproducible builds are only supported for Wasm projects"
+        );
+        let workspace_root = self.workspace_root()?;
+        let relative_root = self.root.strip_prefix(workspace_root)?;
+        let cargo_args = [
+            "build",
+            "--release",
+            "--locked",
+            "--target",
+            "wasm32-unknown-unknown",
+        ];
+        let mut command = match docker_image {
+            Some(image) => {
+                let mut command = Command::new("docker");
+                command
+                    .args(["run", "--rm"])
+                    .arg("--volume")
+                    .arg(format!(
+                        "{}:{REPRODUCIBLE_BUILD_ROOT}",
+                        workspace_root.display()
+                    ))
+                    .arg("--workdir")
+                    .arg(Path::new(REPRODUCIBLE_BUILD_ROOT).join(relative_root))
+                    .args(["--env", "CARGO_TARGET_DIR=target/reproducible"])
+                    .args(["--env", "CARGO_INCREMENTAL=0"])
+                    .args(["--env", "CARGO_PROFILE_RELEASE_CODEGEN_UNITS=1"])
+                    .arg(image)
+                    .arg("cargo")
+                    .args(cargo_args);
+                command
+            }
+            None => {
+                let mut rustflags = format!(
+                    "--remap-path-prefix={}={REPRODUCIBLE_BUILD_ROOT}",
+                    workspace_root.display()
+                );
+                if let Some(cargo_home) = Self::cargo_home() {
+                    rustflags.push_str(&format!(
+                        " --remap-path-prefix={}=/cargo",
+                        cargo_home.display()
+                    ));
+                }
+                let mut command = Command::new("cargo");
+                command
+                    .args(cargo_args)
+                    .env(
+                        "CARGO_TARGET_DIR",
+                        workspace_root.join("target/reproducible"),
+                    )
+                    .env("CARGO_INCREMENTAL", "0")
+                    .env("CARGO_PROFILE_RELEASE_CODEGEN_UNITS", "1")
+                    .env("RUSTFLAGS", rustflags)
+                    .current_dir(&self.root);
+                command
+            }
+        };
+        debug!("Running reproducible build: {command:?}");
+        let cargo_build = command.spawn()?.wait()?;
+        ensure!(cargo_build.success(), "reproducible build failed");
+        let build_path = workspace_root.join("target/reproducible/wasm32-unknown-unknown/release");
+        self.bytecode_paths(name, &build_path)
+    }
+
+    /// Builds the project's contracts with Foundry, and writes the creation bytecode of the
+    /// named contract next to its artifact, returning its path.
+    fn build_evm(&self, name: Option<String>) -> Result<PathBuf> {
+        let forge_build = Command::new("forge")
+            .arg("build")
+            .current_dir(&self.root)
+            .spawn()
+            .context("failed to run `forge`; is Foundry installed?")?
+            .wait()?;
+        ensure!(forge_build.success(), "build failed");
+        let name = match name {
+            Some(name) => name,
+            None => self.evm_contract_name()?,
+        };
+        let artifact_directory = self.root.join(FOUNDRY_OUT_DIR).join(format!("{name}.sol"));
+        let artifact_path = artifact_directory.join(format!("{name}.json"));
+        let artifact: serde_json::Value = serde_json::from_str(
+            &fs_err::read_to_string(&artifact_path)
+                .with_context(|| format!("no artifact for contract {name}"))?,
+        )?;
+        let bytecode = artifact["bytecode"]["object"]
+            .as_str()
+            .with_context(|| format!("no bytecode in {}", artifact_path.display()))?;
+        let bytecode = hex::decode(bytecode.trim_start_matches("0x"))?;
+        ensure!(
+            !bytecode.is_empty(),
+            "contract {name} has no bytecode; is it abstract or an interface?"
+        );
+        let bytecode_path = artifact_directory.join(format!("{name}.bin"));
+        fs_err::write(&bytecode_path, bytecode)?;
+        Ok(bytecode_path)
+    }
+
+    /// Returns the name of the contract of an EVM project: the stem of the only Solidity
+    /// file in `src`.
+    fn evm_contract_name(&self) -> Result<String> {
+        let mut names = Vec::new();
+        for entry in fs_err::read_dir(self.root.join("src"))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "sol") {
+                if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    names.push(stem.to_owned());
+                }
+            }
+        }
+        match <[String; 1]>::try_from(names) {
+            Ok([name]) => Ok(name),
+            Err(names) => anyhow::bail!(
+                "expected a single Solidity file in src, found {}; \
+                 specify the name of the contract to build",
+                names.len()
+            ),
+        }
+    }
+
+    /// Returns the directory where Cargo keeps the registry sources, if known.
+    fn cargo_home() -> Option<PathBuf> {
+        std::env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| Some(PathBuf::from(std::env::var_os("HOME")?).join(".cargo")))
+    }
+
+    /// Returns the paths of the contract and service bytecode files in `build_path`.
+    fn bytecode_paths(
+        &self,
+        name: Option<String>,
+        build_path: &Path,
+    ) -> Result<(PathBuf, PathBuf), anyhow::Error> {
+        let name = match name {
+            Some(name) => name,
+            None => self.project_package_name()?.replace('-', "_"),
+        };
+        let contract_name = format!("{name}_contract");
+        let service_name = format!("{name}_service");
         Ok((
             build_path.join(contract_name).with_extension("wasm"),
             build_path.join(service_name).with_extension("wasm"),
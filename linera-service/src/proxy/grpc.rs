@@ -40,12 +40,15 @@ use linera_rpc::{
             PendingBlobRequest, PendingBlobResult, RawCertificate, RawCertificatesBatch,
             SubscriptionRequest, VersionInfo,
         },
+        deadline::{self, GrpcDeadlineLayer},
         pool::GrpcConnectionPool,
         GrpcProtoConversionError, GrpcProxyable, GRPC_CHUNKED_MESSAGE_FILL_LIMIT,
         GRPC_MAX_MESSAGE_SIZE,
     },
 };
 use linera_sdk::{linera_base_types::Blob, views::ViewError};
+#[cfg(with_metrics)]
+use linera_service::tracing::log_filter;
 use linera_storage::{Arc as CacheArc, ResultReadCertificates, Storage};
 use prost::Message;
 use tokio::{select, task::JoinSet};
@@ -309,12 +312,12 @@ where
         let mut join_set = JoinSet::new();
 
         #[cfg(with_metrics)]
-        monitoring_server::start_metrics_with_profiling(
+        monitoring_server::start_metrics_with_routes(
             self.metrics_address(),
             shutdown_signal.clone(),
-            enable_memory_profiling,
-        )
-        .await;
+            monitoring_server::MemoryProfiling::try_activate(enable_memory_profiling).await,
+            log_filter::router(),
+        );
 
         let (health_reporter, health_service) = tonic_health::server::health_reporter();
         health_reporter
@@ -335,10 +338,12 @@ where
             // Extract OpenTelemetry context from incoming requests (trace context + baggage)
             .layer(OtelContextLayer)
             .layer(PrometheusMetricsMiddlewareLayer)
+            .layer(GrpcDeadlineLayer)
             .into_inner();
         #[cfg(not(feature = "opentelemetry"))]
         let layers = ServiceBuilder::new()
             .layer(PrometheusMetricsMiddlewareLayer)
+            .layer(GrpcDeadlineLayer)
             .into_inner();
 
         let public_server = join_set.spawn_task(
@@ -412,13 +417,24 @@ where
     /// children of the proxy's span. This enables proper distributed tracing.
     #[cfg(feature = "opentelemetry")]
     fn create_forwarding_request<T>(inner: T) -> Request<T> {
-        linera_rpc::propagation::create_request_with_current_span_context(inner)
+        Self::with_remaining_timeout(
+            linera_rpc::propagation::create_request_with_current_span_context(inner),
+        )
     }
 
     /// Creates a tonic::Request without OpenTelemetry context (feature disabled).
     #[cfg(not(feature = "opentelemetry"))]
     fn create_forwarding_request<T>(inner: T) -> Request<T> {
-        Request::new(inner)
+        Self::with_remaining_timeout(Request::new(inner))
+    }
+
+    /// Gives a forwarded request the time the client has left to wait for the response,
+    /// so that the shard stops working on it once the client gave up.
+    fn with_remaining_timeout<T>(mut request: Request<T>) -> Request<T> {
+        if let Some(timeout) = deadline::remaining_timeout() {
+            request.set_timeout(timeout);
+        }
+        request
     }
 
     /// Returns the appropriate gRPC status for the given [`ViewError`].
@@ -431,8 +447,10 @@ where
             | ViewError::InconsistentEntries
             | ViewError::PostLoadValuesError
             | ViewError::HasPendingChanges
+            | ViewError::UnsupportedSchemaVersion { .. }
             | ViewError::IoError(_) => Status::internal(err.to_string()),
             ViewError::MalformedContent(_) => Status::invalid_argument(err.to_string()),
+            ViewError::DeadlineExceeded(_) => Status::deadline_exceeded(err.to_string()),
             ViewError::KeyTooLong | ViewError::ArithmeticError(_) => {
                 Status::out_of_range(err.to_string())
             }
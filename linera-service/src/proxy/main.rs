@@ -33,6 +33,8 @@ use linera_rpc::{
     RpcMessage,
 };
 use linera_sdk::linera_base_types::Blob;
+#[cfg(with_metrics)]
+use linera_service::{alerting::AlertMonitor, tracing::log_filter};
 use linera_service::{
     storage::{CommonStorageOptions, Runnable, StorageConfig},
     util,
@@ -157,6 +159,19 @@ impl Runnable for ProxyContext {
         let shutdown_notifier = CancellationToken::new();
         tokio::spawn(listen_for_shutdown_signals(shutdown_notifier.clone()));
 
+        #[cfg(with_metrics)]
+        if let Some(alerts) = self.config.alerts.clone() {
+            let source = format!(
+                "validator-{}-proxy-{}",
+                self.config.validator.public_key, self.id
+            );
+            tokio::spawn(AlertMonitor::new(alerts, source).run(shutdown_notifier.clone()));
+        }
+        #[cfg(not(with_metrics))]
+        if self.config.alerts.is_some() {
+            tracing::warn!("Alerts are configured but this binary was built without metrics");
+        }
+
         let enable_memory_profiling = self.enable_memory_profiling;
         let proxy = Proxy::from_context(self, storage)?;
         match proxy {
@@ -320,12 +335,12 @@ where
         let address = self.get_listen_address();
 
         #[cfg(with_metrics)]
-        monitoring_server::start_metrics_with_profiling(
+        monitoring_server::start_metrics_with_routes(
             address,
             shutdown_signal.clone(),
-            enable_memory_profiling,
-        )
-        .await;
+            monitoring_server::MemoryProfiling::try_activate(enable_memory_profiling).await,
+            log_filter::router(),
+        );
 
         self.public_config
             .protocol
@@ -618,6 +633,7 @@ impl ProxyOptions {
             &format!("validator-{public_key}-proxy"),
             self.otlp_exporter_endpoint.as_deref(),
         );
+        linera_service::virtual_time::follow_from_env()?;
 
         let store_config = self
             .storage_config
@@ -0,0 +1,442 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Limits on the GraphQL queries accepted by a node service, and persisted queries.
+//!
+//! Ad-hoc queries are rejected if their fields are nested too deeply or if they select too
+//! many fields, counting those of fragments each time they are spread. Frontends can
+//! register the queries they use in a file of persisted queries: these are exempt from the
+//! limits, and clients can send their ID instead of their text, as in Apollo's persisted
+//! queries, with `"extensions": { "persistedQuery": { "sha256Hash": "<ID>" } }`.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use async_graphql::{
+    parser::types::{DocumentOperations, ExecutableDocument, Selection, SelectionSet},
+    Request, Value,
+};
+use sha2::{Digest as _, Sha256};
+
+/// Options limiting the GraphQL queries of the node service.
+#[derive(Clone, Debug, Default, clap::Args)]
+pub struct QueryLimitOptions {
+    /// Reject queries whose fields are nested deeper than this, unless they are persisted.
+    #[arg(long)]
+    pub max_query_depth: Option<usize>,
+
+    /// Reject queries selecting more fields than this, counting the fields of fragments
+    /// each time they are used, unless they are persisted.
+    #[arg(long)]
+    pub max_query_complexity: Option<usize>,
+
+    /// Path to a JSON file of persisted queries, mapping query IDs to query texts. Clients
+    /// may send the ID in `extensions.persistedQuery.sha256Hash` instead of the text, and
+    /// these queries are not limited.
+    #[arg(long)]
+    pub persisted_queries: Option<PathBuf>,
+
+    /// Only run the persisted queries.
+    #[arg(long, requires = "persisted_queries")]
+    pub only_persisted_queries: bool,
+}
+
+/// An error checking a request against the limits.
+#[derive(Debug, thiserror::Error)]
+pub enum QueryLimitError {
+    /// The request refers to an unknown persisted query.
+    #[error("PersistedQueryNotFound: no persisted query with ID {0}")]
+    PersistedQueryNotFound(String),
+    /// The request has both a persisted query ID and a different query text.
+    #[error("the query does not match the persisted query {0}")]
+    PersistedQueryMismatch(String),
+    /// Only persisted queries are allowed.
+    #[error("only persisted queries are allowed")]
+    NotPersisted,
+    /// The fields of the query are nested too deeply.
+    #[error("the query has a depth of {depth}, more than the limit of {limit}")]
+    TooDeep {
+        /// The depth of the query.
+        depth: usize,
+        /// The maximum depth.
+        limit: usize,
+    },
+    /// The query selects too many fields.
+    #[error("the query has a complexity of {complexity}, more than the limit of {limit}")]
+    TooComplex {
+        /// The complexity of the query.
+        complexity: usize,
+        /// The maximum complexity.
+        limit: usize,
+    },
+    /// The request could not be parsed.
+    #[error("invalid GraphQL request: {0}")]
+    InvalidRequest(String),
+}
+
+/// The depth and complexity of a selection set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueryCost {
+    /// The maximum number of nested fields.
+    pub depth: usize,
+    /// The number of fields, counting those of fragments each time they are spread.
+    pub complexity: usize,
+}
+
+/// The limits on the queries of a node service, and its persisted queries.
+#[derive(Debug, Default)]
+pub struct QueryLimits {
+    max_depth: Option<usize>,
+    max_complexity: Option<usize>,
+    /// The persisted queries, by ID.
+    persisted_queries: BTreeMap<String, String>,
+    /// The SHA-256 hashes of the persisted queries, to recognize them when sent in full.
+    persisted_hashes: BTreeSet<String>,
+    only_persisted: bool,
+}
+
+impl QueryLimits {
+    /// Returns the limits set by the options, or `None` if queries are not limited.
+    pub fn new(options: QueryLimitOptions) -> anyhow::Result<Option<Self>> {
+        let QueryLimitOptions {
+            max_query_depth,
+            max_query_complexity,
+            persisted_queries,
+            only_persisted_queries,
+        } = options;
+        if max_query_depth.is_none()
+            && max_query_complexity.is_none()
+            && persisted_queries.is_none()
+        {
+            return Ok(None);
+        }
+        let persisted_queries = match persisted_queries {
+            Some(path) => read_persisted_queries(&path)?,
+            None => BTreeMap::new(),
+        };
+        Ok(Some(Self {
+            max_depth: max_query_depth,
+            max_complexity: max_query_complexity,
+            persisted_hashes: persisted_queries
+                .values()
+                .map(|query| sha256(query))
+                .collect(),
+            persisted_queries,
+            only_persisted: only_persisted_queries,
+        }))
+    }
+
+    /// Replaces the ID of a persisted query with its text, and checks that the request is
+    /// allowed.
+    pub fn check(&self, request: &mut Request) -> Result<(), QueryLimitError> {
+        let persisted_id = match request.extensions.get("persistedQuery") {
+            Some(Value::Object(persisted)) => match persisted.get("sha256Hash") {
+                Some(Value::String(id)) => Some(id.clone()),
+                _ => return Err(invalid_persisted_query()),
+            },
+            Some(_) => return Err(invalid_persisted_query()),
+            None => None,
+        };
+        if let Some(query) = self.resolve(
+            &request.query,
+            request.operation_name.as_deref(),
+            persisted_id.as_deref(),
+        )? {
+            request.query = query;
+        }
+        Ok(())
+    }
+
+    /// Like [`QueryLimits::check`], for a request in JSON. Returns the request with the
+    /// text of its persisted query, if it refers to one.
+    pub fn check_json(&self, request: &str) -> Result<Option<String>, QueryLimitError> {
+        let mut json = serde_json::from_str::<serde_json::Value>(request)
+            .map_err(|error| QueryLimitError::InvalidRequest(error.to_string()))?;
+        let persisted_id = match json.pointer("/extensions/persistedQuery") {
+            Some(persisted) => Some(
+                persisted
+                    .get("sha256Hash")
+                    .and_then(serde_json::Value::as_str)
+                    .ok_or_else(invalid_persisted_query)?,
+            ),
+            None => None,
+        };
+        let query = json
+            .get("query")
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default();
+        let operation_name = json
+            .get("operationName")
+            .and_then(serde_json::Value::as_str);
+        let Some(query) = self.resolve(query, operation_name, persisted_id)? else {
+            return Ok(None);
+        };
+        json["query"] = serde_json::Value::String(query);
+        Ok(Some(json.to_string()))
+    }
+
+    /// Returns the text of the persisted query if the request only has its ID, after
+    /// checking that the request is allowed.
+    fn resolve(
+        &self,
+        query: &str,
+        operation_name: Option<&str>,
+        persisted_id: Option<&str>,
+    ) -> Result<Option<String>, QueryLimitError> {
+        if let Some(id) = persisted_id {
+            match self.persisted_queries.get(id) {
+                Some(persisted) if query.is_empty() => return Ok(Some(persisted.clone())),
+                Some(persisted) if query == persisted => return Ok(None),
+                Some(_) => return Err(QueryLimitError::PersistedQueryMismatch(id.to_string())),
+                None if query.is_empty() => {
+                    return Err(QueryLimitError::PersistedQueryNotFound(id.to_string()))
+                }
+                None => {}
+            }
+        }
+        if self.persisted_hashes.contains(&sha256(query)) {
+            return Ok(None);
+        }
+        if self.only_persisted {
+            return Err(QueryLimitError::NotPersisted);
+        }
+        if self.max_depth.is_none() && self.max_complexity.is_none() {
+            return Ok(None);
+        }
+        let cost = query_cost(query, operation_name)?;
+        if let Some(limit) = self.max_depth.filter(|limit| cost.depth > *limit) {
+            return Err(QueryLimitError::TooDeep {
+                depth: cost.depth,
+                limit,
+            });
+        }
+        if let Some(limit) = self.max_complexity.filter(|limit| cost.complexity > *limit) {
+            return Err(QueryLimitError::TooComplex {
+                complexity: cost.complexity,
+                limit,
+            });
+        }
+        Ok(None)
+    }
+}
+
+/// Reads a JSON file of persisted queries, mapping query IDs to query texts.
+pub fn read_persisted_queries(path: &Path) -> anyhow::Result<BTreeMap<String, String>> {
+    let bytes = fs_err::read(path)?;
+    let queries = serde_json::from_slice::<BTreeMap<String, String>>(&bytes).map_err(|error| {
+        anyhow::anyhow!("invalid persisted queries in {}: {error}", path.display())
+    })?;
+    for (id, query) in &queries {
+        async_graphql::parser::parse_query(query)
+            .map_err(|error| anyhow::anyhow!("invalid persisted query {id}: {error}"))?;
+    }
+    Ok(queries)
+}
+
+/// Returns the highest cost of the operations of a query that would run: the one named
+/// `operation_name` if given, or else all of them.
+pub fn query_cost(query: &str, operation_name: Option<&str>) -> Result<QueryCost, QueryLimitError> {
+    let document = async_graphql::parser::parse_query(query)
+        .map_err(|error| QueryLimitError::InvalidRequest(error.to_string()))?;
+    let operations = match &document.operations {
+        DocumentOperations::Single(operation) => vec![&operation.node],
+        DocumentOperations::Multiple(operations) => operations
+            .iter()
+            .filter(|(name, _)| {
+                operation_name.is_none_or(|operation_name| name.as_str() == operation_name)
+            })
+            .map(|(_, operation)| &operation.node)
+            .collect(),
+    };
+    let mut measure = Measure {
+        document: &document,
+        fragments: HashMap::new(),
+        visiting: HashSet::new(),
+    };
+    let mut cost = QueryCost::default();
+    for operation in operations {
+        let operation_cost = measure.selection_set(&operation.selection_set.node)?;
+        cost.depth = cost.depth.max(operation_cost.depth);
+        cost.complexity = cost.complexity.max(operation_cost.complexity);
+    }
+    Ok(cost)
+}
+
+/// Computes the cost of selection sets, remembering that of each fragment.
+struct Measure<'a> {
+    document: &'a ExecutableDocument,
+    fragments: HashMap<&'a str, QueryCost>,
+    /// The fragments being measured, to detect cycles.
+    visiting: HashSet<&'a str>,
+}
+
+impl<'a> Measure<'a> {
+    fn selection_set(
+        &mut self,
+        selection_set: &'a SelectionSet,
+    ) -> Result<QueryCost, QueryLimitError> {
+        let mut cost = QueryCost::default();
+        for selection in &selection_set.items {
+            let selection_cost = match &selection.node {
+                Selection::Field(field) => {
+                    let fields = self.selection_set(&field.node.selection_set.node)?;
+                    QueryCost {
+                        depth: fields.depth + 1,
+                        complexity: fields.complexity.saturating_add(1),
+                    }
+                }
+                Selection::InlineFragment(fragment) => {
+                    self.selection_set(&fragment.node.selection_set.node)?
+                }
+                Selection::FragmentSpread(spread) => {
+                    self.fragment(spread.node.fragment_name.node.as_str())?
+                }
+            };
+            cost.depth = cost.depth.max(selection_cost.depth);
+            cost.complexity = cost.complexity.saturating_add(selection_cost.complexity);
+        }
+        Ok(cost)
+    }
+
+    fn fragment(&mut self, name: &'a str) -> Result<QueryCost, QueryLimitError> {
+        if let Some(cost) = self.fragments.get(name) {
+            return Ok(*cost);
+        }
+        let fragment =
+            self.document.fragments.get(name).ok_or_else(|| {
+                QueryLimitError::InvalidRequest(format!("unknown fragment {name}"))
+            })?;
+        if !self.visiting.insert(name) {
+            return Err(QueryLimitError::InvalidRequest(format!(
+                "the fragment {name} spreads itself"
+            )));
+        }
+        let cost = self.selection_set(&fragment.node.selection_set.node)?;
+        self.visiting.remove(name);
+        self.fragments.insert(name, cost);
+        Ok(cost)
+    }
+}
+
+fn invalid_persisted_query() -> QueryLimitError {
+    QueryLimitError::InvalidRequest(
+        "`extensions.persistedQuery` should have a `sha256Hash` string".to_string(),
+    )
+}
+
+fn sha256(query: &str) -> String {
+    hex::encode(Sha256::digest(query.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use async_graphql::Request;
+    use serde_json::json;
+
+    use super::{query_cost, sha256, QueryCost, QueryLimitError, QueryLimits};
+
+    fn limits(only_persisted: bool) -> QueryLimits {
+        let persisted_queries = BTreeMap::from([(
+            "balances".to_string(),
+            "query { chains { list { balance } } }".to_string(),
+        )]);
+        QueryLimits {
+            max_depth: Some(2),
+            max_complexity: Some(4),
+            persisted_hashes: persisted_queries
+                .values()
+                .map(|query| sha256(query))
+                .collect(),
+            persisted_queries,
+            only_persisted,
+        }
+    }
+
+    #[test]
+    fn test_query_cost() {
+        let query = "query A { a { b c } ...F } \
+                     query B { x { y { z } } } \
+                     fragment F on Query { d { ...G ...G } } \
+                     fragment G on D { e f }";
+        assert_eq!(
+            query_cost(query, Some("A")).unwrap(),
+            QueryCost {
+                depth: 2,
+                complexity: 8
+            }
+        );
+        assert_eq!(
+            query_cost(query, None).unwrap(),
+            QueryCost {
+                depth: 3,
+                complexity: 8
+            }
+        );
+        assert!(matches!(
+            query_cost("{ ...F } fragment F on Query { a { ...F } }", None),
+            Err(QueryLimitError::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_limits() {
+        let limits = limits(false);
+        let mut request = Request::new("{ a { b } c }");
+        limits.check(&mut request).unwrap();
+
+        let mut request = Request::new("{ a { b { c } } }");
+        assert!(matches!(
+            limits.check(&mut request),
+            Err(QueryLimitError::TooDeep { depth: 3, limit: 2 })
+        ));
+        let mut request = Request::new("{ a b c d e }");
+        assert!(matches!(
+            limits.check(&mut request),
+            Err(QueryLimitError::TooComplex {
+                complexity: 5,
+                limit: 4
+            })
+        ));
+        // Persisted queries are not limited, whether sent in full or by ID.
+        let mut request = Request::new("query { chains { list { balance } } }");
+        limits.check(&mut request).unwrap();
+        let mut request = Request::new("");
+        request.extensions.insert(
+            "persistedQuery".to_string(),
+            async_graphql::Value::from_json(json!({ "sha256Hash": "balances" })).unwrap(),
+        );
+        limits.check(&mut request).unwrap();
+        assert_eq!(request.query, "query { chains { list { balance } } }");
+    }
+
+    #[test]
+    fn test_persisted_queries() {
+        let limits = limits(true);
+        let persisted = |id: &str, query: &str| {
+            json!({ "query": query, "extensions": { "persistedQuery": { "sha256Hash": id } } })
+                .to_string()
+        };
+        let resolved = limits
+            .check_json(&persisted("balances", ""))
+            .unwrap()
+            .unwrap();
+        let resolved = serde_json::from_str::<serde_json::Value>(&resolved).unwrap();
+        assert_eq!(resolved["query"], "query { chains { list { balance } } }");
+        assert!(matches!(
+            limits.check_json(&persisted("unknown", "")),
+            Err(QueryLimitError::PersistedQueryNotFound(_))
+        ));
+        assert!(matches!(
+            limits.check_json(&persisted("balances", "{ a }")),
+            Err(QueryLimitError::PersistedQueryMismatch(_))
+        ));
+        assert!(matches!(
+            limits.check_json(&json!({ "query": "{ a }" }).to_string()),
+            Err(QueryLimitError::NotPersisted)
+        ));
+    }
+}
@@ -0,0 +1,765 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    fmt,
+    future::Future,
+    io,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use hmac::{Hmac, Mac as _};
+use linera_base::crypto::{
+    BcsHashable, CryptoError, CryptoHash, ValidatorPublicKey, ValidatorSecretKey,
+    ValidatorSignature, ValidatorSigner,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt as _, AsyncWriteExt as _},
+    net::{TcpListener, TcpStream},
+    sync::Semaphore,
+};
+use tracing::{debug, info, warn};
+
+/// The maximum size of a message exchanged with the signer.
+const MAX_MESSAGE_SIZE: u32 = 1 << 20;
+
+/// How long the workers wait for the signer before giving up on a request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The maximum number of connections that a worker opens to the signer, i.e. of its
+/// concurrent requests.
+const MAX_CONNECTIONS: usize = 16;
+
+/// The minimum length of the key shared by the signer and the workers, in bytes.
+pub const MIN_AUTH_KEY_LENGTH: usize = 16;
+
+/// The size of the authentication tag appended to each message.
+const TAG_SIZE: usize = 32;
+
+const CLIENT_PROOF_LABEL: &[u8] = b"linera-signer-client";
+const SERVER_PROOF_LABEL: &[u8] = b"linera-signer-server";
+const SESSION_KEY_LABEL: &[u8] = b"linera-signer-session";
+const REQUEST_LABEL: &[u8] = b"request";
+const RESPONSE_LABEL: &[u8] = b"response";
+
+/// A request to the signer.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum SignerRequest {
+    /// Returns the public key of the validator.
+    PublicKey,
+    /// Signs the given prehashed message.
+    Sign(CryptoHash),
+    /// Returns an attestation report binding the validator's public key and the given
+    /// nonce to the enclave the signer runs in.
+    AttestationReport(CryptoHash),
+}
+
+/// A response from the signer.
+#[derive(Debug, Serialize, Deserialize)]
+pub enum SignerResponse {
+    /// The public key of the validator.
+    PublicKey(ValidatorPublicKey),
+    /// The signature of the requested prehash.
+    Signature(ValidatorSignature),
+    /// The raw attestation report, as produced by the enclave platform.
+    AttestationReport(Vec<u8>),
+    /// The request failed.
+    Error(String),
+}
+
+/// The first message of a connection, sent by the signer.
+#[derive(Serialize, Deserialize)]
+struct ServerHello {
+    nonce: [u8; 32],
+}
+
+/// The worker's answer, proving that it holds the shared key.
+#[derive(Serialize, Deserialize)]
+struct ClientHello {
+    nonce: [u8; 32],
+    proof: [u8; 32],
+}
+
+/// The signer's proof that it holds the shared key too.
+#[derive(Serialize, Deserialize)]
+struct ServerProof {
+    proof: [u8; 32],
+}
+
+/// An error communicating with the signer.
+#[derive(Debug, Error)]
+pub enum RemoteSignerError {
+    /// An I/O error on the connection to the signer.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// A message could not be encoded or decoded.
+    #[error(transparent)]
+    Bcs(#[from] bcs::Error),
+    /// A message exceeds [`MAX_MESSAGE_SIZE`].
+    #[error("message of {0} bytes exceeds the maximum size")]
+    MessageTooLarge(usize),
+    /// The signer did not answer within [`REQUEST_TIMEOUT`].
+    #[error("the signer did not answer in time")]
+    Timeout,
+    /// The shared key is shorter than [`MIN_AUTH_KEY_LENGTH`].
+    #[error(
+        "the signer's authentication key has {0} bytes but needs at least {MIN_AUTH_KEY_LENGTH}"
+    )]
+    AuthKeyTooShort(usize),
+    /// The other end does not hold the shared key, or a message was tampered with.
+    #[error("failed to authenticate the connection to the signer")]
+    Unauthenticated,
+    /// The signer failed to handle the request.
+    #[error("the signer returned an error: {0}")]
+    Signer(String),
+    /// The signer answered with a response of the wrong kind.
+    #[error("unexpected response from the signer")]
+    UnexpectedResponse,
+    /// The signer returned a signature that does not match the validator's key.
+    #[error("the signer returned an invalid signature: {0}")]
+    InvalidSignature(#[from] CryptoError),
+}
+
+/// The key shared by the signer and the validator's workers. It authenticates both ends
+/// of every connection, and each message on it.
+#[derive(Clone)]
+pub struct SignerAuthKey(Vec<u8>);
+
+impl SignerAuthKey {
+    /// Returns the key with the given bytes, which must be at least
+    /// [`MIN_AUTH_KEY_LENGTH`] long.
+    pub fn new(key: impl Into<Vec<u8>>) -> Result<Self, RemoteSignerError> {
+        let key = key.into();
+        if key.len() < MIN_AUTH_KEY_LENGTH {
+            return Err(RemoteSignerError::AuthKeyTooShort(key.len()));
+        }
+        Ok(Self(key))
+    }
+
+    fn tag(&self, label: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+        tag(&self.0, label, parts)
+    }
+
+    fn check(&self, label: &[u8], parts: &[&[u8]], tag: &[u8]) -> Result<(), RemoteSignerError> {
+        check_tag(&self.0, label, parts, tag)
+    }
+}
+
+impl FromStr for SignerAuthKey {
+    type Err = RemoteSignerError;
+
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        Self::new(key.as_bytes())
+    }
+}
+
+impl fmt::Debug for SignerAuthKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SignerAuthKey(..)")
+    }
+}
+
+fn keyed_mac(key: &[u8], label: &[u8], parts: &[&[u8]]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(label);
+    for part in parts {
+        mac.update(part);
+    }
+    mac
+}
+
+fn tag(key: &[u8], label: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut tag = [0; 32];
+    tag.copy_from_slice(&keyed_mac(key, label, parts).finalize().into_bytes());
+    tag
+}
+
+fn check_tag(
+    key: &[u8],
+    label: &[u8],
+    parts: &[&[u8]],
+    tag: &[u8],
+) -> Result<(), RemoteSignerError> {
+    keyed_mac(key, label, parts)
+        .verify_slice(tag)
+        .map_err(|_| RemoteSignerError::Unauthenticated)
+}
+
+/// How the signer obtains attestation reports for the enclave it runs in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum AttestationProvider {
+    /// No attestation reports are available.
+    #[default]
+    None,
+    /// Gramine's `/dev/attestation` interface, for SGX enclaves.
+    Gramine,
+    /// The Linux `configfs-tsm` interface, for SEV-SNP and TDX confidential VMs.
+    ConfigfsTsm,
+}
+
+impl AttestationProvider {
+    /// Produces an attestation report carrying the given report data.
+    fn report(self, report_data: &[u8; 64]) -> Result<Vec<u8>, String> {
+        let result = match self {
+            AttestationProvider::None => return Err("no attestation provider".to_string()),
+            AttestationProvider::Gramine => {
+                fs_err::write("/dev/attestation/user_report_data", report_data)
+                    .and_then(|()| fs_err::read("/dev/attestation/quote"))
+            }
+            AttestationProvider::ConfigfsTsm => {
+                let entry = Path::new("/sys/kernel/config/tsm/report")
+                    .join(format!("linera-signer-{}", std::process::id()));
+                let result = fs_err::create_dir_all(&entry)
+                    .and_then(|()| fs_err::write(entry.join("inblob"), report_data))
+                    .and_then(|()| fs_err::read(entry.join("outblob")));
+                if let Err(error) = fs_err::remove_dir(&entry) {
+                    warn!("Failed to remove the attestation report entry: {error}");
+                }
+                result
+            }
+        };
+        result.map_err(|error| error.to_string())
+    }
+}
+
+/// The key and nonce that an attestation report is bound to.
+#[derive(Serialize, Deserialize)]
+struct AttestedValidatorKey {
+    public_key: ValidatorPublicKey,
+    nonce: CryptoHash,
+}
+
+impl BcsHashable<'_> for AttestedValidatorKey {}
+
+/// Returns the report data that the signer embeds in an attestation report for the given
+/// public key and nonce: the hash of both, padded with zeros to 64 bytes. Verifiers check
+/// that the report carries this value before trusting the measurement it attests.
+pub fn attestation_report_data(public_key: ValidatorPublicKey, nonce: CryptoHash) -> [u8; 64] {
+    let hash = CryptoHash::new(&AttestedValidatorKey { public_key, nonce });
+    let mut report_data = [0; 64];
+    report_data[..32].copy_from_slice(&hash.as_bytes().0);
+    report_data
+}
+
+/// An attestation report, with what a verifier needs to check it: the report must carry
+/// the [`attestation_report_data`] of the validator's public key and the verifier's nonce.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AttestationEvidence {
+    /// The public key of the validator.
+    pub public_key: ValidatorPublicKey,
+    /// The nonce chosen by the verifier.
+    pub nonce: CryptoHash,
+    /// The report data that the report must carry, hex-encoded.
+    pub report_data: String,
+    /// The raw attestation report, hex-encoded.
+    pub report: String,
+}
+
+impl AttestationEvidence {
+    /// Returns the evidence for a report obtained for the given public key and nonce.
+    pub fn new(public_key: ValidatorPublicKey, nonce: CryptoHash, report: &[u8]) -> Self {
+        Self {
+            public_key,
+            nonce,
+            report_data: hex::encode(attestation_report_data(public_key, nonce)),
+            report: hex::encode(report),
+        }
+    }
+}
+
+/// Runs `future`, failing if it takes longer than [`REQUEST_TIMEOUT`].
+async fn with_timeout<T>(
+    future: impl Future<Output = Result<T, RemoteSignerError>>,
+) -> Result<T, RemoteSignerError> {
+    tokio::time::timeout(REQUEST_TIMEOUT, future)
+        .await
+        .map_err(|_| RemoteSignerError::Timeout)?
+}
+
+async fn write_frame(stream: &mut TcpStream, frame: &[u8]) -> Result<(), RemoteSignerError> {
+    let length = u32::try_from(frame.len())
+        .ok()
+        .filter(|length| *length <= MAX_MESSAGE_SIZE)
+        .ok_or(RemoteSignerError::MessageTooLarge(frame.len()))?;
+    stream.write_all(&length.to_be_bytes()).await?;
+    stream.write_all(frame).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+async fn read_frame(stream: &mut TcpStream) -> Result<Vec<u8>, RemoteSignerError> {
+    let length = stream.read_u32().await?;
+    if length > MAX_MESSAGE_SIZE {
+        return Err(RemoteSignerError::MessageTooLarge(length as usize));
+    }
+    let mut frame = vec![0; length as usize];
+    stream.read_exact(&mut frame).await?;
+    Ok(frame)
+}
+
+async fn write_plain(
+    stream: &mut TcpStream,
+    message: &impl Serialize,
+) -> Result<(), RemoteSignerError> {
+    write_frame(stream, &bcs::to_bytes(message)?).await
+}
+
+async fn read_plain<T: DeserializeOwned>(stream: &mut TcpStream) -> Result<T, RemoteSignerError> {
+    Ok(bcs::from_bytes(&read_frame(stream).await?)?)
+}
+
+/// An authenticated connection between a worker and the signer.
+///
+/// Both ends prove that they hold the [`SignerAuthKey`] for fresh nonces, and derive a
+/// session key from it. Each message then carries an HMAC over its direction, its sequence
+/// number and its contents, so that messages cannot be forged, replayed or reordered.
+struct Channel {
+    stream: TcpStream,
+    session_key: [u8; 32],
+    sent: u64,
+    received: u64,
+}
+
+impl Channel {
+    /// Opens a connection to the signer at `address`, as a worker.
+    async fn connect(address: &str, auth_key: &SignerAuthKey) -> Result<Self, RemoteSignerError> {
+        let mut stream = TcpStream::connect(address).await?;
+        stream.set_nodelay(true)?;
+        let ServerHello {
+            nonce: server_nonce,
+        } = read_plain(&mut stream).await?;
+        let client_nonce = rand::random::<[u8; 32]>();
+        let nonces = [&server_nonce[..], &client_nonce[..]];
+        let hello = ClientHello {
+            nonce: client_nonce,
+            proof: auth_key.tag(CLIENT_PROOF_LABEL, &nonces),
+        };
+        write_plain(&mut stream, &hello).await?;
+        let ServerProof { proof } = read_plain(&mut stream).await?;
+        auth_key.check(SERVER_PROOF_LABEL, &nonces, &proof)?;
+        Ok(Self::new(stream, auth_key.tag(SESSION_KEY_LABEL, &nonces)))
+    }
+
+    /// Accepts a connection from a worker, as the signer.
+    async fn accept(
+        mut stream: TcpStream,
+        auth_key: &SignerAuthKey,
+    ) -> Result<Self, RemoteSignerError> {
+        stream.set_nodelay(true)?;
+        let server_nonce = rand::random::<[u8; 32]>();
+        let hello = ServerHello {
+            nonce: server_nonce,
+        };
+        write_plain(&mut stream, &hello).await?;
+        let ClientHello {
+            nonce: client_nonce,
+            proof,
+        } = read_plain(&mut stream).await?;
+        let nonces = [&server_nonce[..], &client_nonce[..]];
+        auth_key.check(CLIENT_PROOF_LABEL, &nonces, &proof)?;
+        let proof = ServerProof {
+            proof: auth_key.tag(SERVER_PROOF_LABEL, &nonces),
+        };
+        write_plain(&mut stream, &proof).await?;
+        Ok(Self::new(stream, auth_key.tag(SESSION_KEY_LABEL, &nonces)))
+    }
+
+    fn new(stream: TcpStream, session_key: [u8; 32]) -> Self {
+        Self {
+            stream,
+            session_key,
+            sent: 0,
+            received: 0,
+        }
+    }
+
+    async fn send(
+        &mut self,
+        label: &[u8],
+        message: &impl Serialize,
+    ) -> Result<(), RemoteSignerError> {
+        let mut frame = bcs::to_bytes(message)?;
+        let tag = tag(
+            &self.session_key,
+            label,
+            &[&self.sent.to_be_bytes(), &frame],
+        );
+        frame.extend_from_slice(&tag);
+        self.sent += 1;
+        write_frame(&mut self.stream, &frame).await
+    }
+
+    async fn receive<T: DeserializeOwned>(&mut self, label: &[u8]) -> Result<T, RemoteSignerError> {
+        let mut frame = read_frame(&mut self.stream).await?;
+        let length = frame
+            .len()
+            .checked_sub(TAG_SIZE)
+            .ok_or(RemoteSignerError::Unauthenticated)?;
+        let tag = frame.split_off(length);
+        check_tag(
+            &self.session_key,
+            label,
+            &[&self.received.to_be_bytes(), &frame],
+            &tag,
+        )?;
+        self.received += 1;
+        Ok(bcs::from_bytes(&frame)?)
+    }
+
+    /// Sends a request to the signer and waits for its response.
+    async fn call(&mut self, request: &SignerRequest) -> Result<SignerResponse, RemoteSignerError> {
+        self.send(REQUEST_LABEL, request).await?;
+        self.receive(RESPONSE_LABEL).await
+    }
+}
+
+/// A [`ValidatorSigner`] that delegates to a signer process over TCP.
+///
+/// Requests run concurrently on a pool of up to [`MAX_CONNECTIONS`] authenticated
+/// connections, which are kept open and reused.
+pub struct RemoteValidatorSigner {
+    address: String,
+    auth_key: SignerAuthKey,
+    public_key: ValidatorPublicKey,
+    /// The open connections that no request is using.
+    idle: Mutex<Vec<Channel>>,
+    /// Bounds the number of connections.
+    connections: Semaphore,
+}
+
+impl RemoteValidatorSigner {
+    /// Connects to the signer at the given address and fetches the validator's public key.
+    pub async fn connect(
+        address: impl Into<String>,
+        auth_key: SignerAuthKey,
+    ) -> Result<Self, RemoteSignerError> {
+        let address = address.into();
+        let mut channel = with_timeout(Channel::connect(&address, &auth_key)).await?;
+        let public_key = match with_timeout(channel.call(&SignerRequest::PublicKey)).await? {
+            SignerResponse::PublicKey(public_key) => public_key,
+            SignerResponse::Error(error) => return Err(RemoteSignerError::Signer(error)),
+            _ => return Err(RemoteSignerError::UnexpectedResponse),
+        };
+        Ok(Self {
+            address,
+            auth_key,
+            public_key,
+            idle: Mutex::new(vec![channel]),
+            connections: Semaphore::new(MAX_CONNECTIONS),
+        })
+    }
+
+    /// Asks the signer for an attestation report binding the validator's public key and
+    /// `nonce` to its enclave. See [`attestation_report_data`].
+    pub async fn attestation_report(
+        &self,
+        nonce: CryptoHash,
+    ) -> Result<Vec<u8>, RemoteSignerError> {
+        match self
+            .request(&SignerRequest::AttestationReport(nonce))
+            .await?
+        {
+            SignerResponse::AttestationReport(report) => Ok(report),
+            _ => Err(RemoteSignerError::UnexpectedResponse),
+        }
+    }
+
+    /// Signs the given prehash, checking the signature against the validator's key.
+    pub async fn try_sign_prehash(
+        &self,
+        prehash: CryptoHash,
+    ) -> Result<ValidatorSignature, RemoteSignerError> {
+        let SignerResponse::Signature(signature) =
+            self.request(&SignerRequest::Sign(prehash)).await?
+        else {
+            return Err(RemoteSignerError::UnexpectedResponse);
+        };
+        signature.check_prehash(prehash, self.public_key)?;
+        Ok(signature)
+    }
+
+    /// Sends a request on an idle connection, or on a new one if there is none. If an idle
+    /// connection turns out to be lost, e.g. because the signer was restarted, retries once
+    /// on a new connection.
+    async fn request(&self, request: &SignerRequest) -> Result<SignerResponse, RemoteSignerError> {
+        let _permit = self
+            .connections
+            .acquire()
+            .await
+            .expect("the connection semaphore is never closed");
+        let idle = self
+            .idle
+            .lock()
+            .expect("the idle connections lock should not be poisoned")
+            .pop();
+        let response = match idle {
+            Some(mut channel) => match with_timeout(channel.call(request)).await {
+                Ok(response) => {
+                    self.release(channel);
+                    response
+                }
+                Err(error) => {
+                    warn!("Lost a connection to the validator signer, reconnecting: {error}");
+                    self.request_on_new_connection(request).await?
+                }
+            },
+            None => self.request_on_new_connection(request).await?,
+        };
+        match response {
+            SignerResponse::Error(error) => Err(RemoteSignerError::Signer(error)),
+            response => Ok(response),
+        }
+    }
+
+    async fn request_on_new_connection(
+        &self,
+        request: &SignerRequest,
+    ) -> Result<SignerResponse, RemoteSignerError> {
+        let mut channel = with_timeout(Channel::connect(&self.address, &self.auth_key)).await?;
+        let response = with_timeout(channel.call(request)).await?;
+        self.release(channel);
+        Ok(response)
+    }
+
+    fn release(&self, channel: Channel) {
+        self.idle
+            .lock()
+            .expect("the idle connections lock should not be poisoned")
+            .push(channel);
+    }
+}
+
+#[async_trait]
+impl ValidatorSigner for RemoteValidatorSigner {
+    fn public_key(&self) -> ValidatorPublicKey {
+        self.public_key
+    }
+
+    async fn sign_prehash(&self, prehash: CryptoHash) -> Result<ValidatorSignature, CryptoError> {
+        self.try_sign_prehash(prehash)
+            .await
+            .map_err(|error| CryptoError::SignerFailed(error.to_string()))
+    }
+}
+
+/// The signer side of the RPC, holding the validator's secret key, e.g. inside an SGX
+/// enclave or a confidential VM.
+///
+/// Messages are BCS-encoded and prefixed with their length as a big-endian `u32`. Only
+/// the holders of the [`SignerAuthKey`], i.e. the validator's own workers, can connect;
+/// the signer signs any prehash they send.
+pub struct SignerServer {
+    secret: ValidatorSecretKey,
+    auth_key: SignerAuthKey,
+    attestation: AttestationProvider,
+    /// Serializes the attestation requests, which share the platform's report interface.
+    attestation_lock: Mutex<()>,
+}
+
+impl SignerServer {
+    /// Creates a signer for the given key, accepting the workers holding `auth_key`.
+    pub fn new(
+        secret: ValidatorSecretKey,
+        auth_key: SignerAuthKey,
+        attestation: AttestationProvider,
+    ) -> Self {
+        Self {
+            secret,
+            auth_key,
+            attestation,
+            attestation_lock: Mutex::new(()),
+        }
+    }
+
+    /// Serves the workers' connections accepted by `listener`, each on its own task.
+    pub async fn serve(self: Arc<Self>, listener: TcpListener) -> Result<(), RemoteSignerError> {
+        info!(
+            "Validator signer for {} listening on {}",
+            self.secret.public(),
+            listener.local_addr()?
+        );
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(error) => {
+                    warn!("Failed to accept a connection: {error}");
+                    continue;
+                }
+            };
+            let server = self.clone();
+            tokio::spawn(async move {
+                if let Err(error) = server.handle_connection(stream).await {
+                    debug!("Closing signer connection from {peer}: {error}");
+                }
+            });
+        }
+    }
+
+    /// Serves attestation reports over HTTP, at `GET /attestation?nonce=<hash>`, so that
+    /// verifiers outside the validator can check its key custody. Unlike [`Self::serve`],
+    /// this needs no authentication: a report cannot be used to sign anything.
+    pub async fn serve_attestation(self: Arc<Self>, listener: TcpListener) -> io::Result<()> {
+        info!(
+            "Serving attestation reports on http://{}/attestation",
+            listener.local_addr()?
+        );
+        let router = Router::new()
+            .route("/attestation", get(attestation_handler))
+            .with_state(self);
+        axum::serve(listener, router).await
+    }
+
+    /// Produces an attestation report binding the validator's public key and `nonce` to
+    /// the enclave the signer runs in.
+    pub async fn attestation_report(
+        self: &Arc<Self>,
+        nonce: CryptoHash,
+    ) -> Result<Vec<u8>, String> {
+        let server = self.clone();
+        tokio::task::spawn_blocking(move || {
+            let report_data = attestation_report_data(server.secret.public(), nonce);
+            let _guard = server
+                .attestation_lock
+                .lock()
+                .expect("the attestation lock should not be poisoned");
+            server.attestation.report(&report_data)
+        })
+        .await
+        .map_err(|error| error.to_string())?
+    }
+
+    async fn handle_connection(
+        self: Arc<Self>,
+        stream: TcpStream,
+    ) -> Result<(), RemoteSignerError> {
+        let mut channel = with_timeout(Channel::accept(stream, &self.auth_key)).await?;
+        loop {
+            let request = match channel.receive(REQUEST_LABEL).await {
+                Err(RemoteSignerError::Io(error))
+                    if error.kind() == io::ErrorKind::UnexpectedEof =>
+                {
+                    return Ok(());
+                }
+                result => result?,
+            };
+            let response = self.handle_request(request).await;
+            channel.send(RESPONSE_LABEL, &response).await?;
+        }
+    }
+
+    async fn handle_request(self: &Arc<Self>, request: SignerRequest) -> SignerResponse {
+        match request {
+            SignerRequest::PublicKey => SignerResponse::PublicKey(self.secret.public()),
+            SignerRequest::Sign(prehash) => {
+                SignerResponse::Signature(ValidatorSignature::sign_prehash(&self.secret, prehash))
+            }
+            SignerRequest::AttestationReport(nonce) => match self.attestation_report(nonce).await {
+                Ok(report) => SignerResponse::AttestationReport(report),
+                Err(error) => SignerResponse::Error(error),
+            },
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AttestationQuery {
+    nonce: CryptoHash,
+}
+
+async fn attestation_handler(
+    State(server): State<Arc<SignerServer>>,
+    Query(query): Query<AttestationQuery>,
+) -> Result<Json<AttestationEvidence>, (StatusCode, String)> {
+    let report = server
+        .attestation_report(query.nonce)
+        .await
+        .map_err(|error| (StatusCode::SERVICE_UNAVAILABLE, error))?;
+    Ok(Json(AttestationEvidence::new(
+        server.secret.public(),
+        query.nonce,
+        &report,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::future::try_join_all;
+    use linera_base::crypto::{CryptoHash, TestString, ValidatorSecretKey, ValidatorSigner};
+    use tokio::net::TcpListener;
+
+    use super::{
+        AttestationProvider, RemoteSignerError, RemoteValidatorSigner, SignerAuthKey, SignerServer,
+    };
+
+    async fn start_signer(secret: ValidatorSecretKey, auth_key: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let auth_key = auth_key.parse().unwrap();
+        let server = Arc::new(SignerServer::new(
+            secret,
+            auth_key,
+            AttestationProvider::None,
+        ));
+        tokio::spawn(server.serve(listener));
+        address
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_signs_with_the_validator_key() {
+        let secret = ValidatorSecretKey::generate();
+        let public_key = secret.public();
+        let address = start_signer(secret, "a shared key for the test").await;
+
+        let auth_key = "a shared key for the test".parse().unwrap();
+        let signer = RemoteValidatorSigner::connect(address, auth_key)
+            .await
+            .unwrap();
+        assert_eq!(signer.public_key(), public_key);
+        let signer = &signer as &dyn ValidatorSigner;
+        let values = (0..40)
+            .map(|i| TestString::new(format!("vote {i}")))
+            .collect::<Vec<_>>();
+        let signatures = try_join_all(values.iter().map(|value| signer.sign(value)))
+            .await
+            .unwrap();
+        for (value, signature) in values.iter().zip(signatures) {
+            signature.check(value, public_key).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_rejects_unauthenticated_workers() {
+        let secret = ValidatorSecretKey::generate();
+        let address = start_signer(secret, "a shared key for the test").await;
+
+        let auth_key = "another key for the test".parse::<SignerAuthKey>().unwrap();
+        assert!(RemoteValidatorSigner::connect(address.clone(), auth_key)
+            .await
+            .is_err());
+
+        let auth_key = "a shared key for the test".parse().unwrap();
+        let signer = RemoteValidatorSigner::connect(address, auth_key)
+            .await
+            .unwrap();
+        let result = signer
+            .attestation_report(CryptoHash::new(&TestString::new("nonce")))
+            .await;
+        assert!(matches!(result, Err(RemoteSignerError::Signer(_))));
+        assert!("too short".parse::<SignerAuthKey>().is_err());
+    }
+}
@@ -264,6 +264,9 @@ async fn main() -> std::io::Result<()> {
         tokio_util::sync::CancellationToken::new(),
         false, // memory profiling disabled for schema export
         false, // not paused
+        false, // no service hot-reload for schema export
+        None,  // no webhook bridge for schema export
+        None,  // no consumer cursors for schema export
     );
     let schema = service.schema().sdl();
     print!("{schema}");
@@ -23,17 +23,20 @@ use std::{
     time::Duration,
 };
 
-use anyhow::{bail, Context};
+use anyhow::{bail, ensure, Context};
 use async_trait::async_trait;
 use futures::{stream::FuturesUnordered, FutureExt as _, StreamExt, TryFutureExt as _};
 use linera_base::{
-    crypto::{CryptoRng, Ed25519SecretKey},
+    crypto::{CryptoHash, CryptoRng, Ed25519SecretKey, ValidatorSigner},
+    data_types::Epoch,
     identifiers::ChainId,
     listen_for_shutdown_signals,
 };
-use linera_client::config::{CommitteeConfig, ValidatorConfig, ValidatorServerConfig};
+use linera_client::config::{AlertConfig, CommitteeConfig, ValidatorConfig, ValidatorServerConfig};
 use linera_core::{
-    worker::WorkerState, ChainWorkerConfig, JoinSetExt as _, CHAIN_INFO_MAX_RECEIVED_LOG_ENTRIES,
+    data_types::{SignedValidatorAddress, ValidatorAddressUpdate},
+    worker::WorkerState,
+    ChainWorkerConfig, JoinSetExt as _, CHAIN_INFO_MAX_RECEIVED_LOG_ENTRIES,
 };
 use linera_execution::{WasmRuntime, WithWasmDefault};
 #[cfg(with_metrics)]
@@ -48,7 +51,10 @@ use linera_rpc::{
     grpc, simple,
 };
 use linera_sdk::linera_base_types::{AccountSecretKey, ValidatorKeypair};
+#[cfg(with_metrics)]
+use linera_service::{alerting::AlertMonitor, execution_profile, tracing::log_filter};
 use linera_service::{
+    remote_signer::{AttestationEvidence, RemoteValidatorSigner, SignerAuthKey},
     storage::{CommonStorageOptions, Runnable, StorageConfig},
     util,
 };
@@ -58,8 +64,12 @@ use tokio::task::JoinSet;
 use tokio_util::sync::CancellationToken;
 use tracing::{error, info};
 
+/// How often each shard looks for closed ephemeral chains to prune.
+const EPHEMERAL_CHAIN_PRUNING_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
 struct ServerContext {
     server_config: ValidatorServerConfig,
+    remote_signer: Option<Arc<RemoteValidatorSigner>>,
     cross_chain_config: CrossChainConfig,
     notification_config: NotificationConfig,
     shard: Option<usize>,
@@ -73,6 +83,7 @@ struct ServerContext {
     allow_revert_confirm: bool,
     reset_on_corrupted_chain_state_mins: Option<u64>,
     recovery_whitelist: Option<HashSet<ChainId>>,
+    ephemeral_chain_retention_mins: Option<u64>,
     #[cfg(with_metrics)]
     enable_memory_profiling: bool,
 }
@@ -89,19 +100,29 @@ impl ServerContext {
     {
         let shard = self.server_config.internal_network.shard(shard_id);
         info!("Shard booted on {}", shard.host);
-        info!(
-            "Public key: {}",
-            self.server_config.validator_secret.public()
-        );
+        info!("Public key: {}", self.server_config.validator.public_key);
+        let (key_pair, remote_signer) = match &self.remote_signer {
+            Some(signer) => (None, Some(signer.clone() as Arc<dyn ValidatorSigner>)),
+            None => (
+                self.server_config
+                    .validator_secret
+                    .as_ref()
+                    .map(|secret| Arc::new(secret.copy())),
+                None,
+            ),
+        };
         let config = ChainWorkerConfig {
             nickname: format!("Shard {} @ {}:{}", shard_id, local_ip_addr, shard.port),
-            key_pair: Some(Arc::new(self.server_config.validator_secret.copy())),
+            key_pair,
+            remote_signer,
             allow_inactive_chains: true,
             block_time_grace_period: self.block_time_grace_period,
             ttl: util::non_zero_duration(self.chain_worker_ttl),
             chain_info_max_received_log_entries: self.chain_info_max_received_log_entries,
             cross_chain_message_chunk_limit: self.cross_chain_message_chunk_limit,
             cross_chain_batch_size_limit: self.cross_chain_batch_size_limit,
+            max_pending_bundles_per_origin: self.cross_chain_config.max_pending_bundles_per_origin,
+            pull_cross_chain_messages: self.cross_chain_config.pull_messages,
             block_cache_size: self.block_cache_size,
             execution_state_cache_size: self.execution_state_cache_size,
             allow_revert_confirm: self.allow_revert_confirm,
@@ -109,6 +130,10 @@ impl ServerContext {
                 .reset_on_corrupted_chain_state_mins
                 .map(|m| Duration::from_secs(m * 60)),
             recovery_whitelist: self.recovery_whitelist.clone(),
+            ephemeral_chain_retention: self
+                .ephemeral_chain_retention_mins
+                .map(|m| Duration::from_secs(m * 60)),
+            address_update: self.server_config.address_update.clone(),
             ..ChainWorkerConfig::default()
         };
         let state = WorkerState::new(storage, config, None);
@@ -142,10 +167,11 @@ impl ServerContext {
 
             #[cfg(with_metrics)]
             if let Some(port) = shard.metrics_port {
-                monitoring_server::start_metrics(
+                monitoring_server::start_metrics_with_routes(
                     (listen_address.clone(), port),
                     shutdown_signal.clone(),
                     monitoring_server::MemoryProfiling::from(enable_memory_profiling),
+                    log_filter::router().merge(execution_profile::router()),
                 );
             }
 
@@ -191,10 +217,11 @@ impl ServerContext {
         for (state, shard_id, shard) in states {
             #[cfg(with_metrics)]
             if let Some(port) = shard.metrics_port {
-                monitoring_server::start_metrics(
+                monitoring_server::start_metrics_with_routes(
                     (listen_address.to_string(), port),
                     shutdown_signal.clone(),
                     monitoring_server::MemoryProfiling::from(enable_memory_profiling),
+                    log_filter::router().merge(execution_profile::router()),
                 );
             }
 
@@ -225,6 +252,52 @@ impl ServerContext {
         join_set
     }
 
+    /// Periodically prunes the closed ephemeral chains handled by each shard, if a
+    /// retention period is configured.
+    fn spawn_ephemeral_chain_pruning<S>(
+        &self,
+        states: &[(WorkerState<S>, ShardId, ShardConfig)],
+        shutdown_signal: &CancellationToken,
+    ) where
+        S: Storage + Clone + Send + Sync + 'static,
+    {
+        if self.ephemeral_chain_retention_mins.is_none() {
+            return;
+        }
+        for (state, shard_id, _) in states {
+            let state = state.clone();
+            let shard_id = *shard_id;
+            let internal_network = self.server_config.internal_network.clone();
+            let shutdown_signal = shutdown_signal.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(EPHEMERAL_CHAIN_PRUNING_INTERVAL);
+                loop {
+                    tokio::select! {
+                        _ = shutdown_signal.cancelled() => break,
+                        _ = interval.tick() => {}
+                    }
+                    match state
+                        .prune_ephemeral_chains(|chain_id| {
+                            internal_network.get_shard_id(chain_id) == shard_id
+                        })
+                        .await
+                    {
+                        Ok(pruned) if !pruned.is_empty() => {
+                            info!(
+                                "Pruned {} ephemeral chains on shard {shard_id}",
+                                pruned.len()
+                            );
+                        }
+                        Ok(_) => {}
+                        Err(error) => {
+                            error!("Failed to prune ephemeral chains on shard {shard_id}: {error}")
+                        }
+                    }
+                }
+            });
+        }
+    }
+
     fn get_listen_address() -> String {
         // Allow local IP address to be different from the public one.
         "0.0.0.0".to_string()
@@ -244,6 +317,20 @@ impl Runnable for ServerContext {
 
         tokio::spawn(listen_for_shutdown_signals(shutdown_notifier.clone()));
 
+        #[cfg(with_metrics)]
+        if let Some(alerts) = self.server_config.alerts.clone() {
+            let public_key = &self.server_config.validator.public_key;
+            let source = match self.shard {
+                Some(shard) => format!("validator-{public_key}-shard-{shard}"),
+                None => format!("validator-{public_key}"),
+            };
+            tokio::spawn(AlertMonitor::new(alerts, source).run(shutdown_notifier.clone()));
+        }
+        #[cfg(not(with_metrics))]
+        if self.server_config.alerts.is_some() {
+            tracing::warn!("Alerts are configured but this binary was built without metrics");
+        }
+
         // Activate memory profiling once before per-shard start_metrics calls.
         #[cfg(with_metrics)]
         let enable_memory_profiling = {
@@ -270,6 +357,8 @@ impl Runnable for ServerContext {
             }
         };
 
+        self.spawn_ephemeral_chain_pruning(&states, &shutdown_notifier);
+
         let mut join_set = match self.server_config.internal_network.protocol {
             NetworkProtocol::Simple(protocol) => self.spawn_simple(
                 &listen_address,
@@ -364,6 +453,11 @@ struct ValidatorOptions {
     /// The network protocol for the frontend.
     external_protocol: NetworkProtocol,
 
+    /// Further public addresses through which the validator can be reached, e.g. other
+    /// load-balanced proxies. Only gRPC is supported.
+    #[serde(default)]
+    additional_networks: Vec<ValidatorPublicNetworkConfig>,
+
     /// The network protocol for workers.
     internal_protocol: NetworkProtocol,
 
@@ -372,6 +466,10 @@ struct ValidatorOptions {
 
     /// The name and the port of the proxies
     proxies: Vec<ProxyConfig>,
+
+    /// The alerts raised by the validator binaries.
+    #[serde(default)]
+    alerts: Option<AlertConfig>,
 }
 
 fn make_server_config<R: CryptoRng>(
@@ -396,6 +494,7 @@ fn make_server_config<R: CryptoRng>(
     };
     let validator = ValidatorConfig {
         network,
+        additional_networks: options.additional_networks,
         public_key,
         account_key: account_secret.public(),
     };
@@ -403,8 +502,10 @@ fn make_server_config<R: CryptoRng>(
         path,
         ValidatorServerConfig {
             validator,
-            validator_secret: validator_keypair.secret_key,
+            validator_secret: Some(validator_keypair.secret_key),
             internal_network,
+            alerts: options.alerts,
+            address_update: None,
         },
     )?)
 }
@@ -499,11 +600,90 @@ enum ServerCommand {
         #[arg(long, value_delimiter = ',')]
         recovery_whitelist: Option<Vec<ChainId>>,
 
+        /// Prune the state of closed ephemeral chains once their latest block is older
+        /// than this many minutes. If not specified, ephemeral chains are never pruned.
+        #[arg(long)]
+        ephemeral_chain_retention_mins: Option<u64>,
+
+        /// Record the time spent in each application and host function of the executed
+        /// blocks, per chain, and serve it on the `/admin/execution-profile` endpoint of
+        /// the metrics server, in the folded format of flamegraph tools or as a pprof
+        /// profile with `?format=pprof`.
+        #[arg(long, env = "LINERA_ENABLE_EXECUTION_PROFILING")]
+        enable_execution_profiling: bool,
+
+        /// The address of a `linera-validator-signer` process holding the validator's key.
+        /// If set, the shards sign their votes through it instead of using the secret key
+        /// of the server configuration, which may then be omitted.
+        #[arg(long, requires = "remote_signer_auth_key")]
+        remote_signer: Option<String>,
+
+        /// The key shared with the `linera-validator-signer`, to authenticate to it.
+        #[arg(
+            long,
+            env = "LINERA_VALIDATOR_SIGNER_AUTH_KEY",
+            hide_env_values = true,
+            requires = "remote_signer"
+        )]
+        remote_signer_auth_key: Option<SignerAuthKey>,
+
         /// OpenTelemetry OTLP exporter endpoint (requires opentelemetry feature).
         #[arg(long, env = "LINERA_OTLP_EXPORTER_ENDPOINT")]
         otlp_exporter_endpoint: Option<String>,
     },
 
+    /// Requests an attestation report from a `linera-validator-signer` running in an
+    /// enclave, binding the validator's public key and the given nonce to the enclave's
+    /// measurement, and prints it as JSON.
+    #[command(name = "attestation-report")]
+    AttestationReport {
+        /// The address of the signer.
+        #[arg(long)]
+        remote_signer: String,
+
+        /// The key shared with the signer, to authenticate to it.
+        #[arg(long, env = "LINERA_VALIDATOR_SIGNER_AUTH_KEY", hide_env_values = true)]
+        remote_signer_auth_key: SignerAuthKey,
+
+        /// A fresh nonce chosen by the verifier, to prevent the replay of old reports.
+        #[arg(long)]
+        nonce: CryptoHash,
+    },
+
+    /// Changes the network address of the validator without a new committee: signs an
+    /// address update, which the shards then include in their chain information responses
+    /// so that clients switch to the new address. The shards must be restarted.
+    #[command(name = "update-address")]
+    UpdateAddress {
+        /// Path to the file containing the server configuration of this Linera validator.
+        #[arg(long = "server")]
+        server_config_path: PathBuf,
+
+        /// The new public network address of the validator, e.g. `grpcs:lb.example.com:443`.
+        /// Can be repeated if the validator is reachable through several proxies.
+        #[arg(long = "address", required = true)]
+        networks: Vec<ValidatorPublicNetworkConfig>,
+
+        /// The current epoch of the network. Clients ignore the update once they know a
+        /// later committee, which publishes its own addresses.
+        #[arg(long)]
+        epoch: Epoch,
+
+        /// The address of a `linera-validator-signer` process holding the validator's key,
+        /// if the server configuration has no secret key.
+        #[arg(long, requires = "remote_signer_auth_key")]
+        remote_signer: Option<String>,
+
+        /// The key shared with the `linera-validator-signer`, to authenticate to it.
+        #[arg(
+            long,
+            env = "LINERA_VALIDATOR_SIGNER_AUTH_KEY",
+            hide_env_values = true,
+            requires = "remote_signer"
+        )]
+        remote_signer_auth_key: Option<SignerAuthKey>,
+    },
+
     /// Act as a trusted third-party and generate all server configurations
     #[command(name = "generate")]
     Generate {
@@ -584,7 +764,10 @@ fn otlp_exporter_endpoint_for(command: &ServerCommand) -> Option<&str> {
             otlp_exporter_endpoint,
             ..
         } => otlp_exporter_endpoint.as_deref(),
-        ServerCommand::Generate { .. } | ServerCommand::EditShards { .. } => None,
+        ServerCommand::Generate { .. }
+        | ServerCommand::EditShards { .. }
+        | ServerCommand::UpdateAddress { .. }
+        | ServerCommand::AttestationReport { .. } => None,
     }
 }
 
@@ -606,7 +789,10 @@ fn log_file_name_for(command: &ServerCommand) -> Cow<'static, str> {
             }
             .into()
         }
-        ServerCommand::Generate { .. } | ServerCommand::EditShards { .. } => "server".into(),
+        ServerCommand::Generate { .. }
+        | ServerCommand::EditShards { .. }
+        | ServerCommand::UpdateAddress { .. }
+        | ServerCommand::AttestationReport { .. } => "server".into(),
     }
 }
 
@@ -615,6 +801,7 @@ async fn run(options: ServerOptions) {
         &log_file_name_for(&options.command),
         otlp_exporter_endpoint_for(&options.command),
     );
+    linera_service::virtual_time::follow_from_env().expect("Failed to follow the virtual time");
 
     #[cfg(with_metrics)]
     let enable_memory_profiling = options.enable_memory_profiling();
@@ -635,15 +822,35 @@ async fn run(options: ServerOptions) {
             allow_revert_confirm,
             reset_on_corrupted_chain_state_mins,
             recovery_whitelist,
+            ephemeral_chain_retention_mins,
+            enable_execution_profiling,
+            remote_signer,
+            remote_signer_auth_key,
             otlp_exporter_endpoint: _,
         } => {
             linera_version::VERSION_INFO.log();
+            if enable_execution_profiling {
+                linera_execution::profiler::enable();
+            }
 
             let server_config: ValidatorServerConfig =
                 util::read_json(&server_config_path).expect("Failed to read server config");
+            let remote_signer = match remote_signer.zip(remote_signer_auth_key) {
+                Some((address, auth_key)) => Some(
+                    connect_remote_signer(&address, auth_key, &server_config)
+                        .await
+                        .expect("Failed to connect to the validator signer"),
+                ),
+                None => None,
+            };
+            assert!(
+                remote_signer.is_some() || server_config.validator_secret.is_some(),
+                "The server config has no secret key and no remote signer is given"
+            );
 
             let job = ServerContext {
                 server_config,
+                remote_signer,
                 cross_chain_config,
                 notification_config,
                 shard,
@@ -657,6 +864,7 @@ async fn run(options: ServerOptions) {
                 allow_revert_confirm,
                 reset_on_corrupted_chain_state_mins,
                 recovery_whitelist: recovery_whitelist.map(HashSet::from_iter),
+                ephemeral_chain_retention_mins,
                 #[cfg(with_metrics)]
                 enable_memory_profiling,
             };
@@ -733,9 +941,100 @@ async fn run(options: ServerOptions) {
                 .await
                 .expect("Failed to write updated server config");
         }
+
+        ServerCommand::UpdateAddress {
+            server_config_path,
+            networks,
+            epoch,
+            remote_signer,
+            remote_signer_auth_key,
+        } => {
+            let mut server_config =
+                persistent::File::<ValidatorServerConfig>::read(&server_config_path)
+                    .expect("Failed to read server config");
+            let signer: Arc<dyn ValidatorSigner> = match remote_signer.zip(remote_signer_auth_key) {
+                Some((address, auth_key)) => {
+                    connect_remote_signer(&address, auth_key, &server_config)
+                        .await
+                        .expect("Failed to connect to the validator signer")
+                }
+                None => Arc::new(
+                    server_config
+                        .validator_secret
+                        .as_ref()
+                        .expect("The server config has no secret key and no remote signer is given")
+                        .copy(),
+                ),
+            };
+            let mut networks = networks.into_iter();
+            server_config.validator.network = networks.next().expect("at least one address");
+            server_config.validator.additional_networks = networks.collect();
+            let version = server_config
+                .address_update
+                .as_ref()
+                .map_or(1, |signed| signed.update.version + 1);
+            let update = ValidatorAddressUpdate {
+                public_key: server_config.validator.public_key,
+                network_address: server_config.validator.network_address(),
+                epoch,
+                version,
+            };
+            info!(
+                "Announcing the address {} (epoch {epoch}, version {version})",
+                update.network_address
+            );
+            let signed_update = SignedValidatorAddress::signed_by(update, &*signer)
+                .await
+                .expect("Failed to sign the address update");
+            server_config.address_update = Some(signed_update);
+            Persist::persist(&mut server_config)
+                .await
+                .expect("Failed to write updated server config");
+        }
+
+        ServerCommand::AttestationReport {
+            remote_signer,
+            remote_signer_auth_key,
+            nonce,
+        } => {
+            let signer = RemoteValidatorSigner::connect(remote_signer, remote_signer_auth_key)
+                .await
+                .expect("Failed to connect to the validator signer");
+            let report = signer
+                .attestation_report(nonce)
+                .await
+                .expect("Failed to obtain an attestation report");
+            let evidence = AttestationEvidence::new(signer.public_key(), nonce, &report);
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&evidence).expect("serialization should not fail")
+            );
+        }
     }
 }
 
+/// Connects to the validator signer at `address` and checks that it holds the key of the
+/// validator described by `server_config`.
+async fn connect_remote_signer(
+    address: &str,
+    auth_key: SignerAuthKey,
+    server_config: &ValidatorServerConfig,
+) -> anyhow::Result<Arc<RemoteValidatorSigner>> {
+    let signer = RemoteValidatorSigner::connect(address, auth_key).await?;
+    ensure!(
+        signer.public_key() == server_config.validator.public_key,
+        "The validator signer at {address} holds the key {} instead of {}",
+        signer.public_key(),
+        server_config.validator.public_key
+    );
+    if server_config.validator_secret.is_some() {
+        tracing::warn!(
+            "Signing with the remote signer; ignoring the secret key in the server config"
+        );
+    }
+    Ok(Arc::new(signer))
+}
+
 fn generate_shard_configs(
     num_shards: &str,
     host: &str,
@@ -776,6 +1075,7 @@ fn generate_shard_configs(
 
 #[cfg(test)]
 mod test {
+    use linera_client::config::{AlertCondition, AlertNotifierConfig};
     use linera_rpc::simple::TransportProtocol;
 
     use super::*;
@@ -816,6 +1116,7 @@ mod test {
                 server_config_path: "server.json".into(),
                 external_protocol: NetworkProtocol::Simple(TransportProtocol::Tcp),
                 internal_protocol: NetworkProtocol::Simple(TransportProtocol::Udp),
+                additional_networks: vec![],
                 host: "host".into(),
                 port: 9000,
                 proxies: vec![ProxyConfig {
@@ -840,10 +1141,86 @@ mod test {
                         metrics_port: Some(5002),
                     },
                 ],
+                alerts: None,
             }
         );
     }
 
+    #[test]
+    fn test_validator_options_with_alerts() {
+        let toml_str = r#"
+            server_config_path = "server.json"
+            host = "host"
+            port = 9000
+            external_protocol = { Simple = "Tcp" }
+            internal_protocol = { Simple = "Udp" }
+            shards = []
+            proxies = []
+
+            [alerts]
+            storage_latency_p99_ms = 250
+            disabled = ["epoch-lag"]
+
+            [[alerts.notifiers]]
+            kind = "pager-duty"
+            routing_key = "key"
+
+            [[alerts.notifiers]]
+            kind = "stdout-json"
+        "#;
+        let options: ValidatorOptions = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            options.alerts,
+            Some(AlertConfig {
+                storage_latency_p99_ms: 250,
+                disabled: [AlertCondition::EpochLag].into_iter().collect(),
+                notifiers: vec![
+                    AlertNotifierConfig::PagerDuty {
+                        routing_key: "key".into(),
+                        url: None,
+                    },
+                    AlertNotifierConfig::StdoutJson,
+                ],
+                ..AlertConfig::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_validator_options_with_additional_networks() {
+        let toml_str = r#"
+            server_config_path = "server.json"
+            host = "proxy1"
+            port = 443
+            external_protocol = { Grpc = "Tls" }
+            internal_protocol = { Grpc = "ClearText" }
+            shards = []
+            proxies = []
+
+            [[additional_networks]]
+            protocol = { Grpc = "Tls" }
+            host = "proxy2"
+            port = 443
+        "#;
+        let options: ValidatorOptions = toml::from_str(toml_str).unwrap();
+        let network = ValidatorPublicNetworkConfig {
+            protocol: options.external_protocol,
+            host: options.host,
+            port: options.port,
+        };
+        let mut rng = Box::<dyn CryptoRng>::from(Some(0));
+        let validator = ValidatorConfig {
+            public_key: ValidatorKeypair::generate_from(&mut rng).public_key,
+            account_key: AccountSecretKey::Ed25519(Ed25519SecretKey::generate_from(&mut rng)).public(),
+            network,
+            additional_networks: options.additional_networks,
+        };
+        assert_eq!(
+            validator.network_address(),
+            "grpcs:proxy1:443,grpcs:proxy2:443"
+        );
+    }
+
     #[test]
     fn test_generate_shard_configs() {
         assert_eq!(
@@ -0,0 +1,168 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! TLS and API keys, to expose a node service beyond localhost.
+//!
+//! With a certificate, the node service is served over HTTPS (and WSS) only. With API keys,
+//! every request must carry one in an `X-Api-Key` header, except the health check at
+//! `/ready`. API keys authenticate the callers of the whole service, and combine with the
+//! access tokens that restrict which mutations each caller may run.
+
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Context as _;
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse as _, Response},
+};
+use axum_server::tls_rustls::RustlsConfig;
+use sha2::{Digest as _, Sha256};
+
+/// The header carrying the API key of a request.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Options securing the node service.
+#[derive(Clone, Debug, Default, clap::Args)]
+pub struct ServiceSecurityOptions {
+    /// Path to a PEM file with the certificate chain of the service. When set, the service
+    /// is served over HTTPS instead of HTTP.
+    #[arg(long, requires = "tls_key")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// Path to a PEM file with the private key of the certificate.
+    #[arg(long, requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+
+    /// Path to a file of API keys, one per line. When set, all requests except `GET /ready`
+    /// require one of them in an `X-Api-Key` header, so that only authenticated callers can
+    /// query the wallet and run mutations. Empty lines and lines starting with `#` are
+    /// ignored.
+    #[arg(long)]
+    pub api_keys: Option<PathBuf>,
+}
+
+impl ServiceSecurityOptions {
+    /// Loads the certificate and key, if TLS is enabled.
+    pub async fn tls_config(&self) -> anyhow::Result<Option<RustlsConfig>> {
+        let (Some(cert), Some(key)) = (&self.tls_cert, &self.tls_key) else {
+            return Ok(None);
+        };
+        // Several crypto providers are linked in: select one before building the config.
+        if rustls::crypto::ring::default_provider()
+            .install_default()
+            .is_err()
+        {
+            tracing::debug!("A rustls crypto provider was already selected");
+        }
+        let config = RustlsConfig::from_pem_file(cert, key)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to load the TLS certificate {} and key {}",
+                    cert.display(),
+                    key.display()
+                )
+            })?;
+        Ok(Some(config))
+    }
+
+    /// Reads the API keys, if they are required.
+    pub fn api_keys(&self) -> anyhow::Result<Option<ApiKeys>> {
+        self.api_keys.as_deref().map(ApiKeys::read).transpose()
+    }
+}
+
+/// The API keys accepted by a node service.
+#[derive(Debug)]
+pub struct ApiKeys {
+    /// The SHA-256 hashes of the keys, so that checking a key takes the same time whatever
+    /// prefix it shares with a valid one.
+    hashes: HashSet<[u8; 32]>,
+}
+
+impl ApiKeys {
+    /// Reads the keys from the file at `path`, which must contain at least one.
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs_err::read_to_string(path)?;
+        let keys = Self::new(
+            contents
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#')),
+        );
+        anyhow::ensure!(
+            !keys.hashes.is_empty(),
+            "the API key file {} has no keys",
+            path.display()
+        );
+        Ok(keys)
+    }
+
+    /// Returns the set of the given keys.
+    pub fn new<'a>(keys: impl IntoIterator<Item = &'a str>) -> Self {
+        let hashes = keys.into_iter().map(hash_key).collect();
+        Self { hashes }
+    }
+
+    /// Returns whether `key` is one of the API keys.
+    pub fn contains(&self, key: &str) -> bool {
+        self.hashes.contains(&hash_key(key))
+    }
+}
+
+fn hash_key(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+/// A middleware rejecting the requests without a valid API key, except the health check.
+pub async fn require_api_key(
+    State(api_keys): State<Arc<ApiKeys>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.uri().path() == "/ready" {
+        return next.run(request).await;
+    }
+    let key = request
+        .headers()
+        .get(API_KEY_HEADER)
+        .and_then(|value| value.to_str().ok());
+    match key {
+        Some(key) if api_keys.contains(key.trim()) => next.run(request).await,
+        Some(_) => (StatusCode::UNAUTHORIZED, "invalid API key").into_response(),
+        None => (
+            StatusCode::UNAUTHORIZED,
+            "requests require an API key, sent as `X-Api-Key: <key>`",
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use super::ApiKeys;
+
+    #[test]
+    fn test_api_keys() -> anyhow::Result<()> {
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(file, "# The frontend.\n  first-key \n\nsecond-key")?;
+        let keys = ApiKeys::read(file.path())?;
+        assert!(keys.contains("first-key"));
+        assert!(keys.contains("second-key"));
+        assert!(!keys.contains("first"));
+        assert!(!keys.contains("# The frontend."));
+        assert!(!keys.contains(""));
+
+        let empty = tempfile::NamedTempFile::new()?;
+        assert!(ApiKeys::read(empty.path()).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{net::SocketAddr, num::NonZeroU16};
+
+use axum::{
+    extract::Extension,
+    http::StatusCode,
+    response::{Html, IntoResponse},
+    routing::{get, post},
+    Json, Router,
+};
+use linera_client::signing_relay::{SigningRelay, SigningRequest};
+use serde::Deserialize;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+/// The page listing the pending signing requests, and signing them with the browser's EVM
+/// wallet. Requests are addressed relative to the page, so that it can be nested.
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Linera signing requests</title></head>
+<body>
+<h1>Linera signing requests</h1>
+<p id="status">Waiting for requests…</p>
+<ul id="requests"></ul>
+<script>
+const base = window.location.pathname.replace(/\/?$/, "/");
+const signing = new Set();
+async function sign(request) {
+  if (signing.has(request.id)) return;
+  signing.add(request.id);
+  try {
+    const accounts = await window.ethereum.request({ method: "eth_requestAccounts" });
+    const account = accounts.find((a) => a.toLowerCase() === request.owner.toLowerCase());
+    if (!account) throw new Error(`connect the account ${request.owner} in your wallet`);
+    const signature = await window.ethereum.request({
+      method: "personal_sign",
+      params: [request.message, account],
+    });
+    const response = await fetch(base + "signatures", {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ id: request.id, signature }),
+    });
+    if (!response.ok) throw new Error(await response.text());
+  } catch (error) {
+    document.getElementById("status").textContent = `Request ${request.id}: ${error.message}`;
+  } finally {
+    signing.delete(request.id);
+  }
+}
+async function poll() {
+  const requests = await (await fetch(base + "requests")).json();
+  const list = document.getElementById("requests");
+  list.replaceChildren(...requests.map((request) => {
+    const item = document.createElement("li");
+    const button = document.createElement("button");
+    button.textContent = "Sign";
+    button.onclick = () => sign(request);
+    item.append(`#${request.id} by ${request.owner}: ${request.message} `, button);
+    return item;
+  }));
+}
+if (!window.ethereum) {
+  document.getElementById("status").textContent = "No EVM wallet found in this browser.";
+}
+setInterval(() => poll().catch(console.error), 1000);
+</script>
+</body>
+</html>
+"#;
+
+/// A signature submitted by the page.
+#[derive(Deserialize)]
+struct Submission {
+    id: u64,
+    signature: String,
+}
+
+/// Returns the routes of the signing page: the page itself, the pending requests, and the
+/// submission of signatures.
+pub fn router(relay: SigningRelay) -> Router {
+    Router::new()
+        .route("/", get(|| async { Html(PAGE) }))
+        .route("/requests", get(requests))
+        .route("/signatures", post(submit))
+        .layer(Extension(relay))
+}
+
+async fn requests(Extension(relay): Extension<SigningRelay>) -> Json<Vec<SigningRequest>> {
+    Json(relay.pending_requests())
+}
+
+async fn submit(
+    Extension(relay): Extension<SigningRelay>,
+    Json(submission): Json<Submission>,
+) -> impl IntoResponse {
+    match relay.submit(submission.id, &submission.signature) {
+        Ok(()) => (StatusCode::OK, String::new()),
+        Err(error) => (StatusCode::BAD_REQUEST, error.to_string()),
+    }
+}
+
+/// Serves the signing page on the given local port, until cancelled.
+pub async fn serve(
+    relay: SigningRelay,
+    port: NonZeroU16,
+    cancellation_token: CancellationToken,
+) -> anyhow::Result<()> {
+    let address = SocketAddr::from(([127, 0, 0, 1], port.get()));
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    info!("Sign with your EVM wallet at http://{address}");
+    axum::serve(listener, router(relay))
+        .with_graceful_shutdown(cancellation_token.cancelled_owned())
+        .await?;
+    Ok(())
+}
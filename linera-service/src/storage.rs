@@ -9,7 +9,13 @@ pub use linera_storage_runtime::{
     AssertStorageV1, CommonStorageOptions, InnerStorageConfig, Runnable, RunnableWithStore,
     StorageConfig, StorageMigration, StoreConfig,
 };
-use linera_views::store::{KeyValueDatabase, KeyValueStore};
+use linera_views::{
+    batch::Batch,
+    store::{
+        KeyValueDatabase, KeyValueStore, ReadableKeyValueStore as _, WritableKeyValueStore as _,
+    },
+};
+use serde::{Deserialize, Serialize};
 
 struct InitializeStorageJob<'a>(&'a GenesisConfig);
 
@@ -50,3 +56,167 @@ pub async fn initialize(
         .run_with_store(cache_sizes, InitializeStorageJob(config))
         .await
 }
+
+/// The content of a single partition (root key) of a namespace.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartitionSnapshot {
+    /// The root key of the partition.
+    pub root_key: Vec<u8>,
+    /// All the key-value pairs stored under the root key.
+    pub key_values: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+/// The full content of a namespace of a key-value database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NamespaceSnapshot {
+    /// The name of the namespace.
+    pub namespace: String,
+    /// The partitions of the namespace.
+    pub partitions: Vec<PartitionSnapshot>,
+}
+
+/// A [`RunnableWithStore`] job that reads the content of the given namespaces.
+pub struct ExportNamespacesJob(pub Vec<String>);
+
+#[async_trait]
+impl RunnableWithStore for ExportNamespacesJob {
+    type Output = Vec<NamespaceSnapshot>;
+
+    async fn run<D>(
+        self,
+        config: D::Config,
+        _namespace: String,
+        _cache_sizes: StorageCacheConfig,
+    ) -> Result<Self::Output, anyhow::Error>
+    where
+        D: KeyValueDatabase + Clone + Send + Sync + 'static,
+        D::Store: KeyValueStore + Clone + Send + Sync + 'static,
+        D::Error: Send + Sync,
+    {
+        let mut snapshots = Vec::new();
+        for namespace in self.0 {
+            let database = D::connect(&config, &namespace).await?;
+            let mut partitions = Vec::new();
+            for root_key in database.list_root_keys().await? {
+                let store = database.open_shared(&root_key)?;
+                let key_values = store.find_key_values_by_prefix(&[]).await?;
+                partitions.push(PartitionSnapshot {
+                    root_key,
+                    key_values,
+                });
+            }
+            snapshots.push(NamespaceSnapshot {
+                namespace,
+                partitions,
+            });
+        }
+        Ok(snapshots)
+    }
+}
+
+/// A [`RunnableWithStore`] job that recreates namespaces from their snapshots.
+/// Existing namespaces with the same names are erased first.
+pub struct ImportNamespacesJob(pub Vec<NamespaceSnapshot>);
+
+#[async_trait]
+impl RunnableWithStore for ImportNamespacesJob {
+    type Output = ();
+
+    async fn run<D>(
+        self,
+        config: D::Config,
+        _namespace: String,
+        _cache_sizes: StorageCacheConfig,
+    ) -> Result<Self::Output, anyhow::Error>
+    where
+        D: KeyValueDatabase + Clone + Send + Sync + 'static,
+        D::Store: KeyValueStore + Clone + Send + Sync + 'static,
+        D::Error: Send + Sync,
+    {
+        for snapshot in self.0 {
+            let database = D::recreate_and_connect(&config, &snapshot.namespace).await?;
+            for partition in snapshot.partitions {
+                let store = database.open_exclusive(&partition.root_key)?;
+                let mut batch = Batch::new();
+                for (key, value) in partition.key_values {
+                    batch.put_key_value_bytes(key, value);
+                }
+                store.write_batch(batch).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_storage::DEFAULT_STORAGE_CACHE_CONFIG;
+    use linera_views::{
+        batch::Batch,
+        memory::MemoryDatabase,
+        random::generate_test_namespace,
+        store::{
+            KeyValueDatabase as _, ReadableKeyValueStore as _, TestKeyValueDatabase as _,
+            WritableKeyValueStore as _,
+        },
+    };
+
+    use super::{
+        ExportNamespacesJob, ImportNamespacesJob, NamespaceSnapshot, RunnableWithStore as _,
+    };
+
+    #[tokio::test]
+    async fn test_export_import_namespaces_round_trip() -> anyhow::Result<()> {
+        let config = MemoryDatabase::new_test_config().await?;
+        let source = generate_test_namespace();
+        let database = MemoryDatabase::recreate_and_connect(&config, &source).await?;
+        for (root_key, values) in [(vec![0], 3u8), (vec![1, 2], 1)] {
+            let mut batch = Batch::new();
+            for index in 0..values {
+                batch.put_key_value_bytes(vec![index], vec![index; 10]);
+            }
+            database
+                .open_exclusive(&root_key)?
+                .write_batch(batch)
+                .await?;
+        }
+
+        let snapshots = ExportNamespacesJob(vec![source.clone()])
+            .run::<MemoryDatabase>(config.clone(), source.clone(), DEFAULT_STORAGE_CACHE_CONFIG)
+            .await?;
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].namespace, source);
+        assert_eq!(snapshots[0].partitions.len(), 2);
+
+        // `linera net snapshot` stores the snapshots with BCS, and `linera net restore`
+        // imports them under new namespaces.
+        let mut snapshots: Vec<NamespaceSnapshot> = bcs::from_bytes(&bcs::to_bytes(&snapshots)?)?;
+        let target = generate_test_namespace();
+        snapshots[0].namespace = target.clone();
+        // Existing data in the target namespace is erased.
+        let database = MemoryDatabase::recreate_and_connect(&config, &target).await?;
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![9], vec![9]);
+        database.open_exclusive(&[9])?.write_batch(batch).await?;
+
+        ImportNamespacesJob(snapshots)
+            .run::<MemoryDatabase>(config.clone(), target.clone(), DEFAULT_STORAGE_CACHE_CONFIG)
+            .await?;
+        let database = MemoryDatabase::connect(&config, &target).await?;
+        let mut root_keys = database.list_root_keys().await?;
+        root_keys.sort();
+        assert_eq!(root_keys, [vec![0], vec![1, 2]]);
+        let key_values = database
+            .open_shared(&[0])?
+            .find_key_values_by_prefix(&[])
+            .await?;
+        assert_eq!(key_values.len(), 3);
+        assert_eq!(key_values[2], (vec![2], vec![2; 10]));
+        let key_values = database
+            .open_shared(&[1, 2])?
+            .find_key_values_by_prefix(&[])
+            .await?;
+        assert_eq!(key_values, [(vec![0], vec![0; 10])]);
+        Ok(())
+    }
+}
@@ -0,0 +1,538 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A wallet stored in a namespace of a key-value database instead of a JSON file, so
+//! that several client processes (e.g. the replicas of a clustered node service) can
+//! share it.
+
+use std::sync::{Arc, RwLock};
+
+use async_trait::async_trait;
+use futures::{
+    future::BoxFuture, stream, FutureExt as _, Stream, StreamExt as _, TryStreamExt as _,
+};
+use linera_base::identifiers::ChainId;
+use linera_client::config::GenesisConfig;
+use linera_core::wallet::Chain;
+use linera_views::{
+    batch::Batch,
+    store::{KeyValueDatabase, KeyValueStore},
+    ViewError,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{RunnableWithStore, StorageCacheConfig};
+
+/// The key of the wallet's metadata.
+const METADATA_KEY: &[u8] = &[0];
+/// The prefix of the keys of the wallet's chains.
+const CHAIN_PREFIX: &[u8] = &[1];
+/// How many times an update of a chain is attempted before giving up on contention.
+const MAX_UPDATE_ATTEMPTS: usize = 10;
+
+/// Returns the namespace holding the wallet of the client using the storage `namespace`.
+pub fn wallet_namespace(namespace: &str) -> String {
+    format!("{namespace}_wallet")
+}
+
+/// An error of a [`StorageWallet`].
+#[derive(Debug, thiserror::Error)]
+pub enum StorageWalletError {
+    /// The underlying store failed.
+    #[error(transparent)]
+    View(#[from] ViewError),
+    /// An entry of the wallet could not be (de)serialized.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// A chain ID could not be (de)serialized.
+    #[error(transparent)]
+    Bcs(#[from] bcs::Error),
+    /// The namespace does not contain a wallet.
+    #[error(
+        "no wallet found in storage; use `linera wallet migrate` to copy a wallet file into \
+         storage"
+    )]
+    NotFound,
+    /// The namespace already contains a wallet.
+    #[error("the storage already contains a wallet")]
+    AlreadyExists,
+    /// The chain to set as the default is not in the wallet.
+    #[error("chain {0} is not in the wallet")]
+    UnknownChain(ChainId),
+    /// Concurrent writers kept overwriting the chain.
+    #[error("too many concurrent updates of chain {0} in the shared wallet")]
+    Contention(ChainId),
+}
+
+/// The wallet-wide data, stored under [`METADATA_KEY`].
+#[derive(Serialize, Deserialize)]
+struct Metadata {
+    genesis_config: GenesisConfig,
+    default: Option<ChainId>,
+}
+
+/// A chain's state, stored under [`CHAIN_PREFIX`] followed by the chain ID.
+#[derive(Clone, Serialize, Deserialize)]
+struct Entry {
+    /// Incremented on every update of the chain, including its removal.
+    version: u64,
+    /// A random value identifying the write that produced this entry.
+    nonce: u64,
+    /// The chain, or `None` if it was removed. Removed chains keep their entry so that the
+    /// removal is versioned like any other update.
+    chain: Option<Chain>,
+}
+
+/// A list of key-value pairs.
+type KeyValues = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// The operations of a key-value store that the wallet needs, in a dyn-compatible form so
+/// that the wallet type does not depend on the storage backend.
+trait WalletStore: Send + Sync {
+    fn read(&self, key: Vec<u8>) -> BoxFuture<'_, Result<Option<Vec<u8>>, ViewError>>;
+
+    fn find_key_values(&self, prefix: Vec<u8>) -> BoxFuture<'_, Result<KeyValues, ViewError>>;
+
+    fn write(&self, batch: Batch) -> BoxFuture<'_, Result<(), ViewError>>;
+}
+
+impl<S: KeyValueStore + Send + Sync + 'static> WalletStore for S {
+    fn read(&self, key: Vec<u8>) -> BoxFuture<'_, Result<Option<Vec<u8>>, ViewError>> {
+        async move { Ok(self.read_value_bytes(&key).await?) }.boxed()
+    }
+
+    fn find_key_values(&self, prefix: Vec<u8>) -> BoxFuture<'_, Result<KeyValues, ViewError>> {
+        async move { Ok(self.find_key_values_by_prefix(&prefix).await?) }.boxed()
+    }
+
+    fn write(&self, batch: Batch) -> BoxFuture<'_, Result<(), ViewError>> {
+        async move { Ok(self.write_batch(batch).await?) }.boxed()
+    }
+}
+
+/// A wallet persisted in a key-value store, with one entry per chain.
+///
+/// Key-value stores offer no compare-and-swap, so concurrent updates, insertions and
+/// removals are optimistic: every entry carries a version, and a write is retried on the
+/// latest state if the entry changed while it was being computed or was overwritten by a
+/// concurrent writer. This narrows, but does not close, the window in which concurrent
+/// updates of the same chain can be lost. Updates of different chains never conflict.
+#[derive(Clone)]
+pub struct StorageWallet {
+    store: Arc<dyn WalletStore>,
+    genesis_config: Arc<GenesisConfig>,
+    default: Arc<RwLock<Option<ChainId>>>,
+}
+
+impl StorageWallet {
+    /// Creates an empty wallet for the given genesis configuration in `store`.
+    pub async fn create<S: KeyValueStore + Send + Sync + 'static>(
+        store: S,
+        genesis_config: GenesisConfig,
+    ) -> Result<Self, StorageWalletError> {
+        let store: Arc<dyn WalletStore> = Arc::new(store);
+        if store.read(METADATA_KEY.to_vec()).await?.is_some() {
+            return Err(StorageWalletError::AlreadyExists);
+        }
+        let wallet = Self {
+            store,
+            genesis_config: Arc::new(genesis_config),
+            default: Arc::default(),
+        };
+        wallet.write_metadata(None).await?;
+        Ok(wallet)
+    }
+
+    /// Opens the wallet in `store`.
+    pub async fn open<S: KeyValueStore + Send + Sync + 'static>(
+        store: S,
+    ) -> Result<Self, StorageWalletError> {
+        let store: Arc<dyn WalletStore> = Arc::new(store);
+        let metadata = Self::read_metadata(&*store).await?;
+        Ok(Self {
+            store,
+            genesis_config: Arc::new(metadata.genesis_config),
+            default: Arc::new(RwLock::new(metadata.default)),
+        })
+    }
+
+    /// Returns the network's genesis configuration.
+    pub fn genesis_config(&self) -> &GenesisConfig {
+        &self.genesis_config
+    }
+
+    /// Returns the admin chain ID from the genesis configuration.
+    pub fn genesis_admin_chain_id(&self) -> ChainId {
+        self.genesis_config.admin_chain_id()
+    }
+
+    /// Returns the default chain, as of opening the wallet or the last change made through
+    /// this handle.
+    pub fn default_chain(&self) -> Option<ChainId> {
+        *self.default.read().unwrap()
+    }
+
+    /// Sets the default chain, which must already be in the wallet.
+    pub async fn set_default_chain(&self, id: ChainId) -> Result<(), StorageWalletError> {
+        if self.read_chain(id).await?.is_none() {
+            return Err(StorageWalletError::UnknownChain(id));
+        }
+        self.write_metadata(Some(id)).await
+    }
+
+    async fn read_metadata(store: &dyn WalletStore) -> Result<Metadata, StorageWalletError> {
+        let bytes = store
+            .read(METADATA_KEY.to_vec())
+            .await?
+            .ok_or(StorageWalletError::NotFound)?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    async fn write_metadata(&self, default: Option<ChainId>) -> Result<(), StorageWalletError> {
+        let metadata = Metadata {
+            genesis_config: (*self.genesis_config).clone(),
+            default,
+        };
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(METADATA_KEY.to_vec(), serde_json::to_vec(&metadata)?);
+        self.store.write(batch).await?;
+        *self.default.write().unwrap() = default;
+        Ok(())
+    }
+
+    /// Makes `id` the default chain if there is none yet.
+    async fn try_set_default(&self, id: ChainId) -> Result<(), StorageWalletError> {
+        let metadata = Self::read_metadata(&*self.store).await?;
+        match metadata.default {
+            Some(default) => *self.default.write().unwrap() = Some(default),
+            None => self.write_metadata(Some(id)).await?,
+        }
+        Ok(())
+    }
+
+    fn chain_key(id: ChainId) -> Result<Vec<u8>, StorageWalletError> {
+        let mut key = CHAIN_PREFIX.to_vec();
+        bcs::serialize_into(&mut key, &id)?;
+        Ok(key)
+    }
+
+    async fn read_entry(&self, id: ChainId) -> Result<Option<Entry>, StorageWalletError> {
+        let Some(bytes) = self.store.read(Self::chain_key(id)?).await? else {
+            return Ok(None);
+        };
+        Ok(Some(serde_json::from_slice(&bytes)?))
+    }
+
+    /// Returns the chain, if it is in the wallet.
+    async fn read_chain(&self, id: ChainId) -> Result<Option<Chain>, StorageWalletError> {
+        Ok(self.read_entry(id).await?.and_then(|entry| entry.chain))
+    }
+
+    /// Writes the chain, or its removal, as the successor of `previous`, and returns the
+    /// written entry.
+    async fn write_entry(
+        &self,
+        id: ChainId,
+        previous: Option<&Entry>,
+        chain: Option<Chain>,
+    ) -> Result<Entry, StorageWalletError> {
+        let entry = Entry {
+            version: previous.map_or(0, |entry| entry.version + 1),
+            nonce: rand::random(),
+            chain,
+        };
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(Self::chain_key(id)?, serde_json::to_vec(&entry)?);
+        self.store.write(batch).await?;
+        Ok(entry)
+    }
+
+    /// Applies `update` to the latest state of the chain until the result is written without
+    /// a conflicting concurrent write, and returns the state it replaced. `update` returns
+    /// the new state, where `Some(None)` removes the chain; if it returns `None`, nothing is
+    /// written and `None` is returned.
+    async fn update(
+        &self,
+        id: ChainId,
+        update: impl Fn(Option<&Chain>) -> Option<Option<Chain>> + Send,
+    ) -> Result<Option<Option<Chain>>, StorageWalletError> {
+        for _ in 0..MAX_UPDATE_ATTEMPTS {
+            let previous = self.read_entry(id).await?;
+            let previous_chain = previous.as_ref().and_then(|entry| entry.chain.as_ref());
+            let Some(chain) = update(previous_chain) else {
+                return Ok(None);
+            };
+            let current = self.read_entry(id).await?;
+            if current.as_ref().map(|entry| (entry.version, entry.nonce))
+                != previous.as_ref().map(|entry| (entry.version, entry.nonce))
+            {
+                continue;
+            }
+            let written = self.write_entry(id, previous.as_ref(), chain).await?;
+            let stored = self.read_entry(id).await?;
+            if stored.is_some_and(|entry| entry.nonce == written.nonce) {
+                return Ok(Some(previous.and_then(|entry| entry.chain)));
+            }
+        }
+        Err(StorageWalletError::Contention(id))
+    }
+
+    async fn read_items(&self) -> Result<Vec<(ChainId, Chain)>, StorageWalletError> {
+        let mut items = Vec::new();
+        for (key, value) in self.store.find_key_values(CHAIN_PREFIX.to_vec()).await? {
+            let entry: Entry = serde_json::from_slice(&value)?;
+            if let Some(chain) = entry.chain {
+                items.push((bcs::from_bytes(&key)?, chain));
+            }
+        }
+        Ok(items)
+    }
+
+    /// Copies the given chains into the wallet and sets the default chain.
+    pub async fn import(
+        &self,
+        chains: impl IntoIterator<Item = (ChainId, Chain)>,
+        default: Option<ChainId>,
+    ) -> Result<(), StorageWalletError> {
+        for (id, chain) in chains {
+            linera_core::Wallet::insert(self, id, chain).await?;
+        }
+        if let Some(default) = default {
+            self.set_default_chain(default).await?;
+        }
+        Ok(())
+    }
+}
+
+impl linera_core::Wallet for StorageWallet {
+    type Error = StorageWalletError;
+
+    async fn get(&self, id: ChainId) -> Result<Option<Chain>, Self::Error> {
+        self.read_chain(id).await
+    }
+
+    async fn remove(&self, id: ChainId) -> Result<Option<Chain>, Self::Error> {
+        let Some(chain) = self
+            .update(id, |previous| previous.is_some().then_some(None))
+            .await?
+            .flatten()
+        else {
+            return Ok(None);
+        };
+        if Self::read_metadata(&*self.store).await?.default == Some(id) {
+            self.write_metadata(None).await?;
+        }
+        Ok(chain)
+    }
+
+    fn items(&self) -> impl Stream<Item = Result<(ChainId, Chain), Self::Error>> {
+        stream::once(self.read_items())
+            .map_ok(|items| stream::iter(items).map(Ok))
+            .try_flatten()
+    }
+
+    async fn insert(&self, id: ChainId, chain: Chain) -> Result<Option<Chain>, Self::Error> {
+        let has_owner = chain.owner.is_some();
+        let previous = self
+            .update(id, |_| Some(Some(chain.clone())))
+            .await?
+            .expect("the update always writes");
+        if has_owner {
+            self.try_set_default(id).await?;
+        }
+        Ok(previous)
+    }
+
+    async fn try_insert(&self, id: ChainId, chain: Chain) -> Result<Option<Chain>, Self::Error> {
+        let inserted = self
+            .update(id, |previous| {
+                previous.is_none().then(|| Some(chain.clone()))
+            })
+            .await?;
+        if inserted.is_some() {
+            self.try_set_default(id).await?;
+            return Ok(None);
+        }
+        self.read_chain(id).await
+    }
+
+    async fn modify(
+        &self,
+        id: ChainId,
+        f: impl Fn(&mut Chain) + Send,
+    ) -> Result<Option<()>, Self::Error> {
+        let outcome = self
+            .update(id, move |previous| {
+                let mut chain = previous?.clone();
+                f(&mut chain);
+                Some(Some(chain))
+            })
+            .await?;
+        Ok(outcome.map(|_| ()))
+    }
+}
+
+/// A [`RunnableWithStore`] job that opens the wallet stored alongside the client's storage.
+pub struct OpenStorageWalletJob;
+
+#[async_trait]
+impl RunnableWithStore for OpenStorageWalletJob {
+    type Output = StorageWallet;
+
+    async fn run<D>(
+        self,
+        config: D::Config,
+        namespace: String,
+        _cache_sizes: StorageCacheConfig,
+    ) -> Result<Self::Output, anyhow::Error>
+    where
+        D: KeyValueDatabase + Clone + Send + Sync + 'static,
+        D::Store: KeyValueStore + Clone + Send + Sync + 'static,
+        D::Error: Send + Sync,
+    {
+        let database = D::maybe_create_and_connect(&config, &wallet_namespace(&namespace)).await?;
+        Ok(StorageWallet::open(database.open_shared(&[])?).await?)
+    }
+}
+
+/// A [`RunnableWithStore`] job that copies a wallet into a new wallet stored alongside the
+/// client's storage.
+pub struct MigrateWalletJob {
+    /// The genesis configuration of the wallet.
+    pub genesis_config: GenesisConfig,
+    /// The chains of the wallet.
+    pub chains: Vec<(ChainId, Chain)>,
+    /// The default chain of the wallet.
+    pub default: Option<ChainId>,
+}
+
+#[async_trait]
+impl RunnableWithStore for MigrateWalletJob {
+    type Output = ();
+
+    async fn run<D>(
+        self,
+        config: D::Config,
+        namespace: String,
+        _cache_sizes: StorageCacheConfig,
+    ) -> Result<Self::Output, anyhow::Error>
+    where
+        D: KeyValueDatabase + Clone + Send + Sync + 'static,
+        D::Store: KeyValueStore + Clone + Send + Sync + 'static,
+        D::Error: Send + Sync,
+    {
+        let database = D::maybe_create_and_connect(&config, &wallet_namespace(&namespace)).await?;
+        let wallet = StorageWallet::create(database.open_shared(&[])?, self.genesis_config).await?;
+        wallet.import(self.chains, self.default).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt as _;
+    use linera_base::{
+        crypto::{AccountPublicKey, CryptoHash},
+        data_types::{Amount, Timestamp},
+        identifiers::ChainId,
+    };
+    use linera_client::config::GenesisConfig;
+    use linera_core::{wallet::Chain, Wallet as _};
+    use linera_execution::committee::Committee;
+    use linera_views::{memory::MemoryDatabase, store::TestKeyValueDatabase as _};
+
+    use super::{StorageWallet, StorageWalletError};
+
+    #[tokio::test]
+    async fn test_storage_wallet_is_shared() -> anyhow::Result<()> {
+        let store = MemoryDatabase::new_test_store().await?;
+        assert!(matches!(
+            StorageWallet::open(store.clone()).await,
+            Err(StorageWalletError::NotFound)
+        ));
+        let genesis_config = GenesisConfig::new(
+            Committee::default(),
+            Timestamp::from(0),
+            "test network".to_string(),
+            AccountPublicKey::test_key(0),
+            Amount::ZERO,
+        );
+        let wallet = StorageWallet::create(store.clone(), genesis_config.clone()).await?;
+        assert!(matches!(
+            StorageWallet::create(store.clone(), genesis_config).await,
+            Err(StorageWalletError::AlreadyExists)
+        ));
+
+        let chain_a = ChainId(CryptoHash::test_hash("a"));
+        let chain_b = ChainId(CryptoHash::test_hash("b"));
+        assert!(wallet
+            .try_insert(chain_a, Chain::default())
+            .await?
+            .is_none());
+        assert!(wallet
+            .try_insert(chain_a, Chain::default())
+            .await?
+            .is_some());
+        wallet.insert(chain_b, Chain::default()).await?;
+        assert_eq!(wallet.default_chain(), Some(chain_a));
+
+        // A second handle, e.g. in another process, sees and updates the same chains.
+        let other = StorageWallet::open(store.clone()).await?;
+        assert_eq!(other.default_chain(), Some(chain_a));
+        other
+            .modify(chain_b, |chain| chain.next_block_height.0 += 1)
+            .await?
+            .expect("chain B is in the wallet");
+        assert_eq!(wallet.get(chain_b).await?.unwrap().next_block_height.0, 1);
+        other.set_default_chain(chain_b).await?;
+        assert!(other.remove(chain_a).await?.is_some());
+        assert!(wallet.remove(chain_a).await?.is_none());
+        assert!(wallet.get(chain_a).await?.is_none());
+
+        let mut chain_ids = wallet.chain_ids().try_collect::<Vec<_>>().await?;
+        chain_ids.sort();
+        assert_eq!(chain_ids, vec![chain_b]);
+        let reopened = StorageWallet::open(store).await?;
+        assert_eq!(reopened.default_chain(), Some(chain_b));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_storage_wallet_versions_removals() -> anyhow::Result<()> {
+        let store = MemoryDatabase::new_test_store().await?;
+        let genesis_config = GenesisConfig::new(
+            Committee::default(),
+            Timestamp::from(0),
+            "test network".to_string(),
+            AccountPublicKey::test_key(0),
+            Amount::ZERO,
+        );
+        let wallet = StorageWallet::create(store, genesis_config).await?;
+        let chain_id = ChainId(CryptoHash::test_hash("a"));
+
+        wallet.insert(chain_id, Chain::default()).await?;
+        wallet.remove(chain_id).await?;
+        // The removal is recorded as a new version rather than by deleting the entry, so a
+        // writer that read the chain before the removal sees its entry change and retries.
+        let removed = wallet
+            .read_entry(chain_id)
+            .await?
+            .expect("removal is versioned");
+        assert_eq!(removed.version, 1);
+        assert!(removed.chain.is_none());
+        assert!(wallet
+            .modify(chain_id, |chain| chain.next_block_height.0 += 1)
+            .await?
+            .is_none());
+        assert!(wallet
+            .try_insert(chain_id, Chain::default())
+            .await?
+            .is_none());
+        assert_eq!(wallet.read_entry(chain_id).await?.unwrap().version, 2);
+        assert_eq!(
+            wallet.chain_ids().try_collect::<Vec<_>>().await?,
+            vec![chain_id]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    io::{BufRead as _, BufReader},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use linera_base::data_types::Timestamp;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+/// How long the client waits for the telemetry endpoint before keeping the reports for
+/// later.
+const SUBMIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Options of the anonymous usage metrics. Reporting is off unless `--enable-telemetry` is
+/// given.
+#[derive(Clone, Debug, clap::Args)]
+pub struct TelemetryOptions {
+    /// Opt in to reporting anonymous usage metrics to the `--telemetry-endpoint`: the name
+    /// of each command (without its arguments), its latency and outcome, and the numbers
+    /// of failed and retried requests to validators. No chain, account or application is
+    /// ever reported.
+    #[arg(long, env = "LINERA_ENABLE_TELEMETRY", requires = "telemetry_endpoint")]
+    pub enable_telemetry: bool,
+
+    /// The URL to which the usage metrics are posted, as a JSON array of reports.
+    #[arg(long, env = "LINERA_TELEMETRY_ENDPOINT")]
+    pub telemetry_endpoint: Option<String>,
+
+    /// The file where the reports are kept until the endpoint receives them. Defaults to
+    /// `telemetry.jsonl` in the Linera configuration directory.
+    #[arg(long, env = "LINERA_TELEMETRY_BUFFER")]
+    pub telemetry_buffer: Option<PathBuf>,
+
+    /// The maximum number of reports kept while the endpoint is unreachable. Older
+    /// reports are dropped first.
+    #[arg(long, default_value = "1000")]
+    pub telemetry_max_buffered_reports: usize,
+}
+
+/// The anonymous usage metrics of one client command.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageReport {
+    /// The name of the command, e.g. `wallet show`.
+    pub command: String,
+    /// How long the command ran, in milliseconds.
+    pub latency_ms: u64,
+    /// Whether the command succeeded.
+    pub success: bool,
+    /// The number of requests to validators.
+    pub validator_requests: u64,
+    /// The number of requests to validators that failed.
+    pub validator_errors: u64,
+    /// The number of requests sent to further validators after the first one failed or
+    /// was slow to respond.
+    pub retries: u64,
+    /// The version of the client.
+    pub version: String,
+    /// The operating system of the client.
+    pub os: String,
+    /// When the command finished.
+    pub timestamp: Timestamp,
+}
+
+impl UsageReport {
+    /// Creates the report of a command that ran for `latency`, with the validator request
+    /// counts of this process.
+    pub fn new(command: String, latency: Duration, success: bool) -> Self {
+        let counts = ValidatorRequestCounts::gather();
+        Self {
+            command,
+            latency_ms: u64::try_from(latency.as_millis()).unwrap_or(u64::MAX),
+            success,
+            validator_requests: counts.requests,
+            validator_errors: counts.requests.saturating_sub(counts.successes),
+            retries: counts.retries,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            os: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+            timestamp: Timestamp::now(),
+        }
+    }
+}
+
+/// The requests made to validators by this process, as recorded by the metrics of the
+/// requests scheduler.
+#[derive(Debug, Default)]
+struct ValidatorRequestCounts {
+    requests: u64,
+    successes: u64,
+    retries: u64,
+}
+
+impl ValidatorRequestCounts {
+    #[cfg(with_metrics)]
+    fn gather() -> Self {
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            reason = "counters hold non-negative integers"
+        )]
+        fn total(family: &prometheus::proto::MetricFamily) -> u64 {
+            family
+                .get_metric()
+                .iter()
+                .map(|metric| metric.get_counter().get_value() as u64)
+                .sum()
+        }
+
+        let mut counts = Self::default();
+        for family in prometheus::gather() {
+            match family.get_name() {
+                "linera_requests_scheduler_request_total" => counts.requests = total(&family),
+                "linera_requests_scheduler_request_success" => counts.successes = total(&family),
+                "linera_requests_scheduler_request_retry_total" => counts.retries = total(&family),
+                _ => {}
+            }
+        }
+        counts
+    }
+
+    #[cfg(not(with_metrics))]
+    fn gather() -> Self {
+        Self::default()
+    }
+}
+
+/// Returns the name of the command in the given command line, e.g. `wallet show`, without
+/// any of its arguments.
+pub fn command_name<C: clap::CommandFactory>(args: impl IntoIterator<Item = String>) -> String {
+    let Ok(matches) = C::command().try_get_matches_from(args) else {
+        return "unknown".to_string();
+    };
+    let mut names = Vec::new();
+    let mut matches = &matches;
+    while let Some((name, sub_matches)) = matches.subcommand() {
+        names.push(name.to_string());
+        matches = sub_matches;
+    }
+    names.join(" ")
+}
+
+impl TelemetryOptions {
+    /// Buffers the report and, if reporting is enabled, sends the buffered reports to the
+    /// endpoint. Reports that could not be sent are kept for the next command.
+    pub async fn submit(&self, report: UsageReport) -> anyhow::Result<()> {
+        let (true, Some(endpoint)) = (self.enable_telemetry, &self.telemetry_endpoint) else {
+            return Ok(());
+        };
+        let buffer = match &self.telemetry_buffer {
+            Some(path) => path.clone(),
+            None => linera_wallet_json::paths::config_dir()?.join("telemetry.jsonl"),
+        };
+        let mut reports = read_buffer(&buffer)?;
+        reports.push(report);
+        let excess = reports
+            .len()
+            .saturating_sub(self.telemetry_max_buffered_reports);
+        reports.drain(..excess);
+        let result = reqwest::Client::new()
+            .post(endpoint)
+            .timeout(SUBMIT_TIMEOUT)
+            .json(&reports)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match result {
+            Ok(_) => {
+                info!(
+                    "Reported {} anonymous usage metrics to {endpoint}",
+                    reports.len()
+                );
+                write_buffer(&buffer, &[])
+            }
+            Err(error) => {
+                debug!(%error, "Keeping {} usage reports for later", reports.len());
+                write_buffer(&buffer, &reports)
+            }
+        }
+    }
+}
+
+/// Reads the reports in the buffer file, skipping the lines that cannot be parsed.
+fn read_buffer(path: &Path) -> anyhow::Result<Vec<UsageReport>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let file = fs_err::File::open(path)?;
+    let mut reports = Vec::new();
+    for line in BufReader::new(file).lines() {
+        if let Ok(report) = serde_json::from_str(&line?) {
+            reports.push(report);
+        }
+    }
+    Ok(reports)
+}
+
+/// Replaces the contents of the buffer file with the given reports, one per line.
+fn write_buffer(path: &Path, reports: &[UsageReport]) -> anyhow::Result<()> {
+    let mut contents = String::new();
+    for report in reports {
+        contents.push_str(&serde_json::to_string(report)?);
+        contents.push('\n');
+    }
+    fs_err::write(path, contents).context("failed to write the usage reports")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{command_name, TelemetryOptions, UsageReport};
+
+    #[derive(clap::Parser)]
+    struct Options {
+        #[arg(long)]
+        _wallet: Option<String>,
+        #[command(subcommand)]
+        _command: Command,
+    }
+
+    #[derive(clap::Subcommand)]
+    enum Command {
+        #[command(subcommand)]
+        Wallet(WalletCommand),
+    }
+
+    #[derive(clap::Subcommand)]
+    enum WalletCommand {
+        Show {
+            _chain_id: Option<String>,
+            #[arg(long)]
+            _short: bool,
+        },
+    }
+
+    #[test]
+    fn command_names_omit_arguments() {
+        let args = [
+            "linera", "--wallet", "w.json", "wallet", "show", "abc", "--short",
+        ];
+        let name = command_name::<Options>(args.map(String::from));
+        assert_eq!(name, "wallet show");
+        assert_eq!(
+            command_name::<Options>(["linera", "--wallet"].map(String::from)),
+            "unknown"
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_are_buffered_while_the_endpoint_is_unreachable() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let buffer = dir.path().join("telemetry.jsonl");
+        let mut options = TelemetryOptions {
+            enable_telemetry: false,
+            // Nothing listens on the discard port.
+            telemetry_endpoint: Some("http://127.0.0.1:9/".to_string()),
+            telemetry_buffer: Some(buffer.clone()),
+            telemetry_max_buffered_reports: 2,
+        };
+        let report = |command: &str| UsageReport::new(command.to_string(), Duration::ZERO, true);
+
+        // Nothing is recorded unless reporting is enabled.
+        options.submit(report("sync")).await?;
+        assert!(!buffer.exists());
+
+        options.enable_telemetry = true;
+        for command in ["sync", "transfer", "wallet show"] {
+            options.submit(report(command)).await?;
+        }
+        let commands = super::read_buffer(&buffer)?
+            .into_iter()
+            .map(|report| report.command)
+            .collect::<Vec<_>>();
+        assert_eq!(commands, ["transfer", "wallet show"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Changing the log filter of a running binary.
+//!
+//! The subscribers installed by this crate wrap their [`EnvFilter`] in a reloadable layer,
+//! so that the filter can be replaced at runtime, e.g. with
+//! `linera_core=debug,linera_rpc=trace,info` to debug specific modules during an incident.
+//! [`router`] exposes the filter over HTTP on `/admin/log-level`.
+
+use std::sync::OnceLock;
+
+use axum::{http::StatusCode, response::IntoResponse, routing::get, Router};
+use tracing::{info, Subscriber};
+use tracing_subscriber::{
+    filter::{LevelFilter, ParseError},
+    reload, EnvFilter,
+};
+
+/// The path of the endpoint reading and changing the log filter.
+pub const LOG_LEVEL_PATH: &str = "/admin/log-level";
+
+/// The filter of the global subscriber, with its subscriber type erased.
+struct LogFilterHandle {
+    reload: Box<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>,
+    current: Box<dyn Fn() -> Option<String> + Send + Sync>,
+}
+
+static LOG_FILTER: OnceLock<LogFilterHandle> = OnceLock::new();
+
+/// An error changing the log filter.
+#[derive(Debug, thiserror::Error)]
+pub enum LogFilterError {
+    /// The directives could not be parsed.
+    #[error("invalid log filter: {0}")]
+    Parse(#[from] ParseError),
+    /// Tracing was not initialized with a reloadable filter.
+    #[error("the log filter of this process cannot be changed")]
+    NotReloadable,
+    /// The subscriber holding the filter is gone.
+    #[error("failed to change the log filter: {0}")]
+    Reload(#[from] reload::Error),
+}
+
+/// Wraps `filter` in a layer that [`set_log_filter`] can change later.
+///
+/// Only the first reloadable filter of the process can be changed.
+pub(crate) fn reloadable<S>(filter: EnvFilter) -> reload::Layer<EnvFilter, S>
+where
+    S: Subscriber + 'static,
+{
+    let (layer, handle) = reload::Layer::new(filter);
+    let current_handle = handle.clone();
+    let filter_handle = LogFilterHandle {
+        reload: Box::new(move |filter| handle.reload(filter)),
+        current: Box::new(move || current_handle.with_current(ToString::to_string).ok()),
+    };
+    if LOG_FILTER.set(filter_handle).is_err() {
+        tracing::debug!("The log filter was already registered");
+    }
+    layer
+}
+
+/// Returns the directives of the current log filter, if it can be changed.
+pub fn log_filter() -> Option<String> {
+    (LOG_FILTER.get()?.current)()
+}
+
+/// Replaces the log filter with the given directives, in the syntax of `RUST_LOG`. Empty
+/// directives log everything at the `info` level.
+pub fn set_log_filter(directives: &str) -> Result<(), LogFilterError> {
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .parse(directives)?;
+    let handle = LOG_FILTER.get().ok_or(LogFilterError::NotReloadable)?;
+    (handle.reload)(filter)?;
+    info!(%directives, "Changed the log filter");
+    Ok(())
+}
+
+/// Returns a router serving the log filter on [`LOG_LEVEL_PATH`]: `GET` returns the current
+/// directives, and `PUT` replaces them with the ones in the request body.
+pub fn router() -> Router {
+    Router::new().route(LOG_LEVEL_PATH, get(get_log_filter).put(put_log_filter))
+}
+
+async fn get_log_filter() -> impl IntoResponse {
+    match log_filter() {
+        Some(filter) => (StatusCode::OK, filter),
+        None => (
+            StatusCode::NOT_FOUND,
+            LogFilterError::NotReloadable.to_string(),
+        ),
+    }
+}
+
+async fn put_log_filter(directives: String) -> impl IntoResponse {
+    match set_log_filter(directives.trim()) {
+        Ok(()) => (StatusCode::OK, log_filter().unwrap_or_default()),
+        Err(error @ LogFilterError::Parse(_)) => (StatusCode::BAD_REQUEST, error.to_string()),
+        Err(error @ LogFilterError::NotReloadable) => (StatusCode::NOT_FOUND, error.to_string()),
+        Err(error @ LogFilterError::Reload(_)) => {
+            (StatusCode::INTERNAL_SERVER_ERROR, error.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::{layer::SubscriberExt as _, EnvFilter, Registry};
+
+    use super::{log_filter, reloadable, set_log_filter, LogFilterError};
+
+    #[test]
+    fn test_set_log_filter() {
+        let subscriber = Registry::default().with(reloadable(EnvFilter::new("warn")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+        assert_eq!(log_filter().as_deref(), Some("warn"));
+
+        set_log_filter("linera_core::chain_worker=debug").unwrap();
+        let filter = log_filter().unwrap();
+        assert!(filter.contains("linera_core::chain_worker=debug"));
+
+        set_log_filter("").unwrap();
+        assert_eq!(log_filter().as_deref(), Some("info"));
+        set_log_filter("linera_core::chain_worker=debug").unwrap();
+
+        assert!(matches!(
+            set_log_filter("linera_core=loud"),
+            Err(LogFilterError::Parse(_))
+        ));
+        assert!(log_filter()
+            .unwrap()
+            .contains("linera_core::chain_worker=debug"));
+    }
+}
@@ -5,6 +5,7 @@
 
 /// Support for emitting traces in the Chrome tracing format.
 pub mod chrome;
+pub mod log_filter;
 pub mod opentelemetry;
 
 use std::{
@@ -81,13 +82,15 @@ impl EnvConfig {
 /// The `LINERA_LOG_DIR` environment variable can be used to configure a directory to
 /// store log files. If it is set, a file named `log_name` with the `log` extension is
 /// created in the directory.
+///
+/// The filter can be changed later with [`log_filter::set_log_filter`].
 pub fn init(log_name: &str) {
     let config = get_env_config(log_name);
     let maybe_log_file_layer = config.maybe_log_file_layer();
     let stderr_layer = config.stderr_layer();
 
     tracing_subscriber::registry()
-        .with(config.env_filter)
+        .with(log_filter::reloadable(config.env_filter))
         .with(maybe_log_file_layer)
         .with(stderr_layer)
         .init();
@@ -64,7 +64,7 @@ fn init_with_tracer_provider(log_name: &str, tracer_provider: &SdkTracerProvider
 
     tracing_subscriber::registry()
         .with(opentelemetry_layer)
-        .with(config.env_filter)
+        .with(crate::tracing::log_filter::reloadable(config.env_filter))
         .with(maybe_log_file_layer)
         .with(stderr_layer)
         .init();
@@ -55,16 +55,89 @@ macro_rules! test_name {
     };
 }
 
+/// Default command running the TypeScript code blocks of Markdown files.
+pub static DEFAULT_TYPESCRIPT_RUNNER: &str = "npx --yes tsx";
+/// Default command running the JavaScript code blocks of Markdown files.
+pub static DEFAULT_JAVASCRIPT_RUNNER: &str = "node";
+/// Default command running the Python code blocks of Markdown files.
+pub static DEFAULT_PYTHON_RUNNER: &str = "python3";
+
+/// The attribute of the code blocks holding the expected output of the previous block.
+const EXPECTED_OUTPUT_ATTRIBUTE: &str = "expected-output";
+/// The delimiter of the here-documents holding the code blocks in the generated script.
+const HEREDOC_DELIMITER: &str = "LINERA_MARKDOWN_EOF";
+
+/// The commands running the code blocks written in other languages than bash. Each command
+/// is given the path of a file holding the code of a block.
+#[derive(Clone, Debug)]
+pub struct ScriptRunners {
+    /// The command running TypeScript code.
+    pub typescript: String,
+    /// The command running JavaScript code.
+    pub javascript: String,
+    /// The command running Python code.
+    pub python: String,
+}
+
+impl Default for ScriptRunners {
+    fn default() -> Self {
+        Self {
+            typescript: DEFAULT_TYPESCRIPT_RUNNER.to_string(),
+            javascript: DEFAULT_JAVASCRIPT_RUNNER.to_string(),
+            python: DEFAULT_PYTHON_RUNNER.to_string(),
+        }
+    }
+}
+
+/// A language of code blocks run through a [`ScriptRunners`] command.
+#[derive(Clone, Copy, Debug)]
+enum ScriptLanguage {
+    TypeScript,
+    JavaScript,
+    Python,
+}
+
+impl ScriptLanguage {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "typescript" | "ts" => Some(Self::TypeScript),
+            "javascript" | "js" => Some(Self::JavaScript),
+            "python" | "py" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::TypeScript => "ts",
+            Self::JavaScript => "js",
+            Self::Python => "py",
+        }
+    }
+
+    fn runner(self, runners: &ScriptRunners) -> &str {
+        match self {
+            Self::TypeScript => &runners.typescript,
+            Self::JavaScript => &runners.javascript,
+            Self::Python => &runners.python,
+        }
+    }
+}
+
 /// A reader over a Markdown document, used to extract embedded code blocks.
 pub struct Markdown<B> {
     buffer: B,
+    runners: ScriptRunners,
 }
 
 impl Markdown<BufReader<fs_err::File>> {
     /// Opens the Markdown file at the given path.
     pub fn new(path: impl AsRef<Path>) -> std::io::Result<Self> {
         let buffer = BufReader::new(fs_err::File::open(path.as_ref())?);
-        Ok(Self { buffer })
+        Ok(Self {
+            buffer,
+            runners: ScriptRunners::default(),
+        })
     }
 }
 
@@ -72,7 +145,20 @@ impl<B> Markdown<B>
 where
     B: BufRead,
 {
-    /// Extracts the embedded bash and GraphQL code blocks as a runnable bash script.
+    /// Uses the given commands to run the code blocks that are not written in bash.
+    pub fn with_runners(self, runners: ScriptRunners) -> Self {
+        Self { runners, ..self }
+    }
+
+    /// Extracts the embedded code blocks as a runnable bash script.
+    ///
+    /// Bash blocks are copied as they are, and GraphQL blocks with a `uri` are sent with
+    /// `curl`. TypeScript, JavaScript and Python blocks are run with the configured
+    /// [`ScriptRunners`], from the current directory; they can read the variables exported
+    /// by earlier bash blocks. A block with the `expected-output` attribute, e.g.
+    /// `` ```text,expected-output ``, must follow one of these blocks and fails the script
+    /// unless it matches the output of that block. Blocks with the `ignore` attribute are
+    /// skipped.
     #[expect(clippy::while_let_on_iterator)]
     pub fn extract_bash_script_to(
         self,
@@ -81,10 +167,71 @@ where
         pause_after_gql_mutations: Option<Duration>,
     ) -> std::io::Result<()> {
         let mut lines = self.buffer.lines();
+        // Whether the output of the last block is available to check an expected output.
+        let mut has_script_output = false;
 
         while let Some(line) = lines.next() {
             let line = line?;
 
+            let info = line.strip_prefix("```").unwrap_or_default();
+            let mut attributes = info.split(',').map(str::trim);
+            let language = attributes.next().unwrap_or_default();
+            let attributes = attributes.collect::<Vec<_>>();
+            if attributes.contains(&EXPECTED_OUTPUT_ATTRIBUTE) {
+                let expected = read_code_block(&mut lines)?;
+                if attributes.contains(&"ignore") {
+                    continue;
+                }
+                if !has_script_output {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "an expected output must follow a TypeScript, JavaScript or Python \
+                         code block",
+                    ));
+                }
+                has_script_output = false;
+                writeln!(
+                    output,
+                    "LINERA_EXPECTED_OUTPUT=$(cat <<'{HEREDOC_DELIMITER}'\n\
+                     {expected}{HEREDOC_DELIMITER}\n\
+                     )"
+                )?;
+                writeln!(
+                    output,
+                    "if [ \"$LINERA_SCRIPT_OUTPUT\" != \"$LINERA_EXPECTED_OUTPUT\" ]; then\n\
+                     \x20   printf 'Unexpected output:\\n%s\\nExpected:\\n%s\\n' \
+                     \"$LINERA_SCRIPT_OUTPUT\" \"$LINERA_EXPECTED_OUTPUT\" >&2\n\
+                     \x20   exit 1\n\
+                     fi\n"
+                )?;
+                continue;
+            }
+            if let Some(language) = ScriptLanguage::from_name(language) {
+                let code = read_code_block(&mut lines)?;
+                if attributes.contains(&"ignore") {
+                    continue;
+                }
+                let extension = language.extension();
+                let runner = language.runner(&self.runners);
+                // The file is written in the current directory so that the code can use
+                // the packages installed there.
+                writeln!(
+                    output,
+                    "LINERA_SCRIPT_DIR=$(mktemp -d ./.linera-script.XXXXXX)\n\
+                     cat > \"$LINERA_SCRIPT_DIR/script.{extension}\" <<'{HEREDOC_DELIMITER}'\n\
+                     {code}{HEREDOC_DELIMITER}\n\
+                     LINERA_SCRIPT_OUTPUT=$( {runner} \"$LINERA_SCRIPT_DIR/script.{extension}\" )\n\
+                     rm -rf \"$LINERA_SCRIPT_DIR\"\n\
+                     printf '%s\\n' \"$LINERA_SCRIPT_OUTPUT\"\n"
+                )?;
+                has_script_output = true;
+                continue;
+            }
+            if !line.starts_with("```") {
+                continue;
+            }
+            has_script_output = false;
+
             if line.starts_with("```bash") {
                 if line.ends_with("ignore") {
                     continue;
@@ -147,6 +294,22 @@ where
     }
 }
 
+/// Reads the lines of a code block up to its closing fence.
+fn read_code_block(
+    lines: &mut impl Iterator<Item = std::io::Result<String>>,
+) -> std::io::Result<String> {
+    let mut code = String::new();
+    for line in lines {
+        let line = line?;
+        if line.starts_with("```") {
+            break;
+        }
+        code += &line;
+        code += "\n";
+    }
+    Ok(code)
+}
+
 /// Returns an HTML response constructing the GraphiQL web page for the given URI.
 pub(crate) async fn graphiql(uri: Uri) -> impl IntoResponse {
     let source = GraphiQLSource::build()
@@ -236,7 +399,10 @@ this will be ignored
 ```
     "#;
     let buffer = std::io::Cursor::new(readme);
-    let markdown = Markdown { buffer };
+    let markdown = Markdown {
+        buffer,
+        runners: ScriptRunners::default(),
+    };
     let mut script = Vec::new();
     markdown
         .extract_bash_script_to(&mut script, None, None)
@@ -244,3 +410,50 @@ this will be ignored
     let expected = "some bash\n\nsome other bash\n\n";
     assert_eq!(String::from_utf8_lossy(&script), expected);
 }
+
+#[test]
+fn test_script_blocks() {
+    let readme = r#"
+```python
+print("hello")
+```
+```text,expected-output
+hello
+```
+```ts,ignore
+skipped
+```
+```javascript
+console.log(1)
+```
+"#;
+    let buffer = std::io::Cursor::new(readme);
+    let markdown = Markdown {
+        buffer,
+        runners: ScriptRunners::default(),
+    }
+    .with_runners(ScriptRunners {
+        javascript: "deno run".to_string(),
+        ..ScriptRunners::default()
+    });
+    let mut script = Vec::new();
+    markdown
+        .extract_bash_script_to(&mut script, None, None)
+        .unwrap();
+    let script = String::from_utf8(script).unwrap();
+    assert!(script.contains("<<'LINERA_MARKDOWN_EOF'\nprint(\"hello\")\nLINERA_MARKDOWN_EOF\n"));
+    assert!(script.contains("$( python3 \"$LINERA_SCRIPT_DIR/script.py\" )"));
+    assert!(script.contains("<<'LINERA_MARKDOWN_EOF'\nhello\nLINERA_MARKDOWN_EOF\n"));
+    assert!(script.contains("$( deno run \"$LINERA_SCRIPT_DIR/script.js\" )"));
+    assert!(!script.contains("skipped"));
+    assert_eq!(script.matches("LINERA_EXPECTED_OUTPUT=").count(), 1);
+
+    let readme = "```text,expected-output\nhello\n```\n";
+    let markdown = Markdown {
+        buffer: std::io::Cursor::new(readme),
+        runners: ScriptRunners::default(),
+    };
+    assert!(markdown
+        .extract_bash_script_to(&mut Vec::new(), None, None)
+        .is_err());
+}
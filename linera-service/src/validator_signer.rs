@@ -0,0 +1,80 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::{Context as _, Result};
+use clap::Parser as _;
+use linera_client::config::ValidatorServerConfig;
+use linera_service::{
+    remote_signer::{AttestationProvider, SignerAuthKey, SignerServer},
+    util,
+};
+use tokio::net::TcpListener;
+
+/// Holds a validator's key and signs on behalf of its workers, which connect with
+/// `linera-server run --remote-signer`. Meant to run inside an enclave or confidential VM
+/// so that the key never leaves it.
+#[derive(clap::Parser)]
+#[command(
+    name = "linera-validator-signer",
+    version = linera_version::VersionInfo::default_clap_str(),
+)]
+struct Args {
+    /// Path to the file containing the server configuration of this validator, including
+    /// its secret key.
+    #[arg(long = "server")]
+    server_config_path: PathBuf,
+
+    /// The address to accept the workers' connections on.
+    #[arg(long, default_value = "127.0.0.1:19100")]
+    listen_address: String,
+
+    /// The key shared with the validator's workers, at least 16 bytes long. Only the
+    /// workers holding it can connect and have their votes signed.
+    #[arg(long, env = "LINERA_VALIDATOR_SIGNER_AUTH_KEY", hide_env_values = true)]
+    auth_key: SignerAuthKey,
+
+    /// The address to serve attestation reports on over HTTP, at
+    /// `/attestation?nonce=<hash>`, for verifiers outside the validator. If not set, the
+    /// reports are only available to the workers.
+    #[arg(long)]
+    attestation_address: Option<String>,
+
+    /// How to obtain attestation reports for the enclave the signer runs in.
+    #[arg(long, value_enum, default_value_t)]
+    attestation: AttestationProvider,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    linera_service::tracing::init("validator-signer");
+
+    let args = Args::parse();
+    let server_config: ValidatorServerConfig = util::read_json(&args.server_config_path)
+        .context("Failed to read the server configuration")?;
+    let secret = server_config
+        .validator_secret
+        .context("The server configuration does not contain the validator's secret key")?;
+    anyhow::ensure!(
+        secret.public() == server_config.validator.public_key,
+        "The validator's secret key does not match its public key"
+    );
+    let listener = TcpListener::bind(&args.listen_address)
+        .await
+        .with_context(|| format!("Failed to listen on {}", args.listen_address))?;
+    let server = Arc::new(SignerServer::new(secret, args.auth_key, args.attestation));
+    if let Some(address) = args.attestation_address {
+        let attestation_listener = TcpListener::bind(&address)
+            .await
+            .with_context(|| format!("Failed to listen on {address}"))?;
+        let server = server.clone();
+        tokio::spawn(async move {
+            if let Err(error) = server.serve_attestation(attestation_listener).await {
+                tracing::error!("Failed to serve attestation reports: {error}");
+            }
+        });
+    }
+    server.serve(listener).await?;
+    Ok(())
+}
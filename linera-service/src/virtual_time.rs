@@ -0,0 +1,218 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Virtual time for local test networks, to test time-dependent applications without
+//! waiting in real time.
+//!
+//! The processes of the network share a clock file holding how far the virtual time is ahead
+//! of the system clock. Each process named by [`VIRTUAL_TIME_ENV`] follows it: block
+//! timestamps, round timeouts and client clocks all move forward when the offset increases.
+//! `linera net up --virtual-time` creates the file and serves an admin endpoint that advances
+//! it.
+
+use std::{
+    env,
+    net::SocketAddr,
+    num::NonZeroU16,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::Context as _;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Json, Router,
+};
+use linera_base::data_types::{TimeDelta, Timestamp};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+/// The environment variable naming the clock file that a process follows.
+pub const VIRTUAL_TIME_ENV: &str = "LINERA_VIRTUAL_TIME";
+
+/// The default local port of the admin endpoint.
+pub const DEFAULT_ADMIN_PORT: NonZeroU16 = NonZeroU16::new(8082).unwrap();
+
+/// How often processes check the clock file for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The file holding the virtual time offset of a local network, in microseconds.
+#[derive(Clone, Debug)]
+pub struct VirtualClockFile {
+    path: PathBuf,
+}
+
+impl VirtualClockFile {
+    /// Returns the clock file at `path`.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Creates the clock file at `path`, starting without any offset.
+    pub fn create(path: impl Into<PathBuf>) -> anyhow::Result<Self> {
+        let clock = Self::new(path);
+        clock.write(TimeDelta::ZERO)?;
+        Ok(clock)
+    }
+
+    /// Returns the path of the clock file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the current offset.
+    pub fn read(&self) -> anyhow::Result<TimeDelta> {
+        let contents = fs_err::read_to_string(&self.path)?;
+        let micros = contents
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid virtual time in {}", self.path.display()))?;
+        Ok(TimeDelta::from_micros(micros))
+    }
+
+    /// Moves the virtual time forward by `delta`, and returns the new offset.
+    pub fn advance(&self, delta: TimeDelta) -> anyhow::Result<TimeDelta> {
+        let offset = self.read()?.saturating_add(delta);
+        self.write(offset)?;
+        Ok(offset)
+    }
+
+    /// Replaces the file at once, so that other processes never read a partial offset.
+    fn write(&self, offset: TimeDelta) -> anyhow::Result<()> {
+        let temporary = self.path.with_extension("tmp");
+        fs_err::write(&temporary, offset.as_micros().to_string())?;
+        fs_err::rename(&temporary, &self.path)?;
+        Ok(())
+    }
+
+    /// Applies the current offset to this process, and keeps applying it as it changes.
+    ///
+    /// Must be called from within a Tokio runtime.
+    pub fn follow(self) -> anyhow::Result<()> {
+        Timestamp::set_virtual_offset(self.read()?);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                match self.read() {
+                    // The offset only increases, so that time never goes backwards.
+                    Ok(offset) if offset > Timestamp::virtual_offset() => {
+                        Timestamp::set_virtual_offset(offset)
+                    }
+                    Ok(_) => {}
+                    Err(error) => warn!(%error, "Failed to read the virtual time"),
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Follows the clock file named by [`VIRTUAL_TIME_ENV`], if it is set.
+///
+/// Must be called from within a Tokio runtime.
+pub fn follow_from_env() -> anyhow::Result<()> {
+    let Some(path) = env::var_os(VIRTUAL_TIME_ENV) else {
+        return Ok(());
+    };
+    let clock = VirtualClockFile::new(path);
+    info!("Following the virtual time in {}", clock.path().display());
+    clock
+        .follow()
+        .with_context(|| format!("invalid {VIRTUAL_TIME_ENV}"))
+}
+
+/// The virtual time, as reported by the admin endpoint.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VirtualTime {
+    /// The current virtual time, in microseconds since the Unix epoch.
+    now: Timestamp,
+    /// How far the virtual time is ahead of the system clock, in microseconds.
+    offset: TimeDelta,
+}
+
+/// A request to move the virtual time forward.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Advance {
+    /// The number of microseconds to move forward by.
+    micros: u64,
+}
+
+/// Returns the routes of the admin endpoint: `GET /time` returns the virtual time, and
+/// `POST /advance` with `{"micros": N}` moves it forward.
+pub fn router(clock: VirtualClockFile) -> Router {
+    Router::new()
+        .route("/time", get(time))
+        .route("/advance", post(advance))
+        .with_state(clock)
+}
+
+async fn time(State(clock): State<VirtualClockFile>) -> impl IntoResponse {
+    match clock.read() {
+        Ok(offset) => Ok(Json(virtual_time(offset))),
+        Err(error) => Err((StatusCode::INTERNAL_SERVER_ERROR, error.to_string())),
+    }
+}
+
+async fn advance(
+    State(clock): State<VirtualClockFile>,
+    Json(request): Json<Advance>,
+) -> impl IntoResponse {
+    match clock.advance(TimeDelta::from_micros(request.micros)) {
+        Ok(offset) => {
+            info!(micros = request.micros, "Virtual time advanced");
+            Ok(Json(virtual_time(offset)))
+        }
+        Err(error) => Err((StatusCode::INTERNAL_SERVER_ERROR, error.to_string())),
+    }
+}
+
+fn virtual_time(offset: TimeDelta) -> VirtualTime {
+    let now = Timestamp::now()
+        .saturating_sub(Timestamp::virtual_offset())
+        .saturating_add(offset);
+    VirtualTime { now, offset }
+}
+
+/// Serves the admin endpoint of the virtual time on the given local port, until cancelled.
+pub async fn serve(
+    clock: VirtualClockFile,
+    port: NonZeroU16,
+    cancellation_token: CancellationToken,
+) -> anyhow::Result<()> {
+    let address = SocketAddr::from(([127, 0, 0, 1], port.get()));
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    info!("Advance the virtual time with `POST http://{address}/advance`");
+    axum::serve(listener, router(clock))
+        .with_graceful_shutdown(cancellation_token.cancelled_owned())
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::data_types::TimeDelta;
+
+    use super::VirtualClockFile;
+
+    #[test]
+    fn test_virtual_clock_file() -> anyhow::Result<()> {
+        let directory = tempfile::tempdir()?;
+        let clock = VirtualClockFile::create(directory.path().join("virtual_time"))?;
+        assert_eq!(clock.read()?, TimeDelta::ZERO);
+        let hour = TimeDelta::from_secs(3600);
+        assert_eq!(clock.advance(hour)?, hour);
+        assert_eq!(clock.advance(hour)?, TimeDelta::from_secs(7200));
+        assert_eq!(
+            VirtualClockFile::new(clock.path()).read()?,
+            TimeDelta::from_secs(7200)
+        );
+        Ok(())
+    }
+}
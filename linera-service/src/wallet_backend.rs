@@ -0,0 +1,141 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! The choice between the wallet backends supported by the client.
+
+use futures::{Stream, StreamExt as _, TryStreamExt as _};
+use linera_base::identifiers::ChainId;
+use linera_client::config::GenesisConfig;
+use linera_core::wallet::Chain;
+use linera_persistent as persistent;
+
+use crate::{
+    storage_wallet::{StorageWallet, StorageWalletError},
+    Wallet,
+};
+
+/// Where the client keeps its wallet.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum WalletBackend {
+    /// A JSON file, given by `--wallet`.
+    #[default]
+    File,
+    /// A namespace of the client's storage, which can be shared by several clients.
+    Storage,
+}
+
+/// An error of a [`ClientWallet`].
+#[derive(Debug, thiserror::Error)]
+pub enum ClientWalletError {
+    /// The wallet file could not be written.
+    #[error(transparent)]
+    File(#[from] persistent::file::Error),
+    /// The storage wallet failed.
+    #[error(transparent)]
+    Storage(#[from] StorageWalletError),
+}
+
+/// The wallet of a client, using either backend.
+pub enum ClientWallet {
+    /// A wallet file.
+    File(Box<Wallet>),
+    /// A wallet in storage.
+    Storage(StorageWallet),
+}
+
+impl ClientWallet {
+    /// Returns the network's genesis configuration.
+    pub fn genesis_config(&self) -> &GenesisConfig {
+        match self {
+            Self::File(wallet) => wallet.genesis_config(),
+            Self::Storage(wallet) => wallet.genesis_config(),
+        }
+    }
+
+    /// Returns the admin chain ID from the genesis configuration.
+    pub fn genesis_admin_chain_id(&self) -> ChainId {
+        match self {
+            Self::File(wallet) => wallet.genesis_admin_chain_id(),
+            Self::Storage(wallet) => wallet.genesis_admin_chain_id(),
+        }
+    }
+
+    /// Returns the default chain, if one is set.
+    pub fn default_chain(&self) -> Option<ChainId> {
+        match self {
+            Self::File(wallet) => wallet.default_chain(),
+            Self::Storage(wallet) => wallet.default_chain(),
+        }
+    }
+
+    /// Sets the default chain, which must already be in the wallet.
+    pub async fn set_default_chain(&mut self, id: ChainId) -> Result<(), ClientWalletError> {
+        match self {
+            Self::File(wallet) => Ok(wallet.set_default_chain(id)?),
+            Self::Storage(wallet) => Ok(wallet.set_default_chain(id).await?),
+        }
+    }
+
+    /// Writes the wallet file. Wallets in storage are written on every change.
+    pub fn save(&self) -> Result<(), ClientWalletError> {
+        match self {
+            Self::File(wallet) => Ok(wallet.save()?),
+            Self::Storage(_) => Ok(()),
+        }
+    }
+}
+
+impl linera_core::Wallet for ClientWallet {
+    type Error = ClientWalletError;
+
+    async fn get(&self, id: ChainId) -> Result<Option<Chain>, Self::Error> {
+        match self {
+            Self::File(wallet) => Ok(linera_core::Wallet::get(wallet, id).await?),
+            Self::Storage(wallet) => Ok(wallet.get(id).await?),
+        }
+    }
+
+    async fn remove(&self, id: ChainId) -> Result<Option<Chain>, Self::Error> {
+        match self {
+            Self::File(wallet) => Ok(linera_core::Wallet::remove(wallet, id).await?),
+            Self::Storage(wallet) => Ok(wallet.remove(id).await?),
+        }
+    }
+
+    fn items(&self) -> impl Stream<Item = Result<(ChainId, Chain), Self::Error>> {
+        match self {
+            Self::File(wallet) => linera_core::Wallet::items(wallet)
+                .map_err(ClientWalletError::from)
+                .left_stream(),
+            Self::Storage(wallet) => wallet
+                .items()
+                .map_err(ClientWalletError::from)
+                .right_stream(),
+        }
+    }
+
+    async fn insert(&self, id: ChainId, chain: Chain) -> Result<Option<Chain>, Self::Error> {
+        match self {
+            Self::File(wallet) => Ok(linera_core::Wallet::insert(wallet, id, chain).await?),
+            Self::Storage(wallet) => Ok(wallet.insert(id, chain).await?),
+        }
+    }
+
+    async fn try_insert(&self, id: ChainId, chain: Chain) -> Result<Option<Chain>, Self::Error> {
+        match self {
+            Self::File(wallet) => Ok(linera_core::Wallet::try_insert(wallet, id, chain).await?),
+            Self::Storage(wallet) => Ok(wallet.try_insert(id, chain).await?),
+        }
+    }
+
+    async fn modify(
+        &self,
+        id: ChainId,
+        f: impl Fn(&mut Chain) + Send,
+    ) -> Result<Option<()>, Self::Error> {
+        match self {
+            Self::File(wallet) => Ok(linera_core::Wallet::modify(wallet, id, f).await?),
+            Self::Storage(wallet) => Ok(wallet.modify(id, f).await?),
+        }
+    }
+}
@@ -0,0 +1,579 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, ensure, Context as _};
+use hmac::{Hmac, Mac as _};
+use linera_base::identifiers::{ApplicationId, ChainId};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use tracing::warn;
+
+/// The default header carrying the hex-encoded HMAC-SHA256 signature of a request.
+const DEFAULT_SIGNATURE_HEADER: &str = "X-Linera-Signature";
+/// The default header carrying the time a request was sent, in seconds since the Unix epoch.
+const DEFAULT_TIMESTAMP_HEADER: &str = "X-Linera-Timestamp";
+/// The default header carrying the delivery ID of a request.
+const DEFAULT_IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+/// The default number of seconds a request's timestamp may differ from the local time.
+const DEFAULT_TOLERANCE_SECS: u64 = 300;
+
+/// The configuration file of the webhook bridge, listing the accepted routes.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// The file remembering the deliveries already executed, so that they are not executed
+    /// again after a restart. Defaults to the configuration file with the extension
+    /// `deliveries.json`.
+    #[serde(default)]
+    pub deliveries_file: Option<PathBuf>,
+    /// The routes of the bridge.
+    #[serde(default)]
+    pub routes: Vec<WebhookRoute>,
+}
+
+/// A route mapping authenticated HTTP POST requests on `/webhooks/<name>` to a GraphQL
+/// mutation of an application.
+///
+/// Requests are signed with the HMAC-SHA256 of `<timestamp>.<delivery ID>.<body>`, so that
+/// a captured request cannot be replayed with a new timestamp or delivery ID.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookRoute {
+    /// The name of the route, used in the URL.
+    pub name: String,
+    /// The chain on which the application is queried.
+    pub chain_id: ChainId,
+    /// The application receiving the mutation.
+    pub application_id: ApplicationId,
+    /// The shared secret used to verify the HMAC-SHA256 signature of requests.
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// The environment variable holding the shared secret, as an alternative to `secret`.
+    #[serde(default)]
+    pub secret_env: Option<String>,
+    /// The header carrying the hex-encoded signature, optionally prefixed with `sha256=`.
+    #[serde(default)]
+    pub signature_header: Option<String>,
+    /// The header carrying the time the request was sent, in seconds since the Unix epoch.
+    #[serde(default)]
+    pub timestamp_header: Option<String>,
+    /// How many seconds the timestamp of a request may differ from the local time.
+    #[serde(default)]
+    pub tolerance_secs: Option<u64>,
+    /// The header carrying the delivery ID, which every request must have. A request with
+    /// an ID that was already executed is answered with the response of the first one and
+    /// not executed again.
+    #[serde(default)]
+    pub idempotency_header: Option<String>,
+    /// The GraphQL document sent to the application.
+    pub query: String,
+    /// The template of the GraphQL variables. Strings of the form `{{ path }}` are replaced
+    /// by the value found at the dot-separated `path` in the JSON body of the request.
+    #[serde(default)]
+    pub variables: Value,
+}
+
+impl WebhookConfig {
+    /// Reads the configuration from a TOML file.
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let content = fs_err::read_to_string(path)?;
+        let mut config: Self = toml::from_str(&content)
+            .with_context(|| format!("invalid webhook configuration in {}", path.display()))?;
+        config
+            .deliveries_file
+            .get_or_insert_with(|| path.with_extension("deliveries.json"));
+        Ok(config)
+    }
+}
+
+/// A route ready to serve requests.
+struct ActiveRoute {
+    chain_id: ChainId,
+    application_id: ApplicationId,
+    secret: Vec<u8>,
+    signature_header: String,
+    timestamp_header: String,
+    tolerance_secs: u64,
+    idempotency_header: String,
+    query: String,
+    variables: Value,
+}
+
+/// A delivery accepted by a route, remembered until its timestamp is stale.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Delivery {
+    /// When the timestamp of the delivery becomes stale, in seconds since the Unix epoch.
+    expires_at: u64,
+    /// The response, or `None` while the request executes.
+    response: Option<Vec<u8>>,
+}
+
+/// The deliveries, by route name and delivery ID.
+type Deliveries = BTreeMap<String, BTreeMap<String, Delivery>>;
+
+/// A request accepted by the bridge, to be sent to an application.
+#[derive(Debug)]
+pub struct WebhookRequest {
+    /// The chain on which the application is queried.
+    pub chain_id: ChainId,
+    /// The application receiving the request.
+    pub application_id: ApplicationId,
+    /// The serialized GraphQL request.
+    pub request: Vec<u8>,
+}
+
+/// The errors of the webhook bridge that are reported to the caller.
+#[derive(Debug, thiserror::Error)]
+pub enum WebhookError {
+    /// No route with the given name exists.
+    #[error("unknown webhook route: {0}")]
+    UnknownRoute(String),
+    /// The signature, timestamp or delivery ID is missing, or the signature is invalid.
+    #[error("missing or invalid webhook signature")]
+    InvalidSignature,
+    /// The timestamp is too far from the local time.
+    #[error("stale webhook timestamp")]
+    StaleTimestamp,
+    /// A request with the same delivery ID is still executing.
+    #[error("webhook delivery {0} is already being processed")]
+    DeliveryInProgress(String),
+    /// The body is not valid JSON or lacks a field used by the template.
+    #[error("invalid webhook payload: {0}")]
+    InvalidPayload(String),
+}
+
+/// Maps authenticated incoming HTTP requests to GraphQL mutations of applications.
+pub struct WebhookBridge {
+    routes: BTreeMap<String, ActiveRoute>,
+    /// The deliveries whose timestamps are not stale yet. Requests with stale timestamps
+    /// are rejected, so older deliveries cannot be replayed and need not be remembered.
+    deliveries: Mutex<Deliveries>,
+    deliveries_file: Option<PathBuf>,
+    /// Serializes the writes of `deliveries_file`.
+    persist_lock: tokio::sync::Mutex<()>,
+}
+
+impl WebhookBridge {
+    /// Creates a bridge from its configuration, resolving the route secrets and loading
+    /// the deliveries already executed.
+    pub fn new(config: WebhookConfig) -> anyhow::Result<Self> {
+        let mut routes = BTreeMap::new();
+        for route in config.routes {
+            let secret = match (route.secret, route.secret_env) {
+                (Some(secret), None) => secret,
+                (None, Some(variable)) => std::env::var(&variable).with_context(|| {
+                    format!("missing secret variable {variable} of route {}", route.name)
+                })?,
+                _ => bail!(
+                    "exactly one of `secret` and `secret_env` must be set for route {}",
+                    route.name
+                ),
+            };
+            ensure!(!secret.is_empty(), "empty secret for route {}", route.name);
+            let active = ActiveRoute {
+                chain_id: route.chain_id,
+                application_id: route.application_id,
+                secret: secret.into_bytes(),
+                signature_header: route
+                    .signature_header
+                    .unwrap_or_else(|| DEFAULT_SIGNATURE_HEADER.to_string()),
+                timestamp_header: route
+                    .timestamp_header
+                    .unwrap_or_else(|| DEFAULT_TIMESTAMP_HEADER.to_string()),
+                tolerance_secs: route.tolerance_secs.unwrap_or(DEFAULT_TOLERANCE_SECS),
+                idempotency_header: route
+                    .idempotency_header
+                    .unwrap_or_else(|| DEFAULT_IDEMPOTENCY_HEADER.to_string()),
+                query: route.query,
+                variables: route.variables,
+            };
+            ensure!(
+                routes.insert(route.name.clone(), active).is_none(),
+                "duplicate webhook route {}",
+                route.name
+            );
+        }
+        let deliveries = match &config.deliveries_file {
+            Some(path) if path.exists() => {
+                let content = fs_err::read(path)?;
+                serde_json::from_slice(&content)
+                    .with_context(|| format!("invalid webhook deliveries in {}", path.display()))?
+            }
+            _ => Deliveries::new(),
+        };
+        Ok(Self {
+            routes,
+            deliveries: Mutex::new(deliveries),
+            deliveries_file: config.deliveries_file,
+            persist_lock: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Verifies a request on the given route and executes it with `execute`, unless its
+    /// delivery ID was already executed, in which case the first response is returned.
+    pub async fn handle<F, Fut, E>(
+        &self,
+        name: &str,
+        headers: &axum::http::HeaderMap,
+        body: &[u8],
+        execute: F,
+    ) -> Result<Vec<u8>, E>
+    where
+        F: FnOnce(WebhookRequest) -> Fut,
+        Fut: std::future::Future<Output = Result<Vec<u8>, E>>,
+        E: From<WebhookError>,
+    {
+        let route = self
+            .routes
+            .get(name)
+            .ok_or_else(|| WebhookError::UnknownRoute(name.to_string()))?;
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .ok_or(WebhookError::InvalidSignature)
+        };
+        let signature = header(&route.signature_header)?;
+        let timestamp = header(&route.timestamp_header)?;
+        let delivery_id = header(&route.idempotency_header)?;
+        verify_signature(&route.secret, timestamp, delivery_id, body, signature)?;
+        let timestamp = timestamp
+            .trim()
+            .parse::<u64>()
+            .map_err(|_| WebhookError::InvalidSignature)?;
+        let now = unix_time();
+        if now.abs_diff(timestamp) > route.tolerance_secs {
+            return Err(WebhookError::StaleTimestamp.into());
+        }
+        let payload: Value = serde_json::from_slice(body)
+            .map_err(|error| WebhookError::InvalidPayload(error.to_string()))?;
+        let variables = render_template(&route.variables, &payload)?;
+        let request = WebhookRequest {
+            chain_id: route.chain_id,
+            application_id: route.application_id,
+            request: serde_json::to_vec(&serde_json::json!({
+                "query": route.query,
+                "variables": variables,
+            }))
+            .expect("serializing JSON values should not fail"),
+        };
+
+        let expires_at = timestamp.saturating_add(route.tolerance_secs);
+        if let Some(response) = self.begin_delivery(name, delivery_id, expires_at, now)? {
+            return Ok(response);
+        }
+        match execute(request).await {
+            Ok(response) => {
+                self.complete_delivery(name, delivery_id, response.clone())
+                    .await;
+                Ok(response)
+            }
+            Err(error) => {
+                // Let the sender retry the delivery.
+                if let Some(deliveries) = self.lock_deliveries().get_mut(name) {
+                    deliveries.remove(delivery_id);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    fn lock_deliveries(&self) -> std::sync::MutexGuard<'_, Deliveries> {
+        self.deliveries
+            .lock()
+            .expect("the webhook deliveries lock should not be poisoned")
+    }
+
+    /// Records that the delivery is executing, unless it already was executed, in which
+    /// case its response is returned. Forgets the deliveries that became stale.
+    fn begin_delivery(
+        &self,
+        name: &str,
+        delivery_id: &str,
+        expires_at: u64,
+        now: u64,
+    ) -> Result<Option<Vec<u8>>, WebhookError> {
+        let mut deliveries = self.lock_deliveries();
+        for route_deliveries in deliveries.values_mut() {
+            route_deliveries.retain(|_, delivery| delivery.expires_at >= now);
+        }
+        let route_deliveries = deliveries.entry(name.to_string()).or_default();
+        match route_deliveries.get(delivery_id) {
+            Some(Delivery {
+                response: Some(response),
+                ..
+            }) => return Ok(Some(response.clone())),
+            Some(Delivery { response: None, .. }) => {
+                return Err(WebhookError::DeliveryInProgress(delivery_id.to_string()));
+            }
+            None => {}
+        }
+        let delivery = Delivery {
+            expires_at,
+            response: None,
+        };
+        route_deliveries.insert(delivery_id.to_string(), delivery);
+        Ok(None)
+    }
+
+    /// Records the response of an executed delivery and persists the executed deliveries.
+    async fn complete_delivery(&self, name: &str, delivery_id: &str, response: Vec<u8>) {
+        if let Some(delivery) = self
+            .lock_deliveries()
+            .get_mut(name)
+            .and_then(|deliveries| deliveries.get_mut(delivery_id))
+        {
+            delivery.response = Some(response);
+        }
+        if let Err(error) = self.persist().await {
+            warn!("Failed to persist the webhook deliveries: {error:#}");
+        }
+    }
+
+    async fn persist(&self) -> anyhow::Result<()> {
+        let Some(path) = &self.deliveries_file else {
+            return Ok(());
+        };
+        let _guard = self.persist_lock.lock().await;
+        let executed = self
+            .lock_deliveries()
+            .iter()
+            .map(|(name, deliveries)| {
+                let executed = deliveries
+                    .iter()
+                    .filter(|(_, delivery)| delivery.response.is_some())
+                    .map(|(id, delivery)| (id.clone(), delivery.clone()))
+                    .collect::<BTreeMap<_, _>>();
+                (name.clone(), executed)
+            })
+            .collect::<Deliveries>();
+        let temporary = path.with_extension("tmp");
+        fs_err::tokio::write(&temporary, serde_json::to_vec(&executed)?).await?;
+        fs_err::tokio::rename(&temporary, path).await?;
+        Ok(())
+    }
+}
+
+/// Returns the current time in seconds since the Unix epoch.
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+/// Checks that `signature` is the hex-encoded HMAC-SHA256 of
+/// `<timestamp>.<delivery ID>.<body>` with the given secret.
+fn verify_signature(
+    secret: &[u8],
+    timestamp: &str,
+    delivery_id: &str,
+    body: &[u8],
+    signature: &str,
+) -> Result<(), WebhookError> {
+    let signature = signature.trim();
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+    let signature = hex::decode(signature).map_err(|_| WebhookError::InvalidSignature)?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.as_bytes());
+    mac.update(b".");
+    mac.update(delivery_id.as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    mac.verify_slice(&signature)
+        .map_err(|_| WebhookError::InvalidSignature)
+}
+
+/// Replaces the placeholders of the template with values from the payload.
+fn render_template(template: &Value, payload: &Value) -> Result<Value, WebhookError> {
+    Ok(match template {
+        Value::String(string) => match placeholder_path(string) {
+            Some(path) => lookup(payload, path)?.clone(),
+            None => Value::String(string.clone()),
+        },
+        Value::Array(values) => Value::Array(
+            values
+                .iter()
+                .map(|value| render_template(value, payload))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| Ok((name.clone(), render_template(value, payload)?)))
+                .collect::<Result<_, WebhookError>>()?,
+        ),
+        value => value.clone(),
+    })
+}
+
+/// Returns the path of a `{{ path }}` placeholder.
+fn placeholder_path(string: &str) -> Option<&str> {
+    let path = string.strip_prefix("{{")?.strip_suffix("}}")?.trim();
+    (!path.is_empty()).then_some(path)
+}
+
+/// Returns the value at the dot-separated `path` of the payload.
+fn lookup<'a>(payload: &'a Value, path: &str) -> Result<&'a Value, WebhookError> {
+    path.split('.').try_fold(payload, |value, segment| {
+        let next = match value {
+            Value::Array(values) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| values.get(index)),
+            value => value.get(segment),
+        };
+        next.ok_or_else(|| WebhookError::InvalidPayload(format!("missing field `{path}`")))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderMap;
+    use hmac::{Hmac, Mac as _};
+    use linera_base::{
+        crypto::CryptoHash,
+        identifiers::{ApplicationId, ChainId},
+    };
+    use serde_json::json;
+    use sha2::Sha256;
+
+    use super::{
+        render_template, unix_time, verify_signature, WebhookBridge, WebhookConfig, WebhookError,
+        WebhookRequest, WebhookRoute,
+    };
+
+    fn sign(secret: &[u8], timestamp: &str, delivery_id: &str, body: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+        mac.update(format!("{timestamp}.{delivery_id}.").as_bytes());
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let body = br#"{"amount":"10"}"#;
+        let signature = sign(b"secret", "1700000000", "delivery-1", body);
+        assert!(verify_signature(b"secret", "1700000000", "delivery-1", body, &signature).is_ok());
+        assert!(verify_signature(
+            b"secret",
+            "1700000000",
+            "delivery-1",
+            body,
+            &format!("sha256={signature}")
+        )
+        .is_ok());
+        assert!(matches!(
+            verify_signature(b"other", "1700000000", "delivery-1", body, &signature),
+            Err(WebhookError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify_signature(b"secret", "1700000001", "delivery-1", body, &signature),
+            Err(WebhookError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify_signature(b"secret", "1700000000", "delivery-2", body, &signature),
+            Err(WebhookError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify_signature(b"secret", "1700000000", "delivery-1", body, "not hex"),
+            Err(WebhookError::InvalidSignature)
+        ));
+    }
+
+    fn respond(
+        response: &'static str,
+    ) -> impl FnOnce(WebhookRequest) -> std::future::Ready<Result<Vec<u8>, WebhookError>> {
+        move |_| std::future::ready(Ok(response.as_bytes().to_vec()))
+    }
+
+    #[tokio::test]
+    async fn test_deliveries_are_executed_once_and_persisted() {
+        let directory = tempfile::tempdir().unwrap();
+        let config = || WebhookConfig {
+            deliveries_file: Some(directory.path().join("deliveries.json")),
+            routes: vec![WebhookRoute {
+                name: "mint".to_string(),
+                chain_id: ChainId(CryptoHash::test_hash("chain")),
+                application_id: ApplicationId::new(CryptoHash::test_hash("application")),
+                secret: Some("secret".to_string()),
+                secret_env: None,
+                signature_header: None,
+                timestamp_header: None,
+                tolerance_secs: None,
+                idempotency_header: None,
+                query: "mutation { mint }".to_string(),
+                variables: json!({}),
+            }],
+        };
+        let headers = |timestamp: u64, delivery_id: &str| {
+            let timestamp = timestamp.to_string();
+            let signature = sign(b"secret", &timestamp, delivery_id, b"{}");
+            let mut headers = HeaderMap::new();
+            headers.insert("X-Linera-Signature", signature.parse().unwrap());
+            headers.insert("X-Linera-Timestamp", timestamp.parse().unwrap());
+            headers.insert("Idempotency-Key", delivery_id.parse().unwrap());
+            headers
+        };
+        let bridge = WebhookBridge::new(config()).unwrap();
+        let now = unix_time();
+        let response = bridge
+            .handle("mint", &headers(now, "1"), b"{}", respond("first"))
+            .await
+            .unwrap();
+        assert_eq!(response, b"first");
+        let response = bridge
+            .handle("mint", &headers(now, "1"), b"{}", respond("second"))
+            .await
+            .unwrap();
+        assert_eq!(response, b"first");
+        assert!(matches!(
+            bridge
+                .handle("mint", &headers(now - 3600, "2"), b"{}", respond("stale"))
+                .await,
+            Err(WebhookError::StaleTimestamp)
+        ));
+
+        // The executed delivery is still known after a restart.
+        let bridge = WebhookBridge::new(config()).unwrap();
+        let response = bridge
+            .handle("mint", &headers(now, "1"), b"{}", respond("third"))
+            .await
+            .unwrap();
+        assert_eq!(response, b"first");
+    }
+
+    #[test]
+    fn test_render_template() {
+        let payload = json!({
+            "data": { "owner": "0x1234", "amounts": [5, 7] },
+            "kind": "payment",
+        });
+        let template = json!({
+            "owner": "{{ data.owner }}",
+            "amount": "{{data.amounts.1}}",
+            "note": "fixed",
+            "nested": ["{{ kind }}", 3],
+        });
+        assert_eq!(
+            render_template(&template, &payload).unwrap(),
+            json!({
+                "owner": "0x1234",
+                "amount": 7,
+                "note": "fixed",
+                "nested": ["payment", 3],
+            })
+        );
+        assert!(matches!(
+            render_template(&json!("{{ data.missing }}"), &payload),
+            Err(WebhookError::InvalidPayload(_))
+        ));
+    }
+}
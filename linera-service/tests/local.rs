@@ -4,8 +4,11 @@
 use std::{path::PathBuf, process::Command};
 
 use anyhow::Result;
-use linera_base::command::resolve_binary;
-use linera_service::cli_wrappers::{local_net::PathProvider, ClientWrapper, Network, OnClientDrop};
+use linera_base::{command::resolve_binary, vm::VmRuntime};
+use linera_service::{
+    cli_wrappers::{local_net::PathProvider, ClientWrapper, Network, OnClientDrop},
+    project::Project,
+};
 
 mod common;
 
@@ -46,6 +49,31 @@ async fn test_project_new() -> Result<()> {
     Ok(())
 }
 
+#[test_log::test(tokio::test)]
+async fn test_project_new_evm() -> Result<()> {
+    let path_provider = PathProvider::create_temporary_directory()?;
+    let id = 0;
+    let client = ClientWrapper::new(
+        path_provider,
+        Network::Grpc,
+        None,
+        id,
+        OnClientDrop::LeakChains,
+    );
+    let tmp_dir = client.project_new_evm("init-test").await?;
+    let project_dir = tmp_dir.path().join("init-test");
+    for file in ["foundry.toml", "src/InitTest.sol", "test/InitTest.t.sol"] {
+        assert!(project_dir.join(file).exists(), "missing {file}");
+    }
+    let contract = std::fs::read_to_string(project_dir.join("src/InitTest.sol"))?;
+    assert!(contract.contains("contract InitTest {"));
+
+    let project = Project::from_existing_project(&project_dir)?;
+    assert_eq!(project.vm_runtime(), VmRuntime::Evm);
+
+    Ok(())
+}
+
 #[test_log::test(tokio::test)]
 async fn test_project_test() -> Result<()> {
     let path_provider = PathProvider::create_temporary_directory()?;
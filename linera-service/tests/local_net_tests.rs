@@ -1019,7 +1019,7 @@ async fn test_end_to_end_benchmark(mut config: LocalNetConfig) -> Result<()> {
     use std::collections::BTreeMap;
 
     use fungible::{FungibleTokenAbi, InitialState, Parameters};
-    use linera_service::cli::command::{BenchmarkCommand, BenchmarkOptions};
+    use linera_service::cli::command::{BenchmarkCommand, BenchmarkOptions, BenchmarkWorkload};
 
     config.num_other_initial_chains = 2;
     let _guard = INTEGRATION_TEST_GUARD.lock().await;
@@ -1075,6 +1075,22 @@ async fn test_end_to_end_benchmark(mut config: LocalNetConfig) -> Result<()> {
         })
         .await?;
 
+    // And with blocks publishing data blobs.
+    client
+        .benchmark(BenchmarkCommand::Single {
+            options: BenchmarkOptions {
+                num_chains: 2,
+                transactions_per_block: 2,
+                bps: 2,
+                runtime_in_seconds: Some(5),
+                workload: BenchmarkWorkload::Blobs,
+                payload_size: 1000,
+                close_chains: true,
+                ..Default::default()
+            },
+        })
+        .await?;
+
     net.ensure_is_running().await?;
     net.terminate().await?;
 
@@ -15,7 +15,7 @@ use linera_core::{
     test_utils::{MemoryStorageBuilder, StorageBuilder, TestBuilder},
     wallet,
 };
-use linera_rpc::{NodeOptions, NodeProvider};
+use linera_rpc::{EndpointSelection, NodeOptions, NodeProvider};
 use linera_service::Wallet;
 
 pub async fn new_test_client_context(
@@ -80,6 +80,8 @@ pub async fn new_test_client_context(
         retry_delay,
         max_retries,
         max_backoff: linera_rpc::node_provider::DEFAULT_MAX_BACKOFF,
+        max_concurrent_requests: None,
+        endpoint_selection: EndpointSelection::default(),
         chain_listeners: JoinSet::default(),
         client_metrics: None,
     })
@@ -118,24 +120,33 @@ async fn test_save_wallet_with_pending_blobs() -> anyhow::Result<()> {
             &wallet::Chain {
                 owner: Some(new_pubkey.into()),
                 timestamp: clock.current_time(),
-                pending_fast_proposal: Some(PendingProposal {
-                    block: ProposedBlock {
-                        chain_id,
-                        epoch: Epoch::ZERO,
-                        transactions: vec![],
-                        height: BlockHeight::ZERO,
-                        timestamp: clock.current_time(),
-                        authenticated_owner: None,
-                        previous_block_hash: None,
+                pending_fast_proposal: Some(wallet::StoredProposal::Inline(Box::new(
+                    PendingProposal {
+                        block: ProposedBlock {
+                            chain_id,
+                            epoch: Epoch::ZERO,
+                            transactions: vec![],
+                            height: BlockHeight::ZERO,
+                            timestamp: clock.current_time(),
+                            authenticated_owner: None,
+                            previous_block_hash: None,
+                        },
+                        blobs: vec![Blob::new_data(b"blob".to_vec())],
+                        auto_retry_outcome: None,
+                        round: None,
                     },
-                    blobs: vec![Blob::new_data(b"blob".to_vec())],
-                    auto_retry_outcome: None,
-                    round: None,
-                }),
+                ))),
                 ..admin_description.into()
             },
         )
         .expect("wallet should be empty");
     wallet.save()?;
+    let chain = Wallet::read(&wallet_path)?
+        .get(chain_id)
+        .expect("the chain should be in the wallet");
+    assert!(matches!(
+        chain.pending_fast_proposal,
+        Some(wallet::StoredProposal::Inline(_))
+    ));
     Ok(())
 }
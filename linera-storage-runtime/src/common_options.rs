@@ -82,6 +82,12 @@ pub struct CommonStorageOptions {
     #[arg(long, default_value = "1", global = true)]
     pub storage_replication_factor: u32,
 
+    /// Log the storage operations that take at least this many milliseconds, with their
+    /// type, a hash of their key prefix, their duration and any backend error. The log is
+    /// off by default.
+    #[arg(long, global = true)]
+    pub storage_slow_operation_threshold_ms: Option<u64>,
+
     /// Enable RocksDB's internal statistics collection and export them as Prometheus
     /// metrics. Off by default; enable it on nodes whose metrics are scraped.
     #[cfg(feature = "rocksdb")]
@@ -100,6 +106,19 @@ pub struct CommonStorageOptions {
         global = true
     )]
     pub rocksdb_statistics_level: RocksDbStatisticsLevel,
+
+    /// Only log the pending ScyllaDB schema migrations, and fail, instead of applying them
+    /// when connecting.
+    #[cfg(feature = "scylladb")]
+    #[arg(long, global = true)]
+    pub storage_schema_migration_dry_run: bool,
+
+    /// Combine the state writes of up to this many chains into one ScyllaDB batch, while
+    /// an earlier write is in flight. This saves round-trips on high-latency databases.
+    /// Writes are not combined by default.
+    #[cfg(feature = "scylladb")]
+    #[arg(long, global = true)]
+    pub storage_write_aggregation_max_writes: Option<usize>,
 }
 
 impl CommonStorageOptions {
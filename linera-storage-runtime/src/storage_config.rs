@@ -1,10 +1,15 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{fmt, path::PathBuf, str::FromStr};
+use std::{fmt, path::PathBuf, str::FromStr, time::Duration};
 
 use anyhow::{anyhow, bail};
 use linera_storage::DEFAULT_NAMESPACE;
+#[cfg(any(
+    feature = "storage-service",
+    all(feature = "rocksdb", feature = "scylladb")
+))]
+use linera_views::backends::failover::FailoverStoreConfig;
 #[cfg(feature = "rocksdb")]
 use linera_views::rocks_db::{PathWithGuard, RocksDbSpawnMode};
 use tracing::error;
@@ -55,6 +60,25 @@ pub enum InnerStorageConfig {
         /// The URI for accessing the database.
         uri: String,
     },
+    /// Two storage services, the second one taking over when the first one fails.
+    #[cfg(feature = "storage-service")]
+    FailoverService {
+        /// The endpoint of the primary storage service.
+        primary_endpoint: String,
+        /// The endpoint of the secondary storage service.
+        secondary_endpoint: String,
+    },
+    /// A RocksDB primary database replicated to a ScyllaDB secondary database, which takes
+    /// over when the primary one fails.
+    #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+    FailoverRocksDbScyllaDb {
+        /// The path used.
+        path_with_guard: PathWithGuard,
+        /// Whether to use `block_in_place` or `spawn_blocking`.
+        spawn_mode: RocksDbSpawnMode,
+        /// The URI for accessing the database.
+        uri: String,
+    },
 }
 
 /// The description of a storage implementation.
@@ -76,6 +100,51 @@ const ROCKS_DB: &str = "rocksdb:";
 const SCYLLA_DB: &str = "scylladb:";
 #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
 const DUAL_ROCKS_DB_SCYLLA_DB: &str = "dualrocksdbscylladb:";
+#[cfg(feature = "storage-service")]
+const FAILOVER_STORAGE_SERVICE: &str = "failoverservice:";
+#[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+const FAILOVER_ROCKS_DB_SCYLLA_DB: &str = "failoverrocksdbscylladb:";
+
+/// Parses `directory:mode:tcp:hostname:port[:namespace]`, the description of a RocksDB
+/// database used together with a ScyllaDB database.
+#[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+fn parse_rocks_db_scylla_db(
+    s: &str,
+    prefix: &str,
+) -> Result<(PathWithGuard, RocksDbSpawnMode, String, String), anyhow::Error> {
+    let parts = s.split(':').collect::<Vec<_>>();
+    if parts.len() != 5 && parts.len() != 6 {
+        bail!("The formatting has to be {prefix}directory:mode:tcp:hostname:port:namespace");
+    }
+    let path = Path::new(parts[0]);
+    let path = path.to_path_buf();
+    let path_with_guard = PathWithGuard::new(path);
+    let spawn_mode_name = parts
+        .get(1)
+        .copied()
+        .expect("validated by the parts length check above");
+    let spawn_mode = match spawn_mode_name {
+        "spawn_blocking" => Ok(RocksDbSpawnMode::SpawnBlocking),
+        "block_in_place" => Ok(RocksDbSpawnMode::BlockInPlace),
+        "runtime" => Ok(RocksDbSpawnMode::get_spawn_mode_from_runtime()),
+        _ => Err(anyhow!("Failed to parse {spawn_mode_name} as a spawn_mode",)),
+    }?;
+    let protocol = parts[2];
+    if protocol != "tcp" {
+        bail!("The only allowed protocol is tcp");
+    }
+    let address = parts[3];
+    let port_str = parts[4];
+    let port = NonZeroU16::from_str(port_str)
+        .map_err(|_| anyhow!("Failed to find parse port {port_str} for {s}"))?;
+    let uri = format!("{address}:{port}");
+    let namespace = if parts.len() == 5 {
+        DEFAULT_NAMESPACE.to_string()
+    } else {
+        parts[5].to_string()
+    };
+    Ok((path_with_guard, spawn_mode, uri, namespace))
+}
 
 impl FromStr for StorageConfig {
     type Err = anyhow::Error;
@@ -223,44 +292,45 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
         }
         #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
         if let Some(s) = input.strip_prefix(DUAL_ROCKS_DB_SCYLLA_DB) {
+            let (path_with_guard, spawn_mode, uri, namespace) =
+                parse_rocks_db_scylla_db(s, DUAL_ROCKS_DB_SCYLLA_DB)?;
+            let inner_storage_config = InnerStorageConfig::DualRocksDbScyllaDb {
+                path_with_guard,
+                spawn_mode,
+                uri,
+            };
+            return Ok(StorageConfig {
+                inner_storage_config,
+                namespace,
+            });
+        }
+        #[cfg(feature = "storage-service")]
+        if let Some(s) = input.strip_prefix(FAILOVER_STORAGE_SERVICE) {
             let parts = s.split(':').collect::<Vec<_>>();
-            if parts.len() != 5 && parts.len() != 6 {
+            if parts.len() != 7 || parts[0] != "tcp" || parts[3] != "tcp" {
                 bail!(
-                    "For DualRocksDbScyllaDb, the formatting has to be dualrocksdbscylladb:directory:mode:tcp:hostname:port:namespace"
+                    "For failover storage services, the formatting has to be \
+failoverservice:tcp:primary_host:port:tcp:secondary_host:port:namespace"
                 );
             }
-            let path = Path::new(parts[0]);
-            let path = path.to_path_buf();
-            let path_with_guard = PathWithGuard::new(path);
-            let spawn_mode_name = parts
-                .get(1)
-                .copied()
-                .expect("validated by the parts length check above");
-            let spawn_mode = match spawn_mode_name {
-                "spawn_blocking" => Ok(RocksDbSpawnMode::SpawnBlocking),
-                "block_in_place" => Ok(RocksDbSpawnMode::BlockInPlace),
-                "runtime" => Ok(RocksDbSpawnMode::get_spawn_mode_from_runtime()),
-                _ => Err(anyhow!("Failed to parse {spawn_mode_name} as a spawn_mode",)),
-            }?;
-            let protocol = parts[2];
-            if protocol != "tcp" {
-                bail!("The only allowed protocol is tcp");
-            }
-            let address = parts[3];
-            let port_str = parts[4];
-            let port = NonZeroU16::from_str(port_str)
-                .map_err(|_| anyhow!("Failed to find parse port {port_str} for {s}"))?;
-            let uri = format!("{address}:{port}");
-            let inner_storage_config = InnerStorageConfig::DualRocksDbScyllaDb {
+            let inner_storage_config = InnerStorageConfig::FailoverService {
+                primary_endpoint: format!("{}:{}", parts[1], parts[2]),
+                secondary_endpoint: format!("{}:{}", parts[4], parts[5]),
+            };
+            return Ok(StorageConfig {
+                inner_storage_config,
+                namespace: parts[6].to_string(),
+            });
+        }
+        #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+        if let Some(s) = input.strip_prefix(FAILOVER_ROCKS_DB_SCYLLA_DB) {
+            let (path_with_guard, spawn_mode, uri, namespace) =
+                parse_rocks_db_scylla_db(s, FAILOVER_ROCKS_DB_SCYLLA_DB)?;
+            let inner_storage_config = InnerStorageConfig::FailoverRocksDbScyllaDb {
                 path_with_guard,
                 spawn_mode,
                 uri,
             };
-            let namespace = if parts.len() == 5 {
-                DEFAULT_NAMESPACE.to_string()
-            } else {
-                parts[5].to_string()
-            };
             return Ok(StorageConfig {
                 inner_storage_config,
                 namespace,
@@ -275,6 +345,10 @@ example service:tcp:127.0.0.1:7878:table_do_my_test"
         error!("Also available is ScyllaDB");
         #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
         error!("Also available is DualRocksDbScyllaDb");
+        #[cfg(feature = "storage-service")]
+        error!("Also available is failover between two storage services");
+        #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+        error!("Also available is failover from RocksDB to ScyllaDB");
         Err(anyhow!("The input has not matched: {input}"))
     }
 }
@@ -286,9 +360,10 @@ impl StorageConfig {
         match &mut self.inner_storage_config {
             #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
             InnerStorageConfig::DualRocksDbScyllaDb {
-                path_with_guard,
-                spawn_mode: _,
-                uri: _,
+                path_with_guard, ..
+            }
+            | InnerStorageConfig::FailoverRocksDbScyllaDb {
+                path_with_guard, ..
             } => {
                 let shard_str = format!("shard_{shard}");
                 path_with_guard.path_buf.push(shard_str);
@@ -298,15 +373,17 @@ impl StorageConfig {
         }
     }
 
-    /// The addition of the common config to get a full configuration
+    /// The addition of the common config to get a full configuration. This also sets the
+    /// process-wide threshold of the slow storage operation log.
     pub fn add_common_storage_options(
         &self,
-        #[cfg_attr(
-            not(any(feature = "storage-service", feature = "rocksdb", feature = "scylladb")),
-            allow(unused_variables)
-        )]
         options: &CommonStorageOptions,
     ) -> Result<StoreConfig, anyhow::Error> {
+        linera_views::slow_log::set_slow_operation_threshold(
+            options
+                .storage_slow_operation_threshold_ms
+                .map(Duration::from_millis),
+        );
         let namespace = self.namespace.clone();
         match &self.inner_storage_config {
             InnerStorageConfig::Memory { genesis_path } => {
@@ -322,43 +399,18 @@ impl StorageConfig {
             }
             #[cfg(feature = "storage-service")]
             InnerStorageConfig::Service { endpoint } => {
-                let inner_config =
-                    linera_storage_service::common::StorageServiceStoreInternalConfig {
-                        endpoint: endpoint.clone(),
-                        max_concurrent_queries: options.storage_max_concurrent_queries,
-                    };
-                let config = linera_storage_service::common::StorageServiceStoreConfig {
-                    inner_config,
-                    storage_cache_config: options.views_storage_cache_config(),
-                };
+                let config = storage_service_config(endpoint, options);
                 Ok(StoreConfig::StorageService { config, namespace })
             }
             #[cfg(feature = "rocksdb")]
             InnerStorageConfig::RocksDb { path, spawn_mode } => {
                 let path_with_guard = PathWithGuard::new(path.to_path_buf());
-                let inner_config = linera_views::rocks_db::RocksDbStoreInternalConfig {
-                    spawn_mode: *spawn_mode,
-                    path_with_guard,
-                    enable_statistics: options.rocksdb_enable_statistics,
-                    statistics_level: options.rocksdb_statistics_level,
-                };
-                let config = linera_views::rocks_db::RocksDbStoreConfig {
-                    inner_config,
-                    storage_cache_config: options.views_storage_cache_config(),
-                };
+                let config = rocks_db_config(path_with_guard, *spawn_mode, options);
                 Ok(StoreConfig::RocksDb { config, namespace })
             }
             #[cfg(feature = "scylladb")]
             InnerStorageConfig::ScyllaDb { uri } => {
-                let inner_config = linera_views::scylla_db::ScyllaDbStoreInternalConfig {
-                    uri: uri.clone(),
-                    max_concurrent_queries: options.storage_max_concurrent_queries,
-                    replication_factor: options.storage_replication_factor,
-                };
-                let config = linera_views::scylla_db::ScyllaDbStoreConfig {
-                    inner_config,
-                    storage_cache_config: options.views_storage_cache_config(),
-                };
+                let config = scylla_db_config(uri, options);
                 Ok(StoreConfig::ScyllaDb { config, namespace })
             }
             #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
@@ -367,37 +419,90 @@ impl StorageConfig {
                 spawn_mode,
                 uri,
             } => {
-                let inner_config = linera_views::rocks_db::RocksDbStoreInternalConfig {
-                    spawn_mode: *spawn_mode,
-                    path_with_guard: path_with_guard.clone(),
-                    enable_statistics: options.rocksdb_enable_statistics,
-                    statistics_level: options.rocksdb_statistics_level,
-                };
-                let first_config = linera_views::rocks_db::RocksDbStoreConfig {
-                    inner_config,
-                    storage_cache_config: options.views_storage_cache_config(),
-                };
-
-                let inner_config = linera_views::scylla_db::ScyllaDbStoreInternalConfig {
-                    uri: uri.clone(),
-                    max_concurrent_queries: options.storage_max_concurrent_queries,
-                    replication_factor: options.storage_replication_factor,
-                };
-                let second_config = linera_views::scylla_db::ScyllaDbStoreConfig {
-                    inner_config,
-                    storage_cache_config: options.views_storage_cache_config(),
-                };
-
                 let config = DualStoreConfig {
-                    first_config,
-                    second_config,
+                    first_config: rocks_db_config(path_with_guard.clone(), *spawn_mode, options),
+                    second_config: scylla_db_config(uri, options),
                 };
                 Ok(StoreConfig::DualRocksDbScyllaDb { config, namespace })
             }
+            #[cfg(feature = "storage-service")]
+            InnerStorageConfig::FailoverService {
+                primary_endpoint,
+                secondary_endpoint,
+            } => {
+                let config = FailoverStoreConfig {
+                    primary_config: storage_service_config(primary_endpoint, options),
+                    secondary_config: storage_service_config(secondary_endpoint, options),
+                };
+                Ok(StoreConfig::FailoverStorageService { config, namespace })
+            }
+            #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+            InnerStorageConfig::FailoverRocksDbScyllaDb {
+                path_with_guard,
+                spawn_mode,
+                uri,
+            } => {
+                let config = FailoverStoreConfig {
+                    primary_config: rocks_db_config(path_with_guard.clone(), *spawn_mode, options),
+                    secondary_config: scylla_db_config(uri, options),
+                };
+                Ok(StoreConfig::FailoverRocksDbScyllaDb { config, namespace })
+            }
         }
     }
 }
 
+#[cfg(feature = "storage-service")]
+fn storage_service_config(
+    endpoint: &str,
+    options: &CommonStorageOptions,
+) -> linera_storage_service::common::StorageServiceStoreConfig {
+    let inner_config = linera_storage_service::common::StorageServiceStoreInternalConfig {
+        endpoint: endpoint.to_string(),
+        max_concurrent_queries: options.storage_max_concurrent_queries,
+    };
+    linera_storage_service::common::StorageServiceStoreConfig {
+        inner_config,
+        storage_cache_config: options.views_storage_cache_config(),
+    }
+}
+
+#[cfg(feature = "rocksdb")]
+fn rocks_db_config(
+    path_with_guard: PathWithGuard,
+    spawn_mode: RocksDbSpawnMode,
+    options: &CommonStorageOptions,
+) -> linera_views::rocks_db::RocksDbStoreConfig {
+    let inner_config = linera_views::rocks_db::RocksDbStoreInternalConfig {
+        spawn_mode,
+        path_with_guard,
+        enable_statistics: options.rocksdb_enable_statistics,
+        statistics_level: options.rocksdb_statistics_level,
+    };
+    linera_views::rocks_db::RocksDbStoreConfig {
+        inner_config,
+        storage_cache_config: options.views_storage_cache_config(),
+    }
+}
+
+#[cfg(feature = "scylladb")]
+fn scylla_db_config(
+    uri: &str,
+    options: &CommonStorageOptions,
+) -> linera_views::scylla_db::ScyllaDbStoreConfig {
+    let inner_config = linera_views::scylla_db::ScyllaDbStoreInternalConfig {
+        uri: uri.to_string(),
+        max_concurrent_queries: options.storage_max_concurrent_queries,
+        replication_factor: options.storage_replication_factor,
+        schema_migration_dry_run: options.storage_schema_migration_dry_run,
+        write_aggregation_max_writes: options.storage_write_aggregation_max_writes,
+    };
+    linera_views::scylla_db::ScyllaDbStoreConfig {
+        inner_config,
+        storage_cache_config: options.views_storage_cache_config(),
+    }
+}
+
 impl fmt::Display for StorageConfig {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let namespace = &self.namespace;
@@ -433,6 +538,31 @@ impl fmt::Display for StorageConfig {
                     namespace
                 )
             }
+            #[cfg(feature = "storage-service")]
+            InnerStorageConfig::FailoverService {
+                primary_endpoint,
+                secondary_endpoint,
+            } => {
+                write!(
+                    f,
+                    "failoverservice:tcp:{primary_endpoint}:tcp:{secondary_endpoint}:{namespace}"
+                )
+            }
+            #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+            InnerStorageConfig::FailoverRocksDbScyllaDb {
+                path_with_guard,
+                spawn_mode,
+                uri,
+            } => {
+                write!(
+                    f,
+                    "failoverrocksdbscylladb:{}:{}:tcp:{}:{}",
+                    path_with_guard.path_buf.display(),
+                    spawn_mode,
+                    uri,
+                    namespace
+                )
+            }
         }
     }
 }
@@ -476,6 +606,29 @@ fn test_shared_store_config_from_str() {
     assert!(StorageConfig::from_str("service:tcp:127.0.0.1:linera").is_err());
 }
 
+#[cfg(feature = "storage-service")]
+#[test]
+fn test_failover_service_config_from_str() {
+    let input = "failoverservice:tcp:127.0.0.1:8942:tcp:10.0.0.2:8943:linera";
+    let config = StorageConfig::from_str(input).unwrap();
+    assert_eq!(
+        config,
+        StorageConfig {
+            inner_storage_config: InnerStorageConfig::FailoverService {
+                primary_endpoint: "127.0.0.1:8942".to_string(),
+                secondary_endpoint: "10.0.0.2:8943".to_string(),
+            },
+            namespace: "linera".into()
+        }
+    );
+    assert_eq!(config.to_string(), input);
+    assert!(StorageConfig::from_str("failoverservice:tcp:127.0.0.1:8942:linera").is_err());
+    assert!(
+        StorageConfig::from_str("failoverservice:tcp:127.0.0.1:8942:udp:10.0.0.2:8943:linera")
+            .is_err()
+    );
+}
+
 #[cfg(feature = "rocksdb")]
 #[test]
 fn test_rocks_db_storage_config_from_str() {
@@ -10,6 +10,11 @@ use linera_execution::WasmRuntime;
 use linera_storage::{DbStorage, Storage, StorageCacheConfig};
 #[cfg(feature = "storage-service")]
 use linera_storage_service::client::StorageServiceDatabase;
+#[cfg(any(
+    feature = "storage-service",
+    all(feature = "rocksdb", feature = "scylladb")
+))]
+use linera_views::backends::failover::FailoverDatabase;
 #[cfg(feature = "rocksdb")]
 use linera_views::rocks_db::RocksDbDatabase;
 #[cfg(feature = "scylladb")]
@@ -69,6 +74,29 @@ pub enum StoreConfig {
         /// The namespace used.
         namespace: String,
     },
+    /// Two storage services, the second one taking over when the first one fails
+    #[cfg(feature = "storage-service")]
+    FailoverStorageService {
+        /// The store configuration.
+        config: linera_views::backends::failover::FailoverStoreConfig<
+            linera_storage_service::common::StorageServiceStoreConfig,
+            linera_storage_service::common::StorageServiceStoreConfig,
+        >,
+        /// The namespace used.
+        namespace: String,
+    },
+    /// A RocksDB key value store replicated to a ScyllaDB key value store, which takes over
+    /// when the first one fails
+    #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+    FailoverRocksDbScyllaDb {
+        /// The store configuration.
+        config: linera_views::backends::failover::FailoverStoreConfig<
+            linera_views::rocks_db::RocksDbStoreConfig,
+            linera_views::scylla_db::ScyllaDbStoreConfig,
+        >,
+        /// The namespace used.
+        namespace: String,
+    },
 }
 
 /// A job that can be run against a high-level [`Storage`].
@@ -185,6 +213,31 @@ impl StoreConfig {
                     .with_allow_application_logs(allow_application_logs);
                 Ok(job.run(storage).await)
             }
+            #[cfg(feature = "storage-service")]
+            StoreConfig::FailoverStorageService { config, namespace } => {
+                let storage = DbStorage::<
+                    FailoverDatabase<StorageServiceDatabase, StorageServiceDatabase>,
+                    _,
+                >::connect(
+                    &config, &namespace, wasm_runtime, cache_sizes
+                )
+                .await?
+                .with_allow_application_logs(allow_application_logs);
+                Ok(job.run(storage).await)
+            }
+            #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+            StoreConfig::FailoverRocksDbScyllaDb { config, namespace } => {
+                let storage =
+                    DbStorage::<FailoverDatabase<RocksDbDatabase, ScyllaDbDatabase>, _>::connect(
+                        &config,
+                        &namespace,
+                        wasm_runtime,
+                        cache_sizes,
+                    )
+                    .await?
+                    .with_allow_application_logs(allow_application_logs);
+                Ok(job.run(storage).await)
+            }
         }
     }
 
@@ -223,6 +276,22 @@ impl StoreConfig {
                     cache_sizes,
                 )
                 .await?),
+            #[cfg(feature = "storage-service")]
+            StoreConfig::FailoverStorageService { config, namespace } => Ok(job
+                .run::<FailoverDatabase<StorageServiceDatabase, StorageServiceDatabase>>(
+                    config,
+                    namespace,
+                    cache_sizes,
+                )
+                .await?),
+            #[cfg(all(feature = "rocksdb", feature = "scylladb"))]
+            StoreConfig::FailoverRocksDbScyllaDb { config, namespace } => Ok(job
+                .run::<FailoverDatabase<RocksDbDatabase, ScyllaDbDatabase>>(
+                    config,
+                    namespace,
+                    cache_sizes,
+                )
+                .await?),
         }
     }
 }
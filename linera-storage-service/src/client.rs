@@ -19,6 +19,7 @@ use linera_views::store::TestKeyValueDatabase;
 use linera_views::{
     batch::{Batch, WriteOperation},
     lru_caching::LruCachingDatabase,
+    slow_log::SlowLogDatabase,
     store::{KeyValueDatabase, ReadableKeyValueStore, WithError, WritableKeyValueStore},
 };
 use serde::de::DeserializeOwned;
@@ -285,6 +286,9 @@ impl WritableKeyValueStore for StorageServiceStoreInternal {
         }
         let mut statements = Vec::new();
         let mut chunk_size = 0;
+        let ttl_ms = batch
+            .ttl
+            .map(|ttl| u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX));
 
         if !self.root_key_written.fetch_or(true, Ordering::SeqCst) {
             let mut full_key = self.start_key.clone();
@@ -296,8 +300,13 @@ impl WritableKeyValueStore for StorageServiceStoreInternal {
             let statement = Statement {
                 operation: Some(operation),
             };
-            statements.push(statement);
-            chunk_size += self.start_key.len();
+            if ttl_ms.is_some() {
+                // The root key itself must not expire.
+                self.submit_statements(vec![statement], None).await?;
+            } else {
+                statements.push(statement);
+                chunk_size += self.start_key.len();
+            }
         }
 
         let bcs_root_key_len = self.start_key.len() - self.prefix_len;
@@ -317,7 +326,8 @@ impl WritableKeyValueStore for StorageServiceStoreInternal {
                 statements.push(statement);
                 chunk_size += operation_size;
             } else {
-                self.submit_statements(mem::take(&mut statements)).await?;
+                self.submit_statements(mem::take(&mut statements), ttl_ms)
+                    .await?;
                 chunk_size = 0;
                 if operation_size > MAX_PAYLOAD_SIZE {
                     // One single operation is especially big. So split it in chunks.
@@ -342,7 +352,8 @@ impl WritableKeyValueStore for StorageServiceStoreInternal {
                         statements = vec![Statement {
                             operation: Some(operation),
                         }];
-                        self.submit_statements(mem::take(&mut statements)).await?;
+                        self.submit_statements(mem::take(&mut statements), ttl_ms)
+                            .await?;
                     }
                 } else {
                     // The operation is small enough, it is just that we have many so we need to split.
@@ -352,7 +363,8 @@ impl WritableKeyValueStore for StorageServiceStoreInternal {
                 }
             }
         }
-        self.submit_statements(mem::take(&mut statements)).await
+        self.submit_statements(mem::take(&mut statements), ttl_ms)
+            .await
     }
 
     async fn clear_journal(&self) -> Result<(), StorageServiceStoreError> {
@@ -372,9 +384,10 @@ impl StorageServiceStoreInternal {
     async fn submit_statements(
         &self,
         statements: Vec<Statement>,
+        ttl_ms: Option<u64>,
     ) -> Result<(), StorageServiceStoreError> {
         if !statements.is_empty() {
-            let query = RequestWriteBatchExtended { statements };
+            let query = RequestWriteBatchExtended { statements, ttl_ms };
             let request = tonic::Request::new(query);
             let channel = self.channel.clone();
             let mut client = StorageServiceClient::new(channel);
@@ -620,9 +633,11 @@ pub async fn storage_service_check_validity(
 
 /// The service database client with metrics
 #[cfg(with_metrics)]
-pub type StorageServiceDatabase =
-    MeteredDatabase<LruCachingDatabase<MeteredDatabase<StorageServiceDatabaseInternal>>>;
+pub type StorageServiceDatabase = MeteredDatabase<
+    LruCachingDatabase<MeteredDatabase<SlowLogDatabase<StorageServiceDatabaseInternal>>>,
+>;
 
 /// The service database client without metrics
 #[cfg(not(with_metrics))]
-pub type StorageServiceDatabase = LruCachingDatabase<StorageServiceDatabaseInternal>;
+pub type StorageServiceDatabase =
+    LruCachingDatabase<SlowLogDatabase<StorageServiceDatabaseInternal>>;
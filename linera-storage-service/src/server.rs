@@ -1,7 +1,7 @@
 // Copyright (c) Zefchain Labs, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::BTreeMap, sync::Arc};
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
 
 use async_lock::RwLock;
 use linera_storage_service::common::{KeyPrefix, MAX_PAYLOAD_SIZE};
@@ -462,8 +462,11 @@ impl StorageService for StorageServer {
         request: Request<RequestWriteBatchExtended>,
     ) -> Result<Response<()>, Status> {
         let request = request.into_inner();
-        let RequestWriteBatchExtended { statements } = request;
-        let mut batch = Batch::default();
+        let RequestWriteBatchExtended { statements, ttl_ms } = request;
+        let mut batch = Batch {
+            ttl: ttl_ms.map(Duration::from_millis),
+            ..Batch::default()
+        };
         for statement in statements {
             match statement.operation.unwrap() {
                 Operation::Delete(key) => {
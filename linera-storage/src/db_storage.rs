@@ -13,11 +13,13 @@ use linera_base::prometheus_util::MeasureLatency as _;
 use linera_base::{
     crypto::CryptoHash,
     data_types::{Blob, BlockHeight, NetworkDescription, TimeDelta, Timestamp},
-    identifiers::{ApplicationId, BlobId, ChainId, EventId, IndexAndEvent, StreamId},
+    deadline,
+    identifiers::{ApplicationId, BlobId, ChainId, EventId, IndexAndEvent, ModuleId, StreamId},
     time::Duration,
 };
 use linera_cache::{Arc as CacheArc, ValueCache};
 use linera_chain::{
+    manager::SupersededRoundState,
     types::{CertificateValue, ConfirmedBlock, ConfirmedBlockCertificate, LiteCertificate},
     ChainStateView,
 };
@@ -150,6 +152,17 @@ pub mod metrics {
         )
     });
 
+    /// The metric counting how often an uploaded blob was already stored and not written
+    /// again.
+    #[doc(hidden)]
+    pub(super) static DEDUPLICATED_BLOB_UPLOAD_COUNTER: LazyLock<IntCounter> =
+        LazyLock::new(|| {
+            register_int_counter(
+                "deduplicated_blob_upload",
+                "The metric counting how often an uploaded blob was already stored",
+            )
+        });
+
     /// The metric counting how often a certificate is read from storage.
     #[doc(hidden)]
     pub static READ_CERTIFICATE_COUNTER: LazyLock<IntCounterVec> = LazyLock::new(|| {
@@ -541,6 +554,7 @@ pub struct DbStorage<Database, Clock = WallClock> {
     wasm_runtime: Option<WasmRuntime>,
     user_contracts: Arc<papaya::HashMap<ApplicationId, UserContractCode>>,
     user_services: Arc<papaya::HashMap<ApplicationId, UserServiceCode>>,
+    service_module_overrides: Arc<papaya::HashMap<ApplicationId, (ModuleId, UserServiceCode)>>,
     shared_committees: SharedCommittees,
     caches: StorageCaches,
     execution_runtime_config: ExecutionRuntimeConfig,
@@ -565,6 +579,13 @@ pub enum RootKey {
     BlockByHeight(ChainId),
     /// The event-to-block-height index of a chain.
     EventBlockHeight(ChainId),
+    /// The client's local index of application events.
+    EventIndex,
+    /// The index of closed ephemeral chains, ordered by closing time.
+    ClosedEphemeralChains,
+    /// The votes and proposals discarded by a chain's manager, ordered by the time they
+    /// were discarded. They are written with a time-to-live.
+    SupersededRoundStates(ChainId),
 }
 
 const CHAIN_ID_TAG: u8 = 2;
@@ -596,6 +617,27 @@ pub(crate) fn to_height_key(height: BlockHeight) -> Vec<u8> {
     bcs::to_bytes(&height).unwrap()
 }
 
+/// Returns the key of a closed ephemeral chain in its index: the big-endian closing time,
+/// followed by the chain ID.
+fn to_closed_ephemeral_chain_key(closed_at: Timestamp, chain_id: ChainId) -> Vec<u8> {
+    let mut key = closed_at.micros().to_be_bytes().to_vec();
+    key.extend(bcs::to_bytes(&chain_id).unwrap());
+    key
+}
+
+fn from_closed_ephemeral_chain_key(key: &[u8]) -> Result<(Timestamp, ChainId), ViewError> {
+    let Some((micros, chain_id)) = key.split_first_chunk::<8>() else {
+        return Err(ViewError::InconsistentEntries);
+    };
+    Ok((
+        Timestamp::from(u64::from_be_bytes(*micros)),
+        bcs::from_bytes(chain_id)?,
+    ))
+}
+
+/// How long the votes and proposals discarded by a chain's manager are kept.
+const SUPERSEDED_ROUND_STATE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
 fn is_chain_state(root_key: &[u8]) -> bool {
     if root_key.is_empty() {
         return false;
@@ -763,6 +805,7 @@ where
     type Context = ViewContext<ChainRuntimeContext<Self>, Database::Store>;
     type Clock = C;
     type BlockExporterContext = ViewContext<u32, Database::Store>;
+    type EventIndexContext = ViewContext<(), Database::Store>;
 
     fn clock(&self) -> &C {
         &self.clock
@@ -776,6 +819,33 @@ where
         &self.shared_committees
     }
 
+    fn service_module_override(
+        &self,
+        application_id: &ApplicationId,
+    ) -> Option<(ModuleId, UserServiceCode)> {
+        self.service_module_overrides
+            .pin()
+            .get(application_id)
+            .cloned()
+    }
+
+    fn set_service_module_override(
+        &self,
+        application_id: ApplicationId,
+        service: Option<(ModuleId, UserServiceCode)>,
+    ) {
+        match service {
+            Some(service) => {
+                self.service_module_overrides
+                    .pin()
+                    .insert(application_id, service);
+            }
+            None => {
+                self.service_module_overrides.pin().remove(&application_id);
+            }
+        }
+    }
+
     #[instrument(level = "trace", skip_all, fields(chain_id = %chain_id))]
     async fn load_chain(
         &self,
@@ -783,6 +853,7 @@ where
     ) -> Result<ChainStateView<Self::Context>, ViewError> {
         #[cfg(with_metrics)]
         let _metric = metrics::LOAD_CHAIN_LATENCY.measure_latency();
+        deadline::check("load_chain")?;
         let runtime_context = ChainRuntimeContext {
             storage: self.clone(),
             thread_pool: self.thread_pool.clone(),
@@ -881,6 +952,7 @@ where
                 .inc();
             return Ok(Some(block));
         }
+        deadline::check("read_confirmed_block")?;
         let root_key = RootKey::BlockHash(hash).bytes();
         let store = self.database.open_shared(&root_key)?;
         let value = store.read_value::<ConfirmedBlock>(BLOCK_KEY).await?;
@@ -913,6 +985,7 @@ where
             }
         }
         if !misses.is_empty() {
+            deadline::check("read_confirmed_blocks")?;
             let miss_hashes: Vec<_> = misses.iter().map(|&i| hashes[i]).collect();
             let root_keys = Self::get_root_keys_for_certificates(&miss_hashes);
             for (miss_idx, root_key) in misses.iter().zip(root_keys) {
@@ -953,6 +1026,7 @@ where
                 .inc();
             return Ok(Some(blob));
         }
+        deadline::check("read_blob")?;
         let root_key = RootKey::BlobId(blob_id).bytes();
         let store = self.database.open_shared(&root_key)?;
         let maybe_blob_bytes = store.read_value_bytes(BLOB_KEY).await?;
@@ -1064,16 +1138,29 @@ where
         }
         let mut batch = MultiPartitionBatch::new();
         let mut blob_states = Vec::new();
+        let mut written_blobs = Vec::new();
         for blob in blobs {
             let root_key = RootKey::BlobId(blob.id()).bytes();
             let store = self.database.open_shared(&root_key)?;
-            let has_state = store.contains_key(BLOB_STATE_KEY).await?;
+            let keys = [BLOB_STATE_KEY.to_vec(), BLOB_KEY.to_vec()];
+            let [has_state, has_blob] = store.contains_keys(&keys).await?[..] else {
+                unreachable!("one result per key");
+            };
             blob_states.push(has_state);
-            if has_state {
+            if has_state && has_blob {
+                // Many clients may upload the same popular blob: don't write it again.
+                #[cfg(with_metrics)]
+                metrics::DEDUPLICATED_BLOB_UPLOAD_COUNTER.inc();
+            } else if has_state {
                 batch.add_blob(blob);
+                written_blobs.push(blob);
             }
         }
         self.write_batch(batch).await?;
+        // Uploaded blobs are about to be needed to handle the certificates using them.
+        for blob in written_blobs {
+            self.cache_blob(blob.clone());
+        }
         Ok(blob_states)
     }
 
@@ -1178,6 +1265,7 @@ where
             return self.deserialize_and_cache_certificate(&raw.0, &raw.1);
         }
         // DB
+        deadline::check("read_certificate")?;
         let root_key = RootKey::BlockHash(hash).bytes();
         let store = self.database.open_shared(&root_key)?;
         let values = store.read_multi_values_bytes(&get_block_keys()).await?;
@@ -1234,6 +1322,7 @@ where
             }
         }
         if !misses.is_empty() {
+            deadline::check("read_certificates")?;
             let miss_hashes: Vec<_> = misses.iter().map(|&i| hashes[i]).collect();
             let root_keys = Self::get_root_keys_for_certificates(&miss_hashes);
             for (miss_idx, root_key) in misses.iter().zip(root_keys) {
@@ -1454,6 +1543,7 @@ where
                 .inc();
             return Ok(Some(event));
         }
+        deadline::check("read_event")?;
         let event_key = to_event_key(&event_id);
         let root_key = RootKey::Event(event_id.chain_id).bytes();
         let store = self.database.open_shared(&root_key)?;
@@ -1495,6 +1585,7 @@ where
         stream_id: &StreamId,
         start_index: u32,
     ) -> Result<Vec<IndexAndEvent>, ViewError> {
+        deadline::check("read_events_from_index")?;
         let root_key = RootKey::Event(*chain_id).bytes();
         let store = self.database.open_shared(&root_key)?;
         // Pair each index with its cached value, or `None` for a cache miss to be
@@ -1596,6 +1687,90 @@ where
         Ok(())
     }
 
+    #[instrument(skip_all, fields(chain_id = %chain_id))]
+    async fn add_closed_ephemeral_chain(
+        &self,
+        chain_id: ChainId,
+        closed_at: Timestamp,
+    ) -> Result<(), ViewError> {
+        let mut batch = MultiPartitionBatch::new();
+        batch.put_key_value(
+            RootKey::ClosedEphemeralChains.bytes(),
+            to_closed_ephemeral_chain_key(closed_at, chain_id),
+            Vec::new(),
+        );
+        self.write_batch(batch).await
+    }
+
+    #[instrument(skip_all)]
+    async fn read_closed_ephemeral_chains(
+        &self,
+        closed_before: Timestamp,
+    ) -> Result<Vec<(Timestamp, ChainId)>, ViewError> {
+        let root_key = RootKey::ClosedEphemeralChains.bytes();
+        let store = self.database.open_shared(&root_key)?;
+        let mut entries = Vec::new();
+        for key in store.find_keys_by_prefix(&[]).await? {
+            let (closed_at, chain_id) = from_closed_ephemeral_chain_key(&key)?;
+            if closed_at <= closed_before {
+                entries.push((closed_at, chain_id));
+            }
+        }
+        entries.sort_unstable();
+        Ok(entries)
+    }
+
+    #[instrument(skip_all, fields(count = entries.len()))]
+    async fn remove_closed_ephemeral_chains(
+        &self,
+        entries: &[(Timestamp, ChainId)],
+    ) -> Result<(), ViewError> {
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let root_key = RootKey::ClosedEphemeralChains.bytes();
+        let store = self.database.open_shared(&root_key)?;
+        let mut batch = Batch::new();
+        for (closed_at, chain_id) in entries {
+            batch.delete_key(to_closed_ephemeral_chain_key(*closed_at, *chain_id));
+        }
+        store.write_batch(batch).await?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(%chain_id))]
+    async fn write_superseded_round_state(
+        &self,
+        chain_id: ChainId,
+        superseded_at: Timestamp,
+        state: &SupersededRoundState,
+    ) -> Result<(), ViewError> {
+        let root_key = RootKey::SupersededRoundStates(chain_id).bytes();
+        let store = self.database.open_shared(&root_key)?;
+        let mut batch = Batch::with_ttl(SUPERSEDED_ROUND_STATE_TTL);
+        batch.put_key_value(superseded_at.micros().to_be_bytes().to_vec(), state)?;
+        store.write_batch(batch).await?;
+        Ok(())
+    }
+
+    #[instrument(skip_all, fields(%chain_id))]
+    async fn read_superseded_round_states(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<Vec<(Timestamp, SupersededRoundState)>, ViewError> {
+        let root_key = RootKey::SupersededRoundStates(chain_id).bytes();
+        let store = self.database.open_shared(&root_key)?;
+        let mut states = Vec::new();
+        for (key, value) in store.find_key_values_by_prefix(&[]).await? {
+            let Ok(micros) = <[u8; 8]>::try_from(key.as_slice()) else {
+                return Err(ViewError::InconsistentEntries);
+            };
+            let superseded_at = Timestamp::from(u64::from_be_bytes(micros));
+            states.push((superseded_at, bcs::from_bytes(&value)?));
+        }
+        Ok(states)
+    }
+
     fn wasm_runtime(&self) -> Option<WasmRuntime> {
         self.wasm_runtime
     }
@@ -1610,6 +1785,13 @@ where
         Ok(ViewContext::create_root_context(store, block_exporter_id).await?)
     }
 
+    #[instrument(skip_all)]
+    async fn event_index_context(&self) -> Result<Self::EventIndexContext, ViewError> {
+        let root_key = RootKey::EventIndex.bytes();
+        let store = self.database.open_exclusive(&root_key)?;
+        Ok(ViewContext::create_root_context(store, ()).await?)
+    }
+
     async fn list_blob_ids(&self) -> Result<Vec<BlobId>, ViewError> {
         let root_keys = self.database.list_root_keys().await?;
         let mut blob_ids = Vec::new();
@@ -1735,6 +1917,7 @@ impl<Database, C> DbStorage<Database, C> {
             wasm_runtime,
             user_contracts: Arc::new(papaya::HashMap::new()),
             user_services: Arc::new(papaya::HashMap::new()),
+            service_module_overrides: Arc::new(papaya::HashMap::new()),
             shared_committees: SharedCommittees::new(),
             caches: StorageCaches::new(cache_sizes),
             execution_runtime_config: ExecutionRuntimeConfig::default(),
@@ -1860,7 +2043,7 @@ mod tests {
     };
     use linera_execution::{
         system::{SystemMessage, SystemOperation},
-        Message, MessageKind, Operation, OutgoingMessage,
+        BlobState, Message, MessageKind, Operation, OutgoingMessage,
     };
     use linera_views::{
         memory::MemoryDatabase,
@@ -1997,6 +2180,42 @@ mod tests {
         assert_eq!(deserialized_height, height);
     }
 
+    #[cfg(with_testing)]
+    #[tokio::test]
+    async fn test_maybe_write_blobs_deduplicates() {
+        let storage = DbStorage::<MemoryDatabase, TestClock>::make_test_storage(None).await;
+        let blob = Blob::new(BlobContent::new_data(b"popular".to_vec()));
+        let blob_id = blob.id();
+        assert_eq!(
+            storage
+                .maybe_write_blobs(std::slice::from_ref(&blob))
+                .await
+                .unwrap(),
+            vec![false]
+        );
+        assert!(!storage.contains_blob(blob_id).await.unwrap());
+
+        storage
+            .maybe_write_blob_states(&[blob_id], BlobState::GENESIS)
+            .await
+            .unwrap();
+        for _ in 0..2 {
+            assert_eq!(
+                storage
+                    .maybe_write_blobs(std::slice::from_ref(&blob))
+                    .await
+                    .unwrap(),
+                vec![true]
+            );
+        }
+        // The uploaded blob is served from the cache.
+        assert!(storage.caches.blob.contains(&blob_id));
+        assert_eq!(
+            storage.read_blob(blob_id).await.unwrap().as_deref(),
+            Some(&blob)
+        );
+    }
+
     #[cfg(with_testing)]
     #[tokio::test]
     async fn test_add_certificate_creates_height_index() {
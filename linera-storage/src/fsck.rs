@@ -0,0 +1,425 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Consistency checks of the data of a validator or client storage, e.g. after a crash or
+//! a restore from a backup.
+//!
+//! The checks walk the chain states, their certificates and the blobs, and report every
+//! invariant that does not hold. Some inconsistencies can be repaired; the others need the
+//! missing data to be downloaded again from other validators.
+
+use std::fmt;
+
+use linera_base::{
+    crypto::CryptoHash,
+    data_types::{ApplicationDescription, Blob, BlockHeight},
+    identifiers::{BlobId, BlobType, ChainId},
+};
+use linera_views::{views::RootView as _, ViewError};
+
+use crate::Storage;
+
+/// The number of certificates read at once.
+const CERTIFICATE_BATCH_SIZE: usize = 100;
+
+/// An invariant of the storage that does not hold.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// The state of a chain could not be loaded.
+    UnreadableChain {
+        /// The chain.
+        chain_id: ChainId,
+        /// The error of the load.
+        error: String,
+    },
+    /// A height below the next block height has no block hash.
+    MissingBlockHash {
+        /// The chain.
+        chain_id: ChainId,
+        /// The height without a block hash.
+        height: BlockHeight,
+    },
+    /// The certificate of a block hash is not in storage.
+    MissingCertificate {
+        /// The chain.
+        chain_id: ChainId,
+        /// The height of the block.
+        height: BlockHeight,
+        /// The hash of the missing certificate.
+        hash: CryptoHash,
+    },
+    /// A certificate is stored under a height or chain that is not the one of its block.
+    MisplacedBlock {
+        /// The chain.
+        chain_id: ChainId,
+        /// The height the certificate is stored under.
+        height: BlockHeight,
+        /// The hash of the certificate.
+        hash: CryptoHash,
+    },
+    /// A block does not point to the block before it.
+    BrokenLink {
+        /// The chain.
+        chain_id: ChainId,
+        /// The height of the block.
+        height: BlockHeight,
+        /// The hash of the block at the previous height.
+        expected: Option<CryptoHash>,
+        /// The previous block hash of the block.
+        found: Option<CryptoHash>,
+    },
+    /// The tip of a chain is not the last executed block.
+    TipMismatch {
+        /// The chain.
+        chain_id: ChainId,
+        /// The hash of the last executed block.
+        expected: Option<CryptoHash>,
+        /// The block hash of the tip.
+        found: Option<CryptoHash>,
+    },
+    /// A blob required by an executed block is not in storage.
+    MissingBlob {
+        /// The chain.
+        chain_id: ChainId,
+        /// The height of the block.
+        height: BlockHeight,
+        /// The missing blob.
+        blob_id: BlobId,
+    },
+    /// The next height to preprocess does not follow the last known block hash.
+    StalePreprocessHeight {
+        /// The chain.
+        chain_id: ChainId,
+        /// The height that follows the last known block hash.
+        expected: BlockHeight,
+        /// The stored next height to preprocess.
+        found: BlockHeight,
+    },
+    /// The content of a blob does not match its ID.
+    CorruptBlob {
+        /// The blob.
+        blob_id: BlobId,
+    },
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn show(hash: &Option<CryptoHash>) -> String {
+            hash.map_or_else(|| "none".to_string(), |hash| hash.to_string())
+        }
+        match self {
+            Inconsistency::UnreadableChain { chain_id, error } => {
+                write!(
+                    f,
+                    "chain {chain_id}: failed to load the chain state: {error}"
+                )
+            }
+            Inconsistency::MissingBlockHash { chain_id, height } => {
+                write!(f, "chain {chain_id}: no block hash at height {height}")
+            }
+            Inconsistency::MissingCertificate {
+                chain_id,
+                height,
+                hash,
+            } => write!(
+                f,
+                "chain {chain_id}: missing certificate {hash} at height {height}"
+            ),
+            Inconsistency::MisplacedBlock {
+                chain_id,
+                height,
+                hash,
+            } => write!(
+                f,
+                "chain {chain_id}: certificate {hash} at height {height} is for another block"
+            ),
+            Inconsistency::BrokenLink {
+                chain_id,
+                height,
+                expected,
+                found,
+            } => write!(
+                f,
+                "chain {chain_id}: block at height {height} points to {}, expected {}",
+                show(found),
+                show(expected)
+            ),
+            Inconsistency::TipMismatch {
+                chain_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "chain {chain_id}: tip is {}, expected {}",
+                show(found),
+                show(expected)
+            ),
+            Inconsistency::MissingBlob {
+                chain_id,
+                height,
+                blob_id,
+            } => write!(
+                f,
+                "chain {chain_id}: blob {blob_id} required at height {height} is missing"
+            ),
+            Inconsistency::StalePreprocessHeight {
+                chain_id,
+                expected,
+                found,
+            } => write!(
+                f,
+                "chain {chain_id}: next height to preprocess is {found}, expected {expected}"
+            ),
+            Inconsistency::CorruptBlob { blob_id } => {
+                write!(f, "blob {blob_id} does not match its content")
+            }
+        }
+    }
+}
+
+/// The outcome of [`check_storage`].
+#[derive(Clone, Debug, Default)]
+pub struct FsckReport {
+    /// The number of chains checked.
+    pub chains: usize,
+    /// The number of certificates checked.
+    pub certificates: usize,
+    /// The number of blobs checked.
+    pub blobs: usize,
+    /// The inconsistencies found that were not repaired.
+    pub inconsistencies: Vec<Inconsistency>,
+    /// The inconsistencies found that were repaired.
+    pub repaired: Vec<Inconsistency>,
+}
+
+impl FsckReport {
+    /// Returns whether the storage is consistent, after the repairs.
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+}
+
+/// Checks the consistency of the chains, certificates and blobs in `storage`.
+///
+/// For each chain, the heights below the next block height must have contiguous block
+/// hashes starting at the latest checkpoint, whose certificates are stored, link to each
+/// other and end at the tip of the chain, and the blobs their blocks require must be
+/// stored. Preprocessed blocks, above the tip, must have their certificates stored. Every
+/// blob must match its ID.
+///
+/// With `repair`, preprocessed block hashes without a certificate are removed, so the
+/// blocks get downloaded again, and the next height to preprocess is recomputed.
+///
+/// Nothing else may write to the storage while it is checked.
+pub async fn check_storage<S: Storage>(storage: &S, repair: bool) -> Result<FsckReport, ViewError> {
+    let mut report = FsckReport::default();
+    for chain_id in storage.list_chain_ids().await? {
+        report.chains += 1;
+        check_chain(storage, chain_id, repair, &mut report).await?;
+    }
+    for blob_id in storage.list_blob_ids().await? {
+        let Some(blob) = storage.read_blob(blob_id).await? else {
+            continue;
+        };
+        report.blobs += 1;
+        if !blob_matches_id(&blob, blob_id) {
+            report
+                .inconsistencies
+                .push(Inconsistency::CorruptBlob { blob_id });
+        }
+    }
+    Ok(report)
+}
+
+async fn check_chain<S: Storage>(
+    storage: &S,
+    chain_id: ChainId,
+    repair: bool,
+    report: &mut FsckReport,
+) -> Result<(), ViewError> {
+    let mut chain = match storage.load_chain(chain_id).await {
+        Ok(chain) => chain,
+        Err(error) => {
+            report.inconsistencies.push(Inconsistency::UnreadableChain {
+                chain_id,
+                error: error.to_string(),
+            });
+            return Ok(());
+        }
+    };
+    let tip = chain.tip_state.get().clone();
+    // Certificates below the latest checkpoint may have been pruned.
+    let start = chain
+        .latest_checkpoint_height
+        .get()
+        .unwrap_or(BlockHeight::ZERO);
+    let mut block_hashes = Vec::new();
+    chain
+        .block_hashes
+        .for_each_index_value(|height, hash| {
+            block_hashes.push((height, hash.into_owned()));
+            Ok(())
+        })
+        .await?;
+
+    let mut executed = Vec::new();
+    let mut preprocessed = Vec::new();
+    let mut expected_height = start;
+    for (height, hash) in &block_hashes {
+        if *height >= tip.next_block_height {
+            preprocessed.push((*height, *hash));
+        } else if *height >= start {
+            while expected_height < *height {
+                report
+                    .inconsistencies
+                    .push(Inconsistency::MissingBlockHash {
+                        chain_id,
+                        height: expected_height,
+                    });
+                expected_height.try_add_assign_one()?;
+            }
+            executed.push((*height, *hash));
+            expected_height = height.try_add_one()?;
+        }
+    }
+    while expected_height < tip.next_block_height {
+        report
+            .inconsistencies
+            .push(Inconsistency::MissingBlockHash {
+                chain_id,
+                height: expected_height,
+            });
+        expected_height.try_add_assign_one()?;
+    }
+
+    let mut previous: Option<(BlockHeight, CryptoHash)> = None;
+    for chunk in executed.chunks(CERTIFICATE_BATCH_SIZE) {
+        let hashes = chunk.iter().map(|(_, hash)| *hash).collect::<Vec<_>>();
+        let certificates = storage.read_certificates(&hashes).await?;
+        for ((height, hash), certificate) in chunk.iter().zip(certificates) {
+            let (height, hash) = (*height, *hash);
+            let Some(certificate) = certificate else {
+                report
+                    .inconsistencies
+                    .push(Inconsistency::MissingCertificate {
+                        chain_id,
+                        height,
+                        hash,
+                    });
+                previous = None;
+                continue;
+            };
+            report.certificates += 1;
+            let block = certificate.block();
+            if block.header.height != height || block.header.chain_id != chain_id {
+                report.inconsistencies.push(Inconsistency::MisplacedBlock {
+                    chain_id,
+                    height,
+                    hash,
+                });
+            }
+            // The link to the block before the first checked one cannot be verified if
+            // that block was pruned.
+            let expected = match previous {
+                Some((previous_height, previous_hash))
+                    if previous_height.try_add_one()? == height =>
+                {
+                    Some(Some(previous_hash))
+                }
+                _ if height == BlockHeight::ZERO => Some(None),
+                _ => None,
+            };
+            if let Some(expected) = expected {
+                if block.header.previous_block_hash != expected {
+                    report.inconsistencies.push(Inconsistency::BrokenLink {
+                        chain_id,
+                        height,
+                        expected,
+                        found: block.header.previous_block_hash,
+                    });
+                }
+            }
+            let required_blob_ids = block.required_blob_ids().into_iter().collect::<Vec<_>>();
+            for blob_id in storage.missing_blobs(&required_blob_ids).await? {
+                report.inconsistencies.push(Inconsistency::MissingBlob {
+                    chain_id,
+                    height,
+                    blob_id,
+                });
+            }
+            previous = Some((height, hash));
+        }
+    }
+
+    let last_executed = executed
+        .last()
+        .filter(|(height, _)| height.try_add_one().ok() == Some(tip.next_block_height))
+        .map(|(_, hash)| *hash);
+    let tip_is_checked =
+        tip.next_block_height > start || tip.next_block_height == BlockHeight::ZERO;
+    if tip_is_checked && last_executed != tip.block_hash {
+        report.inconsistencies.push(Inconsistency::TipMismatch {
+            chain_id,
+            expected: last_executed,
+            found: tip.block_hash,
+        });
+    }
+
+    let mut changed = false;
+    let mut next_height_to_preprocess = tip.next_block_height;
+    for chunk in preprocessed.chunks(CERTIFICATE_BATCH_SIZE) {
+        let hashes = chunk.iter().map(|(_, hash)| *hash).collect::<Vec<_>>();
+        let certificates = storage.read_certificates(&hashes).await?;
+        for ((height, hash), certificate) in chunk.iter().zip(certificates) {
+            if certificate.is_some() {
+                report.certificates += 1;
+                next_height_to_preprocess = height.try_add_one()?;
+                continue;
+            }
+            let inconsistency = Inconsistency::MissingCertificate {
+                chain_id,
+                height: *height,
+                hash: *hash,
+            };
+            if repair {
+                chain.block_hashes.remove(height)?;
+                report.repaired.push(inconsistency);
+                changed = true;
+            } else {
+                report.inconsistencies.push(inconsistency);
+                next_height_to_preprocess = height.try_add_one()?;
+            }
+        }
+    }
+    let stored = *chain.next_height_to_preprocess.get();
+    if stored != next_height_to_preprocess {
+        let inconsistency = Inconsistency::StalePreprocessHeight {
+            chain_id,
+            expected: next_height_to_preprocess,
+            found: stored,
+        };
+        if repair {
+            chain
+                .next_height_to_preprocess
+                .set(next_height_to_preprocess);
+            report.repaired.push(inconsistency);
+            changed = true;
+        } else {
+            report.inconsistencies.push(inconsistency);
+        }
+    }
+    if changed {
+        chain.save().await?;
+    }
+    Ok(())
+}
+
+/// Returns whether the hash of the blob's content is its ID.
+fn blob_matches_id(blob: &Blob, blob_id: BlobId) -> bool {
+    if blob_id.blob_type == BlobType::ApplicationDescription
+        && bcs::from_bytes::<ApplicationDescription>(blob.bytes()).is_err()
+    {
+        return false;
+    }
+    Blob::new(blob.content().clone()).id() == blob_id
+}
@@ -6,6 +6,7 @@
 #![deny(missing_docs)]
 
 mod db_storage;
+pub mod fsck;
 
 use std::sync::Arc as StdArc;
 
@@ -17,12 +18,16 @@ use linera_base::{
         ApplicationDescription, Blob, BlockHeight, ChainDescription, CompressedBytecode, Epoch,
         NetworkDescription, TimeDelta, Timestamp,
     },
-    identifiers::{ApplicationId, BlobId, BlobType, ChainId, EventId, IndexAndEvent, StreamId},
+    ensure,
+    identifiers::{
+        ApplicationId, BlobId, BlobType, ChainId, EventId, IndexAndEvent, ModuleId, StreamId,
+    },
     time::Duration,
     vm::VmRuntime,
 };
 pub use linera_cache::{Arc, DEFAULT_CLEANUP_INTERVAL_SECS};
 use linera_chain::{
+    manager::SupersededRoundState,
     types::{ConfirmedBlock, ConfirmedBlockCertificate},
     ChainError, ChainStateView,
 };
@@ -64,6 +69,9 @@ pub trait Storage: linera_base::util::traits::AutoTraits + Sized {
     /// The low-level storage implementation in use by the block exporter.
     type BlockExporterContext: Context<Extra = u32> + Clone;
 
+    /// The low-level storage implementation in use by the client's index of events.
+    type EventIndexContext: Context<Extra = ()> + Clone;
+
     /// Returns the current wall clock time.
     fn clock(&self) -> &Self::Clock;
 
@@ -255,6 +263,43 @@ pub trait Storage: linera_base::util::traits::AutoTraits + Sized {
         information: &NetworkDescription,
     ) -> Result<(), ViewError>;
 
+    /// Adds an ephemeral chain that was closed at `closed_at` to the index of chains that
+    /// may be pruned once their retention period has passed.
+    async fn add_closed_ephemeral_chain(
+        &self,
+        chain_id: ChainId,
+        closed_at: Timestamp,
+    ) -> Result<(), ViewError>;
+
+    /// Reads the entries of the index of closed ephemeral chains that were closed at or
+    /// before `closed_before`, oldest first.
+    async fn read_closed_ephemeral_chains(
+        &self,
+        closed_before: Timestamp,
+    ) -> Result<Vec<(Timestamp, ChainId)>, ViewError>;
+
+    /// Removes the given entries from the index of closed ephemeral chains.
+    async fn remove_closed_ephemeral_chains(
+        &self,
+        entries: &[(Timestamp, ChainId)],
+    ) -> Result<(), ViewError>;
+
+    /// Writes the votes and proposals that a chain's manager discarded at `superseded_at`.
+    /// They are written with a time-to-live, so storage drops them after a while.
+    async fn write_superseded_round_state(
+        &self,
+        chain_id: ChainId,
+        superseded_at: Timestamp,
+        state: &SupersededRoundState,
+    ) -> Result<(), ViewError>;
+
+    /// Reads the discarded votes and proposals of a chain that have not expired yet, oldest
+    /// first.
+    async fn read_superseded_round_states(
+        &self,
+        chain_id: ChainId,
+    ) -> Result<Vec<(Timestamp, SupersededRoundState)>, ViewError>;
+
     /// Initializes a chain in a simple way (used for testing and to create a genesis state).
     ///
     /// # Notes
@@ -423,12 +468,71 @@ pub trait Storage: linera_base::util::traits::AutoTraits + Sized {
         }
     }
 
+    /// Returns the module whose service is used instead of the application's own for
+    /// queries from outside the chain, and the loaded service, if one was set with
+    /// [`Storage::override_service_module`].
+    fn service_module_override(
+        &self,
+        _application_id: &ApplicationId,
+    ) -> Option<(ModuleId, UserServiceCode)> {
+        None
+    }
+
+    /// Stores or clears an already loaded service override. Storage implementations that
+    /// don't support overrides ignore this.
+    fn set_service_module_override(
+        &self,
+        _application_id: ApplicationId,
+        _service: Option<(ModuleId, UserServiceCode)>,
+    ) {
+    }
+
+    /// Makes queries to the given application from outside the chain, e.g. from the node
+    /// service, run the service bytecode of `module_id`, or the application's own service
+    /// if `None`. The service is loaded first, so that an invalid module is rejected. Oracle
+    /// calls from contracts always use the application's own service.
+    async fn override_service_module(
+        &self,
+        application_id: ApplicationId,
+        module_id: Option<ModuleId>,
+    ) -> Result<(), ExecutionError> {
+        let Some(module_id) = module_id else {
+            self.set_service_module_override(application_id, None);
+            return Ok(());
+        };
+        let description_blob_id = application_id.description_blob_id();
+        let blob = self
+            .read_blob(description_blob_id)
+            .await?
+            .ok_or(ExecutionError::BlobsNotFound(vec![description_blob_id]))?;
+        let description: ApplicationDescription = bcs::from_bytes(blob.bytes())?;
+        ensure!(
+            module_id.vm_runtime == description.module_id.vm_runtime,
+            ExecutionError::IncompatibleServiceModule {
+                application_id: Box::new(application_id),
+                module_id: Box::new(module_id),
+            }
+        );
+        let description = ApplicationDescription {
+            module_id,
+            ..description
+        };
+        let service = self
+            .load_service(&description, &TransactionTracker::default())
+            .await?;
+        self.set_service_module_override(application_id, Some((module_id, service)));
+        Ok(())
+    }
+
     /// Returns the storage context used by the block exporter with the given ID.
     async fn block_exporter_context(
         &self,
         block_exporter_id: u32,
     ) -> Result<Self::BlockExporterContext, ViewError>;
 
+    /// Returns the storage context used by the client's local index of application events.
+    async fn event_index_context(&self) -> Result<Self::EventIndexContext, ViewError>;
+
     /// Returns the process-wide committee cache shared by all chains.
     fn shared_committees(&self) -> &SharedCommittees;
 
@@ -602,6 +706,18 @@ impl<S: Storage> ExecutionRuntimeContext for ChainRuntimeContext<S> {
         Ok(service)
     }
 
+    async fn get_user_service_for_query(
+        &self,
+        description: &ApplicationDescription,
+        txn_tracker: &TransactionTracker,
+    ) -> Result<UserServiceCode, ExecutionError> {
+        let application_id = description.into();
+        if let Some((_, service)) = self.storage.service_module_override(&application_id) {
+            return Ok(service);
+        }
+        self.get_user_service(description, txn_tracker).await
+    }
+
     async fn get_blob(&self, blob_id: BlobId) -> Result<Option<StdArc<Blob>>, ViewError> {
         Ok(self.storage.read_blob(blob_id).await?.map(Arc::into_std))
     }
@@ -686,6 +802,7 @@ mod tests {
     use linera_chain::{
         block::{Block, ConfirmedBlock},
         data_types::{BlockExecutionOutcome, ProposedBlock},
+        ChainTipState,
     };
     use linera_execution::{BlobOrigin, BlobState};
     #[cfg(feature = "scylladb")]
@@ -1010,6 +1127,186 @@ mod tests {
         Ok(())
     }
 
+    async fn test_storage_closed_ephemeral_chains<S: Storage + Sync>(
+        storage: &S,
+    ) -> Result<(), ViewError>
+    where
+        S::Context: Send + Sync,
+    {
+        let chain_a = ChainId(CryptoHash::test_hash("ephemeral_chain_a"));
+        let chain_b = ChainId(CryptoHash::test_hash("ephemeral_chain_b"));
+        let chain_c = ChainId(CryptoHash::test_hash("ephemeral_chain_c"));
+        // The index is ordered by closing time, not by insertion order or chain ID.
+        storage
+            .add_closed_ephemeral_chain(chain_c, Timestamp::from(300))
+            .await?;
+        storage
+            .add_closed_ephemeral_chain(chain_a, Timestamp::from(1 << 40))
+            .await?;
+        storage
+            .add_closed_ephemeral_chain(chain_b, Timestamp::from(200))
+            .await?;
+
+        assert!(storage
+            .read_closed_ephemeral_chains(Timestamp::from(199))
+            .await?
+            .is_empty());
+        let entries = storage
+            .read_closed_ephemeral_chains(Timestamp::from(300))
+            .await?;
+        assert_eq!(
+            entries,
+            vec![
+                (Timestamp::from(200), chain_b),
+                (Timestamp::from(300), chain_c)
+            ]
+        );
+
+        storage
+            .remove_closed_ephemeral_chains(&entries[..1])
+            .await?;
+        let entries = storage
+            .read_closed_ephemeral_chains(Timestamp::from(u64::MAX))
+            .await?;
+        assert_eq!(
+            entries,
+            vec![
+                (Timestamp::from(300), chain_c),
+                (Timestamp::from(1 << 40), chain_a)
+            ]
+        );
+        Ok(())
+    }
+
+    /// Test function for the service module overrides used to hot-reload services
+    async fn test_storage_service_module_override<S: Storage + Sync>(
+        storage: &S,
+    ) -> Result<(), ViewError> {
+        let module_id = |vm_runtime| {
+            ModuleId::new(
+                CryptoHash::test_hash("contract"),
+                CryptoHash::test_hash("service"),
+                vm_runtime,
+            )
+        };
+        let description = ApplicationDescription {
+            module_id: module_id(VmRuntime::Wasm),
+            creator_chain_id: ChainId(CryptoHash::test_hash("creator")),
+            block_height: BlockHeight(0),
+            application_index: 0,
+            parameters: vec![],
+            required_application_ids: vec![],
+        };
+        let application_id = ApplicationId::from(&description);
+
+        // The application description is not in storage yet.
+        let description_blob = Blob::new_application_description(&description);
+        let result = storage
+            .override_service_module(application_id, Some(module_id(VmRuntime::Wasm)))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ExecutionError::BlobsNotFound(blob_ids)) if blob_ids == vec![description_blob.id()]
+        ));
+        storage.write_blob(&description_blob).await?;
+
+        // A module for a different VM cannot serve the application.
+        let result = storage
+            .override_service_module(application_id, Some(module_id(VmRuntime::Evm)))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ExecutionError::IncompatibleServiceModule { .. })
+        ));
+
+        // The service bytecode of the new module is missing.
+        let service_blob_id = description.service_bytecode_blob_id();
+        let result = storage
+            .override_service_module(application_id, Some(module_id(VmRuntime::Wasm)))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ExecutionError::BlobsNotFound(blob_ids)) if blob_ids == vec![service_blob_id]
+        ));
+        assert!(storage.service_module_override(&application_id).is_none());
+
+        storage
+            .override_service_module(application_id, None)
+            .await
+            .expect("clearing an override should not fail");
+        assert!(storage.service_module_override(&application_id).is_none());
+        Ok(())
+    }
+
+    async fn test_storage_fsck<S: Storage + Sync>(storage: &S) -> Result<(), ViewError>
+    where
+        S::Context: Send + Sync,
+    {
+        let chain_id = ChainId(CryptoHash::test_hash("fsck_chain"));
+        let mut chain = storage.load_chain(chain_id).await?;
+        chain.save().await?;
+
+        let report = fsck::check_storage(storage, false).await?;
+        assert!(report.chains >= 1);
+        assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+
+        // A preprocessed block whose certificate was lost, e.g. in a botched restore.
+        let hash = CryptoHash::test_hash("lost certificate");
+        let mut chain = storage.load_chain(chain_id).await?;
+        chain.block_hashes.insert(&BlockHeight(2), hash)?;
+        chain.next_height_to_preprocess.set(BlockHeight(3));
+        chain.save().await?;
+
+        let report = fsck::check_storage(storage, false).await?;
+        assert_eq!(
+            report.inconsistencies,
+            vec![fsck::Inconsistency::MissingCertificate {
+                chain_id,
+                height: BlockHeight(2),
+                hash,
+            }]
+        );
+        assert!(!report.is_consistent());
+
+        let report = fsck::check_storage(storage, true).await?;
+        assert_eq!(report.repaired.len(), 2);
+        assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+        let chain = storage.load_chain(chain_id).await?;
+        assert_eq!(chain.block_hashes.get(&BlockHeight(2)).await?, None);
+        assert_eq!(*chain.next_height_to_preprocess.get(), BlockHeight::ZERO);
+        drop(chain);
+
+        let report = fsck::check_storage(storage, false).await?;
+        assert!(report.inconsistencies.is_empty());
+        assert!(report.repaired.is_empty());
+
+        // An executed block whose certificate was lost cannot be repaired.
+        let hash = CryptoHash::test_hash("lost executed certificate");
+        let mut chain = storage.load_chain(chain_id).await?;
+        chain.block_hashes.insert(&BlockHeight(0), hash)?;
+        *chain.tip_state.get_mut() = ChainTipState {
+            block_hash: Some(hash),
+            next_block_height: BlockHeight(1),
+        };
+        chain.next_height_to_preprocess.set(BlockHeight(1));
+        chain.save().await?;
+
+        let report = fsck::check_storage(storage, true).await?;
+        assert_eq!(
+            report.inconsistencies,
+            vec![fsck::Inconsistency::MissingCertificate {
+                chain_id,
+                height: BlockHeight(0),
+                hash,
+            }]
+        );
+        assert!(report.repaired.is_empty());
+        assert!(!report.is_consistent());
+        let chain = storage.load_chain(chain_id).await?;
+        assert_eq!(chain.block_hashes.get(&BlockHeight(0)).await?, Some(hash));
+        Ok(())
+    }
+
     /// Generic test function to test Storage trait features
     #[test_case(DbStorage::<MemoryDatabase, _>::make_test_storage(None).await; "memory")]
     #[cfg_attr(feature = "scylladb", test_case(DbStorage::<ScyllaDbDatabase, _>::make_test_storage(None).await; "scylla_db"))]
@@ -1023,6 +1320,9 @@ mod tests {
         test_storage_certificate(&storage).await?;
         test_storage_event(&storage).await?;
         test_storage_network_description(&storage).await?;
+        test_storage_closed_ephemeral_chains(&storage).await?;
+        test_storage_service_module_override(&storage).await?;
+        test_storage_fsck(&storage).await?;
         Ok(())
     }
 }
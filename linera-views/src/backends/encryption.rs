@@ -0,0 +1,578 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Adds encryption at rest to a given store.
+//!
+//! Values are encrypted with XChaCha20-Poly1305 under a random nonce. The associated data
+//! binds each value to its root key and key, so that values cannot be moved around in the
+//! underlying store without being detected.
+//!
+//! Keys can optionally be obfuscated as well. Each byte of a key is masked with a byte
+//! derived from the secret key and the bytes preceding it. This keeps the encryption of a
+//! key prefix a prefix of the encryption of the key, so that prefix searches and prefix
+//! deletions still work, at the cost of revealing which keys share a common prefix. The
+//! order of keys is not preserved, so the results of prefix searches are sorted again
+//! after decryption. Root keys are never encrypted.
+
+use std::{fmt, sync::Arc};
+
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use thiserror::Error;
+
+#[cfg(with_testing)]
+use crate::memory::MemoryDatabase;
+#[cfg(with_testing)]
+use crate::store::TestKeyValueDatabase;
+use crate::{
+    batch::{Batch, WriteOperation},
+    store::{
+        KeyValueDatabase, KeyValueStoreError, ReadableKeyValueStore, WithError,
+        WritableKeyValueStore,
+    },
+};
+
+/// The size of the nonce stored in front of every encrypted value.
+const NONCE_SIZE: usize = 24;
+
+/// The size of the authentication tag stored at the end of every encrypted value.
+const TAG_SIZE: usize = 16;
+
+/// The number of PBKDF2 rounds used to derive a key from a passphrase.
+const PBKDF2_ROUNDS: u32 = 600_000;
+
+/// The secret from which the encryption keys are derived.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum EncryptionKey {
+    /// A 256-bit data key, e.g. as issued by a key management service.
+    Key([u8; 32]),
+    /// A passphrase, stretched with PBKDF2-HMAC-SHA256 and the given salt.
+    Passphrase {
+        /// The passphrase.
+        passphrase: String,
+        /// The salt. It must be the same every time the database is opened.
+        salt: String,
+    },
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EncryptionKey::Key(_) => f.write_str("Key(<redacted>)"),
+            EncryptionKey::Passphrase { salt, .. } => f
+                .debug_struct("Passphrase")
+                .field("passphrase", &"<redacted>")
+                .field("salt", salt)
+                .finish(),
+        }
+    }
+}
+
+/// The configuration type for the `EncryptedDatabase`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EncryptedConfig<C> {
+    /// The inner configuration of the `EncryptedDatabase`.
+    pub inner_config: C,
+    /// The secret from which the encryption keys are derived.
+    pub key: EncryptionKey,
+    /// Whether to obfuscate keys in addition to encrypting values.
+    pub encrypt_keys: bool,
+}
+
+/// The composed error type built from the inner error type.
+#[derive(Error, Debug)]
+pub enum EncryptionError<E> {
+    /// inner store error
+    #[error(transparent)]
+    InnerStoreError(#[from] E),
+
+    /// A value could not be decrypted
+    #[error("failed to decrypt a value: the key is wrong or the data is corrupted")]
+    DecryptionFailed,
+}
+
+impl<E: KeyValueStoreError> From<bcs::Error> for EncryptionError<E> {
+    fn from(error: bcs::Error) -> Self {
+        let error = E::from(error);
+        EncryptionError::InnerStoreError(error)
+    }
+}
+
+impl<E: KeyValueStoreError + 'static> KeyValueStoreError for EncryptionError<E> {
+    const BACKEND: &'static str = "encryption";
+
+    fn must_reload_view(&self) -> bool {
+        match self {
+            EncryptionError::InnerStoreError(e) => e.must_reload_view(),
+            EncryptionError::DecryptionFailed => false,
+        }
+    }
+}
+
+/// The keys derived from an `EncryptionKey`.
+struct Cipher {
+    /// The cipher used for values.
+    values: XChaCha20Poly1305,
+    /// The secret from which the key masks are derived, if keys are encrypted.
+    keys: Option<[u8; 32]>,
+}
+
+impl Cipher {
+    fn new(key: &EncryptionKey, encrypt_keys: bool) -> Self {
+        let master = match key {
+            EncryptionKey::Key(key) => *key,
+            EncryptionKey::Passphrase { passphrase, salt } => {
+                pbkdf2::pbkdf2_hmac_array::<sha2::Sha256, 32>(
+                    passphrase.as_bytes(),
+                    salt.as_bytes(),
+                    PBKDF2_ROUNDS,
+                )
+            }
+        };
+        let derive = |purpose: &[u8]| -> [u8; 32] {
+            Sha3_256::new()
+                .chain_update(purpose)
+                .chain_update(master)
+                .finalize()
+                .into()
+        };
+        let values = XChaCha20Poly1305::new(&derive(b"linera-views value encryption").into());
+        let keys = encrypt_keys.then(|| derive(b"linera-views key encryption"));
+        Cipher { values, keys }
+    }
+
+    /// Returns the hasher deriving the mask of the byte following `prefix`.
+    fn key_hasher(&self, secret: &[u8; 32], prefix: &[u8]) -> Sha3_256 {
+        Sha3_256::new().chain_update(secret).chain_update(prefix)
+    }
+
+    /// Encrypts the bytes of `key` following the plaintext `prefix`.
+    fn encrypt_key_suffix(&self, prefix: &[u8], key: &[u8]) -> Vec<u8> {
+        let Some(secret) = &self.keys else {
+            return key.to_vec();
+        };
+        let mut hasher = self.key_hasher(secret, prefix);
+        key.iter()
+            .map(|byte| {
+                let mask = hasher.clone().finalize()[0];
+                hasher.update([*byte]);
+                byte ^ mask
+            })
+            .collect()
+    }
+
+    /// Decrypts the bytes of a key following the plaintext `prefix`.
+    fn decrypt_key_suffix(&self, prefix: &[u8], key: &[u8]) -> Vec<u8> {
+        let Some(secret) = &self.keys else {
+            return key.to_vec();
+        };
+        let mut hasher = self.key_hasher(secret, prefix);
+        key.iter()
+            .map(|byte| {
+                let byte = byte ^ hasher.clone().finalize()[0];
+                hasher.update([byte]);
+                byte
+            })
+            .collect()
+    }
+
+    fn encrypt_key(&self, key: &[u8]) -> Vec<u8> {
+        self.encrypt_key_suffix(&[], key)
+    }
+
+    /// Encrypts the value stored under `key` in the partition of the given root key.
+    fn encrypt_value(&self, root_key: &[u8], key: &[u8], value: &[u8]) -> Vec<u8> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let aad = Self::associated_data(root_key, key);
+        let ciphertext = self
+            .values
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: value,
+                    aad: &aad,
+                },
+            )
+            .expect("encryption does not fail for values fitting in memory");
+        let mut result = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+        result.extend_from_slice(&nonce);
+        result.extend(ciphertext);
+        result
+    }
+
+    /// Decrypts the value stored under `key` in the partition of the given root key.
+    fn decrypt_value<E>(
+        &self,
+        root_key: &[u8],
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<Vec<u8>, EncryptionError<E>> {
+        if value.len() < NONCE_SIZE + TAG_SIZE {
+            return Err(EncryptionError::DecryptionFailed);
+        }
+        let (nonce, ciphertext) = value.split_at(NONCE_SIZE);
+        let aad = Self::associated_data(root_key, key);
+        self.values
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| EncryptionError::DecryptionFailed)
+    }
+
+    fn associated_data(root_key: &[u8], key: &[u8]) -> Vec<u8> {
+        let mut aad = Vec::with_capacity(8 + root_key.len() + key.len());
+        aad.extend_from_slice(&(root_key.len() as u64).to_be_bytes());
+        aad.extend_from_slice(root_key);
+        aad.extend_from_slice(key);
+        aad
+    }
+}
+
+/// A key-value database encrypting the data at rest.
+#[derive(Clone)]
+pub struct EncryptedDatabase<D> {
+    /// The underlying database.
+    database: D,
+    /// The keys used for encryption.
+    cipher: Arc<Cipher>,
+}
+
+/// A key-value store encrypting the data at rest.
+#[derive(Clone)]
+pub struct EncryptedStore<S> {
+    /// The underlying store.
+    store: S,
+    /// The keys used for encryption.
+    cipher: Arc<Cipher>,
+    /// The root key of the store, authenticated together with each value.
+    root_key: Vec<u8>,
+}
+
+impl<D> WithError for EncryptedDatabase<D>
+where
+    D: WithError,
+    D::Error: 'static,
+{
+    type Error = EncryptionError<D::Error>;
+}
+
+impl<S> WithError for EncryptedStore<S>
+where
+    S: WithError,
+    S::Error: 'static,
+{
+    type Error = EncryptionError<S::Error>;
+}
+
+impl<S> ReadableKeyValueStore for EncryptedStore<S>
+where
+    S: ReadableKeyValueStore,
+    S::Error: 'static,
+{
+    const MAX_KEY_SIZE: usize = S::MAX_KEY_SIZE;
+
+    fn root_key(&self) -> Result<Vec<u8>, Self::Error> {
+        Ok(self.store.root_key()?)
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let encrypted_key = self.cipher.encrypt_key(key);
+        let Some(value) = self.store.read_value_bytes(&encrypted_key).await? else {
+            return Ok(None);
+        };
+        Ok(Some(self.cipher.decrypt_value(
+            &self.root_key,
+            key,
+            &value,
+        )?))
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        let encrypted_key = self.cipher.encrypt_key(key);
+        Ok(self.store.contains_key(&encrypted_key).await?)
+    }
+
+    async fn contains_keys(&self, keys: &[Vec<u8>]) -> Result<Vec<bool>, Self::Error> {
+        let encrypted_keys = keys
+            .iter()
+            .map(|key| self.cipher.encrypt_key(key))
+            .collect::<Vec<_>>();
+        Ok(self.store.contains_keys(&encrypted_keys).await?)
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: &[Vec<u8>],
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        let encrypted_keys = keys
+            .iter()
+            .map(|key| self.cipher.encrypt_key(key))
+            .collect::<Vec<_>>();
+        let values = self.store.read_multi_values_bytes(&encrypted_keys).await?;
+        keys.iter()
+            .zip(values)
+            .map(|(key, value)| {
+                value
+                    .map(|value| self.cipher.decrypt_value(&self.root_key, key, &value))
+                    .transpose()
+            })
+            .collect()
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let encrypted_prefix = self.cipher.encrypt_key(key_prefix);
+        let mut keys = self
+            .store
+            .find_keys_by_prefix(&encrypted_prefix)
+            .await?
+            .into_iter()
+            .map(|suffix| self.cipher.decrypt_key_suffix(key_prefix, &suffix))
+            .collect::<Vec<_>>();
+        if self.cipher.keys.is_some() {
+            keys.sort_unstable();
+        }
+        Ok(keys)
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        let encrypted_prefix = self.cipher.encrypt_key(key_prefix);
+        let mut key_values = self
+            .store
+            .find_key_values_by_prefix(&encrypted_prefix)
+            .await?
+            .into_iter()
+            .map(|(suffix, value)| {
+                let suffix = self.cipher.decrypt_key_suffix(key_prefix, &suffix);
+                let key = [key_prefix, &suffix].concat();
+                let value = self.cipher.decrypt_value(&self.root_key, &key, &value)?;
+                Ok((suffix, value))
+            })
+            .collect::<Result<Vec<_>, Self::Error>>()?;
+        if self.cipher.keys.is_some() {
+            key_values.sort_unstable_by(|(key1, _), (key2, _)| key1.cmp(key2));
+        }
+        Ok(key_values)
+    }
+}
+
+impl<S> WritableKeyValueStore for EncryptedStore<S>
+where
+    S: WritableKeyValueStore,
+    S::Error: 'static,
+{
+    const MAX_VALUE_SIZE: usize = S::MAX_VALUE_SIZE - NONCE_SIZE - TAG_SIZE;
+
+    async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        let mut encrypted_batch = Batch {
+            ttl: batch.ttl,
+            ..Batch::new()
+        };
+        for operation in batch.operations {
+            match operation {
+                WriteOperation::Delete { key } => {
+                    encrypted_batch.delete_key(self.cipher.encrypt_key(&key));
+                }
+                WriteOperation::Put { key, value } => {
+                    let value = self.cipher.encrypt_value(&self.root_key, &key, &value);
+                    encrypted_batch.put_key_value_bytes(self.cipher.encrypt_key(&key), value);
+                }
+                WriteOperation::DeletePrefix { key_prefix } => {
+                    encrypted_batch.delete_key_prefix(self.cipher.encrypt_key(&key_prefix));
+                }
+            }
+        }
+        Ok(self.store.write_batch(encrypted_batch).await?)
+    }
+
+    async fn clear_journal(&self) -> Result<(), Self::Error> {
+        Ok(self.store.clear_journal().await?)
+    }
+}
+
+impl<D> KeyValueDatabase for EncryptedDatabase<D>
+where
+    D: KeyValueDatabase,
+    D::Store: ReadableKeyValueStore<Error = D::Error>,
+    D::Error: 'static,
+{
+    type Config = EncryptedConfig<D::Config>;
+
+    type Store = EncryptedStore<D::Store>;
+
+    fn get_name() -> String {
+        format!("encrypted {}", D::get_name())
+    }
+
+    async fn connect(config: &Self::Config, namespace: &str) -> Result<Self, Self::Error> {
+        let database = D::connect(&config.inner_config, namespace).await?;
+        let cipher = Arc::new(Cipher::new(&config.key, config.encrypt_keys));
+        Ok(Self { database, cipher })
+    }
+
+    fn open_shared(&self, root_key: &[u8]) -> Result<Self::Store, Self::Error> {
+        let store = self.database.open_shared(root_key)?;
+        self.wrap(store)
+    }
+
+    fn open_exclusive(&self, root_key: &[u8]) -> Result<Self::Store, Self::Error> {
+        let store = self.database.open_exclusive(root_key)?;
+        self.wrap(store)
+    }
+
+    async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
+        Ok(D::list_all(&config.inner_config).await?)
+    }
+
+    async fn list_root_keys(&self) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(self.database.list_root_keys().await?)
+    }
+
+    async fn delete_all(config: &Self::Config) -> Result<(), Self::Error> {
+        Ok(D::delete_all(&config.inner_config).await?)
+    }
+
+    async fn exists(config: &Self::Config, namespace: &str) -> Result<bool, Self::Error> {
+        Ok(D::exists(&config.inner_config, namespace).await?)
+    }
+
+    async fn create(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        Ok(D::create(&config.inner_config, namespace).await?)
+    }
+
+    async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        Ok(D::delete(&config.inner_config, namespace).await?)
+    }
+}
+
+impl<D> EncryptedDatabase<D>
+where
+    D: KeyValueDatabase,
+    D::Store: ReadableKeyValueStore<Error = D::Error>,
+    D::Error: 'static,
+{
+    fn wrap(&self, store: D::Store) -> Result<EncryptedStore<D::Store>, EncryptionError<D::Error>> {
+        let root_key = store.root_key()?;
+        Ok(EncryptedStore {
+            store,
+            cipher: self.cipher.clone(),
+            root_key,
+        })
+    }
+}
+
+/// A memory database with encryption.
+#[cfg(with_testing)]
+pub type EncryptedMemoryDatabase = EncryptedDatabase<MemoryDatabase>;
+
+#[cfg(with_testing)]
+impl<D> TestKeyValueDatabase for EncryptedDatabase<D>
+where
+    D: TestKeyValueDatabase,
+    D::Store: ReadableKeyValueStore<Error = D::Error>,
+    D::Error: 'static,
+{
+    async fn new_test_config() -> Result<EncryptedConfig<D::Config>, Self::Error> {
+        let inner_config = D::new_test_config().await?;
+        Ok(EncryptedConfig {
+            inner_config,
+            key: EncryptionKey::Key([7; 32]),
+            encrypt_keys: true,
+        })
+    }
+}
+
+#[cfg(with_testing)]
+impl<D: crate::backends::DatabaseBackup> crate::backends::DatabaseBackup for EncryptedDatabase<D> {
+    fn backup_to(&self, dir: &std::path::Path) -> anyhow::Result<()> {
+        self.database.backup_to(dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_views::{
+        batch::Batch,
+        encryption::{EncryptedConfig, EncryptedDatabase, EncryptionError, EncryptionKey},
+        memory::{MemoryDatabase, MemoryStoreConfig},
+        random::generate_test_namespace,
+        store::{KeyValueDatabase as _, ReadableKeyValueStore as _, WritableKeyValueStore as _},
+    };
+
+    fn config(key: [u8; 32]) -> EncryptedConfig<MemoryStoreConfig> {
+        EncryptedConfig {
+            inner_config: MemoryStoreConfig {
+                kill_on_drop: false,
+            },
+            key: EncryptionKey::Key(key),
+            encrypt_keys: true,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encryption_hides_and_authenticates_data() {
+        let namespace = generate_test_namespace();
+        let database =
+            EncryptedDatabase::<MemoryDatabase>::recreate_and_connect(&config([1; 32]), &namespace)
+                .await
+                .unwrap();
+        let store = database.open_exclusive(&[5]).unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"secret key".to_vec(), b"secret value".to_vec());
+        batch.put_key_value_bytes(b"secret other".to_vec(), b"other value".to_vec());
+        store.write_batch(batch).await.unwrap();
+
+        // The underlying store contains neither the keys nor the values in clear.
+        let raw_database = MemoryDatabase::connect(
+            &MemoryStoreConfig {
+                kill_on_drop: false,
+            },
+            &namespace,
+        )
+        .await
+        .unwrap();
+        let raw_store = raw_database.open_exclusive(&[5]).unwrap();
+        let raw_key_values = raw_store.find_key_values_by_prefix(&[]).await.unwrap();
+        assert_eq!(raw_key_values.len(), 2);
+        for (key, value) in &raw_key_values {
+            assert!(!key.starts_with(b"secret"));
+            assert!(!value.windows(5).any(|window| window == b"value"));
+        }
+
+        // Prefix searches still work.
+        let keys = store.find_keys_by_prefix(b"secret ").await.unwrap();
+        assert_eq!(keys, vec![b"key".to_vec(), b"other".to_vec()]);
+
+        // A different key cannot read the data.
+        let other_database =
+            EncryptedDatabase::<MemoryDatabase>::connect(&config([2; 32]), &namespace)
+                .await
+                .unwrap();
+        let other_store = other_database.open_exclusive(&[5]).unwrap();
+        assert_eq!(
+            other_store.read_value_bytes(b"secret key").await.unwrap(),
+            None
+        );
+        let mut config = config([2; 32]);
+        config.encrypt_keys = false;
+        let other_database = EncryptedDatabase::<MemoryDatabase>::connect(&config, &namespace)
+            .await
+            .unwrap();
+        let other_store = other_database.open_exclusive(&[5]).unwrap();
+        let (raw_key, _) = &raw_key_values[0];
+        assert!(matches!(
+            other_store.read_value_bytes(raw_key).await,
+            Err(EncryptionError::DecryptionFailed)
+        ));
+    }
+}
@@ -0,0 +1,976 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Implements [`crate::store::KeyValueStore`] by replicating the data of a primary store to
+//! a secondary store.
+//!
+//! Every write goes to both replicas. If a replica fails to apply a write, the partition
+//! (root key) it was written to is marked as stale in that replica, and the write succeeds
+//! as long as the other replica applied it. Reads go to the primary replica, unless its
+//! copy of the partition is stale, and fall back to the secondary replica when the primary
+//! replica fails.
+//!
+//! The stale partitions are also recorded in the replica that applied the write, so that
+//! they are still known after a restart. A background task periodically re-synchronizes the
+//! stale partitions by copying them from the other replica, a bounded number of keys at a
+//! time.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+use futures::join;
+use linera_base::task::Task;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{error, info, warn};
+
+#[cfg(with_testing)]
+use crate::store::TestKeyValueDatabase;
+use crate::{
+    batch::Batch,
+    store::{
+        KeyValueDatabase, KeyValueStore, KeyValueStoreError, ReadableKeyValueStore, WithError,
+        WritableKeyValueStore,
+    },
+};
+
+/// How long the background task waits between two re-synchronizations of the stale
+/// partitions.
+const RESYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The maximum number of keys copied at once when re-synchronizing a partition. Writes wait
+/// for at most one such chunk to be copied.
+const COPY_CHUNK_SIZE: usize = 1000;
+
+/// The root key of the partition in which each replica records the partitions that are
+/// stale in the other replica. It is not listed by [`KeyValueDatabase::list_root_keys`].
+const STALE_PARTITIONS_ROOT_KEY: &[u8] = b"\xfffailover_stale_partitions";
+
+/// The initial configuration of the system.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverStoreConfig<C1, C2> {
+    /// The config of the primary replica.
+    pub primary_config: C1,
+    /// The config of the secondary replica.
+    pub secondary_config: C2,
+}
+
+/// One of the two replicas of a failover database.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Replica {
+    /// The primary replica.
+    Primary,
+    /// The secondary replica.
+    Secondary,
+}
+
+impl Replica {
+    /// Returns the key under which a partition that is stale in this replica is recorded
+    /// in the other replica.
+    fn stale_partition_key(self, root_key: &[u8]) -> Vec<u8> {
+        let mut key = vec![self as u8];
+        key.extend_from_slice(root_key);
+        key
+    }
+
+    /// Parses a key returned by [`Replica::stale_partition_key`].
+    fn from_stale_partition_key(key: &[u8]) -> Option<(Self, &[u8])> {
+        let (tag, root_key) = key.split_first()?;
+        let replica = match *tag {
+            tag if tag == Replica::Primary as u8 => Replica::Primary,
+            tag if tag == Replica::Secondary as u8 => Replica::Secondary,
+            _ => return None,
+        };
+        Some((replica, root_key))
+    }
+}
+
+/// The partitions that missed writes in each replica.
+#[derive(Debug, Default)]
+struct FailoverState {
+    /// The number of missed writes of the partitions whose primary copy is stale.
+    stale_in_primary: Mutex<BTreeMap<Vec<u8>, u64>>,
+    /// The number of missed writes of the partitions whose secondary copy is stale.
+    stale_in_secondary: Mutex<BTreeMap<Vec<u8>, u64>>,
+    /// Held by writes, and exclusively while a chunk of a partition is copied, so that no
+    /// write is lost by being overwritten with an older value.
+    resync_lock: tokio::sync::RwLock<()>,
+}
+
+impl FailoverState {
+    fn stale(&self, replica: Replica) -> std::sync::MutexGuard<'_, BTreeMap<Vec<u8>, u64>> {
+        match replica {
+            Replica::Primary => &self.stale_in_primary,
+            Replica::Secondary => &self.stale_in_secondary,
+        }
+        .lock()
+        .expect("the failover state should not be poisoned")
+    }
+
+    fn is_stale(&self, replica: Replica, root_key: &[u8]) -> bool {
+        self.stale(replica).contains_key(root_key)
+    }
+
+    /// Returns the number of missed writes of the partition, if it is stale.
+    fn missed_writes(&self, replica: Replica, root_key: &[u8]) -> Option<u64> {
+        self.stale(replica).get(root_key).copied()
+    }
+
+    fn mark_stale(&self, replica: Replica, root_key: &[u8]) {
+        let mut stale = self.stale(replica);
+        let missed_writes = stale.entry(root_key.to_vec()).or_default();
+        if *missed_writes == 0 {
+            warn!(?replica, ?root_key, "Partition is now stale");
+        }
+        *missed_writes += 1;
+    }
+
+    /// Marks the partition as up to date, unless it missed more writes than `missed_writes`
+    /// in the meantime. Returns whether it is now up to date.
+    fn clear_stale(&self, replica: Replica, root_key: &[u8], missed_writes: Option<u64>) -> bool {
+        let mut stale = self.stale(replica);
+        if stale.get(root_key).copied() != missed_writes {
+            return false;
+        }
+        stale.remove(root_key);
+        true
+    }
+}
+
+/// A database replicated to a secondary database, failing over to it when the primary
+/// database fails.
+#[derive(Debug, Clone)]
+pub struct FailoverDatabase<D1, D2> {
+    /// The primary database.
+    primary_database: D1,
+    /// The secondary database.
+    secondary_database: D2,
+    /// The partitions that missed writes.
+    state: Arc<FailoverState>,
+}
+
+/// A partition opened in both replicas.
+#[derive(Clone)]
+pub struct FailoverStore<S1, S2> {
+    /// The partition in the primary replica.
+    primary: S1,
+    /// The partition in the secondary replica.
+    secondary: S2,
+    /// The partition of the primary replica recording the partitions stale in the
+    /// secondary replica.
+    primary_stale_partitions: S1,
+    /// The partition of the secondary replica recording the partitions stale in the
+    /// primary replica.
+    secondary_stale_partitions: S2,
+    /// The root key of the partition.
+    root_key: Vec<u8>,
+    /// The partitions that missed writes.
+    state: Arc<FailoverState>,
+}
+
+impl<D1, D2> WithError for FailoverDatabase<D1, D2>
+where
+    D1: WithError,
+    D2: WithError,
+{
+    type Error = FailoverStoreError<D1::Error, D2::Error>;
+}
+
+impl<S1, S2> WithError for FailoverStore<S1, S2>
+where
+    S1: WithError,
+    S2: WithError,
+{
+    type Error = FailoverStoreError<S1::Error, S2::Error>;
+}
+
+/// Runs a read on the primary replica, falling back to the secondary replica.
+macro_rules! read_with_failover {
+    ($store:ident . $method:ident ( $($arg:expr),* )) => {{
+        if $store.state.is_stale(Replica::Primary, &$store.root_key) {
+            $store
+                .secondary
+                .$method($($arg),*)
+                .await
+                .map_err(FailoverStoreError::Secondary)
+        } else {
+            match $store.primary.$method($($arg),*).await {
+                Ok(result) => Ok(result),
+                Err(error) if !$store.state.is_stale(Replica::Secondary, &$store.root_key) => {
+                    warn!(%error, "Failed to read from the primary store; reading from the secondary store");
+                    $store
+                        .secondary
+                        .$method($($arg),*)
+                        .await
+                        .map_err(FailoverStoreError::Secondary)
+                }
+                Err(error) => Err(FailoverStoreError::Primary(error)),
+            }
+        }
+    }};
+}
+
+impl<S1, S2> ReadableKeyValueStore for FailoverStore<S1, S2>
+where
+    S1: ReadableKeyValueStore,
+    S2: ReadableKeyValueStore,
+{
+    // TODO(#2524): consider changing MAX_KEY_SIZE into a function.
+    const MAX_KEY_SIZE: usize = if S1::MAX_KEY_SIZE < S2::MAX_KEY_SIZE {
+        S1::MAX_KEY_SIZE
+    } else {
+        S2::MAX_KEY_SIZE
+    };
+
+    fn root_key(&self) -> Result<Vec<u8>, Self::Error> {
+        self.primary.root_key().map_err(FailoverStoreError::Primary)
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        read_with_failover!(self.read_value_bytes(key))
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        read_with_failover!(self.contains_key(key))
+    }
+
+    async fn contains_keys(&self, keys: &[Vec<u8>]) -> Result<Vec<bool>, Self::Error> {
+        read_with_failover!(self.contains_keys(keys))
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: &[Vec<u8>],
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        read_with_failover!(self.read_multi_values_bytes(keys))
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+        read_with_failover!(self.find_keys_by_prefix(key_prefix))
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        read_with_failover!(self.find_key_values_by_prefix(key_prefix))
+    }
+}
+
+/// Returns a batch recording that the partition is stale in the given replica.
+fn stale_partition_batch(replica: Replica, root_key: &[u8]) -> Batch {
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(replica.stale_partition_key(root_key), Vec::new());
+    batch
+}
+
+impl<S1, S2> FailoverStore<S1, S2>
+where
+    S1: WritableKeyValueStore,
+    S2: WritableKeyValueStore,
+{
+    /// Marks the replicas that failed to apply a write as stale, and returns an error only
+    /// if both failed, or if the replica that applied the write failed to record that the
+    /// other one is stale.
+    async fn record_write(
+        &self,
+        primary: Result<(), S1::Error>,
+        secondary: Result<(), S2::Error>,
+    ) -> Result<(), FailoverStoreError<S1::Error, S2::Error>> {
+        match (primary, secondary) {
+            (Ok(()), Ok(())) => Ok(()),
+            (Err(error), Ok(())) => {
+                warn!(%error, "Failed to write to the primary store");
+                self.state.mark_stale(Replica::Primary, &self.root_key);
+                self.secondary_stale_partitions
+                    .write_batch(stale_partition_batch(Replica::Primary, &self.root_key))
+                    .await
+                    .map_err(FailoverStoreError::Secondary)
+            }
+            (Ok(()), Err(error)) => {
+                warn!(%error, "Failed to write to the secondary store");
+                self.state.mark_stale(Replica::Secondary, &self.root_key);
+                self.primary_stale_partitions
+                    .write_batch(stale_partition_batch(Replica::Secondary, &self.root_key))
+                    .await
+                    .map_err(FailoverStoreError::Primary)
+            }
+            (Err(error), Err(_)) => Err(FailoverStoreError::Primary(error)),
+        }
+    }
+}
+
+impl<S1, S2> WritableKeyValueStore for FailoverStore<S1, S2>
+where
+    S1: WritableKeyValueStore,
+    S2: WritableKeyValueStore,
+{
+    const MAX_VALUE_SIZE: usize = if S1::MAX_VALUE_SIZE < S2::MAX_VALUE_SIZE {
+        S1::MAX_VALUE_SIZE
+    } else {
+        S2::MAX_VALUE_SIZE
+    };
+
+    async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        let _guard = self.state.resync_lock.read().await;
+        let (primary, secondary) = join!(
+            self.primary.write_batch(batch.clone()),
+            self.secondary.write_batch(batch)
+        );
+        self.record_write(primary, secondary).await
+    }
+
+    async fn clear_journal(&self) -> Result<(), Self::Error> {
+        let _guard = self.state.resync_lock.read().await;
+        let (primary, secondary) =
+            join!(self.primary.clear_journal(), self.secondary.clear_journal());
+        self.record_write(primary, secondary).await
+    }
+}
+
+/// Replaces the content of the partition `root_key` of `target` with the one in `source`,
+/// [`COPY_CHUNK_SIZE`] keys at a time. Each chunk is read from `source` and written to
+/// `target` while holding `lock` exclusively, so that the writes applied to both replicas
+/// between two chunks are not overwritten with older values.
+async fn copy_partition<D, T>(
+    source: &D,
+    target: &T,
+    root_key: &[u8],
+    lock: &tokio::sync::RwLock<()>,
+) -> Result<(), String>
+where
+    D: KeyValueDatabase<Store: KeyValueStore>,
+    T: KeyValueDatabase<Store: KeyValueStore>,
+{
+    let source = source
+        .open_exclusive(root_key)
+        .map_err(|error| error.to_string())?;
+    let target = target
+        .open_exclusive(root_key)
+        .map_err(|error| error.to_string())?;
+    let (source_keys, target_keys) = join!(
+        source.find_keys_by_prefix(&[]),
+        target.find_keys_by_prefix(&[])
+    );
+    // Keys created after this point are written to both replicas.
+    let keys = source_keys
+        .map_err(|error| error.to_string())?
+        .into_iter()
+        .chain(target_keys.map_err(|error| error.to_string())?)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    for chunk in keys.chunks(COPY_CHUNK_SIZE) {
+        let _guard = lock.write().await;
+        let values = source
+            .read_multi_values_bytes(chunk)
+            .await
+            .map_err(|error| error.to_string())?;
+        let mut batch = Batch::new();
+        for (key, value) in chunk.iter().zip(values) {
+            match value {
+                Some(value) => batch.put_key_value_bytes(key.clone(), value),
+                None => batch.delete_key(key.clone()),
+            }
+        }
+        target
+            .write_batch(batch)
+            .await
+            .map_err(|error| error.to_string())?;
+    }
+    Ok(())
+}
+
+/// Reads the partitions recorded as stale in the other replica by `database`.
+async fn read_stale_partitions<D>(database: &D) -> Result<Vec<(Replica, Vec<u8>)>, String>
+where
+    D: KeyValueDatabase<Store: KeyValueStore>,
+{
+    let keys = database
+        .open_shared(STALE_PARTITIONS_ROOT_KEY)
+        .map_err(|error| error.to_string())?
+        .find_keys_by_prefix(&[])
+        .await
+        .map_err(|error| error.to_string())?;
+    Ok(keys
+        .iter()
+        .filter_map(|key| Replica::from_stale_partition_key(key))
+        .map(|(replica, root_key)| (replica, root_key.to_vec()))
+        .collect())
+}
+
+/// Deletes the records that the partition is stale from `database`.
+async fn forget_stale_partition<D>(database: &D, root_key: &[u8]) -> Result<(), String>
+where
+    D: KeyValueDatabase<Store: KeyValueStore>,
+{
+    let mut batch = Batch::new();
+    batch.delete_key(Replica::Primary.stale_partition_key(root_key));
+    batch.delete_key(Replica::Secondary.stale_partition_key(root_key));
+    database
+        .open_shared(STALE_PARTITIONS_ROOT_KEY)
+        .map_err(|error| error.to_string())?
+        .write_batch(batch)
+        .await
+        .map_err(|error| error.to_string())
+}
+
+impl<D1, D2> FailoverDatabase<D1, D2>
+where
+    D1: KeyValueDatabase<Store: KeyValueStore>,
+    D2: KeyValueDatabase<Store: KeyValueStore>,
+{
+    /// Creates a failover database from two connected databases. Nothing is copied between
+    /// them: they are assumed to hold the same data.
+    pub fn new(primary_database: D1, secondary_database: D2) -> Self {
+        Self {
+            primary_database,
+            secondary_database,
+            state: Arc::default(),
+        }
+    }
+
+    /// Returns the number of partitions that are stale in the given replica.
+    pub fn stale_partition_count(&self, replica: Replica) -> usize {
+        self.state.stale(replica).len()
+    }
+
+    /// Loads the partitions that were recorded as stale, e.g. before a restart. A replica
+    /// that cannot be read is skipped: it is then likely that its own partitions are stale,
+    /// which the other replica records.
+    pub async fn load_stale_partitions(&self) {
+        let (primary, secondary) = join!(
+            read_stale_partitions(&self.primary_database),
+            read_stale_partitions(&self.secondary_database)
+        );
+        for (result, source) in [(primary, Replica::Primary), (secondary, Replica::Secondary)] {
+            match result {
+                Ok(partitions) => {
+                    for (replica, root_key) in partitions {
+                        self.state.mark_stale(replica, &root_key);
+                    }
+                }
+                Err(error) => warn!(
+                    %error,
+                    ?source,
+                    "Failed to read the stale partitions recorded in a replica"
+                ),
+            }
+        }
+    }
+
+    /// Copies the stale partitions of each replica from the other replica. Partitions that
+    /// missed writes in both replicas are copied from the primary one. Returns the number of
+    /// partitions that remain stale.
+    pub async fn resync(&self) -> usize {
+        let stale_in_primary = self.state.stale(Replica::Primary).clone();
+        let stale_in_secondary = self.state.stale(Replica::Secondary).clone();
+        let root_keys = stale_in_primary
+            .keys()
+            .chain(stale_in_secondary.keys())
+            .collect::<BTreeSet<_>>();
+        for root_key in root_keys {
+            // Writes may have failed since the stale partitions were listed.
+            let in_primary = self.state.missed_writes(Replica::Primary, root_key);
+            let in_secondary = self.state.missed_writes(Replica::Secondary, root_key);
+            let lock = &self.state.resync_lock;
+            let (result, source) = if in_secondary.is_some() {
+                if in_primary.is_some() {
+                    error!(
+                        ?root_key,
+                        "Partition missed writes in both replicas; keeping the primary copy"
+                    );
+                }
+                let result = copy_partition(
+                    &self.primary_database,
+                    &self.secondary_database,
+                    root_key,
+                    lock,
+                )
+                .await;
+                (result, Replica::Primary)
+            } else if in_primary.is_some() {
+                let result = copy_partition(
+                    &self.secondary_database,
+                    &self.primary_database,
+                    root_key,
+                    lock,
+                )
+                .await;
+                (result, Replica::Secondary)
+            } else {
+                continue;
+            };
+            if let Err(error) = result {
+                warn!(
+                    %error,
+                    ?root_key,
+                    "Failed to re-synchronize stale partition"
+                );
+                continue;
+            }
+            // The partition stays stale if a write failed again while it was copied.
+            let cleared_in_primary = self
+                .state
+                .clear_stale(Replica::Primary, root_key, in_primary);
+            let cleared_in_secondary =
+                self.state
+                    .clear_stale(Replica::Secondary, root_key, in_secondary);
+            if !(cleared_in_primary && cleared_in_secondary) {
+                continue;
+            }
+            info!(?root_key, ?source, "Re-synchronized stale partition");
+            let (primary, secondary) = join!(
+                forget_stale_partition(&self.primary_database, root_key),
+                forget_stale_partition(&self.secondary_database, root_key)
+            );
+            if let Err(error) = primary.and(secondary) {
+                // The partition is only copied again after a restart.
+                warn!(%error, ?root_key, "Failed to forget re-synchronized partition");
+            }
+        }
+        self.stale_partition_count(Replica::Primary)
+            + self.stale_partition_count(Replica::Secondary)
+    }
+}
+
+impl<D1, D2> FailoverDatabase<D1, D2>
+where
+    D1: KeyValueDatabase<Store: KeyValueStore> + Clone + 'static,
+    D2: KeyValueDatabase<Store: KeyValueStore> + Clone + 'static,
+{
+    /// Spawns the task re-synchronizing the stale partitions, which stops once the database
+    /// and all its stores are dropped.
+    fn spawn_resync_task(&self) {
+        let primary_database = self.primary_database.clone();
+        let secondary_database = self.secondary_database.clone();
+        let state = Arc::downgrade(&self.state);
+        Task::spawn(async move {
+            loop {
+                linera_base::time::timer::sleep(RESYNC_INTERVAL).await;
+                let Some(state) = Weak::upgrade(&state) else {
+                    break;
+                };
+                if state.stale(Replica::Primary).is_empty()
+                    && state.stale(Replica::Secondary).is_empty()
+                {
+                    continue;
+                }
+                let database = FailoverDatabase {
+                    primary_database: primary_database.clone(),
+                    secondary_database: secondary_database.clone(),
+                    state,
+                };
+                database.resync().await;
+            }
+        })
+        .forget();
+    }
+}
+
+impl<D1, D2> KeyValueDatabase for FailoverDatabase<D1, D2>
+where
+    D1: KeyValueDatabase<Store: KeyValueStore> + Clone + 'static,
+    D2: KeyValueDatabase<Store: KeyValueStore> + Clone + 'static,
+{
+    type Config = FailoverStoreConfig<D1::Config, D2::Config>;
+    type Store = FailoverStore<D1::Store, D2::Store>;
+
+    fn get_name() -> String {
+        format!("failover {} to {}", D1::get_name(), D2::get_name())
+    }
+
+    async fn connect(config: &Self::Config, namespace: &str) -> Result<Self, Self::Error> {
+        let primary_database = D1::connect(&config.primary_config, namespace)
+            .await
+            .map_err(FailoverStoreError::Primary)?;
+        let secondary_database = D2::connect(&config.secondary_config, namespace)
+            .await
+            .map_err(FailoverStoreError::Secondary)?;
+        let database = Self::new(primary_database, secondary_database);
+        database.load_stale_partitions().await;
+        database.spawn_resync_task();
+        Ok(database)
+    }
+
+    fn open_shared(&self, root_key: &[u8]) -> Result<Self::Store, Self::Error> {
+        Ok(FailoverStore {
+            primary: self
+                .primary_database
+                .open_shared(root_key)
+                .map_err(FailoverStoreError::Primary)?,
+            secondary: self
+                .secondary_database
+                .open_shared(root_key)
+                .map_err(FailoverStoreError::Secondary)?,
+            primary_stale_partitions: self
+                .primary_database
+                .open_shared(STALE_PARTITIONS_ROOT_KEY)
+                .map_err(FailoverStoreError::Primary)?,
+            secondary_stale_partitions: self
+                .secondary_database
+                .open_shared(STALE_PARTITIONS_ROOT_KEY)
+                .map_err(FailoverStoreError::Secondary)?,
+            root_key: root_key.to_vec(),
+            state: self.state.clone(),
+        })
+    }
+
+    fn open_exclusive(&self, root_key: &[u8]) -> Result<Self::Store, Self::Error> {
+        Ok(FailoverStore {
+            primary: self
+                .primary_database
+                .open_exclusive(root_key)
+                .map_err(FailoverStoreError::Primary)?,
+            secondary: self
+                .secondary_database
+                .open_exclusive(root_key)
+                .map_err(FailoverStoreError::Secondary)?,
+            primary_stale_partitions: self
+                .primary_database
+                .open_shared(STALE_PARTITIONS_ROOT_KEY)
+                .map_err(FailoverStoreError::Primary)?,
+            secondary_stale_partitions: self
+                .secondary_database
+                .open_shared(STALE_PARTITIONS_ROOT_KEY)
+                .map_err(FailoverStoreError::Secondary)?,
+            root_key: root_key.to_vec(),
+            state: self.state.clone(),
+        })
+    }
+
+    async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
+        match D1::list_all(&config.primary_config).await {
+            Ok(namespaces) => Ok(namespaces),
+            Err(error) => {
+                warn!(%error, "Failed to list the namespaces of the primary store");
+                D2::list_all(&config.secondary_config)
+                    .await
+                    .map_err(FailoverStoreError::Secondary)
+            }
+        }
+    }
+
+    async fn list_root_keys(&self) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let (primary, secondary) = join!(
+            self.primary_database.list_root_keys(),
+            self.secondary_database.list_root_keys()
+        );
+        let root_keys = match (primary, secondary) {
+            (Ok(primary), Ok(secondary)) => primary
+                .into_iter()
+                .chain(secondary)
+                .collect::<BTreeSet<_>>(),
+            (Ok(root_keys), Err(error)) => {
+                warn!(%error, "Failed to list the root keys of the secondary store");
+                root_keys.into_iter().collect()
+            }
+            (Err(error), Ok(root_keys)) => {
+                warn!(%error, "Failed to list the root keys of the primary store");
+                root_keys.into_iter().collect()
+            }
+            (Err(error), Err(_)) => return Err(FailoverStoreError::Primary(error)),
+        };
+        Ok(root_keys
+            .into_iter()
+            .filter(|root_key| root_key != STALE_PARTITIONS_ROOT_KEY)
+            .collect())
+    }
+
+    async fn exists(config: &Self::Config, namespace: &str) -> Result<bool, Self::Error> {
+        Ok(D1::exists(&config.primary_config, namespace)
+            .await
+            .map_err(FailoverStoreError::Primary)?
+            && D2::exists(&config.secondary_config, namespace)
+                .await
+                .map_err(FailoverStoreError::Secondary)?)
+    }
+
+    /// Creates the namespace in the replicas that do not have it. If one replica already
+    /// had it, its content is copied to the other one.
+    async fn create(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        let exists1 = D1::exists(&config.primary_config, namespace)
+            .await
+            .map_err(FailoverStoreError::Primary)?;
+        let exists2 = D2::exists(&config.secondary_config, namespace)
+            .await
+            .map_err(FailoverStoreError::Secondary)?;
+        if exists1 && exists2 {
+            return Err(FailoverStoreError::StoreAlreadyExists);
+        }
+        if !exists1 {
+            D1::create(&config.primary_config, namespace)
+                .await
+                .map_err(FailoverStoreError::Primary)?;
+        }
+        if !exists2 {
+            D2::create(&config.secondary_config, namespace)
+                .await
+                .map_err(FailoverStoreError::Secondary)?;
+        }
+        if exists1 == exists2 {
+            return Ok(());
+        }
+        let primary_database = D1::connect(&config.primary_config, namespace)
+            .await
+            .map_err(FailoverStoreError::Primary)?;
+        let secondary_database = D2::connect(&config.secondary_config, namespace)
+            .await
+            .map_err(FailoverStoreError::Secondary)?;
+        // Nothing else writes to the new replica yet.
+        let lock = tokio::sync::RwLock::new(());
+        if exists1 {
+            info!(
+                namespace,
+                "Copying the primary store to the new secondary store"
+            );
+            let root_keys = primary_database
+                .list_root_keys()
+                .await
+                .map_err(FailoverStoreError::Primary)?;
+            for root_key in root_keys {
+                if root_key == STALE_PARTITIONS_ROOT_KEY {
+                    continue;
+                }
+                copy_partition(&primary_database, &secondary_database, &root_key, &lock)
+                    .await
+                    .map_err(FailoverStoreError::Resync)?;
+            }
+        } else {
+            info!(
+                namespace,
+                "Copying the secondary store to the new primary store"
+            );
+            let root_keys = secondary_database
+                .list_root_keys()
+                .await
+                .map_err(FailoverStoreError::Secondary)?;
+            for root_key in root_keys {
+                if root_key == STALE_PARTITIONS_ROOT_KEY {
+                    continue;
+                }
+                copy_partition(&secondary_database, &primary_database, &root_key, &lock)
+                    .await
+                    .map_err(FailoverStoreError::Resync)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        D1::delete(&config.primary_config, namespace)
+            .await
+            .map_err(FailoverStoreError::Primary)?;
+        D2::delete(&config.secondary_config, namespace)
+            .await
+            .map_err(FailoverStoreError::Secondary)?;
+        Ok(())
+    }
+}
+
+#[cfg(with_testing)]
+impl<D1, D2> TestKeyValueDatabase for FailoverDatabase<D1, D2>
+where
+    D1: TestKeyValueDatabase<Store: KeyValueStore> + Clone + 'static,
+    D2: TestKeyValueDatabase<Store: KeyValueStore> + Clone + 'static,
+{
+    async fn new_test_config() -> Result<Self::Config, Self::Error> {
+        let primary_config = D1::new_test_config()
+            .await
+            .map_err(FailoverStoreError::Primary)?;
+        let secondary_config = D2::new_test_config()
+            .await
+            .map_err(FailoverStoreError::Secondary)?;
+        Ok(FailoverStoreConfig {
+            primary_config,
+            secondary_config,
+        })
+    }
+}
+
+/// The error type for [`FailoverStore`].
+#[derive(Error, Debug)]
+pub enum FailoverStoreError<E1, E2> {
+    /// Store already exists during a create operation
+    #[error("Store already exists during a create operation")]
+    StoreAlreadyExists,
+
+    /// Serialization error with BCS.
+    #[error(transparent)]
+    BcsError(#[from] bcs::Error),
+
+    /// Copying a partition from one replica to the other failed.
+    #[error("Failed to copy a partition between the replicas: {0}")]
+    Resync(String),
+
+    /// Primary store.
+    #[error("Error in primary store: {0}")]
+    Primary(E1),
+
+    /// Secondary store.
+    #[error("Error in secondary store: {0}")]
+    Secondary(E2),
+}
+
+impl<E1, E2> KeyValueStoreError for FailoverStoreError<E1, E2>
+where
+    E1: KeyValueStoreError,
+    E2: KeyValueStoreError,
+{
+    const BACKEND: &'static str = "failover_store";
+
+    fn must_reload_view(&self) -> bool {
+        match self {
+            FailoverStoreError::Primary(e) => e.must_reload_view(),
+            FailoverStoreError::Secondary(e) => e.must_reload_view(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FailoverDatabase, Replica, COPY_CHUNK_SIZE};
+    use crate::{
+        batch::Batch,
+        memory::{MemoryDatabase, MemoryStoreError},
+        store::{
+            KeyValueDatabase as _, ReadableKeyValueStore as _, TestKeyValueDatabase as _,
+            WritableKeyValueStore as _,
+        },
+    };
+
+    async fn failover_database() -> FailoverDatabase<MemoryDatabase, MemoryDatabase> {
+        let config = MemoryDatabase::new_test_config().await.unwrap();
+        let primary = MemoryDatabase::recreate_and_connect(
+            &config,
+            &crate::random::generate_test_namespace(),
+        )
+        .await
+        .unwrap();
+        let secondary = MemoryDatabase::recreate_and_connect(
+            &config,
+            &crate::random::generate_test_namespace(),
+        )
+        .await
+        .unwrap();
+        FailoverDatabase::new(primary, secondary)
+    }
+
+    #[tokio::test]
+    async fn test_failover_writes_to_both_replicas() {
+        let database = failover_database().await;
+        let store = database.open_shared(&[1]).unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![2], vec![3]);
+        store.write_batch(batch).await.unwrap();
+
+        assert_eq!(store.read_value_bytes(&[2]).await.unwrap(), Some(vec![3]));
+        let primary = database.primary_database.open_shared(&[1]).unwrap();
+        assert_eq!(primary.read_value_bytes(&[2]).await.unwrap(), Some(vec![3]));
+        let secondary = database.secondary_database.open_shared(&[1]).unwrap();
+        assert_eq!(
+            secondary.read_value_bytes(&[2]).await.unwrap(),
+            Some(vec![3])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failover_reads_and_resyncs_stale_partition() {
+        let database = failover_database().await;
+        let store = database.open_shared(&[1]).unwrap();
+        let primary = database.primary_database.open_shared(&[1]).unwrap();
+        let secondary = database.secondary_database.open_shared(&[1]).unwrap();
+
+        // A write that only reached the secondary replica.
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![0], vec![0]);
+        primary.write_batch(batch).await.unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![2], vec![3]);
+        secondary.write_batch(batch).await.unwrap();
+        database.state.mark_stale(Replica::Primary, &[1]);
+        assert_eq!(database.stale_partition_count(Replica::Primary), 1);
+
+        // Reads use the secondary replica while the primary one is stale.
+        assert_eq!(store.read_value_bytes(&[2]).await.unwrap(), Some(vec![3]));
+        assert_eq!(primary.read_value_bytes(&[2]).await.unwrap(), None);
+
+        assert_eq!(database.resync().await, 0);
+        assert_eq!(primary.read_value_bytes(&[2]).await.unwrap(), Some(vec![3]));
+        assert_eq!(primary.read_value_bytes(&[0]).await.unwrap(), None);
+        assert_eq!(database.stale_partition_count(Replica::Primary), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failover_persists_stale_partitions() {
+        let database = failover_database().await;
+        let store = database.open_shared(&[1]).unwrap();
+        store
+            .record_write(Err(MemoryStoreError::NamespaceNotFound), Ok(()))
+            .await
+            .unwrap();
+        assert_eq!(database.stale_partition_count(Replica::Primary), 1);
+        assert_eq!(
+            database.list_root_keys().await.unwrap(),
+            Vec::<Vec<u8>>::new()
+        );
+
+        // The stale partition is still known after a restart, until it is re-synchronized.
+        let restarted = FailoverDatabase::new(
+            database.primary_database.clone(),
+            database.secondary_database.clone(),
+        );
+        restarted.load_stale_partitions().await;
+        assert_eq!(restarted.stale_partition_count(Replica::Primary), 1);
+        assert_eq!(restarted.stale_partition_count(Replica::Secondary), 0);
+        assert_eq!(restarted.resync().await, 0);
+
+        let restarted = FailoverDatabase::new(
+            database.primary_database.clone(),
+            database.secondary_database.clone(),
+        );
+        restarted.load_stale_partitions().await;
+        assert_eq!(restarted.stale_partition_count(Replica::Primary), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failover_resyncs_large_partition_in_chunks() {
+        let database = failover_database().await;
+        let primary = database.primary_database.open_shared(&[1]).unwrap();
+        let secondary = database.secondary_database.open_shared(&[1]).unwrap();
+
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![0xff, 0xff, 0xff], vec![0]);
+        primary.write_batch(batch).await.unwrap();
+        let mut batch = Batch::new();
+        for index in 0..(2 * COPY_CHUNK_SIZE + 1) as u32 {
+            batch.put_key_value_bytes(index.to_be_bytes().to_vec(), index.to_le_bytes().to_vec());
+        }
+        secondary.write_batch(batch).await.unwrap();
+        database.state.mark_stale(Replica::Primary, &[1]);
+
+        assert_eq!(database.resync().await, 0);
+        assert_eq!(
+            primary.find_key_values_by_prefix(&[]).await.unwrap(),
+            secondary.find_key_values_by_prefix(&[]).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failover_keeps_partition_stale_after_new_failure() {
+        let database = failover_database().await;
+        database.state.mark_stale(Replica::Secondary, &[1]);
+        let missed_writes = database.state.missed_writes(Replica::Secondary, &[1]);
+
+        // A write fails again while the partition is being copied.
+        database.state.mark_stale(Replica::Secondary, &[1]);
+        assert!(!database
+            .state
+            .clear_stale(Replica::Secondary, &[1], missed_writes));
+        assert_eq!(database.stale_partition_count(Replica::Secondary), 1);
+    }
+}
@@ -9,6 +9,7 @@
 use std::{convert::Infallible, future::Future, ops::Bound, rc::Rc};
 
 use futures::future;
+use linera_base::time::{SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use web_sys::{js_sys, wasm_bindgen::JsValue};
@@ -29,6 +30,10 @@ pub struct IndexedDbStoreConfig;
 /// The prefixes being used in the system
 static ROOT_KEY_DOMAIN: [u8; 1] = [0];
 static STORED_ROOT_KEYS_PREFIX: [u8; 1] = [1];
+/// The domain of the values written with a TTL. Such a value is stored under its full key
+/// with the first byte replaced by this domain, prefixed with its expiration time in seconds
+/// since the Unix epoch (8 bytes, big-endian). A key is never present in both domains.
+static EXPIRING_KEY_DOMAIN: u8 = 2;
 
 const OBJECT_STORE_NAME: &str = "linera";
 
@@ -66,6 +71,60 @@ impl IndexedDbStore {
         full_key.extend(key);
         full_key
     }
+
+    /// Reads the value written with a TTL under the given full key, if it has not expired.
+    async fn read_expiring_value(&self, full_key: &[u8]) -> Result<Option<Vec<u8>>> {
+        if !self.start_key.starts_with(&ROOT_KEY_DOMAIN) {
+            return Ok(None);
+        }
+        let key = expiring_key(full_key);
+        let entry = self
+            .with_object_store(move |o| o.get(&js_sys::Uint8Array::from(key.as_slice())))
+            .await??;
+        let now = unix_seconds(SystemTime::now());
+        Ok(entry.and_then(|entry| {
+            let entry = js_sys::Uint8Array::new(&entry).to_vec();
+            live_value(&entry, now).map(<[u8]>::to_vec)
+        }))
+    }
+
+    /// Returns the values written with a TTL under the given full key prefix that have not
+    /// expired, with their keys stripped of the prefix.
+    async fn find_expiring_key_values(
+        &self,
+        full_key_prefix: &[u8],
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        if !self.start_key.starts_with(&ROOT_KEY_DOMAIN) {
+            return Ok(Vec::new());
+        }
+        let key_prefix = expiring_key(full_key_prefix);
+        let range = prefix_to_range(&key_prefix);
+        let now = unix_seconds(SystemTime::now());
+        self.with_object_store(move |object_store| async move {
+            let mut key_values = vec![];
+            let mut cursor = object_store.cursor().range(range)?.open().await?;
+
+            while let Some(key) = cursor.primary_key() {
+                let key = js_sys::Uint8Array::new(&key);
+                let entry = js_sys::Uint8Array::new(
+                    &cursor
+                        .value()
+                        .expect("we should have a value because we have a key"),
+                )
+                .to_vec();
+                if let Some(value) = live_value(&entry, now) {
+                    key_values.push((
+                        key.subarray(key_prefix.len() as u32, key.length()).to_vec(),
+                        value.to_vec(),
+                    ));
+                }
+                cursor.advance(1).await?;
+            }
+
+            Ok(key_values)
+        })
+        .await?
+    }
 }
 
 impl IndexedDbDatabase {
@@ -75,6 +134,59 @@ impl IndexedDbDatabase {
             start_key,
         }
     }
+
+    /// Deletes the values written with a TTL that have expired. IndexedDB has no native
+    /// expiration, so this runs whenever the database is opened.
+    async fn remove_expired_values(&self) -> Result<()> {
+        let range = prefix_to_range(&[EXPIRING_KEY_DOMAIN]);
+        let now = unix_seconds(SystemTime::now());
+        self.database
+            .transaction(&[OBJECT_STORE_NAME])
+            .rw()
+            .run(move |transaction| async move {
+                let object_store = transaction.object_store(OBJECT_STORE_NAME)?;
+                let mut expired_keys = vec![];
+                let mut cursor = object_store.cursor().range(range)?.open().await?;
+                while let Some(key) = cursor.primary_key() {
+                    let entry = js_sys::Uint8Array::new(
+                        &cursor
+                            .value()
+                            .expect("we should have a value because we have a key"),
+                    )
+                    .to_vec();
+                    if live_value(&entry, now).is_none() {
+                        expired_keys.push(key);
+                    }
+                    cursor.advance(1).await?;
+                }
+                for key in expired_keys {
+                    object_store.delete(&key).await?;
+                }
+                Ok(())
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Returns the key of the expiring key domain under which a value with the given full key
+/// is stored when it was written with a TTL.
+fn expiring_key(full_key: &[u8]) -> Vec<u8> {
+    let mut key = full_key.to_vec();
+    key[0] = EXPIRING_KEY_DOMAIN;
+    key
+}
+
+/// Returns the value of an entry of the expiring key domain, or `None` if it has expired.
+fn live_value(entry: &[u8], now: u64) -> Option<&[u8]> {
+    let (expiration, value) = entry.split_first_chunk::<8>()?;
+    (u64::from_be_bytes(*expiration) > now).then_some(value)
 }
 
 fn database_name(namespace: &str) -> String {
@@ -110,18 +222,27 @@ impl ReadableKeyValueStore for IndexedDbStore {
     }
 
     async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>> {
-        let key = self.full_key(key);
+        let full_key = self.full_key(key);
+        let key = full_key.clone();
         let value = self
             .with_object_store(move |o| o.get(&js_sys::Uint8Array::from(key.as_slice())))
             .await??;
-        Ok(value.map(|v| js_sys::Uint8Array::new(&v).to_vec()))
+        match value {
+            Some(value) => Ok(Some(js_sys::Uint8Array::new(&value).to_vec())),
+            None => self.read_expiring_value(&full_key).await,
+        }
     }
 
     async fn contains_key(&self, key: &[u8]) -> Result<bool> {
-        let key = self.full_key(key);
-        Ok(self
+        let full_key = self.full_key(key);
+        let key = full_key.clone();
+        if self
             .with_object_store(move |o| o.contains(&js_sys::Uint8Array::from(key.as_slice())))
-            .await??)
+            .await??
+        {
+            return Ok(true);
+        }
+        Ok(self.read_expiring_value(&full_key).await?.is_some())
     }
 
     async fn contains_keys(&self, keys: &[Vec<u8>]) -> Result<Vec<bool>> {
@@ -143,7 +264,7 @@ impl ReadableKeyValueStore for IndexedDbStore {
     async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Vec<Vec<u8>>> {
         let key_prefix = self.full_key(key_prefix);
         let range = prefix_to_range(&key_prefix);
-        Ok(self
+        let mut keys = self
             .with_object_store(|o| o.get_all_keys_in(range, None))
             .await??
             .into_iter()
@@ -151,7 +272,14 @@ impl ReadableKeyValueStore for IndexedDbStore {
                 let key = js_sys::Uint8Array::new(&key);
                 key.subarray(key_prefix.len() as u32, key.length()).to_vec()
             })
-            .collect())
+            .collect::<Vec<_>>();
+        let expiring_key_values = self.find_expiring_key_values(&key_prefix).await?;
+        if !expiring_key_values.is_empty() {
+            keys.extend(expiring_key_values.into_iter().map(|(key, _)| key));
+            keys.sort_unstable();
+            keys.dedup();
+        }
+        Ok(keys)
     }
 
     async fn find_key_values_by_prefix(
@@ -159,28 +287,36 @@ impl ReadableKeyValueStore for IndexedDbStore {
         key_prefix: &[u8],
     ) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
         let key_prefix = self.full_key(key_prefix);
+        let expiring_key_values = self.find_expiring_key_values(&key_prefix).await?;
         let range = prefix_to_range(&key_prefix);
-        self.with_object_store(|object_store| async move {
-            let mut key_values = vec![];
-            let mut cursor = object_store.cursor().range(range)?.open().await?;
-
-            while let Some(key) = cursor.primary_key() {
-                let key = js_sys::Uint8Array::new(&key);
-                key_values.push((
-                    key.subarray(key_prefix.len() as u32, key.length()).to_vec(),
-                    js_sys::Uint8Array::new(
-                        &cursor
-                            .value()
-                            .expect("we should have a value because we have a key"),
-                    )
-                    .to_vec(),
-                ));
-                cursor.advance(1).await?;
-            }
+        let mut key_values = self
+            .with_object_store(|object_store| async move {
+                let mut key_values = vec![];
+                let mut cursor = object_store.cursor().range(range)?.open().await?;
+
+                while let Some(key) = cursor.primary_key() {
+                    let key = js_sys::Uint8Array::new(&key);
+                    key_values.push((
+                        key.subarray(key_prefix.len() as u32, key.length()).to_vec(),
+                        js_sys::Uint8Array::new(
+                            &cursor
+                                .value()
+                                .expect("we should have a value because we have a key"),
+                        )
+                        .to_vec(),
+                    ));
+                    cursor.advance(1).await?;
+                }
 
-            Ok(key_values)
-        })
-        .await?
+                Ok(key_values)
+            })
+            .await??;
+        if !expiring_key_values.is_empty() {
+            key_values.extend(expiring_key_values);
+            key_values.sort_unstable_by(|(key1, _), (key2, _)| key1.cmp(key2));
+            key_values.dedup_by(|(key1, _), (key2, _)| key1 == key2);
+        }
+        Ok(key_values)
     }
 }
 
@@ -189,6 +325,12 @@ impl WritableKeyValueStore for IndexedDbStore {
 
     async fn write_batch(&self, batch: Batch) -> Result<()> {
         let mut start_key = self.start_key.clone();
+        // Round up, so that the values are kept at least for the whole TTL.
+        let expiration = batch.ttl.map(|ttl| {
+            SystemTime::now()
+                .checked_add(ttl)
+                .map_or(u64::MAX, |time| unix_seconds(time).saturating_add(1))
+        });
         self.database
             .database
             .transaction(&[OBJECT_STORE_NAME])
@@ -198,26 +340,48 @@ impl WritableKeyValueStore for IndexedDbStore {
 
                 for ent in batch.operations {
                     match ent {
+                        // Each write removes the key from the other domain, so that it is
+                        // never in both.
                         WriteOperation::Put { key, value } => {
                             let key = [start_key.as_slice(), key.as_slice()].concat();
+                            let (key, value, other_key) = match expiration {
+                                Some(expiration) => {
+                                    let entry =
+                                        [&expiration.to_be_bytes()[..], &value[..]].concat();
+                                    (expiring_key(&key), entry, key)
+                                }
+                                None => {
+                                    let other_key = expiring_key(&key);
+                                    (key, value, other_key)
+                                }
+                            };
                             object_store
                                 .put_kv(
                                     &js_sys::Uint8Array::from(&key[..]),
                                     &js_sys::Uint8Array::from(&value[..]),
                                 )
                                 .await?;
+                            object_store
+                                .delete(&js_sys::Uint8Array::from(&other_key[..]))
+                                .await?;
                         }
                         WriteOperation::Delete { key } => {
                             let key = [start_key.as_slice(), key.as_slice()].concat();
                             object_store
                                 .delete(&js_sys::Uint8Array::from(&key[..]))
                                 .await?;
+                            object_store
+                                .delete(&js_sys::Uint8Array::from(&expiring_key(&key)[..]))
+                                .await?;
                         }
                         WriteOperation::DeletePrefix { key_prefix } => {
                             let key_prefix = [start_key.as_slice(), key_prefix.as_slice()].concat();
                             object_store
                                 .delete_range(prefix_to_range(&key_prefix[..]))
                                 .await?;
+                            object_store
+                                .delete_range(prefix_to_range(&expiring_key(&key_prefix)[..]))
+                                .await?;
                         }
                     }
                 }
@@ -251,7 +415,7 @@ impl KeyValueDatabase for IndexedDbDatabase {
     }
 
     async fn connect(_config: &Self::Config, namespace: &str) -> Result<Self> {
-        Ok(Self {
+        let database = Self {
             database: indexed_db::Factory::<Infallible>::get()?
                 .open(
                     &database_name(namespace),
@@ -267,7 +431,9 @@ impl KeyValueDatabase for IndexedDbDatabase {
                 .await?
                 .into(),
             namespace: namespace.to_string(),
-        })
+        };
+        database.remove_expired_values().await?;
+        Ok(database)
     }
 
     fn open_shared(&self, root_key: &[u8]) -> Result<Self::Store> {
@@ -245,6 +245,13 @@ where
         Ok(self.database.list_root_keys().await?)
     }
 
+    async fn read_multi_values_from_roots(
+        &self,
+        keys: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        Ok(self.database.read_multi_values_from_roots(keys).await?)
+    }
+
     async fn delete_all(config: &Self::Config) -> Result<(), Self::Error> {
         Ok(D::delete_all(config).await?)
     }
@@ -427,6 +427,14 @@ where
         self.database.list_root_keys().await
     }
 
+    async fn read_multi_values_from_roots(
+        &self,
+        keys: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        // Like reads from shared partitions, these reads bypass the cache.
+        self.database.read_multi_values_from_roots(keys).await
+    }
+
     async fn delete_all(config: &Self::Config) -> Result<(), Self::Error> {
         D::delete_all(&config.inner_config).await
     }
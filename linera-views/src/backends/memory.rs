@@ -2,12 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0
 
 //! Implements [`crate::store::KeyValueDatabase`] in memory.
+//!
+//! Values written with a time-to-live are removed by the first write to their partition
+//! after they expire.
 
 use std::{
     collections::BTreeMap,
     sync::{Arc, LazyLock, Mutex, RwLock},
 };
 
+use linera_base::time::Instant;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -31,7 +35,27 @@ pub struct MemoryStoreConfig {
 }
 
 /// The values in a partition.
-type MemoryStoreMap = BTreeMap<Vec<u8>, Vec<u8>>;
+#[derive(Default)]
+struct MemoryStoreMap {
+    /// The values, by key.
+    values: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// When the values written with a time-to-live expire.
+    expirations: BTreeMap<Vec<u8>, Instant>,
+}
+
+impl MemoryStoreMap {
+    /// Removes the values that expired before `now`.
+    fn remove_expired(&mut self, now: Instant) {
+        let values = &mut self.values;
+        self.expirations.retain(|key, expiration| {
+            let expired = *expiration <= now;
+            if expired {
+                values.remove(key);
+            }
+            !expired
+        });
+    }
+}
 
 /// The container for the `MemoryStoreMap`s by namespace and then root key
 #[derive(Default)]
@@ -58,7 +82,7 @@ impl MemoryDatabases {
             return Err(MemoryStoreError::NamespaceNotFound);
         };
         let store = stores.entry(root_key.to_vec()).or_insert_with(|| {
-            let map = MemoryStoreMap::new();
+            let map = MemoryStoreMap::default();
             Arc::new(RwLock::new(map))
         });
         let map = store.clone();
@@ -126,7 +150,7 @@ impl ReadableKeyValueStore for MemoryStore {
             .map
             .read()
             .expect("MemoryStore lock should not be poisoned");
-        Ok(map.get(key).cloned())
+        Ok(map.values.get(key).cloned())
     }
 
     async fn contains_key(&self, key: &[u8]) -> Result<bool, MemoryStoreError> {
@@ -134,7 +158,7 @@ impl ReadableKeyValueStore for MemoryStore {
             .map
             .read()
             .expect("MemoryStore lock should not be poisoned");
-        Ok(map.contains_key(key))
+        Ok(map.values.contains_key(key))
     }
 
     async fn contains_keys(&self, keys: &[Vec<u8>]) -> Result<Vec<bool>, MemoryStoreError> {
@@ -144,7 +168,7 @@ impl ReadableKeyValueStore for MemoryStore {
             .expect("MemoryStore lock should not be poisoned");
         Ok(keys
             .iter()
-            .map(|key| map.contains_key(key))
+            .map(|key| map.values.contains_key(key))
             .collect::<Vec<_>>())
     }
 
@@ -158,7 +182,7 @@ impl ReadableKeyValueStore for MemoryStore {
             .expect("MemoryStore lock should not be poisoned");
         let mut result = Vec::new();
         for key in keys {
-            result.push(map.get(key).cloned());
+            result.push(map.values.get(key).cloned());
         }
         Ok(result)
     }
@@ -173,7 +197,10 @@ impl ReadableKeyValueStore for MemoryStore {
             .expect("MemoryStore lock should not be poisoned");
         let mut values = Vec::new();
         let len = key_prefix.len();
-        for (key, _value) in map.range(get_key_range_for_prefix(key_prefix.to_vec())) {
+        for (key, _value) in map
+            .values
+            .range(get_key_range_for_prefix(key_prefix.to_vec()))
+        {
             values.push(key[len..].to_vec())
         }
         Ok(values)
@@ -189,7 +216,10 @@ impl ReadableKeyValueStore for MemoryStore {
             .expect("MemoryStore lock should not be poisoned");
         let mut key_values = Vec::new();
         let len = key_prefix.len();
-        for (key, value) in map.range(get_key_range_for_prefix(key_prefix.to_vec())) {
+        for (key, value) in map
+            .values
+            .range(get_key_range_for_prefix(key_prefix.to_vec()))
+        {
             let key_value = (key[len..].to_vec(), value.to_vec());
             key_values.push(key_value);
         }
@@ -205,21 +235,31 @@ impl WritableKeyValueStore for MemoryStore {
             .map
             .write()
             .expect("MemoryStore lock should not be poisoned");
+        let now = Instant::now();
+        map.remove_expired(now);
+        let expiration = batch.ttl.and_then(|ttl| now.checked_add(ttl));
         for ent in batch.operations {
             match ent {
                 WriteOperation::Put { key, value } => {
-                    map.insert(key, value);
+                    match expiration {
+                        Some(expiration) => map.expirations.insert(key.clone(), expiration),
+                        None => map.expirations.remove(&key),
+                    };
+                    map.values.insert(key, value);
                 }
                 WriteOperation::Delete { key } => {
-                    map.remove(&key);
+                    map.expirations.remove(&key);
+                    map.values.remove(&key);
                 }
                 WriteOperation::DeletePrefix { key_prefix } => {
                     let key_list = map
+                        .values
                         .range(get_key_range_for_prefix(key_prefix))
                         .map(|x| x.0.to_vec())
                         .collect::<Vec<_>>();
                     for key in key_list {
-                        map.remove(&key);
+                        map.expirations.remove(&key);
+                        map.values.remove(&key);
                     }
                 }
             }
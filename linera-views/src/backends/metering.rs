@@ -38,6 +38,8 @@ pub struct KeyValueStoreMetrics {
     open_exclusive_latency: HistogramVec,
     list_all_latency: HistogramVec,
     list_root_keys_latency: HistogramVec,
+    read_multi_values_from_roots_latency: HistogramVec,
+    read_multi_values_from_roots_num_entries: HistogramVec,
     delete_all_latency: HistogramVec,
     exists_latency: HistogramVec,
     create_latency: HistogramVec,
@@ -168,6 +170,16 @@ impl KeyValueStoreMetrics {
         let list_root_keys_latency =
             register_histogram_vec(&entry1, &entry2, &[], latency_buckets.clone());
 
+        let entry1 = format!("{var_name}_read_multi_values_from_roots_latency");
+        let entry2 = format!("{title_name} read multi values from roots latency");
+        let read_multi_values_from_roots_latency =
+            register_histogram_vec(&entry1, &entry2, &[], latency_buckets.clone());
+
+        let entry1 = format!("{var_name}_read_multi_values_from_roots_num_entries");
+        let entry2 = format!("{title_name} read multi values from roots num entries");
+        let read_multi_values_from_roots_num_entries =
+            register_histogram_vec(&entry1, &entry2, &[], count_buckets.clone());
+
         let entry1 = format!("{var_name}_delete_all_latency");
         let entry2 = format!("{title_name} delete all latency");
         let delete_all_latency =
@@ -280,6 +292,8 @@ impl KeyValueStoreMetrics {
             open_exclusive_latency,
             list_all_latency,
             list_root_keys_latency,
+            read_multi_values_from_roots_latency,
+            read_multi_values_from_roots_num_entries,
             delete_all_latency,
             exists_latency,
             create_latency,
@@ -536,6 +550,21 @@ where
         self.database.list_root_keys().await
     }
 
+    async fn read_multi_values_from_roots(
+        &self,
+        keys: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        let _latency = self
+            .counter
+            .read_multi_values_from_roots_latency
+            .measure_latency();
+        self.counter
+            .read_multi_values_from_roots_num_entries
+            .with_label_values(&[])
+            .observe(keys.len() as f64);
+        self.database.read_multi_values_from_roots(keys).await
+    }
+
     async fn delete_all(config: &Self::Config) -> Result<(), Self::Error> {
         let name = D::get_name();
         let counter = get_counter(&name);
@@ -12,8 +12,16 @@ pub mod memory;
 
 pub mod lru_caching;
 
+pub mod slow_log;
+
+pub mod encryption;
+
 pub mod dual;
 
+pub mod failover;
+
+pub mod write_aggregator;
+
 #[cfg(with_scylladb)]
 pub mod scylla_db;
 
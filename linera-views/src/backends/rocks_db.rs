@@ -12,17 +12,23 @@
 )]
 
 use std::{
-    ffi::OsString,
+    ffi::{CStr, OsString},
     fmt::Display,
     path::PathBuf,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
     },
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use linera_base::ensure;
-use rocksdb::{BlockBasedOptions, Cache, DBCompactionStyle, SliceTransform, WriteBufferManager};
+use rocksdb::{
+    compaction_filter::CompactionFilter,
+    compaction_filter_factory::{CompactionFilterContext, CompactionFilterFactory},
+    BlockBasedOptions, Cache, CompactionDecision, DBCompactionStyle, SliceTransform,
+    WriteBufferManager,
+};
 use serde::{Deserialize, Serialize};
 use sysinfo::{MemoryRefreshKind, RefreshKind, System};
 use tempfile::TempDir;
@@ -36,6 +42,7 @@ use crate::{
     batch::{Batch, WriteOperation},
     common::get_upper_bound_option,
     lru_caching::{LruCachingConfig, LruCachingDatabase},
+    slow_log::SlowLogDatabase,
     store::{
         KeyValueDatabase, KeyValueStoreError, ReadableKeyValueStore, WithError,
         WritableKeyValueStore,
@@ -46,6 +53,12 @@ use crate::{
 /// The prefixes being used in the system
 static ROOT_KEY_DOMAIN: [u8; 1] = [0];
 static STORED_ROOT_KEYS_PREFIX: u8 = 1;
+/// The domain of the values written with a TTL. Such a value is stored under its full key
+/// with the first byte replaced by this domain, prefixed with its expiration time in seconds
+/// since the Unix epoch (8 bytes, big-endian). A key is never present in both domains.
+static EXPIRING_KEY_DOMAIN: u8 = 2;
+/// The name of the compaction filter dropping the expired values.
+const EXPIRATION_FILTER_NAME: &CStr = c"linera-expiration";
 
 // The maximum size of values in RocksDB is 3 GiB
 // For offset reasons we decrease by 400
@@ -134,26 +147,113 @@ fn check_key_size(key: &[u8]) -> Result<(), RocksDbStoreInternalError> {
     Ok(())
 }
 
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Returns the key of the expiring key domain under which a value with the given full key
+/// is stored when it was written with a TTL.
+fn expiring_key(full_key: &[u8]) -> Vec<u8> {
+    let mut key = full_key.to_vec();
+    key[0] = EXPIRING_KEY_DOMAIN;
+    key
+}
+
+/// Returns the value of an entry of the expiring key domain, or `None` if it has expired.
+fn live_value(entry: &[u8], now: u64) -> Option<&[u8]> {
+    let (expiration, value) = entry.split_first_chunk::<8>()?;
+    (u64::from_be_bytes(*expiration) > now).then_some(value)
+}
+
+/// Creates the compaction filters dropping the expired values. The current time is read
+/// once per compaction, so filtering an entry needs no clock access or shared state.
+struct ExpirationFilterFactory;
+
+impl CompactionFilterFactory for ExpirationFilterFactory {
+    type Filter = ExpirationFilter;
+
+    fn create(&mut self, _context: CompactionFilterContext) -> ExpirationFilter {
+        ExpirationFilter {
+            now: unix_seconds(SystemTime::now()),
+        }
+    }
+
+    fn name(&self) -> &CStr {
+        EXPIRATION_FILTER_NAME
+    }
+}
+
+/// Drops the entries of the expiring key domain that expired before the compaction started.
+struct ExpirationFilter {
+    now: u64,
+}
+
+impl CompactionFilter for ExpirationFilter {
+    fn filter(&mut self, _level: u32, key: &[u8], value: &[u8]) -> CompactionDecision {
+        if key.first() == Some(&EXPIRING_KEY_DOMAIN) && live_value(value, self.now).is_none() {
+            CompactionDecision::Remove
+        } else {
+            CompactionDecision::Keep
+        }
+    }
+
+    fn name(&self) -> &CStr {
+        EXPIRATION_FILTER_NAME
+    }
+}
+
 #[derive(Clone)]
 struct RocksDbStoreExecutor {
     db: Arc<DB>,
     start_key: Vec<u8>,
+    /// Whether the database may contain values written with a TTL. Until then, reads and
+    /// writes skip the expiring key domain.
+    ttl_used: Arc<AtomicBool>,
 }
 
 impl RocksDbStoreExecutor {
+    fn may_have_expiring_values(&self) -> bool {
+        self.start_key.first() == ROOT_KEY_DOMAIN.first() && self.ttl_used.load(Ordering::Acquire)
+    }
+
+    fn full_keys(&self, keys: Vec<Vec<u8>>) -> Result<Vec<Vec<u8>>, RocksDbStoreInternalError> {
+        keys.into_iter()
+            .map(|key| {
+                check_key_size(&key)?;
+                let mut full_key = self.start_key.to_vec();
+                full_key.extend(key);
+                Ok(full_key)
+            })
+            .collect()
+    }
+
+    /// Reads the values that were written with a TTL under the given full keys, if they
+    /// have not expired yet.
+    fn read_expiring_values(
+        &self,
+        full_keys: &[&Vec<u8>],
+    ) -> Result<Vec<Option<Vec<u8>>>, RocksDbStoreInternalError> {
+        let now = unix_seconds(SystemTime::now());
+        let keys = full_keys.iter().map(|full_key| expiring_key(full_key));
+        self.db
+            .multi_get(keys)
+            .into_iter()
+            .map(|entry| Ok(entry?.and_then(|entry| live_value(&entry, now).map(<[u8]>::to_vec))))
+            .collect()
+    }
+
     fn contains_keys_internal(
         &self,
         keys: Vec<Vec<u8>>,
     ) -> Result<Vec<bool>, RocksDbStoreInternalError> {
-        let size = keys.len();
-        let mut results = vec![false; size];
+        let full_keys = self.full_keys(keys)?;
+        let mut results = vec![false; full_keys.len()];
         let mut indices = Vec::new();
         let mut keys_red = Vec::new();
-        for (i, key) in keys.into_iter().enumerate() {
-            check_key_size(&key)?;
-            let mut full_key = self.start_key.to_vec();
-            full_key.extend(key);
-            if self.db.key_may_exist(&full_key) {
+        for (i, full_key) in full_keys.iter().enumerate() {
+            if self.db.key_may_exist(full_key) {
                 indices.push(i);
                 keys_red.push(full_key);
             }
@@ -162,6 +262,17 @@ impl RocksDbStoreExecutor {
         for (index, value) in indices.into_iter().zip(values_red) {
             results[index] = value?.is_some();
         }
+        if self.may_have_expiring_values() {
+            let (indices, missing_keys): (Vec<_>, Vec<_>) = full_keys
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| !results[*i])
+                .unzip();
+            let values = self.read_expiring_values(&missing_keys)?;
+            for (index, value) in indices.into_iter().zip(values) {
+                results[index] = value.is_some();
+            }
+        }
         Ok(results)
     }
 
@@ -169,19 +280,21 @@ impl RocksDbStoreExecutor {
         &self,
         keys: Vec<Vec<u8>>,
     ) -> Result<Vec<Option<Vec<u8>>>, RocksDbStoreInternalError> {
-        for key in &keys {
-            check_key_size(key)?;
-        }
-        let full_keys = keys
-            .into_iter()
-            .map(|key| {
-                let mut full_key = self.start_key.to_vec();
-                full_key.extend(key);
-                full_key
-            })
-            .collect::<Vec<_>>();
+        let full_keys = self.full_keys(keys)?;
         let entries = self.db.multi_get(&full_keys);
-        Ok(entries.into_iter().collect::<Result<_, _>>()?)
+        let mut values = entries.into_iter().collect::<Result<Vec<_>, _>>()?;
+        if self.may_have_expiring_values() {
+            let (indices, missing_keys): (Vec<_>, Vec<_>) = full_keys
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| values[*i].is_none())
+                .unzip();
+            let expiring_values = self.read_expiring_values(&missing_keys)?;
+            for (index, value) in indices.into_iter().zip(expiring_values) {
+                values[index] = value;
+            }
+        }
+        Ok(values)
     }
 
     fn get_find_prefix_iterator(
@@ -220,6 +333,18 @@ impl RocksDbStoreExecutor {
             keys.push(key[len..].to_vec());
             iter.next();
         }
+        if self.may_have_expiring_values() {
+            let now = unix_seconds(SystemTime::now());
+            let mut iter = self.get_find_prefix_iterator(&expiring_key(&prefix));
+            while let Some((key, entry)) = iter.item() {
+                if live_value(entry, now).is_some() {
+                    keys.push(key[len..].to_vec());
+                }
+                iter.next();
+            }
+            keys.sort_unstable();
+            keys.dedup();
+        }
         Ok(keys)
     }
 
@@ -240,6 +365,18 @@ impl RocksDbStoreExecutor {
             key_values.push(key_value);
             iter.next();
         }
+        if self.may_have_expiring_values() {
+            let now = unix_seconds(SystemTime::now());
+            let mut iter = self.get_find_prefix_iterator(&expiring_key(&prefix));
+            while let Some((key, entry)) = iter.item() {
+                if let Some(value) = live_value(entry, now) {
+                    key_values.push((key[len..].to_vec(), value.to_vec()));
+                }
+                iter.next();
+            }
+            key_values.sort_unstable_by(|(key1, _), (key2, _)| key1.cmp(key2));
+            key_values.dedup_by(|(key1, _), (key2, _)| key1 == key2);
+        }
         Ok(key_values)
     }
 
@@ -248,6 +385,17 @@ impl RocksDbStoreExecutor {
         batch: Batch,
         write_root_key: bool,
     ) -> Result<(), RocksDbStoreInternalError> {
+        // Round up, so that the values are kept at least for the whole TTL.
+        let expiration = batch.ttl.map(|ttl| {
+            SystemTime::now()
+                .checked_add(ttl)
+                .map_or(u64::MAX, |time| unix_seconds(time).saturating_add(1))
+        });
+        if expiration.is_some() {
+            self.ttl_used.store(true, Ordering::Release);
+        }
+        // Each write removes the key from the other domain, so that it is never in both.
+        let may_expire = self.may_have_expiring_values();
         let mut inner_batch = rocksdb::WriteBatchWithTransaction::default();
         for operation in batch.operations {
             match operation {
@@ -255,13 +403,26 @@ impl RocksDbStoreExecutor {
                     check_key_size(&key)?;
                     let mut full_key = self.start_key.to_vec();
                     full_key.extend(key);
+                    if may_expire {
+                        inner_batch.delete(expiring_key(&full_key));
+                    }
                     inner_batch.delete(&full_key)
                 }
                 WriteOperation::Put { key, value } => {
                     check_key_size(&key)?;
                     let mut full_key = self.start_key.to_vec();
                     full_key.extend(key);
-                    inner_batch.put(&full_key, value)
+                    if let Some(expiration) = expiration {
+                        let mut entry = expiration.to_be_bytes().to_vec();
+                        entry.extend(value);
+                        inner_batch.put(expiring_key(&full_key), entry);
+                        inner_batch.delete(&full_key);
+                    } else {
+                        if may_expire {
+                            inner_batch.delete(expiring_key(&full_key));
+                        }
+                        inner_batch.put(&full_key, value)
+                    }
                 }
                 WriteOperation::DeletePrefix { key_prefix } => {
                     check_key_size(&key_prefix)?;
@@ -269,6 +430,12 @@ impl RocksDbStoreExecutor {
                     full_key1.extend(&key_prefix);
                     let full_key2 =
                         get_upper_bound_option(&full_key1).expect("the first entry cannot be 255");
+                    if may_expire {
+                        let expiring_key1 = expiring_key(&full_key1);
+                        let expiring_key2 = get_upper_bound_option(&expiring_key1)
+                            .expect("the first entry cannot be 255");
+                        inner_batch.delete_range(expiring_key1, expiring_key2);
+                    }
                     inner_batch.delete_range(&full_key1, &full_key2);
                 }
             }
@@ -281,6 +448,14 @@ impl RocksDbStoreExecutor {
         self.db.write(inner_batch)?;
         Ok(())
     }
+
+    /// Returns whether the database contains values written with a TTL.
+    fn contains_expiring_values(&self) -> Result<bool, RocksDbStoreInternalError> {
+        let mut iter = self.get_find_prefix_iterator(&[EXPIRING_KEY_DOMAIN]);
+        let found = iter.valid();
+        iter.status()?;
+        Ok(found)
+    }
 }
 
 /// The inner client
@@ -508,12 +683,22 @@ impl RocksDbStoreInternal {
             options.set_statistics_level(config.statistics_level.to_rocksdb());
         }
 
+        // Drop the values written with a TTL once they have expired.
+        options.set_compaction_filter_factory(ExpirationFilterFactory);
+
         let db = Arc::new(DB::open(&options, path_buf)?);
         #[cfg(with_metrics)]
         if config.enable_statistics {
             statistics_metrics::register(Arc::new(options), db.clone());
         }
-        let executor = RocksDbStoreExecutor { db, start_key };
+        let executor = RocksDbStoreExecutor {
+            db,
+            start_key,
+            ttl_used: Arc::default(),
+        };
+        if executor.contains_expiring_values()? {
+            executor.ttl_used.store(true, Ordering::Release);
+        }
         Ok(RocksDbStoreInternal {
             executor,
             path_with_guard,
@@ -791,26 +976,40 @@ impl ReadableKeyValueStore for RocksDbStoreInternal {
         key: &[u8],
     ) -> Result<Option<Vec<u8>>, RocksDbStoreInternalError> {
         check_key_size(key)?;
-        let db = self.executor.db.clone();
+        let executor = self.executor.clone();
         let mut full_key = self.executor.start_key.to_vec();
         full_key.extend(key);
         self.spawn_mode
-            .spawn(move |x| Ok(db.get(&x)?), full_key)
+            .spawn(
+                move |x| {
+                    if let Some(value) = executor.db.get(&x)? {
+                        return Ok(Some(value));
+                    }
+                    if !executor.may_have_expiring_values() {
+                        return Ok(None);
+                    }
+                    Ok(executor.read_expiring_values(&[&x])?.pop().flatten())
+                },
+                full_key,
+            )
             .await
     }
 
     async fn contains_key(&self, key: &[u8]) -> Result<bool, RocksDbStoreInternalError> {
         check_key_size(key)?;
-        let db = self.executor.db.clone();
+        let executor = self.executor.clone();
         let mut full_key = self.executor.start_key.to_vec();
         full_key.extend(key);
         self.spawn_mode
             .spawn(
                 move |x| {
-                    if !db.key_may_exist(&x) {
+                    if executor.db.key_may_exist(&x) && executor.db.get(&x)?.is_some() {
+                        return Ok(true);
+                    }
+                    if !executor.may_have_expiring_values() {
                         return Ok(false);
                     }
-                    Ok(db.get(&x)?.is_some())
+                    Ok(executor.read_expiring_values(&[&x])?[0].is_some())
                 },
                 full_key,
             )
@@ -1106,12 +1305,15 @@ pub type RocksDbStoreConfig = LruCachingConfig<RocksDbStoreInternalConfig>;
 #[cfg(with_metrics)]
 pub type RocksDbDatabase = MeteredDatabase<
     LruCachingDatabase<
-        MeteredDatabase<ValueSplittingDatabase<MeteredDatabase<RocksDbDatabaseInternal>>>,
+        MeteredDatabase<
+            ValueSplittingDatabase<MeteredDatabase<SlowLogDatabase<RocksDbDatabaseInternal>>>,
+        >,
     >,
 >;
 /// The `RocksDbDatabase` composed type
 #[cfg(not(with_metrics))]
-pub type RocksDbDatabase = LruCachingDatabase<ValueSplittingDatabase<RocksDbDatabaseInternal>>;
+pub type RocksDbDatabase =
+    LruCachingDatabase<ValueSplittingDatabase<SlowLogDatabase<RocksDbDatabaseInternal>>>;
 
 #[cfg(with_testing)]
 impl crate::backends::DatabaseBackup for RocksDbDatabaseInternal {
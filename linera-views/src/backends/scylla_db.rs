@@ -14,7 +14,7 @@ use std::{
         atomic::{AtomicI64, Ordering},
         Arc,
     },
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use async_lock::{Semaphore, SemaphoreGuard};
@@ -37,7 +37,7 @@ use scylla::{
     },
     response::PagingState,
     statement::{batch::BatchType, prepared::PreparedStatement, Consistency},
-    value::CqlValue,
+    value::{CqlValue, Row},
 };
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -48,14 +48,16 @@ use crate::metering::MeteredDatabase;
 use crate::store::TestKeyValueDatabase;
 use crate::{
     batch::{SimpleUnorderedBatch, UnorderedBatch},
-    common::{get_uleb128_size, get_upper_bound_option},
+    common::{get_uleb128_size, get_upper_bound_option, group_keys_by_root},
     journaling::{JournalingError, JournalingKeyValueDatabase},
     lru_caching::{LruCachingConfig, LruCachingDatabase},
+    slow_log::SlowLogDatabase,
     store::{
         DirectWritableKeyValueStore, KeyValueDatabase, KeyValueStoreError, ReadableKeyValueStore,
         WithError,
     },
     value_splitting::{ValueSplittingDatabase, ValueSplittingError},
+    write_aggregator::{AggregatedWrite, WriteAggregator, WriteAggregatorConfig},
 };
 
 /// Fundamental constant in ScyllaDB: The maximum size of a multi keys query
@@ -101,6 +103,69 @@ const MAX_BATCH_SIZE: usize = 5000;
 /// The keyspace to use for the ScyllaDB database.
 const KEYSPACE: &str = "kv";
 
+/// The table of the keyspace recording the schema version of each namespace.
+const SCHEMA_VERSIONS_TABLE: &str = "schema_versions";
+
+/// The table of the keyspace holding the locks of the namespaces being migrated.
+const SCHEMA_MIGRATION_LOCKS_TABLE: &str = "schema_migration_locks";
+
+/// How long a migration lock is held at most, so that a crashed migrator does not block
+/// the namespace forever.
+const SCHEMA_MIGRATION_LOCK_TTL: Duration = Duration::from_secs(300);
+
+/// How often a migrator waiting for the lock of a namespace retries to acquire it.
+const SCHEMA_MIGRATION_LOCK_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The options of the table of a namespace. Changing them requires a new entry in
+/// [`SCHEMA_MIGRATIONS`] so that existing tables are upgraded too.
+const TABLE_OPTIONS: &str = "compaction = { \
+        'class'          : 'LeveledCompactionStrategy', \
+        'sstable_size_in_mb' : 160 \
+    } \
+    AND compression = { \
+        'sstable_compression': 'LZ4Compressor', \
+        'chunk_length_in_kb':'4' \
+    } \
+    AND caching = { \
+        'enabled': 'true' \
+    } \
+    AND gc_grace_seconds = 0 \
+    AND tombstone_gc = {'mode': 'immediate'}";
+
+/// A change to the layout of the table of a namespace.
+struct SchemaMigration {
+    /// The schema version reached once the migration is applied.
+    version: u32,
+    /// What the migration changes, for the logs.
+    description: &'static str,
+    /// The CQL statements of the migration, where `{table}` stands for the table of the
+    /// namespace and `{options}` for [`TABLE_OPTIONS`]. They must be idempotent: a
+    /// migrator may stop after applying only some of them, and the next one starts over.
+    statements: &'static [&'static str],
+}
+
+/// The migrations of the table layout, by increasing version. Tables are always created
+/// with the latest layout, so the migrations only apply to existing tables.
+const SCHEMA_MIGRATIONS: &[SchemaMigration] = &[SchemaMigration {
+    version: 1,
+    description: "apply the current compaction, compression, caching and tombstone settings",
+    statements: &["ALTER TABLE {table} WITH {options}"],
+}];
+
+/// The schema version of the tables created by this code.
+const LATEST_SCHEMA_VERSION: u32 = SCHEMA_MIGRATIONS[SCHEMA_MIGRATIONS.len() - 1].version;
+
+/// The maximal time-to-live accepted by ScyllaDB, in seconds (20 years).
+const MAX_TTL_SECONDS: u64 = 630_720_000;
+
+/// Converts a batch's time-to-live into ScyllaDB's TTL, in whole seconds. Rounding up
+/// keeps the values at least for the requested duration.
+fn ttl_seconds(ttl: Duration) -> i32 {
+    let seconds = ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0);
+    // `MAX_TTL_SECONDS` fits in an `i32`.
+    i32::try_from(seconds.clamp(1, MAX_TTL_SECONDS)).unwrap_or(i32::MAX)
+}
+
 /// The client for ScyllaDB:
 /// * The session allows to pass queries
 /// * The namespace that is being assigned to the database
@@ -116,11 +181,14 @@ struct ScyllaDbClient {
     write_batch_deletion: PreparedStatement,
     write_batch_insertion: PreparedStatement,
     // Variants carrying an explicit `USING TIMESTAMP ?` marker, used by the
-    // single-batch exclusive-mode write path (`write_batch_exclusive`).
+    // single-batch exclusive-mode write path (`exclusive_batch`).
     write_batch_delete_prefix_unbounded_ts: PreparedStatement,
     write_batch_delete_prefix_bounded_ts: PreparedStatement,
     write_batch_deletion_ts: PreparedStatement,
     write_batch_insertion_ts: PreparedStatement,
+    // Insertions of values that expire, with a `USING TTL ?` marker.
+    write_batch_insertion_ttl: PreparedStatement,
+    write_batch_insertion_ts_ttl: PreparedStatement,
     find_keys_by_prefix_unbounded: PreparedStatement,
     find_keys_by_prefix_bounded: PreparedStatement,
     find_key_values_by_prefix_unbounded: PreparedStatement,
@@ -204,6 +272,20 @@ impl ScyllaDbClient {
             ))
             .await?;
 
+        let write_batch_insertion_ttl = session
+            .prepare(format!(
+                "INSERT INTO {KEYSPACE}.\"{namespace}\" (root_key, k, v) VALUES (?, ?, ?) \
+                 USING TTL ?"
+            ))
+            .await?;
+
+        let write_batch_insertion_ts_ttl = session
+            .prepare(format!(
+                "INSERT INTO {KEYSPACE}.\"{namespace}\" (root_key, k, v) VALUES (?, ?, ?) \
+                 USING TIMESTAMP ? AND TTL ?"
+            ))
+            .await?;
+
         let find_keys_by_prefix_unbounded = session
             .prepare(format!(
                 "SELECT k FROM {KEYSPACE}.\"{namespace}\" WHERE root_key = ? AND k >= ?"
@@ -242,6 +324,8 @@ impl ScyllaDbClient {
             write_batch_delete_prefix_bounded_ts,
             write_batch_deletion_ts,
             write_batch_insertion_ts,
+            write_batch_insertion_ttl,
+            write_batch_insertion_ts_ttl,
             find_keys_by_prefix_unbounded,
             find_keys_by_prefix_bounded,
             find_key_values_by_prefix_unbounded,
@@ -521,11 +605,13 @@ impl ScyllaDbClient {
     }
 
     /// Issues an unlogged batch containing the single-key deletions and the
-    /// insertions, letting the coordinator assign the write timestamp.
+    /// insertions, letting the coordinator assign the write timestamp. The insertions
+    /// expire after `ttl`, if any.
     async fn write_simple_batch(
         &self,
         root_key: &[u8],
         batch: SimpleUnorderedBatch,
+        ttl: Option<Duration>,
     ) -> Result<(), ScyllaDbStoreInternalError> {
         if batch.deletions.is_empty() && batch.insertions.is_empty() {
             return Ok(());
@@ -536,15 +622,25 @@ impl ScyllaDbClient {
         let q_deletion = &self.write_batch_deletion;
         for key in batch.deletions {
             Self::check_batch_key(&key)?;
-            batch_values.push(vec![root_key.to_vec(), key]);
+            batch_values.push(vec![CqlValue::Blob(root_key.to_vec()), CqlValue::Blob(key)]);
             batch_query.append_statement(q_deletion.clone());
         }
-        let q_insertion = &self.write_batch_insertion;
         for (key, value) in batch.insertions {
             Self::check_batch_key(&key)?;
             Self::check_value_size(&value)?;
-            batch_values.push(vec![root_key.to_vec(), key, value]);
-            batch_query.append_statement(q_insertion.clone());
+            let mut values = vec![
+                CqlValue::Blob(root_key.to_vec()),
+                CqlValue::Blob(key),
+                CqlValue::Blob(value),
+            ];
+            match ttl {
+                None => batch_query.append_statement(self.write_batch_insertion.clone()),
+                Some(ttl) => {
+                    values.push(CqlValue::Int(ttl_seconds(ttl)));
+                    batch_query.append_statement(self.write_batch_insertion_ttl.clone());
+                }
+            }
+            batch_values.push(values);
         }
         session
             .batch(&batch_query, batch_values)
@@ -553,10 +649,10 @@ impl ScyllaDbClient {
         Ok(())
     }
 
-    /// Issues the whole write as a single atomic unlogged batch, used in
-    /// exclusive mode. Every statement carries an explicit `USING TIMESTAMP`:
-    /// the prefix-deletions use `t`, while the single-key deletions, the
-    /// insertions, and the sentinel write use `t + 1`. The higher timestamp on
+    /// Builds the single atomic unlogged batch of the given writes, used in exclusive
+    /// mode. Every statement carries an explicit `USING TIMESTAMP`: the
+    /// prefix-deletions use the `T` of their write, while the single-key deletions,
+    /// the insertions, and the sentinel write use `T + 1`. The higher timestamp on
     /// the data ensures a range tombstone never shadows an insertion belonging
     /// to the same logical batch (at equal timestamps, dead cells win over live
     /// cells). Because the intended ordering is fixed by these timestamps rather
@@ -564,80 +660,106 @@ impl ScyllaDbClient {
     /// share one batch, preserving the atomicity that `write_batch` callers rely
     /// on. The sentinel write at `WRITETIME_SENTINEL_KEY` lets a future process
     /// recover this store's timestamp floor (see `ensure_ts_seeded`).
-    async fn write_batch_exclusive(
+    ///
+    /// The writes of several partitions may be combined by the [`WriteAggregator`]:
+    /// each of them is still atomic within its partition, and since the timestamps
+    /// are fixed, writing them again is idempotent.
+    fn exclusive_batch(
         &self,
-        root_key: &[u8],
-        batch: UnorderedBatch,
-        t: i64,
-    ) -> Result<(), ScyllaDbStoreInternalError> {
-        let UnorderedBatch {
-            key_prefix_deletions,
-            simple_unordered_batch:
-                SimpleUnorderedBatch {
-                    deletions,
-                    insertions,
-                },
-        } = batch;
-        let session = &self.session;
+        writes: &[&ExclusiveWrite],
+    ) -> Result<(scylla::statement::batch::Batch, Vec<Vec<CqlValue>>), ScyllaDbStoreInternalError>
+    {
         let mut batch_query = scylla::statement::batch::Batch::new(BatchType::Unlogged);
         let mut batch_values = Vec::new();
-
-        // Prefix-deletions at timestamp `t`.
-        for key_prefix in key_prefix_deletions {
-            Self::check_key_size(&key_prefix)?;
-            match get_upper_bound_option(&key_prefix) {
-                None => {
-                    batch_values.push(vec![
-                        CqlValue::BigInt(t),
-                        CqlValue::Blob(root_key.to_vec()),
-                        CqlValue::Blob(key_prefix),
-                    ]);
-                    batch_query
-                        .append_statement(self.write_batch_delete_prefix_unbounded_ts.clone());
-                }
-                Some(upper_bound) => {
-                    batch_values.push(vec![
-                        CqlValue::BigInt(t),
-                        CqlValue::Blob(root_key.to_vec()),
-                        CqlValue::Blob(key_prefix),
-                        CqlValue::Blob(upper_bound),
-                    ]);
-                    batch_query.append_statement(self.write_batch_delete_prefix_bounded_ts.clone());
+        for write in writes {
+            let ExclusiveWrite {
+                root_key,
+                batch:
+                    UnorderedBatch {
+                        key_prefix_deletions,
+                        simple_unordered_batch:
+                            SimpleUnorderedBatch {
+                                deletions,
+                                insertions,
+                            },
+                        ttl,
+                    },
+                timestamp: t,
+            } = write;
+
+            // Prefix-deletions at timestamp `t`.
+            for key_prefix in key_prefix_deletions {
+                Self::check_key_size(key_prefix)?;
+                match get_upper_bound_option(key_prefix) {
+                    None => {
+                        batch_values.push(vec![
+                            CqlValue::BigInt(*t),
+                            CqlValue::Blob(root_key.clone()),
+                            CqlValue::Blob(key_prefix.clone()),
+                        ]);
+                        batch_query
+                            .append_statement(self.write_batch_delete_prefix_unbounded_ts.clone());
+                    }
+                    Some(upper_bound) => {
+                        batch_values.push(vec![
+                            CqlValue::BigInt(*t),
+                            CqlValue::Blob(root_key.clone()),
+                            CqlValue::Blob(key_prefix.clone()),
+                            CqlValue::Blob(upper_bound),
+                        ]);
+                        batch_query
+                            .append_statement(self.write_batch_delete_prefix_bounded_ts.clone());
+                    }
                 }
             }
-        }
 
-        // Single-key deletions, insertions, and the sentinel at timestamp `t + 1`.
-        let t_data = t + 1;
-        for key in deletions {
-            Self::check_batch_key(&key)?;
+            // Single-key deletions, insertions, and the sentinel at timestamp `t + 1`.
+            let t_data = t + 1;
+            for key in deletions {
+                Self::check_batch_key(key)?;
+                batch_values.push(vec![
+                    CqlValue::BigInt(t_data),
+                    CqlValue::Blob(root_key.clone()),
+                    CqlValue::Blob(key.clone()),
+                ]);
+                batch_query.append_statement(self.write_batch_deletion_ts.clone());
+            }
+            for (key, value) in insertions {
+                Self::check_batch_key(key)?;
+                Self::check_value_size(value)?;
+                let mut values = vec![
+                    CqlValue::Blob(root_key.clone()),
+                    CqlValue::Blob(key.clone()),
+                    CqlValue::Blob(value.clone()),
+                    CqlValue::BigInt(t_data),
+                ];
+                match ttl {
+                    None => batch_query.append_statement(self.write_batch_insertion_ts.clone()),
+                    Some(ttl) => {
+                        values.push(CqlValue::Int(ttl_seconds(*ttl)));
+                        batch_query.append_statement(self.write_batch_insertion_ts_ttl.clone());
+                    }
+                }
+                batch_values.push(values);
+            }
             batch_values.push(vec![
-                CqlValue::BigInt(t_data),
-                CqlValue::Blob(root_key.to_vec()),
-                CqlValue::Blob(key),
-            ]);
-            batch_query.append_statement(self.write_batch_deletion_ts.clone());
-        }
-        for (key, value) in insertions {
-            Self::check_batch_key(&key)?;
-            Self::check_value_size(&value)?;
-            batch_values.push(vec![
-                CqlValue::Blob(root_key.to_vec()),
-                CqlValue::Blob(key),
-                CqlValue::Blob(value),
+                CqlValue::Blob(root_key.clone()),
+                CqlValue::Blob(WRITETIME_SENTINEL_KEY.to_vec()),
+                CqlValue::Blob(Vec::new()),
                 CqlValue::BigInt(t_data),
             ]);
             batch_query.append_statement(self.write_batch_insertion_ts.clone());
         }
-        batch_values.push(vec![
-            CqlValue::Blob(root_key.to_vec()),
-            CqlValue::Blob(WRITETIME_SENTINEL_KEY.to_vec()),
-            CqlValue::Blob(Vec::new()),
-            CqlValue::BigInt(t_data),
-        ]);
-        batch_query.append_statement(self.write_batch_insertion_ts.clone());
+        Ok((batch_query, batch_values))
+    }
 
-        session
+    /// Executes a batch built by `exclusive_batch`.
+    async fn execute_exclusive_batch(
+        &self,
+        batch_query: scylla::statement::batch::Batch,
+        batch_values: Vec<Vec<CqlValue>>,
+    ) -> Result<(), ScyllaDbStoreInternalError> {
+        self.session
             .batch(&batch_query, batch_values)
             .await
             .map_err(ScyllaDbStoreInternalError::WriteBatchExecutionError)?;
@@ -732,6 +854,8 @@ pub struct ScyllaDbStoreInternal {
     /// Value 0 means unseeded; populated lazily on first write by reading
     /// `WRITETIME` of a sentinel row. Each batch reserves 2 µs (T and T+1).
     ts_floor: Arc<AtomicI64>,
+    /// Combines the exclusive-mode writes of the stores of the database, if configured.
+    write_aggregator: Option<Arc<WriteAggregator<ExclusiveWrite>>>,
 }
 
 /// Database-level connection to ScyllaDB for managing namespaces and partitions.
@@ -739,6 +863,40 @@ pub struct ScyllaDbStoreInternal {
 pub struct ScyllaDbDatabaseInternal {
     store: Arc<ScyllaDbClient>,
     semaphore: Option<Arc<Semaphore>>,
+    write_aggregator: Option<Arc<WriteAggregator<ExclusiveWrite>>>,
+}
+
+/// An exclusive-mode write of a store, with the base timestamp `T` of its statements.
+struct ExclusiveWrite {
+    root_key: Vec<u8>,
+    batch: UnorderedBatch,
+    timestamp: i64,
+}
+
+impl AggregatedWrite for ExclusiveWrite {
+    fn len(&self) -> usize {
+        // The statements of the batch and the sentinel write.
+        self.batch.len() + 1
+    }
+
+    fn num_bytes(&self) -> usize {
+        let UnorderedBatch {
+            key_prefix_deletions,
+            simple_unordered_batch:
+                SimpleUnorderedBatch {
+                    deletions,
+                    insertions,
+                },
+            ttl: _,
+        } = &self.batch;
+        let keys = key_prefix_deletions.iter().chain(deletions);
+        let num_bytes = keys.map(Vec::len).sum::<usize>()
+            + insertions
+                .iter()
+                .map(|(key, value)| key.len() + value.len())
+                .sum::<usize>();
+        num_bytes + (self.len() * self.root_key.len())
+    }
 }
 
 impl WithError for ScyllaDbDatabaseInternal {
@@ -812,6 +970,42 @@ pub enum ScyllaDbStoreInternalError {
     /// timestamp sentinel).
     #[error("The key must be of nonzero length")]
     ZeroLengthKey,
+
+    /// The namespace was migrated by a newer version of the code.
+    #[error(
+        "The schema of namespace {namespace} has version {version}, but this code only \
+         supports versions up to {LATEST_SCHEMA_VERSION}"
+    )]
+    UnsupportedSchemaVersion {
+        /// The namespace.
+        namespace: String,
+        /// The schema version of the namespace.
+        version: u32,
+    },
+
+    /// A schema version cannot be represented in the schema versions table.
+    #[error("Invalid schema version {version} of namespace {namespace}")]
+    InvalidSchemaVersion {
+        /// The namespace.
+        namespace: String,
+        /// The schema version.
+        version: i64,
+    },
+
+    /// Schema migrations are pending but were not applied because of a dry run.
+    #[error(
+        "Dry run: schema migrations to versions {versions:?} of namespace {namespace} are pending"
+    )]
+    PendingSchemaMigrations {
+        /// The namespace.
+        namespace: String,
+        /// The versions of the pending migrations.
+        versions: Vec<u32>,
+    },
+
+    /// Another migrator kept holding the migration lock of the namespace.
+    #[error("Timed out waiting for the schema migration lock of namespace {0}")]
+    SchemaMigrationLockTimeout(String),
 }
 
 impl KeyValueStoreError for ScyllaDbStoreInternalError {
@@ -922,7 +1116,7 @@ impl DirectWritableKeyValueStore for ScyllaDbStoreInternal {
     // We therefore order the prefix-deletions strictly before the insertions:
     //   * In exclusive mode we own the timestamps, so we issue a single atomic CQL
     //     batch with explicit per-statement `USING TIMESTAMP` (`T` for the
-    //     prefix-deletions, `T + 1` for the data). See `write_batch_exclusive`.
+    //     prefix-deletions, `T + 1` for the data). See `exclusive_batch`.
     //   * In shared mode the coordinator owns the timestamps, so we split the write
     //     into two sequential CQL batches.
     type Batch = UnorderedBatch;
@@ -933,14 +1127,40 @@ impl DirectWritableKeyValueStore for ScyllaDbStoreInternal {
         ScyllaDbClient::check_batch_len(&batch)?;
         if self.is_exclusive {
             // A single atomic batch; ordering is pinned by the explicit timestamps.
-            let t = self.next_batch_ts().await?;
-            store.write_batch_exclusive(&self.root_key, batch, t).await
+            let write = ExclusiveWrite {
+                root_key: self.root_key.clone(),
+                batch,
+                timestamp: self.next_batch_ts().await?,
+            };
+            match &self.write_aggregator {
+                None => {
+                    let (batch_query, batch_values) = store.exclusive_batch(&[&write])?;
+                    store
+                        .execute_exclusive_batch(batch_query, batch_values)
+                        .await
+                }
+                Some(write_aggregator) => {
+                    // Do not hold a permit while waiting for the other writes.
+                    drop(_guard);
+                    let flush = |writes: Vec<&ExclusiveWrite>| {
+                        let batch = store.exclusive_batch(&writes);
+                        async move {
+                            let (batch_query, batch_values) = batch?;
+                            let _guard = self.acquire().await;
+                            store
+                                .execute_exclusive_batch(batch_query, batch_values)
+                                .await
+                        }
+                    };
+                    write_aggregator.write(write, flush).await
+                }
+            }
         } else {
             store
                 .write_batch_prefix_deletes(&self.root_key, batch.key_prefix_deletions)
                 .await?;
             store
-                .write_simple_batch(&self.root_key, batch.simple_unordered_batch)
+                .write_simple_batch(&self.root_key, batch.simple_unordered_batch, batch.ttl)
                 .await?;
             Ok(())
         }
@@ -1029,6 +1249,14 @@ pub struct ScyllaDbStoreInternalConfig {
     pub max_concurrent_queries: Option<usize>,
     /// The replication factor.
     pub replication_factor: u32,
+    /// Whether pending schema migrations are only logged, and connecting fails, instead
+    /// of applying them.
+    #[serde(default)]
+    pub schema_migration_dry_run: bool,
+    /// The maximal number of exclusive-mode writes of different partitions combined into
+    /// one batch. Writes are not combined if this is `None`.
+    #[serde(default)]
+    pub write_aggregation_max_writes: Option<usize>,
 }
 
 impl KeyValueDatabase for ScyllaDbDatabaseInternal {
@@ -1045,12 +1273,24 @@ impl KeyValueDatabase for ScyllaDbDatabaseInternal {
     ) -> Result<Self, ScyllaDbStoreInternalError> {
         Self::check_namespace(namespace)?;
         let session = ScyllaDbClient::build_default_session(&config.uri).await?;
+        Self::migrate_schema(&session, namespace, config.schema_migration_dry_run).await?;
         let store = ScyllaDbClient::new(session, namespace).await?;
         let store = Arc::new(store);
         let semaphore = config
             .max_concurrent_queries
             .map(|n| Arc::new(Semaphore::new(n)));
-        Ok(Self { store, semaphore })
+        let write_aggregator = config.write_aggregation_max_writes.map(|max_writes| {
+            Arc::new(WriteAggregator::new(WriteAggregatorConfig {
+                max_writes,
+                max_batch_size: MAX_BATCH_SIZE,
+                max_batch_total_size: MAX_BATCH_TOTAL_SIZE,
+            }))
+        });
+        Ok(Self {
+            store,
+            semaphore,
+            write_aggregator,
+        })
     }
 
     fn open_shared(&self, root_key: &[u8]) -> Result<Self::Store, ScyllaDbStoreInternalError> {
@@ -1063,6 +1303,7 @@ impl KeyValueDatabase for ScyllaDbDatabaseInternal {
             root_key,
             is_exclusive: false,
             ts_floor: Arc::new(AtomicI64::new(0)),
+            write_aggregator: None,
         })
     }
 
@@ -1076,6 +1317,7 @@ impl KeyValueDatabase for ScyllaDbDatabaseInternal {
             root_key,
             is_exclusive: true,
             ts_floor: Arc::new(AtomicI64::new(0)),
+            write_aggregator: self.write_aggregator.clone(),
         })
     }
 
@@ -1106,7 +1348,7 @@ impl KeyValueDatabase for ScyllaDbDatabaseInternal {
         let mut rows_stream = result.rows_stream::<(String, String, String, String)>()?;
         while let Some(row) = rows_stream.next().await {
             let (_, object_kind, name, _) = row?;
-            if object_kind == "table" {
+            if object_kind == "table" && !Self::is_reserved_table(&name) {
                 namespaces.push(name);
             }
         }
@@ -1135,6 +1377,37 @@ impl KeyValueDatabase for ScyllaDbDatabaseInternal {
         Ok(root_keys.into_iter().collect::<Vec<_>>())
     }
 
+    async fn read_multi_values_from_roots(
+        &self,
+        keys: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<Vec<Option<Vec<u8>>>, ScyllaDbStoreInternalError> {
+        let mut values = vec![None; keys.len()];
+        if keys.is_empty() {
+            return Ok(values);
+        }
+        // Each root key is a separate partition, so the partitions are queried
+        // concurrently, all under a single permit.
+        let groups = group_keys_by_root(keys)
+            .into_iter()
+            .map(|(root_key, group)| (get_big_root_key(&root_key), group))
+            .collect::<Vec<_>>();
+        let store = self.store.deref();
+        let _guard = self.acquire().await;
+        let handles = groups.iter().flat_map(|(root_key, (_, keys))| {
+            keys.chunks(MAX_MULTI_KEYS)
+                .map(|keys| store.read_multi_values_internal(root_key, keys.to_vec()))
+        });
+        let results = join_all(handles)
+            .await
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()?;
+        let positions = groups.iter().flat_map(|(_, (positions, _))| positions);
+        for (position, value) in positions.zip(results.into_iter().flatten()) {
+            values[*position] = value;
+        }
+        Ok(values)
+    }
+
     async fn delete_all(store_config: &Self::Config) -> Result<(), ScyllaDbStoreInternalError> {
         let session = ScyllaDbClient::build_default_session(&store_config.uri).await?;
         let statement = session
@@ -1224,25 +1497,17 @@ impl KeyValueDatabase for ScyllaDbDatabaseInternal {
                     v blob, \
                     PRIMARY KEY (root_key, k) \
                 ) \
-                WITH compaction = {{ \
-                    'class'          : 'LeveledCompactionStrategy', \
-                    'sstable_size_in_mb' : 160 \
-                }} \
-                AND compression = {{ \
-                    'sstable_compression': 'LZ4Compressor', \
-                    'chunk_length_in_kb':'4' \
-                }} \
-                AND caching = {{ \
-                    'enabled': 'true' \
-                }} \
-                AND gc_grace_seconds = 0 \
-                AND tombstone_gc = {{'mode': 'immediate'}}"
+                WITH {TABLE_OPTIONS}"
             ))
             .await?;
         session
             .execute_single_page(&statement, &[], PagingState::start())
             .await
             .map_err(ScyllaDbStoreInternalError::ExecutionError)?;
+
+        // The new table has the latest layout.
+        Self::create_schema_tables(&session).await?;
+        Self::write_schema_version(&session, namespace, LATEST_SCHEMA_VERSION).await?;
         Ok(())
     }
 
@@ -1274,17 +1539,268 @@ impl ScyllaDbStoreInternal {
 }
 
 impl ScyllaDbDatabaseInternal {
+    /// Obtains the semaphore lock on the database if needed.
+    async fn acquire(&self) -> Option<SemaphoreGuard<'_>> {
+        match &self.semaphore {
+            None => None,
+            Some(count) => Some(count.acquire().await),
+        }
+    }
+
     fn check_namespace(namespace: &str) -> Result<(), ScyllaDbStoreInternalError> {
         if !namespace.is_empty()
             && namespace.len() <= 48
             && namespace
                 .chars()
                 .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !Self::is_reserved_table(namespace)
         {
             return Ok(());
         }
         Err(ScyllaDbStoreInternalError::InvalidNamespace)
     }
+
+    /// Returns whether the table of the keyspace holds metadata rather than a namespace.
+    fn is_reserved_table(name: &str) -> bool {
+        name == SCHEMA_VERSIONS_TABLE || name == SCHEMA_MIGRATION_LOCKS_TABLE
+    }
+
+    async fn execute_statement(
+        session: &Session,
+        statement: String,
+    ) -> Result<(), ScyllaDbStoreInternalError> {
+        let statement = session.prepare(statement).await?;
+        session
+            .execute_single_page(&statement, &[], PagingState::start())
+            .await
+            .map_err(ScyllaDbStoreInternalError::ExecutionError)?;
+        Ok(())
+    }
+
+    /// Creates the tables recording the schema versions and the migration locks, if
+    /// needed.
+    async fn create_schema_tables(session: &Session) -> Result<(), ScyllaDbStoreInternalError> {
+        Self::execute_statement(
+            session,
+            format!(
+                "CREATE TABLE IF NOT EXISTS {KEYSPACE}.{SCHEMA_VERSIONS_TABLE} (\
+                    namespace text PRIMARY KEY, \
+                    version int \
+                )"
+            ),
+        )
+        .await?;
+        Self::execute_statement(
+            session,
+            format!(
+                "CREATE TABLE IF NOT EXISTS {KEYSPACE}.{SCHEMA_MIGRATION_LOCKS_TABLE} (\
+                    namespace text PRIMARY KEY, \
+                    owner text \
+                )"
+            ),
+        )
+        .await
+    }
+
+    /// Reads the schema version of the namespace. Namespaces created before the versions
+    /// were recorded have none.
+    async fn read_schema_version(
+        session: &Session,
+        namespace: &str,
+    ) -> Result<Option<u32>, ScyllaDbStoreInternalError> {
+        let statement = session
+            .prepare(format!(
+                "SELECT version FROM {KEYSPACE}.{SCHEMA_VERSIONS_TABLE} WHERE namespace = ?"
+            ))
+            .await?;
+        let (result, _) = session
+            .execute_single_page(&statement, (namespace,), PagingState::start())
+            .await
+            .map_err(ScyllaDbStoreInternalError::ExecutionError)?;
+        let rows = result.into_rows_result()?;
+        let mut rows = rows.rows::<(i32,)>()?;
+        let Some(row) = rows.next() else {
+            return Ok(None);
+        };
+        let version = row?.0;
+        let version = u32::try_from(version).map_err(|_| {
+            ScyllaDbStoreInternalError::InvalidSchemaVersion {
+                namespace: namespace.to_string(),
+                version: version.into(),
+            }
+        })?;
+        Ok(Some(version))
+    }
+
+    async fn write_schema_version(
+        session: &Session,
+        namespace: &str,
+        version: u32,
+    ) -> Result<(), ScyllaDbStoreInternalError> {
+        let statement = session
+            .prepare(format!(
+                "INSERT INTO {KEYSPACE}.{SCHEMA_VERSIONS_TABLE} (namespace, version) VALUES (?, ?)"
+            ))
+            .await?;
+        let version = i32::try_from(version).map_err(|_| {
+            ScyllaDbStoreInternalError::InvalidSchemaVersion {
+                namespace: namespace.to_string(),
+                version: version.into(),
+            }
+        })?;
+        session
+            .execute_single_page(&statement, (namespace, version), PagingState::start())
+            .await
+            .map_err(ScyllaDbStoreInternalError::ExecutionError)?;
+        Ok(())
+    }
+
+    /// Tries to acquire the migration lock of the namespace with a lightweight
+    /// transaction. Returns whether it was acquired.
+    async fn try_lock_schema(
+        session: &Session,
+        namespace: &str,
+        owner: &str,
+    ) -> Result<bool, ScyllaDbStoreInternalError> {
+        let statement = session
+            .prepare(format!(
+                "INSERT INTO {KEYSPACE}.{SCHEMA_MIGRATION_LOCKS_TABLE} (namespace, owner) \
+                 VALUES (?, ?) IF NOT EXISTS USING TTL {}",
+                SCHEMA_MIGRATION_LOCK_TTL.as_secs()
+            ))
+            .await?;
+        let (result, _) = session
+            .execute_single_page(&statement, (namespace, owner), PagingState::start())
+            .await
+            .map_err(ScyllaDbStoreInternalError::ExecutionError)?;
+        let rows = result.into_rows_result()?;
+        // The first column of the result of a lightweight transaction is `[applied]`.
+        let mut rows = rows.rows::<Row>()?;
+        Ok(match rows.next() {
+            Some(row) => matches!(row?.columns.first(), Some(Some(CqlValue::Boolean(true)))),
+            None => false,
+        })
+    }
+
+    /// Releases the migration lock of the namespace, if still held by `owner`.
+    async fn unlock_schema(
+        session: &Session,
+        namespace: &str,
+        owner: &str,
+    ) -> Result<(), ScyllaDbStoreInternalError> {
+        let statement = session
+            .prepare(format!(
+                "DELETE FROM {KEYSPACE}.{SCHEMA_MIGRATION_LOCKS_TABLE} WHERE namespace = ? \
+                 IF owner = ?"
+            ))
+            .await?;
+        session
+            .execute_single_page(&statement, (namespace, owner), PagingState::start())
+            .await
+            .map_err(ScyllaDbStoreInternalError::ExecutionError)?;
+        Ok(())
+    }
+
+    /// Upgrades the table of the namespace to the latest schema version.
+    ///
+    /// Concurrent migrators are serialized by a lock with a time-to-live. With `dry_run`,
+    /// the pending migrations are only logged, the table is left unchanged and an error
+    /// is returned.
+    async fn migrate_schema(
+        session: &Session,
+        namespace: &str,
+        dry_run: bool,
+    ) -> Result<(), ScyllaDbStoreInternalError> {
+        Self::create_schema_tables(session).await?;
+        let version = Self::read_schema_version(session, namespace).await?;
+        let pending = Self::pending_schema_migrations(namespace, version)?;
+        if pending.is_empty() {
+            return Ok(());
+        }
+        if dry_run {
+            let table = format!("{KEYSPACE}.\"{namespace}\"");
+            for migration in &pending {
+                tracing::info!(
+                    namespace,
+                    version = migration.version,
+                    "Pending schema migration: {}",
+                    migration.description
+                );
+                for statement in migration.statements {
+                    tracing::info!("  {}", Self::migration_statement(statement, &table));
+                }
+            }
+            return Err(ScyllaDbStoreInternalError::PendingSchemaMigrations {
+                namespace: namespace.to_string(),
+                versions: pending.iter().map(|migration| migration.version).collect(),
+            });
+        }
+
+        let owner = format!("{:016x}", rand::random::<u64>());
+        let mut waited = Duration::ZERO;
+        while !Self::try_lock_schema(session, namespace, &owner).await? {
+            if waited >= SCHEMA_MIGRATION_LOCK_TTL {
+                return Err(ScyllaDbStoreInternalError::SchemaMigrationLockTimeout(
+                    namespace.to_string(),
+                ));
+            }
+            tracing::info!(namespace, "Waiting for another schema migrator");
+            linera_base::time::timer::sleep(SCHEMA_MIGRATION_LOCK_RETRY_INTERVAL).await;
+            waited += SCHEMA_MIGRATION_LOCK_RETRY_INTERVAL;
+        }
+        let result = Self::apply_schema_migrations(session, namespace).await;
+        Self::unlock_schema(session, namespace, &owner).await?;
+        result
+    }
+
+    /// Applies the pending migrations of the namespace, while holding its lock.
+    async fn apply_schema_migrations(
+        session: &Session,
+        namespace: &str,
+    ) -> Result<(), ScyllaDbStoreInternalError> {
+        // Another migrator may have upgraded the table while we waited for the lock.
+        let version = Self::read_schema_version(session, namespace).await?;
+        let table = format!("{KEYSPACE}.\"{namespace}\"");
+        for migration in Self::pending_schema_migrations(namespace, version)? {
+            tracing::info!(
+                namespace,
+                version = migration.version,
+                "Applying schema migration: {}",
+                migration.description
+            );
+            for statement in migration.statements {
+                Self::execute_statement(session, Self::migration_statement(statement, &table))
+                    .await?;
+            }
+            Self::write_schema_version(session, namespace, migration.version).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the migrations to apply to a namespace with the given schema version.
+    fn pending_schema_migrations(
+        namespace: &str,
+        version: Option<u32>,
+    ) -> Result<Vec<&'static SchemaMigration>, ScyllaDbStoreInternalError> {
+        let version = version.unwrap_or(0);
+        ensure!(
+            version <= LATEST_SCHEMA_VERSION,
+            ScyllaDbStoreInternalError::UnsupportedSchemaVersion {
+                namespace: namespace.to_string(),
+                version,
+            }
+        );
+        Ok(SCHEMA_MIGRATIONS
+            .iter()
+            .filter(|migration| migration.version > version)
+            .collect())
+    }
+
+    fn migration_statement(statement: &str, table: &str) -> String {
+        statement
+            .replace("{table}", table)
+            .replace("{options}", TABLE_OPTIONS)
+    }
 }
 
 #[cfg(with_testing)]
@@ -1297,6 +1813,8 @@ impl TestKeyValueDatabase for JournalingKeyValueDatabase<ScyllaDbDatabaseInterna
             uri,
             max_concurrent_queries: Some(10),
             replication_factor: 1,
+            schema_migration_dry_run: false,
+            write_aggregation_max_writes: None,
         })
     }
 }
@@ -1307,7 +1825,9 @@ pub type ScyllaDbDatabase = MeteredDatabase<
     LruCachingDatabase<
         MeteredDatabase<
             ValueSplittingDatabase<
-                MeteredDatabase<JournalingKeyValueDatabase<ScyllaDbDatabaseInternal>>,
+                MeteredDatabase<
+                    SlowLogDatabase<JournalingKeyValueDatabase<ScyllaDbDatabaseInternal>>,
+                >,
             >,
         >,
     >,
@@ -1316,7 +1836,7 @@ pub type ScyllaDbDatabase = MeteredDatabase<
 /// The `ScyllaDbDatabase` composed type
 #[cfg(not(with_metrics))]
 pub type ScyllaDbDatabase = LruCachingDatabase<
-    ValueSplittingDatabase<JournalingKeyValueDatabase<ScyllaDbDatabaseInternal>>,
+    ValueSplittingDatabase<SlowLogDatabase<JournalingKeyValueDatabase<ScyllaDbDatabaseInternal>>>,
 >;
 
 /// The `ScyllaDbStoreConfig` input type
@@ -0,0 +1,375 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Logs the operations of a key-value store that take longer than a threshold.
+//!
+//! Each slow operation is logged as a `tracing` event with the target [`LOG_TARGET`] and
+//! the fields `operation`, `key_prefix_hash`, `duration_ms`, `backend` and, if the operation
+//! failed, `error`. With `RUST_LOG_FORMAT=json`, every event is a single JSON object, so
+//! that chain stalls can be correlated with the storage hot spots they hit.
+//!
+//! The log is off until [`set_slow_operation_threshold`] is called.
+
+use std::{
+    future::Future,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use linera_base::time::{Duration, Instant};
+use sha3::{Digest as _, Sha3_256};
+
+#[cfg(with_testing)]
+use crate::store::TestKeyValueDatabase;
+use crate::{
+    batch::{Batch, WriteOperation},
+    store::{
+        KeyValueDatabase, KeyValueStoreError, ReadableKeyValueStore, WithError,
+        WritableKeyValueStore,
+    },
+};
+
+/// The `tracing` target of the slow operation log.
+pub const LOG_TARGET: &str = "linera_views::slow_operations";
+
+/// The maximal number of key bytes taken into account in the key prefix hash.
+const MAX_KEY_PREFIX_LEN: usize = 16;
+
+/// The threshold in microseconds, or `u64::MAX` if the log is off.
+static THRESHOLD_MICROS: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// Sets the duration from which operations are logged, or turns the log off with `None`.
+pub fn set_slow_operation_threshold(threshold: Option<Duration>) {
+    let micros = threshold.map_or(u64::MAX, |threshold| {
+        u64::try_from(threshold.as_micros()).unwrap_or(u64::MAX)
+    });
+    THRESHOLD_MICROS.store(micros, Ordering::Relaxed);
+}
+
+/// Returns the duration from which operations are logged, if the log is on.
+pub fn slow_operation_threshold() -> Option<Duration> {
+    match THRESHOLD_MICROS.load(Ordering::Relaxed) {
+        u64::MAX => None,
+        micros => Some(Duration::from_micros(micros)),
+    }
+}
+
+/// Returns a short, stable hash of the partition and the first bytes of the key prefix, to
+/// group the log entries of the same hot spot without writing out the keys.
+fn key_prefix_hash(root_key: &[u8], key_prefix: &[u8]) -> String {
+    let key_prefix = &key_prefix[..key_prefix.len().min(MAX_KEY_PREFIX_LEN)];
+    let mut hasher = Sha3_256::new();
+    hasher.update((root_key.len() as u64).to_le_bytes());
+    hasher.update(root_key);
+    hasher.update(key_prefix);
+    let hash = hasher.finalize();
+    hash[..8].iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Returns the longest common prefix of the given keys.
+fn common_prefix<'a>(mut keys: impl Iterator<Item = &'a [u8]>) -> &'a [u8] {
+    let Some(mut prefix) = keys.next() else {
+        return &[];
+    };
+    for key in keys {
+        let len = prefix
+            .iter()
+            .zip(key)
+            .take_while(|(byte, other)| byte == other)
+            .count();
+        prefix = &prefix[..len];
+    }
+    prefix
+}
+
+/// Runs `future`, and logs it if it takes at least the threshold.
+async fn measure<T, E: KeyValueStoreError>(
+    operation: &'static str,
+    root_key: &[u8],
+    key_prefix: &[u8],
+    future: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let Some(threshold) = slow_operation_threshold() else {
+        return future.await;
+    };
+    let start = Instant::now();
+    let result = future.await;
+    let duration = start.elapsed();
+    if duration >= threshold {
+        tracing::warn!(
+            target: LOG_TARGET,
+            operation,
+            key_prefix_hash = key_prefix_hash(root_key, key_prefix),
+            duration_ms = duration.as_secs_f64() * 1000.0,
+            backend = E::BACKEND,
+            error = result.as_ref().err().map(tracing::field::display),
+            "Slow storage operation"
+        );
+    }
+    result
+}
+
+/// A database that logs its slow operations.
+#[derive(Clone)]
+pub struct SlowLogDatabase<D> {
+    /// The underlying database.
+    database: D,
+}
+
+/// A store that logs its slow operations.
+#[derive(Clone)]
+pub struct SlowLogStore<S> {
+    /// The root key of the partition, to tell the partitions apart in the log.
+    root_key: Vec<u8>,
+    /// The underlying store.
+    store: S,
+}
+
+impl<D> WithError for SlowLogDatabase<D>
+where
+    D: WithError,
+{
+    type Error = D::Error;
+}
+
+impl<S> WithError for SlowLogStore<S>
+where
+    S: WithError,
+{
+    type Error = S::Error;
+}
+
+impl<S> ReadableKeyValueStore for SlowLogStore<S>
+where
+    S: ReadableKeyValueStore,
+{
+    const MAX_KEY_SIZE: usize = S::MAX_KEY_SIZE;
+
+    fn root_key(&self) -> Result<Vec<u8>, Self::Error> {
+        self.store.root_key()
+    }
+
+    async fn read_value_bytes(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        let future = self.store.read_value_bytes(key);
+        measure("read_value_bytes", &self.root_key, key, future).await
+    }
+
+    async fn contains_key(&self, key: &[u8]) -> Result<bool, Self::Error> {
+        let future = self.store.contains_key(key);
+        measure("contains_key", &self.root_key, key, future).await
+    }
+
+    async fn contains_keys(&self, keys: &[Vec<u8>]) -> Result<Vec<bool>, Self::Error> {
+        let key_prefix = common_prefix(keys.iter().map(Vec::as_slice));
+        let future = self.store.contains_keys(keys);
+        measure("contains_keys", &self.root_key, key_prefix, future).await
+    }
+
+    async fn read_multi_values_bytes(
+        &self,
+        keys: &[Vec<u8>],
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        let key_prefix = common_prefix(keys.iter().map(Vec::as_slice));
+        let future = self.store.read_multi_values_bytes(keys);
+        measure(
+            "read_multi_values_bytes",
+            &self.root_key,
+            key_prefix,
+            future,
+        )
+        .await
+    }
+
+    async fn find_keys_by_prefix(&self, key_prefix: &[u8]) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let future = self.store.find_keys_by_prefix(key_prefix);
+        measure("find_keys_by_prefix", &self.root_key, key_prefix, future).await
+    }
+
+    async fn find_key_values_by_prefix(
+        &self,
+        key_prefix: &[u8],
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Self::Error> {
+        let future = self.store.find_key_values_by_prefix(key_prefix);
+        measure(
+            "find_key_values_by_prefix",
+            &self.root_key,
+            key_prefix,
+            future,
+        )
+        .await
+    }
+}
+
+impl<S> WritableKeyValueStore for SlowLogStore<S>
+where
+    S: WritableKeyValueStore,
+{
+    const MAX_VALUE_SIZE: usize = S::MAX_VALUE_SIZE;
+
+    async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
+        let key_prefix = common_prefix(batch.operations.iter().map(|operation| match operation {
+            WriteOperation::Delete { key } | WriteOperation::Put { key, .. } => key.as_slice(),
+            WriteOperation::DeletePrefix { key_prefix } => key_prefix.as_slice(),
+        }))
+        .to_vec();
+        let future = self.store.write_batch(batch);
+        measure("write_batch", &self.root_key, &key_prefix, future).await
+    }
+
+    async fn clear_journal(&self) -> Result<(), Self::Error> {
+        let future = self.store.clear_journal();
+        measure("clear_journal", &self.root_key, &[], future).await
+    }
+}
+
+impl<D> KeyValueDatabase for SlowLogDatabase<D>
+where
+    D: KeyValueDatabase,
+{
+    type Config = D::Config;
+    type Store = SlowLogStore<D::Store>;
+
+    fn get_name() -> String {
+        D::get_name()
+    }
+
+    async fn connect(config: &Self::Config, namespace: &str) -> Result<Self, Self::Error> {
+        let future = D::connect(config, namespace);
+        let database = measure("connect", &[], namespace.as_bytes(), future).await?;
+        Ok(Self { database })
+    }
+
+    fn open_shared(&self, root_key: &[u8]) -> Result<Self::Store, Self::Error> {
+        let store = self.database.open_shared(root_key)?;
+        let root_key = root_key.to_vec();
+        Ok(SlowLogStore { root_key, store })
+    }
+
+    fn open_exclusive(&self, root_key: &[u8]) -> Result<Self::Store, Self::Error> {
+        let store = self.database.open_exclusive(root_key)?;
+        let root_key = root_key.to_vec();
+        Ok(SlowLogStore { root_key, store })
+    }
+
+    async fn list_all(config: &Self::Config) -> Result<Vec<String>, Self::Error> {
+        D::list_all(config).await
+    }
+
+    async fn list_root_keys(&self) -> Result<Vec<Vec<u8>>, Self::Error> {
+        let future = self.database.list_root_keys();
+        measure("list_root_keys", &[], &[], future).await
+    }
+
+    async fn read_multi_values_from_roots(
+        &self,
+        keys: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        let root_key = common_prefix(keys.iter().map(|(root_key, _)| root_key.as_slice())).to_vec();
+        let key_prefix = common_prefix(keys.iter().map(|(_, key)| key.as_slice())).to_vec();
+        let future = self.database.read_multi_values_from_roots(keys);
+        measure(
+            "read_multi_values_from_roots",
+            &root_key,
+            &key_prefix,
+            future,
+        )
+        .await
+    }
+
+    async fn delete_all(config: &Self::Config) -> Result<(), Self::Error> {
+        D::delete_all(config).await
+    }
+
+    async fn exists(config: &Self::Config, namespace: &str) -> Result<bool, Self::Error> {
+        D::exists(config, namespace).await
+    }
+
+    async fn create(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        D::create(config, namespace).await
+    }
+
+    async fn delete(config: &Self::Config, namespace: &str) -> Result<(), Self::Error> {
+        D::delete(config, namespace).await
+    }
+}
+
+#[cfg(with_testing)]
+impl<D> TestKeyValueDatabase for SlowLogDatabase<D>
+where
+    D: TestKeyValueDatabase,
+{
+    async fn new_test_config() -> Result<D::Config, Self::Error> {
+        D::new_test_config().await
+    }
+}
+
+#[cfg(with_testing)]
+impl<D: crate::backends::DatabaseBackup> crate::backends::DatabaseBackup for SlowLogDatabase<D> {
+    fn backup_to(&self, dir: &std::path::Path) -> anyhow::Result<()> {
+        self.database.backup_to(dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::time::Duration;
+    use linera_views::{
+        batch::Batch,
+        memory::{MemoryDatabase, MemoryStoreConfig},
+        random::generate_test_namespace,
+        slow_log::{set_slow_operation_threshold, SlowLogDatabase},
+        store::{KeyValueDatabase as _, ReadableKeyValueStore as _, WritableKeyValueStore as _},
+    };
+
+    use super::{common_prefix, key_prefix_hash};
+
+    #[test]
+    fn test_key_prefix_hash() {
+        let hash = key_prefix_hash(&[1], b"prefix");
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, key_prefix_hash(&[1], b"prefix"));
+        assert_ne!(hash, key_prefix_hash(&[2], b"prefix"));
+        // The root key and the prefix are not confused.
+        assert_ne!(
+            key_prefix_hash(&[1, 2], &[3]),
+            key_prefix_hash(&[1], &[2, 3])
+        );
+        // Keys with a long common prefix share their hash.
+        assert_eq!(
+            key_prefix_hash(&[1], &[7; 40]),
+            key_prefix_hash(&[1], &[7; 20])
+        );
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        let keys: [&[u8]; 3] = [b"abcd", b"abce", b"abx"];
+        assert_eq!(common_prefix(keys.into_iter()), b"ab");
+        assert_eq!(common_prefix(std::iter::empty()), b"");
+        assert_eq!(common_prefix([b"key".as_slice()].into_iter()), b"key");
+    }
+
+    #[tokio::test]
+    async fn test_slow_log_store_passes_operations_through() {
+        // Log every operation, so that the logging path is exercised.
+        set_slow_operation_threshold(Some(Duration::ZERO));
+        let config = MemoryStoreConfig {
+            kill_on_drop: false,
+        };
+        let namespace = generate_test_namespace();
+        let database = SlowLogDatabase::<MemoryDatabase>::recreate_and_connect(&config, &namespace)
+            .await
+            .unwrap();
+        let store = database.open_exclusive(&[3]).unwrap();
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(b"key1".to_vec(), b"value1".to_vec());
+        batch.put_key_value_bytes(b"key2".to_vec(), b"value2".to_vec());
+        store.write_batch(batch).await.unwrap();
+        assert_eq!(
+            store.read_value_bytes(b"key1").await.unwrap(),
+            Some(b"value1".to_vec())
+        );
+        let keys = store.find_keys_by_prefix(b"key").await.unwrap();
+        assert_eq!(keys, vec![b"1".to_vec(), b"2".to_vec()]);
+        set_slow_operation_threshold(None);
+    }
+}
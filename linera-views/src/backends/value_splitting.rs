@@ -3,7 +3,9 @@
 
 //! Adds support for large values to a given store by splitting them between several keys.
 
+use futures::{stream, StreamExt as _, TryStreamExt as _};
 use linera_base::ensure;
+use sha2::{Digest as _, Sha256};
 use thiserror::Error;
 
 use crate::{
@@ -19,6 +21,23 @@ use crate::{
     store::TestKeyValueDatabase,
 };
 
+/// The flag set on the segment count of values whose header also records the length and
+/// the checksum of the full value. Values written in a single segment do not carry it.
+const CHECKED_VALUE_FLAG: u32 = 1 << 31;
+
+/// The size of the header of a checked value: the flagged segment count, the length of
+/// the full value as a `u64` and its checksum.
+const CHECKED_HEADER_SIZE: usize = 4 + 8 + CHECKSUM_SIZE;
+
+/// The size of the checksum of a checked value: a prefix of its SHA-256 hash.
+const CHECKSUM_SIZE: usize = 4;
+
+/// The number of segments requested from the underlying store in a single read.
+const SEGMENTS_PER_READ: usize = 16;
+
+/// The maximum number of concurrent segment reads for a single call.
+const MAX_CONCURRENT_SEGMENT_READS: usize = 8;
+
 /// A key-value database with no size limit for values.
 ///
 /// It wraps a key-value store, potentially _with_ a size limit, and automatically
@@ -56,6 +75,10 @@ pub enum ValueSplittingError<E> {
     /// No count of size `u32` is available in the value
     #[error("no count of size u32 is available in the value")]
     NoCountAvailable,
+
+    /// The reassembled value does not match the length or checksum in its header
+    #[error("the reassembled value does not match the length or checksum in its header")]
+    CorruptedValue,
 }
 
 impl<E: KeyValueStoreError> From<bcs::Error> for ValueSplittingError<E> {
@@ -110,27 +133,22 @@ where
         let Some(value) = value else {
             return Ok(None);
         };
-        let count = Self::read_count_from_value(&value)?;
-        let mut big_value = value[4..].to_vec();
-        if count == 1 {
+        let header = ValueHeader::read(&value)?;
+        let mut big_value = value[header.size..].to_vec();
+        if header.count == 1 {
+            header.check(&big_value)?;
             return Ok(Some(big_value));
         }
         let mut big_keys = Vec::new();
-        for i in 1..count {
+        for i in 1..header.count {
             let big_key_segment = Self::get_segment_key(key, i)?;
             big_keys.push(big_key_segment);
         }
-        let segments = self.store.read_multi_values_bytes(&big_keys).await?;
+        let segments = self.read_segments(&big_keys).await?;
         for segment in segments {
-            match segment {
-                None => {
-                    return Err(ValueSplittingError::MissingSegment);
-                }
-                Some(segment) => {
-                    big_value.extend(segment);
-                }
-            }
+            big_value.extend(segment.ok_or(ValueSplittingError::MissingSegment)?);
         }
+        header.check(&big_value)?;
         Ok(Some(big_value))
     }
 
@@ -165,38 +183,36 @@ where
         let values = self.store.read_multi_values_bytes(&big_keys).await?;
         let mut big_values = Vec::<Option<Vec<u8>>>::new();
         let mut keys_add = Vec::new();
-        let mut n_blocks = Vec::new();
+        let mut headers = Vec::new();
         for (key, value) in keys.iter().zip(values) {
             match value {
                 None => {
-                    n_blocks.push(0);
+                    headers.push(None);
                     big_values.push(None);
                 }
                 Some(value) => {
-                    let count = Self::read_count_from_value(&value)?;
-                    let big_value = value[4..].to_vec();
-                    for i in 1..count {
+                    let header = ValueHeader::read(&value)?;
+                    let big_value = value[header.size..].to_vec();
+                    for i in 1..header.count {
                         let big_key_segment = Self::get_segment_key(key, i)?;
                         keys_add.push(big_key_segment);
                     }
-                    n_blocks.push(count);
+                    headers.push(Some(header));
                     big_values.push(Some(big_value));
                 }
             }
         }
-        if !keys_add.is_empty() {
-            let mut segments = self
-                .store
-                .read_multi_values_bytes(&keys_add)
-                .await?
-                .into_iter();
-            for (big_value, count) in big_values.iter_mut().zip(&n_blocks) {
-                if let Some(value) = big_value {
-                    for _ in 1..*count {
-                        let segment = segments.next().unwrap().unwrap();
-                        value.extend(segment);
-                    }
+        let mut segments = self.read_segments(&keys_add).await?.into_iter();
+        for (big_value, header) in big_values.iter_mut().zip(&headers) {
+            if let (Some(value), Some(header)) = (big_value, header) {
+                for _ in 1..header.count {
+                    let segment = segments
+                        .next()
+                        .flatten()
+                        .ok_or(ValueSplittingError::MissingSegment)?;
+                    value.extend(segment);
                 }
+                header.check(value)?;
             }
         }
         Ok(big_values)
@@ -227,9 +243,9 @@ where
             }
             big_key.truncate(big_key.len() - 4);
             let key = big_key;
-            let count = Self::read_count_from_value(&value)?;
-            let mut big_value = value[4..].to_vec();
-            for idx in 1..count {
+            let header = ValueHeader::read(&value)?;
+            let mut big_value = value[header.size..].to_vec();
+            for idx in 1..header.count {
                 let (big_key, value) = small_kv_iterator
                     .next()
                     .ok_or(ValueSplittingError::MissingSegment)?;
@@ -241,6 +257,7 @@ where
                 );
                 big_value.extend(value);
             }
+            header.check(&big_value)?;
             key_values.push((key, big_value));
         }
         Ok(key_values)
@@ -255,7 +272,11 @@ where
     const MAX_VALUE_SIZE: usize = usize::MAX;
 
     async fn write_batch(&self, batch: Batch) -> Result<(), Self::Error> {
-        let mut batch_new = Batch::new();
+        // All the segments of a value expire together.
+        let mut batch_new = Batch {
+            ttl: batch.ttl,
+            ..Batch::new()
+        };
         for operation in batch.operations {
             match operation {
                 WriteOperation::Delete { key } => {
@@ -269,13 +290,20 @@ where
                     let value_ext = if value.len() <= K::MAX_VALUE_SIZE - 4 {
                         Self::get_initial_count_first_chunk(count, &value)?
                     } else {
-                        let remainder = value.split_off(K::MAX_VALUE_SIZE - 4);
+                        let length = value.len() as u64;
+                        let checksum = checksum(&value);
+                        let remainder = value.split_off(K::MAX_VALUE_SIZE - CHECKED_HEADER_SIZE);
                         for value_chunk in remainder.chunks(K::MAX_VALUE_SIZE) {
                             let big_key_segment = Self::get_segment_key(&key, count)?;
                             batch_new.put_key_value_bytes(big_key_segment, value_chunk.to_vec());
                             count += 1;
                         }
-                        Self::get_initial_count_first_chunk(count, &value)?
+                        let mut value_ext = Vec::with_capacity(CHECKED_HEADER_SIZE + value.len());
+                        value_ext.extend((count | CHECKED_VALUE_FLAG).to_be_bytes());
+                        value_ext.extend(length.to_be_bytes());
+                        value_ext.extend(checksum);
+                        value_ext.extend(value);
+                        value_ext
                     };
                     batch_new.put_key_value_bytes(big_key, value_ext);
                 }
@@ -328,6 +356,64 @@ where
         Ok(self.database.list_root_keys().await?)
     }
 
+    async fn read_multi_values_from_roots(
+        &self,
+        keys: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> Result<Vec<Option<Vec<u8>>>, Self::Error> {
+        let big_keys = keys
+            .iter()
+            .map(|(root_key, key)| {
+                let big_key = ValueSplittingStore::<D::Store>::get_segment_key(key, 0)?;
+                Ok((root_key.clone(), big_key))
+            })
+            .collect::<Result<Vec<_>, Self::Error>>()?;
+        let values = self.database.read_multi_values_from_roots(big_keys).await?;
+        let mut big_values = Vec::<Option<Vec<u8>>>::new();
+        let mut keys_add = Vec::new();
+        let mut headers = Vec::new();
+        for ((root_key, key), value) in keys.iter().zip(values) {
+            match value {
+                None => {
+                    headers.push(None);
+                    big_values.push(None);
+                }
+                Some(value) => {
+                    let header = ValueHeader::read(&value)?;
+                    let big_value = value[header.size..].to_vec();
+                    for i in 1..header.count {
+                        let big_key_segment =
+                            ValueSplittingStore::<D::Store>::get_segment_key(key, i)?;
+                        keys_add.push((root_key.clone(), big_key_segment));
+                    }
+                    headers.push(Some(header));
+                    big_values.push(Some(big_value));
+                }
+            }
+        }
+        // The remaining segments of big values are read in a second round-trip.
+        let mut segments = if keys_add.is_empty() {
+            Vec::new().into_iter()
+        } else {
+            self.database
+                .read_multi_values_from_roots(keys_add)
+                .await?
+                .into_iter()
+        };
+        for (big_value, header) in big_values.iter_mut().zip(&headers) {
+            if let (Some(value), Some(header)) = (big_value, header) {
+                for _ in 1..header.count {
+                    let segment = segments
+                        .next()
+                        .flatten()
+                        .ok_or(ValueSplittingError::MissingSegment)?;
+                    value.extend(segment);
+                }
+                header.check(value)?;
+            }
+        }
+        Ok(big_values)
+    }
+
     async fn delete_all(config: &Self::Config) -> Result<(), Self::Error> {
         Ok(D::delete_all(config).await?)
     }
@@ -365,6 +451,84 @@ impl<D: crate::backends::DatabaseBackup> crate::backends::DatabaseBackup
     }
 }
 
+impl<S> ValueSplittingStore<S>
+where
+    S: ReadableKeyValueStore,
+    S::Error: 'static,
+{
+    /// Reads the given segments from the underlying store, in batches of
+    /// [`SEGMENTS_PER_READ`] with up to [`MAX_CONCURRENT_SEGMENT_READS`] batches in flight.
+    async fn read_segments(
+        &self,
+        keys: &[Vec<u8>],
+    ) -> Result<Vec<Option<Vec<u8>>>, ValueSplittingError<S::Error>> {
+        let batches = stream::iter(keys.chunks(SEGMENTS_PER_READ))
+            .map(|keys| self.store.read_multi_values_bytes(keys))
+            .buffered(MAX_CONCURRENT_SEGMENT_READS)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(batches.into_iter().flatten().collect())
+    }
+}
+
+/// The header at the start of the first segment of a value.
+struct ValueHeader {
+    /// The number of segments of the value.
+    count: u32,
+    /// The length and checksum of the full value, if recorded.
+    expected: Option<(u64, [u8; CHECKSUM_SIZE])>,
+    /// The size of the header in bytes.
+    size: usize,
+}
+
+impl ValueHeader {
+    /// Reads the header at the start of the first segment of a value.
+    fn read<E>(value: &[u8]) -> Result<Self, ValueSplittingError<E>> {
+        let count_bytes = value
+            .get(0..4)
+            .ok_or(ValueSplittingError::NoCountAvailable)?;
+        let count = u32::from_be_bytes(count_bytes.try_into().expect("slice has length 4"));
+        if count & CHECKED_VALUE_FLAG == 0 {
+            return Ok(ValueHeader {
+                count,
+                expected: None,
+                size: 4,
+            });
+        }
+        let header = value
+            .get(4..CHECKED_HEADER_SIZE)
+            .ok_or(ValueSplittingError::CorruptedValue)?;
+        let (length, checksum) = header.split_at(8);
+        Ok(ValueHeader {
+            count: count & !CHECKED_VALUE_FLAG,
+            expected: Some((
+                u64::from_be_bytes(length.try_into().expect("slice has length 8")),
+                checksum.try_into().expect("slice has the checksum size"),
+            )),
+            size: CHECKED_HEADER_SIZE,
+        })
+    }
+
+    /// Checks the reassembled value against the length and checksum of the header.
+    fn check<E>(&self, value: &[u8]) -> Result<(), ValueSplittingError<E>> {
+        if let Some((length, expected_checksum)) = &self.expected {
+            ensure!(
+                value.len() as u64 == *length && checksum(value) == *expected_checksum,
+                ValueSplittingError::CorruptedValue
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Computes the checksum stored in the header of checked values.
+fn checksum(value: &[u8]) -> [u8; CHECKSUM_SIZE] {
+    let hash = Sha256::digest(value);
+    hash[..CHECKSUM_SIZE]
+        .try_into()
+        .expect("SHA-256 hashes are longer than the checksum")
+}
+
 impl<D> ValueSplittingStore<D>
 where
     D: WithError,
@@ -394,15 +558,6 @@ where
         Ok(value_ext)
     }
 
-    fn read_count_from_value(value: &[u8]) -> Result<u32, ValueSplittingError<D::Error>> {
-        if value.len() < 4 {
-            return Err(ValueSplittingError::NoCountAvailable);
-        }
-        let mut bytes = value[0..4].to_vec();
-        bytes.reverse();
-        Ok(bcs::from_bytes::<u32>(&bytes)?)
-    }
-
     fn read_index_from_key(key: &[u8]) -> Result<u32, ValueSplittingError<D::Error>> {
         let len = key.len();
         if len < 4 {
@@ -514,10 +669,12 @@ mod tests {
     use linera_views::{
         batch::Batch,
         store::{ReadableKeyValueStore, WritableKeyValueStore},
-        value_splitting::{LimitedTestMemoryStore, ValueSplittingStore},
+        value_splitting::{LimitedTestMemoryStore, ValueSplittingError, ValueSplittingStore},
     };
     use rand::Rng;
 
+    use super::CHECKED_HEADER_SIZE;
+
     // The key splitting means that when a key is overwritten
     // some previous segments may still be present.
     #[tokio::test]
@@ -556,7 +713,7 @@ mod tests {
         let mut batch = Batch::new();
         let mut value = Vec::new();
         let mut rng = crate::random::make_deterministic_rng();
-        for _ in 0..2 * MAX_LEN - 4 {
+        for _ in 0..2 * MAX_LEN - CHECKED_HEADER_SIZE {
             value.push(rng.gen::<u8>());
         }
         batch.put_key_value_bytes(key.clone(), value.clone());
@@ -577,7 +734,7 @@ mod tests {
                 )
             };
             if index == 0 {
-                value_concat.extend(&value_read[4..]);
+                value_concat.extend(&value_read[CHECKED_HEADER_SIZE..]);
             } else {
                 value_concat.extend(&value_read);
             }
@@ -595,7 +752,7 @@ mod tests {
         let mut batch = Batch::new();
         let mut value = Vec::new();
         let mut rng = crate::random::make_deterministic_rng();
-        for _ in 0..3 * MAX_LEN - 4 {
+        for _ in 0..3 * MAX_LEN - CHECKED_HEADER_SIZE {
             value.push(rng.gen::<u8>());
         }
         batch.put_key_value_bytes(key.clone(), value.clone());
@@ -611,4 +768,82 @@ mod tests {
         let keys = store.find_keys_by_prefix(&[0]).await.unwrap();
         assert_eq!(keys, vec![vec![0, 0, 0, 0, 1], vec![0, 0, 0, 0, 2]]);
     }
+
+    #[tokio::test]
+    async fn test_value_splitting4_many_segments() {
+        let store = LimitedTestMemoryStore::new();
+        const MAX_LEN: usize = LimitedTestMemoryStore::MAX_VALUE_SIZE;
+        let big_store = ValueSplittingStore::new(store);
+        let mut rng = crate::random::make_deterministic_rng();
+        // Enough segments to need several concurrent batches of reads.
+        let values = (0..3)
+            .map(|i| {
+                (0..(40 + i) * MAX_LEN)
+                    .map(|_| rng.gen::<u8>())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let keys = (0..3u8).map(|i| vec![0, i]).collect::<Vec<_>>();
+        let mut batch = Batch::new();
+        for (key, value) in keys.iter().zip(&values) {
+            batch.put_key_value_bytes(key.clone(), value.clone());
+        }
+        big_store.write_batch(batch).await.unwrap();
+        let value_read = big_store.read_value_bytes(&keys[0]).await.unwrap();
+        assert_eq!(value_read.as_ref(), Some(&values[0]));
+        let values_read = big_store.read_multi_values_bytes(&keys).await.unwrap();
+        assert_eq!(
+            values_read,
+            values.into_iter().map(Some).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_value_splitting5_corruption_is_detected() {
+        let store = LimitedTestMemoryStore::new();
+        const MAX_LEN: usize = LimitedTestMemoryStore::MAX_VALUE_SIZE;
+        let big_store = ValueSplittingStore::new(store.clone());
+        let key = vec![0, 0];
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(key.clone(), vec![1; 3 * MAX_LEN]);
+        big_store.write_batch(batch).await.unwrap();
+        // Overwrite the second segment with different bytes.
+        let mut batch = Batch::new();
+        batch.put_key_value_bytes(vec![0, 0, 0, 0, 0, 2], vec![2; MAX_LEN]);
+        store.write_batch(batch).await.unwrap();
+        assert!(matches!(
+            big_store.read_value_bytes(&key).await,
+            Err(ValueSplittingError::CorruptedValue)
+        ));
+        assert!(matches!(
+            big_store.find_key_values_by_prefix(&[0]).await,
+            Err(ValueSplittingError::CorruptedValue)
+        ));
+        // Remove the segment altogether.
+        let mut batch = Batch::new();
+        batch.delete_key(vec![0, 0, 0, 0, 0, 2]);
+        store.write_batch(batch).await.unwrap();
+        assert!(matches!(
+            big_store.read_multi_values_bytes(&[key]).await,
+            Err(ValueSplittingError::MissingSegment)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_value_splitting6_reads_unchecked_values() {
+        let store = LimitedTestMemoryStore::new();
+        const MAX_LEN: usize = LimitedTestMemoryStore::MAX_VALUE_SIZE;
+        let big_store = ValueSplittingStore::new(store.clone());
+        // A value split in two segments with only a segment count in its header, as
+        // written by earlier versions.
+        let value = vec![3; 2 * MAX_LEN - 4];
+        let mut batch = Batch::new();
+        let mut first_segment = 2u32.to_be_bytes().to_vec();
+        first_segment.extend(&value[..MAX_LEN - 4]);
+        batch.put_key_value_bytes(vec![0, 0, 0, 0, 0, 0], first_segment);
+        batch.put_key_value_bytes(vec![0, 0, 0, 0, 0, 1], value[MAX_LEN - 4..].to_vec());
+        store.write_batch(batch).await.unwrap();
+        let value_read = big_store.read_value_bytes(&[0, 0]).await.unwrap();
+        assert_eq!(value_read, Some(value));
+    }
 }
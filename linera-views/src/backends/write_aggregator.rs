@@ -0,0 +1,234 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Combines the writes of several partitions into fewer requests to the database.
+//!
+//! Each partition, e.g. the state of a chain, is written by its own store, and on
+//! high-latency backends every write costs a round-trip. A [`WriteAggregator`] is shared
+//! by the stores of a database: while a combined write is in flight, the writes submitted
+//! by other stores are queued, and the next one of them to run writes all the queued
+//! writes together, within the limits of a single database batch. Under low load, writes
+//! are not delayed.
+//!
+//! Every write is still applied as a whole or not at all within its partition. If a
+//! combined write fails, each of its writes is retried on its own, so that the error is
+//! reported to the store that caused it.
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// The limits of the writes combined by a [`WriteAggregator`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WriteAggregatorConfig {
+    /// The maximal number of writes combined into one request.
+    pub max_writes: usize,
+    /// The maximal number of operations in a combined write.
+    pub max_batch_size: usize,
+    /// The maximal number of bytes of a combined write.
+    pub max_batch_total_size: usize,
+}
+
+/// A write that can be combined with others by a [`WriteAggregator`].
+pub trait AggregatedWrite {
+    /// The number of operations of the write.
+    fn len(&self) -> usize;
+
+    /// The number of bytes of the write.
+    fn num_bytes(&self) -> usize;
+}
+
+/// A write submitted to the aggregator, and whether it was written.
+struct Entry<W> {
+    write: W,
+    /// `None` until a combined write including this one completes, then whether it
+    /// succeeded.
+    outcome: Mutex<Option<bool>>,
+}
+
+/// Combines the writes submitted concurrently by several stores into fewer requests.
+pub struct WriteAggregator<W> {
+    config: WriteAggregatorConfig,
+    /// The writes waiting for the combined write in flight to complete.
+    queue: Mutex<VecDeque<Arc<Entry<W>>>>,
+    /// Held while a combined write is in flight.
+    flush_lock: async_lock::Mutex<()>,
+}
+
+impl<W: AggregatedWrite> WriteAggregator<W> {
+    /// Creates an aggregator with the given limits.
+    pub fn new(config: WriteAggregatorConfig) -> Self {
+        Self {
+            config,
+            queue: Mutex::new(VecDeque::new()),
+            flush_lock: async_lock::Mutex::new(()),
+        }
+    }
+
+    /// Returns the limits of the combined writes.
+    pub fn config(&self) -> WriteAggregatorConfig {
+        self.config
+    }
+
+    /// Writes `write` with `flush`, possibly together with the writes submitted
+    /// concurrently by other stores, and returns once it is written.
+    ///
+    /// `flush` must write all the given writes in a single request, and must be
+    /// idempotent: a write is retried on its own if the combined write fails.
+    pub async fn write<F, Fut, E>(&self, write: W, flush: F) -> Result<(), E>
+    where
+        F: Fn(Vec<&W>) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+    {
+        let entry = Arc::new(Entry {
+            write,
+            outcome: Mutex::new(None),
+        });
+        self.queue.lock().unwrap().push_back(entry.clone());
+        let guard = self.flush_lock.lock().await;
+        let outcome = *entry.outcome.lock().unwrap();
+        let written = match outcome {
+            Some(written) => written,
+            None => {
+                // Our write was not handled by a previous combined write, so we write it
+                // now, together with the queued ones. If the future of an earlier
+                // combined write was dropped, our entry may no longer be in the queue.
+                let group = self.take_group(&entry);
+                let writes = group.iter().map(|entry| &entry.write).collect();
+                let written = flush(writes).await.is_ok();
+                for member in &group {
+                    *member.outcome.lock().unwrap() = Some(written);
+                }
+                written
+            }
+        };
+        drop(guard);
+        if written {
+            Ok(())
+        } else {
+            flush(vec![&entry.write]).await
+        }
+    }
+
+    /// Removes `entry` and as many of the queued writes as fit within the limits from
+    /// the queue, in order, and returns them, starting with `entry`.
+    fn take_group(&self, entry: &Arc<Entry<W>>) -> Vec<Arc<Entry<W>>> {
+        let mut queue = self.queue.lock().unwrap();
+        queue.retain(|queued| !Arc::ptr_eq(queued, entry));
+        let mut batch_size = entry.write.len();
+        let mut batch_total_size = entry.write.num_bytes();
+        let mut group = vec![entry.clone()];
+        while let Some(next) = queue.front() {
+            batch_size += next.write.len();
+            batch_total_size += next.write.num_bytes();
+            if group.len() == self.config.max_writes
+                || batch_size > self.config.max_batch_size
+                || batch_total_size > self.config.max_batch_total_size
+            {
+                break;
+            }
+            group.extend(queue.pop_front());
+        }
+        group
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures::future::join_all;
+
+    use super::{AggregatedWrite, WriteAggregator, WriteAggregatorConfig};
+
+    struct TestWrite {
+        id: usize,
+        len: usize,
+        fails_when_combined: bool,
+    }
+
+    impl AggregatedWrite for TestWrite {
+        fn len(&self) -> usize {
+            self.len
+        }
+
+        fn num_bytes(&self) -> usize {
+            10 * self.len
+        }
+    }
+
+    fn write(id: usize, len: usize) -> TestWrite {
+        TestWrite {
+            id,
+            len,
+            fails_when_combined: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writes_are_combined_within_limits() {
+        let aggregator = WriteAggregator::new(WriteAggregatorConfig {
+            max_writes: 3,
+            max_batch_size: 5,
+            max_batch_total_size: 1000,
+        });
+        let requests = Mutex::new(Vec::<Vec<usize>>::new());
+        let flush = |writes: Vec<&TestWrite>| {
+            requests
+                .lock()
+                .unwrap()
+                .push(writes.iter().map(|write| write.id).collect());
+            async {
+                // Let the other writes be submitted while this one is in flight.
+                tokio::task::yield_now().await;
+                Ok::<(), ()>(())
+            }
+        };
+        let writes = [1, 1, 1, 1, 4, 1]
+            .into_iter()
+            .enumerate()
+            .map(|(id, len)| aggregator.write(write(id, len), flush));
+        let results = join_all(writes).await;
+        assert!(results.iter().all(Result::is_ok));
+
+        let requests = requests.into_inner().unwrap();
+        // The first write is alone in flight while the others are queued, then at most
+        // three writes and five operations are combined.
+        assert_eq!(requests, [vec![0], vec![1, 2, 3], vec![4, 5]]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_combined_write_is_retried_per_write() {
+        let aggregator = WriteAggregator::new(WriteAggregatorConfig {
+            max_writes: 10,
+            max_batch_size: 10,
+            max_batch_total_size: 1000,
+        });
+        let flush = |writes: Vec<&TestWrite>| {
+            let fails = (writes.len() > 1 && writes.iter().any(|write| write.fails_when_combined))
+                || writes.iter().any(|write| write.id == 2);
+            async move {
+                tokio::task::yield_now().await;
+                if fails {
+                    Err(())
+                } else {
+                    Ok(())
+                }
+            }
+        };
+        let mut failing = write(1, 1);
+        failing.fails_when_combined = true;
+        let results = join_all([
+            aggregator.write(write(0, 1), flush),
+            aggregator.write(failing, flush),
+            aggregator.write(write(2, 1), flush),
+            aggregator.write(write(3, 1), flush),
+        ])
+        .await;
+        assert_eq!(results, [Ok(()), Ok(()), Err(()), Ok(())]);
+    }
+}
@@ -11,6 +11,13 @@
 //! one by one. The only purpose of the batch is to write some transactions into the
 //! database.
 //!
+//! A batch may also be given a time-to-live (TTL), for data that only needs a short
+//! retention. The values it inserts are then kept at least until the TTL has elapsed, and
+//! may be removed at any time afterwards. ScyllaDB expires them natively, RocksDB drops them
+//! in a compaction filter, IndexedDB when the database is opened, and the memory store on
+//! the next write; RocksDB and IndexedDB also skip expired values when reading. Batches too
+//! large to be written at once go through a journal, which does not record the TTL.
+//!
 //! Note that normal users should not have to manipulate batches. The functionality
 //! is public because some other libraries require it. But the users using views should
 //! not have to deal with batches.
@@ -25,7 +32,7 @@ use std::{
 
 use bcs::serialized_size;
 use custom_debug_derive::Debug;
-use linera_base::hex_debug;
+use linera_base::{hex_debug, time::Duration};
 use linera_witty::{WitLoad, WitStore, WitType};
 use serde::{Deserialize, Serialize};
 
@@ -69,6 +76,8 @@ pub enum WriteOperation {
 pub struct Batch {
     /// The write operations.
     pub operations: Vec<WriteOperation>,
+    /// The time-to-live of the values inserted by this batch, if they may expire.
+    pub ttl: Option<Duration>,
 }
 
 /// A batch of deletions and insertions that operate on disjoint keys, thus can be
@@ -94,6 +103,10 @@ pub struct UnorderedBatch {
     pub key_prefix_deletions: Vec<Vec<u8>>,
     /// The batch of deletions and insertions.
     pub simple_unordered_batch: SimpleUnorderedBatch,
+    /// The time-to-live of the insertions, if they may expire. This is not persisted in
+    /// journals: values written through a journal do not expire.
+    #[serde(skip)]
+    pub ttl: Option<Duration>,
 }
 
 impl UnorderedBatch {
@@ -160,6 +173,21 @@ impl Batch {
         Self::default()
     }
 
+    /// Creates an empty batch whose values are kept at least for the given time-to-live, and
+    /// may be removed afterwards.
+    /// ```rust
+    /// # use linera_views::batch::Batch;
+    /// # use std::time::Duration;
+    /// let mut batch = Batch::with_ttl(Duration::from_secs(60));
+    /// batch.put_key_value_bytes(vec![0, 1], vec![3, 4, 5]);
+    /// ```
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            operations: Vec::new(),
+            ttl: Some(ttl),
+        }
+    }
+
     /// The total size of the batch
     pub fn size(&self) -> usize {
         self.operations
@@ -268,6 +296,7 @@ impl Batch {
         UnorderedBatch {
             key_prefix_deletions,
             simple_unordered_batch,
+            ttl: self.ttl,
         }
     }
 
@@ -4,7 +4,7 @@
 //! This provides some common code for the linera-views.
 
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     ops::{
         Bound,
         Bound::{Excluded, Included, Unbounded},
@@ -203,6 +203,21 @@ pub(crate) fn insert_key_prefix(prefixes: &mut BTreeSet<Vec<u8>>, prefix: Vec<u8
     }
 }
 
+/// The keys of a read spanning several root keys, grouped by root key, together with
+/// their positions in the original list.
+pub(crate) type KeysByRoot = BTreeMap<Vec<u8>, (Vec<usize>, Vec<Vec<u8>>)>;
+
+/// Groups a list of `(root_key, key)` pairs by root key.
+pub(crate) fn group_keys_by_root(keys: Vec<(Vec<u8>, Vec<u8>)>) -> KeysByRoot {
+    let mut groups = KeysByRoot::new();
+    for (position, (root_key, key)) in keys.into_iter().enumerate() {
+        let (positions, keys) = groups.entry(root_key).or_default();
+        positions.push(position);
+        keys.push(key);
+    }
+    groups
+}
+
 #[test]
 fn suffix_closed_set_test1_the_lower_bound() {
     let mut set = BTreeSet::<Vec<u8>>::new();
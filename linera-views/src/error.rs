@@ -22,6 +22,10 @@ pub enum ViewError {
     )]
     TryLockError(Vec<u8>),
 
+    /// The deadline of the request the storage operation was done for has passed.
+    #[error(transparent)]
+    DeadlineExceeded(#[from] linera_base::deadline::DeadlineExceeded),
+
     /// Tokio errors can happen while joining.
     #[error("panic in sub-task: {0}")]
     TokioJoinError(#[from] tokio::task::JoinError),
@@ -66,6 +70,15 @@ pub enum ViewError {
     /// The canonical byte stream is malformed.
     #[error("malformed canonical content stream: {0}")]
     MalformedContent(&'static str),
+
+    /// The stored state was written by a newer schema than the one of the code.
+    #[error("stored schema version {stored} is newer than the supported version {supported}")]
+    UnsupportedSchemaVersion {
+        /// The schema version of the stored state.
+        stored: u32,
+        /// The latest schema version known to the code.
+        supported: u32,
+    },
 }
 
 impl ViewError {
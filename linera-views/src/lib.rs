@@ -82,6 +82,9 @@ pub use error::ViewError;
 /// Elementary data-structures implementing the [`views::View`] trait.
 pub mod views;
 
+/// Versioned schemas for root views, and the migrations between them.
+pub mod migration;
+
 /// Backend implementing the [`crate::store::KeyValueStore`] trait.
 pub mod backends;
 
@@ -109,7 +112,9 @@ pub use backends::metering;
 pub use backends::rocks_db;
 #[cfg(with_scylladb)]
 pub use backends::scylla_db;
-pub use backends::{journaling, lru_caching, memory, value_splitting};
+pub use backends::{
+    encryption, journaling, lru_caching, memory, slow_log, value_splitting, write_aggregator,
+};
 /// Re-exports used by the derive macros of this library.
 #[doc(hidden)]
 #[allow(deprecated)]
@@ -117,7 +122,7 @@ pub use generic_array;
 #[doc(hidden)]
 pub use sha3;
 pub use views::{
-    bucket_queue_view, collection_view, hashable_wrapper, historical_hash_wrapper,
-    key_value_store_view, lazy_register_view, log_view, map_view, queue_view,
-    reentrant_collection_view, register_view, set_view,
+    bucket_queue_view, collection_view, delta_register_view, hashable_wrapper,
+    historical_hash_wrapper, key_value_store_view, lazy_register_view, log_view, map_view,
+    queue_view, reentrant_collection_view, register_view, set_view,
 };
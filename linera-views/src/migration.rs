@@ -0,0 +1,168 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Versioned schemas for root views.
+//!
+//! A root view implementing [`VersionedView`] declares the version of its schema. Its stored
+//! state records the version it was written with, and loading the view with
+//! [`VersionedView::load_migrated`] first runs the migrations from that version to the
+//! current one.
+//!
+//! Migrations run in steps. Each step returns a batch of changes and, unless the migration
+//! is complete, a cursor to resume from. The batch is written atomically with the progress
+//! of the migration, so large states can be migrated in bounded steps, and an interrupted
+//! migration resumes where it stopped.
+
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+use static_assertions as sa;
+
+use crate::{
+    batch::Batch,
+    context::{BaseKey, Context},
+    store::{ReadableKeyValueStore as _, WritableKeyValueStore as _},
+    views::{RootView, MIN_VIEW_TAG},
+    ViewError,
+};
+
+/// The tag of the key storing the schema state, right after the base key of the view.
+const SCHEMA_TAG: u8 = 0;
+// The fields of views only use tags of at least `MIN_VIEW_TAG`. Journals use the same tag
+// at the root, but only with longer keys.
+sa::const_assert!(SCHEMA_TAG < MIN_VIEW_TAG);
+
+/// The schema version of a stored view, and the progress of its migration to the next
+/// version.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SchemaState {
+    /// The schema version of the stored state. States stored without a version have
+    /// version 0.
+    pub version: u32,
+    /// Where the migration to the next version resumes, if it is under way.
+    pub cursor: Option<Vec<u8>>,
+}
+
+/// The outcome of one step of a migration.
+#[derive(Debug, Default)]
+pub struct MigrationStep {
+    /// The changes made by this step.
+    pub batch: Batch,
+    /// Where the next step resumes, or `None` if the migration is complete.
+    pub cursor: Option<Vec<u8>>,
+}
+
+impl MigrationStep {
+    /// Completes the migration with the given changes.
+    pub fn done(batch: Batch) -> Self {
+        Self {
+            batch,
+            cursor: None,
+        }
+    }
+
+    /// Makes the given changes and resumes the migration at `cursor`.
+    pub fn resume_at(batch: Batch, cursor: Vec<u8>) -> Self {
+        Self {
+            batch,
+            cursor: Some(cursor),
+        }
+    }
+}
+
+/// Returns the key storing the schema state of the view with the given base key.
+fn schema_key(base_key: &BaseKey) -> Vec<u8> {
+    base_key.base_index(&[SCHEMA_TAG])
+}
+
+/// Reads the schema state of the view stored at the context's base key.
+pub async fn read_schema_state<C: Context>(context: &C) -> Result<SchemaState, ViewError> {
+    let key = schema_key(context.base_key());
+    Ok(context.store().read_value(&key).await?.unwrap_or_default())
+}
+
+/// A root view whose schema is versioned.
+#[cfg_attr(not(web), trait_variant::make(Send))]
+pub trait VersionedView: RootView {
+    /// The current schema version. Versions start at 0, the version of states stored
+    /// before they were versioned.
+    const SCHEMA_VERSION: u32;
+
+    /// Runs a step of the migration from `version` to `version + 1`, resuming at `cursor`
+    /// if the migration is under way. The context is the one of the root view.
+    ///
+    /// Steps should keep their batches small enough to be written at once. States stored
+    /// without a version run all the migrations, even if they are empty: new states should
+    /// be marked with [`VersionedView::mark_current`] instead.
+    async fn migration_step(
+        context: &Self::Context,
+        version: u32,
+        cursor: Option<Vec<u8>>,
+    ) -> Result<MigrationStep, ViewError>;
+
+    /// Runs at most `max_steps` pending migration steps, or all of them if `None`, and
+    /// returns the resulting schema state.
+    fn migrate(
+        context: &Self::Context,
+        max_steps: Option<usize>,
+    ) -> impl Future<Output = Result<SchemaState, ViewError>> {
+        async move {
+            let key = schema_key(context.base_key());
+            let mut state = read_schema_state(context).await?;
+            if state.version > Self::SCHEMA_VERSION {
+                return Err(ViewError::UnsupportedSchemaVersion {
+                    stored: state.version,
+                    supported: Self::SCHEMA_VERSION,
+                });
+            }
+            let mut steps = 0;
+            while state.version < Self::SCHEMA_VERSION && max_steps.is_none_or(|max| steps < max) {
+                let MigrationStep { mut batch, cursor } =
+                    Self::migration_step(context, state.version, state.cursor.take()).await?;
+                let done = cursor.is_none();
+                state = SchemaState {
+                    version: if done {
+                        state.version + 1
+                    } else {
+                        state.version
+                    },
+                    cursor,
+                };
+                batch.put_key_value(key.clone(), &state)?;
+                context.store().write_batch(batch).await?;
+                steps += 1;
+                if done {
+                    tracing::info!(version = state.version, "Migrated view schema");
+                } else {
+                    tracing::debug!(version = state.version, steps, "Migrating view schema");
+                }
+            }
+            Ok(state)
+        }
+    }
+
+    /// Loads the view, after completing the pending migrations.
+    fn load_migrated(context: Self::Context) -> impl Future<Output = Result<Self, ViewError>> {
+        async {
+            Self::migrate(&context, None).await?;
+            Self::load(context).await
+        }
+    }
+
+    /// Records that the state at the context's base key has the current schema, without
+    /// migrating it, e.g. when an application is instantiated.
+    fn mark_current(context: &Self::Context) -> impl Future<Output = Result<(), ViewError>> {
+        async move {
+            let mut batch = Batch::new();
+            batch.put_key_value(
+                schema_key(context.base_key()),
+                &SchemaState {
+                    version: Self::SCHEMA_VERSION,
+                    cursor: None,
+                },
+            )?;
+            context.store().write_batch(batch).await?;
+            Ok(())
+        }
+    }
+}
@@ -11,7 +11,7 @@ use serde::{de::DeserializeOwned, Serialize};
 use crate::random::generate_test_namespace;
 use crate::{
     batch::{Batch, SimplifiedBatch},
-    common::from_bytes_option,
+    common::{from_bytes_option, group_keys_by_root},
     ViewError,
 };
 
@@ -149,7 +149,7 @@ pub trait KeyValueDatabase: WithError + linera_base::util::traits::AutoTraits +
     type Config: Send + Sync;
 
     /// The result of opening a partition.
-    type Store;
+    type Store: ReadableKeyValueStore<Error = Self::Error>;
 
     /// The name of this database.
     fn get_name() -> String;
@@ -175,6 +175,33 @@ pub trait KeyValueDatabase: WithError + linera_base::util::traits::AutoTraits +
     /// It is possible that some root keys have no keys.
     async fn list_root_keys(&self) -> Result<Vec<Vec<u8>>, Self::Error>;
 
+    /// Reads the values of a list of `(root_key, key)` pairs, each key being read in the
+    /// partition of its root key, e.g. the states of many chains at once.
+    ///
+    /// Backends that support it read all the values in one round-trip. By default, the
+    /// keys of each root key are read from a shared partition, concurrently.
+    fn read_multi_values_from_roots(
+        &self,
+        keys: Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> impl Future<Output = Result<Vec<Option<Vec<u8>>>, Self::Error>> {
+        async move {
+            let mut values = vec![None; keys.len()];
+            let groups = group_keys_by_root(keys);
+            let reads = groups.iter().map(|(root_key, (_, keys))| async move {
+                self.open_shared(root_key)?
+                    .read_multi_values_bytes(keys)
+                    .await
+            });
+            let results = futures::future::try_join_all(reads).await?;
+            for ((positions, _), group_values) in groups.values().zip(results) {
+                for (position, value) in positions.iter().zip(group_values) {
+                    values[*position] = value;
+                }
+            }
+            Ok(values)
+        }
+    }
+
     /// Deletes all the existing namespaces.
     fn delete_all(config: &Self::Config) -> impl Future<Output = Result<(), Self::Error>> {
         async {
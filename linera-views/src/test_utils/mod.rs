@@ -854,6 +854,58 @@ where
     assert_eq!(key_values.len(), 0);
 }
 
+/// Tests that reading keys spanning several root keys returns the same values as reading
+/// them from each partition.
+pub async fn read_multi_values_from_roots_admin_test<D>()
+where
+    D: TestKeyValueDatabase,
+    D::Store: KeyValueStore,
+{
+    let database = D::connect_test_namespace().await.expect("database");
+    let mut rng = make_deterministic_rng();
+    let mut keys = Vec::new();
+    for root_key in [vec![], vec![1], vec![1, 2], vec![3]] {
+        let store = database.open_exclusive(&root_key).expect("store");
+        let mut batch = Batch::new();
+        for _ in 0..5 {
+            let key = get_random_byte_vector(&mut rng, &[0], 4);
+            let value = get_random_byte_vector(&mut rng, &[], 10);
+            batch.put_key_value_bytes(key.clone(), value);
+            keys.push((root_key.clone(), key));
+        }
+        // A key that is only present in the last partition.
+        if root_key == [3] {
+            batch.put_key_value_bytes(vec![0, 255], vec![42]);
+        }
+        keys.push((root_key.clone(), vec![0, 255]));
+        store.write_batch(batch).await.expect("write batch");
+    }
+    keys.shuffle(&mut rng);
+    // Duplicates are allowed.
+    keys.push(keys[0].clone());
+
+    let values = database
+        .read_multi_values_from_roots(keys.clone())
+        .await
+        .expect("read multi values from roots");
+    assert_eq!(values.len(), keys.len());
+    for ((root_key, key), value) in keys.iter().zip(values) {
+        let store = database.open_shared(root_key).expect("store");
+        let expected = store.read_value_bytes(key).await.expect("read value");
+        assert_eq!(value, expected);
+        if key == &[0, 255] {
+            assert_eq!(value.is_some(), root_key == &[3]);
+        } else {
+            assert!(value.is_some());
+        }
+    }
+    assert!(database
+        .read_multi_values_from_roots(Vec::new())
+        .await
+        .expect("empty read")
+        .is_empty());
+}
+
 /// A store can be in exclusive access where it stores the absence of values
 /// or in shared access where only values are stored and (key, value) once
 /// written are never modified nor erased.
@@ -0,0 +1,526 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! A register storing the changes of its value as small deltas.
+//!
+//! A [`RegisterView`](crate::register_view::RegisterView) rewrites its whole value every
+//! time it is saved. For registers updated in almost every block, such as balances and
+//! counters, a [`DeltaRegisterView`] instead writes the difference with the stored value
+//! under a new key, and only writes the full value again after [`MAX_DELTAS`] deltas, or
+//! when a delta would not be smaller than the value. All the changes made between two
+//! saves are coalesced into a single delta.
+
+use allocative::Allocative;
+use linera_base::data_types::Amount;
+#[cfg(with_metrics)]
+use linera_base::prometheus_util::MeasureLatency as _;
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{
+    batch::Batch,
+    common::{from_bytes_option_or_default, HasherOutput},
+    context::Context,
+    hashable_wrapper::WrappedHashableContainerView,
+    views::{ClonableView, HashableView, Hasher, ReplaceContext, View, MIN_VIEW_TAG},
+    ViewError,
+};
+
+#[cfg(with_metrics)]
+mod metrics {
+    use std::sync::LazyLock;
+
+    use linera_base::prometheus_util::{exponential_bucket_latencies, register_histogram_vec};
+    use prometheus::HistogramVec;
+
+    /// The runtime of hash computation
+    pub static DELTA_REGISTER_VIEW_HASH_RUNTIME: LazyLock<HistogramVec> = LazyLock::new(|| {
+        register_histogram_vec(
+            "delta_register_view_hash_runtime",
+            "DeltaRegisterView hash runtime",
+            &[],
+            exponential_bucket_latencies(5.0),
+        )
+    });
+}
+
+/// The maximum number of deltas stored after a full value. The next save writes the full
+/// value again.
+pub const MAX_DELTAS: usize = 16;
+
+/// Key tags to create the sub-keys of a `DeltaRegisterView` on top of the base key.
+#[repr(u8)]
+enum KeyTag {
+    /// Prefix for the full value.
+    Snapshot = MIN_VIEW_TAG,
+    /// Prefix for the deltas applied to the full value, by index.
+    Delta,
+}
+
+/// A value that can be stored as a full value followed by a series of differences.
+pub trait Delta: Sized {
+    /// The difference between two values.
+    type Delta: Serialize + DeserializeOwned;
+
+    /// Returns the difference turning `self` into `new`.
+    fn delta(&self, new: &Self) -> Self::Delta;
+
+    /// Applies a difference returned by [`Delta::delta`].
+    fn apply_delta(&mut self, delta: Self::Delta);
+}
+
+/// The wrapping difference between two integers, serialized in as few bytes as its
+/// magnitude requires.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IntegerDelta(i128);
+
+impl Serialize for IntegerDelta {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Zigzag encoding maps small negative and positive differences to small numbers.
+        let zigzag = ((self.0 << 1) ^ (self.0 >> 127)).cast_unsigned();
+        let bytes = zigzag.to_le_bytes();
+        let len = bytes.len() - (zigzag.leading_zeros() / 8) as usize;
+        bytes[..len].to_vec().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for IntegerDelta {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = Vec::<u8>::deserialize(deserializer)?;
+        let mut buffer = [0u8; 16];
+        if bytes.len() > buffer.len() {
+            return Err(serde::de::Error::invalid_length(
+                bytes.len(),
+                &"at most 16 bytes",
+            ));
+        }
+        buffer[..bytes.len()].copy_from_slice(&bytes);
+        let zigzag = u128::from_le_bytes(buffer);
+        Ok(IntegerDelta(
+            (zigzag >> 1).cast_signed() ^ -(zigzag & 1).cast_signed(),
+        ))
+    }
+}
+
+impl Delta for u128 {
+    type Delta = IntegerDelta;
+
+    fn delta(&self, new: &Self) -> IntegerDelta {
+        IntegerDelta(new.wrapping_sub(*self).cast_signed())
+    }
+
+    fn apply_delta(&mut self, delta: IntegerDelta) {
+        *self = self.wrapping_add(delta.0.cast_unsigned());
+    }
+}
+
+macro_rules! impl_unsigned_delta {
+    ($($int:ty),*) => {
+        $(
+            impl Delta for $int {
+                type Delta = IntegerDelta;
+
+                fn delta(&self, new: &Self) -> IntegerDelta {
+                    IntegerDelta(i128::from(*new) - i128::from(*self))
+                }
+
+                #[expect(
+                    clippy::cast_possible_truncation,
+                    reason = "differences are applied modulo the integer size, as they are computed"
+                )]
+                fn apply_delta(&mut self, delta: IntegerDelta) {
+                    let mut value = u128::from(*self);
+                    value.apply_delta(delta);
+                    *self = value as $int;
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned_delta!(u8, u16, u32, u64);
+
+impl Delta for Amount {
+    type Delta = IntegerDelta;
+
+    fn delta(&self, new: &Self) -> IntegerDelta {
+        self.to_attos().delta(&new.to_attos())
+    }
+
+    fn apply_delta(&mut self, delta: IntegerDelta) {
+        let mut attos = self.to_attos();
+        attos.apply_delta(delta);
+        *self = Amount::from_attos(attos);
+    }
+}
+
+/// A view that supports modifying a single value of type `T`, storing the changes as
+/// deltas.
+#[derive(Debug, Allocative)]
+#[allocative(bound = "C, T: Allocative")]
+pub struct DeltaRegisterView<C, T> {
+    /// Whether to clear storage before applying updates.
+    delete_storage_first: bool,
+    /// The view context.
+    #[allocative(skip)]
+    context: C,
+    /// The value persisted in storage, with the stored deltas applied.
+    stored_value: Box<T>,
+    /// The number of deltas persisted in storage after the full value.
+    stored_delta_count: u32,
+    /// Pending update not yet persisted to storage.
+    update: Option<Box<T>>,
+}
+
+impl<C, T, C2> ReplaceContext<C2> for DeltaRegisterView<C, T>
+where
+    C: Context,
+    C2: Context,
+    T: Delta + Default + Send + Sync + Serialize + DeserializeOwned + Clone,
+{
+    type Target = DeltaRegisterView<C2, T>;
+
+    async fn with_context(
+        &mut self,
+        ctx: impl FnOnce(&Self::Context) -> C2 + Clone,
+    ) -> Self::Target {
+        DeltaRegisterView {
+            delete_storage_first: self.delete_storage_first,
+            context: ctx(&self.context),
+            stored_value: self.stored_value.clone(),
+            stored_delta_count: self.stored_delta_count,
+            update: self.update.clone(),
+        }
+    }
+}
+
+impl<C, T> View for DeltaRegisterView<C, T>
+where
+    C: Context,
+    T: Delta + Default + Send + Sync + Serialize + DeserializeOwned,
+{
+    const NUM_INIT_KEYS: usize = 1 + MAX_DELTAS;
+
+    type Context = C;
+
+    fn context(&self) -> C {
+        self.context.clone()
+    }
+
+    fn pre_load(context: &C) -> Result<Vec<Vec<u8>>, ViewError> {
+        let base_key = context.base_key();
+        let mut keys = vec![base_key.base_tag(KeyTag::Snapshot as u8)];
+        for index in (0u32..).take(MAX_DELTAS) {
+            keys.push(base_key.derive_tag_key(KeyTag::Delta as u8, &index)?);
+        }
+        Ok(keys)
+    }
+
+    fn post_load(context: C, values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
+        let (snapshot, deltas) = values.split_first().ok_or(ViewError::PostLoadValuesError)?;
+        let mut value: T = from_bytes_option_or_default(snapshot)?;
+        let mut stored_delta_count = 0;
+        for delta in deltas.iter().map_while(Option::as_ref) {
+            value.apply_delta(bcs::from_bytes(delta)?);
+            stored_delta_count += 1;
+        }
+        Ok(Self {
+            delete_storage_first: false,
+            context,
+            stored_value: Box::new(value),
+            stored_delta_count,
+            update: None,
+        })
+    }
+
+    fn rollback(&mut self) {
+        self.delete_storage_first = false;
+        self.update = None;
+    }
+
+    async fn has_pending_changes(&self) -> bool {
+        if self.delete_storage_first {
+            return true;
+        }
+        self.update.is_some()
+    }
+
+    fn pre_save(&self, batch: &mut Batch) -> Result<bool, ViewError> {
+        let mut delete_view = false;
+        let base_key = self.context.base_key();
+        if self.delete_storage_first {
+            batch.delete_key_prefix(base_key.bytes.clone());
+            delete_view = true;
+        } else if let Some(value) = &self.update {
+            match self.encode_delta(value)? {
+                Some(delta) => {
+                    let key =
+                        base_key.derive_tag_key(KeyTag::Delta as u8, &self.stored_delta_count)?;
+                    batch.put_key_value_bytes(key, delta);
+                }
+                None => {
+                    batch.delete_key_prefix(base_key.base_tag(KeyTag::Delta as u8));
+                    batch.put_key_value(base_key.base_tag(KeyTag::Snapshot as u8), value)?;
+                }
+            }
+        }
+        Ok(delete_view)
+    }
+
+    fn post_save(&mut self) {
+        if self.delete_storage_first {
+            *self.stored_value = Default::default();
+            self.stored_delta_count = 0;
+        } else if let Some(value) = self.update.take() {
+            if matches!(self.encode_delta(&value), Ok(Some(_))) {
+                self.stored_delta_count += 1;
+            } else {
+                self.stored_delta_count = 0;
+            }
+            self.stored_value = value;
+        }
+        self.delete_storage_first = false;
+        self.update = None;
+    }
+
+    fn clear(&mut self) {
+        self.delete_storage_first = true;
+        self.update = Some(Box::default());
+    }
+}
+
+impl<C, T> DeltaRegisterView<C, T>
+where
+    C: Context,
+    T: Delta + Serialize,
+{
+    /// Returns the serialized delta to store for the new value, or `None` if the full
+    /// value should be stored instead.
+    fn encode_delta(&self, value: &T) -> Result<Option<Vec<u8>>, ViewError> {
+        if self.stored_delta_count as usize >= MAX_DELTAS {
+            return Ok(None);
+        }
+        let delta = bcs::to_bytes(&self.stored_value.delta(value))?;
+        if delta.len() >= bcs::serialized_size(value)? {
+            return Ok(None);
+        }
+        Ok(Some(delta))
+    }
+}
+
+impl<C, T> ClonableView for DeltaRegisterView<C, T>
+where
+    C: Context,
+    T: Delta + Clone + Default + Send + Sync + Serialize + DeserializeOwned,
+{
+    fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
+        Ok(DeltaRegisterView {
+            delete_storage_first: self.delete_storage_first,
+            context: self.context.clone(),
+            stored_value: self.stored_value.clone(),
+            stored_delta_count: self.stored_delta_count,
+            update: self.update.clone(),
+        })
+    }
+}
+
+impl<C, T> DeltaRegisterView<C, T>
+where
+    C: Context,
+{
+    /// Access the current value in the register.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::delta_register_view::DeltaRegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut register = DeltaRegisterView::<_, u64>::load(context).await.unwrap();
+    /// let value = register.get();
+    /// assert_eq!(*value, 0);
+    /// # })
+    /// ```
+    pub fn get(&self) -> &T {
+        match &self.update {
+            None => &self.stored_value,
+            Some(value) => value,
+        }
+    }
+
+    /// Sets the value in the register.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::delta_register_view::DeltaRegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut register = DeltaRegisterView::load(context).await.unwrap();
+    /// register.set(5u64);
+    /// let value = register.get();
+    /// assert_eq!(*value, 5);
+    /// # })
+    /// ```
+    pub fn set(&mut self, value: T) {
+        self.delete_storage_first = false;
+        self.update = Some(Box::new(value));
+    }
+
+    /// Obtains the extra data.
+    pub fn extra(&self) -> &C::Extra {
+        self.context.extra()
+    }
+}
+
+impl<C, T> DeltaRegisterView<C, T>
+where
+    C: Context,
+    T: Clone + Serialize,
+{
+    /// Obtains a mutable reference to the value in the register.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::delta_register_view::DeltaRegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut register: DeltaRegisterView<_, u64> = DeltaRegisterView::load(context).await.unwrap();
+    /// *register.get_mut() += 3;
+    /// assert_eq!(*register.get(), 3);
+    /// # })
+    /// ```
+    pub fn get_mut(&mut self) -> &mut T {
+        self.delete_storage_first = false;
+        self.update.get_or_insert_with(|| self.stored_value.clone())
+    }
+
+    fn compute_hash(&self) -> Result<<sha3::Sha3_256 as Hasher>::Output, ViewError> {
+        #[cfg(with_metrics)]
+        let _hash_latency = metrics::DELTA_REGISTER_VIEW_HASH_RUNTIME.measure_latency();
+        let mut hasher = sha3::Sha3_256::default();
+        hasher.update_with_bcs_bytes(self.get())?;
+        Ok(hasher.finalize())
+    }
+}
+
+impl<C, T> HashableView for DeltaRegisterView<C, T>
+where
+    C: Context,
+    T: Delta + Clone + Default + Send + Sync + Serialize + DeserializeOwned,
+{
+    type Hasher = sha3::Sha3_256;
+
+    async fn hash_mut(&mut self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.compute_hash()
+    }
+
+    async fn hash(&self) -> Result<<Self::Hasher as Hasher>::Output, ViewError> {
+        self.compute_hash()
+    }
+}
+
+/// Type wrapping `DeltaRegisterView` while memoizing the hash.
+pub type HashedDeltaRegisterView<C, T> =
+    WrappedHashableContainerView<C, DeltaRegisterView<C, T>, HasherOutput>;
+
+#[cfg(with_graphql)]
+mod graphql {
+    use std::borrow::Cow;
+
+    use super::DeltaRegisterView;
+    use crate::context::Context;
+
+    impl<C, T> async_graphql::OutputType for DeltaRegisterView<C, T>
+    where
+        C: Context,
+        T: async_graphql::OutputType + Send + Sync,
+    {
+        fn type_name() -> Cow<'static, str> {
+            T::type_name()
+        }
+
+        fn create_type_info(registry: &mut async_graphql::registry::Registry) -> String {
+            T::create_type_info(registry)
+        }
+
+        async fn resolve(
+            &self,
+            ctx: &async_graphql::ContextSelectionSet<'_>,
+            field: &async_graphql::Positioned<async_graphql::parser::types::Field>,
+        ) -> async_graphql::ServerResult<async_graphql::Value> {
+            self.get().resolve(ctx, field).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linera_base::data_types::Amount;
+
+    use super::{Delta as _, DeltaRegisterView, IntegerDelta, MAX_DELTAS};
+    use crate::{
+        batch::Batch,
+        context::{Context as _, MemoryContext},
+        store::{ReadableKeyValueStore as _, WritableKeyValueStore as _},
+        views::View,
+    };
+
+    async fn save(
+        register: &mut DeltaRegisterView<MemoryContext<()>, Amount>,
+    ) -> anyhow::Result<()> {
+        let mut batch = Batch::new();
+        register.pre_save(&mut batch)?;
+        register.context().store().write_batch(batch).await?;
+        register.post_save();
+        Ok(())
+    }
+
+    #[test]
+    fn test_integer_delta_encoding() {
+        for (old, new) in [(5u64, 6), (6, 5), (0, u64::MAX), (u64::MAX, 0), (7, 7)] {
+            let delta = old.delta(&new);
+            let bytes = bcs::to_bytes(&delta).unwrap();
+            assert_eq!(bcs::from_bytes::<IntegerDelta>(&bytes).unwrap(), delta);
+            let mut value = old;
+            value.apply_delta(delta);
+            assert_eq!(value, new);
+        }
+        assert_eq!(bcs::to_bytes(&5u64.delta(&6)).unwrap().len(), 2);
+        assert_eq!(bcs::to_bytes(&6u64.delta(&5)).unwrap().len(), 2);
+
+        let mut amount = Amount::from_tokens(10);
+        amount.apply_delta(amount.delta(&Amount::from_millis(1)));
+        assert_eq!(amount, Amount::from_millis(1));
+    }
+
+    #[tokio::test]
+    async fn test_delta_register_view() -> anyhow::Result<()> {
+        let context = MemoryContext::new_for_testing(());
+        let key_count = || async {
+            let store = context.store();
+            anyhow::Ok(store.find_keys_by_prefix(&[]).await?.len())
+        };
+
+        // Each save stores a delta, until a full value is stored again.
+        for step in 1..=(2 * MAX_DELTAS + 3) as u64 {
+            let mut register = DeltaRegisterView::<_, Amount>::load(context.clone()).await?;
+            assert_eq!(*register.get(), Amount::from_attos(u128::from(step - 1)));
+            *register.get_mut() = register.get().saturating_add(Amount::from_attos(3));
+            // Changes between saves are coalesced.
+            *register.get_mut() = register.get().saturating_sub(Amount::from_attos(2));
+            save(&mut register).await?;
+            assert!(key_count().await? <= 1 + MAX_DELTAS);
+        }
+        let register = DeltaRegisterView::<_, Amount>::load(context.clone()).await?;
+        assert_eq!(
+            *register.get(),
+            Amount::from_attos(2 * MAX_DELTAS as u128 + 3)
+        );
+
+        let mut register = DeltaRegisterView::<_, Amount>::load(context.clone()).await?;
+        register.clear();
+        save(&mut register).await?;
+        assert_eq!(key_count().await?, 0);
+        let register = DeltaRegisterView::<_, Amount>::load(context.clone()).await?;
+        assert_eq!(*register.get(), Amount::ZERO);
+        Ok(())
+    }
+}
@@ -19,7 +19,7 @@ use crate::{
     hashable_wrapper::WrappedHashableContainerView,
     historical_hash_wrapper::HistoricallyHashableView,
     store::ReadableKeyValueStore as _,
-    views::{ClonableView, HashableView, Hasher, View, ViewError, MIN_VIEW_TAG},
+    views::{ClonableView, HashableView, Hasher, ReplaceContext, View, ViewError, MIN_VIEW_TAG},
 };
 
 #[cfg(with_metrics)]
@@ -64,6 +64,27 @@ pub struct LogView<C, T> {
     new_values: Vec<T>,
 }
 
+impl<C, T, C2> ReplaceContext<C2> for LogView<C, T>
+where
+    C: Context,
+    C2: Context,
+    T: Send + Sync + Serialize + Clone,
+{
+    type Target = LogView<C2, T>;
+
+    async fn with_context(
+        &mut self,
+        ctx: impl FnOnce(&Self::Context) -> C2 + Clone,
+    ) -> Self::Target {
+        LogView {
+            context: ctx(&self.context),
+            delete_storage_first: self.delete_storage_first,
+            stored_count: self.stored_count,
+            new_values: self.new_values.clone(),
+        }
+    }
+}
+
 impl<C, T> View for LogView<C, T>
 where
     C: Context,
@@ -278,6 +278,24 @@ where
         self.deletion_set.insert_key_prefix(key_prefix);
     }
 
+    /// Inserts or resets the values of all the given keys.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.extend_from_iter([(vec![0, 1], "Hello"), (vec![0, 2], "Bonjour")]);
+    /// assert_eq!(map.keys().await.unwrap(), vec![vec![0, 1], vec![0, 2]]);
+    /// # })
+    /// ```
+    pub fn extend_from_iter(&mut self, key_values: impl IntoIterator<Item = (Vec<u8>, V)>) {
+        for (short_key, value) in key_values {
+            self.insert(short_key, value);
+        }
+    }
+
     /// Obtains the extra data.
     pub fn extra(&self) -> &C::Extra {
         self.context.extra()
@@ -903,6 +921,73 @@ where
     pub async fn key_values(&self) -> Result<Vec<(Vec<u8>, V)>, ViewError> {
         self.key_values_by_prefix(Vec::new()).await
     }
+
+    /// Keeps only the entries for which the predicate returns true. The entries are read
+    /// from storage once, and if none is kept, the whole map is deleted as a single range.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.extend_from_iter([(vec![0, 1], 1), (vec![0, 2], 2), (vec![3], 3)]);
+    /// map.retain(|_key, value| Ok(*value > 1)).await.unwrap();
+    /// assert_eq!(map.keys().await.unwrap(), vec![vec![0, 2], vec![3]]);
+    /// # })
+    /// ```
+    pub async fn retain<F>(&mut self, mut f: F) -> Result<(), ViewError>
+    where
+        F: FnMut(&[u8], &V) -> Result<bool, ViewError> + Send,
+    {
+        let mut removed = Vec::new();
+        let mut kept_any = false;
+        self.for_each_key_value(
+            |key, value| {
+                if f(key, &value)? {
+                    kept_any = true;
+                } else {
+                    removed.push(key.to_vec());
+                }
+                Ok(())
+            },
+            Vec::new(),
+        )
+        .await?;
+        if removed.is_empty() {
+            return Ok(());
+        }
+        if !kept_any {
+            self.clear();
+            return Ok(());
+        }
+        for key in removed {
+            self.remove(key);
+        }
+        Ok(())
+    }
+
+    /// Removes all the entries whose keys start with the given prefix and returns them,
+    /// in lexicographic order. They are deleted from storage as a single range when the
+    /// view is saved.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::ByteMapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = ByteMapView::load(context).await.unwrap();
+    /// map.extend_from_iter([(vec![0, 1], 1), (vec![0, 2], 2), (vec![3], 3)]);
+    /// let drained = map.drain_prefix(vec![0]).await.unwrap();
+    /// assert_eq!(drained, vec![(vec![0, 1], 1), (vec![0, 2], 2)]);
+    /// assert_eq!(map.keys().await.unwrap(), vec![vec![3]]);
+    /// # })
+    /// ```
+    pub async fn drain_prefix(&mut self, prefix: Vec<u8>) -> Result<Vec<(Vec<u8>, V)>, ViewError> {
+        let key_values = self.key_values_by_prefix(prefix.clone()).await?;
+        self.remove_by_prefix(prefix);
+        Ok(key_values)
+    }
 }
 
 impl<C, V> ByteMapView<C, V>
@@ -1137,6 +1222,39 @@ where
         Ok(())
     }
 
+    /// Inserts or resets the values at all the given indices.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::MapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = MapView::<_, u32, String>::load(context).await.unwrap();
+    /// map.extend_from_iter([(24, "Hello".to_string()), (37, "Bonjour".to_string())])
+    ///     .unwrap();
+    /// assert_eq!(map.indices().await.unwrap(), vec![24, 37]);
+    /// # })
+    /// ```
+    pub fn extend_from_iter<Q>(
+        &mut self,
+        index_values: impl IntoIterator<Item = (Q, V)>,
+    ) -> Result<(), ViewError>
+    where
+        Q: Borrow<I>,
+    {
+        let key_values = index_values
+            .into_iter()
+            .map(|(index, value)| {
+                Ok((
+                    BaseKey::derive_short_key(Borrow::<I>::borrow(&index))?,
+                    value,
+                ))
+            })
+            .collect::<Result<Vec<_>, ViewError>>()?;
+        self.map.extend_from_iter(key_values);
+        Ok(())
+    }
+
     /// Obtains the extra data.
     pub fn extra(&self) -> &C::Extra {
         self.map.extra()
@@ -1529,6 +1647,63 @@ where
     pub async fn iterative_count(&self) -> Result<usize, ViewError> {
         self.map.iterative_count().await
     }
+
+    /// Keeps only the entries for which the predicate returns true. See
+    /// [`ByteMapView::retain`].
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::MapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = MapView::<_, u32, u64>::load(context).await.unwrap();
+    /// map.extend_from_iter([(1, 10), (2, 0), (3, 30)]).unwrap();
+    /// map.retain(|_index, balance| Ok(*balance > 0)).await.unwrap();
+    /// assert_eq!(map.indices().await.unwrap(), vec![1, 3]);
+    /// # })
+    /// ```
+    pub async fn retain<F>(&mut self, mut f: F) -> Result<(), ViewError>
+    where
+        F: FnMut(&I, &V) -> Result<bool, ViewError> + Send,
+    {
+        self.map
+            .retain(|key, value| {
+                let index = BaseKey::deserialize_value(key)?;
+                f(&index, value)
+            })
+            .await
+    }
+
+    /// Removes all the entries whose index serialization starts with the serialization
+    /// of `prefix`, and returns them in the order determined by the serialization. E.g.
+    /// for tuples or structs, `prefix` can be the first fields. See
+    /// [`ByteMapView::drain_prefix`].
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::map_view::MapView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut map = MapView::<_, (u32, u32), u64>::load(context).await.unwrap();
+    /// map.extend_from_iter([((1, 2), 12), ((1, 3), 13), ((2, 1), 21)])
+    ///     .unwrap();
+    /// let drained = map.drain_prefix(&1u32).await.unwrap();
+    /// assert_eq!(drained, vec![((1, 2), 12), ((1, 3), 13)]);
+    /// assert_eq!(map.indices().await.unwrap(), vec![(2, 1)]);
+    /// # })
+    /// ```
+    pub async fn drain_prefix<P>(&mut self, prefix: &P) -> Result<Vec<(I, V)>, ViewError>
+    where
+        P: Serialize + ?Sized,
+    {
+        let prefix = BaseKey::derive_short_key(prefix)?;
+        self.map
+            .drain_prefix(prefix)
+            .await?
+            .into_iter()
+            .map(|(key, value)| Ok((BaseKey::deserialize_value(&key)?, value)))
+            .collect()
+    }
 }
 
 impl<C, I, V> MapView<C, I, V>
@@ -2391,6 +2566,9 @@ mod graphql {
 pub mod tests {
     use std::borrow::Borrow;
 
+    use super::*;
+    use crate::{context::MemoryContext, store::WritableKeyValueStore as _};
+
     fn check_str<T: Borrow<str>>(s: T) {
         let ser1 = bcs::to_bytes("Hello").unwrap();
         let ser2 = bcs::to_bytes(s.borrow()).unwrap();
@@ -2411,4 +2589,31 @@ pub mod tests {
         check_array_u8(vec![23, 67, 123]);
         check_array_u8([23, 67, 123]);
     }
+
+    #[tokio::test]
+    async fn test_map_view_bulk_operations_after_flush() -> Result<(), ViewError> {
+        let context = MemoryContext::new_for_testing(());
+        let mut map = MapView::<_, (u32, u32), u64>::load(context).await?;
+        map.extend_from_iter([((1, 1), 11), ((1, 2), 12), ((2, 1), 21), ((2, 2), 0)])?;
+        let mut batch = Batch::new();
+        map.pre_save(&mut batch)?;
+        map.context().store().write_batch(batch).await?;
+        map.post_save();
+
+        // The drained prefix is deleted as a single range, not key by key.
+        let drained = map.drain_prefix(&1u32).await?;
+        assert_eq!(drained, vec![((1, 1), 11), ((1, 2), 12)]);
+        assert_eq!(map.get(&(1, 1)).await?, None);
+        map.insert(&(1, 3), 13)?;
+        map.retain(|_, value| Ok(*value > 0)).await?;
+        let mut batch = Batch::new();
+        map.pre_save(&mut batch)?;
+        assert_eq!(batch.operations.len(), 3);
+        map.context().store().write_batch(batch).await?;
+        map.post_save();
+
+        let map = MapView::<_, (u32, u32), u64>::load(map.context()).await?;
+        assert_eq!(map.index_values().await?, vec![((1, 3), 13), ((2, 1), 21)]);
+        Ok(())
+    }
 }
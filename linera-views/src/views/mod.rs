@@ -21,6 +21,9 @@ pub mod register_view;
 /// The `LazyRegisterView` implements a register for a single value with lazy loading.
 pub mod lazy_register_view;
 
+/// The `DeltaRegisterView` implements a register for a single value, storing its changes as deltas.
+pub mod delta_register_view;
+
 /// The `LogView` implements a log list that can be pushed.
 pub mod log_view;
 
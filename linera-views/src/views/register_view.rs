@@ -198,6 +198,22 @@ where
     pub fn extra(&self) -> &C::Extra {
         self.context.extra()
     }
+
+    /// Accesses the value persisted in storage, ignoring any pending update.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::context::MemoryContext;
+    /// # use linera_views::register_view::RegisterView;
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut register = RegisterView::load(context).await.unwrap();
+    /// register.set(5);
+    /// assert_eq!(*register.stored_value(), 0);
+    /// # })
+    /// ```
+    pub fn stored_value(&self) -> &T {
+        &self.stored_value
+    }
 }
 
 impl<C, T> RegisterView<C, T>
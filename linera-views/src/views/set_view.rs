@@ -10,7 +10,10 @@ use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{
     batch::Batch,
-    common::{CustomSerialize, HasherOutput, Update},
+    common::{
+        get_key_range_for_prefix, CustomSerialize, DeletionSet, HasherOutput,
+        SuffixClosedSetIterator, Update,
+    },
     context::{BaseKey, Context},
     hashable_wrapper::WrappedHashableContainerView,
     historical_hash_wrapper::HistoricallyHashableView,
@@ -43,8 +46,8 @@ pub struct ByteSetView<C> {
     /// The view context.
     #[allocative(skip)]
     context: C,
-    /// Whether to clear storage before applying updates.
-    delete_storage_first: bool,
+    /// Tracks deleted key prefixes.
+    deletion_set: DeletionSet,
     /// Pending changes not yet persisted to storage.
     updates: BTreeMap<Vec<u8>, Update<()>>,
 }
@@ -58,7 +61,7 @@ impl<C: Context, C2: Context> ReplaceContext<C2> for ByteSetView<C> {
     ) -> Self::Target {
         ByteSetView {
             context: ctx(&self.context),
-            delete_storage_first: self.delete_storage_first,
+            deletion_set: self.deletion_set.clone(),
             updates: self.updates.clone(),
         }
     }
@@ -80,26 +83,23 @@ impl<C: Context> View for ByteSetView<C> {
     fn post_load(context: C, _values: &[Option<Vec<u8>>]) -> Result<Self, ViewError> {
         Ok(Self {
             context,
-            delete_storage_first: false,
+            deletion_set: DeletionSet::new(),
             updates: BTreeMap::new(),
         })
     }
 
     fn rollback(&mut self) {
-        self.delete_storage_first = false;
+        self.deletion_set.rollback();
         self.updates.clear();
     }
 
     async fn has_pending_changes(&self) -> bool {
-        if self.delete_storage_first {
-            return true;
-        }
-        !self.updates.is_empty()
+        self.deletion_set.has_pending_changes() || !self.updates.is_empty()
     }
 
     fn pre_save(&self, batch: &mut Batch) -> Result<bool, ViewError> {
         let mut delete_view = false;
-        if self.delete_storage_first {
+        if self.deletion_set.delete_storage_first {
             delete_view = true;
             batch.delete_key_prefix(self.context.base_key().bytes.clone());
             for (index, update) in self.updates.iter() {
@@ -110,6 +110,10 @@ impl<C: Context> View for ByteSetView<C> {
                 }
             }
         } else {
+            for index in &self.deletion_set.deleted_prefixes {
+                let key = self.context.base_key().base_index(index);
+                batch.delete_key_prefix(key);
+            }
             for (index, update) in self.updates.iter() {
                 let key = self.context.base_key().base_index(index);
                 match update {
@@ -122,12 +126,13 @@ impl<C: Context> View for ByteSetView<C> {
     }
 
     fn post_save(&mut self) {
-        self.delete_storage_first = false;
+        self.deletion_set.delete_storage_first = false;
+        self.deletion_set.deleted_prefixes.clear();
         self.updates.clear();
     }
 
     fn clear(&mut self) {
-        self.delete_storage_first = true;
+        self.deletion_set.clear();
         self.updates.clear();
     }
 }
@@ -136,7 +141,7 @@ impl<C: Context> ClonableView for ByteSetView<C> {
     fn clone_unchecked(&mut self) -> Result<Self, ViewError> {
         Ok(ByteSetView {
             context: self.context.clone(),
-            delete_storage_first: self.delete_storage_first,
+            deletion_set: self.deletion_set.clone(),
             updates: self.updates.clone(),
         })
     }
@@ -170,14 +175,57 @@ impl<C: Context> ByteSetView<C> {
     /// # })
     /// ```
     pub fn remove(&mut self, short_key: Vec<u8>) {
-        if self.delete_storage_first {
-            // Optimization: No need to mark `short_key` for deletion as we are going to remove all the keys at once.
+        if self.deletion_set.contains_prefix_of(&short_key) {
+            // Optimization: No need to mark `short_key` for deletion as we are going to remove a range of keys containing it.
             self.updates.remove(&short_key);
         } else {
             self.updates.insert(short_key, Update::Removed);
         }
     }
 
+    /// Inserts all the given values.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::ByteSetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = ByteSetView::load(context).await.unwrap();
+    /// set.extend_from_iter([vec![0, 1], vec![0, 2]]);
+    /// assert_eq!(set.keys().await.unwrap(), vec![vec![0, 1], vec![0, 2]]);
+    /// # })
+    /// ```
+    pub fn extend_from_iter(&mut self, short_keys: impl IntoIterator<Item = Vec<u8>>) {
+        for short_key in short_keys {
+            self.insert(short_key);
+        }
+    }
+
+    /// Removes all the values starting with the given prefix. They are deleted from
+    /// storage as a single range when the view is saved.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::ByteSetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = ByteSetView::load(context).await.unwrap();
+    /// set.insert(vec![0, 1]);
+    /// set.insert(vec![1, 2]);
+    /// set.remove_by_prefix(vec![0]);
+    /// assert_eq!(set.keys().await.unwrap(), vec![vec![1, 2]]);
+    /// # })
+    /// ```
+    pub fn remove_by_prefix(&mut self, key_prefix: Vec<u8>) {
+        let key_list = self
+            .updates
+            .range(get_key_range_for_prefix(key_prefix.clone()))
+            .map(|x| x.0.to_vec())
+            .collect::<Vec<_>>();
+        for key in key_list {
+            self.updates.remove(&key);
+        }
+        self.deletion_set.insert_key_prefix(key_prefix);
+    }
+
     /// Gets the extra data.
     pub fn extra(&self) -> &C::Extra {
         self.context.extra()
@@ -205,7 +253,7 @@ impl<C: Context> ByteSetView<C> {
             };
             return Ok(value);
         }
-        if self.delete_storage_first {
+        if self.deletion_set.contains_prefix_of(short_key) {
             return Ok(false);
         }
         let key = self.context.base_key().base_index(short_key);
@@ -280,30 +328,50 @@ impl<C: Context> ByteSetView<C> {
     /// assert_eq!(count, 2);
     /// # })
     /// ```
-    pub async fn for_each_key_while<F>(&self, mut f: F) -> Result<(), ViewError>
+    pub async fn for_each_key_while<F>(&self, f: F) -> Result<(), ViewError>
     where
         F: FnMut(&[u8]) -> Result<bool, ViewError> + Send,
     {
-        let mut updates = self.updates.iter();
+        self.for_each_key_while_by_prefix(f, Vec::new()).await
+    }
+
+    /// Applies a function f on each key having the specified prefix. The keys sent to
+    /// the function are shortened by the prefix. Keys are visited in lexicographic
+    /// order. If the function returns false, then the loop ends prematurely.
+    async fn for_each_key_while_by_prefix<F>(
+        &self,
+        mut f: F,
+        prefix: Vec<u8>,
+    ) -> Result<(), ViewError>
+    where
+        F: FnMut(&[u8]) -> Result<bool, ViewError> + Send,
+    {
+        let prefix_len = prefix.len();
+        let mut updates = self.updates.range(get_key_range_for_prefix(prefix.clone()));
         let mut update = updates.next();
-        if !self.delete_storage_first {
-            let base = &self.context.base_key().bytes;
-            for index in self.context.store().find_keys_by_prefix(base).await? {
+        if !self.deletion_set.contains_prefix_of(&prefix) {
+            let iter = self
+                .deletion_set
+                .deleted_prefixes
+                .range(get_key_range_for_prefix(prefix.clone()));
+            let mut suffix_closed_set = SuffixClosedSetIterator::new(prefix_len, iter);
+            let base = self.context.base_key().base_index(&prefix);
+            for index in self.context.store().find_keys_by_prefix(&base).await? {
                 loop {
                     match update {
-                        Some((key, value)) if key <= &index => {
+                        Some((key, value)) if &key[prefix_len..] <= index.as_slice() => {
                             if let Update::Set(_) = value {
-                                if !f(key)? {
+                                if !f(&key[prefix_len..])? {
                                     return Ok(());
                                 }
                             }
                             update = updates.next();
-                            if key == &index {
+                            if key[prefix_len..] == index {
                                 break;
                             }
                         }
                         _ => {
-                            if !f(&index)? {
+                            if !suffix_closed_set.find_key(&index) && !f(&index)? {
                                 return Ok(());
                             }
                             break;
@@ -314,7 +382,7 @@ impl<C: Context> ByteSetView<C> {
         }
         while let Some((key, value)) = update {
             if let Update::Set(_) = value {
-                if !f(key)? {
+                if !f(&key[prefix_len..])? {
                     return Ok(());
                 }
             }
@@ -354,6 +422,78 @@ impl<C: Context> ByteSetView<C> {
         })
         .await
     }
+
+    /// Keeps only the keys for which the predicate returns true. The keys are read from
+    /// storage once, and if none is kept, the whole set is deleted as a single range.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::ByteSetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = ByteSetView::load(context).await.unwrap();
+    /// set.extend_from_iter([vec![0, 1], vec![0, 2], vec![3]]);
+    /// set.retain(|key| Ok(key.len() == 2)).await.unwrap();
+    /// assert_eq!(set.keys().await.unwrap(), vec![vec![0, 1], vec![0, 2]]);
+    /// # })
+    /// ```
+    pub async fn retain<F>(&mut self, mut f: F) -> Result<(), ViewError>
+    where
+        F: FnMut(&[u8]) -> Result<bool, ViewError> + Send,
+    {
+        let mut removed = Vec::new();
+        let mut kept_any = false;
+        self.for_each_key(|key| {
+            if f(key)? {
+                kept_any = true;
+            } else {
+                removed.push(key.to_vec());
+            }
+            Ok(())
+        })
+        .await?;
+        if removed.is_empty() {
+            return Ok(());
+        }
+        if !kept_any {
+            self.clear();
+            return Ok(());
+        }
+        for key in removed {
+            self.remove(key);
+        }
+        Ok(())
+    }
+
+    /// Removes all the keys starting with the given prefix and returns them, in
+    /// lexicographic order. They are deleted from storage as a single range when the
+    /// view is saved.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::ByteSetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = ByteSetView::load(context).await.unwrap();
+    /// set.extend_from_iter([vec![0, 1], vec![0, 2], vec![3]]);
+    /// let drained = set.drain_prefix(vec![0]).await.unwrap();
+    /// assert_eq!(drained, vec![vec![0, 1], vec![0, 2]]);
+    /// assert_eq!(set.keys().await.unwrap(), vec![vec![3]]);
+    /// # })
+    /// ```
+    pub async fn drain_prefix(&mut self, prefix: Vec<u8>) -> Result<Vec<Vec<u8>>, ViewError> {
+        let mut keys = Vec::new();
+        self.for_each_key_while_by_prefix(
+            |key| {
+                let mut big_key = prefix.clone();
+                big_key.extend(key);
+                keys.push(big_key);
+                Ok(true)
+            },
+            prefix.clone(),
+        )
+        .await?;
+        self.remove_by_prefix(prefix);
+        Ok(keys)
+    }
 }
 
 impl<C: Context> HashableView for ByteSetView<C> {
@@ -503,6 +643,32 @@ impl<C: Context, I: Serialize> SetView<C, I> {
         Ok(())
     }
 
+    /// Inserts all the given values.
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::SetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = SetView::<_, u32>::load(context).await.unwrap();
+    /// set.extend_from_iter([34, 37, 42]).unwrap();
+    /// assert_eq!(set.indices().await.unwrap(), vec![34, 37, 42]);
+    /// # })
+    /// ```
+    pub fn extend_from_iter<Q>(
+        &mut self,
+        indices: impl IntoIterator<Item = Q>,
+    ) -> Result<(), ViewError>
+    where
+        Q: Borrow<I>,
+    {
+        let short_keys = indices
+            .into_iter()
+            .map(|index| BaseKey::derive_short_key(Borrow::<I>::borrow(&index)))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.set.extend_from_iter(short_keys);
+        Ok(())
+    }
+
     /// Obtains the extra data.
     pub fn extra(&self) -> &C::Extra {
         self.set.extra()
@@ -639,6 +805,59 @@ impl<C: Context, I: Serialize + DeserializeOwned + Send> SetView<C, I> {
             .await?;
         Ok(())
     }
+
+    /// Keeps only the indices for which the predicate returns true. See
+    /// [`ByteSetView::retain`].
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::SetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = SetView::<_, u32>::load(context).await.unwrap();
+    /// set.extend_from_iter([34, 37, 42]).unwrap();
+    /// set.retain(|index| Ok(index % 2 == 0)).await.unwrap();
+    /// assert_eq!(set.indices().await.unwrap(), vec![34, 42]);
+    /// # })
+    /// ```
+    pub async fn retain<F>(&mut self, mut f: F) -> Result<(), ViewError>
+    where
+        F: FnMut(&I) -> Result<bool, ViewError> + Send,
+    {
+        self.set
+            .retain(|key| {
+                let index = BaseKey::deserialize_value(key)?;
+                f(&index)
+            })
+            .await
+    }
+
+    /// Removes all the indices whose serialization starts with the serialization of
+    /// `prefix`, and returns them in the order determined by the serialization. E.g. for
+    /// tuples or structs, `prefix` can be the first fields. See
+    /// [`ByteSetView::drain_prefix`].
+    /// ```rust
+    /// # tokio_test::block_on(async {
+    /// # use linera_views::{context::MemoryContext, set_view::SetView};
+    /// # use linera_views::views::View;
+    /// # let context = MemoryContext::new_for_testing(());
+    /// let mut set = SetView::<_, (u32, u32)>::load(context).await.unwrap();
+    /// set.extend_from_iter([(1, 2), (1, 3), (2, 1)]).unwrap();
+    /// assert_eq!(set.drain_prefix(&1u32).await.unwrap(), vec![(1, 2), (1, 3)]);
+    /// assert_eq!(set.indices().await.unwrap(), vec![(2, 1)]);
+    /// # })
+    /// ```
+    pub async fn drain_prefix<P>(&mut self, prefix: &P) -> Result<Vec<I>, ViewError>
+    where
+        P: Serialize + ?Sized,
+    {
+        let prefix = BaseKey::derive_short_key(prefix)?;
+        self.set
+            .drain_prefix(prefix)
+            .await?
+            .iter()
+            .map(|key| Ok(BaseKey::deserialize_value(key)?))
+            .collect()
+    }
 }
 
 impl<C, I> HashableView for SetView<C, I>
@@ -1890,6 +2109,48 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_set_view_bulk_operations_after_flush() -> Result<(), ViewError> {
+        let context = MemoryContext::new_for_testing(());
+        let mut set = SetView::<_, (u32, u32)>::load(context).await?;
+        set.extend_from_iter([(1, 1), (1, 2), (2, 1), (2, 2), (3, 1)])?;
+        let mut batch = Batch::new();
+        set.pre_save(&mut batch)?;
+        set.context().store().write_batch(batch).await?;
+        set.post_save();
+
+        // The drained prefix is deleted as a single range, not key by key.
+        assert_eq!(set.drain_prefix(&1u32).await?, vec![(1, 1), (1, 2)]);
+        set.insert(&(1, 3))?;
+        assert!(!set.contains(&(1, 1)).await?);
+        set.retain(|(first, second)| Ok(*first == 1 || *second == 1))
+            .await?;
+        assert_eq!(set.indices().await?, vec![(1, 3), (2, 1), (3, 1)]);
+        let mut batch = Batch::new();
+        set.pre_save(&mut batch)?;
+        assert_eq!(batch.operations.len(), 3);
+        set.context().store().write_batch(batch).await?;
+        set.post_save();
+
+        let set = SetView::<_, (u32, u32)>::load(set.context()).await?;
+        assert_eq!(set.indices().await?, vec![(1, 3), (2, 1), (3, 1)]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_view_retain_nothing_clears_the_set() -> Result<(), ViewError> {
+        let context = MemoryContext::new_for_testing(());
+        let mut set = SetView::<_, u32>::load(context).await?;
+        set.retain(|_| Ok(false)).await?;
+        assert!(!set.has_pending_changes().await);
+        set.extend_from_iter(0..10u32)?;
+        set.retain(|_| Ok(false)).await?;
+        assert!(set.indices().await?.is_empty());
+        let mut batch = Batch::new();
+        assert!(set.pre_save(&mut batch)?);
+        Ok(())
+    }
+
     #[cfg(with_graphql)]
     mod graphql_tests {
         use async_graphql::{EmptyMutation, EmptySubscription, Object, Schema};
@@ -8,13 +8,17 @@ use linera_views::rocks_db::RocksDbDatabase;
 #[cfg(with_scylladb)]
 use linera_views::scylla_db::ScyllaDbDatabase;
 use linera_views::{
+    encryption::EncryptedMemoryDatabase,
     memory::MemoryDatabase,
     store::{KeyValueStore, TestKeyValueDatabase},
-    test_utils::{namespace_admin_test, root_key_admin_test},
+    test_utils::{
+        namespace_admin_test, read_multi_values_from_roots_admin_test, root_key_admin_test,
+    },
 };
 use test_case::test_case;
 
 #[test_case(PhantomData::<MemoryDatabase>; "MemoryDatabase")]
+#[test_case(PhantomData::<EncryptedMemoryDatabase>; "EncryptedMemoryDatabase")]
 #[cfg_attr(with_rocksdb, test_case(PhantomData::<RocksDbDatabase>; "RocksDbDatabase"))]
 #[cfg_attr(with_scylladb, test_case(PhantomData::<ScyllaDbDatabase>; "ScyllaDbDatabase"))]
 #[tokio::test]
@@ -26,6 +30,7 @@ where
 }
 
 #[test_case(PhantomData::<MemoryDatabase>; "MemoryDatabase")]
+#[test_case(PhantomData::<EncryptedMemoryDatabase>; "EncryptedMemoryDatabase")]
 #[cfg_attr(with_rocksdb, test_case(PhantomData::<RocksDbDatabase>; "RocksDbDatabase"))]
 #[cfg_attr(with_scylladb, test_case(PhantomData::<ScyllaDbDatabase>; "ScyllaDbDatabase"))]
 #[tokio::test]
@@ -35,3 +40,16 @@ where
 {
     root_key_admin_test::<K>().await;
 }
+
+#[test_case(PhantomData::<MemoryDatabase>; "MemoryDatabase")]
+#[test_case(PhantomData::<EncryptedMemoryDatabase>; "EncryptedMemoryDatabase")]
+#[cfg_attr(with_rocksdb, test_case(PhantomData::<RocksDbDatabase>; "RocksDbDatabase"))]
+#[cfg_attr(with_scylladb, test_case(PhantomData::<ScyllaDbDatabase>; "ScyllaDbDatabase"))]
+#[tokio::test]
+async fn read_multi_values_from_roots_admin_test_cases<K: TestKeyValueDatabase>(
+    _view_type: PhantomData<K>,
+) where
+    K::Store: KeyValueStore,
+{
+    read_multi_values_from_roots_admin_test::<K>().await;
+}
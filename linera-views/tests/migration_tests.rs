@@ -0,0 +1,147 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use linera_views::{
+    batch::Batch,
+    context::{Context, MemoryContext},
+    map_view::MapView,
+    migration::{read_schema_state, MigrationStep, SchemaState, VersionedView},
+    register_view::RegisterView,
+    views::{RootView, View},
+    ViewError,
+};
+
+/// The number of balances converted by each step of the first migration.
+const BALANCES_PER_STEP: usize = 2;
+
+#[derive(RootView)]
+struct Ledger<C> {
+    /// The balances, in cents since version 1 and in whole tokens before.
+    balances: MapView<C, u32, u64>,
+    /// The sum of the balances, since version 2.
+    total: RegisterView<C, u64>,
+}
+
+impl<C: Context> VersionedView for Ledger<C> {
+    const SCHEMA_VERSION: u32 = 2;
+
+    async fn migration_step(
+        context: &C,
+        version: u32,
+        cursor: Option<Vec<u8>>,
+    ) -> Result<MigrationStep, ViewError> {
+        let mut ledger = Self::load(context.clone()).await?;
+        let mut batch = Batch::new();
+        match version {
+            0 => {
+                let start = match cursor {
+                    Some(cursor) => Some(bcs::from_bytes::<u32>(&cursor)?),
+                    None => None,
+                };
+                let indices = ledger
+                    .balances
+                    .indices()
+                    .await?
+                    .into_iter()
+                    .filter(|index| start.is_none_or(|start| *index > start))
+                    .take(BALANCES_PER_STEP)
+                    .collect::<Vec<_>>();
+                for index in &indices {
+                    let balance = ledger.balances.get_mut(index).await?.unwrap();
+                    *balance *= 100;
+                }
+                ledger.pre_save(&mut batch)?;
+                Ok(match indices.last() {
+                    Some(last) if indices.len() == BALANCES_PER_STEP => {
+                        MigrationStep::resume_at(batch, bcs::to_bytes(last)?)
+                    }
+                    _ => MigrationStep::done(batch),
+                })
+            }
+            1 => {
+                let mut total = 0;
+                ledger
+                    .balances
+                    .for_each_index_value(|_, balance| {
+                        total += *balance;
+                        Ok(())
+                    })
+                    .await?;
+                ledger.total.set(total);
+                ledger.pre_save(&mut batch)?;
+                Ok(MigrationStep::done(batch))
+            }
+            _ => unreachable!("no migration from version {version}"),
+        }
+    }
+}
+
+async fn create_legacy_ledger(context: &MemoryContext<()>) -> Result<()> {
+    let mut ledger = Ledger::load(context.clone()).await?;
+    for (index, balance) in [(1, 5), (2, 7), (3, 1), (4, 2), (5, 10)] {
+        ledger.balances.insert(&index, balance)?;
+    }
+    ledger.save().await?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_migration_resumes_in_steps() -> Result<()> {
+    let context = MemoryContext::new_for_testing(());
+    create_legacy_ledger(&context).await?;
+    assert_eq!(read_schema_state(&context).await?, SchemaState::default());
+
+    let state = Ledger::migrate(&context, Some(1)).await?;
+    assert_eq!(state.version, 0);
+    assert_eq!(state.cursor, Some(bcs::to_bytes(&2u32)?));
+    let ledger = Ledger::load(context.clone()).await?;
+    assert_eq!(ledger.balances.get(&2).await?, Some(700));
+    assert_eq!(ledger.balances.get(&3).await?, Some(1));
+
+    // The migration resumes where it stopped, without converting balances twice.
+    let ledger = Ledger::load_migrated(context.clone()).await?;
+    assert_eq!(
+        ledger.balances.index_values().await?,
+        vec![(1, 500), (2, 700), (3, 100), (4, 200), (5, 1000)]
+    );
+    assert_eq!(*ledger.total.get(), 2500);
+    let state = read_schema_state(&context).await?;
+    assert_eq!(state.version, 2);
+    assert_eq!(state.cursor, None);
+
+    // Loading again does not migrate anything.
+    let ledger = Ledger::load_migrated(context.clone()).await?;
+    assert_eq!(ledger.balances.get(&1).await?, Some(500));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_migration_of_current_and_newer_states() -> Result<()> {
+    let context = MemoryContext::new_for_testing(());
+    Ledger::mark_current(&context).await?;
+    let mut ledger = Ledger::load_migrated(context.clone()).await?;
+    ledger.balances.insert(&1, 5)?;
+    ledger.save().await?;
+    let ledger = Ledger::load_migrated(context.clone()).await?;
+    assert_eq!(ledger.balances.get(&1).await?, Some(5));
+
+    let mut batch = Batch::new();
+    let key = [context.base_key().bytes.clone(), vec![0]].concat();
+    batch.put_key_value(
+        key,
+        &SchemaState {
+            version: 3,
+            cursor: None,
+        },
+    )?;
+    linera_views::store::WritableKeyValueStore::write_batch(context.store(), batch).await?;
+    assert!(matches!(
+        Ledger::load_migrated(context.clone()).await,
+        Err(ViewError::UnsupportedSchemaVersion {
+            stored: 3,
+            supported: 2
+        })
+    ));
+    Ok(())
+}
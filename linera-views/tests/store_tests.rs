@@ -4,6 +4,7 @@
 use linera_views::{
     batch::Batch,
     context::{Context as _, MemoryContext},
+    encryption::EncryptedMemoryDatabase,
     key_value_store_view::ViewContainer,
     memory::MemoryDatabase,
     random::make_deterministic_rng,
@@ -55,6 +56,14 @@ async fn test_reads_memory() {
     }
 }
 
+#[tokio::test]
+async fn test_reads_encrypted_memory() {
+    for scenario in get_random_test_scenarios() {
+        let store = EncryptedMemoryDatabase::new_test_store().await.unwrap();
+        run_reads(store, scenario).await;
+    }
+}
+
 #[cfg(with_rocksdb)]
 #[tokio::test]
 async fn test_reads_rocks_db() {
@@ -133,6 +142,12 @@ async fn test_memory_writes_from_blank() {
     run_writes_from_blank(&store).await;
 }
 
+#[tokio::test]
+async fn test_encrypted_memory_writes_from_blank() {
+    let store = EncryptedMemoryDatabase::new_test_store().await.unwrap();
+    run_writes_from_blank(&store).await;
+}
+
 #[tokio::test]
 async fn test_key_value_store_view_memory_writes_from_blank() {
     let context = MemoryContext::new_for_testing(());
@@ -190,6 +205,70 @@ async fn test_big_value_read_write() {
     }
 }
 
+#[tokio::test]
+async fn test_memory_ttl_write() {
+    use std::time::Duration;
+
+    let store = MemoryDatabase::new_test_store().await.unwrap();
+    let mut batch = Batch::with_ttl(Duration::from_millis(10));
+    batch.put_key_value_bytes(vec![1], vec![10]);
+    batch.put_key_value_bytes(vec![2], vec![20]);
+    store.write_batch(batch).await.unwrap();
+    // Overwriting a value without a TTL makes it permanent.
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(vec![2], vec![21]);
+    store.write_batch(batch).await.unwrap();
+    assert_eq!(store.read_value_bytes(&[1]).await.unwrap(), Some(vec![10]));
+
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    // Expired values are removed on the next write.
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(vec![3], vec![30]);
+    store.write_batch(batch).await.unwrap();
+    assert_eq!(store.read_value_bytes(&[1]).await.unwrap(), None);
+    assert_eq!(store.read_value_bytes(&[2]).await.unwrap(), Some(vec![21]));
+    assert_eq!(store.read_value_bytes(&[3]).await.unwrap(), Some(vec![30]));
+}
+
+#[cfg(with_rocksdb)]
+#[tokio::test]
+async fn test_rocks_db_ttl_write() {
+    use std::time::Duration;
+
+    let store = linera_views::rocks_db::RocksDbDatabase::new_test_store()
+        .await
+        .unwrap();
+    let mut batch = Batch::with_ttl(Duration::from_millis(10));
+    batch.put_key_value_bytes(vec![1], vec![10]);
+    batch.put_key_value_bytes(vec![2], vec![20]);
+    batch.put_key_value_bytes(vec![3], vec![30]);
+    store.write_batch(batch).await.unwrap();
+    // Overwriting a value without a TTL makes it permanent.
+    let mut batch = Batch::new();
+    batch.put_key_value_bytes(vec![2], vec![21]);
+    store.write_batch(batch).await.unwrap();
+    let mut batch = Batch::with_ttl(Duration::from_secs(3600));
+    batch.put_key_value_bytes(vec![4], vec![40]);
+    store.write_batch(batch).await.unwrap();
+
+    // Expiration times are rounded up to the next second.
+    tokio::time::sleep(Duration::from_millis(2100)).await;
+    // Expired values are no longer read, even before a compaction removes them.
+    assert_eq!(
+        store.find_key_values_by_prefix(&[]).await.unwrap(),
+        vec![(vec![2], vec![21]), (vec![4], vec![40])]
+    );
+    assert_eq!(
+        store
+            .read_multi_values_bytes(&[vec![1], vec![2], vec![3], vec![4]])
+            .await
+            .unwrap(),
+        vec![None, Some(vec![21]), None, Some(vec![40])]
+    );
+    assert!(store.contains_key(&[4]).await.unwrap());
+    assert!(!store.contains_key(&[3]).await.unwrap());
+}
+
 #[cfg(with_scylladb)]
 #[tokio::test]
 async fn scylla_db_tombstone_triggering_test() {
@@ -259,6 +338,12 @@ async fn test_memory_writes_from_state() {
     run_writes_from_state(&store).await;
 }
 
+#[tokio::test]
+async fn test_encrypted_memory_writes_from_state() {
+    let store = EncryptedMemoryDatabase::new_test_store().await.unwrap();
+    run_writes_from_state(&store).await;
+}
+
 #[cfg(with_rocksdb)]
 #[tokio::test]
 async fn test_rocks_db_writes_from_state() {
@@ -349,6 +434,68 @@ async fn test_scylla_db_exclusive_seed_after_restart() {
     assert_eq!(store.read_value_bytes(&key).await.unwrap(), Some(vec![2]));
 }
 
+// With write aggregation, the concurrent writes of several exclusive stores may be
+// combined into one batch, and each store still reads back its own writes.
+#[cfg(with_scylladb)]
+#[tokio::test]
+async fn test_scylla_db_aggregated_exclusive_writes() {
+    use linera_views::{
+        random::generate_test_namespace, scylla_db::ScyllaDbDatabase, store::KeyValueDatabase as _,
+    };
+
+    let mut config = ScyllaDbDatabase::new_test_config().await.unwrap();
+    config.inner_config.write_aggregation_max_writes = Some(4);
+    let namespace = generate_test_namespace();
+    let database = ScyllaDbDatabase::recreate_and_connect(&config, &namespace)
+        .await
+        .unwrap();
+    let stores = (0..10u8)
+        .map(|index| database.open_exclusive(&[index]).unwrap())
+        .collect::<Vec<_>>();
+    let writes = stores.iter().zip(0u8..).map(|(store, index)| async move {
+        for round in 0..3u8 {
+            let mut batch = Batch::new();
+            batch.delete_key_prefix(vec![1]);
+            batch.put_key_value_bytes(vec![1, round], vec![index, round]);
+            store.write_batch(batch).await.unwrap();
+        }
+    });
+    futures::future::join_all(writes).await;
+
+    let database = ScyllaDbDatabase::connect(&config, &namespace)
+        .await
+        .unwrap();
+    for index in 0..10u8 {
+        let store = database.open_exclusive(&[index]).unwrap();
+        let key_values = store.find_key_values_by_prefix(&[1]).await.unwrap();
+        assert_eq!(key_values, vec![(vec![2], vec![index, 2])]);
+    }
+}
+
+// A newly created namespace has the latest schema, so connecting in dry-run mode finds no
+// pending migration.
+#[cfg(with_scylladb)]
+#[tokio::test]
+async fn test_scylla_db_schema_is_up_to_date_after_create() {
+    use linera_views::{
+        random::generate_test_namespace, scylla_db::ScyllaDbDatabase, store::KeyValueDatabase as _,
+    };
+
+    let mut config = ScyllaDbDatabase::new_test_config().await.unwrap();
+    let namespace = generate_test_namespace();
+    ScyllaDbDatabase::recreate_and_connect(&config, &namespace)
+        .await
+        .unwrap();
+    config.inner_config.schema_migration_dry_run = true;
+    ScyllaDbDatabase::connect(&config, &namespace)
+        .await
+        .unwrap();
+    // The metadata tables are not listed as namespaces.
+    let namespaces = ScyllaDbDatabase::list_all(&config).await.unwrap();
+    assert!(namespaces.contains(&namespace));
+    assert!(!namespaces.iter().any(|name| name.starts_with("schema_")));
+}
+
 #[cfg(with_scylladb)]
 #[tokio::test]
 async fn test_scylladb_access() {
@@ -0,0 +1,223 @@
+// Copyright (c) Zefchain Labs, Inc.
+// SPDX-License-Identifier: Apache-2.0
+
+//! Passphrase-encrypted archives of a wallet and its keystore, to move them to another
+//! machine without copying secret keys in plaintext.
+//!
+//! An archive starts with a header made of a magic string, a format version and a random
+//! salt, followed by a random nonce and the contents encrypted with XChaCha20-Poly1305.
+//! The encryption key is derived from the passphrase with PBKDF2-HMAC-SHA256 and the salt,
+//! and the header is authenticated together with the contents.
+
+use std::{io::Write as _, path::Path};
+
+use chacha20poly1305::{
+    aead::{rand_core::RngCore as _, Aead, AeadCore, KeyInit, OsRng, Payload},
+    XChaCha20Poly1305, XNonce,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{Keystore, PersistentWallet};
+
+/// The magic string at the start of every archive.
+const MAGIC: &[u8] = b"linera-wallet-archive";
+
+/// The version of the archive format.
+const VERSION: u8 = 1;
+
+/// The size of the salt stored in the header.
+const SALT_SIZE: usize = 16;
+
+/// The size of the nonce stored after the header.
+const NONCE_SIZE: usize = 24;
+
+/// The size of the header: the magic string, the version and the salt.
+const HEADER_SIZE: usize = MAGIC.len() + 1 + SALT_SIZE;
+
+/// The number of PBKDF2 rounds used to derive the key from the passphrase.
+const PBKDF2_ROUNDS: u32 = 600_000;
+
+/// An error while creating or restoring an archive.
+#[derive(Debug, thiserror::Error)]
+pub enum ArchiveError {
+    /// The data does not start with the header of an archive.
+    #[error("not a wallet archive")]
+    NotAnArchive,
+    /// The archive was created by a newer client.
+    #[error("unsupported wallet archive version {0}")]
+    UnsupportedVersion(u8),
+    /// The archive could not be decrypted.
+    #[error("the passphrase is wrong or the wallet archive is corrupted")]
+    DecryptionFailed,
+    /// The passphrase is empty.
+    #[error("the passphrase must not be empty")]
+    EmptyPassphrase,
+    /// Restoring the archive would overwrite an existing file.
+    #[error("refusing to overwrite existing file {0}")]
+    AlreadyExists(std::path::PathBuf),
+    /// The wallet or keystore file could not be read.
+    #[error(transparent)]
+    Persistent(#[from] linera_persistent::file::Error),
+    /// A file could not be read or written.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The contents are not valid JSON.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// The contents of an archive: the wallet and keystore files, as JSON.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveContents {
+    /// The contents of the wallet file.
+    pub wallet: serde_json::Value,
+    /// The contents of the keystore file.
+    pub keystore: serde_json::Value,
+}
+
+/// Returns the cipher for the given passphrase and salt.
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<XChaCha20Poly1305, ArchiveError> {
+    if passphrase.is_empty() {
+        return Err(ArchiveError::EmptyPassphrase);
+    }
+    let key =
+        pbkdf2::pbkdf2_hmac_array::<sha2::Sha256, 32>(passphrase.as_bytes(), salt, PBKDF2_ROUNDS);
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+/// Encrypts `contents` into an archive with the given passphrase.
+pub fn seal(contents: &ArchiveContents, passphrase: &str) -> Result<Vec<u8>, ArchiveError> {
+    let mut salt = [0u8; SALT_SIZE];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = cipher(passphrase, &salt)?;
+    let mut archive = Vec::with_capacity(HEADER_SIZE + NONCE_SIZE);
+    archive.extend_from_slice(MAGIC);
+    archive.push(VERSION);
+    archive.extend_from_slice(&salt);
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let plaintext = serde_json::to_vec(contents)?;
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: &plaintext,
+                aad: &archive,
+            },
+        )
+        .expect("encryption does not fail for values fitting in memory");
+    archive.extend_from_slice(&nonce);
+    archive.extend_from_slice(&ciphertext);
+    Ok(archive)
+}
+
+/// Decrypts an archive created by [`seal`] with the given passphrase.
+pub fn open(archive: &[u8], passphrase: &str) -> Result<ArchiveContents, ArchiveError> {
+    let Some(version) = archive
+        .strip_prefix(MAGIC)
+        .and_then(|rest| rest.first().copied())
+    else {
+        return Err(ArchiveError::NotAnArchive);
+    };
+    if version != VERSION {
+        return Err(ArchiveError::UnsupportedVersion(version));
+    }
+    if archive.len() < HEADER_SIZE + NONCE_SIZE {
+        return Err(ArchiveError::NotAnArchive);
+    }
+    let (header, rest) = archive.split_at(HEADER_SIZE);
+    let (nonce, ciphertext) = rest.split_at(NONCE_SIZE);
+    let cipher = cipher(passphrase, &header[MAGIC.len() + 1..])?;
+    let plaintext = cipher
+        .decrypt(
+            XNonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| ArchiveError::DecryptionFailed)?;
+    Ok(serde_json::from_slice(&plaintext)?)
+}
+
+/// Writes an archive of the wallet and keystore files at the given paths to `output`.
+///
+/// Both files are locked while they are read, so that they are consistent.
+pub fn export(
+    wallet_path: &Path,
+    keystore_path: &Path,
+    output: &Path,
+    passphrase: &str,
+) -> Result<(), ArchiveError> {
+    let _wallet = PersistentWallet::read(wallet_path)?;
+    let _keystore = Keystore::read(keystore_path)?;
+    let contents = ArchiveContents {
+        wallet: serde_json::from_slice(&fs_err::read(wallet_path)?)?,
+        keystore: serde_json::from_slice(&fs_err::read(keystore_path)?)?,
+    };
+    fs_err::write(output, seal(&contents, passphrase)?)?;
+    Ok(())
+}
+
+/// Restores the wallet and keystore files from the archive at `input` to the given paths,
+/// which must not exist yet.
+pub fn import(
+    input: &Path,
+    wallet_path: &Path,
+    keystore_path: &Path,
+    passphrase: &str,
+) -> Result<(), ArchiveError> {
+    for path in [wallet_path, keystore_path] {
+        if path.exists() {
+            return Err(ArchiveError::AlreadyExists(path.to_path_buf()));
+        }
+    }
+    let contents = open(&fs_err::read(input)?, passphrase)?;
+    write_new(keystore_path, &contents.keystore)?;
+    write_new(wallet_path, &contents.wallet)?;
+    // Check that the restored files can be loaded.
+    PersistentWallet::read(wallet_path)?;
+    Keystore::read(keystore_path)?;
+    Ok(())
+}
+
+/// Writes `value` to a new file at `path` that only its owner can read.
+fn write_new(path: &Path, value: &serde_json::Value) -> Result<(), ArchiveError> {
+    let mut options = fs_err::OpenOptions::new();
+    #[cfg(target_family = "unix")]
+    fs_err::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.write(true).create_new(true).open(path)?;
+    serde_json::to_writer_pretty(&mut file, value)?;
+    file.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{open, seal, ArchiveContents, ArchiveError};
+
+    #[test]
+    fn test_archive_round_trip() -> Result<(), ArchiveError> {
+        let contents = ArchiveContents {
+            wallet: serde_json::json!({ "chains": {}, "default": null }),
+            keystore: serde_json::json!({ "keys": [["owner", "secret"]] }),
+        };
+        let mut archive = seal(&contents, "correct horse")?;
+        assert_eq!(open(&archive, "correct horse")?, contents);
+        assert!(matches!(
+            open(&archive, "wrong horse"),
+            Err(ArchiveError::DecryptionFailed)
+        ));
+        assert!(matches!(
+            open(b"{}", "correct horse"),
+            Err(ArchiveError::NotAnArchive)
+        ));
+        // The header is authenticated too.
+        let salt_index = super::MAGIC.len() + 1;
+        archive[salt_index] ^= 1;
+        assert!(matches!(
+            open(&archive, "correct horse"),
+            Err(ArchiveError::DecryptionFailed)
+        ));
+        Ok(())
+    }
+}
@@ -38,6 +38,26 @@ impl ChainDetails {
         }
     }
 
+    fn to_json(&self) -> serde_json::Value {
+        let parent = match self.origin {
+            Some(ChainOrigin::Child { parent, .. }) => Some(parent),
+            Some(ChainOrigin::Root(_)) | None => None,
+        };
+        serde_json::json!({
+            "chainId": self.chain_id,
+            "isDefault": self.is_default,
+            "isAdmin": self.is_admin,
+            "isFollowOnly": self.user_chain.is_follow_only(),
+            "parentChain": parent,
+            "owner": self.user_chain.owner,
+            "timestamp": self.user_chain.timestamp,
+            "nextBlockHeight": self.user_chain.next_block_height,
+            "epoch": self.user_chain.epoch,
+            "blockHash": self.user_chain.block_hash,
+            "hasPendingFastProposal": self.user_chain.pending_fast_proposal.is_some(),
+        })
+    }
+
     fn print_paragraph(&self) {
         println!("-----------------------");
         println!("{:<20}  {}", "Chain ID:", self.chain_id);
@@ -96,6 +116,23 @@ pub fn pretty_print(wallet: &crate::PersistentWallet, chain_ids: Vec<ChainId>) {
     let plural_s = if total_chains == 1 { "" } else { "s" };
     tracing::info!("Found {total_chains} chain{plural_s}");
 
+    for chain in sorted_details(wallet, chain_ids) {
+        chain.print_paragraph();
+    }
+    println!("------------------------");
+}
+
+/// Returns the wallet chain details as a JSON array, in the order of [`pretty_print`].
+pub fn to_json(wallet: &crate::PersistentWallet, chain_ids: Vec<ChainId>) -> serde_json::Value {
+    sorted_details(wallet, chain_ids)
+        .iter()
+        .map(ChainDetails::to_json)
+        .collect()
+}
+
+/// Returns the details of the given chains: the default chain first, then the admin chain,
+/// then the others by root chain.
+fn sorted_details(wallet: &crate::PersistentWallet, chain_ids: Vec<ChainId>) -> Vec<ChainDetails> {
     let mut chains = chain_ids
         .into_iter()
         .map(|chain_id| ChainDetails::new(chain_id, wallet.data()))
@@ -108,8 +145,5 @@ pub fn pretty_print(wallet: &crate::PersistentWallet, chain_ids: Vec<ChainId>) {
         let chain_id = chain.chain_id;
         (!chain.is_default, !chain.is_admin, root_id, chain_id)
     });
-    for chain in chains {
-        chain.print_paragraph();
-    }
-    println!("------------------------");
+    chains
 }
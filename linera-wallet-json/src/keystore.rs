@@ -3,37 +3,147 @@
 
 //! A keystore backed by a JSON file holding the client's signing keys.
 
-use std::path::Path;
+use std::{
+    collections::BTreeSet,
+    ops::{Deref, DerefMut},
+    path::Path,
+};
 
 use linera_base::{
     crypto::{AccountPublicKey, AccountSignature, CryptoHash, InMemorySigner, Signer},
     identifiers::AccountOwner,
 };
+use linera_client::signing_relay::{SigningRelay, SigningRelayError};
 use linera_persistent::{self as persistent, Persist as _};
+use serde::{Deserialize, Serialize};
+
+/// The contents of the keystore file.
+#[derive(Serialize, Deserialize)]
+struct Contents {
+    #[serde(flatten)]
+    signer: InMemorySigner,
+    /// The owners whose keys stay in an external EVM wallet, such as MetaMask.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    external_evm_owners: BTreeSet<AccountOwner>,
+}
+
+/// An error of the [`Keystore`] signer.
+#[derive(Debug, thiserror::Error)]
+pub enum KeystoreError {
+    /// The keystore has no key for the owner.
+    #[error(transparent)]
+    InMemory(<InMemorySigner as Signer>::Error),
+    /// The owner's key is in an external wallet, and no signing relay is running.
+    #[error("the key of {0} is in an external wallet, but no signing relay is running")]
+    NoSigningRelay(AccountOwner),
+    /// The external wallet did not sign.
+    #[error(transparent)]
+    SigningRelay(#[from] SigningRelayError),
+}
+
+/// Where the contents of a [`Keystore`] are kept.
+enum Backend {
+    /// A JSON file with exclusive locking, saved on every change.
+    File(persistent::File<Contents>),
+    /// Memory only, for keystores read from a secret that the client cannot write.
+    Memory(persistent::Memory<Contents>),
+}
+
+impl Deref for Backend {
+    type Target = Contents;
 
-/// A persistent keystore backed by a JSON file with exclusive locking.
-pub struct Keystore(persistent::File<InMemorySigner>);
+    fn deref(&self) -> &Contents {
+        match self {
+            Backend::File(file) => file,
+            Backend::Memory(memory) => memory,
+        }
+    }
+}
+
+impl DerefMut for Backend {
+    fn deref_mut(&mut self) -> &mut Contents {
+        match self {
+            Backend::File(file) => file.as_mut(),
+            Backend::Memory(memory) => memory.as_mut(),
+        }
+    }
+}
+
+impl Backend {
+    async fn persist(&mut self) -> Result<(), persistent::file::Error> {
+        match self {
+            Backend::File(file) => file.persist().await,
+            Backend::Memory(_) => Ok(()),
+        }
+    }
+
+    fn into_value(self) -> Contents {
+        match self {
+            Backend::File(file) => file.into_value(),
+            Backend::Memory(memory) => memory.into_value(),
+        }
+    }
+}
+
+/// A persistent keystore backed by a JSON file with exclusive locking, or a read-only
+/// keystore held in memory.
+///
+/// Owners whose keys stay in an external EVM wallet sign through a [`SigningRelay`].
+pub struct Keystore {
+    backend: Backend,
+    signing_relay: Option<SigningRelay>,
+}
 
 impl Signer for Keystore {
-    type Error = <InMemorySigner as Signer>::Error;
+    type Error = KeystoreError;
 
     async fn sign(
         &self,
         owner: &AccountOwner,
         value: &CryptoHash,
     ) -> Result<AccountSignature, Self::Error> {
-        (*self.0).sign(owner, value).await
+        if self.backend.external_evm_owners.contains(owner) {
+            let relay = self
+                .signing_relay
+                .as_ref()
+                .ok_or(KeystoreError::NoSigningRelay(*owner))?;
+            return Ok(relay.sign(*owner, *value).await?);
+        }
+        self.backend
+            .signer
+            .sign(owner, value)
+            .await
+            .map_err(KeystoreError::InMemory)
     }
 
     async fn contains_key(&self, owner: &AccountOwner) -> Result<bool, Self::Error> {
-        (*self.0).contains_key(owner).await
+        if self.backend.external_evm_owners.contains(owner) {
+            return Ok(true);
+        }
+        self.backend
+            .signer
+            .contains_key(owner)
+            .await
+            .map_err(KeystoreError::InMemory)
     }
 }
 
 impl Keystore {
     /// Reads an existing keystore from disk.
     pub fn read(path: &Path) -> Result<Self, persistent::file::Error> {
-        Ok(Self(persistent::File::read(path)?))
+        Ok(Self {
+            backend: Backend::File(persistent::File::read(path)?),
+            signing_relay: None,
+        })
+    }
+
+    /// Parses the contents of a keystore file, without writing them anywhere. Keys added
+    /// to this keystore are lost when it is dropped.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        Ok(Self {
+            backend: Backend::Memory(persistent::Memory::new(serde_json::from_str(json)?)),
+            signing_relay: None,
+        })
     }
 
     /// Creates a new keystore at `path`. If `testing_prng_seed` is provided,
@@ -42,15 +152,22 @@ impl Keystore {
         path: &Path,
         testing_prng_seed: Option<u64>,
     ) -> Result<Self, persistent::file::Error> {
-        Ok(Self(persistent::File::read_or_create(path, || {
-            Ok(InMemorySigner::new(testing_prng_seed))
-        })?))
+        let file = persistent::File::read_or_create(path, || {
+            Ok(Contents {
+                signer: InMemorySigner::new(testing_prng_seed),
+                external_evm_owners: BTreeSet::new(),
+            })
+        })?;
+        Ok(Self {
+            backend: Backend::File(file),
+            signing_relay: None,
+        })
     }
 
     /// Generates a new key pair, persists the keystore, and returns the public key.
     pub async fn generate_key(&mut self) -> Result<AccountPublicKey, persistent::file::Error> {
-        let key = self.0.generate_new();
-        self.0.persist().await?;
+        let key = self.backend.signer.generate_new();
+        self.backend.persist().await?;
         Ok(key)
     }
 
@@ -59,20 +176,43 @@ impl Keystore {
         &mut self,
         count: usize,
     ) -> Result<Vec<AccountPublicKey>, persistent::file::Error> {
-        let keys: Vec<_> = std::iter::repeat_with(|| self.0.generate_new())
+        let keys: Vec<_> = std::iter::repeat_with(|| self.backend.signer.generate_new())
             .take(count)
             .collect();
-        self.0.persist().await?;
+        self.backend.persist().await?;
         Ok(keys)
     }
 
+    /// Records that the key of the given EVM address stays in an external wallet, and
+    /// persists the keystore. Returns the corresponding owner.
+    pub async fn add_external_evm_owner(
+        &mut self,
+        address: [u8; 20],
+    ) -> Result<AccountOwner, persistent::file::Error> {
+        let owner = AccountOwner::Address20(address);
+        if self.backend.external_evm_owners.insert(owner) {
+            self.backend.persist().await?;
+        }
+        Ok(owner)
+    }
+
+    /// Returns whether some owners' keys stay in an external wallet.
+    pub fn has_external_evm_owners(&self) -> bool {
+        !self.backend.external_evm_owners.is_empty()
+    }
+
+    /// Sets the relay through which external wallets sign.
+    pub fn set_signing_relay(&mut self, signing_relay: SigningRelay) {
+        self.signing_relay = Some(signing_relay);
+    }
+
     /// Saves the keystore to disk.
     pub async fn save(&mut self) -> Result<(), persistent::file::Error> {
-        self.0.persist().await
+        self.backend.persist().await
     }
 
     /// Consumes the keystore and returns the inner signer.
     pub fn into_signer(self) -> InMemorySigner {
-        self.0.into_value()
+        self.backend.into_value().signer
     }
 }
@@ -5,6 +5,7 @@
 
 #![deny(missing_docs)]
 
+pub mod archive;
 pub mod display;
 pub mod keystore;
 pub mod paths;